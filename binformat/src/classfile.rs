@@ -0,0 +1,453 @@
+//! Java class files and the jars they're packaged in.
+//!
+//! Bytecode refers to classes, fields, methods and literals by their index in the class's
+//! constant pool, so the pool is kept around to name them once the bytecode is disassembled.
+
+use std::io::Read;
+
+/// Signature at the start of a class file.
+const MAGIC: u32 = 0xCAFE_BABE;
+
+/// Signature of a jar's end of central directory record, `PK\x05\x06`.
+const END_OF_CENTRAL_DIRECTORY: u32 = 0x0605_4B50;
+
+/// Signature of an entry in a jar's central directory, `PK\x01\x02`.
+const CENTRAL_DIRECTORY_ENTRY: u32 = 0x0201_4B50;
+
+/// Signature of the header in front of a file in a jar, `PK\x03\x04`.
+const LOCAL_FILE_HEADER: u32 = 0x0403_4B50;
+
+/// Largest class file that's decompressed, so a malformed jar can't exhaust memory.
+const MAX_CLASS_SIZE: u64 = 0x400_0000;
+
+#[derive(Debug, Clone)]
+enum Constant {
+    /// Second slot taken up by a long or double.
+    Unusable,
+    Utf8(String),
+    Integer(i32),
+    Float(f32),
+    Long(i64),
+    Double(f64),
+    Class(u16),
+    String(u16),
+    /// Field, method or interface method, as the index of its class and its name and type.
+    Member(u16, u16),
+    NameAndType(u16, u16),
+    MethodHandle(u16),
+    MethodType(u16),
+    /// Dynamically computed constant or call site, as the index of its name and type.
+    Dynamic(u16),
+    Module(u16),
+    Package(u16),
+}
+
+#[derive(Debug, Clone)]
+pub struct Method {
+    /// Qualified name of the method with its parameters, e.g. `java.lang.Object.equals(Object)`.
+    pub name: String,
+    /// Offset of the method's bytecode from the start of the class file.
+    pub offset: usize,
+    pub size: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Class {
+    /// Qualified name of the class, e.g. `java.lang.Object`.
+    pub name: String,
+    pub superclass: Option<String>,
+    /// Methods that have bytecode, abstract and native methods have none.
+    pub methods: Vec<Method>,
+    pool: Vec<Constant>,
+}
+
+/// Big endian reads that fail instead of panicking when out of bounds.
+struct Reader<'data> {
+    data: &'data [u8],
+    offset: usize,
+}
+
+impl<'data> Reader<'data> {
+    fn bytes(&mut self, len: usize) -> Option<&'data [u8]> {
+        let bytes = self.data.get(self.offset..self.offset.checked_add(len)?)?;
+        self.offset += len;
+        Some(bytes)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.bytes(1).map(|bytes| bytes[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.bytes(2).map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_be_bytes(self.bytes(4)?.try_into().ok()?))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_be_bytes(self.bytes(8)?.try_into().ok()?))
+    }
+}
+
+/// Whether the data starts like a class file.
+pub fn is_class(data: &[u8]) -> bool {
+    data.get(..4).is_some_and(|magic| magic == MAGIC.to_be_bytes())
+}
+
+/// Whether the data starts like a zip archive, which a jar is.
+pub fn is_jar(data: &[u8]) -> bool {
+    data.get(..4).is_some_and(|magic| magic == LOCAL_FILE_HEADER.to_le_bytes())
+}
+
+impl Class {
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let mut reader = Reader { data, offset: 0 };
+        if reader.u32()? != MAGIC {
+            return None;
+        }
+
+        // minor and major version
+        reader.u32()?;
+
+        let count = reader.u16()? as usize;
+        let mut pool = vec![Constant::Unusable];
+        while pool.len() < count {
+            let tag = reader.u8()?;
+            let constant = match tag {
+                1 => {
+                    let len = reader.u16()? as usize;
+                    Constant::Utf8(String::from_utf8_lossy(reader.bytes(len)?).into_owned())
+                }
+                3 => Constant::Integer(reader.u32()? as i32),
+                4 => Constant::Float(f32::from_bits(reader.u32()?)),
+                5 => Constant::Long(reader.u64()? as i64),
+                6 => Constant::Double(f64::from_bits(reader.u64()?)),
+                7 => Constant::Class(reader.u16()?),
+                8 => Constant::String(reader.u16()?),
+                9..=11 => Constant::Member(reader.u16()?, reader.u16()?),
+                12 => Constant::NameAndType(reader.u16()?, reader.u16()?),
+                15 => {
+                    // kind of reference
+                    reader.u8()?;
+                    Constant::MethodHandle(reader.u16()?)
+                }
+                16 => Constant::MethodType(reader.u16()?),
+                17 | 18 => {
+                    // index of the bootstrap method
+                    reader.u16()?;
+                    Constant::Dynamic(reader.u16()?)
+                }
+                19 => Constant::Module(reader.u16()?),
+                20 => Constant::Package(reader.u16()?),
+                _ => return None,
+            };
+
+            pool.push(constant);
+
+            // longs and doubles take up two entries
+            if tag == 5 || tag == 6 {
+                pool.push(Constant::Unusable);
+            }
+        }
+
+        let mut class = Self {
+            name: String::new(),
+            superclass: None,
+            methods: Vec::new(),
+            pool,
+        };
+
+        // access flags
+        reader.u16()?;
+        class.name = class.class_name(reader.u16()?)?;
+        class.superclass = class.class_name(reader.u16()?);
+
+        let interfaces = reader.u16()? as usize;
+        reader.bytes(interfaces * 2)?;
+
+        let fields = reader.u16()?;
+        for _ in 0..fields {
+            reader.bytes(6)?;
+            skip_attributes(&mut reader)?;
+        }
+
+        let methods = reader.u16()?;
+        for _ in 0..methods {
+            // access flags
+            reader.u16()?;
+            let name = class.utf8(reader.u16()?)?.to_string();
+            let descriptor = parameters(class.utf8(reader.u16()?)?);
+
+            let attributes = reader.u16()?;
+            for _ in 0..attributes {
+                let attribute = class.utf8(reader.u16()?)?;
+                let len = reader.u32()? as usize;
+                let start = reader.offset;
+                reader.bytes(len)?;
+
+                if attribute != "Code" {
+                    continue;
+                }
+
+                // maximum stack size and number of locals
+                let mut code = Reader { data, offset: start + 4 };
+                let size = code.u32()? as usize;
+                let offset = code.offset;
+                code.bytes(size)?;
+
+                let name = format!("{}.{name}{descriptor}", class.name);
+                class.methods.push(Method { name, offset, size });
+            }
+        }
+
+        Some(class)
+    }
+
+    fn utf8(&self, idx: u16) -> Option<&str> {
+        match self.pool.get(idx as usize)? {
+            Constant::Utf8(str) => Some(str),
+            _ => None,
+        }
+    }
+
+    fn class_name(&self, idx: u16) -> Option<String> {
+        match self.pool.get(idx as usize)? {
+            Constant::Class(name) => Some(class_name(self.utf8(*name)?)),
+            _ => None,
+        }
+    }
+
+    /// Name of a field or method, along with the parameters of a method.
+    fn member(&self, idx: u16) -> Option<String> {
+        let (class, name_and_type) = match self.pool.get(idx as usize)? {
+            Constant::Member(class, name_and_type) => (*class, *name_and_type),
+            _ => return None,
+        };
+
+        let (name, descriptor) = self.name_and_type(name_and_type)?;
+        Some(format!("{}.{name}{}", self.class_name(class)?, parameters(descriptor)))
+    }
+
+    fn name_and_type(&self, idx: u16) -> Option<(&str, &str)> {
+        match self.pool.get(idx as usize)? {
+            Constant::NameAndType(name, descriptor) => {
+                Some((self.utf8(*name)?, self.utf8(*descriptor)?))
+            }
+            _ => None,
+        }
+    }
+
+    /// Entry of the constant pool as an instruction refers to it.
+    pub fn constant(&self, idx: u16) -> Option<String> {
+        Some(match self.pool.get(idx as usize)? {
+            Constant::Unusable | Constant::NameAndType(..) => return None,
+            Constant::Utf8(str) => str.clone(),
+            Constant::Integer(value) => value.to_string(),
+            Constant::Float(value) => format!("{value:?}f"),
+            Constant::Long(value) => format!("{value}L"),
+            Constant::Double(value) => format!("{value:?}"),
+            Constant::Class(..) => self.class_name(idx)?,
+            Constant::String(str) => format!("{:?}", self.utf8(*str)?),
+            Constant::Member(..) => self.member(idx)?,
+            Constant::MethodHandle(member) => self.member(*member)?,
+            Constant::MethodType(descriptor) => parameters(self.utf8(*descriptor)?),
+            Constant::Dynamic(name_and_type) => {
+                let (name, descriptor) = self.name_and_type(*name_and_type)?;
+                format!("{name}{}", parameters(descriptor))
+            }
+            Constant::Module(name) | Constant::Package(name) => self.utf8(*name)?.to_string(),
+        })
+    }
+}
+
+fn skip_attributes(reader: &mut Reader) -> Option<()> {
+    let attributes = reader.u16()?;
+    for _ in 0..attributes {
+        reader.u16()?;
+        let len = reader.u32()? as usize;
+        reader.bytes(len)?;
+    }
+
+    Some(())
+}
+
+/// Internal name of a class as it's written in Java, `java/lang/Object` being `java.lang.Object`.
+/// Array classes are named by their descriptor instead.
+pub(crate) fn class_name(name: &str) -> String {
+    match name.starts_with('[') {
+        true => descriptor_types(name).pop().unwrap_or_default(),
+        false => name.replace('/', "."),
+    }
+}
+
+/// Types in a field or method descriptor as they're written in Java, without the return type.
+pub(crate) fn descriptor_types(descriptor: &str) -> Vec<String> {
+    let mut types = Vec::new();
+    let mut dimensions = 0;
+    let mut chars = descriptor.chars();
+
+    while let Some(chr) = chars.next() {
+        let ty = match chr {
+            '[' => {
+                dimensions += 1;
+                continue;
+            }
+            'B' => "byte".to_string(),
+            'C' => "char".to_string(),
+            'D' => "double".to_string(),
+            'F' => "float".to_string(),
+            'I' => "int".to_string(),
+            'J' => "long".to_string(),
+            'S' => "short".to_string(),
+            'Z' => "boolean".to_string(),
+            'V' => "void".to_string(),
+            'L' => {
+                let name: String = chars.by_ref().take_while(|&chr| chr != ';').collect();
+                name.replace('/', ".")
+            }
+            ')' => break,
+            _ => continue,
+        };
+
+        types.push(ty + &"[]".repeat(dimensions));
+        dimensions = 0;
+    }
+
+    types
+}
+
+/// Parameters of a method descriptor as they're written in Java, or nothing for a field.
+pub(crate) fn parameters(descriptor: &str) -> String {
+    match descriptor.strip_prefix('(') {
+        Some(descriptor) => format!("({})", descriptor_types(descriptor).join(", ")),
+        None => String::new(),
+    }
+}
+
+/// Class files packaged in a jar, along with their path in the jar.
+pub fn jar_classes(data: &[u8]) -> Option<Vec<(String, Vec<u8>)>> {
+    let u16_at = |offset: usize| -> Option<u16> {
+        Some(u16::from_le_bytes(data.get(offset..offset.checked_add(2)?)?.try_into().ok()?))
+    };
+    let u32_at = |offset: usize| -> Option<u32> {
+        Some(u32::from_le_bytes(data.get(offset..offset.checked_add(4)?)?.try_into().ok()?))
+    };
+
+    // the end of central directory record is followed by a comment of at most 64 KiB
+    let search_start = data.len().saturating_sub(22 + 0xFFFF);
+    let end = (search_start..data.len().saturating_sub(21))
+        .rev()
+        .find(|&offset| u32_at(offset) == Some(END_OF_CENTRAL_DIRECTORY))?;
+
+    let entries = u16_at(end + 10)?;
+    let mut offset = u32_at(end + 16)? as usize;
+    let mut classes = Vec::new();
+
+    for _ in 0..entries {
+        if u32_at(offset)? != CENTRAL_DIRECTORY_ENTRY {
+            break;
+        }
+
+        let method = u16_at(offset + 10)?;
+        let compressed_size = u32_at(offset + 20)? as usize;
+        let size = u32_at(offset + 24)? as u64;
+        let name_len = u16_at(offset + 28)? as usize;
+        let extra_len = u16_at(offset + 30)? as usize;
+        let comment_len = u16_at(offset + 32)? as usize;
+        let header = u32_at(offset + 42)? as usize;
+        let name = data.get(offset + 46..offset + 46 + name_len)?;
+        let name = String::from_utf8_lossy(name).into_owned();
+        offset += 46 + name_len + extra_len + comment_len;
+
+        if !name.ends_with(".class") || size > MAX_CLASS_SIZE {
+            continue;
+        }
+
+        if u32_at(header) != Some(LOCAL_FILE_HEADER) {
+            continue;
+        }
+
+        let start = header + 30 + u16_at(header + 26)? as usize + u16_at(header + 28)? as usize;
+        let compressed = match data.get(start..start.saturating_add(compressed_size)) {
+            Some(compressed) => compressed,
+            None => continue,
+        };
+
+        let bytes = match method {
+            0 => compressed.to_vec(),
+            8 => {
+                let mut bytes = Vec::new();
+                let decoder = flate2::read::DeflateDecoder::new(compressed);
+                if decoder.take(size).read_to_end(&mut bytes).is_err() {
+                    continue;
+                }
+                bytes
+            }
+            _ => continue,
+        };
+
+        classes.push((name, bytes));
+    }
+
+    Some(classes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf8(data: &mut Vec<u8>, str: &str) {
+        data.push(1);
+        data.extend_from_slice(&(str.len() as u16).to_be_bytes());
+        data.extend_from_slice(str.as_bytes());
+    }
+
+    /// Class `Foo` with a method `run(int)` that only returns, and the offset of its bytecode.
+    fn class() -> (Vec<u8>, usize) {
+        let mut data = vec![0xCA, 0xFE, 0xBA, 0xBE, 0, 0, 0, 52, 0, 8];
+        utf8(&mut data, "Foo");
+        data.extend_from_slice(&[7, 0, 1]);
+        utf8(&mut data, "java/lang/Object");
+        data.extend_from_slice(&[7, 0, 3]);
+        utf8(&mut data, "run");
+        utf8(&mut data, "(I)V");
+        utf8(&mut data, "Code");
+
+        // access flags, this class, superclass, interfaces, fields and methods
+        data.extend_from_slice(&[0, 0x21, 0, 2, 0, 4, 0, 0, 0, 0, 0, 1]);
+
+        // access flags, name, descriptor and the method's `Code` attribute
+        data.extend_from_slice(&[0, 1, 0, 5, 0, 6, 0, 1, 0, 7, 0, 0, 0, 13]);
+        data.extend_from_slice(&[0, 1, 0, 1, 0, 0, 0, 1]);
+        let code = data.len();
+        data.push(0xB1);
+
+        // exception table and attributes of the code, then attributes of the class
+        data.extend_from_slice(&[0, 0, 0, 0, 0, 0]);
+        (data, code)
+    }
+
+    #[test]
+    fn minimal() {
+        let (data, code) = class();
+        let class = Class::parse(&data).unwrap();
+        assert_eq!(class.name, "Foo");
+        assert_eq!(class.superclass.as_deref(), Some("java.lang.Object"));
+        assert_eq!(class.methods.len(), 1);
+        assert_eq!(class.methods[0].name, "Foo.run(int)");
+        assert_eq!(class.methods[0].offset, code);
+        assert_eq!(class.methods[0].size, 1);
+    }
+
+    #[test]
+    fn truncated() {
+        let (data, _) = class();
+
+        // attributes of the class itself aren't read
+        for len in 0..data.len() - 2 {
+            assert!(Class::parse(&data[..len]).is_none());
+        }
+    }
+}
@@ -0,0 +1,703 @@
+//! Memory and threads of a crashed process, as written to an ELF core dump or a Windows minidump.
+//!
+//! Neither has sections, only the memory that was mapped when the process crashed along with the
+//! registers of every thread. ELF core dumps usually leave out the code of files the process
+//! mapped, so [`Module`]s tell where it can be read from instead.
+
+use object::elf;
+use object::read::elf::{ElfFile, FileHeader, ProgramHeader};
+use object::{Architecture, Endianness, Object, ObjectKind};
+use processor_shared::{Permissions, PhysAddr};
+use std::ops::Range;
+
+/// Signature at the start of a minidump.
+const MINIDUMP_MAGIC: &[u8] = b"MDMP";
+
+/// Types of the streams of a minidump that are parsed.
+const THREAD_LIST_STREAM: u32 = 3;
+const MODULE_LIST_STREAM: u32 = 4;
+const MEMORY_LIST_STREAM: u32 = 5;
+const EXCEPTION_STREAM: u32 = 6;
+const SYSTEM_INFO_STREAM: u32 = 7;
+const MEMORY64_LIST_STREAM: u32 = 9;
+const MEMORY_INFO_LIST_STREAM: u32 = 16;
+
+/// Kind of dump, which decides the calling convention of the code in it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Elf,
+    Minidump,
+}
+
+/// Memory that was mapped into the process.
+#[derive(Debug, Clone)]
+pub struct Region {
+    pub start: PhysAddr,
+    pub end: PhysAddr,
+    /// Range of the dump holding the region's bytes, which is shorter than the region if only part
+    /// of it got dumped.
+    pub data: Range<usize>,
+    pub permissions: Permissions,
+}
+
+/// File that was mapped into the process.
+#[derive(Debug, Clone)]
+pub struct Module {
+    pub path: String,
+    pub start: PhysAddr,
+    pub end: PhysAddr,
+    /// Offset into the file at which the mapping starts.
+    pub file_offset: u64,
+}
+
+impl Module {
+    /// Name of the file without the directories it's in.
+    pub fn name(&self) -> &str {
+        self.path.rsplit(['/', '\\']).next().unwrap_or(&self.path)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Registers {
+    pub pc: PhysAddr,
+    pub sp: PhysAddr,
+    /// Frame pointer, which points to the frame of the caller unless it's omitted.
+    pub fp: PhysAddr,
+}
+
+#[derive(Debug, Clone)]
+pub struct Thread {
+    pub id: u32,
+    pub registers: Registers,
+}
+
+#[derive(Debug, Clone)]
+pub struct CoreDump {
+    pub kind: Kind,
+    pub arch: Architecture,
+    pub endianness: Endianness,
+    pub regions: Vec<Region>,
+    pub modules: Vec<Module>,
+    pub threads: Vec<Thread>,
+    /// Index of the thread that crashed.
+    pub crashed: Option<usize>,
+    /// Why the process crashed, e.g. `SIGSEGV at 0x0`.
+    pub reason: Option<String>,
+}
+
+impl CoreDump {
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        if data.starts_with(MINIDUMP_MAGIC) {
+            return parse_minidump(data);
+        }
+
+        match object::File::parse(data).ok()? {
+            object::File::Elf32(ref elf) if elf.kind() == ObjectKind::Core => parse_elf(elf, data),
+            object::File::Elf64(ref elf) if elf.kind() == ObjectKind::Core => parse_elf(elf, data),
+            _ => None,
+        }
+    }
+
+    /// Name of the format, as it's shown in place of an object format.
+    pub fn format(&self) -> &'static str {
+        match self.kind {
+            Kind::Elf => "ELF core dump",
+            Kind::Minidump => "Minidump",
+        }
+    }
+
+    /// Module the address is mapped from.
+    pub fn module_by_addr(&self, addr: PhysAddr) -> Option<&Module> {
+        self.modules.iter().find(|module| (module.start..module.end).contains(&addr))
+    }
+}
+
+fn read(data: &[u8], offset: usize, size: usize, little_endian: bool) -> Option<u64> {
+    let bytes = data.get(offset..offset.checked_add(size)?)?;
+    let mut value = [0; 8];
+    Some(if little_endian {
+        value[..size].copy_from_slice(bytes);
+        u64::from_le_bytes(value)
+    } else {
+        value[8 - size..].copy_from_slice(bytes);
+        u64::from_be_bytes(value)
+    })
+}
+
+/// Range of the dump holding `size` bytes at `offset`, cut short if the dump is truncated.
+fn clamp(data: &[u8], offset: usize, size: usize) -> Range<usize> {
+    let end = offset.saturating_add(size).min(data.len());
+    offset.min(end)..end
+}
+
+/// Offset of the thread's id and registers in `elf_prstatus`, along with the index of the program
+/// counter, stack pointer and frame pointer among the registers.
+fn prstatus_layout(arch: Architecture) -> Option<(usize, usize, [usize; 3])> {
+    match arch {
+        Architecture::X86_64 => Some((32, 112, [16, 19, 4])),
+        Architecture::Aarch64 => Some((32, 112, [32, 31, 29])),
+        Architecture::Riscv64 => Some((32, 112, [0, 2, 8])),
+        Architecture::I386 => Some((24, 72, [12, 15, 5])),
+        Architecture::Arm => Some((24, 72, [15, 13, 11])),
+        _ => None,
+    }
+}
+
+fn signal_name(signal: u64) -> String {
+    match signal {
+        4 => "SIGILL".to_string(),
+        5 => "SIGTRAP".to_string(),
+        6 => "SIGABRT".to_string(),
+        7 => "SIGBUS".to_string(),
+        8 => "SIGFPE".to_string(),
+        11 => "SIGSEGV".to_string(),
+        signal => format!("signal {signal}"),
+    }
+}
+
+fn parse_elf<'data, Elf: FileHeader>(
+    obj: &ElfFile<'data, Elf>,
+    data: &'data [u8],
+) -> Option<CoreDump> {
+    let endian = obj.endian();
+    let little_endian = obj.is_little_endian();
+    let width = if obj.is_64() { 8 } else { 4 };
+    let mut dump = CoreDump {
+        kind: Kind::Elf,
+        arch: obj.architecture(),
+        endianness: obj.endianness(),
+        regions: Vec::new(),
+        modules: Vec::new(),
+        threads: Vec::new(),
+        crashed: None,
+        reason: None,
+    };
+
+    for header in obj.raw_segments() {
+        match header.p_type(endian) {
+            elf::PT_LOAD => {
+                let start = header.p_vaddr(endian).into() as usize;
+                let memsz = header.p_memsz(endian).into() as usize;
+                let filesz = header.p_filesz(endian).into() as usize;
+                let offset = header.p_offset(endian).into() as usize;
+                let flags = header.p_flags(endian);
+
+                dump.regions.push(Region {
+                    start,
+                    end: start + memsz,
+                    data: clamp(data, offset, filesz.min(memsz)),
+                    permissions: Permissions {
+                        read: flags & elf::PF_R != 0,
+                        write: flags & elf::PF_W != 0,
+                        execute: flags & elf::PF_X != 0,
+                    },
+                });
+            }
+            elf::PT_NOTE => {
+                let mut notes = match header.notes(endian, data) {
+                    Ok(Some(notes)) => notes,
+                    _ => continue,
+                };
+
+                while let Ok(Some(note)) = notes.next() {
+                    if note.name() != b"CORE" {
+                        continue;
+                    }
+
+                    let desc = note.desc();
+                    match note.n_type(endian) {
+                        elf::NT_PRSTATUS => {
+                            let (id_offset, regs_offset, [pc, sp, fp]) =
+                                prstatus_layout(dump.arch)?;
+                            let reg = |idx: usize| {
+                                read(desc, regs_offset + idx * width, width, little_endian)
+                                    .map_or(0, |value| value as usize)
+                            };
+
+                            // the thread that crashed comes first and is the one with a signal
+                            let signal = read(desc, 12, 2, little_endian).unwrap_or(0);
+                            if signal != 0 && dump.crashed.is_none() {
+                                dump.crashed = Some(dump.threads.len());
+                                dump.reason = Some(signal_name(signal));
+                            }
+
+                            dump.threads.push(Thread {
+                                id: read(desc, id_offset, 4, little_endian).unwrap_or(0) as u32,
+                                registers: Registers {
+                                    pc: reg(pc),
+                                    sp: reg(sp),
+                                    fp: reg(fp),
+                                },
+                            });
+                        }
+                        // follows the status of the thread that crashed, with the faulting address
+                        elf::NT_SIGINFO => {
+                            let signal = read(desc, 0, 4, little_endian).unwrap_or(0);
+                            let addr = read(desc, 4 + width * 2, width, little_endian);
+                            if let (4 | 7 | 8 | 11, Some(addr)) = (signal, addr) {
+                                let name = signal_name(signal);
+                                dump.reason = Some(format!("{name} at {addr:#x}"));
+                            }
+                        }
+                        elf::NT_FILE => dump.modules = parse_file_note(desc, width, little_endian),
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Some(dump)
+}
+
+/// Files mapped into the process, listed as the number of mappings and the size of a page,
+/// followed by the range and offset in pages of every mapping and then the path of every mapping.
+fn parse_file_note(desc: &[u8], width: usize, little_endian: bool) -> Vec<Module> {
+    let field = |offset: usize| read(desc, offset, width, little_endian).map(|v| v as usize);
+    let (count, page_size) = match (field(0), field(width)) {
+        (Some(count), Some(page_size)) => (count, page_size as u64),
+        _ => return Vec::new(),
+    };
+
+    let mut paths = match desc.get(width * 2 + count.saturating_mul(width * 3)..) {
+        Some(paths) => paths.split(|&b| b == 0),
+        None => return Vec::new(),
+    };
+
+    let mut modules = Vec::new();
+    for idx in 0..count {
+        let entry = width * 2 + idx * width * 3;
+        let range = (field(entry), field(entry + width), field(entry + 2 * width));
+        let (start, end, offset) = match range {
+            (Some(start), Some(end), Some(offset)) => (start, end, offset as u64),
+            _ => break,
+        };
+
+        let path = match paths.next() {
+            Some(path) => String::from_utf8_lossy(path).into_owned(),
+            None => break,
+        };
+
+        modules.push(Module {
+            path,
+            start,
+            end,
+            file_offset: offset * page_size,
+        });
+    }
+
+    modules
+}
+
+/// String of a minidump, stored as it's length in bytes followed by UTF-16.
+fn minidump_string(data: &[u8], rva: usize) -> Option<String> {
+    let len = read(data, rva, 4, true)? as usize;
+    let bytes = data.get(rva + 4..rva + 4 + len)?;
+    let units: Vec<u16> = bytes.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect();
+    Some(String::from_utf16_lossy(&units))
+}
+
+/// Offset of the program counter, stack pointer and frame pointer in a thread's `CONTEXT`, along
+/// with the size of the registers.
+fn context_layout(arch: Architecture) -> Option<([usize; 3], usize)> {
+    match arch {
+        Architecture::X86_64 => Some(([0xF8, 0x98, 0xA0], 8)),
+        Architecture::Aarch64 => Some(([0x108, 0x100, 0xF0], 8)),
+        Architecture::I386 => Some(([0xB8, 0xC4, 0xB4], 4)),
+        Architecture::Arm => Some(([0x40, 0x38, 0x30], 4)),
+        _ => None,
+    }
+}
+
+fn exception_name(code: u32) -> String {
+    let name = match code {
+        0xC0000005 => "EXCEPTION_ACCESS_VIOLATION",
+        0xC000001D => "EXCEPTION_ILLEGAL_INSTRUCTION",
+        0xC0000094 => "EXCEPTION_INT_DIVIDE_BY_ZERO",
+        0xC0000096 => "EXCEPTION_PRIV_INSTRUCTION",
+        0xC00000FD => "EXCEPTION_STACK_OVERFLOW",
+        0xC0000374 => "STATUS_HEAP_CORRUPTION",
+        0xC0000409 => "STATUS_STACK_BUFFER_OVERRUN",
+        0x80000003 => "EXCEPTION_BREAKPOINT",
+        0x80000004 => "EXCEPTION_SINGLE_STEP",
+        0xE06D7363 => "C++ exception",
+        code => return format!("exception {code:#X}"),
+    };
+
+    name.to_string()
+}
+
+/// Permissions of memory by it's `PAGE_*` protection.
+fn page_permissions(protect: u32) -> Permissions {
+    let protect = protect & 0xFF;
+    Permissions {
+        read: protect & !0x01 != 0,
+        write: protect & (0x04 | 0x08 | 0x40 | 0x80) != 0,
+        execute: protect & (0x10 | 0x20 | 0x40 | 0x80) != 0,
+    }
+}
+
+fn parse_minidump(data: &[u8]) -> Option<CoreDump> {
+    let u16_at = |offset: usize| read(data, offset, 2, true).map(|v| v as u16);
+    let u32_at = |offset: usize| read(data, offset, 4, true).map(|v| v as u32);
+    let u64_at = |offset: usize| read(data, offset, 8, true);
+
+    let mut dump = CoreDump {
+        kind: Kind::Minidump,
+        arch: Architecture::Unknown,
+        endianness: Endianness::Little,
+        regions: Vec::new(),
+        modules: Vec::new(),
+        threads: Vec::new(),
+        crashed: None,
+        reason: None,
+    };
+
+    let mut contexts = Vec::new();
+    let mut memory_info = Vec::new();
+    let mut exception = None;
+
+    let streams = u32_at(8)? as usize;
+    let directory = u32_at(12)? as usize;
+    for idx in 0..streams {
+        let entry = directory + idx * 12;
+        let (kind, rva) = (u32_at(entry)?, u32_at(entry + 8)? as usize);
+
+        match kind {
+            THREAD_LIST_STREAM => {
+                for thread in 0..u32_at(rva)? as usize {
+                    let thread = rva + 4 + thread * 48;
+                    let context = (u32_at(thread + 44)? as usize, u32_at(thread + 40)? as usize);
+                    contexts.push((u32_at(thread)?, context));
+                }
+            }
+            MODULE_LIST_STREAM => {
+                for module in 0..u32_at(rva)? as usize {
+                    let module = rva + 4 + module * 108;
+                    let start = u64_at(module)? as usize;
+                    dump.modules.push(Module {
+                        path: minidump_string(data, u32_at(module + 20)? as usize)?,
+                        start,
+                        end: start + u32_at(module + 8)? as usize,
+                        file_offset: 0,
+                    });
+                }
+            }
+            MEMORY_LIST_STREAM => {
+                for range in 0..u32_at(rva)? as usize {
+                    let range = rva + 4 + range * 16;
+                    let start = u64_at(range)? as usize;
+                    let size = u32_at(range + 8)? as usize;
+                    dump.regions.push(Region {
+                        start,
+                        end: start + size,
+                        data: clamp(data, u32_at(range + 12)? as usize, size),
+                        permissions: Permissions::default(),
+                    });
+                }
+            }
+            // the memory of every range follows the previous one
+            MEMORY64_LIST_STREAM => {
+                let mut offset = u64_at(rva + 8)? as usize;
+                for range in 0..u64_at(rva)? as usize {
+                    let range = rva + 16 + range * 16;
+                    let start = u64_at(range)? as usize;
+                    let size = u64_at(range + 8)? as usize;
+                    dump.regions.push(Region {
+                        start,
+                        end: start + size,
+                        data: clamp(data, offset, size),
+                        permissions: Permissions::default(),
+                    });
+                    offset = offset.saturating_add(size);
+                }
+            }
+            EXCEPTION_STREAM => {
+                let context = (u32_at(rva + 164)? as usize, u32_at(rva + 160)? as usize);
+                let (thread, code, addr) = (u32_at(rva)?, u32_at(rva + 8)?, u64_at(rva + 24)?);
+                exception = Some((thread, context));
+                dump.reason = Some(format!("{} at {addr:#x}", exception_name(code)));
+            }
+            SYSTEM_INFO_STREAM => {
+                dump.arch = match u16_at(rva)? {
+                    0 => Architecture::I386,
+                    5 => Architecture::Arm,
+                    9 => Architecture::X86_64,
+                    12 => Architecture::Aarch64,
+                    _ => Architecture::Unknown,
+                };
+            }
+            MEMORY_INFO_LIST_STREAM => {
+                let (header, size) = (u32_at(rva)? as usize, u32_at(rva + 4)? as usize);
+                for info in 0..u64_at(rva + 8)? as usize {
+                    let info = rva + header + info * size;
+                    let start = u64_at(info)? as usize;
+                    let end = start + u64_at(info + 24)? as usize;
+                    memory_info.push((start..end, page_permissions(u32_at(info + 36)?)));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for region in dump.regions.iter_mut() {
+        let info = memory_info.iter().find(|(range, _)| range.contains(&region.start));
+        region.permissions = match info {
+            Some((_, permissions)) => *permissions,
+            // without memory info, only the images of modules are assumed to be code
+            None => {
+                let execute = dump.modules.iter().any(|m| (m.start..m.end).contains(&region.start));
+                Permissions {
+                    read: true,
+                    write: !execute,
+                    execute,
+                }
+            }
+        };
+    }
+
+    // the context of the thread that raised an exception is the one it raised it in
+    let (offsets, width) = context_layout(dump.arch)?;
+    for (id, (rva, size)) in contexts {
+        let (rva, _) = match exception {
+            Some((thread, context)) if thread == id => context,
+            _ => (rva, size),
+        };
+
+        let reg = |offset: usize| read(data, rva + offset, width, true).map_or(0, |v| v as usize);
+        if exception.is_some_and(|(thread, _)| thread == id) {
+            dump.crashed = Some(dump.threads.len());
+        }
+
+        dump.threads.push(Thread {
+            id,
+            registers: Registers {
+                pc: reg(offsets[0]),
+                sp: reg(offsets[1]),
+                fp: reg(offsets[2]),
+            },
+        });
+    }
+
+    Some(dump)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn put(data: &mut [u8], offset: usize, bytes: &[u8]) {
+        data[offset..offset + bytes.len()].copy_from_slice(bytes);
+    }
+
+    fn put32(data: &mut [u8], offset: usize, value: u32) {
+        put(data, offset, &value.to_le_bytes());
+    }
+
+    fn put64(data: &mut [u8], offset: usize, value: u64) {
+        put(data, offset, &value.to_le_bytes());
+    }
+
+    /// `elf_prstatus` of an x86_64 thread that got a signal, zero if it didn't.
+    fn prstatus(id: u32, signal: u16, [pc, sp, fp]: [u64; 3]) -> Vec<u8> {
+        let mut desc = vec![0; 336];
+        put(&mut desc, 12, &signal.to_le_bytes());
+        put32(&mut desc, 32, id);
+        put64(&mut desc, 112 + 16 * 8, pc);
+        put64(&mut desc, 112 + 19 * 8, sp);
+        put64(&mut desc, 112 + 4 * 8, fp);
+        desc
+    }
+
+    /// x86_64 ELF core dump of two threads, the second of which crashed, and 16 bytes of code.
+    fn elf_core() -> Vec<u8> {
+        let mut notes = Vec::new();
+        let threads = [
+            prstatus(100, 0, [0x401000, 0x7FFE_0000, 0]),
+            prstatus(101, 11, [0x401008, 0x7FFD_0000, 0x7FFD_0010]),
+        ];
+        for desc in threads {
+            notes.extend_from_slice(&5u32.to_le_bytes());
+            notes.extend_from_slice(&(desc.len() as u32).to_le_bytes());
+            notes.extend_from_slice(&elf::NT_PRSTATUS.to_le_bytes());
+            notes.extend_from_slice(b"CORE\0\0\0\0");
+            notes.extend_from_slice(&desc);
+        }
+
+        let notes_offset = 64 + 2 * 56;
+        let code_offset = notes_offset + notes.len();
+        let mut data = vec![0; notes_offset];
+        put(&mut data, 0, &[0x7F, b'E', b'L', b'F', 2, 1, 1]);
+        put(&mut data, 16, &elf::ET_CORE.to_le_bytes());
+        put(&mut data, 18, &elf::EM_X86_64.to_le_bytes());
+        put32(&mut data, 20, 1);
+        put64(&mut data, 32, 64);
+        put(&mut data, 52, &[64, 0, 56, 0, 2, 0]);
+
+        // type and flags, offset, address, physical address, size in the file and in memory and
+        // the alignment of every program header
+        let note = elf::PT_NOTE as u64;
+        let load = elf::PT_LOAD as u64 | ((elf::PF_R | elf::PF_X) as u64) << 32;
+        let (notes_offset, notes_size, code) =
+            (notes_offset as u64, notes.len() as u64, code_offset as u64);
+        let headers = [
+            [note, notes_offset, 0, 0, notes_size, 0, 4],
+            [load, code, 0x400000, 0x400000, 16, 0x2000, 0x1000],
+        ];
+        for (idx, field) in headers.iter().flatten().enumerate() {
+            put64(&mut data, 64 + idx * 8, *field);
+        }
+
+        data.extend_from_slice(&notes);
+        data.extend_from_slice(&[0xCC; 16]);
+        data
+    }
+
+    /// x86_64 minidump of two threads, the second of which raised an access violation, along with
+    /// a module and 16 bytes of its code.
+    fn minidump() -> Vec<u8> {
+        let mut data = vec![0; 0x810];
+        put(&mut data, 0, MINIDUMP_MAGIC);
+        put32(&mut data, 8, 5);
+        put32(&mut data, 12, 32);
+
+        let streams = [
+            (SYSTEM_INFO_STREAM, 0x100),
+            (THREAD_LIST_STREAM, 0x140),
+            (EXCEPTION_STREAM, 0x200),
+            (MEMORY_LIST_STREAM, 0x300),
+            (MODULE_LIST_STREAM, 0x340),
+        ];
+        for (idx, (kind, rva)) in streams.into_iter().enumerate() {
+            put32(&mut data, 32 + idx * 12, kind);
+            put32(&mut data, 32 + idx * 12 + 8, rva);
+        }
+
+        // AMD64
+        put(&mut data, 0x100, &9u16.to_le_bytes());
+
+        // id and context of every thread
+        put32(&mut data, 0x140, 2);
+        for (idx, (id, context)) in [(1, 0x500), (2, 0x600)].into_iter().enumerate() {
+            let thread = 0x144 + idx * 48;
+            put32(&mut data, thread, id);
+            put32(&mut data, thread + 40, 0x100);
+            put32(&mut data, thread + 44, context);
+        }
+
+        // thread and code of the exception, which was raised at address zero in its own context
+        put32(&mut data, 0x200, 2);
+        put32(&mut data, 0x208, 0xC0000005);
+        put32(&mut data, 0x200 + 160, 0x100);
+        put32(&mut data, 0x200 + 164, 0x700);
+
+        put32(&mut data, 0x300, 1);
+        put64(&mut data, 0x304, 0x1_4000_1000);
+        put32(&mut data, 0x30C, 0x10);
+        put32(&mut data, 0x310, 0x800);
+
+        put32(&mut data, 0x340, 1);
+        put64(&mut data, 0x344, 0x1_4000_0000);
+        put32(&mut data, 0x34C, 0x3000);
+        put32(&mut data, 0x344 + 20, 0x400);
+
+        let name: Vec<u8> = "C:\\app.exe".encode_utf16().flat_map(u16::to_le_bytes).collect();
+        put32(&mut data, 0x400, name.len() as u32);
+        put(&mut data, 0x404, &name);
+
+        for (context, pc) in [
+            (0x500, 0x1_4000_1000),
+            (0x600, 0x1_4000_1004),
+            (0x700, 0x1_4000_1008),
+        ] {
+            put64(&mut data, context + 0xF8, pc);
+            put64(&mut data, context + 0x98, 0x10_0000 + context as u64);
+            put64(&mut data, context + 0xA0, 0x10_0010 + context as u64);
+        }
+
+        data[0x800..].fill(0xCC);
+        data
+    }
+
+    #[test]
+    fn elf() {
+        let data = elf_core();
+        let dump = CoreDump::parse(&data).unwrap();
+        assert_eq!(dump.kind, Kind::Elf);
+        assert_eq!(dump.arch, Architecture::X86_64);
+        assert_eq!(dump.threads.len(), 2);
+
+        // threads without a signal didn't crash
+        assert_eq!(dump.crashed, Some(1));
+        assert_eq!(dump.reason.as_deref(), Some("SIGSEGV"));
+
+        let thread = &dump.threads[1];
+        let registers = thread.registers;
+        assert_eq!(thread.id, 101);
+        assert_eq!(registers.pc, 0x401008);
+        assert_eq!(registers.sp, 0x7FFD_0000);
+        assert_eq!(registers.fp, 0x7FFD_0010);
+
+        assert_eq!(dump.regions.len(), 1);
+        let region = &dump.regions[0];
+        assert_eq!((region.start, region.end), (0x400000, 0x402000));
+        assert_eq!(data[region.data.clone()], [0xCC; 16]);
+        assert!((region.start..region.end).contains(&registers.pc));
+
+        let permissions = Permissions {
+            read: true,
+            write: false,
+            execute: true,
+        };
+        assert_eq!(region.permissions, permissions);
+    }
+
+    #[test]
+    fn truncated_elf() {
+        let data = elf_core();
+        let dump = CoreDump::parse(&data[..data.len() - 8]).unwrap();
+        assert_eq!(dump.regions[0].data.len(), 8);
+        assert_eq!(dump.regions[0].end, 0x402000);
+    }
+
+    #[test]
+    fn minidump_streams() {
+        let data = minidump();
+        let dump = CoreDump::parse(&data).unwrap();
+        assert_eq!(dump.kind, Kind::Minidump);
+        assert_eq!(dump.arch, Architecture::X86_64);
+        assert_eq!(dump.threads.len(), 2);
+        assert_eq!(dump.crashed, Some(1));
+        let reason = dump.reason.as_deref();
+        assert_eq!(reason, Some("EXCEPTION_ACCESS_VIOLATION at 0x0"));
+
+        // the thread that crashed has the registers of the context the exception was raised in
+        assert_eq!(dump.threads[0].registers.pc, 0x1_4000_1000);
+        let thread = &dump.threads[1];
+        assert_eq!(thread.id, 2);
+        assert_eq!(thread.registers.pc, 0x1_4000_1008);
+        assert_eq!(thread.registers.sp, 0x10_0700);
+        assert_eq!(thread.registers.fp, 0x10_0710);
+
+        assert_eq!(dump.modules.len(), 1);
+        let module = dump.module_by_addr(thread.registers.pc).unwrap();
+        assert_eq!(module.name(), "app.exe");
+        assert_eq!((module.start, module.end), (0x1_4000_0000, 0x1_4000_3000));
+
+        // memory in the image of a module is assumed to be code
+        assert_eq!(dump.regions.len(), 1);
+        let region = &dump.regions[0];
+        assert_eq!((region.start, region.end), (0x1_4000_1000, 0x1_4000_1010));
+        assert_eq!(region.data, 0x800..0x810);
+        assert!(region.permissions.execute && !region.permissions.write);
+    }
+
+    #[test]
+    fn truncated_minidump() {
+        let data = minidump();
+        for len in 0..0x102 {
+            assert!(CoreDump::parse(&data[..len]).is_none());
+        }
+    }
+}
@@ -0,0 +1,356 @@
+//! Android's dex files, which hold the Dalvik bytecode of every class in an app.
+//!
+//! Unlike class files, all classes share the same pools of strings, types, fields and methods,
+//! which instructions index into.
+
+use crate::classfile::{descriptor_types, parameters};
+
+/// Signature at the start of a dex file, followed by a three digit version and a null byte.
+const MAGIC: &[u8] = b"dex\n";
+
+/// Value of the endian tag in a little endian dex file.
+const ENDIAN_CONSTANT: u32 = 0x1234_5678;
+
+/// Value of an index that doesn't refer to anything.
+const NO_INDEX: u32 = u32::MAX;
+
+#[derive(Debug, Clone)]
+pub struct Method {
+    /// Qualified name of the method with its parameters, e.g. `java.lang.Object.equals(Object)`.
+    pub name: String,
+    /// Offset of the method's instructions from the start of the file.
+    pub offset: usize,
+    pub size: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Class {
+    /// Qualified name of the class, e.g. `java.lang.Object`.
+    pub name: String,
+    pub superclass: Option<String>,
+    /// Methods that have instructions, abstract and native methods have none.
+    pub methods: Vec<Method>,
+}
+
+/// Field or method, as the type of the class it's part of, its type or prototype and its name.
+#[derive(Debug, Clone, Copy)]
+struct Member {
+    class: u16,
+    ty: u16,
+    name: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Dex {
+    pub classes: Vec<Class>,
+    strings: Vec<String>,
+    /// Descriptor of each type, as an index into the strings.
+    types: Vec<u32>,
+    /// Descriptor of each prototype's parameters.
+    protos: Vec<String>,
+    fields: Vec<Member>,
+    methods: Vec<Member>,
+}
+
+fn u16_at(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(data.get(offset..offset.checked_add(2)?)?.try_into().ok()?))
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(offset..offset.checked_add(4)?)?.try_into().ok()?))
+}
+
+fn uleb128(data: &[u8], offset: &mut usize) -> Option<u32> {
+    let mut value = 0u32;
+    for shift in (0..35).step_by(7) {
+        let byte = *data.get(*offset)?;
+        *offset += 1;
+        value |= ((byte & 0x7F) as u32).checked_shl(shift)?;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+
+    None
+}
+
+/// Offset and number of the entries of one of the header's sections.
+fn section(data: &[u8], header_offset: usize) -> Option<(usize, usize)> {
+    let size = u32_at(data, header_offset)? as usize;
+    let offset = u32_at(data, header_offset + 4)? as usize;
+    Some((offset, size))
+}
+
+/// Whether the data starts like a dex file.
+pub fn is_dex(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+impl Dex {
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        if !is_dex(data) || u32_at(data, 0x28)? != ENDIAN_CONSTANT {
+            return None;
+        }
+
+        let mut dex = Self {
+            classes: Vec::new(),
+            strings: Vec::new(),
+            types: Vec::new(),
+            protos: Vec::new(),
+            fields: Vec::new(),
+            methods: Vec::new(),
+        };
+
+        // strings are stored in modified UTF-8, which only differs in how it encodes nulls and
+        // characters outside of the basic multilingual plane
+        let (offset, size) = section(data, 0x38)?;
+        for idx in 0..size {
+            let mut offset = u32_at(data, offset + idx * 4)? as usize;
+            uleb128(data, &mut offset)?;
+            let len = data.get(offset..)?.iter().position(|&b| b == 0)?;
+            dex.strings.push(String::from_utf8_lossy(&data[offset..offset + len]).into_owned());
+        }
+
+        let (offset, size) = section(data, 0x40)?;
+        for idx in 0..size {
+            dex.types.push(u32_at(data, offset + idx * 4)?);
+        }
+
+        let (offset, size) = section(data, 0x48)?;
+        for idx in 0..size {
+            let parameters_offset = u32_at(data, offset + idx * 12 + 8)? as usize;
+            let mut descriptor = String::from("(");
+            if parameters_offset != 0 {
+                let count = u32_at(data, parameters_offset)? as usize;
+                for param in 0..count {
+                    let ty = u16_at(data, parameters_offset + 4 + param * 2)?;
+                    descriptor += dex.descriptor(ty as u32)?;
+                }
+            }
+            descriptor += ")";
+            dex.protos.push(descriptor);
+        }
+
+        for (header_offset, members) in [(0x50, &mut dex.fields), (0x58, &mut dex.methods)] {
+            let (offset, size) = section(data, header_offset)?;
+            for idx in 0..size {
+                members.push(Member {
+                    class: u16_at(data, offset + idx * 8)?,
+                    ty: u16_at(data, offset + idx * 8 + 2)?,
+                    name: u32_at(data, offset + idx * 8 + 4)?,
+                });
+            }
+        }
+
+        let (offset, size) = section(data, 0x60)?;
+        for idx in 0..size {
+            let def = offset + idx * 32;
+            let name = dex.type_name(u32_at(data, def)?)?;
+            let superclass = dex.type_name(u32_at(data, def + 8)?);
+            let class_data = u32_at(data, def + 24)? as usize;
+
+            let methods = match class_data {
+                0 => Vec::new(),
+                _ => dex.class_methods(data, class_data).unwrap_or_default(),
+            };
+
+            dex.classes.push(Class {
+                name,
+                superclass,
+                methods,
+            });
+        }
+
+        Some(dex)
+    }
+
+    /// Methods with code of a class, given the offset of its class data item.
+    fn class_methods(&self, data: &[u8], mut offset: usize) -> Option<Vec<Method>> {
+        let static_fields = uleb128(data, &mut offset)?;
+        let instance_fields = uleb128(data, &mut offset)?;
+        let direct_methods = uleb128(data, &mut offset)?;
+        let virtual_methods = uleb128(data, &mut offset)?;
+
+        for _ in 0..static_fields.checked_add(instance_fields)? {
+            uleb128(data, &mut offset)?;
+            uleb128(data, &mut offset)?;
+        }
+
+        let mut methods = Vec::new();
+        for count in [direct_methods, virtual_methods] {
+            // method indices are stored as the difference to the previous one in the list
+            let mut idx = 0u32;
+            for _ in 0..count {
+                idx = idx.wrapping_add(uleb128(data, &mut offset)?);
+                let _access_flags = uleb128(data, &mut offset)?;
+                let code = uleb128(data, &mut offset)? as usize;
+                if code == 0 {
+                    continue;
+                }
+
+                let units = u32_at(data, code + 12)? as usize;
+                let size = units * 2;
+                let offset = code + 16;
+                if data.get(offset..offset + size).is_none() {
+                    continue;
+                }
+
+                let name = self.method(idx)?;
+                methods.push(Method { name, offset, size });
+            }
+        }
+
+        Some(methods)
+    }
+
+    fn descriptor(&self, ty: u32) -> Option<&str> {
+        self.strings.get(*self.types.get(ty as usize)? as usize).map(String::as_str)
+    }
+
+    /// String literal, quoted like it'd be written in Java.
+    pub fn string(&self, idx: u32) -> Option<String> {
+        self.strings.get(idx as usize).map(|str| format!("{str:?}"))
+    }
+
+    /// Name of a type as it's written in Java.
+    pub fn type_name(&self, ty: u32) -> Option<String> {
+        if ty == NO_INDEX {
+            return None;
+        }
+
+        descriptor_types(self.descriptor(ty)?).pop()
+    }
+
+    pub fn field(&self, idx: u32) -> Option<String> {
+        let field = self.fields.get(idx as usize)?;
+        let class = self.type_name(field.class as u32)?;
+        Some(format!("{class}.{}", self.strings.get(field.name as usize)?))
+    }
+
+    pub fn method(&self, idx: u32) -> Option<String> {
+        let method = self.methods.get(idx as usize)?;
+        let class = self.type_name(method.class as u32)?;
+        let name = self.strings.get(method.name as usize)?;
+        Some(format!("{class}.{name}{}", self.proto(method.ty as u32)?))
+    }
+
+    /// Parameters of a method's prototype.
+    pub fn proto(&self, idx: u32) -> Option<String> {
+        self.protos.get(idx as usize).map(|descriptor| parameters(descriptor))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uleb128(data: &mut Vec<u8>, mut value: u32) {
+        loop {
+            let byte = (value & 0x7F) as u8;
+            value >>= 7;
+            if value == 0 {
+                data.push(byte);
+                break;
+            }
+            data.push(byte | 0x80);
+        }
+    }
+
+    fn put(data: &mut [u8], offset: usize, value: u32) {
+        data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    /// Dex file of a class `Foo` with a method `run()` that only returns, and the given number
+    /// of static and instance fields in its class data. Returns the offset of the method's code,
+    /// which is followed by the class data.
+    fn dex(fields: (u32, u32)) -> (Vec<u8>, usize) {
+        let mut data = vec![0; 0x70];
+        data[..8].copy_from_slice(b"dex\n035\0");
+        put(&mut data, 0x28, ENDIAN_CONSTANT);
+
+        // string, type, prototype and method ids and the class definition follow the header
+        let ids = [
+            (0x38, 4, 4),
+            (0x40, 3, 4),
+            (0x48, 1, 12),
+            (0x58, 1, 8),
+            (0x60, 1, 32),
+        ];
+        let mut sections = Vec::new();
+        for (header, count, size) in ids {
+            let offset = data.len();
+            put(&mut data, header, count);
+            put(&mut data, header + 4, offset as u32);
+            data.resize(offset + count as usize * size, 0);
+            sections.push(offset);
+        }
+
+        for (idx, string) in ["LFoo;", "Ljava/lang/Object;", "V", "run"].iter().enumerate() {
+            let offset = data.len();
+            put(&mut data, sections[0] + idx * 4, offset as u32);
+            uleb128(&mut data, string.len() as u32);
+            data.extend_from_slice(string.as_bytes());
+            data.push(0);
+        }
+
+        for ty in 0..3 {
+            put(&mut data, sections[1] + ty * 4, ty as u32);
+        }
+
+        // the prototype returns void without parameters, the method is named `run`
+        put(&mut data, sections[2], 2);
+        put(&mut data, sections[2] + 4, 2);
+        put(&mut data, sections[3] + 4, 3);
+
+        // a single `return-void`
+        let code = data.len();
+        data.extend_from_slice(&[1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0x0E, 0]);
+
+        let class = sections[4];
+        let class_data = data.len() as u32;
+        put(&mut data, class + 8, 1);
+        put(&mut data, class + 16, NO_INDEX);
+        put(&mut data, class + 24, class_data);
+        for count in [fields.0, fields.1, 1, 0, 0, 1, code as u32] {
+            uleb128(&mut data, count);
+        }
+
+        (data, code)
+    }
+
+    #[test]
+    fn minimal() {
+        let (data, code) = dex((0, 0));
+        let dex = Dex::parse(&data).unwrap();
+        assert_eq!(dex.classes.len(), 1);
+
+        let class = &dex.classes[0];
+        assert_eq!(class.name, "Foo");
+        assert_eq!(class.superclass.as_deref(), Some("java.lang.Object"));
+        assert_eq!(class.methods.len(), 1);
+        assert_eq!(class.methods[0].name, "Foo.run()");
+        assert_eq!(class.methods[0].offset, code + 16);
+        assert_eq!(class.methods[0].size, 2);
+    }
+
+    #[test]
+    fn truncated() {
+        let (data, code) = dex((0, 0));
+        for len in 0..code {
+            assert!(Dex::parse(&data[..len]).is_none());
+        }
+
+        // classes whose code is cut off are kept, without their methods
+        for len in code..data.len() {
+            let dex = Dex::parse(&data[..len]).unwrap();
+            assert!(dex.classes[0].methods.is_empty());
+        }
+    }
+
+    #[test]
+    fn too_many_fields() {
+        let (data, _) = dex((u32::MAX, 1));
+        let dex = Dex::parse(&data).unwrap();
+        assert!(dex.classes[0].methods.is_empty());
+    }
+}
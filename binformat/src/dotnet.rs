@@ -0,0 +1,624 @@
+//! Metadata of .NET assemblies, which describe the managed types and methods that are compiled to
+//! CIL instead of native code.
+//!
+//! The CLR header is found through the PE's COM descriptor directory and points to the metadata
+//! root, whose `#~` stream holds the tables and whose heaps hold the names they refer to.
+
+use object::pe;
+use object::read::pe::{ImageNtHeaders, PeFile};
+use object::LittleEndian as LE;
+use object::Object;
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Signature at the start of the metadata root, `BSJB`.
+const METADATA_SIGNATURE: u32 = 0x424A_5342;
+
+const MODULE: usize = 0x00;
+const TYPE_REF: usize = 0x01;
+const TYPE_DEF: usize = 0x02;
+const FIELD: usize = 0x04;
+const METHOD_DEF: usize = 0x06;
+const PARAM: usize = 0x08;
+const INTERFACE_IMPL: usize = 0x09;
+const MEMBER_REF: usize = 0x0A;
+const DECL_SECURITY: usize = 0x0E;
+const STAND_ALONE_SIG: usize = 0x11;
+const EVENT: usize = 0x14;
+const PROPERTY: usize = 0x17;
+const MODULE_REF: usize = 0x1A;
+const TYPE_SPEC: usize = 0x1B;
+const ASSEMBLY: usize = 0x20;
+const ASSEMBLY_REF: usize = 0x23;
+const FILE: usize = 0x26;
+const EXPORTED_TYPE: usize = 0x27;
+const MANIFEST_RESOURCE: usize = 0x28;
+const NESTED_CLASS: usize = 0x29;
+const GENERIC_PARAM: usize = 0x2A;
+const METHOD_SPEC: usize = 0x2B;
+const GENERIC_PARAM_CONSTRAINT: usize = 0x2C;
+
+/// Table of tokens that refer to the user string heap instead of a table.
+const USER_STRING: u32 = 0x70;
+
+/// Tag of a coded index that doesn't refer to any table.
+const UNUSED: usize = usize::MAX;
+
+/// Column of a metadata table.
+#[derive(Clone, Copy)]
+enum Column {
+    U16,
+    U32,
+    Str,
+    Guid,
+    Blob,
+    /// Row of a table.
+    Table(usize),
+    /// Row of one of several tables, the lowest bits telling which.
+    Coded(&'static [usize]),
+}
+
+use Column::*;
+
+const TYPE_DEF_OR_REF: Column = Coded(&[TYPE_DEF, TYPE_REF, TYPE_SPEC]);
+const HAS_CONSTANT: Column = Coded(&[FIELD, PARAM, PROPERTY]);
+const HAS_CUSTOM_ATTRIBUTE: Column = Coded(&[
+    METHOD_DEF,
+    FIELD,
+    TYPE_REF,
+    TYPE_DEF,
+    PARAM,
+    INTERFACE_IMPL,
+    MEMBER_REF,
+    MODULE,
+    DECL_SECURITY,
+    PROPERTY,
+    EVENT,
+    STAND_ALONE_SIG,
+    MODULE_REF,
+    TYPE_SPEC,
+    ASSEMBLY,
+    ASSEMBLY_REF,
+    FILE,
+    EXPORTED_TYPE,
+    MANIFEST_RESOURCE,
+    GENERIC_PARAM,
+    GENERIC_PARAM_CONSTRAINT,
+    METHOD_SPEC,
+]);
+const HAS_FIELD_MARSHAL: Column = Coded(&[FIELD, PARAM]);
+const HAS_DECL_SECURITY: Column = Coded(&[TYPE_DEF, METHOD_DEF, ASSEMBLY]);
+const MEMBER_REF_PARENT: Column = Coded(&[TYPE_DEF, TYPE_REF, MODULE_REF, METHOD_DEF, TYPE_SPEC]);
+const HAS_SEMANTICS: Column = Coded(&[EVENT, PROPERTY]);
+const METHOD_DEF_OR_REF: Column = Coded(&[METHOD_DEF, MEMBER_REF]);
+const MEMBER_FORWARDED: Column = Coded(&[FIELD, METHOD_DEF]);
+const IMPLEMENTATION: Column = Coded(&[FILE, ASSEMBLY_REF, EXPORTED_TYPE]);
+const CUSTOM_ATTRIBUTE_TYPE: Column = Coded(&[UNUSED, UNUSED, METHOD_DEF, MEMBER_REF, UNUSED]);
+const RESOLUTION_SCOPE: Column = Coded(&[MODULE, MODULE_REF, ASSEMBLY_REF, TYPE_REF]);
+const TYPE_OR_METHOD_DEF: Column = Coded(&[TYPE_DEF, METHOD_DEF]);
+
+/// Columns of every table, as laid out in ECMA-335 II.22.
+const TABLES: [&[Column]; 0x2D] = [
+    /* Module */ &[U16, Str, Guid, Guid, Guid],
+    /* TypeRef */ &[RESOLUTION_SCOPE, Str, Str],
+    /* TypeDef */ &[U32, Str, Str, TYPE_DEF_OR_REF, Table(FIELD), Table(METHOD_DEF)],
+    /* FieldPtr */ &[Table(FIELD)],
+    /* Field */ &[U16, Str, Blob],
+    /* MethodPtr */ &[Table(METHOD_DEF)],
+    /* MethodDef */ &[U32, U16, U16, Str, Blob, Table(PARAM)],
+    /* ParamPtr */ &[Table(PARAM)],
+    /* Param */ &[U16, U16, Str],
+    /* InterfaceImpl */ &[Table(TYPE_DEF), TYPE_DEF_OR_REF],
+    /* MemberRef */ &[MEMBER_REF_PARENT, Str, Blob],
+    /* Constant */ &[U16, HAS_CONSTANT, Blob],
+    /* CustomAttribute */ &[HAS_CUSTOM_ATTRIBUTE, CUSTOM_ATTRIBUTE_TYPE, Blob],
+    /* FieldMarshal */ &[HAS_FIELD_MARSHAL, Blob],
+    /* DeclSecurity */ &[U16, HAS_DECL_SECURITY, Blob],
+    /* ClassLayout */ &[U16, U32, Table(TYPE_DEF)],
+    /* FieldLayout */ &[U32, Table(FIELD)],
+    /* StandAloneSig */ &[Blob],
+    /* EventMap */ &[Table(TYPE_DEF), Table(EVENT)],
+    /* EventPtr */ &[Table(EVENT)],
+    /* Event */ &[U16, Str, TYPE_DEF_OR_REF],
+    /* PropertyMap */ &[Table(TYPE_DEF), Table(PROPERTY)],
+    /* PropertyPtr */ &[Table(PROPERTY)],
+    /* Property */ &[U16, Str, Blob],
+    /* MethodSemantics */ &[U16, Table(METHOD_DEF), HAS_SEMANTICS],
+    /* MethodImpl */ &[Table(TYPE_DEF), METHOD_DEF_OR_REF, METHOD_DEF_OR_REF],
+    /* ModuleRef */ &[Str],
+    /* TypeSpec */ &[Blob],
+    /* ImplMap */ &[U16, MEMBER_FORWARDED, Str, Table(MODULE_REF)],
+    /* FieldRVA */ &[U32, Table(FIELD)],
+    /* EncLog */ &[U32, U32],
+    /* EncMap */ &[U32],
+    /* Assembly */ &[U32, U16, U16, U16, U16, U32, Blob, Str, Str],
+    /* AssemblyProcessor */ &[U32],
+    /* AssemblyOS */ &[U32, U32, U32],
+    /* AssemblyRef */ &[U16, U16, U16, U16, U32, Blob, Str, Str, Blob],
+    /* AssemblyRefProcessor */ &[U32, Table(ASSEMBLY_REF)],
+    /* AssemblyRefOS */ &[U32, U32, U32, Table(ASSEMBLY_REF)],
+    /* File */ &[U32, Str, Blob],
+    /* ExportedType */ &[U32, U32, Str, Str, IMPLEMENTATION],
+    /* ManifestResource */ &[U32, U32, Str, IMPLEMENTATION],
+    /* NestedClass */ &[Table(TYPE_DEF), Table(TYPE_DEF)],
+    /* GenericParam */ &[U16, U16, TYPE_OR_METHOD_DEF, Str],
+    /* MethodSpec */ &[METHOD_DEF_OR_REF, Blob],
+    /* GenericParamConstraint */ &[Table(GENERIC_PARAM), TYPE_DEF_OR_REF],
+];
+
+/// Method with a CIL body.
+#[derive(Debug, Clone)]
+pub struct Method {
+    /// Metadata token that calls refer to the method by.
+    pub token: u32,
+    /// Name including the type it's defined in, e.g. `System.Console::WriteLine`.
+    pub name: String,
+    /// Address of the first instruction.
+    pub addr: usize,
+    /// Size of the instructions in bytes.
+    pub size: usize,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Metadata {
+    /// Version of the runtime the assembly targets, e.g. `v4.0.30319`.
+    pub runtime_version: String,
+    /// Methods with a body, sorted by address.
+    pub methods: Vec<Method>,
+    /// Names of the types, methods and fields that tokens refer to.
+    names: HashMap<u32, String>,
+    /// Heap of string literals, which `ldstr` refers to.
+    user_strings: Vec<u8>,
+}
+
+impl Metadata {
+    /// Name of what a metadata token refers to, string literals being quoted.
+    pub fn token_name(&self, token: u32) -> Option<String> {
+        if token >> 24 == USER_STRING {
+            return user_string(&self.user_strings, (token & 0xFF_FFFF) as usize)
+                .map(|string| format!("{string:?}"));
+        }
+
+        self.names.get(&token).cloned()
+    }
+}
+
+/// Little endian reads that fail instead of panicking when out of bounds.
+fn u16_at(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(data.get(offset..offset.checked_add(2)?)?.try_into().ok()?))
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(offset..offset.checked_add(4)?)?.try_into().ok()?))
+}
+
+fn cstr_at(data: &[u8], offset: usize) -> Option<&str> {
+    let bytes = data.get(offset..)?;
+    let len = bytes.iter().position(|&b| b == 0)?;
+    std::str::from_utf8(&bytes[..len]).ok()
+}
+
+/// Length prefix of a blob, along with how many bytes the prefix took up.
+fn compressed_len(data: &[u8], offset: usize) -> Option<(usize, usize)> {
+    let first = *data.get(offset)? as usize;
+    Some(match first {
+        _ if first & 0x80 == 0 => (first, 1),
+        _ if first & 0xC0 == 0x80 => ((first & 0x3F) << 8 | *data.get(offset + 1)? as usize, 2),
+        _ => {
+            let rest = data.get(offset + 1..offset + 4)?;
+            let len = (first & 0x1F) << 24 | (rest[0] as usize) << 16 | (rest[1] as usize) << 8;
+            (len | rest[2] as usize, 4)
+        }
+    })
+}
+
+/// UTF-16 string literal, which ends in a byte telling whether it has special characters.
+fn user_string(heap: &[u8], offset: usize) -> Option<String> {
+    let (len, prefix) = compressed_len(heap, offset)?;
+    let start = offset + prefix;
+    let bytes = heap.get(start..start + len.saturating_sub(1))?;
+    let units: Vec<u16> = bytes.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect();
+    Some(String::from_utf16_lossy(&units))
+}
+
+/// Rows of the metadata tables, along with the sizes needed to find them.
+struct Tables<'data> {
+    data: &'data [u8],
+    strings: &'data [u8],
+    rows: [usize; 64],
+    /// Offset of every table from the start of the `#~` stream.
+    offsets: [usize; 64],
+    large_strings: bool,
+    large_guids: bool,
+    large_blobs: bool,
+}
+
+impl<'data> Tables<'data> {
+    fn parse(data: &'data [u8], strings: &'data [u8]) -> Option<Self> {
+        let heap_sizes = *data.get(6)?;
+        let valid = u32_at(data, 8)? as u64 | (u32_at(data, 12)? as u64) << 32;
+
+        let mut tables = Self {
+            data,
+            strings,
+            rows: [0; 64],
+            offsets: [0; 64],
+            large_strings: heap_sizes & 0x1 != 0,
+            large_guids: heap_sizes & 0x2 != 0,
+            large_blobs: heap_sizes & 0x4 != 0,
+        };
+
+        let mut offset = 24;
+        for table in (0..64).filter(|table| valid & (1 << table) != 0) {
+            // tables that aren't known can't be skipped over, as their size isn't known
+            if table >= TABLES.len() {
+                return None;
+            }
+
+            tables.rows[table] = u32_at(data, offset)? as usize;
+            offset += 4;
+        }
+
+        // extra data that's only there for some versions of the format
+        if heap_sizes & 0x40 != 0 {
+            offset += 4;
+        }
+
+        for table in 0..TABLES.len() {
+            tables.offsets[table] = offset;
+            offset += tables.rows[table] * tables.row_size(table);
+        }
+
+        Some(tables)
+    }
+
+    fn column_size(&self, column: Column) -> usize {
+        let large = match column {
+            U16 => return 2,
+            U32 => return 4,
+            Str => self.large_strings,
+            Guid => self.large_guids,
+            Blob => self.large_blobs,
+            Table(table) => self.rows[table] > 0xFFFF,
+            Coded(tables) => {
+                let max_rows = 1 << (16 - tag_bits(tables));
+                tables.iter().any(|&table| table != UNUSED && self.rows[table] >= max_rows)
+            }
+        };
+
+        if large {
+            4
+        } else {
+            2
+        }
+    }
+
+    fn row_size(&self, table: usize) -> usize {
+        TABLES[table].iter().map(|&column| self.column_size(column)).sum()
+    }
+
+    /// Value in a column of a row, which is one-based like the tokens that refer to it.
+    fn get(&self, table: usize, row: usize, column: usize) -> Option<u32> {
+        if row == 0 || row > self.rows[table] {
+            return None;
+        }
+
+        let columns = TABLES[table];
+        let skipped: usize = columns[..column].iter().map(|&col| self.column_size(col)).sum();
+        let offset = self.offsets[table] + (row - 1) * self.row_size(table) + skipped;
+        match self.column_size(columns[column]) {
+            2 => u16_at(self.data, offset).map(u32::from),
+            _ => u32_at(self.data, offset),
+        }
+    }
+
+    fn string(&self, table: usize, row: usize, column: usize) -> Option<&'data str> {
+        cstr_at(self.strings, self.get(table, row, column)? as usize)
+    }
+
+    /// Table and row a coded index refers to.
+    fn coded(&self, table: usize, row: usize, column: usize) -> Option<(usize, usize)> {
+        let tables = match TABLES[table][column] {
+            Coded(tables) => tables,
+            _ => return None,
+        };
+
+        let value = self.get(table, row, column)? as usize;
+        let bits = tag_bits(tables);
+        let target = *tables.get(value & ((1 << bits) - 1))?;
+        (target != UNUSED).then_some((target, value >> bits))
+    }
+
+    /// Rows of the table a list column of a row refers to, which run until the next row's list.
+    fn list(&self, table: usize, row: usize, column: usize, target: usize) -> Range<usize> {
+        let start = self.get(table, row, column).unwrap_or(1) as usize;
+        let end = match self.get(table, row + 1, column) {
+            Some(end) => end as usize,
+            None => self.rows[target] + 1,
+        };
+
+        start..end.max(start)
+    }
+}
+
+/// Number of bits that tell which table a coded index refers to.
+fn tag_bits(tables: &[usize]) -> usize {
+    (usize::BITS - (tables.len() - 1).leading_zeros()) as usize
+}
+
+/// Size of a method body's header and of the code that follows it.
+fn method_header(body: &[u8]) -> Option<(usize, usize)> {
+    match *body.first()? {
+        // tiny header, the size of the code being in the upper 6 bits
+        header if header & 0x3 == 0x2 => Some((1, (header >> 2) as usize)),
+        // fat header, its size in 4 byte units being in the upper 4 bits of its flags
+        header if header & 0x3 == 0x3 => {
+            let flags = u16_at(body, 0)?;
+            Some(((flags >> 12) as usize * 4, u32_at(body, 4)? as usize))
+        }
+        _ => None,
+    }
+}
+
+fn token(table: usize, row: usize) -> u32 {
+    (table as u32) << 24 | row as u32
+}
+
+/// Parse the metadata of a PE, if it's a .NET assembly.
+pub fn parse<'data, Pe: ImageNtHeaders>(obj: &'data PeFile<'data, Pe>) -> Option<Metadata> {
+    let data = obj.data();
+    let sections = obj.section_table();
+    let rva_data = |rva: u32, size: u32| {
+        let bytes = sections.pe_data_at(data, rva)?;
+        bytes.get(..size as usize)
+    };
+
+    let directory = obj.data_directory(pe::IMAGE_DIRECTORY_ENTRY_COM_DESCRIPTOR)?;
+    let cli_header = rva_data(directory.virtual_address.get(LE), directory.size.get(LE))?;
+    let root = rva_data(u32_at(cli_header, 8)?, u32_at(cli_header, 12)?)?;
+
+    if u32_at(root, 0)? != METADATA_SIGNATURE {
+        return None;
+    }
+
+    let version_len = u32_at(root, 12)? as usize;
+    let runtime_version = cstr_at(root, 16).unwrap_or_default().to_string();
+
+    let mut offset = 16 + version_len + 2;
+    let stream_count = u16_at(root, offset)?;
+    offset += 2;
+
+    let mut streams = HashMap::new();
+    for _ in 0..stream_count {
+        let start = u32_at(root, offset)? as usize;
+        let size = u32_at(root, offset + 4)? as usize;
+        let name = cstr_at(root, offset + 8)?;
+        streams.insert(name, root.get(start..start + size)?);
+
+        // names are padded to a multiple of 4 bytes, including their terminator
+        offset += 8 + ((name.len() + 4) & !3);
+    }
+
+    let strings = streams.get("#Strings").copied().unwrap_or_default();
+    let table_stream = streams.get("#~").or_else(|| streams.get("#-"))?;
+    let tables = Tables::parse(table_stream, strings)?;
+
+    let mut metadata = Metadata {
+        runtime_version,
+        user_strings: streams.get("#US").copied().unwrap_or_default().to_vec(),
+        ..Metadata::default()
+    };
+
+    // nested types are named after the type they're nested in
+    let mut enclosing = HashMap::new();
+    for row in 1..=tables.rows[NESTED_CLASS] {
+        if let (Some(nested), Some(outer)) =
+            (tables.get(NESTED_CLASS, row, 0), tables.get(NESTED_CLASS, row, 1))
+        {
+            enclosing.insert(nested as usize, outer as usize);
+        }
+    }
+
+    let mut type_names = HashMap::new();
+    for row in 1..=tables.rows[TYPE_DEF] {
+        let mut name = tables.string(TYPE_DEF, row, 1).unwrap_or_default().to_string();
+        let mut outer = row;
+        while let Some(&parent) = enclosing.get(&outer) {
+            // malformed binaries might nest types in themselves
+            if parent == row || name.len() > 1024 {
+                break;
+            }
+
+            name = format!("{}/{name}", tables.string(TYPE_DEF, parent, 1).unwrap_or_default());
+            outer = parent;
+        }
+
+        let namespace = tables.string(TYPE_DEF, outer, 2).unwrap_or_default();
+        if !namespace.is_empty() {
+            name = format!("{namespace}.{name}");
+        }
+
+        type_names.insert(row, name.clone());
+        metadata.names.insert(token(TYPE_DEF, row), name);
+    }
+
+    for row in 1..=tables.rows[TYPE_REF] {
+        let name = tables.string(TYPE_REF, row, 1).unwrap_or_default();
+        let name = match tables.string(TYPE_REF, row, 2) {
+            Some(namespace) if !namespace.is_empty() => format!("{namespace}.{name}"),
+            _ => name.to_string(),
+        };
+
+        metadata.names.insert(token(TYPE_REF, row), name);
+    }
+
+    for row in 1..=tables.rows[TYPE_DEF] {
+        let type_name = &type_names[&row];
+
+        for field in tables.list(TYPE_DEF, row, 4, FIELD) {
+            let name = tables.string(FIELD, field, 1).unwrap_or_default();
+            metadata.names.insert(token(FIELD, field), format!("{type_name}::{name}"));
+        }
+
+        for method in tables.list(TYPE_DEF, row, 5, METHOD_DEF) {
+            let name = tables.string(METHOD_DEF, method, 3).unwrap_or_default();
+            metadata.names.insert(token(METHOD_DEF, method), format!("{type_name}::{name}"));
+        }
+    }
+
+    for row in 1..=tables.rows[MEMBER_REF] {
+        let name = tables.string(MEMBER_REF, row, 1).unwrap_or_default();
+        let parent = tables.coded(MEMBER_REF, row, 0);
+        let parent = parent.and_then(|(table, row)| metadata.names.get(&token(table, row)));
+        let name = match parent {
+            Some(parent) => format!("{parent}::{name}"),
+            None => name.to_string(),
+        };
+
+        metadata.names.insert(token(MEMBER_REF, row), name);
+    }
+
+    for row in 1..=tables.rows[METHOD_SPEC] {
+        let method = tables.coded(METHOD_SPEC, row, 0);
+        if let Some(name) = method.and_then(|(table, row)| metadata.names.get(&token(table, row))) {
+            metadata.names.insert(token(METHOD_SPEC, row), name.clone());
+        }
+    }
+
+    let image_base = obj.relative_address_base() as usize;
+    for row in 1..=tables.rows[METHOD_DEF] {
+        // abstract, runtime provided and native methods don't have a body
+        let rva = match tables.get(METHOD_DEF, row, 0) {
+            Some(0) | None => continue,
+            Some(rva) => rva,
+        };
+
+        let body = match sections.pe_data_at(data, rva) {
+            Some(body) => body,
+            None => continue,
+        };
+
+        let (header_size, size) = match method_header(body) {
+            Some(header) => header,
+            None => continue,
+        };
+
+        let token = token(METHOD_DEF, row);
+        metadata.methods.push(Method {
+            token,
+            name: metadata.names.get(&token).cloned().unwrap_or_default(),
+            addr: image_base + rva as usize + header_size,
+            size,
+        });
+    }
+
+    metadata.methods.sort_unstable_by_key(|method| method.addr);
+
+    log::complex!(
+        w "[dotnet::parse] found ",
+        g metadata.methods.len().to_string(),
+        w " managed methods targeting ",
+        g metadata.runtime_version.clone(),
+        w "."
+    );
+
+    Some(metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Header of a `#~` stream with the given heap size flags and number of rows per table.
+    fn stream(heap_sizes: u8, rows: &[(usize, u32)]) -> Vec<u8> {
+        let valid = rows.iter().fold(0u64, |valid, &(table, _)| valid | 1 << table);
+        let mut data = vec![0; 24];
+        data[4] = 2;
+        data[6] = heap_sizes;
+        data[8..16].copy_from_slice(&valid.to_le_bytes());
+        for (_, count) in rows {
+            data.extend_from_slice(&count.to_le_bytes());
+        }
+        data
+    }
+
+    #[test]
+    fn heap_sizes() {
+        let data = stream(0, &[(MODULE, 1), (TYPE_DEF, 1)]);
+        let tables = Tables::parse(&data, &[]).unwrap();
+        assert_eq!(tables.row_size(MODULE), 10);
+        assert_eq!(tables.row_size(TYPE_DEF), 14);
+        assert_eq!(tables.offsets[MODULE], 32);
+        assert_eq!(tables.offsets[TYPE_DEF], 42);
+
+        // large string, guid and blob heaps, followed by extra data
+        let data = stream(0x47, &[(MODULE, 1), (TYPE_DEF, 1)]);
+        let tables = Tables::parse(&data, &[]).unwrap();
+        assert_eq!(tables.row_size(MODULE), 18);
+        assert_eq!(tables.row_size(TYPE_DEF), 18);
+        assert_eq!(tables.offsets[MODULE], 36);
+        assert_eq!(tables.offsets[TYPE_DEF], 54);
+    }
+
+    #[test]
+    fn index_widths() {
+        // TypeDefOrRef uses 2 bits to tell the table, leaving 14 for the row
+        let data = stream(0, &[(TYPE_REF, 0x3FFF), (TYPE_DEF, 1)]);
+        let tables = Tables::parse(&data, &[]).unwrap();
+        assert_eq!(tables.row_size(TYPE_DEF), 14);
+
+        let data = stream(0, &[(TYPE_REF, 0x4000), (TYPE_DEF, 1)]);
+        let tables = Tables::parse(&data, &[]).unwrap();
+        assert_eq!(tables.row_size(TYPE_DEF), 16);
+
+        // type references themselves are in a ResolutionScope, which also widens
+        assert_eq!(tables.row_size(TYPE_REF), 8);
+        assert_eq!(tables.offsets[TYPE_DEF], 32 + 0x4000 * 8);
+
+        // rows of a single table only need 4 bytes past 0xFFFF
+        let data = stream(0, &[(TYPE_DEF, 1), (METHOD_DEF, 0xFFFF)]);
+        let tables = Tables::parse(&data, &[]).unwrap();
+        assert_eq!(tables.row_size(TYPE_DEF), 14);
+
+        let data = stream(0, &[(TYPE_DEF, 1), (METHOD_DEF, 0x10000)]);
+        let tables = Tables::parse(&data, &[]).unwrap();
+        assert_eq!(tables.row_size(TYPE_DEF), 16);
+
+        // MemberRefParent refers to five tables, leaving 13 bits for the row
+        let data = stream(0, &[(TYPE_REF, 0x1FFF), (MEMBER_REF, 1)]);
+        let tables = Tables::parse(&data, &[]).unwrap();
+        assert_eq!(tables.row_size(MEMBER_REF), 6);
+
+        let data = stream(0, &[(TYPE_REF, 0x2000), (MEMBER_REF, 1)]);
+        let tables = Tables::parse(&data, &[]).unwrap();
+        assert_eq!(tables.row_size(MEMBER_REF), 8);
+    }
+
+    #[test]
+    fn rows() {
+        let mut data = stream(0, &[(MODULE, 1)]);
+        data.extend_from_slice(&[0, 0, 1, 0, 1, 0, 0, 0, 0, 0]);
+        let tables = Tables::parse(&data, b"\0Foo\0").unwrap();
+        assert_eq!(tables.string(MODULE, 1, 1), Some("Foo"));
+        assert!(tables.get(MODULE, 0, 1).is_none());
+        assert!(tables.get(MODULE, 2, 1).is_none());
+
+        // tables added in later versions of the format aren't known
+        assert!(Tables::parse(&stream(0, &[(0x2D, 1)]), &[]).is_none());
+        assert!(Tables::parse(&data[..20], &[]).is_none());
+    }
+
+    #[test]
+    fn method_headers() {
+        // tiny header of 5 bytes of code
+        assert_eq!(method_header(&[5 << 2 | 0x2]), Some((1, 5)));
+
+        // fat header of 3 units with initialized locals and 32 bytes of code
+        let fat = [0x13, 0x30, 8, 0, 0x20, 0, 0, 0, 0, 0, 0, 0];
+        assert_eq!(method_header(&fat), Some((12, 0x20)));
+        assert!(method_header(&fat[..6]).is_none());
+
+        assert!(method_header(&[]).is_none());
+        assert!(method_header(&[0x1]).is_none());
+    }
+}
@@ -1,10 +1,11 @@
 use std::fmt;
-use crate::{datastructure, RawSymbol};
-use processor_shared::{AddressMap, Addressed, Section, SectionKind};
+use crate::kernel::{Kernel, Layout};
+use crate::{datastructure, AutoStart, AutoStartKind, Export, Import, RawSymbol};
+use processor_shared::{AddressMap, Addressed, Permissions, Section, SectionKind};
 use object::elf;
-use object::read::elf::{ElfFile, FileHeader, SectionHeader};
+use object::read::elf::{Dyn, ElfFile, FileHeader, ProgramHeader, SectionHeader};
 use object::{
-    Endian, Object, ObjectSection, ObjectSymbol, ObjectSymbolTable, RelocationKind,
+    Endian, Object, ObjectKind, ObjectSection, ObjectSymbol, ObjectSymbolTable, RelocationKind,
     RelocationTarget,
 };
 
@@ -15,6 +16,18 @@ pub struct ElfDebugInfo<'data, Elf: FileHeader> {
     pub sections: Vec<Section>,
     /// Any parsed but not yet relocated symbols.
     pub syms: AddressMap<RawSymbol<'data>>,
+    /// Functions imported through dynamic relocations.
+    pub imports: Vec<Import>,
+    /// Symbols in the dynamic symbol table that are defined by the object.
+    pub exports: Vec<Export>,
+    /// Where the sections of a relocatable object are placed, as they aren't placed anywhere yet.
+    layout: Option<Layout>,
+    /// Bytes of the sections of a relocatable object with their relocations applied.
+    pub relocated: Vec<Vec<u8>>,
+    /// Symbols and alternative instructions of a kernel or kernel module.
+    pub kernel: Kernel,
+    /// Constructors and destructors.
+    pub autostart: Vec<AutoStart>,
 }
 
 impl<'data, Elf: FileHeader> ElfDebugInfo<'data, Elf> {
@@ -23,10 +36,25 @@ impl<'data, Elf: FileHeader> ElfDebugInfo<'data, Elf> {
             obj,
             syms: AddressMap::default(),
             sections: Vec::new(),
+            imports: Vec::new(),
+            exports: Vec::new(),
+            layout: None,
+            relocated: Vec::new(),
+            kernel: Kernel::default(),
+            autostart: Vec::new(),
         };
         this.sections = parse_sections(obj);
+        if obj.kind() == ObjectKind::Relocatable {
+            let layout = Layout::new(obj, &mut this.sections);
+            this.relocated = layout.relocate(obj, &mut this.sections);
+            this.imports = layout.imports(obj);
+            this.layout = Some(layout);
+        }
         this.parse_symbols();
         this.parse_imports();
+        this.parse_constructors();
+        this.exports = crate::parse_exports(obj);
+        this.kernel = Kernel::parse(&this.sections, obj.is_64(), obj.is_little_endian());
         Ok(this)
     }
 
@@ -85,6 +113,11 @@ impl<'data, Elf: FileHeader> ElfDebugInfo<'data, Elf> {
                     };
 
                     // TODO: find modules
+                    self.imports.push(Import {
+                        name: name.to_string(),
+                        module: None,
+                        addr,
+                    });
                     self.syms.push(Addressed {
                         addr,
                         item: RawSymbol { name, module: None },
@@ -94,15 +127,105 @@ impl<'data, Elf: FileHeader> ElfDebugInfo<'data, Elf> {
         }
     }
 
+    /// Functions in the init, fini, ctors and dtors arrays and those the dynamic section points
+    /// to, which run before `main` and after it returns.
+    pub fn parse_constructors(&mut self) {
+        let endian = self.obj.endian();
+        let width = if self.obj.is_64() { 8 } else { 4 };
+
+        // arrays of position independent binaries are often only filled in by relocations
+        let relocated: std::collections::HashMap<u64, u64> = self
+            .obj
+            .dynamic_relocations()
+            .into_iter()
+            .flatten()
+            .filter(|(_, reloc)| matches!(reloc.target(), RelocationTarget::Absolute))
+            .map(|(offset, reloc)| (offset, reloc.addend() as u64))
+            .collect();
+
+        let mut starts = Vec::new();
+        for section in self.sections.iter() {
+            let (kind, prefix) = match (section.ident, section.name.as_str()) {
+                ("PREINIT_ARRAY", _) => (AutoStartKind::Constructor, "preinit_array"),
+                ("INIT_ARRAY", _) => (AutoStartKind::Constructor, "init_array"),
+                ("FINI_ARRAY", _) => (AutoStartKind::Destructor, "fini_array"),
+                (_, ".ctors") => (AutoStartKind::Constructor, "ctors"),
+                (_, ".dtors") => (AutoStartKind::Destructor, "dtors"),
+                _ => continue,
+            };
+
+            for (idx, entry) in section.bytes().chunks_exact(width).enumerate() {
+                let addr = section.start + idx * width;
+                let value = match width {
+                    8 => endian.read_u64_bytes(entry.try_into().unwrap()),
+                    _ => endian.read_u32_bytes(entry.try_into().unwrap()) as u64,
+                };
+
+                let value = match value {
+                    0 => relocated.get(&(addr as u64)).copied().unwrap_or(0),
+                    value => value,
+                };
+
+                // `.ctors` and `.dtors` start with -1 and end with 0
+                if value == 0 || value == u64::MAX || value == u32::MAX as u64 {
+                    continue;
+                }
+
+                starts.push((value as usize, kind, format!("{prefix}_{idx}")));
+            }
+        }
+
+        let data = self.obj.data();
+        let dynamic = self
+            .obj
+            .raw_segments()
+            .iter()
+            .find_map(|phdr| phdr.dynamic(endian, data).ok().flatten());
+
+        for entry in dynamic.into_iter().flatten() {
+            let tag: u64 = entry.d_tag(endian).into();
+            let (kind, name) = match tag as u32 {
+                elf::DT_INIT => (AutoStartKind::Constructor, "_init"),
+                elf::DT_FINI => (AutoStartKind::Destructor, "_fini"),
+                _ => continue,
+            };
+
+            let addr: u64 = entry.d_val(endian).into();
+            starts.push((addr as usize, kind, name.to_string()));
+        }
+
+        for (addr, kind, name) in starts {
+            if addr != 0 && !self.autostart.iter().any(|start| start.addr == addr) {
+                self.autostart.push(AutoStart { addr, kind, name });
+            }
+        }
+    }
+
     pub fn parse_symbols(&mut self) {
-        self.syms.extend(crate::parse_symbol_table(self.obj));
-        self.syms.push(Addressed {
-            addr: self.obj.entry() as usize,
-            item: RawSymbol {
-                name: "entry",
-                module: None,
-            },
-        });
+        let layout = match self.layout {
+            Some(ref layout) => layout,
+            None => {
+                self.syms.extend(crate::parse_symbol_table(self.obj));
+                self.syms.push(Addressed {
+                    addr: self.obj.entry() as usize,
+                    item: RawSymbol {
+                        name: "entry",
+                        module: None,
+                    },
+                });
+                return;
+            }
+        };
+
+        for sym in self.obj.symbols() {
+            let name = sym.name().ok().filter(|name| !name.is_empty());
+            if let (Some(name), Some(addr)) = (name, layout.symbol(&sym)) {
+                self.syms.push(Addressed {
+                    addr,
+                    item: RawSymbol { name, module: None },
+                });
+            }
+        }
     }
 }
 
@@ -138,6 +261,12 @@ fn parse_sections<'data, Elf: FileHeader>(obj: &'data ElfFile<'data, Elf>) -> Ve
     for (header, section) in section_headers.iter().zip(obj.sections()) {
         let sh_flags = header.sh_flags(endian).into();
         let (name, bytes, start, end) = crate::parse_section_generics(&section);
+        let file_offset = section.file_range().map(|(offset, _)| offset);
+        let permissions = Permissions {
+            read: sh_flags as u32 & elf::SHF_ALLOC != 0,
+            write: sh_flags as u32 & elf::SHF_WRITE != 0,
+            execute: sh_flags as u32 & elf::SHF_EXECINSTR != 0,
+        };
 
         let (mut kind, ident) = match header.sh_type(endian) {
             // Section header table entry is unused.
@@ -247,14 +376,18 @@ fn parse_sections<'data, Elf: FileHeader>(obj: &'data ElfFile<'data, Elf>) -> Ve
             kind = SectionKind::Debug;
         }
 
-        sections.push(Section::new(
+        let mut parsed = Section::new(
             name,
             ident,
             kind,
             bytes,
             start,
             end
-        ));
+        );
+
+        parsed.file_offset = file_offset;
+        parsed.permissions = permissions;
+        sections.push(parsed);
     }
 
     sections
@@ -0,0 +1,534 @@
+//! Function names, boundaries and source lines of Go binaries, from the pclntab the runtime keeps
+//! for stack traces. It's there even when a binary is stripped.
+//!
+//! The table is found in `.gopclntab` or `__gopclntab`, through the `runtime.pclntab` symbol, or
+//! by searching the sections for its header, as PE binaries don't give it a section.
+
+use crate::RawSymbol;
+use object::{Object, ObjectSection};
+use processor_shared::AddressMap;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Sections the table is put in by ELF and Mach-O binaries.
+const SECTIONS: [&str; 2] = [".gopclntab", "__gopclntab"];
+
+const START_SYMBOL: &str = "runtime.pclntab";
+const END_SYMBOL: &str = "runtime.epclntab";
+
+/// Layout of the table, which changed a couple of times since it was introduced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Version {
+    Go12,
+    Go116,
+    Go118,
+    Go120,
+}
+
+impl Version {
+    fn from_magic(magic: u32) -> Option<Self> {
+        match magic {
+            0xFFFF_FFFB => Some(Self::Go12),
+            0xFFFF_FFFA => Some(Self::Go116),
+            0xFFFF_FFF0 => Some(Self::Go118),
+            0xFFFF_FFF1 => Some(Self::Go120),
+            _ => None,
+        }
+    }
+
+    /// Whether the table stores addresses as offsets from the start of the text.
+    fn has_text_start(self) -> bool {
+        matches!(self, Self::Go118 | Self::Go120)
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Go12 => "Go 1.2",
+            Self::Go116 => "Go 1.16",
+            Self::Go118 => "Go 1.18",
+            Self::Go120 => "Go 1.20",
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Function {
+    pub name: String,
+    pub start: usize,
+    /// Address right after the function, which is where the next one starts.
+    pub end: usize,
+}
+
+/// Source line that the instructions starting at an address were compiled from.
+#[derive(Debug, Clone)]
+pub struct Line {
+    pub addr: usize,
+    /// Index into [`Pclntab::files`].
+    pub file: usize,
+    pub line: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Pclntab {
+    pub version: Version,
+    pub functions: Vec<Function>,
+    /// Paths of the source files, as they were on the machine the binary was built on.
+    pub files: Vec<String>,
+    /// Where every function's source line changes, sorted by address.
+    pub lines: Vec<Line>,
+}
+
+/// Reads the table's fields, which use the target's endianness and pointer size.
+struct Reader<'data> {
+    data: &'data [u8],
+    little_endian: bool,
+    ptr_size: usize,
+}
+
+impl<'data> Reader<'data> {
+    fn u32(&self, offset: usize) -> Option<u32> {
+        let bytes: [u8; 4] = self.data.get(offset..offset.checked_add(4)?)?.try_into().ok()?;
+        Some(if self.little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    }
+
+    fn uintptr(&self, offset: usize) -> Option<u64> {
+        if self.ptr_size == 4 {
+            return self.u32(offset).map(u64::from);
+        }
+
+        let bytes: [u8; 8] = self.data.get(offset..offset.checked_add(8)?)?.try_into().ok()?;
+        Some(if self.little_endian {
+            u64::from_le_bytes(bytes)
+        } else {
+            u64::from_be_bytes(bytes)
+        })
+    }
+
+    fn cstr(&self, offset: usize) -> Option<String> {
+        let bytes = self.data.get(offset..)?;
+        let len = bytes.iter().position(|&b| b == 0)?;
+        Some(String::from_utf8_lossy(&bytes[..len]).into_owned())
+    }
+
+    /// Unsigned LEB128 number, moving the offset past it.
+    fn uvarint(&self, offset: &mut usize) -> Option<u32> {
+        let mut value = 0u32;
+        for shift in (0..35).step_by(7) {
+            let byte = *self.data.get(*offset)?;
+            *offset += 1;
+            value |= ((byte & 0x7F) as u32) << shift;
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+
+        None
+    }
+
+    /// Values of a pc-value table along with the address each one starts at.
+    fn pc_values(&self, mut offset: usize, entry: u64, end: u64, quantum: u64) -> Vec<(u64, i32)> {
+        let mut values = Vec::new();
+        let mut pc = entry;
+        let mut value = -1i32;
+
+        while pc < end {
+            let delta = match self.uvarint(&mut offset) {
+                Some(0) if !values.is_empty() => break,
+                Some(delta) => delta,
+                None => break,
+            };
+
+            // zig-zag encoded, the lowest bit being the sign
+            let delta = match delta & 1 {
+                0 => (delta >> 1) as i32,
+                _ => !(delta >> 1) as i32,
+            };
+
+            let pc_delta = match self.uvarint(&mut offset) {
+                Some(pc_delta) => pc_delta as u64 * quantum,
+                None => break,
+            };
+
+            value = value.wrapping_add(delta);
+            values.push((pc, value));
+            pc = match pc.checked_add(pc_delta) {
+                Some(pc) => pc,
+                None => break,
+            };
+        }
+
+        values
+    }
+}
+
+/// Where the parts of the table are, as offsets from its start.
+struct Layout {
+    version: Version,
+    /// Size of an instruction, which addresses in pc-value tables are a multiple of.
+    quantum: u64,
+    func_count: usize,
+    text_start: u64,
+    func_names: usize,
+    cu_table: usize,
+    file_table: usize,
+    pc_table: usize,
+    func_table: usize,
+    /// Where the offsets of functions in the function table start from.
+    func_data: usize,
+}
+
+impl Layout {
+    fn parse(reader: &Reader, text_addr: u64) -> Option<Self> {
+        let version = Version::from_magic(reader.u32(0)?)?;
+        let header = reader.data.get(4..8)?;
+        let quantum = header[2] as u64;
+        let ptr_size = reader.ptr_size;
+        let valid_sizes = matches!(quantum, 1 | 2 | 4) && header[3] as usize == ptr_size;
+        if header[..2] != [0, 0] || !valid_sizes {
+            return None;
+        }
+
+        let word = |idx: usize| reader.uintptr(8 + idx * ptr_size).map(|word| word as usize);
+        let func_count = word(0)?;
+
+        // every function takes up at least an entry in the function table
+        if func_count == 0 || func_count > reader.data.len() / 8 {
+            return None;
+        }
+
+        let mut layout = match version {
+            Version::Go12 => {
+                let func_table = 8 + ptr_size;
+                let file_table = reader.u32(func_table + (func_count * 2 + 1) * ptr_size)?;
+                Layout {
+                    version,
+                    quantum,
+                    func_count,
+                    text_start: 0,
+                    func_names: 0,
+                    cu_table: 0,
+                    file_table: file_table as usize,
+                    pc_table: 0,
+                    func_table,
+                    func_data: 0,
+                }
+            }
+            Version::Go116 => Layout {
+                version,
+                quantum,
+                func_count,
+                text_start: 0,
+                func_names: word(2)?,
+                cu_table: word(3)?,
+                file_table: word(4)?,
+                pc_table: word(5)?,
+                func_table: word(6)?,
+                func_data: word(6)?,
+            },
+            Version::Go118 | Version::Go120 => Layout {
+                version,
+                quantum,
+                func_count,
+                text_start: word(2)? as u64,
+                func_names: word(3)?,
+                cu_table: word(4)?,
+                file_table: word(5)?,
+                pc_table: word(6)?,
+                func_table: word(7)?,
+                func_data: word(7)?,
+            },
+        };
+
+        // position independent binaries only get the start of the text once they're relocated
+        if layout.version.has_text_start() && layout.text_start == 0 {
+            layout.text_start = text_addr;
+        }
+
+        Some(layout)
+    }
+
+    /// Address of a function and the offset of its metadata.
+    fn func_entry(&self, reader: &Reader, idx: usize) -> Option<(u64, usize)> {
+        if self.version.has_text_start() {
+            let entry = self.func_table.checked_add(idx.checked_mul(8)?)?;
+            let addr = self.text_start.checked_add(reader.u32(entry)? as u64)?;
+            let func = reader.u32(entry.checked_add(4)?).map(|offset| offset as usize);
+            return Some((addr, func.unwrap_or(0)));
+        }
+
+        let entry = self.func_table.checked_add(idx.checked_mul(2 * reader.ptr_size)?)?;
+        let addr = reader.uintptr(entry)?;
+        let func = reader.uintptr(entry.checked_add(reader.ptr_size)?);
+        Some((addr, func.map_or(0, |offset| offset as usize)))
+    }
+
+    /// Address where the last function ends.
+    fn text_end(&self, reader: &Reader) -> Option<u64> {
+        if self.version.has_text_start() {
+            let entry = self.func_table.checked_add(self.func_count.checked_mul(8)?)?;
+            return self.text_start.checked_add(reader.u32(entry)? as u64);
+        }
+
+        let entry = self.func_count.checked_mul(2 * reader.ptr_size)?;
+        reader.uintptr(self.func_table.checked_add(entry)?)
+    }
+
+    /// Offset of the first field after a function's address.
+    fn fields(&self, reader: &Reader) -> usize {
+        match self.version.has_text_start() {
+            true => 4,
+            false => reader.ptr_size,
+        }
+    }
+
+    /// Offset of the name of a source file, given its number in a function's pc-file table.
+    fn file_name(&self, reader: &Reader, func: usize, file: i32) -> Option<usize> {
+        let file = u32::try_from(file).ok()? as usize;
+        if self.version == Version::Go12 {
+            let entry = self.file_table.checked_add(file.checked_mul(4)?)?;
+            return reader.u32(entry).map(|offset| offset as usize);
+        }
+
+        // files are numbered per compilation unit
+        let cu = reader.u32(func.checked_add(self.fields(reader) + 28)?)? as usize;
+        let entry = cu.checked_add(file)?.checked_mul(4)?;
+        match reader.u32(self.cu_table.checked_add(entry)?)? {
+            u32::MAX => None,
+            offset => self.file_table.checked_add(offset as usize),
+        }
+    }
+}
+
+/// Find and parse the table, if the binary was built by Go.
+///
+/// `syms` are the symbols that were already found, which might point to the table.
+pub fn parse<'data: 'file, 'file, Obj: Object<'data, 'file>>(
+    obj: &'file Obj,
+    syms: &AddressMap<RawSymbol<'data>>,
+) -> Option<Pclntab> {
+    let little_endian = obj.is_little_endian();
+    let ptr_size = if obj.is_64() { 8 } else { 4 };
+    let text_addr = obj
+        .sections()
+        .find(|section| matches!(section.name(), Ok(".text" | "__text")))
+        .map_or(0, |section| section.address());
+
+    let table = |data| parse_table(Reader { data, little_endian, ptr_size }, text_addr);
+
+    for section in obj.sections() {
+        if section.name().is_ok_and(|name| SECTIONS.contains(&name)) {
+            return section.data().ok().and_then(table);
+        }
+    }
+
+    let symbol = |name: &str| syms.iter().find(|sym| sym.item.name == name).map(|sym| sym.addr);
+    if let Some(start) = symbol(START_SYMBOL) {
+        let data = obj.sections().find_map(|section| {
+            let offset = (start as u64).checked_sub(section.address())?;
+            let data = section.data().ok()?.get(offset as usize..)?;
+            let len = symbol(END_SYMBOL).map_or(data.len(), |end| end.saturating_sub(start));
+            Some(&data[..len.min(data.len())])
+        });
+
+        if let Some(pclntab) = data.and_then(table) {
+            return Some(pclntab);
+        }
+    }
+
+    // the table is aligned, so only aligned magic numbers have to be tried
+    for section in obj.sections() {
+        let data = match section.data() {
+            Ok(data) => data,
+            Err(..) => continue,
+        };
+
+        for offset in (0..data.len().saturating_sub(8)).step_by(4) {
+            let magic = Reader { data, little_endian, ptr_size }.u32(offset);
+            if magic.and_then(Version::from_magic).is_none() {
+                continue;
+            }
+
+            if let Some(pclntab) = table(&data[offset..]) {
+                return Some(pclntab);
+            }
+        }
+    }
+
+    None
+}
+
+fn parse_table(reader: Reader, text_addr: u64) -> Option<Pclntab> {
+    let layout = Layout::parse(&reader, text_addr)?;
+    let fields = layout.fields(&reader);
+    let text_end = layout.text_end(&reader)?;
+
+    let mut pclntab = Pclntab {
+        version: layout.version,
+        functions: Vec::with_capacity(layout.func_count),
+        files: Vec::new(),
+        lines: Vec::new(),
+    };
+
+    // offsets of file names that were already seen, along with their index
+    let mut files: HashMap<usize, usize> = HashMap::new();
+
+    for idx in 0..layout.func_count {
+        let (start, func) = match layout.func_entry(&reader, idx) {
+            Some(entry) => entry,
+            None => break,
+        };
+
+        let end = match layout.func_entry(&reader, idx + 1) {
+            Some((end, _)) if idx + 1 < layout.func_count => end,
+            _ => text_end,
+        };
+
+        let func = match layout.func_data.checked_add(func) {
+            Some(func) => func,
+            None => continue,
+        };
+
+        let name = func
+            .checked_add(fields)
+            .and_then(|offset| reader.u32(offset))
+            .and_then(|offset| reader.cstr(layout.func_names.checked_add(offset as usize)?));
+
+        let name = match name {
+            Some(name) if !name.is_empty() => name,
+            _ => continue,
+        };
+
+        // the first function not being readable means this isn't a table after all
+        if start >= end && pclntab.functions.is_empty() {
+            return None;
+        }
+
+        let pc_values = |field: usize| {
+            let offset = func.checked_add(fields + field).and_then(|offset| reader.u32(offset));
+            let offset = match offset {
+                Some(0) | None => return Vec::new(),
+                Some(offset) => layout.pc_table.checked_add(offset as usize),
+            };
+
+            match offset {
+                Some(offset) => reader.pc_values(offset, start, end, layout.quantum),
+                None => Vec::new(),
+            }
+        };
+
+        let file_values = pc_values(16);
+        let mut last = None;
+        for (addr, line) in pc_values(20) {
+            let file = file_values.iter().rev().find(|(start, _)| *start <= addr);
+            let file = match file.and_then(|(_, file)| layout.file_name(&reader, func, *file)) {
+                Some(offset) => offset,
+                None => continue,
+            };
+
+            let file = match files.get(&file) {
+                Some(&idx) => idx,
+                None => {
+                    pclntab.files.push(reader.cstr(file).unwrap_or_default());
+                    files.insert(file, pclntab.files.len() - 1);
+                    pclntab.files.len() - 1
+                }
+            };
+
+            let line = match usize::try_from(line) {
+                Ok(line) => line,
+                Err(..) => continue,
+            };
+
+            if last != Some((file, line)) {
+                pclntab.lines.push(Line { addr: addr as usize, file, line });
+                last = Some((file, line));
+            }
+        }
+
+        pclntab.functions.push(Function {
+            name,
+            start: start as usize,
+            end: end as usize,
+        });
+    }
+
+    if pclntab.functions.is_empty() {
+        return None;
+    }
+
+    log::complex!(
+        w "[go::parse] found ",
+        g pclntab.functions.len().to_string(),
+        w " functions in a ",
+        g pclntab.version.to_string(),
+        w " pclntab."
+    );
+
+    Some(pclntab)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Header of a little endian Go 1.18 table on a 64-bit target, followed by `words`.
+    fn table(words: &[u64]) -> Vec<u8> {
+        let mut data = vec![0xF0, 0xFF, 0xFF, 0xFF, 0, 0, 1, 8];
+        for word in words {
+            data.extend_from_slice(&word.to_le_bytes());
+        }
+        data
+    }
+
+    fn parse(data: &[u8]) -> Option<Pclntab> {
+        let reader = Reader {
+            data,
+            little_endian: true,
+            ptr_size: 8,
+        };
+
+        parse_table(reader, 0x1000)
+    }
+
+    #[test]
+    fn truncated_header() {
+        let data = table(&[1, 0, 0x1000]);
+        for len in 0..data.len() {
+            assert!(parse(&data[..len]).is_none());
+        }
+    }
+
+    #[test]
+    fn function_table_past_the_end() {
+        let data = table(&[1, 0, 0x1000, 0, 0, 0, 0, u64::MAX - 3]);
+        assert!(parse(&data).is_none());
+    }
+
+    #[test]
+    fn text_past_the_end() {
+        // the function table starts right after the header, with an entry pointing past the text
+        let mut data = table(&[1, 0, u64::MAX, 0, 0, 0, 0, 72]);
+        data.extend_from_slice(&[0xFF; 16]);
+        assert!(parse(&data).is_none());
+    }
+
+    #[test]
+    fn pc_values_past_the_end() {
+        // the value goes up by one, after which the address moves past the end of the space
+        let data = [0x02, 0x7F, 0x02, 0x7F];
+        let reader = Reader {
+            data: &data,
+            little_endian: true,
+            ptr_size: 8,
+        };
+
+        let entry = u64::MAX - 0x10;
+        assert_eq!(reader.pc_values(0, entry, u64::MAX, 1), [(entry, 0)]);
+    }
+}
@@ -0,0 +1,590 @@
+//! Linux kernel images and loadable kernel modules.
+//!
+//! Modules are relocatable objects, so all their sections are at address zero until the kernel
+//! loads them. [`Layout`] places them one after another like a loader would and applies their
+//! relocations, giving every symbol the kernel provides a slot of it's own to be called through.
+//!
+//! Both `vmlinux` and modules list the symbols they export to modules in `__ksymtab`, which is
+//! still there once the symbol table is stripped, and keep instructions the kernel patches in at
+//! boot in `.altinstr_replacement`.
+
+use crate::{Export, Import};
+use object::read::elf::{ElfFile, FileHeader};
+use object::{
+    Object, ObjectSection, ObjectSymbol, Relocation, RelocationEncoding, RelocationKind,
+    RelocationTarget, SymbolSection,
+};
+use processor_shared::{Section, SectionKind};
+use std::collections::BTreeMap;
+
+/// Address of the first section of a relocatable object, as nothing can be at address zero.
+const RELOCATABLE_BASE: usize = 0x10000;
+
+/// Size of an `alt_instr` entry in `.altinstructions` and the offset of the length of the
+/// instructions it replaces, which is followed by the length of the replacement. The entry changed
+/// across kernel versions and architectures, but always starts with two relative offsets.
+const ALT_INSTR_FORMATS: [(usize, usize); 3] = [(14, 12), (13, 10), (12, 10)];
+
+/// Addresses given to the sections and undefined symbols of a relocatable object.
+pub struct Layout {
+    /// Address of every section by it's index, if it's loaded at all.
+    bases: Vec<Option<usize>>,
+    /// Address of the slot of every undefined symbol by it's index.
+    externs: BTreeMap<usize, usize>,
+}
+
+impl Layout {
+    /// Place every loaded section after the previous one, followed by a section of pointer sized
+    /// slots for the undefined symbols.
+    pub fn new<'data, Elf: FileHeader>(
+        obj: &'data ElfFile<'data, Elf>,
+        sections: &mut Vec<Section>,
+    ) -> Self {
+        let mut bases = vec![None; sections.len()];
+        let mut addr = RELOCATABLE_BASE;
+
+        for section in obj.sections() {
+            let idx = section.index().0;
+            let parsed = match sections.get_mut(idx) {
+                Some(parsed) if parsed.permissions.read => parsed,
+                _ => continue,
+            };
+
+            addr = addr.next_multiple_of(section.align().max(1) as usize);
+            let size = parsed.end - parsed.start;
+            parsed.start = addr;
+            parsed.end = addr + size;
+            bases[idx] = Some(addr);
+            addr += size;
+        }
+
+        let width = if obj.is_64() { 8 } else { 4 };
+        let start = addr.next_multiple_of(width);
+        let mut externs = BTreeMap::new();
+        for sym in obj.symbols() {
+            if sym.is_undefined() && sym.name().is_ok_and(|name| !name.is_empty()) {
+                externs.insert(sym.index().0, start + externs.len() * width);
+            }
+        }
+
+        // like `.bss`, the slots don't have any bytes in the file
+        if !externs.is_empty() {
+            let end = start + externs.len() * width;
+            let mut section =
+                Section::new("extern".to_string(), "EXTERN", SectionKind::Raw, &[], start, end);
+            section.permissions.read = true;
+            sections.push(section);
+        }
+
+        Self { bases, externs }
+    }
+
+    /// Address of a symbol once the object is laid out.
+    pub fn symbol<'data>(&self, sym: &impl ObjectSymbol<'data>) -> Option<usize> {
+        match sym.section() {
+            SymbolSection::Section(idx) => {
+                Some(self.bases.get(idx.0).copied()?? + sym.address() as usize)
+            }
+            SymbolSection::Undefined => self.externs.get(&sym.index().0).copied(),
+            SymbolSection::Absolute => Some(sym.address() as usize),
+            _ => None,
+        }
+    }
+
+    /// Symbols the object expects to be provided by whatever loads it.
+    pub fn imports<'data, Elf: FileHeader>(&self, obj: &'data ElfFile<'data, Elf>) -> Vec<Import> {
+        obj.symbols()
+            .filter_map(|sym| {
+                Some(Import {
+                    name: sym.name().ok()?.to_string(),
+                    module: None,
+                    addr: *self.externs.get(&sym.index().0)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Apply the relocations of every loaded section. Returns the relocated bytes, which the
+    /// sections borrow from instead of the memory mapped binary.
+    pub fn relocate<'data, Elf: FileHeader>(
+        &self,
+        obj: &'data ElfFile<'data, Elf>,
+        sections: &mut [Section],
+    ) -> Vec<Vec<u8>> {
+        let little_endian = obj.is_little_endian();
+        let mut relocated = Vec::new();
+        let mut unsupported = 0;
+
+        for section in obj.sections() {
+            let idx = section.index().0;
+            let base = match self.bases.get(idx) {
+                Some(Some(base)) if !sections[idx].bytes().is_empty() => *base,
+                _ => continue,
+            };
+
+            let mut relocations = section.relocations().peekable();
+            if relocations.peek().is_none() {
+                continue;
+            }
+
+            let mut bytes = sections[idx].bytes().to_vec();
+            for (offset, relocation) in relocations {
+                let target = match relocation.target() {
+                    RelocationTarget::Symbol(idx) => {
+                        obj.symbol_by_index(idx).ok().and_then(|sym| self.symbol(&sym))
+                    }
+                    RelocationTarget::Section(idx) => self.bases.get(idx.0).copied().flatten(),
+                    _ => None,
+                };
+
+                let offset = offset as usize;
+                let place = match base.checked_add(offset) {
+                    Some(place) => place,
+                    None => {
+                        unsupported += 1;
+                        continue;
+                    }
+                };
+
+                let applied = target.is_some_and(|target| {
+                    apply(&mut bytes, offset, place, target, &relocation, little_endian)
+                });
+
+                unsupported += !applied as usize;
+            }
+
+            // the bytes are on the heap, so they don't move along with the vector they're kept in
+            let borrowed: &'static [u8] = unsafe { std::mem::transmute(&bytes[..]) };
+            let old = &sections[idx];
+            let (name, kind) = (old.name.clone(), old.kind.clone());
+            let mut new = Section::new(name, old.ident, kind, borrowed, old.start, old.end);
+            new.file_offset = old.file_offset;
+            new.permissions = old.permissions;
+            sections[idx] = new;
+            relocated.push(bytes);
+        }
+
+        if unsupported > 0 {
+            log::complex!(
+                w "[kernel::relocate] skipped ",
+                y unsupported.to_string(),
+                w " unsupported relocations.",
+            );
+        }
+
+        relocated
+    }
+}
+
+/// Write the value of a relocation, returning whether it's supported.
+fn apply(
+    bytes: &mut [u8],
+    offset: usize,
+    place: usize,
+    target: usize,
+    relocation: &Relocation,
+    little_endian: bool,
+) -> bool {
+    let size = match relocation.encoding() {
+        RelocationEncoding::AArch64Call => 4,
+        _ => relocation.size() as usize / 8,
+    };
+
+    let end = match offset.checked_add(size) {
+        Some(end) => end,
+        None => return false,
+    };
+
+    let field = match bytes.get_mut(offset..end) {
+        Some(field) if (1..=8).contains(&size) => field,
+        _ => return false,
+    };
+
+    // relocations without an explicit addend store it where the value goes
+    let addend = match relocation.has_implicit_addend() {
+        true => read_signed(field, little_endian),
+        false => relocation.addend(),
+    };
+
+    let value = match relocation.kind() {
+        RelocationKind::Absolute => (target as i64).wrapping_add(addend),
+        RelocationKind::Relative | RelocationKind::PltRelative => {
+            (target as i64).wrapping_add(addend).wrapping_sub(place as i64)
+        }
+        _ => return false,
+    };
+
+    match (relocation.encoding(), size) {
+        // branches encode the number of instructions to jump in the low 26 bits
+        (RelocationEncoding::AArch64Call, _) => {
+            let insn = u32::from_le_bytes(field.try_into().unwrap());
+            let insn = (insn & !0x3FF_FFFF) | ((value >> 2) as u32 & 0x3FF_FFFF);
+            field.copy_from_slice(&insn.to_le_bytes());
+        }
+        (
+            RelocationEncoding::Generic
+            | RelocationEncoding::X86Signed
+            | RelocationEncoding::X86RipRelative
+            | RelocationEncoding::X86Branch,
+            1 | 2 | 4 | 8,
+        ) => {
+            if little_endian {
+                field.copy_from_slice(&value.to_le_bytes()[..size]);
+            } else {
+                field.copy_from_slice(&value.to_be_bytes()[8 - size..]);
+            }
+        }
+        _ => return false,
+    }
+
+    true
+}
+
+fn read_signed(bytes: &[u8], little_endian: bool) -> i64 {
+    let mut value = [0; 8];
+    if little_endian {
+        value[..bytes.len()].copy_from_slice(bytes);
+        let shift = 64 - bytes.len() * 8;
+        (i64::from_le_bytes(value) << shift) >> shift
+    } else {
+        value[8 - bytes.len()..].copy_from_slice(bytes);
+        let shift = 64 - bytes.len() * 8;
+        (i64::from_be_bytes(value) << shift) >> shift
+    }
+}
+
+fn read_unsigned(bytes: &[u8], offset: usize, size: usize, little_endian: bool) -> Option<u64> {
+    let bytes = bytes.get(offset..offset.checked_add(size)?)?;
+    let mut value = [0; 8];
+    Some(if little_endian {
+        value[..size].copy_from_slice(bytes);
+        u64::from_le_bytes(value)
+    } else {
+        value[8 - size..].copy_from_slice(bytes);
+        u64::from_be_bytes(value)
+    })
+}
+
+/// Instructions that replace others at boot if the CPU supports a feature, e.g. `rep movsb` in
+/// place of a call to `memcpy`.
+#[derive(Debug, Clone, Copy)]
+pub struct Alternative {
+    /// Address of the instructions that get replaced.
+    pub site: usize,
+    pub site_len: u8,
+    /// Address of the replacement in `.altinstr_replacement`.
+    pub replacement: usize,
+    pub replacement_len: u8,
+}
+
+/// Symbols recovered from the tables of a kernel or kernel module.
+#[derive(Debug, Default)]
+pub struct Kernel {
+    /// Symbols exported to modules through `__ksymtab` and `__ksymtab_gpl`.
+    pub exports: Vec<Export>,
+    pub alternatives: Vec<Alternative>,
+}
+
+impl Kernel {
+    /// Parse the tables out of sections that are already laid out and relocated.
+    pub fn parse(sections: &[Section], is_64: bool, little_endian: bool) -> Self {
+        let section = |name: &str| sections.iter().find(|section| section.name == name);
+
+        let mut exports = Vec::new();
+        if let Some(strings) = section("__ksymtab_strings") {
+            for table in [section("__ksymtab"), section("__ksymtab_gpl")].into_iter().flatten() {
+                exports.extend(ksymtab(table, strings, is_64, little_endian));
+            }
+        }
+
+        let alternatives = match (section(".altinstructions"), section(".altinstr_replacement")) {
+            (Some(table), Some(replacements)) => {
+                alternatives(table, replacements, sections, little_endian)
+            }
+            _ => Vec::new(),
+        };
+
+        if !exports.is_empty() || !alternatives.is_empty() {
+            log::complex!(
+                w "[kernel::parse] found ",
+                g exports.len().to_string(),
+                w " exported symbols and ",
+                g alternatives.len().to_string(),
+                w " alternative instructions.",
+            );
+        }
+
+        Self {
+            exports,
+            alternatives,
+        }
+    }
+
+    /// Names of exported symbols and of the replacement of every alternative, which is named
+    /// after the address of the instructions it replaces.
+    pub fn symbols(&self) -> Vec<(usize, String)> {
+        let exports = self.exports.iter().map(|export| (export.addr, export.name.clone()));
+        let alternatives = self
+            .alternatives
+            .iter()
+            .filter(|alt| alt.replacement_len > 0)
+            .map(|alt| (alt.replacement, format!("altinstr_{:x}", alt.site)));
+
+        exports.chain(alternatives).collect()
+    }
+}
+
+/// Entries of a `__ksymtab`. Depending on the kernel's configuration these either hold offsets
+/// relative to the entry or pointers, and since 5.4 also the symbol's namespace.
+fn ksymtab(table: &Section, strings: &Section, is_64: bool, little_endian: bool) -> Vec<Export> {
+    let bytes = table.bytes();
+    let ptr = if is_64 { 8 } else { 4 };
+    let formats = [(true, 12), (true, 8), (false, ptr * 3), (false, ptr * 2)];
+
+    let name = |addr: usize| -> Option<String> {
+        let bytes = strings.bytes().get(addr.checked_sub(strings.start)?..)?;
+        let len = bytes.iter().position(|&b| b == 0)?;
+        std::str::from_utf8(&bytes[..len]).ok().map(str::to_string)
+    };
+
+    // the wrong format reads names that aren't in `__ksymtab_strings`
+    let parse = |relative: bool, size: usize| -> Option<Vec<Export>> {
+        if bytes.is_empty() || !bytes.len().is_multiple_of(size) {
+            return None;
+        }
+
+        let field_size = if relative { 4 } else { ptr };
+        let field = |offset: usize| -> Option<usize> {
+            let value = read_unsigned(bytes, offset, field_size, little_endian)?;
+            match relative {
+                true => (table.start + offset).checked_add_signed(value as i32 as isize),
+                false => Some(value as usize),
+            }
+        };
+
+        (0..bytes.len())
+            .step_by(size)
+            .map(|offset| {
+                Some(Export {
+                    name: name(field(offset + field_size)?)?,
+                    ordinal: None,
+                    addr: field(offset)?,
+                    forward: None,
+                })
+            })
+            .collect()
+    };
+
+    match formats.into_iter().find_map(|(relative, size)| parse(relative, size)) {
+        Some(exports) => exports,
+        None => {
+            log::complex!(
+                w "[kernel::ksymtab] format of ",
+                b &table.name,
+                y " isn't known.",
+            );
+            Vec::new()
+        }
+    }
+}
+
+/// Entries of `.altinstructions`, which are only accepted if every entry replaces code with
+/// instructions in `.altinstr_replacement`.
+fn alternatives(
+    table: &Section,
+    replacements: &Section,
+    sections: &[Section],
+    little_endian: bool,
+) -> Vec<Alternative> {
+    let bytes = table.bytes();
+    let is_code = |addr: usize| {
+        sections.iter().any(|section| {
+            section.kind == SectionKind::Code && (section.start..section.end).contains(&addr)
+        })
+    };
+
+    let parse = |size: usize, len_offset: usize| -> Option<Vec<Alternative>> {
+        if bytes.is_empty() || !bytes.len().is_multiple_of(size) {
+            return None;
+        }
+
+        let relative = |offset: usize| -> Option<usize> {
+            let value = read_unsigned(bytes, offset, 4, little_endian)?;
+            (table.start + offset).checked_add_signed(value as i32 as isize)
+        };
+
+        (0..bytes.len())
+            .step_by(size)
+            .map(|offset| {
+                let alt = Alternative {
+                    site: relative(offset)?,
+                    site_len: bytes[offset + len_offset],
+                    replacement: relative(offset + 4)?,
+                    replacement_len: bytes[offset + len_offset + 1],
+                };
+
+                let end = alt.replacement.checked_add(alt.replacement_len as usize)?;
+                let valid = is_code(alt.site)
+                    && alt.replacement >= replacements.start
+                    && end <= replacements.end;
+
+                valid.then_some(alt)
+            })
+            .collect()
+    };
+
+    match ALT_INSTR_FORMATS.into_iter().find_map(|(size, len_offset)| parse(size, len_offset)) {
+        Some(alternatives) => alternatives,
+        None => {
+            log::complex!(
+                w "[kernel::alternatives] ",
+                y "format of .altinstructions isn't known.",
+            );
+            Vec::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use object::elf;
+    use object::endian::{LittleEndian as LE, U16, U32, U64};
+    use object::pod::bytes_of;
+    use object::read::elf::ElfFile64;
+
+    static TEXT: [u8; 8] = [0; 8];
+    const NAMES: &[u8] = b"\0.text\0.rela.text\0.symtab\0.shstrtab\0";
+
+    fn section_header(
+        name: u32,
+        kind: u32,
+        offset: usize,
+        size: usize,
+        link: u32,
+        info: u32,
+        entsize: u64,
+    ) -> elf::SectionHeader64<LE> {
+        elf::SectionHeader64 {
+            sh_name: U32::new(LE, name),
+            sh_type: U32::new(LE, kind),
+            sh_flags: U64::new(LE, 0),
+            sh_addr: U64::new(LE, 0),
+            sh_offset: U64::new(LE, offset as u64),
+            sh_size: U64::new(LE, size as u64),
+            sh_link: U32::new(LE, link),
+            sh_info: U32::new(LE, info),
+            sh_addralign: U64::new(LE, 1),
+            sh_entsize: U64::new(LE, entsize),
+        }
+    }
+
+    /// Relocatable x86-64 object with an 8 byte `.text` section, relocated by `relocations`
+    /// against the section's symbol.
+    fn object(relocations: &[(u64, i64)]) -> Vec<u8> {
+        let header_size = std::mem::size_of::<elf::FileHeader64<LE>>();
+        let text = header_size;
+        let rela = text + TEXT.len();
+        let rela_size = relocations.len() * std::mem::size_of::<elf::Rela64<LE>>();
+        let symtab = rela + rela_size;
+        let symtab_size = 2 * std::mem::size_of::<elf::Sym64<LE>>();
+        let names = symtab + symtab_size;
+        let headers = (names + NAMES.len()).next_multiple_of(8);
+
+        let header = elf::FileHeader64 {
+            e_ident: elf::Ident {
+                magic: elf::ELFMAG,
+                class: elf::ELFCLASS64,
+                data: elf::ELFDATA2LSB,
+                version: elf::EV_CURRENT,
+                os_abi: 0,
+                abi_version: 0,
+                padding: [0; 7],
+            },
+            e_type: U16::new(LE, elf::ET_REL),
+            e_machine: U16::new(LE, elf::EM_X86_64),
+            e_version: U32::new(LE, elf::EV_CURRENT as u32),
+            e_entry: U64::new(LE, 0),
+            e_phoff: U64::new(LE, 0),
+            e_shoff: U64::new(LE, headers as u64),
+            e_flags: U32::new(LE, 0),
+            e_ehsize: U16::new(LE, header_size as u16),
+            e_phentsize: U16::new(LE, 0),
+            e_phnum: U16::new(LE, 0),
+            e_shentsize: U16::new(LE, std::mem::size_of::<elf::SectionHeader64<LE>>() as u16),
+            e_shnum: U16::new(LE, 5),
+            e_shstrndx: U16::new(LE, 4),
+        };
+
+        let mut data = bytes_of(&header).to_vec();
+        data.extend_from_slice(&TEXT);
+
+        for &(offset, addend) in relocations {
+            let rela = elf::Rela64 {
+                r_offset: U64::new(LE, offset),
+                r_info: U64::new(LE, (1 << 32) | elf::R_X86_64_64 as u64),
+                r_addend: object::endian::I64::new(LE, addend),
+            };
+            data.extend_from_slice(bytes_of(&rela));
+        }
+
+        let section_sym = elf::Sym64 {
+            st_name: U32::new(LE, 0),
+            st_info: elf::STT_SECTION,
+            st_other: 0,
+            st_shndx: U16::new(LE, 1),
+            st_value: U64::new(LE, 0),
+            st_size: U64::new(LE, 0),
+        };
+        data.extend_from_slice(&[0; std::mem::size_of::<elf::Sym64<LE>>()]);
+        data.extend_from_slice(bytes_of(&section_sym));
+        data.extend_from_slice(NAMES);
+        data.resize(headers, 0);
+
+        let sym_size = std::mem::size_of::<elf::Sym64<LE>>() as u64;
+        let rela_entsize = std::mem::size_of::<elf::Rela64<LE>>() as u64;
+        for header in [
+            section_header(0, elf::SHT_NULL, 0, 0, 0, 0, 0),
+            section_header(1, elf::SHT_PROGBITS, text, TEXT.len(), 0, 0, 0),
+            section_header(7, elf::SHT_RELA, rela, rela_size, 3, 1, rela_entsize),
+            section_header(18, elf::SHT_SYMTAB, symtab, symtab_size, 4, 2, sym_size),
+            section_header(26, elf::SHT_STRTAB, names, NAMES.len(), 0, 0, 0),
+        ] {
+            data.extend_from_slice(bytes_of(&header));
+        }
+
+        data
+    }
+
+    fn relocate(data: &[u8]) -> Vec<Vec<u8>> {
+        let obj = ElfFile64::<LE>::parse(data).unwrap();
+        let mut sections: Vec<Section> = obj
+            .sections()
+            .map(|section| {
+                let name = section.name().unwrap().to_string();
+                let bytes: &'static [u8] = if name == ".text" { &TEXT } else { &[] };
+                let end = bytes.len();
+                let mut section = Section::new(name, "", SectionKind::Code, bytes, 0, end);
+                section.permissions.read = !bytes.is_empty();
+                section
+            })
+            .collect();
+
+        let layout = Layout::new(&obj, &mut sections);
+        layout.relocate(&obj, &mut sections)
+    }
+
+    #[test]
+    fn relocations_are_applied() {
+        let relocated = relocate(&object(&[(0, 4)]));
+        let value = (RELOCATABLE_BASE as u64 + 4).to_le_bytes();
+        assert_eq!(relocated, [value.to_vec()]);
+    }
+
+    #[test]
+    fn out_of_range_relocations_are_skipped() {
+        let relocated = relocate(&object(&[(0, 4), (0x100, 0), (u64::MAX - 3, 0)]));
+        let value = (RELOCATABLE_BASE as u64 + 4).to_le_bytes();
+        assert_eq!(relocated, [value.to_vec()]);
+    }
+}
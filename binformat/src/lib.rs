@@ -1,8 +1,15 @@
 use object::{Object, ObjectSection, ObjectSymbol};
 use processor_shared::{AddressMap, Addressed};
 
+pub mod classfile;
+pub mod coredump;
+pub mod dex;
+pub mod dotnet;
 pub mod elf;
+pub mod go;
+pub mod kernel;
 pub mod macho;
+pub mod objc;
 pub mod pe;
 
 pub struct RawSymbol<'data> {
@@ -10,6 +17,90 @@ pub struct RawSymbol<'data> {
     pub module: Option<&'data str>,
 }
 
+/// Function imported from a shared library.
+#[derive(Debug, Clone)]
+pub struct Import {
+    pub name: String,
+    /// Library the function is imported from, if known.
+    pub module: Option<String>,
+    /// Address of the thunk or table entry through which the function is called.
+    pub addr: usize,
+}
+
+/// Function exported by the object.
+#[derive(Debug, Clone)]
+pub struct Export {
+    pub name: String,
+    /// Index into a PE's export table.
+    pub ordinal: Option<u32>,
+    /// Zero if the export is forwarded.
+    pub addr: usize,
+    /// Library and function a PE export is forwarded to.
+    pub forward: Option<String>,
+}
+
+/// Why code runs without the binary calling it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoStartKind {
+    /// Called by the loader before the entrypoint and whenever a thread starts or exits.
+    TlsCallback,
+    /// Called when an exception is raised.
+    ExceptionHandler,
+    /// Resolves a delay-loaded import the first time it's called.
+    DelayImport,
+    /// Called before the entrypoint hands control to `main`.
+    Constructor,
+    /// Called once `main` returns or the library is unloaded.
+    Destructor,
+}
+
+impl std::fmt::Display for AutoStartKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TlsCallback => f.pad("TLS callback"),
+            Self::ExceptionHandler => f.pad("Exception handler"),
+            Self::DelayImport => f.pad("Delay-load thunk"),
+            Self::Constructor => f.pad("Constructor"),
+            Self::Destructor => f.pad("Destructor"),
+        }
+    }
+}
+
+/// Code that runs without being called by the binary, where malware likes to hide.
+#[derive(Debug, Clone)]
+pub struct AutoStart {
+    pub addr: usize,
+    pub kind: AutoStartKind,
+    /// Name the function gets if it doesn't have a symbol.
+    pub name: String,
+}
+
+fn parse_exports<'data, Obj: Object<'data, 'data>>(obj: &'data Obj) -> Vec<Export> {
+    let exports = match obj.exports() {
+        Ok(exports) => exports,
+        Err(err) => {
+            log::complex!(
+                w "[parse_exports] ",
+                y err.to_string(),
+                y "."
+            );
+            return Vec::new();
+        }
+    };
+
+    exports
+        .iter()
+        .filter_map(|export| {
+            Some(Export {
+                name: std::str::from_utf8(export.name()).ok()?.to_string(),
+                ordinal: None,
+                addr: export.address() as usize,
+                forward: None,
+            })
+        })
+        .collect()
+}
+
 fn parse_symbol_table<'data, Obj: Object<'data, 'data>>(
     obj: &'data Obj,
 ) -> AddressMap<RawSymbol<'data>> {
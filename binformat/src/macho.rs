@@ -1,8 +1,9 @@
-use crate::RawSymbol;
-use processor_shared::{AddressMap, Addressed, Section, SectionKind};
+use crate::{Export, Import, RawSymbol};
+use processor_shared::{AddressMap, Addressed, Permissions, Section, SectionKind};
 use object::macho::{self, DyldInfoCommand, DysymtabCommand, LinkeditDataCommand};
-use object::read::macho::{MachHeader, MachOFile, SymbolTable};
-use object::{Endianness, Object, ObjectSection, ObjectSegment, ReadRef, SectionFlags};
+use object::read::macho::{LoadCommandData, LoadCommandVariant, MachHeader, MachOFile};
+use object::read::macho::{Segment as _, SymbolTable};
+use object::{Endianness, Object, ObjectSection, ObjectSegment, ReadRef, SectionFlags, SegmentFlags};
 use std::mem::size_of;
 
 #[derive(Debug, Clone, Copy)]
@@ -107,6 +108,13 @@ impl ChainedFixupPointerGeneric {
     }
 }
 
+/// Load command with a summary of what it holds.
+#[derive(Debug, Clone)]
+pub struct LoadCommand {
+    pub name: &'static str,
+    pub desc: String,
+}
+
 pub struct MachoDebugInfo<'data, Mach: MachHeader> {
     /// Parsed Mach-O header.
     obj: &'data MachOFile<'data, Mach>,
@@ -118,6 +126,16 @@ pub struct MachoDebugInfo<'data, Mach: MachHeader> {
     pub syms: AddressMap<RawSymbol<'data>>,
     /// Parsed sections with extra metadata.
     pub sections: Vec<Section>,
+    /// Symbols bound to a dynamic library.
+    pub imports: Vec<Import>,
+    /// Symbols defined by the object that are visible to others.
+    pub exports: Vec<Export>,
+    /// Load commands in the order they appear in the header.
+    pub load_cmds: Vec<LoadCommand>,
+    /// Addresses of functions listed in `LC_FUNCTION_STARTS`.
+    pub function_starts: Vec<usize>,
+    /// Objective-C classes and selector references.
+    pub objc: crate::objc::Metadata,
     // ---- Required load commands ----
     chained_fixups: Option<&'data LinkeditDataCommand<Mach::Endian>>,
     symtab: Option<SymbolTable<'data, Mach>>,
@@ -133,6 +151,11 @@ impl<'data, Mach: MachHeader<Endian = Endianness>> MachoDebugInfo<'data, Mach> {
             base_addr: obj.segments().next().map(|seg| seg.address()).unwrap_or(0),
             syms: AddressMap::default(),
             sections: Vec::new(),
+            imports: Vec::new(),
+            exports: Vec::new(),
+            load_cmds: Vec::new(),
+            function_starts: Vec::new(),
+            objc: Default::default(),
             dylibs: Vec::new(),
             chained_fixups: None,
             symtab: None,
@@ -154,6 +177,9 @@ impl<'data, Mach: MachHeader<Endian = Endianness>> MachoDebugInfo<'data, Mach> {
             );
         }
         this.parse_dylid_info()?;
+        this.parse_imports();
+        this.objc = crate::objc::parse(obj, this.base_addr, &this.syms);
+        this.exports = crate::parse_exports(obj);
         Ok(this)
     }
 
@@ -185,11 +211,47 @@ impl<'data, Mach: MachHeader<Endian = Endianness>> MachoDebugInfo<'data, Mach> {
             if lcmd.cmd() == macho::LC_DYLD_CHAINED_FIXUPS {
                 self.chained_fixups = Some(lcmd.data()?);
             }
+            if lcmd.cmd() == macho::LC_FUNCTION_STARTS {
+                self.parse_function_starts(lcmd.data()?);
+            }
+
+            self.load_cmds.push(describe_load_cmd(&lcmd, endian)?);
         }
 
         Ok(())
     }
 
+    /// Function starts are encoded as ULEB128 deltas, starting at the __TEXT segment.
+    fn parse_function_starts(&mut self, cmd: &LinkeditDataCommand<Mach::Endian>) {
+        let endian = self.obj.endian();
+        let off = cmd.dataoff.get(endian) as u64;
+        let size = cmd.datasize.get(endian) as u64;
+
+        let mut bytes = match self.obj.data().read_bytes_at(off, size) {
+            Ok(bytes) => bytes,
+            Err(()) => {
+                log::complex!(
+                    w "[macho::parse_function_starts] ",
+                    y "Failed to read function starts at offset ",
+                    g format!("{off:#x}"),
+                    y "."
+                );
+                return;
+            }
+        };
+
+        let mut addr = self.base_addr as usize;
+        while let Some(delta) = read_uleb128(&mut bytes) {
+            // the list is terminated by a zero delta
+            if delta == 0 {
+                break;
+            }
+
+            addr += delta as usize;
+            self.function_starts.push(addr);
+        }
+    }
+
     fn parse_dylid_info(&mut self) -> Result<(), object::Error> {
         let endian = self.obj.endian();
         let dylib_info = match self.dylid_info {
@@ -236,6 +298,19 @@ impl<'data, Mach: MachHeader<Endian = Endianness>> MachoDebugInfo<'data, Mach> {
         Ok(())
     }
 
+    /// Collect the symbols bound to a dynamic library while parsing fixups.
+    fn parse_imports(&mut self) {
+        for Addressed { addr, item } in self.syms.iter() {
+            if let Some(module) = item.module {
+                self.imports.push(Import {
+                    name: item.name.to_string(),
+                    module: Some(module.to_string()),
+                    addr: *addr,
+                });
+            }
+        }
+    }
+
     fn parse_base_addr(&mut self) -> Result<(), object::Error> {
         // Macho addresses are relative to the __TEXT segment.
         for segment in self.obj.segments() {
@@ -261,6 +336,155 @@ impl<'data, Mach: MachHeader<Endian = Endianness>> MachoDebugInfo<'data, Mach> {
     }
 }
 
+fn read_uleb128(bytes: &mut &[u8]) -> Option<u64> {
+    let mut value = 0u64;
+    let mut shift = 0;
+
+    loop {
+        let (byte, rest) = bytes.split_first()?;
+        *bytes = rest;
+
+        if shift < 64 {
+            value |= ((byte & 0x7f) as u64) << shift;
+        }
+
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+
+        shift += 7;
+    }
+}
+
+/// Version encoded as `xxxx.yy.zz` in a 32-bit integer.
+fn format_version(version: u32) -> String {
+    let (major, minor, patch) = (version >> 16, (version >> 8) & 0xff, version & 0xff);
+    format!("{major}.{minor}.{patch}")
+}
+
+fn describe_load_cmd<E: object::Endian>(
+    lcmd: &LoadCommandData<E>,
+    endian: E,
+) -> Result<LoadCommand, object::Error> {
+    let string = |s| String::from_utf8_lossy(lcmd.string(endian, s).unwrap_or(b"")).into_owned();
+
+    let (name, desc) = match lcmd.variant()? {
+        LoadCommandVariant::Segment32(segment, _) => {
+            let name = String::from_utf8_lossy(segment.name()).into_owned();
+            ("LC_SEGMENT", name)
+        }
+        LoadCommandVariant::Segment64(segment, _) => {
+            let name = String::from_utf8_lossy(segment.name()).into_owned();
+            ("LC_SEGMENT_64", name)
+        }
+        LoadCommandVariant::EntryPoint(cmd) => (
+            "LC_MAIN",
+            format!(
+                "entry offset {:#x}, stack size {:#x}",
+                cmd.entryoff.get(endian),
+                cmd.stacksize.get(endian)
+            ),
+        ),
+        LoadCommandVariant::Dylib(cmd) | LoadCommandVariant::IdDylib(cmd) => {
+            let name = match lcmd.cmd() {
+                macho::LC_ID_DYLIB => "LC_ID_DYLIB",
+                macho::LC_LOAD_WEAK_DYLIB => "LC_LOAD_WEAK_DYLIB",
+                macho::LC_REEXPORT_DYLIB => "LC_REEXPORT_DYLIB",
+                macho::LC_LAZY_LOAD_DYLIB => "LC_LAZY_LOAD_DYLIB",
+                macho::LC_LOAD_UPWARD_DYLIB => "LC_LOAD_UPWARD_DYLIB",
+                _ => "LC_LOAD_DYLIB",
+            };
+
+            let dylib = &cmd.dylib;
+            let desc = format!(
+                "{} (version {}, compatible with {})",
+                string(dylib.name),
+                format_version(dylib.current_version.get(endian)),
+                format_version(dylib.compatibility_version.get(endian)),
+            );
+
+            (name, desc)
+        }
+        LoadCommandVariant::LoadDylinker(cmd) => ("LC_LOAD_DYLINKER", string(cmd.name)),
+        LoadCommandVariant::Rpath(cmd) => ("LC_RPATH", string(cmd.path)),
+        LoadCommandVariant::EncryptionInfo32(cmd) => (
+            "LC_ENCRYPTION_INFO",
+            describe_encryption(
+                cmd.cryptoff.get(endian),
+                cmd.cryptsize.get(endian),
+                cmd.cryptid.get(endian),
+            ),
+        ),
+        LoadCommandVariant::EncryptionInfo64(cmd) => (
+            "LC_ENCRYPTION_INFO_64",
+            describe_encryption(
+                cmd.cryptoff.get(endian),
+                cmd.cryptsize.get(endian),
+                cmd.cryptid.get(endian),
+            ),
+        ),
+        LoadCommandVariant::LinkeditData(cmd) => {
+            let name = match lcmd.cmd() {
+                macho::LC_CODE_SIGNATURE => "LC_CODE_SIGNATURE",
+                macho::LC_SEGMENT_SPLIT_INFO => "LC_SEGMENT_SPLIT_INFO",
+                macho::LC_FUNCTION_STARTS => "LC_FUNCTION_STARTS",
+                macho::LC_DATA_IN_CODE => "LC_DATA_IN_CODE",
+                macho::LC_DYLIB_CODE_SIGN_DRS => "LC_DYLIB_CODE_SIGN_DRS",
+                macho::LC_LINKER_OPTIMIZATION_HINT => "LC_LINKER_OPTIMIZATION_HINT",
+                macho::LC_DYLD_EXPORTS_TRIE => "LC_DYLD_EXPORTS_TRIE",
+                _ => "LC_DYLD_CHAINED_FIXUPS",
+            };
+
+            let desc = format!(
+                "{:#x} bytes at offset {:#x}",
+                cmd.datasize.get(endian),
+                cmd.dataoff.get(endian)
+            );
+
+            (name, desc)
+        }
+        LoadCommandVariant::Uuid(cmd) => {
+            let uuid: String = cmd.uuid.iter().map(|byte| format!("{byte:02X}")).collect();
+            ("LC_UUID", uuid)
+        }
+        LoadCommandVariant::Symtab(cmd) => {
+            ("LC_SYMTAB", format!("{} symbols", cmd.nsyms.get(endian)))
+        }
+        LoadCommandVariant::Dysymtab(..) => ("LC_DYSYMTAB", String::new()),
+        LoadCommandVariant::DyldInfo(..) => ("LC_DYLD_INFO", String::new()),
+        LoadCommandVariant::Thread(..) => ("LC_UNIXTHREAD", String::new()),
+        LoadCommandVariant::SourceVersion(..) => ("LC_SOURCE_VERSION", String::new()),
+        LoadCommandVariant::BuildVersion(cmd) => (
+            "LC_BUILD_VERSION",
+            format!(
+                "platform {}, minimum OS {}, SDK {}",
+                cmd.platform.get(endian),
+                format_version(cmd.minos.get(endian)),
+                format_version(cmd.sdk.get(endian))
+            ),
+        ),
+        LoadCommandVariant::VersionMin(cmd) => (
+            "LC_VERSION_MIN",
+            format!(
+                "minimum OS {}, SDK {}",
+                format_version(cmd.version.get(endian)),
+                format_version(cmd.sdk.get(endian))
+            ),
+        ),
+        _ => ("UNKNOWN", format!("{:#x}", lcmd.cmd())),
+    };
+
+    Ok(LoadCommand { name, desc })
+}
+
+fn describe_encryption(off: u32, size: u32, id: u32) -> String {
+    let state = match id {
+        0 => "not encrypted",
+        _ => "encrypted",
+    };
+    format!("{size:#x} bytes at offset {off:#x}, {state} (cryptid {id})")
+}
+
 /// Common Mach-O dwarf section names I've found so far.
 const DWARF_SECTIONS: [&str; 20] = [
     "__debug_abbrev",
@@ -285,10 +509,31 @@ const DWARF_SECTIONS: [&str; 20] = [
     "__debug_types",
 ];
 
+/// Mach-O sections are protected as the segment they're part of is initially.
+fn segment_permissions<'data, Mach: MachHeader>(
+    obj: &'data MachOFile<'data, Mach>,
+    segment: Option<&str>,
+) -> Permissions {
+    let segment = segment.and_then(|name| {
+        obj.segments().find(|seg| seg.name().ok().flatten() == Some(name))
+    });
+
+    match segment.map(|seg| seg.flags()) {
+        Some(SegmentFlags::MachO { initprot, .. }) => Permissions {
+            read: initprot & macho::VM_PROT_READ != 0,
+            write: initprot & macho::VM_PROT_WRITE != 0,
+            execute: initprot & macho::VM_PROT_EXECUTE != 0,
+        },
+        _ => Permissions::default(),
+    }
+}
+
 fn parse_sections<'data, Mach: MachHeader>(obj: &'data MachOFile<'data, Mach>) -> Vec<Section> {
     let mut sections = Vec::new();
     for section in obj.sections() {
         let (name, bytes, start, end) = crate::parse_section_generics(&section);
+        let file_offset = section.file_range().map(|(offset, _)| offset);
+        let permissions = segment_permissions(obj, section.segment_name().ok().flatten());
         let section_flags = match section.flags() {
             SectionFlags::MachO { flags } => flags,
             _ => unreachable!()
@@ -387,14 +632,18 @@ fn parse_sections<'data, Mach: MachHeader>(obj: &'data MachOFile<'data, Mach>) -
             kind = SectionKind::Debug;
         }
 
-        sections.push(Section::new(
+        let mut parsed = Section::new(
             name,
             ident,
             kind,
             bytes,
             start,
             end
-        ));
+        );
+
+        parsed.file_offset = file_offset;
+        parsed.permissions = permissions;
+        sections.push(parsed);
     }
 
     sections
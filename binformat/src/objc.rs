@@ -0,0 +1,338 @@
+//! Objective-C runtime metadata of Mach-O binaries, as laid out by the modern (ObjC 2) runtime.
+//!
+//! Classes are listed in `__objc_classlist`, each pointing to a read-only description with its
+//! name and methods. Class methods are found the same way through the class' metaclass.
+
+use crate::RawSymbol;
+use object::read::macho::{MachHeader, MachOFile};
+use object::{Endianness, Object, ObjectSection};
+use processor_shared::AddressMap;
+use std::collections::HashMap;
+
+/// Set in the `entsize` of a method list whose entries hold 32-bit offsets instead of pointers.
+const RELATIVE_METHODS: u32 = 0x8000_0000;
+
+/// Bits of a method list's `entsize` that hold flags instead of the size.
+const METHOD_LIST_FLAGS: u32 = 0xFFFF_0003;
+
+/// Low bits of a class' data pointer that hold flags, used by Swift classes.
+const CLASS_DATA_FLAGS: u64 = 0x7;
+
+/// Prefix of the symbols of classes, which superclasses in other libraries are bound to.
+const CLASS_SYMBOL_PREFIX: &str = "_OBJC_CLASS_$_";
+
+/// Method implemented by a class.
+#[derive(Debug, Clone)]
+pub struct Method {
+    pub selector: String,
+    /// Encoded types of the return value and arguments, e.g. `v16@0:8`.
+    pub types: String,
+    /// Address of the implementation.
+    pub imp: usize,
+    /// Whether it's a method of the class itself, instead of its instances.
+    pub class_method: bool,
+}
+
+impl Method {
+    /// Name of the method the way it's written in Objective-C, e.g. `-[NSString length]`.
+    pub fn symbol(&self, class: &str) -> String {
+        let kind = if self.class_method { '+' } else { '-' };
+        format!("{kind}[{class} {}]", self.selector)
+    }
+}
+
+/// Class defined by the binary.
+#[derive(Debug, Clone)]
+pub struct Class {
+    pub name: String,
+    /// Address of the class object.
+    pub addr: usize,
+    /// Name of the class it inherits from, if any.
+    pub superclass: Option<String>,
+    pub methods: Vec<Method>,
+}
+
+/// Objective-C classes and selectors referenced by a binary.
+#[derive(Debug, Default, Clone)]
+pub struct Metadata {
+    pub classes: Vec<Class>,
+    /// Addresses in `__objc_selrefs` along with the selector they refer to.
+    pub selector_refs: Vec<(usize, String)>,
+}
+
+impl Metadata {
+    /// Every method that's implemented by the binary, named after its class.
+    pub fn method_symbols(&self) -> impl Iterator<Item = (usize, String)> + '_ {
+        self.classes.iter().flat_map(|class| {
+            class
+                .methods
+                .iter()
+                .filter(|method| method.imp != 0)
+                .map(|method| (method.imp, method.symbol(&class.name)))
+        })
+    }
+}
+
+/// Mapped sections of the binary, to follow the pointers between the metadata.
+struct Memory<'data> {
+    sections: Vec<(u64, &'data [u8])>,
+    /// Address of the `__TEXT` segment, which chained fixups store offsets from.
+    base_addr: u64,
+    is_64: bool,
+    little_endian: bool,
+}
+
+impl<'data> Memory<'data> {
+    fn bytes(&self, addr: u64, len: usize) -> Option<&'data [u8]> {
+        let (start, data) = self.sections.iter().find(|(start, data)| {
+            addr >= *start && addr < start + data.len() as u64
+        })?;
+
+        let offset = (addr - start) as usize;
+        data.get(offset..offset.checked_add(len)?)
+    }
+
+    fn is_mapped(&self, addr: u64) -> bool {
+        self.bytes(addr, 1).is_some()
+    }
+
+    fn read_u32(&self, addr: u64) -> Option<u32> {
+        let bytes: [u8; 4] = self.bytes(addr, 4)?.try_into().ok()?;
+        Some(if self.little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    }
+
+    fn read_i32(&self, addr: u64) -> Option<i32> {
+        self.read_u32(addr).map(|value| value as i32)
+    }
+
+    /// Raw pointer, which might still be encoded as a chained fixup.
+    fn read_raw_ptr(&self, addr: u64) -> Option<u64> {
+        if !self.is_64 {
+            return self.read_u32(addr).map(u64::from);
+        }
+
+        let bytes: [u8; 8] = self.bytes(addr, 8)?.try_into().ok()?;
+        Some(if self.little_endian {
+            u64::from_le_bytes(bytes)
+        } else {
+            u64::from_be_bytes(bytes)
+        })
+    }
+
+    /// Pointer to somewhere in the binary, if it points to mapped memory.
+    ///
+    /// Binaries using chained fixups store rebases either as an address in the low 36 bits, or
+    /// as an offset from the `__TEXT` segment in the low 32 bits. Whichever is mapped is used,
+    /// binds to other libraries aren't mapped at all.
+    fn read_ptr(&self, addr: u64) -> Option<u64> {
+        let raw = self.read_raw_ptr(addr)?;
+        if raw == 0 {
+            return None;
+        }
+
+        [raw, raw & 0xF_FFFF_FFFF, self.base_addr + (raw & 0xFFFF_FFFF)]
+            .into_iter()
+            .find(|&target| self.is_mapped(target))
+    }
+
+    fn read_cstr(&self, addr: u64) -> Option<String> {
+        let (start, data) = self.sections.iter().find(|(start, data)| {
+            addr >= *start && addr < start + data.len() as u64
+        })?;
+
+        let bytes = &data[(addr - start) as usize..];
+        let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+        Some(String::from_utf8_lossy(&bytes[..len]).into_owned())
+    }
+
+    fn ptr_size(&self) -> u64 {
+        if self.is_64 { 8 } else { 4 }
+    }
+}
+
+/// Parse the classes and selector references of a binary.
+///
+/// `syms` are the symbols that were already found, which tell the names of superclasses that are
+/// bound to other libraries.
+pub fn parse<'data, Mach: MachHeader<Endian = Endianness>>(
+    obj: &'data MachOFile<'data, Mach>,
+    base_addr: u64,
+    syms: &AddressMap<RawSymbol<'data>>,
+) -> Metadata {
+    let memory = Memory {
+        sections: obj
+            .sections()
+            .filter_map(|section| Some((section.address(), section.data().ok()?)))
+            .collect(),
+        base_addr,
+        is_64: obj.is_64(),
+        little_endian: obj.is_little_endian(),
+    };
+
+    let section = |name: &str| {
+        obj.sections()
+            .find(|section| section.name().ok() == Some(name))
+            .map(|section| (section.address(), section.size()))
+    };
+
+    // superclasses in other libraries are bound to their class' symbol
+    let bound_classes: HashMap<usize, &str> = syms
+        .iter()
+        .filter_map(|sym| Some((sym.addr, sym.item.name.strip_prefix(CLASS_SYMBOL_PREFIX)?)))
+        .collect();
+
+    let mut metadata = Metadata::default();
+
+    if let Some((start, size)) = section("__objc_classlist") {
+        for entry in (start..start + size).step_by(memory.ptr_size() as usize) {
+            let class = memory.read_ptr(entry);
+            match class.and_then(|addr| parse_class(&memory, addr, &bound_classes)) {
+                Some(class) => metadata.classes.push(class),
+                None => log::complex!(
+                    w "[objc::parse] ",
+                    y "Failed to parse class listed at ",
+                    g format!("{entry:#x}"),
+                    y "."
+                ),
+            }
+        }
+    }
+
+    if let Some((start, size)) = section("__objc_selrefs") {
+        for entry in (start..start + size).step_by(memory.ptr_size() as usize) {
+            if let Some(selector) = memory.read_ptr(entry).and_then(|addr| memory.read_cstr(addr)) {
+                metadata.selector_refs.push((entry as usize, selector));
+            }
+        }
+    }
+
+    if !metadata.classes.is_empty() {
+        log::complex!(
+            w "[objc::parse] found ",
+            g metadata.classes.len().to_string(),
+            w " classes with ",
+            g metadata.method_symbols().count().to_string(),
+            w " methods."
+        );
+    }
+
+    metadata
+}
+
+fn parse_class(memory: &Memory, addr: u64, bound_classes: &HashMap<usize, &str>) -> Option<Class> {
+    let (name, mut methods) = parse_class_data(memory, addr, false)?;
+
+    // class methods are the instance methods of the metaclass
+    if let Some(metaclass) = memory.read_ptr(addr) {
+        if let Some((_, class_methods)) = parse_class_data(memory, metaclass, true) {
+            methods.extend(class_methods);
+        }
+    }
+
+    let superclass_field = addr + memory.ptr_size();
+    let superclass = match memory.read_ptr(superclass_field) {
+        Some(superclass) => parse_class_data(memory, superclass, false).map(|(name, _)| name),
+        None => bound_classes.get(&(superclass_field as usize)).map(|name| name.to_string()),
+    };
+
+    Some(Class {
+        name,
+        addr: addr as usize,
+        superclass,
+        methods,
+    })
+}
+
+/// Name and methods from the read-only data of a class.
+fn parse_class_data(
+    memory: &Memory,
+    addr: u64,
+    class_method: bool,
+) -> Option<(String, Vec<Method>)> {
+    let ptr_size = memory.ptr_size();
+
+    // isa, superclass, cache and vtable come before the data
+    let data = memory.read_ptr(addr + 4 * ptr_size)? & !CLASS_DATA_FLAGS;
+
+    // flags, instance start and size, with 4 bytes of padding on 64-bit, and the ivar layout
+    let name_field = data + if memory.is_64 { 16 } else { 12 } + ptr_size;
+    let name = memory.read_cstr(memory.read_ptr(name_field)?)?;
+
+    let methods = match memory.read_ptr(name_field + ptr_size) {
+        Some(list) => parse_method_list(memory, list, class_method),
+        None => Vec::new(),
+    };
+
+    Some((name, methods))
+}
+
+fn parse_method_list(memory: &Memory, addr: u64, class_method: bool) -> Vec<Method> {
+    let mut methods = Vec::new();
+
+    let (entsize, count) = match (memory.read_u32(addr), memory.read_u32(addr + 4)) {
+        (Some(entsize), Some(count)) => (entsize, count),
+        _ => return methods,
+    };
+
+    let relative = entsize & RELATIVE_METHODS != 0;
+    let size = (entsize & !METHOD_LIST_FLAGS) as u64;
+    if size == 0 {
+        return methods;
+    }
+
+    for idx in 0..count as u64 {
+        let entry = addr + 8 + idx * size;
+        let method = if relative {
+            parse_relative_method(memory, entry, class_method)
+        } else {
+            parse_method(memory, entry, class_method)
+        };
+
+        if let Some(method) = method {
+            methods.push(method);
+        }
+    }
+
+    methods
+}
+
+/// Method whose selector, types and implementation are pointers.
+fn parse_method(memory: &Memory, addr: u64, class_method: bool) -> Option<Method> {
+    let ptr_size = memory.ptr_size();
+
+    Some(Method {
+        selector: memory.read_cstr(memory.read_ptr(addr)?)?,
+        types: memory
+            .read_ptr(addr + ptr_size)
+            .and_then(|types| memory.read_cstr(types))
+            .unwrap_or_default(),
+        imp: memory.read_ptr(addr + 2 * ptr_size).unwrap_or(0) as usize,
+        class_method,
+    })
+}
+
+/// Method whose fields are offsets from themselves, the selector's pointing to a selector
+/// reference instead of the selector itself.
+fn parse_relative_method(memory: &Memory, addr: u64, class_method: bool) -> Option<Method> {
+    let relative = |field: u64| {
+        let offset = memory.read_i32(field)?;
+        Some(field.wrapping_add_signed(offset as i64))
+    };
+
+    let selector_ref = relative(addr)?;
+    let imp = match memory.read_i32(addr + 8)? {
+        0 => 0,
+        _ => relative(addr + 8)? as usize,
+    };
+
+    Some(Method {
+        selector: memory.read_cstr(memory.read_ptr(selector_ref)?)?,
+        types: relative(addr + 4).and_then(|types| memory.read_cstr(types)).unwrap_or_default(),
+        imp,
+        class_method,
+    })
+}
@@ -1,9 +1,9 @@
-use crate::{datastructure, RawSymbol};
-use processor_shared::{AddressMap, Addressed, Section, SectionKind};
+use crate::{datastructure, AutoStart, AutoStartKind, Export, Import, RawSymbol};
+use processor_shared::{AddressMap, Addressed, Permissions, Section, SectionKind};
 use object::pe;
-use object::read::pe::{ImageNtHeaders, ImageThunkData, PeFile};
+use object::read::pe::{ExportTarget, ImageNtHeaders, ImageThunkData, PeFile};
 use object::LittleEndian as LE;
-use object::Object;
+use object::{Object, ObjectSection};
 use std::mem::size_of;
 
 datastructure! {
@@ -14,6 +14,16 @@ datastructure! {
     }
 }
 
+/// Offset of the SafeSEH table into the load config of an x86 image, followed by its length.
+const SAFESEH_TABLE: usize = 0x40;
+
+/// Unwind info has an exception handler.
+const UNW_FLAG_EHANDLER: u8 = 0x1;
+/// Unwind info has a termination handler.
+const UNW_FLAG_UHANDLER: u8 = 0x2;
+/// Unwind info continues that of another function.
+const UNW_FLAG_CHAININFO: u8 = 0x4;
+
 pub struct PeDebugInfo<'data, Pe: ImageNtHeaders> {
     /// Parsed PE32/64 header.
     obj: &'data PeFile<'data, Pe>,
@@ -21,6 +31,14 @@ pub struct PeDebugInfo<'data, Pe: ImageNtHeaders> {
     pub sections: Vec<Section>,
     /// Any parsed but not yet relocated symbols.
     pub syms: AddressMap<RawSymbol<'data>>,
+    /// Functions imported through the import address table.
+    pub imports: Vec<Import>,
+    /// Functions in the export table.
+    pub exports: Vec<Export>,
+    /// Managed types and methods, if it's a .NET assembly.
+    pub dotnet: Option<crate::dotnet::Metadata>,
+    /// TLS callbacks, exception handlers and delay-load thunks.
+    pub autostart: Vec<AutoStart>,
 }
 
 impl<'data, Pe: ImageNtHeaders> PeDebugInfo<'data, Pe> {
@@ -29,10 +47,19 @@ impl<'data, Pe: ImageNtHeaders> PeDebugInfo<'data, Pe> {
             obj,
             syms: AddressMap::default(),
             sections: Vec::new(),
+            imports: Vec::new(),
+            exports: Vec::new(),
+            dotnet: None,
+            autostart: Vec::new(),
         };
         this.sections = parse_sections(obj);
         this.parse_symbols();
         this.parse_imports()?;
+        this.parse_delay_imports();
+        this.parse_exports()?;
+        this.parse_tls_callbacks();
+        this.parse_exception_handlers();
+        this.dotnet = crate::dotnet::parse(obj);
         Ok(this)
     }
 
@@ -87,6 +114,11 @@ impl<'data, Pe: ImageNtHeaders> PeDebugInfo<'data, Pe> {
 
                     let module =
                         std::str::from_utf8(module).ok().and_then(|x| x.strip_suffix(".dll"));
+                    self.imports.push(Import {
+                        name: name.to_string(),
+                        module: module.map(|module| module.to_string()),
+                        addr: addr as usize,
+                    });
                     self.syms.push(Addressed {
                         addr: addr as usize,
                         item: RawSymbol { name, module },
@@ -101,6 +133,241 @@ impl<'data, Pe: ImageNtHeaders> PeDebugInfo<'data, Pe> {
         Ok(())
     }
 
+    /// Pointer sized value at a relative address.
+    fn pointer(&self, rva: u32) -> Option<u64> {
+        let bytes = self.obj.section_table().pe_data_at(self.obj.data(), rva)?;
+        match self.obj.is_64() {
+            true => Some(u64::from_le_bytes(bytes.get(..8)?.try_into().unwrap())),
+            false => Some(u32::from_le_bytes(bytes.get(..4)?.try_into().unwrap()) as u64),
+        }
+    }
+
+    /// Contents of a data directory.
+    fn directory(&self, idx: usize) -> Option<&'data [u8]> {
+        let dir = self.obj.data_directory(idx)?;
+        dir.data(self.obj.data(), &self.obj.section_table()).ok()
+    }
+
+    /// Imports that are only resolved once they're first called, through a thunk that calls the
+    /// delay-load helper.
+    pub fn parse_delay_imports(&mut self) {
+        let descriptors = match self.directory(pe::IMAGE_DIRECTORY_ENTRY_DELAY_IMPORT) {
+            Some(descriptors) => descriptors,
+            None => return,
+        };
+
+        let base = self.obj.relative_address_base();
+        let width: u32 = if self.obj.is_64() { 8 } else { 4 };
+        let ordinal_flag = 1u64 << (width * 8 - 1);
+        let string = |rva: u32| {
+            let bytes = self.obj.section_table().pe_data_at(self.obj.data(), rva)?;
+            let len = bytes.iter().position(|&byte| byte == 0)?;
+            std::str::from_utf8(&bytes[..len]).ok()
+        };
+
+        for desc in descriptors.chunks_exact(size_of::<pe::ImageDelayloadDescriptor>()) {
+            let field =
+                |idx: usize| u32::from_le_bytes(desc[idx * 4..idx * 4 + 4].try_into().unwrap());
+            let (attributes, name, iat, int) = (field(0), field(1), field(3), field(4));
+            if name == 0 {
+                break;
+            }
+
+            // descriptors of old linkers hold addresses instead of relative addresses
+            let rva = |addr: u32| match attributes & 1 {
+                0 => addr.wrapping_sub(base as u32),
+                _ => addr,
+            };
+
+            let module =
+                string(rva(name)).map(|module| module.strip_suffix(".dll").unwrap_or(module));
+            for idx in 0.. {
+                let thunk = match self.pointer(rva(int) + idx * width) {
+                    Some(0) | None => break,
+                    Some(thunk) => thunk,
+                };
+
+                // skip over imports by ordinal
+                if thunk & ordinal_flag != 0 {
+                    continue;
+                }
+
+                // the name follows a hint into the export table
+                let name = match string(rva(thunk as u32) + 2) {
+                    Some(name) => name,
+                    None => continue,
+                };
+
+                let slot = rva(iat) + idx * width;
+                let addr = base as usize + slot as usize;
+                self.imports.push(Import {
+                    name: name.to_string(),
+                    module: module.map(|module| module.to_string()),
+                    addr,
+                });
+                self.syms.push(Addressed {
+                    addr,
+                    item: RawSymbol { name, module },
+                });
+
+                // the slot points to the thunk until the import is resolved
+                if let Some(thunk) = self.pointer(slot).filter(|&thunk| thunk != 0) {
+                    self.autostart.push(AutoStart {
+                        addr: thunk as usize,
+                        kind: AutoStartKind::DelayImport,
+                        name: format!("__imp_load_{name}"),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Callbacks of the TLS directory, which the loader calls before the entrypoint.
+    pub fn parse_tls_callbacks(&mut self) {
+        let tls = match self.directory(pe::IMAGE_DIRECTORY_ENTRY_TLS) {
+            Some(tls) => tls,
+            None => return,
+        };
+
+        // the array of callbacks follows the range of the template and the index
+        let base = self.obj.relative_address_base();
+        let width = if self.obj.is_64() { 8 } else { 4 };
+        let callbacks = match tls.get(3 * width..4 * width) {
+            Some(bytes) if width == 8 => u64::from_le_bytes(bytes.try_into().unwrap()),
+            Some(bytes) => u32::from_le_bytes(bytes.try_into().unwrap()) as u64,
+            None => return,
+        };
+
+        let mut entry = match callbacks.checked_sub(base) {
+            Some(rva) if callbacks != 0 => rva as u32,
+            _ => return,
+        };
+
+        while let Some(callback) = self.pointer(entry).filter(|&callback| callback != 0) {
+            let idx = self
+                .autostart
+                .iter()
+                .filter(|start| start.kind == AutoStartKind::TlsCallback)
+                .count();
+            self.autostart.push(AutoStart {
+                addr: callback as usize,
+                kind: AutoStartKind::TlsCallback,
+                name: format!("tls_callback_{idx}"),
+            });
+
+            entry += width as u32;
+        }
+    }
+
+    /// Handlers registered in the SafeSEH table of x86 images or in the unwind info of x86-64
+    /// images.
+    pub fn parse_exception_handlers(&mut self) {
+        let base = self.obj.relative_address_base() as usize;
+        let mut handlers = std::collections::BTreeSet::new();
+
+        match self.obj.nt_headers().file_header().machine.get(LE) {
+            pe::IMAGE_FILE_MACHINE_I386 => {
+                let config = match self.directory(pe::IMAGE_DIRECTORY_ENTRY_LOAD_CONFIG) {
+                    Some(config) => config,
+                    None => return,
+                };
+
+                // older load configs end before the SafeSEH table
+                let field = |offset: usize| {
+                    let bytes = config.get(offset..offset + 4)?;
+                    Some(u32::from_le_bytes(bytes.try_into().unwrap()))
+                };
+
+                let (table, count) = match (field(SAFESEH_TABLE), field(SAFESEH_TABLE + 4)) {
+                    (Some(table), Some(count)) if table != 0 => (table, count),
+                    _ => return,
+                };
+
+                let table = table.wrapping_sub(base as u32);
+                for idx in 0..count {
+                    let entry =
+                        self.obj.section_table().pe_data_at(self.obj.data(), table + idx * 4);
+                    match entry.and_then(|entry| entry.get(..4)) {
+                        Some(rva) => handlers.insert(u32::from_le_bytes(rva.try_into().unwrap())),
+                        None => break,
+                    };
+                }
+            }
+            pe::IMAGE_FILE_MACHINE_AMD64 => {
+                let functions = match self.directory(pe::IMAGE_DIRECTORY_ENTRY_EXCEPTION) {
+                    Some(functions) => functions,
+                    None => return,
+                };
+
+                for function in functions.chunks_exact(size_of::<ExceptionDirectoryEntry>()) {
+                    let unwind_info = u32::from_le_bytes(function[8..12].try_into().unwrap());
+                    if let Some(handler) = self.unwind_handler(unwind_info) {
+                        handlers.insert(handler);
+                    }
+                }
+            }
+            _ => return,
+        }
+
+        self.autostart.extend(handlers.into_iter().map(|rva| AutoStart {
+            addr: base + rva as usize,
+            kind: AutoStartKind::ExceptionHandler,
+            name: format!("exception_handler_{:x}", base + rva as usize),
+        }));
+    }
+
+    /// Relative address of the language specific handler of x86-64 unwind info.
+    fn unwind_handler(&self, rva: u32) -> Option<u32> {
+        let info = self.obj.section_table().pe_data_at(self.obj.data(), rva)?;
+        let flags = info.first()? >> 3;
+        if flags & (UNW_FLAG_EHANDLER | UNW_FLAG_UHANDLER) == 0 || flags & UNW_FLAG_CHAININFO != 0 {
+            return None;
+        }
+
+        // the handler follows the unwind codes, of which there's always an even number
+        let codes = (*info.get(2)? as usize + 1) & !1;
+        let offset = 4 + codes * 2;
+        let handler = u32::from_le_bytes(info.get(offset..offset + 4)?.try_into().unwrap());
+        (handler != 0).then_some(handler)
+    }
+
+    pub fn parse_exports(&mut self) -> Result<(), object::Error> {
+        let export_table = match self.obj.export_table()? {
+            Some(table) => table,
+            None => return Ok(()),
+        };
+
+        for export in export_table.exports()? {
+            let name = match export.name {
+                Some(name) => String::from_utf8_lossy(name).into_owned(),
+                None => format!("ordinal_{}", export.ordinal),
+            };
+
+            let base = self.obj.relative_address_base() as usize;
+            let (addr, forward) = match export.target {
+                ExportTarget::Address(rva) => (base + rva as usize, None),
+                ExportTarget::ForwardByName(module, name) => {
+                    let module = String::from_utf8_lossy(module);
+                    let name = String::from_utf8_lossy(name);
+                    (0, Some(format!("{module}.{name}")))
+                }
+                ExportTarget::ForwardByOrdinal(module, ordinal) => {
+                    let module = String::from_utf8_lossy(module);
+                    (0, Some(format!("{module}.#{ordinal}")))
+                }
+            };
+
+            self.exports.push(Export {
+                name,
+                ordinal: Some(export.ordinal),
+                addr,
+                forward,
+            });
+        }
+
+        Ok(())
+    }
+
     pub fn parse_symbols(&mut self) {
         self.syms.extend(crate::parse_symbol_table(self.obj));
         self.syms.push(Addressed {
@@ -151,6 +418,12 @@ fn parse_sections<'data, Pe: ImageNtHeaders>(obj: &'data PeFile<'data, Pe>) -> V
         let (name, bytes, start, end) = crate::parse_section_generics(&section);
 
         let characteristics = header.characteristics.get(LE);
+        let file_offset = section.file_range().map(|(offset, _)| offset);
+        let permissions = Permissions {
+            read: characteristics & pe::IMAGE_SCN_MEM_READ != 0,
+            write: characteristics & pe::IMAGE_SCN_MEM_WRITE != 0,
+            execute: characteristics & pe::IMAGE_SCN_MEM_EXECUTE != 0,
+        };
         let (mut kind, ident) = (SectionKind::Raw, "UNKNOWN");
 
         // Section contains code.
@@ -168,14 +441,18 @@ fn parse_sections<'data, Pe: ImageNtHeaders>(obj: &'data PeFile<'data, Pe>) -> V
             kind = SectionKind::Debug;
         }
 
-        sections.push(Section::new(
+        let mut parsed = Section::new(
             name,
             ident,
             kind,
             bytes,
             start,
             end
-        ));
+        );
+
+        parsed.file_offset = file_offset;
+        parsed.permissions = permissions;
+        sections.push(parsed);
     }
 
     sections
@@ -1,3 +1,4 @@
+use std::net::{Ipv4Addr, SocketAddr};
 use std::path::PathBuf;
 
 macro_rules! exit {
@@ -16,10 +17,18 @@ OPTIONS:
   -L, --libs          Print linked shared libraries 
   -N, --names         Print all symbols exposed by object
   -S, --simplify      Replace common types with shortened paths
-  -D, --disassemble   Path to object you're disassembling
+  -D, --disassemble   Print the disassembly of an object without opening a window
+      --section <n>   Only disassemble the section with the given name
+      --symbol <n>    Only disassemble the symbol with the given name
+      --html <path>   Write the disassembly to an HTML page instead of stdout
+      --serve [addr]  Answer JSON-RPC requests on a socket, 127.0.0.1:6510 by default
   -T, --tracing       Trace all syscalls performed
   -C, --config        Path to config used for disassembling
-  -B, --debug         Enable extra debug information";
+  -B, --debug         Enable extra debug information
+      --safe-mode     Disable plugins, scripting, analysis passes and networking";
+
+/// Port used by `--serve` when no address is given.
+const DEFAULT_PORT: u16 = 6510;
 
 const ABBRV: &[&str] = &["-H", "-L", "-S", "-D", "-C", "-T", "-B"];
 const NAMES: &[&str] = &[
@@ -28,9 +37,14 @@ const NAMES: &[&str] = &[
     "--names",
     "--simplify",
     "--disassemble",
+    "--section",
+    "--symbol",
+    "--html",
+    "--serve",
     "--tracing",
     "--config",
     "--debug",
+    "--safe-mode",
 ];
 
 #[derive(Default, Debug, Clone)]
@@ -44,15 +58,35 @@ pub struct Cli {
     /// Strip symbols into a simpler format.
     pub simplify: bool,
 
-    /// Disassemble object into `readable` assembly,
+    /// Print the disassembly of the object to stdout, without opening the GUI.
     pub disassemble: bool,
 
+    /// Open the object in the GUI, when no other action was given.
+    pub gui: bool,
+
+    /// Names of the sections to disassemble, every code section if empty.
+    pub sections: Vec<String>,
+
+    /// Names of the symbols to disassemble.
+    pub symbols: Vec<String>,
+
+    /// Path of the HTML page the disassembly is exported to.
+    pub html: Option<PathBuf>,
+
+    /// Address of the socket that analysis requests are served on.
+    pub serve: Option<SocketAddr>,
+
     /// Record syscalls.
     pub tracing: bool,
 
     /// Show egui debug overlay.
     pub debug: bool,
 
+    /// Disable every [`Feature`] for analyzing untrusted binaries.
+    ///
+    /// [`Feature`]: crate::Feature
+    pub safe_mode: bool,
+
     /// Path to symbol being disassembled.
     pub path: Option<PathBuf>,
 
@@ -62,8 +96,13 @@ pub struct Cli {
 
 impl Cli {
     pub fn parse() -> Self {
+        Self::parse_from(std::env::args().skip(1))
+    }
+
+    /// Parse arguments that don't include the name of the executable.
+    fn parse_from(args: impl IntoIterator<Item = String>) -> Self {
         let mut cli = Cli::default();
-        let mut args = std::env::args().skip(1).peekable();
+        let mut args = args.into_iter().peekable();
 
         while let Some(arg) = args.next() {
             match arg.as_str() {
@@ -90,14 +129,43 @@ impl Cli {
                 "-D" | "--disassemble" => {
                     cli.disassemble = true;
 
-                    if let Some(path) = args.next().as_deref() {
-                        if !NAMES.contains(&path) && !ABBRV.contains(&path) {
-                            cli.path = Some(PathBuf::from(path));
-                        }
+                    // the path is optional, so don't consume the flag that follows
+                    let is_path = |arg: &String| {
+                        !NAMES.contains(&arg.as_str()) && !ABBRV.contains(&arg.as_str())
+                    };
+
+                    if let Some(path) = args.next_if(is_path) {
+                        cli.path = Some(PathBuf::from(path));
+                    }
+                }
+                "--section" => match args.next() {
+                    Some(name) => cli.sections.push(name),
+                    None => exit!(1 => "Missing name of a section."),
+                },
+                "--symbol" => match args.next() {
+                    Some(name) => cli.symbols.push(name),
+                    None => exit!(1 => "Missing name of a symbol."),
+                },
+                "--html" => match args.next() {
+                    Some(path) => cli.html = Some(PathBuf::from(path)),
+                    None => exit!(1 => "Missing path of the HTML page."),
+                },
+                "--serve" => {
+                    // the address is optional, so only consume what follows if it's one
+                    let addr = args.peek().and_then(|arg| arg.parse::<SocketAddr>().ok());
+                    if addr.is_some() {
+                        args.next();
                     }
+
+                    let default = SocketAddr::from((Ipv4Addr::LOCALHOST, DEFAULT_PORT));
+                    cli.serve = Some(addr.unwrap_or(default));
                 }
                 "-T" | "--tracing" => cli.tracing = true,
                 "-B" | "--debug" => cli.debug = true,
+                "--safe-mode" => cli.safe_mode = true,
+                path if !path.starts_with('-') && cli.path.is_none() => {
+                    cli.path = Some(PathBuf::from(path));
+                }
                 unknown => {
                     let mut distance = u32::MAX;
                     let mut best_guess = "";
@@ -124,22 +192,101 @@ impl Cli {
     }
 
     fn validate_args(&mut self) {
+        if self.safe_mode && self.tracing {
+            exit!(1 => "Tracing runs the object, which isn't allowed in safe mode.");
+        }
+
         if self.disassemble || self.libs || self.names {
             if self.path.is_none() {
                 exit!(1 => "Missing path to an object.");
             }
-        } else {
+        } else if self.serve.is_none() {
             // no action arguments were given
-            self.disassemble = true;
+            self.gui = true;
+        }
+
+        let selected = !self.sections.is_empty() || !self.symbols.is_empty();
+        if !self.disassemble && selected {
+            exit!(1 => "Sections and symbols can only be selected when disassembling.");
+        }
+
+        if !self.disassemble && self.html.is_some() {
+            exit!(1 => "Only the disassembly can be exported to HTML.");
+        }
+
+        if self.gui {
             return;
         }
 
-        if self.tracing && !self.disassemble {
+        // only the GUI runs the object
+        if self.tracing {
             exit!(1 => "Invalid combination of arguements.\n\n{HELP}");
         }
 
-        if self.disassemble as usize + self.libs as usize + self.names as usize > 1 {
+        let serve = self.serve.is_some();
+        let actions = [self.disassemble, self.libs, self.names, serve];
+        if actions.into_iter().filter(|&action| action).count() > 1 {
             exit!(1 => "Invalid combination of arguements.\n\n{HELP}");
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Cli {
+        Cli::parse_from(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn gui_by_default() {
+        let cli = parse(&[]);
+        assert!(cli.gui);
+        assert_eq!(cli.path, None);
+
+        let cli = parse(&["./a.out"]);
+        assert!(cli.gui);
+        assert!(!cli.disassemble);
+        assert_eq!(cli.path, Some(PathBuf::from("./a.out")));
+    }
+
+    #[test]
+    fn disassemble() {
+        let cli = parse(&["-D", "./a.out"]);
+        assert!(cli.disassemble);
+        assert!(!cli.gui);
+        assert_eq!(cli.path, Some(PathBuf::from("./a.out")));
+
+        // the path can come after the flag that follows
+        let cli = parse(&["--disassemble", "--section", ".text", "./a.out"]);
+        assert!(cli.disassemble);
+        assert_eq!(cli.sections, [".text"]);
+        assert_eq!(cli.path, Some(PathBuf::from("./a.out")));
+    }
+
+    #[test]
+    fn disassemble_selection() {
+        let cli = parse(&[
+            "-D",
+            "./a.out",
+            "--section",
+            ".text",
+            "--section",
+            ".init",
+            "--symbol",
+            "main",
+        ]);
+        assert_eq!(cli.sections, [".text", ".init"]);
+        assert_eq!(cli.symbols, ["main"]);
+        assert_eq!(cli.html, None);
+    }
+
+    #[test]
+    fn disassemble_html() {
+        let cli = parse(&["-D", "./a.out", "--html", "listing.html"]);
+        assert!(cli.disassemble);
+        assert!(!cli.gui);
+        assert_eq!(cli.html, Some(PathBuf::from("listing.html")));
+    }
+}
@@ -1,16 +1,58 @@
 use std::fmt;
 use std::path::{Path, PathBuf};
 
+use processor_shared::{AddressMode, RegisterNames};
+
 use crate::debug::CompleteExpr;
 
 pub const HELP: &str = "\
 Available commands:
-    pwd                -- Display the current path
-    cd <path>          -- Change the current directory to the specified path
-    quit               -- Exit the program
-    goto <expr>        -- Jump to code/data at the specified expression
-    clear              -- Clear out terminal
-    help               -- Display this help message";
+    pwd                 -- Display the current path
+    cd <path>           -- Change the current directory to the specified path
+    quit                -- Exit the program
+    goto <expr>         -- Jump to code/data at the specified expression
+    rename <expr> <n>   -- Rename the symbol at the specified expression
+    comment <expr> <c>  -- Attach a comment to the specified expression
+    bookmark <expr> <d> -- Bookmark the specified expression
+    patch <expr> <hex>  -- Overwrite the code at the specified expression with bytes
+    struct [def]        -- Define structures in C, e.g. 'struct point { int x; int y; };', or list them
+    enum [def]          -- Define enumerations in C, e.g. 'enum prot { READ = 1, WRITE = 2 };', or list them
+    apply <expr> [name] -- Show the data or immediates at the specified expression as a structure or enumeration
+    back                -- Go back to the previous location (Alt+Left)
+    forward             -- Go forward to the next location (Alt+Right)
+    address <mode>      -- Display addresses as 'va', 'rva' or 'section' offsets
+    registers <names>   -- Name registers by their 'abi' alias or 'arch' name
+    save                -- Save the project of the loaded binary
+    export <path>       -- Export the listing as an HTML page, or the symbols as a .map or .csv file
+    import <path>       -- Import symbols and comments from a Ghidra CSV, radare2 script, map file or kallsyms
+    script <path>       -- Run a script with the loaded binary as 'bin'
+    plugins             -- List the loaded and rejected plugins
+    connect <addr>      -- Debug a target over the GDB remote protocol, e.g. 'localhost:1234'
+    launch <path>       -- Start a program on this machine and debug it, only supported on Windows
+    attach <pid>        -- Snapshot the executable memory of a local process and disassemble it
+    detach              -- Stop debugging, letting the target continue
+    continue            -- Run the target until it stops (F5)
+    step                -- Run a single instruction of the target (F10)
+    stop                -- Interrupt the running target (F5)
+    break <expr>        -- Toggle a breakpoint at the specified expression (F9)
+    cond <expr> <cond>  -- Only stop at a breakpoint when a condition holds, e.g. 'rax == 0'
+    watch <expr>        -- Toggle a watchpoint stopping the target when it writes the address
+    rwatch <expr>       -- Toggle a watchpoint stopping the target when it reads the address
+    awatch <expr>       -- Toggle a watchpoint stopping the target when it accesses the address
+    sync                -- Show the code in the target's memory instead of the binary's
+    memory <expr>       -- Show the target's memory at the specified expression
+    write <expr> <hex>  -- Overwrite the target's memory at the specified expression with bytes
+    trace               -- Record the instructions the target executes, single-stepping it
+    trace save <path>   -- Save the recorded instructions as a drcov file
+    trace load <path>   -- Show how often instructions ran according to a drcov file
+    trace clear         -- Stop showing how often instructions ran
+    emulate <expr> ...  -- Run the function at the specified expression with the arguments after it
+    timings [path]      -- Show how long every stage of loading took, or save them as JSON
+    overlay [path]      -- Show the data appended past the end of the image, or extract it
+    carve [dir]         -- List the files embedded in the binary, or extract them into a directory
+    crypto              -- List the constants of cryptographic algorithms found in the binary
+    clear               -- Clear out terminal
+    help                -- Display this help message";
 
 #[derive(Debug, PartialEq)]
 pub enum Command {
@@ -19,6 +61,49 @@ pub enum Command {
     ChangeDir(PathBuf),
     Quit,
     Goto(usize),
+    Rename(usize, String),
+    Comment(usize, String),
+    Bookmark(usize, String),
+    Patch(usize, Vec<u8>),
+    Struct(String),
+    Structs,
+    Enum(String),
+    Enums,
+    Apply(usize, String),
+    Back,
+    Forward,
+    AddressMode(AddressMode),
+    RegisterNames(RegisterNames),
+    Save,
+    Export(PathBuf),
+    ExportSymbols(PathBuf),
+    Import(PathBuf),
+    Script(PathBuf),
+    Plugins,
+    Connect(String),
+    Launch(PathBuf),
+    Attach(u32),
+    Detach,
+    Continue,
+    Step,
+    Stop,
+    Break(usize),
+    Condition(usize, String),
+    Watch(usize),
+    ReadWatch(usize),
+    AccessWatch(usize),
+    Sync,
+    Memory(usize),
+    Write(usize, Vec<u8>),
+    Trace,
+    SaveTrace(PathBuf),
+    LoadTrace(PathBuf),
+    ClearTrace,
+    Emulate(usize, Vec<u64>),
+    Timings(Option<PathBuf>),
+    Overlay(Option<PathBuf>),
+    Carve(Option<PathBuf>),
+    Crypto,
     Clear,
     Help,
 }
@@ -31,6 +116,11 @@ pub enum Error {
     PathIsntFile(PathBuf),
     PathIsntDir(PathBuf),
     InvalidEnv,
+    UnknownAddressMode(String),
+    UnknownRegisterNames(String),
+    InvalidBytes(String),
+    UnknownTraceAction(String),
+    InvalidPid(String),
     Debugger(crate::debug::Error),
 }
 
@@ -52,6 +142,21 @@ impl fmt::Display for Error {
                 f.write_fmt(format_args!("Path {path:?} isn't a directory."))
             }
             Self::InvalidEnv => f.write_str("Invalid environmental variable pair."),
+            Self::UnknownAddressMode(mode) => f.write_fmt(format_args!(
+                "Address mode '{mode}' is unknown, expected 'va', 'rva' or 'section'."
+            )),
+            Self::UnknownRegisterNames(names) => f.write_fmt(format_args!(
+                "Register names '{names}' are unknown, expected 'abi' or 'arch'."
+            )),
+            Self::InvalidBytes(bytes) => f.write_fmt(format_args!(
+                "Bytes '{bytes}' are invalid, expected pairs of hex digits like '90 90'."
+            )),
+            Self::UnknownTraceAction(action) => f.write_fmt(format_args!(
+                "Trace action '{action}' is unknown, expected 'save', 'load' or 'clear'."
+            )),
+            Self::InvalidPid(pid) => {
+                f.write_fmt(format_args!("Process id '{pid}' is invalid, expected a number."))
+            }
             Self::Debugger(err) => err.fmt(f),
         }
     }
@@ -89,13 +194,46 @@ fn possible_command(unknown: &str) -> Option<&str> {
         "quit",
         "run",
         "goto",
+        "rename",
+        "comment",
+        "bookmark",
+        "patch",
+        "struct",
+        "enum",
+        "apply",
+        "back",
+        "forward",
+        "address",
+        "registers",
+        "save",
+        "export",
+        "import",
+        "script",
+        "plugins",
         "set",
+        "connect",
+        "launch",
+        "attach",
+        "detach",
         "break",
+        "condition",
+        "watch",
+        "rwatch",
+        "awatch",
         "delete",
         "stop",
         "continue",
+        "step",
+        "sync",
+        "memory",
+        "write",
         "clear",
         "trace",
+        "emulate",
+        "timings",
+        "overlay",
+        "carve",
+        "crypto",
         "follow-children",
         "help",
     ];
@@ -312,9 +450,45 @@ impl<'src> Context<'src> {
         Ok(format!("{var}={val}"))
     }
 
+    /// Parse hex encoded bytes, optionally separated by whitespace.
+    fn parse_bytes(&mut self) -> Result<Vec<u8>, Error> {
+        let s = self.parse_arg("bytes")?;
+        let digits: Vec<u8> = s.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+
+        if !digits.len().is_multiple_of(2) {
+            return Err(Error::InvalidBytes(s.to_string()));
+        }
+
+        digits
+            .chunks(2)
+            .map(|pair| {
+                std::str::from_utf8(pair)
+                    .ok()
+                    .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                    .ok_or_else(|| Error::InvalidBytes(s.to_string()))
+            })
+            .collect()
+    }
+
     fn parse_debug_expr(&mut self) -> Result<usize, Error> {
         let offset = self.offset;
         let s = self.parse_arg("expr")?;
+        self.eval_debug_expr(offset, s)
+    }
+
+    /// Parse an expression that can't contain any whitespace as it's followed by other arguments.
+    fn parse_debug_expr_word(&mut self) -> Result<usize, Error> {
+        self.skip_whitespace();
+        let offset = self.offset;
+        let s = self.parse_till_whitespace();
+        if s.is_empty() {
+            return Err(Error::Missing("expr"));
+        }
+
+        self.eval_debug_expr(offset, s)
+    }
+
+    fn eval_debug_expr(&mut self, offset: usize, s: &str) -> Result<usize, Error> {
         let expr = CompleteExpr::parse(s).map_err(Error::Debugger)?;
 
         let err = match expr.eval(self.index) {
@@ -347,6 +521,115 @@ impl<'src> Context<'src> {
             "cd" => Command::ChangeDir(self.parse_dir_path()?),
             "quit" | "q" => Command::Quit,
             "goto" | "g" => Command::Goto(self.parse_debug_expr()?),
+            "rename" => {
+                let addr = self.parse_debug_expr_word()?;
+                Command::Rename(addr, self.parse_arg("name")?.to_string())
+            }
+            "comment" => {
+                let addr = self.parse_debug_expr_word()?;
+                // an empty comment removes the existing comment
+                Command::Comment(addr, self.src().trim().to_string())
+            }
+            "bookmark" => {
+                let addr = self.parse_debug_expr_word()?;
+                Command::Bookmark(addr, self.src().trim().to_string())
+            }
+            "patch" => {
+                let addr = self.parse_debug_expr_word()?;
+                Command::Patch(addr, self.parse_bytes()?)
+            }
+            "struct" => match self.src().trim() {
+                "" => Command::Structs,
+                def => Command::Struct(format!("struct {def}")),
+            },
+            "enum" => match self.src().trim() {
+                "" => Command::Enums,
+                def => Command::Enum(format!("enum {def}")),
+            },
+            "apply" => {
+                let addr = self.parse_debug_expr_word()?;
+                // an empty name stops showing the structure
+                Command::Apply(addr, self.src().trim().to_string())
+            }
+            "back" => Command::Back,
+            "forward" => Command::Forward,
+            "address" => Command::AddressMode(match self.parse_arg("mode")? {
+                "va" => AddressMode::Virtual,
+                "rva" => AddressMode::Relative,
+                "section" => AddressMode::Section,
+                mode => return Err(Error::UnknownAddressMode(mode.to_string())),
+            }),
+            "registers" => Command::RegisterNames(match self.parse_arg("names")? {
+                "abi" => RegisterNames::Abi,
+                "arch" => RegisterNames::Architectural,
+                names => return Err(Error::UnknownRegisterNames(names.to_string())),
+            }),
+            "save" => Command::Save,
+            "export" => {
+                let path = expand_homedir(PathBuf::from(self.parse_arg("path")?));
+                match path.extension().and_then(|ext| ext.to_str()) {
+                    Some("map" | "csv") => Command::ExportSymbols(path),
+                    _ => Command::Export(path),
+                }
+            }
+            "import" => Command::Import(self.parse_file_path()?),
+            "script" => Command::Script(self.parse_file_path()?),
+            "plugins" => Command::Plugins,
+            "connect" => Command::Connect(self.parse_arg("address")?.to_string()),
+            "launch" => Command::Launch(self.parse_file_path()?),
+            "attach" => {
+                let pid = self.parse_arg("pid")?;
+                Command::Attach(pid.parse().map_err(|_| Error::InvalidPid(pid.to_string()))?)
+            }
+            "detach" => Command::Detach,
+            "continue" | "c" => Command::Continue,
+            "step" | "s" => Command::Step,
+            "stop" => Command::Stop,
+            "break" | "b" => Command::Break(self.parse_debug_expr()?),
+            "condition" | "cond" => {
+                let addr = self.parse_debug_expr_word()?;
+                // an empty condition makes the breakpoint unconditional
+                Command::Condition(addr, self.src().trim().to_string())
+            }
+            "watch" => Command::Watch(self.parse_debug_expr()?),
+            "rwatch" => Command::ReadWatch(self.parse_debug_expr()?),
+            "awatch" => Command::AccessWatch(self.parse_debug_expr()?),
+            "sync" => Command::Sync,
+            "memory" | "m" => Command::Memory(self.parse_debug_expr()?),
+            "write" => {
+                let addr = self.parse_debug_expr_word()?;
+                Command::Write(addr, self.parse_bytes()?)
+            }
+            "trace" if self.src().trim().is_empty() => Command::Trace,
+            "trace" => match self.parse_next("action")? {
+                "save" => {
+                    Command::SaveTrace(expand_homedir(PathBuf::from(self.parse_arg("path")?)))
+                }
+                "load" => Command::LoadTrace(self.parse_file_path()?),
+                "clear" => Command::ClearTrace,
+                action => return Err(Error::UnknownTraceAction(action.to_string())),
+            },
+            "emulate" => {
+                let addr = self.parse_debug_expr_word()?;
+                let mut args = Vec::new();
+                while !self.src().trim().is_empty() {
+                    args.push(self.parse_debug_expr_word()? as u64);
+                }
+                Command::Emulate(addr, args)
+            }
+            "timings" => match self.src().trim() {
+                "" => Command::Timings(None),
+                path => Command::Timings(Some(expand_homedir(PathBuf::from(path)))),
+            },
+            "overlay" => match self.src().trim() {
+                "" => Command::Overlay(None),
+                path => Command::Overlay(Some(expand_homedir(PathBuf::from(path)))),
+            },
+            "carve" => match self.src().trim() {
+                "" => Command::Carve(None),
+                dir => Command::Carve(Some(expand_homedir(PathBuf::from(dir)))),
+            },
+            "crypto" => Command::Crypto,
             "clear" => Command::Clear,
             "help" | "?" => Command::Help,
             name => return Err(Error::UnknownName(name.to_string())),
@@ -423,6 +706,92 @@ mod tests {
         );
     }
 
+    #[test]
+    fn annotations() {
+        eval_eq!(["abc::f"; 0x1234], "rename abc::f main", Command::Rename(0x1234, "main".into()));
+        eval_eq!(
+            "comment 0x10   checks  argc ",
+            Command::Comment(0x10, "checks  argc".into())
+        );
+        eval_eq!("comment 0x10", Command::Comment(0x10, String::new()));
+        eval_eq!(
+            ["abc::f"; 0x1234],
+            "bookmark abc::f entry point",
+            Command::Bookmark(0x1234, "entry point".into())
+        );
+    }
+
+    #[test]
+    fn patch() {
+        eval_eq!("patch 0x10 90 90", Command::Patch(0x10, vec![0x90, 0x90]));
+        eval_eq!(
+            ["abc::f"; 0x1234],
+            "patch abc::f c3",
+            Command::Patch(0x1234, vec![0xc3])
+        );
+        eval_eq!("patch 0x10 0F1f00", Command::Patch(0x10, vec![15, 31, 0]));
+    }
+
+    #[test]
+    fn structs() {
+        eval_eq!("struct", Command::Structs);
+        eval_eq!(
+            "struct point { int x; int y; };",
+            Command::Struct("struct point { int x; int y; };".into())
+        );
+        eval_eq!(
+            ["abc::f"; 0x1234],
+            "apply abc::f point",
+            Command::Apply(0x1234, "point".into())
+        );
+        eval_eq!("apply 0x10", Command::Apply(0x10, String::new()));
+        eval_eq!("enum", Command::Enums);
+        eval_eq!(
+            "enum prot { READ = 1, WRITE = 2 };",
+            Command::Enum("enum prot { READ = 1, WRITE = 2 };".into())
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn patch_invalid_bytes() {
+        eval_eq!("patch 0x10 909", Command::Patch(0x10, Vec::new()));
+    }
+
+    #[test]
+    #[should_panic]
+    fn rename_missing_name() {
+        eval_eq!("rename 0x10", Command::Rename(0x10, String::new()));
+    }
+
+    #[test]
+    fn address_mode() {
+        eval_eq!("address va", Command::AddressMode(AddressMode::Virtual));
+        eval_eq!("address  rva ", Command::AddressMode(AddressMode::Relative));
+        eval_eq!(
+            "address section",
+            Command::AddressMode(AddressMode::Section)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn address_mode_invalid() {
+        eval_eq!(
+            "address physical",
+            Command::AddressMode(AddressMode::Virtual)
+        );
+    }
+
+    #[test]
+    fn register_names() {
+        eval_eq!("registers abi", Command::RegisterNames(RegisterNames::Abi));
+        eval_eq!(
+            "registers  arch ",
+            Command::RegisterNames(RegisterNames::Architectural)
+        );
+    }
+
     #[test]
     fn change_dir() {
         let home = expand_homedir(PathBuf::from("~"));
@@ -431,6 +800,147 @@ mod tests {
         eval_eq!("cd . ", Command::ChangeDir(PathBuf::from(".")));
     }
 
+    #[test]
+    fn export() {
+        let home = expand_homedir(PathBuf::from("~"));
+        eval_eq!("export out.html", Command::Export("out.html".into()));
+        eval_eq!("export ~/a.html ", Command::Export(home.join("a.html")));
+        eval_eq!("export a.map", Command::ExportSymbols("a.map".into()));
+        eval_eq!("export ~/a.csv", Command::ExportSymbols(home.join("a.csv")));
+    }
+
+    #[test]
+    #[should_panic]
+    fn export_missing_path() {
+        eval_eq!("export", Command::Export(PathBuf::new()));
+    }
+
+    #[test]
+    fn import() {
+        eval_eq!("import Cargo.toml", Command::Import("Cargo.toml".into()));
+    }
+
+    #[test]
+    #[should_panic]
+    fn import_missing_file() {
+        eval_eq!("import missing.map", Command::Import("missing.map".into()));
+    }
+
+    #[test]
+    fn script() {
+        eval_eq!(
+            "script Cargo.toml",
+            Command::Script(PathBuf::from("Cargo.toml"))
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn script_missing() {
+        eval_eq!(
+            "script missing.rhai",
+            Command::Script(PathBuf::from("missing.rhai"))
+        );
+    }
+
+    #[test]
+    fn debugging() {
+        eval_eq!(
+            "connect localhost:1234 ",
+            Command::Connect("localhost:1234".into())
+        );
+        eval_eq!("launch Cargo.toml", Command::Launch("Cargo.toml".into()));
+        eval_eq!("attach 1234 ", Command::Attach(1234));
+        eval_eq!(["abc::f"; 0x1234], "break abc::f", Command::Break(0x1234));
+        eval_eq!("b 0x10 + 4", Command::Break(0x14));
+        eval_eq!("c", Command::Continue);
+        eval_eq!("step", Command::Step);
+        eval_eq!("m 0x7ffc0000", Command::Memory(0x7ffc0000));
+        eval_eq!("write 0x10 de ad", Command::Write(0x10, vec![0xde, 0xad]));
+    }
+
+    #[test]
+    fn conditions() {
+        eval_eq!(
+            "condition 0x10 rax == 1 && [rsp] != 0",
+            Command::Condition(0x10, "rax == 1 && [rsp] != 0".into())
+        );
+        eval_eq!("cond 0x10 ", Command::Condition(0x10, String::new()));
+        eval_eq!("watch 0x20 + 8", Command::Watch(0x28));
+        eval_eq!("rwatch 0x20", Command::ReadWatch(0x20));
+        eval_eq!("awatch 0x20", Command::AccessWatch(0x20));
+    }
+
+    #[test]
+    fn trace() {
+        let home = expand_homedir(PathBuf::from("~"));
+        eval_eq!("trace ", Command::Trace);
+        eval_eq!(
+            "trace save ~/a.drcov",
+            Command::SaveTrace(home.join("a.drcov"))
+        );
+        eval_eq!(
+            "trace load Cargo.toml",
+            Command::LoadTrace("Cargo.toml".into())
+        );
+        eval_eq!("trace clear", Command::ClearTrace);
+    }
+
+    #[test]
+    fn timings() {
+        let home = expand_homedir(PathBuf::from("~"));
+        eval_eq!("timings", Command::Timings(None));
+        eval_eq!(
+            "timings ~/timings.json ",
+            Command::Timings(Some(home.join("timings.json")))
+        );
+    }
+
+    #[test]
+    fn overlay() {
+        let home = expand_homedir(PathBuf::from("~"));
+        eval_eq!("overlay", Command::Overlay(None));
+        eval_eq!(
+            "overlay ~/payload.zip",
+            Command::Overlay(Some(home.join("payload.zip")))
+        );
+    }
+
+    #[test]
+    fn carve() {
+        let home = expand_homedir(PathBuf::from("~"));
+        eval_eq!("carve", Command::Carve(None));
+        eval_eq!("carve ~/carved ", Command::Carve(Some(home.join("carved"))));
+    }
+
+    #[test]
+    fn emulate() {
+        eval_eq!("emulate 0x1000", Command::Emulate(0x1000, Vec::new()));
+        eval_eq!(
+            ["abc::f"; 0x1234],
+            "emulate abc::f 0x10000 5 ",
+            Command::Emulate(0x1234, vec![0x10000, 5])
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn trace_unknown_action() {
+        eval_eq!("trace replay", Command::Trace);
+    }
+
+    #[test]
+    #[should_panic]
+    fn connect_missing_address() {
+        eval_eq!("connect", Command::Connect(String::new()));
+    }
+
+    #[test]
+    #[should_panic]
+    fn attach_invalid_pid() {
+        eval_eq!("attach firefox", Command::Attach(0));
+    }
+
     #[test]
     #[should_panic]
     fn change_dir_invalid() {
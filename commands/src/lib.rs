@@ -7,3 +7,30 @@ pub use gui::{Command, Error as CommandError, HELP as CMD_HELP};
 use once_cell::sync::Lazy;
 
 pub static ARGS: Lazy<cli::Cli> = Lazy::new(cli::Cli::parse);
+
+/// Feature that can be disabled in the config, or all at once by launching with `--safe-mode`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feature {
+    Plugins,
+    Scripting,
+    /// Analysis passes that run automatically after a binary is loaded.
+    Analysis,
+    /// Symbol servers, debuginfod and anything else that talks to the network.
+    Network,
+}
+
+impl Feature {
+    pub fn enabled(self) -> bool {
+        if ARGS.safe_mode {
+            return false;
+        }
+
+        let features = &config::CONFIG.features;
+        match self {
+            Self::Plugins => features.plugins,
+            Self::Scripting => features.scripting,
+            Self::Analysis => features.analysis,
+            Self::Network => features.network,
+        }
+    }
+}
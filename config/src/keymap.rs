@@ -0,0 +1,361 @@
+//! Shortcuts bound to actions of the GUI, loaded from `keymap.yaml` in bite's data directory.
+//!
+//! Shortcuts are written as keys joined by `+` with their modifiers, e.g. `Ctrl+Shift+P`, where
+//! `Ctrl` stands for `Cmd` on macOS. Several of them separated by spaces have to be pressed one
+//! after another, e.g. `G G`.
+
+use egui::{Key, Modifiers};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Shortcuts of every preset.
+const DEFAULT: &[(&str, &str)] = &[
+    ("open", "Ctrl+O"),
+    ("open_in_new_tab", "Ctrl+Shift+O"),
+    ("save", "Ctrl+S"),
+    ("command_palette", "Ctrl+Shift+P"),
+    ("next_tab", "Ctrl+Tab"),
+    ("back", "Alt+Left"),
+    ("forward", "Alt+Right"),
+    ("deselect", "Escape"),
+    ("select_up", "Shift+Up"),
+    ("select_down", "Shift+Down"),
+    ("toggle_fold", "Space"),
+    ("page_up", "PageUp"),
+    ("page_down", "PageDown"),
+    ("code_start", "Home"),
+    ("code_end", "End"),
+    ("entrypoint", "Ctrl+Home"),
+    ("continue", "F5"),
+    ("step", "F10"),
+    ("toggle_breakpoint", "F9"),
+];
+
+/// Shortcuts added by the vim preset.
+const VIM: &[(&str, &str)] = &[
+    ("scroll_down", "J"),
+    ("scroll_up", "K"),
+    ("top", "G G"),
+    ("bottom", "Shift+G"),
+    ("goto", "/"),
+];
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Preset {
+    #[default]
+    Default,
+    /// Navigating the listing with j/k, gg/G and / to search.
+    Vim,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 2] = [Preset::Default, Preset::Vim];
+
+    fn bindings(self) -> impl Iterator<Item = &'static (&'static str, &'static str)> {
+        let vim: &[_] = match self {
+            Preset::Default => &[],
+            Preset::Vim => VIM,
+        };
+
+        DEFAULT.iter().chain(vim)
+    }
+}
+
+impl fmt::Display for Preset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Preset::Default => f.write_str("Default"),
+            Preset::Vim => f.write_str("Vim"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Chord {
+    pub modifiers: Modifiers,
+    pub key: Key,
+}
+
+impl Chord {
+    /// Whether the chord produces text when typed, e.g. `J` but not `Ctrl+J` or `F5`.
+    pub fn is_text(&self) -> bool {
+        let modifiers = self.modifiers;
+        let modified = modifiers.ctrl || modifiers.alt || modifiers.command || modifiers.mac_cmd;
+        let arrow = matches!(
+            self.key,
+            Key::ArrowDown | Key::ArrowLeft | Key::ArrowRight | Key::ArrowUp
+        );
+
+        // besides space, only keys that are a single character produce text
+        !modified
+            && (self.key == Key::Space || !arrow && self.key.symbol_or_name().chars().count() == 1)
+    }
+
+    /// Text typed by the chord, as far as it can be told without knowing the keyboard layout.
+    pub fn text(&self) -> Option<String> {
+        if !self.is_text() {
+            return None;
+        }
+
+        Some(match (self.key, self.modifiers.shift) {
+            (Key::Space, _) => " ".to_string(),
+            (key, true) => key.symbol_or_name().to_string(),
+            (key, false) => key.symbol_or_name().to_lowercase(),
+        })
+    }
+
+    /// Whether pressing some keys matches the chord.
+    fn matches(&self, pressed: &Chord) -> bool {
+        self.key == pressed.key && pressed.modifiers.matches_exact(self.modifiers)
+    }
+}
+
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.command || self.modifiers.ctrl || self.modifiers.mac_cmd {
+            f.write_str("Ctrl+")?;
+        }
+
+        if self.modifiers.alt {
+            f.write_str("Alt+")?;
+        }
+
+        if self.modifiers.shift {
+            f.write_str("Shift+")?;
+        }
+
+        f.write_str(self.key.name())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shortcut(pub Vec<Chord>);
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct InvalidShortcut(pub String);
+
+impl fmt::Display for InvalidShortcut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_fmt(format_args!(
+            "Shortcut '{}' is invalid, expected keys like 'Ctrl+Shift+P' or 'G G'.",
+            self.0
+        ))
+    }
+}
+
+impl FromStr for Shortcut {
+    type Err = InvalidShortcut;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidShortcut(s.to_string());
+        let mut chords = Vec::new();
+
+        for chord in s.split_whitespace() {
+            let mut modifiers = Modifiers::NONE;
+            let mut parts: Vec<&str> = chord.split('+').collect();
+
+            // `+` itself can be a key, which leaves an empty part
+            let key = match parts.pop() {
+                Some("") if chord.ends_with("++") || chord == "+" => "+",
+                Some(key) => key,
+                None => return Err(invalid()),
+            };
+
+            for modifier in parts.iter().filter(|part| !part.is_empty()) {
+                match modifier.to_ascii_lowercase().as_str() {
+                    "ctrl" | "cmd" | "command" => modifiers = modifiers | Modifiers::COMMAND,
+                    "alt" | "option" => modifiers = modifiers | Modifiers::ALT,
+                    "shift" => modifiers = modifiers | Modifiers::SHIFT,
+                    _ => return Err(invalid()),
+                }
+            }
+
+            let key = Key::from_name(key).ok_or_else(invalid)?;
+            chords.push(Chord { modifiers, key });
+        }
+
+        if chords.is_empty() {
+            return Err(invalid());
+        }
+
+        Ok(Self(chords))
+    }
+}
+
+impl fmt::Display for Shortcut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, chord) in self.0.iter().enumerate() {
+            if idx != 0 {
+                f.write_str(" ")?;
+            }
+
+            chord.fmt(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Keymap {
+    #[serde(default)]
+    pub preset: Preset,
+
+    /// Shortcuts that differ from the preset by action, an empty one unbinds the action.
+    #[serde(default)]
+    pub overrides: BTreeMap<String, String>,
+}
+
+fn path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("bite").join("keymap.yaml"))
+}
+
+impl Keymap {
+    pub fn load() -> Self {
+        let raw = match path().map(std::fs::read_to_string) {
+            Some(Ok(raw)) => raw,
+            _ => return Self::default(),
+        };
+
+        match serde_yaml::from_str::<Self>(&raw) {
+            Ok(keymap) => {
+                for shortcut in keymap.overrides.values().filter(|s| !s.trim().is_empty()) {
+                    if let Err(err) = Shortcut::from_str(shortcut) {
+                        log::report!(log::Level::Warn, "{err}");
+                    }
+                }
+
+                keymap
+            }
+            Err(err) => {
+                log::report!(log::Level::Warn, "Failed to parse keymap: {err}.");
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        let path = path().ok_or(std::io::ErrorKind::NotFound)?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        let raw = serde_yaml::to_string(self).map_err(std::io::Error::other)?;
+        std::fs::write(path, raw)
+    }
+
+    /// Shortcuts of all actions that are bound, by action.
+    pub fn bindings(&self) -> BTreeMap<String, Shortcut> {
+        let mut bindings = BTreeMap::new();
+
+        for (action, shortcut) in self.preset.bindings() {
+            if let Ok(shortcut) = shortcut.parse() {
+                bindings.insert(action.to_string(), shortcut);
+            }
+        }
+
+        for (action, shortcut) in self.overrides.iter() {
+            if shortcut.trim().is_empty() {
+                bindings.remove(action);
+                continue;
+            }
+
+            // invalid shortcuts are reported when loading the keymap
+            if let Ok(shortcut) = shortcut.parse() {
+                bindings.insert(action.clone(), shortcut);
+            }
+        }
+
+        bindings
+    }
+
+    /// Bind an action to a shortcut, or unbind it if the shortcut is empty.
+    pub fn bind(&mut self, action: &str, shortcut: &str) -> Result<(), InvalidShortcut> {
+        let shortcut = match shortcut.trim() {
+            "" => String::new(),
+            shortcut => Shortcut::from_str(shortcut)?.to_string(),
+        };
+
+        let preset = self.preset.bindings().find(|(name, _)| *name == action);
+        let preset = preset.and_then(|(_, shortcut)| Shortcut::from_str(shortcut).ok());
+
+        // keep only what differs from the preset, so changes to presets still apply
+        if preset.map(|preset| preset.to_string()).unwrap_or_default() == shortcut {
+            self.overrides.remove(action);
+        } else {
+            self.overrides.insert(action.to_string(), shortcut);
+        }
+
+        Ok(())
+    }
+}
+
+/// Follows the keys that are pressed, to match shortcuts made of several chords.
+#[derive(Debug, Default)]
+pub struct Matcher {
+    pending: Vec<Chord>,
+    /// Chords that started a shortcut which the last chord pressed didn't continue.
+    abandoned: Vec<Chord>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Match<'a> {
+    /// No shortcut starts with the keys pressed.
+    None,
+    /// The keys pressed are the start of a shortcut.
+    Pending,
+    Action(&'a str),
+}
+
+impl Matcher {
+    pub fn press<'a>(
+        &mut self,
+        bindings: &'a BTreeMap<String, Shortcut>,
+        chord: Chord,
+    ) -> Match<'a> {
+        self.abandoned.clear();
+        self.pending.push(chord);
+        if let Some(matched) = self.advance(bindings) {
+            return matched;
+        }
+
+        self.abandoned = std::mem::take(&mut self.pending);
+        self.abandoned.pop();
+        if self.abandoned.is_empty() {
+            return Match::None;
+        }
+
+        // the chord might start another shortcut instead of continuing this one
+        self.pending.push(chord);
+        self.advance(bindings).unwrap_or_else(|| {
+            self.pending.clear();
+            Match::None
+        })
+    }
+
+    /// Chords that were pressed before the last one, without completing a shortcut.
+    pub fn abandoned(&self) -> &[Chord] {
+        &self.abandoned
+    }
+
+    fn advance<'a>(&mut self, bindings: &'a BTreeMap<String, Shortcut>) -> Option<Match<'a>> {
+        let mut pending = false;
+        for (action, shortcut) in bindings {
+            let starts_with = shortcut.0.len() >= self.pending.len()
+                && shortcut.0.iter().zip(self.pending.iter()).all(|(c, p)| c.matches(p));
+
+            if starts_with && shortcut.0.len() == self.pending.len() {
+                self.pending.clear();
+                return Some(Match::Action(action));
+            }
+
+            pending |= starts_with;
+        }
+
+        pending.then_some(Match::Pending)
+    }
+}
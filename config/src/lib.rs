@@ -1,20 +1,96 @@
+pub mod keymap;
+pub mod settings;
+pub mod theme;
+
 use once_cell::sync::Lazy;
 
 pub static CONFIG: Lazy<Config> = Lazy::new(Config::parse);
 
 use egui::Color32;
 use serde::de::{self, Deserializer, Visitor};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
 #[derive(Debug, Deserialize)]
 pub struct Config {
     #[serde(default = "defaults::colors")]
     pub colors: Colors,
+    #[serde(default = "defaults::listing")]
+    pub listing: Listing,
+    #[serde(default = "defaults::features")]
+    pub features: Features,
+}
+
+/// Features that can be turned off, e.g. when analyzing untrusted samples.
+#[derive(Debug, Deserialize)]
+pub struct Features {
+    /// Loading plugins from bite's data directory.
+    #[serde(default = "defaults::enabled")]
+    pub plugins: bool,
+    /// Running user scripts.
+    #[serde(default = "defaults::enabled")]
+    pub scripting: bool,
+    /// Analysis passes that run automatically after a binary is loaded.
+    #[serde(default = "defaults::enabled")]
+    pub analysis: bool,
+    /// Anything that talks to the network, such as symbol servers and debuginfod.
+    #[serde(default = "defaults::enabled")]
+    pub network: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Listing {
+    /// Columns that are shown, in the order they're displayed.
+    #[serde(default = "defaults::columns")]
+    pub columns: Vec<Column>,
+}
+
+/// Column of the listing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Column {
+    Address,
+    /// Offset of instructions into the function containing them.
+    Offset,
+    /// Encoded bytes of instructions.
+    Bytes,
+    /// Gutter containing labels and section markers.
+    Label,
+    /// Instructions and data.
+    Instruction,
+    /// User comments.
+    Comment,
+}
+
+impl Column {
+    pub const ALL: [Column; 6] = [
+        Column::Label,
+        Column::Address,
+        Column::Offset,
+        Column::Bytes,
+        Column::Instruction,
+        Column::Comment,
+    ];
+}
+
+impl fmt::Display for Column {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Column::Address => "Address",
+            Column::Offset => "Offset",
+            Column::Bytes => "Bytes",
+            Column::Label => "Label",
+            Column::Instruction => "Instruction",
+            Column::Comment => "Comment",
+        })
+    }
 }
 
 #[derive(Debug, Deserialize)]
 pub struct Colors {
+    /// Whether backgrounds are dark, which decides the style of widgets.
+    #[serde(default = "defaults::enabled")]
+    pub dark: bool,
     #[serde(default = "defaults::src_colors")]
     pub src: SourceColors,
     #[serde(default = "defaults::asm_colors")]
@@ -33,6 +109,21 @@ pub struct Colors {
     pub bg_primary: Color32,
     #[serde(default = "defaults::bg_secondary", deserialize_with = "color32")]
     pub bg_secondary: Color32,
+    /// Text without any highlighting.
+    #[serde(default = "defaults::text", deserialize_with = "color32")]
+    pub text: Color32,
+    /// Text of widgets that aren't active.
+    #[serde(default = "defaults::text_inactive", deserialize_with = "color32")]
+    pub text_inactive: Color32,
+    /// Hints, such as autocompletions and ordinals.
+    #[serde(default = "defaults::hint", deserialize_with = "color32")]
+    pub hint: Color32,
+    /// Frame around the terminal.
+    #[serde(default = "defaults::frame", deserialize_with = "color32")]
+    pub frame: Color32,
+    /// Anything that's enabled or went well, such as mitigations.
+    #[serde(default = "defaults::success", deserialize_with = "color32")]
+    pub success: Color32,
 }
 
 #[derive(Debug, Deserialize)]
@@ -138,7 +229,7 @@ impl Colors {
     }
 }
 
-/// Default color values when one is missing in the config.yaml's colors field.
+/// Default values when one is missing in the config.yaml.
 /// Important to note is that any update to these defaults should also be reflected in the
 /// config.yaml.
 mod defaults {
@@ -156,6 +247,27 @@ mod defaults {
     pub fn asm_colors() -> super::AsmColors {
         serde_yaml::from_str("").unwrap()
     }
+    pub fn listing() -> super::Listing {
+        serde_yaml::from_str("").unwrap()
+    }
+    pub fn features() -> super::Features {
+        serde_yaml::from_str("").unwrap()
+    }
+
+    pub fn enabled() -> bool {
+        true
+    }
+
+    pub fn columns() -> Vec<super::Column> {
+        use super::Column;
+        vec![
+            Column::Label,
+            Column::Address,
+            Column::Bytes,
+            Column::Instruction,
+            Column::Comment,
+        ]
+    }
 
     pub fn anything() -> Color32 {
         Color32::from_rgb(0xc8, 0xc8, 0xc8)
@@ -250,29 +362,58 @@ mod defaults {
     pub fn bg_secondary() -> Color32 {
         Color32::from_rgb(0x2d, 0x2d, 0x2d)
     }
+    pub fn text() -> Color32 {
+        Color32::from_rgb(0xff, 0xff, 0xff)
+    }
+    pub fn text_inactive() -> Color32 {
+        Color32::from_rgb(0xaa, 0xaa, 0xaa)
+    }
+    pub fn hint() -> Color32 {
+        Color32::from_rgb(0x60, 0x60, 0x60)
+    }
+    pub fn frame() -> Color32 {
+        Color32::from_rgb(0x35, 0x35, 0x35)
+    }
+    pub fn success() -> Color32 {
+        Color32::from_rgb(0x02, 0xed, 0x6e)
+    }
 }
 
 impl Config {
     pub fn parse() -> Self {
-        let path = match dirs::data_dir() {
+        let raw = match dirs::data_dir() {
             Some(mut dir) => {
                 dir.push("bite");
                 dir.push("config.yaml");
-                dir
+                std::fs::read_to_string(dir).unwrap_or_default()
+            },
+            None => {
+                log::report!(log::Level::Warn, "No data directory is set, using default config.");
+                String::new()
             },
-            None => log::error!("You must have a data directory set."),
         };
 
-        let raw = std::fs::read_to_string(path).unwrap_or_default();
-        match serde_yaml::from_str(&raw) {
+        let mut config: Self = match serde_yaml::from_str(&raw) {
             Ok(parsed) => parsed,
             Err(err) => {
-                log::warning!("Failed to parse config.\nError: {err}.");
+                log::report!(log::Level::Warn, "Failed to parse config: {err}.");
 
                 // parse everything as default
                 defaults::config()
             }
+        };
+
+        // colors set in the config take precedence over the theme chosen in the settings
+        let value: Option<serde_yaml::Value> = serde_yaml::from_str(&raw).ok();
+        if value.as_ref().and_then(|value| value.get("colors")).is_none() {
+            let name = settings::theme_name();
+            match theme::load(&name) {
+                Ok(colors) => config.colors = colors,
+                Err(err) => log::report!(log::Level::Warn, "Failed to load theme '{name}': {err}."),
+            }
         }
+
+        config
     }
 }
 
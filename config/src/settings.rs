@@ -0,0 +1,114 @@
+//! Preferences changed from the settings dialog, stored in `settings.yaml` in bite's directory
+//! under the platform's config directory.
+//!
+//! Unlike `config.yaml`, which is only ever edited by hand, these are written by bite itself.
+
+use crate::{Column, CONFIG};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+pub static SETTINGS: Lazy<RwLock<Settings>> = Lazy::new(|| RwLock::new(Settings::load()));
+
+/// Size of the monospace font if there are no settings.
+pub const DEFAULT_FONT_SIZE: f32 = 14.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Size of the monospace font used by the listing and most panes.
+    pub font_size: f32,
+    /// Name of the color theme, either a preset or a custom theme in bite's `themes` directory.
+    pub theme: String,
+    /// Columns of the listing that are shown, in the order they're displayed.
+    pub columns: Vec<Column>,
+    /// Number of bytes shown per instruction before the rest is cut off, by default as many as
+    /// the longest instruction of the architecture has.
+    pub max_bytes: Option<usize>,
+    /// Name functions whose start is known without a symbol, e.g. through `LC_FUNCTION_STARTS`.
+    pub discover_functions: bool,
+    /// Language used to highlight source files whose extension doesn't tell.
+    pub default_syntax: Syntax,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            font_size: DEFAULT_FONT_SIZE,
+            theme: crate::theme::DEFAULT.to_string(),
+            columns: CONFIG.listing.columns.clone(),
+            max_bytes: None,
+            discover_functions: true,
+            default_syntax: Syntax::None,
+        }
+    }
+}
+
+/// Language of source code.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Syntax {
+    /// Plain text, without highlighting.
+    #[default]
+    None,
+    Rust,
+    C,
+    Cpp,
+}
+
+impl Syntax {
+    pub const ALL: [Syntax; 4] = [Syntax::None, Syntax::Rust, Syntax::C, Syntax::Cpp];
+}
+
+impl fmt::Display for Syntax {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Syntax::None => "None",
+            Syntax::Rust => "Rust",
+            Syntax::C => "C",
+            Syntax::Cpp => "C++",
+        })
+    }
+}
+
+fn path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("bite").join("settings.yaml"))
+}
+
+/// Name of the theme in the settings, read without the rest of them as their defaults depend on
+/// the config, which in turn depends on the theme.
+pub(crate) fn theme_name() -> String {
+    let raw = path().and_then(|path| std::fs::read_to_string(path).ok()).unwrap_or_default();
+    let value: Option<serde_yaml::Value> = serde_yaml::from_str(&raw).ok();
+    let name = value.as_ref().and_then(|value| value.get("theme")).and_then(|v| v.as_str());
+    name.unwrap_or(crate::theme::DEFAULT).to_string()
+}
+
+impl Settings {
+    pub fn load() -> Self {
+        let raw = match path().map(std::fs::read_to_string) {
+            Some(Ok(raw)) => raw,
+            _ => return Self::default(),
+        };
+
+        match serde_yaml::from_str(&raw) {
+            Ok(settings) => settings,
+            Err(err) => {
+                log::report!(log::Level::Warn, "Failed to parse settings: {err}.");
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        let path = path().ok_or(std::io::ErrorKind::NotFound)?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        let raw = serde_yaml::to_string(self).map_err(std::io::Error::other)?;
+        std::fs::write(path, raw)
+    }
+}
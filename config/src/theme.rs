@@ -0,0 +1,196 @@
+//! Color themes, mapping every kind of token and part of the interface to a color.
+//!
+//! Besides the presets, custom themes are TOML files in bite's `themes` directory named after the
+//! theme, e.g. `themes/nord.toml`. They have the same keys as the `colors` of the config, with
+//! the source and assembly colors in `[src]` and `[asm]` tables. Colors that are left out are
+//! taken from the dark theme.
+
+use crate::{defaults, AsmColors, Colors, SourceColors};
+use egui::Color32;
+use std::fmt;
+use std::path::PathBuf;
+
+/// Theme used if none is chosen.
+pub const DEFAULT: &str = "dark";
+
+/// Themes that ship with bite.
+pub const PRESETS: [&str; 3] = ["dark", "light", "solarized"];
+
+#[derive(Debug)]
+pub enum Error {
+    /// There's neither a preset nor a custom theme with the name.
+    Missing(String),
+    Io(std::io::Error),
+    Toml(toml::de::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing(name) => f.write_fmt(format_args!("There is no theme named '{name}'.")),
+            Self::Io(err) => f.write_fmt(format_args!("{err}")),
+            Self::Toml(err) => f.write_fmt(format_args!("{err}")),
+        }
+    }
+}
+
+fn dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("bite").join("themes"))
+}
+
+/// Names of the presets followed by those of the custom themes.
+pub fn names() -> Vec<String> {
+    let mut names: Vec<String> = PRESETS.iter().map(ToString::to_string).collect();
+    let mut custom: Vec<String> = dir()
+        .and_then(|dir| std::fs::read_dir(dir).ok())
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != "toml" {
+                return None;
+            }
+
+            Some(path.file_stem()?.to_str()?.to_string())
+        })
+        .filter(|name| !PRESETS.contains(&name.as_str()))
+        .collect();
+
+    custom.sort();
+    names.extend(custom);
+    names
+}
+
+/// Colors of a preset or custom theme.
+pub fn load(name: &str) -> Result<Colors, Error> {
+    match name {
+        "dark" => return Ok(defaults::colors()),
+        "light" => return Ok(light()),
+        "solarized" => return Ok(solarized()),
+        _ => {}
+    }
+
+    let path = match dir() {
+        Some(dir) => dir.join(format!("{name}.toml")),
+        None => return Err(Error::Missing(name.to_string())),
+    };
+
+    if !path.is_file() {
+        return Err(Error::Missing(name.to_string()));
+    }
+
+    parse(&std::fs::read_to_string(path).map_err(Error::Io)?)
+}
+
+/// Parse the colors of a custom theme.
+pub fn parse(raw: &str) -> Result<Colors, Error> {
+    toml::from_str(raw).map_err(Error::Toml)
+}
+
+const fn rgb(hex: u32) -> Color32 {
+    Color32::from_rgb((hex >> 16) as u8, (hex >> 8) as u8, hex as u8)
+}
+
+fn light() -> Colors {
+    Colors {
+        dark: false,
+        src: SourceColors {
+            keyword: rgb(0xc24100),
+            tipe: rgb(0xb36b00),
+            field: rgb(0x1d6fa5),
+            function: rgb(0x1a8f3c),
+            operator: rgb(0xb35f00),
+            string: rgb(0x1a8f3c),
+            variable: rgb(0x9c3b94),
+            constant: rgb(0x6c3fa0),
+            highlight: rgb(0xd35400),
+        },
+        asm: AsmColors {
+            section: rgb(0x0b72a8),
+            opcode: rgb(0x1e1e1e),
+            component: rgb(0xc2185b),
+            register: rgb(0xc2185b),
+            label: rgb(0x0b72a8),
+            segment: rgb(0x1a8f3c),
+            invalid: rgb(0xd32f2f),
+            pointer: rgb(0xd32f2f),
+            expr: rgb(0x707070),
+            immediate: rgb(0x0b72a8),
+            annotation: rgb(0x0b72a8),
+            primitive: rgb(0x2a7f74),
+            string: rgb(0xa86200),
+        },
+        comment: rgb(0x707070),
+        address: rgb(0x9a9a9a),
+        brackets: rgb(0xa0a0a0),
+        bytes: rgb(0x1a8f3c),
+        delimiter: rgb(0x707070),
+        bg_primary: rgb(0xf5f5f5),
+        bg_secondary: rgb(0xebebeb),
+        text: rgb(0x1e1e1e),
+        text_inactive: rgb(0x5a5a5a),
+        hint: rgb(0x9a9a9a),
+        frame: rgb(0xd4d4d4),
+        success: rgb(0x1a8f3c),
+    }
+}
+
+/// Dark variant of Ethan Schoonover's Solarized.
+fn solarized() -> Colors {
+    const BASE03: Color32 = rgb(0x002b36);
+    const BASE02: Color32 = rgb(0x073642);
+    const BASE01: Color32 = rgb(0x586e75);
+    const BASE00: Color32 = rgb(0x657b83);
+    const BASE0: Color32 = rgb(0x839496);
+    const BASE1: Color32 = rgb(0x93a1a1);
+    const YELLOW: Color32 = rgb(0xb58900);
+    const ORANGE: Color32 = rgb(0xcb4b16);
+    const RED: Color32 = rgb(0xdc322f);
+    const MAGENTA: Color32 = rgb(0xd33682);
+    const VIOLET: Color32 = rgb(0x6c71c4);
+    const BLUE: Color32 = rgb(0x268bd2);
+    const CYAN: Color32 = rgb(0x2aa198);
+    const GREEN: Color32 = rgb(0x859900);
+
+    Colors {
+        dark: true,
+        src: SourceColors {
+            keyword: GREEN,
+            tipe: YELLOW,
+            field: BLUE,
+            function: BLUE,
+            operator: GREEN,
+            string: CYAN,
+            variable: VIOLET,
+            constant: MAGENTA,
+            highlight: ORANGE,
+        },
+        asm: AsmColors {
+            section: BLUE,
+            opcode: BASE1,
+            component: MAGENTA,
+            register: MAGENTA,
+            label: BLUE,
+            segment: GREEN,
+            invalid: RED,
+            pointer: RED,
+            expr: BASE00,
+            immediate: CYAN,
+            annotation: BLUE,
+            primitive: CYAN,
+            string: YELLOW,
+        },
+        comment: BASE01,
+        address: BASE01,
+        brackets: BASE01,
+        bytes: GREEN,
+        delimiter: BASE00,
+        bg_primary: BASE03,
+        bg_secondary: BASE02,
+        text: BASE1,
+        text_inactive: BASE0,
+        hint: BASE01,
+        frame: BASE02,
+        success: GREEN,
+    }
+}
@@ -0,0 +1,396 @@
+//! Conditions of breakpoints, which are evaluated over the registers and memory of the target
+//! every time it hits one:
+//!
+//! ```text
+//! rdi == 0x10 && u8[rsi + 4] != 0
+//! ```
+//!
+//! Operators are those of Rust on unsigned 64-bit integers with the same precedence, besides `!`
+//! being a logical not and `~` a bitwise not. `[expr]` reads a pointer from memory, `u8[expr]`
+//! up to `u64[expr]` read integers of that size.
+
+use crate::registers::Registers;
+use crate::target::Target;
+use crate::Error;
+use std::fmt;
+
+const MAX_DEPTH: usize = 64;
+
+/// Binary operators and how tightly they bind, in the order they're matched in.
+const OPERATORS: [(&str, u8); 18] = [
+    ("*", 10),
+    ("/", 10),
+    ("%", 10),
+    ("+", 9),
+    ("-", 9),
+    ("<<", 8),
+    (">>", 8),
+    ("&&", 3),
+    ("||", 2),
+    ("&", 7),
+    ("^", 6),
+    ("|", 5),
+    ("==", 4),
+    ("!=", 4),
+    ("<=", 4),
+    (">=", 4),
+    ("<", 4),
+    (">", 4),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expr {
+    Number(u64),
+    Register(String),
+    /// Integer in memory of some size in bytes, or a pointer.
+    Memory(Box<Expr>, Option<usize>),
+    Unary(char, Box<Expr>),
+    Binary(&'static str, Box<Expr>, Box<Expr>),
+}
+
+/// What conditions are evaluated over.
+pub(crate) trait State {
+    fn register(&self, name: &str) -> Option<u64>;
+
+    /// Read an integer of some size in bytes, or a pointer.
+    fn memory(&mut self, addr: u64, size: Option<usize>) -> Result<u64, Error>;
+}
+
+/// A target that stopped, with the registers it stopped with.
+pub(crate) struct Stopped<'a> {
+    pub target: &'a mut dyn Target,
+    pub registers: Registers,
+}
+
+impl State for Stopped<'_> {
+    fn register(&self, name: &str) -> Option<u64> {
+        self.registers.get(name)?.value()
+    }
+
+    fn memory(&mut self, addr: u64, size: Option<usize>) -> Result<u64, Error> {
+        self.target.read_int(addr, size)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Condition {
+    src: String,
+    expr: Expr,
+}
+
+struct Parser<'src> {
+    src: &'src str,
+    offset: usize,
+    depth: usize,
+}
+
+impl<'src> Parser<'src> {
+    fn rest(&self) -> &'src str {
+        &self.src[self.offset..]
+    }
+
+    fn error(&self, msg: &str) -> Error {
+        Error::Expression(format!("{msg} at position {}", self.offset))
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.offset += rest.len() - rest.trim_start().len();
+    }
+
+    /// Consume a token if it's next.
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        if self.rest().starts_with(token) {
+            self.offset += token.len();
+            return true;
+        }
+
+        false
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), Error> {
+        match self.eat(token) {
+            true => Ok(()),
+            false => Err(self.error(&format!("Expected '{token}'"))),
+        }
+    }
+
+    /// Consume a word made up of alphanumeric characters and underscores.
+    fn word(&mut self) -> &'src str {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .unwrap_or(rest.len());
+        self.offset += len;
+        &rest[..len]
+    }
+
+    fn binary(&mut self, min_precedence: u8) -> Result<Expr, Error> {
+        let mut lhs = self.unary()?;
+        loop {
+            self.skip_whitespace();
+            let rest = self.rest();
+            let operator = OPERATORS.iter().find(|(op, _)| rest.starts_with(op));
+            let (op, precedence) = match operator {
+                Some(&(op, precedence)) if precedence >= min_precedence => (op, precedence),
+                _ => break,
+            };
+
+            self.offset += op.len();
+            let rhs = self.binary(precedence + 1)?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+
+        Ok(lhs)
+    }
+
+    /// Every nested expression passes through here, which limits how deep they're nested.
+    fn unary(&mut self) -> Result<Expr, Error> {
+        self.depth += 1;
+        if self.depth == MAX_DEPTH {
+            return Err(self.error("Expression is nested too deeply"));
+        }
+
+        let op = ['-', '!', '~'].into_iter().find(|op| self.eat(&op.to_string()));
+        let expr = match op {
+            Some(op) => Expr::Unary(op, Box::new(self.unary()?)),
+            None => self.atom()?,
+        };
+
+        self.depth -= 1;
+        Ok(expr)
+    }
+
+    fn atom(&mut self) -> Result<Expr, Error> {
+        if self.eat("(") {
+            let expr = self.binary(0)?;
+            self.expect(")")?;
+            return Ok(expr);
+        }
+
+        if self.eat("[") {
+            return self.memory(None);
+        }
+
+        let start = self.offset;
+        let word = self.word();
+        if word.is_empty() {
+            return Err(self.error("Expected a number or register"));
+        }
+
+        if word.starts_with(|c: char| c.is_ascii_digit()) {
+            let number = match word.strip_prefix("0x").or(word.strip_prefix("0X")) {
+                Some(hex) => u64::from_str_radix(hex, 16),
+                None => word.parse(),
+            };
+
+            return number.map(Expr::Number).map_err(|_| {
+                Error::Expression(format!("Number '{word}' is invalid at position {start}"))
+            });
+        }
+
+        let size = match word {
+            "u8" => Some(1),
+            "u16" => Some(2),
+            "u32" => Some(4),
+            "u64" => Some(8),
+            _ => None,
+        };
+
+        if size.is_some() && self.eat("[") {
+            return self.memory(size);
+        }
+
+        Ok(Expr::Register(word.to_ascii_lowercase()))
+    }
+
+    fn memory(&mut self, size: Option<usize>) -> Result<Expr, Error> {
+        let addr = self.binary(0)?;
+        self.expect("]")?;
+        Ok(Expr::Memory(Box::new(addr), size))
+    }
+}
+
+impl Expr {
+    fn eval(&self, state: &mut impl State) -> Result<u64, Error> {
+        Ok(match self {
+            Self::Number(value) => *value,
+            Self::Register(name) => state
+                .register(name)
+                .ok_or_else(|| Error::Expression(format!("Register '{name}' is unknown")))?,
+            Self::Memory(addr, size) => {
+                let addr = addr.eval(state)?;
+                state.memory(addr, *size)?
+            }
+            Self::Unary(op, expr) => {
+                let value = expr.eval(state)?;
+                match op {
+                    '-' => value.wrapping_neg(),
+                    '!' => (value == 0) as u64,
+                    _ => !value,
+                }
+            }
+            // the right side isn't evaluated if the left side decides the outcome
+            Self::Binary("&&", lhs, rhs) => (lhs.eval(state)? != 0 && rhs.eval(state)? != 0) as u64,
+            Self::Binary("||", lhs, rhs) => (lhs.eval(state)? != 0 || rhs.eval(state)? != 0) as u64,
+            Self::Binary(op, lhs, rhs) => {
+                let (lhs, rhs) = (lhs.eval(state)?, rhs.eval(state)?);
+                match *op {
+                    "*" => lhs.wrapping_mul(rhs),
+                    "/" | "%" if rhs == 0 => {
+                        return Err(Error::Expression("Division by zero".to_string()))
+                    }
+                    "/" => lhs / rhs,
+                    "%" => lhs % rhs,
+                    "+" => lhs.wrapping_add(rhs),
+                    "-" => lhs.wrapping_sub(rhs),
+                    "<<" => lhs.checked_shl(rhs as u32).unwrap_or(0),
+                    ">>" => lhs.checked_shr(rhs as u32).unwrap_or(0),
+                    "&" => lhs & rhs,
+                    "^" => lhs ^ rhs,
+                    "|" => lhs | rhs,
+                    "==" => (lhs == rhs) as u64,
+                    "!=" => (lhs != rhs) as u64,
+                    "<=" => (lhs <= rhs) as u64,
+                    ">=" => (lhs >= rhs) as u64,
+                    "<" => (lhs < rhs) as u64,
+                    _ => (lhs > rhs) as u64,
+                }
+            }
+        })
+    }
+
+    fn registers<'a>(&'a self, names: &mut Vec<&'a str>) {
+        match self {
+            Self::Number(_) => {}
+            Self::Register(name) => names.push(name),
+            Self::Memory(expr, _) | Self::Unary(_, expr) => expr.registers(names),
+            Self::Binary(_, lhs, rhs) => {
+                lhs.registers(names);
+                rhs.registers(names);
+            }
+        }
+    }
+}
+
+impl Condition {
+    pub fn parse(src: &str) -> Result<Self, Error> {
+        let mut parser = Parser {
+            src,
+            offset: 0,
+            depth: 0,
+        };
+
+        let expr = parser.binary(0)?;
+        parser.skip_whitespace();
+        if !parser.rest().is_empty() {
+            return Err(parser.error("Unexpected characters"));
+        }
+
+        Ok(Self {
+            src: src.trim().to_string(),
+            expr,
+        })
+    }
+
+    /// Names of the registers the condition reads.
+    pub fn registers(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        self.expr.registers(&mut names);
+        names
+    }
+
+    pub(crate) fn holds(&self, state: &mut impl State) -> Result<bool, Error> {
+        Ok(self.expr.eval(state)? != 0)
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.src)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    struct Mock {
+        registers: HashMap<&'static str, u64>,
+        memory: HashMap<u64, u64>,
+    }
+
+    impl State for Mock {
+        fn register(&self, name: &str) -> Option<u64> {
+            self.registers.get(name).copied()
+        }
+
+        fn memory(&mut self, addr: u64, size: Option<usize>) -> Result<u64, Error> {
+            let value = self.memory.get(&addr).ok_or(Error::Remote(14))?;
+            Ok(match size {
+                Some(size) if size < 8 => value & ((1 << (size * 8)) - 1),
+                _ => *value,
+            })
+        }
+    }
+
+    fn eval(src: &str) -> Result<u64, Error> {
+        let mut state = Mock {
+            registers: HashMap::from([("rax", 0x10), ("rsp", 0x7000)]),
+            memory: HashMap::from([(0x7008, 0x1234)]),
+        };
+
+        Condition::parse(src)?.expr.eval(&mut state)
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(eval("1 + 2 * 3").unwrap(), 7);
+        assert_eq!(eval("(1 + 2) * 3").unwrap(), 9);
+        assert_eq!(eval("1 << 4 | 1").unwrap(), 0x11);
+        assert_eq!(eval("rax & 0xf0 == 0x10").unwrap(), 1);
+        assert_eq!(eval("1 == 1 && 2 == 3 || 4 > 3").unwrap(), 1);
+        assert_eq!(eval("10 - 2 - 3").unwrap(), 5);
+    }
+
+    #[test]
+    fn unary() {
+        assert_eq!(eval("-1").unwrap(), u64::MAX);
+        assert_eq!(eval("!0").unwrap(), 1);
+        assert_eq!(eval("~0 != 0").unwrap(), 1);
+        assert_eq!(eval("!(RAX != 0x10)").unwrap(), 1);
+    }
+
+    #[test]
+    fn memory() {
+        assert_eq!(eval("[rsp + 8]").unwrap(), 0x1234);
+        assert_eq!(eval("u8[rsp+8] == 0x34").unwrap(), 1);
+
+        // memory that can't be read isn't read when the left side decides the outcome
+        assert_eq!(eval("rax == 0 && [0] == 0").unwrap(), 0);
+        assert!(eval("[0] == 0").is_err());
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(eval("").is_err());
+        assert!(eval("rax ==").is_err());
+        assert!(eval("(rax").is_err());
+        assert!(eval("rax rbx").is_err());
+        assert!(eval("0xzz").is_err());
+        assert!(eval("rbx").is_err());
+        assert!(eval("1 / 0").is_err());
+        assert!(Condition::parse(&"(".repeat(100)).is_err());
+        assert!(Condition::parse(&"-".repeat(100)).is_err());
+    }
+
+    #[test]
+    fn registers() {
+        let condition = Condition::parse("rdi == 1 && [rsi + rcx] != 0").unwrap();
+        assert_eq!(condition.registers(), ["rdi", "rsi", "rcx"]);
+        assert_eq!(condition.to_string(), "rdi == 1 && [rsi + rcx] != 0");
+    }
+}
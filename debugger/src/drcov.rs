@@ -0,0 +1,324 @@
+//! Coverage files written by DynamoRIO's `drcov` tool, which lists the basic blocks that ran in
+//! each module of a process:
+//!
+//! ```text
+//! DRCOV VERSION: 2
+//! DRCOV FLAVOR: drcov
+//! Module Table: version 2, count 1
+//! Columns: id, base, end, entry, checksum, timestamp, path
+//!   0, 0x400000, 0x401000, 0x0000000000000000, 0x00000000, 0x00000000, /bin/cat
+//! BB Table: 1 bbs
+//! <start: u32, size: u16, module: u16>...
+//! ```
+//!
+//! Blocks are usually stored as little-endian binary, some tools write them out as text instead.
+
+use crate::{Error, Module};
+use std::ops::Range;
+use std::path::Path;
+
+/// Columns of the module table when the file doesn't list them, as in version 1.
+const DEFAULT_COLUMNS: [&str; 5] = ["id", "base", "end", "entry", "path"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Block {
+    /// Offset from the base of the module.
+    pub start: u32,
+    pub size: u16,
+    pub module: u16,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Coverage {
+    pub modules: Vec<Module>,
+    pub blocks: Vec<Block>,
+}
+
+/// Splits the header off a file, line by line.
+struct Lines<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Lines<'a> {
+    fn next(&mut self) -> Result<&'a str, Error> {
+        let len = self.bytes.iter().position(|&b| b == b'\n').unwrap_or(self.bytes.len());
+        let line = std::str::from_utf8(&self.bytes[..len])
+            .map_err(|_| Error::Coverage("header isn't text"))?;
+
+        self.bytes = self.bytes.get(len + 1..).unwrap_or_default();
+        Ok(line.trim_end_matches('\r'))
+    }
+}
+
+fn parse_number(s: &str) -> Option<u64> {
+    let s = s.trim();
+    match s.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+}
+
+impl Coverage {
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        let mut lines = Lines { bytes };
+
+        if !lines.next()?.starts_with("DRCOV VERSION:") {
+            return Err(Error::Coverage("it's missing the drcov header"));
+        }
+
+        // the flavor is optional, so skip ahead to the module table
+        let mut line = lines.next()?;
+        while !line.starts_with("Module Table:") {
+            if lines.bytes.is_empty() {
+                return Err(Error::Coverage("it's missing the module table"));
+            }
+            line = lines.next()?;
+        }
+
+        // either 'Module Table: <count>' or 'Module Table: version <n>, count <count>'
+        let count = line.rsplit([' ', ':']).next().and_then(parse_number);
+        let count = count.ok_or(Error::Coverage("module count is invalid"))? as usize;
+
+        let mut line = lines.next()?;
+        let mut columns = DEFAULT_COLUMNS.to_vec();
+        if let Some(names) = line.strip_prefix("Columns:") {
+            columns = names.split(',').map(str::trim).collect();
+            line = lines.next()?;
+        }
+
+        let column = |names: &[&str]| columns.iter().position(|column| names.contains(column));
+        let base = column(&["base", "start"]).ok_or(Error::Coverage("modules have no base"))?;
+        let end = column(&["end"]).ok_or(Error::Coverage("modules have no end"))?;
+        let path = column(&["path"]).ok_or(Error::Coverage("modules have no path"))?;
+
+        let mut modules = Vec::with_capacity(count);
+        for _ in 0..count {
+            // paths might contain commas, they're always the last column
+            let fields: Vec<&str> = line.splitn(columns.len(), ',').map(str::trim).collect();
+            let field = |idx: usize| fields.get(idx).copied();
+
+            modules.push(Module {
+                base: field(base).and_then(parse_number).ok_or(Error::Coverage("invalid base"))?,
+                end: field(end).and_then(parse_number).ok_or(Error::Coverage("invalid end"))?,
+                path: field(path).ok_or(Error::Coverage("module has no path"))?.to_string(),
+            });
+
+            line = lines.next()?;
+        }
+
+        let count = line
+            .strip_prefix("BB Table:")
+            .and_then(|count| count.split_whitespace().next())
+            .and_then(parse_number)
+            .ok_or(Error::Coverage("it's missing the block table"))? as usize;
+
+        let blocks = match lines.bytes.starts_with(b"module id") {
+            true => Self::parse_text_blocks(lines, count)?,
+            false => Self::parse_blocks(lines.bytes, count)?,
+        };
+
+        Ok(Self { modules, blocks })
+    }
+
+    fn parse_blocks(bytes: &[u8], count: usize) -> Result<Vec<Block>, Error> {
+        let bytes = bytes.get(..count * 8).ok_or(Error::Coverage("blocks are truncated"))?;
+
+        let blocks = bytes
+            .chunks_exact(8)
+            .map(|block| Block {
+                start: u32::from_le_bytes([block[0], block[1], block[2], block[3]]),
+                size: u16::from_le_bytes([block[4], block[5]]),
+                module: u16::from_le_bytes([block[6], block[7]]),
+            })
+            .collect();
+
+        Ok(blocks)
+    }
+
+    /// Blocks written as `module[  0]: 0x0000000000001000,   8`.
+    fn parse_text_blocks(mut lines: Lines, count: usize) -> Result<Vec<Block>, Error> {
+        let mut blocks = Vec::with_capacity(count);
+
+        // skip the header of the table
+        lines.next()?;
+
+        for _ in 0..count {
+            let line = lines.next()?;
+            let block = line.strip_prefix("module[").and_then(|line| {
+                let (module, rest) = line.split_once("]:")?;
+                let (start, size) = rest.split_once(',')?;
+
+                Some(Block {
+                    start: parse_number(start)? as u32,
+                    size: parse_number(size)? as u16,
+                    module: parse_number(module)? as u16,
+                })
+            });
+
+            blocks.push(block.ok_or(Error::Coverage("blocks are invalid"))?);
+        }
+
+        Ok(blocks)
+    }
+
+    /// Write the coverage out as version 2 with the blocks in binary.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut header = String::from("DRCOV VERSION: 2\nDRCOV FLAVOR: drcov\n");
+
+        header += &format!("Module Table: version 2, count {}\n", self.modules.len());
+        header += "Columns: id, base, end, entry, checksum, timestamp, path\n";
+        for (idx, module) in self.modules.iter().enumerate() {
+            header += &format!(
+                "{idx:3}, {:#018x}, {:#018x}, {:#018x}, {:#010x}, {:#010x}, {}\n",
+                module.base, module.end, 0, 0, 0, module.path
+            );
+        }
+        header += &format!("BB Table: {} bbs\n", self.blocks.len());
+
+        let mut bytes = header.into_bytes();
+        for block in self.blocks.iter() {
+            bytes.extend_from_slice(&block.start.to_le_bytes());
+            bytes.extend_from_slice(&block.size.to_le_bytes());
+            bytes.extend_from_slice(&block.module.to_le_bytes());
+        }
+
+        bytes
+    }
+
+    /// Index of the module that's a file, matching it by name as it might've been somewhere else
+    /// when the coverage was recorded. A file that covers a single module is assumed to be it.
+    pub fn module_of(&self, path: &Path) -> Option<usize> {
+        let name = path.file_name()?.to_str()?;
+
+        // paths might've been recorded on another OS
+        let position = self.modules.iter().position(|module| {
+            module.path.rsplit(['/', '\\']).next().is_some_and(|file| file == name)
+        });
+
+        position.or(Some(0).filter(|_| self.modules.len() == 1))
+    }
+
+    /// Blocks that ran in a module, relative to it's base.
+    pub fn blocks_of(&self, module: usize) -> impl Iterator<Item = Range<u64>> + '_ {
+        self.blocks
+            .iter()
+            .filter(move |block| block.module as usize == module)
+            .map(|block| {
+                let start = block.start as u64;
+                start..start + block.size as u64
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coverage() -> Coverage {
+        Coverage {
+            modules: vec![
+                Module {
+                    base: 0x400000,
+                    end: 0x401000,
+                    path: "/usr/bin/cat".to_string(),
+                },
+                Module {
+                    base: 0x7f0000000000,
+                    end: 0x7f0000100000,
+                    path: "C:\\Windows\\System32\\a, b.dll".to_string(),
+                },
+            ],
+            blocks: vec![
+                Block {
+                    start: 0x10,
+                    size: 4,
+                    module: 0,
+                },
+                Block {
+                    start: 0x20,
+                    size: 8,
+                    module: 1,
+                },
+                Block {
+                    start: 0x10,
+                    size: 4,
+                    module: 0,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn roundtrip() {
+        let coverage = coverage();
+        assert_eq!(Coverage::parse(&coverage.to_bytes()).unwrap(), coverage);
+    }
+
+    #[test]
+    fn version_3() {
+        let file = b"DRCOV VERSION: 3\n\
+            DRCOV FLAVOR: drcov-64\n\
+            Module Table: version 4, count 1\n\
+            Columns: id, containing_id, start, end, entry, offset, checksum, timestamp, path\n  \
+            0,   0, 0x0000000000400000, 0x0000000000401000, 0x0000000000400100, \
+            0000000000000000, 0x00000000, 0x00000000, /bin/true\n\
+            BB Table: 1 bbs\n\
+            \x00\x01\x00\x00\x03\x00\x00\x00";
+
+        let coverage = Coverage::parse(file).unwrap();
+        assert_eq!(coverage.modules[0].base, 0x400000);
+        assert_eq!(coverage.modules[0].path, "/bin/true");
+        assert_eq!(
+            coverage.blocks,
+            [Block {
+                start: 0x100,
+                size: 3,
+                module: 0
+            }]
+        );
+    }
+
+    #[test]
+    fn text_blocks() {
+        let file = b"DRCOV VERSION: 1\n\
+            Module Table: 1\n\
+            0, 0x400000, 0x401000, 0x400000, /bin/true\n\
+            BB Table: 2 bbs\n\
+            module id, start, size:\n\
+            module[  0]: 0x0000000000000010,   4\n\
+            module[  0]: 0x0000000000000020,  12\n";
+
+        let coverage = Coverage::parse(file).unwrap();
+        assert_eq!(coverage.modules[0].end, 0x401000);
+        assert_eq!(
+            coverage.blocks[1],
+            Block {
+                start: 0x20,
+                size: 12,
+                module: 0
+            }
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(Coverage::parse(b"").is_err());
+        assert!(Coverage::parse(b"DRCOV VERSION: 2\nBB Table: 1 bbs\n").is_err());
+
+        // the blocks are truncated
+        let mut bytes = coverage().to_bytes();
+        bytes.pop();
+        assert!(Coverage::parse(&bytes).is_err());
+    }
+
+    #[test]
+    fn modules() {
+        let coverage = coverage();
+        assert_eq!(coverage.module_of(Path::new("/tmp/cat")), Some(0));
+        assert_eq!(coverage.module_of(Path::new("a, b.dll")), Some(1));
+        assert_eq!(coverage.module_of(Path::new("ls")), None);
+
+        let blocks: Vec<_> = coverage.blocks_of(0).collect();
+        assert_eq!(blocks, [0x10..0x14, 0x10..0x14]);
+    }
+}
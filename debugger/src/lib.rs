@@ -0,0 +1,194 @@
+//! Debugging of targets that run elsewhere, such as QEMU, an embedded probe or `gdbserver`, using
+//! the [GDB remote serial protocol].
+//!
+//! A [`Remote`] sends a request and waits for the reply of the stub it's connected to. As running
+//! the target blocks until it stops, a [`Session`] does that on a thread of its own so that the
+//! GUI stays responsive.
+//!
+//! Addresses are those of the target, so they only line up with the listing if the binary isn't
+//! relocated when it's loaded, as is the case for firmware and executables that aren't PIE.
+//!
+//! On Windows, programs on this machine are debugged through the Win32 debug API instead. Both are
+//! a [`Target`], which a [`Session`] drives the same way. Such targets report the modules they
+//! load, along with the functions those export.
+//!
+//! Processes on this machine can also be read without debugging them, see [`Process`].
+//!
+//! [GDB remote serial protocol]: https://sourceware.org/gdb/current/onlinedocs/gdb.html/Remote-Protocol.html
+
+mod condition;
+mod drcov;
+mod memory;
+mod module;
+mod packet;
+mod process;
+mod registers;
+mod remote;
+mod session;
+mod target;
+mod trace;
+mod windows;
+
+pub use condition::Condition;
+pub use drcov::Coverage;
+pub use memory::Region;
+pub use module::Module;
+pub use process::Process;
+pub use registers::{Register, Registers};
+pub use remote::{Remote, Watch};
+pub use session::Session;
+pub use target::{Interrupter, Target};
+pub use trace::Trace;
+
+use object::Architecture;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    /// The stub replied with an error number.
+    Remote(u8),
+    /// The stub doesn't support a request.
+    Unsupported(&'static str),
+    /// A reply that doesn't follow the protocol.
+    Malformed(String),
+    /// Packets kept getting corrupted.
+    Checksum,
+    /// Registers of the architecture aren't known.
+    UnknownArchitecture(Architecture),
+    /// The target has to be stopped first.
+    Busy,
+    /// A coverage file that can't be read.
+    Coverage(&'static str),
+    /// A condition that can't be parsed or evaluated.
+    Expression(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => f.write_fmt(format_args!("{err}.")),
+            Self::Remote(errno) => f.write_fmt(format_args!("Target replied with error {errno}.")),
+            Self::Unsupported(request) => {
+                f.write_fmt(format_args!("Target doesn't support {request}."))
+            }
+            Self::Malformed(reply) => f.write_fmt(format_args!("Unexpected reply '{reply}'.")),
+            Self::Checksum => f.write_str("Packets sent by the target are corrupted."),
+            Self::UnknownArchitecture(arch) => {
+                f.write_fmt(format_args!("Debugging {arch:?} targets isn't supported."))
+            }
+            Self::Busy => f.write_str("Target is running."),
+            Self::Coverage(reason) => {
+                f.write_fmt(format_args!("Coverage file is invalid, {reason}."))
+            }
+            Self::Expression(msg) => f.write_fmt(format_args!("{msg}.")),
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+/// Why the target stopped running.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stop {
+    /// Stopped by a signal, breakpoints and steps stop with `SIGTRAP`.
+    Signal(u8),
+    /// The process exited with a status.
+    Exited(u8),
+    /// The process was terminated by a signal.
+    Killed(u8),
+    /// Stopped by a watchpoint on memory at an address.
+    Watchpoint(u64),
+}
+
+impl Stop {
+    fn parse(reply: &[u8]) -> Result<Self, Error> {
+        let malformed = || Error::Malformed(String::from_utf8_lossy(reply).into_owned());
+        let number = reply.get(1..3).and_then(packet::parse_hex).ok_or_else(malformed)? as u8;
+
+        // stop replies list why the target stopped as 'name:value;' pairs after the signal
+        let pairs = reply[3..].split(|&byte| byte == b';');
+        let mut watched = pairs.filter_map(|pair| {
+            let colon = pair.iter().position(|&byte| byte == b':')?;
+            let (name, value) = (&pair[..colon], &pair[colon + 1..]);
+            match name {
+                b"watch" | b"rwatch" | b"awatch" => packet::parse_hex(value),
+                _ => None,
+            }
+        });
+
+        match reply[0] {
+            b'T' => Ok(watched.next_back().map_or(Self::Signal(number), Self::Watchpoint)),
+            b'S' => Ok(Self::Signal(number)),
+            b'W' => Ok(Self::Exited(number)),
+            b'X' => Ok(Self::Killed(number)),
+            _ => Err(malformed()),
+        }
+    }
+
+    /// Whether the target is gone.
+    pub fn is_exit(&self) -> bool {
+        matches!(self, Self::Exited(_) | Self::Killed(_))
+    }
+}
+
+impl fmt::Display for Stop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Signal(signal) => {
+                f.write_fmt(format_args!("stopped by {}", signal_name(*signal)))
+            }
+            Self::Exited(status) => f.write_fmt(format_args!("exited with status {status}")),
+            Self::Killed(signal) => f.write_fmt(format_args!("killed by {}", signal_name(*signal))),
+            Self::Watchpoint(addr) => {
+                f.write_fmt(format_args!("stopped by watchpoint at {addr:#X}"))
+            }
+        }
+    }
+}
+
+/// Name of a signal, which the protocol numbers the same way on every OS.
+fn signal_name(signal: u8) -> String {
+    let name = match signal {
+        2 => "SIGINT",
+        4 => "SIGILL",
+        5 => "SIGTRAP",
+        6 => "SIGABRT",
+        7 => "SIGEMT",
+        8 => "SIGFPE",
+        9 => "SIGKILL",
+        10 => "SIGBUS",
+        11 => "SIGSEGV",
+        15 => "SIGTERM",
+        _ => return format!("signal {signal}"),
+    };
+
+    name.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stops() {
+        assert_eq!(Stop::parse(b"S05").unwrap(), Stop::Signal(5));
+        assert_eq!(Stop::parse(b"T0bthread:p1.1;").unwrap(), Stop::Signal(11));
+        assert_eq!(Stop::parse(b"W00").unwrap(), Stop::Exited(0));
+        assert_eq!(Stop::parse(b"X09").unwrap(), Stop::Killed(9));
+        assert_eq!(
+            Stop::parse(b"T05thread:p1.1;awatch:7ffc10;").unwrap(),
+            Stop::Watchpoint(0x7ffc10)
+        );
+        assert!(Stop::parse(b"OK").is_err());
+        assert!(Stop::parse(b"").is_err());
+        assert_eq!(Stop::Signal(5).to_string(), "stopped by SIGTRAP");
+        assert_eq!(Stop::Exited(0).to_string(), "exited with status 0");
+        assert_eq!(Stop::Killed(40).to_string(), "killed by signal 40");
+        assert!(Stop::Killed(9).is_exit());
+    }
+}
@@ -0,0 +1,139 @@
+//! Memory regions of a target, as described by the stub or by `/proc/<pid>/maps` of a process.
+
+use std::ops::Range;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    pub range: Range<u64>,
+    /// Either the `rwxp` flags of a process' mapping, or the type of memory of a device such as
+    /// `ram` or `flash`.
+    pub permissions: String,
+    /// File that's mapped, or pseudo-names such as `[stack]`.
+    pub name: String,
+    /// Offset into the file that's mapped.
+    pub offset: u64,
+}
+
+/// Parse the memory map that embedded stubs describe as XML, such as:
+///
+/// ```xml
+/// <memory type="flash" start="0x8000000" length="0x100000">
+///   <property name="blocksize">0x800</property>
+/// </memory>
+/// ```
+pub(crate) fn parse_xml(xml: &str) -> Vec<Region> {
+    let mut regions = Vec::new();
+
+    for element in xml.split("<memory").skip(1) {
+        let tag = match element.split_once('>') {
+            Some((tag, _)) => tag,
+            None => continue,
+        };
+
+        let attribute = |name: &str| {
+            let start = tag.find(&format!("{name}=\""))? + name.len() + 2;
+            let len = tag[start..].find('"')?;
+            Some(&tag[start..start + len])
+        };
+
+        let number = |name: &str| {
+            let value = attribute(name)?;
+            match value.strip_prefix("0x") {
+                Some(hex) => u64::from_str_radix(hex, 16).ok(),
+                None => value.parse().ok(),
+            }
+        };
+
+        if let (Some(start), Some(len)) = (number("start"), number("length")) {
+            regions.push(Region {
+                range: start..start.saturating_add(len),
+                permissions: attribute("type").unwrap_or("ram").to_string(),
+                name: String::new(),
+                offset: 0,
+            });
+        }
+    }
+
+    regions
+}
+
+/// Parse the mappings of a process, as listed by Linux in `/proc/<pid>/maps`:
+///
+/// ```text
+/// 55d0c4a00000-55d0c4a2c000 r-xp 00000000 08:01 1048602    /usr/bin/cat
+/// ```
+pub(crate) fn parse_maps(maps: &str) -> Vec<Region> {
+    let mut regions = Vec::new();
+
+    for line in maps.lines() {
+        let mut fields = line.split_whitespace();
+        let (range, permissions) = match (fields.next(), fields.next()) {
+            (Some(range), Some(permissions)) => (range, permissions),
+            _ => continue,
+        };
+
+        let (start, end) = match range.split_once('-') {
+            Some((start, end)) => (start, end),
+            None => continue,
+        };
+
+        let (start, end) = match (u64::from_str_radix(start, 16), u64::from_str_radix(end, 16)) {
+            (Ok(start), Ok(end)) => (start, end),
+            _ => continue,
+        };
+
+        let offset = fields.next().and_then(|offset| u64::from_str_radix(offset, 16).ok());
+
+        // skip the device and inode, the name might contain spaces
+        let name = fields.skip(2).collect::<Vec<_>>().join(" ");
+        regions.push(Region {
+            range: start..end,
+            permissions: permissions.to_string(),
+            name,
+            offset: offset.unwrap_or(0),
+        });
+    }
+
+    regions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn xml() {
+        let xml = r#"<?xml version="1.0"?>
+            <memory-map>
+              <memory type="flash" start="0x8000000" length="0x100000">
+                <property name="blocksize">0x800</property>
+              </memory>
+              <memory type="ram" start="0x20000000" length="131072"/>
+            </memory-map>"#;
+
+        let regions = parse_xml(xml);
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[0].range, 0x8000000..0x8100000);
+        assert_eq!(regions[0].permissions, "flash");
+        assert_eq!(regions[1].range, 0x20000000..0x20020000);
+    }
+
+    #[test]
+    fn maps() {
+        let maps = "\
+55d0c4a00000-55d0c4a2c000 r-xp 00000000 08:01 1048602    /usr/bin/cat
+7ffc5a3e0000-7ffc5a401000 rw-p 00000000 00:00 0          [stack]
+7f0000000000-7f0000001000 ---p 00000000 00:00 0
+7f0000001000-7f0000002000 r--p 00002000 08:01 42         /tmp/with space.so";
+
+        let regions = parse_maps(maps);
+        assert_eq!(regions.len(), 4);
+        assert_eq!(regions[0].range, 0x55d0c4a00000..0x55d0c4a2c000);
+        assert_eq!(regions[0].permissions, "r-xp");
+        assert_eq!(regions[0].name, "/usr/bin/cat");
+        assert_eq!(regions[1].name, "[stack]");
+        assert_eq!(regions[2].name, "");
+        assert_eq!(regions[3].name, "/tmp/with space.so");
+        assert_eq!(regions[3].offset, 0x2000);
+    }
+}
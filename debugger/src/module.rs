@@ -0,0 +1,44 @@
+//! Files a target has mapped as code, such as its executable and the libraries it loaded.
+
+use object::{Object, ObjectSymbol, SymbolKind};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Module {
+    pub base: u64,
+    pub end: u64,
+    pub path: String,
+}
+
+impl Module {
+    /// Functions the module's file exports or has symbols for, at the address they're loaded at.
+    /// Empty if the file can't be read.
+    pub fn functions(&self) -> Vec<(u64, String)> {
+        let data = match std::fs::read(&self.path) {
+            Ok(data) => data,
+            Err(..) => return Vec::new(),
+        };
+
+        let obj = match object::File::parse(&data[..]) {
+            Ok(obj) => obj,
+            Err(..) => return Vec::new(),
+        };
+
+        // exports are relative to the image base the file asks for, not where it got loaded
+        let bias = self.base.wrapping_sub(obj.relative_address_base());
+        let rebase = |addr: u64| addr.wrapping_add(bias);
+
+        let mut functions: Vec<(u64, String)> = obj
+            .symbols()
+            .filter(|symbol| symbol.kind() == SymbolKind::Text && symbol.is_definition())
+            .filter_map(|symbol| Some((rebase(symbol.address()), symbol.name().ok()?.to_string())))
+            .collect();
+
+        for export in obj.exports().unwrap_or_default() {
+            if let Ok(name) = std::str::from_utf8(export.name()) {
+                functions.push((rebase(export.address()), name.to_string()));
+            }
+        }
+
+        functions
+    }
+}
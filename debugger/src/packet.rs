@@ -0,0 +1,137 @@
+//! Framing of packets as `$data#checksum`.
+
+use std::io::{self, BufRead};
+
+/// Characters in the data of a packet that have to be escaped.
+const ESCAPED: &[u8] = b"$#}*";
+
+/// Frame data as a packet, escaping the characters that have a meaning in the protocol.
+pub(crate) fn frame(data: &[u8]) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(data.len() + 4);
+    packet.push(b'$');
+
+    for &byte in data {
+        if ESCAPED.contains(&byte) {
+            packet.push(b'}');
+            packet.push(byte ^ 0x20);
+        } else {
+            packet.push(byte);
+        }
+    }
+
+    let checksum = checksum(&packet[1..]);
+    packet.push(b'#');
+    packet.extend(format!("{checksum:02x}").bytes());
+    packet
+}
+
+/// Read the next packet, skipping anything in front of it such as acknowledgements.
+///
+/// Returns `None` if the checksum of the packet doesn't match.
+pub(crate) fn read(reader: &mut impl BufRead) -> io::Result<Option<Vec<u8>>> {
+    let mut skipped = Vec::new();
+    reader.read_until(b'$', &mut skipped)?;
+    if skipped.last() != Some(&b'$') {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+
+    let mut data = Vec::new();
+    reader.read_until(b'#', &mut data)?;
+    if data.pop() != Some(b'#') {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+
+    let mut expected = [0; 2];
+    reader.read_exact(&mut expected)?;
+
+    match parse_hex(&expected) {
+        Some(expected) if expected == checksum(&data) as u64 => Ok(Some(decode(&data))),
+        _ => Ok(None),
+    }
+}
+
+fn checksum(data: &[u8]) -> u8 {
+    data.iter().fold(0, |sum, &byte| sum.wrapping_add(byte))
+}
+
+/// Undo the escaping and run-length encoding of the data of a packet.
+fn decode(data: &[u8]) -> Vec<u8> {
+    let mut decoded = Vec::with_capacity(data.len());
+    let mut bytes = data.iter();
+
+    while let Some(&byte) = bytes.next() {
+        match byte {
+            b'}' => match bytes.next() {
+                Some(&escaped) => decoded.push(escaped ^ 0x20),
+                None => break,
+            },
+            // the previous character is repeated as often as the next one, minus 29
+            b'*' => match (decoded.last().copied(), bytes.next()) {
+                (Some(prev), Some(&count)) => {
+                    let count = count.saturating_sub(29) as usize;
+                    decoded.extend(std::iter::repeat_n(prev, count));
+                }
+                _ => break,
+            },
+            _ => decoded.push(byte),
+        }
+    }
+
+    decoded
+}
+
+pub(crate) fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+pub(crate) fn decode_hex(hex: &[u8]) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+
+    hex.chunks(2).map(|pair| parse_hex(pair).map(|byte| byte as u8)).collect()
+}
+
+/// Parse a big-endian hex number such as the addresses in replies.
+pub(crate) fn parse_hex(hex: &[u8]) -> Option<u64> {
+    let hex = std::str::from_utf8(hex).ok()?;
+    u64::from_str_radix(hex, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn framing() {
+        assert_eq!(frame(b"g"), b"$g#67");
+        assert_eq!(frame(b"OK"), b"$OK#9a");
+        assert_eq!(frame(b"a}b"), b"$a}]b#9d");
+    }
+
+    #[test]
+    fn reading() {
+        let mut stream: &[u8] = b"+$OK#9a$T05#b9";
+        assert_eq!(read(&mut stream).unwrap(), Some(b"OK".to_vec()));
+        assert_eq!(read(&mut stream).unwrap(), Some(b"T05".to_vec()));
+        assert!(read(&mut stream).is_err());
+
+        let mut corrupted: &[u8] = b"$OK#00";
+        assert_eq!(read(&mut corrupted).unwrap(), None);
+    }
+
+    #[test]
+    fn run_length() {
+        assert_eq!(decode(b"0* "), b"0000");
+        assert_eq!(decode(b"a}]b"), b"a}b");
+        assert_eq!(decode(b"*!"), b"");
+    }
+
+    #[test]
+    fn hex() {
+        assert_eq!(encode_hex(&[0x00, 0xab, 0x10]), "00ab10");
+        assert_eq!(decode_hex(b"00ab10"), Some(vec![0x00, 0xab, 0x10]));
+        assert_eq!(decode_hex(b"0"), None);
+        assert_eq!(parse_hex(b"7fff0010"), Some(0x7fff0010));
+    }
+}
@@ -0,0 +1,209 @@
+//! Read-only access to a process running on this machine, through the files Linux exposes for it
+//! in `/proc/<pid>`.
+//!
+//! Unlike a [`Remote`], the process isn't stopped or controlled. Its memory is only read, which is
+//! enough to snapshot code it unpacked or modified at runtime.
+//!
+//! [`Remote`]: crate::Remote
+
+use crate::memory::{self, Region};
+use crate::Error;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::PathBuf;
+
+/// Unit of the offsets of mapped files in an `NT_FILE` note, which the offsets listed in
+/// `/proc/<pid>/maps` are always a multiple of.
+const PAGE_SIZE: u64 = 0x1000;
+
+const ET_CORE: u16 = 4;
+const PT_LOAD: u32 = 1;
+const PT_NOTE: u32 = 4;
+const NT_FILE: u32 = 0x46494c45;
+const PF_X: u32 = 1;
+const PF_W: u32 = 2;
+const PF_R: u32 = 4;
+
+/// Process whose memory is read through `/proc/<pid>/mem`.
+pub struct Process {
+    pid: u32,
+    mem: File,
+}
+
+/// Integers of an ELF file, written in the byte order and pointer width of the process.
+struct Writer {
+    bytes: Vec<u8>,
+    little_endian: bool,
+    is_64: bool,
+}
+
+impl Writer {
+    fn int(&mut self, value: u64, size: usize) {
+        match self.little_endian {
+            true => self.bytes.extend_from_slice(&value.to_le_bytes()[..size]),
+            false => self.bytes.extend_from_slice(&value.to_be_bytes()[8 - size..]),
+        }
+    }
+
+    fn word(&mut self, value: u64) {
+        self.int(value, if self.is_64 { 8 } else { 4 });
+    }
+
+    fn align(&mut self, alignment: usize) {
+        self.bytes.resize(self.bytes.len().next_multiple_of(alignment), 0);
+    }
+
+    /// Program header, which orders it's fields differently for 32-bit and 64-bit processes.
+    fn program_header(&mut self, kind: u32, flags: u32, offset: u64, addr: u64, size: (u64, u64)) {
+        let (file_size, memory_size) = size;
+        self.int(kind as u64, 4);
+        if self.is_64 {
+            self.int(flags as u64, 4);
+        }
+        self.word(offset);
+        self.word(addr);
+        self.word(0);
+        self.word(file_size);
+        self.word(memory_size);
+        if !self.is_64 {
+            self.int(flags as u64, 4);
+        }
+        self.word(if kind == PT_NOTE { 4 } else { PAGE_SIZE });
+    }
+}
+
+impl Process {
+    /// Open the memory of a process, which requires being allowed to trace it.
+    pub fn attach(pid: u32) -> Result<Self, Error> {
+        let mem = File::open(format!("/proc/{pid}/mem"))?;
+        Ok(Self { pid, mem })
+    }
+
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    /// Path of the process' executable.
+    pub fn executable(&self) -> Result<PathBuf, Error> {
+        Ok(std::fs::read_link(format!("/proc/{}/exe", self.pid))?)
+    }
+
+    /// Mappings of the process.
+    pub fn memory_map(&self) -> Result<Vec<Region>, Error> {
+        let maps = std::fs::read_to_string(format!("/proc/{}/maps", self.pid))?;
+        Ok(memory::parse_maps(&maps))
+    }
+
+    /// Read memory of the process, stopping early at memory that can't be read.
+    pub fn read_memory(&mut self, addr: u64, len: usize) -> Result<Vec<u8>, Error> {
+        let mut bytes = vec![0; len];
+        let mut read = 0;
+
+        while read < len {
+            self.mem.seek(SeekFrom::Start(addr + read as u64))?;
+            match self.mem.read(&mut bytes[read..]) {
+                Ok(0) => break,
+                Ok(count) => read += count,
+                Err(_) if read != 0 => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        bytes.truncate(read);
+        Ok(bytes)
+    }
+
+    /// Executable memory of the process as an ELF core dump, along with the files it mapped so
+    /// that their functions can be named.
+    pub fn snapshot(&mut self) -> Result<Vec<u8>, Error> {
+        // the core dump describes the same machine as the executable does
+        let mut header = [0; 64];
+        File::open(format!("/proc/{}/exe", self.pid))?.read_exact(&mut header)?;
+        if !header.starts_with(b"\x7fELF") {
+            return Err(Error::Unsupported("snapshots of executables that aren't ELF"));
+        }
+
+        let is_64 = header[4] == 2;
+        let little_endian = header[5] == 1;
+        let flags = match is_64 {
+            true => &header[48..52],
+            false => &header[36..40],
+        };
+
+        let regions = self.memory_map()?;
+        let mut loads = Vec::new();
+        for region in regions.iter().filter(|region| region.permissions.contains('x')) {
+            let len = (region.range.end - region.range.start) as usize;
+            // some mappings of the kernel such as `[vsyscall]` can't be read
+            if let Ok(bytes) = self.read_memory(region.range.start, len) {
+                loads.push((region, bytes));
+            }
+        }
+
+        let files: Vec<&Region> =
+            regions.iter().filter(|region| region.name.starts_with('/')).collect();
+
+        let mut desc = Writer { bytes: Vec::new(), little_endian, is_64 };
+        desc.word(files.len() as u64);
+        desc.word(PAGE_SIZE);
+        for file in files.iter() {
+            desc.word(file.range.start);
+            desc.word(file.range.end);
+            desc.word(file.offset / PAGE_SIZE);
+        }
+        for file in files.iter() {
+            desc.bytes.extend_from_slice(file.name.as_bytes());
+            desc.bytes.push(0);
+        }
+        desc.align(4);
+
+        let mut note = Writer { bytes: Vec::new(), little_endian, is_64 };
+        note.int(5, 4);
+        note.int(desc.bytes.len() as u64, 4);
+        note.int(NT_FILE as u64, 4);
+        note.bytes.extend_from_slice(b"CORE\0\0\0\0");
+        note.bytes.extend_from_slice(&desc.bytes);
+
+        let (header_size, program_header_size) = if is_64 { (64, 56) } else { (52, 32) };
+        let program_headers = 1 + loads.len();
+        let note_offset = header_size + program_header_size * program_headers;
+        let mut offset = (note_offset + note.bytes.len()).next_multiple_of(PAGE_SIZE as usize);
+
+        let mut elf = Writer { bytes: Vec::new(), little_endian, is_64 };
+        elf.bytes.extend_from_slice(&header[..16]);
+        elf.int(ET_CORE as u64, 2);
+        elf.bytes.extend_from_slice(&header[18..20]);
+        elf.int(1, 4);
+        elf.word(0);
+        elf.word(header_size as u64);
+        elf.word(0);
+        elf.bytes.extend_from_slice(flags);
+        elf.int(header_size as u64, 2);
+        elf.int(program_header_size as u64, 2);
+        elf.int(program_headers as u64, 2);
+        elf.int(0, 6);
+
+        let note_size = note.bytes.len() as u64;
+        elf.program_header(PT_NOTE, 0, note_offset as u64, 0, (note_size, 0));
+        for (region, bytes) in loads.iter() {
+            // permissions are listed as `rwxp`, with dashes for those that are missing
+            let flags = [(b'r', PF_R), (b'w', PF_W), (b'x', PF_X)]
+                .into_iter()
+                .zip(region.permissions.bytes())
+                .filter(|((flag, _), permission)| flag == permission)
+                .fold(0, |flags, ((_, bit), _)| flags | bit);
+
+            let size = (bytes.len() as u64, region.range.end - region.range.start);
+            elf.program_header(PT_LOAD, flags, offset as u64, region.range.start, size);
+            offset += bytes.len().next_multiple_of(PAGE_SIZE as usize);
+        }
+
+        elf.bytes.extend_from_slice(&note.bytes);
+        for (_, bytes) in loads.iter() {
+            elf.align(PAGE_SIZE as usize);
+            elf.bytes.extend_from_slice(bytes);
+        }
+
+        Ok(elf.bytes)
+    }
+}
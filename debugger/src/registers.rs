@@ -0,0 +1,271 @@
+//! Layout of the registers of each architecture, in the order GDB numbers them. Every target
+//! reports its registers laid out this way.
+
+use object::Architecture;
+
+/// Register as it's laid out among the others.
+struct Layout {
+    name: &'static str,
+    size: usize,
+    float: bool,
+}
+
+const fn reg(name: &'static str, size: usize) -> Layout {
+    Layout {
+        name,
+        size,
+        float: false,
+    }
+}
+
+/// Floating point or vector register.
+const fn fp(name: &'static str, size: usize) -> Layout {
+    Layout {
+        name,
+        size,
+        float: true,
+    }
+}
+
+#[rustfmt::skip]
+const X86_64: &[Layout] = &[
+    reg("rax", 8), reg("rbx", 8), reg("rcx", 8), reg("rdx", 8),
+    reg("rsi", 8), reg("rdi", 8), reg("rbp", 8), reg("rsp", 8),
+    reg("r8", 8), reg("r9", 8), reg("r10", 8), reg("r11", 8),
+    reg("r12", 8), reg("r13", 8), reg("r14", 8), reg("r15", 8),
+    reg("rip", 8), reg("eflags", 4),
+    reg("cs", 4), reg("ss", 4), reg("ds", 4), reg("es", 4), reg("fs", 4), reg("gs", 4),
+    fp("st0", 10), fp("st1", 10), fp("st2", 10), fp("st3", 10),
+    fp("st4", 10), fp("st5", 10), fp("st6", 10), fp("st7", 10),
+    fp("fctrl", 4), fp("fstat", 4), fp("ftag", 4), fp("fiseg", 4),
+    fp("fioff", 4), fp("foseg", 4), fp("fooff", 4), fp("fop", 4),
+    fp("xmm0", 16), fp("xmm1", 16), fp("xmm2", 16), fp("xmm3", 16),
+    fp("xmm4", 16), fp("xmm5", 16), fp("xmm6", 16), fp("xmm7", 16),
+    fp("xmm8", 16), fp("xmm9", 16), fp("xmm10", 16), fp("xmm11", 16),
+    fp("xmm12", 16), fp("xmm13", 16), fp("xmm14", 16), fp("xmm15", 16),
+    fp("mxcsr", 4),
+];
+
+#[rustfmt::skip]
+const I386: &[Layout] = &[
+    reg("eax", 4), reg("ecx", 4), reg("edx", 4), reg("ebx", 4),
+    reg("esp", 4), reg("ebp", 4), reg("esi", 4), reg("edi", 4),
+    reg("eip", 4), reg("eflags", 4),
+    reg("cs", 4), reg("ss", 4), reg("ds", 4), reg("es", 4), reg("fs", 4), reg("gs", 4),
+];
+
+#[rustfmt::skip]
+const AARCH64: &[Layout] = &[
+    reg("x0", 8), reg("x1", 8), reg("x2", 8), reg("x3", 8),
+    reg("x4", 8), reg("x5", 8), reg("x6", 8), reg("x7", 8),
+    reg("x8", 8), reg("x9", 8), reg("x10", 8), reg("x11", 8),
+    reg("x12", 8), reg("x13", 8), reg("x14", 8), reg("x15", 8),
+    reg("x16", 8), reg("x17", 8), reg("x18", 8), reg("x19", 8),
+    reg("x20", 8), reg("x21", 8), reg("x22", 8), reg("x23", 8),
+    reg("x24", 8), reg("x25", 8), reg("x26", 8), reg("x27", 8),
+    reg("x28", 8), reg("x29", 8), reg("x30", 8),
+    reg("sp", 8), reg("pc", 8), reg("cpsr", 4),
+    fp("v0", 16), fp("v1", 16), fp("v2", 16), fp("v3", 16),
+    fp("v4", 16), fp("v5", 16), fp("v6", 16), fp("v7", 16),
+    fp("v8", 16), fp("v9", 16), fp("v10", 16), fp("v11", 16),
+    fp("v12", 16), fp("v13", 16), fp("v14", 16), fp("v15", 16),
+    fp("v16", 16), fp("v17", 16), fp("v18", 16), fp("v19", 16),
+    fp("v20", 16), fp("v21", 16), fp("v22", 16), fp("v23", 16),
+    fp("v24", 16), fp("v25", 16), fp("v26", 16), fp("v27", 16),
+    fp("v28", 16), fp("v29", 16), fp("v30", 16), fp("v31", 16),
+    fp("fpsr", 4), fp("fpcr", 4),
+];
+
+#[rustfmt::skip]
+const ARM: &[Layout] = &[
+    reg("r0", 4), reg("r1", 4), reg("r2", 4), reg("r3", 4),
+    reg("r4", 4), reg("r5", 4), reg("r6", 4), reg("r7", 4),
+    reg("r8", 4), reg("r9", 4), reg("r10", 4), reg("r11", 4),
+    reg("r12", 4), reg("sp", 4), reg("lr", 4), reg("pc", 4),
+];
+
+#[rustfmt::skip]
+macro_rules! riscv {
+    ($size:expr) => {[
+        reg("zero", $size), reg("ra", $size), reg("sp", $size), reg("gp", $size),
+        reg("tp", $size), reg("t0", $size), reg("t1", $size), reg("t2", $size),
+        reg("fp", $size), reg("s1", $size), reg("a0", $size), reg("a1", $size),
+        reg("a2", $size), reg("a3", $size), reg("a4", $size), reg("a5", $size),
+        reg("a6", $size), reg("a7", $size), reg("s2", $size), reg("s3", $size),
+        reg("s4", $size), reg("s5", $size), reg("s6", $size), reg("s7", $size),
+        reg("s8", $size), reg("s9", $size), reg("s10", $size), reg("s11", $size),
+        reg("t3", $size), reg("t4", $size), reg("t5", $size), reg("t6", $size),
+        reg("pc", $size),
+    ]};
+}
+
+const RISCV32: &[Layout] = &riscv!(4);
+
+const RISCV64: &[Layout] = &riscv!(8);
+
+#[rustfmt::skip]
+macro_rules! mips {
+    ($size:expr) => {[
+        reg("zero", $size), reg("at", $size), reg("v0", $size), reg("v1", $size),
+        reg("a0", $size), reg("a1", $size), reg("a2", $size), reg("a3", $size),
+        reg("t0", $size), reg("t1", $size), reg("t2", $size), reg("t3", $size),
+        reg("t4", $size), reg("t5", $size), reg("t6", $size), reg("t7", $size),
+        reg("s0", $size), reg("s1", $size), reg("s2", $size), reg("s3", $size),
+        reg("s4", $size), reg("s5", $size), reg("s6", $size), reg("s7", $size),
+        reg("t8", $size), reg("t9", $size), reg("k0", $size), reg("k1", $size),
+        reg("gp", $size), reg("sp", $size), reg("fp", $size), reg("ra", $size),
+        reg("sr", $size), reg("lo", $size), reg("hi", $size), reg("bad", $size),
+        reg("cause", $size), reg("pc", $size),
+    ]};
+}
+
+const MIPS: &[Layout] = &mips!(4);
+
+const MIPS64: &[Layout] = &mips!(8);
+
+/// Registers of an architecture, in the order GDB numbers them.
+fn layout(arch: Architecture) -> Option<&'static [Layout]> {
+    Some(match arch {
+        Architecture::X86_64 => X86_64,
+        Architecture::I386 | Architecture::X86_64_X32 => I386,
+        Architecture::Aarch64 | Architecture::Aarch64_Ilp32 => AARCH64,
+        Architecture::Arm => ARM,
+        Architecture::Riscv32 => RISCV32,
+        Architecture::Riscv64 => RISCV64,
+        Architecture::Mips => MIPS,
+        Architecture::Mips64 => MIPS64,
+        _ => return None,
+    })
+}
+
+/// Whether the registers of an architecture are known.
+pub(crate) fn supported(arch: Architecture) -> bool {
+    layout(arch).is_some()
+}
+
+/// Names of the program counter and stack pointer.
+fn special(arch: Architecture) -> (&'static str, &'static str) {
+    match arch {
+        Architecture::X86_64 => ("rip", "rsp"),
+        Architecture::I386 | Architecture::X86_64_X32 => ("eip", "esp"),
+        _ => ("pc", "sp"),
+    }
+}
+
+/// Integer of at most 8 bytes.
+pub(crate) fn int(bytes: &[u8], little_endian: bool) -> u64 {
+    let bytes = bytes.iter();
+    match little_endian {
+        true => bytes.rev().fold(0, |value, &byte| value << 8 | byte as u64),
+        false => bytes.fold(0, |value, &byte| value << 8 | byte as u64),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Register {
+    pub name: &'static str,
+    /// Value in the byte order of the target.
+    pub bytes: Vec<u8>,
+    /// Whether it's a floating point or vector register.
+    pub float: bool,
+    little_endian: bool,
+}
+
+impl Register {
+    /// Value of registers that fit in 64 bits.
+    pub fn value(&self) -> Option<u64> {
+        if self.bytes.len() > 8 {
+            return None;
+        }
+
+        Some(int(&self.bytes, self.little_endian))
+    }
+
+    /// Value as hex, with the most significant byte first.
+    pub fn hex(&self) -> String {
+        let mut bytes = self.bytes.clone();
+        if self.little_endian {
+            bytes.reverse();
+        }
+
+        bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+}
+
+/// Registers of the thread that stopped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Registers {
+    pub registers: Vec<Register>,
+    pc: &'static str,
+    sp: &'static str,
+}
+
+impl Registers {
+    /// Split bytes into registers, targets might leave out the ones at the end.
+    pub(crate) fn parse(arch: Architecture, little_endian: bool, bytes: &[u8]) -> Option<Self> {
+        let mut registers = Vec::new();
+        let mut offset = 0;
+
+        for layout in layout(arch)? {
+            let bytes = match bytes.get(offset..offset + layout.size) {
+                Some(bytes) => bytes,
+                None => break,
+            };
+
+            registers.push(Register {
+                name: layout.name,
+                bytes: bytes.to_vec(),
+                float: layout.float,
+                little_endian,
+            });
+
+            offset += layout.size;
+        }
+
+        let (pc, sp) = special(arch);
+        Some(Self { registers, pc, sp })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Register> {
+        self.registers.iter().find(|reg| reg.name == name)
+    }
+
+    /// Address of the instruction that runs next.
+    pub fn pc(&self) -> Option<u64> {
+        self.get(self.pc)?.value()
+    }
+
+    pub fn sp(&self) -> Option<u64> {
+        self.get(self.sp)?.value()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn x86_64() {
+        let mut bytes = vec![0; 16 * 8];
+        bytes.extend(0x401000u64.to_le_bytes());
+        bytes.extend(0x246u32.to_le_bytes());
+        bytes[7 * 8] = 0x80;
+
+        let regs = Registers::parse(Architecture::X86_64, true, &bytes).unwrap();
+        assert_eq!(regs.registers.len(), 18);
+        assert_eq!(regs.pc(), Some(0x401000));
+        assert_eq!(regs.sp(), Some(0x80));
+        assert_eq!(regs.get("eflags").unwrap().hex(), "00000246");
+        assert!(regs.registers.iter().all(|reg| !reg.float));
+    }
+
+    #[test]
+    fn big_endian() {
+        let mut bytes = vec![0; 37 * 4];
+        bytes.extend(0xbfc00000u32.to_be_bytes());
+
+        let regs = Registers::parse(Architecture::Mips, false, &bytes).unwrap();
+        assert_eq!(regs.pc(), Some(0xbfc00000));
+        assert_eq!(regs.get("pc").unwrap().hex(), "bfc00000");
+    }
+}
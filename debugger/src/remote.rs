@@ -0,0 +1,523 @@
+use crate::memory::{self, Region};
+use crate::packet::{self, decode_hex, encode_hex, parse_hex};
+use crate::registers::{self, Registers};
+use crate::{Error, Interrupter, Stop, Target};
+use object::{Architecture, Endianness};
+use std::io::{BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// How often a corrupted packet is sent again.
+const RETRIES: usize = 3;
+
+/// Largest packet that's sent if the stub doesn't say what it accepts.
+const DEFAULT_PACKET_SIZE: usize = 0x400;
+
+/// How long to wait for a stub to accept the connection, before trying its next address.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Connection to a stub that controls the target.
+pub struct Remote {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    /// Whether packets have to be acknowledged, stubs usually allow turning this off.
+    ack: bool,
+    /// Largest packet the stub accepts.
+    packet_size: usize,
+    /// Whether the stub describes the memory of the target, which embedded stubs usually do.
+    memory_map: bool,
+    arch: Architecture,
+    endianness: Endianness,
+}
+
+/// Accesses of memory that trigger a watchpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Watch {
+    Write,
+    Read,
+    Access,
+}
+
+impl Watch {
+    /// Type of breakpoint in the requests that insert and remove it.
+    fn kind(self) -> u8 {
+        match self {
+            Self::Write => 2,
+            Self::Read => 3,
+            Self::Access => 4,
+        }
+    }
+}
+
+impl Remote {
+    pub fn connect(
+        addr: impl ToSocketAddrs,
+        arch: Architecture,
+        endianness: Endianness,
+    ) -> Result<Self, Error> {
+        if !registers::supported(arch) {
+            return Err(Error::UnknownArchitecture(arch));
+        }
+
+        let stream = Self::open(addr)?;
+        stream.set_nodelay(true)?;
+
+        let mut this = Self {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+            ack: true,
+            packet_size: DEFAULT_PACKET_SIZE,
+            memory_map: false,
+            arch,
+            endianness,
+        };
+
+        let features = this.request("qSupported:swbreak+;hwbreak+")?;
+        let mut no_ack = false;
+        for feature in features.split(|&byte| byte == b';') {
+            if let Some(size) = feature.strip_prefix(b"PacketSize=") {
+                this.packet_size = parse_hex(size).map_or(DEFAULT_PACKET_SIZE, |s| s as usize);
+            }
+
+            no_ack |= feature == b"QStartNoAckMode+";
+            this.memory_map |= feature == b"qXfer:memory-map:read+";
+        }
+
+        if no_ack && this.request("QStartNoAckMode")? == b"OK" {
+            this.ack = false;
+        }
+
+        Ok(this)
+    }
+
+    fn open(addr: impl ToSocketAddrs) -> Result<TcpStream, Error> {
+        let mut last_err = std::io::ErrorKind::AddrNotAvailable.into();
+
+        for addr in addr.to_socket_addrs()? {
+            match TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT) {
+                Ok(stream) => return Ok(stream),
+                Err(err) => last_err = err,
+            }
+        }
+
+        Err(Error::Io(last_err))
+    }
+
+    fn send(&mut self, data: &[u8]) -> Result<(), Error> {
+        let packet = packet::frame(data);
+
+        for _ in 0..RETRIES {
+            self.writer.write_all(&packet)?;
+
+            if !self.ack || self.acknowledged()? {
+                return Ok(());
+            }
+        }
+
+        Err(Error::Checksum)
+    }
+
+    /// Wait for the stub to either acknowledge the last packet or ask for it again.
+    fn acknowledged(&mut self) -> Result<bool, Error> {
+        let mut byte = [0];
+        loop {
+            self.reader.read_exact(&mut byte)?;
+            match byte[0] {
+                b'+' => return Ok(true),
+                b'-' => return Ok(false),
+                _ => continue,
+            }
+        }
+    }
+
+    fn receive(&mut self) -> Result<Vec<u8>, Error> {
+        for _ in 0..RETRIES {
+            match packet::read(&mut self.reader)? {
+                Some(data) => {
+                    if self.ack {
+                        self.writer.write_all(b"+")?;
+                    }
+
+                    return Ok(data);
+                }
+                None if self.ack => self.writer.write_all(b"-")?,
+                None => break,
+            }
+        }
+
+        Err(Error::Checksum)
+    }
+
+    fn request(&mut self, request: &str) -> Result<Vec<u8>, Error> {
+        self.send(request.as_bytes())?;
+        let reply = self.receive()?;
+
+        if let [b'E', errno @ ..] = &reply[..] {
+            if let Some(errno) = parse_hex(errno) {
+                return Err(Error::Remote(errno as u8));
+            }
+        }
+
+        Ok(reply)
+    }
+
+    /// Send a request that's answered with `OK`, or nothing if the stub doesn't support it.
+    fn command(&mut self, request: &str, name: &'static str) -> Result<(), Error> {
+        match &self.request(request)?[..] {
+            b"OK" => Ok(()),
+            b"" => Err(Error::Unsupported(name)),
+            reply => Err(Self::malformed(reply)),
+        }
+    }
+
+    fn malformed(reply: &[u8]) -> Error {
+        Error::Malformed(String::from_utf8_lossy(reply).into_owned())
+    }
+
+    /// Read an object the stub provides, such as its memory map.
+    fn transfer(&mut self, object: &str, annex: &str) -> Result<Vec<u8>, Error> {
+        let len = self.packet_size / 2;
+        let mut data = Vec::new();
+
+        loop {
+            let offset = data.len();
+            let reply = self.request(&format!("qXfer:{object}:read:{annex}:{offset:x},{len:x}"))?;
+
+            // `m` is followed by more data, `l` by the last of it
+            match reply.split_first() {
+                Some((b'm', chunk)) if !chunk.is_empty() => data.extend_from_slice(chunk),
+                Some((b'm' | b'l', chunk)) => {
+                    data.extend_from_slice(chunk);
+                    return Ok(data);
+                }
+                None => return Err(Error::Unsupported("transferring objects")),
+                Some(_) => return Err(Self::malformed(&reply)),
+            }
+        }
+    }
+
+    /// Send a host I/O request, which is answered with `F` followed by a result or `-1,errno`,
+    /// and the data that was read.
+    fn file_request(&mut self, request: &str) -> Result<(u64, Vec<u8>), Error> {
+        let reply = self.request(request)?;
+        let (result, data) = match reply.iter().position(|&byte| byte == b';') {
+            Some(pos) => (&reply[..pos], reply[pos + 1..].to_vec()),
+            None => (&reply[..], Vec::new()),
+        };
+
+        match result {
+            [] => Err(Error::Unsupported("reading files")),
+            [b'F', b'-', ..] => {
+                let errno = result.split(|&byte| byte == b',').nth(1).and_then(parse_hex);
+                Err(Error::Remote(errno.unwrap_or(0) as u8))
+            }
+            [b'F', value @ ..] => match parse_hex(value) {
+                Some(value) => Ok((value, data)),
+                None => Err(Self::malformed(&reply)),
+            },
+            _ => Err(Self::malformed(&reply)),
+        }
+    }
+
+    /// Read a file on the machine the stub runs on.
+    pub fn read_file(&mut self, path: &str) -> Result<Vec<u8>, Error> {
+        // the flags and mode are those of the protocol, which opens files read-only with 0
+        let path = encode_hex(path.as_bytes());
+        let (fd, _) = self.file_request(&format!("vFile:open:{path},0,0"))?;
+
+        // data in replies is escaped, so it might take up twice as much space
+        let len = self.packet_size / 2;
+        let mut contents = Vec::new();
+        let result = loop {
+            let offset = contents.len();
+            match self.file_request(&format!("vFile:pread:{fd:x},{len:x},{offset:x}")) {
+                Ok((_, data)) if data.is_empty() => break Ok(contents),
+                Ok((_, data)) => contents.extend(data),
+                Err(err) => break Err(err),
+            }
+        };
+
+        self.file_request(&format!("vFile:close:{fd:x}"))?;
+        result
+    }
+
+    /// Process that's debugged, for stubs that debug a process instead of a whole machine.
+    fn pid(&mut self) -> Result<u64, Error> {
+        let reply = self.request("qC")?;
+        let id = reply.strip_prefix(b"QC").ok_or_else(|| Self::malformed(&reply))?;
+
+        // stubs that debug multiple processes reply with `p<pid>.<tid>`
+        let id = id.strip_prefix(b"p").unwrap_or(id);
+        let pid = id.split(|&byte| byte == b'.').next().unwrap_or(id);
+        parse_hex(pid).ok_or_else(|| Self::malformed(&reply))
+    }
+
+    /// Why the target is stopped, which it is right after connecting.
+    pub fn halt_reason(&mut self) -> Result<Stop, Error> {
+        let reply = self.request("?")?;
+        Stop::parse(&reply)
+    }
+
+    /// Size of the instructions that breakpoints are placed on.
+    fn breakpoint_kind(&self) -> usize {
+        match self.arch {
+            Architecture::X86_64 | Architecture::X86_64_X32 | Architecture::I386 => 1,
+            _ => 4,
+        }
+    }
+
+    fn wait(&mut self) -> Result<Stop, Error> {
+        loop {
+            let reply = self.receive()?;
+
+            // output of the target while it runs
+            if let [b'O', hex @ ..] = &reply[..] {
+                if let Some(output) = decode_hex(hex) {
+                    log::complex!(
+                        w "[debugger] ",
+                        w String::from_utf8_lossy(&output).trim_end().to_string(),
+                    );
+                    continue;
+                }
+            }
+
+            return Stop::parse(&reply);
+        }
+    }
+}
+
+impl Target for Remote {
+    fn arch(&self) -> Architecture {
+        self.arch
+    }
+
+    fn endianness(&self) -> Endianness {
+        self.endianness
+    }
+
+    fn interrupter(&self) -> Result<Interrupter, Error> {
+        let writer = self.writer.try_clone()?;
+        Ok(Interrupter::new(move || {
+            (&writer).write_all(&[0x03])?;
+            Ok(())
+        }))
+    }
+
+    /// Regions of memory of the target, either from the stub's memory map or from the mappings
+    /// of the process that's debugged.
+    fn memory_map(&mut self) -> Result<Vec<Region>, Error> {
+        if self.memory_map {
+            let xml = self.transfer("memory-map", "")?;
+            return Ok(memory::parse_xml(&String::from_utf8_lossy(&xml)));
+        }
+
+        let pid = self.pid()?;
+        let maps = self.read_file(&format!("/proc/{pid}/maps"))?;
+        Ok(memory::parse_maps(&String::from_utf8_lossy(&maps)))
+    }
+
+    /// Read memory of the target, stopping early at memory that can't be read.
+    fn read_memory(&mut self, addr: u64, len: usize) -> Result<Vec<u8>, Error> {
+        // every byte is sent as two hex digits
+        let chunk_size = self.packet_size / 2;
+        let mut bytes = Vec::with_capacity(len);
+
+        while bytes.len() < len {
+            let addr = addr + bytes.len() as u64;
+            let count = std::cmp::min(len - bytes.len(), chunk_size);
+            let reply = match self.request(&format!("m{addr:x},{count:x}")) {
+                Ok(reply) => reply,
+                Err(Error::Remote(_)) if !bytes.is_empty() => break,
+                Err(err) => return Err(err),
+            };
+
+            let chunk = decode_hex(&reply).ok_or_else(|| Self::malformed(&reply))?;
+            if chunk.is_empty() {
+                break;
+            }
+
+            bytes.extend(chunk);
+        }
+
+        Ok(bytes)
+    }
+
+    fn write_memory(&mut self, addr: u64, bytes: &[u8]) -> Result<(), Error> {
+        // leave room for the address and length in front of the data
+        let chunk_size = (self.packet_size.saturating_sub(32) / 2).max(1);
+
+        for (idx, chunk) in bytes.chunks(chunk_size).enumerate() {
+            let addr = addr + (idx * chunk_size) as u64;
+            let request = format!("M{addr:x},{:x}:{}", chunk.len(), encode_hex(chunk));
+            self.command(&request, "writing memory")?;
+        }
+
+        Ok(())
+    }
+
+    fn registers(&mut self) -> Result<Registers, Error> {
+        let reply = self.request("g")?;
+        let bytes = decode_hex(&reply).ok_or_else(|| Self::malformed(&reply))?;
+        let little_endian = self.endianness == Endianness::Little;
+        let registers = Registers::parse(self.arch, little_endian, &bytes);
+        registers.ok_or(Error::UnknownArchitecture(self.arch))
+    }
+
+    fn insert_breakpoint(&mut self, addr: u64) -> Result<(), Error> {
+        let request = format!("Z0,{addr:x},{:x}", self.breakpoint_kind());
+        self.command(&request, "breakpoints")
+    }
+
+    fn remove_breakpoint(&mut self, addr: u64) -> Result<(), Error> {
+        let request = format!("z0,{addr:x},{:x}", self.breakpoint_kind());
+        self.command(&request, "breakpoints")
+    }
+
+    /// Stop the target when memory is accessed, which the hardware of the target does.
+    fn insert_watchpoint(&mut self, addr: u64, len: usize, watch: Watch) -> Result<(), Error> {
+        let request = format!("Z{},{addr:x},{len:x}", watch.kind());
+        self.command(&request, "watchpoints")
+    }
+
+    fn remove_watchpoint(&mut self, addr: u64, len: usize, watch: Watch) -> Result<(), Error> {
+        let request = format!("z{},{addr:x},{len:x}", watch.kind());
+        self.command(&request, "watchpoints")
+    }
+
+    /// Run until the target stops.
+    fn resume(&mut self) -> Result<Stop, Error> {
+        self.send(b"c")?;
+        self.wait()
+    }
+
+    /// Run a single instruction.
+    fn step(&mut self) -> Result<Stop, Error> {
+        self.send(b"s")?;
+        self.wait()
+    }
+
+    /// Stop debugging, letting the target continue.
+    fn detach(&mut self) -> Result<(), Error> {
+        self.command("D", "detaching")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread::JoinHandle;
+
+    /// Stub that answers requests with fixed replies, returning the requests it got.
+    ///
+    /// Replies are matched by the start of the request, `|` separates packets sent in a row.
+    fn stub(replies: &'static [(&'static str, &'static str)]) -> (u16, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let handle = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut writer = stream;
+            let mut requests = Vec::new();
+            let mut ack = true;
+
+            while let Ok(Some(request)) = packet::read(&mut reader) {
+                let request = String::from_utf8(request).unwrap();
+                if ack {
+                    writer.write_all(b"+").unwrap();
+                }
+
+                let reply = replies
+                    .iter()
+                    .find(|(prefix, _)| request.starts_with(prefix))
+                    .map_or("", |(_, reply)| reply);
+
+                for reply in reply.split('|') {
+                    writer.write_all(&packet::frame(reply.as_bytes())).unwrap();
+                    if ack {
+                        reader.read_exact(&mut [0]).unwrap();
+                    }
+                }
+
+                ack &= request != "QStartNoAckMode";
+                requests.push(request);
+            }
+
+            requests
+        });
+
+        (port, handle)
+    }
+
+    #[test]
+    fn session() {
+        let (port, stub) = stub(&[
+            ("qSupported", "PacketSize=8;QStartNoAckMode+"),
+            ("QStartNoAckMode", "OK"),
+            ("?", "S05"),
+            ("m1000,4", "90909090"),
+            ("m1004,2", "c3"),
+            ("m1005,1", "E14"),
+            ("m2000,4", "00104000"),
+            ("m2004,4", "00000000"),
+            ("Z0,1000,1", "OK"),
+            ("c", "O68690a|T05thread:01;"),
+            ("D", "OK"),
+        ]);
+
+        let mut remote = Remote::connect(
+            ("127.0.0.1", port),
+            Architecture::X86_64,
+            Endianness::Little,
+        )
+        .unwrap();
+
+        assert!(!remote.ack);
+        assert_eq!(remote.packet_size, 8);
+        assert_eq!(remote.halt_reason().unwrap(), Stop::Signal(5));
+        assert_eq!(
+            remote.read_memory(0x1000, 6).unwrap(),
+            [0x90, 0x90, 0x90, 0x90, 0xc3]
+        );
+        assert_eq!(remote.read_pointers(0x2000, 1).unwrap(), [0x401000]);
+        remote.insert_breakpoint(0x1000).unwrap();
+        assert!(matches!(
+            remote.remove_breakpoint(0x1000),
+            Err(Error::Unsupported(_))
+        ));
+        assert_eq!(remote.resume().unwrap(), Stop::Signal(5));
+        remote.detach().unwrap();
+        drop(remote);
+
+        let requests = stub.join().unwrap();
+        assert_eq!(requests[0], "qSupported:swbreak+;hwbreak+");
+        assert_eq!(requests.last().unwrap(), "D");
+    }
+
+    #[test]
+    fn memory_map() {
+        let (port, stub) = stub(&[
+            ("qSupported", "PacketSize=400"),
+            ("qC", "QCp1f.1f"),
+            ("vFile:open:2f70726f632f33312f6d617073,", "F5"),
+            (
+                "vFile:pread:5,200,0",
+                "F2b;400000-401000 r-xp 00000000 08:01 1 /bin/a\n",
+            ),
+            ("vFile:pread:5,200,2b", "F0;"),
+            ("vFile:close:5", "F0"),
+        ]);
+
+        let mut remote =
+            Remote::connect(("127.0.0.1", port), Architecture::Arm, Endianness::Little).unwrap();
+
+        let regions = remote.memory_map().unwrap();
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].range, 0x400000..0x401000);
+        assert_eq!(regions[0].name, "/bin/a");
+
+        drop(remote);
+        assert_eq!(stub.join().unwrap().last().unwrap(), "vFile:close:5");
+    }
+}
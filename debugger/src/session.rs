@@ -0,0 +1,366 @@
+use crate::condition::Stopped;
+use crate::{Condition, Error, Interrupter, Module, Registers, Remote, Stop, Target, Trace, Watch};
+use object::{Architecture, Endianness};
+use std::collections::BTreeMap;
+use std::net::ToSocketAddrs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+/// Signal that stops the target after a breakpoint or step.
+const SIGTRAP: u8 = 5;
+
+/// Target that's being debugged, which runs on a thread of its own when it's resumed.
+pub struct Session {
+    target: Arc<Mutex<Box<dyn Target>>>,
+    interrupter: Interrupter,
+    /// Where the target stopped after it was resumed.
+    stops: Receiver<Result<Stop, Error>>,
+    sender: Sender<Result<Stop, Error>>,
+    running: bool,
+    /// Why the target stopped the last time.
+    stop: Stop,
+    /// Breakpoints by address, along with the condition under which they stop the target.
+    breakpoints: BTreeMap<u64, Option<Condition>>,
+    /// Watchpoints by address, along with how many bytes they watch.
+    watchpoints: BTreeMap<u64, (usize, Watch)>,
+    /// Instructions executed while tracing, during which the target is single-stepped instead
+    /// of resumed.
+    trace: Option<Arc<Mutex<Trace>>>,
+    /// Asks the thread that's tracing the target to stop, as it isn't running in between steps.
+    interrupted: Arc<AtomicBool>,
+    /// Modules the target has loaded.
+    modules: Vec<Module>,
+    /// Modules that were loaded since they were last taken.
+    loaded: Vec<Module>,
+}
+
+impl Session {
+    pub fn connect(
+        addr: impl ToSocketAddrs,
+        arch: Architecture,
+        endianness: Endianness,
+    ) -> Result<Self, Error> {
+        let mut remote = Remote::connect(addr, arch, endianness)?;
+        let stop = remote.halt_reason()?;
+        Self::new(Box::new(remote), stop)
+    }
+
+    /// Start a program on this machine and debug it, stopping before it runs any of its code.
+    #[cfg(all(target_os = "windows", target_arch = "x86_64"))]
+    pub fn launch(path: &Path) -> Result<Self, Error> {
+        let local = crate::windows::Local::launch(path)?;
+        Self::new(Box::new(local), Stop::Signal(SIGTRAP))
+    }
+
+    /// Start a program on this machine and debug it, which is only supported on 64-bit Windows.
+    #[cfg(not(all(target_os = "windows", target_arch = "x86_64")))]
+    pub fn launch(_path: &Path) -> Result<Self, Error> {
+        Err(Error::Unsupported("debugging programs on this machine"))
+    }
+
+    fn new(target: Box<dyn Target>, stop: Stop) -> Result<Self, Error> {
+        let interrupter = target.interrupter()?;
+        let (sender, stops) = mpsc::channel();
+
+        let mut this = Self {
+            target: Arc::new(Mutex::new(target)),
+            interrupter,
+            stops,
+            sender,
+            running: false,
+            stop,
+            breakpoints: BTreeMap::new(),
+            watchpoints: BTreeMap::new(),
+            trace: None,
+            interrupted: Arc::new(AtomicBool::new(false)),
+            modules: Vec::new(),
+            loaded: Vec::new(),
+        };
+
+        this.refresh_modules()?;
+        Ok(this)
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// Why the target stopped the last time.
+    pub fn stop(&self) -> Stop {
+        self.stop
+    }
+
+    /// The target, as long as it isn't running.
+    pub fn target(&self) -> Option<MutexGuard<'_, Box<dyn Target>>> {
+        match self.running {
+            true => None,
+            false => self.target.lock().ok(),
+        }
+    }
+
+    /// Modules the target has loaded.
+    pub fn modules(&self) -> &[Module] {
+        &self.modules
+    }
+
+    /// Modules that were loaded since this was last called, including the ones loaded before
+    /// the session started.
+    pub fn take_loaded(&mut self) -> Vec<Module> {
+        std::mem::take(&mut self.loaded)
+    }
+
+    /// Ask the target which modules it has loaded, remembering the ones that are new.
+    fn refresh_modules(&mut self) -> Result<(), Error> {
+        let modules = self.target.lock().unwrap().modules()?;
+        for module in modules.iter().filter(|module| !self.modules.contains(module)) {
+            self.loaded.push(module.clone());
+        }
+
+        self.modules = modules;
+        Ok(())
+    }
+
+    /// Run until the target stops, which is reported by [`Session::poll`]. Breakpoints with a
+    /// condition that doesn't hold let the target continue.
+    pub fn resume(&mut self) {
+        let breakpoints = self.breakpoints.clone();
+        let interrupted = Arc::clone(&self.interrupted);
+
+        let trace = match self.trace.clone() {
+            Some(trace) => trace,
+            None => {
+                return self.run(move |target| loop {
+                    let stop = target.resume()?;
+                    if stop != Stop::Signal(SIGTRAP) || interrupted.load(Ordering::Relaxed) {
+                        return Ok(stop);
+                    }
+
+                    let registers = target.registers()?;
+                    if !passes(target, registers, &breakpoints) {
+                        return Ok(stop);
+                    }
+                })
+            }
+        };
+
+        // breakpoints are checked after every step, as they don't trap when single-stepping
+        self.run(move |target| loop {
+            let stop = step_traced(target, &trace)?;
+            if stop != Stop::Signal(SIGTRAP) || interrupted.load(Ordering::Relaxed) {
+                return Ok(stop);
+            }
+
+            let registers = target.registers()?;
+            let pc = registers.pc();
+            let breakpoint = pc.is_some_and(|pc| breakpoints.contains_key(&pc));
+            if breakpoint && !passes(target, registers, &breakpoints) {
+                return Ok(stop);
+            }
+        });
+    }
+
+    /// Run a single instruction, which is reported by [`Session::poll`].
+    pub fn step(&mut self) {
+        match self.trace.clone() {
+            Some(trace) => self.run(move |target| step_traced(target, &trace)),
+            None => self.run(|target| target.step()),
+        }
+    }
+
+    fn run(&mut self, run: impl FnOnce(&mut dyn Target) -> Result<Stop, Error> + Send + 'static) {
+        if self.running || self.stop.is_exit() {
+            return;
+        }
+
+        self.running = true;
+        self.interrupted.store(false, Ordering::Relaxed);
+        let target = Arc::clone(&self.target);
+        let sender = self.sender.clone();
+        std::thread::spawn(move || {
+            let stop = run(&mut **target.lock().unwrap());
+            let _ = sender.send(stop);
+        });
+    }
+
+    /// Start recording the instructions that are executed, or stop and return what's recorded.
+    pub fn toggle_trace(&mut self) -> Result<Option<Trace>, Error> {
+        if self.running {
+            return Err(Error::Busy);
+        }
+
+        match self.trace.take() {
+            Some(trace) => Ok(Some(std::mem::take(&mut trace.lock().unwrap()))),
+            None => {
+                self.trace = Some(Arc::default());
+                Ok(None)
+            }
+        }
+    }
+
+    /// Instructions executed since tracing started, which keeps growing while it's running.
+    pub fn trace(&self) -> Option<MutexGuard<'_, Trace>> {
+        self.trace.as_ref().and_then(|trace| trace.lock().ok())
+    }
+
+    /// Stop the target while it's running.
+    pub fn interrupt(&self) -> Result<(), Error> {
+        if !self.running {
+            return Ok(());
+        }
+
+        // the target isn't running in between steps, or while a condition is evaluated
+        self.interrupted.store(true, Ordering::Relaxed);
+        match self.trace {
+            Some(_) => Ok(()),
+            None => self.interrupter.interrupt(),
+        }
+    }
+
+    /// Check whether the target stopped since it was resumed.
+    pub fn poll(&mut self) -> Option<Result<Stop, Error>> {
+        let stop = self.stops.try_recv().ok()?;
+        self.running = false;
+
+        let stop = match stop {
+            Ok(stop) if !stop.is_exit() => self.refresh_modules().map(|_| stop),
+            stop => stop,
+        };
+
+        if let Ok(stop) = stop {
+            self.stop = stop;
+        }
+
+        Some(stop)
+    }
+
+    pub fn breakpoints(&self) -> &BTreeMap<u64, Option<Condition>> {
+        &self.breakpoints
+    }
+
+    /// Insert a breakpoint, or remove it if there already is one. Returns whether it's inserted.
+    pub fn toggle_breakpoint(&mut self, addr: u64) -> Result<bool, Error> {
+        let mut target = match self.target() {
+            Some(target) => target,
+            None => return Err(Error::Busy),
+        };
+
+        if self.breakpoints.contains_key(&addr) {
+            target.remove_breakpoint(addr)?;
+            drop(target);
+            self.breakpoints.remove(&addr);
+            return Ok(false);
+        }
+
+        target.insert_breakpoint(addr)?;
+        drop(target);
+        self.breakpoints.insert(addr, None);
+        Ok(true)
+    }
+
+    /// Only stop at a breakpoint when a condition holds, inserting the breakpoint if there
+    /// isn't one yet.
+    pub fn set_condition(&mut self, addr: u64, condition: Option<Condition>) -> Result<(), Error> {
+        let mut target = match self.target() {
+            Some(target) => target,
+            None => return Err(Error::Busy),
+        };
+
+        // registers are checked up front, unlike memory which might only be readable later on
+        if let Some(condition) = condition.as_ref() {
+            let registers = target.registers()?;
+            for name in condition.registers() {
+                if registers.get(name).is_none() {
+                    return Err(Error::Expression(format!("Register '{name}' is unknown")));
+                }
+            }
+        }
+
+        if !self.breakpoints.contains_key(&addr) {
+            target.insert_breakpoint(addr)?;
+        }
+
+        drop(target);
+        self.breakpoints.insert(addr, condition);
+        Ok(())
+    }
+
+    pub fn watchpoints(&self) -> &BTreeMap<u64, (usize, Watch)> {
+        &self.watchpoints
+    }
+
+    /// Insert a watchpoint, or remove the one at an address. Returns whether it's inserted.
+    pub fn toggle_watchpoint(
+        &mut self,
+        addr: u64,
+        len: usize,
+        watch: Watch,
+    ) -> Result<bool, Error> {
+        let mut target = match self.target() {
+            Some(target) => target,
+            None => return Err(Error::Busy),
+        };
+
+        if let Some(&(len, watch)) = self.watchpoints.get(&addr) {
+            target.remove_watchpoint(addr, len, watch)?;
+            drop(target);
+            self.watchpoints.remove(&addr);
+            return Ok(false);
+        }
+
+        target.insert_watchpoint(addr, len, watch)?;
+        drop(target);
+        self.watchpoints.insert(addr, (len, watch));
+        Ok(true)
+    }
+
+    /// Stop debugging, letting the target continue.
+    pub fn detach(mut self) -> Result<(), Error> {
+        // the target has to be stopped before it can be detached from
+        if self.running {
+            self.interrupt()?;
+            let stop = self.stops.recv_timeout(Duration::from_secs(1)).map_err(|_| Error::Busy)?;
+            self.stop = stop?;
+        }
+
+        if self.stop.is_exit() {
+            return Ok(());
+        }
+
+        let mut target = self.target.lock().unwrap();
+        target.detach()
+    }
+}
+
+/// Program counter of a target that's stopped.
+fn pc(target: &mut dyn Target) -> Result<u64, Error> {
+    target
+        .registers()?
+        .pc()
+        .ok_or(Error::Unsupported("reading the program counter"))
+}
+
+/// Whether the target is at a breakpoint with a condition that doesn't hold, so it continues.
+/// Conditions that can't be evaluated, such as when memory can't be read, stop it.
+fn passes(
+    target: &mut dyn Target,
+    registers: Registers,
+    breakpoints: &BTreeMap<u64, Option<Condition>>,
+) -> bool {
+    let condition = match registers.pc().and_then(|pc| breakpoints.get(&pc)) {
+        Some(Some(condition)) => condition,
+        _ => return false,
+    };
+
+    let mut state = Stopped { target, registers };
+    matches!(condition.holds(&mut state), Ok(false))
+}
+
+/// Run a single instruction, recording it's address.
+fn step_traced(target: &mut dyn Target, trace: &Mutex<Trace>) -> Result<Stop, Error> {
+    let pc = pc(target)?;
+    trace.lock().unwrap().record(pc);
+    target.step()
+}
@@ -0,0 +1,92 @@
+//! Requests that every kind of target answers, however it's debugged.
+
+use crate::registers::{self, Registers};
+use crate::{Error, Module, Region, Stop, Watch};
+use object::{Architecture, Endianness};
+
+/// Handle for stopping the target while it runs, without waiting for the [`Target`].
+pub struct Interrupter(Box<dyn Fn() -> Result<(), Error> + Send>);
+
+impl Interrupter {
+    pub(crate) fn new(interrupt: impl Fn() -> Result<(), Error> + Send + 'static) -> Self {
+        Self(Box::new(interrupt))
+    }
+
+    pub fn interrupt(&self) -> Result<(), Error> {
+        (self.0)()
+    }
+}
+
+/// Target that's being debugged. Requests other than [`Target::interrupter`] are only answered
+/// while it's stopped.
+pub trait Target: Send {
+    fn arch(&self) -> Architecture;
+
+    fn endianness(&self) -> Endianness;
+
+    fn interrupter(&self) -> Result<Interrupter, Error>;
+
+    /// Regions of memory of the target.
+    fn memory_map(&mut self) -> Result<Vec<Region>, Error>;
+
+    /// Files the target has loaded, for targets that report them.
+    fn modules(&mut self) -> Result<Vec<Module>, Error> {
+        Ok(Vec::new())
+    }
+
+    /// Read memory of the target, stopping early at memory that can't be read.
+    fn read_memory(&mut self, addr: u64, len: usize) -> Result<Vec<u8>, Error>;
+
+    fn write_memory(&mut self, addr: u64, bytes: &[u8]) -> Result<(), Error>;
+
+    /// Registers of the thread that stopped.
+    fn registers(&mut self) -> Result<Registers, Error>;
+
+    fn insert_breakpoint(&mut self, addr: u64) -> Result<(), Error>;
+
+    fn remove_breakpoint(&mut self, addr: u64) -> Result<(), Error>;
+
+    /// Stop the target when memory is accessed, which the hardware of the target does.
+    fn insert_watchpoint(&mut self, addr: u64, len: usize, watch: Watch) -> Result<(), Error>;
+
+    fn remove_watchpoint(&mut self, addr: u64, len: usize, watch: Watch) -> Result<(), Error>;
+
+    /// Run until the target stops.
+    fn resume(&mut self) -> Result<Stop, Error>;
+
+    /// Run a single instruction.
+    fn step(&mut self) -> Result<Stop, Error>;
+
+    /// Stop debugging, letting the target continue.
+    fn detach(&mut self) -> Result<(), Error>;
+
+    /// Read consecutive pointer sized values, such as the ones on the stack.
+    fn read_pointers(&mut self, addr: u64, count: usize) -> Result<Vec<u64>, Error> {
+        let width = pointer_width(self.arch());
+        let little_endian = self.endianness() == Endianness::Little;
+        let bytes = self.read_memory(addr, count * width)?;
+
+        let pointers = bytes.chunks_exact(width).map(|ptr| registers::int(ptr, little_endian));
+        Ok(pointers.collect())
+    }
+
+    /// Read an integer of some size in bytes, or a pointer.
+    fn read_int(&mut self, addr: u64, size: Option<usize>) -> Result<u64, Error> {
+        let size = size.unwrap_or_else(|| pointer_width(self.arch()));
+        let bytes = self.read_memory(addr, size)?;
+        if bytes.len() < size {
+            return Err(Error::Expression(format!(
+                "Memory at {addr:#x} can't be read"
+            )));
+        }
+
+        Ok(registers::int(
+            &bytes,
+            self.endianness() == Endianness::Little,
+        ))
+    }
+}
+
+fn pointer_width(arch: Architecture) -> usize {
+    arch.address_size().map_or(8, |size| size.bytes() as usize)
+}
@@ -0,0 +1,110 @@
+//! Addresses of the instructions a target executed, recorded by single-stepping it.
+
+use crate::drcov::{Block, Coverage};
+use crate::Module;
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Trace {
+    /// Addresses in the order they were executed.
+    addrs: Vec<u64>,
+}
+
+impl Trace {
+    pub fn record(&mut self, addr: u64) {
+        self.addrs.push(addr);
+    }
+
+    pub fn addrs(&self) -> &[u64] {
+        &self.addrs
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.addrs.is_empty()
+    }
+
+    /// How many times each address was executed.
+    pub fn hits(&self) -> BTreeMap<u64, usize> {
+        let mut hits = BTreeMap::new();
+        for &addr in self.addrs.iter() {
+            *hits.entry(addr).or_insert(0) += 1;
+        }
+        hits
+    }
+
+    /// Instructions in the blocks of a module that ran, given the addresses of the instructions
+    /// in a range of it.
+    pub fn from_coverage(
+        coverage: &Coverage,
+        module: usize,
+        base: u64,
+        mut instructions: impl FnMut(Range<u64>) -> Vec<u64>,
+    ) -> Self {
+        let mut addrs = Vec::new();
+        for block in coverage.blocks_of(module) {
+            addrs.extend(instructions(base + block.start..base + block.end));
+        }
+
+        Self { addrs }
+    }
+
+    /// Coverage of a single module in which every instruction that ran is a block, which keeps
+    /// the order they were executed in.
+    pub fn to_coverage(&self, module: Module, mut width: impl FnMut(u64) -> u16) -> Coverage {
+        let blocks = self
+            .addrs
+            .iter()
+            .filter(|&&addr| (module.base..module.end).contains(&addr))
+            .map(|&addr| Block {
+                start: (addr - module.base) as u32,
+                size: width(addr),
+                module: 0,
+            })
+            .collect();
+
+        Coverage {
+            modules: vec![module],
+            blocks,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coverage() {
+        let mut trace = Trace::default();
+        for addr in [0x1000, 0x1004, 0x1000, 0x1004, 0x9000] {
+            trace.record(addr);
+        }
+
+        assert_eq!(trace.hits()[&0x1004], 2);
+
+        let module = Module {
+            base: 0x1000,
+            end: 0x2000,
+            path: "/bin/true".to_string(),
+        };
+
+        // the address outside of the module is dropped
+        let coverage = trace.to_coverage(module, |_| 4);
+        assert_eq!(coverage.blocks.len(), 4);
+        assert_eq!(
+            coverage.blocks[1],
+            Block {
+                start: 4,
+                size: 4,
+                module: 0
+            }
+        );
+
+        // instructions are 2 bytes wide
+        let replayed =
+            Trace::from_coverage(&coverage, 0, 0x400000, |range| range.step_by(2).collect());
+        assert_eq!(replayed.addrs()[..3], [0x400000, 0x400002, 0x400004]);
+        assert_eq!(replayed.hits()[&0x400006], 2);
+    }
+}
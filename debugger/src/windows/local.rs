@@ -0,0 +1,930 @@
+//! Target that debugs a program on this machine, through the Win32 debug API.
+
+use super::{image_size, permissions, signal, watch_control, watchpoint_hit, PAGE_SIZE, SIGTRAP};
+use crate::registers::Registers;
+use crate::{Error, Interrupter, Module, Region, Stop, Target, Watch};
+use object::{Architecture, Endianness, Object};
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::c_void;
+use std::os::windows::ffi::OsStrExt;
+use std::path::Path;
+use std::sync::mpsc::{self, Sender};
+
+const INT3: u8 = 0xCC;
+
+/// Trap flag of `eflags`, which makes the thread stop after running an instruction.
+const TRAP_FLAG: u32 = 0x100;
+
+type Request = Box<dyn FnOnce(&mut Debuggee) + Send>;
+
+/// Program that runs on this machine, debugged by a thread of its own.
+pub(crate) struct Local {
+    pid: u32,
+    requests: Sender<Request>,
+}
+
+impl Local {
+    /// Start a program, returning once it's stopped by the loader before running any of its code.
+    pub fn launch(path: &Path) -> Result<Self, Error> {
+        // only the registers of 64-bit processes are read, as those of others are emulated
+        let data = std::fs::read(path)?;
+        if let Ok(obj) = object::File::parse(&data[..]) {
+            if obj.architecture() != Architecture::X86_64 {
+                return Err(Error::UnknownArchitecture(obj.architecture()));
+            }
+        }
+
+        let (requests, receiver) = mpsc::channel::<Request>();
+        let (started, start) = mpsc::channel();
+        let path = path.to_path_buf();
+
+        std::thread::spawn(move || {
+            let mut debuggee = match Debuggee::create(&path) {
+                Ok(debuggee) => debuggee,
+                Err(err) => {
+                    let _ = started.send(Err(err));
+                    return;
+                }
+            };
+
+            let _ = started.send(Ok(debuggee.pid));
+            for request in receiver {
+                request(&mut debuggee);
+                if debuggee.detached {
+                    break;
+                }
+            }
+        });
+
+        let pid = start.recv().map_err(|_| gone())??;
+        Ok(Self { pid, requests })
+    }
+
+    /// Have the thread that debugs the process make a request, waiting for its result.
+    fn call<T: Send + 'static>(
+        &self,
+        request: impl FnOnce(&mut Debuggee) -> Result<T, Error> + Send + 'static,
+    ) -> Result<T, Error> {
+        let (sender, reply) = mpsc::channel();
+        let request = Box::new(move |debuggee: &mut Debuggee| {
+            let _ = sender.send(request(debuggee));
+        });
+
+        self.requests.send(request).map_err(|_| gone())?;
+        reply.recv().map_err(|_| gone())?
+    }
+}
+
+/// The thread that debugs the process is gone, which happens after detaching.
+fn gone() -> Error {
+    Error::Io(std::io::ErrorKind::BrokenPipe.into())
+}
+
+impl Target for Local {
+    fn arch(&self) -> Architecture {
+        Architecture::X86_64
+    }
+
+    fn endianness(&self) -> Endianness {
+        Endianness::Little
+    }
+
+    fn interrupter(&self) -> Result<Interrupter, Error> {
+        let process = Handle::open(self.pid)?;
+        Ok(Interrupter::new(move || {
+            // this starts a thread in the process that hits a breakpoint
+            match unsafe { DebugBreakProcess(process.0) } {
+                0 => Err(Error::Io(std::io::Error::last_os_error())),
+                _ => Ok(()),
+            }
+        }))
+    }
+
+    fn memory_map(&mut self) -> Result<Vec<Region>, Error> {
+        self.call(|debuggee| Ok(debuggee.memory_map()))
+    }
+
+    fn modules(&mut self) -> Result<Vec<Module>, Error> {
+        self.call(|debuggee| Ok(debuggee.modules.clone()))
+    }
+
+    fn read_memory(&mut self, addr: u64, len: usize) -> Result<Vec<u8>, Error> {
+        self.call(move |debuggee| debuggee.read_memory(addr, len))
+    }
+
+    fn write_memory(&mut self, addr: u64, bytes: &[u8]) -> Result<(), Error> {
+        let bytes = bytes.to_vec();
+        self.call(move |debuggee| debuggee.write_memory(addr, &bytes))
+    }
+
+    fn registers(&mut self) -> Result<Registers, Error> {
+        self.call(|debuggee| debuggee.registers())
+    }
+
+    fn insert_breakpoint(&mut self, addr: u64) -> Result<(), Error> {
+        self.call(move |debuggee| debuggee.insert_breakpoint(addr))
+    }
+
+    fn remove_breakpoint(&mut self, addr: u64) -> Result<(), Error> {
+        self.call(move |debuggee| debuggee.remove_breakpoint(addr))
+    }
+
+    fn insert_watchpoint(&mut self, addr: u64, len: usize, watch: Watch) -> Result<(), Error> {
+        self.call(move |debuggee| debuggee.insert_watchpoint(addr, len, watch))
+    }
+
+    fn remove_watchpoint(&mut self, addr: u64, len: usize, watch: Watch) -> Result<(), Error> {
+        self.call(move |debuggee| debuggee.remove_watchpoint(addr, len, watch))
+    }
+
+    fn resume(&mut self) -> Result<Stop, Error> {
+        self.call(|debuggee| debuggee.run(false))
+    }
+
+    fn step(&mut self) -> Result<Stop, Error> {
+        self.call(|debuggee| debuggee.run(true))
+    }
+
+    fn detach(&mut self) -> Result<(), Error> {
+        self.call(|debuggee| debuggee.detach())
+    }
+}
+
+/// Handle that's closed once it's dropped.
+struct Handle(isize);
+
+impl Handle {
+    fn open(pid: u32) -> Result<Self, Error> {
+        match unsafe { OpenProcess(PROCESS_ALL_ACCESS, 0, pid) } {
+            0 => Err(Error::Io(std::io::Error::last_os_error())),
+            handle => Ok(Self(handle)),
+        }
+    }
+}
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        unsafe { CloseHandle(self.0) };
+    }
+}
+
+/// Process that's debugged, only accessed by the thread that started it.
+struct Debuggee {
+    pid: u32,
+    process: Handle,
+    /// Threads by their id. Handles of threads are closed by the system once they exit.
+    threads: HashMap<u32, isize>,
+    /// Thread that reported the last event, which is the one that stopped.
+    thread: u32,
+    /// Event the process waits on to be continued, along with how it's continued.
+    pending: Option<(u32, u32, u32)>,
+    /// Breakpoints by address, along with the byte that they replaced.
+    breakpoints: BTreeMap<u64, u8>,
+    /// Breakpoint that's removed for a single step, as the thread stopped at it.
+    stepping_over: Option<u64>,
+    /// Whether the thread runs a single instruction, instead of running until it stops.
+    stepping: bool,
+    /// Watchpoints in each of the debug registers.
+    watchpoints: [Option<(u64, usize, Watch)>; 4],
+    modules: Vec<Module>,
+    detached: bool,
+}
+
+impl Debuggee {
+    fn create(path: &Path) -> Result<Self, Error> {
+        let path = path.as_os_str().encode_wide();
+        let mut command_line: Vec<u16> = "\"".encode_utf16().chain(path).collect();
+        command_line.extend("\"\0".encode_utf16());
+
+        let startup_info = StartupInfo {
+            size: std::mem::size_of::<StartupInfo>() as u32,
+            ..unsafe { std::mem::zeroed() }
+        };
+
+        let mut info: ProcessInformation = unsafe { std::mem::zeroed() };
+        let created = unsafe {
+            CreateProcessW(
+                std::ptr::null(),
+                command_line.as_mut_ptr(),
+                std::ptr::null(),
+                std::ptr::null(),
+                0,
+                DEBUG_ONLY_THIS_PROCESS | CREATE_NEW_CONSOLE,
+                std::ptr::null(),
+                std::ptr::null(),
+                &startup_info,
+                &mut info,
+            )
+        };
+
+        if created == 0 {
+            return Err(Error::Io(std::io::Error::last_os_error()));
+        }
+
+        drop(Handle(info.thread));
+        let mut this = Self {
+            pid: info.pid,
+            process: Handle(info.process),
+            threads: HashMap::new(),
+            thread: info.tid,
+            pending: None,
+            breakpoints: BTreeMap::new(),
+            stepping_over: None,
+            stepping: false,
+            watchpoints: [None; 4],
+            modules: Vec::new(),
+            detached: false,
+        };
+
+        // the loader stops the process once it's loaded the libraries the program imports
+        match this.wait()? {
+            Stop::Signal(_) => Ok(this),
+            stop => Err(Error::Io(std::io::Error::other(format!(
+                "Program {stop} before it started"
+            )))),
+        }
+    }
+
+    /// Continue the event the process is stopped at, and wait until it stops again.
+    fn wait(&mut self) -> Result<Stop, Error> {
+        loop {
+            if let Some((pid, tid, status)) = self.pending.take() {
+                if unsafe { ContinueDebugEvent(pid, tid, status) } == 0 {
+                    return Err(Error::Io(std::io::Error::last_os_error()));
+                }
+            }
+
+            let mut event: DebugEvent = unsafe { std::mem::zeroed() };
+            if unsafe { WaitForDebugEvent(&mut event, INFINITE) } == 0 {
+                return Err(Error::Io(std::io::Error::last_os_error()));
+            }
+
+            self.thread = event.tid;
+            self.pending = Some((event.pid, event.tid, DBG_CONTINUE));
+
+            match event.code {
+                CREATE_PROCESS_DEBUG_EVENT => {
+                    let info = unsafe { event.info.create_process };
+                    self.threads.insert(event.tid, info.thread);
+                    self.apply_watchpoints(info.thread);
+                    self.load_module(info.file, info.base);
+                }
+                CREATE_THREAD_DEBUG_EVENT => {
+                    let info = unsafe { event.info.create_thread };
+                    self.threads.insert(event.tid, info.thread);
+                    self.apply_watchpoints(info.thread);
+                }
+                EXIT_THREAD_DEBUG_EVENT => {
+                    self.threads.remove(&event.tid);
+                }
+                LOAD_DLL_DEBUG_EVENT => {
+                    let info = unsafe { event.info.load_dll };
+                    self.load_module(info.file, info.base);
+                }
+                UNLOAD_DLL_DEBUG_EVENT => {
+                    let info = unsafe { event.info.unload_dll };
+                    self.modules.retain(|module| module.base != info.base);
+                }
+                OUTPUT_DEBUG_STRING_EVENT => {
+                    let info = unsafe { event.info.debug_string };
+                    self.output(info);
+                }
+                EXCEPTION_DEBUG_EVENT => {
+                    let info = unsafe { event.info.exception };
+                    if let Some(stop) = self.exception(info)? {
+                        return Ok(stop);
+                    }
+                }
+                EXIT_PROCESS_DEBUG_EVENT => {
+                    let info = unsafe { event.info.exit_process };
+                    return Ok(Stop::Exited(info.exit_code as u8));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Handle an exception, returning how the process stopped unless it's passed on to the
+    /// program.
+    fn exception(&mut self, info: ExceptionInfo) -> Result<Option<Stop>, Error> {
+        let record = info.record;
+        let addr = record.address as u64;
+
+        match record.code {
+            EXCEPTION_BREAKPOINT => {
+                // the thread stopped after the breakpoint, it runs the replaced instruction next
+                if self.breakpoints.contains_key(&addr) {
+                    let mut context = self.context()?;
+                    context.rip = addr;
+                    self.set_context(self.thread(), &context)?;
+                }
+
+                Ok(Some(Stop::Signal(SIGTRAP)))
+            }
+            EXCEPTION_SINGLE_STEP => {
+                let stepped_over = self.stepping_over.take();
+                if let Some(addr) = stepped_over {
+                    if self.breakpoints.contains_key(&addr) {
+                        self.write_process(addr, &[INT3])?;
+                    }
+                }
+
+                // debug registers say which of the watchpoints was hit
+                let mut context = self.context()?;
+                let hit = watchpoint_hit(context.dr[4]);
+                if let Some((addr, ..)) = hit.and_then(|idx| self.watchpoints[idx]) {
+                    context.dr[4] = 0;
+                    self.set_context(self.thread(), &context)?;
+                    return Ok(Some(Stop::Watchpoint(addr)));
+                }
+
+                // stepping over a breakpoint while resuming continues running
+                match stepped_over.is_some() && !self.stepping {
+                    true => Ok(None),
+                    false => Ok(Some(Stop::Signal(SIGTRAP))),
+                }
+            }
+            // exceptions are handled by the program first, it only stops if it doesn't
+            _ if info.first_chance != 0 => {
+                self.pass_exception();
+                Ok(None)
+            }
+            code => {
+                // continuing lets the system terminate the process
+                self.pass_exception();
+                Ok(Some(Stop::Signal(signal(code))))
+            }
+        }
+    }
+
+    /// Continue the exception the process is stopped at by letting the program handle it.
+    fn pass_exception(&mut self) {
+        if let Some((_, _, status)) = self.pending.as_mut() {
+            *status = DBG_EXCEPTION_NOT_HANDLED;
+        }
+    }
+
+    /// Remember a module that was loaded, closing the handle to its file.
+    fn load_module(&mut self, file: isize, base: usize) {
+        if file == 0 {
+            return;
+        }
+
+        let file = Handle(file);
+        let mut path = vec![0u16; 0x8000];
+        let len = unsafe { GetFinalPathNameByHandleW(file.0, path.as_mut_ptr(), 0x8000, 0) };
+        let path = String::from_utf16_lossy(&path[..(len as usize).min(path.len())]);
+        let path = path.strip_prefix(r"\\?\").unwrap_or(&path).to_string();
+
+        // the headers of the PE file are mapped along with the rest of it
+        let base = base as u64;
+        let size = image_size(base, |addr, len| self.read_process(addr, len).ok());
+        let end = base.saturating_add(size.unwrap_or(PAGE_SIZE));
+        self.modules.push(Module { base, end, path });
+    }
+
+    /// Print a string the program sent to the debugger.
+    fn output(&mut self, info: DebugStringInfo) {
+        let unicode = info.unicode != 0;
+        let len = info.len as usize * if unicode { 2 } else { 1 };
+        let bytes = match self.read_process(info.data as u64, len) {
+            Ok(bytes) => bytes,
+            Err(..) => return,
+        };
+
+        let output = match unicode {
+            true => {
+                let wide: Vec<u16> =
+                    bytes.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect();
+                String::from_utf16_lossy(&wide)
+            }
+            false => String::from_utf8_lossy(&bytes).into_owned(),
+        };
+
+        log::complex!(
+            w "[debugger] ",
+            w output.trim_end_matches('\0').trim_end().to_string(),
+        );
+    }
+
+    fn thread(&self) -> isize {
+        self.threads.get(&self.thread).copied().unwrap_or_default()
+    }
+
+    fn context(&self) -> Result<Context, Error> {
+        let mut context: Context = unsafe { std::mem::zeroed() };
+        context.flags = CONTEXT_ALL;
+
+        match unsafe { GetThreadContext(self.thread(), &mut context) } {
+            0 => Err(Error::Io(std::io::Error::last_os_error())),
+            _ => Ok(context),
+        }
+    }
+
+    fn set_context(&self, thread: isize, context: &Context) -> Result<(), Error> {
+        match unsafe { SetThreadContext(thread, context) } {
+            0 => Err(Error::Io(std::io::Error::last_os_error())),
+            _ => Ok(()),
+        }
+    }
+
+    /// Read memory as it's in the process, including the breakpoints.
+    fn read_process(&self, addr: u64, len: usize) -> Result<Vec<u8>, Error> {
+        let mut bytes = Vec::with_capacity(len);
+
+        while bytes.len() < len {
+            let addr = addr + bytes.len() as u64;
+            let count = std::cmp::min(len - bytes.len(), (PAGE_SIZE - addr % PAGE_SIZE) as usize);
+            let mut chunk = vec![0; count];
+            let mut read = 0;
+
+            let ok = unsafe {
+                ReadProcessMemory(
+                    self.process.0,
+                    addr as *const c_void,
+                    chunk.as_mut_ptr() as *mut c_void,
+                    count,
+                    &mut read,
+                )
+            };
+
+            match ok {
+                0 if bytes.is_empty() => return Err(Error::Io(std::io::Error::last_os_error())),
+                0 => break,
+                _ => bytes.extend_from_slice(&chunk[..read]),
+            }
+        }
+
+        Ok(bytes)
+    }
+
+    fn write_process(&self, addr: u64, bytes: &[u8]) -> Result<(), Error> {
+        let mut written = 0;
+        let ok = unsafe {
+            WriteProcessMemory(
+                self.process.0,
+                addr as *mut c_void,
+                bytes.as_ptr() as *const c_void,
+                bytes.len(),
+                &mut written,
+            )
+        };
+
+        if ok == 0 {
+            return Err(Error::Io(std::io::Error::last_os_error()));
+        }
+
+        unsafe { FlushInstructionCache(self.process.0, addr as *const c_void, bytes.len()) };
+        Ok(())
+    }
+
+    /// Read memory, showing the bytes that breakpoints replaced.
+    fn read_memory(&self, addr: u64, len: usize) -> Result<Vec<u8>, Error> {
+        let mut bytes = self.read_process(addr, len)?;
+        let end = addr + bytes.len() as u64;
+
+        for (&bp, &byte) in self.breakpoints.range(addr..end) {
+            bytes[(bp - addr) as usize] = byte;
+        }
+
+        Ok(bytes)
+    }
+
+    /// Write memory, keeping the breakpoints that are written over.
+    fn write_memory(&mut self, addr: u64, bytes: &[u8]) -> Result<(), Error> {
+        self.write_process(addr, bytes)?;
+
+        let end = addr + bytes.len() as u64;
+        let written: Vec<u64> = self.breakpoints.range(addr..end).map(|(&bp, _)| bp).collect();
+        for bp in written {
+            self.breakpoints.insert(bp, bytes[(bp - addr) as usize]);
+            self.write_process(bp, &[INT3])?;
+        }
+
+        Ok(())
+    }
+
+    fn registers(&self) -> Result<Registers, Error> {
+        let context = self.context()?;
+        let fx = &context.float_save;
+        let half = |offset: usize| u16::from_le_bytes([fx[offset], fx[offset + 1]]) as u32;
+        let word = |offset: usize| u32::from_le_bytes(fx[offset..offset + 4].try_into().unwrap());
+
+        // registers are laid out as they are in the reply to `g`
+        let mut bytes = Vec::new();
+        for idx in [0, 3, 1, 2, 6, 7, 5, 4, 8, 9, 10, 11, 12, 13, 14, 15] {
+            bytes.extend(context.gp[idx].to_le_bytes());
+        }
+
+        bytes.extend(context.rip.to_le_bytes());
+        bytes.extend(context.eflags.to_le_bytes());
+        for segment in [
+            context.cs, context.ss, context.ds, context.es, context.fs, context.gs,
+        ] {
+            bytes.extend((segment as u32).to_le_bytes());
+        }
+
+        for idx in 0..8 {
+            bytes.extend_from_slice(&fx[32 + idx * 16..][..10]);
+        }
+
+        let (fctrl, fstat, ftag, fop) = (half(0), half(2), fx[4] as u32, half(6));
+        let (fioff, fiseg, fooff, foseg) = (word(8), half(12), word(16), half(20));
+        for value in [fctrl, fstat, ftag, fiseg, fioff, foseg, fooff, fop] {
+            bytes.extend(value.to_le_bytes());
+        }
+
+        for idx in 0..16 {
+            bytes.extend_from_slice(&fx[160 + idx * 16..][..16]);
+        }
+
+        bytes.extend(context.mxcsr.to_le_bytes());
+        Registers::parse(Architecture::X86_64, true, &bytes)
+            .ok_or(Error::UnknownArchitecture(Architecture::X86_64))
+    }
+
+    fn insert_breakpoint(&mut self, addr: u64) -> Result<(), Error> {
+        if self.breakpoints.contains_key(&addr) {
+            return Ok(());
+        }
+
+        let byte = self.read_process(addr, 1)?;
+        self.write_process(addr, &[INT3])?;
+        self.breakpoints.insert(addr, byte[0]);
+        Ok(())
+    }
+
+    fn remove_breakpoint(&mut self, addr: u64) -> Result<(), Error> {
+        match self.breakpoints.remove(&addr) {
+            Some(byte) => self.write_process(addr, &[byte]),
+            None => Ok(()),
+        }
+    }
+
+    /// Watch memory with one of the debug registers, which watch 1, 2, 4 or 8 aligned bytes.
+    fn insert_watchpoint(&mut self, addr: u64, len: usize, watch: Watch) -> Result<(), Error> {
+        if watch == Watch::Read {
+            return Err(Error::Unsupported("watchpoints on reads"));
+        }
+
+        if !matches!(len, 1 | 2 | 4 | 8) || !addr.is_multiple_of(len as u64) {
+            return Err(Error::Unsupported("watchpoints on unaligned memory"));
+        }
+
+        let slot = self.watchpoints.iter().position(Option::is_none);
+        let slot = slot.ok_or(Error::Unsupported("more than 4 watchpoints"))?;
+        self.watchpoints[slot] = Some((addr, len, watch));
+        self.apply_all_watchpoints();
+        Ok(())
+    }
+
+    fn remove_watchpoint(&mut self, addr: u64, len: usize, watch: Watch) -> Result<(), Error> {
+        for slot in self.watchpoints.iter_mut() {
+            if *slot == Some((addr, len, watch)) {
+                *slot = None;
+            }
+        }
+
+        self.apply_all_watchpoints();
+        Ok(())
+    }
+
+    fn apply_all_watchpoints(&self) {
+        for &thread in self.threads.values() {
+            self.apply_watchpoints(thread);
+        }
+    }
+
+    /// Set the debug registers of a thread to the watchpoints.
+    fn apply_watchpoints(&self, thread: isize) {
+        let mut context: Context = unsafe { std::mem::zeroed() };
+        context.flags = CONTEXT_DEBUG_REGISTERS;
+        if unsafe { GetThreadContext(thread, &mut context) } == 0 {
+            return;
+        }
+
+        for (idx, watchpoint) in self.watchpoints.iter().enumerate() {
+            if let Some((addr, ..)) = watchpoint {
+                context.dr[idx] = *addr;
+            }
+        }
+
+        context.dr[5] = watch_control(&self.watchpoints);
+        let _ = self.set_context(thread, &context);
+    }
+
+    /// Regions of memory that are committed, named after the module they belong to.
+    fn memory_map(&self) -> Vec<Region> {
+        let mut regions = Vec::new();
+        let mut addr = 0u64;
+
+        loop {
+            let mut info: MemoryInfo = unsafe { std::mem::zeroed() };
+            let size = std::mem::size_of::<MemoryInfo>();
+            let len =
+                unsafe { VirtualQueryEx(self.process.0, addr as *const c_void, &mut info, size) };
+            if len == 0 || info.size == 0 {
+                break;
+            }
+
+            let start = info.base as u64;
+            let end = start.saturating_add(info.size as u64);
+            if info.state == MEM_COMMIT {
+                let module = self.modules.iter().find(|m| (m.base..m.end).contains(&start));
+                regions.push(Region {
+                    range: start..end,
+                    permissions: permissions(info.protect, info.kind),
+                    name: module.map(|module| module.path.clone()).unwrap_or_default(),
+                    offset: module.map_or(0, |module| start - module.base),
+                });
+            }
+
+            addr = end;
+        }
+
+        regions
+    }
+
+    /// Run until the process stops, or a single instruction of the thread that stopped.
+    fn run(&mut self, step: bool) -> Result<Stop, Error> {
+        let mut context = self.context()?;
+
+        // the instruction a breakpoint replaced is run on its own, putting the breakpoint back
+        if self.breakpoints.contains_key(&context.rip) {
+            let byte = self.breakpoints[&context.rip];
+            self.write_process(context.rip, &[byte])?;
+            self.stepping_over = Some(context.rip);
+        }
+
+        if step || self.stepping_over.is_some() {
+            context.eflags |= TRAP_FLAG;
+            self.set_context(self.thread(), &context)?;
+        }
+
+        self.stepping = step;
+        self.wait()
+    }
+
+    /// Stop debugging, restoring the memory that breakpoints replaced and letting the process run.
+    fn detach(&mut self) -> Result<(), Error> {
+        for (&addr, &byte) in self.breakpoints.iter() {
+            self.write_process(addr, &[byte])?;
+        }
+
+        self.breakpoints.clear();
+        self.watchpoints = [None; 4];
+        self.apply_all_watchpoints();
+
+        if let Some((pid, tid, status)) = self.pending.take() {
+            unsafe { ContinueDebugEvent(pid, tid, status) };
+        }
+
+        if unsafe { DebugActiveProcessStop(self.pid) } == 0 {
+            return Err(Error::Io(std::io::Error::last_os_error()));
+        }
+
+        self.detached = true;
+        Ok(())
+    }
+}
+
+impl Drop for Debuggee {
+    fn drop(&mut self) {
+        if !self.detached {
+            unsafe { TerminateProcess(self.process.0, 1) };
+        }
+    }
+}
+
+const INFINITE: u32 = 0xFFFFFFFF;
+const PROCESS_ALL_ACCESS: u32 = 0x1FFFFF;
+
+const DEBUG_ONLY_THIS_PROCESS: u32 = 0x00000002;
+const CREATE_NEW_CONSOLE: u32 = 0x00000010;
+
+const DBG_CONTINUE: u32 = 0x00010002;
+const DBG_EXCEPTION_NOT_HANDLED: u32 = 0x80010001;
+
+const EXCEPTION_DEBUG_EVENT: u32 = 1;
+const CREATE_THREAD_DEBUG_EVENT: u32 = 2;
+const CREATE_PROCESS_DEBUG_EVENT: u32 = 3;
+const EXIT_THREAD_DEBUG_EVENT: u32 = 4;
+const EXIT_PROCESS_DEBUG_EVENT: u32 = 5;
+const LOAD_DLL_DEBUG_EVENT: u32 = 6;
+const UNLOAD_DLL_DEBUG_EVENT: u32 = 7;
+const OUTPUT_DEBUG_STRING_EVENT: u32 = 8;
+
+const EXCEPTION_BREAKPOINT: u32 = 0x80000003;
+const EXCEPTION_SINGLE_STEP: u32 = 0x80000004;
+
+const CONTEXT_AMD64: u32 = 0x00100000;
+const CONTEXT_ALL: u32 = CONTEXT_AMD64 | 0x1F;
+const CONTEXT_DEBUG_REGISTERS: u32 = CONTEXT_AMD64 | 0x10;
+
+const MEM_COMMIT: u32 = 0x1000;
+
+#[repr(C)]
+struct StartupInfo {
+    size: u32,
+    reserved: *const u16,
+    desktop: *const u16,
+    title: *const u16,
+    x: u32,
+    y: u32,
+    x_size: u32,
+    y_size: u32,
+    x_count_chars: u32,
+    y_count_chars: u32,
+    fill_attribute: u32,
+    flags: u32,
+    show_window: u16,
+    reserved2_len: u16,
+    reserved2: *const u8,
+    stdin: isize,
+    stdout: isize,
+    stderr: isize,
+}
+
+#[repr(C)]
+struct ProcessInformation {
+    process: isize,
+    thread: isize,
+    pid: u32,
+    tid: u32,
+}
+
+#[repr(C)]
+struct DebugEvent {
+    code: u32,
+    pid: u32,
+    tid: u32,
+    info: DebugInfo,
+}
+
+#[repr(C)]
+union DebugInfo {
+    exception: ExceptionInfo,
+    create_thread: CreateThreadInfo,
+    create_process: CreateProcessInfo,
+    exit_process: ExitProcessInfo,
+    load_dll: LoadDllInfo,
+    unload_dll: UnloadDllInfo,
+    debug_string: DebugStringInfo,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct ExceptionRecord {
+    code: u32,
+    flags: u32,
+    record: usize,
+    address: usize,
+    parameter_count: u32,
+    parameters: [usize; 15],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct ExceptionInfo {
+    record: ExceptionRecord,
+    first_chance: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct CreateThreadInfo {
+    thread: isize,
+    local_base: usize,
+    start: usize,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct CreateProcessInfo {
+    file: isize,
+    process: isize,
+    thread: isize,
+    base: usize,
+    debug_info_offset: u32,
+    debug_info_size: u32,
+    local_base: usize,
+    start: usize,
+    image_name: usize,
+    unicode: u16,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct ExitProcessInfo {
+    exit_code: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct LoadDllInfo {
+    file: isize,
+    base: usize,
+    debug_info_offset: u32,
+    debug_info_size: u32,
+    image_name: usize,
+    unicode: u16,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct UnloadDllInfo {
+    base: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct DebugStringInfo {
+    data: usize,
+    unicode: u16,
+    len: u16,
+}
+
+/// Registers of a thread, as they're laid out by `CONTEXT` on x86-64.
+#[repr(C, align(16))]
+struct Context {
+    home: [u64; 6],
+    flags: u32,
+    mxcsr: u32,
+    cs: u16,
+    ds: u16,
+    es: u16,
+    fs: u16,
+    gs: u16,
+    ss: u16,
+    eflags: u32,
+    /// `Dr0` to `Dr3`, followed by `Dr6` and `Dr7`.
+    dr: [u64; 6],
+    /// General purpose registers in the order `rax`, `rcx`, `rdx`, `rbx`, `rsp`, `rbp`, `rsi`,
+    /// `rdi` and `r8` to `r15`.
+    gp: [u64; 16],
+    rip: u64,
+    /// State of the FPU and SSE registers, as saved by `fxsave`.
+    float_save: [u8; 512],
+    vector: [u8; 464],
+}
+
+const _: () = assert!(std::mem::size_of::<Context>() == 1232);
+const _: () = assert!(std::mem::size_of::<DebugEvent>() == 176);
+
+#[repr(C)]
+struct MemoryInfo {
+    base: usize,
+    allocation_base: usize,
+    allocation_protect: u32,
+    partition: u16,
+    size: usize,
+    state: u32,
+    protect: u32,
+    kind: u32,
+}
+
+extern "system" {
+    fn CreateProcessW(
+        application_name: *const u16,
+        command_line: *mut u16,
+        process_attributes: *const c_void,
+        thread_attributes: *const c_void,
+        inherit_handles: i32,
+        creation_flags: u32,
+        environment: *const c_void,
+        current_directory: *const u16,
+        startup_info: *const StartupInfo,
+        process_information: *mut ProcessInformation,
+    ) -> i32;
+    fn OpenProcess(access: u32, inherit_handle: i32, pid: u32) -> isize;
+    fn TerminateProcess(process: isize, exit_code: u32) -> i32;
+    fn CloseHandle(handle: isize) -> i32;
+    fn WaitForDebugEvent(event: *mut DebugEvent, milliseconds: u32) -> i32;
+    fn ContinueDebugEvent(pid: u32, tid: u32, status: u32) -> i32;
+    fn DebugActiveProcessStop(pid: u32) -> i32;
+    fn DebugBreakProcess(process: isize) -> i32;
+    fn ReadProcessMemory(
+        process: isize,
+        addr: *const c_void,
+        buffer: *mut c_void,
+        size: usize,
+        read: *mut usize,
+    ) -> i32;
+    fn WriteProcessMemory(
+        process: isize,
+        addr: *mut c_void,
+        buffer: *const c_void,
+        size: usize,
+        written: *mut usize,
+    ) -> i32;
+    fn FlushInstructionCache(process: isize, addr: *const c_void, size: usize) -> i32;
+    fn GetThreadContext(thread: isize, context: *mut Context) -> i32;
+    fn SetThreadContext(thread: isize, context: *const Context) -> i32;
+    fn VirtualQueryEx(
+        process: isize,
+        addr: *const c_void,
+        info: *mut MemoryInfo,
+        size: usize,
+    ) -> usize;
+    fn GetFinalPathNameByHandleW(file: isize, path: *mut u16, len: u32, flags: u32) -> u32;
+}
@@ -0,0 +1,204 @@
+//! Debugging programs on this machine through the Win32 debug API.
+//!
+//! Windows only reports debug events to the thread that started the process, so the process is
+//! started on a thread of its own which then answers every request of the [`Local`] target. What
+//! the events report is decoded here, which doesn't need Windows.
+
+// only the target uses these, which is only built on 64-bit Windows
+#![cfg_attr(
+    not(all(target_os = "windows", target_arch = "x86_64")),
+    allow(dead_code)
+)]
+
+#[cfg(all(target_os = "windows", target_arch = "x86_64"))]
+mod local;
+
+#[cfg(all(target_os = "windows", target_arch = "x86_64"))]
+pub(crate) use local::Local;
+
+use crate::Watch;
+
+/// Signals stops are reported with, numbered the way GDB does.
+const SIGINT: u8 = 2;
+const SIGILL: u8 = 4;
+pub(crate) const SIGTRAP: u8 = 5;
+const SIGABRT: u8 = 6;
+const SIGFPE: u8 = 8;
+const SIGSEGV: u8 = 11;
+
+/// Memory is read a page at a time, so that the pages before one that isn't mapped are read.
+const PAGE_SIZE: u64 = 0x1000;
+
+const DBG_CONTROL_C: u32 = 0x40010005;
+
+const EXCEPTION_ACCESS_VIOLATION: u32 = 0xC0000005;
+const EXCEPTION_IN_PAGE_ERROR: u32 = 0xC0000006;
+const EXCEPTION_ILLEGAL_INSTRUCTION: u32 = 0xC000001D;
+const EXCEPTION_FLT_DENORMAL_OPERAND: u32 = 0xC000008D;
+const EXCEPTION_INT_OVERFLOW: u32 = 0xC0000095;
+const EXCEPTION_PRIV_INSTRUCTION: u32 = 0xC0000096;
+const EXCEPTION_STACK_OVERFLOW: u32 = 0xC00000FD;
+
+const MEM_PRIVATE: u32 = 0x20000;
+
+const PAGE_EXECUTE: u32 = 0x10;
+const PAGE_EXECUTE_READ: u32 = 0x20;
+const PAGE_EXECUTE_READWRITE: u32 = 0x40;
+const PAGE_EXECUTE_WRITECOPY: u32 = 0x80;
+const PAGE_READONLY: u32 = 0x02;
+const PAGE_READWRITE: u32 = 0x04;
+const PAGE_WRITECOPY: u32 = 0x08;
+
+/// Signal of an exception that the program didn't handle.
+fn signal(code: u32) -> u8 {
+    match code {
+        DBG_CONTROL_C => SIGINT,
+        EXCEPTION_ACCESS_VIOLATION | EXCEPTION_IN_PAGE_ERROR | EXCEPTION_STACK_OVERFLOW => SIGSEGV,
+        EXCEPTION_ILLEGAL_INSTRUCTION | EXCEPTION_PRIV_INSTRUCTION => SIGILL,
+        EXCEPTION_FLT_DENORMAL_OPERAND..=EXCEPTION_INT_OVERFLOW => SIGFPE,
+        _ => SIGABRT,
+    }
+}
+
+/// Protection of a region as the `rwxp` flags of a mapping.
+fn permissions(protect: u32, kind: u32) -> String {
+    let rwx = match protect & 0xff {
+        PAGE_READONLY => "r--",
+        PAGE_READWRITE | PAGE_WRITECOPY => "rw-",
+        PAGE_EXECUTE => "--x",
+        PAGE_EXECUTE_READ => "r-x",
+        PAGE_EXECUTE_READWRITE | PAGE_EXECUTE_WRITECOPY => "rwx",
+        _ => "---",
+    };
+
+    let shared = if kind == MEM_PRIVATE { 'p' } else { 's' };
+    format!("{rwx}{shared}")
+}
+
+/// Debug register whose watchpoint stopped a thread, which DR6 has one of its low bits set for.
+fn watchpoint_hit(dr6: u64) -> Option<usize> {
+    (0..4).find(|&idx| dr6 & (1 << idx) != 0)
+}
+
+/// Value of DR7 that enables the watchpoint in each of the debug registers, which watch either
+/// writes or any access of 1, 2, 4 or 8 bytes.
+fn watch_control(watchpoints: &[Option<(u64, usize, Watch)>]) -> u64 {
+    let mut dr7 = 0;
+    for (idx, watchpoint) in watchpoints.iter().enumerate() {
+        let (_, len, watch) = match watchpoint {
+            Some(watchpoint) => *watchpoint,
+            None => continue,
+        };
+
+        let kind = match watch {
+            Watch::Write => 0b01,
+            Watch::Read | Watch::Access => 0b11,
+        };
+
+        let len = match len {
+            1 => 0b00,
+            2 => 0b01,
+            8 => 0b10,
+            _ => 0b11,
+        };
+
+        dr7 |= 1 << (idx * 2) | (kind | len << 2) << (16 + idx * 4);
+    }
+
+    dr7
+}
+
+/// Size of a PE image that's loaded at `base`, which `read` reads the headers of. The size is in
+/// the optional header, whose offset is in the DOS header.
+fn image_size(base: u64, read: impl Fn(u64, usize) -> Option<Vec<u8>>) -> Option<u64> {
+    let header = read(base.checked_add(0x3c)?, 4)?;
+    let offset = u32::from_le_bytes(header.try_into().ok()?) as u64;
+    let size = read(base.checked_add(offset)?.checked_add(0x50)?, 4)?;
+    Some(u32::from_le_bytes(size.try_into().ok()?) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signals() {
+        assert_eq!(signal(DBG_CONTROL_C), SIGINT);
+        assert_eq!(signal(EXCEPTION_ACCESS_VIOLATION), SIGSEGV);
+        assert_eq!(signal(EXCEPTION_STACK_OVERFLOW), SIGSEGV);
+        assert_eq!(signal(EXCEPTION_PRIV_INSTRUCTION), SIGILL);
+        // integer division by zero is in between the floating point exceptions and overflow
+        assert_eq!(signal(0xC0000094), SIGFPE);
+        assert_eq!(signal(EXCEPTION_INT_OVERFLOW), SIGFPE);
+        // exceptions thrown by C++ programs
+        assert_eq!(signal(0xE06D7363), SIGABRT);
+    }
+
+    #[test]
+    fn protections() {
+        const MEM_IMAGE: u32 = 0x1000000;
+        const PAGE_GUARD: u32 = 0x100;
+        const PAGE_NOACCESS: u32 = 0x01;
+
+        // image sections are mapped as shared, allocations are private
+        assert_eq!(permissions(PAGE_EXECUTE_READ, MEM_IMAGE), "r-xs");
+        assert_eq!(permissions(PAGE_READWRITE, MEM_PRIVATE), "rw-p");
+        assert_eq!(permissions(PAGE_WRITECOPY, MEM_IMAGE), "rw-s");
+        // modifiers such as guard pages are above the protection itself
+        assert_eq!(
+            permissions(PAGE_GUARD | PAGE_READWRITE, MEM_PRIVATE),
+            "rw-p"
+        );
+        assert_eq!(permissions(PAGE_NOACCESS, MEM_PRIVATE), "---p");
+    }
+
+    #[test]
+    fn watchpoints_hit() {
+        assert_eq!(watchpoint_hit(0b0100), Some(2));
+        // bits that are reserved read as set, and single steps set BS instead
+        assert_eq!(watchpoint_hit(0xFFFF0FF0), None);
+        assert_eq!(watchpoint_hit(0xFFFF0FF0 | 1 << 14), None);
+        assert_eq!(watchpoint_hit(0xFFFF0FF0 | 0b1000), Some(3));
+        // the first one is reported if several were hit at once
+        assert_eq!(watchpoint_hit(0b0110), Some(1));
+    }
+
+    #[test]
+    fn watch_controls() {
+        let mut watchpoints = [None; 4];
+        assert_eq!(watch_control(&watchpoints), 0);
+
+        watchpoints[0] = Some((0x1000, 4, Watch::Write));
+        assert_eq!(watch_control(&watchpoints), 0b1101 << 16 | 0b01);
+
+        watchpoints[2] = Some((0x2000, 8, Watch::Access));
+        assert_eq!(
+            watch_control(&watchpoints),
+            0b1011 << 24 | 0b1101 << 16 | 0b01_00_01
+        );
+    }
+
+    /// Reads of an image that's loaded at `base`.
+    fn mapped(image: &[u8], base: u64) -> impl Fn(u64, usize) -> Option<Vec<u8>> + '_ {
+        move |addr, len| {
+            let offset = addr.checked_sub(base)? as usize;
+            image.get(offset..offset + len).map(<[u8]>::to_vec)
+        }
+    }
+
+    #[test]
+    fn image_sizes() {
+        const BASE: u64 = 0x140000000;
+
+        let mut image = vec![0; 0x200];
+        image[0x3c..0x40].copy_from_slice(&0x80u32.to_le_bytes());
+        image[0xd0..0xd4].copy_from_slice(&0x5000u32.to_le_bytes());
+        assert_eq!(image_size(BASE, mapped(&image, BASE)), Some(0x5000));
+
+        // the offset of the optional header points past what's mapped
+        image[0x3c..0x40].copy_from_slice(&0x1000u32.to_le_bytes());
+        assert_eq!(image_size(BASE, mapped(&image, BASE)), None);
+
+        assert_eq!(image_size(u64::MAX - 8, |_, _| Some(vec![0; 4])), None);
+    }
+}
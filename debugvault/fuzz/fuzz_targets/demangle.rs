@@ -0,0 +1,11 @@
+//! Run using `cargo +nightly fuzz run demangle` from within `debugvault`.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(s) = std::str::from_utf8(data) {
+        let _ = debugvault::Symbol::demangle(s);
+    }
+});
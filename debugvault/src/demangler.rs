@@ -58,7 +58,8 @@ impl TokenStream {
             tokens: Vec::with_capacity(1),
         };
 
-        this.tokens.push(Token::from_string(s.to_string(), CONFIG.colors.asm.component));
+        // the token refers to the pinned copy of the name instead of copying it again
+        this.tokens.push(Token::from_str(this.inner(), CONFIG.colors.asm.component));
         this
     }
 
@@ -73,15 +74,106 @@ impl TokenStream {
         self.tokens.push(Token::from_str(text, color));
     }
 
+    /// Push a token that owns its text, interning it if it's short.
     #[inline]
     pub fn push_string(&mut self, text: String, color: Color32) {
-        self.tokens.push(Token::from_string(text, color));
+        self.tokens.push(Token::from_owned(text, color));
     }
 
     #[inline]
     pub fn tokens(&self) -> &[Token] {
         self.tokens.as_slice()
     }
+
+    /// Drop all but the first `len` tokens.
+    #[inline]
+    pub fn truncate(&mut self, len: usize) {
+        self.tokens.truncate(len);
+    }
+
+    /// Free the space reserved for tokens that were never pushed.
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        self.tokens.shrink_to_fit();
+    }
+}
+
+/// What part of a demangled name a token is in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NamePart {
+    /// Path making up the name, along with anything else outside of generics.
+    Segment,
+    /// Anything within the angle brackets of a list of generics.
+    Generic,
+    /// Named or erased lifetime, e.g. `'a` or `'_`.
+    Lifetime,
+    /// Separators, brackets and operators.
+    Punctuation,
+}
+
+/// Tokens of a demangled name along with the part of the name they're in.
+#[derive(Debug, Clone)]
+pub struct NameTokens<'a> {
+    tokens: std::slice::Iter<'a, Token>,
+    /// Number of angle brackets the next token is within.
+    depth: usize,
+    /// Whether the previous token was the `operator` keyword, as in `operator<`.
+    operator: bool,
+}
+
+impl<'a> NameTokens<'a> {
+    pub(crate) fn new(tokens: &'a [Token]) -> Self {
+        Self {
+            tokens: tokens.iter(),
+            depth: 0,
+            operator: false,
+        }
+    }
+}
+
+fn is_punctuation(text: &str) -> bool {
+    text.chars()
+        .all(|c| !c.is_alphanumeric() && !matches!(c, '_' | '\'' | '"' | '`'))
+}
+
+impl<'a> Iterator for NameTokens<'a> {
+    type Item = (NamePart, &'a Token);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let token = self.tokens.next()?;
+        let text = token.text.trim();
+
+        // chars such as 'a' are quoted on both ends
+        if text.len() > 1 && text.starts_with('\'') && !text.ends_with('\'') {
+            return Some((NamePart::Lifetime, token));
+        }
+
+        let part = if is_punctuation(text) {
+            NamePart::Punctuation
+        } else if self.depth == 0 {
+            NamePart::Segment
+        } else {
+            NamePart::Generic
+        };
+
+        // neither arrows of function signatures nor operators such as `operator<` are brackets
+        let operator = std::mem::replace(&mut self.operator, text == "operator");
+        if text != "->" && !operator {
+            for c in text.chars() {
+                match c {
+                    '<' => self.depth += 1,
+                    '>' => self.depth = self.depth.saturating_sub(1),
+                    _ => {}
+                }
+            }
+        }
+
+        Some((part, token))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.tokens.size_hint()
+    }
 }
 
 impl PartialEq for TokenStream {
@@ -89,3 +181,59 @@ impl PartialEq for TokenStream {
         self.inner == other.inner
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parts(s: &str) -> Vec<(NamePart, String)> {
+        let stream = parse(s);
+        NameTokens::new(stream.tokens())
+            .filter(|(_, token)| !token.text.trim().is_empty())
+            .map(|(part, token)| (part, token.text.trim().to_string()))
+            .collect()
+    }
+
+    fn text(parts: &[(NamePart, String)], part: NamePart) -> Vec<&str> {
+        parts
+            .iter()
+            .filter(|(p, _)| *p == part)
+            .map(|(_, text)| text.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn segments_and_generics() {
+        let parts = parts("_RINvNtC3std3mem8align_ofjTddNvC4core3ptrEE");
+        assert_eq!(text(&parts, NamePart::Segment), ["std", "mem", "align_of"]);
+        assert_eq!(
+            text(&parts, NamePart::Generic),
+            ["usize", "f64", "f64", "core", "ptr"]
+        );
+        assert!(text(&parts, NamePart::Punctuation).contains(&"<"));
+    }
+
+    #[test]
+    fn lifetimes() {
+        let parts = parts("_RINvC4bite6decodeFG0_RL1_hRL0_tEuE");
+        assert_eq!(text(&parts, NamePart::Lifetime), ["'a", "'b", "'a", "'b"]);
+        assert_eq!(text(&parts, NamePart::Generic), ["for", "fn", "u8", "u16"]);
+    }
+
+    #[test]
+    fn closing_brackets() {
+        let parts = parts("_ZN4bite6decodeIiE5entryEv");
+        assert_eq!(text(&parts, NamePart::Generic), ["int"]);
+        assert_eq!(text(&parts, NamePart::Segment), ["bite", "decode", "entry"]);
+    }
+
+    #[test]
+    fn operators() {
+        let parts = parts("_ZN4biteltIiEEbi");
+        assert_eq!(
+            text(&parts, NamePart::Segment),
+            ["bool", "bite", "operator"]
+        );
+        assert_eq!(text(&parts, NamePart::Generic), ["int"]);
+    }
+}
@@ -1,9 +1,10 @@
 use crate::intern::InternMap;
+use crate::types::{natural_align, Enum, Field, Struct};
 use crate::{AddressMap, Addressed, FileAttr};
 use object::{Object, ObjectSection, ObjectSymbol};
 use rustc_hash::FxHasher;
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::hash::BuildHasherDefault;
 use std::io;
 use std::path::{Path, PathBuf};
@@ -11,6 +12,9 @@ use typed_arena::Arena;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// Types followed when naming the type of a member, e.g. a pointer to a const typedef.
+const MAX_TYPE_DEPTH: usize = 8;
+
 #[derive(Debug)]
 pub enum Error {
     Gimli(gimli::Error),
@@ -39,6 +43,10 @@ impl From<object::Error> for Error {
 pub struct Dwarf {
     /// Mapping from addresses starting at the header base to source files.
     pub file_attrs: AddressMap<FileAttr>,
+    /// Layouts of the named structures, the first definition of a name wins.
+    pub structs: Vec<Struct>,
+    /// Constants of the named enumerations, the first definition of a name wins.
+    pub enums: Vec<Enum>,
 }
 
 impl Dwarf {
@@ -59,8 +67,14 @@ impl Dwarf {
         let mut dwarf = gimli::Dwarf::load(&mut load_section)?;
         dwarf.populate_abbreviations_cache(gimli::AbbreviationsCacheStrategy::All);
         let file_attrs = dump_line(&dwarf)?;
-
-        Ok(Dwarf { file_attrs })
+        let structs = dump_structs(&dwarf)?;
+        let enums = dump_enums(&dwarf)?;
+
+        Ok(Dwarf {
+            file_attrs,
+            structs,
+            enums,
+        })
     }
 
     #[allow(dead_code)]
@@ -74,6 +88,8 @@ impl Dwarf {
     #[allow(dead_code)]
     pub fn merge(&mut self, other: Self) {
         self.file_attrs.extend(other.file_attrs);
+        self.structs.extend(other.structs);
+        self.enums.extend(other.enums);
     }
 }
 
@@ -428,3 +444,238 @@ fn dump_line_program<R: Reader>(
 
     Ok(())
 }
+
+fn dump_structs<R: Reader>(dwarf: &gimli::Dwarf<R>) -> Result<Vec<Struct>> {
+    let mut iter = dwarf.units();
+    let mut structs = Vec::new();
+    let mut seen = HashSet::new();
+
+    while let Some(header) = iter.next()? {
+        let unit = match dwarf.unit(header) {
+            Ok(unit) => unit,
+            Err(..) => continue,
+        };
+
+        let mut entries = unit.entries();
+        while let Some((_, entry)) = entries.next_dfs()? {
+            if entry.tag() != gimli::DW_TAG_structure_type {
+                continue;
+            }
+
+            // forward declarations don't have any members
+            if entry.attr_value(gimli::DW_AT_declaration)?.is_some() {
+                continue;
+            }
+
+            let name = match entry_name(dwarf, &unit, entry)? {
+                Some(name) => name,
+                None => continue,
+            };
+
+            if !seen.insert(name.clone()) {
+                continue;
+            }
+
+            let size = entry.attr_value(gimli::DW_AT_byte_size)?.and_then(|v| v.udata_value());
+            let mut tree = unit.entries_tree(Some(entry.offset()))?;
+            let mut children = tree.root()?.children();
+            let mut fields = Vec::new();
+
+            while let Some(child) = children.next()? {
+                let member = child.entry();
+                if member.tag() != gimli::DW_TAG_member {
+                    continue;
+                }
+
+                // bit-fields are located by their bit offset instead
+                let offset = member
+                    .attr_value(gimli::DW_AT_data_member_location)?
+                    .and_then(|value| value.udata_value());
+
+                let (name, offset) = match (entry_name(dwarf, &unit, member)?, offset) {
+                    (Some(name), Some(offset)) => (name, offset as usize),
+                    _ => continue,
+                };
+
+                let (ty, size, align) = match member.attr_value(gimli::DW_AT_type)? {
+                    Some(gimli::AttributeValue::UnitRef(ty)) => type_name(dwarf, &unit, ty, 0)?,
+                    _ => ("void".to_string(), 0, 1),
+                };
+
+                fields.push(Field {
+                    name,
+                    ty,
+                    offset,
+                    size,
+                    align,
+                });
+            }
+
+            fields.sort_by_key(|field| field.offset);
+            structs.push(Struct {
+                name,
+                size: size.unwrap_or(0) as usize,
+                fields,
+            });
+        }
+    }
+
+    if !structs.is_empty() {
+        log::complex!(
+            w "[dwarf::dump_structs] found ",
+            g structs.len().to_string(),
+            w " structures."
+        );
+    }
+
+    Ok(structs)
+}
+
+fn dump_enums<R: Reader>(dwarf: &gimli::Dwarf<R>) -> Result<Vec<Enum>> {
+    let mut iter = dwarf.units();
+    let mut enums = Vec::new();
+    let mut seen = HashSet::new();
+
+    while let Some(header) = iter.next()? {
+        let unit = match dwarf.unit(header) {
+            Ok(unit) => unit,
+            Err(..) => continue,
+        };
+
+        let mut entries = unit.entries();
+        while let Some((_, entry)) = entries.next_dfs()? {
+            if entry.tag() != gimli::DW_TAG_enumeration_type {
+                continue;
+            }
+
+            // forward declarations don't have any constants
+            if entry.attr_value(gimli::DW_AT_declaration)?.is_some() {
+                continue;
+            }
+
+            let name = match entry_name(dwarf, &unit, entry)? {
+                Some(name) => name,
+                None => continue,
+            };
+
+            if !seen.insert(name.clone()) {
+                continue;
+            }
+
+            let mut tree = unit.entries_tree(Some(entry.offset()))?;
+            let mut children = tree.root()?.children();
+            let mut variants = Vec::new();
+
+            while let Some(child) = children.next()? {
+                let enumerator = child.entry();
+                if enumerator.tag() != gimli::DW_TAG_enumerator {
+                    continue;
+                }
+
+                // constants of signed enumerations are encoded as signed data
+                let value = enumerator.attr_value(gimli::DW_AT_const_value)?.and_then(|value| {
+                    value.udata_value().or_else(|| value.sdata_value().map(|value| value as u64))
+                });
+
+                if let (Some(name), Some(value)) = (entry_name(dwarf, &unit, enumerator)?, value) {
+                    variants.push((name, value));
+                }
+            }
+
+            enums.push(Enum { name, variants });
+        }
+    }
+
+    if !enums.is_empty() {
+        log::complex!(
+            w "[dwarf::dump_enums] found ",
+            g enums.len().to_string(),
+            w " enumerations."
+        );
+    }
+
+    Ok(enums)
+}
+
+fn entry_name<R: Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &gimli::Unit<R>,
+    entry: &gimli::DebuggingInformationEntry<R>,
+) -> Result<Option<String>> {
+    match entry.attr_value(gimli::DW_AT_name)? {
+        Some(name) => Ok(Some(
+            dwarf.attr_string(unit, name)?.to_string_lossy()?.into_owned(),
+        )),
+        None => Ok(None),
+    }
+}
+
+/// Type as written in C, its size and its alignment.
+///
+/// Alignment is rarely described, so it's assumed to be the natural alignment of the type's size
+/// unless it's derived from another type.
+fn type_name<R: Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &gimli::Unit<R>,
+    offset: gimli::UnitOffset,
+    depth: usize,
+) -> Result<(String, usize, usize)> {
+    let entry = unit.entry(offset)?;
+    let size = entry.attr_value(gimli::DW_AT_byte_size)?.and_then(|v| v.udata_value());
+    let size = size.unwrap_or(0) as usize;
+    let align = match entry.attr_value(gimli::DW_AT_alignment)?.and_then(|v| v.udata_value()) {
+        Some(align) => align as usize,
+        None => natural_align(size),
+    };
+
+    let inner = match entry.attr_value(gimli::DW_AT_type)? {
+        Some(gimli::AttributeValue::UnitRef(inner)) if depth < MAX_TYPE_DEPTH => {
+            type_name(dwarf, unit, inner, depth + 1)?
+        }
+        _ => ("void".to_string(), 0, 1),
+    };
+
+    let name = entry_name(dwarf, unit, &entry)?.unwrap_or_else(|| "?".to_string());
+    let ty = match entry.tag() {
+        gimli::DW_TAG_structure_type | gimli::DW_TAG_class_type => {
+            (format!("struct {name}"), size, align)
+        }
+        gimli::DW_TAG_union_type => (format!("union {name}"), size, align),
+        gimli::DW_TAG_enumeration_type => (format!("enum {name}"), size, align),
+        gimli::DW_TAG_typedef => (name, inner.1, inner.2),
+        gimli::DW_TAG_pointer_type
+        | gimli::DW_TAG_reference_type
+        | gimli::DW_TAG_rvalue_reference_type => {
+            let separator = if inner.0.ends_with('*') { "" } else { " " };
+            let size = unit.encoding().address_size as usize;
+            (format!("{}{separator}*", inner.0), size, size)
+        }
+        gimli::DW_TAG_array_type => {
+            let mut count = 0;
+            let mut tree = unit.entries_tree(Some(offset))?;
+            let mut children = tree.root()?.children();
+            while let Some(child) = children.next()? {
+                let range = child.entry();
+                let len = match range.attr_value(gimli::DW_AT_count)? {
+                    Some(count) => count.udata_value(),
+                    None => range
+                        .attr_value(gimli::DW_AT_upper_bound)?
+                        .and_then(|bound| bound.udata_value())
+                        .map(|bound| bound + 1),
+                };
+
+                if let Some(len) = len {
+                    count = if count == 0 { len } else { count * len };
+                }
+            }
+
+            let (ty, size, align) = inner;
+            (format!("{ty}[{count}]"), size * count as usize, align)
+        }
+        // qualifiers don't change how a value is shown
+        gimli::DW_TAG_const_type | gimli::DW_TAG_volatile_type => inner,
+        _ => (name, size, align),
+    };
+
+    Ok(ty)
+}
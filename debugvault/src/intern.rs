@@ -1,3 +1,5 @@
+use std::borrow::Borrow;
+use std::fmt;
 use std::hash::{BuildHasherDefault, Hash};
 use std::sync::Arc;
 use dashmap::DashMap;
@@ -23,7 +25,10 @@ impl<K: Hash + Eq, V: ?Sized> InternMap<K, V> {
         value
     }
 
-    pub fn get(&self, key: &K) -> Option<Arc<V>> {
+    pub fn get<Q: ?Sized + Hash + Eq>(&self, key: &Q) -> Option<Arc<V>>
+    where
+        K: Borrow<Q>,
+    {
         self.map.get(key).map(|v| v.clone())
     }
 
@@ -31,3 +36,15 @@ impl<K: Hash + Eq, V: ?Sized> InternMap<K, V> {
         self.map.len()
     }
 }
+
+impl<K: Hash + Eq, V: ?Sized> Default for InternMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Hash + Eq, V: ?Sized> fmt::Debug for InternMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InternMap").field("len", &self.len()).finish()
+    }
+}
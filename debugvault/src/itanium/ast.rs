@@ -4,7 +4,7 @@ use super::error::{self, Result};
 use super::index_str::IndexStr;
 use super::subs::{Substitutable, SubstitutionTable};
 use crate::TokenStream;
-use tokenizing::{colors, Color32};
+use tokenizing::Color32;
 use config::CONFIG;
 
 use std::cell::Cell;
@@ -296,6 +296,16 @@ impl<'prev, 'subs> ArgScope<'prev, 'subs> for Option<ArgScopeStack<'prev, 'subs>
     }
 }
 
+/// Max number of substitutions and template arguments demangled within each other.
+const MAX_DEMANGLE_DEPTH: u32 = 128;
+
+/// Max number of substitutions and template arguments demangled.
+///
+/// Substitutions can refer to other substitutions more than once, so without this bound a short
+/// symbol can expand exponentially and exhaust memory even if it never exceeds
+/// [MAX_DEMANGLE_DEPTH].
+const MAX_DEMANGLE_COMPLEXITY: u32 = 16384;
+
 /// Common state that is required when demangling a mangled symbol's parsed AST.
 #[derive(Debug)]
 pub(crate) struct DemangleContext<'a> {
@@ -353,6 +363,15 @@ pub(crate) struct DemangleContext<'a> {
 
     /// Whether to show types of expression literals.
     show_expression_literal_types: bool,
+
+    /// Number of references currently being demangled within each other.
+    depth: u32,
+
+    /// Number of references demangled so far.
+    complexity: u32,
+
+    /// Whether demangling was given up on as references nest too deep or expand too much.
+    pub exhausted: bool,
 }
 
 impl<'a> DemangleContext<'a> {
@@ -370,7 +389,33 @@ impl<'a> DemangleContext<'a> {
             is_template_prefix_in_nested_name: false,
             is_template_argument_pack: false,
             show_expression_literal_types: false,
+            depth: 0,
+            complexity: 0,
+            exhausted: false,
+        }
+    }
+
+    /// Demangle a node that's referred to instead of being nested in its parent, such as a
+    /// substitution or template argument.
+    ///
+    /// References can refer to each other, even to themselves when a template argument is
+    /// resolved in the wrong scope. Once either [MAX_DEMANGLE_DEPTH] or [MAX_DEMANGLE_COMPLEXITY]
+    /// is reached, nothing else is demangled and [DemangleContext::exhausted] is set.
+    #[inline]
+    fn demangle_reference<F: FnOnce(&mut Self)>(&mut self, f: F) {
+        if self.exhausted {
+            return;
         }
+
+        if self.depth >= MAX_DEMANGLE_DEPTH || self.complexity >= MAX_DEMANGLE_COMPLEXITY {
+            self.exhausted = true;
+            return;
+        }
+
+        self.depth += 1;
+        self.complexity += 1;
+        f(self);
+        self.depth -= 1;
     }
 
     #[inline]
@@ -782,7 +827,10 @@ macro_rules! define_handle {
                                      {
                 match *self {
                     $typename::WellKnown(ref comp) => comp.demangle(ctx, scope),
-                    $typename::BackReference(idx) => ctx.subs[idx].demangle(ctx, scope),
+                    $typename::BackReference(idx) => {
+                        let subs = ctx.subs;
+                        ctx.demangle_reference(|ctx| subs[idx].demangle(ctx, scope))
+                    }
                     $(
                         $typename::$extra_variant(ref extra) => extra.demangle(ctx, scope),
                     )*
@@ -818,7 +866,8 @@ impl<'subs> Demangle<'subs> for NonSubstitution {
         ctx: &'ctx mut DemangleContext<'subs>,
         scope: Option<ArgScopeStack<'prev, 'subs>>,
     ) {
-        ctx.subs.non_substitution(self.0).demangle(ctx, scope)
+        let subs = ctx.subs;
+        ctx.demangle_reference(|ctx| subs.non_substitution(self.0).demangle(ctx, scope))
     }
 }
 
@@ -1116,7 +1165,7 @@ impl<'subs> Demangle<'subs> for Encoding {
                     let scope = scope.push(template_args);
                     if !name.is_ctor_dtor_conversion(ctx.subs) {
                         fun_ty.0[0].demangle(ctx, scope);
-                        ctx.push(" ", colors::WHITE);
+                        ctx.push(" ", CONFIG.colors.text);
                     }
 
                     scope
@@ -3232,7 +3281,7 @@ impl<'subs> Demangle<'subs> for Type {
             }
             Type::VendorExtension(ref name, ref template_args, ref ty) => {
                 ty.demangle(ctx, scope);
-                ctx.push(" ", colors::WHITE);
+                ctx.push(" ", CONFIG.colors.text);
                 name.demangle(ctx, scope);
                 if let Some(ref args) = *template_args {
                     args.demangle(ctx, scope);
@@ -4388,7 +4437,7 @@ impl<'subs> Demangle<'subs> for TemplateParam {
             ctx.push_owned((self.0 + 1).to_string(), CONFIG.colors.asm.immediate);
         } else {
             let arg = self.resolve(scope);
-            arg.demangle(ctx, scope)
+            ctx.demangle_reference(|ctx| arg.demangle(ctx, scope))
         }
     }
 }
@@ -4565,7 +4614,7 @@ impl<'subs> Demangle<'subs> for TemplateArgs {
         inner_barrier!(ctx);
 
         if ctx.last_char_written == Some('<') {
-            ctx.push(" ", colors::WHITE);
+            ctx.push(" ", CONFIG.colors.text);
         }
 
         ctx.push("<", CONFIG.colors.asm.label);
@@ -40,8 +40,7 @@ use error::{Error, Result};
 use index_str::IndexStr;
 
 pub fn parse(s: &str) -> Option<TokenStream> {
-    let sym = Symbol::new(s).ok()?;
-    Some(sym.demangle())
+    Symbol::new(s).ok()?.demangle()
 }
 
 /// A mangled symbol that has been parsed into an AST.
@@ -82,14 +81,12 @@ impl Symbol<'_> {
         })
     }
 
-    /// Demangle the symbol and return it as a String.
-    ///
-    /// Unlike the `ToString` implementation, this function allows options to
-    /// be specified.
+    /// Demangle the symbol, failing if its substitutions nest too deep or
+    /// expand too much to be demangled.
     #[inline]
-    fn demangle(&self) -> TokenStream {
+    fn demangle(&self) -> Option<TokenStream> {
         let mut ctx = ast::DemangleContext::new(&self.substitutions, self.raw);
         self.parsed.demangle(&mut ctx, None);
-        ctx.stream
+        (!ctx.exhausted).then_some(ctx.stream)
     }
 }
@@ -158,6 +158,36 @@ fn recursion_limit() {
     simple_assert_parse_err::<TypeHandle, _>("TypeHandle", mangled, Error::TooMuchRecursion);
 }
 
+#[test]
+fn demangle_recursion_limit() {
+    // `a::a::a::..` nested 10,000 times, each prefix referring to the one before it
+    let mangled = format!("_ZN{}E", "1a".repeat(10_000));
+    assert!(super::parse(&mangled).is_none());
+    assert!(super::parse("_ZN1a1a1aE").is_some());
+}
+
+/// Template arguments where each is a function pointer taking the previous one twice.
+fn exponential_substitutions(levels: u32) -> String {
+    let mut mangled = String::from("_Z1fIPFviE");
+    for level in 0..levels {
+        let seq_id = std::char::from_digit(level * 2, 36).unwrap().to_ascii_uppercase();
+        mangled += &format!("PFvS{seq_id}_S{seq_id}_E");
+    }
+
+    mangled + "Evv"
+}
+
+#[test]
+fn demangle_complexity_limit() {
+    let symbol = super::parse(&exponential_substitutions(1)).unwrap();
+    assert_eq!(
+        String::from_iter(symbol.tokens().iter().map(|t| &t.text[..])),
+        "void f<void (*)(int), void (*)(void (int), void (int))>"
+    );
+
+    assert!(super::parse(&exponential_substitutions(18)).is_none());
+}
+
 macro_rules! assert_parse {
     ( $production:ident {
         $( with subs $subs:expr => {
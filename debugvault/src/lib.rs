@@ -1,13 +1,18 @@
 use binformat::RawSymbol;
+use dashmap::DashMap;
 use demangler::TokenStream;
 use dwarf::Dwarf;
+use intern::InternMap;
 use processor_shared::{AddressMap, Addressed};
+use rustc_hash::FxHasher;
+use std::hash::BuildHasherDefault;
 use std::path::Path;
 use std::sync::Arc;
 use std::fmt;
 use tokenizing::Token;
 
 pub mod prefix;
+pub mod types;
 mod demangler;
 mod dwarf;
 mod error;
@@ -18,6 +23,8 @@ mod pdb;
 mod rust;
 mod rust_legacy;
 
+pub use demangler::{NamePart, NameTokens};
+
 pub enum Error {
     Object(object::Error),
     Dwarf(dwarf::Error),
@@ -34,9 +41,11 @@ pub struct FileAttr {
 }
 
 pub struct Symbol {
-    name: TokenStream,
+    /// Shared with every other symbol of the same name, see [`DemangleCache`].
+    name: Arc<TokenStream>,
     name_as_str: Arc<str>,
-    module: Option<String>,
+    /// Shared with every other symbol imported from the same module.
+    module: Option<Arc<str>>,
     is_intrinsics: bool,
 }
 
@@ -60,10 +69,59 @@ fn is_name_an_intrinsic(name: &str) -> bool {
     false
 }
 
+/// Demangled name along with the text it's made up of.
+type Demangled = (Arc<TokenStream>, Arc<str>);
+
+/// Demangle a name without looking at names demangled before.
+fn demangle(name: &str) -> Demangled {
+    let mut demangled = demangler::parse(name);
+    demangled.shrink_to_fit();
+    let name_as_str = String::from_iter(demangled.tokens().iter().map(|t| &t.text[..]));
+    (Arc::new(demangled), Arc::from(name_as_str))
+}
+
+/// Demangled names by their mangled name, shared between every symbol with that name.
+///
+/// Binaries often name a function more than once, e.g. in both the symbol table and the dynamic
+/// symbol table of an ELF, and functions recognized after parsing are named again later on. Names
+/// are only demangled the first time they're seen.
+#[derive(Default)]
+pub struct DemangleCache {
+    names: DashMap<Box<str>, Demangled, BuildHasherDefault<FxHasher>>,
+}
+
+impl DemangleCache {
+    /// Demangle a name, or look it up if it was demangled before.
+    fn demangle(&self, name: &str) -> Demangled {
+        if let Some(demangled) = self.names.get(name) {
+            return demangled.clone();
+        }
+
+        // names being demangled on another thread at the same time are kept only once
+        let demangled = demangle(name);
+        self.names.entry(Box::from(name)).or_insert(demangled).clone()
+    }
+
+    /// Number of distinct names demangled.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+impl fmt::Debug for DemangleCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DemangleCache").field("len", &self.len()).finish()
+    }
+}
+
 impl Default for Symbol {
     fn default() -> Self {
         Self {
-            name: TokenStream::new(""),
+            name: Arc::new(TokenStream::new("")),
             name_as_str: Arc::from(""),
             module: None,
             is_intrinsics: false,
@@ -72,11 +130,41 @@ impl Default for Symbol {
 }
 
 impl Symbol {
+    /// Demangle a symbol name using any of the supported mangling schemes.
+    pub fn demangle(name: &str) -> Self {
+        let (demangled, name_as_str) = demangle(name);
+
+        Self {
+            name_as_str,
+            name: demangled,
+            module: None,
+            is_intrinsics: is_name_an_intrinsic(name),
+        }
+    }
+
+    /// Demangle a symbol name, reusing the result if the name was demangled before.
+    pub fn demangle_cached(name: &str, cache: &DemangleCache) -> Self {
+        let (demangled, name_as_str) = cache.demangle(name);
+
+        Self {
+            name_as_str,
+            name: demangled,
+            module: None,
+            is_intrinsics: is_name_an_intrinsic(name),
+        }
+    }
+
     #[inline]
     pub fn name(&self) -> &[Token] {
         self.name.tokens()
     }
 
+    /// Tokens of the demangled name along with the part of the name they're in.
+    #[inline]
+    pub fn tokens(&self) -> NameTokens<'_> {
+        NameTokens::new(self.name.tokens())
+    }
+
     #[inline]
     pub fn module(&self) -> Option<&str> {
         self.module.as_deref()
@@ -123,6 +211,18 @@ pub struct Index {
     /// Efficient string match searcher.
     pub prefixes: prefix::PrefixMatcher,
 
+    /// Layouts of structures described by the debug info.
+    pub structs: Vec<types::Struct>,
+
+    /// Constants of enumerations described by the debug info.
+    pub enums: Vec<types::Enum>,
+
+    /// Names that were demangled, shared by symbols added after parsing.
+    pub demangled: DemangleCache,
+
+    /// Names of the modules symbols are imported from.
+    modules: InternMap<Box<str>, str>,
+
     /// Number of named compiler artifacts.
     named_len: usize,
 }
@@ -143,7 +243,11 @@ impl Index {
         };
 
         match dwarf {
-            Ok(dwarf) => this.file_attrs.extend(dwarf.file_attrs),
+            Ok(dwarf) => {
+                this.file_attrs.extend(dwarf.file_attrs);
+                this.structs.extend(dwarf.structs);
+                this.enums.extend(dwarf.enums);
+            }
             Err(err) => log::complex!(
                 w "[dwarf::parse] ",
                 y format!("Failed to parse dwarf: {err:?}"),
@@ -167,28 +271,36 @@ impl Index {
         //       otherwise the PDB will be dropped and so will the symbols.
         if let Some(ref mut pdb) = pdb {
             this.file_attrs.extend(std::mem::take(&mut pdb.file_attrs));
+            this.structs.extend(std::mem::take(&mut pdb.structs));
+            this.enums.extend(std::mem::take(&mut pdb.enums));
             syms.extend(std::mem::take(&mut pdb.syms));
         }
 
-        log::PROGRESS.set("Parsing symbols.", syms.len());
-        parallel_compute(syms.mapping, &mut this.syms, |Addressed { addr, item }| {
-            let demangled = demangler::parse(item.name);
-            let is_intrinsics = is_name_an_intrinsic(item.name);
-            let name_as_str = String::from_iter(demangled.tokens().iter().map(|t| &t.text[..]));
-            let name_as_str = Arc::from(name_as_str);
-            let symbol = Symbol {
-                name_as_str,
-                name: demangled,
-                module: item.module.map(|x| x.to_string()),
-                is_intrinsics,
-            };
-
-            log::PROGRESS.step();
-            Addressed {
-                addr: *addr,
-                item: Arc::new(symbol),
-            }
-        });
+        let task = log::PROGRESS.start("Parsing symbols", syms.len());
+        let task = &task;
+        let cache = &this.demangled;
+        let modules = &this.modules;
+        log::time!(
+            "Demangling",
+            parallel_compute(syms.mapping, &mut this.syms, |Addressed { addr, item }| {
+                // imports from the same module share its name
+                let module = item.module.map(|module| match modules.get(module) {
+                    Some(module) => module,
+                    None => modules.add(Box::from(module), module),
+                });
+
+                let symbol = Symbol {
+                    module,
+                    ..Symbol::demangle_cached(item.name, cache)
+                };
+
+                task.step();
+                Addressed {
+                    addr: *addr,
+                    item: Arc::new(symbol),
+                }
+            })
+        );
 
         this.sort_and_validate();
         this.build_prefix_tree();
@@ -196,7 +308,9 @@ impl Index {
         log::complex!(
             w "[index::parse] found ",
             g this.syms.len().to_string(),
-            w " functions."
+            w " functions with ",
+            g this.demangled.len().to_string(),
+            w " distinct names."
         );
 
         Ok(this)
@@ -230,16 +344,16 @@ impl Index {
     }
 
     fn build_prefix_tree(&mut self) {
-        log::PROGRESS.set("Building prefix tree", self.syms.len() + 1);
+        let task = log::PROGRESS.start("Building prefix tree", self.syms.len() + 1);
 
         // Radix-prefix tree for fast lookups.
         for Addressed { item: func, .. } in self.syms.iter() {
             self.prefixes.insert(func);
-            log::PROGRESS.step();
+            task.step();
         }
 
         self.prefixes.reorder();
-        log::PROGRESS.step();
+        task.step();
     }
 
     pub fn named_funcs_count(&self) -> usize {
@@ -268,13 +382,48 @@ impl Index {
         self.syms.iter().find(|func| func.item.as_str() == name).map(|func| func.addr)
     }
 
+    /// Name functions that were recognized after parsing.
+    /// Addresses that already have a symbol keep their name.
+    pub fn insert_functions(&mut self, funcs: Vec<(usize, String)>) {
+        let mut inserted = AddressMap::default();
+        for (addr, name) in funcs {
+            if addr == 0 || self.syms.search(addr).is_ok() {
+                continue;
+            }
+
+            if let Err(idx) = inserted.search(addr) {
+                let item = Arc::new(Symbol::demangle_cached(&name, &self.demangled));
+                inserted.insert(idx, Addressed { addr, item });
+            }
+        }
+
+        for Addressed { item: func, .. } in inserted.iter() {
+            self.prefixes.insert(func);
+        }
+
+        self.prefixes.reorder();
+        self.named_len += inserted.iter().filter(|func| !func.item.intrinsic()).count();
+        self.syms.extend(inserted);
+        self.syms.sort_unstable();
+    }
+
+    /// Add source lines that were recognized after parsing.
+    /// Addresses that already have a source line keep it.
+    pub fn insert_file_attrs(&mut self, file_attrs: Vec<Addressed<FileAttr>>) {
+        for file_attr in file_attrs {
+            if let Err(idx) = self.file_attrs.search(file_attr.addr) {
+                self.file_attrs.insert(idx, file_attr);
+            }
+        }
+    }
+
     /// Only used for tests.
     #[doc(hidden)]
     pub fn insert_func(&mut self, addr: usize, name: &str) {
         self.syms.push(Addressed {
             addr,
             item: Arc::new(Symbol {
-                name: TokenStream::simple(name),
+                name: Arc::new(TokenStream::simple(name)),
                 name_as_str: Arc::from(name),
                 module: None,
                 is_intrinsics: false,
@@ -305,13 +454,13 @@ pub fn macho_dwarf(obj: &object::File, path: &Path) -> Result<Dwarf, dwarf::Erro
         let dsymutil_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("bin/dsymutil_aarch64");
 
         if dsymutil_path.exists() {
-            log::PROGRESS.set("Running dsymutil.", 1);
+            let task = log::PROGRESS.start("Running dsymutil", 1);
             let exit_status = std::process::Command::new(dsymutil_path)
                 .arg("--linker=parallel")
                 .arg(path)
                 .spawn()?
                 .wait()?;
-            log::PROGRESS.step();
+            task.step();
 
             if !exit_status.success() {
                 log::complex!(
@@ -4,7 +4,10 @@ use crate::TokenStream;
 use tokenizing::Color32;
 
 /// Max recursion depth
-const MAX_DEPTH: usize = 256;
+const MAX_DEPTH: usize = 128;
+
+/// Scope of a path that isn't nested inside of anything.
+static NO_SCOPE: Scope = Scope(Vec::new());
 
 #[derive(Debug)]
 pub(super) struct Backrefs {
@@ -86,8 +89,6 @@ pub(super) struct Context<'a> {
 impl Context<'_> {
     /// Create an initialized parser that hasn't started parsing yet.
     pub fn new(s: &str) -> Self {
-        static NO_SCOPE: Scope = Scope(Vec::new());
-
         Self {
             stream: TokenStream::new(s),
             offset: 0,
@@ -99,6 +100,13 @@ impl Context<'_> {
         }
     }
 
+    /// Run a closure that demangles a path outside of the scope of the symbol being demangled.
+    pub fn without_scope<F: FnOnce(&mut Self)>(&mut self, f: F) {
+        let scope = std::mem::replace(&mut self.scope, &NO_SCOPE);
+        f(self);
+        self.scope = scope;
+    }
+
     /// Pushes a [`Literal`] to the [`TokenStream`], resolving any indexing within a literal.
     pub fn push_literal(&mut self, literal: &Literal, color: Color32) {
         let literal = &self.stream.inner()[literal.start..literal.end];
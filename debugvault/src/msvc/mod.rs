@@ -67,7 +67,6 @@ mod tests;
 
 use bitflags::bitflags;
 use context::{Backrefs, Context};
-use tokenizing::colors;
 use config::CONFIG;
 
 #[cfg(test)]
@@ -228,6 +227,16 @@ enum Type {
 
 impl Parse for Type {
     fn parse(ctx: &mut Context, backrefs: &mut Backrefs) -> Option<Self> {
+        // pointers, references and function parameters nest types arbitrarily deep
+        ctx.descent()?;
+        let tipe = Type::parse_nested(ctx, backrefs);
+        ctx.ascent();
+        tipe
+    }
+}
+
+impl Type {
+    fn parse_nested(ctx: &mut Context, backrefs: &mut Backrefs) -> Option<Self> {
         match ctx.peek_slice(..2) {
             Some(b"W4") => {
                 ctx.offset += 2;
@@ -604,19 +613,19 @@ impl<'a> PositionalDemangle<'a> for Type {
             Type::Function(func) => {
                 func.return_type.demangle_pre(ctx, backrefs);
                 func.calling_conv.demangle(ctx, backrefs);
-                ctx.stream.push(" ", colors::WHITE);
+                ctx.stream.push(" ", CONFIG.colors.text);
             }
             Type::MemberFunction(func) => {
                 func.storage_scope.demangle(ctx, backrefs);
                 func.return_type.demangle_pre(ctx, backrefs);
                 func.calling_conv.demangle(ctx, backrefs);
-                ctx.stream.push(" ", colors::WHITE);
+                ctx.stream.push(" ", CONFIG.colors.text);
             }
             Type::MemberFunctionPtr(func) => {
                 func.storage_scope.demangle(ctx, backrefs);
                 func.return_type.demangle_pre(ctx, backrefs);
                 func.calling_conv.demangle(ctx, backrefs);
-                ctx.stream.push(" ", colors::WHITE);
+                ctx.stream.push(" ", CONFIG.colors.text);
                 func.class_name.demangle(ctx, backrefs);
             }
             Type::Inherited(func) => {
@@ -624,7 +633,7 @@ impl<'a> PositionalDemangle<'a> for Type {
                 func.storage_scope.demangle(ctx, backrefs);
                 func.return_type.demangle_pre(ctx, backrefs);
                 func.calling_conv.demangle(ctx, backrefs);
-                ctx.stream.push(" ", colors::WHITE);
+                ctx.stream.push(" ", CONFIG.colors.text);
                 func.class_name.demangle(ctx, backrefs);
                 func.params.demangle(ctx, backrefs);
             }
@@ -650,7 +659,7 @@ impl<'a> PositionalDemangle<'a> for Type {
                 storage.demangle(ctx, backrefs);
                 tipe.demangle_pre(ctx, backrefs);
                 modi.demangle(ctx, backrefs);
-                ctx.stream.push(" ", colors::WHITE);
+                ctx.stream.push(" ", CONFIG.colors.text);
             }
             Type::Encoded(_) => {}
             Type::Array(array) => {
@@ -752,9 +761,8 @@ impl Parse for SymbolType {
                 ctx.offset -= 1;
                 Type::Variable(Variable::parse(ctx, backrefs)?)
             }
-            b'5' => {
-                todo!()
-            }
+            // there's no storage class '5'
+            b'5' => return None,
             // virtual function table
             b'6' => {
                 let quali = Qualifiers::parse(ctx, backrefs)?;
@@ -1082,7 +1090,7 @@ impl<'a> PositionalDemangle<'a> for FunctionReturnType {
     fn demangle_pre(&'a self, ctx: &mut Context<'a>, backrefs: &mut Backrefs) {
         self.0.demangle_pre(ctx, backrefs);
         if self.0 != Type::Unit {
-            ctx.stream.push(" ", colors::WHITE);
+            ctx.stream.push(" ", CONFIG.colors.text);
         }
     }
 
@@ -1332,8 +1340,9 @@ impl<'a> Demangle<'a> for Intrinsics {
     fn demangle(&'a self, ctx: &mut Context<'a>, backrefs: &mut Backrefs) {
         let literal = match *self {
             Intrinsics::Ctor => {
+                // a template named after its own constructor would otherwise demangle forever
                 match ctx.scope.0.first() {
-                    Some(path) => path.demangle(ctx, backrefs),
+                    Some(path) => ctx.without_scope(|ctx| path.demangle(ctx, backrefs)),
                     _ => {
                         ctx.stream.push("`", CONFIG.colors.brackets);
                         ctx.stream.push("unnamed constructor", CONFIG.colors.asm.primitive);
@@ -1346,7 +1355,7 @@ impl<'a> Demangle<'a> for Intrinsics {
                 ctx.stream.push("~", CONFIG.colors.asm.component);
 
                 match ctx.scope.0.first() {
-                    Some(path) => path.demangle(ctx, backrefs),
+                    Some(path) => ctx.without_scope(|ctx| path.demangle(ctx, backrefs)),
                     _ => {
                         ctx.stream.push("`", CONFIG.colors.brackets);
                         ctx.stream.push("unnamed destructor", CONFIG.colors.asm.primitive);
@@ -2169,18 +2178,12 @@ impl Parse for NestedPath {
                     ctx.offset += 1;
 
                     if ctx.eat_slice(b"0x") {
-                        let mut len = 0;
-
                         // skip over anonymous namespace disambiguator
                         while let Some(b'0'..=b'9' | b'a'..=b'f' | b'A'..=b'F') = ctx.peek() {
-                            len += 1;
                             ctx.offset += 1;
                         }
 
-                        backrefs.memorize_path(&NestedPath::Literal(Literal {
-                            start: ctx.offset,
-                            end: ctx.offset + len,
-                        }));
+                        backrefs.memorize_path(&NestedPath::Anonymous);
                     }
 
                     ctx.consume(b'@')?;
@@ -2395,7 +2398,6 @@ impl Parse for Symbol {
                 n = n.checked_add(digit)?;
             }
 
-            ctx.ascent();
             log::complex!(
                 w "[parse::parse] ",
                 y "TODO: return thread safe static guard"
@@ -2436,7 +2438,7 @@ impl<'a> Demangle<'a> for Symbol {
             if let Type::MemberFunction(ref func) = self.tipe {
                 func.storage_scope.demangle(ctx, backrefs);
                 func.calling_conv.demangle(ctx, backrefs);
-                ctx.stream.push(" ", colors::WHITE);
+                ctx.stream.push(" ", CONFIG.colors.text);
                 self.path.scope.demangle(ctx, backrefs);
                 ctx.stream.push("::", CONFIG.colors.delimiter);
                 ctx.stream.push("operator ", CONFIG.colors.asm.primitive);
@@ -2452,7 +2454,7 @@ impl<'a> Demangle<'a> for Symbol {
                 if let Type::MemberFunction(ref func) = self.tipe {
                     func.storage_scope.demangle(ctx, backrefs);
                     func.calling_conv.demangle(ctx, backrefs);
-                    ctx.stream.push(" ", colors::WHITE);
+                    ctx.stream.push(" ", CONFIG.colors.text);
                     self.path.scope.demangle(ctx, backrefs);
                     ctx.stream.push("::", CONFIG.colors.delimiter);
                     ctx.stream.push("operator", CONFIG.colors.asm.primitive);
@@ -74,6 +74,29 @@ fn anonymous() {
     eq!("?A@?A0x43583946@@3VB@@B" => "class B const `anonymous namespace'::A");
 }
 
+#[test]
+fn anonymous_backref() {
+    eq!("?f@?A0x43583946@1@YAXXZ" =>
+        "void __cdecl `anonymous namespace'::`anonymous namespace'::f(void)");
+    eq!("?g@?A0x4358394643583946@1@YAXXZ" =>
+        "void __cdecl `anonymous namespace'::`anonymous namespace'::g(void)");
+}
+
+#[test]
+fn malformed() {
+    // there's no storage class '5'
+    assert!(parse("?x@@5H").is_none());
+    // thread safe static guard followed by a symbol
+    assert!(parse("?$TSS0@?1??GetCategoryMap@CDynamicRegistrationInfoSource@XPerfAddIn@@SAPEBU_ATL_CATMAP_ENTRY@ATL@@XZ@4HA").is_some());
+    // template named after a constructor of itself
+    assert!(parse("??$?0_W@0@").is_some());
+    // pointers and function pointers nested 10,000 times
+    let pointers = format!("?f@@YAX{}H@Z", "PEA".repeat(10_000));
+    let functions = format!("?f@@YAX{}H{}@Z", "P6AX".repeat(10_000), "@Z".repeat(10_000));
+    assert!(parse(&pointers).is_none());
+    assert!(parse(&functions).is_none());
+}
+
 #[test]
 fn triple_q0() {
     eq!("??__E??_7name0@name1@@6B@@@YMXXZ@?A0x647dec29@@$$FYMXXZ" =>
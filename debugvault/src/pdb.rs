@@ -1,13 +1,18 @@
 use crate::intern::InternMap;
+use crate::types::{natural_align, Enum, Field, Struct};
 use crate::{AddressMap, Addressed, FileAttr, RawSymbol};
 use crossbeam_queue::SegQueue;
 use object::Object;
-use pdb::{FallibleIterator, SymbolData};
+use pdb::{FallibleIterator, PrimitiveKind, SymbolData, TypeData};
 use std::borrow::Cow;
+use std::collections::HashSet;
 use std::path::Path;
 use std::pin::Pin;
 use std::sync::Arc;
 
+/// Types followed when naming the type of a member, e.g. a pointer to a const typedef.
+const MAX_TYPE_DEPTH: usize = 8;
+
 pub struct PDB<'data> {
     /// Mapping from addresses starting at the header base to source files.
     pub file_attrs: AddressMap<FileAttr>,
@@ -17,6 +22,10 @@ pub struct PDB<'data> {
     dbi: pdb::DebugInformation<'data>,
     /// Mapping from addresses starting at the header base to functions.
     pub syms: AddressMap<RawSymbol<'data>>,
+    /// Layouts of the named structures, the first definition of a name wins.
+    pub structs: Vec<Struct>,
+    /// Constants of the named enumerations, the first definition of a name wins.
+    pub enums: Vec<Enum>,
 }
 
 impl<'data> PDB<'data> {
@@ -43,6 +52,8 @@ fn parse_pdb<'data>(
         global_syms: pdb.global_symbols()?,
         dbi: pdb.debug_information()?,
         syms: AddressMap::default(),
+        structs: Vec::new(),
+        enums: Vec::new(),
     });
 
     // Mapping from offset's to rva's.
@@ -86,7 +97,7 @@ fn parse_pdb<'data>(
         id += 1;
     }
 
-    log::PROGRESS.set("Parsing pdb.", module_info_queue.len());
+    let task = log::PROGRESS.start("Parsing pdb", module_info_queue.len());
 
     // Parse local symbols.
     let mut file_attrs = AddressMap::default();
@@ -110,7 +121,7 @@ fn parse_pdb<'data>(
                             &mut file_attrs,
                             &mut syms,
                         )?;
-                        log::PROGRESS.step();
+                        task.step();
                     }
 
                     Ok((file_attrs, syms))
@@ -170,9 +181,206 @@ fn parse_pdb<'data>(
         };
     }
 
+    match parse_types(&mut pdb) {
+        Ok((structs, enums)) => {
+            this.structs = structs;
+            this.enums = enums;
+        }
+        Err(err) => log::complex!(
+            w "[index::pdb::parse] ",
+            y format!("Failed to parse types: {err}."),
+        ),
+    }
+
     Ok(this)
 }
 
+/// Structures and enumerations described by the type information.
+fn parse_types<'s, S: pdb::Source<'s> + 's>(
+    pdb: &mut pdb::PDB<'s, S>,
+) -> Result<(Vec<Struct>, Vec<Enum>), pdb::Error> {
+    let info = pdb.type_information()?;
+    let mut finder = info.finder();
+    let mut iter = info.iter();
+
+    // members can only be looked up once the finder has seen every type
+    let mut classes = Vec::new();
+    let mut enumerations = Vec::new();
+    while let Some(item) = iter.next()? {
+        finder.update(&iter);
+        match item.parse() {
+            Ok(TypeData::Class(class)) if !class.properties.forward_reference() => {
+                classes.push(class);
+            }
+            Ok(TypeData::Enumeration(enumeration))
+                if !enumeration.properties.forward_reference() =>
+            {
+                enumerations.push(enumeration);
+            }
+            _ => {}
+        }
+    }
+
+    let mut structs = Vec::new();
+    let mut seen = HashSet::new();
+    for class in classes {
+        let name = class.name.to_string().into_owned();
+        if !seen.insert(name.clone()) {
+            continue;
+        }
+
+        let mut fields = Vec::new();
+        let mut list = class.fields;
+        while let Some(idx) = list.take() {
+            if let Ok(TypeData::FieldList(members)) = finder.find(idx)?.parse() {
+                for member in members.fields {
+                    if let TypeData::Member(member) = member {
+                        let (ty, size, align) = type_name(&finder, member.field_type, 0);
+                        fields.push(Field {
+                            name: member.name.to_string().into_owned(),
+                            ty,
+                            offset: member.offset as usize,
+                            size,
+                            align,
+                        });
+                    }
+                }
+
+                list = members.continuation;
+            }
+        }
+
+        fields.sort_by_key(|field| field.offset);
+        structs.push(Struct {
+            name,
+            size: class.size as usize,
+            fields,
+        });
+    }
+
+    let mut enums = Vec::new();
+    let mut seen = HashSet::new();
+    for enumeration in enumerations {
+        let name = enumeration.name.to_string().into_owned();
+        if !seen.insert(name.clone()) {
+            continue;
+        }
+
+        let mut variants = Vec::new();
+        let mut list = Some(enumeration.fields);
+        while let Some(idx) = list.take() {
+            if let Ok(TypeData::FieldList(constants)) = finder.find(idx)?.parse() {
+                for constant in constants.fields {
+                    if let TypeData::Enumerate(constant) = constant {
+                        let value = match constant.value {
+                            pdb::Variant::U8(value) => value as u64,
+                            pdb::Variant::U16(value) => value as u64,
+                            pdb::Variant::U32(value) => value as u64,
+                            pdb::Variant::U64(value) => value,
+                            pdb::Variant::I8(value) => value as u64,
+                            pdb::Variant::I16(value) => value as u64,
+                            pdb::Variant::I32(value) => value as u64,
+                            pdb::Variant::I64(value) => value as u64,
+                        };
+
+                        variants.push((constant.name.to_string().into_owned(), value));
+                    }
+                }
+
+                list = constants.continuation;
+            }
+        }
+
+        enums.push(Enum { name, variants });
+    }
+
+    if !structs.is_empty() || !enums.is_empty() {
+        log::complex!(
+            w "[index::pdb::parse] found ",
+            g structs.len().to_string(),
+            w " structures and ",
+            g enums.len().to_string(),
+            w " enumerations."
+        );
+    }
+
+    Ok((structs, enums))
+}
+
+/// Type as written in C, its size and its alignment.
+///
+/// Alignment isn't described, so it's assumed to be the natural alignment of the type's size
+/// unless it's derived from another type.
+fn type_name(
+    finder: &pdb::TypeFinder,
+    idx: pdb::TypeIndex,
+    depth: usize,
+) -> (String, usize, usize) {
+    let data = match finder.find(idx).and_then(|item| item.parse()) {
+        Ok(data) if depth < MAX_TYPE_DEPTH => data,
+        _ => return ("?".to_string(), 0, 1),
+    };
+
+    let pointer = |inner: String, size: usize| {
+        let separator = if inner.ends_with('*') { "" } else { " " };
+        (format!("{inner}{separator}*"), size, size)
+    };
+
+    match data {
+        TypeData::Primitive(primitive) => {
+            let (name, size) = match primitive.kind {
+                PrimitiveKind::Char | PrimitiveKind::RChar | PrimitiveKind::I8 => ("char", 1),
+                PrimitiveKind::UChar | PrimitiveKind::U8 => ("unsigned char", 1),
+                PrimitiveKind::Bool8 => ("bool", 1),
+                PrimitiveKind::WChar => ("wchar_t", 2),
+                PrimitiveKind::Short | PrimitiveKind::I16 => ("short", 2),
+                PrimitiveKind::UShort | PrimitiveKind::U16 => ("unsigned short", 2),
+                PrimitiveKind::Long | PrimitiveKind::I32 => ("int", 4),
+                PrimitiveKind::ULong | PrimitiveKind::U32 => ("unsigned int", 4),
+                PrimitiveKind::Quad | PrimitiveKind::I64 => ("long long", 8),
+                PrimitiveKind::UQuad | PrimitiveKind::U64 => ("unsigned long long", 8),
+                PrimitiveKind::F32 => ("float", 4),
+                PrimitiveKind::F64 => ("double", 8),
+                PrimitiveKind::Void => ("void", 0),
+                _ => ("?", 0),
+            };
+
+            match primitive.indirection {
+                Some(pdb::Indirection::Near64) => pointer(name.to_string(), 8),
+                Some(..) => pointer(name.to_string(), 4),
+                None => (name.to_string(), size, natural_align(size)),
+            }
+        }
+        TypeData::Pointer(ptr) => {
+            let (inner, ..) = type_name(finder, ptr.underlying_type, depth + 1);
+            pointer(inner, ptr.attributes.size() as usize)
+        }
+        // qualifiers don't change how a value is shown
+        TypeData::Modifier(modifier) => type_name(finder, modifier.underlying_type, depth + 1),
+        TypeData::Class(class) => (
+            format!("struct {}", class.name.to_string()),
+            class.size as usize,
+            natural_align(class.size as usize),
+        ),
+        TypeData::Union(union) => (
+            format!("union {}", union.name.to_string()),
+            union.size as usize,
+            natural_align(union.size as usize),
+        ),
+        TypeData::Enumeration(enumeration) => {
+            let (_, size, align) = type_name(finder, enumeration.underlying_type, depth + 1);
+            let name = enumeration.name.to_string();
+            (format!("enum {name}"), size, align)
+        }
+        TypeData::Array(array) => {
+            let (inner, size, align) = type_name(finder, array.element_type, depth + 1);
+            let total = array.dimensions.last().copied().unwrap_or(0) as usize;
+            (format!("{inner}[{}]", total / size.max(1)), total, align)
+        }
+        _ => ("?".to_string(), 0, 1),
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 fn parse_pdb_module<'data>(
     module_id: u64,
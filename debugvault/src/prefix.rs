@@ -28,8 +28,11 @@ impl PrefixMatcher {
     }
 
     /// Sorts elements to allow for searching.
+    ///
+    /// Sorted by the whole string, as [`cmp`] treats prefixes as equal which isn't a total order.
+    /// Strings with the same prefix still end up next to each other.
     pub fn reorder(&mut self) {
-        self.items.sort_unstable_by(|a, b| cmp(a.as_str(), b.as_str()));
+        self.items.sort_unstable_by(|a, b| a.as_str().cmp(b.as_str()));
         self.items.shrink_to_fit();
     }
 
@@ -81,7 +84,7 @@ mod test {
     fn symbol(s: &str) -> Arc<Symbol> {
         Arc::new(Symbol {
             name_as_str: Arc::from(s),
-            name: TokenStream::simple(s),
+            name: Arc::new(TokenStream::simple(s)),
             module: None,
             is_intrinsics: false
         })
@@ -73,11 +73,20 @@
 //! <dyn-bounds> = [<binder>] {<dyn-trait>} "E"
 //! <dyn-trait> = <path> {<dyn-trait-assoc-binding>}
 //! <dyn-trait-assoc-binding> = "p" <undisambiguated-identifier> <type>
-//! <const> = <type> <const-data>
-//!         | "p" // placeholder, shown as _
+//! <const> = <type> <const-data>       // integers, bool and char
+//!         | "e" <const-data>           // *"str"
+//!         | "R" <const>                // &T
+//!         | "Q" <const>                // &mut T
+//!         | "A" {<const>} "E"          // [T, U, ..]
+//!         | "T" {<const>} "E"          // (T, U, ..)
+//!         | "V" <path> <const-fields>  // Struct { .. }
+//!         | "p"                        // placeholder, shown as _
 //!         | <backref>
 //!
 //! <const-data> = ["n"] {<hex-digit>} "_"
+//! <const-fields> = "U"                            // unit struct
+//!                | "T" {<const>} "E"              // tuple struct
+//!                | "S" {<identifier> <const>} "E" // struct with named fields
 //! <base-62-number> = {<0-9a-zA-Z>} "_"
 //! <backref> = "B" <base-62-number>
 //!
@@ -90,11 +99,23 @@ mod tests;
 
 use crate::TokenStream;
 use config::CONFIG;
-use tokenizing::{colors, Color32};
+use tokenizing::Color32;
 
 /// Max recursion depth.
 const MAX_DEPTH: usize = 256;
 
+/// Max number of paths and types visited, including the ones reached through backrefs.
+///
+/// Backrefs can point to other backrefs, so without this bound a short symbol can expand
+/// exponentially and exhaust memory even if it never exceeds [MAX_DEPTH].
+const MAX_COMPLEXITY: usize = 4096;
+
+/// Names given to lifetimes, symbols using more lifetimes fail to parse.
+const LIFETIMES: [&str; 26] = [
+    "'a", "'b", "'c", "'d", "'e", "'f", "'g", "'h", "'i", "'j", "'k", "'l", "'m", "'n", "'o", "'p",
+    "'q", "'r", "'s", "'t", "'u", "'v", "'w", "'x", "'y", "'z",
+];
+
 /// Try to parse a rust v0 symbol.
 pub fn parse(s: &str) -> Option<TokenStream> {
     // macOS prefixes symbols with an extra underscore therefore '__R' is allowed
@@ -116,9 +137,21 @@ struct Parser {
     stream: TokenStream,
     offset: usize,
     depth: usize,
+    complexity: usize,
+    /// Number of lifetimes introduced by binders that are currently in scope.
+    bound_lifetimes: usize,
     printing: bool,
 }
 
+/// Parser state that can be rolled back to when an alternative fails to parse.
+#[derive(Clone, Copy)]
+struct Checkpoint {
+    offset: usize,
+    depth: usize,
+    bound_lifetimes: usize,
+    tokens: usize,
+}
+
 /// Differentiator for nested path's.
 #[derive(PartialEq)]
 enum NameSpace {
@@ -137,6 +170,8 @@ impl<'src> Parser {
             stream: TokenStream::new(s),
             offset: 0,
             depth: 0,
+            complexity: 0,
+            bound_lifetimes: 0,
             printing: true,
         }
     }
@@ -154,6 +189,13 @@ impl<'src> Parser {
         }
     }
 
+    #[inline]
+    fn push_string(&mut self, text: String, color: Color32) {
+        if self.printing {
+            self.stream.push_string(text, color);
+        }
+    }
+
     /// View the current byte in the mangled symbol without incrementing the offset.
     #[inline]
     fn peek(&self) -> Option<u8> {
@@ -168,40 +210,62 @@ impl<'src> Parser {
         matches
     }
 
-    /// Fails if recursion depth is reached, otherwise increments depth.
+    /// Fails if recursion depth or complexity is reached, otherwise increments both.
     #[inline]
     fn recurse_deeper(&mut self) -> Option<()> {
         self.depth += 1;
-        (self.depth < MAX_DEPTH).then_some(())
+        self.complexity += 1;
+        (self.depth < MAX_DEPTH && self.complexity < MAX_COMPLEXITY).then_some(())
+    }
+
+    /// Save the current state of the parser.
+    #[inline]
+    fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            offset: self.offset,
+            depth: self.depth,
+            bound_lifetimes: self.bound_lifetimes,
+            tokens: self.stream.tokens().len(),
+        }
+    }
+
+    /// Undo everything parsed since the [Checkpoint] was made.
+    #[inline]
+    fn restore(&mut self, checkpoint: Checkpoint) {
+        self.offset = checkpoint.offset;
+        self.depth = checkpoint.depth;
+        self.bound_lifetimes = checkpoint.bound_lifetimes;
+        self.stream.truncate(checkpoint.tokens);
     }
 
     /// Run a closure where each function called in it isn't appended to the [TokenStream].
     fn dont_print<F: FnOnce(&mut Self) -> Option<()>>(&mut self, f: F) -> Option<()> {
-        self.printing = false;
-        f(self)?;
-        self.printing = true;
-
-        Some(())
+        let printing = std::mem::replace(&mut self.printing, false);
+        let result = f(self);
+        self.printing = printing;
+        result
     }
 
     /// Run a closure that consumes a base64 number, modifies the offset to that backref.
     /// If the backref is ahead of the current offset, the function fails.
-    /// If the recursion depth is greater than [MAX_DEPTH], the function fails.
-    /// Restores offset after executing the closure.
+    /// If the recursion depth is greater than [MAX_DEPTH] or the complexity is greater than
+    /// [MAX_COMPLEXITY], the function fails.
+    /// Restores offset after executing the closure, even if the closure fails.
     fn backref<F: FnOnce(&mut Self) -> Option<()>>(&mut self, f: F) -> Option<()> {
         self.recurse_deeper()?;
 
-        let current = self.offset;
+        let start = self.offset;
         let backref = self.base62()?;
 
-        if backref >= current {
+        if backref >= start {
             return None;
         }
 
-        let current = self.offset;
+        let saved = self.offset;
         self.offset = backref;
-        f(self)?;
-        self.offset = current;
+        let result = f(self);
+        self.offset = saved;
+        result?;
 
         self.depth -= 1;
         Some(())
@@ -324,13 +388,14 @@ impl<'src> Parser {
     fn generic(&mut self) -> Option<()> {
         if let Some(lifetime) = self.lifetime() {
             self.push(lifetime, CONFIG.colors.asm.annotation);
-            self.push(" ", colors::WHITE);
             return Some(());
         }
 
+        let checkpoint = self.checkpoint();
         if self.tipe().is_some() {
             return Some(());
         }
+        self.restore(checkpoint);
 
         if self.eat(b'K') {
             return self.constant();
@@ -339,94 +404,281 @@ impl<'src> Parser {
         None
     }
 
-    /// Parses a series of ascii hex numbers, ending in a '_'.
-    fn hex_nibbles(&mut self) -> Option<&[u8]> {
-        let mut len = 0;
+    /// Parses a series of lowercase hex digits, ending in a '_'.
+    fn hex_nibbles(&mut self) -> Option<&'src str> {
+        let len = self.src().bytes().position(|b| !matches!(b, b'0'..=b'9' | b'a'..=b'f'))?;
+        let nibbles = &self.src()[..len];
+        self.offset += len;
+        self.eat(b'_').then_some(nibbles)
+    }
 
-        loop {
-            match self.peek()? {
-                b'0'..=b'9' | b'a'..=b'f' => {}
-                b'_' => {
-                    self.offset += 1;
-                    break;
-                }
-                _ => return None,
-            }
+    /// Appends an integer, values that don't fit in an u64 are shown in hex.
+    fn integer(&mut self, signed: bool) -> Option<()> {
+        if signed && self.eat(b'n') {
+            self.push("-", CONFIG.colors.asm.immediate);
+        }
 
-            len += 1;
-            self.offset += 1;
+        let nibbles = self.hex_nibbles()?.trim_start_matches('0');
+        let value = match u64::from_str_radix(nibbles, 16) {
+            Ok(value) => value.to_string(),
+            Err(_) if nibbles.is_empty() => "0".to_string(),
+            Err(_) => format!("0x{nibbles}"),
+        };
+
+        self.push_string(value, CONFIG.colors.asm.immediate);
+        Some(())
+    }
+
+    /// Appends a string literal made up of the hex encoded bytes of an utf-8 string.
+    fn string(&mut self) -> Option<()> {
+        let nibbles = self.hex_nibbles()?.as_bytes();
+        if nibbles.len() % 2 != 0 {
+            return None;
         }
 
-        self.offset -= 1;
-        let hex_nibbles = self.src()[..len].as_bytes();
-        self.offset += 1;
-        Some(hex_nibbles)
+        let bytes = nibbles
+            .chunks_exact(2)
+            .map(|byte| u8::from_str_radix(std::str::from_utf8(byte).ok()?, 16).ok())
+            .collect::<Option<Vec<u8>>>()?;
+
+        let string = String::from_utf8(bytes).ok()?;
+        self.push_string(format!("{string:?}"), CONFIG.colors.asm.string);
+        Some(())
     }
 
-    /// Appends a constant which is either a placeholder 'p', backref or a series of hex numbers.
+    /// Appends a constant generic argument.
+    ///
+    /// Constants that aren't a literal are wrapped in braces, like they would be in source code.
     fn constant(&mut self) -> Option<()> {
-        // placeholder
-        if self.eat(b'p') {
-            self.push("_", CONFIG.colors.brackets);
-            return Some(());
+        let literal = match self.src().as_bytes() {
+            [b'R', b'e', ..] => true,
+            [b'e' | b'R' | b'Q' | b'A' | b'T' | b'V', ..] => false,
+            _ => true,
+        };
+
+        if literal {
+            return self.const_value();
+        }
+
+        self.push("{ ", CONFIG.colors.brackets);
+        self.const_value()?;
+        self.push(" }", CONFIG.colors.brackets);
+        Some(())
+    }
+
+    /// Appends a constant value, failing if the recursion depth is greater than [MAX_DEPTH].
+    fn const_value(&mut self) -> Option<()> {
+        self.recurse_deeper()?;
+
+        match self.peek()? {
+            // placeholder
+            b'p' => {
+                self.offset += 1;
+                self.push("_", CONFIG.colors.brackets);
+            }
+            // unsigned integers
+            b'h' | b't' | b'm' | b'y' | b'o' | b'j' => {
+                self.offset += 1;
+                self.integer(false)?;
+            }
+            // signed integers
+            b'a' | b's' | b'l' | b'x' | b'n' | b'i' => {
+                self.offset += 1;
+                self.integer(true)?;
+            }
+            b'b' => {
+                self.offset += 1;
+
+                let value = match self.hex_nibbles()? {
+                    "0" => "false",
+                    "1" => "true",
+                    _ => return None,
+                };
+
+                self.push(value, CONFIG.colors.asm.immediate);
+            }
+            b'c' => {
+                self.offset += 1;
+
+                let value = u32::from_str_radix(self.hex_nibbles()?, 16).ok()?;
+                let chr = char::from_u32(value)?;
+                self.push_string(format!("{chr:?}"), CONFIG.colors.asm.string);
+            }
+            // *"str"
+            b'e' => {
+                self.offset += 1;
+
+                self.push("*", CONFIG.colors.asm.pointer);
+                self.string()?;
+            }
+            // &T
+            b'R' => {
+                self.offset += 1;
+
+                // &*"str" is shown as just "str"
+                if self.eat(b'e') {
+                    self.string()?;
+                } else {
+                    self.push("&", CONFIG.colors.asm.pointer);
+                    self.const_value()?;
+                }
+            }
+            // &mut T
+            b'Q' => {
+                self.offset += 1;
+
+                self.push("&", CONFIG.colors.asm.pointer);
+                self.push("mut ", CONFIG.colors.asm.annotation);
+                self.const_value()?;
+            }
+            // [T, U, ..]
+            b'A' => {
+                self.offset += 1;
+
+                self.push("[", CONFIG.colors.brackets);
+                self.delimited(", ", Self::const_value)?;
+                self.push("]", CONFIG.colors.brackets);
+            }
+            // (T, U, ..)
+            b'T' => {
+                self.offset += 1;
+
+                self.push("(", CONFIG.colors.brackets);
+                self.const_tuple()?;
+                self.push(")", CONFIG.colors.brackets);
+            }
+            // Struct { .. }
+            b'V' => {
+                self.offset += 1;
+                self.path()?;
+
+                match self.peek()? {
+                    b'U' => self.offset += 1,
+                    b'T' => {
+                        self.offset += 1;
+
+                        self.push("(", CONFIG.colors.brackets);
+                        self.delimited(", ", Self::const_value)?;
+                        self.push(")", CONFIG.colors.brackets);
+                    }
+                    b'S' => {
+                        self.offset += 1;
+
+                        self.push(" { ", CONFIG.colors.brackets);
+                        self.delimited(", ", |this| {
+                            this.disambiguator();
+                            let field = this.ident()?;
+                            this.push(field, CONFIG.colors.asm.component);
+                            this.push(": ", CONFIG.colors.delimiter);
+                            this.const_value()
+                        })?;
+                        self.push(" }", CONFIG.colors.brackets);
+                    }
+                    _ => return None,
+                }
+            }
+            b'B' => {
+                self.offset += 1;
+                self.backref(Self::const_value)?;
+            }
+            _ => return None,
         }
 
-        if self.eat(b'B') {
-            return self.backref(Self::constant);
+        self.depth -= 1;
+        Some(())
+    }
+
+    /// Appends the values of a tuple till the 'E' (end of list) character appears.
+    fn const_tuple(&mut self) -> Option<()> {
+        let mut len = 0;
+        while !self.eat(b'E') {
+            if len != 0 {
+                self.push(", ", CONFIG.colors.asm.expr);
+            }
+
+            self.const_value()?;
+            len += 1;
         }
 
-        // NOTE: can't implement generic constants using just &'static str's
+        // tuples of a single value need a trailing comma
+        if len == 1 {
+            self.push(",", CONFIG.colors.asm.expr);
+        }
 
-        self.offset += 1;
-        self.hex_nibbles()?;
-        self.push("_", CONFIG.colors.brackets);
         Some(())
     }
 
-    /// Parses a lifetime if it's not a '_ or a part of token that
-    /// ends up using more than 25 lifetimes.
-    fn lifetime(&mut self) -> Option<&'src str> {
+    /// Parses a lifetime, erased lifetimes are shown as '_.
+    ///
+    /// Lifetimes are indexed starting at the innermost lifetime bound by a binder. Any lifetimes
+    /// that aren't bound are named as if they were bound after all the other lifetimes.
+    fn lifetime(&mut self) -> Option<&'static str> {
         if !self.eat(b'L') {
             return None;
         }
 
-        Some(match self.base62()? {
-            1 => "'a",
-            2 => "'b",
-            3 => "'c",
-            4 => "'d",
-            5 => "'e",
-            6 => "'f",
-            7 => "'g",
-            8 => "'h",
-            9 => "'i",
-            10 => "'j",
-            11 => "'k",
-            12 => "'l",
-            13 => "'m",
-            14 => "'n",
-            15 => "'o",
-            16 => "'p",
-            17 => "'q",
-            18 => "'s",
-            19 => "'t",
-            20 => "'u",
-            21 => "'v",
-            22 => "'w",
-            23 => "'x",
-            24 => "'y",
-            25 => "'z",
-            _ => return None,
-        })
+        let lifetime = self.base62()?;
+        if lifetime == 0 {
+            return Some("'_");
+        }
+
+        let idx = self.bound_lifetimes.checked_sub(lifetime).unwrap_or(lifetime - 1);
+        LIFETIMES.get(idx).copied()
+    }
+
+    /// Appends an optional lifetime surrounded by `before` and `after`, erased lifetimes aren't
+    /// shown at all.
+    fn print_lifetime(&mut self, before: &'static str, after: &'static str) {
+        match self.lifetime() {
+            Some("'_") | None => {}
+            Some(lifetime) => {
+                if !before.is_empty() {
+                    self.push(before, CONFIG.colors.asm.expr);
+                }
+                self.push(lifetime, CONFIG.colors.asm.annotation);
+                if !after.is_empty() {
+                    self.push(after, CONFIG.colors.text);
+                }
+            }
+        }
     }
 
-    /// Parses a index into the lifetimes being used.
+    /// Parses the number of lifetimes bound by a binder, zero if there isn't a binder.
     fn binder(&mut self) -> Option<usize> {
         if !self.eat(b'G') {
-            return None;
+            return Some(0);
+        }
+
+        self.base62()?.checked_add(1)
+    }
+
+    /// Appends a `for<'a, 'b, ..>` and binds it's lifetimes till [Parser::unbind_lifetimes].
+    fn bind_lifetimes(&mut self, count: usize) -> Option<()> {
+        if count == 0 {
+            return Some(());
         }
 
-        self.base62()
+        let depth = self.bound_lifetimes;
+        let lifetimes = LIFETIMES.get(depth..depth.checked_add(count)?)?;
+
+        self.push("for", CONFIG.colors.asm.pointer);
+        self.push("<", CONFIG.colors.asm.annotation);
+        for (idx, lifetime) in lifetimes.iter().enumerate() {
+            if idx != 0 {
+                self.push(", ", CONFIG.colors.asm.expr);
+            }
+            self.push(lifetime, CONFIG.colors.asm.annotation);
+        }
+        self.push("> ", CONFIG.colors.asm.annotation);
+
+        self.bound_lifetimes += count;
+        Some(())
+    }
+
+    /// Releases lifetimes bound using [Parser::bind_lifetimes].
+    #[inline]
+    fn unbind_lifetimes(&mut self, count: usize) {
+        self.bound_lifetimes -= count;
     }
 
     /// Parses a type that can be represented using just a single character.
@@ -587,10 +839,12 @@ impl<'src> Parser {
         }
 
         // named type
+        let checkpoint = self.checkpoint();
         if self.path().is_some() {
             self.depth -= 1;
             return Some(());
         }
+        self.restore(checkpoint);
 
         match self.peek()? {
             // [T; N]
@@ -600,7 +854,7 @@ impl<'src> Parser {
                 self.push("[", CONFIG.colors.brackets);
                 self.tipe()?;
                 self.push("; ", CONFIG.colors.brackets);
-                self.constant()?;
+                self.const_value()?;
                 self.push("]", CONFIG.colors.brackets);
             }
             // [T]
@@ -624,10 +878,7 @@ impl<'src> Parser {
                 self.offset += 1;
 
                 self.push("&", CONFIG.colors.asm.pointer);
-                if let Some(lifetime) = self.lifetime() {
-                    self.push(lifetime, CONFIG.colors.asm.annotation);
-                    self.push(" ", colors::WHITE);
-                }
+                self.print_lifetime("", " ");
 
                 self.tipe()?;
             }
@@ -636,10 +887,7 @@ impl<'src> Parser {
                 self.offset += 1;
 
                 self.push("&", CONFIG.colors.asm.pointer);
-                if let Some(lifetime) = self.lifetime() {
-                    self.push(lifetime, CONFIG.colors.asm.annotation);
-                    self.push(" ", colors::WHITE);
-                }
+                self.print_lifetime("", " ");
 
                 self.push("mut ", CONFIG.colors.asm.annotation);
                 self.tipe()?;
@@ -663,7 +911,9 @@ impl<'src> Parser {
             // fn(..) -> ..
             b'F' => {
                 self.offset += 1;
-                self.binder();
+
+                let bound = self.binder()?;
+                self.bind_lifetimes(bound)?;
 
                 if self.eat(b'U') {
                     self.push("unsafe ", CONFIG.colors.asm.pointer);
@@ -691,13 +941,17 @@ impl<'src> Parser {
                 self.push(")", CONFIG.colors.brackets);
                 self.push(" -> ", CONFIG.colors.brackets);
                 self.tipe()?;
+
+                self.unbind_lifetimes(bound);
             }
             // dyn ..
             b'D' => {
                 self.offset += 1;
-                self.binder();
                 self.push("dyn ", CONFIG.colors.asm.pointer);
 
+                let bound = self.binder()?;
+                self.bind_lifetimes(bound)?;
+
                 // associated traits e.g. Send + Sync + Pin
                 self.delimited(" + ", |this| {
                     this.path()?;
@@ -715,10 +969,9 @@ impl<'src> Parser {
                     Some(())
                 })?;
 
-                if let Some(lifetime) = self.lifetime() {
-                    self.push(" + ", CONFIG.colors.asm.expr);
-                    self.push(lifetime, CONFIG.colors.asm.annotation);
-                }
+                self.unbind_lifetimes(bound);
+
+                self.print_lifetime(" + ", "");
             }
             b'B' => {
                 self.offset += 1;
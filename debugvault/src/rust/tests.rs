@@ -54,7 +54,7 @@ fn pointers() {
 #[test]
 fn arrays() {
     eq!("_RINvC4bite6decodeANtNvC3std5array5Arrayjf_E" =>
-         "bite::decode::<[std::array::Array; _]>");
+         "bite::decode::<[std::array::Array; 15]>");
 }
 
 #[test]
@@ -74,6 +74,51 @@ fn constants() {
     eq!("_RNvMNtCs9ltgdHTiPiY_4core5sliceSRe4iterCslWKjbRFJPpS_3log" => "<[&str]>::iter");
 }
 
+#[test]
+fn const_generics() {
+    eq!("_RINvC4bite6decodeKj10_E" => "bite::decode::<16>");
+    eq!("_RINvC4bite6decodeKj0_E" => "bite::decode::<0>");
+    eq!("_RINvC4bite6decodeKln5_E" => "bite::decode::<-5>");
+    eq!("_RINvC4bite6decodeKpE" => "bite::decode::<_>");
+    eq!("_RINvC4bite6decodeKoffffffffffffffffff_E" =>
+        "bite::decode::<0xffffffffffffffffff>");
+}
+
+#[test]
+fn bool_and_char_constants() {
+    eq!("_RINvC4bite6decodeKb1_Kb0_E" => "bite::decode::<true, false>");
+    eq!("_RINvC4bite6decodeKc61_Kca_Kc27_E" => "bite::decode::<'a', '\\n', '\\''>");
+    eq!("_RINvC4bite6decodeKc1f600_E" => "bite::decode::<'😀'>");
+    assert!(parse("_RINvC4bite6decodeKb2_E").is_none());
+    assert!(parse("_RINvC4bite6decodeKcd800_E").is_none());
+}
+
+#[test]
+fn str_constants() {
+    eq!("_RINvC4bite6decodeKRe68690a_E" => "bite::decode::<\"hi\\n\">");
+    eq!("_RINvC4bite6decodeKe6869_E" => "bite::decode::<{ *\"hi\" }>");
+    assert!(parse("_RINvC4bite6decodeKRe686_E").is_none());
+    assert!(parse("_RINvC4bite6decodeKRec328_E").is_none());
+}
+
+#[test]
+fn compound_constants() {
+    eq!("_RINvC4bite6decodeKRAj1_j2_EE" => "bite::decode::<{ &[1, 2] }>");
+    eq!("_RINvC4bite6decodeKQTj1_EE" => "bite::decode::<{ &mut (1,) }>");
+    eq!("_RINvC4bite6decodeKTj1_b1_EE" => "bite::decode::<{ (1, true) }>");
+    eq!("_RINvC4bite6decodeKVNtC3foo3BarS1xj1_1yb0_EE" =>
+        "bite::decode::<{ foo::Bar { x: 1, y: false } }>");
+    eq!("_RINvC4bite6decodeKVNtC3foo3BarTj1_EE" => "bite::decode::<{ foo::Bar(1) }>");
+    eq!("_RINvC4bite6decodeKVNtC3foo3BarUE" => "bite::decode::<{ foo::Bar }>");
+}
+
+#[test]
+fn truncated_constants() {
+    assert!(parse("_RINvC4bite6decodeK").is_none());
+    assert!(parse("_RINvC4bite6decodeKE").is_none());
+    assert!(parse("_RINvC4bite6decodeKj").is_none());
+}
+
 #[test]
 fn fn_signature() {
     eq!("_RINvNtC3std3mem8align_ofFUdddEoE" =>
@@ -86,6 +131,20 @@ fn fn_signature() {
          "std::mem::align_of::<fn(f64, f64, f64) -> u128>");
 }
 
+#[test]
+fn binders() {
+    eq!("_RINvNtC3std3mem8align_ofFG_RL0_hEuE" =>
+         "std::mem::align_of::<for<'a> fn(&'a u8) -> ()>");
+
+    eq!("_RINvC1a1fFG_RL0_hFG_RL0_hRL1_hEuEuE" =>
+         "a::f::<for<'a> fn(&'a u8, for<'b> fn(&'b u8, &'a u8) -> ()) -> ()>");
+
+    eq!("_RINvNtC4core4simd3mulDG0_NvNtC4core3mem4ReadEL_E" =>
+         "core::simd::mul::<dyn for<'a, 'b> core::mem::Read>");
+
+    eq!("_RINvC1a1fL_E" => "a::f::<'_>");
+}
+
 #[test]
 fn dyn_traits() {
     eq!("_RINvNtC4core4simd3mulDNvNtC4core3mem4Readp4ItemReEL_E" =>
@@ -108,7 +167,7 @@ fn closures() {
 #[test]
 fn complex() {
     eq!("_RNvXs5_NtCsd4VYFwevHkG_4bite6decodeINtB5_5ArrayNtNtB5_6x86_646PrefixKj4_EINtNtNtCs9ltgdHTiPiY_4core3ops5index8IndexMutjE9index_mutB7_" =>
-        "<bite::decode::Array<bite::decode::x86_64::Prefix, _> as core::ops::index::IndexMut<usize>>::index_mut");
+        "<bite::decode::Array<bite::decode::x86_64::Prefix, 4> as core::ops::index::IndexMut<usize>>::index_mut");
 }
 
 #[test]
@@ -119,3 +178,81 @@ fn too_many_arguements() {
     )
     .unwrap();
 }
+
+fn base62(mut num: usize) -> String {
+    if num == 0 {
+        return "_".to_string();
+    }
+
+    num -= 1;
+    let mut digits = Vec::new();
+    loop {
+        let digit = (num % 62) as u8;
+        digits.push(match digit {
+            0..=9 => b'0' + digit,
+            10..=35 => b'a' + digit - 10,
+            _ => b'A' + digit - 36,
+        });
+        num /= 62;
+        if num == 0 {
+            break;
+        }
+    }
+
+    digits.reverse();
+    digits.push(b'_');
+    String::from_utf8(digits).unwrap()
+}
+
+/// Symbol where each generic is a path with generics, both referring to the previous generic.
+fn nested_backrefs(levels: usize) -> String {
+    let mut mangled = String::from("IC1aC1b");
+    let mut prev = 4;
+    for _ in 0..levels {
+        let offset = mangled.len();
+        let backref = format!("B{}", base62(prev));
+        mangled += &format!("I{backref}{backref}E");
+        prev = offset;
+    }
+
+    format!("_R{mangled}E")
+}
+
+#[test]
+fn exponential_backrefs() {
+    let symbol = parse(&nested_backrefs(2)).unwrap();
+    assert_eq!(
+        String::from_iter(symbol.tokens().iter().map(|t| &t.text[..])),
+        "a::<b, b::<b>, b::<b>::<b::<b>>>"
+    );
+
+    assert!(parse(&nested_backrefs(64)).is_none());
+}
+
+#[test]
+fn type_backrefs() {
+    eq!("_RINvC1a1fTuuEB7_E" => "a::f::<((), ()), ((), ())>");
+    eq!("_RINvC1a1fTuuETB7_B7_EE" => "a::f::<((), ()), (((), ()), ((), ()))>");
+}
+
+#[test]
+fn reentrant() {
+    let symbols = [
+        "_RNvNvXs2_C7mycrateINtC7mycrate3FoopEINtNtC3std7convert4FrompE4from3MSG",
+        "_RINvC1a1fFG_RL0_hFG_RL0_hRL1_hEuEuE",
+        "_RINvC1a1fTuuETB7_B7_EE",
+    ];
+
+    let expected: Vec<_> = symbols.iter().map(|s| parse(s).unwrap().tokens().len()).collect();
+    std::thread::scope(|scope| {
+        for _ in 0..8 {
+            scope.spawn(|| {
+                for _ in 0..64 {
+                    for (symbol, len) in symbols.iter().zip(&expected) {
+                        assert_eq!(parse(symbol).unwrap().tokens().len(), *len);
+                    }
+                }
+            });
+        }
+    });
+}
@@ -0,0 +1,748 @@
+//! Layouts of C structures and the constants of enumerations, either described by debug info or
+//! defined by the user.
+//!
+//! User defined structures are written in a subset of C: a definition consists of the members'
+//! types and names, which are laid out with their natural alignment like a compiler would.
+//!
+//! ```text
+//! struct entry {
+//!     const char *name;
+//!     unsigned int flags;
+//!     struct entry *next;
+//! };
+//! ```
+//!
+//! Enumerations are written the same way, their constants can be given as expressions of the
+//! constants before them.
+//!
+//! ```text
+//! enum prot {
+//!     PROT_NONE,
+//!     PROT_READ = 1 << 0,
+//!     PROT_WRITE = 1 << 1,
+//!     PROT_RW = PROT_READ | PROT_WRITE,
+//! };
+//! ```
+
+use std::fmt;
+
+/// Member of a structure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field {
+    pub name: String,
+    /// Type as written in C without the member's name, e.g. `char *` or `int[4]`.
+    pub ty: String,
+    /// Offset from the start of the structure.
+    pub offset: usize,
+    pub size: usize,
+    /// Alignment of the member's type, that of a single element for arrays.
+    pub align: usize,
+}
+
+impl Field {
+    /// Whether the member holds an address.
+    pub fn is_pointer(&self) -> bool {
+        self.ty.ends_with('*')
+    }
+
+    /// Whether the member holds a string, i.e. it's an array of characters.
+    pub fn is_string(&self) -> bool {
+        self.ty.starts_with("char[")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Struct {
+    pub name: String,
+    pub size: usize,
+    /// Members sorted by their offset.
+    pub fields: Vec<Field>,
+}
+
+impl Struct {
+    /// Member containing the byte at an offset into the structure.
+    pub fn field_at(&self, offset: usize) -> Option<&Field> {
+        self.fields
+            .iter()
+            .find(|field| (field.offset..field.offset + field.size.max(1)).contains(&offset))
+    }
+
+    /// Alignment of the structure, that of its most aligned member.
+    pub fn align(&self) -> usize {
+        self.fields.iter().map(|field| field.align).max().unwrap_or(1)
+    }
+}
+
+impl fmt::Display for Struct {
+    /// Definition of the structure in the syntax [`parse`] accepts.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_fmt(format_args!("struct {} {{", self.name))?;
+        for field in self.fields.iter() {
+            let (ty, dims) = match field.ty.find('[') {
+                Some(idx) => field.ty.split_at(idx),
+                None => (&field.ty[..], ""),
+            };
+
+            let separator = if ty.ends_with('*') { "" } else { " " };
+            f.write_fmt(format_args!(" {ty}{separator}{}{dims};", field.name))?;
+        }
+        f.write_str(" };")
+    }
+}
+
+/// Named constants, either distinct values or a set of flags that are combined.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Enum {
+    pub name: String,
+    /// Constants in the order they're defined.
+    pub variants: Vec<(String, u64)>,
+}
+
+impl Enum {
+    /// Whether the constants are flags, which is assumed when most of them have a single bit set
+    /// and they aren't simply counting up.
+    pub fn is_flags(&self) -> bool {
+        let single = self.variants.iter().filter(|(_, value)| value.is_power_of_two()).count();
+        let multiple = self.variants.iter().filter(|(_, value)| value.count_ones() > 1).count();
+        let counting = self.variants.windows(2).all(|pair| pair[1].1 == pair[0].1.wrapping_add(1));
+        single >= 2 && single > multiple && !counting
+    }
+
+    /// Symbolic form of a value, e.g. `O_RDWR|O_CLOEXEC`.
+    ///
+    /// Flags that don't add up to the value are followed by the bits that are left.
+    pub fn render(&self, value: u64) -> Option<String> {
+        // negative constants are often compared against 32-bit immediates
+        let widen = |value: u64| match value >> 32 {
+            0 => value as u32 as i32 as i64 as u64,
+            _ => value,
+        };
+        let matches = |variant: u64| variant == value || widen(variant) == widen(value);
+
+        if let Some((name, _)) = self.variants.iter().find(|(_, variant)| matches(*variant)) {
+            return Some(name.clone());
+        }
+
+        if value == 0 || !self.is_flags() {
+            return None;
+        }
+
+        // masks combining several flags are preferred over the flags they combine
+        let mut candidates: Vec<&(String, u64)> =
+            self.variants.iter().filter(|(_, variant)| *variant != 0).collect();
+        candidates.sort_by_key(|(_, variant)| std::cmp::Reverse(variant.count_ones()));
+
+        let mut rest = value;
+        let mut used = Vec::new();
+        for (name, variant) in candidates {
+            if rest & variant == *variant {
+                rest &= !variant;
+                used.push((*variant, name.clone()));
+            }
+        }
+
+        if used.is_empty() {
+            return None;
+        }
+
+        used.sort();
+        let mut names: Vec<String> = used.into_iter().map(|(_, name)| name).collect();
+        if rest != 0 {
+            names.push(format!("{rest:#x}"));
+        }
+
+        Some(names.join("|"))
+    }
+}
+
+impl fmt::Display for Enum {
+    /// Definition of the enumeration in the syntax [`parse_enums`] accepts.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_fmt(format_args!("enum {} {{", self.name))?;
+        for (idx, (name, value)) in self.variants.iter().enumerate() {
+            let separator = if idx == 0 { " " } else { ", " };
+            match *value as i64 {
+                value @ i64::MIN..=-1 => {
+                    f.write_fmt(format_args!("{separator}{name} = {value}"))?
+                }
+                _ => f.write_fmt(format_args!("{separator}{name} = {value:#x}"))?,
+            }
+        }
+        f.write_str(" };")
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// Input ended in the middle of a definition.
+    UnexpectedEnd,
+    /// Token that isn't valid where it's found.
+    Unexpected(String),
+    /// Type of a member that isn't a primitive or a known structure.
+    UnknownType(String),
+    /// Member or structure that's larger than the address space.
+    TooLarge(String),
+    /// Input without any definitions.
+    Empty,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedEnd => f.write_str("Unexpected end of the definition."),
+            Self::Unexpected(token) => f.write_fmt(format_args!("Unexpected '{token}'.")),
+            Self::UnknownType(ty) => f.write_fmt(format_args!("Type '{ty}' is unknown.")),
+            Self::TooLarge(name) => f.write_fmt(format_args!("'{name}' is too large.")),
+            Self::Empty => f.write_str(
+                "Expected a definition like 'struct name { int a; };' or 'enum name { A = 1 };'.",
+            ),
+        }
+    }
+}
+
+/// Largest power of two up to 8 that divides a size.
+pub(crate) fn natural_align(size: usize) -> usize {
+    match size {
+        0 => 1,
+        size => (1 << size.trailing_zeros()).min(8),
+    }
+}
+
+/// Size of a primitive C type, `long` is assumed to be as wide as a pointer.
+fn primitive_size(ty: &str, pointer_size: usize) -> Option<usize> {
+    let size = match ty {
+        "char" | "signed char" | "unsigned char" | "bool" | "_Bool" | "int8_t" | "uint8_t"
+        | "BYTE" | "UCHAR" | "BOOLEAN" => 1,
+        "short" | "unsigned short" | "int16_t" | "uint16_t" | "WORD" | "USHORT" | "WCHAR" => 2,
+        "int" | "unsigned" | "signed" | "float" | "int32_t" | "uint32_t" | "DWORD" | "BOOL"
+        | "LONG" | "ULONG" | "UINT" => 4,
+        "long long" | "unsigned long long" | "double" | "int64_t" | "uint64_t" | "QWORD"
+        | "ULONGLONG" | "LONGLONG" => 8,
+        "long" | "unsigned long" | "size_t" | "ssize_t" | "intptr_t" | "uintptr_t"
+        | "ptrdiff_t" | "SIZE_T" | "ULONG_PTR" | "HANDLE" => pointer_size,
+        _ => return None,
+    };
+
+    Some(size)
+}
+
+fn tokenize(src: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut rest = src;
+
+    while let Some(chr) = rest.chars().next() {
+        if chr.is_whitespace() {
+            rest = &rest[chr.len_utf8()..];
+        } else if let Some(comment) = rest.strip_prefix("//") {
+            rest = comment.split_once('\n').map_or("", |(_, rest)| rest);
+        } else if let Some(comment) = rest.strip_prefix("/*") {
+            rest = comment.split_once("*/").map_or("", |(_, rest)| rest);
+        } else if chr.is_alphanumeric() || chr == '_' {
+            let len = rest.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(rest.len());
+            tokens.push(&rest[..len]);
+            rest = &rest[len..];
+        } else {
+            tokens.push(&rest[..chr.len_utf8()]);
+            rest = &rest[chr.len_utf8()..];
+        }
+    }
+
+    tokens
+}
+
+struct Parser<'src, F> {
+    tokens: Vec<&'src str>,
+    pos: usize,
+    pointer_size: usize,
+    /// Size and alignment of structures defined elsewhere.
+    known: F,
+    parsed: Vec<Struct>,
+}
+
+impl<'src, F: Fn(&str) -> Option<(usize, usize)>> Parser<'src, F> {
+    fn peek(&self) -> Option<&'src str> {
+        self.tokens.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Result<&'src str, Error> {
+        let token = self.peek().ok_or(Error::UnexpectedEnd)?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), Error> {
+        match self.next()? {
+            token if token == expected => Ok(()),
+            token => Err(Error::Unexpected(token.to_string())),
+        }
+    }
+
+    fn ident(&mut self) -> Result<&'src str, Error> {
+        match self.next()? {
+            token if token.starts_with(|c: char| c.is_alphabetic() || c == '_') => Ok(token),
+            token => Err(Error::Unexpected(token.to_string())),
+        }
+    }
+
+    /// Size and alignment of a type that isn't a pointer.
+    fn layout(&self, ty: &str) -> Result<(usize, usize), Error> {
+        if let Some(size) = primitive_size(ty, self.pointer_size) {
+            return Ok((size, natural_align(size)));
+        }
+
+        let name = ty.strip_prefix("struct ").unwrap_or(ty);
+        if let Some(earlier) = self.parsed.iter().find(|earlier| earlier.name == name) {
+            return Ok((earlier.size, earlier.align()));
+        }
+
+        (self.known)(name).ok_or_else(|| Error::UnknownType(ty.to_string()))
+    }
+
+    /// Members up to and including the closing brace.
+    fn fields(&mut self) -> Result<Vec<Field>, Error> {
+        let mut fields = Vec::new();
+        let mut offset = 0usize;
+
+        while self.peek() != Some("}") {
+            // the base type is all words up to the first declarator
+            let mut words = Vec::new();
+            loop {
+                // the name of a member is followed by the end of the declaration or an array
+                let next = self.tokens.get(self.pos + 1).copied();
+                let is_name = matches!(next, Some(";" | "," | "[" | ":"));
+
+                match self.peek() {
+                    Some("const" | "volatile") => self.pos += 1,
+                    Some("*") | None => break,
+                    Some(_) if is_name => break,
+                    Some(_) => words.push(self.ident()?),
+                }
+            }
+
+            if words.len() > 1 && words.last() == Some(&"int") {
+                words.pop();
+            }
+
+            if words.is_empty() {
+                return Err(Error::Unexpected(self.next()?.to_string()));
+            }
+
+            let base = words.join(" ");
+            loop {
+                let mut pointers = 0;
+                while self.peek() == Some("*") {
+                    pointers += 1;
+                    self.pos += 1;
+                }
+
+                while let Some("const" | "volatile") = self.peek() {
+                    self.pos += 1;
+                }
+
+                let name = self.ident()?;
+                let too_large = || Error::TooLarge(name.to_string());
+                let mut dims = String::new();
+                let mut count = 1;
+                while self.peek() == Some("[") {
+                    self.pos += 1;
+                    let len = self.next()?;
+                    let len: usize = len.parse().map_err(|_| Error::Unexpected(len.to_string()))?;
+                    self.expect("]")?;
+                    dims += &format!("[{len}]");
+                    count = len.checked_mul(count).ok_or_else(too_large)?;
+                }
+
+                let (ty, (size, align)) = match pointers {
+                    0 => (base.clone(), self.layout(&base)?),
+                    n => (
+                        format!("{base} {}", "*".repeat(n)),
+                        (self.pointer_size, self.pointer_size),
+                    ),
+                };
+
+                let size = size.checked_mul(count).ok_or_else(too_large)?;
+                offset = offset.checked_next_multiple_of(align).ok_or_else(too_large)?;
+                fields.push(Field {
+                    name: name.to_string(),
+                    ty: ty + &dims,
+                    offset,
+                    size,
+                    align,
+                });
+                offset = offset.checked_add(size).ok_or_else(too_large)?;
+
+                match self.next()? {
+                    "," => continue,
+                    ";" => break,
+                    token => return Err(Error::Unexpected(token.to_string())),
+                }
+            }
+        }
+
+        self.expect("}")?;
+        Ok(fields)
+    }
+
+    /// Definition of a structure, optionally through a `typedef`.
+    fn definition(&mut self) -> Result<Struct, Error> {
+        let typedef = self.peek() == Some("typedef");
+        if typedef {
+            self.pos += 1;
+        }
+
+        self.expect("struct")?;
+        let tag = match self.peek() {
+            Some("{") => None,
+            _ => Some(self.ident()?.to_string()),
+        };
+
+        self.expect("{")?;
+        let fields = self.fields()?;
+
+        // a typedef is referred to by its alias rather than its tag
+        let name = match tag {
+            Some(tag) if !typedef => tag,
+            _ => self.ident()?.to_string(),
+        };
+
+        self.expect(";")?;
+        let mut parsed = Struct {
+            name,
+            size: fields.last().map_or(0, |field| field.offset + field.size),
+            fields,
+        };
+
+        parsed.size = match parsed.size.checked_next_multiple_of(parsed.align()) {
+            Some(size) => size,
+            None => return Err(Error::TooLarge(parsed.name)),
+        };
+
+        Ok(parsed)
+    }
+}
+
+/// Parses enumerations, which don't depend on the size of types.
+struct EnumParser<'src> {
+    tokens: Vec<&'src str>,
+    pos: usize,
+    /// Constants of the enumeration that's being parsed.
+    variants: Vec<(String, u64)>,
+}
+
+impl<'src> EnumParser<'src> {
+    fn peek(&self) -> Option<&'src str> {
+        self.tokens.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Result<&'src str, Error> {
+        let token = self.peek().ok_or(Error::UnexpectedEnd)?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), Error> {
+        match self.next()? {
+            token if token == expected => Ok(()),
+            token => Err(Error::Unexpected(token.to_string())),
+        }
+    }
+
+    fn ident(&mut self) -> Result<&'src str, Error> {
+        match self.next()? {
+            token if token.starts_with(|c: char| c.is_alphabetic() || c == '_') => Ok(token),
+            token => Err(Error::Unexpected(token.to_string())),
+        }
+    }
+
+    /// Number, earlier constant or parenthesized expression, optionally negated.
+    fn atom(&mut self) -> Result<u64, Error> {
+        let token = self.next()?;
+        match token {
+            "-" => Ok(self.atom()?.wrapping_neg()),
+            "~" => Ok(!self.atom()?),
+            "(" => {
+                let value = self.expr()?;
+                self.expect(")")?;
+                Ok(value)
+            }
+            _ => {
+                // integer literals can have suffixes like `1u` or `0x10UL`
+                let literal = token.trim_end_matches(['u', 'U', 'l', 'L']);
+                let value = match literal.strip_prefix("0x").or(literal.strip_prefix("0X")) {
+                    Some(hex) => u64::from_str_radix(hex, 16).ok(),
+                    None => literal.parse().ok(),
+                };
+
+                value
+                    .or_else(|| {
+                        let (_, value) = self.variants.iter().find(|(name, _)| name == token)?;
+                        Some(*value)
+                    })
+                    .ok_or_else(|| Error::Unexpected(token.to_string()))
+            }
+        }
+    }
+
+    /// Shifts bind tighter than additions, which bind tighter than bitwise or.
+    fn expr(&mut self) -> Result<u64, Error> {
+        let mut value = self.sum()?;
+        while self.peek() == Some("|") {
+            self.pos += 1;
+            value |= self.sum()?;
+        }
+
+        Ok(value)
+    }
+
+    fn sum(&mut self) -> Result<u64, Error> {
+        let mut value = self.shift()?;
+        loop {
+            match self.peek() {
+                Some("+") => {
+                    self.pos += 1;
+                    value = value.wrapping_add(self.shift()?);
+                }
+                Some("-") => {
+                    self.pos += 1;
+                    value = value.wrapping_sub(self.shift()?);
+                }
+                _ => return Ok(value),
+            }
+        }
+    }
+
+    fn shift(&mut self) -> Result<u64, Error> {
+        let mut value = self.atom()?;
+        while self.peek() == Some("<") {
+            self.pos += 1;
+            self.expect("<")?;
+            value = value.checked_shl(self.atom()? as u32).unwrap_or(0);
+        }
+
+        Ok(value)
+    }
+
+    /// Definition of an enumeration, optionally through a `typedef`.
+    fn definition(&mut self) -> Result<Enum, Error> {
+        let typedef = self.peek() == Some("typedef");
+        if typedef {
+            self.pos += 1;
+        }
+
+        self.expect("enum")?;
+        let tag = match self.peek() {
+            Some("{") => None,
+            _ => Some(self.ident()?.to_string()),
+        };
+
+        self.expect("{")?;
+        self.variants.clear();
+        let mut value = 0u64;
+        while self.peek() != Some("}") {
+            let name = self.ident()?.to_string();
+            if self.peek() == Some("=") {
+                self.pos += 1;
+                value = self.expr()?;
+            }
+
+            self.variants.push((name, value));
+            value = value.wrapping_add(1);
+
+            match self.next()? {
+                "," => continue,
+                "}" => {
+                    self.pos -= 1;
+                    break;
+                }
+                token => return Err(Error::Unexpected(token.to_string())),
+            }
+        }
+
+        self.expect("}")?;
+        let name = match tag {
+            Some(tag) if !typedef => tag,
+            _ => self.ident()?.to_string(),
+        };
+
+        self.expect(";")?;
+        Ok(Enum {
+            name,
+            variants: std::mem::take(&mut self.variants),
+        })
+    }
+}
+
+/// Enumerations defined in C, see [`types`](self).
+pub fn parse_enums(src: &str) -> Result<Vec<Enum>, Error> {
+    let mut parser = EnumParser {
+        tokens: tokenize(src),
+        pos: 0,
+        variants: Vec::new(),
+    };
+
+    let mut parsed = Vec::new();
+    while parser.peek().is_some() {
+        parsed.push(parser.definition()?);
+    }
+
+    if parsed.is_empty() {
+        return Err(Error::Empty);
+    }
+
+    Ok(parsed)
+}
+
+/// Structures defined in C, see [`types`](self).
+///
+/// Members can be other structures that were defined before or that `known` returns the size
+/// and alignment of.
+pub fn parse(
+    src: &str,
+    pointer_size: usize,
+    known: impl Fn(&str) -> Option<(usize, usize)>,
+) -> Result<Vec<Struct>, Error> {
+    let mut parser = Parser {
+        tokens: tokenize(src),
+        pos: 0,
+        pointer_size,
+        known,
+        parsed: Vec::new(),
+    };
+
+    while parser.peek().is_some() {
+        let definition = parser.definition()?;
+        parser.parsed.push(definition);
+    }
+
+    if parser.parsed.is_empty() {
+        return Err(Error::Empty);
+    }
+
+    Ok(parser.parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(src: &str) -> Result<Vec<Struct>, Error> {
+        super::parse(src, 8, |_| None)
+    }
+
+    #[test]
+    fn natural_alignment() {
+        let parsed = parse("struct a { char c; int i; char *p; short s; };").unwrap();
+        let offsets: Vec<usize> = parsed[0].fields.iter().map(|field| field.offset).collect();
+        assert_eq!(offsets, [0, 4, 8, 16]);
+        assert_eq!(parsed[0].size, 24);
+        assert_eq!(parsed[0].fields[2].ty, "char *");
+    }
+
+    #[test]
+    fn arrays_and_declarator_lists() {
+        let parsed = parse("typedef struct { unsigned long long a, b; char name[16]; } pair;");
+        let parsed = parsed.unwrap();
+        assert_eq!(parsed[0].name, "pair");
+        assert_eq!(parsed[0].fields[1].offset, 8);
+        assert_eq!(parsed[0].fields[2].ty, "char[16]");
+        assert_eq!(parsed[0].size, 32);
+    }
+
+    #[test]
+    fn nested() {
+        let src = "struct point { int x; int y; }; struct rect { struct point min, max; };";
+        let parsed = parse(src).unwrap();
+        assert_eq!(parsed[1].size, 16);
+        assert_eq!(parsed[1].field_at(12).unwrap().name, "max");
+    }
+
+    #[test]
+    fn element_alignment() {
+        // arrays are aligned like their elements rather than by their size
+        let parsed = parse("struct s { char tag; char name[16]; };").unwrap();
+        assert_eq!(parsed[0].fields[1].offset, 1);
+        assert_eq!(parsed[0].size, 17);
+
+        let parsed = parse("struct t { short tag; int v[2]; };").unwrap();
+        assert_eq!(parsed[0].fields[1].offset, 4);
+        assert_eq!(parsed[0].align(), 4);
+
+        // and structures like their most aligned member
+        let src = "struct point { int x; int y; }; struct s { struct point p; int x; };";
+        let parsed = parse(src).unwrap();
+        assert_eq!(parsed[1].fields[0].align, 4);
+        assert_eq!(parsed[1].size, 12);
+
+        let parsed = super::parse("struct s { char c; struct ext e; };", 8, |_| Some((6, 2)));
+        assert_eq!(parsed.unwrap()[0].size, 8);
+    }
+
+    #[test]
+    fn roundtrip() {
+        let parsed = parse("struct s { const char *name; int v[3]; /* flags */ short f; };");
+        let parsed = parsed.unwrap();
+        let printed = parsed[0].to_string();
+        assert_eq!(printed, "struct s { char *name; int v[3]; short f; };");
+        assert_eq!(parse(&printed).unwrap(), parsed);
+    }
+
+    #[test]
+    fn enums() {
+        let src = "typedef enum { A, B = 0x10, C, D = -1 } e; enum f { X = 1 << 2, Y = X | 1 };";
+        let parsed = parse_enums(src).unwrap();
+        assert_eq!(parsed[0].name, "e");
+        assert_eq!(parsed[0].variants[2], ("C".to_string(), 0x11));
+        assert_eq!(parsed[0].render(0xffffffff).as_deref(), Some("D"));
+        assert_eq!(parsed[0].render(u64::MAX).as_deref(), Some("D"));
+        assert_eq!(parsed[1].variants[1].1, 5);
+        assert_eq!(parse_enums(&parsed[0].to_string()).unwrap()[0], parsed[0]);
+    }
+
+    #[test]
+    fn flags() {
+        let src = "enum o { O_RDONLY, O_WRONLY, O_RDWR, O_CREAT = 0x40, O_CLOEXEC = 0x80000 };";
+        let parsed = parse_enums(src).unwrap();
+        assert!(parsed[0].is_flags());
+        assert_eq!(parsed[0].render(0).as_deref(), Some("O_RDONLY"));
+        assert_eq!(
+            parsed[0].render(0x80042).as_deref(),
+            Some("O_RDWR|O_CREAT|O_CLOEXEC")
+        );
+        assert_eq!(parsed[0].render(0x101).as_deref(), Some("O_WRONLY|0x100"));
+
+        let parsed = parse_enums("enum s { SIGHUP = 1, SIGINT, SIGQUIT, SIGILL };").unwrap();
+        assert!(!parsed[0].is_flags());
+        assert_eq!(parsed[0].render(5), None);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(parse(""), Err(Error::Empty));
+        assert_eq!(
+            parse("struct s { foo x; };"),
+            Err(Error::UnknownType("foo".into()))
+        );
+        assert_eq!(parse("struct s { int x; "), Err(Error::UnexpectedEnd));
+    }
+
+    #[test]
+    fn too_large() {
+        let huge = usize::MAX / 2 + 1;
+        assert_eq!(
+            parse(&format!("struct s {{ int v[{huge}]; }};")),
+            Err(Error::TooLarge("v".into()))
+        );
+        assert_eq!(
+            parse(&format!("struct s {{ char v[{huge}][2]; }};")),
+            Err(Error::TooLarge("v".into()))
+        );
+        assert_eq!(
+            parse(&format!("struct s {{ char a[{huge}]; char b[{huge}]; }};")),
+            Err(Error::TooLarge("b".into()))
+        );
+
+        // the padding at the end doesn't fit either
+        let src = format!("struct s {{ int a; char b[{}]; }};", usize::MAX - 4);
+        assert_eq!(parse(&src), Err(Error::TooLarge("s".into())));
+    }
+}
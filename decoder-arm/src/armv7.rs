@@ -5,9 +5,10 @@ use core::fmt::{self, Display, Formatter};
 
 use decoder::{Decoded, Decodable, Error, ErrorKind, Reader, ToTokens};
 use debugvault::Index;
-use tokenizing::TokenStream;
+use tokenizing::{TokenKind, TokenStream};
 use config::CONFIG;
 
+mod lower;
 mod thumb;
 
 // opcode, s, w, cond
@@ -1684,21 +1685,41 @@ impl Operand {
         match self {
             Operand::RegList(list) => format_reg_list(stream, *list),
             Operand::BankedReg(bank, reg) => {
-                stream.push(reg.as_str(), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    reg.as_str(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push("_", CONFIG.colors.asm.expr);
-                stream.push(bank.as_str(), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    bank.as_str(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
             }
             Operand::BankedSPSR(bank) => {
-                stream.push("spsr", CONFIG.colors.asm.register);
+                stream.push_kind("spsr", CONFIG.colors.asm.register, TokenKind::Register);
                 stream.push("_", CONFIG.colors.asm.expr);
-                stream.push(bank.as_str(), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    bank.as_str(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
             }
             Operand::Reg(reg) => {
-                stream.push(reg.as_str(), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    reg.as_str(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
             }
             Operand::RegDeref(reg) => {
                 stream.push("[", CONFIG.colors.brackets);
-                stream.push(reg.as_str(), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    reg.as_str(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push("]", CONFIG.colors.brackets);
             }
             Operand::RegShift(shift) => format_shift(stream, *shift),
@@ -1716,7 +1737,11 @@ impl Operand {
             }
             Operand::RegDerefPostindexReg(reg, offsreg, add, wback) => {
                 stream.push("[", CONFIG.colors.brackets);
-                stream.push(reg.as_str(), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    reg.as_str(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push("]", CONFIG.colors.brackets);
                 stream.push(", ", CONFIG.colors.asm.expr);
 
@@ -1724,7 +1749,11 @@ impl Operand {
                     stream.push("-", CONFIG.colors.asm.expr);
                 }
 
-                stream.push(offsreg.as_str(), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    offsreg.as_str(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
 
                 if *wback {
                     stream.push("!", CONFIG.colors.asm.expr);
@@ -1732,106 +1761,168 @@ impl Operand {
             }
             Operand::RegDerefPreindexReg(reg, offsreg, add, wback) => {
                 stream.push("[", CONFIG.colors.brackets);
-                stream.push(reg.as_str(), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    reg.as_str(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push(", ", CONFIG.colors.asm.expr);
 
                 if !*add {
                     stream.push("-", CONFIG.colors.asm.expr);
                 }
 
-                stream.push(offsreg.as_str(), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    offsreg.as_str(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push("]", CONFIG.colors.brackets);
 
                 if *wback {
                     stream.push("!", CONFIG.colors.asm.expr);
                 }
             }
-            Operand::Imm12(imm) => {
-                match symbols.get_sym_by_addr(*imm as usize) {
-                    Some(symbol) => {
-                        stream.push("<", CONFIG.colors.asm.immediate);
-                        for token in symbol.name() {
-                            stream.push_token(token.clone());
-                        }
-                        stream.push(">", CONFIG.colors.asm.immediate);
-                    }
-                    None => {
-                        stream.push_owned(decoder::encode_uhex(*imm as u64), CONFIG.colors.asm.immediate);
+            Operand::Imm12(imm) => match symbols.get_sym_by_addr(*imm as usize) {
+                Some(symbol) => {
+                    let start = stream.inner.len();
+                    stream.push_kind("<", CONFIG.colors.asm.immediate, TokenKind::Immediate);
+                    for token in symbol.name() {
+                        stream.push_token(token.clone());
                     }
+                    stream.push_kind(">", CONFIG.colors.asm.immediate, TokenKind::Immediate);
+                    stream.set_target(start, *imm as usize);
                 }
-            }
-            Operand::Imm32(imm) => {
-                match symbols.get_sym_by_addr(*imm as usize) {
-                    Some(symbol) => {
-                        stream.push("<", CONFIG.colors.asm.immediate);
-                        for token in symbol.name() {
-                            stream.push_token(token.clone());
-                        }
-                        stream.push(">", CONFIG.colors.asm.immediate);
-                    }
-                    None => {
-                        stream.push_owned(decoder::encode_uhex(*imm as u64), CONFIG.colors.asm.immediate);
-                    }
+                None => {
+                    stream.push_owned_kind(
+                        decoder::encode_uhex(*imm as u64),
+                        CONFIG.colors.asm.immediate,
+                        TokenKind::Immediate,
+                    );
                 }
-            }
-            Operand::Imm64(imm) => {
-                match symbols.get_sym_by_addr(*imm as usize) {
-                    Some(symbol) => {
-                        stream.push("<", CONFIG.colors.asm.immediate);
-                        for token in symbol.name() {
-                            stream.push_token(token.clone());
-                        }
-                        stream.push(">", CONFIG.colors.asm.immediate);
+            },
+            Operand::Imm32(imm) => match symbols.get_sym_by_addr(*imm as usize) {
+                Some(symbol) => {
+                    let start = stream.inner.len();
+                    stream.push_kind("<", CONFIG.colors.asm.immediate, TokenKind::Immediate);
+                    for token in symbol.name() {
+                        stream.push_token(token.clone());
                     }
-                    None => {
-                        stream.push("#", CONFIG.colors.asm.expr);
-                        stream.push_owned(decoder::encode_uhex(*imm), CONFIG.colors.asm.immediate);
+                    stream.push_kind(">", CONFIG.colors.asm.immediate, TokenKind::Immediate);
+                    stream.set_target(start, *imm as usize);
+                }
+                None => {
+                    stream.push_owned_kind(
+                        decoder::encode_uhex(*imm as u64),
+                        CONFIG.colors.asm.immediate,
+                        TokenKind::Immediate,
+                    );
+                }
+            },
+            Operand::Imm64(imm) => match symbols.get_sym_by_addr(*imm as usize) {
+                Some(symbol) => {
+                    let start = stream.inner.len();
+                    stream.push_kind("<", CONFIG.colors.asm.immediate, TokenKind::Immediate);
+                    for token in symbol.name() {
+                        stream.push_token(token.clone());
                     }
+                    stream.push_kind(">", CONFIG.colors.asm.immediate, TokenKind::Immediate);
+                    stream.set_target(start, *imm as usize);
+                }
+                None => {
+                    stream.push("#", CONFIG.colors.asm.expr);
+                    stream.push_owned_kind(
+                        decoder::encode_uhex(*imm),
+                        CONFIG.colors.asm.immediate,
+                        TokenKind::Immediate,
+                    );
                 }
             }
             Operand::Imm64Special(imm) => {
                 stream.push("#", CONFIG.colors.asm.expr);
-                stream.push_owned(decoder::encode_uhex(*imm), CONFIG.colors.asm.immediate);
+                stream.push_owned_kind(
+                    decoder::encode_uhex(*imm),
+                    CONFIG.colors.asm.immediate,
+                    TokenKind::Immediate,
+                );
             }
             Operand::BranchOffset(offs) => {
                 if *offs >= 0 {
                     stream.push("$", CONFIG.colors.asm.expr);
-                    stream.push("+", CONFIG.colors.asm.immediate);
+                    stream.push_kind("+", CONFIG.colors.asm.immediate, TokenKind::Immediate);
                 } else {
-                    stream.push("$", CONFIG.colors.asm.immediate);
+                    stream.push_kind("$", CONFIG.colors.asm.immediate, TokenKind::Immediate);
                 }
-                stream.push_owned(decoder::encode_hex((offs * 4) as i64), CONFIG.colors.asm.immediate);
+                stream.push_owned_kind(
+                    decoder::encode_hex((offs * 4) as i64),
+                    CONFIG.colors.asm.immediate,
+                    TokenKind::Immediate,
+                );
             }
             Operand::BranchThumbOffset(offs) => {
                 if *offs >= 0 {
                     stream.push("$", CONFIG.colors.asm.expr);
-                    stream.push("+", CONFIG.colors.asm.immediate);
+                    stream.push_kind("+", CONFIG.colors.asm.immediate, TokenKind::Immediate);
                 } else {
-                    stream.push("$", CONFIG.colors.asm.immediate);
+                    stream.push_kind("$", CONFIG.colors.asm.immediate, TokenKind::Immediate);
                 }
-                stream.push_owned(decoder::encode_hex((offs * 2) as i64), CONFIG.colors.asm.immediate);
+                stream.push_owned_kind(
+                    decoder::encode_hex((offs * 2) as i64),
+                    CONFIG.colors.asm.immediate,
+                    TokenKind::Immediate,
+                );
             }
             Operand::Coprocessor(num) => {
-                stream.push("p", CONFIG.colors.asm.register);
-                stream.push_owned(num.to_string(), CONFIG.colors.asm.register);
+                stream.push_kind("p", CONFIG.colors.asm.register, TokenKind::Register);
+                stream.push_owned_kind(
+                    num.to_string(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
             }
             Operand::CoprocOption(num) => {
                 stream.push("{", CONFIG.colors.brackets);
-                stream.push_owned(decoder::encode_hex(*num as i64), CONFIG.colors.asm.register);
+                stream.push_owned_kind(
+                    decoder::encode_hex(*num as i64),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push("}", CONFIG.colors.brackets);
             }
             Operand::RegWBack(reg, wback) => {
-                stream.push(reg.as_str(), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    reg.as_str(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
 
                 if *wback {
                     stream.push("!", CONFIG.colors.asm.expr);
                 }
             }
-            Operand::CReg(creg) => stream.push(creg.as_str(), CONFIG.colors.asm.register),
-            Operand::StatusRegMask(mask) => stream.push(mask.as_str(), CONFIG.colors.asm.register),
-            Operand::APSR => stream.push("apsr", CONFIG.colors.asm.register),
-            Operand::SPSR => stream.push("spsr", CONFIG.colors.asm.register),
-            Operand::CPSR => stream.push("cpsr", CONFIG.colors.asm.register),
+            Operand::CReg(creg) => {
+                stream.push_kind(
+                    creg.as_str(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
+            }
+            Operand::StatusRegMask(mask) => {
+                stream.push_kind(
+                    mask.as_str(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
+            }
+            Operand::APSR => {
+                stream.push_kind("apsr", CONFIG.colors.asm.register, TokenKind::Register);
+            }
+            Operand::SPSR => {
+                stream.push_kind("spsr", CONFIG.colors.asm.register, TokenKind::Register);
+            }
+            Operand::CPSR => {
+                stream.push_kind("cpsr", CONFIG.colors.asm.register, TokenKind::Register);
+            }
             Operand::Nothing => panic!("tried to print Nothing operand"),
         }
     }
@@ -1921,7 +2012,11 @@ fn format_reg_list(stream: &mut TokenStream, mut list: u16) {
             } else {
                 tail = true;
             }
-            stream.push(Reg::from_u8(i).as_str(), CONFIG.colors.asm.register);
+            stream.push_kind(
+                Reg::from_u8(i).as_str(),
+                CONFIG.colors.asm.register,
+                TokenKind::Register,
+            );
         }
         i += 1;
         list >>= 1;
@@ -1933,21 +2028,41 @@ fn format_shift(stream: &mut TokenStream, shift: RegShift) {
     match shift.into_shift() {
         RegShiftStyle::RegImm(imm_shift) => {
             if imm_shift.imm() == 0 && imm_shift.stype() == ShiftStyle::LSL {
-                stream.push(imm_shift.shiftee().as_str(), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    imm_shift.shiftee().as_str(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
             } else {
-                stream.push(imm_shift.shiftee().as_str(), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    imm_shift.shiftee().as_str(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push(", ", CONFIG.colors.asm.expr);
                 stream.push(imm_shift.stype().as_str(), CONFIG.colors.asm.segment);
                 stream.push(" ", CONFIG.colors.asm.expr);
-                stream.push_owned(imm_shift.imm().to_string(), CONFIG.colors.asm.immediate);
+                stream.push_owned_kind(
+                    imm_shift.imm().to_string(),
+                    CONFIG.colors.asm.immediate,
+                    TokenKind::Immediate,
+                );
             }
         }
         RegShiftStyle::RegReg(reg_shift) => {
-            stream.push(reg_shift.shiftee().as_str(), CONFIG.colors.asm.register);
+            stream.push_kind(
+                reg_shift.shiftee().as_str(),
+                CONFIG.colors.asm.register,
+                TokenKind::Register,
+            );
             stream.push(", ", CONFIG.colors.asm.expr);
             stream.push(reg_shift.stype().as_str(), CONFIG.colors.asm.segment);
             stream.push(" ", CONFIG.colors.asm.expr);
-            stream.push(reg_shift.shifter().as_str(), CONFIG.colors.asm.register);
+            stream.push_kind(
+                reg_shift.shifter().as_str(),
+                CONFIG.colors.asm.register,
+                TokenKind::Register,
+            );
         }
     }
 }
@@ -1965,10 +2080,10 @@ fn format_reg_shift_mem(
     match (pre, wback) {
         (true, true) => {
             stream.push("[", CONFIG.colors.brackets);
-            stream.push(rd.as_str(), CONFIG.colors.asm.register);
+            stream.push_kind(rd.as_str(), CONFIG.colors.asm.register, TokenKind::Register);
             stream.push(", ", CONFIG.colors.asm.expr);
-            stream.push(rd.as_str(), CONFIG.colors.asm.register);
-            stream.push(op, CONFIG.colors.asm.immediate);
+            stream.push_kind(rd.as_str(), CONFIG.colors.asm.register, TokenKind::Register);
+            stream.push_kind(op, CONFIG.colors.asm.immediate, TokenKind::Immediate);
             format_shift(stream, shift);
             stream.push("]", CONFIG.colors.brackets);
             stream.push("!", CONFIG.colors.asm.expr);
@@ -1976,10 +2091,10 @@ fn format_reg_shift_mem(
 
         (true, false) => {
             stream.push("[", CONFIG.colors.brackets);
-            stream.push(rd.as_str(), CONFIG.colors.asm.register);
+            stream.push_kind(rd.as_str(), CONFIG.colors.asm.register, TokenKind::Register);
             stream.push(", ", CONFIG.colors.asm.expr);
-            stream.push(rd.as_str(), CONFIG.colors.asm.register);
-            stream.push(op, CONFIG.colors.asm.immediate);
+            stream.push_kind(rd.as_str(), CONFIG.colors.asm.register, TokenKind::Register);
+            stream.push_kind(op, CONFIG.colors.asm.immediate, TokenKind::Immediate);
             format_shift(stream, shift);
             stream.push("]", CONFIG.colors.brackets);
         }
@@ -1991,10 +2106,10 @@ fn format_reg_shift_mem(
         }
         (false, false) => {
             stream.push("[", CONFIG.colors.brackets);
-            stream.push(rd.as_str(), CONFIG.colors.asm.register);
+            stream.push_kind(rd.as_str(), CONFIG.colors.asm.register, TokenKind::Register);
             stream.push("]", CONFIG.colors.brackets);
             stream.push(", ", CONFIG.colors.asm.expr);
-            stream.push(op, CONFIG.colors.asm.immediate);
+            stream.push_kind(op, CONFIG.colors.asm.immediate, TokenKind::Immediate);
             format_shift(stream, shift)
         }
     }
@@ -2012,36 +2127,60 @@ fn format_reg_imm_mem(
         match (pre, wback) {
             (true, true) => {
                 stream.push("[", CONFIG.colors.brackets);
-                stream.push(rn.as_str(), CONFIG.colors.asm.register);
+                stream.push_kind(rn.as_str(), CONFIG.colors.asm.register, TokenKind::Register);
                 stream.push(", ", CONFIG.colors.asm.expr);
                 if add {
-                    stream.push_owned(decoder::encode_hex(imm as i64), CONFIG.colors.asm.immediate);
+                    stream.push_owned_kind(
+                        decoder::encode_hex(imm as i64),
+                        CONFIG.colors.asm.immediate,
+                        TokenKind::Immediate,
+                    );
                 } else {
-                    stream.push_owned(decoder::encode_hex(imm as i64 * -1), CONFIG.colors.asm.immediate);
+                    stream.push_owned_kind(
+                        decoder::encode_hex(imm as i64 * -1),
+                        CONFIG.colors.asm.immediate,
+                        TokenKind::Immediate,
+                    );
                 }
                 stream.push("]", CONFIG.colors.brackets);
                 stream.push("!", CONFIG.colors.asm.expr);
             }
             (true, false) => {
                 stream.push("[", CONFIG.colors.brackets);
-                stream.push(rn.as_str(), CONFIG.colors.asm.register);
+                stream.push_kind(rn.as_str(), CONFIG.colors.asm.register, TokenKind::Register);
                 stream.push(", ", CONFIG.colors.asm.expr);
                 if add {
-                    stream.push_owned(decoder::encode_hex(imm as i64), CONFIG.colors.asm.immediate);
+                    stream.push_owned_kind(
+                        decoder::encode_hex(imm as i64),
+                        CONFIG.colors.asm.immediate,
+                        TokenKind::Immediate,
+                    );
                 } else {
-                    stream.push_owned(decoder::encode_hex(imm as i64 * -1), CONFIG.colors.asm.immediate);
+                    stream.push_owned_kind(
+                        decoder::encode_hex(imm as i64 * -1),
+                        CONFIG.colors.asm.immediate,
+                        TokenKind::Immediate,
+                    );
                 }
                 stream.push("]", CONFIG.colors.brackets);
             }
             (false, _) => {
                 stream.push("[", CONFIG.colors.brackets);
-                stream.push(rn.as_str(), CONFIG.colors.asm.register);
+                stream.push_kind(rn.as_str(), CONFIG.colors.asm.register, TokenKind::Register);
                 stream.push("]", CONFIG.colors.brackets);
                 stream.push(", ", CONFIG.colors.asm.expr);
                 if add {
-                    stream.push_owned(decoder::encode_hex(imm as i64), CONFIG.colors.asm.immediate);
+                    stream.push_owned_kind(
+                        decoder::encode_hex(imm as i64),
+                        CONFIG.colors.asm.immediate,
+                        TokenKind::Immediate,
+                    );
                 } else {
-                    stream.push_owned(decoder::encode_hex(imm as i64 * -1), CONFIG.colors.asm.immediate);
+                    stream.push_owned_kind(
+                        decoder::encode_hex(imm as i64 * -1),
+                        CONFIG.colors.asm.immediate,
+                        TokenKind::Immediate,
+                    );
                 }
             }
         }
@@ -2049,18 +2188,18 @@ fn format_reg_imm_mem(
         match (pre, wback) {
             (true, true) => {
                 stream.push("[", CONFIG.colors.brackets);
-                stream.push(rn.as_str(), CONFIG.colors.asm.register);
+                stream.push_kind(rn.as_str(), CONFIG.colors.asm.register, TokenKind::Register);
                 stream.push("]", CONFIG.colors.brackets);
                 stream.push("!", CONFIG.colors.asm.expr);
             }
             (true, false) => {
                 stream.push("[", CONFIG.colors.brackets);
-                stream.push(rn.as_str(), CONFIG.colors.asm.register);
+                stream.push_kind(rn.as_str(), CONFIG.colors.asm.register, TokenKind::Register);
                 stream.push("]", CONFIG.colors.brackets);
             }
             (false, _) => {
                 stream.push("[", CONFIG.colors.brackets);
-                stream.push(rn.as_str(), CONFIG.colors.asm.register);
+                stream.push_kind(rn.as_str(), CONFIG.colors.asm.register, TokenKind::Register);
                 stream.push("]", CONFIG.colors.brackets);
             }
         }
@@ -2099,6 +2238,10 @@ impl Decoded for Instruction {
             }
         }
     }
+
+    fn lower(&self, stmts: &mut Vec<decoder::ir::Stmt>) {
+        self.lower_into(stmts);
+    }
 }
 
 impl Display for Instruction {
@@ -2199,7 +2342,11 @@ impl ToTokens for Instruction {
                     stream.push_owned(op, CONFIG.colors.asm.opcode);
                     if let Operand::Imm12(mode) = &self.operands[1] {
                         stream.push(", #", CONFIG.colors.asm.expr);
-                        stream.push_owned(decoder::encode_hex(*mode as i64), CONFIG.colors.asm.immediate);
+                        stream.push_owned_kind(
+                            decoder::encode_hex(*mode as i64),
+                            CONFIG.colors.asm.immediate,
+                            TokenKind::Immediate,
+                        );
                     }
                     return;
                 } else {
@@ -2226,7 +2373,11 @@ impl ToTokens for Instruction {
                         ConditionedOpcode(Opcode::POP, self.s(), self.w(), self.condition)
                             .tokenize(stream, symbols);
                         stream.push(" {", CONFIG.colors.brackets);
-                        stream.push(rt.as_str(), CONFIG.colors.asm.register);
+                        stream.push_kind(
+                            rt.as_str(),
+                            CONFIG.colors.asm.register,
+                            TokenKind::Register,
+                        );
                         stream.push("}", CONFIG.colors.brackets);
                         return;
                     }
@@ -2241,7 +2392,11 @@ impl ToTokens for Instruction {
                         ConditionedOpcode(Opcode::PUSH, self.s(), self.w(), self.condition)
                             .tokenize(stream, symbols);
                         stream.push(" {", CONFIG.colors.brackets);
-                        stream.push(rt.as_str(), CONFIG.colors.asm.register);
+                        stream.push_kind(
+                            rt.as_str(),
+                            CONFIG.colors.asm.register,
+                            TokenKind::Register,
+                        );
                         stream.push("}", CONFIG.colors.brackets);
                         return;
                     }
@@ -2288,7 +2443,7 @@ impl ToTokens for Instruction {
                     ConditionedOpcode(self.opcode, self.s(), self.w(), self.condition)
                         .tokenize(stream, symbols);
                     stream.push(" ", CONFIG.colors.asm.expr);
-                    stream.push(rr.as_str(), CONFIG.colors.asm.register);
+                    stream.push_kind(rr.as_str(), CONFIG.colors.asm.register, TokenKind::Register);
                     if wback {
                         stream.push("!", CONFIG.colors.asm.expr);
                     }
@@ -2302,8 +2457,12 @@ impl ToTokens for Instruction {
             },
             Opcode::STCL(coproc) => {
                 stream.push("stcl ", CONFIG.colors.asm.opcode);
-                stream.push("p", CONFIG.colors.asm.register);
-                stream.push_owned(coproc.to_string(), CONFIG.colors.asm.register);
+                stream.push_kind("p", CONFIG.colors.asm.register, TokenKind::Register);
+                stream.push_owned_kind(
+                    coproc.to_string(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 let ops = self.operands.iter();
                 for op in ops {
                     if let Operand::Nothing = op {
@@ -2315,8 +2474,12 @@ impl ToTokens for Instruction {
             }
             Opcode::STC(coproc) => {
                 stream.push("stc ", CONFIG.colors.asm.opcode);
-                stream.push("p", CONFIG.colors.asm.register);
-                stream.push_owned(coproc.to_string(), CONFIG.colors.asm.register);
+                stream.push_kind("p", CONFIG.colors.asm.register, TokenKind::Register);
+                stream.push_owned_kind(
+                    coproc.to_string(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 let ops = self.operands.iter();
                 for op in ops {
                     if let Operand::Nothing = op {
@@ -2328,8 +2491,12 @@ impl ToTokens for Instruction {
             }
             Opcode::STC2L(coproc) => {
                 stream.push("stc2l ", CONFIG.colors.asm.opcode);
-                stream.push("p", CONFIG.colors.asm.register);
-                stream.push_owned(coproc.to_string(), CONFIG.colors.asm.register);
+                stream.push_kind("p", CONFIG.colors.asm.register, TokenKind::Register);
+                stream.push_owned_kind(
+                    coproc.to_string(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 let ops = self.operands.iter();
                 for op in ops {
                     if let Operand::Nothing = op {
@@ -2341,8 +2508,12 @@ impl ToTokens for Instruction {
             }
             Opcode::STC2(coproc) => {
                 stream.push("stc2 ", CONFIG.colors.asm.opcode);
-                stream.push("p", CONFIG.colors.asm.register);
-                stream.push_owned(coproc.to_string(), CONFIG.colors.asm.register);
+                stream.push_kind("p", CONFIG.colors.asm.register, TokenKind::Register);
+                stream.push_owned_kind(
+                    coproc.to_string(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 let ops = self.operands.iter();
                 for op in ops {
                     if let Operand::Nothing = op {
@@ -2354,8 +2525,12 @@ impl ToTokens for Instruction {
             }
             Opcode::LDC(coproc) => {
                 stream.push("ldc ", CONFIG.colors.asm.opcode);
-                stream.push("p", CONFIG.colors.asm.register);
-                stream.push_owned(coproc.to_string(), CONFIG.colors.asm.register);
+                stream.push_kind("p", CONFIG.colors.asm.register, TokenKind::Register);
+                stream.push_owned_kind(
+                    coproc.to_string(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 let ops = self.operands.iter();
                 for op in ops {
                     if let Operand::Nothing = op {
@@ -2367,8 +2542,12 @@ impl ToTokens for Instruction {
             }
             Opcode::LDCL(coproc) => {
                 stream.push("ldcl ", CONFIG.colors.asm.opcode);
-                stream.push("p", CONFIG.colors.asm.register);
-                stream.push_owned(coproc.to_string(), CONFIG.colors.asm.register);
+                stream.push_kind("p", CONFIG.colors.asm.register, TokenKind::Register);
+                stream.push_owned_kind(
+                    coproc.to_string(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 let ops = self.operands.iter();
                 for op in ops {
                     if let Operand::Nothing = op {
@@ -2380,8 +2559,12 @@ impl ToTokens for Instruction {
             }
             Opcode::LDC2(coproc) => {
                 stream.push("ldc2 ", CONFIG.colors.asm.opcode);
-                stream.push("p", CONFIG.colors.asm.register);
-                stream.push_owned(coproc.to_string(), CONFIG.colors.asm.register);
+                stream.push_kind("p", CONFIG.colors.asm.register, TokenKind::Register);
+                stream.push_owned_kind(
+                    coproc.to_string(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 let ops = self.operands.iter();
                 for op in ops {
                     if let Operand::Nothing = op {
@@ -2393,8 +2576,12 @@ impl ToTokens for Instruction {
             }
             Opcode::LDC2L(coproc) => {
                 stream.push("ldc2l ", CONFIG.colors.asm.opcode);
-                stream.push("p", CONFIG.colors.asm.register);
-                stream.push_owned(coproc.to_string(), CONFIG.colors.asm.register);
+                stream.push_kind("p", CONFIG.colors.asm.register, TokenKind::Register);
+                stream.push_owned_kind(
+                    coproc.to_string(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 let ops = self.operands.iter();
                 for op in ops {
                     if let Operand::Nothing = op {
@@ -2406,10 +2593,18 @@ impl ToTokens for Instruction {
             }
             Opcode::MRRC2(coproc, opc) => {
                 stream.push("mrrc2 ", CONFIG.colors.asm.opcode);
-                stream.push("p", CONFIG.colors.asm.register);
-                stream.push_owned(coproc.to_string(), CONFIG.colors.asm.register);
+                stream.push_kind("p", CONFIG.colors.asm.register, TokenKind::Register);
+                stream.push_owned_kind(
+                    coproc.to_string(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push(", ", CONFIG.colors.asm.expr);
-                stream.push_owned(opc.to_string(), CONFIG.colors.asm.register);
+                stream.push_owned_kind(
+                    opc.to_string(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 let ops = self.operands.iter();
                 for op in ops {
                     if let Operand::Nothing = op {
@@ -2421,10 +2616,18 @@ impl ToTokens for Instruction {
             }
             Opcode::MCRR2(coproc, opc) => {
                 stream.push("mcrr2 ", CONFIG.colors.asm.opcode);
-                stream.push("p", CONFIG.colors.asm.register);
-                stream.push_owned(coproc.to_string(), CONFIG.colors.asm.register);
+                stream.push_kind("p", CONFIG.colors.asm.register, TokenKind::Register);
+                stream.push_owned_kind(
+                    coproc.to_string(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push(", ", CONFIG.colors.asm.expr);
-                stream.push_owned(opc.to_string(), CONFIG.colors.asm.register);
+                stream.push_owned_kind(
+                    opc.to_string(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 let ops = self.operands.iter();
                 for op in ops {
                     if let Operand::Nothing = op {
@@ -2436,10 +2639,18 @@ impl ToTokens for Instruction {
             }
             Opcode::MRC2(coproc, opc1, opc2) => {
                 stream.push("mrc2 ", CONFIG.colors.asm.opcode);
-                stream.push("p", CONFIG.colors.asm.register);
-                stream.push_owned(coproc.to_string(), CONFIG.colors.asm.register);
+                stream.push_kind("p", CONFIG.colors.asm.register, TokenKind::Register);
+                stream.push_owned_kind(
+                    coproc.to_string(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push(", ", CONFIG.colors.asm.expr);
-                stream.push_owned(opc1.to_string(), CONFIG.colors.asm.register);
+                stream.push_owned_kind(
+                    opc1.to_string(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 let ops = self.operands.iter();
                 for op in ops {
                     if let Operand::Nothing = op {
@@ -2450,14 +2661,26 @@ impl ToTokens for Instruction {
                 }
 
                 stream.push(", ", CONFIG.colors.asm.expr);
-                stream.push_owned(opc2.to_string(), CONFIG.colors.asm.register);
+                stream.push_owned_kind(
+                    opc2.to_string(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
             }
             Opcode::MCR2(coproc, opc1, opc2) => {
                 stream.push("mcr2 ", CONFIG.colors.asm.opcode);
-                stream.push("p", CONFIG.colors.asm.register);
-                stream.push_owned(coproc.to_string(), CONFIG.colors.asm.register);
+                stream.push_kind("p", CONFIG.colors.asm.register, TokenKind::Register);
+                stream.push_owned_kind(
+                    coproc.to_string(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push(", ", CONFIG.colors.asm.expr);
-                stream.push_owned(opc1.to_string(), CONFIG.colors.asm.register);
+                stream.push_owned_kind(
+                    opc1.to_string(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 let ops = self.operands.iter();
                 for op in ops {
                     if let Operand::Nothing = op {
@@ -2468,14 +2691,26 @@ impl ToTokens for Instruction {
                 }
 
                 stream.push(", ", CONFIG.colors.asm.expr);
-                stream.push_owned(opc2.to_string(), CONFIG.colors.asm.register);
+                stream.push_owned_kind(
+                    opc2.to_string(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
             }
             Opcode::CDP2(coproc, opc1, opc2) => {
                 stream.push("cdp2 ", CONFIG.colors.asm.opcode);
-                stream.push("p", CONFIG.colors.asm.register);
-                stream.push_owned(coproc.to_string(), CONFIG.colors.asm.register);
+                stream.push_kind("p", CONFIG.colors.asm.register, TokenKind::Register);
+                stream.push_owned_kind(
+                    coproc.to_string(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push(", ", CONFIG.colors.asm.expr);
-                stream.push_owned(opc1.to_string(), CONFIG.colors.asm.register);
+                stream.push_owned_kind(
+                    opc1.to_string(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 let ops = self.operands.iter();
                 for op in ops {
                     if let Operand::Nothing = op {
@@ -2486,7 +2721,11 @@ impl ToTokens for Instruction {
                 }
 
                 stream.push(", ", CONFIG.colors.asm.expr);
-                stream.push_owned(opc2.to_string(), CONFIG.colors.asm.register);
+                stream.push_owned_kind(
+                    opc2.to_string(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
             }
             _ => {
                 ConditionedOpcode(self.opcode, self.s(), self.w(), self.condition)
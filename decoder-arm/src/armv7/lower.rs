@@ -0,0 +1,486 @@
+//! Lowering of `armv7` instructions into the architecture independent [`ir`](decoder::ir).
+
+use crate::armv7::{
+    ConditionCode, Instruction, Opcode, Operand, Reg, RegShift, RegShiftStyle, ShiftStyle,
+};
+use decoder::ir::{self, BinaryOp, Cond, Condition, Expr, Flags, Stmt, UnaryOp};
+
+const SP: u8 = 13;
+const PC: u8 = 15;
+
+fn reg(reg: Reg) -> ir::Reg {
+    ir::Reg::full(reg.as_str(), 4)
+}
+
+fn condition(code: ConditionCode) -> Option<Condition> {
+    Some(match code {
+        ConditionCode::EQ => Condition::Eq,
+        ConditionCode::NE => Condition::Ne,
+        ConditionCode::HS => Condition::AboveEq,
+        ConditionCode::LO => Condition::Below,
+        ConditionCode::MI => Condition::Negative,
+        ConditionCode::PL => Condition::NotNegative,
+        ConditionCode::VS => Condition::Overflow,
+        ConditionCode::VC => Condition::NoOverflow,
+        ConditionCode::HI => Condition::Above,
+        ConditionCode::LS => Condition::BelowEq,
+        ConditionCode::GE => Condition::Ge,
+        ConditionCode::LT => Condition::Lt,
+        ConditionCode::GT => Condition::Gt,
+        ConditionCode::LE => Condition::Le,
+        ConditionCode::AL => return None,
+    })
+}
+
+fn shift_op(style: ShiftStyle) -> BinaryOp {
+    match style {
+        ShiftStyle::LSL => BinaryOp::Shl,
+        ShiftStyle::LSR => BinaryOp::Shr,
+        ShiftStyle::ASR => BinaryOp::Sar,
+        ShiftStyle::ROR => BinaryOp::Ror,
+    }
+}
+
+/// Number of bytes accessed by a load or store and whether loads are sign extended.
+fn access(opcode: Opcode) -> Option<(u8, bool)> {
+    Some(match opcode {
+        Opcode::LDR | Opcode::LDRT | Opcode::STR | Opcode::STRT => (4, false),
+        Opcode::LDRD | Opcode::STRD => (4, false),
+        Opcode::LDRB | Opcode::LDRBT | Opcode::STRB | Opcode::STRBT => (1, false),
+        Opcode::LDRH | Opcode::LDRHT | Opcode::STRH | Opcode::STRHT => (2, false),
+        Opcode::LDRSB | Opcode::LDRSBT => (1, true),
+        Opcode::LDRSH | Opcode::LDRSHT => (2, true),
+        _ => return None,
+    })
+}
+
+fn is_store(opcode: Opcode) -> bool {
+    matches!(
+        opcode,
+        Opcode::STR
+            | Opcode::STRT
+            | Opcode::STRD
+            | Opcode::STRB
+            | Opcode::STRBT
+            | Opcode::STRH
+            | Opcode::STRHT
+    )
+}
+
+/// Base register offset by a value that is either added or subtracted.
+fn offset(base: Reg, value: Expr, add: bool) -> Expr {
+    let base = Expr::Reg(reg(base));
+    match (add, value) {
+        (true, value) => Expr::sum(base, value),
+        (false, Expr::Const(0)) => base,
+        (false, value) => Expr::binary(BinaryOp::Sub, base, value),
+    }
+}
+
+/// Continue execution at an address, where jumping to the link register returns.
+fn jump(target: Expr) -> Stmt {
+    match target {
+        Expr::Reg(ir::Reg { name: "lr", .. }) => Stmt::Return,
+        target => Stmt::Jump(target),
+    }
+}
+
+/// Make the effects of an instruction depend on a condition.
+fn conditional(cond: Condition, stmt: Stmt) -> Stmt {
+    let cond = Cond::Flags(cond);
+    match stmt {
+        Stmt::Assign { dst, src } => Stmt::Assign {
+            dst,
+            src: Expr::select(cond, src, Expr::Reg(dst)),
+        },
+        Stmt::Store { addr, size, src } => Stmt::Store {
+            src: Expr::select(cond, src, Expr::load(addr.clone(), size)),
+            addr,
+            size,
+        },
+        Stmt::Jump(target) => Stmt::Branch { cond, target },
+        Stmt::Flags(..) => Stmt::Flags(Flags::Unknown),
+        _ => Stmt::Unknown,
+    }
+}
+
+impl Instruction {
+    /// Operands up to the first [`Operand::Nothing`].
+    fn ops(&self) -> &[Operand] {
+        let count = self.operands.iter().position(|op| *op == Operand::Nothing);
+        &self.operands[..count.unwrap_or(4)]
+    }
+
+    fn shifted(&self, shift: RegShift) -> Expr {
+        match shift.into_shift() {
+            RegShiftStyle::RegImm(shift) => {
+                let value = self.read(&Operand::Reg(shift.shiftee()));
+                let amount = match (shift.stype(), shift.imm()) {
+                    (ShiftStyle::LSL, 0) => return value,
+                    // `ror #0` encodes `rrx`, which shifts in the carry flag
+                    (ShiftStyle::ROR, 0) => return Expr::Unknown,
+                    (_, 0) => 32,
+                    (_, amount) => amount,
+                };
+
+                Expr::binary(shift_op(shift.stype()), value, Expr::Const(amount as u64))
+            }
+            RegShiftStyle::RegReg(shift) => {
+                let value = self.read(&Operand::Reg(shift.shiftee()));
+                let amount = self.read(&Operand::Reg(shift.shifter()));
+                Expr::binary(shift_op(shift.stype()), value, amount)
+            }
+        }
+    }
+
+    fn read(&self, op: &Operand) -> Expr {
+        match *op {
+            // the value of `pc` depends on whether the instruction was decoded as thumb
+            Operand::Reg(reg) if reg.number() == PC => Expr::Unknown,
+            Operand::Reg(reg) => Expr::Reg(self::reg(reg)),
+            Operand::RegShift(shift) => self.shifted(shift),
+            Operand::Imm12(imm) => Expr::Const(imm as u64),
+            Operand::Imm32(imm) => Expr::Const(imm as u64),
+            Operand::Imm64(imm) | Operand::Imm64Special(imm) => Expr::Const(imm),
+            _ => Expr::Unknown,
+        }
+    }
+
+    fn write(&self, op: &Operand, src: Expr, stmts: &mut Vec<Stmt>) {
+        match *op {
+            // writing `pc` is a branch
+            Operand::Reg(dst) if dst.number() == PC => stmts.push(jump(src)),
+            Operand::Reg(dst) => stmts.push(Stmt::Assign { dst: reg(dst), src }),
+            _ => stmts.push(Stmt::Unknown),
+        }
+    }
+
+    /// Address of a memory operand, and the register written back with its new value.
+    fn address(&self, op: &Operand) -> (Expr, Option<(Reg, Expr)>) {
+        let (base, value, add, wback, pre) = match *op {
+            Operand::RegDeref(base) => (base, Expr::Const(0), true, false, true),
+            Operand::RegDerefPreindexOffset(base, off, add, wback) => {
+                (base, Expr::Const(off as u64), add, wback, true)
+            }
+            Operand::RegDerefPostindexOffset(base, off, add, _) => {
+                (base, Expr::Const(off as u64), add, true, false)
+            }
+            Operand::RegDerefPreindexReg(base, idx, add, wback) => {
+                (base, self.read(&Operand::Reg(idx)), add, wback, true)
+            }
+            Operand::RegDerefPostindexReg(base, idx, add, _) => {
+                (base, self.read(&Operand::Reg(idx)), add, true, false)
+            }
+            Operand::RegDerefPreindexRegShift(base, shift, add, wback) => {
+                (base, self.shifted(shift), add, wback, true)
+            }
+            Operand::RegDerefPostindexRegShift(base, shift, add, _) => {
+                (base, self.shifted(shift), add, true, false)
+            }
+            _ => return (Expr::Unknown, None),
+        };
+
+        // addresses relative to `pc` aren't resolved
+        if base.number() == PC {
+            return (Expr::Unknown, None);
+        }
+
+        let offset = offset(base, value, add);
+        let writeback = wback.then(|| (base, offset.clone()));
+        match pre {
+            true => (offset, writeback),
+            false => (Expr::Reg(reg(base)), writeback),
+        }
+    }
+
+    fn lower_memory(&self, bytes: u8, signed: bool, stmts: &mut Vec<Stmt>) {
+        let Some((mem, regs)) = self.ops().split_last() else {
+            return stmts.push(Stmt::Unknown);
+        };
+
+        let (addr, writeback) = self.address(mem);
+        let mut target = None;
+
+        for (idx, op) in regs.iter().enumerate() {
+            let addr = Expr::sum(addr.clone(), Expr::Const(idx as u64 * bytes as u64));
+
+            if is_store(self.opcode) {
+                let src = self.read(op);
+                stmts.push(Stmt::Store {
+                    addr,
+                    size: bytes,
+                    src,
+                });
+                continue;
+            }
+
+            let value = match signed {
+                true => Expr::sign_extend(Expr::load(addr, bytes), bytes),
+                false => Expr::load(addr, bytes),
+            };
+
+            // loads into `pc` branch after the base register is written back
+            match op {
+                Operand::Reg(dst) if dst.number() == PC => target = Some(value),
+                op => self.write(op, value, stmts),
+            }
+        }
+
+        if let Some((base, offset)) = writeback {
+            stmts.push(Stmt::Assign {
+                dst: reg(base),
+                src: offset,
+            });
+        }
+
+        if let Some(target) = target {
+            stmts.push(Stmt::Jump(target));
+        }
+    }
+
+    /// Lower `ldm`, `stm`, `push` and `pop`.
+    fn lower_multiple(&self, stmts: &mut Vec<Stmt>) {
+        let sp = Reg::from_u8(SP);
+        let (load, add, pre, base, wback, list) = match (self.opcode, self.ops()) {
+            (Opcode::PUSH, [Operand::RegList(list)]) => (false, false, true, sp, true, *list),
+            (Opcode::POP, [Operand::RegList(list)]) => (true, true, false, sp, true, *list),
+            (
+                Opcode::LDM(add, pre, ..),
+                [Operand::RegWBack(base, wback), Operand::RegList(list)],
+            ) => (true, add, pre, *base, *wback, *list),
+            (
+                Opcode::STM(add, pre, ..),
+                [Operand::RegWBack(base, wback), Operand::RegList(list)],
+            ) => (false, add, pre, *base, *wback, *list),
+            _ => return stmts.push(Stmt::Unknown),
+        };
+
+        let size = list.count_ones() as i64 * 4;
+        let first = match (add, pre) {
+            (true, false) => 0,
+            (true, true) => 4,
+            (false, false) => 4 - size,
+            (false, true) => -size,
+        };
+
+        let addrs = (0..16).filter(|num| list & (1 << num) != 0).enumerate();
+        let mut target = None;
+
+        for (idx, num) in addrs {
+            let off = first + idx as i64 * 4;
+            let addr = offset(base, Expr::Const(off.unsigned_abs()), off >= 0);
+            let op = Operand::Reg(Reg::from_u8(num));
+
+            if !load {
+                stmts.push(Stmt::Store {
+                    addr,
+                    size: 4,
+                    src: self.read(&op),
+                });
+            } else if num == PC {
+                target = Some(Expr::load(addr, 4));
+            } else {
+                self.write(&op, Expr::load(addr, 4), stmts);
+            }
+        }
+
+        if wback {
+            stmts.push(Stmt::Assign {
+                dst: reg(base),
+                src: offset(base, Expr::Const(size as u64), add),
+            });
+        }
+
+        // popping the return address off the stack returns
+        match target {
+            Some(_) if base.number() == SP => stmts.push(Stmt::Return),
+            Some(target) => stmts.push(Stmt::Jump(target)),
+            None => {}
+        }
+    }
+
+    pub(super) fn lower_into(&self, stmts: &mut Vec<Stmt>) {
+        match condition(self.condition) {
+            // conditional branches are the common case, so they get a proper branch
+            Some(cond) if self.opcode == Opcode::B => stmts.push(Stmt::Branch {
+                cond: Cond::Flags(cond),
+                target: self.read(&self.operands[0]),
+            }),
+            Some(cond) => {
+                let mut unconditional = Vec::new();
+                self.lower_unconditional(&mut unconditional);
+                stmts.extend(unconditional.into_iter().map(|stmt| conditional(cond, stmt)));
+            }
+            None => self.lower_unconditional(stmts),
+        }
+    }
+
+    fn lower_unconditional(&self, stmts: &mut Vec<Stmt>) {
+        let ops = self.ops();
+
+        if let Some((bytes, signed)) = access(self.opcode) {
+            return self.lower_memory(bytes, signed, stmts);
+        }
+
+        // the destination is also the first source when only two operands are listed
+        let (dst, lhs, rhs) = match *ops {
+            [dst, rhs] => (dst, dst, rhs),
+            [dst, lhs, rhs, ..] => (dst, lhs, rhs),
+            _ => (Operand::Nothing, Operand::Nothing, Operand::Nothing),
+        };
+
+        let binop = match self.opcode {
+            Opcode::ADD => Some(BinaryOp::Add),
+            Opcode::SUB => Some(BinaryOp::Sub),
+            Opcode::AND => Some(BinaryOp::And),
+            Opcode::ORR => Some(BinaryOp::Or),
+            Opcode::EOR => Some(BinaryOp::Xor),
+            Opcode::LSL => Some(BinaryOp::Shl),
+            Opcode::LSR => Some(BinaryOp::Shr),
+            Opcode::ASR => Some(BinaryOp::Sar),
+            Opcode::ROR => Some(BinaryOp::Ror),
+            Opcode::MUL => Some(BinaryOp::Mul),
+            Opcode::SDIV => Some(BinaryOp::SignedDiv),
+            Opcode::UDIV => Some(BinaryOp::Div),
+            _ => None,
+        };
+
+        if let (Some(binop), Operand::Reg(..)) = (binop, dst) {
+            let value = Expr::binary(binop, self.read(&lhs), self.read(&rhs));
+
+            if self.s {
+                let flags = match binop {
+                    BinaryOp::Sub => Flags::Compare(self.read(&lhs), self.read(&rhs)),
+                    _ => Flags::Result(value.clone()),
+                };
+
+                stmts.push(Stmt::Flags(flags));
+            }
+
+            return self.write(&dst, value, stmts);
+        }
+
+        // comparisons list the unused destination register in the `arm` encoding
+        let (cmp_lhs, cmp_rhs) = match *ops {
+            [.., lhs, rhs] => (self.read(&lhs), self.read(&rhs)),
+            _ => (Expr::Unknown, Expr::Unknown),
+        };
+
+        // moves list the unused first source register when their operand is shifted
+        let last = ops.last().map_or(Expr::Unknown, |op| self.read(op));
+
+        match self.opcode {
+            Opcode::NOP
+            | Opcode::HINT
+            | Opcode::YIELD
+            | Opcode::WFE
+            | Opcode::WFI
+            | Opcode::SEV
+            | Opcode::CSDB
+            | Opcode::DMB
+            | Opcode::DSB
+            | Opcode::ISB
+            | Opcode::PLD
+            | Opcode::PLI
+            | Opcode::CLREX => {}
+            Opcode::CMP => stmts.push(Stmt::Flags(Flags::Compare(cmp_lhs, cmp_rhs))),
+            Opcode::CMN => {
+                let value = Expr::binary(BinaryOp::Add, cmp_lhs, cmp_rhs);
+                stmts.push(Stmt::Flags(Flags::Result(value)));
+            }
+            Opcode::TST => stmts.push(Stmt::Flags(Flags::Test(cmp_lhs, cmp_rhs))),
+            Opcode::TEQ => {
+                let value = Expr::binary(BinaryOp::Xor, cmp_lhs, cmp_rhs);
+                stmts.push(Stmt::Flags(Flags::Result(value)));
+            }
+            Opcode::MOV | Opcode::MVN => {
+                let value = match self.opcode {
+                    Opcode::MOV => last,
+                    _ => Expr::unary(UnaryOp::Not, last),
+                };
+
+                if self.s {
+                    stmts.push(Stmt::Flags(Flags::Result(value.clone())));
+                }
+
+                self.write(&ops[0], value, stmts);
+            }
+            Opcode::RSB => {
+                if self.s {
+                    let flags = Flags::Compare(self.read(&rhs), self.read(&lhs));
+                    stmts.push(Stmt::Flags(flags));
+                }
+
+                let value = Expr::binary(BinaryOp::Sub, self.read(&rhs), self.read(&lhs));
+                self.write(&dst, value, stmts);
+            }
+            Opcode::BIC | Opcode::ORN => {
+                let binop = match self.opcode {
+                    Opcode::BIC => BinaryOp::And,
+                    _ => BinaryOp::Or,
+                };
+
+                let rhs = Expr::unary(UnaryOp::Not, self.read(&rhs));
+                let value = Expr::binary(binop, self.read(&lhs), rhs);
+
+                if self.s {
+                    stmts.push(Stmt::Flags(Flags::Result(value.clone())));
+                }
+
+                self.write(&dst, value, stmts);
+            }
+            Opcode::MLA | Opcode::MLS => {
+                let product = Expr::binary(BinaryOp::Mul, self.read(&lhs), self.read(&rhs));
+                let value = match self.opcode {
+                    Opcode::MLA => Expr::binary(BinaryOp::Add, product, self.read(&ops[3])),
+                    _ => Expr::binary(BinaryOp::Sub, self.read(&ops[3]), product),
+                };
+
+                self.write(&dst, value, stmts);
+            }
+            Opcode::MOVT => {
+                let kept = Expr::binary(BinaryOp::And, self.read(&dst), Expr::Const(0xffff));
+                let high = Expr::binary(BinaryOp::Shl, self.read(&rhs), Expr::Const(16));
+                self.write(&dst, Expr::binary(BinaryOp::Or, kept, high), stmts);
+            }
+            Opcode::UXTB | Opcode::UXTH | Opcode::SXTB | Opcode::SXTH if ops.len() == 2 => {
+                let value = self.read(&ops[1]);
+                let value = match self.opcode {
+                    Opcode::UXTB => Expr::truncate(value, 1),
+                    Opcode::UXTH => Expr::truncate(value, 2),
+                    Opcode::SXTB => Expr::sign_extend(value, 1),
+                    _ => Expr::sign_extend(value, 2),
+                };
+
+                self.write(&ops[0], value, stmts);
+            }
+            Opcode::ADR => self.write(&ops[0], last, stmts),
+            Opcode::PUSH | Opcode::POP | Opcode::LDM(..) | Opcode::STM(..) => {
+                self.lower_multiple(stmts)
+            }
+            Opcode::B => stmts.push(Stmt::Jump(last)),
+            Opcode::BX => stmts.push(jump(last)),
+            Opcode::BL | Opcode::BLX => stmts.push(Stmt::Call(last)),
+            Opcode::CBZ | Opcode::CBNZ => {
+                let condition = match self.opcode {
+                    Opcode::CBZ => Condition::Eq,
+                    _ => Condition::Ne,
+                };
+
+                stmts.push(Stmt::Branch {
+                    cond: Cond::Compare(condition, self.read(&ops[0]), Expr::Const(0)),
+                    target: last,
+                });
+            }
+            Opcode::UDF | Opcode::BKPT => stmts.push(Stmt::Halt),
+            _ => {
+                stmts.push(Stmt::Unknown);
+
+                // assume the first register operand is the destination
+                if let [Operand::Reg(..), _, ..] = *ops {
+                    self.write(&ops[0], Expr::Unknown, stmts);
+                }
+            }
+        }
+    }
+}
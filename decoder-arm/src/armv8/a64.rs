@@ -9,7 +9,7 @@ use core::fmt::{self, Display, Formatter};
 
 use debugvault::Index;
 use decoder::{Decodable, Decoded, Error, ErrorKind, Reader, ToTokens};
-use tokenizing::TokenStream;
+use tokenizing::{TokenKind, TokenStream};
 use config::CONFIG;
 
 #[allow(non_snake_case)]
@@ -275,6 +275,10 @@ impl Decoded for Instruction {
             }
         }
     }
+
+    fn lower(&self, stmts: &mut Vec<decoder::ir::Stmt>) {
+        self.lower_into(stmts);
+    }
 }
 
 impl Display for Instruction {
@@ -327,7 +331,11 @@ impl ToTokens for Instruction {
                 stream.push("mov ", CONFIG.colors.asm.opcode);
                 self.operands[0].tokenize(stream, symbols);
                 stream.push(", #", CONFIG.colors.asm.expr);
-                stream.push_owned(decoder::encode_uhex(imm), CONFIG.colors.asm.immediate);
+                stream.push_owned_kind(
+                    decoder::encode_uhex(imm),
+                    CONFIG.colors.asm.immediate,
+                    TokenKind::Immediate,
+                );
                 return;
             }
             Opcode::MOVZ => {
@@ -348,7 +356,11 @@ impl ToTokens for Instruction {
                 stream.push("mov ", CONFIG.colors.asm.opcode);
                 self.operands[0].tokenize(stream, symbols);
                 stream.push(", #", CONFIG.colors.asm.expr);
-                stream.push_owned(decoder::encode_uhex(imm), CONFIG.colors.asm.immediate);
+                stream.push_owned_kind(
+                    decoder::encode_uhex(imm),
+                    CONFIG.colors.asm.immediate,
+                    TokenKind::Immediate,
+                );
                 return;
             }
             Opcode::ORR => {
@@ -559,9 +571,10 @@ impl ToTokens for Instruction {
                                 stream.push(", ", CONFIG.colors.asm.expr);
                                 self.operands[1].tokenize(stream, symbols);
                                 stream.push(", #", CONFIG.colors.asm.expr);
-                                stream.push_owned(
+                                stream.push_owned_kind(
                                     decoder::encode_uhex((size - imms - 1) as u64),
                                     CONFIG.colors.asm.immediate,
+                                    TokenKind::Immediate,
                                 );
                                 return;
                             }
@@ -571,14 +584,16 @@ impl ToTokens for Instruction {
                                 stream.push(", ", CONFIG.colors.asm.expr);
                                 self.operands[1].tokenize(stream, symbols);
                                 stream.push(", #", CONFIG.colors.asm.expr);
-                                stream.push_owned(
+                                stream.push_owned_kind(
                                     decoder::encode_uhex((size - immr) as u64),
                                     CONFIG.colors.asm.immediate,
+                                    TokenKind::Immediate,
                                 );
                                 stream.push(", #", CONFIG.colors.asm.expr);
-                                stream.push_owned(
+                                stream.push_owned_kind(
                                     decoder::encode_uhex((imms + 1) as u64),
                                     CONFIG.colors.asm.immediate,
+                                    TokenKind::Immediate,
                                 );
                                 return;
                             }
@@ -621,14 +636,16 @@ impl ToTokens for Instruction {
                                 stream.push("bfc ", CONFIG.colors.asm.opcode);
                                 self.operands[0].tokenize(stream, symbols);
                                 stream.push(", #", CONFIG.colors.asm.expr);
-                                stream.push_owned(
+                                stream.push_owned_kind(
                                     decoder::encode_uhex(lsb as u64),
                                     CONFIG.colors.asm.immediate,
+                                    TokenKind::Immediate,
                                 );
                                 stream.push(", #", CONFIG.colors.asm.expr);
-                                stream.push_owned(
+                                stream.push_owned_kind(
                                     decoder::encode_uhex(width as u64),
                                     CONFIG.colors.asm.immediate,
+                                    TokenKind::Immediate,
                                 );
                                 return;
                             } else {
@@ -637,14 +654,16 @@ impl ToTokens for Instruction {
                                 stream.push(", ", CONFIG.colors.asm.expr);
                                 self.operands[1].tokenize(stream, symbols);
                                 stream.push(", #", CONFIG.colors.asm.expr);
-                                stream.push_owned(
+                                stream.push_owned_kind(
                                     decoder::encode_uhex(lsb as u64),
                                     CONFIG.colors.asm.immediate,
+                                    TokenKind::Immediate,
                                 );
                                 stream.push(", #", CONFIG.colors.asm.expr);
-                                stream.push_owned(
+                                stream.push_owned_kind(
                                     decoder::encode_uhex(width as u64),
                                     CONFIG.colors.asm.immediate,
+                                    TokenKind::Immediate,
                                 );
                                 return;
                             }
@@ -659,9 +678,17 @@ impl ToTokens for Instruction {
                         stream.push(", ", CONFIG.colors.asm.expr);
                         self.operands[1].tokenize(stream, symbols);
                         stream.push(", #", CONFIG.colors.asm.expr);
-                        stream.push_owned(decoder::encode_uhex(lsb as u64), CONFIG.colors.asm.immediate);
+                        stream.push_owned_kind(
+                            decoder::encode_uhex(lsb as u64),
+                            CONFIG.colors.asm.immediate,
+                            TokenKind::Immediate,
+                        );
                         stream.push(", #", CONFIG.colors.asm.expr);
-                        stream.push_owned(decoder::encode_uhex(width as u64), CONFIG.colors.asm.immediate);
+                        stream.push_owned_kind(
+                            decoder::encode_uhex(width as u64),
+                            CONFIG.colors.asm.immediate,
+                            TokenKind::Immediate,
+                        );
                         return;
                     }
                 }
@@ -725,14 +752,16 @@ impl ToTokens for Instruction {
                         stream.push(", ", CONFIG.colors.asm.expr);
                         self.operands[1].tokenize(stream, symbols);
                         stream.push(", #", CONFIG.colors.asm.expr);
-                        stream.push_owned(
+                        stream.push_owned_kind(
                             decoder::encode_uhex((size - imms) as u64),
                             CONFIG.colors.asm.immediate,
+                            TokenKind::Immediate,
                         );
                         stream.push(", #", CONFIG.colors.asm.expr);
-                        stream.push_owned(
+                        stream.push_owned_kind(
                             decoder::encode_uhex((immr + 1) as u64),
                             CONFIG.colors.asm.immediate,
+                            TokenKind::Immediate,
                         );
                         return;
                     }
@@ -782,13 +811,21 @@ impl ToTokens for Instruction {
             }
             Opcode::SYS(ops) => {
                 stream.push("sys #", CONFIG.colors.asm.opcode);
-                stream.push_owned(decoder::encode_uhex(ops.op1() as u64), CONFIG.colors.asm.immediate);
+                stream.push_owned_kind(
+                    decoder::encode_uhex(ops.op1() as u64),
+                    CONFIG.colors.asm.immediate,
+                    TokenKind::Immediate,
+                );
                 stream.push(", ", CONFIG.colors.asm.expr);
                 self.operands[1].tokenize(stream, symbols);
                 stream.push(", ", CONFIG.colors.asm.expr);
                 self.operands[2].tokenize(stream, symbols);
                 stream.push(", #", CONFIG.colors.asm.expr);
-                stream.push_owned(decoder::encode_uhex(ops.op2() as u64), CONFIG.colors.asm.immediate);
+                stream.push_owned_kind(
+                    decoder::encode_uhex(ops.op2() as u64),
+                    CONFIG.colors.asm.immediate,
+                    TokenKind::Immediate,
+                );
                 stream.push(", ", CONFIG.colors.asm.expr);
                 self.operands[0].tokenize(stream, symbols);
                 return;
@@ -797,13 +834,21 @@ impl ToTokens for Instruction {
                 stream.push("sysl ", CONFIG.colors.asm.opcode);
                 self.operands[2].tokenize(stream, symbols);
                 stream.push(", #", CONFIG.colors.asm.expr);
-                stream.push_owned(decoder::encode_uhex(ops.op1() as u64), CONFIG.colors.asm.immediate);
+                stream.push_owned_kind(
+                    decoder::encode_uhex(ops.op1() as u64),
+                    CONFIG.colors.asm.immediate,
+                    TokenKind::Immediate,
+                );
                 stream.push(", ", CONFIG.colors.asm.expr);
                 self.operands[0].tokenize(stream, symbols);
                 stream.push(", ", CONFIG.colors.asm.expr);
                 self.operands[1].tokenize(stream, symbols);
                 stream.push(", #", CONFIG.colors.asm.expr);
-                stream.push_owned(decoder::encode_uhex(ops.op2() as u64), CONFIG.colors.asm.immediate);
+                stream.push_owned_kind(
+                    decoder::encode_uhex(ops.op2() as u64),
+                    CONFIG.colors.asm.immediate,
+                    TokenKind::Immediate,
+                );
                 return;
             }
             Opcode::HINT => {
@@ -824,9 +869,10 @@ impl ToTokens for Instruction {
                         0x15 => stream.push("sevl", CONFIG.colors.asm.opcode),
                         _ => {
                             stream.push("hint #", CONFIG.colors.asm.opcode);
-                            stream.push_owned(
+                            stream.push_owned_kind(
                                 decoder::encode_uhex(hint_num as u64),
                                 CONFIG.colors.asm.immediate,
+                                TokenKind::Immediate,
                             );
                         }
                     };
@@ -3015,7 +3061,7 @@ fn format_register_32(stream: &mut TokenStream, reg: u16) {
         "w27", "w28", "w29", "w30", "w31",
     ];
 
-    stream.push(LOOKUP[reg as usize], CONFIG.colors.asm.register);
+    stream.push_kind(LOOKUP[reg as usize], CONFIG.colors.asm.register, TokenKind::Register);
 }
 
 fn format_register_64(stream: &mut TokenStream, reg: u16) {
@@ -3025,7 +3071,7 @@ fn format_register_64(stream: &mut TokenStream, reg: u16) {
         "x27", "x28", "x29", "x30", "x31",
     ];
 
-    stream.push(LOOKUP[reg as usize], CONFIG.colors.asm.register);
+    stream.push_kind(LOOKUP[reg as usize], CONFIG.colors.asm.register, TokenKind::Register);
 }
 
 fn format_register_b(stream: &mut TokenStream, reg: u16) {
@@ -3035,7 +3081,7 @@ fn format_register_b(stream: &mut TokenStream, reg: u16) {
         "b27", "b28", "b29", "b30", "b31",
     ];
 
-    stream.push(LOOKUP[reg as usize], CONFIG.colors.asm.register);
+    stream.push_kind(LOOKUP[reg as usize], CONFIG.colors.asm.register, TokenKind::Register);
 }
 
 fn format_register_h(stream: &mut TokenStream, reg: u16) {
@@ -3045,7 +3091,7 @@ fn format_register_h(stream: &mut TokenStream, reg: u16) {
         "h27", "h28", "h29", "h30", "h31",
     ];
 
-    stream.push(LOOKUP[reg as usize], CONFIG.colors.asm.register);
+    stream.push_kind(LOOKUP[reg as usize], CONFIG.colors.asm.register, TokenKind::Register);
 }
 
 fn format_register_s(stream: &mut TokenStream, reg: u16) {
@@ -3055,7 +3101,7 @@ fn format_register_s(stream: &mut TokenStream, reg: u16) {
         "s27", "s28", "s29", "s30", "s31",
     ];
 
-    stream.push(LOOKUP[reg as usize], CONFIG.colors.asm.register);
+    stream.push_kind(LOOKUP[reg as usize], CONFIG.colors.asm.register, TokenKind::Register);
 }
 
 fn format_register_d(stream: &mut TokenStream, reg: u16) {
@@ -3065,7 +3111,7 @@ fn format_register_d(stream: &mut TokenStream, reg: u16) {
         "d27", "d28", "d29", "d30", "d31",
     ];
 
-    stream.push(LOOKUP[reg as usize], CONFIG.colors.asm.register);
+    stream.push_kind(LOOKUP[reg as usize], CONFIG.colors.asm.register, TokenKind::Register);
 }
 
 fn format_register_q(stream: &mut TokenStream, reg: u16) {
@@ -3075,7 +3121,7 @@ fn format_register_q(stream: &mut TokenStream, reg: u16) {
         "q27", "q28", "q29", "q30", "q31",
     ];
 
-    stream.push(LOOKUP[reg as usize], CONFIG.colors.asm.register);
+    stream.push_kind(LOOKUP[reg as usize], CONFIG.colors.asm.register, TokenKind::Register);
 }
 
 fn format_register_ctrl(stream: &mut TokenStream, reg: u16) {
@@ -3084,7 +3130,7 @@ fn format_register_ctrl(stream: &mut TokenStream, reg: u16) {
         "c14", "c15",
     ];
 
-    stream.push(LOOKUP[reg as usize], CONFIG.colors.asm.register);
+    stream.push_kind(LOOKUP[reg as usize], CONFIG.colors.asm.register, TokenKind::Register);
 }
 
 /// the way a shift operation is carried out.
@@ -3331,10 +3377,18 @@ impl ToTokens for Operand {
                 if *reg == 31 {
                     match size {
                         SizeCode::X => {
-                            stream.push("xzr", CONFIG.colors.asm.register);
+                            stream.push_kind(
+                                "xzr",
+                                CONFIG.colors.asm.register,
+                                TokenKind::Register,
+                            );
                         }
                         SizeCode::W => {
-                            stream.push("wzr", CONFIG.colors.asm.register);
+                            stream.push_kind(
+                                "wzr",
+                                CONFIG.colors.asm.register,
+                                TokenKind::Register,
+                            );
                         }
                     }
                 } else {
@@ -3362,7 +3416,11 @@ impl ToTokens for Operand {
                 let policy = op & 1;
 
                 if ty == 0b11 || target == 0b11 {
-                    stream.push_owned(format!("{:#02x}", op), CONFIG.colors.asm.immediate);
+                    stream.push_owned_kind(
+                        format!("{:#02x}", op),
+                        CONFIG.colors.asm.immediate,
+                        TokenKind::Immediate,
+                    );
                 } else {
                     let op = format!(
                         "{}{}{}",
@@ -3376,9 +3434,27 @@ impl ToTokens for Operand {
             Operand::SystemReg(reg) => {
                 // TODO: look up system register names better
                 match reg {
-                    0x4000 => stream.push("midr_el1", CONFIG.colors.asm.register),
-                    0x5e82 => stream.push("tpidr_el0", CONFIG.colors.asm.register),
-                    0x5f02 => stream.push("cntvct_el0", CONFIG.colors.asm.register),
+                    0x4000 => {
+                        stream.push_kind(
+                            "midr_el1",
+                            CONFIG.colors.asm.register,
+                            TokenKind::Register,
+                        );
+                    }
+                    0x5e82 => {
+                        stream.push_kind(
+                            "tpidr_el0",
+                            CONFIG.colors.asm.register,
+                            TokenKind::Register,
+                        );
+                    }
+                    0x5f02 => {
+                        stream.push_kind(
+                            "cntvct_el0",
+                            CONFIG.colors.asm.register,
+                            TokenKind::Register,
+                        );
+                    }
                     _ => {
                         // syntax for otherwise-undescribed system register names is described in
                         // MRS or similar, S<op0>_<op1>_<Cn>_<Cm>_<op2>
@@ -3389,22 +3465,46 @@ impl ToTokens for Operand {
                         let op1 = (reg >> 11) & 0b111;
                         let op0 = ((reg >> 14) & 0b1) + 2;
 
-                        stream.push_owned(format!("s{op0}"), CONFIG.colors.asm.immediate);
+                        stream.push_owned_kind(
+                            format!("s{op0}"),
+                            CONFIG.colors.asm.immediate,
+                            TokenKind::Immediate,
+                        );
                         stream.push("_", CONFIG.colors.asm.expr);
-                        stream.push_owned(format!("{op1}"), CONFIG.colors.asm.immediate);
+                        stream.push_owned_kind(
+                            format!("{op1}"),
+                            CONFIG.colors.asm.immediate,
+                            TokenKind::Immediate,
+                        );
                         stream.push("_", CONFIG.colors.asm.expr);
-                        stream.push_owned(format!("c{CRn}"), CONFIG.colors.asm.register);
+                        stream.push_owned_kind(
+                            format!("c{CRn}"),
+                            CONFIG.colors.asm.register,
+                            TokenKind::Register,
+                        );
                         stream.push("_", CONFIG.colors.asm.expr);
-                        stream.push_owned(format!("c{CRm}"), CONFIG.colors.asm.register);
+                        stream.push_owned_kind(
+                            format!("c{CRm}"),
+                            CONFIG.colors.asm.register,
+                            TokenKind::Register,
+                        );
                         stream.push("_", CONFIG.colors.asm.expr);
-                        stream.push_owned(format!("{op2}"), CONFIG.colors.asm.immediate);
+                        stream.push_owned_kind(
+                            format!("{op2}"),
+                            CONFIG.colors.asm.immediate,
+                            TokenKind::Immediate,
+                        );
                     }
                 }
             }
             Operand::PstateField(reg) => {
                 // `MSR (immediate)` writes to the `PSTATE` registers, setting a few bit patterns as
                 // selected by `reg`.
-                stream.push_owned(format!("pstate.{:#x}", reg), CONFIG.colors.asm.register);
+                stream.push_owned_kind(
+                    format!("pstate.{:#x}", reg),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
             }
             Operand::SIMDRegister(size, reg) => match size {
                 SIMDSizeCode::B => format_register_b(stream, *reg),
@@ -3416,11 +3516,11 @@ impl ToTokens for Operand {
             Operand::SIMDRegisterElements(vector_width, reg, lane_width) => {
                 let num_items = vector_width.width() / lane_width.width();
                 let op = format!("v{}.{}{}", reg, num_items, lane_width.name());
-                stream.push_owned(op, CONFIG.colors.asm.register);
+                stream.push_owned_kind(op, CONFIG.colors.asm.register, TokenKind::Register);
             }
             Operand::SIMDRegisterElementsLane(_vector_width, reg, lane_width, lane) => {
                 let op = format!("v{}.{}[{}]", reg, lane_width.name(), lane);
-                stream.push_owned(op, CONFIG.colors.asm.register);
+                stream.push_owned_kind(op, CONFIG.colors.asm.register, TokenKind::Register);
             }
             Operand::SIMDRegisterElementsMultipleLane(
                 _vector_width,
@@ -3430,13 +3530,13 @@ impl ToTokens for Operand {
                 num_lanes,
             ) => {
                 let op = format!("v{}.{}{}[{}]", reg, num_lanes, lane_width.name(), lane);
-                stream.push_owned(op, CONFIG.colors.asm.register);
+                stream.push_owned_kind(op, CONFIG.colors.asm.register, TokenKind::Register);
             }
             Operand::SIMDRegisterGroup(vector_width, reg, lane_width, group_size) => {
                 let num_items = vector_width.width() / lane_width.width();
                 let format_reg = |stream: &mut TokenStream, reg, elems, lane_size: SIMDSizeCode| {
                     let op = format!("v{}.{}{}", reg, elems, lane_size.name());
-                    stream.push_owned(op, CONFIG.colors.asm.register);
+                    stream.push_owned_kind(op, CONFIG.colors.asm.register, TokenKind::Register);
                 };
 
                 stream.push("{", CONFIG.colors.brackets);
@@ -3450,7 +3550,7 @@ impl ToTokens for Operand {
             Operand::SIMDRegisterGroupLane(reg, lane_width, group_size, lane) => {
                 let format_reg = |stream: &mut TokenStream, reg, lane_size: SIMDSizeCode| {
                     let op = format!("v{}.{}", reg, lane_size.name());
-                    stream.push_owned(op, CONFIG.colors.asm.register);
+                    stream.push_owned_kind(op, CONFIG.colors.asm.register, TokenKind::Register);
                 };
 
                 stream.push("{", CONFIG.colors.brackets);
@@ -3460,17 +3560,25 @@ impl ToTokens for Operand {
                     format_reg(stream, (*reg + i as u16) % 32, *lane_width);
                 }
                 stream.push("}[", CONFIG.colors.brackets);
-                stream.push_owned(lane.to_string(), CONFIG.colors.asm.register);
+                stream.push_owned_kind(
+                    lane.to_string(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push("]", CONFIG.colors.brackets);
             }
             Operand::RegisterOrSP(size, reg) => {
                 if *reg == 31 {
                     match size {
                         SizeCode::X => {
-                            stream.push("sp", CONFIG.colors.asm.register);
+                            stream.push_kind("sp", CONFIG.colors.asm.register, TokenKind::Register);
                         }
                         SizeCode::W => {
-                            stream.push("wsp", CONFIG.colors.asm.register);
+                            stream.push_kind(
+                                "wsp",
+                                CONFIG.colors.asm.register,
+                                TokenKind::Register,
+                            );
                         }
                     }
                 } else {
@@ -3510,84 +3618,126 @@ impl ToTokens for Operand {
             Operand::PCOffset(offs) => {
                 if *offs >= 0 {
                     stream.push("$", CONFIG.colors.asm.expr);
-                    stream.push("+", CONFIG.colors.asm.immediate);
+                    stream.push_kind("+", CONFIG.colors.asm.immediate, TokenKind::Immediate);
                 } else {
                     stream.push("$", CONFIG.colors.asm.expr);
                 }
-                stream.push_owned(decoder::encode_hex(*offs), CONFIG.colors.asm.immediate);
+                stream.push_owned_kind(
+                    decoder::encode_hex(*offs),
+                    CONFIG.colors.asm.immediate,
+                    TokenKind::Immediate,
+                );
             }
             Operand::Immediate(imm) => match symbols.get_sym_by_addr(*imm as usize) {
                 Some(symbol) => {
-                    stream.push("<", CONFIG.colors.asm.immediate);
+                    let start = stream.inner.len();
+                    stream.push_kind("<", CONFIG.colors.asm.immediate, TokenKind::Immediate);
                     for token in symbol.name() {
                         stream.push_token(token.clone());
                     }
-                    stream.push(">", CONFIG.colors.asm.immediate);
+                    stream.push_kind(">", CONFIG.colors.asm.immediate, TokenKind::Immediate);
+                    stream.set_target(start, *imm as usize);
                 }
                 None => {
                     stream.push("#", CONFIG.colors.asm.expr);
-                    stream.push_owned(decoder::encode_uhex(*imm as u64), CONFIG.colors.asm.immediate);
+                    stream.push_owned_kind(
+                        decoder::encode_uhex(*imm as u64),
+                        CONFIG.colors.asm.immediate,
+                        TokenKind::Immediate,
+                    );
                 }
             },
             Operand::ImmediateDouble(d) => {
                 if *d as i64 as f64 == *d {
                     let imm = format!("#{d:0.1}");
-                    stream.push_owned(imm, CONFIG.colors.asm.immediate);
+                    stream.push_owned_kind(imm, CONFIG.colors.asm.immediate, TokenKind::Immediate);
                 } else {
                     let imm = format!("#{d:0.}");
-                    stream.push_owned(imm, CONFIG.colors.asm.immediate);
+                    stream.push_owned_kind(imm, CONFIG.colors.asm.immediate, TokenKind::Immediate);
                 }
             }
             Operand::Imm16(imm) => match symbols.get_sym_by_addr(*imm as usize) {
                 Some(symbol) => {
-                    stream.push("<", CONFIG.colors.asm.immediate);
+                    let start = stream.inner.len();
+                    stream.push_kind("<", CONFIG.colors.asm.immediate, TokenKind::Immediate);
                     for token in symbol.name() {
                         stream.push_token(token.clone());
                     }
-                    stream.push(">", CONFIG.colors.asm.immediate);
+                    stream.push_kind(">", CONFIG.colors.asm.immediate, TokenKind::Immediate);
+                    stream.set_target(start, *imm as usize);
                 }
                 None => {
                     stream.push("#", CONFIG.colors.asm.expr);
-                    stream.push_owned(decoder::encode_uhex(*imm as u64), CONFIG.colors.asm.immediate);
+                    stream.push_owned_kind(
+                        decoder::encode_uhex(*imm as u64),
+                        CONFIG.colors.asm.immediate,
+                        TokenKind::Immediate,
+                    );
                 }
             },
             Operand::Imm64(imm) => match symbols.get_sym_by_addr(*imm as usize) {
                 Some(symbol) => {
-                    stream.push("<", CONFIG.colors.asm.immediate);
+                    let start = stream.inner.len();
+                    stream.push_kind("<", CONFIG.colors.asm.immediate, TokenKind::Immediate);
                     for token in symbol.name() {
                         stream.push_token(token.clone());
                     }
-                    stream.push(">", CONFIG.colors.asm.immediate);
+                    stream.push_kind(">", CONFIG.colors.asm.immediate, TokenKind::Immediate);
+                    stream.set_target(start, *imm as usize);
                 }
                 None => {
                     stream.push("#", CONFIG.colors.asm.expr);
-                    stream.push_owned(decoder::encode_uhex(*imm as u64), CONFIG.colors.asm.immediate);
+                    stream.push_owned_kind(
+                        decoder::encode_uhex(*imm as u64),
+                        CONFIG.colors.asm.immediate,
+                        TokenKind::Immediate,
+                    );
                 }
             },
             Operand::Imm64Special(imm) => {
                 stream.push("#", CONFIG.colors.asm.expr);
-                stream.push_owned(decoder::encode_uhex(*imm as u64), CONFIG.colors.asm.immediate);
+                stream.push_owned_kind(
+                    decoder::encode_uhex(*imm as u64),
+                    CONFIG.colors.asm.immediate,
+                    TokenKind::Immediate,
+                );
             }
             Operand::ImmShift(imm, shift) => {
                 stream.push("#", CONFIG.colors.asm.expr);
-                stream.push_owned(decoder::encode_uhex(*imm as u64), CONFIG.colors.asm.immediate);
+                stream.push_owned_kind(
+                    decoder::encode_uhex(*imm as u64),
+                    CONFIG.colors.asm.immediate,
+                    TokenKind::Immediate,
+                );
 
                 if *shift != 0 {
                     stream.push(", ", CONFIG.colors.asm.expr);
                     stream.push("lsl ", CONFIG.colors.asm.opcode);
                     stream.push("#", CONFIG.colors.asm.expr);
-                    stream.push_owned(shift.to_string(), CONFIG.colors.asm.immediate);
+                    stream.push_owned_kind(
+                        shift.to_string(),
+                        CONFIG.colors.asm.immediate,
+                        TokenKind::Immediate,
+                    );
                 }
             }
             Operand::ImmShiftMSL(imm, shift) => {
                 stream.push("#", CONFIG.colors.asm.expr);
-                stream.push_owned(decoder::encode_uhex(*imm as u64), CONFIG.colors.asm.immediate);
+                stream.push_owned_kind(
+                    decoder::encode_uhex(*imm as u64),
+                    CONFIG.colors.asm.immediate,
+                    TokenKind::Immediate,
+                );
 
                 if *shift != 0 {
                     stream.push(", ", CONFIG.colors.asm.expr);
                     stream.push("msl ", CONFIG.colors.asm.opcode);
                     stream.push("#", CONFIG.colors.asm.expr);
-                    stream.push_owned(shift.to_string(), CONFIG.colors.asm.immediate);
+                    stream.push_owned_kind(
+                        shift.to_string(),
+                        CONFIG.colors.asm.immediate,
+                        TokenKind::Immediate,
+                    );
                 }
             }
             Operand::RegShift(shift_type, amount, size, reg) => match size {
@@ -3602,7 +3752,11 @@ impl ToTokens for Operand {
                         stream.push(shift_type.as_str(), CONFIG.colors.asm.opcode);
                         stream.push(" ", CONFIG.colors.asm.expr);
                         stream.push("#", CONFIG.colors.asm.expr);
-                        stream.push_owned(amount.to_string(), CONFIG.colors.asm.immediate);
+                        stream.push_owned_kind(
+                            amount.to_string(),
+                            CONFIG.colors.asm.immediate,
+                            TokenKind::Immediate,
+                        );
                     } else {
                         Operand::Register(SizeCode::X, *reg).tokenize(stream, symbols);
                         stream.push(", ", CONFIG.colors.asm.expr);
@@ -3618,7 +3772,11 @@ impl ToTokens for Operand {
                         stream.push(shift_type.as_str(), CONFIG.colors.asm.opcode);
                         stream.push(" ", CONFIG.colors.asm.expr);
                         stream.push("#", CONFIG.colors.asm.expr);
-                        stream.push_owned(amount.to_string(), CONFIG.colors.asm.immediate);
+                        stream.push_owned_kind(
+                            amount.to_string(),
+                            CONFIG.colors.asm.immediate,
+                            TokenKind::Immediate,
+                        );
                     } else {
                         Operand::Register(SizeCode::W, *reg).tokenize(stream, symbols);
                         stream.push(", ", CONFIG.colors.asm.expr);
@@ -3653,7 +3811,11 @@ impl ToTokens for Operand {
                     stream.push(extend.as_str(), CONFIG.colors.asm.opcode);
                     stream.push(" ", CONFIG.colors.asm.expr);
                     stream.push("#", CONFIG.colors.asm.expr);
-                    stream.push_owned(amount.to_string(), CONFIG.colors.asm.immediate);
+                    stream.push_owned_kind(
+                        amount.to_string(),
+                        CONFIG.colors.asm.immediate,
+                        TokenKind::Immediate,
+                    );
                     stream.push("]", CONFIG.colors.brackets);
                 }
             }
@@ -3663,7 +3825,11 @@ impl ToTokens for Operand {
                     Operand::RegisterOrSP(SizeCode::X, *reg).tokenize(stream, symbols);
                     stream.push(", ", CONFIG.colors.asm.expr);
                     stream.push("#", CONFIG.colors.asm.expr);
-                    stream.push_owned(decoder::encode_hex(*offset as i64), CONFIG.colors.asm.immediate);
+                    stream.push_owned_kind(
+                        decoder::encode_hex(*offset as i64),
+                        CONFIG.colors.asm.immediate,
+                        TokenKind::Immediate,
+                    );
                     stream.push("]", CONFIG.colors.brackets);
 
                     if *wback_bit {
@@ -3680,7 +3846,11 @@ impl ToTokens for Operand {
                 Operand::RegisterOrSP(SizeCode::X, *reg).tokenize(stream, symbols);
                 stream.push("], ", CONFIG.colors.asm.expr);
                 stream.push("#", CONFIG.colors.asm.expr);
-                stream.push_owned(decoder::encode_hex(*offset as i64), CONFIG.colors.asm.immediate);
+                stream.push_owned_kind(
+                    decoder::encode_hex(*offset as i64),
+                    CONFIG.colors.asm.immediate,
+                    TokenKind::Immediate,
+                );
             }
             Operand::RegPostIndexReg(reg, offset_reg) => {
                 stream.push("[", CONFIG.colors.brackets);
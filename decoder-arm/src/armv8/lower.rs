@@ -0,0 +1,425 @@
+//! Lowering of `aarch64` instructions into the architecture independent [`ir`](decoder::ir).
+
+use super::a64::{Instruction, Opcode, Operand, ShiftStyle, SizeCode};
+use decoder::ir::{BinaryOp, Cond, Condition, Expr, Flags, Reg, Stmt, UnaryOp};
+
+#[rustfmt::skip]
+const GPR_NAMES: [&str; 32] = [
+    "x0", "x1", "x2", "x3", "x4", "x5", "x6", "x7", "x8", "x9", "x10", "x11", "x12", "x13", "x14",
+    "x15", "x16", "x17", "x18", "x19", "x20", "x21", "x22", "x23", "x24", "x25", "x26", "x27",
+    "x28", "x29", "x30", "sp",
+];
+
+const SP: u16 = 31;
+
+fn size(size: SizeCode) -> u8 {
+    match size {
+        SizeCode::X => 8,
+        SizeCode::W => 4,
+    }
+}
+
+fn gpr(num: u16, bytes: u8) -> Reg {
+    Reg::full(GPR_NAMES[num as usize & 31], bytes)
+}
+
+/// Register `31` is the zero register for some operands, and the stack pointer for others.
+fn read_reg(num: u16, bytes: u8, zero: bool) -> Expr {
+    match num == SP && zero {
+        true => Expr::Const(0),
+        false => Expr::Reg(gpr(num, bytes)),
+    }
+}
+
+fn condition(code: u8) -> Option<Condition> {
+    Some(match code {
+        0b0000 => Condition::Eq,
+        0b0001 => Condition::Ne,
+        0b0010 => Condition::AboveEq,
+        0b0011 => Condition::Below,
+        0b0100 => Condition::Negative,
+        0b0101 => Condition::NotNegative,
+        0b0110 => Condition::Overflow,
+        0b0111 => Condition::NoOverflow,
+        0b1000 => Condition::Above,
+        0b1001 => Condition::BelowEq,
+        0b1010 => Condition::Ge,
+        0b1011 => Condition::Lt,
+        0b1100 => Condition::Gt,
+        0b1101 => Condition::Le,
+        // `al` and `nv` always hold
+        _ => return None,
+    })
+}
+
+/// Register shifted or extended by some amount.
+fn shifted(style: ShiftStyle, amount: u8, value: Expr) -> Expr {
+    let amount = Expr::Const(amount as u64);
+    let extended = match style {
+        ShiftStyle::LSL => return Expr::binary(BinaryOp::Shl, value, amount),
+        ShiftStyle::LSR => return Expr::binary(BinaryOp::Shr, value, amount),
+        ShiftStyle::ASR => return Expr::binary(BinaryOp::Sar, value, amount),
+        ShiftStyle::ROR => return Expr::binary(BinaryOp::Ror, value, amount),
+        ShiftStyle::UXTB => Expr::truncate(value, 1),
+        ShiftStyle::UXTH => Expr::truncate(value, 2),
+        ShiftStyle::UXTW => Expr::truncate(value, 4),
+        ShiftStyle::SXTB => Expr::sign_extend(value, 1),
+        ShiftStyle::SXTH => Expr::sign_extend(value, 2),
+        ShiftStyle::SXTW => Expr::sign_extend(value, 4),
+        ShiftStyle::UXTX | ShiftStyle::SXTX => value,
+    };
+
+    match amount {
+        Expr::Const(0) => extended,
+        amount => Expr::binary(BinaryOp::Shl, extended, amount),
+    }
+}
+
+/// Number of bytes accessed by a load or store and whether loads are sign extended.
+fn access(opcode: Opcode, reg: &Operand) -> Option<(u8, bool)> {
+    let reg_size = match reg {
+        Operand::Register(sz, _) => size(*sz),
+        _ => return None,
+    };
+
+    Some(match opcode {
+        Opcode::LDR | Opcode::LDUR | Opcode::STR | Opcode::STUR => (reg_size, false),
+        Opcode::LDP | Opcode::STP => (reg_size, false),
+        Opcode::LDRB | Opcode::LDURB | Opcode::STRB | Opcode::STURB => (1, false),
+        Opcode::LDRH | Opcode::LDURH | Opcode::STRH | Opcode::STURH => (2, false),
+        Opcode::LDRSB | Opcode::LDURSB => (1, true),
+        Opcode::LDRSH | Opcode::LDURSH => (2, true),
+        Opcode::LDRSW | Opcode::LDURSW => (4, true),
+        _ => return None,
+    })
+}
+
+fn is_store(opcode: Opcode) -> bool {
+    matches!(
+        opcode,
+        Opcode::STR
+            | Opcode::STUR
+            | Opcode::STP
+            | Opcode::STRB
+            | Opcode::STURB
+            | Opcode::STRH
+            | Opcode::STURH
+    )
+}
+
+impl Instruction {
+    /// Size in bytes of the instruction's destination register.
+    fn width(&self) -> u8 {
+        match self.operands[0] {
+            Operand::Register(sz, _) | Operand::RegisterOrSP(sz, _) => size(sz),
+            _ => 8,
+        }
+    }
+
+    fn read(&self, op: &Operand) -> Expr {
+        match *op {
+            Operand::Register(sz, num) => read_reg(num, size(sz), true),
+            Operand::RegisterOrSP(sz, num) => read_reg(num, size(sz), false),
+            Operand::Immediate(imm) => Expr::Const(imm as u64),
+            Operand::Imm16(imm) => Expr::Const(imm as u64),
+            Operand::Imm64(imm) | Operand::Imm64Special(imm) => Expr::Const(imm),
+            Operand::ImmShift(imm, shift) => Expr::Const((imm as u64) << shift),
+            Operand::RegShift(style, amount, sz, num) => {
+                let value = read_reg(num, size(sz), true);
+                match (style, amount) {
+                    (ShiftStyle::LSL, 0) => value,
+                    (style, amount) => shifted(style, amount, value),
+                }
+            }
+            _ => Expr::Unknown,
+        }
+    }
+
+    fn write(&self, op: &Operand, src: Expr, stmts: &mut Vec<Stmt>) {
+        let (num, sz, zero) = match *op {
+            Operand::Register(sz, num) => (num, sz, true),
+            Operand::RegisterOrSP(sz, num) => (num, sz, false),
+            _ => return stmts.push(Stmt::Unknown),
+        };
+
+        // writes to the zero register are discarded
+        if num == SP && zero {
+            return;
+        }
+
+        // writing a w register clears the upper half of the x register
+        let src = match (sz, src) {
+            (SizeCode::W, Expr::Const(imm)) => Expr::Const(imm as u32 as u64),
+            (SizeCode::W, src @ Expr::Load { size: ..=4, .. }) => src,
+            (SizeCode::W, src) => Expr::truncate(src, 4),
+            (SizeCode::X, src) => src,
+        };
+
+        stmts.push(Stmt::Assign {
+            dst: gpr(num, 8),
+            src,
+        });
+    }
+
+    /// Address of a memory operand, and the register written back with its new value.
+    fn address(&self, op: &Operand) -> (Expr, Option<(Reg, Expr)>) {
+        let base = |num: u16| Expr::Reg(gpr(num, 8));
+        let offset = |num: u16, off: i32| match off {
+            ..=-1 => Expr::binary(
+                BinaryOp::Sub,
+                base(num),
+                Expr::Const(off.unsigned_abs() as u64),
+            ),
+            _ => Expr::sum(base(num), Expr::Const(off as u64)),
+        };
+
+        match *op {
+            Operand::RegPreIndex(num, off, false) => (offset(num, off), None),
+            Operand::RegPreIndex(num, off, true) => {
+                (offset(num, off), Some((gpr(num, 8), offset(num, off))))
+            }
+            Operand::RegPostIndex(num, off) => (base(num), Some((gpr(num, 8), offset(num, off)))),
+            Operand::RegRegOffset(num, idx, sz, style, amount) => {
+                let idx = read_reg(idx, size(sz), true);
+                let idx = match (style, amount) {
+                    (ShiftStyle::LSL | ShiftStyle::UXTX | ShiftStyle::SXTX, 0) => idx,
+                    (style, amount) => shifted(style, amount, idx),
+                };
+
+                (Expr::sum(base(num), idx), None)
+            }
+            Operand::Imm64(addr) => (Expr::Const(addr), None),
+            _ => (Expr::Unknown, None),
+        }
+    }
+
+    fn lower_memory(&self, bytes: u8, signed: bool, stmts: &mut Vec<Stmt>) {
+        let pair = matches!(self.opcode, Opcode::LDP | Opcode::STP);
+        let regs = match pair {
+            true => &self.operands[..2],
+            false => &self.operands[..1],
+        };
+
+        let (addr, writeback) = self.address(&self.operands[regs.len()]);
+        for (idx, reg) in regs.iter().enumerate() {
+            let addr = Expr::sum(addr.clone(), Expr::Const(idx as u64 * bytes as u64));
+
+            if is_store(self.opcode) {
+                let src = self.read(reg);
+                stmts.push(Stmt::Store {
+                    addr,
+                    size: bytes,
+                    src,
+                });
+                continue;
+            }
+
+            let value = match signed {
+                true => Expr::sign_extend(Expr::load(addr, bytes), bytes),
+                false => Expr::load(addr, bytes),
+            };
+
+            self.write(reg, value, stmts);
+        }
+
+        if let Some((dst, src)) = writeback {
+            stmts.push(Stmt::Assign { dst, src });
+        }
+    }
+
+    /// Lower `ubfm` and `sbfm`, which are usually aliases of shifts and extensions.
+    fn lower_bitfield(&self, stmts: &mut Vec<Stmt>) {
+        let [dst, src, Operand::Immediate(immr), Operand::Immediate(imms)] = self.operands else {
+            return stmts.push(Stmt::Unknown);
+        };
+
+        let bits = self.width() as u32 * 8;
+        let value = self.read(&src);
+        let signed = self.opcode == Opcode::SBFM;
+
+        let value = if imms == bits - 1 {
+            let op = if signed { BinaryOp::Sar } else { BinaryOp::Shr };
+            Expr::binary(op, value, Expr::Const(immr as u64))
+        } else if !signed && imms + 1 == immr {
+            Expr::binary(BinaryOp::Shl, value, Expr::Const((bits - 1 - imms) as u64))
+        } else if immr == 0 && matches!(imms, 7 | 15 | 31) {
+            let bytes = (imms as u8 + 1) / 8;
+            match signed {
+                true => Expr::sign_extend(value, bytes),
+                false => Expr::truncate(value, bytes),
+            }
+        } else {
+            Expr::Unknown
+        };
+
+        self.write(&dst, value, stmts);
+    }
+
+    pub(super) fn lower_into(&self, stmts: &mut Vec<Stmt>) {
+        let ops = &self.operands;
+
+        if let Some((bytes, signed)) = access(self.opcode, &ops[0]) {
+            return self.lower_memory(bytes, signed, stmts);
+        }
+
+        let binary = |binop: BinaryOp| Expr::binary(binop, self.read(&ops[1]), self.read(&ops[2]));
+
+        match self.opcode {
+            Opcode::HINT => {}
+            Opcode::ADD | Opcode::SUB | Opcode::AND | Opcode::ORR | Opcode::EOR => {
+                let binop = match self.opcode {
+                    Opcode::ADD => BinaryOp::Add,
+                    Opcode::SUB => BinaryOp::Sub,
+                    Opcode::AND => BinaryOp::And,
+                    Opcode::ORR => BinaryOp::Or,
+                    _ => BinaryOp::Xor,
+                };
+
+                let value = match (binop, self.read(&ops[1]), self.read(&ops[2])) {
+                    (BinaryOp::Or, Expr::Const(0), value) => value,
+                    (binop, lhs, rhs) => match rhs {
+                        Expr::Const(0) if binop != BinaryOp::And => lhs,
+                        rhs => Expr::binary(binop, lhs, rhs),
+                    },
+                };
+
+                self.write(&ops[0], value, stmts);
+            }
+            Opcode::ORN | Opcode::BIC | Opcode::EON => {
+                let binop = match self.opcode {
+                    Opcode::ORN => BinaryOp::Or,
+                    Opcode::BIC => BinaryOp::And,
+                    _ => BinaryOp::Xor,
+                };
+
+                let rhs = Expr::unary(UnaryOp::Not, self.read(&ops[2]));
+                let value = Expr::binary(binop, self.read(&ops[1]), rhs);
+                self.write(&ops[0], value, stmts);
+            }
+            Opcode::SUBS => {
+                stmts.push(Stmt::Flags(Flags::Compare(
+                    self.read(&ops[1]),
+                    self.read(&ops[2]),
+                )));
+                self.write(&ops[0], binary(BinaryOp::Sub), stmts);
+            }
+            Opcode::ADDS => {
+                stmts.push(Stmt::Flags(Flags::Result(binary(BinaryOp::Add))));
+                self.write(&ops[0], binary(BinaryOp::Add), stmts);
+            }
+            Opcode::ANDS => {
+                stmts.push(Stmt::Flags(Flags::Test(
+                    self.read(&ops[1]),
+                    self.read(&ops[2]),
+                )));
+                self.write(&ops[0], binary(BinaryOp::And), stmts);
+            }
+            Opcode::LSLV => self.write(&ops[0], binary(BinaryOp::Shl), stmts),
+            Opcode::LSRV => self.write(&ops[0], binary(BinaryOp::Shr), stmts),
+            Opcode::ASRV => self.write(&ops[0], binary(BinaryOp::Sar), stmts),
+            Opcode::RORV => self.write(&ops[0], binary(BinaryOp::Ror), stmts),
+            Opcode::SDIV => self.write(&ops[0], binary(BinaryOp::SignedDiv), stmts),
+            Opcode::UDIV => self.write(&ops[0], binary(BinaryOp::Div), stmts),
+            Opcode::MADD | Opcode::MSUB => {
+                let binop = match self.opcode {
+                    Opcode::MADD => BinaryOp::Add,
+                    _ => BinaryOp::Sub,
+                };
+
+                let value = match self.read(&ops[3]) {
+                    Expr::Const(0) if binop == BinaryOp::Add => binary(BinaryOp::Mul),
+                    acc => Expr::binary(binop, acc, binary(BinaryOp::Mul)),
+                };
+
+                self.write(&ops[0], value, stmts);
+            }
+            Opcode::MOVZ => self.write(&ops[0], self.read(&ops[1]), stmts),
+            Opcode::MOVN => {
+                let value = Expr::unary(UnaryOp::Not, self.read(&ops[1]));
+                self.write(&ops[0], value, stmts);
+            }
+            Opcode::MOVK => {
+                let Operand::ImmShift(imm, shift) = ops[1] else {
+                    return stmts.push(Stmt::Unknown);
+                };
+
+                let mask = Expr::Const(!(0xffff << shift));
+                let kept = Expr::binary(BinaryOp::And, self.read(&ops[0]), mask);
+                let value = Expr::binary(BinaryOp::Or, kept, Expr::Const((imm as u64) << shift));
+                self.write(&ops[0], value, stmts);
+            }
+            Opcode::ADR | Opcode::ADRP => self.write(&ops[0], self.read(&ops[1]), stmts),
+            Opcode::UBFM | Opcode::SBFM => self.lower_bitfield(stmts),
+            Opcode::CSEL | Opcode::CSINC | Opcode::CSINV | Opcode::CSNEG => {
+                let Operand::ConditionCode(code) = ops[3] else {
+                    return stmts.push(Stmt::Unknown);
+                };
+
+                let otherwise = self.read(&ops[2]);
+                let otherwise = match self.opcode {
+                    Opcode::CSINC => Expr::binary(BinaryOp::Add, otherwise, Expr::Const(1)),
+                    Opcode::CSINV => Expr::unary(UnaryOp::Not, otherwise),
+                    Opcode::CSNEG => Expr::unary(UnaryOp::Neg, otherwise),
+                    _ => otherwise,
+                };
+
+                let value = match condition(code) {
+                    Some(condition) => {
+                        Expr::select(Cond::Flags(condition), self.read(&ops[1]), otherwise)
+                    }
+                    None => self.read(&ops[1]),
+                };
+
+                self.write(&ops[0], value, stmts);
+            }
+            Opcode::B => stmts.push(Stmt::Jump(self.read(&ops[0]))),
+            Opcode::BR => stmts.push(Stmt::Jump(self.read(&ops[0]))),
+            Opcode::BL | Opcode::BLR => stmts.push(Stmt::Call(self.read(&ops[0]))),
+            Opcode::RET => stmts.push(Stmt::Return),
+            Opcode::Bcc(code) => match condition(code) {
+                Some(condition) => stmts.push(Stmt::Branch {
+                    cond: Cond::Flags(condition),
+                    target: self.read(&ops[0]),
+                }),
+                None => stmts.push(Stmt::Jump(self.read(&ops[0]))),
+            },
+            Opcode::CBZ | Opcode::CBNZ => {
+                let condition = match self.opcode {
+                    Opcode::CBZ => Condition::Eq,
+                    _ => Condition::Ne,
+                };
+
+                stmts.push(Stmt::Branch {
+                    cond: Cond::Compare(condition, self.read(&ops[0]), Expr::Const(0)),
+                    target: self.read(&ops[1]),
+                });
+            }
+            Opcode::TBZ | Opcode::TBNZ => {
+                let condition = match self.opcode {
+                    Opcode::TBZ => Condition::Eq,
+                    _ => Condition::Ne,
+                };
+
+                let Operand::Imm16(bit) = ops[1] else {
+                    return stmts.push(Stmt::Unknown);
+                };
+
+                let mask = Expr::Const(1 << bit);
+                let bit = Expr::binary(BinaryOp::And, self.read(&ops[0]), mask);
+                stmts.push(Stmt::Branch {
+                    cond: Cond::Compare(condition, bit, Expr::Const(0)),
+                    target: self.read(&ops[2]),
+                });
+            }
+            Opcode::BRK | Opcode::HLT => stmts.push(Stmt::Halt),
+            _ => {
+                stmts.push(Stmt::Unknown);
+
+                // assume the first register operand is the destination
+                if let (Operand::Register(..), false) = (ops[0], ops[1] == Operand::Nothing) {
+                    self.write(&ops[0], Expr::Unknown, stmts);
+                }
+            }
+        }
+    }
+}
@@ -1,2 +1,7 @@
 /// `yaxpeax-arm`'s `ARMv8/aarch64` decoder and `Arch` implementation.
 pub mod a64;
+
+mod lower;
+
+/// Reference of common `aarch64` instructions.
+pub mod reference;
@@ -0,0 +1,398 @@
+use decoder::Reference;
+
+const NZCV: &str = "N Z C V";
+
+/// Sorted by mnemonic.
+static REFERENCE: &[Reference] = &[
+    Reference {
+        mnemonic: "adc",
+        operation: "Add with carry: Rd = Rn + Rm + C.",
+        flags: "reads C",
+        forms: &["Rd, Rn, Rm"],
+    },
+    Reference {
+        mnemonic: "add",
+        operation: "Add: Rd = Rn + op2.",
+        flags: "",
+        forms: &[
+            "Rd, Rn, #imm{, lsl #12}",
+            "Rd, Rn, Rm{, shift #amount}",
+            "Rd, Rn, Rm, extend",
+        ],
+    },
+    Reference {
+        mnemonic: "adds",
+        operation: "Add and set flags: Rd = Rn + op2.",
+        flags: NZCV,
+        forms: &[
+            "Rd, Rn, #imm{, lsl #12}",
+            "Rd, Rn, Rm{, shift #amount}",
+            "Rd, Rn, Rm, extend",
+        ],
+    },
+    Reference {
+        mnemonic: "adr",
+        operation: "Form a pc relative address: Rd = pc + offset.",
+        flags: "",
+        forms: &["Xd, label"],
+    },
+    Reference {
+        mnemonic: "adrp",
+        operation: "Form a pc relative address to a 4KB page: Rd = (pc & !0xfff) + offset.",
+        flags: "",
+        forms: &["Xd, label"],
+    },
+    Reference {
+        mnemonic: "and",
+        operation: "Bitwise and: Rd = Rn & op2.",
+        flags: "",
+        forms: &["Rd, Rn, #imm", "Rd, Rn, Rm{, shift #amount}"],
+    },
+    Reference {
+        mnemonic: "ands",
+        operation: "Bitwise and and set flags: Rd = Rn & op2.",
+        flags: "N Z set, C V cleared",
+        forms: &["Rd, Rn, #imm", "Rd, Rn, Rm{, shift #amount}"],
+    },
+    Reference {
+        mnemonic: "asr",
+        operation: "Arithmetic shift right, preserving the sign.",
+        flags: "",
+        forms: &["Rd, Rn, #shift", "Rd, Rn, Rm"],
+    },
+    Reference {
+        mnemonic: "b",
+        operation: "Branch to the target.",
+        flags: "",
+        forms: &["label"],
+    },
+    Reference {
+        mnemonic: "b.cond",
+        operation: "Branch to the target if the condition holds.",
+        flags: "reads the flags of the condition",
+        forms: &["label"],
+    },
+    Reference {
+        mnemonic: "bic",
+        operation: "Bit clear: Rd = Rn & !op2.",
+        flags: "",
+        forms: &["Rd, Rn, Rm{, shift #amount}"],
+    },
+    Reference {
+        mnemonic: "bl",
+        operation: "Branch with link: x30 = return address, branch to the target.",
+        flags: "",
+        forms: &["label"],
+    },
+    Reference {
+        mnemonic: "blr",
+        operation: "Branch with link to register: x30 = return address, branch to Xn.",
+        flags: "",
+        forms: &["Xn"],
+    },
+    Reference {
+        mnemonic: "br",
+        operation: "Branch to the address in Xn.",
+        flags: "",
+        forms: &["Xn"],
+    },
+    Reference {
+        mnemonic: "cbnz",
+        operation: "Branch to the target if Rt isn't zero.",
+        flags: "",
+        forms: &["Rt, label"],
+    },
+    Reference {
+        mnemonic: "cbz",
+        operation: "Branch to the target if Rt is zero.",
+        flags: "",
+        forms: &["Rt, label"],
+    },
+    Reference {
+        mnemonic: "ccmp",
+        operation: "Conditional compare: flags = Rn - op2 if the condition holds, \
+                    otherwise flags = nzcv.",
+        flags: NZCV,
+        forms: &["Rn, Rm, #nzcv, cond", "Rn, #imm, #nzcv, cond"],
+    },
+    Reference {
+        mnemonic: "cmn",
+        operation: "Compare negative: computes Rn + op2 and discards the result.",
+        flags: NZCV,
+        forms: &["Rn, #imm", "Rn, Rm{, shift #amount}"],
+    },
+    Reference {
+        mnemonic: "cmp",
+        operation: "Compare: computes Rn - op2 and discards the result.",
+        flags: NZCV,
+        forms: &["Rn, #imm", "Rn, Rm{, shift #amount}"],
+    },
+    Reference {
+        mnemonic: "csel",
+        operation: "Conditional select: Rd = cond ? Rn : Rm.",
+        flags: "reads the flags of the condition",
+        forms: &["Rd, Rn, Rm, cond"],
+    },
+    Reference {
+        mnemonic: "cset",
+        operation: "Conditional set: Rd = cond ? 1 : 0.",
+        flags: "reads the flags of the condition",
+        forms: &["Rd, cond"],
+    },
+    Reference {
+        mnemonic: "csinc",
+        operation: "Conditional select increment: Rd = cond ? Rn : Rm + 1.",
+        flags: "reads the flags of the condition",
+        forms: &["Rd, Rn, Rm, cond"],
+    },
+    Reference {
+        mnemonic: "dmb",
+        operation: "Data memory barrier, orders memory accesses in the given domain.",
+        flags: "",
+        forms: &["option"],
+    },
+    Reference {
+        mnemonic: "eor",
+        operation: "Bitwise exclusive or: Rd = Rn ^ op2.",
+        flags: "",
+        forms: &["Rd, Rn, #imm", "Rd, Rn, Rm{, shift #amount}"],
+    },
+    Reference {
+        mnemonic: "ldp",
+        operation: "Load a pair of registers from memory.",
+        flags: "",
+        forms: &[
+            "Rt1, Rt2, [Xn{, #imm}]",
+            "Rt1, Rt2, [Xn, #imm]!",
+            "Rt1, Rt2, [Xn], #imm",
+        ],
+    },
+    Reference {
+        mnemonic: "ldr",
+        operation: "Load a register from memory.",
+        flags: "",
+        forms: &[
+            "Rt, [Xn{, #imm}]",
+            "Rt, [Xn, #imm]!",
+            "Rt, [Xn], #imm",
+            "Rt, label",
+        ],
+    },
+    Reference {
+        mnemonic: "ldrb",
+        operation: "Load a zero extended byte from memory.",
+        flags: "",
+        forms: &["Wt, [Xn{, #imm}]", "Wt, [Xn, Rm{, extend}]"],
+    },
+    Reference {
+        mnemonic: "ldrh",
+        operation: "Load a zero extended halfword from memory.",
+        flags: "",
+        forms: &["Wt, [Xn{, #imm}]", "Wt, [Xn, Rm{, extend}]"],
+    },
+    Reference {
+        mnemonic: "ldur",
+        operation: "Load a register from memory using an unscaled offset.",
+        flags: "",
+        forms: &["Rt, [Xn{, #simm9}]"],
+    },
+    Reference {
+        mnemonic: "lsl",
+        operation: "Logical shift left.",
+        flags: "",
+        forms: &["Rd, Rn, #shift", "Rd, Rn, Rm"],
+    },
+    Reference {
+        mnemonic: "lsr",
+        operation: "Logical shift right.",
+        flags: "",
+        forms: &["Rd, Rn, #shift", "Rd, Rn, Rm"],
+    },
+    Reference {
+        mnemonic: "madd",
+        operation: "Multiply add: Rd = Ra + Rn * Rm.",
+        flags: "",
+        forms: &["Rd, Rn, Rm, Ra"],
+    },
+    Reference {
+        mnemonic: "mov",
+        operation: "Move: Rd = op2.",
+        flags: "",
+        forms: &["Rd, Rm", "Rd, #imm"],
+    },
+    Reference {
+        mnemonic: "movk",
+        operation: "Move wide with keep: insert a 16-bit immediate, keeping the other bits.",
+        flags: "",
+        forms: &["Rd, #imm16{, lsl #shift}"],
+    },
+    Reference {
+        mnemonic: "movn",
+        operation: "Move wide with not: Rd = !(imm16 << shift).",
+        flags: "",
+        forms: &["Rd, #imm16{, lsl #shift}"],
+    },
+    Reference {
+        mnemonic: "movz",
+        operation: "Move wide with zero: Rd = imm16 << shift.",
+        flags: "",
+        forms: &["Rd, #imm16{, lsl #shift}"],
+    },
+    Reference {
+        mnemonic: "msub",
+        operation: "Multiply subtract: Rd = Ra - Rn * Rm.",
+        flags: "",
+        forms: &["Rd, Rn, Rm, Ra"],
+    },
+    Reference {
+        mnemonic: "mul",
+        operation: "Multiply: Rd = Rn * Rm.",
+        flags: "",
+        forms: &["Rd, Rn, Rm"],
+    },
+    Reference {
+        mnemonic: "mvn",
+        operation: "Bitwise not: Rd = !op2.",
+        flags: "",
+        forms: &["Rd, Rm{, shift #amount}"],
+    },
+    Reference {
+        mnemonic: "neg",
+        operation: "Negate: Rd = -op2.",
+        flags: "",
+        forms: &["Rd, Rm{, shift #amount}"],
+    },
+    Reference {
+        mnemonic: "nop",
+        operation: "No operation.",
+        flags: "",
+        forms: &[""],
+    },
+    Reference {
+        mnemonic: "orr",
+        operation: "Bitwise or: Rd = Rn | op2.",
+        flags: "",
+        forms: &["Rd, Rn, #imm", "Rd, Rn, Rm{, shift #amount}"],
+    },
+    Reference {
+        mnemonic: "ret",
+        operation: "Return to the address in Xn, x30 if omitted.",
+        flags: "",
+        forms: &["", "Xn"],
+    },
+    Reference {
+        mnemonic: "sdiv",
+        operation: "Signed divide: Rd = Rn / Rm, rounding towards zero.",
+        flags: "",
+        forms: &["Rd, Rn, Rm"],
+    },
+    Reference {
+        mnemonic: "stp",
+        operation: "Store a pair of registers to memory.",
+        flags: "",
+        forms: &[
+            "Rt1, Rt2, [Xn{, #imm}]",
+            "Rt1, Rt2, [Xn, #imm]!",
+            "Rt1, Rt2, [Xn], #imm",
+        ],
+    },
+    Reference {
+        mnemonic: "str",
+        operation: "Store a register to memory.",
+        flags: "",
+        forms: &["Rt, [Xn{, #imm}]", "Rt, [Xn, #imm]!", "Rt, [Xn], #imm"],
+    },
+    Reference {
+        mnemonic: "strb",
+        operation: "Store the lowest byte of a register to memory.",
+        flags: "",
+        forms: &["Wt, [Xn{, #imm}]", "Wt, [Xn, Rm{, extend}]"],
+    },
+    Reference {
+        mnemonic: "strh",
+        operation: "Store the lowest halfword of a register to memory.",
+        flags: "",
+        forms: &["Wt, [Xn{, #imm}]", "Wt, [Xn, Rm{, extend}]"],
+    },
+    Reference {
+        mnemonic: "stur",
+        operation: "Store a register to memory using an unscaled offset.",
+        flags: "",
+        forms: &["Rt, [Xn{, #simm9}]"],
+    },
+    Reference {
+        mnemonic: "sub",
+        operation: "Subtract: Rd = Rn - op2.",
+        flags: "",
+        forms: &[
+            "Rd, Rn, #imm{, lsl #12}",
+            "Rd, Rn, Rm{, shift #amount}",
+            "Rd, Rn, Rm, extend",
+        ],
+    },
+    Reference {
+        mnemonic: "subs",
+        operation: "Subtract and set flags: Rd = Rn - op2.",
+        flags: NZCV,
+        forms: &[
+            "Rd, Rn, #imm{, lsl #12}",
+            "Rd, Rn, Rm{, shift #amount}",
+            "Rd, Rn, Rm, extend",
+        ],
+    },
+    Reference {
+        mnemonic: "svc",
+        operation: "Supervisor call, traps into the operating system.",
+        flags: "",
+        forms: &["#imm16"],
+    },
+    Reference {
+        mnemonic: "sxtw",
+        operation: "Sign extend a word: Xd = sign extended Wn.",
+        flags: "",
+        forms: &["Xd, Wn"],
+    },
+    Reference {
+        mnemonic: "tbnz",
+        operation: "Branch to the target if the selected bit of Rt isn't zero.",
+        flags: "",
+        forms: &["Rt, #bit, label"],
+    },
+    Reference {
+        mnemonic: "tbz",
+        operation: "Branch to the target if the selected bit of Rt is zero.",
+        flags: "",
+        forms: &["Rt, #bit, label"],
+    },
+    Reference {
+        mnemonic: "tst",
+        operation: "Test bits: computes Rn & op2 and discards the result.",
+        flags: "N Z set, C V cleared",
+        forms: &["Rn, #imm", "Rn, Rm{, shift #amount}"],
+    },
+    Reference {
+        mnemonic: "udiv",
+        operation: "Unsigned divide: Rd = Rn / Rm.",
+        flags: "",
+        forms: &["Rd, Rn, Rm"],
+    },
+    Reference {
+        mnemonic: "uxtb",
+        operation: "Zero extend a byte: Wd = zero extended lowest byte of Wn.",
+        flags: "",
+        forms: &["Wd, Wn"],
+    },
+];
+
+/// Look up the reference of a mnemonic as it's displayed, e.g. `b.` or `dmb ish`.
+pub fn reference(mnemonic: &str) -> Option<&'static Reference> {
+    let mnemonic = mnemonic.split_whitespace().next()?;
+
+    // conditional branches are displayed as `b.` followed by the condition
+    let family = match mnemonic {
+        "b." => "b.cond",
+        _ => mnemonic,
+    };
+
+    decoder::lookup_reference(REFERENCE, family)
+}
@@ -1 +1,2 @@
 mod a64;
+mod reference;
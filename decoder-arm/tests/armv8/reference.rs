@@ -0,0 +1,45 @@
+use arm::armv8::reference::reference;
+use debugvault::Index;
+use decoder::{Decodable, Reader, ToTokens};
+use tokenizing::TokenStream;
+
+type InstDecoder = arm::armv8::a64::Decoder;
+
+fn mnemonic(data: [u8; 4]) -> String {
+    let mut reader = Reader::new(&data[..]);
+    let instr = InstDecoder::default().decode(&mut reader).unwrap();
+    let mut stream = TokenStream::new();
+    instr.tokenize(&mut stream, &Index::default());
+    stream.inner[0].text.to_string()
+}
+
+#[test]
+fn lookup() {
+    for mnemonic in [
+        "adrp", "b", "bl", "cbz", "ldp", "mov", "stp", "subs", "tbnz", "uxtb",
+    ] {
+        assert_eq!(reference(mnemonic).map(|r| r.mnemonic), Some(mnemonic));
+    }
+
+    assert_eq!(reference("dmb ish").map(|r| r.mnemonic), Some("dmb"));
+    assert_eq!(reference("fmadd"), None);
+}
+
+#[test]
+fn decoded_mnemonics() {
+    // nop
+    assert_eq!(
+        reference(&mnemonic([0x1f, 0x20, 0x03, 0xd5])).map(|r| r.mnemonic),
+        Some("nop")
+    );
+    // b.eq #0x8
+    assert_eq!(
+        reference(&mnemonic([0x40, 0x00, 0x00, 0x54])).map(|r| r.mnemonic),
+        Some("b.cond")
+    );
+    // ret
+    assert_eq!(
+        reference(&mnemonic([0xc0, 0x03, 0x5f, 0xd6])).map(|r| r.mnemonic),
+        Some("ret")
+    );
+}
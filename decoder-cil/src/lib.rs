@@ -0,0 +1,489 @@
+//! Common Intermediate Language disassembler, the bytecode of .NET assemblies.
+
+mod lower;
+mod tests;
+
+use config::CONFIG;
+use debugvault::Index;
+use decoder::{Error, ErrorKind};
+use tokenizing::{TokenKind, TokenStream};
+
+/// Prefix of two byte opcodes.
+const EXTENDED: u8 = 0xFE;
+
+/// Longest instruction that isn't a `switch`, which is as long as its number of targets.
+const MAX_WIDTH: usize = 10;
+
+/// How an opcode's operand is encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    None,
+    /// 8-bit signed integer.
+    Int8,
+    /// 8-bit unsigned integer, used by prefixes.
+    UInt8,
+    Int32,
+    Int64,
+    Float32,
+    Float64,
+    /// 8-bit index of an argument or local.
+    ShortVar,
+    /// 16-bit index of an argument or local.
+    Var,
+    /// 8-bit offset from the next instruction.
+    ShortBranch,
+    /// 32-bit offset from the next instruction.
+    Branch,
+    /// Number of targets followed by a 32-bit offset for every target.
+    Switch,
+    /// Metadata token of a method, field, type, signature or string.
+    Token,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    Nothing,
+    Int(i64),
+    Float(f64),
+    /// Index of an argument or local.
+    Var(u16),
+    /// Offset of the destination from the next instruction, see [`Instruction::targets`].
+    Branch(i64),
+    /// Offsets of the destinations from the next instruction.
+    Switch(Vec<i64>),
+    /// Metadata token, the top byte being the table it indexes into.
+    Token(u32),
+}
+
+#[derive(Debug, Clone)]
+pub struct Instruction {
+    mnemonic: &'static str,
+    operand: Operand,
+    width: usize,
+    /// Address of the instruction, once it's known.
+    addr: Option<usize>,
+}
+
+impl Instruction {
+    pub fn mnemonic(&self) -> &'static str {
+        self.mnemonic
+    }
+
+    pub fn operand(&self) -> &Operand {
+        &self.operand
+    }
+
+    /// Addresses the instruction branches to, once its own address is known.
+    pub fn targets(&self) -> Vec<usize> {
+        let next = match self.addr {
+            Some(addr) => (addr + self.width) as i64,
+            None => return Vec::new(),
+        };
+
+        match self.operand {
+            Operand::Branch(offset) => vec![next.wrapping_add(offset) as usize],
+            Operand::Switch(ref offsets) => {
+                offsets.iter().map(|offset| next.wrapping_add(*offset) as usize).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Write the instruction, naming the metadata tokens it refers to.
+    pub fn tokenize_with(&self, stream: &mut TokenStream, names: &dyn Fn(u32) -> Option<String>) {
+        stream.push(self.mnemonic, CONFIG.colors.asm.opcode);
+
+        if self.operand == Operand::Nothing {
+            return;
+        }
+
+        stream.push(" ", CONFIG.colors.text);
+        match self.operand {
+            Operand::Nothing => {}
+            Operand::Int(imm) => stream.push_owned_kind(
+                decoder::encode_hex(imm),
+                CONFIG.colors.asm.immediate,
+                TokenKind::Immediate,
+            ),
+            Operand::Float(imm) => stream.push_owned_kind(
+                imm.to_string(),
+                CONFIG.colors.asm.immediate,
+                TokenKind::Immediate,
+            ),
+            Operand::Var(idx) => stream.push_owned_kind(
+                format!("V_{idx}"),
+                CONFIG.colors.asm.register,
+                TokenKind::Register,
+            ),
+            Operand::Branch(..) | Operand::Switch(..) => self.tokenize_targets(stream),
+            Operand::Token(token) => match names(token) {
+                Some(name) => {
+                    let start = stream.inner.len();
+                    stream.push_owned(name, CONFIG.colors.asm.label);
+                    stream.inner[start].kind = TokenKind::Symbol;
+                }
+                None => stream.push_owned_kind(
+                    format!("{token:#010x}"),
+                    CONFIG.colors.asm.immediate,
+                    TokenKind::Immediate,
+                ),
+            },
+        }
+    }
+
+    fn tokenize_targets(&self, stream: &mut TokenStream) {
+        let offsets = match self.operand {
+            Operand::Branch(offset) => vec![offset],
+            Operand::Switch(ref offsets) => offsets.clone(),
+            _ => return,
+        };
+
+        let is_switch = matches!(self.operand, Operand::Switch(..));
+        if is_switch {
+            stream.push("(", CONFIG.colors.delimiter);
+        }
+
+        let targets = self.targets();
+        for (idx, offset) in offsets.iter().enumerate() {
+            if idx != 0 {
+                stream.push(", ", CONFIG.colors.delimiter);
+            }
+
+            match targets.get(idx) {
+                Some(&target) => {
+                    let start = stream.inner.len();
+                    stream.push_owned(format!("{target:#x}"), CONFIG.colors.asm.pointer);
+                    stream.set_target(start, target);
+                }
+                // only the offset is known until the instruction is placed at an address
+                None => stream.push_owned_kind(
+                    format!("{offset:+}"),
+                    CONFIG.colors.asm.immediate,
+                    TokenKind::Immediate,
+                ),
+            }
+        }
+
+        if is_switch {
+            stream.push(")", CONFIG.colors.delimiter);
+        }
+    }
+}
+
+impl decoder::ToTokens for Instruction {
+    fn tokenize(&self, stream: &mut TokenStream, _: &Index) {
+        self.tokenize_with(stream, &|_| None);
+    }
+}
+
+impl decoder::Decoded for Instruction {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn update_rel_addrs(&mut self, addr: usize, _: Option<&Instruction>) {
+        self.addr = Some(addr);
+    }
+
+    fn lower(&self, stmts: &mut Vec<decoder::ir::Stmt>) {
+        self.lower_into(stmts);
+    }
+}
+
+#[derive(Default)]
+pub struct Decoder;
+
+impl decoder::Decodable for Decoder {
+    type Instruction = Instruction;
+
+    fn decode(&self, reader: &mut decoder::Reader) -> Result<Self::Instruction, Error> {
+        reader.mark();
+        decode(reader).map_err(|err| Error::new(err, reader.offset().max(1)))
+    }
+
+    fn max_width(&self) -> usize {
+        MAX_WIDTH
+    }
+}
+
+fn read<const N: usize>(reader: &mut decoder::Reader) -> Result<[u8; N], ErrorKind> {
+    let mut bytes = [0u8; N];
+    reader.next_n(&mut bytes).ok_or(ErrorKind::ExhaustedInput)?;
+    Ok(bytes)
+}
+
+fn decode(reader: &mut decoder::Reader) -> Result<Instruction, ErrorKind> {
+    let opcode = reader.next().ok_or(ErrorKind::ExhaustedInput)?;
+    let (mnemonic, encoding) = match opcode {
+        EXTENDED => {
+            let opcode = reader.next().ok_or(ErrorKind::ExhaustedInput)?;
+            extended_opcode(opcode).ok_or(ErrorKind::InvalidOpcode)?
+        }
+        opcode => single_opcode(opcode).ok_or(ErrorKind::InvalidOpcode)?,
+    };
+
+    let operand = match encoding {
+        Encoding::None => Operand::Nothing,
+        Encoding::Int8 => Operand::Int(i8::from_le_bytes(read(reader)?) as i64),
+        Encoding::UInt8 => Operand::Int(u8::from_le_bytes(read(reader)?) as i64),
+        Encoding::Int32 => Operand::Int(i32::from_le_bytes(read(reader)?) as i64),
+        Encoding::Int64 => Operand::Int(i64::from_le_bytes(read(reader)?)),
+        Encoding::Float32 => Operand::Float(f32::from_le_bytes(read(reader)?) as f64),
+        Encoding::Float64 => Operand::Float(f64::from_le_bytes(read(reader)?)),
+        Encoding::ShortVar => Operand::Var(u8::from_le_bytes(read(reader)?) as u16),
+        Encoding::Var => Operand::Var(u16::from_le_bytes(read(reader)?)),
+        Encoding::ShortBranch => Operand::Branch(i8::from_le_bytes(read(reader)?) as i64),
+        Encoding::Branch => Operand::Branch(i32::from_le_bytes(read(reader)?) as i64),
+        Encoding::Switch => {
+            let count = u32::from_le_bytes(read(reader)?);
+            let mut offsets = Vec::new();
+            for _ in 0..count {
+                offsets.push(i32::from_le_bytes(read(reader)?) as i64);
+            }
+            Operand::Switch(offsets)
+        }
+        Encoding::Token => Operand::Token(u32::from_le_bytes(read(reader)?)),
+    };
+
+    Ok(Instruction {
+        mnemonic,
+        operand,
+        width: reader.offset(),
+        addr: None,
+    })
+}
+
+fn single_opcode(opcode: u8) -> Option<(&'static str, Encoding)> {
+    use Encoding::*;
+
+    Some(match opcode {
+        0x00 => ("nop", None),
+        0x01 => ("break", None),
+        0x02 => ("ldarg.0", None),
+        0x03 => ("ldarg.1", None),
+        0x04 => ("ldarg.2", None),
+        0x05 => ("ldarg.3", None),
+        0x06 => ("ldloc.0", None),
+        0x07 => ("ldloc.1", None),
+        0x08 => ("ldloc.2", None),
+        0x09 => ("ldloc.3", None),
+        0x0A => ("stloc.0", None),
+        0x0B => ("stloc.1", None),
+        0x0C => ("stloc.2", None),
+        0x0D => ("stloc.3", None),
+        0x0E => ("ldarg.s", ShortVar),
+        0x0F => ("ldarga.s", ShortVar),
+        0x10 => ("starg.s", ShortVar),
+        0x11 => ("ldloc.s", ShortVar),
+        0x12 => ("ldloca.s", ShortVar),
+        0x13 => ("stloc.s", ShortVar),
+        0x14 => ("ldnull", None),
+        0x15 => ("ldc.i4.m1", None),
+        0x16 => ("ldc.i4.0", None),
+        0x17 => ("ldc.i4.1", None),
+        0x18 => ("ldc.i4.2", None),
+        0x19 => ("ldc.i4.3", None),
+        0x1A => ("ldc.i4.4", None),
+        0x1B => ("ldc.i4.5", None),
+        0x1C => ("ldc.i4.6", None),
+        0x1D => ("ldc.i4.7", None),
+        0x1E => ("ldc.i4.8", None),
+        0x1F => ("ldc.i4.s", Int8),
+        0x20 => ("ldc.i4", Int32),
+        0x21 => ("ldc.i8", Int64),
+        0x22 => ("ldc.r4", Float32),
+        0x23 => ("ldc.r8", Float64),
+        0x25 => ("dup", None),
+        0x26 => ("pop", None),
+        0x27 => ("jmp", Token),
+        0x28 => ("call", Token),
+        0x29 => ("calli", Token),
+        0x2A => ("ret", None),
+        0x2B => ("br.s", ShortBranch),
+        0x2C => ("brfalse.s", ShortBranch),
+        0x2D => ("brtrue.s", ShortBranch),
+        0x2E => ("beq.s", ShortBranch),
+        0x2F => ("bge.s", ShortBranch),
+        0x30 => ("bgt.s", ShortBranch),
+        0x31 => ("ble.s", ShortBranch),
+        0x32 => ("blt.s", ShortBranch),
+        0x33 => ("bne.un.s", ShortBranch),
+        0x34 => ("bge.un.s", ShortBranch),
+        0x35 => ("bgt.un.s", ShortBranch),
+        0x36 => ("ble.un.s", ShortBranch),
+        0x37 => ("blt.un.s", ShortBranch),
+        0x38 => ("br", Branch),
+        0x39 => ("brfalse", Branch),
+        0x3A => ("brtrue", Branch),
+        0x3B => ("beq", Branch),
+        0x3C => ("bge", Branch),
+        0x3D => ("bgt", Branch),
+        0x3E => ("ble", Branch),
+        0x3F => ("blt", Branch),
+        0x40 => ("bne.un", Branch),
+        0x41 => ("bge.un", Branch),
+        0x42 => ("bgt.un", Branch),
+        0x43 => ("ble.un", Branch),
+        0x44 => ("blt.un", Branch),
+        0x45 => ("switch", Switch),
+        0x46 => ("ldind.i1", None),
+        0x47 => ("ldind.u1", None),
+        0x48 => ("ldind.i2", None),
+        0x49 => ("ldind.u2", None),
+        0x4A => ("ldind.i4", None),
+        0x4B => ("ldind.u4", None),
+        0x4C => ("ldind.i8", None),
+        0x4D => ("ldind.i", None),
+        0x4E => ("ldind.r4", None),
+        0x4F => ("ldind.r8", None),
+        0x50 => ("ldind.ref", None),
+        0x51 => ("stind.ref", None),
+        0x52 => ("stind.i1", None),
+        0x53 => ("stind.i2", None),
+        0x54 => ("stind.i4", None),
+        0x55 => ("stind.i8", None),
+        0x56 => ("stind.r4", None),
+        0x57 => ("stind.r8", None),
+        0x58 => ("add", None),
+        0x59 => ("sub", None),
+        0x5A => ("mul", None),
+        0x5B => ("div", None),
+        0x5C => ("div.un", None),
+        0x5D => ("rem", None),
+        0x5E => ("rem.un", None),
+        0x5F => ("and", None),
+        0x60 => ("or", None),
+        0x61 => ("xor", None),
+        0x62 => ("shl", None),
+        0x63 => ("shr", None),
+        0x64 => ("shr.un", None),
+        0x65 => ("neg", None),
+        0x66 => ("not", None),
+        0x67 => ("conv.i1", None),
+        0x68 => ("conv.i2", None),
+        0x69 => ("conv.i4", None),
+        0x6A => ("conv.i8", None),
+        0x6B => ("conv.r4", None),
+        0x6C => ("conv.r8", None),
+        0x6D => ("conv.u4", None),
+        0x6E => ("conv.u8", None),
+        0x6F => ("callvirt", Token),
+        0x70 => ("cpobj", Token),
+        0x71 => ("ldobj", Token),
+        0x72 => ("ldstr", Token),
+        0x73 => ("newobj", Token),
+        0x74 => ("castclass", Token),
+        0x75 => ("isinst", Token),
+        0x76 => ("conv.r.un", None),
+        0x79 => ("unbox", Token),
+        0x7A => ("throw", None),
+        0x7B => ("ldfld", Token),
+        0x7C => ("ldflda", Token),
+        0x7D => ("stfld", Token),
+        0x7E => ("ldsfld", Token),
+        0x7F => ("ldsflda", Token),
+        0x80 => ("stsfld", Token),
+        0x81 => ("stobj", Token),
+        0x82 => ("conv.ovf.i1.un", None),
+        0x83 => ("conv.ovf.i2.un", None),
+        0x84 => ("conv.ovf.i4.un", None),
+        0x85 => ("conv.ovf.i8.un", None),
+        0x86 => ("conv.ovf.u1.un", None),
+        0x87 => ("conv.ovf.u2.un", None),
+        0x88 => ("conv.ovf.u4.un", None),
+        0x89 => ("conv.ovf.u8.un", None),
+        0x8A => ("conv.ovf.i.un", None),
+        0x8B => ("conv.ovf.u.un", None),
+        0x8C => ("box", Token),
+        0x8D => ("newarr", Token),
+        0x8E => ("ldlen", None),
+        0x8F => ("ldelema", Token),
+        0x90 => ("ldelem.i1", None),
+        0x91 => ("ldelem.u1", None),
+        0x92 => ("ldelem.i2", None),
+        0x93 => ("ldelem.u2", None),
+        0x94 => ("ldelem.i4", None),
+        0x95 => ("ldelem.u4", None),
+        0x96 => ("ldelem.i8", None),
+        0x97 => ("ldelem.i", None),
+        0x98 => ("ldelem.r4", None),
+        0x99 => ("ldelem.r8", None),
+        0x9A => ("ldelem.ref", None),
+        0x9B => ("stelem.i", None),
+        0x9C => ("stelem.i1", None),
+        0x9D => ("stelem.i2", None),
+        0x9E => ("stelem.i4", None),
+        0x9F => ("stelem.i8", None),
+        0xA0 => ("stelem.r4", None),
+        0xA1 => ("stelem.r8", None),
+        0xA2 => ("stelem.ref", None),
+        0xA3 => ("ldelem", Token),
+        0xA4 => ("stelem", Token),
+        0xA5 => ("unbox.any", Token),
+        0xB3 => ("conv.ovf.i1", None),
+        0xB4 => ("conv.ovf.u1", None),
+        0xB5 => ("conv.ovf.i2", None),
+        0xB6 => ("conv.ovf.u2", None),
+        0xB7 => ("conv.ovf.i4", None),
+        0xB8 => ("conv.ovf.u4", None),
+        0xB9 => ("conv.ovf.i8", None),
+        0xBA => ("conv.ovf.u8", None),
+        0xC2 => ("refanyval", Token),
+        0xC3 => ("ckfinite", None),
+        0xC6 => ("mkrefany", Token),
+        0xD0 => ("ldtoken", Token),
+        0xD1 => ("conv.u2", None),
+        0xD2 => ("conv.u1", None),
+        0xD3 => ("conv.i", None),
+        0xD4 => ("conv.ovf.i", None),
+        0xD5 => ("conv.ovf.u", None),
+        0xD6 => ("add.ovf", None),
+        0xD7 => ("add.ovf.un", None),
+        0xD8 => ("mul.ovf", None),
+        0xD9 => ("mul.ovf.un", None),
+        0xDA => ("sub.ovf", None),
+        0xDB => ("sub.ovf.un", None),
+        0xDC => ("endfinally", None),
+        0xDD => ("leave", Branch),
+        0xDE => ("leave.s", ShortBranch),
+        0xDF => ("stind.i", None),
+        0xE0 => ("conv.u", None),
+        _ => return Option::None,
+    })
+}
+
+fn extended_opcode(opcode: u8) -> Option<(&'static str, Encoding)> {
+    use Encoding::*;
+
+    Some(match opcode {
+        0x00 => ("arglist", None),
+        0x01 => ("ceq", None),
+        0x02 => ("cgt", None),
+        0x03 => ("cgt.un", None),
+        0x04 => ("clt", None),
+        0x05 => ("clt.un", None),
+        0x06 => ("ldftn", Token),
+        0x07 => ("ldvirtftn", Token),
+        0x09 => ("ldarg", Var),
+        0x0A => ("ldarga", Var),
+        0x0B => ("starg", Var),
+        0x0C => ("ldloc", Var),
+        0x0D => ("ldloca", Var),
+        0x0E => ("stloc", Var),
+        0x0F => ("localloc", None),
+        0x11 => ("endfilter", None),
+        0x12 => ("unaligned.", UInt8),
+        0x13 => ("volatile.", None),
+        0x14 => ("tail.", None),
+        0x15 => ("initobj", Token),
+        0x16 => ("constrained.", Token),
+        0x17 => ("cpblk", None),
+        0x18 => ("initblk", None),
+        0x19 => ("no.", UInt8),
+        0x1A => ("rethrow", None),
+        0x1C => ("sizeof", Token),
+        0x1D => ("refanytype", None),
+        0x1E => ("readonly.", None),
+        _ => return Option::None,
+    })
+}
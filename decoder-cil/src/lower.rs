@@ -0,0 +1,68 @@
+//! Lowering of instructions into the architecture independent [`ir`](decoder::ir).
+//!
+//! CIL works on an evaluation stack that isn't modelled, so only control flow is lowered and the
+//! values it depends on are unknown.
+
+use crate::Instruction;
+use decoder::ir::{Cond, Condition, Expr, Stmt};
+
+fn condition(mnemonic: &str) -> Option<Condition> {
+    Some(match mnemonic.trim_end_matches(".s") {
+        "brtrue" => Condition::Ne,
+        "brfalse" => Condition::Eq,
+        "beq" => Condition::Eq,
+        "bne.un" => Condition::Ne,
+        "bge" => Condition::Ge,
+        "bgt" => Condition::Gt,
+        "ble" => Condition::Le,
+        "blt" => Condition::Lt,
+        "bge.un" => Condition::AboveEq,
+        "bgt.un" => Condition::Above,
+        "ble.un" => Condition::BelowEq,
+        "blt.un" => Condition::Below,
+        _ => return None,
+    })
+}
+
+impl Instruction {
+    pub(crate) fn lower_into(&self, stmts: &mut Vec<Stmt>) {
+        let targets = self.targets();
+        let target = || targets.first().map_or(Expr::Unknown, |addr| Expr::Const(*addr as u64));
+
+        let stmt = match self.mnemonic {
+            "br" | "br.s" | "leave" | "leave.s" => Stmt::Jump(target()),
+            "brtrue" | "brtrue.s" | "brfalse" | "brfalse.s" => {
+                let condition = condition(self.mnemonic).unwrap();
+                Stmt::Branch {
+                    cond: Cond::Compare(condition, Expr::Unknown, Expr::Const(0)),
+                    target: target(),
+                }
+            }
+            "switch" => {
+                for addr in targets {
+                    stmts.push(Stmt::Branch {
+                        cond: Cond::Compare(Condition::Eq, Expr::Unknown, Expr::Unknown),
+                        target: Expr::Const(addr as u64),
+                    });
+                }
+                return;
+            }
+            mnemonic => match condition(mnemonic) {
+                Some(condition) => Stmt::Branch {
+                    cond: Cond::Compare(condition, Expr::Unknown, Expr::Unknown),
+                    target: target(),
+                },
+                None => match mnemonic {
+                    "call" | "callvirt" | "calli" | "newobj" => Stmt::Call(Expr::Unknown),
+                    "jmp" => Stmt::Jump(Expr::Unknown),
+                    "ret" => Stmt::Return,
+                    "throw" | "rethrow" | "break" => Stmt::Halt,
+                    "nop" => return,
+                    _ => Stmt::Unknown,
+                },
+            },
+        };
+
+        stmts.push(stmt);
+    }
+}
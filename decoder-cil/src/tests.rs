@@ -0,0 +1,60 @@
+#![cfg(test)]
+
+use decoder::{Decodable, Decoded, ToTokens};
+
+fn test_display(bytes: &[u8], str: &str) {
+    let mut reader = decoder::Reader::new(bytes);
+    let mut line = tokenizing::TokenStream::new();
+    let symbols = debugvault::Index::default();
+    let decoder = crate::Decoder;
+
+    let decoded = match decoder.decode(&mut reader) {
+        Ok(mut inst) => {
+            inst.update_rel_addrs(0x1000, None);
+            inst.tokenize(&mut line, &symbols);
+            line.to_string()
+        }
+        Err(err) => format!("{err:?}"),
+    };
+
+    assert_eq!(decoded, str);
+}
+
+#[test]
+fn ret() {
+    test_display(&[0x2a], "ret");
+}
+
+#[test]
+fn ldc() {
+    test_display(&[0x1f, 0xff], "ldc.i4.s -0x1");
+    test_display(&[0x20, 0x78, 0x56, 0x34, 0x12], "ldc.i4 0x12345678");
+}
+
+#[test]
+fn ldloc() {
+    test_display(&[0x11, 0x05], "ldloc.s V_5");
+    test_display(&[0xfe, 0x0c, 0x00, 0x01], "ldloc V_256");
+}
+
+#[test]
+fn call() {
+    test_display(&[0x28, 0x01, 0x00, 0x00, 0x0a], "call 0x0a000001");
+}
+
+#[test]
+fn branch() {
+    test_display(&[0x2b, 0x02], "br.s 0x1004");
+    test_display(&[0x38, 0xfb, 0xff, 0xff, 0xff], "br 0x1000");
+}
+
+#[test]
+fn switch() {
+    let bytes = [0x45, 0x02, 0, 0, 0, 0x00, 0, 0, 0, 0x10, 0, 0, 0];
+    test_display(&bytes, "switch (0x100d, 0x101d)");
+}
+
+#[test]
+fn invalid() {
+    test_display(&[0x24], "Error { kind: InvalidOpcode, size: 1 }");
+}
@@ -0,0 +1,563 @@
+//! Bytecode of Android's Dalvik and ART runtimes, as stored in the code items of a dex file.
+//!
+//! Instructions are made up of 16-bit code units, and the tables of `packed-switch`,
+//! `sparse-switch` and `fill-array-data` are stored in between them as payloads.
+
+use config::CONFIG;
+use debugvault::Index;
+use decoder::ir::{Cond, Condition, Expr, Stmt};
+use decoder::{Error, ErrorKind};
+use tokenizing::{TokenKind, TokenStream};
+
+/// Longest instruction that isn't a payload, which is a `const-wide`.
+const MAX_WIDTH: usize = 10;
+
+/// Pools of a dex file that instructions index into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pool {
+    String,
+    Type,
+    Field,
+    Method,
+    Proto,
+    CallSite,
+    MethodHandle,
+}
+
+impl Pool {
+    /// Prefix of the index when it can't be named, as shown by `dexdump`.
+    fn prefix(self) -> &'static str {
+        match self {
+            Pool::String => "string",
+            Pool::Type => "type",
+            Pool::Field => "field",
+            Pool::Method => "method",
+            Pool::Proto => "proto",
+            Pool::CallSite => "call_site",
+            Pool::MethodHandle => "method_handle",
+        }
+    }
+}
+
+/// How an opcode's operands are encoded, named after the formats in the dex specification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    F10x,
+    F12x,
+    F11n,
+    F11x,
+    F10t,
+    F20t,
+    F22x,
+    F21t,
+    F21s,
+    /// 16-bit immediate shifted into the top of a 32-bit value.
+    F21h,
+    /// 16-bit immediate shifted into the top of a 64-bit value.
+    F21hw,
+    F21c(Pool),
+    F23x,
+    F22b,
+    F22t,
+    F22s,
+    F22c(Pool),
+    F30t,
+    F32x,
+    F31i,
+    F31t,
+    F31c(Pool),
+    F35c(Pool),
+    F3rc(Pool),
+    F45cc,
+    F4rcc,
+    F51l,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operand {
+    Register(u16),
+    /// Registers passed to a call.
+    Registers(Vec<u16>),
+    /// First and last register of a range passed to a call.
+    RegisterRange(u16, u16),
+    Int(i64),
+    /// Offset of the destination from the instruction in code units, see
+    /// [`Instruction::targets`].
+    Branch(i32),
+    /// Index into one of the dex file's pools.
+    Index(Pool, u32),
+}
+
+#[derive(Debug, Clone)]
+pub struct Instruction {
+    mnemonic: &'static str,
+    operands: Vec<Operand>,
+    width: usize,
+    /// Address of the instruction, once it's known.
+    addr: Option<usize>,
+}
+
+impl Instruction {
+    pub fn mnemonic(&self) -> &'static str {
+        self.mnemonic
+    }
+
+    pub fn operands(&self) -> &[Operand] {
+        &self.operands
+    }
+
+    /// Addresses the instruction branches to, once its own address is known. Switches branch to
+    /// their payload, which holds the actual destinations.
+    pub fn targets(&self) -> Vec<usize> {
+        let addr = match self.addr {
+            Some(addr) => addr as i64,
+            None => return Vec::new(),
+        };
+
+        self.operands
+            .iter()
+            .filter_map(|operand| match operand {
+                Operand::Branch(offset) => Some(addr.wrapping_add(*offset as i64 * 2) as usize),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Write the instruction, naming the strings, types, fields and methods it refers to.
+    pub fn tokenize_with(
+        &self,
+        stream: &mut TokenStream,
+        names: &dyn Fn(Pool, u32) -> Option<String>,
+    ) {
+        stream.push(self.mnemonic, CONFIG.colors.asm.opcode);
+
+        let targets = self.targets();
+        for (idx, operand) in self.operands.iter().enumerate() {
+            stream.push(if idx == 0 { " " } else { ", " }, CONFIG.colors.delimiter);
+
+            match *operand {
+                Operand::Register(reg) => tokenize_register(stream, reg),
+                Operand::Registers(ref regs) => {
+                    stream.push("{", CONFIG.colors.delimiter);
+                    for (idx, reg) in regs.iter().enumerate() {
+                        if idx != 0 {
+                            stream.push(", ", CONFIG.colors.delimiter);
+                        }
+                        tokenize_register(stream, *reg);
+                    }
+                    stream.push("}", CONFIG.colors.delimiter);
+                }
+                Operand::RegisterRange(first, last) => {
+                    stream.push("{", CONFIG.colors.delimiter);
+                    tokenize_register(stream, first);
+                    stream.push(" .. ", CONFIG.colors.delimiter);
+                    tokenize_register(stream, last);
+                    stream.push("}", CONFIG.colors.delimiter);
+                }
+                Operand::Int(imm) => stream.push_owned_kind(
+                    decoder::encode_hex(imm),
+                    CONFIG.colors.asm.immediate,
+                    TokenKind::Immediate,
+                ),
+                Operand::Branch(offset) => match targets.first() {
+                    Some(&target) => {
+                        let start = stream.inner.len();
+                        stream.push_owned(format!("{target:#x}"), CONFIG.colors.asm.pointer);
+                        stream.set_target(start, target);
+                    }
+                    // only the offset is known until the instruction is placed at an address
+                    None => stream.push_owned_kind(
+                        format!("{offset:+}"),
+                        CONFIG.colors.asm.immediate,
+                        TokenKind::Immediate,
+                    ),
+                },
+                Operand::Index(pool, idx) => match names(pool, idx) {
+                    Some(name) => {
+                        let color = match pool {
+                            Pool::String => CONFIG.colors.asm.string,
+                            _ => CONFIG.colors.asm.label,
+                        };
+
+                        let start = stream.inner.len();
+                        stream.push_owned(name, color);
+                        stream.inner[start].kind = TokenKind::Symbol;
+                    }
+                    None => stream.push_owned_kind(
+                        format!("{}@{idx:04x}", pool.prefix()),
+                        CONFIG.colors.asm.immediate,
+                        TokenKind::Immediate,
+                    ),
+                },
+            }
+        }
+    }
+
+    fn lower_into(&self, stmts: &mut Vec<Stmt>) {
+        let target = match self.targets().first() {
+            Some(addr) => Expr::Const(*addr as u64),
+            None => Expr::Unknown,
+        };
+
+        // registers aren't lowered, so only what values are compared against is known
+        let compared = match self.operands.len() {
+            2 => Expr::Const(0),
+            _ => Expr::Unknown,
+        };
+
+        let condition = match self.mnemonic.trim_end_matches('z') {
+            "if-eq" => Some(Condition::Eq),
+            "if-ne" => Some(Condition::Ne),
+            "if-lt" => Some(Condition::Lt),
+            "if-ge" => Some(Condition::Ge),
+            "if-gt" => Some(Condition::Gt),
+            "if-le" => Some(Condition::Le),
+            _ => None,
+        };
+
+        if let Some(condition) = condition {
+            stmts.push(Stmt::Branch {
+                cond: Cond::Compare(condition, Expr::Unknown, compared),
+                target,
+            });
+            return;
+        }
+
+        let stmt = match self.mnemonic {
+            "goto" | "goto/16" | "goto/32" => Stmt::Jump(target),
+            mnemonic if mnemonic.starts_with("invoke-") => Stmt::Call(Expr::Unknown),
+            mnemonic if mnemonic.starts_with("return") => Stmt::Return,
+            "throw" => Stmt::Halt,
+            "nop" => return,
+            _ => Stmt::Unknown,
+        };
+
+        stmts.push(stmt);
+    }
+}
+
+fn tokenize_register(stream: &mut TokenStream, reg: u16) {
+    stream.push_owned_kind(format!("v{reg}"), CONFIG.colors.asm.register, TokenKind::Register);
+}
+
+impl decoder::ToTokens for Instruction {
+    fn tokenize(&self, stream: &mut TokenStream, _: &Index) {
+        self.tokenize_with(stream, &|_, _| None);
+    }
+}
+
+impl decoder::Decoded for Instruction {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn update_rel_addrs(&mut self, addr: usize, _: Option<&Instruction>) {
+        self.addr = Some(addr);
+    }
+
+    fn lower(&self, stmts: &mut Vec<Stmt>) {
+        self.lower_into(stmts);
+    }
+}
+
+#[derive(Default)]
+pub struct Decoder;
+
+impl decoder::Decodable for Decoder {
+    type Instruction = Instruction;
+
+    fn decode(&self, reader: &mut decoder::Reader) -> Result<Self::Instruction, Error> {
+        reader.mark();
+        decode(reader).map_err(|err| Error::new(err, reader.offset().max(2)))
+    }
+
+    fn max_width(&self) -> usize {
+        MAX_WIDTH
+    }
+}
+
+fn unit(reader: &mut decoder::Reader) -> Result<u16, ErrorKind> {
+    let mut bytes = [0u8; 2];
+    reader.next_n(&mut bytes).ok_or(ErrorKind::ExhaustedInput)?;
+    Ok(u16::from_le_bytes(bytes))
+}
+
+fn units32(reader: &mut decoder::Reader) -> Result<u32, ErrorKind> {
+    Ok(unit(reader)? as u32 | ((unit(reader)? as u32) << 16))
+}
+
+/// Skip over the code units of a payload, which aren't instructions.
+fn skip(reader: &mut decoder::Reader, units: usize) -> Result<(), ErrorKind> {
+    for _ in 0..units {
+        unit(reader)?;
+    }
+
+    Ok(())
+}
+
+fn decode(reader: &mut decoder::Reader) -> Result<Instruction, ErrorKind> {
+    use Operand::*;
+
+    let first = unit(reader)?;
+    let opcode = first as u8;
+    let aa = first >> 8;
+    let (a, b) = (aa & 0xF, aa >> 4);
+
+    // payloads are identified by a `nop` with a non-zero upper byte
+    if opcode == 0 && aa != 0 {
+        let (mnemonic, operands) = match aa {
+            0x01 => {
+                let size = unit(reader)?;
+                let first_key = units32(reader)? as i32;
+                skip(reader, size as usize * 2)?;
+                ("packed-switch-payload", vec![Int(first_key as i64), Int(size as i64)])
+            }
+            0x02 => {
+                let size = unit(reader)?;
+                skip(reader, size as usize * 4)?;
+                ("sparse-switch-payload", vec![Int(size as i64)])
+            }
+            0x03 => {
+                let element_width = unit(reader)?;
+                let size = units32(reader)?;
+                let len = element_width as usize * size as usize;
+                skip(reader, len.div_ceil(2))?;
+                ("fill-array-data-payload", vec![Int(element_width as i64), Int(size as i64)])
+            }
+            _ => return Err(ErrorKind::InvalidOpcode),
+        };
+
+        return Ok(Instruction {
+            mnemonic,
+            operands,
+            width: reader.offset(),
+            addr: None,
+        });
+    }
+
+    let (mnemonic, format) = opcode_info(opcode).ok_or(ErrorKind::InvalidOpcode)?;
+    let operands = match format {
+        Format::F10x => Vec::new(),
+        Format::F12x => vec![Register(a), Register(b)],
+        Format::F11n => vec![Register(a), Int(((b as i8) << 4 >> 4) as i64)],
+        Format::F11x => vec![Register(aa)],
+        Format::F10t => vec![Branch(aa as u8 as i8 as i32)],
+        Format::F20t => vec![Branch(unit(reader)? as i16 as i32)],
+        Format::F22x => vec![Register(aa), Register(unit(reader)?)],
+        Format::F21t => vec![Register(aa), Branch(unit(reader)? as i16 as i32)],
+        Format::F21s => vec![Register(aa), Int(unit(reader)? as i16 as i64)],
+        Format::F21h => vec![Register(aa), Int(((unit(reader)? as i16 as i32) << 16) as i64)],
+        Format::F21hw => vec![Register(aa), Int((unit(reader)? as i16 as i64) << 48)],
+        Format::F21c(pool) => vec![Register(aa), Index(pool, unit(reader)? as u32)],
+        Format::F23x => {
+            let cc_bb = unit(reader)?;
+            vec![Register(aa), Register(cc_bb & 0xFF), Register(cc_bb >> 8)]
+        }
+        Format::F22b => {
+            let cc_bb = unit(reader)?;
+            vec![Register(aa), Register(cc_bb & 0xFF), Int((cc_bb >> 8) as u8 as i8 as i64)]
+        }
+        Format::F22t => vec![Register(a), Register(b), Branch(unit(reader)? as i16 as i32)],
+        Format::F22s => vec![Register(a), Register(b), Int(unit(reader)? as i16 as i64)],
+        Format::F22c(pool) => vec![Register(a), Register(b), Index(pool, unit(reader)? as u32)],
+        Format::F30t => vec![Branch(units32(reader)? as i32)],
+        Format::F32x => vec![Register(unit(reader)?), Register(unit(reader)?)],
+        Format::F31i => vec![Register(aa), Int(units32(reader)? as i32 as i64)],
+        Format::F31t => vec![Register(aa), Branch(units32(reader)? as i32)],
+        Format::F31c(pool) => vec![Register(aa), Index(pool, units32(reader)?)],
+        Format::F35c(pool) => {
+            let idx = unit(reader)?;
+            vec![listed_registers(reader, aa)?, Index(pool, idx as u32)]
+        }
+        Format::F3rc(pool) => {
+            let idx = unit(reader)?;
+            vec![register_range(reader, aa)?, Index(pool, idx as u32)]
+        }
+        Format::F45cc => {
+            let method = unit(reader)?;
+            let registers = listed_registers(reader, aa)?;
+            let proto = unit(reader)?;
+            vec![registers, Index(Pool::Method, method as u32), Index(Pool::Proto, proto as u32)]
+        }
+        Format::F4rcc => {
+            let method = unit(reader)?;
+            let registers = register_range(reader, aa)?;
+            let proto = unit(reader)?;
+            vec![registers, Index(Pool::Method, method as u32), Index(Pool::Proto, proto as u32)]
+        }
+        Format::F51l => {
+            let low = units32(reader)? as u64;
+            let high = units32(reader)? as u64;
+            vec![Register(aa), Int(((high << 32) | low) as i64)]
+        }
+    };
+
+    Ok(Instruction {
+        mnemonic,
+        operands,
+        width: reader.offset(),
+        addr: None,
+    })
+}
+
+/// Up to five registers, the count and fifth register being in the first code unit and the
+/// others in the one after the index.
+fn listed_registers(reader: &mut decoder::Reader, aa: u16) -> Result<Operand, ErrorKind> {
+    let (count, g) = (aa >> 4, aa & 0xF);
+    if count > 5 {
+        return Err(ErrorKind::InvalidOperand);
+    }
+
+    let fedc = unit(reader)?;
+    let regs = [fedc & 0xF, (fedc >> 4) & 0xF, (fedc >> 8) & 0xF, fedc >> 12, g];
+    Ok(Operand::Registers(regs[..count as usize].to_vec()))
+}
+
+/// Range of registers, the count being in the first code unit and the first register in the one
+/// after the index.
+fn register_range(reader: &mut decoder::Reader, count: u16) -> Result<Operand, ErrorKind> {
+    let first = unit(reader)?;
+    Ok(match count {
+        0 => Operand::Registers(Vec::new()),
+        count => Operand::RegisterRange(first, first.wrapping_add(count - 1)),
+    })
+}
+
+fn opcode_info(opcode: u8) -> Option<(&'static str, Format)> {
+    use Format::*;
+
+    const UNARY: [&str; 21] = [
+        "neg-int", "not-int", "neg-long", "not-long", "neg-float", "neg-double", "int-to-long",
+        "int-to-float", "int-to-double", "long-to-int", "long-to-float", "long-to-double",
+        "float-to-int", "float-to-long", "float-to-double", "double-to-int", "double-to-long",
+        "double-to-float", "int-to-byte", "int-to-char", "int-to-short",
+    ];
+
+    const BINARY: [&str; 32] = [
+        "add-int", "sub-int", "mul-int", "div-int", "rem-int", "and-int", "or-int", "xor-int",
+        "shl-int", "shr-int", "ushr-int", "add-long", "sub-long", "mul-long", "div-long",
+        "rem-long", "and-long", "or-long", "xor-long", "shl-long", "shr-long", "ushr-long",
+        "add-float", "sub-float", "mul-float", "div-float", "rem-float", "add-double",
+        "sub-double", "mul-double", "div-double", "rem-double",
+    ];
+
+    const BINARY_2ADDR: [&str; 32] = [
+        "add-int/2addr", "sub-int/2addr", "mul-int/2addr", "div-int/2addr", "rem-int/2addr",
+        "and-int/2addr", "or-int/2addr", "xor-int/2addr", "shl-int/2addr", "shr-int/2addr",
+        "ushr-int/2addr", "add-long/2addr", "sub-long/2addr", "mul-long/2addr", "div-long/2addr",
+        "rem-long/2addr", "and-long/2addr", "or-long/2addr", "xor-long/2addr", "shl-long/2addr",
+        "shr-long/2addr", "ushr-long/2addr", "add-float/2addr", "sub-float/2addr",
+        "mul-float/2addr", "div-float/2addr", "rem-float/2addr", "add-double/2addr",
+        "sub-double/2addr", "mul-double/2addr", "div-double/2addr", "rem-double/2addr",
+    ];
+
+    const LIT16: [&str; 8] = [
+        "add-int/lit16", "rsub-int", "mul-int/lit16", "div-int/lit16", "rem-int/lit16",
+        "and-int/lit16", "or-int/lit16", "xor-int/lit16",
+    ];
+
+    const LIT8: [&str; 11] = [
+        "add-int/lit8", "rsub-int/lit8", "mul-int/lit8", "div-int/lit8", "rem-int/lit8",
+        "and-int/lit8", "or-int/lit8", "xor-int/lit8", "shl-int/lit8", "shr-int/lit8",
+        "ushr-int/lit8",
+    ];
+
+    const CMP: [&str; 5] = ["cmpl-float", "cmpg-float", "cmpl-double", "cmpg-double", "cmp-long"];
+
+    const IF_TEST: [&str; 6] = ["if-eq", "if-ne", "if-lt", "if-ge", "if-gt", "if-le"];
+
+    const IF_TESTZ: [&str; 6] = ["if-eqz", "if-nez", "if-ltz", "if-gez", "if-gtz", "if-lez"];
+
+    const ARRAY: [&str; 14] = [
+        "aget", "aget-wide", "aget-object", "aget-boolean", "aget-byte", "aget-char", "aget-short",
+        "aput", "aput-wide", "aput-object", "aput-boolean", "aput-byte", "aput-char", "aput-short",
+    ];
+
+    const INSTANCE: [&str; 14] = [
+        "iget", "iget-wide", "iget-object", "iget-boolean", "iget-byte", "iget-char", "iget-short",
+        "iput", "iput-wide", "iput-object", "iput-boolean", "iput-byte", "iput-char", "iput-short",
+    ];
+
+    const STATIC: [&str; 14] = [
+        "sget", "sget-wide", "sget-object", "sget-boolean", "sget-byte", "sget-char", "sget-short",
+        "sput", "sput-wide", "sput-object", "sput-boolean", "sput-byte", "sput-char", "sput-short",
+    ];
+
+    const INVOKE: [&str; 5] = [
+        "invoke-virtual", "invoke-super", "invoke-direct", "invoke-static", "invoke-interface",
+    ];
+
+    const INVOKE_RANGE: [&str; 5] = [
+        "invoke-virtual/range", "invoke-super/range", "invoke-direct/range",
+        "invoke-static/range", "invoke-interface/range",
+    ];
+
+    let idx = opcode as usize;
+    Some(match opcode {
+        0x00 => ("nop", F10x),
+        0x01 => ("move", F12x),
+        0x02 => ("move/from16", F22x),
+        0x03 => ("move/16", F32x),
+        0x04 => ("move-wide", F12x),
+        0x05 => ("move-wide/from16", F22x),
+        0x06 => ("move-wide/16", F32x),
+        0x07 => ("move-object", F12x),
+        0x08 => ("move-object/from16", F22x),
+        0x09 => ("move-object/16", F32x),
+        0x0A => ("move-result", F11x),
+        0x0B => ("move-result-wide", F11x),
+        0x0C => ("move-result-object", F11x),
+        0x0D => ("move-exception", F11x),
+        0x0E => ("return-void", F10x),
+        0x0F => ("return", F11x),
+        0x10 => ("return-wide", F11x),
+        0x11 => ("return-object", F11x),
+        0x12 => ("const/4", F11n),
+        0x13 => ("const/16", F21s),
+        0x14 => ("const", F31i),
+        0x15 => ("const/high16", F21h),
+        0x16 => ("const-wide/16", F21s),
+        0x17 => ("const-wide/32", F31i),
+        0x18 => ("const-wide", F51l),
+        0x19 => ("const-wide/high16", F21hw),
+        0x1A => ("const-string", F21c(Pool::String)),
+        0x1B => ("const-string/jumbo", F31c(Pool::String)),
+        0x1C => ("const-class", F21c(Pool::Type)),
+        0x1D => ("monitor-enter", F11x),
+        0x1E => ("monitor-exit", F11x),
+        0x1F => ("check-cast", F21c(Pool::Type)),
+        0x20 => ("instance-of", F22c(Pool::Type)),
+        0x21 => ("array-length", F12x),
+        0x22 => ("new-instance", F21c(Pool::Type)),
+        0x23 => ("new-array", F22c(Pool::Type)),
+        0x24 => ("filled-new-array", F35c(Pool::Type)),
+        0x25 => ("filled-new-array/range", F3rc(Pool::Type)),
+        0x26 => ("fill-array-data", F31t),
+        0x27 => ("throw", F11x),
+        0x28 => ("goto", F10t),
+        0x29 => ("goto/16", F20t),
+        0x2A => ("goto/32", F30t),
+        0x2B => ("packed-switch", F31t),
+        0x2C => ("sparse-switch", F31t),
+        0x2D..=0x31 => (CMP[idx - 0x2D], F23x),
+        0x32..=0x37 => (IF_TEST[idx - 0x32], F22t),
+        0x38..=0x3D => (IF_TESTZ[idx - 0x38], F21t),
+        0x44..=0x51 => (ARRAY[idx - 0x44], F23x),
+        0x52..=0x5F => (INSTANCE[idx - 0x52], F22c(Pool::Field)),
+        0x60..=0x6D => (STATIC[idx - 0x60], F21c(Pool::Field)),
+        0x6E..=0x72 => (INVOKE[idx - 0x6E], F35c(Pool::Method)),
+        0x74..=0x78 => (INVOKE_RANGE[idx - 0x74], F3rc(Pool::Method)),
+        0x7B..=0x8F => (UNARY[idx - 0x7B], F12x),
+        0x90..=0xAF => (BINARY[idx - 0x90], F23x),
+        0xB0..=0xCF => (BINARY_2ADDR[idx - 0xB0], F12x),
+        0xD0..=0xD7 => (LIT16[idx - 0xD0], F22s),
+        0xD8..=0xE2 => (LIT8[idx - 0xD8], F22b),
+        0xFA => ("invoke-polymorphic", F45cc),
+        0xFB => ("invoke-polymorphic/range", F4rcc),
+        0xFC => ("invoke-custom", F35c(Pool::CallSite)),
+        0xFD => ("invoke-custom/range", F3rc(Pool::CallSite)),
+        0xFE => ("const-method-handle", F21c(Pool::MethodHandle)),
+        0xFF => ("const-method-type", F21c(Pool::Proto)),
+        _ => return None,
+    })
+}
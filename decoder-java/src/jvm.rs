@@ -0,0 +1,675 @@
+//! Bytecode of the Java virtual machine, as stored in the `Code` attribute of a class file.
+//!
+//! The padding of `tableswitch` and `lookupswitch` depends on where the instruction is in its
+//! method, so a [`Reader`](decoder::Reader) has to start at the beginning of a method's code.
+
+use config::CONFIG;
+use debugvault::Index;
+use decoder::ir::{Cond, Condition, Expr, Stmt};
+use decoder::{Error, ErrorKind};
+use tokenizing::{TokenKind, TokenStream};
+
+/// Prefix that widens the local index of the next instruction to 16 bits.
+const WIDE: u8 = 0xC4;
+
+/// Longest instruction that isn't a switch, which is a `wide iinc`.
+const MAX_WIDTH: usize = 6;
+
+/// How an opcode's operands are encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    None,
+    /// 8-bit signed integer.
+    Byte,
+    /// 16-bit signed integer.
+    Short,
+    /// 8-bit index of a local variable.
+    Local,
+    /// Index of a local variable followed by an 8-bit signed increment.
+    Increment,
+    /// 8-bit index into the constant pool.
+    ShortConstant,
+    /// 16-bit index into the constant pool.
+    Constant,
+    /// Index of an interface method followed by the number of arguments and a zero.
+    Interface,
+    /// Index of a call site followed by two zeros.
+    Dynamic,
+    /// Index of an array class followed by its number of dimensions.
+    MultiArray,
+    /// Type of a primitive array.
+    ArrayType,
+    /// 16-bit offset from the instruction.
+    Branch,
+    /// 32-bit offset from the instruction.
+    WideBranch,
+    TableSwitch,
+    LookupSwitch,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    Nothing,
+    Int(i32),
+    /// Index of a local variable.
+    Local(u16),
+    /// Local variable and how much it's incremented by.
+    Increment(u16, i16),
+    /// Index into the constant pool of the class.
+    Constant(u16),
+    /// Index of an array class in the constant pool and the number of dimensions created.
+    MultiArray(u16, u8),
+    /// Element type of a primitive array.
+    ArrayType(&'static str),
+    /// Offset of the destination from the instruction, see [`Instruction::targets`].
+    Branch(i32),
+    /// Offsets of the destinations for each value starting at `low`, and of the default.
+    TableSwitch {
+        low: i32,
+        offsets: Vec<i32>,
+        default: i32,
+    },
+    /// Offsets of the destinations for each matched value, and of the default.
+    LookupSwitch {
+        pairs: Vec<(i32, i32)>,
+        default: i32,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct Instruction {
+    mnemonic: &'static str,
+    operand: Operand,
+    /// Whether the instruction is prefixed by `wide`.
+    wide: bool,
+    width: usize,
+    /// Address of the instruction, once it's known.
+    addr: Option<usize>,
+}
+
+impl Instruction {
+    pub fn mnemonic(&self) -> &'static str {
+        self.mnemonic
+    }
+
+    pub fn operand(&self) -> &Operand {
+        &self.operand
+    }
+
+    /// Address of the instruction, once it's known.
+    pub fn addr(&self) -> Option<usize> {
+        self.addr
+    }
+
+    /// Addresses the instruction branches to once its own address is known, with the default of
+    /// a switch last.
+    pub fn targets(&self) -> Vec<usize> {
+        let addr = match self.addr {
+            Some(addr) => addr as i64,
+            None => return Vec::new(),
+        };
+
+        let target = |offset: i32| addr.wrapping_add(offset as i64) as usize;
+        match self.operand {
+            Operand::Branch(offset) => vec![target(offset)],
+            Operand::TableSwitch {
+                ref offsets,
+                default,
+                ..
+            } => offsets.iter().chain([&default]).map(|offset| target(*offset)).collect(),
+            Operand::LookupSwitch { ref pairs, default } => pairs
+                .iter()
+                .map(|(_, offset)| *offset)
+                .chain([default])
+                .map(target)
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Write the instruction, naming the constant pool entries it refers to.
+    pub fn tokenize_with(&self, stream: &mut TokenStream, names: &dyn Fn(u16) -> Option<String>) {
+        if self.wide {
+            stream.push("wide ", CONFIG.colors.asm.opcode);
+        }
+
+        stream.push(self.mnemonic, CONFIG.colors.asm.opcode);
+
+        if self.operand == Operand::Nothing {
+            return;
+        }
+
+        stream.push(" ", CONFIG.colors.text);
+        match self.operand {
+            Operand::Nothing => {}
+            Operand::Int(imm) => tokenize_int(stream, imm as i64),
+            Operand::Local(idx) => tokenize_local(stream, idx),
+            Operand::Increment(idx, imm) => {
+                tokenize_local(stream, idx);
+                stream.push(", ", CONFIG.colors.delimiter);
+                tokenize_int(stream, imm as i64);
+            }
+            Operand::Constant(idx) => tokenize_constant(stream, idx, names),
+            Operand::MultiArray(idx, dimensions) => {
+                tokenize_constant(stream, idx, names);
+                stream.push(", ", CONFIG.colors.delimiter);
+                tokenize_int(stream, dimensions as i64);
+            }
+            Operand::ArrayType(ty) => stream.push(ty, CONFIG.colors.asm.primitive),
+            Operand::Branch(..) => self.tokenize_targets(stream, &[]),
+            Operand::TableSwitch { low, ref offsets, .. } => {
+                let values: Vec<i64> = (0..offsets.len()).map(|n| low as i64 + n as i64).collect();
+                self.tokenize_targets(stream, &values);
+            }
+            Operand::LookupSwitch { ref pairs, .. } => {
+                let values: Vec<i64> = pairs.iter().map(|(value, _)| *value as i64).collect();
+                self.tokenize_targets(stream, &values);
+            }
+        }
+    }
+
+    /// Write the destinations of a branch, or those of a switch along with the value of each.
+    fn tokenize_targets(&self, stream: &mut TokenStream, values: &[i64]) {
+        let offsets: Vec<i32> = match self.operand {
+            Operand::Branch(offset) => vec![offset],
+            Operand::TableSwitch {
+                ref offsets,
+                default,
+                ..
+            } => offsets.iter().copied().chain([default]).collect(),
+            Operand::LookupSwitch { ref pairs, default } => {
+                pairs.iter().map(|(_, offset)| *offset).chain([default]).collect()
+            }
+            _ => return,
+        };
+
+        let is_switch = !matches!(self.operand, Operand::Branch(..));
+        if is_switch {
+            stream.push("(", CONFIG.colors.delimiter);
+        }
+
+        let targets = self.targets();
+        for (idx, offset) in offsets.iter().enumerate() {
+            if idx != 0 {
+                stream.push(", ", CONFIG.colors.delimiter);
+            }
+
+            if is_switch {
+                match values.get(idx) {
+                    Some(value) => tokenize_int(stream, *value),
+                    None => stream.push("default", CONFIG.colors.asm.opcode),
+                }
+                stream.push(": ", CONFIG.colors.delimiter);
+            }
+
+            match targets.get(idx) {
+                Some(&target) => {
+                    let start = stream.inner.len();
+                    stream.push_owned(format!("{target:#x}"), CONFIG.colors.asm.pointer);
+                    stream.set_target(start, target);
+                }
+                // only the offset is known until the instruction is placed at an address
+                None => stream.push_owned_kind(
+                    format!("{offset:+}"),
+                    CONFIG.colors.asm.immediate,
+                    TokenKind::Immediate,
+                ),
+            }
+        }
+
+        if is_switch {
+            stream.push(")", CONFIG.colors.delimiter);
+        }
+    }
+
+    fn lower_into(&self, stmts: &mut Vec<Stmt>) {
+        let targets = self.targets();
+        let target = || targets.first().map_or(Expr::Unknown, |addr| Expr::Const(*addr as u64));
+
+        // the stack isn't modelled, so only what values are compared against is known
+        let if_zero = |condition| Stmt::Branch {
+            cond: Cond::Compare(condition, Expr::Unknown, Expr::Const(0)),
+            target: target(),
+        };
+        let if_cmp = |condition| Stmt::Branch {
+            cond: Cond::Compare(condition, Expr::Unknown, Expr::Unknown),
+            target: target(),
+        };
+
+        let stmt = match self.mnemonic {
+            "goto" | "goto_w" => Stmt::Jump(target()),
+            "jsr" | "jsr_w" => Stmt::Call(target()),
+            "tableswitch" | "lookupswitch" => {
+                for addr in targets.iter() {
+                    stmts.push(Stmt::Branch {
+                        cond: Cond::Compare(Condition::Eq, Expr::Unknown, Expr::Unknown),
+                        target: Expr::Const(*addr as u64),
+                    });
+                }
+                return;
+            }
+            "ifeq" | "ifnull" => if_zero(Condition::Eq),
+            "ifne" | "ifnonnull" => if_zero(Condition::Ne),
+            "iflt" => if_zero(Condition::Lt),
+            "ifge" => if_zero(Condition::Ge),
+            "ifgt" => if_zero(Condition::Gt),
+            "ifle" => if_zero(Condition::Le),
+            "if_icmpeq" | "if_acmpeq" => if_cmp(Condition::Eq),
+            "if_icmpne" | "if_acmpne" => if_cmp(Condition::Ne),
+            "if_icmplt" => if_cmp(Condition::Lt),
+            "if_icmpge" => if_cmp(Condition::Ge),
+            "if_icmpgt" => if_cmp(Condition::Gt),
+            "if_icmple" => if_cmp(Condition::Le),
+            "invokevirtual" | "invokespecial" | "invokestatic" | "invokeinterface"
+            | "invokedynamic" => Stmt::Call(Expr::Unknown),
+            "ret" => Stmt::Jump(Expr::Unknown),
+            "ireturn" | "lreturn" | "freturn" | "dreturn" | "areturn" | "return" => Stmt::Return,
+            "athrow" => Stmt::Halt,
+            "nop" => return,
+            _ => Stmt::Unknown,
+        };
+
+        stmts.push(stmt);
+    }
+}
+
+fn tokenize_int(stream: &mut TokenStream, imm: i64) {
+    stream.push_owned_kind(
+        decoder::encode_hex(imm),
+        CONFIG.colors.asm.immediate,
+        TokenKind::Immediate,
+    );
+}
+
+fn tokenize_local(stream: &mut TokenStream, idx: u16) {
+    stream.push_owned_kind(format!("l{idx}"), CONFIG.colors.asm.register, TokenKind::Register);
+}
+
+fn tokenize_constant(stream: &mut TokenStream, idx: u16, names: &dyn Fn(u16) -> Option<String>) {
+    match names(idx) {
+        Some(name) => {
+            let start = stream.inner.len();
+            stream.push_owned(name, CONFIG.colors.asm.label);
+            stream.inner[start].kind = TokenKind::Symbol;
+        }
+        None => stream.push_owned_kind(
+            format!("#{idx}"),
+            CONFIG.colors.asm.immediate,
+            TokenKind::Immediate,
+        ),
+    }
+}
+
+impl decoder::ToTokens for Instruction {
+    fn tokenize(&self, stream: &mut TokenStream, _: &Index) {
+        self.tokenize_with(stream, &|_| None);
+    }
+}
+
+impl decoder::Decoded for Instruction {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn update_rel_addrs(&mut self, addr: usize, _: Option<&Instruction>) {
+        self.addr = Some(addr);
+    }
+
+    fn lower(&self, stmts: &mut Vec<Stmt>) {
+        self.lower_into(stmts);
+    }
+}
+
+#[derive(Default)]
+pub struct Decoder;
+
+impl decoder::Decodable for Decoder {
+    type Instruction = Instruction;
+
+    fn decode(&self, reader: &mut decoder::Reader) -> Result<Self::Instruction, Error> {
+        reader.mark();
+        decode(reader).map_err(|err| Error::new(err, reader.offset().max(1)))
+    }
+
+    fn max_width(&self) -> usize {
+        MAX_WIDTH
+    }
+}
+
+fn read<const N: usize>(reader: &mut decoder::Reader) -> Result<[u8; N], ErrorKind> {
+    let mut bytes = [0u8; N];
+    reader.next_n(&mut bytes).ok_or(ErrorKind::ExhaustedInput)?;
+    Ok(bytes)
+}
+
+fn read_u8(reader: &mut decoder::Reader) -> Result<u8, ErrorKind> {
+    reader.next().ok_or(ErrorKind::ExhaustedInput)
+}
+
+fn read_u16(reader: &mut decoder::Reader) -> Result<u16, ErrorKind> {
+    read(reader).map(u16::from_be_bytes)
+}
+
+fn read_i32(reader: &mut decoder::Reader) -> Result<i32, ErrorKind> {
+    read(reader).map(i32::from_be_bytes)
+}
+
+fn decode(reader: &mut decoder::Reader) -> Result<Instruction, ErrorKind> {
+    let mut opcode = read_u8(reader)?;
+    let wide = opcode == WIDE;
+    if wide {
+        opcode = read_u8(reader)?;
+    }
+
+    let (mnemonic, encoding) = opcode_info(opcode).ok_or(ErrorKind::InvalidOpcode)?;
+    if wide && !matches!(encoding, Encoding::Local | Encoding::Increment) {
+        return Err(ErrorKind::InvalidOperand);
+    }
+
+    let local = |reader: &mut decoder::Reader| match wide {
+        true => read_u16(reader),
+        false => read_u8(reader).map(u16::from),
+    };
+
+    let operand = match encoding {
+        Encoding::None => Operand::Nothing,
+        Encoding::Byte => Operand::Int(read_u8(reader)? as i8 as i32),
+        Encoding::Short => Operand::Int(read_u16(reader)? as i16 as i32),
+        Encoding::Local => Operand::Local(local(reader)?),
+        Encoding::Increment => {
+            let idx = local(reader)?;
+            let imm = match wide {
+                true => read_u16(reader)? as i16,
+                false => read_u8(reader)? as i8 as i16,
+            };
+            Operand::Increment(idx, imm)
+        }
+        Encoding::ShortConstant => Operand::Constant(read_u8(reader)? as u16),
+        Encoding::Constant => Operand::Constant(read_u16(reader)?),
+        Encoding::Interface | Encoding::Dynamic => {
+            let idx = read_u16(reader)?;
+            read::<2>(reader)?;
+            Operand::Constant(idx)
+        }
+        Encoding::MultiArray => {
+            let idx = read_u16(reader)?;
+            Operand::MultiArray(idx, read_u8(reader)?)
+        }
+        Encoding::ArrayType => Operand::ArrayType(match read_u8(reader)? {
+            4 => "boolean",
+            5 => "char",
+            6 => "float",
+            7 => "double",
+            8 => "byte",
+            9 => "short",
+            10 => "int",
+            11 => "long",
+            _ => return Err(ErrorKind::InvalidOperand),
+        }),
+        Encoding::Branch => Operand::Branch(read_u16(reader)? as i16 as i32),
+        Encoding::WideBranch => Operand::Branch(read_i32(reader)?),
+        Encoding::TableSwitch => {
+            skip_padding(reader)?;
+            let default = read_i32(reader)?;
+            let low = read_i32(reader)?;
+            let high = read_i32(reader)?;
+            if high < low {
+                return Err(ErrorKind::InvalidOperand);
+            }
+
+            let mut offsets = Vec::new();
+            for _ in low..=high {
+                offsets.push(read_i32(reader)?);
+            }
+            Operand::TableSwitch {
+                low,
+                offsets,
+                default,
+            }
+        }
+        Encoding::LookupSwitch => {
+            skip_padding(reader)?;
+            let default = read_i32(reader)?;
+            let count = read_i32(reader)?;
+            if count < 0 {
+                return Err(ErrorKind::InvalidOperand);
+            }
+
+            let mut pairs = Vec::new();
+            for _ in 0..count {
+                pairs.push((read_i32(reader)?, read_i32(reader)?));
+            }
+            Operand::LookupSwitch { pairs, default }
+        }
+    };
+
+    Ok(Instruction {
+        mnemonic,
+        operand,
+        wide,
+        width: reader.offset(),
+        addr: None,
+    })
+}
+
+/// Skip the bytes after a switch's opcode that align its operands to four bytes from the start
+/// of the method.
+fn skip_padding(reader: &mut decoder::Reader) -> Result<(), ErrorKind> {
+    while !reader.total_offset().is_multiple_of(4) {
+        read_u8(reader)?;
+    }
+
+    Ok(())
+}
+
+fn opcode_info(opcode: u8) -> Option<(&'static str, Encoding)> {
+    use Encoding::*;
+
+    Some(match opcode {
+        0x00 => ("nop", None),
+        0x01 => ("aconst_null", None),
+        0x02 => ("iconst_m1", None),
+        0x03 => ("iconst_0", None),
+        0x04 => ("iconst_1", None),
+        0x05 => ("iconst_2", None),
+        0x06 => ("iconst_3", None),
+        0x07 => ("iconst_4", None),
+        0x08 => ("iconst_5", None),
+        0x09 => ("lconst_0", None),
+        0x0A => ("lconst_1", None),
+        0x0B => ("fconst_0", None),
+        0x0C => ("fconst_1", None),
+        0x0D => ("fconst_2", None),
+        0x0E => ("dconst_0", None),
+        0x0F => ("dconst_1", None),
+        0x10 => ("bipush", Byte),
+        0x11 => ("sipush", Short),
+        0x12 => ("ldc", ShortConstant),
+        0x13 => ("ldc_w", Constant),
+        0x14 => ("ldc2_w", Constant),
+        0x15 => ("iload", Local),
+        0x16 => ("lload", Local),
+        0x17 => ("fload", Local),
+        0x18 => ("dload", Local),
+        0x19 => ("aload", Local),
+        0x1A => ("iload_0", None),
+        0x1B => ("iload_1", None),
+        0x1C => ("iload_2", None),
+        0x1D => ("iload_3", None),
+        0x1E => ("lload_0", None),
+        0x1F => ("lload_1", None),
+        0x20 => ("lload_2", None),
+        0x21 => ("lload_3", None),
+        0x22 => ("fload_0", None),
+        0x23 => ("fload_1", None),
+        0x24 => ("fload_2", None),
+        0x25 => ("fload_3", None),
+        0x26 => ("dload_0", None),
+        0x27 => ("dload_1", None),
+        0x28 => ("dload_2", None),
+        0x29 => ("dload_3", None),
+        0x2A => ("aload_0", None),
+        0x2B => ("aload_1", None),
+        0x2C => ("aload_2", None),
+        0x2D => ("aload_3", None),
+        0x2E => ("iaload", None),
+        0x2F => ("laload", None),
+        0x30 => ("faload", None),
+        0x31 => ("daload", None),
+        0x32 => ("aaload", None),
+        0x33 => ("baload", None),
+        0x34 => ("caload", None),
+        0x35 => ("saload", None),
+        0x36 => ("istore", Local),
+        0x37 => ("lstore", Local),
+        0x38 => ("fstore", Local),
+        0x39 => ("dstore", Local),
+        0x3A => ("astore", Local),
+        0x3B => ("istore_0", None),
+        0x3C => ("istore_1", None),
+        0x3D => ("istore_2", None),
+        0x3E => ("istore_3", None),
+        0x3F => ("lstore_0", None),
+        0x40 => ("lstore_1", None),
+        0x41 => ("lstore_2", None),
+        0x42 => ("lstore_3", None),
+        0x43 => ("fstore_0", None),
+        0x44 => ("fstore_1", None),
+        0x45 => ("fstore_2", None),
+        0x46 => ("fstore_3", None),
+        0x47 => ("dstore_0", None),
+        0x48 => ("dstore_1", None),
+        0x49 => ("dstore_2", None),
+        0x4A => ("dstore_3", None),
+        0x4B => ("astore_0", None),
+        0x4C => ("astore_1", None),
+        0x4D => ("astore_2", None),
+        0x4E => ("astore_3", None),
+        0x4F => ("iastore", None),
+        0x50 => ("lastore", None),
+        0x51 => ("fastore", None),
+        0x52 => ("dastore", None),
+        0x53 => ("aastore", None),
+        0x54 => ("bastore", None),
+        0x55 => ("castore", None),
+        0x56 => ("sastore", None),
+        0x57 => ("pop", None),
+        0x58 => ("pop2", None),
+        0x59 => ("dup", None),
+        0x5A => ("dup_x1", None),
+        0x5B => ("dup_x2", None),
+        0x5C => ("dup2", None),
+        0x5D => ("dup2_x1", None),
+        0x5E => ("dup2_x2", None),
+        0x5F => ("swap", None),
+        0x60 => ("iadd", None),
+        0x61 => ("ladd", None),
+        0x62 => ("fadd", None),
+        0x63 => ("dadd", None),
+        0x64 => ("isub", None),
+        0x65 => ("lsub", None),
+        0x66 => ("fsub", None),
+        0x67 => ("dsub", None),
+        0x68 => ("imul", None),
+        0x69 => ("lmul", None),
+        0x6A => ("fmul", None),
+        0x6B => ("dmul", None),
+        0x6C => ("idiv", None),
+        0x6D => ("ldiv", None),
+        0x6E => ("fdiv", None),
+        0x6F => ("ddiv", None),
+        0x70 => ("irem", None),
+        0x71 => ("lrem", None),
+        0x72 => ("frem", None),
+        0x73 => ("drem", None),
+        0x74 => ("ineg", None),
+        0x75 => ("lneg", None),
+        0x76 => ("fneg", None),
+        0x77 => ("dneg", None),
+        0x78 => ("ishl", None),
+        0x79 => ("lshl", None),
+        0x7A => ("ishr", None),
+        0x7B => ("lshr", None),
+        0x7C => ("iushr", None),
+        0x7D => ("lushr", None),
+        0x7E => ("iand", None),
+        0x7F => ("land", None),
+        0x80 => ("ior", None),
+        0x81 => ("lor", None),
+        0x82 => ("ixor", None),
+        0x83 => ("lxor", None),
+        0x84 => ("iinc", Increment),
+        0x85 => ("i2l", None),
+        0x86 => ("i2f", None),
+        0x87 => ("i2d", None),
+        0x88 => ("l2i", None),
+        0x89 => ("l2f", None),
+        0x8A => ("l2d", None),
+        0x8B => ("f2i", None),
+        0x8C => ("f2l", None),
+        0x8D => ("f2d", None),
+        0x8E => ("d2i", None),
+        0x8F => ("d2l", None),
+        0x90 => ("d2f", None),
+        0x91 => ("i2b", None),
+        0x92 => ("i2c", None),
+        0x93 => ("i2s", None),
+        0x94 => ("lcmp", None),
+        0x95 => ("fcmpl", None),
+        0x96 => ("fcmpg", None),
+        0x97 => ("dcmpl", None),
+        0x98 => ("dcmpg", None),
+        0x99 => ("ifeq", Branch),
+        0x9A => ("ifne", Branch),
+        0x9B => ("iflt", Branch),
+        0x9C => ("ifge", Branch),
+        0x9D => ("ifgt", Branch),
+        0x9E => ("ifle", Branch),
+        0x9F => ("if_icmpeq", Branch),
+        0xA0 => ("if_icmpne", Branch),
+        0xA1 => ("if_icmplt", Branch),
+        0xA2 => ("if_icmpge", Branch),
+        0xA3 => ("if_icmpgt", Branch),
+        0xA4 => ("if_icmple", Branch),
+        0xA5 => ("if_acmpeq", Branch),
+        0xA6 => ("if_acmpne", Branch),
+        0xA7 => ("goto", Branch),
+        0xA8 => ("jsr", Branch),
+        0xA9 => ("ret", Local),
+        0xAA => ("tableswitch", TableSwitch),
+        0xAB => ("lookupswitch", LookupSwitch),
+        0xAC => ("ireturn", None),
+        0xAD => ("lreturn", None),
+        0xAE => ("freturn", None),
+        0xAF => ("dreturn", None),
+        0xB0 => ("areturn", None),
+        0xB1 => ("return", None),
+        0xB2 => ("getstatic", Constant),
+        0xB3 => ("putstatic", Constant),
+        0xB4 => ("getfield", Constant),
+        0xB5 => ("putfield", Constant),
+        0xB6 => ("invokevirtual", Constant),
+        0xB7 => ("invokespecial", Constant),
+        0xB8 => ("invokestatic", Constant),
+        0xB9 => ("invokeinterface", Interface),
+        0xBA => ("invokedynamic", Dynamic),
+        0xBB => ("new", Constant),
+        0xBC => ("newarray", ArrayType),
+        0xBD => ("anewarray", Constant),
+        0xBE => ("arraylength", None),
+        0xBF => ("athrow", None),
+        0xC0 => ("checkcast", Constant),
+        0xC1 => ("instanceof", Constant),
+        0xC2 => ("monitorenter", None),
+        0xC3 => ("monitorexit", None),
+        0xC5 => ("multianewarray", MultiArray),
+        0xC6 => ("ifnull", Branch),
+        0xC7 => ("ifnonnull", Branch),
+        0xC8 => ("goto_w", WideBranch),
+        0xC9 => ("jsr_w", WideBranch),
+        0xCA => ("breakpoint", None),
+        0xFE => ("impdep1", None),
+        0xFF => ("impdep2", None),
+        _ => return Option::None,
+    })
+}
@@ -0,0 +1,5 @@
+//! Disassemblers of the JVM's bytecode and of Dalvik, the bytecode of Android's dex files.
+
+pub mod dalvik;
+pub mod jvm;
+mod tests;
@@ -0,0 +1,142 @@
+#![cfg(test)]
+
+use decoder::{Decodable, Decoded, ToTokens};
+
+fn test_display<D: Decodable>(decoder: D, bytes: &[u8], str: &str)
+where
+    D::Instruction: ToTokens,
+{
+    let mut reader = decoder::Reader::new(bytes);
+    let mut line = tokenizing::TokenStream::new();
+    let symbols = debugvault::Index::default();
+
+    let decoded = match decoder.decode(&mut reader) {
+        Ok(mut inst) => {
+            inst.update_rel_addrs(0x1000, None);
+            inst.tokenize(&mut line, &symbols);
+            line.to_string()
+        }
+        Err(err) => format!("{err:?}"),
+    };
+
+    assert_eq!(decoded, str);
+}
+
+fn jvm(bytes: &[u8], str: &str) {
+    test_display(crate::jvm::Decoder, bytes, str);
+}
+
+fn dalvik(bytes: &[u8], str: &str) {
+    test_display(crate::dalvik::Decoder, bytes, str);
+}
+
+#[test]
+fn jvm_constants() {
+    jvm(&[0x03], "iconst_0");
+    jvm(&[0x10, 0xff], "bipush -0x1");
+    jvm(&[0x11, 0x12, 0x34], "sipush 0x1234");
+    jvm(&[0x12, 0x07], "ldc #7");
+}
+
+#[test]
+fn jvm_locals() {
+    jvm(&[0x15, 0x04], "iload l4");
+    jvm(&[0x84, 0x01, 0xff], "iinc l1, -0x1");
+    jvm(&[0xc4, 0x84, 0x01, 0x00, 0x00, 0x10], "wide iinc l256, 0x10");
+    jvm(&[0xc4, 0x2a], "Error { kind: InvalidOperand, size: 2 }");
+}
+
+#[test]
+fn jvm_invoke() {
+    jvm(&[0xb6, 0x00, 0x0d], "invokevirtual #13");
+    jvm(&[0xb9, 0x00, 0x02, 0x01, 0x00], "invokeinterface #2");
+    jvm(&[0xc5, 0x00, 0x03, 0x02], "multianewarray #3, 0x2");
+    jvm(&[0xbc, 0x0a], "newarray int");
+}
+
+#[test]
+fn jvm_branch() {
+    jvm(&[0xa7, 0xff, 0xfd], "goto 0xffd");
+    jvm(&[0x99, 0x00, 0x08], "ifeq 0x1008");
+    jvm(&[0xc8, 0x00, 0x01, 0x00, 0x00], "goto_w 0x11000");
+}
+
+#[test]
+fn jvm_switch() {
+    #[rustfmt::skip]
+    let bytes = [
+        0xaa, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x20,
+        0x00, 0x00, 0x00, 0x01,
+        0x00, 0x00, 0x00, 0x02,
+        0x00, 0x00, 0x00, 0x18,
+        0x00, 0x00, 0x00, 0x1c,
+    ];
+    jvm(&bytes, "tableswitch (0x1: 0x1018, 0x2: 0x101c, default: 0x1020)");
+
+    #[rustfmt::skip]
+    let bytes = [
+        0xab, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x20,
+        0x00, 0x00, 0x00, 0x01,
+        0x00, 0x00, 0x00, 0x0a,
+        0x00, 0x00, 0x00, 0x18,
+    ];
+    jvm(&bytes, "lookupswitch (0xa: 0x1018, default: 0x1020)");
+}
+
+#[test]
+fn jvm_invalid() {
+    jvm(&[0xcb], "Error { kind: InvalidOpcode, size: 1 }");
+}
+
+#[test]
+fn dalvik_moves() {
+    dalvik(&[0x0e, 0x00], "return-void");
+    dalvik(&[0x01, 0x21], "move v1, v2");
+    dalvik(&[0x12, 0xf0], "const/4 v0, -0x1");
+    dalvik(&[0x15, 0x01, 0x80, 0x3f], "const/high16 v1, 0x3f800000");
+    dalvik(&[0x03, 0x00, 0x00, 0x01, 0x02, 0x00], "move/16 v256, v2");
+}
+
+#[test]
+fn dalvik_arithmetic() {
+    dalvik(&[0x90, 0x00, 0x01, 0x02], "add-int v0, v1, v2");
+    dalvik(&[0xd8, 0x00, 0x01, 0xff], "add-int/lit8 v0, v1, -0x1");
+    dalvik(&[0xb0, 0x10], "add-int/2addr v0, v1");
+}
+
+#[test]
+fn dalvik_pools() {
+    dalvik(&[0x1a, 0x00, 0x03, 0x00], "const-string v0, string@0003");
+    dalvik(&[0x52, 0x10, 0x05, 0x00], "iget v0, v1, field@0005");
+    dalvik(
+        &[0x6e, 0x20, 0x04, 0x00, 0x10, 0x00],
+        "invoke-virtual {v0, v1}, method@0004",
+    );
+    dalvik(
+        &[0x74, 0x03, 0x04, 0x00, 0x05, 0x00],
+        "invoke-virtual/range {v5 .. v7}, method@0004",
+    );
+}
+
+#[test]
+fn dalvik_branch() {
+    dalvik(&[0x28, 0xfe], "goto 0xffc");
+    dalvik(&[0x38, 0x00, 0x04, 0x00], "if-eqz v0, 0x1008");
+    dalvik(&[0x32, 0x10, 0x04, 0x00], "if-eq v0, v1, 0x1008");
+}
+
+#[test]
+fn dalvik_payload() {
+    let bytes = [0x00, 0x01, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 1, 0, 0, 0, 2, 0, 0, 0];
+    let mut reader = decoder::Reader::new(&bytes);
+    let inst = crate::dalvik::Decoder.decode(&mut reader).unwrap();
+    assert_eq!(inst.width(), bytes.len());
+    assert_eq!(inst.mnemonic(), "packed-switch-payload");
+}
+
+#[test]
+fn dalvik_invalid() {
+    dalvik(&[0x3e, 0x00], "Error { kind: InvalidOpcode, size: 2 }");
+}
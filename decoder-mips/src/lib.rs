@@ -1,11 +1,12 @@
 //! MIPS V disassembler.
 
+mod lower;
 mod tests;
 
 use decoder::{Error, ErrorKind};
 use debugvault::Index;
 use std::borrow::Cow;
-use tokenizing::{colors, TokenStream};
+use tokenizing::{TokenStream, TokenKind};
 use config::CONFIG;
 
 macro_rules! operands {
@@ -78,6 +79,10 @@ impl decoder::Decoded for Instruction {
     }
 
     fn update_rel_addrs(&mut self, _: usize, _: Option<&Instruction>) {}
+
+    fn lower(&self, stmts: &mut Vec<decoder::ir::Stmt>) {
+        self.lower_into(stmts);
+    }
 }
 
 #[derive(Default)]
@@ -250,15 +255,23 @@ impl decoder::ToTokens for Instruction {
 
         // there are operands
         if self.operand_count > 0 {
-            stream.push(" ", colors::WHITE);
+            stream.push(" ", CONFIG.colors.text);
 
             // iterate through operands
             for idx in 0..self.operand_count {
                 let operand = self.operands[idx].clone();
 
                 match operand {
-                    Cow::Owned(s) => stream.push_owned(s, CONFIG.colors.asm.immediate),
-                    Cow::Borrowed(s) => stream.push(s, CONFIG.colors.asm.register),
+                    Cow::Owned(s) => {
+                        stream.push_owned_kind(
+                            s,
+                            CONFIG.colors.asm.immediate,
+                            TokenKind::Immediate,
+                        );
+                    }
+                    Cow::Borrowed(s) => {
+                        stream.push_kind(s, CONFIG.colors.asm.register, TokenKind::Register);
+                    }
                 };
 
                 // separator
@@ -0,0 +1,196 @@
+//! Lowering of instructions into the architecture independent [`ir`](decoder::ir).
+
+use crate::{Instruction, REGISTERS};
+use decoder::ir::{BinaryOp, Cond, Condition, Expr, Reg, Stmt, UnaryOp};
+
+const HI: Reg = Reg::full("hi", 4);
+const LO: Reg = Reg::full("lo", 4);
+const RA: Reg = Reg::full("ra", 4);
+
+/// Operand of an instruction, recovered from its textual form.
+#[derive(Clone, Copy)]
+enum Operand {
+    Register(Reg),
+    Immediate(u16),
+    Nothing,
+}
+
+fn operand(text: &str) -> Operand {
+    if let Some(name) = REGISTERS.iter().find(|name| **name == text) {
+        return Operand::Register(Reg::full(name, 4));
+    }
+
+    let imm = text.strip_prefix("0x").and_then(|hex| u16::from_str_radix(hex, 16).ok());
+    imm.map_or(Operand::Nothing, Operand::Immediate)
+}
+
+fn binary_op(mnemomic: &str) -> Option<BinaryOp> {
+    Some(match mnemomic {
+        "add" | "addu" | "addi" | "addiu" => BinaryOp::Add,
+        "sub" | "subu" => BinaryOp::Sub,
+        "and" | "andi" => BinaryOp::And,
+        "or" | "ori" => BinaryOp::Or,
+        "xor" | "xori" => BinaryOp::Xor,
+        "sll" | "sllv" => BinaryOp::Shl,
+        "srl" | "srlv" => BinaryOp::Shr,
+        "sra" | "srav" => BinaryOp::Sar,
+        _ => return None,
+    })
+}
+
+impl Instruction {
+    fn operand(&self, idx: usize) -> Operand {
+        match idx < self.operand_count {
+            true => operand(&self.operands[idx]),
+            false => Operand::Nothing,
+        }
+    }
+
+    /// Value of an operand, with immediates of arithmetic instructions being sign extended.
+    fn read(&self, op: Operand) -> Expr {
+        let logical = matches!(self.mnemomic, "andi" | "ori" | "xori");
+
+        match op {
+            Operand::Register(reg) if reg.name == "zero" => Expr::Const(0),
+            Operand::Register(reg) => Expr::Reg(reg),
+            Operand::Immediate(imm) if logical => Expr::Const(imm as u64),
+            Operand::Immediate(imm) => Expr::Const(imm as i16 as i32 as u32 as u64),
+            Operand::Nothing => Expr::Unknown,
+        }
+    }
+
+    fn write(&self, op: Operand, src: Expr, stmts: &mut Vec<Stmt>) {
+        match op {
+            // writes to the zero register are discarded
+            Operand::Register(reg) if reg.name == "zero" => {}
+            Operand::Register(dst) => stmts.push(Stmt::Assign { dst, src }),
+            _ => stmts.push(Stmt::Unknown),
+        }
+    }
+
+    pub(crate) fn lower_into(&self, stmts: &mut Vec<Stmt>) {
+        let (a, b, c) = (self.operand(0), self.operand(1), self.operand(2));
+
+        if let Some(binop) = binary_op(self.mnemomic) {
+            let value = Expr::binary(binop, self.read(b), self.read(c));
+            return self.write(a, value, stmts);
+        }
+
+        // loads and stores are listed as `rt, rs, offset`
+        let addr = || Expr::sum(self.read(b), self.read(c));
+
+        match self.mnemomic {
+            "nop" => {}
+            "break" => stmts.push(Stmt::Halt),
+            "nor" => {
+                let value = Expr::binary(BinaryOp::Or, self.read(b), self.read(c));
+                self.write(a, Expr::unary(UnaryOp::Not, value), stmts);
+            }
+            "slt" | "slti" | "sltu" | "sltiu" => {
+                let condition = match self.mnemomic {
+                    "slt" | "slti" => Condition::Lt,
+                    _ => Condition::Below,
+                };
+
+                let cond = Cond::Compare(condition, self.read(b), self.read(c));
+                self.write(a, Expr::cond(cond), stmts);
+            }
+            "lui" => {
+                let value = Expr::binary(BinaryOp::Shl, self.read(b), Expr::Const(16));
+                self.write(a, value, stmts);
+            }
+            "lb" => self.write(a, Expr::sign_extend(Expr::load(addr(), 1), 1), stmts),
+            "lh" => self.write(a, Expr::sign_extend(Expr::load(addr(), 2), 2), stmts),
+            "lbu" => self.write(a, Expr::load(addr(), 1), stmts),
+            "lhu" => self.write(a, Expr::load(addr(), 2), stmts),
+            "lw" | "lwu" => self.write(a, Expr::load(addr(), 4), stmts),
+            "sb" | "sh" | "sw" => {
+                let size = match self.mnemomic {
+                    "sb" => 1,
+                    "sh" => 2,
+                    _ => 4,
+                };
+
+                stmts.push(Stmt::Store {
+                    addr: addr(),
+                    size,
+                    src: self.read(a),
+                });
+            }
+            "mfhi" => self.write(a, Expr::Reg(HI), stmts),
+            "mflo" => self.write(a, Expr::Reg(LO), stmts),
+            "mthi" => stmts.push(Stmt::Assign {
+                dst: HI,
+                src: self.read(a),
+            }),
+            "mtlo" => stmts.push(Stmt::Assign {
+                dst: LO,
+                src: self.read(a),
+            }),
+            "mult" | "multu" => {
+                stmts.push(Stmt::Assign {
+                    dst: LO,
+                    src: Expr::binary(BinaryOp::Mul, self.read(a), self.read(b)),
+                });
+                stmts.push(Stmt::Assign {
+                    dst: HI,
+                    src: Expr::Unknown,
+                });
+            }
+            "div" | "divu" => {
+                let (div, rem) = match self.mnemomic {
+                    "div" => (BinaryOp::SignedDiv, BinaryOp::SignedRem),
+                    _ => (BinaryOp::Div, BinaryOp::Rem),
+                };
+
+                stmts.push(Stmt::Assign {
+                    dst: LO,
+                    src: Expr::binary(div, self.read(a), self.read(b)),
+                });
+                stmts.push(Stmt::Assign {
+                    dst: HI,
+                    src: Expr::binary(rem, self.read(a), self.read(b)),
+                });
+            }
+            // branch offsets aren't resolved to an address
+            "beq" | "bne" => {
+                let condition = match self.mnemomic {
+                    "beq" => Condition::Eq,
+                    _ => Condition::Ne,
+                };
+
+                stmts.push(Stmt::Branch {
+                    cond: Cond::Compare(condition, self.read(a), self.read(b)),
+                    target: Expr::Unknown,
+                });
+            }
+            "bgez" | "blez" | "bgtz" => {
+                let condition = match self.mnemomic {
+                    "bgez" => Condition::Ge,
+                    "blez" => Condition::Le,
+                    _ => Condition::Gt,
+                };
+
+                stmts.push(Stmt::Branch {
+                    cond: Cond::Compare(condition, self.read(a), Expr::Const(0)),
+                    target: Expr::Unknown,
+                });
+            }
+            "j" => stmts.push(Stmt::Jump(Expr::Unknown)),
+            "jr" => match a {
+                Operand::Register(RA) => stmts.push(Stmt::Return),
+                Operand::Register(reg) => stmts.push(Stmt::Jump(Expr::Reg(reg))),
+                // `jal` is listed as `jr` with an immediate target
+                _ => stmts.push(Stmt::Call(Expr::Unknown)),
+            },
+            _ => {
+                stmts.push(Stmt::Unknown);
+
+                // assume the first register operand is the destination
+                if let (Operand::Register(..), 2..) = (a, self.operand_count) {
+                    self.write(a, Expr::Unknown, stmts);
+                }
+            }
+        }
+    }
+}
@@ -1,11 +1,12 @@
 //! Riscv64gc/Riscv32gc disassembler.
 
+mod lower;
 mod tests;
 
 use decoder::{Error, ErrorKind, ToTokens};
 use debugvault::Index;
 use once_cell::sync::Lazy;
-use tokenizing::{TokenStream, colors};
+use tokenizing::{TokenStream, TokenKind};
 use config::CONFIG;
 
 macro_rules! operands {
@@ -733,15 +734,29 @@ pub enum Operand {
 impl ToTokens for Operand {
     fn tokenize(&self, stream: &mut TokenStream, symbols: &Index) {
         match self {
-            Self::Register(reg) => stream.push(reg.as_str(), CONFIG.colors.asm.register),
+            Self::Register(reg) => {
+                stream.push_kind(
+                    reg.as_str(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
+            }
             Self::Immediate(imm) => {
                 match symbols.get_sym_by_addr(*imm as usize) {
                     Some(symbol) => {
+                        let start = stream.inner.len();
                         for token in symbol.name() {
                             stream.push_token(token.clone());
                         }
+                        stream.set_target(start, *imm as usize);
+                    }
+                    None => {
+                        stream.push_owned_kind(
+                            imm.to_string(),
+                            CONFIG.colors.asm.immediate,
+                            TokenKind::Immediate,
+                        );
                     }
-                    None => stream.push_owned(imm.to_string(), CONFIG.colors.asm.immediate),
                 }
             }
             Self::Nothing => unreachable!("empty operand encountered"),
@@ -775,6 +790,10 @@ impl decoder::Decoded for Instruction {
             }
         }
     }
+
+    fn lower(&self, stmts: &mut Vec<decoder::ir::Stmt>) {
+        self.lower_into(stmts);
+    }
 }
 
 pub struct Decoder {
@@ -976,7 +995,7 @@ impl ToTokens for Instruction {
 
         // there are operands
         if self.operand_count > 0 {
-            stream.push(" ", colors::WHITE);
+            stream.push(" ", CONFIG.colors.text);
 
             // iterate through operands
             for idx in 0..self.operand_count {
@@ -1308,7 +1327,7 @@ static MAPPING: Lazy<[fn(&mut Instruction); 284]> = Lazy::new(|| unsafe {
 
     MAPPING[Opcode::BLT as usize] = |inst| {
         if inst.operands[0] == Operand::Register(Register::Zero) {
-            inst.opcode = Opcode::BLTZ;
+            inst.opcode = Opcode::BGTZ;
             inst.operands.swap(0, 1);
             inst.operands.swap(1, 2);
             inst.operand_count = 2;
@@ -1316,7 +1335,7 @@ static MAPPING: Lazy<[fn(&mut Instruction); 284]> = Lazy::new(|| unsafe {
         }
 
         if inst.operands[1] == Operand::Register(Register::Zero) {
-            inst.opcode = Opcode::BGTZ;
+            inst.opcode = Opcode::BLTZ;
             inst.operands.swap(0, 1);
             inst.operands.swap(1, 2);
             inst.operand_count = 2;
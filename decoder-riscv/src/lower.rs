@@ -0,0 +1,267 @@
+//! Lowering of instructions into the architecture independent [`ir`](decoder::ir).
+
+use crate::{Instruction, Opcode, Operand, Register};
+use decoder::ir::{BinaryOp, Cond, Condition, Expr, Reg, Stmt, UnaryOp};
+
+/// Instructions don't know whether they were decoded as rv32 or rv64, so registers are always
+/// treated as 64 bits wide.
+const XLEN: u8 = 8;
+
+const SP: Reg = Reg::full("sp", XLEN);
+
+fn reg(reg: Register) -> Reg {
+    Reg::full(reg.as_str(), XLEN)
+}
+
+/// Operation performed by an arithmetic instruction and whether it operates on words.
+fn binary_op(opcode: Opcode) -> Option<(BinaryOp, bool)> {
+    use Opcode::*;
+
+    Some(match opcode {
+        ADD | ADDI | C_ADD | C_ADDI => (BinaryOp::Add, false),
+        ADDW | ADDIW | C_ADDW | C_ADDIW => (BinaryOp::Add, true),
+        SUB | C_SUB => (BinaryOp::Sub, false),
+        SUBW | C_SUBW => (BinaryOp::Sub, true),
+        XOR | XORI | C_XOR => (BinaryOp::Xor, false),
+        OR | ORI | C_OR => (BinaryOp::Or, false),
+        AND | ANDI | C_AND | C_ANDI => (BinaryOp::And, false),
+        SLL | SLLI | C_SLLI | C_SLLI64 => (BinaryOp::Shl, false),
+        SLLW | SLLIW => (BinaryOp::Shl, true),
+        SRL | SRLI | C_SRLI | C_SRLI64 => (BinaryOp::Shr, false),
+        SRLW | SRLIW => (BinaryOp::Shr, true),
+        SRA | SRAI | C_SRAI | C_SRAI64 => (BinaryOp::Sar, false),
+        SRAW | SRAIW => (BinaryOp::Sar, true),
+        MUL => (BinaryOp::Mul, false),
+        MULW => (BinaryOp::Mul, true),
+        DIV => (BinaryOp::SignedDiv, false),
+        DIVW => (BinaryOp::SignedDiv, true),
+        DIVU => (BinaryOp::Div, false),
+        DIVUW => (BinaryOp::Div, true),
+        REM => (BinaryOp::SignedRem, false),
+        REMW => (BinaryOp::SignedRem, true),
+        REMU => (BinaryOp::Rem, false),
+        REMUW => (BinaryOp::Rem, true),
+        _ => return None,
+    })
+}
+
+/// Number of bytes loaded and whether the value is sign extended.
+fn load_size(opcode: Opcode) -> Option<(u8, bool)> {
+    use Opcode::*;
+
+    Some(match opcode {
+        LB => (1, true),
+        LBU => (1, false),
+        LH => (2, true),
+        LHU => (2, false),
+        LW | C_LW | C_LWSP => (4, true),
+        LWU => (4, false),
+        LD | C_LD | C_LDSP => (8, false),
+        _ => return None,
+    })
+}
+
+/// Number of bytes stored.
+fn store_size(opcode: Opcode) -> Option<u8> {
+    use Opcode::*;
+
+    Some(match opcode {
+        SB => 1,
+        SH => 2,
+        SW | C_SW | C_SWSP => 4,
+        SD | C_SD | C_SDSP => 8,
+        _ => return None,
+    })
+}
+
+fn branch_condition(opcode: Opcode) -> Option<Condition> {
+    use Opcode::*;
+
+    Some(match opcode {
+        BEQ | BEQZ | C_BEQZ => Condition::Eq,
+        BNE | BNEZ | C_BNEZ => Condition::Ne,
+        BLT => Condition::Lt,
+        BGE | BGEZ => Condition::Ge,
+        BLTU => Condition::Below,
+        BGEU => Condition::AboveEq,
+        BLEZ => Condition::Le,
+        BLTZ => Condition::Lt,
+        BGTZ => Condition::Gt,
+        _ => return None,
+    })
+}
+
+impl Instruction {
+    fn operands(&self) -> &[Operand] {
+        &self.operands[..self.operand_count]
+    }
+
+    fn read(&self, op: Operand) -> Expr {
+        match op {
+            Operand::Register(Register::Zero) => Expr::Const(0),
+            Operand::Register(reg) => Expr::Reg(self::reg(reg)),
+            Operand::Immediate(imm) => Expr::Const(imm as i64 as u64),
+            Operand::Nothing => Expr::Unknown,
+        }
+    }
+
+    fn write(&self, op: Operand, src: Expr, stmts: &mut Vec<Stmt>) {
+        match op {
+            // writes to the zero register are discarded
+            Operand::Register(Register::Zero) => {}
+            Operand::Register(reg) => stmts.push(Stmt::Assign {
+                dst: self::reg(reg),
+                src,
+            }),
+            _ => stmts.push(Stmt::Unknown),
+        }
+    }
+
+    /// Address of a jump or call.
+    fn target(&self, op: Operand) -> Expr {
+        match op {
+            Operand::Immediate(..) if !self.opcode.is_relative() => Expr::Unknown,
+            op => self.read(op),
+        }
+    }
+
+    pub(crate) fn lower_into(&self, stmts: &mut Vec<Stmt>) {
+        use Opcode::*;
+
+        let ops = self.operands();
+        let last = ops.last().copied().unwrap_or_default();
+        let first = ops.first().copied().unwrap_or_default();
+
+        if let Some((binop, word)) = binary_op(self.opcode) {
+            let (dst, lhs, rhs) = match *ops {
+                [dst, rhs] => (dst, dst, rhs),
+                [dst, lhs, rhs] => (dst, lhs, rhs),
+                _ => return stmts.push(Stmt::Unknown),
+            };
+
+            let value = Expr::binary(binop, self.read(lhs), self.read(rhs));
+            let value = match word {
+                true => Expr::sign_extend(value, 4),
+                false => value,
+            };
+
+            return self.write(dst, value, stmts);
+        }
+
+        if let Some((size, signed)) = load_size(self.opcode) {
+            let addr = match *ops {
+                [_, offset] => Expr::sum(Expr::Reg(SP), self.read(offset)),
+                [_, base, offset] => Expr::sum(self.read(base), self.read(offset)),
+                _ => return stmts.push(Stmt::Unknown),
+            };
+
+            let value = match signed {
+                true => Expr::sign_extend(Expr::load(addr, size), size),
+                false => Expr::load(addr, size),
+            };
+
+            return self.write(first, value, stmts);
+        }
+
+        if let Some(size) = store_size(self.opcode) {
+            let addr = match *ops {
+                [_, offset] => Expr::sum(Expr::Reg(SP), self.read(offset)),
+                [_, base, offset] => Expr::sum(self.read(base), self.read(offset)),
+                _ => return stmts.push(Stmt::Unknown),
+            };
+
+            let src = self.read(first);
+            return stmts.push(Stmt::Store { addr, size, src });
+        }
+
+        if let Some(condition) = branch_condition(self.opcode) {
+            // branches that compare against zero only list a single register
+            let cond = match *ops {
+                [lhs, _] => Cond::Compare(condition, self.read(lhs), Expr::Const(0)),
+                [lhs, rhs, _] if self.opcode != C_BEQZ && self.opcode != C_BNEZ => {
+                    Cond::Compare(condition, self.read(lhs), self.read(rhs))
+                }
+                [lhs, _, _] => Cond::Compare(condition, self.read(lhs), Expr::Const(0)),
+                _ => return stmts.push(Stmt::Unknown),
+            };
+
+            let target = self.target(last);
+            return stmts.push(Stmt::Branch { cond, target });
+        }
+
+        let unary = |stmts: &mut Vec<Stmt>, f: fn(Expr) -> Expr| match *ops {
+            [dst, src] => self.write(dst, f(self.read(src)), stmts),
+            _ => stmts.push(Stmt::Unknown),
+        };
+
+        match self.opcode {
+            NOP | C_NOP | FENCE | FENCE_I => {}
+            LI | C_LI | MV | C_MV => unary(stmts, |value| value),
+            NOT => unary(stmts, |value| Expr::unary(UnaryOp::Not, value)),
+            NEG => unary(stmts, |value| Expr::unary(UnaryOp::Neg, value)),
+            NEGW => unary(stmts, |value| {
+                Expr::sign_extend(Expr::unary(UnaryOp::Neg, value), 4)
+            }),
+            SEXT_W => unary(stmts, |value| Expr::sign_extend(value, 4)),
+            SEQZ => unary(stmts, |value| {
+                Expr::cond(Cond::Compare(Condition::Eq, value, Expr::Const(0)))
+            }),
+            SNEZ => unary(stmts, |value| {
+                Expr::cond(Cond::Compare(Condition::Ne, value, Expr::Const(0)))
+            }),
+            SLTZ => unary(stmts, |value| {
+                Expr::cond(Cond::Compare(Condition::Lt, value, Expr::Const(0)))
+            }),
+            SGTZ => unary(stmts, |value| {
+                Expr::cond(Cond::Compare(Condition::Gt, value, Expr::Const(0)))
+            }),
+            LUI | C_LUI => unary(stmts, |value| {
+                Expr::binary(BinaryOp::Shl, value, Expr::Const(12))
+            }),
+            SLT | SLTI | SLTU | SLTIU => match *ops {
+                [dst, lhs, rhs] => {
+                    let condition = match self.opcode {
+                        SLT | SLTI => Condition::Lt,
+                        _ => Condition::Below,
+                    };
+
+                    let cond = Cond::Compare(condition, self.read(lhs), self.read(rhs));
+                    self.write(dst, Expr::cond(cond), stmts);
+                }
+                _ => stmts.push(Stmt::Unknown),
+            },
+            C_ADDI16SP => stmts.push(Stmt::Assign {
+                dst: SP,
+                src: Expr::sum(Expr::Reg(SP), self.read(first)),
+            }),
+            C_ADDI4SPN => {
+                let value = Expr::sum(Expr::Reg(SP), self.read(last));
+                self.write(first, value, stmts);
+            }
+            RET => stmts.push(Stmt::Return),
+            J | C_J | JR | C_JR | TAIL => stmts.push(Stmt::Jump(self.target(last))),
+            // `c.jalr` is only decoded from `c.jr` encodings
+            C_JALR => stmts.push(Stmt::Jump(self.read(first))),
+            JAL | C_JAL | CALL => stmts.push(Stmt::Call(self.target(last))),
+            // the base register of `jalr` isn't decoded
+            JALR => match first {
+                Operand::Register(Register::Zero) => stmts.push(Stmt::Jump(Expr::Unknown)),
+                _ => stmts.push(Stmt::Call(Expr::Unknown)),
+            },
+            EBREAK | C_EBREAK => stmts.push(Stmt::Halt),
+            _ => {
+                stmts.push(Stmt::Unknown);
+
+                // assume the first register operand is the destination
+                if let Operand::Register(reg) = first {
+                    if reg != Register::Zero && ops.len() > 1 {
+                        stmts.push(Stmt::Assign {
+                            dst: self::reg(reg),
+                            src: Expr::Unknown,
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
@@ -897,3 +897,44 @@ fn sha256() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+#[test]
+fn lowering() {
+    use decoder::ir::{Cond, Condition, Expr, Reg, Stmt};
+    use decoder::{Decoded, Reader};
+
+    let lower = |bytes: &[u8]| {
+        let mut reader = Reader::new(bytes);
+        let mut instr = crate::Decoder { is_64: true }.decode(&mut reader).unwrap();
+        instr.update_rel_addrs(0x1000, None);
+
+        let mut stmts = Vec::new();
+        instr.lower(&mut stmts);
+        stmts
+    };
+
+    let a0 = Reg::full("a0", 8);
+    let a1 = Reg::full("a1", 8);
+    let sp = Reg::full("sp", 8);
+
+    // ld a0, 8(sp)
+    assert_eq!(
+        lower(&[0x03, 0x35, 0x81, 0x00]),
+        [Stmt::Assign {
+            dst: a0,
+            src: Expr::load(Expr::sum(Expr::Reg(sp), Expr::Const(8)), 8),
+        }]
+    );
+
+    // beq a0, a1, 16
+    assert_eq!(
+        lower(&[0x63, 0x08, 0xb5, 0x00]),
+        [Stmt::Branch {
+            cond: Cond::Compare(Condition::Eq, Expr::Reg(a0), Expr::Reg(a1)),
+            target: Expr::Const(0x1010),
+        }]
+    );
+
+    // addi zero, zero, 0
+    assert_eq!(lower(&[0x13, 0x00, 0x00, 0x00]), []);
+}
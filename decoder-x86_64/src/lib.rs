@@ -9,10 +9,11 @@
 
 pub mod long_mode;
 pub mod protected_mode;
+pub mod reference;
 mod safer_unchecked;
 
 use debugvault::Index;
-use tokenizing::TokenStream;
+use tokenizing::{TokenKind, TokenStream};
 use config::CONFIG;
 
 const MEM_SIZE_STRINGS: [&str; 64] = [
@@ -30,13 +31,21 @@ impl decoder::ToTokens for Number {
     fn tokenize(&self, stream: &mut TokenStream, _: &Index) {
         if self.0 == i32::MIN {
             stream.push(" - ", CONFIG.colors.asm.expr);
-            stream.push("0x7fffffff", CONFIG.colors.asm.immediate);
+            stream.push_kind("0x7fffffff", CONFIG.colors.asm.immediate, TokenKind::Immediate);
         } else if self.0 < 0 {
             stream.push(" - ", CONFIG.colors.asm.expr);
-            stream.push_owned(decoder::encode_hex(-self.0 as i64), CONFIG.colors.asm.immediate);
+            stream.push_owned_kind(
+                decoder::encode_hex(-self.0 as i64),
+                CONFIG.colors.asm.immediate,
+                TokenKind::Immediate,
+            );
         } else {
             stream.push(" + ", CONFIG.colors.asm.expr);
-            stream.push_owned(decoder::encode_hex(self.0 as i64), CONFIG.colors.asm.immediate);
+            stream.push_owned_kind(
+                decoder::encode_hex(self.0 as i64),
+                CONFIG.colors.asm.immediate,
+                TokenKind::Immediate,
+            );
         }
     }
 }
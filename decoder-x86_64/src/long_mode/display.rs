@@ -8,7 +8,7 @@ use crate::{Number, MEM_SIZE_STRINGS};
 
 use decoder::ToTokens;
 use debugvault::Index;
-use tokenizing::{colors, TokenStream};
+use tokenizing::{TokenStream, TokenKind};
 use config::CONFIG;
 
 impl fmt::Display for Decoder {
@@ -276,7 +276,7 @@ impl Operand {
                 }
                 None => {
                     let text = decoder::encode_hex(addr as i64);
-                    stream.push_owned(text, CONFIG.colors.asm.immediate);
+                    stream.push_owned_kind(text, CONFIG.colors.asm.immediate, TokenKind::Immediate);
                 }
             },
             Operand::ImmediateU8(_) => match symbols.get_sym_by_addr(addr) {
@@ -289,7 +289,7 @@ impl Operand {
                 }
                 None => {
                     let text = decoder::encode_hex(addr as i64);
-                    stream.push_owned(text, CONFIG.colors.asm.immediate);
+                    stream.push_owned_kind(text, CONFIG.colors.asm.immediate, TokenKind::Immediate);
                 }
             },
             Operand::ImmediateI16(_) => match symbols.get_sym_by_addr(addr) {
@@ -302,7 +302,7 @@ impl Operand {
                 }
                 None => {
                     let text = decoder::encode_hex(addr as i64);
-                    stream.push_owned(text, CONFIG.colors.asm.immediate);
+                    stream.push_owned_kind(text, CONFIG.colors.asm.immediate, TokenKind::Immediate);
                 }
             },
             Operand::ImmediateU16(_) => match symbols.get_sym_by_addr(addr) {
@@ -315,7 +315,7 @@ impl Operand {
                 }
                 None => {
                     let text = decoder::encode_hex(addr as i64);
-                    stream.push_owned(text, CONFIG.colors.asm.immediate);
+                    stream.push_owned_kind(text, CONFIG.colors.asm.immediate, TokenKind::Immediate);
                 }
             },
             Operand::ImmediateI32(_) => match symbols.get_sym_by_addr(addr) {
@@ -328,7 +328,7 @@ impl Operand {
                 }
                 None => {
                     let text = decoder::encode_hex(addr as i64);
-                    stream.push_owned(text, CONFIG.colors.asm.immediate);
+                    stream.push_owned_kind(text, CONFIG.colors.asm.immediate, TokenKind::Immediate);
                 }
             },
             Operand::ImmediateU32(_) => match symbols.get_sym_by_addr(addr) {
@@ -341,7 +341,7 @@ impl Operand {
                 }
                 None => {
                     let text = decoder::encode_hex(addr as i64);
-                    stream.push_owned(text, CONFIG.colors.asm.immediate);
+                    stream.push_owned_kind(text, CONFIG.colors.asm.immediate, TokenKind::Immediate);
                 }
             },
             Operand::ImmediateI64(_) => match symbols.get_sym_by_addr(addr) {
@@ -354,7 +354,7 @@ impl Operand {
                 }
                 None => {
                     let text = decoder::encode_hex(addr as i64);
-                    stream.push_owned(text, CONFIG.colors.asm.immediate);
+                    stream.push_owned_kind(text, CONFIG.colors.asm.immediate, TokenKind::Immediate);
                 }
             },
             Operand::ImmediateU64(_) => match symbols.get_sym_by_addr(addr) {
@@ -367,7 +367,7 @@ impl Operand {
                 }
                 None => {
                     let text = decoder::encode_hex(addr as i64);
-                    stream.push_owned(text, CONFIG.colors.asm.immediate);
+                    stream.push_owned_kind(text, CONFIG.colors.asm.immediate, TokenKind::Immediate);
                 }
             },
             Operand::DisplacementU32(_) => {
@@ -382,7 +382,11 @@ impl Operand {
                     }
                     None => {
                         let text = decoder::encode_hex(addr as i64);
-                        stream.push_owned(text, CONFIG.colors.asm.immediate);
+                        stream.push_owned_kind(
+                            text,
+                            CONFIG.colors.asm.immediate,
+                            TokenKind::Immediate,
+                        );
                     }
                 }
                 stream.push("]", CONFIG.colors.brackets);
@@ -399,7 +403,11 @@ impl Operand {
                     }
                     None => {
                         let text = decoder::encode_hex(addr as i64);
-                        stream.push_owned(text, CONFIG.colors.asm.immediate);
+                        stream.push_owned_kind(
+                            text,
+                            CONFIG.colors.asm.immediate,
+                            TokenKind::Immediate,
+                        );
                     }
                 }
                 stream.push("]", CONFIG.colors.brackets);
@@ -415,7 +423,11 @@ impl Operand {
                         stream.push(">", CONFIG.colors.asm.label);
                     }
                     None => {
-                        stream.push(regspec_label(spec), CONFIG.colors.asm.register);
+                        stream.push_kind(
+                            regspec_label(spec),
+                            CONFIG.colors.asm.register,
+                            TokenKind::Register,
+                        );
                         Number(disp).tokenize(stream, symbols);
                     }
                 }
@@ -432,9 +444,17 @@ impl Operand {
                         stream.push(">", CONFIG.colors.asm.label);
                     }
                     None => {
-                        stream.push(regspec_label(spec), CONFIG.colors.asm.register);
+                        stream.push_kind(
+                            regspec_label(spec),
+                            CONFIG.colors.asm.register,
+                            TokenKind::Register,
+                        );
                         stream.push(" * ", CONFIG.colors.asm.expr);
-                        stream.push_owned(scale.to_string(), CONFIG.colors.asm.immediate);
+                        stream.push_owned_kind(
+                            scale.to_string(),
+                            CONFIG.colors.asm.immediate,
+                            TokenKind::Immediate,
+                        );
                     }
                 }
                 stream.push("]", CONFIG.colors.brackets);
@@ -450,9 +470,17 @@ impl Operand {
                         stream.push(">", CONFIG.colors.asm.label);
                     }
                     None => {
-                        stream.push(regspec_label(spec), CONFIG.colors.asm.register);
+                        stream.push_kind(
+                            regspec_label(spec),
+                            CONFIG.colors.asm.register,
+                            TokenKind::Register,
+                        );
                         stream.push(" * ", CONFIG.colors.asm.expr);
-                        stream.push_owned(format!("{scale}"), CONFIG.colors.asm.immediate);
+                        stream.push_owned_kind(
+                            format!("{scale}"),
+                            CONFIG.colors.asm.immediate,
+                            TokenKind::Immediate,
+                        );
                         Number(disp).tokenize(stream, symbols);
                     }
                 }
@@ -469,9 +497,17 @@ impl Operand {
                         stream.push(">", CONFIG.colors.asm.label);
                     }
                     None => {
-                        stream.push(regspec_label(base), CONFIG.colors.asm.register);
+                        stream.push_kind(
+                            regspec_label(base),
+                            CONFIG.colors.asm.register,
+                            TokenKind::Register,
+                        );
                         stream.push(" + ", CONFIG.colors.asm.expr);
-                        stream.push(regspec_label(index), CONFIG.colors.asm.register);
+                        stream.push_kind(
+                            regspec_label(index),
+                            CONFIG.colors.asm.register,
+                            TokenKind::Register,
+                        );
                     }
                 }
                 stream.push("]", CONFIG.colors.brackets);
@@ -487,9 +523,17 @@ impl Operand {
                         stream.push(">", CONFIG.colors.asm.label);
                     }
                     None => {
-                        stream.push(regspec_label(base), CONFIG.colors.asm.register);
+                        stream.push_kind(
+                            regspec_label(base),
+                            CONFIG.colors.asm.register,
+                            TokenKind::Register,
+                        );
                         stream.push(" + ", CONFIG.colors.asm.expr);
-                        stream.push(regspec_label(index), CONFIG.colors.asm.register);
+                        stream.push_kind(
+                            regspec_label(index),
+                            CONFIG.colors.asm.register,
+                            TokenKind::Register,
+                        );
                         Number(disp).tokenize(stream, symbols);
                     }
                 }
@@ -506,11 +550,23 @@ impl Operand {
                         stream.push(">", CONFIG.colors.asm.label);
                     }
                     None => {
-                        stream.push(regspec_label(base), CONFIG.colors.asm.register);
+                        stream.push_kind(
+                            regspec_label(base),
+                            CONFIG.colors.asm.register,
+                            TokenKind::Register,
+                        );
                         stream.push(" + ", CONFIG.colors.asm.expr);
-                        stream.push(regspec_label(index), CONFIG.colors.asm.register);
+                        stream.push_kind(
+                            regspec_label(index),
+                            CONFIG.colors.asm.register,
+                            TokenKind::Register,
+                        );
                         stream.push(" * ", CONFIG.colors.asm.expr);
-                        stream.push_owned(scale.to_string(), CONFIG.colors.asm.immediate);
+                        stream.push_owned_kind(
+                            scale.to_string(),
+                            CONFIG.colors.asm.immediate,
+                            TokenKind::Immediate,
+                        );
                     }
                 }
                 stream.push("]", CONFIG.colors.brackets);
@@ -526,11 +582,23 @@ impl Operand {
                         stream.push(">", CONFIG.colors.asm.label);
                     }
                     None => {
-                        stream.push(regspec_label(base), CONFIG.colors.asm.register);
+                        stream.push_kind(
+                            regspec_label(base),
+                            CONFIG.colors.asm.register,
+                            TokenKind::Register,
+                        );
                         stream.push(" + ", CONFIG.colors.asm.expr);
-                        stream.push(regspec_label(index), CONFIG.colors.asm.register);
+                        stream.push_kind(
+                            regspec_label(index),
+                            CONFIG.colors.asm.register,
+                            TokenKind::Register,
+                        );
                         stream.push(" * ", CONFIG.colors.asm.expr);
-                        stream.push_owned(scale.to_string(), CONFIG.colors.asm.immediate);
+                        stream.push_owned_kind(
+                            scale.to_string(),
+                            CONFIG.colors.asm.immediate,
+                            TokenKind::Immediate,
+                        );
                         Number(disp).tokenize(stream, symbols);
                     }
                 }
@@ -547,14 +615,22 @@ impl Operand {
                         stream.push(">", CONFIG.colors.asm.label);
                     }
                     None => {
-                        stream.push(regspec_label(spec), CONFIG.colors.asm.register);
+                        stream.push_kind(
+                            regspec_label(spec),
+                            CONFIG.colors.asm.register,
+                            TokenKind::Register,
+                        );
                         Number(disp).tokenize(stream, symbols);
                     }
                 }
                 stream.push("]", CONFIG.colors.brackets);
 
                 stream.push("{", CONFIG.colors.brackets);
-                stream.push(regspec_label(mask_reg), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(mask_reg),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push("}", CONFIG.colors.brackets);
             }
             Operand::RegScaleMasked(ref spec, scale, ref mask_reg) => {
@@ -568,15 +644,27 @@ impl Operand {
                         stream.push(">", CONFIG.colors.asm.label);
                     }
                     None => {
-                        stream.push(regspec_label(spec), CONFIG.colors.asm.register);
+                        stream.push_kind(
+                            regspec_label(spec),
+                            CONFIG.colors.asm.register,
+                            TokenKind::Register,
+                        );
                         stream.push(" * ", CONFIG.colors.asm.expr);
-                        stream.push_owned(scale.to_string(), CONFIG.colors.asm.register);
+                        stream.push_owned_kind(
+                            scale.to_string(),
+                            CONFIG.colors.asm.register,
+                            TokenKind::Register,
+                        );
                     }
                 }
                 stream.push("]", CONFIG.colors.brackets);
 
                 stream.push("{", CONFIG.colors.brackets);
-                stream.push(regspec_label(mask_reg), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(mask_reg),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push("}", CONFIG.colors.brackets);
             }
             Operand::RegScaleDispMasked(ref spec, scale, disp, ref mask_reg) => {
@@ -590,17 +678,29 @@ impl Operand {
                         stream.push(">", CONFIG.colors.asm.label);
                     }
                     None => {
-                        stream.push(regspec_label(spec), CONFIG.colors.asm.register);
+                        stream.push_kind(
+                            regspec_label(spec),
+                            CONFIG.colors.asm.register,
+                            TokenKind::Register,
+                        );
                         stream.push(" * ", CONFIG.colors.asm.expr);
-                        stream.push_owned(scale.to_string(), CONFIG.colors.asm.register);
-                        stream.push(" ", colors::WHITE);
+                        stream.push_owned_kind(
+                            scale.to_string(),
+                            CONFIG.colors.asm.register,
+                            TokenKind::Register,
+                        );
+                        stream.push(" ", CONFIG.colors.text);
                         Number(disp).tokenize(stream, symbols);
                     }
                 }
                 stream.push("]", CONFIG.colors.brackets);
 
                 stream.push("{", CONFIG.colors.brackets);
-                stream.push(regspec_label(mask_reg), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(mask_reg),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push("}", CONFIG.colors.brackets);
             }
             Operand::RegIndexBaseMasked(ref base, ref index, ref mask_reg) => {
@@ -614,15 +714,27 @@ impl Operand {
                         stream.push(">", CONFIG.colors.asm.label);
                     }
                     None => {
-                        stream.push(regspec_label(base), CONFIG.colors.asm.register);
+                        stream.push_kind(
+                            regspec_label(base),
+                            CONFIG.colors.asm.register,
+                            TokenKind::Register,
+                        );
                         stream.push(" + ", CONFIG.colors.asm.expr);
-                        stream.push(regspec_label(index), CONFIG.colors.asm.register);
+                        stream.push_kind(
+                            regspec_label(index),
+                            CONFIG.colors.asm.register,
+                            TokenKind::Register,
+                        );
                     }
                 }
                 stream.push("]", CONFIG.colors.brackets);
 
                 stream.push("{", CONFIG.colors.brackets);
-                stream.push(regspec_label(mask_reg), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(mask_reg),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push("}", CONFIG.colors.brackets);
             }
             Operand::RegIndexBaseDispMasked(ref base, ref index, disp, ref mask_reg) => {
@@ -636,17 +748,29 @@ impl Operand {
                         stream.push(">", CONFIG.colors.asm.label);
                     }
                     None => {
-                        stream.push(regspec_label(base), CONFIG.colors.asm.register);
+                        stream.push_kind(
+                            regspec_label(base),
+                            CONFIG.colors.asm.register,
+                            TokenKind::Register,
+                        );
                         stream.push(" + ", CONFIG.colors.asm.expr);
-                        stream.push(regspec_label(index), CONFIG.colors.asm.register);
-                        stream.push(" ", colors::WHITE);
+                        stream.push_kind(
+                            regspec_label(index),
+                            CONFIG.colors.asm.register,
+                            TokenKind::Register,
+                        );
+                        stream.push(" ", CONFIG.colors.text);
                         Number(disp).tokenize(stream, symbols);
                     }
                 }
                 stream.push("]", CONFIG.colors.brackets);
 
                 stream.push("{", CONFIG.colors.brackets);
-                stream.push(regspec_label(mask_reg), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(mask_reg),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push("}", CONFIG.colors.brackets);
             }
             Operand::RegIndexBaseScaleMasked(ref base, ref index, scale, ref mask_reg) => {
@@ -660,17 +784,33 @@ impl Operand {
                         stream.push(">", CONFIG.colors.asm.label);
                     }
                     None => {
-                        stream.push(regspec_label(base), CONFIG.colors.asm.register);
+                        stream.push_kind(
+                            regspec_label(base),
+                            CONFIG.colors.asm.register,
+                            TokenKind::Register,
+                        );
                         stream.push(" + ", CONFIG.colors.asm.expr);
-                        stream.push(regspec_label(index), CONFIG.colors.asm.register);
+                        stream.push_kind(
+                            regspec_label(index),
+                            CONFIG.colors.asm.register,
+                            TokenKind::Register,
+                        );
                         stream.push(" * ", CONFIG.colors.asm.expr);
-                        stream.push_owned(scale.to_string(), CONFIG.colors.asm.immediate);
+                        stream.push_owned_kind(
+                            scale.to_string(),
+                            CONFIG.colors.asm.immediate,
+                            TokenKind::Immediate,
+                        );
                     }
                 }
                 stream.push("]", CONFIG.colors.brackets);
 
                 stream.push("{", CONFIG.colors.brackets);
-                stream.push(regspec_label(mask_reg), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(mask_reg),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push("}", CONFIG.colors.brackets);
             }
             Operand::RegIndexBaseScaleDispMasked(
@@ -690,18 +830,34 @@ impl Operand {
                         stream.push(">", CONFIG.colors.asm.label);
                     }
                     None => {
-                        stream.push(regspec_label(base), CONFIG.colors.asm.register);
+                        stream.push_kind(
+                            regspec_label(base),
+                            CONFIG.colors.asm.register,
+                            TokenKind::Register,
+                        );
                         stream.push(" + ", CONFIG.colors.asm.expr);
-                        stream.push(regspec_label(index), CONFIG.colors.asm.register);
+                        stream.push_kind(
+                            regspec_label(index),
+                            CONFIG.colors.asm.register,
+                            TokenKind::Register,
+                        );
                         stream.push(" * ", CONFIG.colors.asm.expr);
-                        stream.push_owned(scale.to_string(), CONFIG.colors.asm.immediate);
+                        stream.push_owned_kind(
+                            scale.to_string(),
+                            CONFIG.colors.asm.immediate,
+                            TokenKind::Immediate,
+                        );
                         Number(disp).tokenize(stream, symbols);
                     }
                 }
                 stream.push("]", CONFIG.colors.brackets);
 
                 stream.push("{", CONFIG.colors.brackets);
-                stream.push(regspec_label(mask_reg), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(mask_reg),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push("}", CONFIG.colors.brackets);
             }
             _ => return false,
@@ -719,8 +875,11 @@ impl Operand {
         imm_override: Option<usize>,
     ) {
         if let Some(addr) = imm_override {
+            let start = stream.inner.len();
+
             // if we we've done a symbolic version of tokenizing
             if self.tokenize_symbolic(stream, symbols, addr) {
+                stream.set_target(start, addr);
                 return;
             }
         }
@@ -728,235 +887,415 @@ impl Operand {
         match *self {
             Operand::ImmediateU8(imm) => {
                 let text = decoder::encode_hex(imm as i64);
-                stream.push_owned(text, CONFIG.colors.asm.immediate);
+                stream.push_owned_kind(text, CONFIG.colors.asm.immediate, TokenKind::Immediate);
             }
             Operand::ImmediateI8(imm) => {
                 let text = decoder::encode_hex(imm as i64);
-                stream.push_owned(text, CONFIG.colors.asm.immediate);
+                stream.push_owned_kind(text, CONFIG.colors.asm.immediate, TokenKind::Immediate);
             }
             Operand::ImmediateU16(imm) => {
                 let text = decoder::encode_hex(imm as i64);
-                stream.push_owned(text, CONFIG.colors.asm.immediate);
+                stream.push_owned_kind(text, CONFIG.colors.asm.immediate, TokenKind::Immediate);
             }
             Operand::ImmediateI16(imm) => {
                 let text = decoder::encode_hex(imm as i64);
-                stream.push_owned(text, CONFIG.colors.asm.immediate);
+                stream.push_owned_kind(text, CONFIG.colors.asm.immediate, TokenKind::Immediate);
             }
             Operand::ImmediateU32(imm) => {
                 let text = decoder::encode_hex(imm as i64);
-                stream.push_owned(text, CONFIG.colors.asm.immediate);
+                stream.push_owned_kind(text, CONFIG.colors.asm.immediate, TokenKind::Immediate);
             }
             Operand::ImmediateI32(imm) => {
                 let text = decoder::encode_hex(imm as i64);
-                stream.push_owned(text, CONFIG.colors.asm.immediate);
+                stream.push_owned_kind(text, CONFIG.colors.asm.immediate, TokenKind::Immediate);
             }
             Operand::ImmediateU64(imm) => {
                 let text = decoder::encode_hex(imm as i64);
-                stream.push_owned(text, CONFIG.colors.asm.immediate);
+                stream.push_owned_kind(text, CONFIG.colors.asm.immediate, TokenKind::Immediate);
             }
             Operand::ImmediateI64(imm) => {
                 let text = decoder::encode_hex(imm);
-                stream.push_owned(text, CONFIG.colors.asm.immediate);
+                stream.push_owned_kind(text, CONFIG.colors.asm.immediate, TokenKind::Immediate);
             }
             Operand::Register(ref spec) => {
-                stream.push(regspec_label(spec), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(spec),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
             }
             Operand::RegisterMaskMerge(ref spec, ref mask, merge_mode) => {
-                stream.push(regspec_label(spec), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(spec),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
 
                 if mask.num != 0 {
                     stream.push("{", CONFIG.colors.brackets);
-                    stream.push(regspec_label(mask), CONFIG.colors.asm.register);
+                    stream.push_kind(
+                        regspec_label(mask),
+                        CONFIG.colors.asm.register,
+                        TokenKind::Register,
+                    );
                     stream.push("}", CONFIG.colors.brackets);
                 }
                 if let MergeMode::Zero = merge_mode {
                     stream.push("{", CONFIG.colors.brackets);
-                    stream.push("z", CONFIG.colors.asm.register);
+                    stream.push_kind("z", CONFIG.colors.asm.register, TokenKind::Register);
                     stream.push("}", CONFIG.colors.brackets);
                 }
             }
             Operand::RegisterMaskMergeSae(ref spec, ref mask, merge_mode, sae_mode) => {
-                stream.push(regspec_label(spec), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(spec),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
 
                 if mask.num != 0 {
                     stream.push("{", CONFIG.colors.brackets);
-                    stream.push(regspec_label(mask), CONFIG.colors.asm.register);
+                    stream.push_kind(
+                        regspec_label(mask),
+                        CONFIG.colors.asm.register,
+                        TokenKind::Register,
+                    );
                     stream.push("}", CONFIG.colors.brackets);
                 }
                 if let MergeMode::Zero = merge_mode {
                     stream.push("{", CONFIG.colors.brackets);
-                    stream.push("z", CONFIG.colors.asm.register);
+                    stream.push_kind("z", CONFIG.colors.asm.register, TokenKind::Register);
                     stream.push("}", CONFIG.colors.brackets);
                 }
 
                 sae_mode.tokenize(stream, symbols);
             }
             Operand::RegisterMaskMergeSaeNoround(ref spec, ref mask, merge_mode) => {
-                stream.push(regspec_label(spec), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(spec),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
 
                 if mask.num != 0 {
                     stream.push("{", CONFIG.colors.brackets);
-                    stream.push(regspec_label(mask), CONFIG.colors.asm.register);
+                    stream.push_kind(
+                        regspec_label(mask),
+                        CONFIG.colors.asm.register,
+                        TokenKind::Register,
+                    );
                     stream.push("}", CONFIG.colors.brackets);
                 }
                 if let MergeMode::Zero = merge_mode {
                     stream.push("{", CONFIG.colors.brackets);
-                    stream.push("z", CONFIG.colors.asm.register);
+                    stream.push_kind("z", CONFIG.colors.asm.register, TokenKind::Register);
                     stream.push("}", CONFIG.colors.brackets);
                 }
 
                 stream.push("{", CONFIG.colors.brackets);
-                stream.push("sae", CONFIG.colors.asm.register);
+                stream.push_kind("sae", CONFIG.colors.asm.register, TokenKind::Register);
                 stream.push("}", CONFIG.colors.brackets);
             }
             Operand::DisplacementU32(imm) => {
                 stream.push("[", CONFIG.colors.brackets);
-                stream.push_owned(decoder::encode_hex(imm as i64), CONFIG.colors.asm.immediate);
+                stream.push_owned_kind(
+                    decoder::encode_hex(imm as i64),
+                    CONFIG.colors.asm.immediate,
+                    TokenKind::Immediate,
+                );
                 stream.push("]", CONFIG.colors.brackets);
             }
             Operand::DisplacementU64(imm) => {
                 stream.push("[", CONFIG.colors.brackets);
-                stream.push_owned(decoder::encode_hex(imm as i64), CONFIG.colors.asm.immediate);
+                stream.push_owned_kind(
+                    decoder::encode_hex(imm as i64),
+                    CONFIG.colors.asm.immediate,
+                    TokenKind::Immediate,
+                );
                 stream.push("]", CONFIG.colors.brackets);
             }
             Operand::RegDisp(ref spec, disp) => {
                 stream.push("[", CONFIG.colors.brackets);
-                stream.push(regspec_label(spec), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(spec),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 Number(disp).tokenize(stream, symbols);
                 stream.push("]", CONFIG.colors.brackets);
             }
             Operand::RegDeref(ref spec) => {
                 stream.push("[", CONFIG.colors.brackets);
-                stream.push(regspec_label(spec), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(spec),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push("]", CONFIG.colors.brackets);
             }
             Operand::RegScale(ref spec, scale) => {
                 stream.push("[", CONFIG.colors.brackets);
-                stream.push(regspec_label(spec), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(spec),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push(" * ", CONFIG.colors.asm.expr);
-                stream.push_owned(scale.to_string(), CONFIG.colors.asm.immediate);
+                stream.push_owned_kind(
+                    scale.to_string(),
+                    CONFIG.colors.asm.immediate,
+                    TokenKind::Immediate,
+                );
                 stream.push("]", CONFIG.colors.brackets);
             }
             Operand::RegScaleDisp(ref spec, scale, disp) => {
                 stream.push("[", CONFIG.colors.brackets);
-                stream.push(regspec_label(spec), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(spec),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push(" * ", CONFIG.colors.asm.expr);
-                stream.push_owned(format!("{scale}"), CONFIG.colors.asm.immediate);
+                stream.push_owned_kind(
+                    format!("{scale}"),
+                    CONFIG.colors.asm.immediate,
+                    TokenKind::Immediate,
+                );
                 Number(disp).tokenize(stream, symbols);
                 stream.push("]", CONFIG.colors.brackets);
             }
             Operand::RegIndexBase(ref base, ref index) => {
                 stream.push("[", CONFIG.colors.brackets);
-                stream.push(regspec_label(base), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(base),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push(" + ", CONFIG.colors.asm.expr);
-                stream.push(regspec_label(index), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(index),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push("]", CONFIG.colors.brackets);
             }
             Operand::RegIndexBaseDisp(ref base, ref index, disp) => {
                 stream.push("[", CONFIG.colors.brackets);
-                stream.push(regspec_label(base), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(base),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push(" + ", CONFIG.colors.asm.expr);
-                stream.push(regspec_label(index), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(index),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 Number(disp).tokenize(stream, symbols);
                 stream.push("]", CONFIG.colors.brackets);
             }
             Operand::RegIndexBaseScale(ref base, ref index, scale) => {
                 stream.push("[", CONFIG.colors.brackets);
-                stream.push(regspec_label(base), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(base),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push(" + ", CONFIG.colors.asm.expr);
-                stream.push(regspec_label(index), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(index),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push(" * ", CONFIG.colors.asm.expr);
-                stream.push_owned(scale.to_string(), CONFIG.colors.asm.immediate);
+                stream.push_owned_kind(
+                    scale.to_string(),
+                    CONFIG.colors.asm.immediate,
+                    TokenKind::Immediate,
+                );
                 stream.push("]", CONFIG.colors.brackets);
             }
             Operand::RegIndexBaseScaleDisp(ref base, ref index, scale, disp) => {
                 stream.push("[", CONFIG.colors.brackets);
-                stream.push(regspec_label(base), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(base),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push(" + ", CONFIG.colors.asm.expr);
-                stream.push(regspec_label(index), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(index),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push(" * ", CONFIG.colors.asm.expr);
-                stream.push_owned(scale.to_string(), CONFIG.colors.asm.immediate);
+                stream.push_owned_kind(
+                    scale.to_string(),
+                    CONFIG.colors.asm.immediate,
+                    TokenKind::Immediate,
+                );
                 Number(disp).tokenize(stream, symbols);
                 stream.push("]", CONFIG.colors.brackets);
             }
             Operand::RegDispMasked(ref spec, disp, ref mask_reg) => {
                 stream.push("[", CONFIG.colors.brackets);
-                stream.push(regspec_label(spec), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(spec),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 Number(disp).tokenize(stream, symbols);
                 stream.push("]", CONFIG.colors.brackets);
 
                 stream.push("{", CONFIG.colors.brackets);
-                stream.push(regspec_label(mask_reg), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(mask_reg),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push("}", CONFIG.colors.brackets);
             }
             Operand::RegDerefMasked(ref spec, ref mask_reg) => {
                 stream.push("[", CONFIG.colors.brackets);
-                stream.push(regspec_label(spec), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(spec),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push("]", CONFIG.colors.brackets);
 
                 stream.push("{", CONFIG.colors.brackets);
-                stream.push(regspec_label(mask_reg), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(mask_reg),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push("}", CONFIG.colors.brackets);
             }
             Operand::RegScaleMasked(ref spec, scale, ref mask_reg) => {
                 stream.push("[", CONFIG.colors.brackets);
-                stream.push(regspec_label(spec), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(spec),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push(" * ", CONFIG.colors.asm.expr);
-                stream.push_owned(scale.to_string(), CONFIG.colors.asm.register);
+                stream.push_owned_kind(
+                    scale.to_string(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push("]", CONFIG.colors.brackets);
 
                 stream.push("{", CONFIG.colors.brackets);
-                stream.push(regspec_label(mask_reg), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(mask_reg),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push("}", CONFIG.colors.brackets);
             }
             Operand::RegScaleDispMasked(ref spec, scale, disp, ref mask_reg) => {
                 stream.push("[", CONFIG.colors.brackets);
-                stream.push(regspec_label(spec), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(spec),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push(" * ", CONFIG.colors.asm.expr);
-                stream.push_owned(scale.to_string(), CONFIG.colors.asm.register);
-                stream.push(" ", colors::WHITE);
+                stream.push_owned_kind(
+                    scale.to_string(),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
+                stream.push(" ", CONFIG.colors.text);
                 Number(disp).tokenize(stream, symbols);
                 stream.push("]", CONFIG.colors.brackets);
 
                 stream.push("{", CONFIG.colors.brackets);
-                stream.push(regspec_label(mask_reg), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(mask_reg),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push("}", CONFIG.colors.brackets);
             }
             Operand::RegIndexBaseMasked(ref base, ref index, ref mask_reg) => {
                 stream.push("[", CONFIG.colors.brackets);
-                stream.push(regspec_label(base), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(base),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push(" + ", CONFIG.colors.asm.expr);
-                stream.push(regspec_label(index), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(index),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push("]", CONFIG.colors.brackets);
 
                 stream.push("{", CONFIG.colors.brackets);
-                stream.push(regspec_label(mask_reg), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(mask_reg),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push("}", CONFIG.colors.brackets);
             }
             Operand::RegIndexBaseDispMasked(ref base, ref index, disp, ref mask_reg) => {
                 stream.push("[", CONFIG.colors.brackets);
-                stream.push(regspec_label(base), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(base),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push(" + ", CONFIG.colors.asm.expr);
-                stream.push(regspec_label(index), CONFIG.colors.asm.register);
-                stream.push(" ", colors::WHITE);
+                stream.push_kind(
+                    regspec_label(index),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
+                stream.push(" ", CONFIG.colors.text);
                 Number(disp).tokenize(stream, symbols);
                 stream.push("]", CONFIG.colors.brackets);
 
                 stream.push("{", CONFIG.colors.brackets);
-                stream.push(regspec_label(mask_reg), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(mask_reg),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push("}", CONFIG.colors.brackets);
             }
             Operand::RegIndexBaseScaleMasked(ref base, ref index, scale, ref mask_reg) => {
                 stream.push("[", CONFIG.colors.brackets);
-                stream.push(regspec_label(base), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(base),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push(" + ", CONFIG.colors.asm.expr);
-                stream.push(regspec_label(index), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(index),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push(" * ", CONFIG.colors.asm.expr);
-                stream.push_owned(scale.to_string(), CONFIG.colors.asm.immediate);
+                stream.push_owned_kind(
+                    scale.to_string(),
+                    CONFIG.colors.asm.immediate,
+                    TokenKind::Immediate,
+                );
                 stream.push("]", CONFIG.colors.brackets);
 
                 stream.push("{", CONFIG.colors.brackets);
-                stream.push(regspec_label(mask_reg), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(mask_reg),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push("}", CONFIG.colors.brackets);
             }
             Operand::RegIndexBaseScaleDispMasked(
@@ -967,16 +1306,32 @@ impl Operand {
                 ref mask_reg,
             ) => {
                 stream.push("[", CONFIG.colors.brackets);
-                stream.push(regspec_label(base), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(base),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push(" + ", CONFIG.colors.asm.expr);
-                stream.push(regspec_label(index), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(index),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push(" * ", CONFIG.colors.asm.expr);
-                stream.push_owned(scale.to_string(), CONFIG.colors.asm.immediate);
+                stream.push_owned_kind(
+                    scale.to_string(),
+                    CONFIG.colors.asm.immediate,
+                    TokenKind::Immediate,
+                );
                 Number(disp).tokenize(stream, symbols);
                 stream.push("]", CONFIG.colors.brackets);
 
                 stream.push("{", CONFIG.colors.brackets);
-                stream.push(regspec_label(mask_reg), CONFIG.colors.asm.register);
+                stream.push_kind(
+                    regspec_label(mask_reg),
+                    CONFIG.colors.asm.register,
+                    TokenKind::Register,
+                );
                 stream.push("}", CONFIG.colors.brackets);
             }
             Operand::Nothing => {}
@@ -2464,17 +2819,15 @@ impl ToTokens for Instruction {
         op.push_str(opcode_name);
         stream.push_owned(op, CONFIG.colors.asm.opcode);
 
-        // slightly hacky but for `int` instructions we tend to incorrectly try to
-        // do symbolic resolution on the immediate which isn't correct
-        let imm_override =
-            if self.imm_override && self.opcode != Opcode::INT && self.opcode != Opcode::INTO {
-                Some(self.imm as usize)
-            } else {
-                None
-            };
+        // only memory operands and the targets of relative branches resolve to an address
+        let relative = self.opcode.is_relative_branch();
+        let imm_override = |op: &Operand| {
+            let resolved = op.is_memory() || relative;
+            (self.imm_override && resolved).then_some(self.target as usize)
+        };
 
         if self.operand_count > 0 {
-            stream.push(" ", colors::WHITE);
+            stream.push(" ", CONFIG.colors.text);
 
             let op = Operand::from_spec(self, self.operands[0]);
 
@@ -2490,7 +2843,7 @@ impl ToTokens for Instruction {
                 stream.push(":", CONFIG.colors.asm.expr);
             }
 
-            op.tokenize(stream, symbols, imm_override);
+            op.tokenize(stream, symbols, imm_override(&op));
 
             for idx in 1..self.operand_count {
                 if self.operands[idx as usize] == OperandSpec::Nothing {
@@ -2511,7 +2864,7 @@ impl ToTokens for Instruction {
                     stream.push(":", CONFIG.colors.asm.expr);
                 }
 
-                op.tokenize(stream, symbols, imm_override);
+                op.tokenize(stream, symbols, imm_override(&op));
 
                 if let Some(evex) = self.prefixes.evex() {
                     if evex.broadcast() && op.is_memory() {
@@ -2574,7 +2927,11 @@ impl ToTokens for Instruction {
 
                         stream.push("{", CONFIG.colors.brackets);
                         stream.push("1to", CONFIG.colors.asm.expr);
-                        stream.push_owned(scale.to_string(), CONFIG.colors.asm.immediate);
+                        stream.push_owned_kind(
+                            scale.to_string(),
+                            CONFIG.colors.asm.immediate,
+                            TokenKind::Immediate,
+                        );
                         stream.push("}", CONFIG.colors.brackets);
                     }
                 }
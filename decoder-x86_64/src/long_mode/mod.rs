@@ -1,5 +1,6 @@
 mod display;
 mod evex;
+mod lower;
 mod tests;
 pub mod uarch;
 mod vex;
@@ -13,7 +14,7 @@ pub use crate::MemoryAccessSize;
 
 use decoder::{Decoded, Decodable, Error, ErrorKind, Reader, ToTokens};
 use debugvault::Index;
-use tokenizing::TokenStream;
+use tokenizing::{TokenKind, TokenStream};
 use config::CONFIG;
 
 /// an `x86_64` register, including its number and type. if `fmt` is enabled, name too.
@@ -592,7 +593,7 @@ impl SaeMode {
 impl ToTokens for SaeMode {
     fn tokenize(&self, stream: &mut TokenStream, _: &Index) {
         stream.push("{", CONFIG.colors.brackets);
-        stream.push(
+        stream.push_kind(
             match self {
                 SaeMode::RoundNearest => "rne-sae",
                 SaeMode::RoundDown => "rd-sae",
@@ -600,6 +601,7 @@ impl ToTokens for SaeMode {
                 SaeMode::RoundZero => "rz-sae",
             },
             CONFIG.colors.asm.register,
+            TokenKind::Register,
         );
         stream.push("}", CONFIG.colors.brackets);
     }
@@ -2613,6 +2615,8 @@ pub struct Instruction {
     disp: u64,
     opcode: Opcode,
     mem_size: u8,
+    /// Address of a relative branch target or of a memory operand, set by `update_rel_addrs`.
+    target: u64,
     imm_override: bool,
 }
 
@@ -2628,6 +2632,7 @@ impl fmt::Debug for Instruction {
             .field("disp", &self.disp)
             .field("opcode", &self.opcode)
             .field("mem_size", &self.mem_size)
+            .field("target", &self.target)
             .field("imm_override", &self.imm_override)
             .finish()
     }
@@ -2640,32 +2645,35 @@ impl Decoded for Instruction {
     }
 
     fn update_rel_addrs(&mut self, addr: usize, _: Option<&Instruction>) {
+        // other immediates are plain values, not offsets
+        let relative = self.opcode.is_relative_branch();
+
         for idx in 0..self.operand_count as usize {
             let operand = Operand::from_spec(&self, self.operands[idx]);
             let addr = addr as u64;
             let addr = match operand {
-                Operand::ImmediateI8(imm) => {
+                Operand::ImmediateI8(imm) if relative => {
                     addr.saturating_add(self.length as u64).saturating_add_signed(imm as i64)
                 }
-                Operand::ImmediateU8(imm) => {
+                Operand::ImmediateU8(imm) if relative => {
                     addr.saturating_add(self.length as u64).saturating_add(imm as u64)
                 }
-                Operand::ImmediateI16(imm) => {
+                Operand::ImmediateI16(imm) if relative => {
                     addr.saturating_add(self.length as u64).saturating_add_signed(imm as i64)
                 }
-                Operand::ImmediateU16(imm) => {
+                Operand::ImmediateU16(imm) if relative => {
                     addr.saturating_add(self.length as u64).saturating_add(imm as u64)
                 }
-                Operand::ImmediateI32(imm) => {
+                Operand::ImmediateI32(imm) if relative => {
                     addr.saturating_add(self.length as u64).saturating_add_signed(imm as i64)
                 }
-                Operand::ImmediateU32(imm) => {
+                Operand::ImmediateU32(imm) if relative => {
                     addr.saturating_add(self.length as u64).saturating_add(imm as u64)
                 }
-                Operand::ImmediateI64(imm) => {
+                Operand::ImmediateI64(imm) if relative => {
                     addr.saturating_add(self.length as u64).saturating_add_signed(imm as i64)
                 }
-                Operand::ImmediateU64(imm) => {
+                Operand::ImmediateU64(imm) if relative => {
                     addr.saturating_add(self.length as u64).saturating_add(imm as u64)
                 }
                 Operand::DisplacementU32(imm) => addr.saturating_add(imm as u64),
@@ -2733,10 +2741,14 @@ impl Decoded for Instruction {
                 _ => continue,
             };
 
-            self.imm = addr;
+            self.target = addr;
             self.imm_override = true;
         }
     }
+
+    fn lower(&self, stmts: &mut Vec<decoder::ir::Stmt>) {
+        self.lower_into(stmts);
+    }
 }
 
 impl Decodable for Decoder {
@@ -4296,6 +4308,22 @@ impl Opcode {
         )
     }
 
+    /// check if the instruction's immediate is an offset from the next instruction, like the
+    /// targets of `jmp`, `call`, `jcc` and `loop`.
+    pub fn is_relative_branch(&self) -> bool {
+        self.is_jcc()
+            || matches!(
+                self,
+                Opcode::JMP
+                    | Opcode::CALL
+                    | Opcode::LOOP
+                    | Opcode::LOOPZ
+                    | Opcode::LOOPNZ
+                    | Opcode::JRCXZ
+                    | Opcode::XBEGIN
+            )
+    }
+
     /// get the [`ConditionCode`] for this instruction, if it is in fact conditional. x86's
     /// conditional instructions are `Jcc`, `CMOVcc`, andd `SETcc`.
     pub fn condition(&self) -> Option<ConditionCode> {
@@ -4387,6 +4415,7 @@ impl Instruction {
             imm: 0,
             operand_count: 0,
             operands: [OperandSpec::Nothing; 4],
+            target: 0,
             imm_override: false,
         }
     }
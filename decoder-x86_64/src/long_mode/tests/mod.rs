@@ -6457,3 +6457,87 @@ mod reg_masks {
         RegSpec::zmm(32);
     }
 }
+
+#[test]
+fn relative_targets() {
+    let mut reader = Reader::new(&[0xe8, 0x00, 0x01, 0x00, 0x00]);
+    let mut instr = Decoder::default().decode(&mut reader).unwrap();
+    instr.update_rel_addrs(0x1000, None);
+
+    let symbols = debugvault::Index::default();
+    let tokens = instr.tokens(&symbols);
+    let target = tokens.iter().find(|token| token.target.is_some()).unwrap();
+    assert_eq!(target.target, Some(0x1105));
+    assert!(tokens[0].target.is_none());
+}
+
+#[test]
+fn token_kinds() {
+    use tokenizing::TokenKind;
+
+    let mut reader = Reader::new(&[0x83, 0xc1, 0x10]);
+    let instr = Decoder::default().decode(&mut reader).unwrap();
+    let tokens = instr.tokens(&debugvault::Index::default());
+
+    let kind_of = |text: &str| tokens.iter().find(|token| &*token.text == text).map(|t| t.kind);
+    assert_eq!(kind_of("ecx"), Some(TokenKind::Register));
+    assert_eq!(kind_of("0x10"), Some(TokenKind::Immediate));
+}
+
+#[test]
+fn immediates_are_not_resolved() {
+    let mut reader = Reader::new(&[0xba, 0x00, 0x00, 0x00, 0x00]);
+    let mut instr = Decoder::default().decode(&mut reader).unwrap();
+    instr.update_rel_addrs(0x1000, None);
+
+    let tokens = instr.tokens(&debugvault::Index::default());
+    assert!(tokens.iter().all(|token| token.target.is_none()));
+
+    let text: String = tokens.iter().map(|token| &*token.text).collect();
+    assert_eq!(text, "mov edx, 0x0");
+}
+
+#[test]
+fn lowering() {
+    use decoder::ir::{BinaryOp, Expr, Reg, Stmt};
+
+    let lower = |bytes: &[u8]| {
+        let mut reader = Reader::new(bytes);
+        let mut instr = Decoder::default().decode(&mut reader).unwrap();
+        instr.update_rel_addrs(0x1000, None);
+
+        let mut stmts = Vec::new();
+        instr.lower(&mut stmts);
+        stmts
+    };
+
+    let rax = Reg::full("rax", 8);
+    let rsp = Reg::full("rsp", 8);
+
+    assert_eq!(
+        lower(&[0xe8, 0x00, 0x01, 0x00, 0x00]),
+        [Stmt::Call(Expr::Const(0x1105))]
+    );
+    assert_eq!(
+        lower(&[0x48, 0x8b, 0x05, 0x10, 0x00, 0x00, 0x00]),
+        [Stmt::Assign {
+            dst: rax,
+            src: Expr::load(Expr::Const(0x1017), 8),
+        }]
+    );
+    assert_eq!(
+        lower(&[0x55]),
+        [
+            Stmt::Store {
+                addr: Expr::binary(BinaryOp::Sub, Expr::Reg(rsp), Expr::Const(8)),
+                size: 8,
+                src: Expr::Reg(Reg::full("rbp", 8)),
+            },
+            Stmt::Assign {
+                dst: rsp,
+                src: Expr::binary(BinaryOp::Sub, Expr::Reg(rsp), Expr::Const(8)),
+            },
+        ]
+    );
+    assert_eq!(lower(&[0xc3]), [Stmt::Return]);
+}
@@ -0,0 +1,376 @@
+//! Lowering of instructions into the architecture independent [`ir`](decoder::ir).
+
+use super::{ConditionCode, Instruction, Opcode, Operand, RegSpec, RegisterBank};
+use decoder::ir::{BinaryOp, Cond, Condition, Expr, Flags, Reg, Stmt, UnaryOp};
+
+const GPR_NAMES: [&str; 8] = ["eax", "ecx", "edx", "ebx", "esp", "ebp", "esi", "edi"];
+
+const EAX: Reg = Reg::full("eax", 4);
+const ECX: Reg = Reg::full("ecx", 4);
+const EDX: Reg = Reg::full("edx", 4);
+const ESP: Reg = Reg::full("esp", 4);
+const EBP: Reg = Reg::full("ebp", 4);
+
+/// Part of a general purpose register, or the entire register for any other bank.
+fn reg(spec: RegSpec) -> Reg {
+    let num = spec.num as usize;
+    match spec.bank {
+        RegisterBank::D => Reg::full(GPR_NAMES[num], 4),
+        RegisterBank::W => Reg::full(GPR_NAMES[num], 2),
+        RegisterBank::B if (4..8).contains(&num) => Reg {
+            name: GPR_NAMES[num - 4],
+            size: 1,
+            offset: 1,
+        },
+        RegisterBank::B => Reg::full(GPR_NAMES[num], 1),
+        _ => Reg::full(spec.name(), spec.width()),
+    }
+}
+
+fn condition(code: ConditionCode) -> Condition {
+    match code {
+        ConditionCode::O => Condition::Overflow,
+        ConditionCode::NO => Condition::NoOverflow,
+        ConditionCode::B => Condition::Below,
+        ConditionCode::AE => Condition::AboveEq,
+        ConditionCode::Z => Condition::Eq,
+        ConditionCode::NZ => Condition::Ne,
+        ConditionCode::A => Condition::Above,
+        ConditionCode::BE => Condition::BelowEq,
+        ConditionCode::S => Condition::Negative,
+        ConditionCode::NS => Condition::NotNegative,
+        ConditionCode::P => Condition::Parity,
+        ConditionCode::NP => Condition::NoParity,
+        ConditionCode::L => Condition::Lt,
+        ConditionCode::GE => Condition::Ge,
+        ConditionCode::G => Condition::Gt,
+        ConditionCode::LE => Condition::Le,
+    }
+}
+
+fn offset(disp: i32) -> Expr {
+    Expr::Const(disp as u32 as u64)
+}
+
+fn scaled(spec: RegSpec, scale: u8) -> Expr {
+    match scale {
+        1 => Expr::Reg(reg(spec)),
+        _ => Expr::binary(
+            BinaryOp::Mul,
+            Expr::Reg(reg(spec)),
+            Expr::Const(scale as u64),
+        ),
+    }
+}
+
+impl Instruction {
+    /// Address of a memory operand.
+    fn address(&self, op: &Operand) -> Option<Expr> {
+        let addr = match *op {
+            Operand::DisplacementU16(disp) => Expr::Const(disp as u64),
+            Operand::DisplacementU32(disp) => Expr::Const(disp as u64),
+            Operand::RegDeref(base) | Operand::RegDerefMasked(base, _) => Expr::Reg(reg(base)),
+            Operand::RegDisp(base, disp) | Operand::RegDispMasked(base, disp, _) => {
+                Expr::sum(Expr::Reg(reg(base)), offset(disp))
+            }
+            Operand::RegScale(idx, scale) | Operand::RegScaleMasked(idx, scale, _) => {
+                scaled(idx, scale)
+            }
+            Operand::RegScaleDisp(idx, scale, disp)
+            | Operand::RegScaleDispMasked(idx, scale, disp, _) => {
+                Expr::sum(scaled(idx, scale), offset(disp))
+            }
+            Operand::RegIndexBase(base, idx) | Operand::RegIndexBaseMasked(base, idx, _) => {
+                Expr::sum(Expr::Reg(reg(base)), Expr::Reg(reg(idx)))
+            }
+            Operand::RegIndexBaseDisp(base, idx, disp)
+            | Operand::RegIndexBaseDispMasked(base, idx, disp, _) => Expr::sum(
+                Expr::sum(Expr::Reg(reg(base)), Expr::Reg(reg(idx))),
+                offset(disp),
+            ),
+            Operand::RegIndexBaseScale(base, idx, scale)
+            | Operand::RegIndexBaseScaleMasked(base, idx, scale, _) => {
+                Expr::sum(Expr::Reg(reg(base)), scaled(idx, scale))
+            }
+            Operand::RegIndexBaseScaleDisp(base, idx, scale, disp)
+            | Operand::RegIndexBaseScaleDispMasked(base, idx, scale, disp, _) => Expr::sum(
+                Expr::sum(Expr::Reg(reg(base)), scaled(idx, scale)),
+                offset(disp),
+            ),
+            _ => return None,
+        };
+
+        let eip_relative = match *op {
+            Operand::RegDeref(base)
+            | Operand::RegDisp(base, _)
+            | Operand::RegIndexBase(base, _)
+            | Operand::RegIndexBaseDisp(base, _, _)
+            | Operand::RegIndexBaseScale(base, _, _)
+            | Operand::RegIndexBaseScaleDisp(base, _, _, _) => base == RegSpec::EIP,
+            _ => false,
+        };
+
+        // the address of eip relative operands is only known once it's been resolved
+        if eip_relative {
+            return Some(self.resolved());
+        }
+
+        Some(addr)
+    }
+
+    /// Address resolved by `update_rel_addrs`.
+    fn resolved(&self) -> Expr {
+        match self.imm_override {
+            true => Expr::Const(self.target as u64),
+            false => Expr::Unknown,
+        }
+    }
+
+    /// Number of bytes accessed by memory operands.
+    fn access_size(&self) -> u8 {
+        match self.mem_size {
+            0 | 63 => 4,
+            size => size,
+        }
+    }
+
+    /// Number of bytes of an operand.
+    fn width(&self, op: &Operand) -> u8 {
+        match *op {
+            Operand::Register(spec) => spec.width(),
+            _ => self.access_size(),
+        }
+    }
+
+    fn read(&self, op: &Operand) -> Expr {
+        match *op {
+            Operand::ImmediateI8(imm) => Expr::Const(imm as i32 as u32 as u64),
+            Operand::ImmediateU8(imm) => Expr::Const(imm as u64),
+            Operand::ImmediateI16(imm) => Expr::Const(imm as i32 as u32 as u64),
+            Operand::ImmediateU16(imm) => Expr::Const(imm as u64),
+            Operand::ImmediateI32(imm) => Expr::Const(imm as u32 as u64),
+            Operand::ImmediateU32(imm) => Expr::Const(imm as u64),
+            Operand::Register(spec) => Expr::Reg(reg(spec)),
+            _ => match self.address(op) {
+                Some(addr) => Expr::load(addr, self.access_size()),
+                None => Expr::Unknown,
+            },
+        }
+    }
+
+    fn write(&self, op: &Operand, src: Expr, stmts: &mut Vec<Stmt>) {
+        match *op {
+            Operand::Register(spec) => stmts.push(Stmt::Assign {
+                dst: reg(spec),
+                src,
+            }),
+            _ => match self.address(op) {
+                Some(addr) => stmts.push(Stmt::Store {
+                    addr,
+                    size: self.access_size(),
+                    src,
+                }),
+                None => stmts.push(Stmt::Unknown),
+            },
+        }
+    }
+
+    /// Target of a jump or call.
+    fn branch_target(&self) -> Expr {
+        match self.operand(0) {
+            op @ Operand::Register(..) => self.read(&op),
+            op if op.is_memory() => self.read(&op),
+            _ => self.resolved(),
+        }
+    }
+
+    pub(super) fn lower_into(&self, stmts: &mut Vec<Stmt>) {
+        let op = |idx: u8| self.operand(idx);
+
+        if let Some(code) = self.opcode.condition() {
+            let cond = Cond::Flags(condition(code));
+            let (dst, src) = (op(0), op(1));
+
+            if self.opcode.is_jcc() {
+                let target = self.resolved();
+                stmts.push(Stmt::Branch { cond, target });
+            } else if self.opcode.is_cmovcc() {
+                let value = Expr::select(cond, self.read(&src), self.read(&dst));
+                self.write(&dst, value, stmts);
+            } else {
+                self.write(&dst, Expr::cond(cond), stmts);
+            }
+
+            return;
+        }
+
+        let binary = |binop: BinaryOp| Expr::binary(binop, self.read(&op(0)), self.read(&op(1)));
+
+        match self.opcode {
+            Opcode::NOP => {}
+            Opcode::MOV | Opcode::MOVZX => self.write(&op(0), self.read(&op(1)), stmts),
+            Opcode::MOVSX => {
+                let src = op(1);
+                let value = Expr::sign_extend(self.read(&src), self.width(&src));
+                self.write(&op(0), value, stmts);
+            }
+            Opcode::LEA => {
+                let addr = self.address(&op(1)).unwrap_or(Expr::Unknown);
+                self.write(&op(0), addr, stmts);
+            }
+            Opcode::XOR | Opcode::SUB if op(0) == op(1) => {
+                stmts.push(Stmt::Flags(Flags::Result(Expr::Const(0))));
+                self.write(&op(0), Expr::Const(0), stmts);
+            }
+            Opcode::ADD | Opcode::OR | Opcode::XOR => {
+                let binop = match self.opcode {
+                    Opcode::ADD => BinaryOp::Add,
+                    Opcode::OR => BinaryOp::Or,
+                    _ => BinaryOp::Xor,
+                };
+
+                stmts.push(Stmt::Flags(Flags::Result(binary(binop))));
+                self.write(&op(0), binary(binop), stmts);
+            }
+            Opcode::SUB => {
+                stmts.push(Stmt::Flags(Flags::Compare(
+                    self.read(&op(0)),
+                    self.read(&op(1)),
+                )));
+                self.write(&op(0), binary(BinaryOp::Sub), stmts);
+            }
+            Opcode::AND => {
+                stmts.push(Stmt::Flags(Flags::Test(
+                    self.read(&op(0)),
+                    self.read(&op(1)),
+                )));
+                self.write(&op(0), binary(BinaryOp::And), stmts);
+            }
+            Opcode::CMP => {
+                stmts.push(Stmt::Flags(Flags::Compare(
+                    self.read(&op(0)),
+                    self.read(&op(1)),
+                )));
+            }
+            Opcode::TEST => {
+                stmts.push(Stmt::Flags(Flags::Test(
+                    self.read(&op(0)),
+                    self.read(&op(1)),
+                )));
+            }
+            Opcode::INC | Opcode::DEC => {
+                let binop = match self.opcode {
+                    Opcode::INC => BinaryOp::Add,
+                    _ => BinaryOp::Sub,
+                };
+
+                let value = Expr::binary(binop, self.read(&op(0)), Expr::Const(1));
+                stmts.push(Stmt::Flags(Flags::Result(value.clone())));
+                self.write(&op(0), value, stmts);
+            }
+            Opcode::NEG => {
+                let value = Expr::unary(UnaryOp::Neg, self.read(&op(0)));
+                stmts.push(Stmt::Flags(Flags::Result(value.clone())));
+                self.write(&op(0), value, stmts);
+            }
+            Opcode::NOT => {
+                let value = Expr::unary(UnaryOp::Not, self.read(&op(0)));
+                self.write(&op(0), value, stmts);
+            }
+            Opcode::SHL | Opcode::SAL | Opcode::SHR | Opcode::SAR | Opcode::ROR => {
+                let binop = match self.opcode {
+                    Opcode::SHL | Opcode::SAL => BinaryOp::Shl,
+                    Opcode::SHR => BinaryOp::Shr,
+                    Opcode::SAR => BinaryOp::Sar,
+                    _ => BinaryOp::Ror,
+                };
+
+                stmts.push(Stmt::Flags(Flags::Unknown));
+                self.write(&op(0), binary(binop), stmts);
+            }
+            Opcode::IMUL if self.operand_count > 1 => {
+                let value = match self.operand_count {
+                    2 => binary(BinaryOp::Mul),
+                    _ => Expr::binary(BinaryOp::Mul, self.read(&op(1)), self.read(&op(2))),
+                };
+
+                stmts.push(Stmt::Flags(Flags::Unknown));
+                self.write(&op(0), value, stmts);
+            }
+            Opcode::MUL | Opcode::IMUL | Opcode::DIV | Opcode::IDIV => {
+                stmts.push(Stmt::Flags(Flags::Unknown));
+                stmts.push(Stmt::Assign {
+                    dst: EAX,
+                    src: Expr::Unknown,
+                });
+                stmts.push(Stmt::Assign {
+                    dst: EDX,
+                    src: Expr::Unknown,
+                });
+            }
+            Opcode::CDQ => stmts.push(Stmt::Assign {
+                dst: EDX,
+                src: Expr::binary(BinaryOp::Sar, Expr::Reg(EAX), Expr::Const(31)),
+            }),
+            Opcode::PUSH => {
+                let value = self.read(&op(0));
+                let esp = Expr::binary(BinaryOp::Sub, Expr::Reg(ESP), Expr::Const(4));
+                stmts.push(Stmt::Store {
+                    addr: esp.clone(),
+                    size: 4,
+                    src: value,
+                });
+                stmts.push(Stmt::Assign { dst: ESP, src: esp });
+            }
+            Opcode::POP => {
+                self.write(&op(0), Expr::load(Expr::Reg(ESP), 4), stmts);
+                stmts.push(Stmt::Assign {
+                    dst: ESP,
+                    src: Expr::binary(BinaryOp::Add, Expr::Reg(ESP), Expr::Const(4)),
+                });
+            }
+            Opcode::LEAVE => {
+                stmts.push(Stmt::Assign {
+                    dst: ESP,
+                    src: Expr::Reg(EBP),
+                });
+                stmts.push(Stmt::Assign {
+                    dst: EBP,
+                    src: Expr::load(Expr::Reg(ESP), 4),
+                });
+                stmts.push(Stmt::Assign {
+                    dst: ESP,
+                    src: Expr::binary(BinaryOp::Add, Expr::Reg(ESP), Expr::Const(4)),
+                });
+            }
+            Opcode::JMP => stmts.push(Stmt::Jump(self.branch_target())),
+            Opcode::CALL => stmts.push(Stmt::Call(self.branch_target())),
+            Opcode::RETURN => stmts.push(Stmt::Return),
+            Opcode::LOOP => {
+                let ecx = Expr::binary(BinaryOp::Sub, Expr::Reg(ECX), Expr::Const(1));
+                stmts.push(Stmt::Assign { dst: ECX, src: ecx });
+                stmts.push(Stmt::Branch {
+                    cond: Cond::Compare(Condition::Ne, Expr::Reg(ECX), Expr::Const(0)),
+                    target: self.resolved(),
+                });
+            }
+            Opcode::JECXZ => stmts.push(Stmt::Branch {
+                cond: Cond::Compare(Condition::Eq, Expr::Reg(ECX), Expr::Const(0)),
+                target: self.resolved(),
+            }),
+            Opcode::HLT | Opcode::UD0 | Opcode::UD1 | Opcode::UD2 => stmts.push(Stmt::Halt),
+            _ => {
+                stmts.push(Stmt::Unknown);
+
+                // assume the first register operand is the destination
+                let first = (self.operand_count > 0).then(|| op(0));
+                if let Some(Operand::Register(spec)) = first {
+                    stmts.push(Stmt::Assign {
+                        dst: reg(spec),
+                        src: Expr::Unknown,
+                    });
+                }
+            }
+        }
+    }
+}
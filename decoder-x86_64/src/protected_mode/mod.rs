@@ -1,5 +1,6 @@
 mod display;
 mod evex;
+mod lower;
 mod tests;
 pub mod uarch;
 mod vex;
@@ -12,7 +13,7 @@ use crate::safer_unchecked::unreachable_kinda_unchecked as unreachable_unchecked
 pub use crate::MemoryAccessSize;
 
 use decoder::{Decoded, Decodable, Error, ErrorKind, Reader, ToTokens};
-use tokenizing::TokenStream;
+use tokenizing::{TokenKind, TokenStream};
 use debugvault::Index;
 use config::CONFIG;
 
@@ -506,7 +507,7 @@ impl SaeMode {
 impl ToTokens for SaeMode {
     fn tokenize(&self, stream: &mut TokenStream, _: &Index) {
         stream.push("{", CONFIG.colors.brackets);
-        stream.push(
+        stream.push_kind(
             match self {
                 SaeMode::RoundNearest => "rne-sae",
                 SaeMode::RoundDown => "rd-sae",
@@ -514,6 +515,7 @@ impl ToTokens for SaeMode {
                 SaeMode::RoundZero => "rz-sae",
             },
             CONFIG.colors.asm.register,
+            TokenKind::Register,
         );
         stream.push("}", CONFIG.colors.brackets);
     }
@@ -2543,6 +2545,8 @@ pub struct Instruction {
     disp: u32,
     opcode: Opcode,
     mem_size: u8,
+    /// Address of a relative branch target or of a memory operand, set by `update_rel_addrs`.
+    target: u32,
     imm_override: bool,
 }
 
@@ -2569,26 +2573,29 @@ impl Decoded for Instruction {
     }
 
     fn update_rel_addrs(&mut self, addr: usize, _: Option<&Instruction>) {
+        // other immediates are plain values, not offsets
+        let relative = self.opcode.is_relative_branch();
+
         for idx in 0..self.operand_count as usize {
             let operand = Operand::from_spec(&self, self.operands[idx]);
             let addr = addr as u32;
             let addr = match operand {
-                Operand::ImmediateI8(imm) => {
+                Operand::ImmediateI8(imm) if relative => {
                     addr.saturating_add(self.length as u32).saturating_add_signed(imm as i32)
                 }
-                Operand::ImmediateU8(imm) => {
+                Operand::ImmediateU8(imm) if relative => {
                     addr.saturating_add(self.length as u32).saturating_add(imm as u32)
                 }
-                Operand::ImmediateI16(imm) => {
+                Operand::ImmediateI16(imm) if relative => {
                     addr.saturating_add(self.length as u32).saturating_add_signed(imm as i32)
                 }
-                Operand::ImmediateU16(imm) => {
+                Operand::ImmediateU16(imm) if relative => {
                     addr.saturating_add(self.length as u32).saturating_add(imm as u32)
                 }
-                Operand::ImmediateI32(imm) => {
+                Operand::ImmediateI32(imm) if relative => {
                     addr.saturating_add(self.length as u32).saturating_add_signed(imm as i32)
                 }
-                Operand::ImmediateU32(imm) => {
+                Operand::ImmediateU32(imm) if relative => {
                     addr.saturating_add(self.length as u32).saturating_add(imm as u32)
                 }
                 Operand::DisplacementU32(imm) => addr.saturating_add(imm as u32),
@@ -2655,10 +2662,14 @@ impl Decoded for Instruction {
                 _ => continue,
             };
 
-            self.imm = addr;
+            self.target = addr;
             self.imm_override = true;
         }
     }
+
+    fn lower(&self, stmts: &mut Vec<decoder::ir::Stmt>) {
+        self.lower_into(stmts);
+    }
 }
 
 impl decoder::Decodable for Decoder {
@@ -4223,6 +4234,22 @@ impl Opcode {
         )
     }
 
+    /// check if the instruction's immediate is an offset from the next instruction, like the
+    /// targets of `jmp`, `call`, `jcc` and `loop`.
+    pub fn is_relative_branch(&self) -> bool {
+        self.is_jcc()
+            || matches!(
+                self,
+                Opcode::JMP
+                    | Opcode::CALL
+                    | Opcode::LOOP
+                    | Opcode::LOOPZ
+                    | Opcode::LOOPNZ
+                    | Opcode::JECXZ
+                    | Opcode::XBEGIN
+            )
+    }
+
     /// get the [`ConditionCode`] for this instruction, if it is in fact conditional. x86's
     /// conditional instructions are `Jcc`, `CMOVcc`, andd `SETcc`.
     pub fn condition(&self) -> Option<ConditionCode> {
@@ -4322,6 +4349,7 @@ impl Instruction {
             imm: 0,
             operand_count: 0,
             operands: [OperandSpec::Nothing; 4],
+            target: 0,
             imm_override: false,
         }
     }
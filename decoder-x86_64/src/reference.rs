@@ -0,0 +1,465 @@
+//! Reference of common x86 instructions, shared between [`long_mode`] and [`protected_mode`].
+//!
+//! [`long_mode`]: crate::long_mode
+//! [`protected_mode`]: crate::protected_mode
+
+use decoder::Reference;
+
+const ARITHMETIC_FLAGS: &str = "OF SF ZF AF PF CF";
+const LOGIC_FLAGS: &str = "OF=0 CF=0, SF ZF PF set, AF undefined";
+const SHIFT_FLAGS: &str = "CF = last bit shifted out, OF SF ZF PF set";
+const ROTATE_FLAGS: &str = "CF = last bit rotated, OF set for single bit rotates";
+
+/// Sorted by mnemonic.
+static REFERENCE: &[Reference] = &[
+    Reference {
+        mnemonic: "adc",
+        operation: "Add with carry: dst = dst + src + CF.",
+        flags: ARITHMETIC_FLAGS,
+        forms: &["r/m, r", "r, r/m", "r/m, imm"],
+    },
+    Reference {
+        mnemonic: "add",
+        operation: "Add: dst = dst + src.",
+        flags: ARITHMETIC_FLAGS,
+        forms: &["r/m, r", "r, r/m", "r/m, imm"],
+    },
+    Reference {
+        mnemonic: "and",
+        operation: "Bitwise and: dst = dst & src.",
+        flags: LOGIC_FLAGS,
+        forms: &["r/m, r", "r, r/m", "r/m, imm"],
+    },
+    Reference {
+        mnemonic: "bsf",
+        operation: "Bit scan forward: dst = index of the lowest set bit of src.",
+        flags: "ZF = src is zero, others undefined",
+        forms: &["r, r/m"],
+    },
+    Reference {
+        mnemonic: "bsr",
+        operation: "Bit scan reverse: dst = index of the highest set bit of src.",
+        flags: "ZF = src is zero, others undefined",
+        forms: &["r, r/m"],
+    },
+    Reference {
+        mnemonic: "bswap",
+        operation: "Reverse the byte order of a register.",
+        flags: "",
+        forms: &["r32", "r64"],
+    },
+    Reference {
+        mnemonic: "bt",
+        operation: "Bit test: CF = bit of dst selected by src.",
+        flags: "CF",
+        forms: &["r/m, r", "r/m, imm8"],
+    },
+    Reference {
+        mnemonic: "call",
+        operation: "Push the address of the next instruction and jump to the target.",
+        flags: "",
+        forms: &["rel32", "r/m"],
+    },
+    Reference {
+        mnemonic: "cdq",
+        operation: "Sign extend eax into edx:eax.",
+        flags: "",
+        forms: &[""],
+    },
+    Reference {
+        mnemonic: "cdqe",
+        operation: "Sign extend eax into rax.",
+        flags: "",
+        forms: &[""],
+    },
+    Reference {
+        mnemonic: "clc",
+        operation: "Clear the carry flag.",
+        flags: "CF=0",
+        forms: &[""],
+    },
+    Reference {
+        mnemonic: "cld",
+        operation: "Clear the direction flag, string instructions increment their pointers.",
+        flags: "DF=0",
+        forms: &[""],
+    },
+    Reference {
+        mnemonic: "cmc",
+        operation: "Complement the carry flag.",
+        flags: "CF",
+        forms: &[""],
+    },
+    Reference {
+        mnemonic: "cmovcc",
+        operation: "Conditional move: dst = src if the condition holds.",
+        flags: "reads the flags of the condition",
+        forms: &["r, r/m"],
+    },
+    Reference {
+        mnemonic: "cmp",
+        operation: "Compare: computes dst - src and discards the result.",
+        flags: ARITHMETIC_FLAGS,
+        forms: &["r/m, r", "r, r/m", "r/m, imm"],
+    },
+    Reference {
+        mnemonic: "cmpxchg",
+        operation: "Compare and exchange: if accumulator == dst then dst = src, \
+                    else accumulator = dst.",
+        flags: ARITHMETIC_FLAGS,
+        forms: &["r/m, r"],
+    },
+    Reference {
+        mnemonic: "cpuid",
+        operation: "Query processor identification and features selected by eax and ecx.",
+        flags: "",
+        forms: &[""],
+    },
+    Reference {
+        mnemonic: "cqo",
+        operation: "Sign extend rax into rdx:rax.",
+        flags: "",
+        forms: &[""],
+    },
+    Reference {
+        mnemonic: "dec",
+        operation: "Decrement: dst = dst - 1.",
+        flags: "OF SF ZF AF PF, CF unaffected",
+        forms: &["r/m"],
+    },
+    Reference {
+        mnemonic: "div",
+        operation: "Unsigned divide of rdx:rax (or a narrower pair) by src, \
+                    quotient in rax and remainder in rdx.",
+        flags: "undefined",
+        forms: &["r/m"],
+    },
+    Reference {
+        mnemonic: "endbr64",
+        operation: "Marks a valid target of an indirect branch (CET), otherwise a nop.",
+        flags: "",
+        forms: &[""],
+    },
+    Reference {
+        mnemonic: "hlt",
+        operation: "Halt the processor until the next interrupt.",
+        flags: "",
+        forms: &[""],
+    },
+    Reference {
+        mnemonic: "idiv",
+        operation: "Signed divide of rdx:rax (or a narrower pair) by src, \
+                    quotient in rax and remainder in rdx.",
+        flags: "undefined",
+        forms: &["r/m"],
+    },
+    Reference {
+        mnemonic: "imul",
+        operation: "Signed multiply.",
+        flags: "OF CF = result was truncated, others undefined",
+        forms: &["r/m", "r, r/m", "r, r/m, imm"],
+    },
+    Reference {
+        mnemonic: "inc",
+        operation: "Increment: dst = dst + 1.",
+        flags: "OF SF ZF AF PF, CF unaffected",
+        forms: &["r/m"],
+    },
+    Reference {
+        mnemonic: "int",
+        operation: "Call the interrupt handler selected by the immediate.",
+        flags: "IF TF cleared",
+        forms: &["imm8"],
+    },
+    Reference {
+        mnemonic: "int3",
+        operation: "Breakpoint trap.",
+        flags: "IF TF cleared",
+        forms: &[""],
+    },
+    Reference {
+        mnemonic: "jcc",
+        operation: "Jump to the target if the condition holds.",
+        flags: "reads the flags of the condition",
+        forms: &["rel8", "rel32"],
+    },
+    Reference {
+        mnemonic: "jmp",
+        operation: "Jump to the target.",
+        flags: "",
+        forms: &["rel8", "rel32", "r/m"],
+    },
+    Reference {
+        mnemonic: "lea",
+        operation: "Load effective address: dst = address of the memory operand.",
+        flags: "",
+        forms: &["r, m"],
+    },
+    Reference {
+        mnemonic: "leave",
+        operation: "Tear down the stack frame: rsp = rbp, pop rbp.",
+        flags: "",
+        forms: &[""],
+    },
+    Reference {
+        mnemonic: "mov",
+        operation: "Move: dst = src.",
+        flags: "",
+        forms: &["r/m, r", "r, r/m", "r/m, imm", "r64, imm64"],
+    },
+    Reference {
+        mnemonic: "movaps",
+        operation: "Move aligned packed single precision floats.",
+        flags: "",
+        forms: &["xmm, xmm/m128", "xmm/m128, xmm"],
+    },
+    Reference {
+        mnemonic: "movd",
+        operation: "Move a doubleword between a general purpose and a vector register.",
+        flags: "",
+        forms: &["xmm, r/m32", "r/m32, xmm"],
+    },
+    Reference {
+        mnemonic: "movdqa",
+        operation: "Move aligned packed integers.",
+        flags: "",
+        forms: &["xmm, xmm/m128", "xmm/m128, xmm"],
+    },
+    Reference {
+        mnemonic: "movdqu",
+        operation: "Move unaligned packed integers.",
+        flags: "",
+        forms: &["xmm, xmm/m128", "xmm/m128, xmm"],
+    },
+    Reference {
+        mnemonic: "movq",
+        operation: "Move a quadword between a general purpose and a vector register.",
+        flags: "",
+        forms: &["xmm, r/m64", "r/m64, xmm", "xmm, xmm/m64"],
+    },
+    Reference {
+        mnemonic: "movsx",
+        operation: "Move with sign extension.",
+        flags: "",
+        forms: &["r, r/m8", "r, r/m16"],
+    },
+    Reference {
+        mnemonic: "movsxd",
+        operation: "Move doubleword with sign extension.",
+        flags: "",
+        forms: &["r64, r/m32"],
+    },
+    Reference {
+        mnemonic: "movups",
+        operation: "Move unaligned packed single precision floats.",
+        flags: "",
+        forms: &["xmm, xmm/m128", "xmm/m128, xmm"],
+    },
+    Reference {
+        mnemonic: "movzx",
+        operation: "Move with zero extension.",
+        flags: "",
+        forms: &["r, r/m8", "r, r/m16"],
+    },
+    Reference {
+        mnemonic: "mul",
+        operation: "Unsigned multiply of the accumulator by src into rdx:rax.",
+        flags: "OF CF = upper half is non-zero, others undefined",
+        forms: &["r/m"],
+    },
+    Reference {
+        mnemonic: "neg",
+        operation: "Two's complement negation: dst = -dst.",
+        flags: "CF = dst was non-zero, OF SF ZF AF PF set",
+        forms: &["r/m"],
+    },
+    Reference {
+        mnemonic: "nop",
+        operation: "No operation.",
+        flags: "",
+        forms: &["", "r/m"],
+    },
+    Reference {
+        mnemonic: "not",
+        operation: "Bitwise not: dst = !dst.",
+        flags: "",
+        forms: &["r/m"],
+    },
+    Reference {
+        mnemonic: "or",
+        operation: "Bitwise or: dst = dst | src.",
+        flags: LOGIC_FLAGS,
+        forms: &["r/m, r", "r, r/m", "r/m, imm"],
+    },
+    Reference {
+        mnemonic: "pop",
+        operation: "Load the value on top of the stack into dst and increment the stack pointer.",
+        flags: "",
+        forms: &["r", "r/m"],
+    },
+    Reference {
+        mnemonic: "push",
+        operation: "Decrement the stack pointer and store src on top of the stack.",
+        flags: "",
+        forms: &["r", "r/m", "imm"],
+    },
+    Reference {
+        mnemonic: "pxor",
+        operation: "Bitwise xor of packed integers.",
+        flags: "",
+        forms: &["xmm, xmm/m128"],
+    },
+    Reference {
+        mnemonic: "rdtsc",
+        operation: "Read the time stamp counter into edx:eax.",
+        flags: "",
+        forms: &[""],
+    },
+    Reference {
+        mnemonic: "ret",
+        operation: "Pop the return address and jump to it, optionally releasing imm16 bytes.",
+        flags: "",
+        forms: &["", "imm16"],
+    },
+    Reference {
+        mnemonic: "rol",
+        operation: "Rotate left.",
+        flags: ROTATE_FLAGS,
+        forms: &["r/m, 1", "r/m, cl", "r/m, imm8"],
+    },
+    Reference {
+        mnemonic: "ror",
+        operation: "Rotate right.",
+        flags: ROTATE_FLAGS,
+        forms: &["r/m, 1", "r/m, cl", "r/m, imm8"],
+    },
+    Reference {
+        mnemonic: "sar",
+        operation: "Arithmetic shift right, preserving the sign.",
+        flags: SHIFT_FLAGS,
+        forms: &["r/m, 1", "r/m, cl", "r/m, imm8"],
+    },
+    Reference {
+        mnemonic: "sbb",
+        operation: "Subtract with borrow: dst = dst - (src + CF).",
+        flags: ARITHMETIC_FLAGS,
+        forms: &["r/m, r", "r, r/m", "r/m, imm"],
+    },
+    Reference {
+        mnemonic: "setcc",
+        operation: "Set byte to 1 if the condition holds, otherwise to 0.",
+        flags: "reads the flags of the condition",
+        forms: &["r/m8"],
+    },
+    Reference {
+        mnemonic: "shl",
+        operation: "Logical shift left.",
+        flags: SHIFT_FLAGS,
+        forms: &["r/m, 1", "r/m, cl", "r/m, imm8"],
+    },
+    Reference {
+        mnemonic: "shr",
+        operation: "Logical shift right.",
+        flags: SHIFT_FLAGS,
+        forms: &["r/m, 1", "r/m, cl", "r/m, imm8"],
+    },
+    Reference {
+        mnemonic: "stc",
+        operation: "Set the carry flag.",
+        flags: "CF=1",
+        forms: &[""],
+    },
+    Reference {
+        mnemonic: "std",
+        operation: "Set the direction flag, string instructions decrement their pointers.",
+        flags: "DF=1",
+        forms: &[""],
+    },
+    Reference {
+        mnemonic: "sub",
+        operation: "Subtract: dst = dst - src.",
+        flags: ARITHMETIC_FLAGS,
+        forms: &["r/m, r", "r, r/m", "r/m, imm"],
+    },
+    Reference {
+        mnemonic: "syscall",
+        operation: "Fast call into the operating system, rcx = return address, r11 = rflags.",
+        flags: "",
+        forms: &[""],
+    },
+    Reference {
+        mnemonic: "test",
+        operation: "Bitwise and of both operands, discarding the result.",
+        flags: LOGIC_FLAGS,
+        forms: &["r/m, r", "r/m, imm"],
+    },
+    Reference {
+        mnemonic: "tzcnt",
+        operation: "Count the number of trailing zero bits.",
+        flags: "CF = src is zero, ZF = result is zero",
+        forms: &["r, r/m"],
+    },
+    Reference {
+        mnemonic: "ud2",
+        operation: "Raise an invalid opcode exception.",
+        flags: "",
+        forms: &[""],
+    },
+    Reference {
+        mnemonic: "xadd",
+        operation: "Exchange and add: tmp = dst + src, src = dst, dst = tmp.",
+        flags: ARITHMETIC_FLAGS,
+        forms: &["r/m, r"],
+    },
+    Reference {
+        mnemonic: "xchg",
+        operation: "Exchange both operands, implicitly locked for memory operands.",
+        flags: "",
+        forms: &["r/m, r"],
+    },
+    Reference {
+        mnemonic: "xor",
+        operation: "Bitwise exclusive or: dst = dst ^ src.",
+        flags: LOGIC_FLAGS,
+        forms: &["r/m, r", "r, r/m", "r/m, imm"],
+    },
+];
+
+/// Look up the reference of a mnemonic as it's displayed, e.g. `lock cmpxchg` or `jnz`.
+pub fn reference(mnemonic: &str) -> Option<&'static Reference> {
+    // skip prefixes such as `lock` and `rep`
+    let mnemonic = mnemonic.split_whitespace().last()?;
+
+    let family = match mnemonic {
+        "jmp" | "jmpf" | "jmpe" | "jrcxz" | "jecxz" | "setssbsy" => mnemonic,
+        _ if mnemonic.starts_with("cmov") => "cmovcc",
+        _ if mnemonic.starts_with("set") => "setcc",
+        _ if mnemonic.starts_with('j') => "jcc",
+        _ => mnemonic,
+    };
+
+    decoder::lookup_reference(REFERENCE, family)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sorted() {
+        assert!(REFERENCE.windows(2).all(|pair| pair[0].mnemonic < pair[1].mnemonic));
+    }
+
+    #[test]
+    fn lookup() {
+        assert_eq!(
+            reference("lock cmpxchg").map(|r| r.mnemonic),
+            Some("cmpxchg")
+        );
+        assert_eq!(reference("jnz").map(|r| r.mnemonic), Some("jcc"));
+        assert_eq!(reference("jmp").map(|r| r.mnemonic), Some("jmp"));
+        assert_eq!(reference("cmovge").map(|r| r.mnemonic), Some("cmovcc"));
+        assert_eq!(reference("setnz").map(|r| r.mnemonic), Some("setcc"));
+        assert_eq!(reference("vfmadd231ps"), None);
+    }
+}
@@ -0,0 +1,244 @@
+//! Architecture independent description of what instructions do.
+//!
+//! Every decoder lowers its instructions into a handful of [`Stmt`]s, so analyses can reason about
+//! loads, stores, arithmetic and control flow without knowing about any particular instruction
+//! set. Lowering is best effort: anything that isn't modelled becomes [`Stmt::Unknown`], and a
+//! register that's written in a way that isn't modelled is assigned [`Expr::Unknown`].
+
+/// General purpose register, or part of one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Reg {
+    /// Name of the full register, e.g. `rax` when accessing `ah`.
+    pub name: &'static str,
+    /// Number of bytes accessed.
+    pub size: u8,
+    /// Offset in bytes of the accessed part, e.g. `1` for `ah`.
+    pub offset: u8,
+}
+
+impl Reg {
+    /// Access an entire register.
+    pub const fn full(name: &'static str, size: u8) -> Self {
+        Self {
+            name,
+            size,
+            offset: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOp {
+    /// Two's complement negation.
+    Neg,
+    /// Bitwise complement.
+    Not,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    /// Unsigned division.
+    Div,
+    /// Signed division.
+    SignedDiv,
+    /// Unsigned remainder.
+    Rem,
+    /// Signed remainder.
+    SignedRem,
+    And,
+    Or,
+    Xor,
+    Shl,
+    /// Logical shift right.
+    Shr,
+    /// Arithmetic shift right.
+    Sar,
+    /// Rotate right.
+    Ror,
+}
+
+/// Relation between two values, or between the operands of the last [`Stmt::Flags`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Condition {
+    Eq,
+    Ne,
+    /// Signed less than.
+    Lt,
+    /// Signed less than or equal.
+    Le,
+    /// Signed greater than.
+    Gt,
+    /// Signed greater than or equal.
+    Ge,
+    /// Unsigned less than.
+    Below,
+    /// Unsigned less than or equal.
+    BelowEq,
+    /// Unsigned greater than.
+    Above,
+    /// Unsigned greater than or equal.
+    AboveEq,
+    /// The result is negative.
+    Negative,
+    /// The result isn't negative.
+    NotNegative,
+    /// The computation overflowed.
+    Overflow,
+    /// The computation didn't overflow.
+    NoOverflow,
+    /// The lowest byte of the result has an even number of set bits.
+    Parity,
+    /// The lowest byte of the result has an odd number of set bits.
+    NoParity,
+}
+
+impl Condition {
+    /// Condition that holds exactly when this one doesn't.
+    pub fn negate(self) -> Self {
+        match self {
+            Self::Eq => Self::Ne,
+            Self::Ne => Self::Eq,
+            Self::Lt => Self::Ge,
+            Self::Le => Self::Gt,
+            Self::Gt => Self::Le,
+            Self::Ge => Self::Lt,
+            Self::Below => Self::AboveEq,
+            Self::BelowEq => Self::Above,
+            Self::Above => Self::BelowEq,
+            Self::AboveEq => Self::Below,
+            Self::Negative => Self::NotNegative,
+            Self::NotNegative => Self::Negative,
+            Self::Overflow => Self::NoOverflow,
+            Self::NoOverflow => Self::Overflow,
+            Self::Parity => Self::NoParity,
+            Self::NoParity => Self::Parity,
+        }
+    }
+}
+
+/// Condition of a branch or a conditional value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cond {
+    /// Condition derived from the flags set by the last [`Stmt::Flags`].
+    Flags(Condition),
+    /// Direct comparison between two values, for architectures without flags or instructions such
+    /// as `cbz` that don't use them.
+    Compare(Condition, Expr, Expr),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    Const(u64),
+    Reg(Reg),
+    /// Zero extended value of `size` bytes read from memory.
+    Load {
+        addr: Box<Expr>,
+        size: u8,
+    },
+    Unary(UnaryOp, Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    /// Lowest number of bytes of a value, zero extended.
+    Truncate(Box<Expr>, u8),
+    /// Lowest number of bytes of a value, sign extended.
+    SignExtend(Box<Expr>, u8),
+    /// One if the condition holds, zero otherwise.
+    Cond(Box<Cond>),
+    /// First value if the condition holds, second value otherwise.
+    Select(Box<Cond>, Box<Expr>, Box<Expr>),
+    /// Value that isn't modelled.
+    Unknown,
+}
+
+impl Expr {
+    pub fn reg(reg: Reg) -> Self {
+        Self::Reg(reg)
+    }
+
+    pub fn load(addr: Expr, size: u8) -> Self {
+        Self::Load {
+            addr: Box::new(addr),
+            size,
+        }
+    }
+
+    pub fn unary(op: UnaryOp, value: Expr) -> Self {
+        Self::Unary(op, Box::new(value))
+    }
+
+    pub fn binary(op: BinaryOp, lhs: Expr, rhs: Expr) -> Self {
+        Self::Binary(op, Box::new(lhs), Box::new(rhs))
+    }
+
+    pub fn truncate(value: Expr, size: u8) -> Self {
+        Self::Truncate(Box::new(value), size)
+    }
+
+    pub fn sign_extend(value: Expr, size: u8) -> Self {
+        Self::SignExtend(Box::new(value), size)
+    }
+
+    pub fn cond(cond: Cond) -> Self {
+        Self::Cond(Box::new(cond))
+    }
+
+    pub fn select(cond: Cond, then: Expr, otherwise: Expr) -> Self {
+        Self::Select(Box::new(cond), Box::new(then), Box::new(otherwise))
+    }
+
+    /// Sum of two values, leaving out additions of zero.
+    pub fn sum(lhs: Expr, rhs: Expr) -> Self {
+        match (lhs, rhs) {
+            (value, Self::Const(0)) | (Self::Const(0), value) => value,
+            (lhs, rhs) => Self::binary(BinaryOp::Add, lhs, rhs),
+        }
+    }
+
+    /// Value of the expression if it doesn't depend on registers or memory.
+    pub fn as_const(&self) -> Option<u64> {
+        match self {
+            Self::Const(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+/// How the condition flags are set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Flags {
+    /// Flags of subtracting the second value from the first, like `cmp`.
+    Compare(Expr, Expr),
+    /// Flags of the bitwise and of two values, like `test`.
+    Test(Expr, Expr),
+    /// Flags describing a value, typically the result of an arithmetic instruction.
+    Result(Expr),
+    /// Flags are changed in a way that isn't modelled.
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Stmt {
+    /// Write a value to a register.
+    ///
+    /// Writing part of a register leaves the rest of it unchanged. Instructions that clear the
+    /// upper half of a register are lowered to an assignment of the full register.
+    Assign { dst: Reg, src: Expr },
+    /// Write a value of `size` bytes to memory.
+    Store { addr: Expr, size: u8, src: Expr },
+    /// Update the condition flags.
+    Flags(Flags),
+    /// Unconditionally continue execution at an address.
+    Jump(Expr),
+    /// Continue execution at an address if the condition holds.
+    Branch { cond: Cond, target: Expr },
+    /// Call the function at an address.
+    Call(Expr),
+    /// Return to the caller.
+    Return,
+    /// Stop execution, e.g. by trapping.
+    Halt,
+    /// Behaviour that isn't modelled.
+    Unknown,
+}
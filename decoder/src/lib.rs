@@ -1,5 +1,7 @@
 //! Shared behaviour required between decoder crates.
 
+pub mod ir;
+
 use std::fmt::Debug;
 use debugvault::Index;
 use tokenizing::{TokenStream, Token};
@@ -56,6 +58,27 @@ pub enum ErrorKind {
     Unpredictable,
 }
 
+/// Explanation of an instruction mnemonic.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Reference {
+    /// Mnemonic being described.
+    pub mnemonic: &'static str,
+    /// What the instruction does.
+    pub operation: &'static str,
+    /// Flags the instruction reads or modifies, empty if it doesn't touch any.
+    pub flags: &'static str,
+    /// Operand forms the mnemonic accepts.
+    pub forms: &'static [&'static str],
+}
+
+/// Find the [`Reference`] of a mnemonic in a table that's sorted by mnemonic.
+pub fn lookup_reference(table: &'static [Reference], mnemonic: &str) -> Option<&'static Reference> {
+    table
+        .binary_search_by_key(&mnemonic, |reference| reference.mnemonic)
+        .ok()
+        .map(|idx| &table[idx])
+}
+
 pub trait ToTokens {
     fn tokenize(&self, stream: &mut TokenStream, symbols: &Index);
 }
@@ -68,6 +91,8 @@ pub trait Decoded: ToTokens {
         stream.inner
     }
     fn update_rel_addrs(&mut self, addr: usize, prev_inst: Option<&Self>);
+    /// Append the architecture independent effects of the instruction, see [`ir`].
+    fn lower(&self, stmts: &mut Vec<ir::Stmt>);
 }
 
 pub trait Decodable {
@@ -1,4 +1,8 @@
-pub const FONT: egui::FontId = egui::FontId::new(14.0, egui::FontFamily::Monospace);
+/// Monospace font used by the listing and most panes, sized according to the settings.
+pub fn font() -> egui::FontId {
+    let size = config::settings::SETTINGS.read().unwrap().font_size;
+    egui::FontId::new(size, egui::FontFamily::Monospace)
+}
 
 pub struct Timer {
     start: std::time::Instant,
@@ -34,7 +38,7 @@ pub fn tokens_to_layoutjob(tokens: Vec<tokenizing::Token>) -> egui::text::Layout
             &token.text,
             0.0,
             egui::TextFormat {
-                font_id: FONT,
+                font_id: font(),
                 color: token.color,
                 ..Default::default()
             },
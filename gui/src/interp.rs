@@ -1,5 +1,6 @@
 use crate::tprint;
 use commands::{Command, CommandError};
+use debugger::Watch;
 
 impl super::UI {
     /// Runs all queued commands, returning if they trigger a process exit.
@@ -11,9 +12,17 @@ impl super::UI {
     fn process_cmd(&mut self, cmd: &str) -> bool {
         let empty_index = debugvault::Index::default();
         let index = self.panels.processor().map(|proc| &proc.index).unwrap_or(&empty_index);
+        let mut command = Command::parse(index, cmd, 0);
 
-        match Command::parse(index, cmd, 0) {
-            Ok(Command::Load(path)) => self.offload_binary_processing(path),
+        // symbols that aren't in the binary might be in a module the debugged target loaded
+        if let (Err(_), Some(symbols)) = (&command, self.panels.module_symbols()) {
+            if let Ok(parsed) = Command::parse(symbols, cmd, 0) {
+                command = Ok(parsed);
+            }
+        }
+
+        match command {
+            Ok(Command::Load(path)) => self.offload_binary_processing(path, false),
             Ok(Command::PrintPath) => match std::env::current_dir() {
                 Ok(path) => tprint!(
                     self.panels.terminal(),
@@ -53,10 +62,259 @@ impl super::UI {
                 }
 
                 self.panels.load_src(addr);
+                self.panels.load_pseudo_code(addr);
+            }
+            Ok(Command::Rename(addr, name)) => match self.panels.project().cloned() {
+                Some(project) => {
+                    project.write().rename(addr, name.as_str());
+                    tprint!(self.panels.terminal(), "Renamed {addr:#X} to '{name}'.");
+                }
+                None => tprint!(self.panels.terminal(), "No targets loaded."),
+            },
+            Ok(Command::Comment(addr, comment)) => match self.panels.project().cloned() {
+                Some(project) => project.write().comment(addr, comment),
+                None => tprint!(self.panels.terminal(), "No targets loaded."),
+            },
+            Ok(Command::Bookmark(addr, desc)) => match self.panels.project().cloned() {
+                Some(project) => {
+                    project.write().bookmark(addr, desc);
+                    tprint!(self.panels.terminal(), "Bookmarked address {addr:#X}.");
+                    self.panels.goto_window(crate::panes::BOOKMARKS);
+                }
+                None => tprint!(self.panels.terminal(), "No targets loaded."),
+            },
+            Ok(Command::Patch(addr, bytes)) => {
+                let project = match self.panels.project().cloned() {
+                    Some(project) => project,
+                    None => {
+                        tprint!(self.panels.terminal(), "No targets loaded.");
+                        return true;
+                    }
+                };
+
+                let patched = match self.panels.listing() {
+                    Some(listing) => listing.patch(addr, &bytes),
+                    None => false,
+                };
+
+                if patched {
+                    let len = bytes.len();
+                    tprint!(self.panels.terminal(), "Patched {len} bytes at {addr:#X}.");
+                    project.write().patch(addr, bytes);
+                } else {
+                    tprint!(self.panels.terminal(), "Address {addr:#X} isn't code.");
+                }
+            }
+            Ok(Command::Struct(def)) => {
+                let (processor, project) = match (
+                    self.panels.processor().cloned(),
+                    self.panels.project().cloned(),
+                ) {
+                    (Some(processor), Some(project)) => (processor, project),
+                    _ => {
+                        tprint!(self.panels.terminal(), "No targets loaded.");
+                        return true;
+                    }
+                };
+
+                match processor.define_structs(&def) {
+                    Ok(names) => {
+                        let mut project = project.write();
+                        for name in &names {
+                            if let Some(def) = processor.struct_by_name(name) {
+                                project.define_struct(name, def.to_string());
+                            }
+                        }
+
+                        tprint!(self.panels.terminal(), "Defined {}.", names.join(", "));
+                    }
+                    Err(err) => tprint!(self.panels.terminal(), "{err}"),
+                }
+            }
+            Ok(Command::Structs) => match self.panels.processor() {
+                Some(processor) => {
+                    let names = processor.struct_names();
+                    if names.is_empty() {
+                        tprint!(self.panels.terminal(), "No structures defined.");
+                    } else {
+                        tprint!(self.panels.terminal(), "{}", names.join(", "));
+                    }
+                }
+                None => tprint!(self.panels.terminal(), "No targets loaded."),
+            },
+            Ok(Command::Enum(def)) => {
+                let (processor, project) = match (
+                    self.panels.processor().cloned(),
+                    self.panels.project().cloned(),
+                ) {
+                    (Some(processor), Some(project)) => (processor, project),
+                    _ => {
+                        tprint!(self.panels.terminal(), "No targets loaded.");
+                        return true;
+                    }
+                };
+
+                match processor.define_enums(&def) {
+                    Ok(names) => {
+                        let mut project = project.write();
+                        for name in &names {
+                            if let Some(def) = processor.enum_by_name(name) {
+                                project.define_enum(name, def.to_string());
+                            }
+                        }
+
+                        tprint!(self.panels.terminal(), "Defined {}.", names.join(", "));
+                    }
+                    Err(err) => tprint!(self.panels.terminal(), "{err}"),
+                }
+            }
+            Ok(Command::Enums) => match self.panels.processor() {
+                Some(processor) => {
+                    let names = processor.enum_names();
+                    tprint!(self.panels.terminal(), "{}", names.join(", "));
+                }
+                None => tprint!(self.panels.terminal(), "No targets loaded."),
+            },
+            Ok(Command::Apply(addr, name)) => {
+                let (processor, project) = match (
+                    self.panels.processor().cloned(),
+                    self.panels.project().cloned(),
+                ) {
+                    (Some(processor), Some(project)) => (processor, project),
+                    _ => {
+                        tprint!(self.panels.terminal(), "No targets loaded.");
+                        return true;
+                    }
+                };
+
+                // instructions can only have enumerations applied, data only structures
+                let is_enum = match name.as_str() {
+                    "" => processor.enum_at(addr).is_some(),
+                    name => processor.enum_by_name(name).is_some(),
+                };
+
+                let applied = match self.panels.listing() {
+                    Some(listing) if is_enum => listing.apply_enum(addr, &name),
+                    Some(listing) => listing.apply_struct(addr, &name),
+                    None => false,
+                };
+
+                if applied && is_enum {
+                    project.write().apply_enum(addr, name);
+                } else if applied {
+                    project.write().apply_struct(addr, name);
+                } else if name.is_empty() {
+                    tprint!(self.panels.terminal(), "Nothing is applied at {addr:#X}.");
+                } else if is_enum {
+                    tprint!(self.panels.terminal(), "Address {addr:#X} isn't code.");
+                } else {
+                    tprint!(
+                        self.panels.terminal(),
+                        "Structure '{name}' is unknown or doesn't fit in the data at {addr:#X}."
+                    );
+                }
+            }
+            Ok(cmd @ (Command::Back | Command::Forward)) => {
+                let listing = match self.panels.listing() {
+                    Some(listing) => listing,
+                    None => {
+                        tprint!(self.panels.terminal(), "No targets loaded.");
+                        return true;
+                    }
+                };
+
+                let moved = match cmd {
+                    Command::Back => listing.go_back(),
+                    _ => listing.go_forward(),
+                };
+
+                if !moved {
+                    tprint!(self.panels.terminal(), "No more locations in history.");
+                }
+            }
+            Ok(Command::AddressMode(mode)) => match self.panels.listing() {
+                Some(listing) => listing.set_address_mode(mode),
+                None => tprint!(self.panels.terminal(), "No targets loaded."),
+            },
+            Ok(Command::RegisterNames(names)) => match self.panels.listing() {
+                Some(listing) => listing.set_register_names(names),
+                None => tprint!(self.panels.terminal(), "No targets loaded."),
+            },
+            Ok(Command::Save) => {
+                if self.panels.project().is_none() {
+                    tprint!(self.panels.terminal(), "No targets loaded.");
+                    return true;
+                }
+
+                self.panels.save_project();
+                tprint!(self.panels.terminal(), "Saved project.");
+            }
+            Ok(Command::Export(path)) => match self.panels.listing() {
+                Some(listing) => match listing.export_html(&path) {
+                    Ok(()) => tprint!(
+                        self.panels.terminal(),
+                        "Exported listing to {}.",
+                        path.display()
+                    ),
+                    Err(err) => tprint!(self.panels.terminal(), "Failed to export listing: {err}."),
+                },
+                None => tprint!(self.panels.terminal(), "No targets loaded."),
+            },
+            Ok(Command::ExportSymbols(path)) => self.panels.export_symbols(&path),
+            Ok(Command::Import(path)) => self.panels.import_analysis(&path),
+            Ok(Command::Script(path)) => {
+                if !commands::Feature::Scripting.enabled() {
+                    tprint!(self.panels.terminal(), "Scripting is disabled.");
+                    return true;
+                }
+
+                let processor = self.panels.processor().cloned();
+                let project = self.panels.project().cloned();
+                let (processor, project) = match (processor, project) {
+                    (Some(processor), Some(project)) => (processor, project),
+                    _ => {
+                        tprint!(self.panels.terminal(), "No targets loaded.");
+                        return true;
+                    }
+                };
+
+                let binary = scripting::Binary::new(processor, &project.read());
+                let result = self.panels.scripts().run_file(&path, &binary);
+                let edits = binary.apply(&mut project.write());
+
+                match result {
+                    Ok(()) => tprint!(self.panels.terminal(), "Script made {edits} changes."),
+                    Err(err) => tprint!(self.panels.terminal(), "{err}"),
+                }
             }
+            Ok(Command::Plugins) => self.panels.show_plugins(),
+            Ok(Command::Connect(addr)) => self.panels.connect(&addr),
+            Ok(Command::Launch(path)) => self.panels.launch(&path),
+            Ok(Command::Attach(pid)) => self.attach(pid),
+            Ok(Command::Detach) => self.panels.detach(),
+            Ok(Command::Continue) => self.panels.resume(),
+            Ok(Command::Step) => self.panels.step(),
+            Ok(Command::Stop) => self.panels.interrupt(),
+            Ok(Command::Break(addr)) => self.panels.toggle_breakpoint(addr),
+            Ok(Command::Condition(addr, condition)) => self.panels.set_condition(addr, &condition),
+            Ok(Command::Watch(addr)) => self.panels.toggle_watchpoint(addr, Watch::Write),
+            Ok(Command::ReadWatch(addr)) => self.panels.toggle_watchpoint(addr, Watch::Read),
+            Ok(Command::AccessWatch(addr)) => self.panels.toggle_watchpoint(addr, Watch::Access),
+            Ok(Command::Sync) => self.panels.sync_memory(),
+            Ok(Command::Memory(addr)) => self.panels.show_memory(addr),
+            Ok(Command::Write(addr, bytes)) => self.panels.write_memory(addr, &bytes),
+            Ok(Command::Trace) => self.panels.toggle_trace(),
+            Ok(Command::SaveTrace(path)) => self.panels.save_trace(&path),
+            Ok(Command::LoadTrace(path)) => self.panels.load_trace(&path),
+            Ok(Command::ClearTrace) => self.panels.clear_trace(),
+            Ok(Command::Emulate(addr, args)) => self.panels.emulate(addr, args),
+            Ok(Command::Timings(path)) => self.panels.timings(path.as_deref()),
+            Ok(Command::Overlay(path)) => self.panels.overlay(path.as_deref()),
+            Ok(Command::Carve(dir)) => self.panels.carve(dir.as_deref()),
+            Ok(Command::Crypto) => self.panels.crypto(),
             Ok(Command::Quit) => return false,
             Ok(Command::Clear) => {
-                log::LOGGER.write().unwrap().clear();
+                log::LOGGER.clear();
                 self.panels.terminal().clear();
             }
             Ok(Command::Help) => tprint!(self.panels.terminal(), "{}", commands::CMD_HELP),
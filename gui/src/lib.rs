@@ -55,9 +55,22 @@ pub enum WinitEvent {
 /// Global UI events.
 pub enum UIEvent {
     BinaryRequested(std::path::PathBuf),
+    /// Open a binary next to the ones that are already open.
+    BinaryRequestedInNewTab(std::path::PathBuf),
     BinaryFailed(processor::Error),
     BinaryLoaded(processor::Processor),
     GotoAddr(usize),
+    /// Focus a function in the call graph.
+    ShowCallGraph(usize),
+    /// Show the debugged target's memory at an address.
+    ReadMemory(usize),
+    WriteMemory(usize, Vec<u8>),
+    /// Read the memory regions of the debugged target again.
+    ReadMemoryMap,
+    ToggleBreakpoint(usize),
+    /// Set the condition of a breakpoint, an empty one makes it unconditional.
+    SetCondition(usize, String),
+    ToggleWatchpoint(usize, debugger::Watch),
 }
 
 #[derive(Clone)]
@@ -136,26 +149,52 @@ impl UI {
     }
 
     pub fn process_args(&mut self) {
-        if let Some(path) = commands::ARGS.path.as_ref().cloned() {
-            self.offload_binary_processing(path);
+        if commands::ARGS.safe_mode {
+            log::complex!(
+                w "[ui::process_args] ",
+                y "Safe mode, plugins, scripting, analysis passes and networking are disabled.",
+            );
         }
-    }
 
-    fn offload_binary_processing(&mut self, path: std::path::PathBuf) {
-        // don't load multiple binaries at a time
-        if self.panels.is_loading() {
-            return;
+        if let Some(path) = commands::ARGS.path.as_ref().cloned() {
+            self.offload_binary_processing(path, false);
         }
+    }
 
-        self.panels.start_loading();
+    fn offload_binary_processing(&mut self, path: std::path::PathBuf, new_tab: bool) {
         let ui_queue = self.ui_queue.clone();
 
-        std::thread::spawn(move || {
-            match processor::Processor::parse(&path) {
+        // any binary that's still loading gets cancelled by starting a new pipeline
+        let pipeline = processor::AnalysisPipeline::spawn(path, move |result| {
+            match result {
                 Ok(diss) => ui_queue.push(UIEvent::BinaryLoaded(diss)),
                 Err(err) => ui_queue.push(UIEvent::BinaryFailed(err)),
             };
         });
+
+        self.panels.start_loading(pipeline, new_tab);
+    }
+
+    /// Snapshot the executable memory of a process on this machine, which gets loaded like a core
+    /// dump.
+    fn attach(&mut self, pid: u32) {
+        let snapshot = debugger::Process::attach(pid).and_then(|mut process| process.snapshot());
+        let snapshot = match snapshot {
+            Ok(snapshot) => snapshot,
+            Err(err) => {
+                tprint!(self.panels.terminal(), "Failed to attach to process {pid}: {err}");
+                return;
+            }
+        };
+
+        let path = std::env::temp_dir().join(format!("bite-{pid}.core"));
+        if let Err(err) = std::fs::write(&path, snapshot) {
+            tprint!(self.panels.terminal(), "Failed to save snapshot of process {pid}: {err}.");
+            return;
+        }
+
+        tprint!(self.panels.terminal(), "Saved snapshot of process {pid} to {}.", path.display());
+        self.offload_binary_processing(path, false);
     }
 
     fn handle_ui_events(&mut self) {
@@ -167,6 +206,10 @@ impl UI {
                     self.panels.goto_window(panes::SOURCE);
                     self.arch.bar.set_checked(panes::SOURCE);
                 }
+                panes::PSEUDO_CODE => {
+                    self.panels.goto_window(panes::PSEUDO_CODE);
+                    self.arch.bar.set_checked(panes::PSEUDO_CODE);
+                }
                 panes::DISASSEMBLY => {
                     self.panels.goto_window(panes::DISASSEMBLY);
                     self.arch.bar.set_checked(panes::DISASSEMBLY);
@@ -179,6 +222,10 @@ impl UI {
                     self.panels.goto_window(panes::LOGGING);
                     self.arch.bar.set_checked(panes::LOGGING);
                 }
+                panes::BOOKMARKS => {
+                    self.panels.goto_window(panes::BOOKMARKS);
+                    self.arch.bar.set_checked(panes::BOOKMARKS);
+                }
                 _ => {}
             }
         }
@@ -187,10 +234,14 @@ impl UI {
             match event {
                 UIEvent::BinaryFailed(err) => {
                     self.panels.stop_loading();
-                    log::warning!("{err:?}");
+                    self.panels.continue_restoring();
+                    log::report!(log::Level::Error, "{err:?}");
                 }
                 UIEvent::BinaryRequested(path) => {
-                    self.offload_binary_processing(path);
+                    self.offload_binary_processing(path, false);
+                }
+                UIEvent::BinaryRequestedInNewTab(path) => {
+                    self.offload_binary_processing(path, true);
                 }
                 UIEvent::BinaryLoaded(disassembly) => {
                     #[cfg(target_os = "macos")]
@@ -203,8 +254,31 @@ impl UI {
                     if let Some(listing) = self.panels.listing() {
                         listing.jump(addr);
                         self.panels.goto_window(panes::DISASSEMBLY);
+                        self.panels.load_pseudo_code(addr);
+                        self.panels.load_function_info(addr);
                     }
                 }
+                UIEvent::ShowCallGraph(addr) => {
+                    self.panels.show_call_graph(addr);
+                }
+                UIEvent::ReadMemory(addr) => {
+                    self.panels.show_memory(addr);
+                }
+                UIEvent::WriteMemory(addr, bytes) => {
+                    self.panels.write_memory(addr, &bytes);
+                }
+                UIEvent::ReadMemoryMap => {
+                    self.panels.read_memory_map();
+                }
+                UIEvent::ToggleBreakpoint(addr) => {
+                    self.panels.toggle_breakpoint(addr);
+                }
+                UIEvent::SetCondition(addr, condition) => {
+                    self.panels.set_condition(addr, &condition);
+                }
+                UIEvent::ToggleWatchpoint(addr, watch) => {
+                    self.panels.toggle_watchpoint(addr, watch);
+                }
             }
         }
     }
@@ -243,14 +317,14 @@ impl UI {
                         );
 
                         if let Err(err) = result {
-                            log::warning!("{err:?}");
+                            log::report!(log::Level::Error, "{err:?}");
                         }
                     }
                     WindowEvent::Resized(size) => {
                         self.instance.resize(size.width, size.height);
                         self.window.request_redraw();
                     }
-                    WindowEvent::DroppedFile(path) => self.offload_binary_processing(path),
+                    WindowEvent::DroppedFile(path) => self.offload_binary_processing(path, false),
                     WindowEvent::CloseRequested => target.exit(),
                     _ => {}
                 },
@@ -263,6 +337,10 @@ impl UI {
                     WinitEvent::Minimize => self.window.set_minimized(true),
                 },
                 Event::AboutToWait => self.window.request_redraw(),
+                Event::LoopExiting => {
+                    self.panels.save_project();
+                    self.panels.save_session();
+                }
                 _ => {}
             }
         });
@@ -0,0 +1,222 @@
+//! Everything that can be done from the menus, the command palette and shortcuts.
+
+use super::{Identifier, PANES};
+use crate::tprint;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Run a terminal command.
+    Command(&'static str),
+    /// Start typing a terminal command, leaving its arguments to the user.
+    Prefill(&'static str),
+    Open,
+    OpenInNewTab,
+    Save,
+    Back,
+    Forward,
+    ShowPanel(Identifier),
+    CommandPalette,
+    Settings,
+    Keybindings,
+    /// Show the next tab of the dock that's focussed.
+    NextTab,
+    /// Continue the debugged target, or interrupt it if it's running.
+    ContinueOrInterrupt,
+    Step,
+    /// Toggle a breakpoint at the selected instruction.
+    ToggleBreakpoint,
+    /// Clear the selection of the listing, or go back if nothing is selected.
+    Deselect,
+    /// Extend the selection of the listing down or up.
+    ExtendSelection(bool),
+    /// Scroll the listing by a number of blocks.
+    Scroll(isize),
+    /// Scroll the listing by a number of pages.
+    Page(isize),
+    /// Jump to the start or the end of the listing.
+    JumpToEdge(bool),
+    /// Fold or unfold the function that's selected.
+    ToggleFold,
+    /// Collapse or expand the prologues, epilogues and canary checks of the listing.
+    ToggleIdioms,
+    /// Jump to the start or the end of the code section that's shown.
+    JumpToCodeEdge(bool),
+    Entrypoint,
+    Exit,
+}
+
+#[derive(Clone, Copy)]
+pub struct Entry {
+    /// Name the action is bound to shortcuts by, in the keymap.
+    pub id: &'static str,
+    pub title: &'static str,
+    pub action: Action,
+}
+
+const fn entry(id: &'static str, title: &'static str, action: Action) -> Entry {
+    Entry { id, title, action }
+}
+
+/// Actions besides showing panels, in the order they're listed.
+pub const ACTIONS: &[Entry] = &[
+    entry("open", "Open binary", Action::Open),
+    entry("open_in_new_tab", "Open binary in new tab", Action::OpenInNewTab),
+    entry("save", "Save project", Action::Save),
+    entry("goto", "Go to or search symbol", Action::Prefill("goto ")),
+    entry("back", "Go back", Action::Back),
+    entry("forward", "Go forward", Action::Forward),
+    entry("top", "Go to start of listing", Action::JumpToEdge(false)),
+    entry("bottom", "Go to end of listing", Action::JumpToEdge(true)),
+    entry("code_start", "Go to start of code", Action::JumpToCodeEdge(false)),
+    entry("code_end", "Go to end of code", Action::JumpToCodeEdge(true)),
+    entry("entrypoint", "Go to entrypoint", Action::Entrypoint),
+    entry("scroll_down", "Scroll down", Action::Scroll(1)),
+    entry("scroll_up", "Scroll up", Action::Scroll(-1)),
+    entry("page_down", "Scroll down a page", Action::Page(1)),
+    entry("page_up", "Scroll up a page", Action::Page(-1)),
+    entry("select_down", "Extend selection down", Action::ExtendSelection(true)),
+    entry("select_up", "Extend selection up", Action::ExtendSelection(false)),
+    entry("deselect", "Clear selection or go back", Action::Deselect),
+    entry("toggle_fold", "Fold or unfold function", Action::ToggleFold),
+    entry("toggle_idioms", "Collapse or expand boilerplate and builtins", Action::ToggleIdioms),
+    entry("rename", "Rename symbol", Action::Prefill("rename ")),
+    entry("comment", "Comment", Action::Prefill("comment ")),
+    entry("bookmark", "Bookmark", Action::Prefill("bookmark ")),
+    entry("patch", "Patch bytes", Action::Prefill("patch ")),
+    entry("export", "Export listing or symbols", Action::Prefill("export ")),
+    entry("import", "Import analysis", Action::Prefill("import ")),
+    entry("script", "Run script", Action::Prefill("script ")),
+    entry("emulate", "Emulate function", Action::Prefill("emulate ")),
+    entry("connect", "Connect to debugger", Action::Prefill("connect ")),
+    entry("continue", "Continue or interrupt target", Action::ContinueOrInterrupt),
+    entry("step", "Step target", Action::Step),
+    entry("toggle_breakpoint", "Toggle breakpoint", Action::ToggleBreakpoint),
+    entry("detach", "Detach from target", Action::Command("detach")),
+    entry("trace", "Toggle tracing", Action::Command("trace")),
+    entry("plugins", "Show plugins", Action::Command("plugins")),
+    entry("clear", "Clear terminal", Action::Command("clear")),
+    entry("help", "Help", Action::Command("help")),
+    entry("next_tab", "Next tab", Action::NextTab),
+    entry("command_palette", "Command palette", Action::CommandPalette),
+    entry("settings", "Settings", Action::Settings),
+    entry("keybindings", "Keybindings", Action::Keybindings),
+    entry("exit", "Exit", Action::Exit),
+];
+
+impl super::Panels {
+    /// All actions, including showing any of the panels.
+    pub(super) fn actions(&self) -> Vec<Entry> {
+        let panels = PANES.iter().chain(self.script_panels.iter()).map(|&title| Entry {
+            id: title,
+            title,
+            action: Action::ShowPanel(title),
+        });
+
+        ACTIONS.iter().copied().chain(panels).collect()
+    }
+
+    /// Run the action bound to a shortcut.
+    pub(super) fn run_bound_action(&mut self, id: &str) {
+        match self.actions().into_iter().find(|entry| entry.id == id) {
+            Some(entry) => self.run_action(entry.action),
+            None => log::complex!(
+                w "[panels::run_bound_action] ",
+                y format!("Keymap binds unknown action '{id}'."),
+            ),
+        }
+    }
+
+    pub fn run_action(&mut self, action: Action) {
+        match action {
+            Action::Command(cmd) => self.terminal().run(cmd),
+            Action::Prefill(cmd) => self.terminal().prefill(cmd),
+            Action::Open => self.ask_for_binary(),
+            Action::OpenInNewTab => self.ask_for_binary_in_new_tab(),
+            Action::Save => self.save_project(),
+            Action::Back => {
+                if let Some(listing) = self.listing() {
+                    listing.go_back();
+                }
+            }
+            Action::Forward => {
+                if let Some(listing) = self.listing() {
+                    listing.go_forward();
+                }
+            }
+            Action::ShowPanel(title) => self.goto_window(title),
+            Action::CommandPalette => self.palette.open(),
+            Action::Settings => self.show_settings(),
+            Action::Keybindings => self.show_keybindings(),
+            Action::NextTab => self.next_tab(),
+            Action::ContinueOrInterrupt => match self.debugger.as_ref().map(|s| s.is_running()) {
+                Some(true) => self.interrupt(),
+                Some(false) => self.resume(),
+                None => {}
+            },
+            Action::Step => {
+                if self.debugger.is_some() {
+                    self.step();
+                }
+            }
+            Action::ToggleBreakpoint => {
+                if self.debugger.is_some() {
+                    if let Some(addr) = self.listing().map(|listing| listing.selected_addr()) {
+                        self.toggle_breakpoint(addr);
+                    }
+                }
+            }
+            Action::Deselect => {
+                if let Some(listing) = self.listing() {
+                    listing.deselect_or_go_back();
+                }
+            }
+            Action::ExtendSelection(down) => {
+                if let Some(listing) = self.listing() {
+                    listing.extend_selection(down);
+                }
+            }
+            Action::Scroll(blocks) => {
+                if let Some(listing) = self.listing() {
+                    listing.scroll_blocks(blocks);
+                }
+            }
+            Action::Page(pages) => {
+                if let Some(listing) = self.listing() {
+                    listing.scroll_pages(pages);
+                }
+            }
+            Action::JumpToEdge(end) => {
+                if let Some(listing) = self.listing() {
+                    listing.jump_to_edge(end);
+                }
+            }
+            Action::ToggleFold => {
+                if let Some(listing) = self.listing() {
+                    listing.toggle_fold(listing.selected_addr());
+                }
+            }
+            Action::ToggleIdioms => {
+                if let Some(listing) = self.listing() {
+                    listing.toggle_idioms();
+                }
+            }
+            Action::JumpToCodeEdge(end) => {
+                if let Some(listing) = self.listing() {
+                    listing.jump_to_code_edge(end);
+                }
+            }
+            Action::Entrypoint => {
+                let entrypoint = self.processor().map(|processor| processor.entrypoint);
+                match entrypoint.filter(|&addr| addr != 0) {
+                    Some(entrypoint) => {
+                        if let Some(listing) = self.listing() {
+                            listing.jump(entrypoint);
+                        }
+                    }
+                    None => tprint!(self.terminal(), "Binary doesn't have an entrypoint."),
+                }
+            }
+            Action::Exit => self.winit_queue.push(crate::WinitEvent::CloseRequest),
+        }
+    }
+}
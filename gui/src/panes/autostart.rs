@@ -0,0 +1,64 @@
+use crate::common::*;
+use crate::{UIEvent, UiQueue};
+use config::CONFIG;
+use processor::{AutoStartKind, Processor};
+use std::sync::Arc;
+use tokenizing::Token;
+
+/// Code that runs without the binary calling it, grouped by why it runs.
+pub struct AutoStart {
+    ui_queue: Arc<UiQueue>,
+    groups: Vec<(AutoStartKind, Vec<(usize, Vec<Token>)>)>,
+}
+
+impl AutoStart {
+    pub fn new(processor: &Processor, ui_queue: Arc<UiQueue>) -> Self {
+        let mut groups: Vec<(AutoStartKind, Vec<(usize, Vec<Token>)>)> = Vec::new();
+        for start in processor.autostart.iter() {
+            // the function might have been named by a symbol instead
+            let name = match processor.index.get_sym_by_addr(start.addr) {
+                Some(symbol) => symbol.as_str().to_string(),
+                None => start.name.clone(),
+            };
+
+            let tokens = vec![
+                Token::from_string(format!("{:0>10X}", start.addr), CONFIG.colors.text),
+                Token::from_str(" | ", CONFIG.colors.text),
+                Token::from_string(name, CONFIG.colors.src.function),
+            ];
+
+            match groups.iter_mut().find(|(kind, _)| *kind == start.kind) {
+                Some((_, starts)) => starts.push((start.addr, tokens)),
+                None => groups.push((start.kind, vec![(start.addr, tokens)])),
+            }
+        }
+
+        Self { ui_queue, groups }
+    }
+}
+
+impl Display for AutoStart {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        let area = egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
+
+        area.show(ui, |ui| {
+            if self.groups.is_empty() {
+                ui.label("No code runs without being called.");
+            }
+
+            for (kind, starts) in self.groups.iter() {
+                let header = format!("{kind}s ({})", starts.len());
+                egui::CollapsingHeader::new(header).default_open(true).show(ui, |ui| {
+                    for (addr, tokens) in starts.iter() {
+                        let label = egui::Label::new(tokens_to_layoutjob(tokens.to_vec()))
+                            .sense(egui::Sense::click());
+
+                        if ui.add(label).double_clicked() {
+                            self.ui_queue.push(UIEvent::GotoAddr(*addr));
+                        }
+                    }
+                });
+            }
+        });
+    }
+}
@@ -0,0 +1,140 @@
+//! Threads of a core dump, along with the backtrace of the one that's selected.
+
+use crate::common::*;
+use crate::{UIEvent, UiQueue};
+use config::CONFIG;
+use processor::{Frame, Processor};
+use std::sync::Arc;
+use tokenizing::Token;
+
+pub struct Backtrace {
+    processor: Arc<Processor>,
+    ui_queue: Arc<UiQueue>,
+    /// Index of the selected thread along with its backtrace.
+    selected: Option<(usize, Vec<Frame>)>,
+}
+
+impl Backtrace {
+    pub fn new(processor: Arc<Processor>, ui_queue: Arc<UiQueue>) -> Self {
+        let mut this = Self {
+            processor,
+            ui_queue,
+            selected: None,
+        };
+
+        // the thread that crashed is usually the one that's interesting
+        let crashed = this.processor.coredump.as_ref().and_then(|dump| match dump.crashed {
+            Some(idx) => Some(idx),
+            None => (!dump.threads.is_empty()).then_some(0),
+        });
+
+        if let Some(idx) = crashed {
+            this.select(idx);
+        }
+
+        this
+    }
+
+    fn select(&mut self, idx: usize) {
+        let threads = match self.processor.coredump {
+            Some(ref dump) => &dump.threads,
+            None => return,
+        };
+
+        let frames = self.processor.backtrace(&threads[idx]);
+        self.selected = Some((idx, frames));
+    }
+}
+
+impl Display for Backtrace {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        let processor = self.processor.clone();
+        let dump = match processor.coredump {
+            Some(ref dump) => dump,
+            None => {
+                ui.label("Not a core dump.");
+                return;
+            }
+        };
+
+        match dump.reason {
+            Some(ref reason) => ui.label(format!("{} crashed with {reason}", dump.format())),
+            None => ui.label(dump.format()),
+        };
+        ui.separator();
+
+        let selected = self.selected.as_ref().map(|(idx, _)| *idx);
+        let mut clicked = None;
+
+        let area = egui::ScrollArea::both()
+            .id_source("backtrace threads")
+            .max_height(ui.available_height() / 3.0)
+            .auto_shrink([false, false])
+            .drag_to_scroll(false);
+
+        area.show(ui, |ui| {
+            for (idx, thread) in dump.threads.iter().enumerate() {
+                let mut tokens = vec![
+                    Token::from_string(format!("Thread {}", thread.id), CONFIG.colors.text),
+                    Token::from_string(
+                        format!(" at {:#x}", thread.registers.pc),
+                        CONFIG.colors.hint,
+                    ),
+                ];
+
+                if dump.crashed == Some(idx) {
+                    tokens.push(Token::from_str(" (crashed)", CONFIG.colors.src.function));
+                }
+
+                let label = ui.selectable_label(selected == Some(idx), tokens_to_layoutjob(tokens));
+                if label.clicked() {
+                    clicked = Some(idx);
+                }
+
+                if label.double_clicked() {
+                    self.ui_queue.push(UIEvent::GotoAddr(thread.registers.pc));
+                }
+            }
+        });
+
+        if let Some(idx) = clicked {
+            self.select(idx);
+        }
+
+        ui.separator();
+
+        let area = egui::ScrollArea::both()
+            .id_source("backtrace frames")
+            .auto_shrink([false, false])
+            .drag_to_scroll(false);
+
+        area.show(ui, |ui| {
+            let frames = match self.selected {
+                Some((_, ref frames)) => frames,
+                None => {
+                    ui.label("Select a thread to show its backtrace.");
+                    return;
+                }
+            };
+
+            for (idx, frame) in frames.iter().enumerate() {
+                let function = frame.function.as_deref().unwrap_or("??");
+                let mut tokens = vec![
+                    Token::from_string(format!("#{idx:<3}"), CONFIG.colors.hint),
+                    Token::from_string(format!("{:0>12X}", frame.addr), CONFIG.colors.text),
+                    Token::from_str(" | ", CONFIG.colors.text),
+                    Token::from_string(function.to_string(), CONFIG.colors.src.function),
+                ];
+
+                if let Some(ref module) = frame.module {
+                    tokens.push(Token::from_string(format!(" in {module}"), CONFIG.colors.hint));
+                }
+
+                let label = ui.selectable_label(false, tokens_to_layoutjob(tokens));
+                if label.double_clicked() {
+                    self.ui_queue.push(UIEvent::GotoAddr(frame.addr));
+                }
+            }
+        });
+    }
+}
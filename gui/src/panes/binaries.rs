@@ -0,0 +1,129 @@
+//! Binaries that are open at the same time, each in a tab of its own.
+
+use super::{Identifier, PanelKind, LOGGING};
+use egui::mutex::RwLock;
+use processor::Processor;
+use project::Project;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+pub struct Binary {
+    pub processor: Arc<Processor>,
+    pub project: Arc<RwLock<Project>>,
+    /// Panes showing the binary, which are only kept here while another binary is active.
+    pub(super) mapping: BTreeMap<Identifier, PanelKind>,
+}
+
+impl Binary {
+    pub fn new(processor: Arc<Processor>, project: Arc<RwLock<Project>>) -> Self {
+        Self {
+            processor,
+            project,
+            mapping: BTreeMap::new(),
+        }
+    }
+
+    /// Name of the tab, which is the binary's file name.
+    fn name(&self) -> String {
+        match self.processor.path.file_name() {
+            Some(name) => name.to_string_lossy().into_owned(),
+            None => self.processor.path.display().to_string(),
+        }
+    }
+}
+
+impl super::Panels {
+    /// Take the panes of the active binary out of the dock.
+    pub(super) fn take_binary_panes(&mut self) -> BTreeMap<Identifier, PanelKind> {
+        let mapping = std::mem::take(&mut self.panes.mapping);
+        let (logging, panes) = mapping.into_iter().partition(|(id, _)| *id == LOGGING);
+        self.panes.mapping = logging;
+        panes
+    }
+
+    /// Show the panes of another open binary instead of the active one's.
+    pub fn switch_binary(&mut self, idx: usize) {
+        if idx == self.active || idx >= self.binaries.len() {
+            return;
+        }
+
+        self.save_project();
+
+        // addresses of the target are those of the binary that was active
+        self.detach();
+        self.clear_trace();
+
+        self.binaries[self.active].mapping = self.take_binary_panes();
+        self.activate_binary(idx);
+    }
+
+    /// Close an open binary, showing the one before it if it was active.
+    pub fn close_binary(&mut self, idx: usize) {
+        if idx >= self.binaries.len() {
+            return;
+        }
+
+        if let Err(err) = self.binaries[idx].project.write().save() {
+            log::complex!(
+                w "[panels::close_binary] ",
+                y err.to_string(),
+            );
+        }
+
+        if idx != self.active {
+            self.binaries.remove(idx);
+            if idx < self.active {
+                self.active -= 1;
+            }
+            return;
+        }
+
+        self.detach();
+        self.clear_trace();
+        self.take_binary_panes();
+        self.binaries.remove(idx);
+
+        match self.binaries.len() {
+            0 => {
+                self.active = 0;
+                self.panes.processor = None;
+                self.panes.project = None;
+            }
+            len => self.activate_binary(idx.min(len - 1)),
+        }
+    }
+
+    fn activate_binary(&mut self, idx: usize) {
+        let binary = &mut self.binaries[idx];
+        self.panes.mapping.append(&mut binary.mapping);
+        self.panes.processor = Some(binary.processor.clone());
+        self.panes.project = Some(binary.project.clone());
+        self.active = idx;
+    }
+
+    /// Row of the open binaries, shown once there's more than one.
+    pub(super) fn binary_tabs(&mut self, ui: &mut egui::Ui) {
+        let mut switch = None;
+        let mut close = None;
+
+        ui.horizontal(|ui| {
+            for (idx, binary) in self.binaries.iter().enumerate() {
+                if ui.selectable_label(idx == self.active, binary.name()).clicked() {
+                    switch = Some(idx);
+                }
+
+                if ui.small_button(crate::icon!(CROSS)).clicked() {
+                    close = Some(idx);
+                }
+
+                ui.separator();
+            }
+        });
+
+        if let Some(idx) = close {
+            self.close_binary(idx);
+        } else if let Some(idx) = switch {
+            self.switch_binary(idx);
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use crate::common::*;
+use crate::{UIEvent, UiQueue};
+use config::CONFIG;
+use egui::mutex::RwLock;
+use processor::Processor;
+use project::Project;
+use std::sync::Arc;
+use tokenizing::Token;
+
+pub struct Bookmarks {
+    processor: Arc<Processor>,
+    project: Arc<RwLock<Project>>,
+    ui_queue: Arc<UiQueue>,
+}
+
+impl Bookmarks {
+    pub fn new(
+        processor: Arc<Processor>,
+        project: Arc<RwLock<Project>>,
+        ui_queue: Arc<UiQueue>,
+    ) -> Self {
+        Self {
+            processor,
+            project,
+            ui_queue,
+        }
+    }
+}
+
+impl Display for Bookmarks {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        let area = egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
+        let mut removed = None;
+
+        area.show(ui, |ui| {
+            let project = self.project.read();
+
+            if project.bookmarks.is_empty() {
+                ui.label("No bookmarks, add one using `bookmark <expr> <desc>`.");
+                return;
+            }
+
+            for (addr, desc) in project.bookmarks.iter() {
+                let mut tokens = Vec::new();
+                tokens.push(Token::from_string(format!("{addr:0>10X}"), CONFIG.colors.text));
+                tokens.push(Token::from_str(" | ", CONFIG.colors.text));
+
+                if let Some(section) = self.processor.section_name(*addr) {
+                    tokens.push(Token::from_string(
+                        format!("{section} "),
+                        CONFIG.colors.asm.section,
+                    ));
+                }
+
+                tokens.push(Token::from_string(desc.clone(), CONFIG.colors.comment));
+
+                ui.horizontal(|ui| {
+                    if ui.small_button(crate::icon!(CROSS)).clicked() {
+                        removed = Some(*addr);
+                    }
+
+                    if ui.link(tokens_to_layoutjob(tokens)).clicked() {
+                        self.ui_queue.push(UIEvent::GotoAddr(*addr));
+                    }
+                });
+            }
+        });
+
+        if let Some(addr) = removed {
+            self.project.write().remove_bookmark(addr);
+        }
+    }
+}
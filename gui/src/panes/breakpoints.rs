@@ -0,0 +1,104 @@
+use crate::common::*;
+use crate::{UIEvent, UiQueue};
+use config::CONFIG;
+use debugger::Watch;
+use std::sync::Arc;
+use tokenizing::Token;
+
+struct Breakpoint {
+    addr: usize,
+    /// Condition as it's being edited, which is set by pressing enter.
+    condition: String,
+}
+
+pub struct Breakpoints {
+    ui_queue: Arc<UiQueue>,
+    breakpoints: Vec<Breakpoint>,
+    watchpoints: Vec<(usize, usize, Watch)>,
+}
+
+impl Breakpoints {
+    pub fn new(
+        ui_queue: Arc<UiQueue>,
+        breakpoints: Vec<(usize, String)>,
+        watchpoints: Vec<(usize, usize, Watch)>,
+    ) -> Self {
+        let breakpoints = breakpoints
+            .into_iter()
+            .map(|(addr, condition)| Breakpoint { addr, condition })
+            .collect();
+
+        Self {
+            ui_queue,
+            breakpoints,
+            watchpoints,
+        }
+    }
+}
+
+impl Display for Breakpoints {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        let area = egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
+
+        area.show(ui, |ui| {
+            if self.breakpoints.is_empty() {
+                ui.label("No breakpoints, add one using `break <expr>`.");
+            }
+
+            for breakpoint in self.breakpoints.iter_mut() {
+                let addr = breakpoint.addr;
+
+                ui.horizontal(|ui| {
+                    if ui.small_button(crate::icon!(CROSS)).clicked() {
+                        self.ui_queue.push(UIEvent::ToggleBreakpoint(addr));
+                    }
+
+                    let addr_text = format!("{addr:0>12X}");
+                    let tokens = vec![Token::from_string(addr_text, CONFIG.colors.text)];
+                    if ui.link(tokens_to_layoutjob(tokens)).clicked() {
+                        self.ui_queue.push(UIEvent::GotoAddr(addr));
+                    }
+
+                    // an empty condition stops the target every time
+                    let edit = egui::TextEdit::singleline(&mut breakpoint.condition)
+                        .hint_text("condition, e.g. rax == 0")
+                        .font(font());
+
+                    let response = ui.add(edit);
+                    if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        let condition = breakpoint.condition.trim().to_string();
+                        self.ui_queue.push(UIEvent::SetCondition(addr, condition));
+                    }
+                });
+            }
+
+            ui.separator();
+
+            if self.watchpoints.is_empty() {
+                ui.label("No watchpoints, add one using `watch <expr>`.");
+            }
+
+            for &(addr, len, watch) in self.watchpoints.iter() {
+                let access = match watch {
+                    Watch::Write => "write",
+                    Watch::Read => "read",
+                    Watch::Access => "access",
+                };
+
+                let tokens = vec![
+                    Token::from_string(format!("{addr:0>12X}"), CONFIG.colors.text),
+                    Token::from_string(format!(" {len} bytes"), CONFIG.colors.asm.immediate),
+                    Token::from_string(format!(" on {access}"), CONFIG.colors.asm.annotation),
+                ];
+
+                ui.horizontal(|ui| {
+                    if ui.small_button(crate::icon!(CROSS)).clicked() {
+                        self.ui_queue.push(UIEvent::ToggleWatchpoint(addr, watch));
+                    }
+
+                    ui.label(tokens_to_layoutjob(tokens));
+                });
+            }
+        });
+    }
+}
@@ -0,0 +1,211 @@
+use crate::common::*;
+use crate::{UIEvent, UiQueue};
+use config::CONFIG;
+use debugvault::NamePart;
+use processor::Processor;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokenizing::Token;
+
+/// How deep the callee tree can be expanded, so recursion doesn't nest forever.
+const MAX_DEPTH: usize = 32;
+
+pub struct CallGraph {
+    processor: Arc<Processor>,
+    ui_queue: Arc<UiQueue>,
+    /// Text functions are filtered by.
+    query: String,
+    /// Functions in the call graph matching the query.
+    matches: Vec<usize>,
+    /// Function whose callers and callees are shown.
+    focus: Option<usize>,
+    /// Whether imported functions are grouped by their library.
+    collapse_modules: bool,
+}
+
+impl CallGraph {
+    pub fn new(processor: Arc<Processor>, ui_queue: Arc<UiQueue>) -> Self {
+        let mut this = Self {
+            processor,
+            ui_queue,
+            query: String::new(),
+            matches: Vec::new(),
+            focus: None,
+            collapse_modules: true,
+        };
+
+        this.search();
+        this
+    }
+
+    /// Show the callers and callees of a single function.
+    pub fn focus(&mut self, addr: usize) {
+        self.focus = Some(addr);
+    }
+
+    fn search(&mut self) {
+        let query = self.query.to_lowercase();
+        let processor = &self.processor;
+
+        self.matches = processor
+            .call_graph()
+            .functions()
+            .filter(|addr| name(processor, *addr).to_lowercase().contains(&query))
+            .collect();
+    }
+
+    /// Link to a function, focusing it when clicked and jumping to it when double-clicked.
+    fn function(&mut self, ui: &mut egui::Ui, addr: usize) {
+        let mut tokens = vec![
+            Token::from_string(format!("{addr:0>10X}"), CONFIG.colors.text),
+            Token::from_str(" | ", CONFIG.colors.text),
+        ];
+        tokens.extend(name_tokens(&self.processor, addr));
+
+        let response = ui.link(tokens_to_layoutjob(tokens));
+        if response.double_clicked() {
+            self.ui_queue.push(UIEvent::GotoAddr(addr));
+        } else if response.clicked() {
+            self.focus = Some(addr);
+        }
+    }
+
+    /// List functions, grouping imported functions by their library if enabled.
+    fn functions(&mut self, ui: &mut egui::Ui, addrs: Vec<usize>, depth: usize) {
+        let mut modules: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+
+        for addr in addrs {
+            let module = self
+                .processor
+                .index
+                .get_sym_by_addr(addr)
+                .and_then(|sym| sym.module().map(str::to_string));
+
+            match module {
+                Some(module) if self.collapse_modules => {
+                    modules.entry(module).or_default().push(addr)
+                }
+                _ => self.callee(ui, addr, depth),
+            }
+        }
+
+        for (module, addrs) in modules {
+            let header = format!("{module} ({})", addrs.len());
+            egui::CollapsingHeader::new(header).id_source((&module, depth)).show(ui, |ui| {
+                for addr in addrs {
+                    self.function(ui, addr);
+                }
+            });
+        }
+    }
+
+    /// Function that can be expanded to show what it calls.
+    fn callee(&mut self, ui: &mut egui::Ui, addr: usize, depth: usize) {
+        let callees: Vec<usize> = self.processor.callees(addr).collect();
+        if callees.is_empty() || depth == MAX_DEPTH {
+            ui.horizontal(|ui| {
+                ui.add_space(ui.spacing().indent);
+                self.function(ui, addr);
+            });
+            return;
+        }
+
+        let id = ui.make_persistent_id((addr, depth));
+        egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, false)
+            .show_header(ui, |ui| self.function(ui, addr))
+            .body(|ui| self.functions(ui, callees, depth + 1));
+    }
+}
+
+/// Name of a function, or a generated one if it doesn't have a symbol.
+pub fn name(processor: &Processor, addr: usize) -> String {
+    match processor.index.get_sym_by_addr(addr) {
+        Some(sym) => sym.as_str().to_string(),
+        None => format!("sub_{addr:x}"),
+    }
+}
+
+/// Name of a function as colored tokens, or a generated one if it doesn't have a symbol.
+///
+/// Punctuation and lifetimes are colored the same no matter which scheme the name was mangled
+/// with, so names read alike in every pane.
+pub fn name_tokens(processor: &Processor, addr: usize) -> Vec<Token> {
+    let sym = match processor.index.get_sym_by_addr(addr) {
+        Some(sym) if !sym.as_str().is_empty() => sym,
+        _ => {
+            let name = format!("sub_{addr:x}");
+            return vec![Token::from_string(name, CONFIG.colors.src.function)];
+        }
+    };
+
+    let mut tokens = Vec::new();
+    for (part, token) in sym.tokens() {
+        let mut token = token.clone();
+        match part {
+            NamePart::Punctuation => token.color = CONFIG.colors.delimiter,
+            NamePart::Lifetime => token.color = CONFIG.colors.asm.annotation,
+            NamePart::Segment | NamePart::Generic => {}
+        }
+        tokens.push(token);
+    }
+
+    tokens
+}
+
+impl Display for CallGraph {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        if self.processor.call_graph().is_empty() {
+            ui.label("No calls were found, analysis may be disabled.");
+            return;
+        }
+
+        ui.horizontal(|ui| {
+            let search = egui::TextEdit::singleline(&mut self.query).hint_text("Search functions");
+            if ui.add(search).changed() {
+                self.focus = None;
+                self.search();
+            }
+
+            ui.checkbox(&mut self.collapse_modules, "Collapse by module");
+
+            if self.focus.is_some() && ui.button("Show all").clicked() {
+                self.focus = None;
+            }
+        });
+
+        ui.separator();
+
+        let area = egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
+        let addr = match self.focus {
+            Some(addr) => addr,
+            None => {
+                let count = self.matches.len();
+                area.show_rows(ui, font().size, count, |ui, row_range| {
+                    for idx in row_range {
+                        self.function(ui, self.matches[idx]);
+                    }
+                });
+                return;
+            }
+        };
+
+        area.show(ui, |ui| {
+            self.function(ui, addr);
+            ui.separator();
+
+            let callers: Vec<usize> = self.processor.callers(addr).collect();
+            egui::CollapsingHeader::new(format!("Callers ({})", callers.len()))
+                .default_open(true)
+                .show(ui, |ui| {
+                    for caller in callers {
+                        self.function(ui, caller);
+                    }
+                });
+
+            let callees: Vec<usize> = self.processor.callees(addr).collect();
+            egui::CollapsingHeader::new(format!("Callees ({})", callees.len()))
+                .default_open(true)
+                .show(ui, |ui| self.functions(ui, callees, 0));
+        });
+    }
+}
@@ -0,0 +1,114 @@
+//! Objective-C classes of a Mach-O binary, along with the methods they implement.
+
+use crate::common::*;
+use crate::{UIEvent, UiQueue};
+use config::CONFIG;
+use processor::Processor;
+use std::sync::Arc;
+use tokenizing::Token;
+
+struct Class {
+    name: String,
+    /// Class and the class it inherits from, as shown in its header.
+    header: String,
+    /// Implementations of the methods with their name, along with how they're shown.
+    methods: Vec<(usize, String, Vec<Token>)>,
+}
+
+pub struct Classes {
+    ui_queue: Arc<UiQueue>,
+    classes: Vec<Class>,
+    /// Text the name of a class or one of its methods has to contain to be shown.
+    query: String,
+}
+
+impl Classes {
+    pub fn new(processor: &Processor, ui_queue: Arc<UiQueue>) -> Self {
+        let mut classes: Vec<Class> = processor
+            .objc
+            .classes
+            .iter()
+            .map(|class| {
+                let header = match class.superclass {
+                    Some(ref superclass) => format!("{} : {superclass}", class.name),
+                    None => class.name.clone(),
+                };
+
+                let methods = class
+                    .methods
+                    .iter()
+                    .map(|method| {
+                        let symbol = method.symbol(&class.name);
+                        let tokens = vec![
+                            Token::from_string(format!("{:0>10X}", method.imp), CONFIG.colors.text),
+                            Token::from_str(" | ", CONFIG.colors.text),
+                            Token::from_string(symbol.clone(), CONFIG.colors.src.function),
+                            Token::from_string(format!(" {}", method.types), CONFIG.colors.hint),
+                        ];
+
+                        (method.imp, symbol.to_lowercase(), tokens)
+                    })
+                    .collect();
+
+                Class {
+                    name: class.name.clone(),
+                    header,
+                    methods,
+                }
+            })
+            .collect();
+
+        classes.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Self {
+            ui_queue,
+            classes,
+            query: String::new(),
+        }
+    }
+}
+
+impl Display for Classes {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        ui.add(egui::TextEdit::singleline(&mut self.query).hint_text("Search"));
+        ui.separator();
+
+        let area = egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
+        area.show(ui, |ui| {
+            if self.classes.is_empty() {
+                ui.label("No Objective-C classes.");
+            }
+
+            let query = self.query.to_lowercase();
+            for class in self.classes.iter() {
+                let class_matches = class.name.to_lowercase().contains(&query);
+                let methods: Vec<_> = class
+                    .methods
+                    .iter()
+                    .filter(|(_, symbol, _)| class_matches || symbol.contains(&query))
+                    .collect();
+
+                if !class_matches && methods.is_empty() {
+                    continue;
+                }
+
+                let header = format!("{} ({})", class.header, class.methods.len());
+                egui::CollapsingHeader::new(header).id_source(&class.name).show(ui, |ui| {
+                    if methods.is_empty() {
+                        ui.label("No methods.");
+                    }
+
+                    for (addr, _, tokens) in methods {
+                        let label = egui::Label::new(tokens_to_layoutjob(tokens.to_vec()))
+                            .sense(egui::Sense::click());
+
+                        // methods that are only declared don't have an implementation
+                        if ui.add(label).double_clicked() && *addr != 0 {
+                            self.ui_queue.push(UIEvent::GotoAddr(*addr));
+                        }
+                    }
+                });
+            }
+        });
+    }
+}
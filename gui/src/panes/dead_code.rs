@@ -0,0 +1,63 @@
+use crate::common::*;
+use crate::{UIEvent, UiQueue};
+use config::CONFIG;
+use processor::{DeadCodeKind, Processor};
+use std::sync::Arc;
+use tokenizing::Token;
+
+/// Unreachable code within functions and functions nothing is known to run, grouped by kind.
+pub struct DeadCode {
+    ui_queue: Arc<UiQueue>,
+    groups: Vec<(DeadCodeKind, Vec<(usize, Vec<Token>)>)>,
+}
+
+impl DeadCode {
+    pub fn new(processor: &Processor, ui_queue: Arc<UiQueue>) -> Self {
+        let mut groups: Vec<(DeadCodeKind, Vec<(usize, Vec<Token>)>)> = Vec::new();
+        for dead in processor.dead_code.iter() {
+            let mut tokens = vec![
+                Token::from_string(format!("{:0>10X}", dead.addr), CONFIG.colors.text),
+                Token::from_str(" | ", CONFIG.colors.text),
+            ];
+            tokens.extend(super::call_graph::name_tokens(processor, dead.function));
+            tokens.push(Token::from_str(" | ", CONFIG.colors.text));
+            tokens.push(Token::from_string(
+                format!("{:#x} bytes", dead.end - dead.addr),
+                CONFIG.colors.asm.immediate,
+            ));
+
+            match groups.iter_mut().find(|(kind, _)| *kind == dead.kind) {
+                Some((_, ranges)) => ranges.push((dead.addr, tokens)),
+                None => groups.push((dead.kind, vec![(dead.addr, tokens)])),
+            }
+        }
+
+        Self { ui_queue, groups }
+    }
+}
+
+impl Display for DeadCode {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        let area = egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
+
+        area.show(ui, |ui| {
+            if self.groups.is_empty() {
+                ui.label("No dead code was found, analysis may be disabled.");
+            }
+
+            for (kind, ranges) in self.groups.iter() {
+                let header = format!("{kind} ({})", ranges.len());
+                egui::CollapsingHeader::new(header).default_open(true).show(ui, |ui| {
+                    for (addr, tokens) in ranges.iter() {
+                        let label = egui::Label::new(tokens_to_layoutjob(tokens.to_vec()))
+                            .sense(egui::Sense::click());
+
+                        if ui.add(label).double_clicked() {
+                            self.ui_queue.push(UIEvent::GotoAddr(*addr));
+                        }
+                    }
+                });
+            }
+        });
+    }
+}
@@ -0,0 +1,578 @@
+//! Driving a target that's debugged, either over the GDB remote serial protocol or on this
+//! machine.
+
+use super::{breakpoints, memory, registers, stack, PanelKind};
+use super::{BREAKPOINTS, MEMORY, MEMORY_MAP, REGISTERS, STACK};
+use crate::tprint;
+use crate::widgets::Terminal;
+use commands::Feature;
+use debugger::{Condition, Module, Session, Target, Watch};
+use processor_shared::SectionKind;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::ops::Range;
+use std::path::Path;
+use std::sync::MutexGuard;
+
+/// Differences in memory that are closer together than this are patched at once.
+const GAP: usize = 16;
+
+impl super::Panels {
+    pub fn connect(&mut self, addr: &str) {
+        let processor = match self.panes.processor.clone() {
+            Some(processor) => processor,
+            None => {
+                tprint!(self.terminal(), "No targets loaded.");
+                return;
+            }
+        };
+
+        let addrs: Vec<SocketAddr> = match addr.to_socket_addrs() {
+            Ok(addrs) => addrs.collect(),
+            Err(err) => {
+                tprint!(self.terminal(), "Address '{addr}' is invalid: {err}.");
+                return;
+            }
+        };
+
+        // anything other than loopback talks to other machines
+        let remote = addrs.iter().any(|addr| !addr.ip().is_loopback());
+        if remote && !Feature::Network.enabled() {
+            tprint!(
+                self.terminal(),
+                "Connecting to '{addr}' requires networking, which is disabled."
+            );
+            return;
+        }
+
+        self.detach();
+
+        match Session::connect(&addrs[..], processor.arch(), processor.endianness()) {
+            Ok(session) => {
+                tprint!(
+                    self.terminal(),
+                    "Connected to '{addr}', target {}.",
+                    session.stop()
+                );
+                self.start_debugging(session);
+            }
+            Err(err) => tprint!(self.terminal(), "Failed to connect to '{addr}': {err}"),
+        }
+    }
+
+    /// Start a program on this machine and debug it.
+    pub fn launch(&mut self, path: &Path) {
+        if self.panes.processor.is_none() {
+            tprint!(self.terminal(), "No targets loaded.");
+            return;
+        }
+
+        self.detach();
+
+        match Session::launch(path) {
+            Ok(session) => {
+                tprint!(
+                    self.terminal(),
+                    "Started '{}', target {}.",
+                    path.display(),
+                    session.stop()
+                );
+                self.start_debugging(session);
+            }
+            Err(err) => tprint!(
+                self.terminal(),
+                "Failed to start '{}': {err}",
+                path.display()
+            ),
+        }
+    }
+
+    /// Show everything about a target that just started being debugged.
+    fn start_debugging(&mut self, session: Session) {
+        self.debugger = Some(session);
+        self.load_module_symbols();
+        self.sync_memory();
+        self.inspect_target();
+        self.read_memory_map();
+        self.refresh_breakpoints();
+        self.goto_window(REGISTERS);
+        self.goto_window(STACK);
+    }
+
+    /// Add the functions of the modules the target loaded since they were last added, so that
+    /// they're named and can be referred to in commands.
+    fn load_module_symbols(&mut self) {
+        let modules = match self.debugger.as_mut() {
+            Some(session) => session.take_loaded(),
+            None => return,
+        };
+
+        if modules.is_empty() {
+            return;
+        }
+
+        let functions: Vec<(usize, String)> = modules
+            .iter()
+            .flat_map(Module::functions)
+            .map(|(addr, name)| (addr as usize, name))
+            .collect();
+
+        let count = functions.len();
+        self.module_symbols.insert_functions(functions);
+        tprint!(
+            self.terminal(),
+            "Loaded {count} functions of {} modules.",
+            modules.len()
+        );
+    }
+
+    /// Functions of the modules the target loaded, while it's being debugged.
+    pub fn module_symbols(&self) -> Option<&debugvault::Index> {
+        self.debugger.as_ref().map(|_| &self.module_symbols)
+    }
+
+    pub fn detach(&mut self) {
+        let session = match self.debugger.take() {
+            Some(session) => session,
+            None => return,
+        };
+
+        match session.detach() {
+            Ok(()) => tprint!(self.terminal(), "Detached from target."),
+            Err(err) => tprint!(self.terminal(), "Failed to detach from target: {err}"),
+        }
+
+        self.forget_target();
+    }
+
+    /// Clear everything that's shown about a target that's gone.
+    fn forget_target(&mut self) {
+        self.module_symbols = debugvault::Index::default();
+
+        if let Some(listing) = self.listing() {
+            listing.set_pc(None);
+            listing.set_breakpoints(Default::default());
+        }
+
+        self.panes.mapping.remove(REGISTERS);
+        self.panes.mapping.remove(STACK);
+        self.panes.mapping.remove(MEMORY_MAP);
+        self.panes.mapping.remove(MEMORY);
+        self.panes.mapping.remove(BREAKPOINTS);
+    }
+
+    pub(super) fn session(&mut self) -> Option<&mut Session> {
+        if self.debugger.is_none() {
+            tprint!(self.terminal(), "Not connected to a target.");
+        }
+
+        self.debugger.as_mut()
+    }
+
+    pub fn resume(&mut self) {
+        if let Some(session) = self.session() {
+            session.resume();
+        }
+    }
+
+    pub fn step(&mut self) {
+        if let Some(session) = self.session() {
+            session.step();
+        }
+    }
+
+    pub fn interrupt(&mut self) {
+        let result = match self.session() {
+            Some(session) => session.interrupt(),
+            None => return,
+        };
+
+        if let Err(err) = result {
+            tprint!(self.terminal(), "Failed to interrupt target: {err}");
+        }
+    }
+
+    pub fn toggle_breakpoint(&mut self, addr: usize) {
+        let result = match self.session() {
+            Some(session) => session.toggle_breakpoint(addr as u64),
+            None => return,
+        };
+
+        match result {
+            Ok(true) => tprint!(self.terminal(), "Inserted breakpoint at {addr:#X}."),
+            Ok(false) => tprint!(self.terminal(), "Removed breakpoint at {addr:#X}."),
+            Err(err) => tprint!(self.terminal(), "Failed to toggle breakpoint: {err}"),
+        }
+
+        self.refresh_breakpoints();
+    }
+
+    /// Only stop at a breakpoint when a condition holds, removing the condition if it's empty.
+    pub fn set_condition(&mut self, addr: usize, condition: &str) {
+        let condition = match condition.trim() {
+            "" => None,
+            condition => match Condition::parse(condition) {
+                Ok(condition) => Some(condition),
+                Err(err) => {
+                    tprint!(self.terminal(), "Condition is invalid: {err}");
+                    return;
+                }
+            },
+        };
+
+        let result = match self.session() {
+            Some(session) => session.set_condition(addr as u64, condition.clone()),
+            None => return,
+        };
+
+        match (result, condition) {
+            (Ok(()), Some(condition)) => tprint!(
+                self.terminal(),
+                "Breakpoint at {addr:#X} stops when '{condition}'."
+            ),
+            (Ok(()), None) => tprint!(self.terminal(), "Breakpoint at {addr:#X} always stops."),
+            (Err(err), _) => tprint!(self.terminal(), "Failed to set condition: {err}"),
+        }
+
+        self.refresh_breakpoints();
+    }
+
+    /// Insert a watchpoint as wide as a pointer, or remove the one at an address.
+    pub fn toggle_watchpoint(&mut self, addr: usize, watch: Watch) {
+        let len = match self.panes.processor.as_ref() {
+            Some(processor) => processor.arch().address_size().map_or(8, |size| size.bytes()),
+            None => return,
+        };
+
+        let result = match self.session() {
+            Some(session) => session.toggle_watchpoint(addr as u64, len as usize, watch),
+            None => return,
+        };
+
+        match result {
+            Ok(true) => tprint!(self.terminal(), "Inserted watchpoint at {addr:#X}."),
+            Ok(false) => tprint!(self.terminal(), "Removed watchpoint at {addr:#X}."),
+            Err(err) => tprint!(self.terminal(), "Failed to toggle watchpoint: {err}"),
+        }
+
+        self.refresh_breakpoints();
+    }
+
+    /// Show the breakpoints and watchpoints of the target in the listing and breakpoints panel.
+    fn refresh_breakpoints(&mut self) {
+        let session = match self.debugger.as_ref() {
+            Some(session) => session,
+            None => return,
+        };
+
+        let breakpoints: Vec<(usize, String)> = session
+            .breakpoints()
+            .iter()
+            .map(|(&addr, condition)| {
+                let condition = condition.as_ref().map(ToString::to_string);
+                (addr as usize, condition.unwrap_or_default())
+            })
+            .collect();
+
+        let watchpoints = session
+            .watchpoints()
+            .iter()
+            .map(|(&addr, &(len, watch))| (addr as usize, len, watch))
+            .collect();
+
+        if let Some(listing) = self.listing() {
+            listing.set_breakpoints(breakpoints.iter().map(|(addr, _)| *addr).collect());
+        }
+
+        let panel = breakpoints::Breakpoints::new(self.ui_queue.clone(), breakpoints, watchpoints);
+        self.panes.mapping.insert(BREAKPOINTS, PanelKind::Breakpoints(panel));
+    }
+
+    /// Replace the code shown in the listing with what's in the target's memory, which differs
+    /// when it's unpacked or patched itself at runtime.
+    pub fn sync_memory(&mut self) {
+        let processor = match self.panes.processor.clone() {
+            Some(processor) => processor,
+            None => return,
+        };
+
+        let mut target = match stopped(&self.debugger, &mut self.panes.terminal) {
+            Some(target) => target,
+            None => return,
+        };
+
+        let mut changes = Vec::new();
+        for section in processor.sections().filter(|s| s.kind == SectionKind::Code) {
+            let len = section.bytes().len();
+            let memory = match target.read_memory(section.start as u64, len) {
+                Ok(memory) => memory,
+                Err(err) => {
+                    tprint!(
+                        self.panes.terminal,
+                        "Failed to read {}: {err}",
+                        section.name
+                    );
+                    continue;
+                }
+            };
+
+            let bytes = processor.patched_bytes(section, section.start, len);
+            for range in differences(&bytes, &memory) {
+                changes.push((section.start + range.start, memory[range].to_vec()));
+            }
+        }
+        drop(target);
+
+        let count: usize = changes.iter().map(|(_, bytes)| bytes.len()).sum();
+        if let Some(listing) = self.listing() {
+            for (addr, bytes) in changes {
+                listing.patch(addr, &bytes);
+            }
+        }
+
+        if count > 0 {
+            tprint!(
+                self.terminal(),
+                "Synced {count} bytes that differ in the target's memory."
+            );
+        }
+    }
+
+    /// Show the regions of memory the target has mapped.
+    pub fn read_memory_map(&mut self) {
+        let mut target = match stopped(&self.debugger, &mut self.panes.terminal) {
+            Some(target) => target,
+            None => return,
+        };
+
+        let regions = match target.memory_map() {
+            Ok(regions) => regions,
+            Err(err) => {
+                tprint!(self.panes.terminal, "Failed to read memory map: {err}");
+                Vec::new()
+            }
+        };
+        drop(target);
+
+        let map = memory::MemoryMap::new(self.ui_queue.clone(), regions);
+        self.panes.mapping.insert(MEMORY_MAP, PanelKind::MemoryMap(map));
+    }
+
+    /// Open the target's memory at an address in the memory view.
+    pub fn show_memory(&mut self, addr: usize) {
+        if self.read_memory(addr) {
+            self.goto_window(MEMORY);
+        }
+    }
+
+    /// Read the target's memory shown in the memory view, returning whether that succeeded.
+    fn read_memory(&mut self, addr: usize) -> bool {
+        let mut target = match stopped(&self.debugger, &mut self.panes.terminal) {
+            Some(target) => target,
+            None => return false,
+        };
+
+        let bytes = match target.read_memory(addr as u64, memory::VIEW_SIZE) {
+            Ok(bytes) => bytes,
+            Err(err) => {
+                tprint!(
+                    self.panes.terminal,
+                    "Failed to read memory at {addr:#X}: {err}"
+                );
+                return false;
+            }
+        };
+        drop(target);
+
+        let previous = match self.panes.mapping.get(MEMORY) {
+            Some(PanelKind::Memory(previous)) => Some(previous),
+            _ => None,
+        };
+
+        let view = memory::MemoryView::new(self.ui_queue.clone(), addr, bytes, previous);
+        self.panes.mapping.insert(MEMORY, PanelKind::Memory(view));
+        true
+    }
+
+    /// Address of the memory that's shown in the memory view.
+    fn memory_addr(&self) -> Option<usize> {
+        match self.panes.mapping.get(MEMORY) {
+            Some(PanelKind::Memory(view)) => Some(view.addr()),
+            _ => None,
+        }
+    }
+
+    pub fn write_memory(&mut self, addr: usize, bytes: &[u8]) {
+        let mut target = match stopped(&self.debugger, &mut self.panes.terminal) {
+            Some(target) => target,
+            None => return,
+        };
+
+        let result = target.write_memory(addr as u64, bytes);
+        drop(target);
+
+        if let Err(err) = result {
+            tprint!(
+                self.terminal(),
+                "Failed to write memory at {addr:#X}: {err}"
+            );
+            return;
+        }
+
+        tprint!(self.terminal(), "Wrote {} bytes at {addr:#X}.", bytes.len());
+
+        // code that's overwritten is disassembled again
+        if let Some(listing) = self.listing() {
+            listing.patch(addr, bytes);
+        }
+
+        if let Some(addr) = self.memory_addr() {
+            self.read_memory(addr);
+        }
+    }
+
+    /// Show the registers and stack of the target, and mark the instruction it's stopped at.
+    fn inspect_target(&mut self) {
+        let processor = match self.panes.processor.clone() {
+            Some(processor) => processor,
+            None => return,
+        };
+
+        let session = match self.debugger.as_ref() {
+            Some(session) => session,
+            None => return,
+        };
+
+        let mut target = match session.target() {
+            Some(target) => target,
+            None => return,
+        };
+
+        let registers = match target.registers() {
+            Ok(registers) => registers,
+            Err(err) => {
+                tprint!(self.panes.terminal, "Failed to read registers: {err}");
+                return;
+            }
+        };
+
+        // the stack might not be mapped, which is shown as an empty stack
+        let ui_queue = self.ui_queue.clone();
+        let modules = session.modules();
+        let symbols = &self.module_symbols;
+        let stack = registers.sp().map(|sp| {
+            let values = target.read_pointers(sp, stack::DEPTH).unwrap_or_default();
+            stack::Stack::new(&processor, ui_queue, sp, &values, |addr| {
+                module_function(modules, symbols, addr)
+            })
+        });
+        drop(target);
+
+        if let Some(stack) = stack {
+            self.panes.mapping.insert(STACK, PanelKind::Stack(stack));
+        }
+
+        let pc = registers.pc();
+        let previous = match self.panes.mapping.get(REGISTERS) {
+            Some(PanelKind::Registers(previous)) => Some(previous),
+            _ => None,
+        };
+
+        let registers = registers::Registers::new(registers, previous);
+        self.panes.mapping.insert(REGISTERS, PanelKind::Registers(registers));
+
+        if let Some(addr) = self.memory_addr() {
+            self.read_memory(addr);
+        }
+
+        self.show_live_trace();
+
+        match pc {
+            Some(pc) => {
+                if let Some(listing) = self.listing() {
+                    listing.set_pc(Some(pc as usize));
+                }
+            }
+            None => tprint!(self.terminal(), "Target didn't send its program counter."),
+        }
+    }
+
+    /// Report the target stopping after it was resumed.
+    pub(super) fn poll_debugger(&mut self) {
+        let stop = match self.debugger.as_mut().and_then(Session::poll) {
+            Some(stop) => stop,
+            None => return,
+        };
+
+        match stop {
+            Ok(stop) if stop.is_exit() => {
+                tprint!(self.terminal(), "Target {stop}.");
+                self.debugger = None;
+                self.forget_target();
+            }
+            Ok(stop) => {
+                tprint!(self.terminal(), "Target {stop}.");
+                self.load_module_symbols();
+                self.inspect_target();
+            }
+            Err(err) => {
+                tprint!(self.terminal(), "Lost connection to target: {err}");
+                self.debugger = None;
+                self.forget_target();
+            }
+        }
+    }
+}
+
+/// Lock the target for a request, which can't be made while it's running.
+fn stopped<'a>(
+    debugger: &'a Option<Session>,
+    terminal: &mut Terminal,
+) -> Option<MutexGuard<'a, Box<dyn Target>>> {
+    let session = match debugger {
+        Some(session) => session,
+        None => {
+            tprint!(terminal, "Not connected to a target.");
+            return None;
+        }
+    };
+
+    let target = session.target();
+    if target.is_none() {
+        tprint!(terminal, "Target is running.");
+    }
+
+    target
+}
+
+/// Function of a module the target loaded that contains an address, along with its address.
+fn module_function(
+    modules: &[Module],
+    symbols: &debugvault::Index,
+    addr: usize,
+) -> Option<(usize, String)> {
+    let module = modules
+        .iter()
+        .find(|module| (module.base..module.end).contains(&(addr as u64)))?;
+    let idx = match symbols.syms.search(addr) {
+        Ok(idx) => idx,
+        Err(idx) => idx.checked_sub(1)?,
+    };
+
+    let symbol = &symbols.syms[idx];
+    let inside = symbol.addr as u64 >= module.base;
+    inside.then(|| (symbol.addr, symbol.item.as_str().to_string()))
+}
+
+/// Ranges in which two copies of memory differ, merging the ones that are close together.
+fn differences(a: &[u8], b: &[u8]) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+
+    for idx in (0..a.len().min(b.len())).filter(|&idx| a[idx] != b[idx]) {
+        match ranges.last_mut() {
+            Some(range) if idx - range.end < GAP => range.end = idx + 1,
+            _ => ranges.push(idx..idx + 1),
+        }
+    }
+
+    ranges
+}
@@ -0,0 +1,156 @@
+use super::{PanelKind, EMULATION};
+use crate::common::*;
+use crate::{tprint, UIEvent, UiQueue};
+use config::CONFIG;
+use processor::{Emulation, Processor};
+use std::sync::Arc;
+use tokenizing::Token;
+
+/// Number of bytes written to memory that are shown per row.
+const BYTES_PER_ROW: usize = 16;
+
+pub struct EmulationInfo {
+    processor: Arc<Processor>,
+    ui_queue: Arc<UiQueue>,
+    args: Vec<u64>,
+    emulation: Result<Emulation, String>,
+}
+
+impl EmulationInfo {
+    pub fn new(
+        processor: Arc<Processor>,
+        ui_queue: Arc<UiQueue>,
+        args: Vec<u64>,
+        emulation: Result<Emulation, String>,
+    ) -> Self {
+        Self {
+            processor,
+            ui_queue,
+            args,
+            emulation,
+        }
+    }
+}
+
+impl super::Panels {
+    /// Run the function containing an address and show what it returned and wrote.
+    pub fn emulate(&mut self, addr: usize, args: Vec<u64>) {
+        let processor = match self.panes.processor.clone() {
+            Some(processor) => processor,
+            None => {
+                tprint!(self.terminal(), "No targets loaded.");
+                return;
+            }
+        };
+
+        let emulation = processor.emulate(addr, &args).map_err(|err| err.to_string());
+        match emulation {
+            Ok(ref emulation) => tprint!(
+                self.terminal(),
+                "Function {} after {} instructions.",
+                emulation.stop,
+                emulation.steps
+            ),
+            Err(ref err) => tprint!(self.terminal(), "Failed to emulate: {err}"),
+        }
+
+        let info = EmulationInfo::new(processor, self.ui_queue.clone(), args, emulation);
+        self.panes.mapping.insert(EMULATION, PanelKind::Emulation(info));
+        self.goto_window(EMULATION);
+    }
+}
+
+fn stat(ui: &mut egui::Ui, name: &str, value: String) {
+    let tokens = vec![
+        Token::from_string(format!("{name:<20}"), CONFIG.colors.text),
+        Token::from_string(value, CONFIG.colors.asm.immediate),
+    ];
+
+    ui.label(tokens_to_layoutjob(tokens));
+}
+
+/// Link to an instruction, labeled with its address.
+fn addr_link(ui: &mut egui::Ui, ui_queue: &UiQueue, name: &str, addr: usize) {
+    let tokens = vec![
+        Token::from_string(format!("{name:<20}"), CONFIG.colors.text),
+        Token::from_string(format!("{addr:#X}"), CONFIG.colors.asm.immediate),
+    ];
+
+    if ui.link(tokens_to_layoutjob(tokens)).clicked() {
+        ui_queue.push(UIEvent::GotoAddr(addr));
+    }
+}
+
+/// Bytes as hex followed by their ascii representation, which is what decoded strings look like.
+fn row_tokens(addr: usize, bytes: &[u8]) -> Vec<Token> {
+    let hex: String = bytes.iter().map(|byte| format!("{byte:02x} ")).collect();
+    let text: String = bytes
+        .iter()
+        .map(|&byte| match byte {
+            0x20..=0x7e => byte as char,
+            _ => '.',
+        })
+        .collect();
+
+    vec![
+        Token::from_string(format!("{addr:0>12X}  "), CONFIG.colors.text),
+        Token::from_string(format!("{hex:<48} "), CONFIG.colors.asm.immediate),
+        Token::from_string(text, CONFIG.colors.asm.primitive),
+    ]
+}
+
+impl Display for EmulationInfo {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        let area = egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
+
+        area.show(ui, |ui| {
+            let emulation = match self.emulation {
+                Ok(ref emulation) => emulation,
+                Err(ref err) => {
+                    ui.label(err);
+                    return;
+                }
+            };
+
+            let name = super::call_graph::name(&self.processor, emulation.addr);
+            let args: Vec<String> = self.args.iter().map(|arg| format!("{arg:#x}")).collect();
+            stat(ui, "Function", format!("{name}({})", args.join(", ")));
+            stat(ui, "Stopped", emulation.stop.to_string());
+            addr_link(ui, &self.ui_queue, "At", emulation.pc);
+            stat(ui, "Instructions", emulation.steps.to_string());
+
+            match emulation.returned {
+                Some(value) => stat(ui, "Returned", format!("{value:#x} ({})", value as i64)),
+                None => stat(ui, "Returned", "unknown".to_string()),
+            }
+
+            ui.separator();
+            let header = format!("Memory written ({} regions)", emulation.writes.len());
+            egui::CollapsingHeader::new(header).default_open(true).show(ui, |ui| {
+                for (addr, bytes) in emulation.writes.iter() {
+                    for (idx, row) in bytes.chunks(BYTES_PER_ROW).enumerate() {
+                        let tokens = row_tokens(addr + idx * BYTES_PER_ROW, row);
+                        ui.label(tokens_to_layoutjob(tokens));
+                    }
+                }
+            });
+
+            let header = format!("Registers ({})", emulation.registers.len());
+            egui::CollapsingHeader::new(header).default_open(false).show(ui, |ui| {
+                for (name, value) in emulation.registers.iter() {
+                    stat(ui, name, format!("{value:#x}"));
+                }
+            });
+
+            // the outcome is only as good as the instructions that are modelled
+            if !emulation.skipped.is_empty() {
+                let header = format!("Not modelled ({})", emulation.skipped.len());
+                egui::CollapsingHeader::new(header).default_open(false).show(ui, |ui| {
+                    for &addr in emulation.skipped.iter() {
+                        addr_link(ui, &self.ui_queue, "Instruction", addr);
+                    }
+                });
+            }
+        });
+    }
+}
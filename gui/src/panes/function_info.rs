@@ -0,0 +1,125 @@
+use crate::common::*;
+use crate::{UIEvent, UiQueue};
+use config::CONFIG;
+use processor::{FunctionStats, Processor};
+use std::sync::Arc;
+use tokenizing::Token;
+
+pub struct FunctionInfo {
+    processor: Arc<Processor>,
+    ui_queue: Arc<UiQueue>,
+    stats: Option<FunctionStats>,
+}
+
+impl FunctionInfo {
+    pub fn new(processor: Arc<Processor>, ui_queue: Arc<UiQueue>, addr: usize) -> Self {
+        Self {
+            stats: processor.function_stats(addr),
+            processor,
+            ui_queue,
+        }
+    }
+}
+
+fn stat(ui: &mut egui::Ui, name: &str, value: String) {
+    let tokens = vec![
+        Token::from_string(format!("{name:<20}"), CONFIG.colors.text),
+        Token::from_string(value, CONFIG.colors.asm.immediate),
+    ];
+
+    ui.label(tokens_to_layoutjob(tokens));
+}
+
+/// Number of instructions in a category along with their share of the function.
+fn category(ui: &mut egui::Ui, name: &str, count: usize, total: usize) {
+    let share = match total {
+        0 => 0.0,
+        _ => count as f64 / total as f64 * 100.0,
+    };
+
+    stat(ui, name, format!("{count} ({share:.1}%)"));
+}
+
+impl Display for FunctionInfo {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        let area = egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
+
+        area.show(ui, |ui| {
+            let stats = match self.stats {
+                Some(ref stats) => stats,
+                None => {
+                    ui.label("Not inside of a function.");
+                    return;
+                }
+            };
+
+            stat(ui, "Function", stats.name.clone());
+            stat(ui, "Address", format!("{:#X}", stats.addr));
+            stat(ui, "Instructions", stats.instructions.to_string());
+            category(ui, "  ALU", stats.alu, stats.instructions);
+            category(ui, "  Memory", stats.memory, stats.instructions);
+            category(ui, "  Branch", stats.branch, stats.instructions);
+            category(ui, "  SIMD", stats.simd, stats.instructions);
+            category(ui, "  Other", stats.other, stats.instructions);
+            stat(ui, "Estimated cycles", format!("~{}", stats.cycles));
+            stat(ui, "Stack frame", format!("{:#x} bytes", stats.stack_frame));
+
+            ui.separator();
+            let header = format!("Callees ({})", stats.callees.len());
+            egui::CollapsingHeader::new(header).default_open(true).show(ui, |ui| {
+                for &addr in stats.callees.iter() {
+                    let mut tokens = vec![
+                        Token::from_string(format!("{addr:0>10X}"), CONFIG.colors.text),
+                        Token::from_str(" | ", CONFIG.colors.text),
+                    ];
+                    tokens.extend(super::call_graph::name_tokens(&self.processor, addr));
+
+                    if ui.link(tokens_to_layoutjob(tokens)).clicked() {
+                        self.ui_queue.push(UIEvent::GotoAddr(addr));
+                    }
+                }
+            });
+
+            let header = format!("Loops ({})", stats.loops.len());
+            egui::CollapsingHeader::new(header).default_open(true).show(ui, |ui| {
+                for def in stats.loops.iter() {
+                    // nested loops are indented below the loops containing them
+                    let blocks = match def.body.len() {
+                        1 => "1 block".to_string(),
+                        n => format!("{n} blocks"),
+                    };
+
+                    let tokens = vec![
+                        Token::from_string("  ".repeat(def.depth), CONFIG.colors.text),
+                        Token::from_string(format!("{:0>10X}", def.header), CONFIG.colors.text),
+                        Token::from_str(" | ", CONFIG.colors.text),
+                        Token::from_string(blocks, CONFIG.colors.asm.immediate),
+                    ];
+
+                    if ui.link(tokens_to_layoutjob(tokens)).clicked() {
+                        self.ui_queue.push(UIEvent::GotoAddr(def.header));
+                    }
+                }
+            });
+
+            let strings: Vec<_> = self.processor.strings_used_by(stats.addr).collect();
+            let header = format!("Strings ({})", strings.len());
+            egui::CollapsingHeader::new(header).default_open(true).show(ui, |ui| {
+                for string in strings {
+                    let tokens = vec![
+                        Token::from_string(format!("{:0>10X}", string.addr), CONFIG.colors.text),
+                        Token::from_str(" | ", CONFIG.colors.text),
+                        Token::from_string(
+                            format!("\"{}\"", string.text.escape_debug()),
+                            CONFIG.colors.asm.string,
+                        ),
+                    ];
+
+                    if ui.link(tokens_to_layoutjob(tokens)).clicked() {
+                        self.ui_queue.push(UIEvent::GotoAddr(string.addr));
+                    }
+                }
+            });
+        });
+    }
+}
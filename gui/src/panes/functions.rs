@@ -3,8 +3,9 @@ use crate::{UiQueue, UIEvent};
 use config::CONFIG;
 use processor_shared::Addressed;
 use processor::Processor;
+use std::collections::BTreeSet;
 use std::sync::Arc;
-use tokenizing::{colors, Token};
+use tokenizing::Token;
 
 pub struct Functions {
     processor: Arc<Processor>,
@@ -13,12 +14,38 @@ pub struct Functions {
     lines_count: usize,
     min_row: usize,
     max_row: usize,
+    /// Instructions that ran according to a trace, which are shown as coverage of each function.
+    covered: BTreeSet<usize>,
+    /// The entrypoint along with the code that runs without being called, like constructors.
+    entrypoints: Vec<(usize, Vec<Token>)>,
 }
 
 impl Functions {
     pub fn new(processor: Arc<Processor>, ui_queue: Arc<UiQueue>) -> Self {
         let function_count = processor.index.named_funcs_count();
 
+        let mut entrypoints = Vec::new();
+        let entry = (processor.entrypoint, "Entrypoint".to_string());
+        let starts = processor.autostart.iter().map(|start| (start.addr, start.kind.to_string()));
+        let starts = std::iter::once(entry).chain(starts);
+        for (addr, kind) in starts.filter(|(addr, _)| *addr != 0) {
+            let mut tokens = vec![
+                Token::from_string(format!("{addr:0>10X}"), CONFIG.colors.text),
+                Token::from_str(" | ", CONFIG.colors.text),
+            ];
+
+            match processor.index.get_sym_by_addr(addr) {
+                Some(symbol) => tokens.extend(symbol.name().iter().cloned()),
+                None => {
+                    let name = format!("sub_{addr:x}");
+                    tokens.push(Token::from_string(name, CONFIG.colors.text));
+                }
+            }
+
+            tokens.push(Token::from_string(format!(" ({kind})"), CONFIG.colors.comment));
+            entrypoints.push((addr, tokens));
+        }
+
         Self {
             processor,
             ui_queue,
@@ -26,14 +53,66 @@ impl Functions {
             lines_count: function_count,
             min_row: 0,
             max_row: 0,
+            covered: BTreeSet::new(),
+            entrypoints,
+        }
+    }
+
+    pub fn set_coverage(&mut self, covered: BTreeSet<usize>) {
+        self.covered = covered;
+
+        // tokenize the functions that are shown again
+        self.max_row = self.min_row;
+    }
+
+    /// Menu listing a function's callers and callees.
+    fn context_menu(&self, ui: &mut egui::Ui, addr: usize) {
+        let callers: Vec<usize> = self.processor.callers(addr).collect();
+        let callees: Vec<usize> = self.processor.callees(addr).collect();
+
+        for (title, addrs) in [("Callers", callers), ("Callees", callees)] {
+            ui.menu_button(format!("{title} ({})", addrs.len()), |ui| {
+                for addr in addrs {
+                    let name = super::call_graph::name(&self.processor, addr);
+                    if ui.button(name).clicked() {
+                        self.ui_queue.push(UIEvent::GotoAddr(addr));
+                        ui.close_menu();
+                    }
+                }
+            });
+        }
+
+        if ui.button("Show in call graph").clicked() {
+            self.ui_queue.push(UIEvent::ShowCallGraph(addr));
+            ui.close_menu();
         }
     }
 }
 
-fn tokenize_functions(index: &debugvault::Index, range: std::ops::Range<usize>) -> Vec<(usize, Vec<Token>)> {
+/// Percentage of a function's instructions that ran.
+fn coverage(processor: &Processor, covered: &BTreeSet<usize>, addr: usize) -> usize {
+    let (start, end, _) = match processor.function_bounds(addr) {
+        Some(bounds) => bounds,
+        None => return 0,
+    };
+
+    let ran = covered.range(start..end).count();
+    let total = processor.instructions_in(start..end).len();
+    match total {
+        0 => 0,
+        total => ran * 100 / total,
+    }
+}
+
+fn tokenize_functions(
+    processor: &Processor,
+    covered: &BTreeSet<usize>,
+    range: std::ops::Range<usize>,
+) -> Vec<(usize, Vec<Token>)> {
     let mut functions = Vec::new();
     let lines_to_read = range.end - range.start;
-    let lines = index
+    let lines = processor
+        .index
         .functions()
         .filter(|func| !func.item.intrinsic())
         .skip(range.start)
@@ -41,8 +120,19 @@ fn tokenize_functions(index: &debugvault::Index, range: std::ops::Range<usize>)
 
     for Addressed { addr, item } in lines {
         let mut tokens = Vec::new();
-        tokens.push(Token::from_string(format!("{addr:0>10X}"), colors::WHITE));
-        tokens.push(Token::from_str(" | ", colors::WHITE));
+        tokens.push(Token::from_string(format!("{addr:0>10X}"), CONFIG.colors.text));
+        tokens.push(Token::from_str(" | ", CONFIG.colors.text));
+
+        if !covered.is_empty() {
+            let percentage = coverage(processor, covered, *addr);
+            let color = match percentage {
+                0 => CONFIG.colors.asm.annotation,
+                _ => CONFIG.colors.asm.immediate,
+            };
+
+            tokens.push(Token::from_string(format!("{percentage:>3}%"), color));
+            tokens.push(Token::from_str(" | ", CONFIG.colors.text));
+        }
 
         if let Some(module) = item.module() {
             tokens.push(Token::from_string(module.to_string(), CONFIG.colors.asm.component));
@@ -61,11 +151,26 @@ fn tokenize_functions(index: &debugvault::Index, range: std::ops::Range<usize>)
 
 impl Display for Functions {
     fn show(&mut self, ui: &mut egui::Ui) {
+        if !self.entrypoints.is_empty() {
+            let header = format!("Entrypoints ({})", self.entrypoints.len());
+            egui::CollapsingHeader::new(header).show(ui, |ui| {
+                for (addr, line) in self.entrypoints.iter() {
+                    let response = ui.link(tokens_to_layoutjob(line.clone()));
+                    if response.clicked() {
+                        self.ui_queue.push(UIEvent::GotoAddr(*addr));
+                    }
+
+                    response.context_menu(|ui| self.context_menu(ui, *addr));
+                }
+            });
+            ui.separator();
+        }
+
         let area = egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
 
-        area.show_rows(ui, FONT.size, self.lines_count, |ui, row_range| {
+        area.show_rows(ui, font().size, self.lines_count, |ui, row_range| {
             if row_range != (self.min_row..self.max_row) {
-                self.lines = tokenize_functions(&self.processor.index, row_range.clone());
+                self.lines = tokenize_functions(&self.processor, &self.covered, row_range.clone());
                 self.lines_count = self.processor.index.named_funcs_count();
                 self.min_row = row_range.start;
                 self.max_row = row_range.end;
@@ -73,10 +178,13 @@ impl Display for Functions {
 
             for (addr, line) in self.lines.iter() {
                 let output = tokens_to_layoutjob(line.clone());
+                let response = ui.link(output);
 
-                if ui.link(output).clicked() {
+                if response.clicked() {
                     self.ui_queue.push(UIEvent::GotoAddr(*addr));
                 }
+
+                response.context_menu(|ui| self.context_menu(ui, *addr));
             }
         });
     }
@@ -0,0 +1,120 @@
+use crate::common::*;
+use crate::{UIEvent, UiQueue};
+use config::CONFIG;
+use processor::Processor;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokenizing::Token;
+
+/// Library of imports that aren't associated with one.
+const UNKNOWN_MODULE: &str = "unknown";
+
+pub struct Imports {
+    ui_queue: Arc<UiQueue>,
+    /// Imports grouped by the library they're imported from.
+    imports: BTreeMap<String, Vec<(usize, Vec<Token>)>>,
+    exports: Vec<(usize, Vec<Token>)>,
+}
+
+impl Imports {
+    pub fn new(processor: &Processor, ui_queue: Arc<UiQueue>) -> Self {
+        let mut imports: BTreeMap<String, Vec<(usize, Vec<Token>)>> = BTreeMap::new();
+        for import in processor.imports.iter() {
+            let module = import.module.as_deref().unwrap_or(UNKNOWN_MODULE);
+            let tokens = vec![
+                Token::from_string(format!("{:0>10X}", import.addr), CONFIG.colors.text),
+                Token::from_str(" | ", CONFIG.colors.text),
+                Token::from_string(import.name.clone(), CONFIG.colors.src.function),
+            ];
+
+            imports.entry(module.to_string()).or_default().push((import.addr, tokens));
+        }
+
+        for imports in imports.values_mut() {
+            imports.sort_by_key(|(addr, _)| *addr);
+        }
+
+        let mut exports = Vec::new();
+        for export in processor.exports.iter() {
+            let mut tokens = Vec::new();
+            if let Some(ordinal) = export.ordinal {
+                tokens.push(Token::from_string(format!("{ordinal:>5} "), CONFIG.colors.hint));
+            }
+
+            tokens.push(Token::from_string(
+                format!("{:0>10X}", export.addr),
+                CONFIG.colors.text,
+            ));
+            tokens.push(Token::from_str(" | ", CONFIG.colors.text));
+            tokens.push(Token::from_string(
+                export.name.clone(),
+                CONFIG.colors.src.function,
+            ));
+
+            if let Some(forward) = export.forward.as_ref() {
+                tokens.push(Token::from_str(" -> ", CONFIG.colors.delimiter));
+                tokens.push(Token::from_string(
+                    forward.clone(),
+                    CONFIG.colors.asm.component,
+                ));
+            }
+
+            exports.push((export.addr, tokens));
+        }
+
+        exports.sort_by_key(|(addr, _)| *addr);
+
+        Self {
+            ui_queue,
+            imports,
+            exports,
+        }
+    }
+}
+
+/// Show a row that jumps to its address when double-clicked.
+fn row(ui: &mut egui::Ui, ui_queue: &UiQueue, addr: usize, tokens: &[Token]) {
+    let label = egui::Label::new(tokens_to_layoutjob(tokens.to_vec())).sense(egui::Sense::click());
+
+    // forwarded exports don't have an address
+    if ui.add(label).double_clicked() && addr != 0 {
+        ui_queue.push(UIEvent::GotoAddr(addr));
+    }
+}
+
+impl Display for Imports {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        let area = egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
+
+        area.show(ui, |ui| {
+            let count: usize = self.imports.values().map(Vec::len).sum();
+            egui::CollapsingHeader::new(format!("Imports ({count})"))
+                .default_open(true)
+                .show(ui, |ui| {
+                    if self.imports.is_empty() {
+                        ui.label("No imports.");
+                    }
+
+                    for (module, imports) in self.imports.iter() {
+                        let header = format!("{module} ({})", imports.len());
+                        egui::CollapsingHeader::new(header).show(ui, |ui| {
+                            for (addr, tokens) in imports.iter() {
+                                row(ui, &self.ui_queue, *addr, tokens);
+                            }
+                        });
+                    }
+                });
+
+            let header = format!("Exports ({})", self.exports.len());
+            egui::CollapsingHeader::new(header).default_open(true).show(ui, |ui| {
+                if self.exports.is_empty() {
+                    ui.label("No exports.");
+                }
+
+                for (addr, tokens) in self.exports.iter() {
+                    row(ui, &self.ui_queue, *addr, tokens);
+                }
+            });
+        });
+    }
+}
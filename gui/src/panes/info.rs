@@ -0,0 +1,459 @@
+use crate::common::*;
+use crate::{tprint, UIEvent, UiQueue};
+use config::CONFIG;
+use processor::{Processor, Verdict};
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+use tokenizing::Token;
+
+pub struct Info {
+    processor: Arc<Processor>,
+    ui_queue: Arc<UiQueue>,
+}
+
+impl Info {
+    pub fn new(processor: Arc<Processor>, ui_queue: Arc<UiQueue>) -> Self {
+        Self {
+            processor,
+            ui_queue,
+        }
+    }
+}
+
+fn field(ui: &mut egui::Ui, name: &str, value: String) {
+    let tokens = vec![
+        Token::from_string(format!("{name:<16}"), CONFIG.colors.text),
+        Token::from_string(value, CONFIG.colors.asm.immediate),
+    ];
+
+    ui.label(tokens_to_layoutjob(tokens));
+}
+
+/// Ask where to save part of the binary and write it there.
+fn extract(processor: &Processor, range: Range<usize>, name: String) {
+    let path = match rfd::FileDialog::new().set_file_name(name).save_file() {
+        Some(path) => path,
+        None => return,
+    };
+
+    if let Err(err) = std::fs::write(&path, &processor.binary()[range]) {
+        log::report!(
+            log::Level::Error,
+            "Failed to save '{}': {err}.",
+            path.display()
+        );
+    }
+}
+
+impl Display for Info {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        let area = egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
+        let processor = &self.processor;
+
+        area.show(ui, |ui| {
+            if processor.detections.iter().any(|detection| detection.is_severe()) {
+                ui.colored_label(
+                    CONFIG.colors.asm.invalid,
+                    "Likely packed or obfuscated, the disassembly may be meaningless.",
+                );
+                ui.add_space(5.0);
+            }
+
+            field(ui, "Path", format!("{:?}", processor.path));
+            match (processor.bytecode_format(), processor.coredump.as_ref()) {
+                (Some(format), _) => field(ui, "Format", format.to_string()),
+                (None, Some(dump)) => {
+                    field(ui, "Format", dump.format().to_string());
+                    field(ui, "Architecture", format!("{:?}", processor.arch()));
+                    field(ui, "Threads", dump.threads.len().to_string());
+                    field(ui, "Modules", dump.modules.len().to_string());
+                    if let Some(ref reason) = dump.reason {
+                        field(ui, "Crashed with", reason.clone());
+                    }
+                }
+                (None, None) => {
+                    field(ui, "Format", format!("{:?}", processor.format()));
+                    field(ui, "Architecture", format!("{:?}", processor.arch()));
+                }
+            }
+            field(ui, "Entrypoint", format!("{:#X}", processor.entrypoint));
+            field(ui, "Image base", format!("{:#X}", processor.image_base));
+            field(ui, "Sections", processor.sections().count().to_string());
+            field(ui, "Segments", processor.segments().count().to_string());
+            field(
+                ui,
+                "Functions",
+                processor.index.named_funcs_count().to_string(),
+            );
+
+            ui.separator();
+            egui::CollapsingHeader::new("Hashes").default_open(true).show(ui, |ui| {
+                for (name, hash) in processor.hashes.iter() {
+                    ui.horizontal(|ui| {
+                        field(ui, name, hash.to_string());
+                        if ui.small_button("Copy").clicked() {
+                            ui.ctx().copy_text(hash.to_string());
+                        }
+                    });
+                }
+            });
+
+            if let Some(ref rich) = processor.rich {
+                ui.separator();
+                egui::CollapsingHeader::new("Rich header").default_open(true).show(ui, |ui| {
+                    if let Some(linker) = rich.linker() {
+                        field(ui, "Linked with", linker.to_string());
+                    }
+                    field(ui, "Offset", format!("{:#X}", rich.offset));
+                    field(ui, "Key", format!("{:#010X}", rich.key));
+                    if !rich.valid {
+                        ui.colored_label(
+                            CONFIG.colors.asm.invalid,
+                            "The checksum doesn't match the key, the header was likely forged.",
+                        );
+                    }
+
+                    for entry in rich.entries.iter() {
+                        let tool = match entry.tool() {
+                            Some(tool) => tool.to_string(),
+                            None => format!("Product {:#X}", entry.product),
+                        };
+
+                        let tokens = vec![
+                            Token::from_string(format!("{tool:<32}"), CONFIG.colors.text),
+                            Token::from_string(
+                                format!("{:<24}", entry.toolchain().unwrap_or("")),
+                                CONFIG.colors.asm.immediate,
+                            ),
+                            Token::from_string(
+                                format!("build {:<8}{} objects", entry.build, entry.count),
+                                CONFIG.colors.comment,
+                            ),
+                        ];
+
+                        ui.label(tokens_to_layoutjob(tokens));
+                    }
+                });
+            }
+
+            if let Some(ref signature) = processor.signature {
+                ui.separator();
+                egui::CollapsingHeader::new("Signature").default_open(true).show(ui, |ui| {
+                    field(ui, "Kind", signature.kind.to_string());
+                    field(ui, "Digest", signature.algorithm.to_string());
+                    if let Some(ref identifier) = signature.identifier {
+                        field(ui, "Identifier", identifier.clone());
+                    }
+                    if let Some(ref team) = signature.team {
+                        field(ui, "Team", team.clone());
+                    }
+                    if let Some(ref time) = signature.signing_time {
+                        field(ui, "Signed at", time.clone());
+                    }
+                    if let Some(ref time) = signature.timestamp {
+                        field(ui, "Timestamp", time.clone());
+                    }
+
+                    match processor.signature_covers() {
+                        Some(true) => ui.colored_label(
+                            CONFIG.colors.success,
+                            "The signed digest matches the current bytes.",
+                        ),
+                        _ => ui.colored_label(
+                            CONFIG.colors.asm.invalid,
+                            "The signed digest doesn't match, the binary was modified since.",
+                        ),
+                    };
+
+                    if signature.adhoc {
+                        ui.label("Ad-hoc signed, there's no signer.");
+                    }
+
+                    for (depth, cert) in signature.chain.iter().enumerate() {
+                        let tokens = vec![
+                            Token::from_string(
+                                format!("{:indent$}{:<32}", "", cert.subject, indent = depth * 2),
+                                CONFIG.colors.text,
+                            ),
+                            Token::from_string(
+                                format!("{} to {}", cert.not_before, cert.not_after),
+                                CONFIG.colors.comment,
+                            ),
+                        ];
+
+                        ui.label(tokens_to_layoutjob(tokens));
+                    }
+                });
+            }
+
+            ui.separator();
+            if processor.detections.is_empty() {
+                ui.label("No signs of packing or obfuscation.");
+            }
+
+            for detection in processor.detections.iter() {
+                let color = match detection.is_severe() {
+                    true => CONFIG.colors.asm.invalid,
+                    false => CONFIG.colors.comment,
+                };
+
+                ui.colored_label(color, detection.to_string());
+            }
+
+            if !processor.mitigations.is_empty() {
+                ui.separator();
+                egui::CollapsingHeader::new("Mitigations").default_open(true).show(ui, |ui| {
+                    for mitigation in processor.mitigations.iter() {
+                        let color = match mitigation.verdict {
+                            Verdict::Enabled => CONFIG.colors.success,
+                            Verdict::Partial => CONFIG.colors.asm.immediate,
+                            Verdict::Disabled => CONFIG.colors.asm.invalid,
+                        };
+
+                        let tokens = vec![
+                            Token::from_string(
+                                format!("{:<16}", mitigation.name),
+                                CONFIG.colors.text,
+                            ),
+                            Token::from_string(format!("{:<10}", mitigation.verdict), color),
+                            Token::from_string(mitigation.rationale.clone(), CONFIG.colors.comment),
+                        ];
+
+                        ui.label(tokens_to_layoutjob(tokens));
+                    }
+                });
+            }
+
+            if let Some(ref overlay) = processor.overlay {
+                ui.separator();
+                egui::CollapsingHeader::new("Overlay").default_open(true).show(ui, |ui| {
+                    field(ui, "Offset", format!("{:#X}", overlay.offset));
+                    field(ui, "Size", overlay.size.to_string());
+                    field(ui, "Entropy", format!("{:.2}", overlay.entropy));
+                    if let Some(kind) = overlay.kind {
+                        field(ui, "Kind", kind.to_string());
+                    }
+                    if overlay.signature != 0 {
+                        field(ui, "Signature", format!("{} bytes", overlay.signature));
+                    }
+
+                    if ui.button("Extract").clicked() {
+                        extract(processor, overlay.range(), "overlay.bin".to_string());
+                    }
+                });
+            }
+
+            if !processor.embedded.is_empty() {
+                ui.separator();
+                let header = format!("Embedded files ({})", processor.embedded.len());
+                egui::CollapsingHeader::new(header).default_open(true).show(ui, |ui| {
+                    for embedded in processor.embedded.iter() {
+                        ui.horizontal(|ui| {
+                            let tokens = vec![
+                                Token::from_string(
+                                    format!("{:<#16X}", embedded.offset),
+                                    CONFIG.colors.text,
+                                ),
+                                Token::from_string(
+                                    format!("{:<20}", embedded.kind.to_string()),
+                                    CONFIG.colors.asm.immediate,
+                                ),
+                                Token::from_string(
+                                    format!("{} bytes", embedded.size),
+                                    CONFIG.colors.comment,
+                                ),
+                            ];
+
+                            ui.label(tokens_to_layoutjob(tokens));
+                            if ui.small_button("Extract").clicked() {
+                                extract(processor, embedded.range(), embedded.file_name());
+                            }
+                        });
+                    }
+                });
+            }
+
+            if !processor.crypto.is_empty() {
+                ui.separator();
+                let header = format!("Cryptographic constants ({})", processor.crypto.len());
+                egui::CollapsingHeader::new(header).default_open(true).show(ui, |ui| {
+                    for constant in processor.crypto.iter() {
+                        let mut tokens = vec![
+                            Token::from_string(
+                                format!("{:<#16X}", constant.addr),
+                                CONFIG.colors.text,
+                            ),
+                            Token::from_string(
+                                constant.name.to_string(),
+                                CONFIG.colors.asm.immediate,
+                            ),
+                        ];
+
+                        if let Some(ref function) = constant.function {
+                            tokens.push(Token::from_string(
+                                format!(" in {function}"),
+                                CONFIG.colors.comment,
+                            ));
+                        }
+
+                        let label = ui.selectable_label(false, tokens_to_layoutjob(tokens));
+                        if label.double_clicked() {
+                            self.ui_queue.push(UIEvent::GotoAddr(constant.addr));
+                        }
+                    }
+                });
+            }
+
+            if !processor.load_commands.is_empty() {
+                ui.separator();
+                let header = format!("Load commands ({})", processor.load_commands.len());
+                egui::CollapsingHeader::new(header).default_open(true).show(ui, |ui| {
+                    for cmd in processor.load_commands.iter() {
+                        let tokens = vec![
+                            Token::from_string(format!("{:<28}", cmd.name), CONFIG.colors.text),
+                            Token::from_string(cmd.desc.clone(), CONFIG.colors.comment),
+                        ];
+
+                        ui.label(tokens_to_layoutjob(tokens));
+                    }
+                });
+            }
+        });
+    }
+}
+
+impl super::Panels {
+    /// Print the data appended past the end of the image, or save it to a file.
+    pub fn overlay(&mut self, path: Option<&Path>) {
+        let processor = match self.processor() {
+            Some(processor) => processor.clone(),
+            None => {
+                tprint!(self.terminal(), "No targets loaded.");
+                return;
+            }
+        };
+
+        let overlay = match processor.overlay {
+            Some(ref overlay) => overlay,
+            None => {
+                tprint!(self.terminal(), "No data is appended to the image.");
+                return;
+            }
+        };
+
+        let path = match path {
+            Some(path) => path,
+            None => {
+                tprint!(self.terminal(), "{overlay}");
+                return;
+            }
+        };
+
+        match std::fs::write(path, &processor.binary()[overlay.range()]) {
+            Ok(()) => tprint!(
+                self.terminal(),
+                "Saved {} bytes to '{}'.",
+                overlay.size,
+                path.display()
+            ),
+            Err(err) => tprint!(
+                self.terminal(),
+                "Failed to save '{}': {err}.",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl super::Panels {
+    /// Print the files embedded in the binary, or extract them into a directory.
+    pub fn carve(&mut self, dir: Option<&Path>) {
+        let processor = match self.processor() {
+            Some(processor) => processor.clone(),
+            None => {
+                tprint!(self.terminal(), "No targets loaded.");
+                return;
+            }
+        };
+
+        if processor.embedded.is_empty() {
+            tprint!(self.terminal(), "No embedded files were found.");
+            return;
+        }
+
+        let dir = match dir {
+            Some(dir) => dir,
+            None => {
+                for embedded in processor.embedded.iter() {
+                    tprint!(
+                        self.terminal(),
+                        "{:#X}: {} ({} bytes)",
+                        embedded.offset,
+                        embedded.kind,
+                        embedded.size
+                    );
+                }
+                return;
+            }
+        };
+
+        if let Err(err) = std::fs::create_dir_all(dir) {
+            tprint!(
+                self.terminal(),
+                "Failed to create '{}': {err}.",
+                dir.display()
+            );
+            return;
+        }
+
+        for embedded in processor.embedded.iter() {
+            let path = dir.join(embedded.file_name());
+            if let Err(err) = std::fs::write(&path, &processor.binary()[embedded.range()]) {
+                tprint!(
+                    self.terminal(),
+                    "Failed to save '{}': {err}.",
+                    path.display()
+                );
+                return;
+            }
+        }
+
+        tprint!(
+            self.terminal(),
+            "Extracted {} files to '{}'.",
+            processor.embedded.len(),
+            dir.display()
+        );
+    }
+}
+
+impl super::Panels {
+    /// Print the constants of cryptographic algorithms found in the binary.
+    pub fn crypto(&mut self) {
+        let processor = match self.processor() {
+            Some(processor) => processor.clone(),
+            None => {
+                tprint!(self.terminal(), "No targets loaded.");
+                return;
+            }
+        };
+
+        if processor.crypto.is_empty() {
+            tprint!(self.terminal(), "No cryptographic constants were found.");
+            return;
+        }
+
+        for constant in processor.crypto.iter() {
+            match constant.function {
+                Some(ref function) => tprint!(
+                    self.terminal(),
+                    "{:#X}: {} in {function}",
+                    constant.addr,
+                    constant.name
+                ),
+                None => tprint!(self.terminal(), "{:#X}: {}", constant.addr, constant.name),
+            }
+        }
+    }
+}
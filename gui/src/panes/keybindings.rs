@@ -0,0 +1,204 @@
+//! Running actions bound to shortcuts in the keymap, and the dialog for rebinding them.
+
+use super::actions::ACTIONS;
+use crate::common::*;
+use crate::tprint;
+use config::keymap::{Chord, Keymap, Match, Matcher, Preset, Shortcut};
+use config::CONFIG;
+use std::collections::BTreeMap;
+
+pub struct Keybindings {
+    keymap: Keymap,
+    /// Shortcuts of the keymap by action, parsed once.
+    bindings: BTreeMap<String, Shortcut>,
+    matcher: Matcher,
+    /// Whether the last key pressed was consumed, in which case so is the text it typed.
+    consumed_text: bool,
+    /// Whether the dialog is open.
+    open: bool,
+    /// Shortcuts as they're typed in the dialog, by action.
+    edits: BTreeMap<&'static str, String>,
+}
+
+impl Keybindings {
+    pub fn load() -> Self {
+        let keymap = Keymap::load();
+
+        Self {
+            bindings: keymap.bindings(),
+            keymap,
+            matcher: Matcher::default(),
+            consumed_text: false,
+            open: false,
+            edits: BTreeMap::new(),
+        }
+    }
+
+    pub fn shortcut(&self, action: &str) -> Option<&Shortcut> {
+        self.bindings.get(action)
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Start editing the shortcuts as they're currently bound.
+    fn reset_edits(&mut self) {
+        self.bindings = self.keymap.bindings();
+        self.edits = ACTIONS
+            .iter()
+            .map(|entry| {
+                let shortcut = self.bindings.get(entry.id);
+                (
+                    entry.id,
+                    shortcut.map(ToString::to_string).unwrap_or_default(),
+                )
+            })
+            .collect();
+    }
+}
+
+impl super::Panels {
+    /// Run the actions whose shortcuts were pressed, consuming their keys.
+    pub(super) fn handle_shortcuts(&mut self, events: &mut Vec<egui::Event>) {
+        // keys that edit text belong to the terminal while a command is typed
+        let typing = !self.panes.terminal.current_line().is_empty();
+        let keys = &mut self.keybindings;
+        let mut triggered = Vec::new();
+        let mut remaining = Vec::with_capacity(events.len());
+
+        for event in events.drain(..) {
+            match event {
+                egui::Event::Key {
+                    key,
+                    pressed: true,
+                    modifiers,
+                    ..
+                } => {
+                    let chord = Chord { modifiers, key };
+                    keys.consumed_text = false;
+
+                    // moving the cursor of the line that's typed
+                    let home_or_end =
+                        matches!(key, egui::Key::Home | egui::Key::End) && modifiers.is_none();
+
+                    if typing && (chord.is_text() || home_or_end) {
+                        remaining.push(event);
+                        continue;
+                    }
+
+                    let matched = keys.matcher.press(&keys.bindings, chord);
+
+                    // keys held back for a shortcut that wasn't finished are typed after all
+                    for chord in keys.matcher.abandoned() {
+                        remaining.extend(chord.text().map(egui::Event::Text));
+                    }
+
+                    match matched {
+                        Match::None => remaining.push(event),
+                        Match::Pending => keys.consumed_text = chord.is_text(),
+                        Match::Action(id) => {
+                            triggered.push(id.to_string());
+                            keys.consumed_text = chord.is_text();
+                        }
+                    }
+                }
+                egui::Event::Text(_) if keys.consumed_text => keys.consumed_text = false,
+                event => remaining.push(event),
+            }
+        }
+
+        *events = remaining;
+        for id in triggered {
+            self.run_bound_action(&id);
+        }
+    }
+
+    pub fn show_keybindings(&mut self) {
+        self.keybindings.reset_edits();
+        self.keybindings.open = true;
+    }
+
+    /// Dialog for choosing a preset and rebinding actions.
+    pub(super) fn keybindings_dialog(&mut self, ctx: &egui::Context) {
+        if !self.keybindings.open {
+            return;
+        }
+
+        let keys = &mut self.keybindings;
+        let mut open = keys.open;
+        let mut save = false;
+        let mut reset = false;
+
+        egui::Window::new(crate::icon!(KEYBOARD, " Keybindings"))
+            .open(&mut open)
+            .collapsible(false)
+            .default_height(500.0)
+            .show(ctx, |ui| {
+                let preset = keys.keymap.preset;
+                egui::ComboBox::from_label("Preset").selected_text(preset.to_string()).show_ui(
+                    ui,
+                    |ui| {
+                        for preset in Preset::ALL {
+                            ui.selectable_value(
+                                &mut keys.keymap.preset,
+                                preset,
+                                preset.to_string(),
+                            );
+                        }
+                    },
+                );
+
+                if keys.keymap.preset != preset {
+                    keys.reset_edits();
+                }
+
+                ui.label(
+                    "Keys are joined by '+', e.g. Ctrl+Shift+P, and sequences by spaces, e.g. G G.",
+                );
+                ui.separator();
+
+                egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                    egui::Grid::new("keybindings").num_columns(2).striped(true).show(ui, |ui| {
+                        for entry in ACTIONS {
+                            let edit = keys.edits.entry(entry.id).or_default();
+                            let valid = keys.keymap.bind(entry.id, edit).is_ok();
+
+                            ui.label(entry.title);
+                            let field =
+                                egui::TextEdit::singleline(edit).font(font()).hint_text("None");
+                            let field = match valid {
+                                true => field,
+                                false => field.text_color(CONFIG.colors.asm.invalid),
+                            };
+
+                            ui.add(field);
+                            ui.end_row();
+                        }
+                    });
+                });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    save = ui.button("Save").clicked();
+                    reset = ui.button("Reset").clicked();
+                });
+            });
+
+        if reset {
+            keys.keymap.overrides.clear();
+            keys.reset_edits();
+        }
+
+        keys.bindings = keys.keymap.bindings();
+
+        if save {
+            match keys.keymap.save() {
+                Ok(()) => tprint!(self.terminal(), "Saved keybindings."),
+                Err(err) => tprint!(self.terminal(), "Failed to save keybindings: {err}."),
+            }
+        }
+
+        self.keybindings.open = open;
+    }
+}
@@ -1,27 +1,100 @@
+use crate::style::STYLE;
+use crate::widgets::Minimap;
 use crate::{common::*, UIEvent, UiQueue};
-use config::CONFIG;
-use debugvault::Index;
+use config::settings::SETTINGS;
+use config::{Column, CONFIG};
 use egui::mutex::RwLock;
+use egui::text::LayoutJob;
 use egui::Color32;
 use infinite_scroll::{Callback, InfiniteScroll};
-use processor::{Block, BlockContent, Processor};
+use processor::{Block, BlockContent, Kind, Line, Processor, Reference, Span};
+use processor_shared::SectionKind;
+use processor_shared::{AddressMode, RegisterNames, SectionKind};
+use project::Project;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::{Range, RangeInclusive};
+use std::path::Path;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
-use tokenizing::{colors, Token, TokenStream};
+use tokenizing::{Token, TokenKind};
 
 pub struct Listing {
     processor: Arc<Processor>,
+    project: Arc<RwLock<Project>>,
     #[allow(dead_code)]
     ui_queue: Arc<UiQueue>,
     boundaries: Arc<RwLock<Vec<usize>>>,
+    /// Functions collapsed into a single line, by where they start along with where they end.
+    folded: Arc<RwLock<BTreeMap<usize, usize>>>,
+    /// Prologues, epilogues and stack canary checks collapsed into a single line, by where they
+    /// start along with where they end, empty unless [`Listing::toggle_idioms`] enabled it.
+    collapsed: Arc<RwLock<BTreeMap<usize, usize>>>,
     scroll: InfiniteScroll<Block, usize>,
     reset_position: Arc<AtomicUsize>,
     current_addr: usize,
-    jump_list: Vec<usize>,
+    /// Address of the last block that is visible.
+    last_visible_addr: usize,
+    minimap: Minimap,
+    /// Branches whose instruction and target were both visible when last shown.
+    arrows: Vec<Arrow>,
+    /// Addresses visited before the current one, most recent last.
+    backward: Vec<usize>,
+    /// Addresses navigated away from using [`Listing::go_back`], most recent last.
+    forward: Vec<usize>,
+    highlight: Option<Highlight>,
+    address_mode: AddressMode,
+    /// Columns that are shown, in the order they're displayed.
+    columns: Vec<Column>,
+    selection: Option<Selection>,
+    /// Text of the selection waiting to be copied to the clipboard.
+    copied: Option<String>,
+    /// Address of the instruction a debugged target is stopped at.
+    pc: Option<usize>,
+    /// Address to scroll to unless it's already visible, which is only known once it's shown.
+    follow: Option<usize>,
+    breakpoints: BTreeSet<usize>,
+    /// How many times each instruction ran according to a trace.
+    hits: BTreeMap<usize, usize>,
+}
+
+/// Branch drawn in the gutter, from the vertical position of its instruction to that of its
+/// target.
+#[derive(Debug, Clone, Copy)]
+struct Arrow {
+    from: f32,
+    to: f32,
+    /// Whether either end is selected.
+    selected: bool,
+}
+
+/// Blocks selected using the mouse or shift and the arrow keys.
+#[derive(Debug, Clone, Copy)]
+struct Selection {
+    /// Address of the block where the selection started.
+    anchor: usize,
+    /// Address of the block the selection was extended to.
+    cursor: usize,
+}
+
+impl Selection {
+    fn at(addr: usize) -> Self {
+        Self {
+            anchor: addr,
+            cursor: addr,
+        }
+    }
+
+    fn range(&self) -> RangeInclusive<usize> {
+        self.anchor.min(self.cursor)..=self.anchor.max(self.cursor)
+    }
 }
 
 impl Listing {
-    pub fn new(processor: Arc<Processor>, ui_queue: Arc<UiQueue>) -> Self {
+    pub fn new(
+        processor: Arc<Processor>,
+        project: Arc<RwLock<Project>>,
+        ui_queue: Arc<UiQueue>,
+    ) -> Self {
         let boundaries: Arc<RwLock<Vec<usize>>> = Arc::default();
 
         {
@@ -35,20 +108,28 @@ impl Listing {
         };
 
         let reset_position = Arc::new(AtomicUsize::new(0));
+        let folded: Arc<RwLock<BTreeMap<usize, usize>>> = Arc::default();
+        let collapsed: Arc<RwLock<BTreeMap<usize, usize>>> = Arc::default();
 
         let start_loader = {
             let reset_position = Arc::clone(&reset_position);
             let boundaries = Arc::clone(&boundaries);
+            let folded = Arc::clone(&folded);
+            let collapsed = Arc::clone(&collapsed);
             let processor = Arc::clone(&processor);
 
             move |cursor: Option<usize>, callback: Callback<Block, usize>| {
                 let boundaries = Arc::clone(&boundaries);
+                let folded = Arc::clone(&folded);
+                let collapsed = Arc::clone(&collapsed);
                 let processor = Arc::clone(&processor);
 
                 let block_idx = cursor.unwrap_or_else(|| reset_position.load(Ordering::SeqCst));
 
                 std::thread::spawn(move || {
                     let boundaries = boundaries.read();
+                    let folded = folded.read();
+                    let collapsed = collapsed.read();
                     let mut all_blocks = Vec::new();
 
                     if block_idx == 0 {
@@ -58,8 +139,8 @@ impl Listing {
                     let mut idx = block_idx - 1;
                     let mut lines_parsed = 0;
                     loop {
-                        let addr = boundaries[idx];
-                        let blocks = processor.parse_blocks(addr);
+                        idx = fold_start(&boundaries, &folded, &collapsed, idx);
+                        let blocks = parse_blocks(&processor, &folded, &collapsed, boundaries[idx]);
                         for block in blocks.into_iter().rev() {
                             lines_parsed += block.len();
                             all_blocks.push(block);
@@ -87,16 +168,22 @@ impl Listing {
         let end_loader = {
             let reset_position = Arc::clone(&reset_position);
             let boundaries = Arc::clone(&boundaries);
+            let folded = Arc::clone(&folded);
+            let collapsed = Arc::clone(&collapsed);
             let processor = Arc::clone(&processor);
 
             move |cursor: Option<usize>, callback: Callback<Block, usize>| {
                 let boundaries = Arc::clone(&boundaries);
+                let folded = Arc::clone(&folded);
+                let collapsed = Arc::clone(&collapsed);
                 let processor = Arc::clone(&processor);
 
                 let block_idx = cursor.unwrap_or_else(|| reset_position.load(Ordering::SeqCst));
 
                 std::thread::spawn(move || {
                     let boundaries = boundaries.read();
+                    let folded = folded.read();
+                    let collapsed = collapsed.read();
                     let mut all_blocks = Vec::new();
 
                     let mut idx = block_idx;
@@ -106,19 +193,16 @@ impl Listing {
                             break;
                         }
 
-                        let addr = boundaries[idx];
-                        let blocks = processor.parse_blocks(addr);
+                        let blocks = parse_blocks(&processor, &folded, &collapsed, boundaries[idx]);
                         for block in blocks {
                             lines_parsed += block.len();
                             all_blocks.push(block);
                         }
 
+                        idx = next_boundary(&boundaries, &folded, &collapsed, idx);
                         if lines_parsed >= 100 {
-                            idx += 1;
                             break;
                         }
-
-                        idx += 1;
                     }
 
                     callback(Ok((all_blocks, Some(idx))));
@@ -130,60 +214,464 @@ impl Listing {
         let current_addr = processor.sections().next().unwrap().start;
 
         Self {
+            minimap: Minimap::new(&processor),
             scroll,
             ui_queue,
             boundaries,
+            folded,
+            collapsed,
             processor,
+            project,
             reset_position,
             current_addr,
-            jump_list: Vec::new(),
+            last_visible_addr: current_addr,
+            arrows: Vec::new(),
+            backward: Vec::new(),
+            forward: Vec::new(),
+            highlight: None,
+            address_mode: AddressMode::default(),
+            columns: SETTINGS.read().unwrap().columns.clone(),
+            selection: None,
+            copied: None,
+            pc: None,
+            follow: None,
+            breakpoints: BTreeSet::new(),
+            hits: BTreeMap::new(),
         }
     }
 
+    /// Scroll to an address without recording it in the navigation history.
+    ///
+    /// Addresses in the middle of an instruction or data scroll to where it starts, and those
+    /// in a folded function to where the function starts.
+    fn seek(&mut self, addr: usize) -> bool {
+        let boundaries = self.boundaries.read();
+        let boundary = match boundaries.binary_search(&addr) {
+            Ok(idx) => idx,
+            Err(idx) if idx > 0 && self.processor.section_by_addr(addr).is_some() => idx - 1,
+            Err(..) => return false,
+        };
+
+        let boundary = fold_start(
+            &boundaries,
+            &self.folded.read(),
+            &self.collapsed.read(),
+            boundary,
+        );
+        drop(boundaries);
+
+        self.reset_position.store(boundary, Ordering::SeqCst);
+        self.scroll.reset();
+        true
+    }
+
     pub fn jump(&mut self, addr: usize) -> bool {
-        if let Ok(boundary) = self.boundaries.read().binary_search(&addr) {
-            self.jump_list.push(self.current_addr);
-            self.reset_position.store(boundary, Ordering::SeqCst);
-            self.scroll.reset();
+        // the function that's jumped into is unfolded to show the address
+        let mut folded = self.folded.write();
+        if let Some((&start, &end)) = folded.range(..addr).next_back() {
+            if addr < end {
+                folded.remove(&start);
+            }
+        }
+        drop(folded);
+
+        let from = self.current_addr;
+        if self.seek(addr) {
+            self.backward.push(from);
+            self.forward.clear();
+
+            if self.processor.index.get_sym_by_addr(addr).is_some() {
+                self.project.write().visit_function(addr);
+            }
+
             return true;
         }
 
         false
     }
 
-    pub fn record_input(&mut self, events: &mut Vec<egui::Event>) {
-        events.retain(|event| match event {
-            egui::Event::Key {
-                key: egui::Key::Escape,
-                pressed: true,
-                modifiers: egui::Modifiers::NONE,
-                ..
-            } => {
-                if let Some(addr) = self.jump_list.pop() {
-                    let boundary = self.boundaries.read().binary_search(&addr).unwrap();
-                    self.reset_position.store(boundary, Ordering::SeqCst);
-                    self.scroll.reset();
+    /// Go to the address visited before the last jump.
+    pub fn go_back(&mut self) -> bool {
+        while let Some(addr) = self.backward.pop() {
+            let from = self.current_addr;
+            if self.seek(addr) {
+                self.forward.push(from);
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Undo the last [`Listing::go_back`].
+    pub fn go_forward(&mut self) -> bool {
+        while let Some(addr) = self.forward.pop() {
+            let from = self.current_addr;
+            if self.seek(addr) {
+                self.backward.push(from);
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Clear the selection, or go back if nothing is selected.
+    pub fn deselect_or_go_back(&mut self) {
+        if self.selection.take().is_none() {
+            self.go_back();
+        }
+    }
+
+    /// Scroll a number of blocks down, or up if negative, without recording it in the navigation
+    /// history.
+    pub fn scroll_blocks(&mut self, blocks: isize) {
+        let addr = {
+            let boundaries = self.boundaries.read();
+            let idx = boundaries.partition_point(|&addr| addr < self.current_addr);
+            let idx = idx.saturating_add_signed(blocks).min(boundaries.len().saturating_sub(1));
+            match boundaries.get(idx) {
+                Some(&addr) => addr,
+                None => return,
+            }
+        };
+
+        self.seek(addr);
+    }
+
+    /// Fold the function containing an address into a single line, or unfold it if it's folded.
+    pub fn toggle_fold(&mut self, addr: usize) {
+        let (start, end) = match self.processor.function_bounds(addr) {
+            Some((start, end, _)) => (start, end),
+            None => return,
+        };
+
+        let mut folded = self.folded.write();
+        if folded.remove(&start).is_none() {
+            folded.insert(start, end);
+        }
+        drop(folded);
+
+        // reload the blocks, staying at the function if its start was scrolled past
+        match (start..end).contains(&self.current_addr) {
+            true => self.seek(start),
+            false => self.seek(self.current_addr),
+        };
+    }
+
+    /// Collapse prologues, epilogues, stack canary checks and inlined builtins into a single line,
+    /// or expand them if they're collapsed.
+    pub fn toggle_idioms(&mut self) {
+        let mut collapsed = self.collapsed.write();
+        match collapsed.is_empty() {
+            true => {
+                let idioms = self.processor.idioms.iter();
+                collapsed.extend(idioms.map(|idiom| (idiom.addr, idiom.item.end)));
+            }
+            false => collapsed.clear(),
+        }
+        drop(collapsed);
+
+        self.seek(self.current_addr);
+    }
+
+    /// Scroll a number of pages down, or up if negative, a page being the blocks that are visible.
+    pub fn scroll_pages(&mut self, pages: isize) {
+        let visible = self.visible_blocks().len().max(1);
+        self.scroll_blocks(pages.saturating_mul(visible as isize));
+    }
+
+    /// Jump to the start or the end of the code section that's shown, or the first one if
+    /// something else is shown.
+    pub fn jump_to_code_edge(&mut self, end: bool) -> bool {
+        let code: Vec<_> =
+            self.processor.sections().filter(|sec| sec.kind == SectionKind::Code).collect();
+        let section = code
+            .iter()
+            .find(|section| (section.start..section.end).contains(&self.current_addr))
+            .or(code.first());
+
+        let addr = match section {
+            Some(section) if end => section.end.saturating_sub(1),
+            Some(section) => section.start,
+            None => return false,
+        };
+
+        self.jump(addr)
+    }
+
+    /// Indices of the boundaries of the blocks that are visible.
+    fn visible_blocks(&self) -> Range<usize> {
+        let boundaries = self.boundaries.read();
+        let first = boundaries.partition_point(|&addr| addr < self.current_addr);
+        let last = boundaries.partition_point(|&addr| addr <= self.last_visible_addr);
+        first..last.max(first)
+    }
+
+    /// Jump to the first or last block of the listing.
+    pub fn jump_to_edge(&mut self, end: bool) -> bool {
+        let addr = match end {
+            true => self.boundaries.read().last().copied(),
+            false => self.boundaries.read().first().copied(),
+        };
+
+        match addr {
+            Some(addr) => self.jump(addr),
+            None => false,
+        }
+    }
+
+    /// Address of the first block that is visible.
+    pub fn current_addr(&self) -> usize {
+        self.current_addr
+    }
+
+    /// Address of the block the selection was extended to, or the first visible block.
+    pub fn selected_addr(&self) -> usize {
+        self.selection.map_or(self.current_addr, |selection| selection.cursor)
+    }
+
+    /// Number of selected blocks along with the number of bytes they span.
+    pub fn selection_size(&self) -> Option<(usize, usize)> {
+        let range = self.selection?.range();
+        let boundaries = self.boundaries.read();
+        let first = boundaries.partition_point(|&addr| addr < *range.start());
+        let last = boundaries.partition_point(|&addr| addr <= *range.end());
+
+        // the last block ends where the next one starts
+        let end = boundaries.get(last).copied().unwrap_or(*range.end() + 1);
+        Some((last - first, end - range.start()))
+    }
+
+    /// Mark the instruction a debugged target is stopped at, scrolling to it if it isn't visible.
+    pub fn set_pc(&mut self, pc: Option<usize>) {
+        self.pc = pc;
+        self.follow = pc;
+    }
+
+    pub fn set_breakpoints(&mut self, breakpoints: BTreeSet<usize>) {
+        self.breakpoints = breakpoints;
+    }
+
+    pub fn set_hits(&mut self, hits: BTreeMap<usize, usize>) {
+        self.hits = hits;
+    }
+
+    /// Change which columns are shown, and in what order.
+    pub fn set_columns(&mut self, columns: Vec<Column>) {
+        self.columns = columns;
+    }
+
+    /// Change how the address column is displayed.
+    pub fn set_address_mode(&mut self, mode: AddressMode) {
+        self.address_mode = mode;
+    }
+
+    /// Write the whole listing to an HTML page, with addresses shown the way they're displayed.
+    pub fn export_html(&self, path: &Path) -> std::io::Result<()> {
+        let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        self.processor.export_html(&[0..usize::MAX], self.address_mode, file)
+    }
+
+    /// Change how registers are named, reloading the blocks that are shown.
+    pub fn set_register_names(&mut self, names: RegisterNames) {
+        self.processor.set_register_names(names);
+        self.seek(self.current_addr);
+    }
+
+    /// Overwrite the code at an address, only recomputing the part of the listing that changed.
+    ///
+    /// Returns `false` if the address isn't part of a code section.
+    pub fn patch(&mut self, addr: usize, bytes: &[u8]) -> bool {
+        match self.processor.patch(addr, bytes) {
+            Some(range) => {
+                self.refresh(range);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Show the data at an address as a structure, or stop showing the structure there if the
+    /// name is empty.
+    pub fn apply_struct(&mut self, addr: usize, name: &str) -> bool {
+        let changed = match name {
+            "" => self.processor.remove_struct(addr),
+            name => self.processor.apply_struct(addr, name),
+        };
+
+        match changed {
+            Some(range) => {
+                self.refresh(range);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Show the immediates of the instruction at an address as constants of an enumeration, or as
+    /// numbers again if the name is empty.
+    pub fn apply_enum(&mut self, addr: usize, name: &str) -> bool {
+        let changed = match name {
+            "" => self.processor.remove_enum(addr),
+            name => self.processor.apply_enum(addr, name),
+        };
+
+        match changed {
+            Some(range) => {
+                self.refresh(range);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Compute the boundaries of the code or data in between two addresses again and reload the
+    /// blocks that are shown, staying at the same address.
+    fn refresh(&mut self, range: Range<usize>) {
+        let section = match self.processor.section_by_addr(range.start) {
+            Some(section) => section,
+            None => return,
+        };
+
+        let mut boundaries = self.boundaries.write();
+
+        if section.kind == SectionKind::Code {
+            // an instruction right before the range might run into it
+            let mut first = boundaries.partition_point(|&addr| addr < range.start);
+            if first > 0 && boundaries[first - 1] >= section.start {
+                first -= 1;
+            }
+
+            let start = match boundaries.get(first) {
+                Some(&start) => start,
+                None => return,
+            };
+
+            let (changed, end) = self.processor.compute_code_boundaries_in(start, range.end);
+            let last = boundaries.partition_point(|&addr| addr < end);
+            boundaries.splice(first..last, changed);
+        } else {
+            // data is split up evenly, so computing the whole section again is cheap
+            let first = boundaries.partition_point(|&addr| addr < section.start);
+            let last = boundaries.partition_point(|&addr| addr <= section.end);
+            let changed = self.processor.compute_section_boundaries(section);
+            boundaries.splice(first..last, changed);
+        }
+
+        // the current address might not be a boundary anymore
+        let current = boundaries.partition_point(|&addr| addr <= self.current_addr);
+        self.reset_position.store(current.saturating_sub(1), Ordering::SeqCst);
+        drop(boundaries);
+
+        self.scroll.reset();
+    }
+
+    /// Move the end of the selection to the next or previous block, starting a selection at the
+    /// first visible block if there isn't one.
+    pub fn extend_selection(&mut self, down: bool) {
+        let boundaries = self.boundaries.read();
+        let selection = self.selection.get_or_insert(Selection::at(self.current_addr));
+
+        let idx = boundaries.partition_point(|&addr| addr < selection.cursor);
+        let next = match down {
+            true => boundaries.get(idx + 1),
+            false => idx.checked_sub(1).and_then(|idx| boundaries.get(idx)),
+        };
+
+        if let Some(&addr) = next {
+            selection.cursor = addr;
+        }
+    }
+
+    /// Text of the selected lines, with the columns laid out the way they're shown.
+    fn selected_text(&self) -> Option<String> {
+        let range = self.selection?.range();
+        let boundaries = self.boundaries.read();
+        let project = self.project.read();
+        let columns = &self.columns;
+
+        let start = boundaries.partition_point(|addr| addr < range.start());
+        let selected = boundaries[start..].iter().take_while(|addr| range.contains(addr));
+
+        let mut text = String::new();
+        for block in selected.flat_map(|&addr| self.processor.parse_blocks(addr)) {
+            let mut lines = block.lines(&self.processor, self.address_mode);
+            match block.content {
+                BlockContent::Instruction { .. } => {
+                    if let Some(comment) = project.comments.get(&block.addr) {
+                        lines[0].comment(comment);
+                    }
+                }
+                BlockContent::Label { .. } => {
+                    if let Some(name) = project.renames.get(&block.addr) {
+                        rename_label(&mut lines, name);
+                    }
                 }
-                false
+                _ => {}
             }
-            _ => true,
-        });
+
+            for line in lines {
+                if !columns.iter().any(|&column| line.has_column(column)) {
+                    continue;
+                }
+
+                let mut spans = line.layout(columns, &self.processor, self.address_mode);
+                rename_symbols(&mut spans, &project.renames);
+                for span in spans {
+                    text.push_str(&span.token.text);
+                }
+                text.push('\n');
+            }
+        }
+
+        Some(text)
     }
-}
 
-fn split_instruction_by_label(tokens: Vec<Token>) -> (Vec<Token>, Vec<Token>, Vec<Token>) {
-    let start = tokens.iter().position(|token| token.text.contains('<'));
-    let end = tokens.iter().rposition(|token| token.text.contains('>'));
+    /// Select the blocks clicked or dragged over, shift-clicking extends the selection.
+    fn select_with_pointer(
+        &mut self,
+        ui: &egui::Ui,
+        area: egui::Rect,
+        rows: &[(usize, egui::Rect)],
+    ) {
+        let (pos, origin, pressed, down, shift) = ui.input(|i| {
+            (
+                i.pointer.interact_pos(),
+                i.pointer.press_origin(),
+                i.pointer.primary_pressed(),
+                i.pointer.primary_down(),
+                i.modifiers.shift,
+            )
+        });
 
-    if let (Some(start), Some(end)) = (start, end) {
-        return (
-            tokens[..start].to_vec(),
-            tokens[start..=end].to_vec(),
-            tokens[end + 1..].to_vec(),
-        );
+        let pos = match pos.filter(|&pos| area.contains(pos)) {
+            Some(pos) => pos,
+            None => return,
+        };
+
+        let addr = match rows.iter().find(|(_, rect)| rect.contains(pos)) {
+            Some(&(addr, _)) => addr,
+            None => return,
+        };
+
+        match self.selection.as_mut() {
+            Some(selection) if pressed && shift => selection.cursor = addr,
+            _ if pressed => self.selection = Some(Selection::at(addr)),
+            // dragging only selects if it started in the listing
+            Some(selection) if down && origin.map_or(false, |pos| area.contains(pos)) => {
+                selection.cursor = addr
+            }
+            _ => {}
+        }
     }
 
-    (tokens, Vec::new(), Vec::new())
+    pub fn record_input(&mut self, events: &[egui::Event]) {
+        // other widgets might have something selected, so copying isn't consumed
+        if events.iter().any(|event| matches!(event, egui::Event::Copy)) {
+            self.copied = self.selected_text();
+        }
+    }
 }
 
 fn draw_horizontal_line(ui: &mut egui::Ui) {
@@ -192,7 +680,7 @@ fn draw_horizontal_line(ui: &mut egui::Ui) {
 
     let dashed_line = egui::Shape::dashed_line(
         &[egui::pos2(5.0, y), egui::pos2(ui.available_width(), y)],
-        egui::Stroke::new(thickness, colors::WHITE),
+        egui::Stroke::new(thickness, CONFIG.colors.text),
         10.0,
         5.0,
     );
@@ -201,27 +689,490 @@ fn draw_horizontal_line(ui: &mut egui::Ui) {
     ui.painter().extend(dashed_line);
 }
 
-fn draw_instruction(ui: &mut egui::Ui, tokens: Vec<Token>, index: &Index, ui_queue: &UiQueue) {
-    let (a, b, c) = split_instruction_by_label(tokens);
-    let label = tokens_to_layoutjob(b);
-    let label_text = label.text.clone();
+/// Number of lines shown when previewing the target of an operand.
+const PREVIEW_LINES: usize = 10;
+
+/// Width of the scrollbar beside the listing, in points.
+const SCROLLBAR_WIDTH: f32 = 10.0;
+
+/// Smallest height of the scrollbar's thumb, so it can still be grabbed in large binaries.
+const MIN_THUMB_HEIGHT: f32 = 16.0;
+
+/// Width of the gutter left of the listing that branch arrows are drawn in.
+const GUTTER_WIDTH: f32 = 40.0;
+
+/// Horizontal distance between nested branch arrows.
+const LANE_SPACING: f32 = 5.0;
+
+/// Height of the menu listing the enumerations that can be applied, in points.
+const MAX_ENUM_MENU_HEIGHT: f32 = 300.0;
+
+/// Blocks at a boundary, collapsing the function or idiom starting there if it's folded.
+fn parse_blocks(
+    processor: &Processor,
+    folded: &BTreeMap<usize, usize>,
+    collapsed: &BTreeMap<usize, usize>,
+    addr: usize,
+) -> Vec<Block> {
+    if folded.contains_key(&addr) {
+        return processor.parse_folded_blocks(addr);
+    }
+
+    match collapsed.contains_key(&addr) {
+        true => processor.parse_collapsed_blocks(addr),
+        false => processor.parse_blocks(addr),
+    }
+}
+
+/// Index of the boundary after the blocks at a boundary, skipping the rest of a folded function
+/// or collapsed idiom.
+fn next_boundary(
+    boundaries: &[usize],
+    folded: &BTreeMap<usize, usize>,
+    collapsed: &BTreeMap<usize, usize>,
+    idx: usize,
+) -> usize {
+    let addr = boundaries[idx];
+    match folded.get(&addr).or_else(|| collapsed.get(&addr)) {
+        Some(&end) => boundaries.partition_point(|&addr| addr < end).max(idx + 1),
+        None => idx + 1,
+    }
+}
+
+/// Index of the boundary the folded function or collapsed idiom containing a boundary starts at,
+/// or the same index if it isn't part of either.
+fn fold_start(
+    boundaries: &[usize],
+    folded: &BTreeMap<usize, usize>,
+    collapsed: &BTreeMap<usize, usize>,
+    idx: usize,
+) -> usize {
+    let mut idx = idx;
+    for ranges in [folded, collapsed] {
+        let addr = boundaries[idx];
+        if let Some((&start, &end)) = ranges.range(..addr).next_back() {
+            if addr < end {
+                idx = boundaries.partition_point(|&boundary| boundary < start).min(idx);
+            }
+        }
+    }
+
+    idx
+}
+
+/// Arrange lines in columns, separating them by newlines.
+fn layout_lines(
+    lines: &[Line],
+    columns: &[Column],
+    processor: &Processor,
+    mode: AddressMode,
+) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    for (idx, line) in lines.iter().enumerate() {
+        if idx != 0 {
+            tokens.push(Token::from_str("\n", CONFIG.colors.text));
+        }
+
+        let spans = line.layout(columns, processor, mode);
+        tokens.extend(spans.into_iter().map(|span| span.token));
+    }
+
+    tokens
+}
+
+/// First few lines of the listing starting at an address.
+fn preview(
+    processor: &Processor,
+    boundaries: &[usize],
+    columns: &[Column],
+    mode: AddressMode,
+    addr: usize,
+) -> Vec<Token> {
+    let start = match boundaries.binary_search(&addr) {
+        Ok(idx) => idx,
+        Err(..) => return Vec::new(),
+    };
+
+    let lines: Vec<Line> = boundaries[start..]
+        .iter()
+        .flat_map(|&addr| processor.parse_blocks(addr))
+        .flat_map(|block| block.lines(processor, mode))
+        .filter(|line| columns.iter().any(|&column| line.has_column(column)))
+        .take(PREVIEW_LINES)
+        .collect();
+
+    layout_lines(&lines, columns, processor, mode)
+}
+
+/// Replace the name of a label with the one given by the user.
+fn rename_label(lines: &mut [Line], name: &str) {
+    for line in lines {
+        let first = match line.spans.iter().position(|span| span.kind == Kind::Label) {
+            Some(first) => first,
+            None => continue,
+        };
+
+        // the name stays where it was, since folded functions show it after their address
+        let column = line.spans[first].column;
+        line.spans.retain(|span| span.kind != Kind::Label);
+        line.spans.insert(
+            first,
+            Span {
+                kind: Kind::Label,
+                column,
+                token: Token::from_string(format!("<{name}>"), CONFIG.colors.asm.label),
+            },
+        );
+    }
+}
+
+/// Replace the names of symbols that instructions refer to with the ones given by the user.
+fn rename_symbols(spans: &mut Vec<Span>, renames: &BTreeMap<usize, String>) {
+    let mut idx = 0;
+    while idx < spans.len() {
+        let token = &spans[idx].token;
+        let target = match token.target {
+            Some(target) if token.kind == TokenKind::Symbol => target,
+            _ => {
+                idx += 1;
+                continue;
+            }
+        };
+
+        let len = spans[idx..].iter().take_while(|span| span.token.target == Some(target)).count();
+        let name = match renames.get(&target) {
+            Some(name) => name,
+            None => {
+                idx += len;
+                continue;
+            }
+        };
+
+        // the name might be surrounded by brackets, depending on the operand
+        let run = &spans[idx..idx + len];
+        let open = run.iter().position(|span| &*span.token.text == "<");
+        let close = run.iter().rposition(|span| &*span.token.text == ">");
+        let name_range = match (open, close) {
+            (Some(open), Some(close)) if open < close => idx + open + 1..idx + close,
+            _ => idx..idx + len,
+        };
+
+        let span = Span {
+            kind: spans[name_range.start].kind,
+            column: spans[name_range.start].column,
+            token: Token::from_string(name.clone(), CONFIG.colors.asm.label)
+                .with_kind(TokenKind::Symbol)
+                .with_target(target),
+        };
+
+        let removed = name_range.len();
+        spans.splice(name_range, [span]);
+        idx += len + 1 - removed;
+    }
+}
+
+/// Tokens that are highlighted throughout the listing.
+#[derive(Debug, PartialEq)]
+enum Highlight {
+    /// Registers or immediates with the same text.
+    Text(TokenKind, String),
+    /// Symbols referring to the same address.
+    Target(usize),
+}
+
+impl Highlight {
+    fn of(token: &Token) -> Option<Self> {
+        match token.kind {
+            TokenKind::Register | TokenKind::Immediate => {
+                Some(Self::Text(token.kind, token.text.to_string()))
+            }
+            TokenKind::Symbol => token.target.map(Self::Target),
+            TokenKind::Other => None,
+        }
+    }
+
+    fn matches(&self, token: &Token) -> bool {
+        match self {
+            Self::Text(kind, text) => token.kind == *kind && &*token.text == text,
+            Self::Target(addr) => token.kind == TokenKind::Symbol && token.target == Some(*addr),
+        }
+    }
+}
+
+fn highlighted_layoutjob(tokens: Vec<Token>, highlight: Option<&Highlight>) -> LayoutJob {
+    let matches: Vec<bool> = tokens
+        .iter()
+        .map(|token| highlight.map_or(false, |h| h.matches(token)))
+        .collect();
+
+    let mut job = tokens_to_layoutjob(tokens);
+    for (section, matches) in job.sections.iter_mut().zip(matches) {
+        if matches {
+            section.format.background = STYLE.selection_color;
+        }
+    }
+
+    job
+}
+
+/// Tooltip explaining what an instruction does.
+fn reference_tooltip(reference: &Reference) -> Vec<Token> {
+    let mut tokens = vec![
+        Token::from_str(reference.mnemonic, CONFIG.colors.asm.opcode),
+        Token::from_string(format!("\n{}", reference.operation), CONFIG.colors.text),
+    ];
+
+    if !reference.flags.is_empty() {
+        tokens.push(Token::from_str("\nflags: ", CONFIG.colors.comment));
+        tokens.push(Token::from_str(reference.flags, CONFIG.colors.text));
+    }
+
+    tokens.push(Token::from_str("\nforms:", CONFIG.colors.comment));
+    for form in reference.forms {
+        tokens.push(Token::from_string(
+            format!("\n    {} ", reference.mnemonic),
+            CONFIG.colors.asm.opcode,
+        ));
+        tokens.push(Token::from_str(form, CONFIG.colors.asm.component));
+    }
+
+    tokens
+}
+
+/// Background of an instruction that ran some number of times, which is more opaque the more
+/// times it ran.
+fn coverage_color(hits: usize) -> Color32 {
+    let [r, g, b, _] = STYLE.coverage_color.to_array();
+    let alpha = 30 + 10 * hits.ilog2().min(6) as u8;
+    Color32::from_rgba_unmultiplied(r, g, b, alpha)
+}
+
+/// Menu applying an enumeration to the immediates of an instruction.
+fn enum_menu(ui: &mut egui::Ui, processor: &Processor, applied: &mut Option<String>) {
+    ui.menu_button("Apply enum", |ui| {
+        egui::ScrollArea::vertical().max_height(MAX_ENUM_MENU_HEIGHT).show(ui, |ui| {
+            for name in processor.enum_names() {
+                if ui.button(&name).clicked() {
+                    *applied = Some(name);
+                    ui.close_menu();
+                }
+            }
+        });
+    });
+
+    // an empty name shows the immediates as numbers again
+    if ui.button("Show as number").clicked() {
+        *applied = Some(String::new());
+        ui.close_menu();
+    }
+}
+
+/// Draws an instruction, the `mnemonic` is the index of the token that shows it's reference.
+///
+/// Returns the name of the enumeration picked for the immediates of the instruction, if any.
+fn draw_instruction(
+    ui: &mut egui::Ui,
+    tokens: Vec<Token>,
+    mnemonic: Option<(usize, &Reference)>,
+    processor: &Processor,
+    highlight: &mut Option<Highlight>,
+    preview: impl Fn(usize) -> Vec<Token>,
+    ui_queue: &UiQueue,
+) -> Option<String> {
+    let mut applied = None;
 
     ui.horizontal(|ui| {
         ui.style_mut().spacing.item_spacing.x = 0.0;
 
-        ui.label(tokens_to_layoutjob(a));
-        if ui.link(label).clicked() {
-            let label_without_arrows = &label_text[1..][..label_text.len() - 2];
-            if let Some(addr) = index.get_func_by_name(label_without_arrows) {
-                ui_queue.push(UIEvent::GotoAddr(addr));
+        let mnemonic_idx = mnemonic.map(|(idx, _)| idx);
+
+        // group tokens by what they represent and the address they refer to
+        let mut tokens = tokens.into_iter().enumerate().peekable();
+        while let Some((idx, token)) = tokens.next() {
+            let (kind, target) = (token.kind, token.target);
+            let clicked_highlight = Highlight::of(&token);
+            let mut group = vec![token];
+
+            // the mnemonic is put in a group of it's own so it can show a tooltip
+            let reference = mnemonic.filter(|(mnemonic_idx, _)| *mnemonic_idx == idx);
+            if let Some((_, reference)) = reference {
+                let job = highlighted_layoutjob(group, highlight.as_ref());
+                ui.label(job).on_hover_ui(|ui| {
+                    ui.label(tokens_to_layoutjob(reference_tooltip(reference)));
+                });
+                continue;
+            }
+
+            while let Some((_, token)) = tokens.next_if(|(idx, token)| {
+                Some(*idx) != mnemonic_idx && token.kind == kind && token.target == target
+            }) {
+                group.push(token);
+            }
+
+            let job = highlighted_layoutjob(group, highlight.as_ref());
+
+            if let Some(addr) = target {
+                let response = ui.link(job);
+                if response.clicked() {
+                    *highlight = clicked_highlight;
+                    ui_queue.push(UIEvent::GotoAddr(addr));
+                }
+
+                response.on_hover_ui_at_pointer(|ui| {
+                    let preview = preview(addr);
+                    if !preview.is_empty() {
+                        ui.label(tokens_to_layoutjob(preview));
+                    }
+                });
+
+                continue;
+            }
+
+            if clicked_highlight.is_none() {
+                ui.label(job);
+                continue;
+            }
+
+            let response = ui.add(egui::Label::new(job).sense(egui::Sense::click()));
+            if response.clicked() {
+                // clicking an already highlighted token clears the highlight
+                if *highlight == clicked_highlight {
+                    *highlight = None;
+                } else {
+                    *highlight = clicked_highlight;
+                }
+            }
+
+            if kind == TokenKind::Immediate {
+                response.context_menu(|ui| enum_menu(ui, processor, &mut applied));
             }
         }
-        ui.label(tokens_to_layoutjob(c));
     });
+
+    applied
 }
 
 impl Display for Listing {
     fn show(&mut self, ui: &mut egui::Ui) {
+        let rect = ui.available_rect_before_wrap();
+        let split = rect.right() - Minimap::WIDTH - STYLE.separator_width;
+        let (listing_rect, minimap_rect) = rect.split_left_right_at_x(split);
+        let minimap_rect = minimap_rect.with_min_x(rect.right() - Minimap::WIDTH);
+
+        let scrollbar_rect = listing_rect.with_min_x(listing_rect.right() - SCROLLBAR_WIDTH);
+        let listing_rect = listing_rect.with_max_x(scrollbar_rect.left() - STYLE.separator_width);
+
+        let gutter_rect = listing_rect.with_max_x(listing_rect.left() + GUTTER_WIDTH);
+        let listing_rect = listing_rect.with_min_x(gutter_rect.right());
+
+        ui.allocate_ui_at_rect(listing_rect, |ui| self.show_blocks(ui));
+        self.branch_arrows(ui, gutter_rect);
+        self.scrollbar(ui, scrollbar_rect);
+
+        let visible = self.current_addr..=self.last_visible_addr.max(self.current_addr);
+        if let Some(addr) = self.minimap.show(ui, minimap_rect, visible) {
+            self.seek(addr);
+        }
+    }
+}
+
+impl Listing {
+    /// Arrows connecting branches to their targets, nested so that shorter branches are closer
+    /// to the listing.
+    fn branch_arrows(&self, ui: &mut egui::Ui, rect: egui::Rect) {
+        let painter = ui.painter_at(rect);
+        let lanes = ((rect.width() - LANE_SPACING) / LANE_SPACING).max(1.0) as usize;
+
+        let mut arrows = self.arrows.clone();
+        arrows.sort_by(|a, b| (a.to - a.from).abs().total_cmp(&(b.to - b.from).abs()));
+
+        // vertical extents of the arrows in each lane
+        let mut taken: Vec<Vec<RangeInclusive<f32>>> = vec![Vec::new(); lanes];
+        for arrow in arrows {
+            let span = arrow.from.min(arrow.to)..=arrow.from.max(arrow.to);
+            let overlaps = |other: &RangeInclusive<f32>| {
+                span.start() <= other.end() && other.start() <= span.end()
+            };
+
+            // arrows that don't fit share the outermost lane
+            let lane =
+                taken.iter().position(|lane| !lane.iter().any(overlaps)).unwrap_or(lanes - 1);
+            taken[lane].push(span);
+
+            let color = match arrow.selected {
+                true => CONFIG.colors.text,
+                false => CONFIG.colors.hint,
+            };
+
+            let right = rect.right() - 2.0;
+            let x = right - LANE_SPACING * (lane + 1) as f32;
+            let points = vec![
+                egui::pos2(right, arrow.from),
+                egui::pos2(x, arrow.from),
+                egui::pos2(x, arrow.to),
+                egui::pos2(right, arrow.to),
+            ];
+
+            painter.add(egui::Shape::line(points, egui::Stroke::new(1.0, color)));
+            painter.add(egui::Shape::convex_polygon(
+                vec![
+                    egui::pos2(right, arrow.to),
+                    egui::pos2(right - 4.0, arrow.to - 3.0),
+                    egui::pos2(right - 4.0, arrow.to + 3.0),
+                ],
+                color,
+                egui::Stroke::NONE,
+            ));
+        }
+    }
+
+    /// Scrollbar whose thumb shows which part of all blocks is visible, positioning the listing
+    /// at the block it's dragged to.
+    fn scrollbar(&mut self, ui: &mut egui::Ui, rect: egui::Rect) {
+        let response = ui.allocate_rect(rect, egui::Sense::click_and_drag());
+        let count = self.boundaries.read().len();
+        if count == 0 {
+            return;
+        }
+
+        let visible = self.visible_blocks();
+        let height = rect.height();
+        let thumb_height = (height * visible.len().max(1) as f32 / count as f32)
+            .clamp(MIN_THUMB_HEIGHT.min(height), height);
+        let track = height - thumb_height;
+
+        // the thumb reaches the bottom once the last page is visible
+        let scrollable = count.saturating_sub(visible.len()).max(1);
+        let top = rect.top() + track * (visible.start as f32 / scrollable as f32).min(1.0);
+        let thumb = egui::Rect::from_x_y_ranges(rect.x_range(), top..=top + thumb_height);
+
+        let color = match response.hovered() || response.dragged() {
+            true => CONFIG.colors.text,
+            false => CONFIG.colors.text_inactive,
+        };
+
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 0.0, CONFIG.colors.bg_secondary);
+        painter.rect_filled(thumb.shrink2(egui::vec2(2.0, 0.0)), 2.0, color);
+
+        let dragged = response.dragged() && response.drag_delta().y != 0.0;
+        if !(response.clicked() || dragged) {
+            return;
+        }
+
+        // the thumb is centered on the pointer
+        let pos = match response.interact_pointer_pos() {
+            Some(pos) => pos,
+            None => return,
+        };
+
+        let fraction = ((pos.y - rect.top() - thumb_height / 2.0) / track.max(1.0)).clamp(0.0, 1.0);
+        let idx = (fraction * scrollable as f32).round() as usize;
+        let addr = self.boundaries.read()[idx.min(count - 1)];
+        self.seek(addr);
+    }
+
+    fn show_blocks(&mut self, ui: &mut egui::Ui) {
         let area = egui::ScrollArea::vertical()
             .drag_to_scroll(false)
             .scroll_bar_visibility(egui::scroll_area::ScrollBarVisibility::AlwaysHidden)
@@ -229,7 +1180,22 @@ impl Display for Listing {
 
         let start_y = ui.cursor().min.y;
 
-        area.show(ui, |ui| {
+        if let Some(text) = self.copied.take() {
+            ui.ctx().copy_text(text);
+        }
+
+        // area taken up by each block, for selecting them with the mouse
+        let mut rows = Vec::new();
+
+        // function whose header was clicked
+        let mut fold = None;
+        let mut applied_enum = None;
+
+        // vertical positions of the instructions that are visible, and of those that branch
+        let mut instructions = BTreeMap::new();
+        let mut branches = Vec::new();
+
+        let output = area.show(ui, |ui| {
             ui.set_width(ui.available_width());
 
             let mut idx = 0;
@@ -242,20 +1208,138 @@ impl Display for Listing {
                     draw_horizontal_line(ui);
                 }
 
-                let mut stream = TokenStream::new();
-                block.tokenize(&mut stream);
+                let mut lines = block.lines(&self.processor, self.address_mode);
+                let columns = &self.columns;
+
+                // skip blocks that only have content in hidden columns
+                if !lines.iter().any(|line| columns.iter().any(|&c| line.has_column(c))) {
+                    return;
+                }
+
+                // the background is drawn once the size of the block is known
+                let background = ui.painter().add(egui::Shape::Noop);
+                let top = ui.cursor().min.y;
 
-                match block.content {
+                let project = self.project.read();
+                match &block.content {
                     BlockContent::Instruction { .. } => {
-                        draw_instruction(ui, stream.inner, &self.processor.index, &self.ui_queue);
+                        if let Some(comment) = project.comments.get(&block.addr) {
+                            lines[0].comment(comment);
+                        }
+
+                        let mut spans =
+                            lines[0].layout(columns, &self.processor, self.address_mode);
+                        rename_symbols(&mut spans, &project.renames);
+
+                        // the mnemonic is the first token of the instruction
+                        let mnemonic = spans
+                            .iter()
+                            .position(|span| span.kind == Kind::Instruction)
+                            .and_then(|idx| {
+                                let reference = self.processor.reference(&spans[idx].token.text)?;
+                                Some((idx, reference))
+                            });
+
+                        let applied = draw_instruction(
+                            ui,
+                            spans.into_iter().map(|span| span.token).collect(),
+                            mnemonic,
+                            &self.processor,
+                            &mut self.highlight,
+                            |addr| {
+                                let boundaries = self.boundaries.read();
+                                preview(
+                                    &self.processor,
+                                    &boundaries,
+                                    columns,
+                                    self.address_mode,
+                                    addr,
+                                )
+                            },
+                            &self.ui_queue,
+                        );
+
+                        if let Some(name) = applied {
+                            applied_enum = Some((block.addr, name));
+                        }
                     }
-                    BlockContent::Label { .. } => {
-                        if ui.link(tokens_to_layoutjob(stream.inner)).clicked() {
-                            self.ui_queue.push(UIEvent::GotoAddr(block.addr));
+                    BlockContent::Label { .. } | BlockContent::Folded { .. } => {
+                        if let Some(name) = project.renames.get(&block.addr) {
+                            rename_label(&mut lines, name);
+                        }
+
+                        // clicking the header of a function folds or unfolds it
+                        let tokens =
+                            layout_lines(&lines, columns, &self.processor, self.address_mode);
+                        if ui.link(tokens_to_layoutjob(tokens)).clicked() {
+                            fold = Some(block.addr);
                         }
                     }
+                    // pointers in data sections and their labels link to where they point
+                    BlockContent::Pointer { .. } | BlockContent::DataLabel { .. } => {
+                        let spans = lines[0].layout(columns, &self.processor, self.address_mode);
+                        draw_instruction(
+                            ui,
+                            spans.into_iter().map(|span| span.token).collect(),
+                            None,
+                            &self.processor,
+                            &mut self.highlight,
+                            |addr| {
+                                let boundaries = self.boundaries.read();
+                                preview(
+                                    &self.processor,
+                                    &boundaries,
+                                    columns,
+                                    self.address_mode,
+                                    addr,
+                                )
+                            },
+                            &self.ui_queue,
+                        );
+                    }
                     _ => {
-                        ui.label(tokens_to_layoutjob(stream.inner));
+                        let tokens =
+                            layout_lines(&lines, columns, &self.processor, self.address_mode);
+                        ui.label(tokens_to_layoutjob(tokens));
+                    }
+                }
+
+                let rect =
+                    egui::Rect::from_x_y_ranges(ui.max_rect().x_range(), top..=ui.cursor().min.y);
+                let color = if self.selection.map_or(false, |s| s.range().contains(&block.addr)) {
+                    Some(STYLE.selection_color)
+                } else if self.pc == Some(block.addr) {
+                    Some(STYLE.pc_color)
+                } else if self.breakpoints.contains(&block.addr) {
+                    Some(STYLE.breakpoint_color)
+                } else {
+                    self.hits.get(&block.addr).map(|&hits| coverage_color(hits))
+                };
+
+                // instructions that ran more than once show how often they did
+                if let Some(&hits) = self.hits.get(&block.addr).filter(|&&hits| hits > 1) {
+                    ui.painter().text(
+                        rect.right_center() - egui::vec2(8.0, 0.0),
+                        egui::Align2::RIGHT_CENTER,
+                        format!("{hits}x"),
+                        font(),
+                        CONFIG.colors.asm.annotation,
+                    );
+                }
+
+                if let Some(color) = color {
+                    let shape = egui::Shape::rect_filled(rect, 0.0, color);
+                    ui.painter().set(background, shape);
+                }
+                rows.push((block.addr, rect));
+
+                if let BlockContent::Instruction { .. } = block.content {
+                    if ui.is_rect_visible(rect) {
+                        let y = rect.center().y;
+                        instructions.insert(block.addr, y);
+                        if let Some(target) = self.processor.branch_target(block.addr) {
+                            branches.push((block.addr, y, target));
+                        }
                     }
                 }
 
@@ -268,6 +1352,48 @@ impl Display for Listing {
             });
         });
 
+        self.select_with_pointer(ui, output.inner_rect, &rows);
+
+        if let Some(addr) = fold {
+            self.toggle_fold(addr);
+        }
+
+        if let Some((addr, name)) = applied_enum {
+            if self.apply_enum(addr, &name) {
+                self.project.write().apply_enum(addr, name);
+            }
+        }
+
+        // branches are only drawn if both ends are visible
+        let selection = self.selection.map(|selection| selection.range());
+        self.arrows = branches
+            .into_iter()
+            .filter(|&(addr, _, target)| addr != target)
+            .filter_map(|(addr, from, target)| {
+                let to = *instructions.get(&target)?;
+                let selected = selection.as_ref().map_or(false, |range| {
+                    range.contains(&addr) || range.contains(&target)
+                });
+
+                Some(Arrow { from, to, selected })
+            })
+            .collect();
+
+        let visible = rows.iter().filter(|(_, rect)| rect.top() < output.inner_rect.bottom());
+        if let Some(&(addr, _)) = visible.last() {
+            self.last_visible_addr = addr;
+        }
+
+        if let Some(addr) = self.follow.take() {
+            let visible = rows
+                .iter()
+                .any(|&(row, rect)| row == addr && output.inner_rect.contains_rect(rect));
+
+            if !visible {
+                self.seek(addr);
+            }
+        }
+
         // Overlay current section.
         let text = self.processor.section_name(self.current_addr).unwrap();
         let max_width = ui.available_width();
@@ -296,8 +1422,8 @@ impl Display for Listing {
             rect.center(),
             egui::Align2::CENTER_CENTER,
             text,
-            FONT,
-            egui::Color32::WHITE,
+            font(),
+            CONFIG.colors.text,
         );
     }
 }
@@ -0,0 +1,103 @@
+//! Messages logged since bite started, filtered by how serious they are and what they say.
+
+use crate::common::*;
+use crate::widgets::Terminal;
+use log::{Filter, Level};
+
+#[derive(Default)]
+pub struct Logs {
+    /// Least serious records that are shown.
+    level: Level,
+    /// Text records have to contain to be shown, ignoring case.
+    query: String,
+    /// Filter of the records that are logged at all, as it's being edited.
+    filter: Option<String>,
+    /// Command that's being typed.
+    command: String,
+}
+
+impl Logs {
+    /// Show the records that match the filters, followed by a line to run commands from.
+    pub fn show(&mut self, ui: &mut egui::Ui, terminal: &mut Terminal) {
+        let query = self.query.to_lowercase();
+        let mut layout = egui::text::LayoutJob::default();
+        let mut text = Vec::new();
+
+        // the logger isn't held on to, as running a command logs
+        for record in log::LOGGER.records().iter() {
+            let record_text = record.text();
+            if record.level < self.level || !record_text.to_lowercase().contains(&query) {
+                continue;
+            }
+
+            record.append_to(&mut layout);
+            text.push(record_text);
+        }
+
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_source("log level")
+                .selected_text(format!("{} and up", self.level))
+                .show_ui(ui, |ui| {
+                    for level in Level::ALL {
+                        ui.selectable_value(&mut self.level, level, format!("{level} and up"));
+                    }
+                });
+
+            ui.add(egui::TextEdit::singleline(&mut self.query).hint_text("Search"));
+            self.filter_edit(ui);
+
+            if ui.button(crate::icon!(COPY, " Copy")).clicked() {
+                ui.ctx().copy_text(text.join("\n"));
+            }
+
+            if ui.button(crate::icon!(BIN, " Clear")).clicked() {
+                log::LOGGER.clear();
+            }
+        });
+
+        ui.with_layout(egui::Layout::bottom_up(egui::Align::Min), |ui| {
+            let edit = egui::TextEdit::singleline(&mut self.command)
+                .font(font())
+                .hint_text("Command")
+                .desired_width(f32::INFINITY);
+
+            let response = ui.add(edit);
+            if response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter)) {
+                terminal.run(&std::mem::take(&mut self.command));
+            }
+
+            ui.with_layout(egui::Layout::top_down(egui::Align::Min), |ui| {
+                let area = egui::ScrollArea::vertical()
+                    .auto_shrink([false, false])
+                    .drag_to_scroll(false)
+                    .stick_to_bottom(true);
+
+                area.show(ui, |ui| ui.label(layout));
+            });
+        });
+    }
+
+    /// Edit the filter of which records are logged, applying it once it's valid.
+    fn filter_edit(&mut self, ui: &mut egui::Ui) {
+        let filter = self.filter.get_or_insert_with(|| log::LOGGER.filter().to_string());
+
+        let parsed = filter.parse::<Filter>();
+        let edit =
+            egui::TextEdit::singleline(filter).hint_text("Filter, e.g. warn,processor=debug");
+        let edit = match parsed {
+            Ok(..) => edit,
+            Err(..) => edit.text_color(config::CONFIG.colors.asm.invalid),
+        };
+
+        let response = ui.add(edit).on_hover_text(format!(
+            "Records that are logged, like the {} environment variable.",
+            log::ENV_VAR
+        ));
+
+        if response.changed() {
+            if let Ok(parsed) = filter.parse() {
+                log::LOGGER.set_filter(parsed);
+            }
+        }
+    }
+}
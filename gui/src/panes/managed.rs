@@ -0,0 +1,120 @@
+//! Methods of a .NET assembly, along with the CIL instructions of the one that's selected.
+
+use crate::common::*;
+use crate::{UIEvent, UiQueue};
+use config::CONFIG;
+use processor::Processor;
+use processor_shared::PhysAddr;
+use std::sync::Arc;
+use tokenizing::Token;
+
+pub struct Managed {
+    processor: Arc<Processor>,
+    ui_queue: Arc<UiQueue>,
+    /// Text the name of a method has to contain to be shown.
+    query: String,
+    /// Index of the selected method along with its instructions.
+    selected: Option<(usize, Vec<(PhysAddr, Vec<Token>)>)>,
+}
+
+impl Managed {
+    pub fn new(processor: Arc<Processor>, ui_queue: Arc<UiQueue>) -> Self {
+        Self {
+            processor,
+            ui_queue,
+            query: String::new(),
+            selected: None,
+        }
+    }
+
+    fn select(&mut self, idx: usize) {
+        let methods = match self.processor.dotnet {
+            Some(ref metadata) => &metadata.methods,
+            None => return,
+        };
+
+        let instructions = self.processor.managed_instructions(&methods[idx]);
+        self.selected = Some((idx, instructions));
+    }
+}
+
+impl Display for Managed {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        let processor = self.processor.clone();
+        let metadata = match processor.dotnet {
+            Some(ref metadata) => metadata,
+            None => {
+                ui.label("Not a .NET assembly.");
+                return;
+            }
+        };
+
+        ui.label(format!("Runtime {}", metadata.runtime_version));
+        ui.add(egui::TextEdit::singleline(&mut self.query).hint_text("Search"));
+        ui.separator();
+
+        let query = self.query.to_lowercase();
+        let selected = self.selected.as_ref().map(|(idx, _)| *idx);
+        let mut clicked = None;
+
+        let area = egui::ScrollArea::both()
+            .id_source("managed methods")
+            .max_height(ui.available_height() / 2.0)
+            .auto_shrink([false, false])
+            .drag_to_scroll(false);
+
+        area.show(ui, |ui| {
+            for (idx, method) in metadata.methods.iter().enumerate() {
+                if !method.name.to_lowercase().contains(&query) {
+                    continue;
+                }
+
+                let tokens = vec![
+                    Token::from_string(format!("{:0>10X}", method.addr), CONFIG.colors.text),
+                    Token::from_str(" | ", CONFIG.colors.text),
+                    Token::from_string(method.name.clone(), CONFIG.colors.src.function),
+                    Token::from_string(format!(" ({} bytes)", method.size), CONFIG.colors.hint),
+                ];
+
+                let label = ui.selectable_label(selected == Some(idx), tokens_to_layoutjob(tokens));
+                if label.clicked() {
+                    clicked = Some(idx);
+                }
+
+                if label.double_clicked() {
+                    self.ui_queue.push(UIEvent::GotoAddr(method.addr));
+                }
+            }
+        });
+
+        if let Some(idx) = clicked {
+            self.select(idx);
+        }
+
+        ui.separator();
+
+        let area = egui::ScrollArea::both()
+            .id_source("managed instructions")
+            .auto_shrink([false, false])
+            .drag_to_scroll(false);
+
+        area.show(ui, |ui| {
+            let instructions = match self.selected {
+                Some((_, ref instructions)) => instructions,
+                None => {
+                    ui.label("Select a method to show its instructions.");
+                    return;
+                }
+            };
+
+            for (addr, tokens) in instructions {
+                let mut line = vec![
+                    Token::from_string(format!("{addr:0>10X}"), CONFIG.colors.text),
+                    Token::from_str("  ", CONFIG.colors.text),
+                ];
+                line.extend(tokens.iter().cloned());
+                ui.label(tokens_to_layoutjob(line));
+            }
+        });
+    }
+}
@@ -0,0 +1,207 @@
+use crate::common::*;
+use crate::{UIEvent, UiQueue};
+use config::CONFIG;
+use debugger::Region;
+use std::sync::Arc;
+use tokenizing::Token;
+
+/// Number of bytes of the target's memory that are shown at once.
+pub const VIEW_SIZE: usize = 0x400;
+
+const BYTES_PER_ROW: usize = 16;
+
+pub struct MemoryMap {
+    ui_queue: Arc<UiQueue>,
+    regions: Vec<Region>,
+}
+
+impl MemoryMap {
+    pub fn new(ui_queue: Arc<UiQueue>, regions: Vec<Region>) -> Self {
+        Self { ui_queue, regions }
+    }
+}
+
+impl Display for MemoryMap {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        if ui.button(crate::icon!(LOOP2, " Refresh")).clicked() {
+            self.ui_queue.push(UIEvent::ReadMemoryMap);
+        }
+
+        let area = egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
+
+        area.show(ui, |ui| {
+            if self.regions.is_empty() {
+                ui.label("Target didn't describe its memory.");
+            }
+
+            for region in self.regions.iter() {
+                let tokens = vec![
+                    Token::from_string(format!("{:0>12X}", region.range.start), CONFIG.colors.text),
+                    Token::from_str("-", CONFIG.colors.delimiter),
+                    Token::from_string(format!("{:0>12X}", region.range.end), CONFIG.colors.text),
+                    Token::from_string(
+                        format!(" {:<6}", region.permissions),
+                        CONFIG.colors.asm.annotation,
+                    ),
+                    Token::from_string(region.name.clone(), CONFIG.colors.src.function),
+                ];
+
+                let label =
+                    egui::Label::new(tokens_to_layoutjob(tokens)).sense(egui::Sense::click());
+                if ui.add(label).double_clicked() {
+                    self.ui_queue.push(UIEvent::ReadMemory(region.range.start as usize));
+                }
+            }
+        });
+    }
+}
+
+/// Hex view of the target's memory, which is read again every time the target stops.
+pub struct MemoryView {
+    ui_queue: Arc<UiQueue>,
+    addr: usize,
+    bytes: Vec<u8>,
+    /// Whether each byte changed since the memory was read the last time.
+    changed: Vec<bool>,
+    /// Row that's being edited, along with its bytes as hex.
+    editing: Option<(usize, String)>,
+}
+
+impl MemoryView {
+    pub fn new(
+        ui_queue: Arc<UiQueue>,
+        addr: usize,
+        bytes: Vec<u8>,
+        previous: Option<&MemoryView>,
+    ) -> Self {
+        let previous = previous.filter(|view| view.addr == addr).map(|view| &view.bytes[..]);
+        let changed = bytes
+            .iter()
+            .enumerate()
+            .map(|(idx, byte)| previous.is_some_and(|prev| prev.get(idx) != Some(byte)))
+            .collect();
+
+        Self {
+            ui_queue,
+            addr,
+            bytes,
+            changed,
+            editing: None,
+        }
+    }
+
+    pub fn addr(&self) -> usize {
+        self.addr
+    }
+
+    fn row_tokens(&self, offset: usize) -> Vec<Token> {
+        let end = std::cmp::min(offset + BYTES_PER_ROW, self.bytes.len());
+        let mut tokens = vec![Token::from_string(
+            format!("{:0>12X}  ", self.addr + offset),
+            CONFIG.colors.text,
+        )];
+
+        for idx in offset..end {
+            let color = match self.changed[idx] {
+                true => CONFIG.colors.asm.invalid,
+                false => CONFIG.colors.asm.immediate,
+            };
+
+            tokens.push(Token::from_string(
+                format!("{:02x} ", self.bytes[idx]),
+                color,
+            ));
+        }
+
+        let padding = (offset + BYTES_PER_ROW - end) * 3;
+        let text: String = self.bytes[offset..end]
+            .iter()
+            .map(|&byte| match byte {
+                0x20..=0x7e => byte as char,
+                _ => '.',
+            })
+            .collect();
+
+        tokens.push(Token::from_string(" ".repeat(padding + 1), CONFIG.colors.text));
+        tokens.push(Token::from_string(text, CONFIG.colors.asm.primitive));
+        tokens
+    }
+}
+
+/// Parse bytes written as hex, optionally separated by whitespace.
+fn parse_bytes(hex: &str) -> Option<Vec<u8>> {
+    let digits: Vec<u8> = hex.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+    if !digits.len().is_multiple_of(2) {
+        return None;
+    }
+
+    digits
+        .chunks(2)
+        .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+        .collect()
+}
+
+impl Display for MemoryView {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            if ui.button(crate::icon!(ARROW_UP, " Previous")).clicked() {
+                let addr = self.addr.saturating_sub(VIEW_SIZE);
+                self.ui_queue.push(UIEvent::ReadMemory(addr));
+            }
+
+            if ui.button(crate::icon!(ARROW_DOWN, " Next")).clicked() {
+                let addr = self.addr.saturating_add(VIEW_SIZE);
+                self.ui_queue.push(UIEvent::ReadMemory(addr));
+            }
+
+            if ui.button(crate::icon!(LOOP2, " Refresh")).clicked() {
+                self.ui_queue.push(UIEvent::ReadMemory(self.addr));
+            }
+        });
+
+        let area = egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
+
+        area.show(ui, |ui| {
+            if self.bytes.is_empty() {
+                ui.label("Memory can't be read.");
+            }
+
+            for offset in (0..self.bytes.len()).step_by(BYTES_PER_ROW) {
+                let addr = self.addr + offset;
+
+                // rows are edited by double-clicking them and written by pressing enter
+                if let Some((row, hex)) = self.editing.as_mut().filter(|(row, _)| *row == addr) {
+                    let response = ui.add(egui::TextEdit::singleline(hex).font(font()));
+                    response.request_focus();
+
+                    if response.lost_focus() {
+                        if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                            match parse_bytes(hex) {
+                                Some(bytes) => {
+                                    self.ui_queue.push(UIEvent::WriteMemory(*row, bytes));
+                                }
+                                None => log::complex!(
+                                    w "[memory::show] ",
+                                    y format!("'{hex}' isn't hex."),
+                                ),
+                            }
+                        }
+
+                        self.editing = None;
+                    }
+
+                    continue;
+                }
+
+                let tokens = self.row_tokens(offset);
+                let label =
+                    egui::Label::new(tokens_to_layoutjob(tokens)).sense(egui::Sense::click());
+                if ui.add(label).double_clicked() {
+                    let end = std::cmp::min(offset + BYTES_PER_ROW, self.bytes.len());
+                    let hex = self.bytes[offset..end].iter().map(|b| format!("{b:02x} ")).collect();
+                    self.editing = Some((addr, hex));
+                }
+            }
+        });
+    }
+}
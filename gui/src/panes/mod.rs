@@ -1,14 +1,54 @@
+mod actions;
+mod autostart;
+mod backtrace;
+mod binaries;
+mod bookmarks;
+mod breakpoints;
+mod call_graph;
+mod classes;
+mod dead_code;
+mod debugging;
+mod emulation;
+mod function_info;
 mod functions;
+mod imports;
+mod info;
+mod keybindings;
 mod listing;
+mod logs;
+mod managed;
+mod memory;
+mod palette;
+mod pseudo_code;
+mod registers;
+mod script;
+mod sections;
+mod session;
+mod settings;
 mod source_code;
+mod stack;
+mod stats;
+mod status_bar;
+mod strings;
+mod syscalls;
+mod toasts;
+mod trace;
 
 use crate::style::{EGUI, STYLE};
 use crate::widgets::{Donut, Terminal};
 use crate::{common::*, WinitQueue};
+use commands::Feature;
 use config::CONFIG;
+use egui::mutex::RwLock;
 use egui_tiles::{Container, SimplificationOptions, Tile, TileId, Tiles, Tree, UiResponse};
-use processor::Processor;
-use tokenizing::colors;
+use plugins::Plugins;
+use processor::{AnalysisPipeline, Processor, Stage};
+use processor_shared::SectionKind;
+use project::export;
+use project::import::{Analysis, Format};
+use project::session::Session;
+use project::Project;
+use scripting::Scripts;
 
 use std::collections::BTreeMap;
 use std::sync::Arc;
@@ -16,21 +56,94 @@ use std::sync::Arc;
 pub type Identifier = &'static str;
 
 pub const SOURCE: Identifier = crate::icon!(EMBED2, " Source");
+pub const PSEUDO_CODE: Identifier = crate::icon!(FILE_TEXT2, " Pseudo-C");
 pub const DISASSEMBLY: Identifier = crate::icon!(PARAGRAPH_LEFT, " Disassembly");
 pub const FUNCTIONS: Identifier = crate::icon!(LIGATURE, " Functions");
 pub const LOGGING: Identifier = crate::icon!(TERMINAL, " Logs");
+pub const BOOKMARKS: Identifier = crate::icon!(BOOKMARKS, " Bookmarks");
+pub const STATS: Identifier = crate::icon!(STATS_BARS, " Stats");
+pub const INFO: Identifier = crate::icon!(INFO, " Binary info");
+pub const IMPORTS: Identifier = crate::icon!(LINK, " Imports/Exports");
+pub const STRINGS: Identifier = crate::icon!(QUOTES_LEFT, " Strings");
+pub const AUTOSTART: Identifier = crate::icon!(POWER, " Auto-start");
+pub const SYSCALLS: Identifier = crate::icon!(ENTER, " Syscalls");
+pub const DEAD_CODE: Identifier = crate::icon!(EYE_BLOCKED, " Dead code");
+pub const SECTIONS: Identifier = crate::icon!(MAP2, " Sections");
+pub const CLASSES: Identifier = crate::icon!(TREE, " Classes");
+pub const MANAGED: Identifier = crate::icon!(LIST_NUMBERED, " Managed methods");
+pub const CALL_GRAPH: Identifier = crate::icon!(TREE, " Call graph");
+pub const FUNCTION_INFO: Identifier = crate::icon!(PIE_CHART, " Function info");
+pub const EMULATION: Identifier = crate::icon!(LAB, " Emulation");
+pub const REGISTERS: Identifier = crate::icon!(LIST, " Registers");
+pub const STACK: Identifier = crate::icon!(STACK, " Stack");
+pub const MEMORY_MAP: Identifier = crate::icon!(MAP, " Memory map");
+pub const MEMORY: Identifier = crate::icon!(DATABASE, " Memory");
+pub const BREAKPOINTS: Identifier = crate::icon!(TARGET, " Breakpoints");
+pub const BACKTRACE: Identifier = crate::icon!(BUG, " Backtrace");
+
+/// Panes besides those registered by scripts, in the order they're listed in menus.
+const PANES: [Identifier; 25] = [
+    DISASSEMBLY,
+    FUNCTIONS,
+    SOURCE,
+    PSEUDO_CODE,
+    BOOKMARKS,
+    STATS,
+    INFO,
+    IMPORTS,
+    STRINGS,
+    AUTOSTART,
+    SYSCALLS,
+    DEAD_CODE,
+    SECTIONS,
+    CLASSES,
+    MANAGED,
+    CALL_GRAPH,
+    BACKTRACE,
+    FUNCTION_INFO,
+    EMULATION,
+    REGISTERS,
+    STACK,
+    MEMORY_MAP,
+    MEMORY,
+    BREAKPOINTS,
+    LOGGING,
+];
 
 enum PanelKind {
     Disassembly(listing::Listing),
     Functions(functions::Functions),
+    Bookmarks(bookmarks::Bookmarks),
     Source(source_code::Source),
-    Logging,
+    PseudoCode(pseudo_code::PseudoCode),
+    Stats(stats::Stats),
+    Info(info::Info),
+    Imports(imports::Imports),
+    Strings(strings::Strings),
+    AutoStart(autostart::AutoStart),
+    Syscalls(syscalls::Syscalls),
+    DeadCode(dead_code::DeadCode),
+    Sections(sections::Sections),
+    Classes(classes::Classes),
+    Managed(managed::Managed),
+    CallGraph(call_graph::CallGraph),
+    Backtrace(backtrace::Backtrace),
+    FunctionInfo(function_info::FunctionInfo),
+    Emulation(emulation::EmulationInfo),
+    Script(script::ScriptPanel),
+    Registers(registers::Registers),
+    Stack(stack::Stack),
+    MemoryMap(memory::MemoryMap),
+    Memory(memory::MemoryView),
+    Breakpoints(breakpoints::Breakpoints),
+    Logging(logs::Logs),
 }
 
 pub struct Tabs {
     mapping: BTreeMap<Identifier, PanelKind>,
     terminal: Terminal,
     processor: Option<Arc<Processor>>,
+    project: Option<Arc<RwLock<Project>>>,
     donut: Donut,
 }
 
@@ -39,11 +152,12 @@ impl Tabs {
         Self {
             mapping: {
                 let mut mapping = BTreeMap::new();
-                mapping.insert(LOGGING, PanelKind::Logging);
+                mapping.insert(LOGGING, PanelKind::Logging(logs::Logs::default()));
                 mapping
             },
             terminal: Terminal::new(),
             processor: None,
+            project: None,
             donut: Donut::new(false),
         }
     }
@@ -108,18 +222,30 @@ impl egui_tiles::Behavior<Identifier> for Tabs {
             match self.mapping.get_mut(pane) {
                 Some(PanelKind::Disassembly(disassembly)) => disassembly.show(ui),
                 Some(PanelKind::Functions(functions)) => functions.show(ui),
+                Some(PanelKind::Bookmarks(bookmarks)) => bookmarks.show(ui),
                 Some(PanelKind::Source(src)) => src.show(ui),
-                Some(PanelKind::Logging) => {
-                    let area = egui::ScrollArea::vertical()
-                        .auto_shrink([false, false])
-                        .drag_to_scroll(false)
-                        .stick_to_bottom(true);
-
-                    area.show(ui, |ui| {
-                        let layout = log::LOGGER.read().unwrap().format();
-                        ui.label(layout);
-                    });
-                }
+                Some(PanelKind::PseudoCode(code)) => code.show(ui),
+                Some(PanelKind::Stats(stats)) => stats.show(ui),
+                Some(PanelKind::Info(info)) => info.show(ui),
+                Some(PanelKind::Imports(imports)) => imports.show(ui),
+                Some(PanelKind::Strings(strings)) => strings.show(ui),
+                Some(PanelKind::AutoStart(autostart)) => autostart.show(ui),
+                Some(PanelKind::Syscalls(syscalls)) => syscalls.show(ui),
+                Some(PanelKind::DeadCode(dead_code)) => dead_code.show(ui),
+                Some(PanelKind::Sections(sections)) => sections.show(ui),
+                Some(PanelKind::Classes(classes)) => classes.show(ui),
+                Some(PanelKind::Managed(managed)) => managed.show(ui),
+                Some(PanelKind::CallGraph(graph)) => graph.show(ui),
+                Some(PanelKind::Backtrace(backtrace)) => backtrace.show(ui),
+                Some(PanelKind::FunctionInfo(info)) => info.show(ui),
+                Some(PanelKind::Emulation(info)) => info.show(ui),
+                Some(PanelKind::Script(panel)) => panel.show(ui),
+                Some(PanelKind::Registers(registers)) => registers.show(ui),
+                Some(PanelKind::Stack(stack)) => stack.show(ui),
+                Some(PanelKind::MemoryMap(map)) => map.show(ui),
+                Some(PanelKind::Memory(memory)) => memory.show(ui),
+                Some(PanelKind::Breakpoints(breakpoints)) => breakpoints.show(ui),
+                Some(PanelKind::Logging(logs)) => logs.show(ui, &mut self.terminal),
                 None => {}
             };
         });
@@ -132,9 +258,47 @@ pub struct Panels {
     tree: Tree<Identifier>,
     panes: Tabs,
     ui_queue: Arc<crate::UiQueue>,
-    #[allow(dead_code)] // used on windows and linux for top bar
     winit_queue: WinitQueue,
     loading: bool,
+    /// Pipeline of the binary that's loaded or being loaded.
+    pipeline: Option<AnalysisPipeline>,
+    /// Whether the binary being loaded is opened next to the others instead of replacing the
+    /// active one.
+    new_tab: bool,
+    /// Binaries that are open, in the order of their tabs.
+    binaries: Vec<binaries::Binary>,
+    /// Index of the binary whose panes are shown.
+    active: usize,
+    /// What was open when bite was last closed, along with the recently opened files.
+    session: Session,
+    /// Whether the dialog offering to restore the previous session is open.
+    restore_open: bool,
+    /// Binaries of the restored session that still have to be opened.
+    restoring: std::collections::VecDeque<project::session::Binary>,
+    /// Binary that's shown once the session is restored.
+    restore_active: Option<usize>,
+    /// Address the listing of the binary being loaded jumps to.
+    jump_to: Option<usize>,
+    plugins: Plugins,
+    /// Whether the plugins dialog is open.
+    plugins_open: bool,
+    palette: palette::Palette,
+    keybindings: keybindings::Keybindings,
+    /// Settings as they're edited in the settings dialog.
+    settings: config::settings::Settings,
+    /// Whether the settings dialog is open.
+    settings_open: bool,
+    scripts: Arc<Scripts>,
+    /// Identifiers of the panels registered by scripts, in the same order.
+    script_panels: Vec<Identifier>,
+    /// Target that's being debugged, either remotely or on this machine.
+    debugger: Option<debugger::Session>,
+    /// Functions of the modules the target loaded, which aren't part of the binary.
+    module_symbols: debugvault::Index,
+    /// Instructions that ran, either traced or loaded from a file.
+    trace: debugger::Trace,
+    /// Problems that were reported, shown in the corner of the window.
+    toasts: toasts::Toasts,
 }
 
 impl Panels {
@@ -148,12 +312,63 @@ impl Panels {
         let root: TileId = tiles.insert_tab_tile(tabs);
         let tree = Tree::new("tree", root, tiles);
 
+        let plugins = match Feature::Plugins.enabled() {
+            true => Plugins::discover(),
+            false => Plugins::default(),
+        };
+
+        let scripts = match Feature::Scripting.enabled() {
+            true => Scripts::load(&plugins),
+            false => Scripts::default(),
+        };
+
+        // panels are only registered once, so their titles can live for the rest of the program
+        let script_panels = scripts
+            .panels
+            .iter()
+            .map(|panel| -> Identifier {
+                Box::leak(format!("{} {}", crate::icon!(FILE_TEXT2), panel.title).into_boxed_str())
+            })
+            .collect();
+
+        let session = Session::load().unwrap_or_else(|err| {
+            log::complex!(
+                w "[panels::new] ",
+                y err.to_string(),
+            );
+            Session::default()
+        });
+
+        // binaries passed as an argument are opened instead
+        let restore_open = commands::ARGS.path.is_none() && !session.binaries.is_empty();
+
         Self {
             tree,
             panes: Tabs::new(),
             ui_queue,
             winit_queue,
             loading: false,
+            pipeline: None,
+            new_tab: false,
+            binaries: Vec::new(),
+            active: 0,
+            session,
+            restore_open,
+            restoring: Default::default(),
+            restore_active: None,
+            jump_to: None,
+            plugins,
+            plugins_open: false,
+            palette: palette::Palette::default(),
+            keybindings: keybindings::Keybindings::load(),
+            settings: config::settings::Settings::default(),
+            settings_open: false,
+            scripts: Arc::new(scripts),
+            script_panels,
+            debugger: None,
+            module_symbols: debugvault::Index::default(),
+            trace: debugger::Trace::default(),
+            toasts: toasts::Toasts::default(),
         }
     }
 
@@ -169,16 +384,157 @@ impl Panels {
         self.panes.processor.as_ref()
     }
 
+    #[inline]
+    pub fn project(&mut self) -> Option<&Arc<RwLock<Project>>> {
+        self.panes.project.as_ref()
+    }
+
+    /// Write the projects of the open binaries to disk if they have any changes.
+    pub fn save_project(&mut self) {
+        for binary in self.binaries.iter() {
+            if let Err(err) = binary.project.write().save() {
+                log::complex!(
+                    w "[panels::save_project] ",
+                    y err.to_string(),
+                );
+            }
+        }
+    }
+
+    /// Add the names, comments and functions another tool found to the project.
+    pub fn import_analysis(&mut self, path: &std::path::Path) {
+        let (processor, project) = match (&self.panes.processor, &self.panes.project) {
+            (Some(processor), Some(project)) => (processor.clone(), project.clone()),
+            _ => {
+                crate::tprint!(self.terminal(), "No targets loaded.");
+                return;
+            }
+        };
+
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) => {
+                crate::tprint!(self.terminal(), "Failed to read {path:?}: {err}.");
+                return;
+            }
+        };
+
+        let format = match Format::detect(path, &text) {
+            Some(format) => format,
+            None => {
+                crate::tprint!(self.terminal(), "Format of {path:?} isn't known.");
+                return;
+            }
+        };
+
+        // map files list addresses relative to a segment, which are either named after a section
+        // or numbered in the order of sections starting from one
+        let segment = |number: u16, name: &str| {
+            let named = processor.sections().find(|s| !name.is_empty() && s.name == name);
+            match named {
+                Some(section) => Some(section.start),
+                None => processor.sections().nth(number.checked_sub(1)? as usize).map(|s| s.start),
+            }
+        };
+
+        let mut analysis = match Analysis::parse(format, &text, segment) {
+            Ok(analysis) => analysis,
+            Err(err) => {
+                crate::tprint!(self.terminal(), "Failed to import {path:?}: {err}");
+                return;
+            }
+        };
+
+        // kallsyms lists where the kernel got loaded, which is randomized unlike in the binary
+        let kernel_base = match processor.index.get_func_by_name("_text") {
+            Some(addr) => Some(addr),
+            None => processor.sections().next().map(|section| section.start),
+        };
+
+        if let Some(addr) = kernel_base {
+            analysis.rebase(addr);
+        }
+
+        let (names, comments, functions) = project.write().import(analysis);
+        crate::tprint!(
+            self.terminal(),
+            "Imported {names} names, {comments} comments and {functions} functions."
+        );
+
+        // functions only become symbols when a binary is loaded, so it's loaded again
+        if functions > 0 {
+            self.ui_queue.push(crate::UIEvent::BinaryRequested(processor.path.clone()));
+        }
+    }
+
+    /// Write the symbols, including renames and discovered functions, as a map file or CSV.
+    pub fn export_symbols(&mut self, path: &std::path::Path) {
+        let (processor, project) = match (&self.panes.processor, &self.panes.project) {
+            (Some(processor), Some(project)) => (processor.clone(), project.clone()),
+            _ => {
+                crate::tprint!(self.terminal(), "No targets loaded.");
+                return;
+            }
+        };
+
+        // imports are already known to any tool that loads the binary
+        let symbols = processor.index.functions().filter(|sym| !sym.item.imported()).map(|sym| {
+            let section = processor.section_by_addr(sym.addr);
+            export::Symbol {
+                addr: sym.addr,
+                name: sym.item.as_str().to_string(),
+                function: section.is_some_and(|section| section.kind == SectionKind::Code),
+            }
+        });
+
+        let symbols = project.read().symbols(symbols);
+        let contents = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("csv") => export::csv(&symbols),
+            _ => {
+                let segments: Vec<export::Segment> = processor
+                    .sections()
+                    .map(|section| export::Segment {
+                        name: section.name.clone(),
+                        start: section.start,
+                        end: section.end,
+                        code: section.kind == SectionKind::Code,
+                    })
+                    .collect();
+
+                let entrypoint = (processor.entrypoint != 0).then_some(processor.entrypoint);
+                export::map(&segments, &symbols, entrypoint)
+            }
+        };
+
+        match std::fs::write(path, contents) {
+            Ok(()) => crate::tprint!(
+                self.terminal(),
+                "Exported {} symbols to {}.",
+                symbols.len(),
+                path.display()
+            ),
+            Err(err) => crate::tprint!(self.terminal(), "Failed to export symbols: {err}."),
+        }
+    }
+
+    #[inline]
+    pub fn scripts(&self) -> &Scripts {
+        &self.scripts
+    }
+
     #[inline]
     pub fn terminal(&mut self) -> &mut Terminal {
         &mut self.panes.terminal
     }
 
-    pub fn is_loading(&self) -> bool {
-        self.loading
-    }
+    pub fn start_loading(&mut self, pipeline: AnalysisPipeline, new_tab: bool) {
+        // stop loading and decoding the previous binary
+        if let Some(previous) = self.pipeline.replace(pipeline) {
+            previous.cancel();
+        }
+
+        self.new_tab = new_tab;
 
-    pub fn start_loading(&mut self) {
         // create new donut to restart internal timer
         self.panes.donut = Donut::new(false);
         self.loading = true;
@@ -201,13 +557,145 @@ impl Panels {
         }
     }
 
-    pub fn load_binary(&mut self, processor: Processor) {
+    /// Show the callers and callees of a function in the call graph.
+    pub fn show_call_graph(&mut self, addr: usize) {
+        if let Some(PanelKind::CallGraph(graph)) = self.panes.mapping.get_mut(CALL_GRAPH) {
+            graph.focus(addr);
+            self.goto_window(CALL_GRAPH);
+        }
+    }
+
+    /// Show the function containing an address as pseudo-C.
+    pub fn load_pseudo_code(&mut self, addr: usize) {
+        if let Some(processor) = self.processor() {
+            let code = pseudo_code::PseudoCode::new(processor, addr);
+            self.panes.mapping.insert(PSEUDO_CODE, PanelKind::PseudoCode(code));
+        }
+    }
+
+    /// Show statistics of the function containing an address.
+    pub fn load_function_info(&mut self, addr: usize) {
+        if let Some(processor) = self.panes.processor.clone() {
+            let info = function_info::FunctionInfo::new(processor, self.ui_queue.clone(), addr);
+            self.panes.mapping.insert(FUNCTION_INFO, PanelKind::FunctionInfo(info));
+        }
+    }
+
+    pub fn load_binary(&mut self, mut processor: Processor) {
+        // don't lose any changes made to the previously loaded binary
+        self.save_project();
+
+        // addresses of the target are those of the previous binary
+        self.detach();
+        self.clear_trace();
+
+        let mut project = match Project::open(&processor.hash, &processor.path) {
+            Ok(project) => project,
+            Err(err) => {
+                log::complex!(
+                    w "[panels::load_binary] ",
+                    y err.to_string(),
+                );
+                Project::new(&processor.hash, &processor.path)
+            }
+        };
+        project.start_session(processor.analysis_time);
+
+        // functions found in earlier sessions, which the analysis might not find again
+        processor.index.insert_functions(project.named_functions());
+
         let processor = Arc::new(processor);
 
+        // finish decoding whatever isn't shown yet in the background
+        let background = processor.clone();
+        std::thread::spawn(move || background.decode_all());
+
+        let binary = scripting::Binary::new(processor.clone(), &project);
+        self.scripts.on_load(&binary);
+        binary.apply(&mut project);
+
+        // patches from earlier sessions only decode the regions they're in again
+        for (&addr, bytes) in &project.patches {
+            if processor.patch(addr, bytes).is_none() {
+                log::complex!(
+                    w "[panels::load_binary] patch at ",
+                    g format!("{addr:#x}"),
+                    w " isn't part of any code.",
+                );
+            }
+        }
+
+        // structures can contain each other, so keep defining them until none are left that can be
+        let mut undefined: Vec<&String> = project.structs.values().collect();
+        while !undefined.is_empty() {
+            let before = undefined.len();
+            undefined.retain(|def| processor.define_structs(def).is_err());
+            if undefined.len() == before {
+                for def in undefined {
+                    log::complex!(
+                        w "[panels::load_binary] failed to define ",
+                        g def,
+                        w ".",
+                    );
+                }
+                break;
+            }
+        }
+
+        for (&addr, name) in &project.applied {
+            if processor.apply_struct(addr, name).is_none() {
+                log::complex!(
+                    w "[panels::load_binary] structure ",
+                    g name,
+                    w " doesn't fit at ",
+                    g format!("{addr:#x}"),
+                    w ".",
+                );
+            }
+        }
+
+        for def in project.enums.values() {
+            if processor.define_enums(def).is_err() {
+                log::complex!(
+                    w "[panels::load_binary] failed to define ",
+                    g def,
+                    w ".",
+                );
+            }
+        }
+
+        for (&addr, name) in &project.applied_enums {
+            if processor.apply_enum(addr, name).is_none() {
+                log::complex!(
+                    w "[panels::load_binary] enumeration ",
+                    g name,
+                    w " can't be applied to ",
+                    g format!("{addr:#x}"),
+                    w ".",
+                );
+            }
+        }
+
+        let project = Arc::new(RwLock::new(project));
+
+        // the panes of the active binary either move to its tab, or are replaced
+        let panes = self.take_binary_panes();
+        let binary = binaries::Binary::new(processor.clone(), project.clone());
+        if self.new_tab && !self.binaries.is_empty() {
+            self.binaries[self.active].mapping = panes;
+            self.binaries.push(binary);
+            self.active = self.binaries.len() - 1;
+        } else if self.binaries.is_empty() {
+            self.binaries.push(binary);
+        } else {
+            self.binaries[self.active] = binary;
+        }
+
         self.panes.mapping.insert(
             DISASSEMBLY,
             PanelKind::Disassembly(listing::Listing::new(
                 processor.clone(),
+                project.clone(),
                 self.ui_queue.clone(),
             )),
         );
@@ -220,7 +708,156 @@ impl Panels {
             )),
         );
 
+        self.panes.mapping.insert(
+            BOOKMARKS,
+            PanelKind::Bookmarks(bookmarks::Bookmarks::new(
+                processor.clone(),
+                project.clone(),
+                self.ui_queue.clone(),
+            )),
+        );
+
+        self.panes.mapping.insert(
+            STATS,
+            PanelKind::Stats(stats::Stats::new(processor.clone(), project.clone())),
+        );
+
+        let info = info::Info::new(processor.clone(), self.ui_queue.clone());
+        self.panes.mapping.insert(INFO, PanelKind::Info(info));
+
+        let imports = imports::Imports::new(&processor, self.ui_queue.clone());
+        self.panes.mapping.insert(IMPORTS, PanelKind::Imports(imports));
+
+        let strings = strings::Strings::new(&processor, self.ui_queue.clone());
+        self.panes.mapping.insert(STRINGS, PanelKind::Strings(strings));
+
+        let autostart = autostart::AutoStart::new(&processor, self.ui_queue.clone());
+        self.panes.mapping.insert(AUTOSTART, PanelKind::AutoStart(autostart));
+
+        let syscalls = syscalls::Syscalls::new(&processor, self.ui_queue.clone());
+        self.panes.mapping.insert(SYSCALLS, PanelKind::Syscalls(syscalls));
+
+        let dead_code = dead_code::DeadCode::new(&processor, self.ui_queue.clone());
+        self.panes.mapping.insert(DEAD_CODE, PanelKind::DeadCode(dead_code));
+
+        let sections = sections::Sections::new(processor.clone(), self.ui_queue.clone());
+        self.panes.mapping.insert(SECTIONS, PanelKind::Sections(sections));
+
+        let classes = classes::Classes::new(&processor, self.ui_queue.clone());
+        self.panes.mapping.insert(CLASSES, PanelKind::Classes(classes));
+
+        let managed = managed::Managed::new(processor.clone(), self.ui_queue.clone());
+        self.panes.mapping.insert(MANAGED, PanelKind::Managed(managed));
+
+        let graph = call_graph::CallGraph::new(processor.clone(), self.ui_queue.clone());
+        self.panes.mapping.insert(CALL_GRAPH, PanelKind::CallGraph(graph));
+
+        let backtrace = backtrace::Backtrace::new(processor.clone(), self.ui_queue.clone());
+        self.panes.mapping.insert(BACKTRACE, PanelKind::Backtrace(backtrace));
+
+        for (idx, &title) in self.script_panels.iter().enumerate() {
+            let scripts = self.scripts.clone();
+            let panel = script::ScriptPanel::new(scripts, idx, binary.clone(), project.clone());
+            self.panes.mapping.insert(title, PanelKind::Script(panel));
+        }
+
+        // the native code of a .NET assembly is only a stub that loads the runtime
+        if processor.dotnet.is_some() {
+            self.goto_window(MANAGED);
+        }
+
+        // a core dump is opened to find out where the process crashed, unlike snapshots of
+        // processes that are still running which don't have threads
+        if processor.coredump.as_ref().is_some_and(|dump| !dump.threads.is_empty()) {
+            self.goto_window(BACKTRACE);
+        }
+
+        // make sure nobody misses the binary being packed
+        if processor.detections.iter().any(|detection| detection.is_severe()) {
+            self.goto_window(INFO);
+        }
+
+        self.session.add_recent(&processor.path);
         self.panes.processor = Some(processor);
+        self.panes.project = Some(project);
+
+        // binaries of a restored session are shown where they were left
+        if let Some(addr) = self.jump_to.take() {
+            if let Some(listing) = self.listing() {
+                listing.jump(addr);
+            }
+        }
+
+        self.restore_open = false;
+        self.save_session();
+        self.continue_restoring();
+    }
+
+    pub fn show_plugins(&mut self) {
+        self.plugins_open = true;
+    }
+
+    /// Dialog listing the plugins that were loaded and why others were rejected.
+    fn plugins_dialog(&mut self, ctx: &egui::Context) {
+        let plugins = &self.plugins;
+        let scripts = &self.scripts;
+
+        egui::Window::new(crate::icon!(POWER_CORD, " Plugins"))
+            .open(&mut self.plugins_open)
+            .collapsible(false)
+            .resizable(true)
+            .show(ctx, |ui| {
+                if !Feature::Plugins.enabled() {
+                    ui.label("Plugins are disabled by the config or safe mode.");
+                    return;
+                }
+
+                ui.label(format!("Plugin API version {}.", plugins::API_VERSION));
+                if let Ok(dir) = plugins::directory() {
+                    ui.label(format!("Plugins are loaded from {dir:?}."));
+                }
+
+                ui.separator();
+                if plugins.loaded.is_empty() {
+                    ui.label("No plugins loaded.");
+                }
+
+                for plugin in plugins.loaded.iter() {
+                    let manifest = &plugin.manifest;
+                    let provides = &manifest.provides;
+
+                    ui.collapsing(format!("{} {}", manifest.name, manifest.version), |ui| {
+                        ui.label(format!("Path: {:?}", plugin.path));
+                        ui.label(format!("Loaders: {}", provides.loaders.join(", ")));
+                        ui.label(format!("Decoders: {}", provides.decoders.join(", ")));
+                        ui.label(format!("Passes: {}", provides.passes.join(", ")));
+                        ui.label(format!("Scripts: {}", provides.scripts.join(", ")));
+                    });
+                }
+
+                if !Feature::Scripting.enabled() {
+                    ui.label("Scripts are disabled by the config or safe mode.");
+                }
+
+                if !plugins.rejected.is_empty() || !scripts.rejected.is_empty() {
+                    ui.separator();
+                    ui.label("Rejected:");
+                }
+
+                for rejected in plugins.rejected.iter() {
+                    ui.colored_label(
+                        CONFIG.colors.asm.invalid,
+                        format!("{:?}: {}", rejected.path, rejected.reason),
+                    );
+                }
+
+                for rejected in scripts.rejected.iter() {
+                    ui.colored_label(
+                        CONFIG.colors.asm.invalid,
+                        format!("{:?}: {}", rejected.path, rejected.reason),
+                    );
+                }
+            });
     }
 
     pub fn ask_for_binary(&self) {
@@ -229,7 +866,21 @@ impl Panels {
         }
     }
 
+    /// Ask for a binary to open next to the ones that are already open.
+    pub fn ask_for_binary_in_new_tab(&self) {
+        if let Some(path) = rfd::FileDialog::new().pick_file() {
+            self.ui_queue.push(crate::UIEvent::BinaryRequestedInNewTab(path));
+        }
+    }
+
     pub fn handle_events(&mut self, events: &mut Vec<egui::Event>) {
+        // typing goes to the command palette or the keybindings being edited instead
+        if self.palette.is_open() || self.keybindings.is_open() {
+            return;
+        }
+
+        self.handle_shortcuts(events);
+
         if let Some(listing) = self.listing() {
             listing.record_input(events);
         }
@@ -314,34 +965,46 @@ impl Panels {
                     ui.close_menu();
                 }
 
-                if ui.button(crate::icon!(CROSS, " Exit")).clicked() {
-                    self.winit_queue.push(crate::WinitEvent::CloseRequest);
+                if ui.button(crate::icon!(FOLDER_OPEN, " Open in new tab")).clicked() {
+                    self.ask_for_binary_in_new_tab();
                     ui.close_menu();
                 }
-            });
 
-            ui.menu_button("Windows", |ui| {
-                if ui.button(DISASSEMBLY).clicked() {
-                    self.goto_window(DISASSEMBLY);
+                ui.menu_button(crate::icon!(HISTORY, " Recent"), |ui| self.recent_menu(ui));
+
+                if ui.button(crate::icon!(COG, " Settings")).clicked() {
+                    self.show_settings();
                     ui.close_menu();
                 }
 
-                if ui.button(FUNCTIONS).clicked() {
-                    self.goto_window(FUNCTIONS);
+                if ui.button(crate::icon!(KEYBOARD, " Keybindings")).clicked() {
+                    self.show_keybindings();
                     ui.close_menu();
                 }
 
-                if ui.button(SOURCE).clicked() {
-                    self.goto_window(SOURCE);
+                if ui.button(crate::icon!(POWER_CORD, " Plugins")).clicked() {
+                    self.show_plugins();
                     ui.close_menu();
                 }
 
-                if ui.button(LOGGING).clicked() {
-                    self.goto_window(LOGGING);
+                if ui.button(crate::icon!(CROSS, " Exit")).clicked() {
+                    self.winit_queue.push(crate::WinitEvent::CloseRequest);
                     ui.close_menu();
                 }
             });
 
+            ui.menu_button("Windows", |ui| {
+                let panels: Vec<Identifier> =
+                    PANES.iter().chain(self.script_panels.iter()).copied().collect();
+
+                for title in panels {
+                    if ui.button(title).clicked() {
+                        self.run_action(actions::Action::ShowPanel(title));
+                        ui.close_menu();
+                    }
+                }
+            });
+
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Max), |ui| {
                 ui.spacing_mut().item_spacing.x = 5.0;
                 self.top_bar_native(ui);
@@ -357,55 +1020,56 @@ impl Panels {
         }
     }
 
-    fn input(&mut self, ctx: &mut egui::Context) {
-        let modifier = if cfg!(target_os = "macos") {
-            egui::Modifiers::MAC_CMD
-        } else {
-            egui::Modifiers::CTRL
-        };
-
-        if ctx.input_mut(|i| i.consume_key(modifier, egui::Key::O)) {
-            self.ask_for_binary();
-        }
-
-        // alt-tab'ing between tabs
-        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::CTRL, egui::Key::Tab)) {
-            for id in self.tree.active_tiles() {
-                if let Some(parent_id) = self.tree.tiles.parent_of(id) {
-                    let parent = self.tree.tiles.get_mut(parent_id).unwrap();
-                    if let Tile::Container(Container::Tabs(tabs)) = parent {
-                        if tabs.children.len() < 2 {
-                            continue;
-                        }
-                        let active_tab = match tabs.active {
-                            Some(active) => active,
-                            None => continue,
-                        };
-
-                        let mut active_idx = 0;
-                        for (idx, child) in tabs.children.iter().enumerate() {
-                            if *child == active_tab {
-                                active_idx = idx;
-                            }
+    /// Show the next tab of the first dock that has several, like alt-tab'ing between windows.
+    fn next_tab(&mut self) {
+        for id in self.tree.active_tiles() {
+            if let Some(parent_id) = self.tree.tiles.parent_of(id) {
+                let parent = self.tree.tiles.get_mut(parent_id).unwrap();
+                if let Tile::Container(Container::Tabs(tabs)) = parent {
+                    if tabs.children.len() < 2 {
+                        continue;
+                    }
+                    let active_tab = match tabs.active {
+                        Some(active) => active,
+                        None => continue,
+                    };
+
+                    let mut active_idx = 0;
+                    for (idx, child) in tabs.children.iter().enumerate() {
+                        if *child == active_tab {
+                            active_idx = idx;
                         }
-
-                        let next_idx = (active_idx + 1) % tabs.children.len();
-                        let next_id = tabs.children[next_idx];
-                        tabs.set_active(next_id);
-                        break;
                     }
+
+                    let next_idx = (active_idx + 1) % tabs.children.len();
+                    let next_id = tabs.children[next_idx];
+                    tabs.set_active(next_id);
+                    break;
                 }
             }
         }
     }
 
     pub fn draw(&mut self, ctx: &mut egui::Context) {
-        // generic keyboard inputs
-        self.input(ctx);
+        self.poll_debugger();
+
+        // loading got cancelled from its progress bar
+        if self.loading && self.pipeline.as_ref().is_some_and(AnalysisPipeline::is_cancelled) {
+            self.stop_loading();
+            self.continue_restoring();
+        }
 
         #[cfg(any(target_family = "windows", target_os = "linux"))]
         egui::TopBottomPanel::top("top bar").show(ctx, |ui| self.top_bar(ui));
 
+        egui::TopBottomPanel::bottom("status bar")
+            .frame({
+                egui::Frame::default()
+                    .inner_margin(egui::Margin::symmetric(8.0, 2.0))
+                    .fill(CONFIG.colors.bg_secondary)
+            })
+            .show(ctx, |ui| self.status_bar(ui));
+
         // terminal needs to be rendered last as it can take focus away from other panels
         let terminal = egui::TopBottomPanel::bottom("terminal")
             .min_height(80.0)
@@ -414,7 +1078,7 @@ impl Panels {
             .frame({
                 egui::Frame::default()
                     .inner_margin(egui::Margin::same(STYLE.separator_width * 2.0))
-                    .fill(colors::GRAY35)
+                    .fill(CONFIG.colors.frame)
             });
 
         let mut visuals = EGUI.visuals.clone();
@@ -427,7 +1091,9 @@ impl Panels {
 
         ctx.set_visuals(visuals);
 
-        let request_focus = self.terminal().should_reset_cursor();
+        let request_focus = self.terminal().should_reset_cursor()
+            && !self.palette.is_open()
+            && !self.keybindings.is_open();
         let term_response = terminal.show(ctx, |ui| {
             let response = ui
                 .with_layout(egui::Layout::top_down_justified(egui::Align::Min), |ui| {
@@ -447,10 +1113,21 @@ impl Panels {
                     egui::Layout::top_down_justified(egui::Align::Center),
                     |ui| {
                         self.panes.donut.show(ui);
+
+                        if let Some(ref pipeline) = self.pipeline {
+                            let stage = pipeline.stage();
+                            let count = Stage::ALL.len();
+                            ui.label(format!("{stage} ({}/{count})", stage.number()));
+                        }
+
                         log::PROGRESS.show(ui);
                     },
                 );
             } else {
+                if self.binaries.len() > 1 {
+                    self.binary_tabs(ui);
+                }
+
                 self.tree.ui(&mut self.panes, ui);
             }
 
@@ -459,5 +1136,12 @@ impl Panels {
                 ui.ctx().memory_mut(|m| m.request_focus(term_response.inner.id));
             }
         });
+
+        self.plugins_dialog(ctx);
+        self.restore_dialog(ctx);
+        self.palette_dialog(ctx);
+        self.keybindings_dialog(ctx);
+        self.settings_dialog(ctx);
+        self.toasts.show(ctx);
     }
 }
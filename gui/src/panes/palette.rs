@@ -0,0 +1,151 @@
+//! Fuzzy searchable list of every action, opened with Ctrl+Shift+P by default.
+
+use super::actions::Entry;
+use crate::common::*;
+use config::CONFIG;
+
+/// Number of matching actions that are shown at once.
+const MAX_SHOWN: usize = 12;
+
+#[derive(Default)]
+pub struct Palette {
+    open: bool,
+    query: String,
+    /// Index into the matching actions.
+    selected: usize,
+}
+
+impl Palette {
+    pub fn open(&mut self) {
+        self.open = true;
+        self.query.clear();
+        self.selected = 0;
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+}
+
+/// How well a query matches a title, lower being better, if all of its characters appear in
+/// order. Characters that follow the previous match or start a word match best.
+fn score(query: &str, title: &str) -> Option<usize> {
+    let mut score = 0;
+    let mut title = title.chars();
+    let mut prev = ' ';
+    // whether the previous character of the title matched
+    let mut matched = false;
+
+    for q in query.chars().filter(|c| !c.is_whitespace()) {
+        loop {
+            let c = title.next()?;
+            let word_start = !prev.is_alphanumeric();
+            prev = c;
+
+            if c.eq_ignore_ascii_case(&q) {
+                score += match (matched, word_start) {
+                    (true, _) => 0,
+                    (false, true) => 1,
+                    (false, false) => 3,
+                };
+                matched = true;
+                break;
+            }
+
+            matched = false;
+        }
+    }
+
+    Some(score)
+}
+
+impl super::Panels {
+    /// Matching actions, best first.
+    fn matches(&self) -> Vec<Entry> {
+        let mut scored: Vec<(usize, Entry)> = self
+            .actions()
+            .into_iter()
+            .filter_map(|entry| score(&self.palette.query, entry.title).map(|s| (s, entry)))
+            .collect();
+
+        // sorting is stable, so equally good matches stay in the order they're listed
+        scored.sort_by_key(|(score, _)| *score);
+        scored.into_iter().map(|(_, entry)| entry).collect()
+    }
+
+    pub(super) fn palette_dialog(&mut self, ctx: &egui::Context) {
+        if !self.palette.open {
+            return;
+        }
+
+        let matches = self.matches();
+        let palette = &mut self.palette;
+        let keybindings = &self.keybindings;
+        let mut chosen = None;
+
+        ctx.input_mut(|i| {
+            if i.consume_key(egui::Modifiers::NONE, egui::Key::Escape) {
+                palette.open = false;
+            }
+
+            if i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown) {
+                palette.selected += 1;
+            }
+
+            if i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp) {
+                palette.selected = palette.selected.saturating_sub(1);
+            }
+
+            if i.consume_key(egui::Modifiers::NONE, egui::Key::Enter) {
+                chosen = matches.get(palette.selected).map(|entry| entry.action);
+            }
+        });
+
+        palette.selected = palette.selected.min(matches.len().min(MAX_SHOWN).saturating_sub(1));
+
+        egui::Window::new("command palette")
+            .title_bar(false)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 40.0])
+            .fixed_size([450.0, 0.0])
+            .show(ctx, |ui| {
+                let edit = egui::TextEdit::singleline(&mut palette.query)
+                    .hint_text("Search actions")
+                    .desired_width(f32::INFINITY)
+                    .font(font());
+
+                let response = ui.add(edit);
+                response.request_focus();
+                if response.changed() {
+                    palette.selected = 0;
+                }
+
+                ui.separator();
+                if matches.is_empty() {
+                    ui.label("No matching actions.");
+                }
+
+                for (idx, entry) in matches.iter().enumerate().take(MAX_SHOWN) {
+                    ui.horizontal(|ui| {
+                        let label = ui.selectable_label(idx == palette.selected, entry.title);
+                        if label.clicked() {
+                            chosen = Some(entry.action);
+                        }
+
+                        if let Some(shortcut) = keybindings.shortcut(entry.id) {
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                let shortcut = shortcut.to_string();
+                                ui.colored_label(CONFIG.colors.asm.annotation, shortcut);
+                            });
+                        }
+                    });
+                }
+            });
+
+        if let Some(action) = chosen {
+            self.palette.open = false;
+            self.run_action(action);
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use crate::common::*;
+use processor::Processor;
+use tokenizing::Token;
+
+pub struct PseudoCode {
+    /// Highlighted listing, or why the function couldn't be lifted.
+    content: Result<Vec<Token>, String>,
+}
+
+impl PseudoCode {
+    pub fn new(processor: &Processor, addr: usize) -> Self {
+        Self {
+            content: processor.lift(addr).map(|code| code.tokens()).map_err(|err| err.to_string()),
+        }
+    }
+}
+
+impl Display for PseudoCode {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        let area = egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
+
+        area.show(ui, |ui| match &self.content {
+            Ok(tokens) => {
+                ui.label(tokens_to_layoutjob(tokens.clone()));
+            }
+            Err(err) => {
+                ui.label(err.as_str());
+            }
+        });
+    }
+}
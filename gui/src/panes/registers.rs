@@ -0,0 +1,67 @@
+use crate::common::*;
+use config::CONFIG;
+use debugger::Register;
+use tokenizing::Token;
+
+pub struct Registers {
+    registers: debugger::Registers,
+    /// Whether each register changed since the target stopped the last time.
+    changed: Vec<bool>,
+}
+
+impl Registers {
+    pub fn new(registers: debugger::Registers, previous: Option<&Registers>) -> Self {
+        let changed = registers
+            .registers
+            .iter()
+            .map(|reg| {
+                let previous = previous.and_then(|prev| prev.registers.get(reg.name));
+                previous.is_some_and(|prev| prev.bytes != reg.bytes)
+            })
+            .collect();
+
+        Self { registers, changed }
+    }
+}
+
+fn row(ui: &mut egui::Ui, reg: &Register, changed: bool) {
+    let color = match changed {
+        true => CONFIG.colors.asm.invalid,
+        false => CONFIG.colors.asm.immediate,
+    };
+
+    let tokens = vec![
+        Token::from_string(format!("{:<8}", reg.name), CONFIG.colors.text),
+        Token::from_string(reg.hex(), color),
+    ];
+
+    ui.label(tokens_to_layoutjob(tokens));
+}
+
+impl Display for Registers {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        let area = egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
+
+        area.show(ui, |ui| {
+            let registers = self.registers.registers.iter().zip(self.changed.iter());
+            let (float, general): (Vec<_>, Vec<_>) = registers.partition(|(reg, _)| reg.float);
+
+            egui::CollapsingHeader::new("General purpose")
+                .default_open(true)
+                .show(ui, |ui| {
+                    for (reg, &changed) in general {
+                        row(ui, reg, changed);
+                    }
+                });
+
+            // stubs leave out the registers they don't have
+            if !float.is_empty() {
+                egui::CollapsingHeader::new("Floating point").show(ui, |ui| {
+                    for (reg, &changed) in float {
+                        row(ui, reg, changed);
+                    }
+                });
+            }
+        });
+    }
+}
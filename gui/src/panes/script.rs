@@ -0,0 +1,59 @@
+use crate::common::*;
+use config::CONFIG;
+use egui::mutex::RwLock;
+use project::Project;
+use scripting::{Binary, Scripts};
+use std::sync::Arc;
+
+/// Panel registered by a script, showing the text it returns.
+pub struct ScriptPanel {
+    scripts: Arc<Scripts>,
+    /// Index of the panel in [`Scripts::panels`].
+    panel: usize,
+    binary: Binary,
+    project: Arc<RwLock<Project>>,
+    text: Result<String, String>,
+}
+
+impl ScriptPanel {
+    pub fn new(
+        scripts: Arc<Scripts>,
+        panel: usize,
+        binary: Binary,
+        project: Arc<RwLock<Project>>,
+    ) -> Self {
+        let mut this = Self {
+            scripts,
+            panel,
+            binary,
+            project,
+            text: Ok(String::new()),
+        };
+
+        this.refresh();
+        this
+    }
+
+    /// Run the script again, applying any changes it makes to the project.
+    fn refresh(&mut self) {
+        let panel = &self.scripts.panels[self.panel];
+        self.text = self.scripts.render(panel, &self.binary).map_err(|err| err.to_string());
+        self.binary.apply(&mut self.project.write());
+    }
+}
+
+impl Display for ScriptPanel {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        if ui.button(crate::icon!(LOOP2, " Refresh")).clicked() {
+            self.refresh();
+        }
+
+        ui.add_space(5.0);
+
+        let area = egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
+        area.show(ui, |ui| match &self.text {
+            Ok(text) => ui.label(egui::RichText::new(text).font(font())),
+            Err(err) => ui.colored_label(CONFIG.colors.asm.invalid, err),
+        });
+    }
+}
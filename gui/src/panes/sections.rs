@@ -0,0 +1,143 @@
+//! Layout of the binary, with every section drawn to scale.
+
+use crate::common::*;
+use crate::{UIEvent, UiQueue};
+use config::CONFIG;
+use processor::Processor;
+use processor_shared::{Section, SectionKind};
+use std::sync::Arc;
+use tokenizing::Token;
+
+/// Height of the bar the sections are drawn in.
+const BAR_HEIGHT: f32 = 32.0;
+
+/// Smallest width of a section in the bar, so tiny sections can still be hovered.
+const MIN_SECTION_WIDTH: f32 = 2.0;
+
+pub struct Sections {
+    processor: Arc<Processor>,
+    ui_queue: Arc<UiQueue>,
+}
+
+impl Sections {
+    pub fn new(processor: Arc<Processor>, ui_queue: Arc<UiQueue>) -> Self {
+        Self {
+            processor,
+            ui_queue,
+        }
+    }
+
+    /// Sections that are loaded into memory, which are the ones that take up space in the bar.
+    fn loaded(&self) -> impl Iterator<Item = &Section> {
+        self.processor
+            .sections()
+            .filter(|sec| !matches!(sec.kind, SectionKind::Unloaded | SectionKind::Debug))
+            .filter(|sec| sec.start < sec.end)
+    }
+
+    /// Draw the loaded sections next to each other, each as wide as its share of their size.
+    fn bar(&self, ui: &mut egui::Ui) {
+        let (rect, _) = ui.allocate_exact_size(
+            egui::vec2(ui.available_width(), BAR_HEIGHT),
+            egui::Sense::hover(),
+        );
+
+        let total: usize = self.loaded().map(|sec| sec.end - sec.start).sum();
+        let mut left = rect.left();
+
+        for section in self.loaded() {
+            let share = (section.end - section.start) as f32 / total.max(1) as f32;
+            let width = (rect.width() * share).max(MIN_SECTION_WIDTH);
+            let piece = egui::Rect::from_min_size(
+                egui::pos2(left, rect.top()),
+                egui::vec2(width, rect.height()),
+            );
+            left += width;
+
+            let color = if section.permissions.execute {
+                CONFIG.colors.asm.opcode
+            } else if section.permissions.write {
+                CONFIG.colors.asm.pointer
+            } else {
+                CONFIG.colors.asm.string
+            };
+
+            let painter = ui.painter_at(piece);
+            painter.rect_filled(piece, 0.0, color.linear_multiply(0.35));
+            painter.rect_stroke(piece, 0.0, egui::Stroke::new(1.0, CONFIG.colors.bg_primary));
+            painter.text(
+                piece.left_center() + egui::vec2(4.0, 0.0),
+                egui::Align2::LEFT_CENTER,
+                &section.name,
+                font(),
+                CONFIG.colors.text,
+            );
+
+            let id = ui.id().with(("section", section.start));
+            let response = ui.interact(piece, id, egui::Sense::click());
+            if response.on_hover_text(describe(section)).clicked() {
+                self.ui_queue.push(UIEvent::GotoAddr(section.start));
+            }
+        }
+    }
+}
+
+/// Name, access, size and placement of a section, as shown when hovering it.
+fn describe(section: &Section) -> String {
+    let file_offset = match section.file_offset {
+        Some(offset) => format!("{offset:#X}"),
+        None => "not in file".to_string(),
+    };
+
+    format!(
+        "{}\nPermissions  {}\nSize         {:#X}\nAddress      {:#X}-{:#X}\nFile offset  {}",
+        section.name,
+        section.permissions,
+        section.end - section.start,
+        section.start,
+        section.end,
+        file_offset,
+    )
+}
+
+impl Display for Sections {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        self.bar(ui);
+        ui.separator();
+
+        let area = egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
+        area.show(ui, |ui| {
+            let header = format!(
+                "{:<20} {:<4} {:>12} {:>12} {:>10} {:>10}",
+                "Name", "Perm", "Start", "End", "Size", "Offset"
+            );
+            ui.label(egui::RichText::new(header).font(font()).color(CONFIG.colors.hint));
+
+            for section in self.processor.sections() {
+                let file_offset = section.file_offset.map_or("-".to_string(), |o| format!("{o:X}"));
+                let tokens = vec![
+                    Token::from_string(format!("{:<20} ", section.name), CONFIG.colors.text),
+                    Token::from_string(
+                        format!("{:<4} ", section.permissions),
+                        CONFIG.colors.asm.annotation,
+                    ),
+                    Token::from_string(
+                        format!("{:>12X} {:>12X} ", section.start, section.end),
+                        CONFIG.colors.address,
+                    ),
+                    Token::from_string(
+                        format!("{:>10X} ", section.end - section.start),
+                        CONFIG.colors.asm.immediate,
+                    ),
+                    Token::from_string(format!("{file_offset:>10}"), CONFIG.colors.bytes),
+                ];
+
+                let label =
+                    egui::Label::new(tokens_to_layoutjob(tokens)).sense(egui::Sense::click());
+                if ui.add(label).double_clicked() && section.kind != SectionKind::Unloaded {
+                    self.ui_queue.push(UIEvent::GotoAddr(section.start));
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,203 @@
+//! Restoring the binaries and layout of the previous session, and remembering recent files.
+
+use super::{Identifier, PanelKind, DISASSEMBLY, PANES};
+use egui_tiles::{Container, Linear, LinearDir, Tile, TileId, Tiles, Tree};
+use project::session::{self, Layout, Session};
+
+fn layout_of(tiles: &Tiles<Identifier>, id: TileId) -> Option<Layout> {
+    let container = match tiles.get(id)? {
+        Tile::Pane(pane) => return Some(Layout::Pane(pane.to_string())),
+        Tile::Container(container) => container,
+    };
+
+    let mut children = Vec::new();
+    let mut active = 0;
+    for &child in container.children() {
+        if let Some(layout) = layout_of(tiles, child) {
+            if let Container::Tabs(tabs) = container {
+                if tabs.active == Some(child) {
+                    active = children.len();
+                }
+            }
+
+            children.push(layout);
+        }
+    }
+
+    Some(match container {
+        Container::Linear(Linear {
+            dir: LinearDir::Horizontal,
+            ..
+        }) => Layout::Horizontal(children),
+        Container::Linear(_) => Layout::Vertical(children),
+        // grids are rare enough to be restored as tabs
+        Container::Tabs(_) | Container::Grid(_) => Layout::Tabs { children, active },
+    })
+}
+
+impl super::Panels {
+    fn identifier(&self, title: &str) -> Option<Identifier> {
+        PANES.iter().chain(self.script_panels.iter()).find(|id| **id == title).copied()
+    }
+
+    fn insert_layout(&self, tiles: &mut Tiles<Identifier>, layout: &Layout) -> Option<TileId> {
+        let children = match layout {
+            Layout::Pane(title) => return self.identifier(title).map(|id| tiles.insert_pane(id)),
+            Layout::Tabs { children, .. }
+            | Layout::Horizontal(children)
+            | Layout::Vertical(children) => children,
+        };
+
+        // panes that no longer exist are left out
+        let ids: Vec<TileId> =
+            children.iter().filter_map(|child| self.insert_layout(tiles, child)).collect();
+
+        if ids.is_empty() {
+            return None;
+        }
+
+        Some(match layout {
+            Layout::Tabs { active, .. } => {
+                let active = ids.get(*active).copied();
+                let tile = tiles.insert_tab_tile(ids);
+                if let (Some(active), Some(Tile::Container(Container::Tabs(tabs)))) =
+                    (active, tiles.get_mut(tile))
+                {
+                    tabs.set_active(active);
+                }
+                tile
+            }
+            Layout::Horizontal(_) => tiles.insert_horizontal_tile(ids),
+            _ => tiles.insert_vertical_tile(ids),
+        })
+    }
+
+    fn apply_layout(&mut self, layout: &Layout) {
+        let mut tiles = Tiles::default();
+        let root = match self.insert_layout(&mut tiles, layout) {
+            // new panes are added next to the root, so it can't be a pane itself
+            Some(root) if matches!(tiles.get(root), Some(Tile::Pane(_))) => {
+                tiles.insert_tab_tile(vec![root])
+            }
+            Some(root) => root,
+            None => return,
+        };
+
+        self.tree = Tree::new("tree", root, tiles);
+    }
+
+    /// Write the open binaries, where their listings are and the layout of the panes to disk.
+    pub fn save_session(&mut self) {
+        let active = self.listing().map(|listing| listing.current_addr());
+        let binaries = self
+            .binaries
+            .iter()
+            .enumerate()
+            .map(|(idx, binary)| {
+                let addr = match binary.mapping.get(DISASSEMBLY) {
+                    _ if idx == self.active => active,
+                    Some(PanelKind::Disassembly(listing)) => Some(listing.current_addr()),
+                    _ => None,
+                };
+
+                session::Binary {
+                    path: binary.processor.path.clone(),
+                    addr,
+                }
+            })
+            .collect();
+
+        let session = Session {
+            recent: self.session.recent.clone(),
+            binaries,
+            active: self.active,
+            layout: self.tree.root.and_then(|root| layout_of(&self.tree.tiles, root)),
+        };
+
+        if let Err(err) = session.save() {
+            log::complex!(
+                w "[panels::save_session] ",
+                y err.to_string(),
+            );
+        }
+    }
+
+    /// Open the binaries of the previous session again, one after another.
+    pub fn restore_session(&mut self) {
+        self.restore_open = false;
+
+        let mut binaries = self.session.binaries.clone().into_iter();
+        let first = match binaries.next() {
+            Some(binary) => binary,
+            None => return,
+        };
+
+        if let Some(layout) = self.session.layout.clone() {
+            self.apply_layout(&layout);
+        }
+
+        self.restoring = binaries.collect();
+        self.restore_active = Some(self.session.active);
+        self.jump_to = first.addr;
+        self.ui_queue.push(crate::UIEvent::BinaryRequested(first.path));
+    }
+
+    /// Open the next binary of the session being restored, as loading one only starts once the
+    /// previous one is done.
+    pub fn continue_restoring(&mut self) {
+        match self.restoring.pop_front() {
+            Some(binary) => {
+                self.jump_to = binary.addr;
+                self.ui_queue.push(crate::UIEvent::BinaryRequestedInNewTab(binary.path));
+            }
+            None => {
+                self.jump_to = None;
+                if let Some(active) = self.restore_active.take() {
+                    self.switch_binary(active);
+                }
+            }
+        }
+    }
+
+    /// Dialog offering to open the binaries that were open when bite was last closed.
+    pub(super) fn restore_dialog(&mut self, ctx: &egui::Context) {
+        if !self.restore_open {
+            return;
+        }
+
+        let binaries = &self.session.binaries;
+        let mut restore = false;
+
+        egui::Window::new(crate::icon!(HISTORY, " Restore session"))
+            .open(&mut self.restore_open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label("These binaries were open when bite was last closed:");
+                for binary in binaries.iter() {
+                    ui.label(binary.path.display().to_string());
+                }
+
+                ui.separator();
+                restore = ui.button("Restore").clicked();
+            });
+
+        if restore {
+            self.restore_session();
+        }
+    }
+
+    /// Menu of the files that were opened recently.
+    pub(super) fn recent_menu(&mut self, ui: &mut egui::Ui) {
+        if self.session.recent.is_empty() {
+            ui.label("No recent files.");
+        }
+
+        for path in self.session.recent.iter() {
+            if ui.button(path.display().to_string()).clicked() {
+                self.ui_queue.push(crate::UIEvent::BinaryRequested(path.clone()));
+                ui.close_menu();
+            }
+        }
+    }
+}
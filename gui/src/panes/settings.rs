@@ -0,0 +1,140 @@
+//! Dialog for changing the preferences that are kept in between sessions.
+
+use super::{PanelKind, DISASSEMBLY};
+use crate::tprint;
+use config::settings::{Settings, Syntax, SETTINGS};
+use config::Column;
+
+/// Show a column that was hidden, where it would be with all columns shown.
+fn show_column(columns: &mut Vec<Column>, column: Column) {
+    let order = |column: &Column| Column::ALL.iter().position(|c| c == column);
+    let idx = columns.iter().filter(|c| order(c) < order(&column)).count();
+    columns.insert(idx, column);
+}
+
+impl super::Panels {
+    pub fn show_settings(&mut self) {
+        self.settings = SETTINGS.read().unwrap().clone();
+        self.settings_open = true;
+    }
+
+    /// Make the settings being edited take effect.
+    fn apply_settings(&mut self) {
+        *SETTINGS.write().unwrap() = self.settings.clone();
+
+        let columns = self.settings.columns.clone();
+        if let Some(listing) = self.listing() {
+            listing.set_columns(columns.clone());
+        }
+
+        for binary in self.binaries.iter_mut() {
+            if let Some(PanelKind::Disassembly(listing)) = binary.mapping.get_mut(DISASSEMBLY) {
+                listing.set_columns(columns.clone());
+            }
+        }
+    }
+
+    pub(super) fn settings_dialog(&mut self, ctx: &egui::Context) {
+        if !self.settings_open {
+            return;
+        }
+
+        let settings = &mut self.settings;
+        let before = settings.clone();
+        let mut open = true;
+        let mut save = false;
+        let mut reset = false;
+
+        egui::Window::new(crate::icon!(COG, " Settings"))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("settings").num_columns(2).spacing([20.0, 8.0]).show(ui, |ui| {
+                    ui.label("Font size");
+                    ui.add(egui::Slider::new(&mut settings.font_size, 8.0..=32.0).step_by(1.0));
+                    ui.end_row();
+
+                    ui.label("Theme");
+                    egui::ComboBox::from_id_source("theme")
+                        .selected_text(settings.theme.as_str())
+                        .show_ui(ui, |ui| {
+                            for theme in config::theme::names() {
+                                ui.selectable_value(&mut settings.theme, theme.clone(), theme);
+                            }
+                        })
+                        .response
+                        .on_hover_text("Takes effect after restarting bite.");
+                    ui.end_row();
+
+                    ui.label("Columns");
+                    ui.vertical(|ui| {
+                        for column in Column::ALL {
+                            let mut shown = settings.columns.contains(&column);
+                            if ui.checkbox(&mut shown, column.to_string()).changed() {
+                                match shown {
+                                    true => show_column(&mut settings.columns, column),
+                                    false => settings.columns.retain(|&c| c != column),
+                                }
+                            }
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label("Bytes");
+                    ui.horizontal(|ui| {
+                        let mut limited = settings.max_bytes.is_some();
+                        ui.checkbox(&mut limited, "Cut off after");
+                        let mut max = settings.max_bytes.unwrap_or(8);
+                        ui.add_enabled(limited, egui::DragValue::new(&mut max).clamp_range(2..=16));
+                        ui.label("bytes");
+                        settings.max_bytes = limited.then_some(max);
+                    })
+                    .response
+                    .on_hover_text("Instructions with more bytes end in '..'.");
+                    ui.end_row();
+
+                    ui.label("Analysis");
+                    ui.checkbox(&mut settings.discover_functions, "Auto-discover functions")
+                        .on_hover_text("Applies to binaries that are opened afterwards.");
+                    ui.end_row();
+
+                    ui.label("Default syntax");
+                    egui::ComboBox::from_id_source("syntax")
+                        .selected_text(settings.default_syntax.to_string())
+                        .show_ui(ui, |ui| {
+                            for syntax in Syntax::ALL {
+                                let text = syntax.to_string();
+                                ui.selectable_value(&mut settings.default_syntax, syntax, text);
+                            }
+                        })
+                        .response
+                        .on_hover_text("Used for source files whose extension doesn't tell.");
+                    ui.end_row();
+                });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    save = ui.button("Save").clicked();
+                    reset = ui.button("Reset").clicked();
+                });
+            });
+
+        if reset {
+            *settings = Settings::default();
+        }
+
+        if *settings != before {
+            self.apply_settings();
+        }
+
+        if save {
+            match self.settings.save() {
+                Ok(()) => tprint!(self.terminal(), "Saved settings."),
+                Err(err) => tprint!(self.terminal(), "Failed to save settings: {err}."),
+            }
+        }
+
+        self.settings_open = open;
+    }
+}
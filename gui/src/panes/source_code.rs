@@ -9,9 +9,9 @@ use tree_sitter::{Language, QueryError};
 use tree_sitter_highlight::{Highlight, HighlightConfiguration, HighlightEvent, Highlighter};
 
 use crate::common::*;
+use config::settings::{Syntax, SETTINGS};
 use config::CONFIG;
 use debugvault::FileAttr;
-use tokenizing::colors;
 
 pub struct Source {
     src: String,
@@ -102,7 +102,7 @@ fn compute_sections<P: AsRef<Path>>(path: P, src: &str) -> Vec<HighlightedSectio
     if last_end < src.len() {
         sections.push(HighlightedSection {
             range: last_end..src.len(),
-            fg_color: Color32::WHITE,
+            fg_color: CONFIG.colors.get_by_style("none"),
             bg_color: Color32::TRANSPARENT,
         });
     }
@@ -151,7 +151,7 @@ impl Source {
             if line_nr == file_attr.line {
                 for section in line.sections.iter_mut() {
                     section.bg_color = CONFIG.colors.src.highlight.linear_multiply(0.5);
-                    section.fg_color = Color32::WHITE;
+                    section.fg_color = CONFIG.colors.text;
                 }
             }
 
@@ -199,7 +199,7 @@ impl Source {
                     egui::TextFormat {
                         color: section.fg_color,
                         background: section.bg_color,
-                        font_id: FONT,
+                        font_id: font(),
                         ..Default::default()
                     },
                 );
@@ -216,22 +216,22 @@ impl Source {
         for line in &self.lines[row_range.clone()] {
             output.push_str(&line.number);
         }
-        ui.label(egui::RichText::new(output).font(FONT).color(colors::GRAY60));
+        ui.label(egui::RichText::new(output).font(font()).color(CONFIG.colors.hint));
     }
 
     pub fn show(&mut self, ui: &mut egui::Ui) {
         let mut area = egui::ScrollArea::vertical().auto_shrink(false).drag_to_scroll(false);
 
         if let Some(scroll) = self.scroll.take() {
-            let row_height = FONT.size;
+            let row_height = font().size;
             let spacing_y = ui.spacing().item_spacing.y;
             let y = scroll as f32 * (row_height + spacing_y);
             area = area.vertical_scroll_offset(y)
         }
 
-        area.show_rows(ui, FONT.size, self.lines.len(), |ui, row_range| {
+        area.show_rows(ui, font().size, self.lines.len(), |ui, row_range| {
             let pad = 8.0;
-            let char_width = ui.fonts(|f| f.glyph_width(&FONT, '1'));
+            let char_width = ui.fonts(|f| f.glyph_width(&font(), '1'));
             let width = char_width * self.max_number_width as f32 + pad;
             let split = width / ui.available_width();
 
@@ -255,27 +255,35 @@ struct LanguageConfig<'a> {
 }
 
 impl LanguageConfig<'_> {
+    /// Language of a file by its extension, or the default syntax in the settings.
     fn guess<P: AsRef<Path>>(path: P) -> Option<Self> {
-        Some(match path.as_ref().extension().and_then(|s| s.to_str()) {
-            Some("rs") => Self {
+        let syntax = match path.as_ref().extension().and_then(|s| s.to_str()) {
+            Some("rs") => Syntax::Rust,
+            Some("c") => Syntax::C,
+            Some("cc" | "cpp" | "h" | "hh" | "hpp" | "cxx" | "cu") => Syntax::Cpp,
+            None | Some(_) => SETTINGS.read().unwrap().default_syntax,
+        };
+
+        Some(match syntax {
+            Syntax::Rust => Self {
                 lang: tree_sitter_rust::language(),
                 highlights_query: tree_sitter_rust::HIGHLIGHT_QUERY,
                 injection_query: Some(tree_sitter_rust::INJECTIONS_QUERY),
                 locals_query: Some(tree_sitter_rust::LOCALS_QUERY),
             },
-            Some("c") => Self {
+            Syntax::C => Self {
                 lang: tree_sitter_c::language(),
                 highlights_query: tree_sitter_c::HIGHLIGHT_QUERY,
                 injection_query: Some(tree_sitter_c::INJECTIONS_QUERY),
                 locals_query: Some(tree_sitter_c::LOCALS_QUERY),
             },
-            Some("cc" | "cpp" | "h" | "hh" | "hpp" | "cxx" | "cu") => Self {
+            Syntax::Cpp => Self {
                 lang: tree_sitter_cpp::language(),
                 highlights_query: tree_sitter_cpp::HIGHLIGHT_QUERY,
                 injection_query: Some(tree_sitter_cpp::INJECTIONS_QUERY),
                 locals_query: Some(tree_sitter_cpp::LOCALS_QUERY),
             },
-            None | Some(_) => return None,
+            Syntax::None => return None,
         })
     }
 
@@ -0,0 +1,88 @@
+use crate::common::*;
+use crate::{UIEvent, UiQueue};
+use config::CONFIG;
+use processor::Processor;
+use processor_shared::SectionKind;
+use std::sync::Arc;
+use tokenizing::Token;
+
+/// Number of values on top of the stack that are read every time the target stops.
+pub const DEPTH: usize = 64;
+
+pub struct Stack {
+    ui_queue: Arc<UiQueue>,
+    /// Values on the stack, along with the code they point to if it's a return address.
+    rows: Vec<(Option<usize>, Vec<Token>)>,
+}
+
+impl Stack {
+    /// Values that don't point into the binary are named after the function `symbol` returns,
+    /// along with its address.
+    pub fn new(
+        processor: &Processor,
+        ui_queue: Arc<UiQueue>,
+        sp: u64,
+        values: &[u64],
+        symbol: impl Fn(usize) -> Option<(usize, String)>,
+    ) -> Self {
+        let width = processor.arch().address_size().map_or(8, |size| size.bytes() as usize);
+        let mut rows = Vec::new();
+
+        for (idx, &value) in values.iter().enumerate() {
+            let addr = sp + (idx * width) as u64;
+            let mut tokens = vec![
+                Token::from_string(format!("{addr:0>10X}"), CONFIG.colors.text),
+                Token::from_str(" | ", CONFIG.colors.text),
+                Token::from_string(
+                    format!("{value:0>digits$X}", digits = width * 2),
+                    CONFIG.colors.asm.immediate,
+                ),
+            ];
+
+            let target = value as usize;
+            let section = processor.section_by_addr(target);
+            let code = section.is_some_and(|section| section.kind == SectionKind::Code);
+
+            // values pointing into code are most likely return addresses
+            let function = processor.function_bounds(target).filter(|_| code);
+            let function =
+                function.map(|(start, _, name)| (start, name)).or_else(|| symbol(target));
+            if let Some((start, name)) = function.as_ref() {
+                tokens.push(Token::from_str(" <", CONFIG.colors.delimiter));
+                tokens.push(Token::from_string(name.clone(), CONFIG.colors.src.function));
+                tokens.push(Token::from_string(
+                    format!("+{:#x}", target - start),
+                    CONFIG.colors.asm.immediate,
+                ));
+                tokens.push(Token::from_str(">", CONFIG.colors.delimiter));
+            }
+
+            rows.push((function.map(|_| target), tokens));
+        }
+
+        Self { ui_queue, rows }
+    }
+}
+
+impl Display for Stack {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        let area = egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
+
+        area.show(ui, |ui| {
+            if self.rows.is_empty() {
+                ui.label("Stack can't be read.");
+            }
+
+            for (target, tokens) in self.rows.iter() {
+                let label = egui::Label::new(tokens_to_layoutjob(tokens.clone()))
+                    .sense(egui::Sense::click());
+
+                if ui.add(label).double_clicked() {
+                    if let Some(addr) = target {
+                        self.ui_queue.push(UIEvent::GotoAddr(*addr));
+                    }
+                }
+            }
+        });
+    }
+}
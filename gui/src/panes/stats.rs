@@ -0,0 +1,120 @@
+use crate::common::*;
+use crate::tprint;
+use config::CONFIG;
+use egui::mutex::RwLock;
+use processor::Processor;
+use project::Project;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokenizing::Token;
+
+pub struct Stats {
+    processor: Arc<Processor>,
+    project: Arc<RwLock<Project>>,
+}
+
+impl Stats {
+    pub fn new(processor: Arc<Processor>, project: Arc<RwLock<Project>>) -> Self {
+        Self { processor, project }
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match secs {
+        0 => format!("{}ms", duration.as_millis()),
+        1..=59 => format!("{:.1}s", duration.as_secs_f64()),
+        60..=3599 => format!("{}m {}s", secs / 60, secs % 60),
+        _ => format!("{}h {}m", secs / 3600, secs / 60 % 60),
+    }
+}
+
+fn stat(ui: &mut egui::Ui, name: &str, value: String) {
+    let tokens = vec![
+        Token::from_string(format!("{name:<20}"), CONFIG.colors.text),
+        Token::from_string(value, CONFIG.colors.asm.immediate),
+    ];
+
+    ui.label(tokens_to_layoutjob(tokens));
+}
+
+impl Display for Stats {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        let area = egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
+
+        area.show(ui, |ui| {
+            let project = self.project.read();
+            let stats = &project.stats;
+
+            ui.label("Usage of this binary, only ever stored in its project file.");
+            ui.add_space(5.0);
+
+            stat(ui, "Sessions", stats.sessions.to_string());
+            stat(ui, "Time spent", format_duration(project.time_spent()));
+            stat(
+                ui,
+                "Functions visited",
+                format!(
+                    "{} of {}",
+                    stats.functions_visited.len(),
+                    self.processor.index.named_funcs_count()
+                ),
+            );
+            stat(ui, "Renames", stats.renames.to_string());
+
+            if let Some(last) = stats.analysis_times.last() {
+                stat(ui, "Last analysis", format_duration(*last));
+
+                let total: Duration = stats.analysis_times.iter().sum();
+                let average = total / stats.analysis_times.len() as u32;
+                stat(ui, "Average analysis", format_duration(average));
+            }
+
+            let timings = &self.processor.timings;
+            if !timings.spans.is_empty() {
+                ui.add_space(5.0);
+                ui.label("Stages of loading the binary this session.");
+                ui.add_space(5.0);
+
+                for span in timings.spans.iter() {
+                    let share =
+                        span.duration.as_secs_f64() / timings.total.as_secs_f64().max(f64::EPSILON);
+                    let value =
+                        format!("{} ({:.0}%)", format_duration(span.duration), share * 100.0);
+                    stat(ui, span.name, value);
+                }
+            }
+        });
+    }
+}
+
+impl super::Panels {
+    /// Print how long every stage of loading the binary took, or save it as JSON.
+    pub fn timings(&mut self, path: Option<&Path>) {
+        let timings = match self.processor() {
+            Some(processor) => processor.timings.clone(),
+            None => {
+                tprint!(self.terminal(), "No targets loaded.");
+                return;
+            }
+        };
+
+        let path = match path {
+            Some(path) => path,
+            None => {
+                tprint!(self.terminal(), "{}", timings.table().trim_end());
+                return;
+            }
+        };
+
+        match std::fs::write(path, timings.to_json()) {
+            Ok(()) => tprint!(self.terminal(), "Saved timings to '{}'.", path.display()),
+            Err(err) => tprint!(
+                self.terminal(),
+                "Failed to save '{}': {err}.",
+                path.display()
+            ),
+        }
+    }
+}
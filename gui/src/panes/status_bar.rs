@@ -0,0 +1,58 @@
+//! Bar along the bottom of the window describing where the listing's cursor is.
+
+use crate::common::*;
+use config::CONFIG;
+use tokenizing::Token;
+
+impl super::Panels {
+    /// Address of the cursor, the section and function it's in and the size of the selection.
+    pub(super) fn status_bar(&mut self, ui: &mut egui::Ui) {
+        let processor = match self.processor() {
+            Some(processor) => processor.clone(),
+            None => return,
+        };
+
+        let (addr, selected) = match self.listing() {
+            Some(listing) => (listing.selected_addr(), listing.selection_size()),
+            None => return,
+        };
+
+        let separator = || Token::from_str(" | ", CONFIG.colors.delimiter);
+        let mut tokens = vec![Token::from_string(
+            format!("{addr:#X}"),
+            CONFIG.colors.address,
+        )];
+
+        if let Some(section) = processor.section_name(addr) {
+            tokens.push(separator());
+            tokens.push(Token::from_string(
+                section.to_string(),
+                CONFIG.colors.asm.section,
+            ));
+        }
+
+        if let Some((start, _, name)) = processor.function_bounds(addr) {
+            tokens.push(separator());
+            tokens.push(Token::from_string(name, CONFIG.colors.asm.label));
+            tokens.push(Token::from_string(
+                format!("+{:#X}", addr - start),
+                CONFIG.colors.asm.immediate,
+            ));
+        }
+
+        if let Some((lines, bytes)) = selected {
+            let lines = match lines {
+                1 => "1 line".to_string(),
+                n => format!("{n} lines"),
+            };
+
+            tokens.push(separator());
+            tokens.push(Token::from_string(
+                format!("{lines}, {bytes} bytes selected"),
+                CONFIG.colors.text,
+            ));
+        }
+
+        ui.label(tokens_to_layoutjob(tokens));
+    }
+}
@@ -0,0 +1,180 @@
+//! Strings of the binary along with the functions using them, or the other way around.
+
+use crate::common::*;
+use crate::{UIEvent, UiQueue};
+use config::CONFIG;
+use processor::Processor;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokenizing::Token;
+
+/// Longest string shown in a row before it's cut off.
+const MAX_SHOWN_LEN: usize = 120;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum View {
+    /// Every string with the functions using it.
+    Strings,
+    /// Every function using strings with the strings it uses.
+    Functions,
+}
+
+/// Row that jumps to its address when double-clicked, with the text it's searched by.
+struct Row {
+    addr: usize,
+    text: String,
+    tokens: Vec<Token>,
+}
+
+impl Row {
+    fn string(addr: usize, text: &str) -> Self {
+        let escaped = text.escape_debug().to_string();
+        let shown = match escaped.char_indices().nth(MAX_SHOWN_LEN) {
+            Some((idx, _)) => format!("\"{}\"..", &escaped[..idx]),
+            None => format!("\"{escaped}\""),
+        };
+
+        Self {
+            addr,
+            text: text.to_lowercase(),
+            tokens: vec![
+                Token::from_string(format!("{addr:0>10X}"), CONFIG.colors.text),
+                Token::from_str(" | ", CONFIG.colors.text),
+                Token::from_string(shown, CONFIG.colors.asm.string),
+            ],
+        }
+    }
+
+    fn function(processor: &Processor, addr: usize) -> Self {
+        let mut tokens = vec![
+            Token::from_string(format!("{addr:0>10X}"), CONFIG.colors.text),
+            Token::from_str(" | ", CONFIG.colors.text),
+        ];
+        tokens.extend(super::call_graph::name_tokens(processor, addr));
+
+        Self {
+            addr,
+            text: super::call_graph::name(processor, addr).to_lowercase(),
+            tokens,
+        }
+    }
+
+    fn show(&self, ui: &mut egui::Ui, ui_queue: &UiQueue) {
+        let label =
+            egui::Label::new(tokens_to_layoutjob(self.tokens.to_vec())).sense(egui::Sense::click());
+
+        if ui.add(label).double_clicked() {
+            ui_queue.push(UIEvent::GotoAddr(self.addr));
+        }
+    }
+}
+
+/// Row that expands into the rows related to it, showing how many there are.
+fn group(
+    ui: &mut egui::Ui,
+    ui_queue: &UiQueue,
+    id: egui::Id,
+    row: &Row,
+    count: usize,
+    rows: Vec<&Row>,
+) {
+    egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, false)
+        .show_header(ui, |ui| {
+            row.show(ui, ui_queue);
+            ui.label(format!(" ({count})"));
+        })
+        .body(|ui| {
+            for row in rows {
+                row.show(ui, ui_queue);
+            }
+        });
+}
+
+pub struct Strings {
+    ui_queue: Arc<UiQueue>,
+    view: View,
+    /// Strings along with the functions using them.
+    strings: Vec<(Row, Vec<Row>)>,
+    /// Functions along with the strings they use, sorted by name.
+    functions: Vec<(Row, Vec<Row>)>,
+    /// Text a string or the name of a function has to contain to be shown.
+    query: String,
+}
+
+impl Strings {
+    pub fn new(processor: &Processor, ui_queue: Arc<UiQueue>) -> Self {
+        let mut used: BTreeMap<usize, Vec<Row>> = BTreeMap::new();
+        let mut strings = Vec::new();
+        for string in processor.strings.iter() {
+            let functions = string
+                .functions
+                .iter()
+                .map(|&function| {
+                    let row = Row::string(string.addr, &string.text);
+                    used.entry(function).or_default().push(row);
+                    Row::function(processor, function)
+                })
+                .collect();
+
+            strings.push((Row::string(string.addr, &string.text), functions));
+        }
+
+        let mut functions: Vec<(Row, Vec<Row>)> = used
+            .into_iter()
+            .map(|(function, strings)| (Row::function(processor, function), strings))
+            .collect();
+
+        functions.sort_by(|(a, _), (b, _)| a.text.cmp(&b.text));
+
+        Self {
+            ui_queue,
+            view: View::Strings,
+            strings,
+            functions,
+            query: String::new(),
+        }
+    }
+}
+
+impl Display for Strings {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.view, View::Strings, "Strings");
+            ui.selectable_value(&mut self.view, View::Functions, "Functions");
+            ui.add(egui::TextEdit::singleline(&mut self.query).hint_text("Search"));
+        });
+        ui.separator();
+
+        let groups = match self.view {
+            View::Strings => &self.strings,
+            View::Functions => &self.functions,
+        };
+
+        let area = egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
+        area.show(ui, |ui| {
+            if groups.is_empty() {
+                ui.label("No strings were found, analysis may be disabled.");
+            }
+
+            let query = self.query.to_lowercase();
+            for (row, rows) in groups.iter() {
+                let row_matches = row.text.contains(&query);
+                let matching: Vec<&Row> =
+                    rows.iter().filter(|row| row_matches || row.text.contains(&query)).collect();
+
+                if !row_matches && matching.is_empty() {
+                    continue;
+                }
+
+                // strings that nothing is known to use don't have anything to expand
+                if rows.is_empty() {
+                    row.show(ui, &self.ui_queue);
+                    continue;
+                }
+
+                let id = ui.make_persistent_id((self.view == View::Strings, row.addr));
+                group(ui, &self.ui_queue, id, row, rows.len(), matching);
+            }
+        });
+    }
+}
@@ -0,0 +1,64 @@
+use crate::common::*;
+use crate::{UIEvent, UiQueue};
+use config::CONFIG;
+use processor::Processor;
+use std::sync::Arc;
+use tokenizing::Token;
+
+/// System calls made directly by the binary, grouped by the call.
+pub struct Syscalls {
+    ui_queue: Arc<UiQueue>,
+    groups: Vec<(String, Vec<(usize, Vec<Token>)>)>,
+}
+
+impl Syscalls {
+    pub fn new(processor: &Processor, ui_queue: Arc<UiQueue>) -> Self {
+        let mut groups: Vec<(String, Vec<(usize, Vec<Token>)>)> = Vec::new();
+        for syscall in processor.syscalls.iter() {
+            let mut tokens = vec![Token::from_string(
+                format!("{:0>10X}", syscall.addr),
+                CONFIG.colors.text,
+            )];
+
+            if let Some((_, _, function)) = processor.function_bounds(syscall.addr) {
+                tokens.push(Token::from_str(" | ", CONFIG.colors.text));
+                tokens.push(Token::from_string(function, CONFIG.colors.src.function));
+            }
+
+            let name = syscall.item.to_string();
+            match groups.iter_mut().find(|(group, _)| *group == name) {
+                Some((_, sites)) => sites.push((syscall.addr, tokens)),
+                None => groups.push((name, vec![(syscall.addr, tokens)])),
+            }
+        }
+
+        groups.sort_by(|(a, _), (b, _)| a.cmp(b));
+        Self { ui_queue, groups }
+    }
+}
+
+impl Display for Syscalls {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        let area = egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
+
+        area.show(ui, |ui| {
+            if self.groups.is_empty() {
+                ui.label("No system calls are made directly.");
+            }
+
+            for (name, sites) in self.groups.iter() {
+                let header = format!("{name} ({})", sites.len());
+                egui::CollapsingHeader::new(header).default_open(false).show(ui, |ui| {
+                    for (addr, tokens) in sites.iter() {
+                        let label = egui::Label::new(tokens_to_layoutjob(tokens.to_vec()))
+                            .sense(egui::Sense::click());
+
+                        if ui.add(label).double_clicked() {
+                            self.ui_queue.push(UIEvent::GotoAddr(*addr));
+                        }
+                    }
+                });
+            }
+        });
+    }
+}
@@ -0,0 +1,96 @@
+//! Notifications in the corner of the window for problems that were reported, see [`log::report!`].
+
+use config::CONFIG;
+use log::{Level, Report};
+
+/// Seconds a toast is shown for before it goes away on its own, errors stay until dismissed.
+const TOAST_DURATION: f64 = 8.0;
+
+/// Width of a toast.
+const TOAST_WIDTH: f32 = 320.0;
+
+struct Toast {
+    report: Report,
+    /// Time the toast was first shown at.
+    shown_at: f64,
+}
+
+#[derive(Default)]
+pub struct Toasts {
+    toasts: Vec<Toast>,
+}
+
+impl Toasts {
+    /// Show the reports that were made since, along with the toasts that haven't gone away yet.
+    pub fn show(&mut self, ctx: &egui::Context) {
+        let now = ctx.input(|input| input.time);
+
+        self.toasts.extend(log::take_reports().into_iter().map(|report| Toast {
+            report,
+            shown_at: now,
+        }));
+
+        self.toasts.retain(|toast| {
+            toast.report.level == Level::Error || now - toast.shown_at < TOAST_DURATION
+        });
+
+        if self.toasts.is_empty() {
+            return;
+        }
+
+        // toasts that expire have to disappear, even if nothing else happens
+        if self.toasts.iter().any(|toast| toast.report.level != Level::Error) {
+            ctx.request_repaint_after(std::time::Duration::from_secs(1));
+        }
+
+        let mut dismissed = None;
+        egui::Area::new(egui::Id::new("toasts"))
+            .order(egui::Order::Foreground)
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-12.0, -40.0))
+            .show(ctx, |ui| {
+                for (idx, toast) in self.toasts.iter().enumerate() {
+                    if toast_ui(ui, &toast.report) {
+                        dismissed = Some(idx);
+                    }
+                }
+            });
+
+        if let Some(idx) = dismissed {
+            self.toasts.remove(idx);
+        }
+    }
+}
+
+/// Draw a single toast, returning whether it was dismissed.
+fn toast_ui(ui: &mut egui::Ui, report: &Report) -> bool {
+    let (title, color) = match report.level {
+        Level::Error => (
+            crate::icon!(CANCEL_CIRCLE, " Error"),
+            CONFIG.colors.asm.invalid,
+        ),
+        Level::Warn => (
+            crate::icon!(WARNING, " Warning"),
+            CONFIG.colors.asm.annotation,
+        ),
+        _ => (crate::icon!(INFO, " Info"), CONFIG.colors.text),
+    };
+
+    let frame = egui::Frame::popup(ui.style())
+        .fill(CONFIG.colors.bg_secondary)
+        .stroke(egui::Stroke::new(1.0, color));
+
+    let mut dismissed = false;
+    frame.show(ui, |ui| {
+        ui.set_width(TOAST_WIDTH);
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new(title).color(color));
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                dismissed = ui.small_button(crate::icon!(CROSS)).on_hover_text("Dismiss").clicked();
+            });
+        });
+
+        ui.add(egui::Label::new(report.message.as_str()).wrap(true));
+    });
+
+    dismissed
+}
@@ -0,0 +1,165 @@
+//! Recording the instructions a debugged target executes, and showing how often they ran.
+
+use super::{PanelKind, FUNCTIONS};
+use crate::tprint;
+use debugger::{Coverage, Module, Session, Trace};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+impl super::Panels {
+    /// Start single-stepping the target whenever it's continued, or stop and show what ran.
+    pub fn toggle_trace(&mut self) {
+        let result = match self.session() {
+            Some(session) => session.toggle_trace(),
+            None => return,
+        };
+
+        match result {
+            Ok(None) => tprint!(
+                self.terminal(),
+                "Tracing, the target is single-stepped when it's continued."
+            ),
+            Ok(Some(trace)) => {
+                tprint!(
+                    self.terminal(),
+                    "Recorded {} instructions.",
+                    trace.addrs().len()
+                );
+                self.show_trace(trace);
+            }
+            Err(err) => tprint!(self.terminal(), "Failed to toggle tracing: {err}"),
+        }
+    }
+
+    /// Overlay how many times each instruction of a trace ran on the listing.
+    pub(super) fn show_trace(&mut self, trace: Trace) {
+        let hits: BTreeMap<usize, usize> =
+            trace.hits().into_iter().map(|(addr, hits)| (addr as usize, hits)).collect();
+
+        if let Some(PanelKind::Functions(functions)) = self.panes.mapping.get_mut(FUNCTIONS) {
+            functions.set_coverage(hits.keys().copied().collect());
+        }
+
+        if let Some(listing) = self.listing() {
+            listing.set_hits(hits);
+        }
+
+        self.trace = trace;
+    }
+
+    /// Show what ran while tracing so far, as the target stopped.
+    pub(super) fn show_live_trace(&mut self) {
+        let trace = self.debugger.as_ref().and_then(Session::trace).map(|trace| trace.clone());
+        if let Some(trace) = trace {
+            self.show_trace(trace);
+        }
+    }
+
+    pub fn clear_trace(&mut self) {
+        self.show_trace(Trace::default());
+    }
+
+    /// Save the trace as coverage of the binary, in which every instruction is a block.
+    pub fn save_trace(&mut self, path: &Path) {
+        let processor = match self.panes.processor.clone() {
+            Some(processor) => processor,
+            None => {
+                tprint!(self.terminal(), "No targets loaded.");
+                return;
+            }
+        };
+
+        if self.trace.is_empty() {
+            tprint!(self.terminal(), "No instructions have been traced.");
+            return;
+        }
+
+        let end = processor.sections().map(|section| section.end).max().unwrap_or_default();
+        let module = Module {
+            base: processor.image_base as u64,
+            end: end as u64,
+            path: processor.path.display().to_string(),
+        };
+
+        let coverage = self.trace.to_coverage(module, |addr| {
+            let instruction = processor.instruction_by_addr(addr as usize);
+            instruction.map_or(1, |instruction| {
+                processor.instruction_width(&instruction) as u16
+            })
+        });
+
+        match std::fs::write(path, coverage.to_bytes()) {
+            Ok(()) => tprint!(
+                self.terminal(),
+                "Saved {} instructions to {path:?}.",
+                coverage.blocks.len()
+            ),
+            Err(err) => tprint!(self.terminal(), "Failed to save trace: {err}."),
+        }
+    }
+
+    /// Show how often instructions ran according to coverage recorded elsewhere, such as by
+    /// DynamoRIO.
+    pub fn load_trace(&mut self, path: &Path) {
+        let processor = match self.panes.processor.clone() {
+            Some(processor) => processor,
+            None => {
+                tprint!(self.terminal(), "No targets loaded.");
+                return;
+            }
+        };
+
+        let coverage = match std::fs::read(path) {
+            Ok(bytes) => Coverage::parse(&bytes),
+            Err(err) => {
+                tprint!(self.terminal(), "Failed to read {path:?}: {err}.");
+                return;
+            }
+        };
+
+        let coverage = match coverage {
+            Ok(coverage) => coverage,
+            Err(err) => {
+                tprint!(self.terminal(), "Failed to load trace: {err}");
+                return;
+            }
+        };
+
+        let module = match coverage.module_of(&processor.path) {
+            Some(module) => module,
+            None => {
+                tprint!(
+                    self.terminal(),
+                    "Trace doesn't cover {:?}.",
+                    processor.path.file_name().unwrap_or_default()
+                );
+                return;
+            }
+        };
+
+        // blocks are relative to wherever the module was loaded
+        let base = processor.image_base as u64;
+        let trace = Trace::from_coverage(&coverage, module, base, |range| {
+            let range = range.start as usize..range.end as usize;
+            processor.instructions_in(range).into_iter().map(|addr| addr as u64).collect()
+        });
+
+        // functions that ran at all are the ones worth triaging
+        let covered: BTreeSet<u64> = trace.addrs().iter().copied().collect();
+        let functions = processor.index.functions().filter(|func| !func.item.intrinsic());
+        let (mut ran, mut total) = (0, 0);
+        for function in functions {
+            total += 1;
+            if let Some((start, end, _)) = processor.function_bounds(function.addr) {
+                ran += covered.range(start as u64..end as u64).next().is_some() as usize;
+            }
+        }
+
+        tprint!(
+            self.terminal(),
+            "Loaded {} instructions that ran, covering {ran} of {total} functions.",
+            trace.addrs().len()
+        );
+        self.show_trace(trace);
+    }
+}
@@ -1,13 +1,17 @@
 use egui::style::{ScrollStyle, Selection, Spacing, Visuals, WidgetVisuals, Widgets};
 use egui::{Color32, FontFamily, FontId, Rounding, Stroke, TextStyle};
 use once_cell::sync::Lazy;
-use tokenizing::colors;
 use config::CONFIG;
 
 #[derive(Debug, Clone)]
 pub struct Style {
     pub separator_width: f32,
     pub selection_color: Color32,
+    /// Background of the instruction a debugged target is stopped at.
+    pub pc_color: Color32,
+    pub breakpoint_color: Color32,
+    /// Background of instructions that ran according to a trace, more opaque the more they ran.
+    pub coverage_color: Color32,
     pub tab_rounding: Rounding,
     pub active_text_color: Color32,
     pub text_color: Color32,
@@ -16,9 +20,12 @@ pub struct Style {
 pub static STYLE: Lazy<Style> = Lazy::new(|| Style {
     separator_width: 3.0,
     selection_color: Color32::from_rgba_unmultiplied(150, 150, 150, 60),
+    pc_color: Color32::from_rgba_unmultiplied(90, 170, 90, 60),
+    breakpoint_color: Color32::from_rgba_unmultiplied(200, 60, 60, 60),
+    coverage_color: Color32::from_rgb(60, 120, 200),
     tab_rounding: Rounding::ZERO,
-    active_text_color: colors::WHITE,
-    text_color: colors::GRAYAA,
+    active_text_color: CONFIG.colors.text,
+    text_color: CONFIG.colors.text_inactive,
 });
 
 pub static EGUI: Lazy<egui::Style> = Lazy::new(|| egui::Style {
@@ -95,7 +102,7 @@ pub static EGUI: Lazy<egui::Style> = Lazy::new(|| egui::Style {
             stroke: Stroke::NONE,
         },
         menu_rounding: STYLE.tab_rounding,
-        dark_mode: true,
+        dark_mode: CONFIG.colors.dark,
         override_text_color: None,
         popup_shadow: egui::epaint::Shadow::NONE,
         window_rounding: STYLE.tab_rounding,
@@ -103,7 +110,11 @@ pub static EGUI: Lazy<egui::Style> = Lazy::new(|| egui::Style {
         panel_fill: CONFIG.colors.bg_primary,
         extreme_bg_color: CONFIG.colors.bg_primary,
         text_cursor: Stroke::new(2.0, Color32::from_rgba_unmultiplied(130, 130, 130, 200)),
-        ..Default::default()
+        // anything the theme doesn't set follows egui's style for light or dark backgrounds
+        ..match CONFIG.colors.dark {
+            true => Visuals::dark(),
+            false => Visuals::light(),
+        }
     },
     wrap: Some(true),
     explanation_tooltips: false,
@@ -97,6 +97,20 @@ mod macos {
                 false,
                 None,
             ));
+            windows.push(CheckMenuItem::with_id(
+                panes::PSEUDO_CODE,
+                "Pseudo-C",
+                true,
+                false,
+                None,
+            ));
+            windows.push(CheckMenuItem::with_id(
+                panes::BOOKMARKS,
+                "Bookmarks",
+                true,
+                false,
+                None,
+            ));
             windows.push(CheckMenuItem::with_id(
                 panes::LOGGING,
                 "Logging",
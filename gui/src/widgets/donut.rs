@@ -1,4 +1,5 @@
 use crate::common::*;
+use config::CONFIG;
 
 pub struct Donut {
     timer: Timer,
@@ -87,14 +88,14 @@ impl Display for Donut {
         // HACK: has to be done this way since egui can't center two
         // widgets at once (progress bar and donut).
         let panel = ui.max_rect();
-        let mut font = FONT;
-        font.size /= 1.5;
+        let mut font_id = font();
+        font_id.size /= 1.5;
         let rect = ui.painter().text(
             panel.center(),
             egui::Align2::CENTER_CENTER,
             self.frame(),
-            font,
-            egui::Color32::WHITE,
+            font_id,
+            CONFIG.colors.text,
         );
         ui.allocate_rect(rect, egui::Sense::hover());
     }
@@ -0,0 +1,151 @@
+//! Overview of the whole address space of a binary, shown beside the listing.
+
+use crate::style::STYLE;
+use config::CONFIG;
+use egui::{Rect, Sense, Stroke};
+use processor::Processor;
+use processor_shared::SectionKind;
+use std::ops::{Range, RangeInclusive};
+
+/// Number of stripes the function density is counted in.
+const BUCKETS: usize = 512;
+
+pub struct Minimap {
+    /// Sections that are loaded, in the order they're shown.
+    sections: Vec<(Range<usize>, SectionKind)>,
+    /// Number of bytes of all sections together, which the height of the minimap represents.
+    len: usize,
+    /// Number of functions starting in each stripe of the minimap.
+    density: Vec<usize>,
+}
+
+impl Minimap {
+    /// Width of the minimap in points.
+    pub const WIDTH: f32 = 14.0;
+
+    pub fn new(processor: &Processor) -> Self {
+        let sections: Vec<_> = processor
+            .sections()
+            .filter(|sec| !matches!(sec.kind, SectionKind::Unloaded | SectionKind::Debug))
+            .filter(|sec| sec.start < sec.end)
+            .map(|sec| (sec.start..sec.end, sec.kind))
+            .collect();
+
+        let mut minimap = Self {
+            len: sections.iter().map(|(range, _)| range.len()).sum(),
+            sections,
+            density: vec![0; BUCKETS],
+        };
+
+        for function in processor.index.functions() {
+            if let Some(offset) = minimap.offset(function.addr) {
+                minimap.density[offset * BUCKETS / minimap.len.max(1)] += 1;
+            }
+        }
+
+        minimap
+    }
+
+    /// Position of an address as if all sections followed each other without gaps.
+    fn offset(&self, addr: usize) -> Option<usize> {
+        let mut offset = 0;
+        for (range, _) in &self.sections {
+            if range.contains(&addr) {
+                return Some(offset + addr - range.start);
+            }
+
+            offset += range.len();
+        }
+
+        None
+    }
+
+    /// Address at a position, see [`Minimap::offset`].
+    fn addr(&self, mut offset: usize) -> Option<usize> {
+        for (range, _) in &self.sections {
+            if offset < range.len() {
+                return Some(range.start + offset);
+            }
+
+            offset -= range.len();
+        }
+
+        self.sections.last().map(|(range, _)| range.end - 1)
+    }
+
+    /// Vertical position of an address within the minimap.
+    fn y(&self, rect: Rect, addr: usize) -> Option<f32> {
+        let offset = self.offset(addr)?;
+        Some(rect.top() + rect.height() * offset as f32 / self.len.max(1) as f32)
+    }
+
+    /// Draw the minimap with the addresses that are visible highlighted.
+    ///
+    /// Returns the address that was clicked or dragged to.
+    pub fn show(
+        &self,
+        ui: &mut egui::Ui,
+        rect: Rect,
+        visible: RangeInclusive<usize>,
+    ) -> Option<usize> {
+        let response = ui.allocate_rect(rect, Sense::click_and_drag());
+        let painter = ui.painter_at(rect);
+
+        painter.rect_filled(rect, 0.0, CONFIG.colors.bg_secondary);
+
+        for (range, kind) in &self.sections {
+            let top = self.y(rect, range.start).unwrap_or(rect.top());
+            let bottom = top + rect.height() * range.len() as f32 / self.len.max(1) as f32;
+            let color = match kind {
+                SectionKind::Code => CONFIG.colors.asm.opcode,
+                _ => CONFIG.colors.bytes,
+            };
+
+            let band = Rect::from_x_y_ranges(rect.x_range(), top..=bottom);
+            painter.rect_filled(band, 0.0, color.linear_multiply(0.15));
+            painter.hline(rect.x_range(), top, Stroke::new(1.0, CONFIG.colors.hint));
+        }
+
+        // functions are drawn as bars from the left, longer where there are more of them
+        let most = self.density.iter().copied().max().unwrap_or(0).max(1);
+        let stripe = rect.height() / BUCKETS as f32;
+        for (idx, &count) in self.density.iter().enumerate() {
+            if count == 0 {
+                continue;
+            }
+
+            let width = rect.width() * (count as f32 / most as f32).sqrt();
+            let top = rect.top() + idx as f32 * stripe;
+            let bar = Rect::from_min_size(
+                egui::pos2(rect.left(), top),
+                egui::vec2(width, stripe.max(1.0)),
+            );
+
+            painter.rect_filled(bar, 0.0, CONFIG.colors.asm.label.linear_multiply(0.6));
+        }
+
+        let (start, end) = visible.into_inner();
+        if let (Some(top), Some(bottom)) = (self.y(rect, start), self.y(rect, end)) {
+            // keep the viewport visible in binaries that are far larger than the screen
+            let bottom = bottom.max(top + 3.0);
+            let viewport = Rect::from_x_y_ranges(rect.x_range(), top..=bottom);
+            painter.rect(
+                viewport,
+                0.0,
+                STYLE.selection_color,
+                Stroke::new(1.0, CONFIG.colors.text),
+            );
+        }
+
+        // holding the pointer still while dragging shouldn't keep reloading the listing
+        let dragged = response.dragged() && response.drag_delta().y != 0.0;
+        if !(response.clicked() || dragged) {
+            return None;
+        }
+
+        let pos = response.interact_pointer_pos()?;
+        let fraction = ((pos.y - rect.top()) / rect.height()).clamp(0.0, 1.0);
+        let offset = (fraction as f64 * self.len as f64) as usize;
+        self.addr(offset.min(self.len.saturating_sub(1)))
+    }
+}
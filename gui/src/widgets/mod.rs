@@ -1,9 +1,11 @@
 mod donut;
+mod minimap;
 mod terminal;
 mod text_edit;
 mod text_select;
 
 pub use donut::Donut;
+pub use minimap::Minimap;
 pub use terminal::Terminal;
 pub use text_edit::TextEdit;
 pub use text_select::TextSelection;
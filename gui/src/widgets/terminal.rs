@@ -1,25 +1,30 @@
 use crate::common::*;
 use crate::style::EGUI;
 use crate::widgets::TextSelection;
+use config::CONFIG;
 
 use debugvault::Index;
-use tokenizing::colors;
 
 use egui::text::LayoutJob;
 use once_cell::sync::Lazy;
 use std::path::PathBuf;
 
-static HISTORY_PATH: Lazy<PathBuf> = Lazy::new(|| {
+/// Where the command history is kept, if it can be.
+static HISTORY_PATH: Lazy<Option<PathBuf>> = Lazy::new(|| {
     let mut path = match dirs::data_dir() {
         Some(dir) => dir,
-        None => log::error!("You must have a data directory set."),
+        None => {
+            log::report!(log::Level::Warn, "No data directory is set, history won't be saved.");
+            return None;
+        }
     };
 
     path.push("bite");
 
     if !path.is_dir() {
         if let Err(err) = std::fs::create_dir(&path) {
-            log::error!("{err}");
+            log::report!(log::Level::Warn, "Failed to create '{}': {err}.", path.display());
+            return None;
         }
     }
 
@@ -27,11 +32,12 @@ static HISTORY_PATH: Lazy<PathBuf> = Lazy::new(|| {
 
     if !path.is_file() {
         if let Err(err) = std::fs::File::create(&path) {
-            log::error!("{err}");
+            log::report!(log::Level::Warn, "Failed to create '{}': {err}.", path.display());
+            return None;
         }
     }
 
-    path
+    Some(path)
 });
 
 #[derive(Default)]
@@ -122,7 +128,7 @@ impl Terminal {
                 cmds
             }
             Err(err) => {
-                log::warning!("Failed in reading command history: '{err}'.");
+                log::report!(log::Level::Warn, "Failed to read command history: {err}.");
                 vec![String::new()]
             }
         };
@@ -145,7 +151,8 @@ impl Terminal {
         self.reset_cursor
     }
 
-    fn current_line(&self) -> &str {
+    /// Command that's being typed.
+    pub fn current_line(&self) -> &str {
         &self.commands[self.command_position]
     }
 
@@ -312,6 +319,23 @@ impl Terminal {
         self.autocomplete.clear();
     }
 
+    /// Run a command as if it was typed.
+    pub fn run(&mut self, cmd: &str) {
+        self.command_position = self.commands.len() - 1;
+        self.commands[self.command_position] = cmd.to_string();
+        self.commit();
+        let _ = self.save_command_history();
+    }
+
+    /// Start typing a command, leaving the rest of it to the user.
+    pub fn prefill(&mut self, cmd: &str) {
+        self.command_position = self.commands.len() - 1;
+        self.commands[self.command_position] = cmd.to_string();
+        self.move_to_end();
+        self.autocomplete.clear();
+        self.reset_cursor = true;
+    }
+
     /// Consumes terminal commands recorded since last frame.
     pub fn take_commands(&mut self) -> &[String] {
         let ncmds = self.commands_unprocessed;
@@ -320,7 +344,11 @@ impl Terminal {
     }
 
     fn read_command_history() -> std::io::Result<Vec<String>> {
-        let data = std::fs::read_to_string(&*HISTORY_PATH)?;
+        let data = match *HISTORY_PATH {
+            Some(ref path) => std::fs::read_to_string(path)?,
+            None => return Ok(Vec::new()),
+        };
+
         Ok(data.lines().map(ToString::to_string).collect())
     }
 
@@ -340,7 +368,10 @@ impl Terminal {
             cmds += "\n";
         }
 
-        std::fs::write(&*HISTORY_PATH, cmds)
+        match *HISTORY_PATH {
+            Some(ref path) => std::fs::write(path, cmds),
+            None => Ok(()),
+        }
     }
 
     /// Process all character having been entered.
@@ -522,7 +553,7 @@ impl Terminal {
                     &s,
                     0.0,
                     egui::TextFormat {
-                        font_id: FONT,
+                        font_id: font(),
                         color,
                         ..Default::default()
                     },
@@ -534,7 +565,7 @@ impl Terminal {
             append(input, color);
 
             if let Some(suggestion) = self.autocomplete.term_suggestion() {
-                append(suggestion, colors::GRAY60);
+                append(suggestion, CONFIG.colors.hint);
             }
 
             let mut text_area = TextSelection::precomputed(&output);
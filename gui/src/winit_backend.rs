@@ -5,6 +5,7 @@ use crate::Arch;
 use crate::Window;
 
 use std::collections::HashMap;
+use std::time::Instant;
 
 use copypasta::ClipboardProvider;
 use egui::emath::{pos2, vec2};
@@ -31,6 +32,75 @@ pub struct Platform {
     // device IDs are opaque, so we have to create our own ID mapping
     device_indices: HashMap<DeviceId, u64>,
     next_device_index: u64,
+
+    kinetic: Kinetic,
+}
+
+/// Points per second below which scrolling that continues after a gesture stops.
+const MIN_KINETIC_VELOCITY: f32 = 20.0;
+
+/// Fraction of the velocity of kinetic scrolling that's left after a second.
+const KINETIC_DECAY: f32 = 0.02;
+
+/// Keeps scrolling after a touchpad gesture ends, slowing down until it stops.
+///
+/// macOS already sends these scroll events itself.
+#[derive(Debug, Default)]
+struct Kinetic {
+    /// Points per second scrolled by the gesture, or since it ended.
+    velocity: egui::Vec2,
+    /// When the last scroll event arrived or the last kinetic scroll happened.
+    last: Option<Instant>,
+    /// Whether the gesture ended and scrolling continues by itself.
+    coasting: bool,
+}
+
+impl Kinetic {
+    /// Follow the speed of a gesture that scrolled by some delta.
+    fn track(&mut self, phase: TouchPhase, delta: egui::Vec2) {
+        let now = Instant::now();
+        match phase {
+            TouchPhase::Started => {
+                self.velocity = egui::Vec2::ZERO;
+                self.coasting = false;
+            }
+            TouchPhase::Moved => {
+                let dt = self.last.map_or(0.0, |last| now.duration_since(last).as_secs_f32());
+                if dt > 0.0 {
+                    // average out the jitter of events that arrive at an uneven rate
+                    self.velocity = self.velocity * 0.5 + delta / dt * 0.5;
+                }
+            }
+            TouchPhase::Ended => self.coasting = self.velocity.length() > MIN_KINETIC_VELOCITY,
+            TouchPhase::Cancelled => self.stop(),
+        }
+
+        self.last = Some(now);
+    }
+
+    fn stop(&mut self) {
+        self.velocity = egui::Vec2::ZERO;
+        self.coasting = false;
+    }
+
+    /// Distance to scroll by since the last frame, if scrolling continues after a gesture.
+    fn step(&mut self) -> Option<egui::Vec2> {
+        if !self.coasting {
+            return None;
+        }
+
+        let now = Instant::now();
+        let dt = self.last.map_or(0.0, |last| now.duration_since(last).as_secs_f32());
+        self.last = Some(now);
+
+        let delta = self.velocity * dt;
+        self.velocity *= KINETIC_DECAY.powf(dt);
+        if self.velocity.length() < MIN_KINETIC_VELOCITY {
+            self.stop();
+        }
+
+        Some(delta)
+    }
 }
 
 impl Platform {
@@ -91,6 +161,7 @@ impl Platform {
             touch_pointer_pressed: 0,
             device_indices: HashMap::new(),
             next_device_index: 1,
+            kinetic: Kinetic::default(),
         }
     }
 
@@ -195,6 +266,9 @@ impl Platform {
                     ));
                 }
                 WindowEvent::MouseInput { state, button, .. } => {
+                    // clicking holds on to content that's still scrolling
+                    self.kinetic.stop();
+
                     // push event only if the cursor is inside the window
                     if let Some(pointer_pos) = self.pointer_pos {
                         self.raw_input.events.push(egui::Event::PointerButton {
@@ -288,14 +362,20 @@ impl Platform {
                         self.raw_input.events.push(egui::Event::PointerGone);
                     }
                 }
-                WindowEvent::MouseWheel { delta, .. } => {
+                WindowEvent::MouseWheel { delta, phase, .. } => {
                     let delta = match *delta {
                         winit::event::MouseScrollDelta::LineDelta(x, y) => {
-                            let line_height = 50.0;
-                            vec2(x, y) * line_height
+                            // a notch of a mouse wheel scrolls a few lines of the listing
+                            self.kinetic.stop();
+                            let line_height = crate::common::font().size * 1.25;
+                            vec2(x, y) * 3.0 * line_height
                         }
                         winit::event::MouseScrollDelta::PixelDelta(delta) => {
-                            vec2(delta.x as f32, delta.y as f32)
+                            let delta = vec2(delta.x as f32, delta.y as f32) / self.scale_factor;
+                            if cfg!(not(target_os = "macos")) {
+                                self.kinetic.track(*phase, delta);
+                            }
+                            delta
                         }
                     };
 
@@ -335,6 +415,10 @@ impl Platform {
 
     /// Starts a new frame by providing a new `Ui` instance to write into.
     pub fn begin_frame(&mut self) {
+        if let Some(delta) = self.kinetic.step() {
+            self.raw_input.events.push(egui::Event::Scroll(delta));
+        }
+
         self.context.begin_frame(self.raw_input.take());
     }
 
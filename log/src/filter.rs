@@ -0,0 +1,166 @@
+//! Deciding which records are kept, based on their level and the module they're logged from.
+
+use crate::Color;
+use std::fmt;
+use std::str::FromStr;
+
+/// Environment variable the filter is read from.
+pub const ENV_VAR: &str = "BITE_LOG";
+
+/// How serious a record is, from least to most.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    Trace,
+    Debug,
+    #[default]
+    Info,
+    Warn,
+    Error,
+}
+
+impl Level {
+    pub const ALL: [Level; 5] = [
+        Level::Trace,
+        Level::Debug,
+        Level::Info,
+        Level::Warn,
+        Level::Error,
+    ];
+
+    /// Level of a record that was logged without one, judged by its colors.
+    pub fn guess(pieces: &[(String, Color)]) -> Self {
+        let colors = || pieces.iter().map(|(_, color)| color);
+        if colors().any(|color| matches!(color, Color::Red)) {
+            Level::Error
+        } else if colors().any(|color| matches!(color, Color::Yellow)) {
+            Level::Warn
+        } else {
+            Level::Info
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Level::Trace => "trace",
+            Level::Debug => "debug",
+            Level::Info => "info",
+            Level::Warn => "warn",
+            Level::Error => "error",
+        })
+    }
+}
+
+impl FromStr for Level {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match &s.trim().to_lowercase()[..] {
+            "trace" => Ok(Level::Trace),
+            "debug" => Ok(Level::Debug),
+            "info" => Ok(Level::Info),
+            "warn" | "warning" => Ok(Level::Warn),
+            "error" => Ok(Level::Error),
+            _ => Err(Error::UnknownLevel(s.trim().to_string())),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    UnknownLevel(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownLevel(level) => f.write_fmt(format_args!("Unknown log level '{level}'.")),
+        }
+    }
+}
+
+/// Least serious level of records that are kept, in general and for specific modules.
+///
+/// Written as a comma separated list of levels, which apply to every module, and `module=level`
+/// pairs, e.g. `warn,processor=debug,gui::panes=trace`. A module also covers its submodules,
+/// the longest module that matches wins.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Filter {
+    default: Level,
+    modules: Vec<(String, Level)>,
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        Self {
+            default: Level::Info,
+            modules: Vec::new(),
+        }
+    }
+}
+
+impl Filter {
+    /// Filter given by the environment, or the default one if it's not set or invalid.
+    pub fn from_env() -> Self {
+        let raw = match std::env::var(ENV_VAR) {
+            Ok(raw) => raw,
+            Err(..) => return Self::default(),
+        };
+
+        match raw.parse() {
+            Ok(filter) => filter,
+            Err(err) => {
+                eprintln!("Ignoring {ENV_VAR}: {err}");
+                Self::default()
+            }
+        }
+    }
+
+    /// Least serious level that's kept for a module.
+    pub fn level(&self, module: &str) -> Level {
+        let matches = |name: &str| {
+            module == name || module.strip_prefix(name).is_some_and(|rest| rest.starts_with("::"))
+        };
+
+        self.modules
+            .iter()
+            .filter(|(name, _)| matches(name))
+            .max_by_key(|(name, _)| name.len())
+            .map_or(self.default, |(_, level)| *level)
+    }
+
+    pub fn enabled(&self, level: Level, module: &str) -> bool {
+        level >= self.level(module)
+    }
+}
+
+impl FromStr for Filter {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut filter = Self::default();
+
+        for directive in s.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            match directive.split_once('=') {
+                Some((module, level)) => {
+                    filter.modules.push((module.trim().to_string(), level.parse()?));
+                }
+                None => filter.default = directive.parse()?,
+            }
+        }
+
+        Ok(filter)
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_fmt(format_args!("{}", self.default))?;
+        for (module, level) in self.modules.iter() {
+            f.write_fmt(format_args!(",{module}={level}"))?;
+        }
+
+        Ok(())
+    }
+}
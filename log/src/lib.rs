@@ -1,19 +1,57 @@
+//! Logging of records to a ring buffer shown in the log tab, and dialogs for errors and warnings
+//! that need the user's attention.
+//!
+//! Every record has a [`Level`] and the module it was logged from. Which records are kept is
+//! decided by a [`Filter`], read from the `BITE_LOG` environment variable the first time anything
+//! is logged, e.g. `BITE_LOG=debug` or `BITE_LOG=warn,processor=debug`.
+
+mod filter;
+mod profile;
 mod progress;
+mod report;
 
+use crossbeam_queue::ArrayQueue;
 use egui::text::LayoutJob;
-pub use progress::ProgressBar;
+pub use filter::{Filter, Level, ENV_VAR};
+pub use profile::{Profile, Profiler, Span};
+pub use progress::{Progress, Task};
+pub use report::{push_report, take_reports, Report};
 pub use rfd::{MessageDialog, MessageLevel};
-use std::sync::RwLock;
+use std::collections::VecDeque;
+use std::sync::{Mutex, MutexGuard, OnceLock, RwLock};
+use std::time::{Duration, Instant};
 
-pub static PROGRESS: ProgressBar = ProgressBar::new();
+/// Tasks that are running, shown while a binary is loading.
+pub static PROGRESS: Progress = Progress::new();
 
-/// Time a given expression.
+/// Stages of loading a binary that were timed, see [`time!`].
+pub static PROFILER: Profiler = Profiler::new();
+
+/// Time a given expression, logging how long it took.
+///
+/// Given a name, the duration is also recorded as a stage in the [`PROFILER`].
 #[macro_export]
 macro_rules! time {
+    ($name:literal, $e:expr) => {{
+        let now = std::time::Instant::now();
+        let result = $e;
+        let elapsed = now.elapsed();
+        $crate::PROFILER.record($name, elapsed);
+        $crate::record!(
+            $crate::Level::Debug;
+            w "[timing] ",
+            w $name,
+            w " took ",
+            y format!("{elapsed:?}"),
+            w "."
+        );
+        result
+    }};
     ($e:expr) => {{
         let now = std::time::Instant::now();
         let result = $e;
-        $crate::complex!(
+        $crate::record!(
+            $crate::Level::Debug;
             w "[timing] ",
             w std::file!(),
             w ":",
@@ -51,6 +89,9 @@ macro_rules! exit {
     }};
 }
 
+/// Show an error dialog and exit, which is only meant for problems bite can't recover from.
+///
+/// Anything else is reported with [`report!`].
 #[macro_export]
 macro_rules! error {
     () => {{
@@ -118,244 +159,251 @@ pub enum Color {
     Gray,
 }
 
-#[macro_export]
-macro_rules! trace {
-    () => {};
-
-    ($($arg:tt)*) => {{
-        $crate::LOGGER.write().unwrap().append(
-            format!($($arg)*) + "\n",
-            $crate::Color::White,
-        );
-    }};
+impl Color {
+    fn text_format(&self) -> egui::TextFormat {
+        egui::TextFormat {
+            font_id: egui::FontId {
+                size: 14.0,
+                family: egui::FontFamily::Monospace,
+            },
+            color: match self {
+                Color::Green => egui::Color32::LIGHT_GREEN,
+                Color::Red => egui::Color32::RED,
+                Color::Blue => egui::Color32::from_rgb(0x3e, 0xbc, 0xe6),
+                Color::Yellow => egui::Color32::GOLD,
+                Color::White => egui::Color32::WHITE,
+                Color::Gray => egui::Color32::GRAY,
+            },
+            ..Default::default()
+        }
+    }
 }
 
 /// Internal macro, don't use.
 #[macro_export]
-macro_rules! complex_recurse {
-    (r $arg:expr $(,)?) => {
-        $crate::LOGGER.write().unwrap().append(
-            $arg,
-            $crate::Color::Red,
-        );
+macro_rules! color {
+    (r) => {
+        $crate::Color::Red
     };
-
-    (r $arg:expr, $($args:tt)+) => {{
-        $crate::complex_recurse!(r $arg,);
-        $crate::complex_recurse!($($args)+);
-    }};
-
-    (g $arg:expr $(,)?) => {
-        $crate::LOGGER.write().unwrap().append(
-            $arg,
-            $crate::Color::Green,
-        );
+    (g) => {
+        $crate::Color::Green
     };
-
-    (g $arg:expr, $($args:tt)+) => {{
-        $crate::complex_recurse!(g $arg,);
-        $crate::complex_recurse!($($args)+);
-    }};
-
-    (b $arg:expr $(,)?) => {
-        $crate::LOGGER.write().unwrap().append(
-            $arg,
-            $crate::Color::Blue,
-        );
+    (b) => {
+        $crate::Color::Blue
     };
-
-    (b $arg:expr, $($args:tt)+) => {{
-        $crate::complex_recurse!(b $arg,);
-        $crate::complex_recurse!($($args)+);
-    }};
-
-    (y $arg:expr $(,)?) => {
-        $crate::LOGGER.write().unwrap().append(
-            $arg,
-            $crate::Color::Yellow,
-        );
+    (y) => {
+        $crate::Color::Yellow
     };
-
-    (y $arg:expr, $($args:tt)+) => {{
-        $crate::complex_recurse!(y $arg,);
-        $crate::complex_recurse!($($args)+);
-    }};
-
-    (w $arg:expr $(,)?) => {
-        $crate::LOGGER.write().unwrap().append(
-            $arg,
-            $crate::Color::White,
-        );
+    (w) => {
+        $crate::Color::White
     };
+}
 
-    (w $arg:expr, $($args:tt)+) => {{
-        $crate::complex_recurse!(w $arg,);
-        $crate::complex_recurse!($($args)+);
+/// Log a record at a level, made up of colored pieces like [`complex!`].
+#[macro_export]
+macro_rules! record {
+    ($level:expr; $($color:ident $arg:expr),+ $(,)?) => {{
+        let pieces = vec![$(($crate::piece($arg), $crate::color!($color)),)+];
+        $crate::LOGGER.record($level, std::module_path!(), pieces);
     }};
 }
 
 /// Multi-color logging.
+///
+/// The level of the record is guessed from its colors, red being an error and yellow a warning.
 #[macro_export]
 macro_rules! complex {
-    () => {
-        $crate::LOGGER.write().unwrap().append(
-            "\n".into(),
-            $crate::Color::White,
-        );
-    };
-
-    (r $arg:expr $(,)?) => {
-        $crate::LOGGER.write().unwrap().append(
-            $arg,
-            $crate::Color::Red,
-        );
-    };
+    ($($color:ident $arg:expr),+ $(,)?) => {{
+        let pieces = vec![$(($crate::piece($arg), $crate::color!($color)),)+];
+        let level = $crate::Level::guess(&pieces);
+        $crate::LOGGER.record(level, std::module_path!(), pieces);
+    }};
+}
 
-    (r $arg:expr, $($args:tt)+) => {{
-        $crate::complex!(r $arg,);
-        $crate::complex_recurse!($($args)+);
+/// Log a formatted message at a level.
+#[macro_export]
+macro_rules! log {
+    ($level:expr, $($arg:tt)*) => {{
+        let color = match $level {
+            $crate::Level::Error => $crate::Color::Red,
+            $crate::Level::Warn => $crate::Color::Yellow,
+            $crate::Level::Info => $crate::Color::White,
+            $crate::Level::Debug | $crate::Level::Trace => $crate::Color::Gray,
+        };
 
-        $crate::LOGGER.write().unwrap().append(
-            "\n",
-            $crate::Color::White,
-        );
+        let pieces = vec![(format!($($arg)*), color)];
+        $crate::LOGGER.record($level, std::module_path!(), pieces);
     }};
+}
 
-    (g $arg:expr $(,)?) => {
-        $crate::LOGGER.write().unwrap().append(
-            $arg,
-            $crate::Color::Green,
-        );
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        $crate::log!($crate::Level::Trace, $($arg)*)
     };
+}
 
-    (g $arg:expr, $($args:tt)+) => {{
-        $crate::complex!(g $arg,);
-        $crate::complex_recurse!($($args)+);
-
-        $crate::LOGGER.write().unwrap().append(
-            "\n",
-            $crate::Color::Green,
-        );
-    }};
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        $crate::log!($crate::Level::Debug, $($arg)*)
+    };
+}
 
-    (b $arg:expr $(,)?) => {
-        $crate::LOGGER.write().unwrap().append(
-            $arg,
-            $crate::Color::Blue,
-        );
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)*) => {
+        $crate::log!($crate::Level::Info, $($arg)*)
     };
+}
 
-    (b $arg:expr, $($args:tt)+) => {{
-        $crate::complex!(b $arg,);
-        $crate::complex_recurse!($($args)+);
+/// Log a warning, unlike [`warning!`] this doesn't interrupt the user with a dialog.
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)*) => {
+        $crate::log!($crate::Level::Warn, $($arg)*)
+    };
+}
 
-        $crate::LOGGER.write().unwrap().append(
-            "\n",
-            $crate::Color::White,
-        );
+/// Report a problem to the user without interrupting them, shown as a toast that can be dismissed.
+///
+/// The report is logged as well, e.g. `report!(Level::Warn, "Failed to parse config: {err}.")`.
+#[macro_export]
+macro_rules! report {
+    ($level:expr, $($arg:tt)*) => {{
+        let message = format!($($arg)*);
+        eprintln!("{message}");
+        $crate::log!($level, "{message}");
+        $crate::push_report($crate::Report { level: $level, message });
     }};
+}
 
-    (y $arg:expr $(,)?) => {
-        $crate::LOGGER.write().unwrap().append(
-            $arg,
-            $crate::Color::Yellow,
-        );
-    };
+/// Internal function, don't use.
+pub fn piece(text: impl Into<String>) -> String {
+    text.into()
+}
 
-    (y $arg:expr, $($args:tt)+) => {{
-        $crate::complex!(y $arg,);
-        $crate::complex_recurse!($($args)+);
+/// When the first record was logged, which the time of every record is relative to.
+static START: OnceLock<Instant> = OnceLock::new();
+
+/// Single message of the log.
+pub struct Record {
+    pub level: Level,
+    /// Path of the module the record was logged from.
+    pub module: &'static str,
+    /// Time since the first record was logged.
+    pub time: Duration,
+    pieces: Vec<(String, Color)>,
+}
 
-        $crate::LOGGER.write().unwrap().append(
-            "\n",
-            $crate::Color::White,
-        );
-    }};
+impl Record {
+    pub fn text(&self) -> String {
+        self.pieces.iter().map(|(text, _)| &text[..]).collect()
+    }
 
-    (w $arg:expr $(,)?) => {
-        $crate::LOGGER.write().unwrap().append(
-            $arg,
-            $crate::Color::White,
-        );
-    };
+    /// Append the record to a layout, prefixed by its time and followed by a newline.
+    pub fn append_to(&self, layout: &mut LayoutJob) {
+        let time = format!("{:>9.3} ", self.time.as_secs_f64());
+        layout.append(&time, 0.0, Color::Gray.text_format());
 
-    (w $arg:expr, $($args:tt)+) => {{
-        $crate::complex!(w $arg,);
-        $crate::complex_recurse!($($args)+);
+        for (text, color) in self.pieces.iter() {
+            layout.append(text, 0.0, color.text_format());
+        }
 
-        $crate::LOGGER.write().unwrap().append(
-            "\n",
-            $crate::Color::White,
-        );
-    }};
+        layout.append("\n", 0.0, Color::White.text_format());
+    }
 }
 
-pub static LOGGER: RwLock<Logger<1000>> = RwLock::new(Logger::new());
-
-type Segment = (String, Color);
+pub static LOGGER: Logger<1000> = Logger::new();
 
+/// Log of the last `N` records that passed the filter.
+///
+/// Records are pushed onto a lock-free queue, so logging never waits on whoever reads the log.
+/// They're only moved into the history once it's read, which drops the oldest records when the
+/// queue is full instead of blocking.
 pub struct Logger<const N: usize> {
-    segments: [Segment; N],
-    head: usize,
-    len: usize,
+    /// Records that were logged since the history was last read.
+    queue: OnceLock<ArrayQueue<Record>>,
+    /// Records that were read, only ever locked by readers.
+    history: Mutex<VecDeque<Record>>,
+    /// Filter records are checked against, read from the environment once something is logged.
+    filter: RwLock<Option<Filter>>,
 }
 
 impl<const N: usize> Logger<N> {
     const fn new() -> Self {
-        const EMPTY_SEGMENT: Segment = (String::new(), Color::White);
-
         Self {
-            segments: [EMPTY_SEGMENT; N],
-            head: 0,
-            len: 0,
+            queue: OnceLock::new(),
+            history: Mutex::new(VecDeque::new()),
+            filter: RwLock::new(None),
         }
     }
 
-    pub fn append(&mut self, line: impl Into<String>, color: Color) {
-        self.segments[self.head] = (line.into(), color);
-        self.head = (self.head + 1) % N;
-        self.len += 1;
+    fn queue(&self) -> &ArrayQueue<Record> {
+        self.queue.get_or_init(|| ArrayQueue::new(N))
     }
 
-    pub fn clear(&mut self) {
-        self.len = 0;
-        self.head = 0;
+    pub fn filter(&self) -> Filter {
+        if let Some(ref filter) = *self.filter.read().unwrap() {
+            return filter.clone();
+        }
+
+        self.filter.write().unwrap().get_or_insert_with(Filter::from_env).clone()
     }
 
-    fn segments(&self) -> impl Iterator<Item = &Segment> {
-        let (a, b) = if self.len < N {
-            (Default::default(), &self.segments[..self.len])
-        } else {
-            // wrapped around, so we need to return two slices
-            self.segments.split_at(self.head)
-        };
+    pub fn set_filter(&self, filter: Filter) {
+        *self.filter.write().unwrap() = Some(filter);
+    }
+
+    fn enabled(&self, level: Level, module: &str) -> bool {
+        if let Some(ref filter) = *self.filter.read().unwrap() {
+            return filter.enabled(level, module);
+        }
+
+        self.filter.write().unwrap().get_or_insert_with(Filter::from_env).enabled(level, module)
+    }
+
+    pub fn record(&self, level: Level, module: &'static str, pieces: Vec<(String, Color)>) {
+        if !self.enabled(level, module) {
+            return;
+        }
+
+        let start = START.get_or_init(Instant::now);
+        self.queue().force_push(Record {
+            level,
+            module,
+            time: start.elapsed(),
+            pieces,
+        });
+    }
+
+    pub fn clear(&self) {
+        while self.queue().pop().is_some() {}
+        self.history.lock().unwrap().clear();
+    }
+
+    /// Records from oldest to newest, including the ones logged since they were last read.
+    ///
+    /// Nothing can be logged to the history while it's held on to, but it's still queued.
+    pub fn records(&self) -> MutexGuard<'_, VecDeque<Record>> {
+        let mut history = self.history.lock().unwrap();
+
+        while let Some(record) = self.queue().pop() {
+            if history.len() == N {
+                history.pop_front();
+            }
+
+            history.push_back(record);
+        }
 
-        b.iter().chain(a)
+        history
     }
 
     pub fn format(&self) -> LayoutJob {
         let mut layout = LayoutJob::default();
 
-        for (line, color) in self.segments() {
-            layout.append(
-                line,
-                0.0,
-                egui::TextFormat {
-                    font_id: egui::FontId {
-                        size: 14.0,
-                        family: egui::FontFamily::Monospace,
-                    },
-                    color: match color {
-                        Color::Green => egui::Color32::LIGHT_GREEN,
-                        Color::Red => egui::Color32::RED,
-                        Color::Blue => egui::Color32::from_rgb(0x3e, 0xbc, 0xe6),
-                        Color::Yellow => egui::Color32::GOLD,
-                        Color::White => egui::Color32::WHITE,
-                        Color::Gray => egui::Color32::GRAY,
-                    },
-                    ..Default::default()
-                },
-            );
+        for record in self.records().iter() {
+            record.append_to(&mut layout);
         }
 
         layout
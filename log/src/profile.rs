@@ -0,0 +1,97 @@
+//! Durations of the stages of loading a binary, so slow ones can be pointed out in reports.
+
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Time spent in a single stage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    pub name: &'static str,
+    pub duration: Duration,
+}
+
+/// Stages that were timed while loading a binary, in the order they finished.
+///
+/// Stages can be nested in each other, e.g. demangling happens while parsing symbols.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Profile {
+    pub spans: Vec<Span>,
+    /// Time it took to load the binary, start to finish.
+    pub total: Duration,
+}
+
+impl Profile {
+    /// Table of the stages with their duration and share of the total.
+    pub fn table(&self) -> String {
+        let mut table = String::new();
+
+        for span in self.spans.iter().chain(std::iter::once(&Span {
+            name: "Total",
+            duration: self.total,
+        })) {
+            let share = span.duration.as_secs_f64() / self.total.as_secs_f64().max(f64::EPSILON);
+            let _ = writeln!(
+                table,
+                "{:<20} {:>10.1}ms {:>6.1}%",
+                span.name,
+                span.duration.as_secs_f64() * 1000.0,
+                share * 100.0
+            );
+        }
+
+        table
+    }
+
+    /// Profile as JSON, with durations in milliseconds.
+    pub fn to_json(&self) -> String {
+        let spans: Vec<String> = self
+            .spans
+            .iter()
+            .map(|span| {
+                format!(
+                    "    {{ \"name\": {:?}, \"ms\": {:.3} }}",
+                    span.name,
+                    span.duration.as_secs_f64() * 1000.0
+                )
+            })
+            .collect();
+
+        format!(
+            "{{\n  \"total_ms\": {:.3},\n  \"stages\": [\n{}\n  ]\n}}\n",
+            self.total.as_secs_f64() * 1000.0,
+            spans.join(",\n")
+        )
+    }
+}
+
+/// Collects the stages that are timed, until they're taken as a [`Profile`].
+pub struct Profiler {
+    spans: Mutex<Vec<Span>>,
+}
+
+impl Profiler {
+    pub const fn new() -> Self {
+        Self {
+            spans: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn record(&self, name: &'static str, duration: Duration) {
+        self.spans.lock().unwrap().push(Span { name, duration });
+    }
+
+    /// Take the stages that were timed since this was last called.
+    pub fn take(&self, total: Duration) -> Profile {
+        Profile {
+            spans: std::mem::take(&mut *self.spans.lock().unwrap()),
+            total,
+        }
+    }
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,91 +1,203 @@
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::RwLock;
+//! Progress of long running tasks, which can be nested and cancelled.
+//!
+//! A task that's started while another one is running on the same thread is shown nested below
+//! it, so deeper code doesn't have to know which stage it's running in.
+
 use egui::Spinner;
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock, Weak};
+
+/// Size of a single bar.
+const BAR_SIZE: egui::Vec2 = egui::vec2(300.0, 6.0);
+
+/// Indentation of every level of nesting.
+const INDENT: f32 = 16.0;
+
+const BG_COLOR: egui::Color32 = egui::Color32::from_gray(66);
+const FG_COLOR: egui::Color32 = egui::Color32::from_rgb(0x34, 0x73, 0xcf);
+
+thread_local! {
+    /// Tasks that were started on this thread and are still running, innermost last.
+    static RUNNING: RefCell<Vec<Weak<State>>> = const { RefCell::new(Vec::new()) };
+}
 
-pub struct ProgressBar {
-    desc: RwLock<&'static str>,
+#[derive(Debug)]
+struct State {
+    name: &'static str,
     steps_done: AtomicUsize,
     step_count: AtomicUsize,
-    size: egui::Vec2,
-    bg_col: egui::Color32,
-    fg_col: egui::Color32,
+    cancelled: AtomicBool,
+    parent: Option<Arc<State>>,
 }
 
-impl ProgressBar {
-    pub const fn new() -> Self {
-        Self {
-            desc: RwLock::new("???"),
-            steps_done: AtomicUsize::new(0),
-            step_count: AtomicUsize::new(0),
-            size: egui::vec2(300.0, 18.0),
-            bg_col: egui::Color32::from_gray(66),
-            fg_col: egui::Color32::from_rgb(0x34, 0x73, 0xcf),
-        }
+impl State {
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+            || self.parent.as_ref().is_some_and(|parent| parent.is_cancelled())
     }
 
-    pub fn unset(&self) {
-        *self.desc.write().unwrap() = "???";
+    fn has_parent(&self, parent: &Arc<State>) -> bool {
+        self.parent.as_ref().is_some_and(|p| Arc::ptr_eq(p, parent))
     }
+}
 
-    pub fn set(&self, desc: &'static str, step_count: usize) {
-        // this can't be a valid progress bar
-        if step_count == 0 {
-            return;
-        }
-
-        *self.desc.write().unwrap() = desc;
-        self.step_count.store(step_count, Ordering::SeqCst);
-        self.steps_done.store(0, Ordering::SeqCst);
-    }
+/// Handle to a running task, which is finished once it's dropped.
+#[derive(Debug)]
+pub struct Task {
+    state: Arc<State>,
+    /// Tasks the task is registered with.
+    progress: &'static Progress,
+}
 
+impl Task {
     pub fn step(&self) {
-        self.steps_done.fetch_add(1, Ordering::Relaxed);
+        self.step_n(1);
     }
 
     pub fn step_n(&self, n: usize) {
-        self.steps_done.fetch_add(n, Ordering::Relaxed);
+        self.state.steps_done.fetch_add(n, Ordering::Relaxed);
     }
 
-    pub fn show(&self, ui: &mut egui::Ui) {
-        let desc = self.desc.read().unwrap();
-        let steps_done = self.steps_done.load(Ordering::Relaxed);
-        let step_count = self.step_count.load(Ordering::Relaxed);
+    pub fn set_steps_done(&self, steps_done: usize) {
+        self.state.steps_done.store(steps_done, Ordering::Relaxed);
+    }
+
+    /// Change the number of steps, for when it's only known after the task started.
+    pub fn set_step_count(&self, step_count: usize) {
+        self.state.step_count.store(step_count, Ordering::Relaxed);
+    }
+
+    /// Ask the task and the tasks nested in it to stop.
+    pub fn cancel(&self) {
+        self.state.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether the task or any task it's nested in got cancelled, which long running loops
+    /// should check to stop early.
+    pub fn is_cancelled(&self) -> bool {
+        self.state.is_cancelled()
+    }
+}
 
-        let progress = steps_done as f64 / step_count as f64;
-        let progress = progress.clamp(0.0, 1.0) as f32;
+impl Drop for Task {
+    fn drop(&mut self) {
+        self.progress.tasks.write().unwrap().retain(|state| !Arc::ptr_eq(state, &self.state));
 
-        let rect = ui.allocate_exact_size(self.size, egui::Sense::hover()).0;
+        // a task might be dropped on another thread than it was started on, in which case it's
+        // pruned from the thread it was started on once that starts another task
+        let _ = RUNNING.try_with(|running| {
+            running
+                .borrow_mut()
+                .retain(|state| state.upgrade().is_some_and(|s| !Arc::ptr_eq(&s, &self.state)));
+        });
+    }
+}
 
-        // Not yet set so don't display text.
-        if *desc == "???" {
-            return;
+/// Every task that's running, across all threads.
+#[derive(Debug)]
+pub struct Progress {
+    tasks: RwLock<Vec<Arc<State>>>,
+}
+
+impl Progress {
+    pub const fn new() -> Self {
+        Self {
+            tasks: RwLock::new(Vec::new()),
         }
+    }
+
+    /// Start a task, nested in the innermost task that's running on this thread.
+    pub fn start(&'static self, name: &'static str, step_count: usize) -> Task {
+        let parent = RUNNING.with(|running| {
+            let mut running = running.borrow_mut();
+            running.retain(|state| state.strong_count() > 0);
+            running.last().and_then(Weak::upgrade)
+        });
+
+        let state = Arc::new(State {
+            name,
+            steps_done: AtomicUsize::new(0),
+            step_count: AtomicUsize::new(step_count),
+            cancelled: AtomicBool::new(false),
+            parent,
+        });
 
-        let (top_rect, bot_rect) = rect.split_top_bottom_at_fraction(0.5);
-        let (bar_rect, spinner_rect) = top_rect.split_left_right_at_fraction(0.95);
-        let (l, r) = bar_rect.split_left_right_at_fraction(progress);
+        RUNNING.with(|running| running.borrow_mut().push(Arc::downgrade(&state)));
+        self.tasks.write().unwrap().push(Arc::clone(&state));
+        Task {
+            state,
+            progress: self,
+        }
+    }
 
-        let painter = ui.painter();
+    pub fn is_empty(&self) -> bool {
+        self.tasks.read().unwrap().is_empty()
+    }
 
-        // Draw background bar.
-        painter.rect_filled(r, 0.0, self.bg_col);
+    /// Draw a bar for every task, with nested tasks below the one they're nested in.
+    ///
+    /// Tasks that aren't nested can be cancelled, which cancels the tasks nested in them too.
+    pub fn show(&self, ui: &mut egui::Ui) {
+        let tasks = self.tasks.read().unwrap();
 
-        // Draw filled bar.
-        painter.rect_filled(l, 0.0, self.fg_col);
+        // tasks whose parent already finished are shown as if they weren't nested
+        let is_root = |state: &Arc<State>| match state.parent {
+            Some(ref parent) => !tasks.iter().any(|task| Arc::ptr_eq(task, parent)),
+            None => true,
+        };
 
-        // Draw spinner.
-        Spinner::new().color(self.fg_col).paint_at(ui, spinner_rect);
+        for root in tasks.iter().filter(|state| is_root(state)) {
+            Self::show_task(ui, &tasks, root, 0);
+        }
+    }
 
-        // Draw centered text.
-        ui.allocate_ui_at_rect(bot_rect, |ui| {
-            ui.with_layout(
-                egui::Layout::centered_and_justified(egui::Direction::LeftToRight),
-                |ui| {
-                    let steps_done = steps_done.clamp(0, step_count);
-                    let status = format!("{desc} {steps_done}/{step_count}");
-                    ui.label(egui::RichText::new(status).size(top_rect.height() * 1.3))
-                },
-            );
+    fn show_task(ui: &mut egui::Ui, tasks: &[Arc<State>], state: &Arc<State>, depth: usize) {
+        let steps_done = state.steps_done.load(Ordering::Relaxed);
+        let step_count = state.step_count.load(Ordering::Relaxed);
+        let progress = match step_count {
+            0 => 0.0,
+            _ => (steps_done as f32 / step_count as f32).clamp(0.0, 1.0),
+        };
+
+        ui.horizontal(|ui| {
+            ui.add_space(depth as f32 * INDENT);
+            ui.vertical(|ui| {
+                ui.horizontal(|ui| {
+                    let status = format!(
+                        "{} {}/{step_count} ({:.0}%)",
+                        state.name,
+                        steps_done.min(step_count),
+                        progress * 100.0
+                    );
+                    ui.label(status);
+
+                    if depth == 0 {
+                        ui.add(Spinner::new().color(FG_COLOR));
+
+                        let cancelled = state.cancelled.load(Ordering::Relaxed);
+                        let button = egui::Button::new("Cancel").small();
+                        if ui.add_enabled(!cancelled, button).clicked() {
+                            state.cancelled.store(true, Ordering::Relaxed);
+                        }
+                    }
+                });
+
+                let rect = ui.allocate_exact_size(BAR_SIZE, egui::Sense::hover()).0;
+                let (done, left) = rect.split_left_right_at_fraction(progress);
+                ui.painter().rect_filled(left, 0.0, BG_COLOR);
+                ui.painter().rect_filled(done, 0.0, FG_COLOR);
+            });
         });
+
+        for child in tasks.iter().filter(|child| child.has_parent(state)) {
+            Self::show_task(ui, tasks, child, depth + 1);
+        }
+    }
+}
+
+impl Default for Progress {
+    fn default() -> Self {
+        Self::new()
     }
 }
@@ -0,0 +1,27 @@
+//! Problems that need the user's attention but don't stop bite from running.
+//!
+//! Reports are queued here until the GUI takes them to show as toasts, so they can be made from
+//! any thread, even before the window is open.
+
+use crate::Level;
+use std::sync::Mutex;
+
+/// Problem that's reported to the user.
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub level: Level,
+    pub message: String,
+}
+
+/// Reports that haven't been taken to be shown yet.
+static REPORTS: Mutex<Vec<Report>> = Mutex::new(Vec::new());
+
+/// Internal function, use [`report!`](crate::report) instead.
+pub fn push_report(report: Report) {
+    REPORTS.lock().unwrap().push(report);
+}
+
+/// Reports made since this was last called, from oldest to newest.
+pub fn take_reports() -> Vec<Report> {
+    std::mem::take(&mut *REPORTS.lock().unwrap())
+}
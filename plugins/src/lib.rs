@@ -0,0 +1,240 @@
+//! Discovery of plugins and negotiation of what they're allowed to provide.
+//!
+//! Every plugin lives in its own folder inside the `plugins` folder of bite's data directory and
+//! describes itself with a `plugin.yaml` manifest:
+//!
+//! ```yaml
+//! name: go-loader
+//! version: 0.2.1
+//! api_version: 1
+//! provides:
+//!   loaders: [gopclntab]
+//!   decoders: []
+//!   passes: [go-strings]
+//!   scripts: [rename.rhai]
+//! ```
+//!
+//! Plugins that require a different [`API_VERSION`] than the one bite implements are rejected
+//! instead of being loaded.
+
+use serde::Deserialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Version of the plugin API, bumped on breaking changes.
+pub const API_VERSION: u32 = 1;
+
+/// File name of the manifest inside a plugin's folder.
+pub const MANIFEST: &str = "plugin.yaml";
+
+#[derive(Debug)]
+pub enum Error {
+    IO(std::io::Error),
+    Deserialize(serde_yaml::Error),
+    MissingDataDir,
+    /// The plugin was written for another version of the API.
+    IncompatibleApi(u32),
+    /// The name of the plugin is already used by another plugin.
+    Duplicate(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IO(err) => f.write_fmt(format_args!("Failed to read manifest: '{err}'.")),
+            Self::Deserialize(err) => f.write_fmt(format_args!("Invalid manifest: '{err}'.")),
+            Self::MissingDataDir => f.write_str("You must have a data directory set."),
+            Self::IncompatibleApi(version) => f.write_fmt(format_args!(
+                "Requires API version {version}, but only version {API_VERSION} is supported."
+            )),
+            Self::Duplicate(name) => {
+                f.write_fmt(format_args!("A plugin named '{name}' is already loaded."))
+            }
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::IO(err)
+    }
+}
+
+impl From<serde_yaml::Error> for Error {
+    fn from(err: serde_yaml::Error) -> Self {
+        Error::Deserialize(err)
+    }
+}
+
+/// Description of a plugin.
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct Manifest {
+    pub name: String,
+
+    /// Version of the plugin itself.
+    pub version: String,
+
+    /// Version of the plugin API the plugin was written for.
+    pub api_version: u32,
+
+    /// What the plugin adds to bite.
+    #[serde(default)]
+    pub provides: Capabilities,
+}
+
+/// Extension points a plugin provides implementations for.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+pub struct Capabilities {
+    /// Binary formats that can be loaded.
+    #[serde(default)]
+    pub loaders: Vec<String>,
+
+    /// Instruction sets that can be decoded.
+    #[serde(default)]
+    pub decoders: Vec<String>,
+
+    /// Analysis passes run after a binary is loaded.
+    #[serde(default)]
+    pub passes: Vec<String>,
+
+    /// Scripts run when bite starts, relative to the plugin's folder.
+    #[serde(default)]
+    pub scripts: Vec<String>,
+}
+
+impl Manifest {
+    pub fn parse(raw: &str) -> Result<Self, Error> {
+        let this: Self = serde_yaml::from_str(raw)?;
+
+        if this.api_version != API_VERSION {
+            return Err(Error::IncompatibleApi(this.api_version));
+        }
+
+        Ok(this)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+}
+
+#[derive(Debug)]
+pub struct Plugin {
+    pub manifest: Manifest,
+
+    /// Folder the plugin was found in.
+    pub path: PathBuf,
+}
+
+/// Plugin that failed to load.
+#[derive(Debug)]
+pub struct Rejected {
+    /// Folder the plugin was found in.
+    pub path: PathBuf,
+    pub reason: Error,
+}
+
+/// All plugins that were found.
+#[derive(Debug, Default)]
+pub struct Plugins {
+    pub loaded: Vec<Plugin>,
+    pub rejected: Vec<Rejected>,
+}
+
+/// Directory where all plugins are stored.
+pub fn directory() -> Result<PathBuf, Error> {
+    match dirs::data_dir() {
+        Some(mut dir) => {
+            dir.push("bite");
+            dir.push("plugins");
+            Ok(dir)
+        }
+        None => Err(Error::MissingDataDir),
+    }
+}
+
+impl Plugins {
+    /// Find the plugins in [`directory`], rejecting those that are incompatible.
+    pub fn discover() -> Self {
+        let mut this = Self::default();
+        let entries = match directory().map(std::fs::read_dir) {
+            Ok(Ok(entries)) => entries,
+            _ => return this,
+        };
+
+        let mut paths: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+        paths.sort_unstable();
+
+        for path in paths.into_iter().filter(|path| path.is_dir()) {
+            let manifest = Manifest::load(&path.join(MANIFEST));
+            this.add(path, manifest);
+        }
+
+        for plugin in this.loaded.iter() {
+            log::complex!(
+                w "[plugins::discover] loaded ",
+                g format!("{} {}", plugin.manifest.name, plugin.manifest.version),
+                w ".",
+            );
+        }
+
+        for Rejected { path, reason } in this.rejected.iter() {
+            log::complex!(
+                w "[plugins::discover] ",
+                y format!("Rejected {path:?}: {reason}"),
+            );
+        }
+
+        this
+    }
+
+    fn add(&mut self, path: PathBuf, manifest: Result<Manifest, Error>) {
+        let manifest = manifest.and_then(|manifest| {
+            match self.loaded.iter().any(|plugin| plugin.manifest.name == manifest.name) {
+                true => Err(Error::Duplicate(manifest.name)),
+                false => Ok(manifest),
+            }
+        });
+
+        match manifest {
+            Ok(manifest) => self.loaded.push(Plugin { manifest, path }),
+            Err(reason) => self.rejected.push(Rejected { path, reason }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manifest() {
+        let manifest = Manifest::parse(
+            "name: go\nversion: 0.1.0\napi_version: 1\nprovides:\n  passes: [go-strings]\n",
+        )
+        .unwrap();
+
+        assert_eq!(manifest.name, "go");
+        assert_eq!(manifest.provides.passes, ["go-strings"]);
+        assert!(manifest.provides.loaders.is_empty());
+    }
+
+    #[test]
+    fn incompatible() {
+        let manifest = Manifest::parse("name: go\nversion: 0.1.0\napi_version: 999\n");
+        assert!(matches!(manifest, Err(Error::IncompatibleApi(999))));
+    }
+
+    #[test]
+    fn duplicate() {
+        let mut plugins = Plugins::default();
+        let raw = "name: go\nversion: 0.1.0\napi_version: 1\n";
+        plugins.add(PathBuf::from("a"), Manifest::parse(raw));
+        plugins.add(PathBuf::from("b"), Manifest::parse(raw));
+        plugins.add(PathBuf::from("c"), Manifest::parse("name: go"));
+
+        assert_eq!(plugins.loaded.len(), 1);
+        assert_eq!(plugins.rejected.len(), 2);
+        assert!(matches!(plugins.rejected[0].reason, Error::Duplicate(_)));
+    }
+}
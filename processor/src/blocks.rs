@@ -1,14 +1,17 @@
-use crate::Processor;
+use crate::{cache, IdiomKind, Processor};
 use binformat::elf::{Elf32Dyn, Elf32Sym, Elf64Dyn, Elf64Sym};
 use binformat::pe::ExceptionDirectoryEntry;
 use binformat::ToData;
 use config::CONFIG;
+use debugvault::types::Struct;
 use debugvault::Symbol;
 use object::Endian;
-use processor_shared::{encode_hex_bytes_truncated, Section, SectionKind};
+use processor_shared::{encode_hex_bytes_truncated, AddressMode, Section, SectionKind};
+use std::fmt;
 use std::mem::size_of;
+use std::path::Path;
 use std::sync::Arc;
-use tokenizing::{colors, Token, TokenStream};
+use tokenizing::{Token, TokenStream};
 
 const BYTES_BLOCK_SIZE: usize = 256;
 
@@ -22,14 +25,16 @@ pub enum BlockContent {
     },
     Label {
         symbol: Arc<Symbol>,
+        header: FunctionHeader,
     },
     Instruction {
         inst: Vec<Token>,
-        bytes: String,
+        /// Hex encoded bytes, shared with the lines of the block.
+        bytes: Arc<str>,
     },
     Error {
         err: decoder::ErrorKind,
-        bytes: String,
+        bytes: Arc<str>,
     },
     CString {
         bytes: Vec<u8>,
@@ -42,14 +47,81 @@ pub enum BlockContent {
         value: u64,
         symbol: Option<Arc<Symbol>>,
     },
+    /// Address in a data section or code without a symbol, that pointers in data sections point at.
+    DataLabel {
+        name: String,
+        /// Addresses of the pointers, see [`Processor::data_xrefs`].
+        xrefs: Vec<usize>,
+    },
     DataStructure {
         ident: &'static str,
         /// (addr, field, type, value).
         fields: Vec<(usize, &'static str, &'static str, String)>,
     },
+    /// Structure applied by the user.
+    Struct {
+        def: Arc<Struct>,
+        /// Values of the fields, in the order they're defined.
+        values: Vec<String>,
+    },
     Bytes {
         bytes: Vec<u8>,
     },
+    /// Function collapsed into a single line.
+    Folded {
+        symbol: Arc<Symbol>,
+        /// Number of instructions in the function.
+        instructions: usize,
+    },
+    /// Prologue, epilogue, stack canary check or inlined builtin collapsed into a single line.
+    Collapsed {
+        kind: IdiomKind,
+        /// Number of instructions collapsed.
+        instructions: usize,
+    },
+}
+
+/// What's known about a function, shown below its name.
+#[derive(Debug, Clone, Default)]
+pub struct FunctionHeader {
+    /// Calling convention the function most likely follows.
+    pub convention: Option<&'static str>,
+    /// Approximate number of arguments, see [`Processor::prototype`].
+    pub args: Option<usize>,
+    /// Bytes reserved on the stack, see [`Processor::stack_frame`].
+    pub stack_frame: u64,
+    /// Number of functions calling the function directly.
+    pub callers: usize,
+    /// Source file and line the function is defined at, if there's debug info.
+    pub source: Option<(Arc<Path>, usize)>,
+}
+
+impl fmt::Display for FunctionHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("; ")?;
+        if let Some(convention) = self.convention {
+            f.write_fmt(format_args!("{convention}, "))?;
+        }
+
+        match self.args {
+            Some(1) => f.write_str("1 arg, ")?,
+            Some(n) => f.write_fmt(format_args!("{n} args, "))?,
+            None => {}
+        }
+
+        f.write_fmt(format_args!("frame {:#x}, ", self.stack_frame))?;
+        match self.callers {
+            1 => f.write_str("1 caller")?,
+            n => f.write_fmt(format_args!("{n} callers"))?,
+        }
+
+        if let Some((path, line)) = &self.source {
+            let file = path.file_name().unwrap_or(path.as_os_str());
+            f.write_fmt(format_args!(", {}:{line}", file.to_string_lossy()))?;
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
@@ -64,126 +136,30 @@ impl Block {
         match &self.content {
             BlockContent::SectionStart { .. } => 2,
             BlockContent::SectionEnd { .. } => 2,
-            BlockContent::Label { .. } => 2,
+            BlockContent::Label { .. } => 3,
             BlockContent::Instruction { .. } => 1,
             BlockContent::Error { .. } => 1,
             BlockContent::CString { bytes } => bytes.len() + 1,
             BlockContent::Pointer { .. } => 1,
+            BlockContent::DataLabel { .. } => 1,
             BlockContent::Got { .. } => 1,
             BlockContent::DataStructure { fields, .. } => 2 + fields.len(),
+            BlockContent::Struct { def, .. } => 2 + def.fields.len(),
             BlockContent::Bytes { bytes } => (bytes.len() / 32) + 1,
+            BlockContent::Folded { .. } => 1,
+            BlockContent::Collapsed { .. } => 1,
         }
     }
 
-    pub fn tokenize(&self, stream: &mut TokenStream) {
-        match &self.content {
-            BlockContent::Label { symbol } => {
-                stream.push("\n<", CONFIG.colors.asm.label);
-                stream.inner.extend_from_slice(symbol.name());
-                stream.push(">", CONFIG.colors.asm.label);
-            }
-            BlockContent::SectionStart { section } => {
-                stream.push("section started", colors::WHITE);
-                stream.push_owned(format!(" {} ", section.name), CONFIG.colors.asm.section);
-                stream.push("{", CONFIG.colors.brackets);
-                if section.ident == "UNKNOWN" {
-                    stream.push_owned(format!("{:?}", section.kind), CONFIG.colors.asm.component);
-                } else {
-                    stream.push(section.ident, CONFIG.colors.asm.component);
-                }
-                stream.push("} ", CONFIG.colors.brackets);
-                stream.push_owned(format!("{:x}", section.start), colors::GREEN);
-                stream.push("-", CONFIG.colors.delimiter);
-                stream.push_owned(format!("{:x}", section.end), colors::GREEN);
-            }
-            BlockContent::SectionEnd { section } => {
-                stream.push("section ended", colors::WHITE);
-                stream.push_owned(format!(" {} ", section.name), CONFIG.colors.asm.section);
-                stream.push("{", CONFIG.colors.brackets);
-                if section.ident == "UNKNOWN" {
-                    stream.push_owned(format!("{:?}", section.kind), CONFIG.colors.asm.component);
-                } else {
-                    stream.push(section.ident, CONFIG.colors.asm.component);
-                }
-                stream.push("} ", CONFIG.colors.brackets);
-                stream.push_owned(format!("{:x}", section.start), colors::GREEN);
-                stream.push("-", CONFIG.colors.delimiter);
-                stream.push_owned(format!("{:x}", section.end), colors::GREEN);
-            }
-            BlockContent::Instruction { inst, bytes } => {
-                stream.push_owned(format!("{:0>10X}  ", self.addr), CONFIG.colors.address);
-                stream.push_owned(bytes.clone(), CONFIG.colors.bytes);
-                stream.inner.extend_from_slice(&inst);
-            }
-            BlockContent::Error { err, bytes } => {
-                stream.push_owned(format!("{:0>10X}  ", self.addr), CONFIG.colors.address);
-                stream.push_owned(bytes.clone(), CONFIG.colors.bytes);
-                stream.push("<", CONFIG.colors.brackets);
-                stream.push_owned(format!("{err:?}"), CONFIG.colors.asm.invalid);
-                stream.push(">", CONFIG.colors.brackets);
-            }
-            BlockContent::CString { bytes } => {
-                stream.push_owned(format!("{:0>10X}  ", self.addr), CONFIG.colors.address);
-                let lossy_string = String::from_utf8_lossy(&bytes);
-                let escaped = format!("\"{}\"", lossy_string.escape_debug());
-                stream.push_owned(escaped, CONFIG.colors.asm.string);
-            }
-            BlockContent::Got { symbol, .. } => {
-                stream.push_owned(format!("{:0>10X}  ", self.addr), CONFIG.colors.address);
-                stream.push("<", CONFIG.colors.asm.label);
-                let name = symbol.name();
-                if name.is_empty() {
-                    stream.push("unresolved", CONFIG.colors.asm.invalid);
-                } else {
-                    stream.inner.extend_from_slice(symbol.name());
-                }
-                stream.push(">", CONFIG.colors.asm.label);
-            }
-            BlockContent::DataStructure { ident, fields } => {
-                // addr  struct Ident {
-                // addr      field: type = value
-                // addr      ...
-                // addr  }
-                let start_addr = fields[0].0;
-                let end_addr = fields[fields.len() - 1].0;
-                stream.push_owned(format!("{:0>10X}  ", start_addr), CONFIG.colors.address);
-                stream.push("struct ", CONFIG.colors.src.keyword);
-                stream.push(ident, CONFIG.colors.src.tipe);
-                stream.push(" {\n", CONFIG.colors.delimiter);
-                for (addr, name, tipe, value) in fields {
-                    stream.push_owned(format!("{:0>10X}  ", addr), CONFIG.colors.address);
-                    stream.push("    ", colors::WHITE);
-                    stream.push(name, CONFIG.colors.src.field);
-                    stream.push(": ", colors::WHITE);
-                    stream.push(tipe, CONFIG.colors.src.tipe);
-                    stream.push(" = ", CONFIG.colors.delimiter);
-                    stream.push_owned(value.clone(), CONFIG.colors.src.constant);
-                    stream.push("\n", colors::WHITE);
-                }
-                stream.push_owned(format!("{:0>10X}  ", end_addr), CONFIG.colors.address);
-                stream.push("}", CONFIG.colors.delimiter);
-            }
-            BlockContent::Pointer { value, symbol, .. } => {
-                stream.push_owned(format!("{:0>10X}  ", self.addr), CONFIG.colors.address);
-                stream.push_owned(format!("{:#x}", value), CONFIG.colors.bytes);
-                if let Some(symbol) = symbol {
-                    stream.push(" <", CONFIG.colors.asm.label);
-                    stream.inner.extend_from_slice(symbol.name());
-                    stream.push(">", CONFIG.colors.asm.label);
-                }
-            }
-            BlockContent::Bytes { bytes } => {
-                let mut off = 0;
-                for chunk in bytes.chunks(32) {
-                    stream.push_owned(format!("{:0>10X}  ", self.addr + off), CONFIG.colors.address);
-                    let s = processor_shared::encode_hex_bytes_truncated(chunk, usize::MAX, false);
-                    stream.push_owned(s, CONFIG.colors.bytes);
-                    stream.push("\n", colors::WHITE);
-                    off += chunk.len();
-                }
-                // Pop last newline.
-                stream.inner.pop();
+    /// Tokenize the block using the columns in the user's config.
+    pub fn tokenize(&self, stream: &mut TokenStream, processor: &Processor, mode: AddressMode) {
+        for (idx, line) in self.lines(processor, mode).into_iter().enumerate() {
+            if idx != 0 {
+                stream.push("\n", CONFIG.colors.text);
             }
+
+            let spans = line.layout(&CONFIG.listing.columns, processor, mode);
+            stream.inner.extend(spans.into_iter().map(|span| span.token));
         }
     }
 }
@@ -203,6 +179,85 @@ impl Processor {
         self.index.get_sym_by_addr(addr)
     }
 
+    /// Parse blocks given an address boundary, collapsing the function starting there into a
+    /// single block.
+    ///
+    /// The function's other boundaries are up to the caller to skip, it ends where the next
+    /// symbol or the section starts, see [`Processor::function_bounds`].
+    pub fn parse_folded_blocks(&self, addr: usize) -> Vec<Block> {
+        let mut blocks = self.parse_blocks(addr);
+        let end = match self.function_bounds(addr) {
+            Some((start, end, _)) if start == addr => end,
+            _ => return blocks,
+        };
+
+        let symbol = blocks.iter().find_map(|block| match &block.content {
+            BlockContent::Label { symbol, .. } => Some(Arc::clone(symbol)),
+            _ => None,
+        });
+
+        let symbol = match symbol {
+            Some(symbol) => symbol,
+            None => return blocks,
+        };
+
+        // section markers still show, everything else is folded
+        blocks.retain(|block| {
+            matches!(
+                block.content,
+                BlockContent::SectionStart { .. } | BlockContent::SectionEnd { .. }
+            )
+        });
+
+        blocks.push(Block {
+            addr,
+            content: BlockContent::Folded {
+                symbol,
+                instructions: self.instructions_in(addr..end).len(),
+            },
+        });
+
+        blocks
+    }
+
+    /// Parse blocks given an address boundary, collapsing the idiom starting there into a
+    /// single block.
+    ///
+    /// The idiom's other instructions are up to the caller to skip, see [`Processor::idiom_at`].
+    pub fn parse_collapsed_blocks(&self, addr: usize) -> Vec<Block> {
+        let mut blocks = self.parse_blocks(addr);
+        let idiom = match self.idiom_at(addr) {
+            Some(idiom) => idiom,
+            None => return blocks,
+        };
+
+        for block in blocks.iter_mut() {
+            if let BlockContent::Instruction { .. } = block.content {
+                block.content = BlockContent::Collapsed {
+                    kind: idiom.kind,
+                    instructions: idiom.instructions,
+                };
+            }
+        }
+
+        blocks
+    }
+
+    /// Header of the function starting at an address.
+    fn function_header(&self, addr: usize) -> FunctionHeader {
+        let end = self.function_bounds(addr).map_or(addr, |(_, end, _)| end);
+        let source = self.index.get_file_by_addr(addr);
+        let prototype = self.prototype(addr);
+
+        FunctionHeader {
+            convention: prototype.map(|p| p.convention).or_else(|| self.calling_convention()),
+            args: prototype.map(|p| p.args),
+            stack_frame: self.stack_frame(addr, end),
+            callers: self.callers(addr).count(),
+            source: source.map(|file| (Arc::clone(&file.path), file.line)),
+        }
+    }
+
     /// Parse blocks given an address boundary.
     pub fn parse_blocks(&self, addr: usize) -> Vec<Block> {
         let mut blocks = Vec::new();
@@ -259,6 +314,8 @@ impl Processor {
             return blocks;
         }
 
+        self.parse_data_label(addr, section, &mut blocks);
+
         match section.kind {
             SectionKind::Code => self.parse_code(addr, section, &mut blocks),
             SectionKind::Ptr32 => self.parse_pointer(addr, section, 4, &mut blocks),
@@ -284,13 +341,26 @@ impl Processor {
             // For any other section kinds just assume they're made of bytes.
             // As a note, we calculate the byte boundaries in blocks of [`BYTES_BLOCK_SIZE`],
             // so this block can be up to [`BYTES_BLOCK_SIZE`] bytes.
-            _ => {
-                let bytes = section.bytes_by_addr(addr, BYTES_BLOCK_SIZE).to_vec();
-                blocks.push(Block {
-                    addr,
-                    content: BlockContent::Bytes { bytes },
-                });
-            }
+            _ => match (self.struct_at(addr), self.pointer_at(addr)) {
+                (Some(def), _) => {
+                    let values = self.struct_values(section, addr, &def);
+                    blocks.push(Block {
+                        addr,
+                        content: BlockContent::Struct { def, values },
+                    });
+                }
+                (None, Some(_)) => {
+                    self.parse_pointer(addr, section, self.pointer_size(), &mut blocks)
+                }
+                (None, None) => {
+                    let len = self.bytes_len(addr);
+                    let bytes = section.bytes_by_addr(addr, len).to_vec();
+                    blocks.push(Block {
+                        addr,
+                        content: BlockContent::Bytes { bytes },
+                    });
+                }
+            },
         }
 
         blocks
@@ -322,6 +392,29 @@ impl Processor {
         });
     }
 
+    /// Label of an address that pointers in data sections point at, unless it's a function with a
+    /// label of its own.
+    fn parse_data_label(&self, addr: usize, section: &Section, blocks: &mut Vec<Block>) {
+        let xrefs = self.data_xrefs(addr);
+        if xrefs.is_empty() {
+            return;
+        }
+
+        if section.kind == SectionKind::Code && self.get_symbol_by_addr(addr, section).is_some() {
+            return;
+        }
+
+        if let Some(name) = self.data_label(addr) {
+            blocks.push(Block {
+                addr,
+                content: BlockContent::DataLabel {
+                    name,
+                    xrefs: xrefs.to_vec(),
+                },
+            });
+        }
+    }
+
     fn parse_pointer(&self, addr: usize, section: &Section, size: usize, blocks: &mut Vec<Block>) {
         let bytes = section.bytes_by_addr(addr, size);
         let value = if size == 4 {
@@ -357,7 +450,10 @@ impl Processor {
             if let Some(symbol) = self.get_symbol_by_addr(addr, section) {
                 blocks.push(Block {
                     addr,
-                    content: BlockContent::Label { symbol },
+                    content: BlockContent::Label {
+                        symbol,
+                        header: self.function_header(addr),
+                    },
                 })
             }
         }
@@ -365,9 +461,11 @@ impl Processor {
         if let Some(inst) = opt_inst {
             let width = self.instruction_width(&inst);
             let inst = self.instruction_tokens(&inst, &self.index);
-            let bytes = section.bytes_by_addr(addr, width);
+            let inst = self.symbolic_immediates(addr, inst);
+            let bytes = self.patched_bytes(section, addr, width);
             let bytes =
                 encode_hex_bytes_truncated(&bytes, self.max_instruction_width * 3 + 1, true);
+            let bytes = Arc::from(bytes.trim_end());
 
             blocks.push(Block {
                 addr,
@@ -377,9 +475,10 @@ impl Processor {
         }
 
         if let Some(err) = opt_err {
-            let bytes = section.bytes_by_addr(addr, err.size());
+            let bytes = self.patched_bytes(section, addr, err.size());
             let bytes =
                 encode_hex_bytes_truncated(&bytes, self.max_instruction_width * 3 + 1, true);
+            let bytes = Arc::from(bytes.trim_end());
 
             blocks.push(Block {
                 addr,
@@ -396,30 +495,13 @@ impl Processor {
     }
 
     fn parse_bytes(&self, addr: usize, section: &Section, blocks: &mut Vec<Block>) {
-        let mut baddr = addr;
-        loop {
-            if baddr == section.end {
-                break;
-            }
-
-            if self.instruction_by_addr(baddr).is_some() {
-                break;
-            }
-
-            if self.error_by_addr(baddr).is_some() {
-                break;
-            }
-
-            if self.get_symbol_by_addr(addr, section).is_some() {
-                break;
-            }
-
-            baddr += 1;
+        if self.get_symbol_by_addr(addr, section).is_some() {
+            return;
         }
 
-        let bytes_len = baddr - addr;
+        let bytes_len = self.code_bytes_end(addr, section) - addr;
         if bytes_len > 0 {
-            let bytes = section.bytes_by_addr(addr, bytes_len).to_vec();
+            let bytes = self.patched_bytes(section, addr, bytes_len).to_vec();
             blocks.push(Block {
                 addr,
                 content: BlockContent::Bytes { bytes },
@@ -441,12 +523,27 @@ impl Processor {
             }
         });
 
+        self.save_cache();
+
         boundaries.sort_unstable();
         boundaries.dedup();
         boundaries
     }
 
-    fn compute_section_boundaries(&self, section: &Section) -> Vec<usize> {
+    /// Write the cache to disk if anything was added to it, without holding on to the lock while
+    /// it's being written.
+    pub(crate) fn save_cache(&self) {
+        let bytes = match self.cache.lock().unwrap().encode_changes() {
+            Some(bytes) => bytes,
+            None => return,
+        };
+
+        if !cache::Cache::write(&self.hash, &bytes) {
+            self.cache.lock().unwrap().dirty = true;
+        }
+    }
+
+    pub fn compute_section_boundaries(&self, section: &Section) -> Vec<usize> {
         let mut boundaries = Vec::new();
 
         if let SectionKind::Unloaded | SectionKind::Debug = section.kind {
@@ -516,12 +613,16 @@ impl Processor {
                 }
             }
             // For any other section kinds just assume they evenly
-            // split in blocks of [`BYTES_BLOCK_SIZE`].
+            // split in blocks of [`BYTES_BLOCK_SIZE`], apart from applied structures.
             _ => {
                 let mut addr = section.start;
                 while addr < section.end {
                     boundaries.push(addr);
-                    addr += BYTES_BLOCK_SIZE;
+                    addr += match (self.struct_at(addr), self.pointer_at(addr)) {
+                        (Some(def), _) => def.size,
+                        (None, Some(_)) => self.pointer_size(),
+                        (None, None) => self.bytes_len(addr),
+                    };
                 }
             }
         }
@@ -529,17 +630,71 @@ impl Processor {
         boundaries
     }
 
+    /// Length of the bytes starting at an address of a data section, these stop where the next
+    /// structure, pointer or label starts.
+    fn bytes_len(&self, addr: usize) -> usize {
+        [self.next_struct(addr), self.next_data_boundary(addr)]
+            .into_iter()
+            .flatten()
+            .map(|next| next - addr)
+            .fold(BYTES_BLOCK_SIZE, std::cmp::min)
+    }
+
     fn compute_code_boundaries(&self, section: &Section, boundaries: &mut Vec<usize>) {
-        let mut addr = section.start;
+        // the cache doesn't know about patches
+        let patched = self.is_patched(section);
+
+        if !patched {
+            if let Some(cached) = self.cache.lock().unwrap().boundaries.get(&section.start) {
+                boundaries.extend_from_slice(cached);
+                return;
+            }
+        }
+
+        let start = boundaries.len();
+        self.walk_code_boundaries(section, section.start, section.end, boundaries);
+
+        if !patched {
+            let mut cache = self.cache.lock().unwrap();
+            cache.boundaries.insert(section.start, boundaries[start..].to_vec());
+            cache.dirty = true;
+        }
+    }
+
+    /// Boundaries of the code starting at `start`, which has to be a boundary itself, up until at
+    /// least `end`.
+    ///
+    /// Returns the boundaries together with the address it stopped at, which is a boundary too
+    /// unless it's the end of the section.
+    pub fn compute_code_boundaries_in(&self, start: usize, end: usize) -> (Vec<usize>, usize) {
+        let mut boundaries = Vec::new();
+        let section = match self.section_by_addr(start) {
+            Some(section) if section.kind == SectionKind::Code => section,
+            _ => return (boundaries, start),
+        };
+
+        let end = std::cmp::min(end, section.end);
+        let addr = self.walk_code_boundaries(section, start, end, &mut boundaries);
+        (boundaries, addr)
+    }
+
+    fn walk_code_boundaries(
+        &self,
+        section: &Section,
+        start: usize,
+        end: usize,
+        boundaries: &mut Vec<usize>,
+    ) -> usize {
+        let mut addr = start;
 
         loop {
-            if addr == section.end {
+            if addr >= end {
                 break;
             }
 
             if let Some(inst) = self.instruction_by_addr(addr) {
                 boundaries.push(addr);
-                addr += self.instruction_width(inst);
+                addr += self.instruction_width(&inst);
                 continue;
             }
 
@@ -549,29 +704,24 @@ impl Processor {
                 continue;
             }
 
-            let mut baddr = addr;
-            loop {
-                if baddr == section.end {
-                    break;
-                }
-
-                if self.instruction_by_addr(baddr).is_some() {
-                    break;
-                }
-
-                if self.error_by_addr(baddr).is_some() {
-                    break;
-                }
-
-                baddr += 1;
-            }
-
+            let baddr = self.code_bytes_end(addr, section);
             let bytes_len = baddr - addr;
             if bytes_len > 0 {
                 boundaries.push(addr);
                 addr = baddr;
             }
         }
+
+        addr
+    }
+
+    /// End of the bytes that aren't decoded starting at `addr`, which is where the next
+    /// instruction or error starts.
+    fn code_bytes_end(&self, addr: usize, section: &Section) -> usize {
+        match self.next_decoded(addr) {
+            Some(next) => std::cmp::min(next, section.end),
+            None => section.end,
+        }
     }
 
     fn compute_cstring_boundaries(&self, section: &Section, boundaries: &mut Vec<usize>) {
@@ -0,0 +1,343 @@
+//! Java class files, jars and Android's dex files, which hold bytecode instead of native code.
+//!
+//! Every method with code gets a section of its own, as the padding of the JVM's switches depends
+//! on where they're in their method and dex files store other data in between methods.
+
+use crate::lines::AddressWidths;
+use crate::{
+    cache, ir, pipeline, sweep, CallGraph, DecodeRegion, Error, Hashes, Instruction, Processor,
+};
+use binformat::{classfile, dex};
+use debugvault::Index;
+use decoder::{Decodable, Decoded};
+use java::dalvik::{self, Pool};
+use java::jvm;
+use memmap2::Mmap;
+use object::{Architecture, BinaryFormat, Endianness};
+use processor_shared::{AddressMap, Permissions, PhysAddr, Section, SectionKind, Segment};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex, RwLock};
+use tokenizing::{Token, TokenStream};
+
+/// Alignment of class files decompressed from a jar.
+const CLASS_ALIGNMENT: usize = 16;
+
+pub(crate) enum Bytecode {
+    Jvm {
+        /// Classes along with the address of their class file, sorted by address.
+        classes: Vec<(PhysAddr, classfile::Class)>,
+        /// Class files decompressed from a jar, as they aren't in the memory mapped binary.
+        inflated: Vec<u8>,
+    },
+    Dalvik(dex::Dex),
+}
+
+impl Bytecode {
+    /// Parse a class file, jar or dex file.
+    pub(crate) fn parse(binary: &[u8]) -> Option<Self> {
+        if classfile::is_class(binary) {
+            let class = classfile::Class::parse(binary)?;
+            return Some(Bytecode::Jvm {
+                classes: vec![(0, class)],
+                inflated: Vec::new(),
+            });
+        }
+
+        if dex::is_dex(binary) {
+            return dex::Dex::parse(binary).map(Bytecode::Dalvik);
+        }
+
+        if !classfile::is_jar(binary) {
+            return None;
+        }
+
+        let mut classes = Vec::new();
+        let mut inflated = Vec::new();
+        for (path, bytes) in classfile::jar_classes(binary)? {
+            let addr = inflated.len();
+            match classfile::Class::parse(&bytes) {
+                Some(class) => classes.push((addr, class)),
+                None => {
+                    log::complex!(
+                        w "[bytecode::parse] failed to parse ",
+                        y path,
+                        w ".",
+                    );
+                    continue;
+                }
+            }
+
+            inflated.extend_from_slice(&bytes);
+            inflated.resize(inflated.len().next_multiple_of(CLASS_ALIGNMENT), 0);
+        }
+
+        // jars are zip files, which are also used for plenty of other things
+        if classes.is_empty() {
+            return None;
+        }
+
+        Some(Bytecode::Jvm { classes, inflated })
+    }
+
+    /// Name of the format, as it's shown in place of an object format.
+    pub(crate) fn format(&self) -> &'static str {
+        match self {
+            Bytecode::Jvm { .. } => "Java class",
+            Bytecode::Dalvik(..) => "Dex",
+        }
+    }
+
+    /// Name of every class along with the address and name of their methods.
+    fn methods(&self) -> Vec<(&str, PhysAddr, &str, usize)> {
+        match self {
+            Bytecode::Jvm { classes, .. } => classes
+                .iter()
+                .flat_map(|(addr, class)| {
+                    class.methods.iter().map(move |method| {
+                        (&class.name[..], addr + method.offset, &method.name[..], method.size)
+                    })
+                })
+                .collect(),
+            Bytecode::Dalvik(dex) => dex
+                .classes
+                .iter()
+                .flat_map(|class| {
+                    class.methods.iter().map(move |method| {
+                        (&class.name[..], method.offset, &method.name[..], method.size)
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    /// Tokens of an instruction, naming the constants it refers to.
+    pub(crate) fn tokens(&self, instruction: &Instruction) -> Vec<Token> {
+        let mut stream = TokenStream::new();
+
+        match self {
+            Bytecode::Jvm { classes, .. } => {
+                let instruction = unsafe { &instruction.jvm };
+                let class = instruction.addr().and_then(|addr| {
+                    let idx = classes.partition_point(|(start, _)| *start <= addr);
+                    classes.get(idx.checked_sub(1)?).map(|(_, class)| class)
+                });
+
+                instruction.tokenize_with(&mut stream, &|idx| class?.constant(idx));
+            }
+            Bytecode::Dalvik(dex) => {
+                let instruction = unsafe { &instruction.dalvik };
+                instruction.tokenize_with(&mut stream, &|pool, idx| match pool {
+                    Pool::String => dex.string(idx),
+                    Pool::Type => dex.type_name(idx),
+                    Pool::Field => dex.field(idx),
+                    Pool::Method => dex.method(idx),
+                    Pool::Proto => dex.proto(idx),
+                    Pool::CallSite | Pool::MethodHandle => None,
+                });
+            }
+        }
+
+        stream.inner
+    }
+}
+
+/// Functions that forward to an [`Instruction`]'s bytecode field, along with the linear sweep that
+/// decodes into it, like `impl_decoder!` does for native code.
+macro_rules! impl_bytecode {
+    ($decoder:expr, $field:ident) => {{
+        fn tokens(instruction: &Instruction, symbols: &Index) -> Vec<Token> {
+            unsafe { instruction.$field.tokens(symbols) }
+        }
+
+        fn width(instruction: &Instruction) -> usize {
+            unsafe { instruction.$field.width() }
+        }
+
+        fn lower(instruction: &Instruction, stmts: &mut Vec<ir::Stmt>) {
+            unsafe { instruction.$field.lower(stmts) }
+        }
+
+        fn decode_region(
+            bytes: &[u8],
+            base: PhysAddr,
+            start: PhysAddr,
+            end: PhysAddr,
+            seam: Option<PhysAddr>,
+        ) -> sweep::Decoded<Instruction> {
+            let decoded = sweep::decode(&$decoder, bytes, base, start, end, seam);
+            let decoded = decoded.map(|instruction| Instruction {
+                $field: std::mem::ManuallyDrop::new(instruction),
+            });
+
+            // SAFETY: every instruction was decoded into the field being dropped
+            unsafe { decoded.drop_with(drop_instruction) }
+        }
+
+        unsafe fn drop_instruction(instruction: &mut Instruction) {
+            std::mem::ManuallyDrop::drop(&mut instruction.$field)
+        }
+
+        let decode_region: DecodeRegion = decode_region;
+        (tokens as _, width as _, lower as _, decode_region, $decoder.max_width())
+    }};
+}
+
+impl Processor {
+    /// Load bytecode, which doesn't have an object format or architecture but otherwise gets
+    /// disassembled like native code.
+    pub(crate) fn parse_bytecode(
+        path: std::path::PathBuf,
+        file: File,
+        mmap: Mmap,
+        bytecode: Bytecode,
+        progress: Arc<pipeline::Progress>,
+        now: std::time::Instant,
+    ) -> Result<Self, Error> {
+        let binary: &'static [u8] = unsafe { std::mem::transmute(&mmap[..]) };
+        let hashes = Hashes::compute(binary, None);
+        let hash = hashes.sha256.clone();
+
+        progress.enter(pipeline::Stage::Symbols)?;
+
+        let (data, in_file): (&'static [u8], bool) = match bytecode {
+            Bytecode::Jvm { ref inflated, .. } if !inflated.is_empty() => {
+                (unsafe { std::mem::transmute::<&[u8], &'static [u8]>(&inflated[..]) }, false)
+            }
+            _ => (binary, true),
+        };
+
+        let mut sections = Vec::new();
+        let mut functions = Vec::new();
+        let mut entrypoint = 0;
+        for (class, addr, name, size) in bytecode.methods() {
+            let mut section = Section::new(
+                class.to_string(),
+                "CODE",
+                SectionKind::Code,
+                &data[addr..addr + size],
+                addr,
+                addr + size,
+            );
+
+            section.file_offset = in_file.then_some(addr as u64);
+            section.permissions = Permissions {
+                read: true,
+                write: false,
+                execute: true,
+            };
+
+            if name.ends_with(".main(java.lang.String[])") && entrypoint == 0 {
+                entrypoint = addr;
+            }
+
+            sections.push(section);
+            functions.push((addr, name.to_string()));
+        }
+
+        sections.sort_unstable_by_key(|section| section.start);
+
+        let mut index = Index::default();
+        index.insert_functions(functions);
+
+        log::complex!(
+            w "[processor::parse_bytecode] found ",
+            g sections.len().to_string(),
+            w " methods with ",
+            g bytecode.format(),
+            w " bytecode.",
+        );
+
+        let segments = vec![Segment {
+            name: "flat (generated)".to_string(),
+            start: 0,
+            end: data.len(),
+        }];
+
+        let address_widths = AddressWidths::new(&sections, &segments, 0);
+
+        progress.enter(pipeline::Stage::Disassembly)?;
+
+        let (instruction_tokens, instruction_width, instruction_lower, decode_region, max_width) =
+            match bytecode {
+                Bytecode::Jvm { .. } => impl_bytecode!(jvm::Decoder, jvm),
+                Bytecode::Dalvik(..) => impl_bytecode!(dalvik::Decoder, dalvik),
+            };
+
+        log::complex!(
+            w "[processor::parse] took ",
+            y format!("{:#?}", now.elapsed()),
+            w " to parse ",
+            w format!("{path:?}.")
+        );
+
+        let regions = sweep::regions(&sections);
+        let cache = cache::Cache::load(&hash);
+        let mut processor = Self {
+            entrypoint,
+            path,
+            hash,
+            hashes,
+            sections,
+            regions,
+            decode_region,
+            patches: RwLock::default(),
+            structs: RwLock::default(),
+            applied: RwLock::default(),
+            enums: RwLock::default(),
+            applied_enums: RwLock::default(),
+            cache: Mutex::new(cache),
+            progress,
+            segments,
+            index,
+            image_base: 0,
+            analysis_time: now.elapsed(),
+            timings: log::Profile::default(),
+            detections: Vec::new(),
+            mitigations: Vec::new(),
+            rich: None,
+            signature: None,
+            signature_covers: Mutex::new(None),
+            overlay: None,
+            embedded: Vec::new(),
+            crypto: Vec::new(),
+            idioms: AddressMap::default(),
+            syscalls: AddressMap::default(),
+            data_pointers: BTreeMap::new(),
+            data_xrefs: BTreeMap::new(),
+            strings: Vec::new(),
+            function_strings: BTreeMap::new(),
+            back_edges: BTreeMap::new(),
+            dead_code: Vec::new(),
+            imports: Vec::new(),
+            exports: Vec::new(),
+            autostart: Vec::new(),
+            load_commands: Vec::new(),
+            objc: Default::default(),
+            dotnet: None,
+            bytecode: Some(bytecode),
+            coredump: None,
+            call_graph: CallGraph::default(),
+            prototypes: BTreeMap::new(),
+            indirect: BTreeMap::new(),
+            address_widths,
+            _file: file,
+            mmap,
+            _owned: Vec::new(),
+            max_instruction_width: max_width,
+            instruction_tokens,
+            instruction_width,
+            instruction_lower,
+            arch: Architecture::Unknown,
+            // only decides the calling convention of native code
+            format: BinaryFormat::Elf,
+            abi_registers: AtomicBool::new(false),
+            endianness: Endianness::Big,
+        };
+
+        processor.progress.finish();
+        processor.timings = log::PROFILER.take(processor.analysis_time);
+        Ok(processor)
+    }
+}
@@ -0,0 +1,371 @@
+//! Results of the most expensive analysis passes, stored on disk so that opening the same binary
+//! again doesn't run them again.
+//!
+//! Only the block boundaries of code sections, calls, the targets of indirect calls and jumps and
+//! the functions recognized by their signature are cached. The passes that build on those, such as
+//! the call graph, strings, system calls and prototypes, run again whenever a binary is opened.
+//!
+//! The cache is keyed by the hash of the binary and holds what the binary looks like without any
+//! patches, so sections that got patched are always decoded again. Library functions depend on the
+//! signatures that are installed, so the analysis records the hash of the signatures it used and
+//! is done again once they change. Addresses are stored as deltas encoded as LEB128, which keeps
+//! the cache of large binaries small.
+
+use processor_shared::PhysAddr;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Version of the cache format, bumped whenever the format or the analysis changes.
+const VERSION: u64 = 3;
+
+/// Bytes every cache file starts with.
+const MAGIC: &[u8; 4] = b"BITC";
+
+/// Results of the passes that run with [`commands::Feature::Analysis`] that are cached.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Analysis {
+    /// Hash of the signatures that library functions were recognized with.
+    pub signatures: [u8; 32],
+    /// Calls to known addresses, as pairs of call site and target.
+    pub calls: Vec<(PhysAddr, PhysAddr)>,
+    /// Indirect calls and jumps whose target is known, as pairs of call site and target.
+    pub indirect: Vec<(PhysAddr, PhysAddr)>,
+    /// Functions recognized by their signature.
+    pub functions: Vec<(PhysAddr, String)>,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Cache {
+    /// Block boundaries of every code section, by the start of the section.
+    pub boundaries: BTreeMap<PhysAddr, Vec<PhysAddr>>,
+    pub analysis: Option<Analysis>,
+    /// Whether anything was added since the cache got loaded.
+    pub dirty: bool,
+}
+
+impl Cache {
+    /// Directory that caches are stored in.
+    pub fn directory() -> Option<PathBuf> {
+        let mut dir = dirs::cache_dir()?;
+        dir.push("bite");
+        dir.push("analysis");
+        Some(dir)
+    }
+
+    /// Load the cache of a binary, returns an empty cache if there isn't one.
+    pub fn load(hash: &str) -> Self {
+        let bytes = match Self::directory().map(|dir| std::fs::read(dir.join(hash))) {
+            Some(Ok(bytes)) => bytes,
+            _ => return Self::default(),
+        };
+
+        match Self::decode(&bytes) {
+            Some(cache) => cache,
+            None => {
+                log::complex!(
+                    w "[cache::load] ",
+                    y "ignoring outdated or corrupt cache of ",
+                    w format!("{hash}."),
+                );
+                Self::default()
+            }
+        }
+    }
+
+    /// Encode the cache if anything was added to it since it was last saved, marking it as saved.
+    ///
+    /// Encoding is done while the cache is locked, writing it with [`Cache::write`] isn't.
+    pub fn encode_changes(&mut self) -> Option<Vec<u8>> {
+        if !self.dirty {
+            return None;
+        }
+
+        self.dirty = false;
+        Some(self.encode())
+    }
+
+    /// Write an encoded cache of a binary to disk, returns whether it was written.
+    pub fn write(hash: &str, bytes: &[u8]) -> bool {
+        let dir = match Self::directory() {
+            Some(dir) => dir,
+            None => return false,
+        };
+
+        // written next to the cache and renamed over it, so a crash can't leave half a cache behind
+        let tmp = dir.join(format!("{hash}.{}.tmp", std::process::id()));
+        let result = std::fs::create_dir_all(&dir)
+            .and_then(|_| std::fs::write(&tmp, bytes))
+            .and_then(|_| std::fs::rename(&tmp, dir.join(hash)));
+
+        if result.is_err() {
+            let _ = std::fs::remove_file(&tmp);
+        }
+
+        match result {
+            Ok(()) => true,
+            Err(err) => {
+                log::complex!(
+                    w "[cache::write] ",
+                    y format!("failed to write cache: '{err}'."),
+                );
+                false
+            }
+        }
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut writer = Writer(MAGIC.to_vec());
+        writer.uint(VERSION);
+
+        writer.uint(self.boundaries.len() as u64);
+        for (&start, boundaries) in &self.boundaries {
+            writer.uint(start as u64);
+            writer.addrs(start, boundaries.iter().copied());
+        }
+
+        match &self.analysis {
+            Some(analysis) => {
+                writer.uint(1);
+                writer.bytes(&analysis.signatures);
+
+                writer.edges(&analysis.calls);
+                writer.edges(&analysis.indirect);
+
+                writer.uint(analysis.functions.len() as u64);
+                for (addr, name) in &analysis.functions {
+                    writer.uint(*addr as u64);
+                    writer.bytes(name.as_bytes());
+                }
+            }
+            None => writer.uint(0),
+        }
+
+        writer.0
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        let mut reader = Reader(bytes.strip_prefix(MAGIC)?);
+        if reader.uint()? != VERSION {
+            return None;
+        }
+
+        let mut boundaries = BTreeMap::new();
+        for _ in 0..reader.uint()? {
+            let start = reader.uint()? as PhysAddr;
+            boundaries.insert(start, reader.addrs(start)?);
+        }
+
+        let analysis = match reader.uint()? {
+            0 => None,
+            _ => {
+                let signatures = reader.bytes()?.try_into().ok()?;
+                let calls = reader.edges()?;
+                let indirect = reader.edges()?;
+
+                let count = reader.uint()?;
+                let mut functions = Vec::new();
+                for _ in 0..count {
+                    let addr = reader.uint()? as PhysAddr;
+                    let name = reader.bytes()?;
+                    functions.push((addr, String::from_utf8(name.to_vec()).ok()?));
+                }
+
+                Some(Analysis {
+                    signatures,
+                    calls,
+                    indirect,
+                    functions,
+                })
+            }
+        };
+
+        if !reader.0.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            boundaries,
+            analysis,
+            dirty: false,
+        })
+    }
+}
+
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn uint(&mut self, mut value: u64) {
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+
+            if value == 0 {
+                self.0.push(byte);
+                break;
+            }
+
+            self.0.push(byte | 0x80);
+        }
+    }
+
+    /// Write sorted addresses as the distance to the one before them.
+    fn addrs(&mut self, base: PhysAddr, addrs: impl ExactSizeIterator<Item = PhysAddr>) {
+        self.uint(addrs.len() as u64);
+
+        let mut prev = base;
+        for addr in addrs {
+            self.uint((addr - prev) as u64);
+            prev = addr;
+        }
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.uint(bytes.len() as u64);
+        self.0.extend_from_slice(bytes);
+    }
+
+    /// Write pairs of addresses, such as a call site and its target, sorted by the first.
+    fn edges(&mut self, edges: &[(PhysAddr, PhysAddr)]) {
+        let mut edges = edges.to_vec();
+        edges.sort_unstable();
+        self.addrs(0, edges.iter().map(|&(site, _)| site));
+        for &(_, target) in &edges {
+            self.uint(target as u64);
+        }
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn uint(&mut self) -> Option<u64> {
+        let mut value = 0u64;
+
+        for shift in (0..64).step_by(7) {
+            let (&byte, rest) = self.0.split_first()?;
+            self.0 = rest;
+            value |= ((byte & 0x7f) as u64) << shift;
+
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+
+        None
+    }
+
+    fn addrs(&mut self, base: PhysAddr) -> Option<Vec<PhysAddr>> {
+        let count = self.uint()? as usize;
+
+        // every address takes up at least a byte
+        if count > self.0.len() {
+            return None;
+        }
+
+        let mut addrs = Vec::with_capacity(count);
+        let mut prev = base;
+        for _ in 0..count {
+            prev = prev.checked_add(self.uint()? as PhysAddr)?;
+            addrs.push(prev);
+        }
+
+        Some(addrs)
+    }
+
+    fn edges(&mut self) -> Option<Vec<(PhysAddr, PhysAddr)>> {
+        let sites = self.addrs(0)?;
+        let mut edges = Vec::with_capacity(sites.len());
+        for site in sites {
+            edges.push((site, self.uint()? as PhysAddr));
+        }
+
+        Some(edges)
+    }
+
+    fn bytes(&mut self) -> Option<&'a [u8]> {
+        let len = self.uint()? as usize;
+        if len > self.0.len() {
+            return None;
+        }
+
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Some(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache() -> Cache {
+        let analysis = Analysis {
+            signatures: [7; 32],
+            calls: vec![(0x1010, 0x2000), (0x1000, 0x3000)],
+            indirect: vec![(0x1020, 0x1000)],
+            functions: vec![(0x2000, "memcpy".to_string())],
+        };
+
+        Cache {
+            boundaries: BTreeMap::from([(0x1000, vec![0x1000, 0x1004, 0x1100])]),
+            analysis: Some(analysis),
+            dirty: true,
+        }
+    }
+
+    #[test]
+    fn roundtrip() {
+        let decoded = Cache::decode(&cache().encode()).unwrap();
+        assert_eq!(decoded.boundaries, cache().boundaries);
+        assert!(!decoded.dirty);
+
+        // edges are sorted by their call site
+        let analysis = decoded.analysis.unwrap();
+        assert_eq!(analysis.calls, [(0x1000, 0x3000), (0x1010, 0x2000)]);
+        assert_eq!(analysis.indirect, cache().analysis.unwrap().indirect);
+        assert_eq!(analysis.functions, cache().analysis.unwrap().functions);
+        assert_eq!(analysis.signatures, [7; 32]);
+
+        let empty = Cache::decode(&Cache::default().encode()).unwrap();
+        assert!(empty.boundaries.is_empty() && empty.analysis.is_none());
+    }
+
+    #[test]
+    fn corrupt() {
+        let bytes = cache().encode();
+        for len in 0..bytes.len() {
+            assert!(Cache::decode(&bytes[..len]).is_none());
+        }
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(Cache::decode(&trailing).is_none());
+
+        // the version follows the magic
+        let mut outdated = bytes.clone();
+        outdated[MAGIC.len()] = VERSION as u8 - 1;
+        assert!(Cache::decode(&outdated).is_none());
+        assert!(Cache::decode(&bytes[MAGIC.len()..]).is_none());
+    }
+
+    #[test]
+    fn oversized_counts() {
+        let mut writer = Writer(Vec::new());
+        writer.uint(u64::MAX);
+        writer.uint(1);
+        assert_eq!(Reader(&writer.0).addrs(0), None);
+        assert_eq!(Reader(&writer.0).bytes(), None);
+
+        let mut writer = Writer(Vec::new());
+        writer.uint(3);
+        writer.0.extend_from_slice(&[1, 2]);
+        assert_eq!(Reader(&writer.0).addrs(0), None);
+        assert_eq!(Reader(&writer.0).bytes(), None);
+
+        // addresses past the end of the address space
+        let mut writer = Writer(Vec::new());
+        writer.addrs(0, [1, usize::MAX].into_iter());
+        assert_eq!(Reader(&writer.0).addrs(0), Some(vec![1, usize::MAX]));
+        assert_eq!(Reader(&writer.0).addrs(1), None);
+    }
+}
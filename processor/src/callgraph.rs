@@ -0,0 +1,105 @@
+//! Whole-program call graph, built from calls to known addresses.
+
+use crate::Processor;
+use decoder::ir;
+use processor_shared::PhysAddr;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Which functions call which.
+#[derive(Debug, Default)]
+pub struct CallGraph {
+    /// Functions called by each function.
+    callees: BTreeMap<PhysAddr, BTreeSet<PhysAddr>>,
+    /// Functions calling each function.
+    callers: BTreeMap<PhysAddr, BTreeSet<PhysAddr>>,
+}
+
+impl CallGraph {
+    /// Connect every call site's function to the function it calls.
+    pub(crate) fn build(processor: &Processor, calls: &[(PhysAddr, PhysAddr)]) -> Self {
+        let mut this = Self::default();
+
+        for &(site, target) in calls {
+            if let Some(caller) = processor.function_start(site) {
+                this.callees.entry(caller).or_default().insert(target);
+                this.callers.entry(target).or_default().insert(caller);
+            }
+        }
+
+        this
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.callees.is_empty()
+    }
+
+    /// Number of distinct caller-callee pairs.
+    pub fn edge_count(&self) -> usize {
+        self.callees.values().map(BTreeSet::len).sum()
+    }
+
+    /// Functions calling the function at an address.
+    pub fn callers(&self, addr: PhysAddr) -> impl Iterator<Item = PhysAddr> + '_ {
+        self.callers.get(&addr).into_iter().flatten().copied()
+    }
+
+    /// Functions called by the function at an address.
+    pub fn callees(&self, addr: PhysAddr) -> impl Iterator<Item = PhysAddr> + '_ {
+        self.callees.get(&addr).into_iter().flatten().copied()
+    }
+
+    /// Functions that either call or are called by another function, sorted by address.
+    pub fn functions(&self) -> impl Iterator<Item = PhysAddr> + '_ {
+        let callers: BTreeSet<PhysAddr> = self.callees.keys().copied().collect();
+        let callees: BTreeSet<PhysAddr> = self.callers.keys().copied().collect();
+        callers.into_iter().chain(callees).collect::<BTreeSet<_>>().into_iter()
+    }
+}
+
+impl Processor {
+    /// Address of every call to a known address along with its target.
+    pub(crate) fn calls(&self) -> Vec<(PhysAddr, PhysAddr)> {
+        let mut calls = Vec::new();
+
+        for instruction in self.instructions_from(0) {
+            for stmt in self.instruction_ir(&instruction.item) {
+                if let ir::Stmt::Call(ir::Expr::Const(addr)) = stmt {
+                    calls.push((instruction.addr, addr as PhysAddr));
+                }
+            }
+        }
+
+        calls
+    }
+
+    /// Start of the function containing an address.
+    pub(crate) fn function_start(&self, addr: PhysAddr) -> Option<PhysAddr> {
+        let syms = &self.index.syms;
+        let idx = match syms.search(addr) {
+            Ok(idx) => idx,
+            Err(idx) => idx.checked_sub(1)?,
+        };
+
+        // functions don't cross sections
+        let start = syms[idx].addr;
+        let section = self.section_by_addr(addr)?;
+        match section.start <= start {
+            true => Some(start),
+            false => None,
+        }
+    }
+
+    /// Functions calling the function at an address.
+    pub fn callers(&self, addr: PhysAddr) -> impl Iterator<Item = PhysAddr> + '_ {
+        self.call_graph.callers(addr)
+    }
+
+    /// Functions called by the function at an address.
+    pub fn callees(&self, addr: PhysAddr) -> impl Iterator<Item = PhysAddr> + '_ {
+        self.call_graph.callees(addr)
+    }
+
+    pub fn call_graph(&self) -> &CallGraph {
+        &self.call_graph
+    }
+}
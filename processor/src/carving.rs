@@ -0,0 +1,210 @@
+//! Files embedded in a binary, such as the archives and images of a firmware image or the
+//! executables an installer drops.
+//!
+//! Every offset holding the signature of a format is checked further to rule out coincidences. The
+//! extent of a file is found by walking its structure, after which scanning continues past it so
+//! that the parts of a file aren't reported on their own.
+
+use crate::overlay;
+use std::fmt;
+use std::io::Read;
+use std::ops::Range;
+
+/// Size of a ZIP's end of central directory record without its comment.
+const EOCD_SIZE: usize = 22;
+
+const PEM_BEGIN: &[u8] = b"-----BEGIN CERTIFICATE-----";
+const PEM_END: &[u8] = b"-----END CERTIFICATE-----";
+
+/// Size of the file the data starts with, if it's valid.
+type Sizer = fn(&[u8]) -> Option<usize>;
+
+/// Magic bytes a file starts with, along with a function finding its size.
+const SIGNATURES: &[(&[u8], FileKind, Sizer)] = &[
+    (b"PK\x03\x04", FileKind::Zip, zip_size),
+    (b"\x1F\x8B", FileKind::Gzip, gzip_size),
+    (b"\x89PNG\r\n\x1A\n", FileKind::Png, png_size),
+    (b"\x7FELF", FileKind::Elf, elf_size),
+    (b"MZ", FileKind::Pe, pe_size),
+    (&[0x30, 0x82], FileKind::Certificate, der_size),
+    (PEM_BEGIN, FileKind::Certificate, pem_size),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    Zip,
+    Gzip,
+    Png,
+    Elf,
+    Pe,
+    /// X.509 certificate, either DER or PEM encoded.
+    Certificate,
+}
+
+impl FileKind {
+    /// Extension of a file holding data of this kind.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Zip => "zip",
+            Self::Gzip => "gz",
+            Self::Png => "png",
+            Self::Elf => "elf",
+            Self::Pe => "exe",
+            Self::Certificate => "crt",
+        }
+    }
+}
+
+impl fmt::Display for FileKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Zip => "ZIP archive",
+            Self::Gzip => "gzip data",
+            Self::Png => "PNG image",
+            Self::Elf => "ELF binary",
+            Self::Pe => "PE binary",
+            Self::Certificate => "X.509 certificate",
+        })
+    }
+}
+
+/// File found inside of the binary.
+#[derive(Debug, Clone)]
+pub struct Embedded {
+    /// Offset into the binary.
+    pub offset: usize,
+    pub size: usize,
+    pub kind: FileKind,
+}
+
+impl Embedded {
+    pub fn range(&self) -> Range<usize> {
+        self.offset..self.offset + self.size
+    }
+
+    /// Name to extract the file as, which is unique within a binary.
+    pub fn file_name(&self) -> String {
+        format!("{:X}.{}", self.offset, self.kind.extension())
+    }
+}
+
+fn u16_le(data: &[u8], offset: usize) -> Option<usize> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_le_bytes([bytes[0], bytes[1]]) as usize)
+}
+
+fn u32_be(data: &[u8], offset: usize) -> Option<usize> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_be_bytes(bytes.try_into().unwrap()) as usize)
+}
+
+/// Up to the end of central directory record, which follows the data of every entry.
+fn zip_size(data: &[u8]) -> Option<usize> {
+    // the version needed to extract must be sensible, e.g. 2.0 is stored as 20
+    if u16_le(data, 4)? > 63 {
+        return None;
+    }
+
+    let eocd = data.windows(4).position(|window| window == b"PK\x05\x06")?;
+    let comment = u16_le(data, eocd + 20)?;
+    let size = eocd + EOCD_SIZE + comment;
+    (size <= data.len()).then_some(size)
+}
+
+/// Up to the trailer of the first member, which requires inflating it.
+fn gzip_size(data: &[u8]) -> Option<usize> {
+    let (method, flags) = (*data.get(2)?, *data.get(3)?);
+    if method != 8 || flags & 0xE0 != 0 {
+        return None;
+    }
+
+    let mut decoder = flate2::bufread::GzDecoder::new(data);
+    std::io::copy(&mut decoder.by_ref(), &mut std::io::sink()).ok()?;
+    Some(data.len() - decoder.into_inner().len())
+}
+
+/// Up to the end of the `IEND` chunk.
+fn png_size(data: &[u8]) -> Option<usize> {
+    let mut offset = 8;
+    loop {
+        let len = u32_be(data, offset)?;
+        let kind = data.get(offset + 4..offset + 8)?;
+        if !kind.iter().all(u8::is_ascii_alphabetic) {
+            return None;
+        }
+
+        // length, type, data and checksum
+        offset = offset.checked_add(len + 12)?;
+        if offset > data.len() {
+            return None;
+        }
+
+        if kind == b"IEND" {
+            return Some(offset);
+        }
+    }
+}
+
+/// Up to the end of the image, as a PE or ELF describes it.
+fn object_size(data: &[u8]) -> Option<usize> {
+    let obj = object::File::parse(data).ok()?;
+    let size = overlay::image_end(&obj, data)?;
+    (size <= data.len()).then_some(size)
+}
+
+fn elf_size(data: &[u8]) -> Option<usize> {
+    // class, byte order and version
+    if !matches!(data.get(4..7)?, [1 | 2, 1 | 2, 1]) {
+        return None;
+    }
+
+    object_size(data)
+}
+
+fn pe_size(data: &[u8]) -> Option<usize> {
+    let header = u16_le(data, 0x3C)? | u16_le(data, 0x3E)? << 16;
+    if data.get(header..header + 4)? != b"PE\0\0" {
+        return None;
+    }
+
+    object_size(data)
+}
+
+/// DER encoded certificate, which is a sequence starting with another sequence and its version.
+fn der_size(data: &[u8]) -> Option<usize> {
+    if data.get(4..6)? != [0x30, 0x82] || data.get(8..10)? != [0xA0, 0x03] {
+        return None;
+    }
+
+    let size = 4 + u16::from_be_bytes([*data.get(2)?, *data.get(3)?]) as usize;
+    (size <= data.len()).then_some(size)
+}
+
+fn pem_size(data: &[u8]) -> Option<usize> {
+    let end = data.windows(PEM_END.len()).position(|window| window == PEM_END)?;
+    Some(end + PEM_END.len())
+}
+
+/// Files embedded in a binary, not including the binary itself at offset zero.
+pub fn scan(binary: &[u8]) -> Vec<Embedded> {
+    let mut embedded = Vec::new();
+    let mut offset = 1;
+
+    while offset < binary.len() {
+        let data = &binary[offset..];
+        let found = SIGNATURES
+            .iter()
+            .filter(|(magic, ..)| data.starts_with(magic))
+            .find_map(|(_, kind, size)| Some((size(data)?, *kind)));
+
+        match found {
+            Some((size, kind)) => {
+                embedded.push(Embedded { offset, size, kind });
+                offset += size.max(1);
+            }
+            None => offset += 1,
+        }
+    }
+
+    embedded
+}
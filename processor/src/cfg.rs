@@ -0,0 +1,621 @@
+//! Control flow graphs of functions.
+//!
+//! Control flow is recovered from the textual form of instructions, so only architectures whose
+//! branch instructions are recognized can be analyzed. On top of the graph, the dominator tree
+//! gives the natural loops of a function: a jump to a block that dominates the jumping block goes
+//! back to the header of a loop.
+
+use crate::Processor;
+use object::Architecture;
+use processor_shared::{PhysAddr, SectionKind};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::ops::Range;
+
+/// Functions with more instructions than this aren't analyzed, to keep the ui responsive.
+const MAX_INSTRUCTIONS: usize = 16384;
+
+/// Instructions compilers pad functions with, which are never meant to be reached.
+const PADDING: &[&str] = &["nop", "int3", "ud2"];
+
+#[derive(Debug)]
+pub enum Error {
+    /// Branches of the architecture aren't recognized.
+    UnsupportedArchitecture(Architecture),
+    /// There isn't a decoded instruction at the address.
+    NoInstructions(PhysAddr),
+    /// Function has more than [`MAX_INSTRUCTIONS`] instructions.
+    TooLarge(PhysAddr),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedArchitecture(arch) => {
+                f.write_fmt(format_args!("Control flow of {arch:?} isn't supported."))
+            }
+            Self::NoInstructions(addr) => {
+                f.write_fmt(format_args!("There are no instructions at {addr:#x}."))
+            }
+            Self::TooLarge(addr) => f.write_fmt(format_args!(
+                "Function at {addr:#x} has more than {MAX_INSTRUCTIONS} instructions."
+            )),
+        }
+    }
+}
+
+/// Words that may precede an x86 mnemonic.
+const X86_PREFIXES: &[&str] = &[
+    "lock", "rep", "repe", "repz", "repne", "repnz", "bnd", "notrack", "xacquire", "xrelease",
+];
+
+/// Decoded instruction split into its mnemonic and operands.
+#[derive(Debug, Clone)]
+pub struct Inst {
+    pub addr: PhysAddr,
+    /// Address of the instruction that follows.
+    pub end: PhysAddr,
+    /// Prefixes such as `lock` or `rep`.
+    pub prefixes: Vec<String>,
+    pub mnemonic: String,
+    pub operands: Vec<String>,
+    /// Where an indirect call or jump goes, if it's known, see [`Processor::indirect_targets`].
+    pub targets: Vec<PhysAddr>,
+}
+
+impl Inst {
+    pub(crate) fn new(addr: PhysAddr, end: PhysAddr, mut text: &str) -> Self {
+        let mut prefixes = Vec::new();
+        let mnemonic = loop {
+            text = text.trim_start();
+            let (word, rest) = text.split_once(' ').unwrap_or((text, ""));
+            text = rest;
+
+            if !X86_PREFIXES.contains(&word) || rest.is_empty() {
+                break word.to_string();
+            }
+
+            prefixes.push(word.to_string());
+        };
+
+        let operands = text
+            .split(", ")
+            .map(|operand| operand.trim().to_string())
+            .filter(|operand| !operand.is_empty())
+            .collect();
+
+        Self {
+            addr,
+            end,
+            prefixes,
+            mnemonic,
+            operands,
+            targets: Vec::new(),
+        }
+    }
+
+    /// Textual form of the instruction as shown in the listing.
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        for prefix in self.prefixes.iter() {
+            text += prefix;
+            text += " ";
+        }
+
+        text += &self.mnemonic;
+        if !self.operands.is_empty() {
+            text += " ";
+            text += &self.operands.join(", ");
+        }
+
+        text
+    }
+}
+
+/// Where a jump or call ends up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    /// Address within the function.
+    Local(PhysAddr),
+    /// Another function, by name.
+    External(String),
+    /// Target that's only known at runtime.
+    Indirect(String),
+}
+
+/// How control leaves a basic block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Exit {
+    /// Execution continues at the next block.
+    Fallthrough(PhysAddr),
+    /// Unconditional jump, the last instruction of the block.
+    Jump(Target),
+    /// Conditional jump, the last instruction of the block.
+    Branch { target: Target, next: PhysAddr },
+    /// Return to the caller.
+    Return,
+    /// Execution doesn't continue, e.g. after a trap or an undecodable instruction.
+    Halt,
+}
+
+/// Sequence of instructions that's only entered at the start and left at the end.
+#[derive(Debug, Clone)]
+pub struct BasicBlock {
+    pub start: PhysAddr,
+    pub end: PhysAddr,
+    pub insts: Vec<Inst>,
+    pub exit: Exit,
+}
+
+impl BasicBlock {
+    /// Addresses of blocks within the function that may execute next.
+    pub fn successors(&self) -> Vec<PhysAddr> {
+        match &self.exit {
+            Exit::Fallthrough(next) => vec![*next],
+            Exit::Jump(Target::Local(target)) => vec![*target],
+            Exit::Branch {
+                target: Target::Local(target),
+                next,
+            } => vec![*target, *next],
+            Exit::Branch { next, .. } => vec![*next],
+            Exit::Jump(..) | Exit::Return | Exit::Halt => Vec::new(),
+        }
+    }
+}
+
+/// Natural loop of a function, the blocks from which a jump back to its header can be reached
+/// without passing through the header.
+#[derive(Debug, Clone)]
+pub struct Loop {
+    /// Start of the block the loop is entered through.
+    pub header: PhysAddr,
+    /// Starts of the blocks jumping back to the header.
+    pub latches: Vec<PhysAddr>,
+    /// Starts of the blocks in the loop, including the header.
+    pub body: BTreeSet<PhysAddr>,
+    /// Number of loops this one is nested in.
+    pub depth: usize,
+}
+
+/// Control flow graph of a single function.
+#[derive(Debug)]
+pub struct Cfg {
+    /// Entrypoint of the function.
+    pub addr: PhysAddr,
+    /// Name of the function's symbol, or a generated name.
+    pub name: String,
+    /// Blocks reachable from the entrypoint, sorted by address.
+    pub blocks: Vec<BasicBlock>,
+    /// Instructions within the function that no path from the entrypoint reaches, besides
+    /// padding. Empty if the function has an indirect jump that isn't resolved, as the blocks it
+    /// jumps to can't be told apart from dead code.
+    pub unreachable: Vec<Range<PhysAddr>>,
+}
+
+impl Cfg {
+    /// Index of the block starting at an address.
+    pub fn block_by_addr(&self, addr: PhysAddr) -> Option<usize> {
+        self.blocks.binary_search_by_key(&addr, |block| block.start).ok()
+    }
+
+    /// Indices of the blocks that may execute before each block.
+    pub fn predecessors(&self) -> Vec<Vec<usize>> {
+        let mut preds = vec![Vec::new(); self.blocks.len()];
+        for (idx, block) in self.blocks.iter().enumerate() {
+            for succ in block.successors() {
+                if let Some(succ) = self.block_by_addr(succ) {
+                    preds[succ].push(idx);
+                }
+            }
+        }
+
+        preds
+    }
+
+    /// Indices of the blocks in the order they're finished by a depth-first search from the
+    /// entrypoint.
+    fn postorder(&self, entry: usize) -> Vec<usize> {
+        let mut visited = vec![false; self.blocks.len()];
+        let mut order = Vec::with_capacity(self.blocks.len());
+        let mut stack = vec![(entry, self.blocks[entry].successors().into_iter())];
+        visited[entry] = true;
+
+        while let Some((idx, succs)) = stack.last_mut() {
+            let next = succs
+                .by_ref()
+                .filter_map(|succ| self.block_by_addr(succ))
+                .find(|&succ| !std::mem::replace(&mut visited[succ], true));
+
+            match next {
+                Some(succ) => stack.push((succ, self.blocks[succ].successors().into_iter())),
+                None => {
+                    order.push(*idx);
+                    stack.pop();
+                }
+            }
+        }
+
+        order
+    }
+
+    /// Immediate dominator of every block by index, the entrypoint being its own.
+    ///
+    /// Uses the iterative algorithm of Cooper, Harvey and Kennedy, which converges in a few
+    /// passes over the graphs of real functions.
+    pub fn dominators(&self) -> Vec<usize> {
+        let entry = self.block_by_addr(self.addr).unwrap_or(0);
+        let postorder = self.postorder(entry);
+        let mut rank = vec![usize::MAX; self.blocks.len()];
+        for (pos, &idx) in postorder.iter().enumerate() {
+            rank[idx] = pos;
+        }
+
+        let preds = self.predecessors();
+        let mut idom = vec![usize::MAX; self.blocks.len()];
+        idom[entry] = entry;
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &idx in postorder.iter().rev().filter(|&&idx| idx != entry) {
+                let mut processed = preds[idx].iter().copied().filter(|&p| idom[p] != usize::MAX);
+                let first = match processed.next() {
+                    Some(first) => first,
+                    None => continue,
+                };
+
+                let new = processed.fold(first, |mut a, mut b| {
+                    while a != b {
+                        while rank[a] < rank[b] {
+                            a = idom[a];
+                        }
+                        while rank[b] < rank[a] {
+                            b = idom[b];
+                        }
+                    }
+                    a
+                });
+
+                if idom[idx] != new {
+                    idom[idx] = new;
+                    changed = true;
+                }
+            }
+        }
+
+        // blocks are only kept if they're reachable, so each one has a dominator
+        for (idx, dom) in idom.iter_mut().enumerate() {
+            if *dom == usize::MAX {
+                *dom = idx;
+            }
+        }
+
+        idom
+    }
+
+    /// Natural loops of the function, sorted by their header.
+    pub fn loops(&self) -> Vec<Loop> {
+        let idom = self.dominators();
+        let dominates = |dom: usize, mut idx: usize| loop {
+            if idx == dom {
+                return true;
+            }
+
+            match idom[idx] {
+                parent if parent == idx => return false,
+                parent => idx = parent,
+            }
+        };
+
+        // edges to a block dominating the block they leave go back to a loop's header
+        let mut latches: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for (idx, block) in self.blocks.iter().enumerate() {
+            for succ in block.successors() {
+                match self.block_by_addr(succ) {
+                    Some(header) if dominates(header, idx) => {
+                        latches.entry(header).or_default().push(idx)
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let preds = self.predecessors();
+        let mut loops: Vec<Loop> = latches
+            .into_iter()
+            .map(|(header, latches)| {
+                let mut body = BTreeSet::from([header]);
+                let mut queue = latches.clone();
+                while let Some(idx) = queue.pop() {
+                    if body.insert(idx) {
+                        queue.extend(preds[idx].iter().copied());
+                    }
+                }
+
+                Loop {
+                    header: self.blocks[header].start,
+                    latches: latches.iter().map(|&idx| self.blocks[idx].start).collect(),
+                    body: body.into_iter().map(|idx| self.blocks[idx].start).collect(),
+                    depth: 0,
+                }
+            })
+            .collect();
+
+        for idx in 0..loops.len() {
+            let header = loops[idx].header;
+            loops[idx].depth = loops
+                .iter()
+                .filter(|other| other.header != header && other.body.contains(&header))
+                .count();
+        }
+
+        loops
+    }
+
+    /// Jumps going back to the header of a loop, from the address of the jumping instruction
+    /// to the header.
+    pub fn back_edges(&self) -> Vec<(PhysAddr, PhysAddr)> {
+        let mut edges = Vec::new();
+        for def in self.loops() {
+            for latch in def.latches {
+                let block = &self.blocks[self.block_by_addr(latch).unwrap()];
+                if let Some(inst) = block.insts.last() {
+                    edges.push((inst.addr, def.header));
+                }
+            }
+        }
+
+        edges.sort_unstable();
+        edges
+    }
+}
+
+/// How an instruction affects control flow.
+enum Flow {
+    Next,
+    Jump(String),
+    Branch(String),
+    Return,
+    Halt,
+}
+
+fn x86_flow(inst: &Inst) -> Flow {
+    let operand = || inst.operands.first().cloned().unwrap_or_default();
+
+    match inst.mnemonic.as_str() {
+        "jmp" | "jmpf" => Flow::Jump(operand()),
+        "ret" | "retf" | "iret" | "iretd" | "iretq" => Flow::Return,
+        "hlt" | "ud0" | "ud1" | "ud2" => Flow::Halt,
+        "loop" | "loope" | "loopz" | "loopne" | "loopnz" => Flow::Branch(operand()),
+        mnemonic if mnemonic.starts_with('j') => Flow::Branch(operand()),
+        _ => Flow::Next,
+    }
+}
+
+impl Processor {
+    /// Whether branches of the architecture are recognized.
+    fn supports_cfg(&self) -> bool {
+        matches!(
+            self.arch,
+            Architecture::X86_64 | Architecture::X86_64_X32 | Architecture::I386
+        )
+    }
+
+    /// Control flow graphs of every function that can be analyzed.
+    pub(crate) fn function_cfgs(&self) -> impl Iterator<Item = Cfg> + '_ {
+        let mut starts = Vec::new();
+        if self.supports_cfg() {
+            starts.extend(self.index.functions().map(|func| func.addr));
+            starts.dedup();
+        }
+
+        starts
+            .into_iter()
+            .filter(|&start| {
+                self.section_by_addr(start).is_some_and(|s| s.kind == SectionKind::Code)
+            })
+            .filter_map(|start| self.cfg(start).ok().filter(|cfg| cfg.addr == start))
+    }
+
+    /// Header of the loop the instruction at an address jumps back to.
+    pub fn loop_header(&self, addr: PhysAddr) -> Option<PhysAddr> {
+        self.back_edges.get(&addr).copied()
+    }
+
+    /// Address and name of the function that contains an address.
+    pub fn function_bounds(&self, addr: PhysAddr) -> Option<(PhysAddr, PhysAddr, String)> {
+        let syms = &self.index.syms;
+        let idx = match syms.search(addr) {
+            Ok(idx) => idx,
+            Err(idx) => idx.checked_sub(1)?,
+        };
+
+        let start = syms[idx].addr;
+        let section = self.section_by_addr(start)?;
+
+        // a function at the start of a section shares its address with the section's symbol
+        let first = syms[..idx].iter().rposition(|sym| sym.addr != start).map_or(0, |pos| pos + 1);
+        let at_start = syms[first..].iter().take_while(|sym| sym.addr == start);
+        let name = at_start
+            .map(|sym| sym.item.as_str())
+            .find(|name| !self.sections.iter().any(|section| section.name == *name))
+            .unwrap_or(syms[idx].item.as_str());
+
+        let end = syms[idx..].iter().find(|sym| sym.addr > start);
+        let end = end.map_or(section.end, |sym| sym.addr.min(section.end));
+        Some((start, end, name.to_string()))
+    }
+
+    /// Interpret the address of a jump or call.
+    fn jump_target(&self, operand: &str, start: PhysAddr, end: PhysAddr, name: &str) -> Target {
+        if let Some(symbol) = operand.strip_prefix('<').and_then(|s| s.strip_suffix('>')) {
+            return match symbol == name {
+                true => Target::Local(start),
+                false => Target::External(symbol.to_string()),
+            };
+        }
+
+        match operand.strip_prefix("0x").map(|hex| PhysAddr::from_str_radix(hex, 16)) {
+            Some(Ok(addr)) if (start..end).contains(&addr) => Target::Local(addr),
+            Some(Ok(addr)) => match self.index.get_sym_by_addr(addr) {
+                Some(symbol) => Target::External(symbol.as_str().to_string()),
+                None => Target::External(format!("sub_{addr:x}")),
+            },
+            _ => Target::Indirect(operand.to_string()),
+        }
+    }
+
+    /// Interpret the address of a jump, using the target of an indirect jump if there's only
+    /// one.
+    fn resolved_target(
+        &self,
+        inst: &Inst,
+        operand: &str,
+        start: PhysAddr,
+        end: PhysAddr,
+        name: &str,
+    ) -> Target {
+        let target = self.jump_target(operand, start, end, name);
+        match (&target, &inst.targets[..]) {
+            (Target::Indirect(..), &[resolved]) => {
+                self.jump_target(&format!("{resolved:#x}"), start, end, name)
+            }
+            _ => target,
+        }
+    }
+
+    /// Recover the control flow graph of the function containing an address.
+    pub fn cfg(&self, addr: PhysAddr) -> Result<Cfg, Error> {
+        if !self.supports_cfg() {
+            return Err(Error::UnsupportedArchitecture(self.arch));
+        }
+
+        let (start, end, name) = self.function_bounds(addr).ok_or(Error::NoInstructions(addr))?;
+        let mut instructions = self.instructions_from(start).peekable();
+        if instructions.peek().map(|instruction| instruction.addr) != Some(start) {
+            return Err(Error::NoInstructions(start));
+        }
+
+        let mut insts = Vec::new();
+        for instruction in instructions {
+            if instruction.addr >= end {
+                break;
+            }
+
+            if insts.len() == MAX_INSTRUCTIONS {
+                return Err(Error::TooLarge(start));
+            }
+
+            let tokens = self.instruction_tokens(&instruction.item, &self.index);
+            let text = String::from_iter(tokens.iter().map(|token| &token.text[..]));
+            let width = self.instruction_width(&instruction.item);
+            let mut inst = Inst::new(instruction.addr, instruction.addr + width, &text);
+            inst.targets = self.indirect_targets(instruction.addr).to_vec();
+            insts.push(inst);
+        }
+
+        // blocks start at the entrypoint, at jump targets and after any jump
+        let mut leaders = BTreeSet::from([start]);
+        let mut flows = Vec::with_capacity(insts.len());
+        for inst in insts.iter() {
+            let flow = x86_flow(inst);
+            match &flow {
+                Flow::Jump(operand) | Flow::Branch(operand) => {
+                    if let Target::Local(addr) =
+                        self.resolved_target(inst, operand, start, end, &name)
+                    {
+                        leaders.insert(addr);
+                    }
+                    leaders.insert(inst.end);
+                }
+                Flow::Return | Flow::Halt => {
+                    leaders.insert(inst.end);
+                }
+                Flow::Next => {}
+            }
+            flows.push(flow);
+        }
+
+        let mut blocks: Vec<BasicBlock> = Vec::new();
+        for (inst, flow) in insts.into_iter().zip(flows) {
+            let block = match blocks.last_mut() {
+                Some(block) if !leaders.contains(&inst.addr) && block.end == inst.addr => block,
+                _ => {
+                    blocks.push(BasicBlock {
+                        start: inst.addr,
+                        end: inst.addr,
+                        insts: Vec::new(),
+                        exit: Exit::Halt,
+                    });
+                    blocks.last_mut().unwrap()
+                }
+            };
+
+            block.end = inst.end;
+            block.exit = match flow {
+                Flow::Next => Exit::Fallthrough(inst.end),
+                Flow::Jump(operand) => {
+                    Exit::Jump(self.resolved_target(&inst, &operand, start, end, &name))
+                }
+                Flow::Branch(operand) => Exit::Branch {
+                    target: self.resolved_target(&inst, &operand, start, end, &name),
+                    next: inst.end,
+                },
+                Flow::Return => Exit::Return,
+                Flow::Halt => Exit::Halt,
+            };
+            block.insts.push(inst);
+        }
+
+        // only keep the blocks that are reachable from the entrypoint
+        let mut reachable = BTreeSet::new();
+        let mut queue = vec![start];
+        while let Some(addr) = queue.pop() {
+            if !reachable.insert(addr) {
+                continue;
+            }
+
+            match blocks.binary_search_by_key(&addr, |block| block.start) {
+                Ok(idx) => queue.extend(blocks[idx].successors()),
+                Err(..) => {
+                    reachable.remove(&addr);
+                }
+            }
+        }
+
+        let (blocks, dead): (Vec<BasicBlock>, Vec<BasicBlock>) =
+            blocks.into_iter().partition(|block| reachable.contains(&block.start));
+        if blocks.is_empty() {
+            return Err(Error::NoInstructions(start));
+        }
+
+        let unresolved = blocks.iter().any(|block| {
+            matches!(
+                block.exit,
+                Exit::Jump(Target::Indirect(..))
+                    | Exit::Branch {
+                        target: Target::Indirect(..),
+                        ..
+                    }
+            )
+        });
+
+        let mut unreachable: Vec<Range<PhysAddr>> = Vec::new();
+        if !unresolved {
+            let dead = dead.iter().flat_map(|block| block.insts.iter());
+            for inst in dead.filter(|inst| !PADDING.contains(&inst.mnemonic.as_str())) {
+                match unreachable.last_mut() {
+                    Some(range) if range.end == inst.addr => range.end = inst.end,
+                    _ => unreachable.push(inst.addr..inst.end),
+                }
+            }
+        }
+
+        Ok(Cfg {
+            addr: start,
+            name,
+            blocks,
+            unreachable,
+        })
+    }
+}
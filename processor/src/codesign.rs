@@ -0,0 +1,821 @@
+//! Authenticode signatures of PE's and code signatures of Mach-O's.
+//!
+//! Both embed a PKCS#7 message holding the signer's certificate chain and the time of signing.
+//! The signature of the message itself isn't checked, as that requires trusting a root certificate.
+//! What is checked is whether the digest the message signs still matches the bytes of the binary,
+//! so that patching or tampering with a signed binary is noticed.
+
+use md5::Md5;
+use object::pe::IMAGE_DIRECTORY_ENTRY_SECURITY as SECURITY;
+use object::read::macho::{MachHeader, MachOFile};
+use object::read::pe::{ImageNtHeaders, PeFile};
+use object::read::File as ObjectFile;
+use object::{macho, Endianness, LittleEndian as LE};
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use std::fmt;
+use std::ops::Range;
+
+/// Type of a certificate holding a PKCS#7 message.
+const WIN_CERT_TYPE_PKCS_SIGNED_DATA: u16 = 2;
+
+/// Offset of the checksum into the optional header, which isn't covered by the digest.
+const CHECKSUM_OFFSET: usize = 64;
+
+/// Magic of the blob holding all other blobs of a code signature.
+const CSMAGIC_EMBEDDED_SIGNATURE: u32 = 0xFADE0CC0;
+const CSMAGIC_CODEDIRECTORY: u32 = 0xFADE0C02;
+const CSMAGIC_BLOBWRAPPER: u32 = 0xFADE0B01;
+
+/// Code directory flag of signatures without a signer.
+const CS_ADHOC: u32 = 0x2;
+
+/// First code directory version that includes a team identifier.
+const CS_SUPPORTSTEAMID: u32 = 0x20200;
+
+const CS_HASHTYPE_SHA1: u8 = 1;
+const CS_HASHTYPE_SHA256: u8 = 2;
+const CS_HASHTYPE_SHA256_TRUNCATED: u8 = 3;
+const CS_HASHTYPE_SHA384: u8 = 4;
+
+/// Object identifiers in their encoded form.
+mod oid {
+    pub const SIGNED_DATA: &[u8] = &[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x07, 0x02];
+    pub const SIGNING_TIME: &[u8] = &[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x09, 0x05];
+    pub const COUNTER_SIGNATURE: &[u8] = &[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x09, 0x06];
+    pub const TIMESTAMP_TOKEN: &[u8] = &[
+        0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x09, 0x10, 0x02, 0x0E,
+    ];
+    /// Microsoft's own attribute for RFC 3161 timestamps.
+    pub const MS_TIMESTAMP: &[u8] = &[0x2B, 0x06, 0x01, 0x04, 0x01, 0x82, 0x37, 0x03, 0x03, 0x01];
+    pub const COMMON_NAME: &[u8] = &[0x55, 0x04, 0x03];
+    pub const ORGANIZATION: &[u8] = &[0x55, 0x04, 0x0A];
+    pub const MD5: &[u8] = &[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x02, 0x05];
+    pub const SHA1: &[u8] = &[0x2B, 0x0E, 0x03, 0x02, 0x1A];
+    pub const SHA256: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01];
+    pub const SHA384: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x02];
+    pub const SHA512: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x03];
+}
+
+/// ASN.1 tags.
+mod tag {
+    pub const INTEGER: u8 = 0x02;
+    pub const OCTET_STRING: u8 = 0x04;
+    pub const OID: u8 = 0x06;
+    pub const UTC_TIME: u8 = 0x17;
+    pub const GENERALIZED_TIME: u8 = 0x18;
+    pub const SEQUENCE: u8 = 0x30;
+    pub const SET: u8 = 0x31;
+    pub const CONTEXT_0: u8 = 0xA0;
+    pub const CONTEXT_1: u8 = 0xA1;
+}
+
+/// Nesting of BER elements with an indefinite length that is followed before giving up.
+const MAX_DEPTH: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DigestAlgorithm {
+    Md5,
+    Sha1,
+    Sha256,
+    /// SHA-256 cut off after 20 bytes, used by older code signatures.
+    Sha256Truncated,
+    Sha384,
+    Sha512,
+}
+
+impl DigestAlgorithm {
+    fn from_oid(oid: &[u8]) -> Option<Self> {
+        match oid {
+            oid::MD5 => Some(Self::Md5),
+            oid::SHA1 => Some(Self::Sha1),
+            oid::SHA256 => Some(Self::Sha256),
+            oid::SHA384 => Some(Self::Sha384),
+            oid::SHA512 => Some(Self::Sha512),
+            _ => None,
+        }
+    }
+
+    fn from_hash_type(hash_type: u8) -> Option<Self> {
+        match hash_type {
+            CS_HASHTYPE_SHA1 => Some(Self::Sha1),
+            CS_HASHTYPE_SHA256 => Some(Self::Sha256),
+            CS_HASHTYPE_SHA256_TRUNCATED => Some(Self::Sha256Truncated),
+            CS_HASHTYPE_SHA384 => Some(Self::Sha384),
+            _ => None,
+        }
+    }
+
+    fn digest<'a>(self, chunks: impl IntoIterator<Item = &'a [u8]>) -> Vec<u8> {
+        fn digest<'a, D: Digest>(chunks: impl IntoIterator<Item = &'a [u8]>) -> Vec<u8> {
+            let mut hasher = D::new();
+            for chunk in chunks {
+                hasher.update(chunk);
+            }
+            hasher.finalize().to_vec()
+        }
+
+        match self {
+            Self::Md5 => digest::<Md5>(chunks),
+            Self::Sha1 => digest::<Sha1>(chunks),
+            Self::Sha256 => digest::<Sha256>(chunks),
+            Self::Sha256Truncated => digest::<Sha256>(chunks)[..20].to_vec(),
+            Self::Sha384 => digest::<Sha384>(chunks),
+            Self::Sha512 => digest::<Sha512>(chunks),
+        }
+    }
+}
+
+impl fmt::Display for DigestAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Md5 => f.pad("MD5"),
+            Self::Sha1 => f.pad("SHA-1"),
+            Self::Sha256 => f.pad("SHA-256"),
+            Self::Sha256Truncated => f.pad("SHA-256 (truncated)"),
+            Self::Sha384 => f.pad("SHA-384"),
+            Self::Sha512 => f.pad("SHA-512"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureKind {
+    Authenticode,
+    /// Signature of Apple's `codesign`.
+    CodeSign,
+}
+
+impl fmt::Display for SignatureKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Authenticode => f.pad("Authenticode"),
+            Self::CodeSign => f.pad("codesign"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Certificate {
+    pub subject: String,
+    pub issuer: String,
+    /// Serial number in hex.
+    pub serial: String,
+    pub not_before: String,
+    pub not_after: String,
+}
+
+/// Parts of the file the signed digest covers.
+#[derive(Debug, Clone)]
+enum Coverage {
+    /// Digest of the whole file except for the checksum, the directory entry pointing to the
+    /// certificate table and the table itself, in the order they appear.
+    Authenticode {
+        excluded: [Range<usize>; 3],
+        digest: Vec<u8>,
+    },
+    /// Digest of every page up to a limit.
+    CodeDirectory {
+        page_size: usize,
+        code_limit: usize,
+        hashes: Vec<Vec<u8>>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct Signature {
+    pub kind: SignatureKind,
+    /// Signer's certificate, followed by the certificates that issued it.
+    pub chain: Vec<Certificate>,
+    /// When the signer claims to have signed the binary.
+    pub signing_time: Option<String>,
+    /// When a timestamping authority countersigned the signature.
+    pub timestamp: Option<String>,
+    pub algorithm: DigestAlgorithm,
+    /// Identifier of a Mach-O, usually its bundle ID.
+    pub identifier: Option<String>,
+    /// Apple team ID of the signer of a Mach-O.
+    pub team: Option<String>,
+    /// Whether the signature lacks a signer, like those the linker adds to Mach-O's.
+    pub adhoc: bool,
+    coverage: Coverage,
+}
+
+impl Signature {
+    /// Whether the signed digest matches the given bytes of the binary.
+    pub fn covers(&self, binary: &[u8]) -> bool {
+        match self.coverage {
+            Coverage::Authenticode {
+                ref excluded,
+                ref digest,
+            } => {
+                let mut chunks = Vec::new();
+                let mut start = 0;
+                for range in excluded {
+                    chunks.push(binary.get(start..range.start).unwrap_or(&[]));
+                    start = range.end;
+                }
+                chunks.push(binary.get(start..).unwrap_or(&[]));
+
+                self.algorithm.digest(chunks) == *digest
+            }
+            Coverage::CodeDirectory {
+                page_size,
+                code_limit,
+                ref hashes,
+            } => {
+                let code = match binary.get(..code_limit) {
+                    Some(code) => code,
+                    None => return false,
+                };
+
+                code.chunks(page_size).count() == hashes.len()
+                    && code
+                        .chunks(page_size)
+                        .zip(hashes)
+                        .all(|(page, hash)| self.algorithm.digest([page]) == *hash)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.adhoc, self.chain.first()) {
+            (true, _) => f.write_fmt(format_args!("Ad-hoc {} signature", self.kind))?,
+            (false, Some(signer)) => f.write_fmt(format_args!(
+                "{} signature by {}",
+                self.kind, signer.subject
+            ))?,
+            (false, None) => f.write_fmt(format_args!("{} signature", self.kind))?,
+        }
+
+        if let Some(time) = self.timestamp.as_ref().or(self.signing_time.as_ref()) {
+            f.write_fmt(format_args!(", signed at {time}"))?;
+        }
+
+        f.write_str(".")
+    }
+}
+
+/// Element of a BER encoded message, which DER is a subset of.
+#[derive(Debug, Clone, Copy)]
+struct Element<'a> {
+    tag: u8,
+    content: &'a [u8],
+    /// Encoding of the whole element, used for comparing names.
+    raw: &'a [u8],
+}
+
+impl<'a> Element<'a> {
+    fn parse(data: &'a [u8]) -> Option<Self> {
+        element(data, 0).map(|(element, _)| element)
+    }
+
+    fn children(&self) -> impl Iterator<Item = Element<'a>> {
+        let mut rest = self.content;
+        std::iter::from_fn(move || {
+            let (element, next) = element(rest, 0)?;
+            rest = next;
+            Some(element)
+        })
+    }
+
+    fn child(&self, idx: usize) -> Option<Element<'a>> {
+        self.children().nth(idx)
+    }
+
+    fn expect(self, tag: u8) -> Option<Self> {
+        (self.tag == tag).then_some(self)
+    }
+}
+
+/// Read an element, along with the bytes that follow it.
+fn element(data: &[u8], depth: usize) -> Option<(Element<'_>, &[u8])> {
+    let tag = *data.first()?;
+    let (header, len) = match *data.get(1)? {
+        // an indefinite length is ended by two zero bytes following the nested elements
+        0x80 => {
+            if depth == MAX_DEPTH || tag & 0x20 == 0 {
+                return None;
+            }
+
+            let mut rest = &data[2..];
+            while !rest.starts_with(&[0, 0]) {
+                rest = element(rest, depth + 1)?.1;
+            }
+
+            let end = data.len() - rest.len();
+            let element = Element {
+                tag,
+                content: &data[2..end],
+                raw: &data[..end + 2],
+            };
+
+            return Some((element, &rest[2..]));
+        }
+        len if len < 0x80 => (2, len as usize),
+        len => {
+            let count = (len & 0x7F) as usize;
+            if count > std::mem::size_of::<u32>() {
+                return None;
+            }
+
+            let bytes = data.get(2..2 + count)?;
+            (
+                2 + count,
+                bytes.iter().fold(0, |len, &byte| len << 8 | byte as usize),
+            )
+        }
+    };
+
+    let raw = data.get(..header.checked_add(len)?)?;
+    let element = Element {
+        tag,
+        content: &raw[header..],
+        raw,
+    };
+
+    Some((element, &data[raw.len()..]))
+}
+
+/// Parts of a PKCS#7 signed message.
+struct SignedData<'a> {
+    /// What's being signed, if it's embedded in the message.
+    content: Option<Element<'a>>,
+    certificates: Vec<Element<'a>>,
+    signer: Option<Element<'a>>,
+}
+
+fn signed_data(data: &[u8]) -> Option<SignedData<'_>> {
+    let info = Element::parse(data)?.expect(tag::SEQUENCE)?;
+    if info.child(0)?.expect(tag::OID)?.content != oid::SIGNED_DATA {
+        return None;
+    }
+
+    let signed = info.child(1)?.expect(tag::CONTEXT_0)?.child(0)?.expect(tag::SEQUENCE)?;
+
+    // skip the version and digest algorithms
+    let mut fields = signed.children().skip(2);
+    let encapsulated = fields.next()?.expect(tag::SEQUENCE)?;
+    let content = encapsulated
+        .child(1)
+        .and_then(|content| content.expect(tag::CONTEXT_0)?.child(0));
+
+    let mut certificates = Vec::new();
+    let mut signer = None;
+    for field in fields {
+        match field.tag {
+            tag::CONTEXT_0 => certificates.extend(field.children()),
+            tag::SET => signer = field.child(0),
+            _ => {}
+        }
+    }
+
+    Some(SignedData {
+        content,
+        certificates,
+        signer,
+    })
+}
+
+/// Value of an attribute of a signer.
+fn attribute<'a>(attributes: Option<Element<'a>>, oid: &[u8]) -> Option<Element<'a>> {
+    attributes?
+        .children()
+        .find(|attribute| attribute.child(0).is_some_and(|id| id.content == oid))?
+        .child(1)?
+        .child(0)
+}
+
+/// Signed and unsigned attributes of a signer.
+fn attributes<'a>(signer: Element<'a>) -> (Option<Element<'a>>, Option<Element<'a>>) {
+    let signed = signer.children().find(|field| field.tag == tag::CONTEXT_0);
+    let unsigned = signer.children().find(|field| field.tag == tag::CONTEXT_1);
+    (signed, unsigned)
+}
+
+fn time(element: Element) -> Option<String> {
+    let text = std::str::from_utf8(element.content).ok()?;
+    let (year, rest) = match element.tag {
+        tag::UTC_TIME => match text.get(..2)?.parse::<u32>().ok()? {
+            year if year >= 50 => (1900 + year, text.get(2..)?),
+            year => (2000 + year, text.get(2..)?),
+        },
+        tag::GENERALIZED_TIME => (text.get(..4)?.parse().ok()?, text.get(4..)?),
+        _ => return None,
+    };
+
+    let field = |idx: usize| rest.get(idx * 2..idx * 2 + 2);
+    Some(format!(
+        "{year}-{}-{} {}:{}:{} UTC",
+        field(0)?,
+        field(1)?,
+        field(2)?,
+        field(3)?,
+        field(4)?
+    ))
+}
+
+/// Time of a RFC 3161 timestamp, which is a signed message of its own.
+fn timestamp_token(token: Element) -> Option<String> {
+    let token = signed_data(token.raw)?;
+    let info = Element::parse(token.content?.expect(tag::OCTET_STRING)?.content)?;
+
+    // the time follows the version, policy, message imprint and serial number
+    time(info.child(4)?)
+}
+
+/// Common name of a distinguished name, or its organization if it lacks one.
+fn name(name: Element) -> String {
+    let value = |oid: &[u8]| {
+        name.children()
+            .flat_map(|set| set.children())
+            .find(|pair| pair.child(0).is_some_and(|id| id.content == oid))?
+            .child(1)
+    };
+
+    let value = match value(oid::COMMON_NAME).or_else(|| value(oid::ORGANIZATION)) {
+        Some(value) => value,
+        None => return "unknown".to_string(),
+    };
+
+    match value.tag {
+        // BMPString
+        0x1E => {
+            let units: Vec<u16> = value
+                .content
+                .chunks_exact(2)
+                .map(|unit| u16::from_be_bytes([unit[0], unit[1]]))
+                .collect();
+            String::from_utf16_lossy(&units)
+        }
+        _ => String::from_utf8_lossy(value.content).into_owned(),
+    }
+}
+
+/// Serial number, issuer, validity and subject of a certificate.
+fn tbs_fields(certificate: Element) -> Option<[Element; 4]> {
+    let tbs = certificate.child(0)?.expect(tag::SEQUENCE)?;
+
+    // the version is optional
+    let mut fields = tbs.children().peekable();
+    fields.next_if(|field| field.tag == tag::CONTEXT_0);
+
+    let serial = fields.next()?.expect(tag::INTEGER)?;
+    let issuer = fields.nth(1)?;
+    let validity = fields.next()?;
+    let subject = fields.next()?;
+    Some([serial, issuer, validity, subject])
+}
+
+fn certificate([serial, issuer, validity, subject]: [Element; 4]) -> Option<Certificate> {
+    Some(Certificate {
+        subject: name(subject),
+        issuer: name(issuer),
+        serial: crate::hashes::hex(serial.content),
+        not_before: time(validity.child(0)?)?,
+        not_after: time(validity.child(1)?)?,
+    })
+}
+
+/// Signer's certificate followed by its issuers, as far as they're included in the message.
+fn chain(signed: &SignedData) -> Vec<Certificate> {
+    let fields: Vec<[Element; 4]> =
+        signed.certificates.iter().filter_map(|cert| tbs_fields(*cert)).collect();
+
+    // the signer is identified by the issuer and serial number of its certificate, otherwise
+    // assume it's the certificate that didn't issue any other
+    let identifier = signed.signer.and_then(|signer| signer.child(1)?.expect(tag::SEQUENCE));
+    let leaf = match identifier {
+        Some(id) => fields.iter().position(|[serial, issuer, ..]| {
+            Some(issuer.raw) == id.child(0).map(|name| name.raw)
+                && Some(serial.raw) == id.child(1).map(|serial| serial.raw)
+        }),
+        None => fields.iter().position(|[.., subject]| {
+            !fields
+                .iter()
+                .any(|[_, issuer, _, other]| issuer.raw == subject.raw && other.raw != subject.raw)
+        }),
+    };
+
+    let mut chain = Vec::new();
+    let mut next = leaf;
+    while let Some(idx) = next {
+        if chain.contains(&idx) {
+            break;
+        }
+
+        chain.push(idx);
+        let [_, issuer, _, subject] = fields[idx];
+        next = match issuer.raw == subject.raw {
+            true => None,
+            false => fields.iter().position(|[.., subject]| subject.raw == issuer.raw),
+        };
+    }
+
+    chain.into_iter().filter_map(|idx| certificate(fields[idx])).collect()
+}
+
+/// Certificate chain, signing time and timestamp of a signed message.
+fn signer(message: &[u8]) -> (Vec<Certificate>, Option<String>, Option<String>) {
+    let signed = match signed_data(message) {
+        Some(signed) => signed,
+        None => return (Vec::new(), None, None),
+    };
+
+    let (attrs, unsigned) = match signed.signer {
+        Some(signer) => attributes(signer),
+        None => (None, None),
+    };
+
+    let signing_time = attribute(attrs, oid::SIGNING_TIME).and_then(time);
+    let timestamp = attribute(unsigned, oid::MS_TIMESTAMP)
+        .or_else(|| attribute(unsigned, oid::TIMESTAMP_TOKEN))
+        .and_then(timestamp_token)
+        .or_else(|| {
+            // older Authenticode timestamps are a countersigning signer
+            let (attrs, _) = attributes(attribute(unsigned, oid::COUNTER_SIGNATURE)?);
+            time(attribute(attrs, oid::SIGNING_TIME)?)
+        });
+
+    (chain(&signed), signing_time, timestamp)
+}
+
+fn authenticode<Pe: ImageNtHeaders>(pe: &PeFile<Pe>, binary: &[u8]) -> Option<Signature> {
+    let dir = pe.data_directory(SECURITY)?;
+
+    // the certificate table's address is an offset into the file instead of the image
+    let offset = dir.virtual_address.get(LE) as usize;
+    let table = offset..offset.checked_add(dir.size.get(LE) as usize)?;
+    let certificate = binary.get(table.clone())?;
+
+    let len = u32::from_le_bytes(certificate.get(..4)?.try_into().unwrap()) as usize;
+    let kind = u16::from_le_bytes(certificate.get(6..8)?.try_into().unwrap());
+    if kind != WIN_CERT_TYPE_PKCS_SIGNED_DATA {
+        return None;
+    }
+
+    let message = certificate.get(8..len)?;
+    let signed = signed_data(message)?;
+
+    // the signed content holds the digest of the file
+    let digest_info = signed.content?.child(1)?.expect(tag::SEQUENCE)?;
+    let algorithm = DigestAlgorithm::from_oid(digest_info.child(0)?.child(0)?.content)?;
+    let digest = digest_info.child(1)?.expect(tag::OCTET_STRING)?.content.to_vec();
+
+    let optional_header = pe.dos_header().nt_headers_offset() as usize
+        + std::mem::size_of::<u32>()
+        + std::mem::size_of::<object::pe::ImageFileHeader>();
+    let checksum = optional_header + CHECKSUM_OFFSET;
+    let directories = match pe.nt_headers().is_type_64() {
+        true => optional_header + std::mem::size_of::<object::pe::ImageOptionalHeader64>(),
+        false => optional_header + std::mem::size_of::<object::pe::ImageOptionalHeader32>(),
+    };
+    let entry = directories + SECURITY * std::mem::size_of::<object::pe::ImageDataDirectory>();
+
+    let (chain, signing_time, timestamp) = signer(message);
+    Some(Signature {
+        kind: SignatureKind::Authenticode,
+        chain,
+        signing_time,
+        timestamp,
+        algorithm,
+        identifier: None,
+        team: None,
+        adhoc: false,
+        coverage: Coverage::Authenticode {
+            excluded: [checksum..checksum + 4, entry..entry + 8, table],
+            digest,
+        },
+    })
+}
+
+fn be32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_be_bytes(bytes.try_into().unwrap()))
+}
+
+fn c_string(data: &[u8], offset: usize) -> Option<String> {
+    let bytes = data.get(offset..)?;
+    let len = bytes.iter().position(|&byte| byte == 0)?;
+    Some(String::from_utf8_lossy(&bytes[..len]).into_owned())
+}
+
+fn codesign<Mach: MachHeader<Endian = Endianness>>(
+    macho: &MachOFile<Mach>,
+    binary: &[u8],
+) -> Option<Signature> {
+    let endian = macho.endian();
+    let mut commands = macho.raw_header().load_commands(endian, binary, 0).ok()?;
+    let cmd = loop {
+        let lcmd = commands.next().ok()??;
+        if lcmd.cmd() == macho::LC_CODE_SIGNATURE {
+            break lcmd.data::<macho::LinkeditDataCommand<Endianness>>().ok()?;
+        }
+    };
+
+    let offset = cmd.dataoff.get(endian) as usize;
+    let blob = binary.get(offset..offset + cmd.datasize.get(endian) as usize)?;
+    if be32(blob, 0)? != CSMAGIC_EMBEDDED_SIGNATURE {
+        return None;
+    }
+
+    // the super blob indexes the blobs by their type and offset
+    let mut directory = None;
+    let mut message = None;
+    for idx in 0..be32(blob, 8)? as usize {
+        let offset = be32(blob, 12 + idx * 8 + 4)? as usize;
+        let len = be32(blob, offset + 4)? as usize;
+        let inner = blob.get(offset..offset.checked_add(len)?)?;
+
+        match be32(inner, 0)? {
+            // alternative code directories using other hashes may follow the first
+            CSMAGIC_CODEDIRECTORY if directory.is_none() => directory = Some(inner),
+            CSMAGIC_BLOBWRAPPER => message = inner.get(8..),
+            _ => {}
+        }
+    }
+
+    let directory = directory?;
+    let version = be32(directory, 8)?;
+    let flags = be32(directory, 12)?;
+    let hash_offset = be32(directory, 16)? as usize;
+    let identifier = c_string(directory, be32(directory, 20)? as usize);
+    let code_slots = be32(directory, 28)? as usize;
+    let code_limit = be32(directory, 32)? as usize;
+    let hash_size = *directory.get(36)? as usize;
+    let algorithm = DigestAlgorithm::from_hash_type(*directory.get(37)?)?;
+
+    // a page size of zero means the code is hashed as a whole
+    let page_size = match *directory.get(39)? {
+        0 => code_limit.max(1),
+        shift => 1usize.checked_shl(shift as u32)?,
+    };
+
+    let hashes = (0..code_slots)
+        .map(|slot| {
+            let start = hash_offset + slot * hash_size;
+            directory.get(start..start + hash_size).map(<[u8]>::to_vec)
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    let team = match version >= CS_SUPPORTSTEAMID {
+        true => match be32(directory, 48)? {
+            0 => None,
+            offset => c_string(directory, offset as usize),
+        },
+        false => None,
+    };
+
+    let (chain, signing_time, timestamp) = match message {
+        Some(message) if !message.is_empty() => signer(message),
+        _ => (Vec::new(), None, None),
+    };
+
+    Some(Signature {
+        kind: SignatureKind::CodeSign,
+        chain,
+        signing_time,
+        timestamp,
+        algorithm,
+        identifier,
+        team,
+        adhoc: flags & CS_ADHOC != 0,
+        coverage: Coverage::CodeDirectory {
+            page_size,
+            code_limit,
+            hashes,
+        },
+    })
+}
+
+/// Code signature of a PE or Mach-O, if it's signed.
+pub fn parse(obj: &ObjectFile, binary: &[u8]) -> Option<Signature> {
+    match obj {
+        ObjectFile::Pe32(pe) => authenticode(pe, binary),
+        ObjectFile::Pe64(pe) => authenticode(pe, binary),
+        ObjectFile::MachO32(macho) => codesign(macho, binary),
+        ObjectFile::MachO64(macho) => codesign(macho, binary),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn content(data: &[u8]) -> Option<(&[u8], &[u8])> {
+        element(data, 0).map(|(element, rest)| (element.content, rest))
+    }
+
+    /// Elements with an indefinite length, nested `depth` times.
+    fn nested(depth: usize) -> Vec<u8> {
+        let mut data = [tag::SEQUENCE, 0x80].repeat(depth);
+        data.extend([0, 0].repeat(depth));
+        data
+    }
+
+    fn signature(coverage: Coverage) -> Signature {
+        Signature {
+            kind: SignatureKind::CodeSign,
+            chain: Vec::new(),
+            signing_time: None,
+            timestamp: None,
+            algorithm: DigestAlgorithm::Sha256,
+            identifier: None,
+            team: None,
+            adhoc: true,
+            coverage,
+        }
+    }
+
+    #[test]
+    fn lengths() {
+        let short = [tag::OCTET_STRING, 0x02, 1, 2, 0xFF];
+        assert_eq!(content(&short), Some((&[1, 2][..], &[0xFF][..])));
+
+        let long = [tag::OCTET_STRING, 0x81, 0x02, 1, 2];
+        assert_eq!(content(&long), Some((&[1, 2][..], &[][..])));
+
+        let mut long = vec![tag::OCTET_STRING, 0x82, 0x01, 0x00];
+        long.extend([7; 0x100]);
+        let (long, rest) = content(&long).unwrap();
+        assert_eq!((long, rest), (&[7; 0x100][..], &[][..]));
+
+        // lengths that go past the end of the data
+        assert_eq!(content(&[tag::OCTET_STRING, 0x03, 1, 2]), None);
+        assert_eq!(content(&[tag::OCTET_STRING, 0x82, 0x01]), None);
+        let huge = [tag::OCTET_STRING, 0x84, 0xFF, 0xFF, 0xFF, 0xFF, 1];
+        assert_eq!(content(&huge), None);
+        assert_eq!(content(&[tag::OCTET_STRING]), None);
+
+        // lengths that don't fit in 32 bits
+        let oversized = [tag::OCTET_STRING, 0x85, 0, 0, 0, 0, 1, 0];
+        assert_eq!(content(&oversized), None);
+    }
+
+    #[test]
+    fn indefinite_lengths() {
+        let data = [tag::SEQUENCE, 0x80, tag::OCTET_STRING, 0x01, 7, 0, 0, 0xFF];
+        let (element, rest) = element(&data, 0).unwrap();
+        assert_eq!(element.content, [tag::OCTET_STRING, 0x01, 7]);
+        assert_eq!(element.raw, &data[..7]);
+        assert_eq!(rest, [0xFF]);
+        assert_eq!(element.child(0).map(|child| child.content), Some(&[7][..]));
+
+        // only constructed elements can have an indefinite length
+        assert_eq!(content(&[tag::OCTET_STRING, 0x80, 0, 0]), None);
+
+        // missing the two zero bytes ending it
+        let unended = [tag::SEQUENCE, 0x80, tag::OCTET_STRING, 0x01, 7];
+        assert_eq!(content(&unended), None);
+
+        assert!(Element::parse(&nested(MAX_DEPTH)).is_some());
+        assert!(Element::parse(&nested(MAX_DEPTH + 1)).is_none());
+    }
+
+    #[test]
+    fn code_directory_coverage() {
+        let mut binary: Vec<u8> = (0..10).collect();
+        let hashes = binary[..9]
+            .chunks(4)
+            .map(|page| DigestAlgorithm::Sha256.digest([page]))
+            .collect();
+
+        let signature = signature(Coverage::CodeDirectory {
+            page_size: 4,
+            code_limit: 9,
+            hashes,
+        });
+        assert!(signature.covers(&binary));
+        assert!(!signature.covers(&binary[..8]));
+
+        // bytes past the code limit aren't covered
+        binary[9] ^= 0xFF;
+        assert!(signature.covers(&binary));
+
+        binary[8] ^= 0xFF;
+        assert!(!signature.covers(&binary));
+    }
+
+    #[test]
+    fn authenticode_coverage() {
+        let mut binary: Vec<u8> = (0..32).collect();
+        let digest = DigestAlgorithm::Sha256.digest([&binary[..4], &binary[8..16], &binary[24..]]);
+
+        let signature = signature(Coverage::Authenticode {
+            excluded: [4..8, 16..20, 20..24],
+            digest,
+        });
+        assert!(signature.covers(&binary));
+
+        // the excluded ranges can change without invalidating the signature
+        binary[4] ^= 0xFF;
+        binary[23] ^= 0xFF;
+        assert!(signature.covers(&binary));
+
+        binary[30] ^= 0xFF;
+        assert!(!signature.covers(&binary));
+    }
+}
@@ -0,0 +1,520 @@
+//! Core dumps, which hold the memory of a crashed process instead of an object.
+//!
+//! Every region of memory gets a section named after the module it was mapped from. Code of
+//! modules that didn't get dumped is read from their file if it's still around, which also names
+//! their functions.
+
+use crate::lines::AddressWidths;
+use crate::{cache, decoders, enums, pipeline, sweep, CallGraph, Error, Hashes, Processor};
+use binformat::coredump::{CoreDump, Kind, Module, Region, Thread};
+use debugvault::Index;
+use memmap2::Mmap;
+use object::{Architecture, BinaryFormat, Endianness, Object, ObjectSection, ObjectSegment};
+use object::{ObjectSymbol, SectionKind as ObjectSectionKind, SymbolKind};
+use processor_shared::{AddressMap, PhysAddr, Section, SectionKind, Segment};
+use std::collections::{BTreeMap, HashSet};
+use std::fs::File;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex, RwLock};
+
+/// Upper limit on the frames of a backtrace, as a corrupted stack might loop.
+const MAX_FRAMES: usize = 256;
+
+/// How many words past the stack pointer get scanned for return addresses if the frame pointers
+/// don't lead anywhere.
+const SCAN_WORDS: usize = 2048;
+
+/// Frame of a thread's stack.
+#[derive(Debug, Clone)]
+pub struct Frame {
+    /// Address execution continues at, which is the program counter for the innermost frame.
+    pub addr: PhysAddr,
+    /// Function the address is in.
+    pub function: Option<String>,
+    /// Module the address is mapped from.
+    pub module: Option<String>,
+}
+
+/// Code and functions of a module's file, mapped to where the module was loaded.
+struct ModuleImage {
+    /// Text sections along with their address.
+    code: Vec<(PhysAddr, String, Vec<u8>)>,
+    functions: Vec<(PhysAddr, String)>,
+}
+
+impl ModuleImage {
+    /// Read a module's file, given the first time it was mapped.
+    fn load(module: &Module, kind: Kind) -> Option<Self> {
+        let data = std::fs::read(&module.path).ok()?;
+        let obj = object::File::parse(&data[..]).ok()?;
+
+        // minidumps list the image base of their modules, ELF core dumps list where each mapping
+        // of a file starts
+        let base = match kind {
+            Kind::Minidump => obj.relative_address_base(),
+            Kind::Elf => {
+                let first = obj.segments().find(|segment| segment.file_range().0 == 0)?;
+                first.address() & !0xFFF
+            }
+        };
+
+        let bias = (module.start as u64).wrapping_sub(base);
+        let rebase = |addr: u64| addr.wrapping_add(bias) as PhysAddr;
+
+        let code = obj
+            .sections()
+            .filter(|section| section.kind() == ObjectSectionKind::Text)
+            .filter_map(|section| {
+                let name = section.name().ok()?.to_string();
+                Some((rebase(section.address()), name, section.data().ok()?.to_vec()))
+            })
+            .collect();
+
+        let functions = obj
+            .symbols()
+            .chain(obj.dynamic_symbols())
+            .filter(|symbol| symbol.kind() == SymbolKind::Text && symbol.is_definition())
+            .filter(|symbol| symbol.address() != 0)
+            .filter_map(|symbol| Some((rebase(symbol.address()), symbol.name().ok()?.to_string())))
+            .chain(obj.exports().ok()?.into_iter().filter_map(|export| {
+                let name = std::str::from_utf8(export.name()).ok()?;
+                Some((rebase(export.address()), name.to_string()))
+            }))
+            .collect();
+
+        Some(Self { code, functions })
+    }
+}
+
+/// Thread that crashed, or the first thread if it isn't known which one did.
+fn crashed_thread(dump: &CoreDump) -> Option<&Thread> {
+    dump.threads.get(dump.crashed.unwrap_or(0))
+}
+
+/// Name of the module a region was mapped from, or whether it's the stack of a thread.
+fn region_name(dump: &CoreDump, region: &Region) -> String {
+    let range = region.start..region.end;
+    let stack = dump.threads.iter().any(|thread| range.contains(&thread.registers.sp));
+    match dump.module_by_addr(region.start) {
+        Some(module) => module.name().to_string(),
+        None if stack => "[stack]".to_string(),
+        None => "memory".to_string(),
+    }
+}
+
+impl Processor {
+    /// Load the memory of a crashed process, along with the code of the modules it had loaded.
+    pub(crate) fn parse_coredump(
+        path: std::path::PathBuf,
+        file: File,
+        mmap: Mmap,
+        dump: CoreDump,
+        progress: Arc<pipeline::Progress>,
+        now: std::time::Instant,
+    ) -> Result<Self, Error> {
+        let binary: &'static [u8] = unsafe { std::mem::transmute(&mmap[..]) };
+        let hashes = Hashes::compute(binary, None);
+        let hash = hashes.sha256.clone();
+
+        progress.enter(pipeline::Stage::Symbols)?;
+
+        let crashed = crashed_thread(&dump).map(|thread| thread.registers);
+
+        let mut sections = Vec::new();
+        let mut segments = Vec::new();
+        for region in dump.regions.iter() {
+            let name = region_name(&dump, region);
+
+            segments.push(Segment {
+                name: name.clone(),
+                start: region.start,
+                end: region.end,
+            });
+
+            // file backed code is usually left out of ELF core dumps
+            if region.data.is_empty() {
+                continue;
+            }
+
+            let kind = match region.permissions.execute {
+                true => SectionKind::Code,
+                false => SectionKind::Raw,
+            };
+
+            let bytes = &binary[region.data.clone()];
+            let mut section = Section::new(name, "MEMORY", kind, bytes, region.start, region.end);
+            section.file_offset = Some(region.data.start as u64);
+            section.permissions = region.permissions;
+            sections.push(section);
+        }
+
+        let mut owned = Vec::new();
+        let mut functions = Vec::new();
+        let mut loaded = HashSet::new();
+        let mut from_files = Vec::new();
+        for module in dump.modules.iter() {
+            if !loaded.insert(&module.path) {
+                continue;
+            }
+
+            let image = match ModuleImage::load(module, dump.kind) {
+                Some(image) => image,
+                None => continue,
+            };
+
+            functions.extend(image.functions);
+            for (start, name, bytes) in image.code {
+                let end = start + bytes.len();
+                // only the first page of a mapping might've been dumped
+                let dumped = sections.iter().any(|s: &Section| {
+                    s.start < end && start < s.end && s.bytes().len() == s.end - s.start
+                });
+                if dumped || bytes.is_empty() {
+                    continue;
+                }
+
+                from_files.push(start..end);
+
+                owned.push(bytes);
+                let bytes: &'static [u8] =
+                    unsafe { std::mem::transmute(&owned.last().unwrap()[..]) };
+                let name = format!("{}:{name}", module.name());
+                let mut section = Section::new(name, "CODE", SectionKind::Code, bytes, start, end);
+                section.permissions.read = true;
+                section.permissions.execute = true;
+                sections.push(section);
+            }
+        }
+
+        sections.retain(|s| {
+            s.ident == "CODE" || !from_files.iter().any(|r| s.start < r.end && r.start < s.end)
+        });
+        sections.sort_unstable_by_key(|section| section.start);
+        segments.sort_unstable_by_key(|segment| segment.start);
+
+        let mut index = Index::default();
+        index.insert_functions(functions);
+
+        log::complex!(
+            w "[processor::parse_coredump] found ",
+            g dump.regions.len().to_string(),
+            w " regions of memory and ",
+            g dump.threads.len().to_string(),
+            w " threads.",
+        );
+
+        if let Some(ref reason) = dump.reason {
+            log::complex!(
+                w "[processor::parse_coredump] process crashed with ",
+                r reason,
+                w ".",
+            );
+        }
+
+        let address_widths = AddressWidths::new(&sections, &segments, 0);
+
+        progress.enter(pipeline::Stage::Disassembly)?;
+
+        let (instruction_tokens, instruction_width, instruction_lower, max_width, decode_region) =
+            decoders(dump.arch)?;
+
+        log::complex!(
+            w "[processor::parse] took ",
+            y format!("{:#?}", now.elapsed()),
+            w " to parse ",
+            w format!("{path:?}.")
+        );
+
+        let regions = sweep::regions(&sections);
+        let cache = cache::Cache::load(&hash);
+        let mut processor = Self {
+            entrypoint: crashed.map_or(0, |registers| registers.pc),
+            path,
+            hash,
+            hashes,
+            sections,
+            regions,
+            decode_region,
+            patches: RwLock::default(),
+            structs: RwLock::default(),
+            applied: RwLock::default(),
+            enums: RwLock::new(enums::known(&index)),
+            applied_enums: RwLock::default(),
+            cache: Mutex::new(cache),
+            progress,
+            segments,
+            index,
+            image_base: 0,
+            analysis_time: now.elapsed(),
+            timings: log::Profile::default(),
+            detections: Vec::new(),
+            mitigations: Vec::new(),
+            rich: None,
+            signature: None,
+            signature_covers: Mutex::new(None),
+            overlay: None,
+            embedded: Vec::new(),
+            crypto: Vec::new(),
+            idioms: AddressMap::default(),
+            syscalls: AddressMap::default(),
+            data_pointers: BTreeMap::new(),
+            data_xrefs: BTreeMap::new(),
+            strings: Vec::new(),
+            function_strings: BTreeMap::new(),
+            back_edges: BTreeMap::new(),
+            dead_code: Vec::new(),
+            imports: Vec::new(),
+            exports: Vec::new(),
+            autostart: Vec::new(),
+            load_commands: Vec::new(),
+            objc: Default::default(),
+            dotnet: None,
+            bytecode: None,
+            call_graph: CallGraph::default(),
+            prototypes: BTreeMap::new(),
+            indirect: BTreeMap::new(),
+            address_widths,
+            _file: file,
+            mmap,
+            _owned: owned,
+            max_instruction_width: max_width,
+            instruction_tokens,
+            instruction_width,
+            instruction_lower,
+            arch: dump.arch,
+            // minidumps are written by Windows, which decides the calling convention
+            format: match dump.kind {
+                Kind::Elf => BinaryFormat::Elf,
+                Kind::Minidump => BinaryFormat::Pe,
+            },
+            abi_registers: AtomicBool::new(false),
+            endianness: dump.endianness,
+            coredump: Some(dump),
+        };
+
+        processor.progress.finish();
+        processor.timings = log::PROFILER.take(processor.analysis_time);
+        Ok(processor)
+    }
+
+    /// Return addresses on a thread's stack, starting with where it stopped.
+    ///
+    /// Follows the chain of frame pointers, and scans the stack for addresses that follow a call
+    /// if the frame pointers were omitted.
+    pub fn backtrace(&self, thread: &Thread) -> Vec<Frame> {
+        let registers = thread.registers;
+        let width = self.arch.address_size().map_or(8, |size| size.bytes() as usize);
+
+        let mut addrs = vec![registers.pc];
+        let mut fp = registers.fp;
+        while addrs.len() < MAX_FRAMES {
+            // offset of the caller's frame pointer and the return address from the frame pointer
+            let (prev, ret) = match self.arch {
+                Architecture::Riscv32 | Architecture::Riscv64 => (-2, -1),
+                Architecture::X86_64
+                | Architecture::X86_64_X32
+                | Architecture::I386
+                | Architecture::Aarch64
+                | Architecture::Aarch64_Ilp32 => (0, 1),
+                _ => break,
+            };
+
+            let word = |idx: isize| self.read_word(fp.wrapping_add_signed(idx * width as isize));
+            match (word(prev), word(ret)) {
+                (Some(prev), Some(ret)) if prev > fp && self.is_return_addr(ret) => {
+                    addrs.push(ret);
+                    fp = prev;
+                }
+                _ => break,
+            }
+        }
+
+        // the frame pointer is just another register if it's omitted
+        if addrs.len() == 1 {
+            for idx in 0..SCAN_WORDS {
+                match self.read_word(registers.sp + idx * width) {
+                    Some(addr) if self.is_return_addr(addr) => addrs.push(addr),
+                    Some(_) => {}
+                    None => break,
+                }
+
+                if addrs.len() == MAX_FRAMES {
+                    break;
+                }
+            }
+        }
+
+        addrs
+            .into_iter()
+            .enumerate()
+            .map(|(idx, addr)| {
+                // return addresses can be past the end of a function that doesn't return
+                let within = if idx == 0 { addr } else { addr - 1 };
+                Frame {
+                    addr,
+                    function: self.function_bounds(within).map(|(_, _, name)| name),
+                    module: self
+                        .coredump
+                        .as_ref()
+                        .and_then(|dump| dump.module_by_addr(addr))
+                        .map(|module| module.name().to_string()),
+                }
+            })
+            .collect()
+    }
+
+    /// Pointer sized word in memory.
+    pub(crate) fn read_word(&self, addr: PhysAddr) -> Option<PhysAddr> {
+        let width = self.arch.address_size().map_or(8, |size| size.bytes() as usize);
+        let bytes = self.section_by_addr(addr)?.bytes_by_addr(addr, width);
+        let bytes: [u8; 8] = match (width, bytes.len()) {
+            (8, 8) => bytes.try_into().ok()?,
+            (4, 4) => {
+                let mut word = [0; 8];
+                match self.endianness {
+                    Endianness::Little => word[..4].copy_from_slice(bytes),
+                    Endianness::Big => word[4..].copy_from_slice(bytes),
+                }
+                word
+            }
+            _ => return None,
+        };
+
+        Some(match self.endianness {
+            Endianness::Little => u64::from_le_bytes(bytes),
+            Endianness::Big => u64::from_be_bytes(bytes),
+        } as PhysAddr)
+    }
+
+    /// Whether an address is in code right after a call.
+    fn is_return_addr(&self, addr: PhysAddr) -> bool {
+        let section = match self.section_by_addr(addr) {
+            Some(section) if section.kind == SectionKind::Code && addr > section.start => section,
+            _ => return false,
+        };
+
+        let before = |len: usize| match addr.checked_sub(len) {
+            Some(start) if start >= section.start => section.bytes_by_addr(start, len),
+            _ => &[],
+        };
+
+        let word = |len: usize| {
+            let bytes: [u8; 4] = before(len).get(..4)?.try_into().ok()?;
+            Some(match self.endianness {
+                Endianness::Little => u32::from_le_bytes(bytes),
+                Endianness::Big => u32::from_be_bytes(bytes),
+            })
+        };
+
+        match self.arch {
+            // `call rel32`, or `call r/m` which is 0xFF with a reg field of 2
+            Architecture::X86_64 | Architecture::X86_64_X32 | Architecture::I386 => {
+                before(5).first() == Some(&0xE8)
+                    || (2..=7).any(|len| match before(len) {
+                        [0xFF, modrm, ..] => (modrm >> 3) & 7 == 2,
+                        _ => false,
+                    })
+            }
+            // `bl` or `blr`
+            Architecture::Aarch64 | Architecture::Aarch64_Ilp32 => word(4).is_some_and(|w| {
+                w & 0xFC000000 == 0x94000000 || w & 0xFFFFFC1F == 0xD63F0000
+            }),
+            // `bl` or `blx`, thumb doesn't have a fixed width
+            Architecture::Arm => {
+                let arm = word(4).is_some_and(|w| {
+                    w & 0x0F000000 == 0x0B000000 || w & 0x0FFFFFF0 == 0x012FFF30
+                });
+                arm || addr & 1 == 1
+            }
+            // `jal ra` or `jalr ra`, or the compressed `c.jalr`
+            Architecture::Riscv32 | Architecture::Riscv64 => {
+                let compressed = match before(2) {
+                    [lo, hi] => u16::from_le_bytes([*lo, *hi]) & 0xF07F == 0x9002,
+                    _ => false,
+                };
+                compressed || word(4).is_some_and(|w| w & 0xFFF == 0x0EF || w & 0x7FFF == 0x0E7)
+            }
+            // `jal`, `bal` or `jalr`, which are followed by a delay slot
+            Architecture::Mips | Architecture::Mips64 => word(8).is_some_and(|w| {
+                w >> 26 == 3 || w & 0xFFFF0000 == 0x04110000 || w & 0xFC00003F == 0x00000009
+            }),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use binformat::coredump::Registers;
+    use processor_shared::Permissions;
+
+    fn thread(id: u32, pc: PhysAddr, sp: PhysAddr) -> Thread {
+        Thread {
+            id,
+            registers: Registers { pc, sp, fp: 0 },
+        }
+    }
+
+    fn region(start: PhysAddr, end: PhysAddr) -> Region {
+        Region {
+            start,
+            end,
+            data: 0..0,
+            permissions: Permissions::default(),
+        }
+    }
+
+    fn dump() -> CoreDump {
+        CoreDump {
+            kind: Kind::Elf,
+            arch: Architecture::X86_64,
+            endianness: Endianness::Little,
+            regions: vec![
+                region(0x400000, 0x402000),
+                region(0x600000, 0x601000),
+                region(0x7FFD_0000, 0x7FFE_0000),
+            ],
+            modules: vec![Module {
+                path: "/usr/bin/app".to_string(),
+                start: 0x400000,
+                end: 0x402000,
+                file_offset: 0,
+            }],
+            threads: vec![
+                thread(100, 0x401000, 0x7FFE_8000),
+                thread(101, 0x401008, 0x7FFD_8000),
+            ],
+            crashed: Some(1),
+            reason: Some("SIGSEGV".to_string()),
+        }
+    }
+
+    #[test]
+    fn crashed() {
+        let mut dump = dump();
+        assert_eq!(crashed_thread(&dump).map(|thread| thread.id), Some(101));
+
+        dump.crashed = None;
+        assert_eq!(crashed_thread(&dump).map(|thread| thread.id), Some(100));
+
+        dump.threads.clear();
+        assert!(crashed_thread(&dump).is_none());
+    }
+
+    #[test]
+    fn segment_names() {
+        let mut dump = dump();
+        let names: Vec<String> = dump.regions.iter().map(|r| region_name(&dump, r)).collect();
+        assert_eq!(names, ["app", "memory", "[stack]"]);
+
+        // the crashing thread runs in the module, on its own stack
+        let registers = crashed_thread(&dump).unwrap().registers;
+        assert_eq!(dump.module_by_addr(registers.pc).map(Module::name), Some("app"));
+        assert!((dump.regions[2].start..dump.regions[2].end).contains(&registers.sp));
+
+        // stacks of threads that didn't crash are named as well
+        dump.threads[0].registers.sp = 0x600800;
+        assert_eq!(region_name(&dump, &dump.regions[1]), "[stack]");
+    }
+}
@@ -0,0 +1,212 @@
+//! Well-known constants of cryptographic algorithms and checksums, which point out the routines
+//! implementing them.
+//!
+//! Tables of words are matched in either byte order. Their words may be a few bytes apart, so that
+//! they're also found as the immediates of consecutive instructions.
+
+use processor_shared::{PhysAddr, Section, SectionKind};
+
+/// Number of bytes that may separate the words of a constant.
+const MAX_GAP: usize = 16;
+
+/// Bytes a constant is made of.
+enum Pattern {
+    Bytes(&'static [u8]),
+    Words(&'static [u32]),
+    Quads(&'static [u64]),
+}
+
+/// Constants, where ones that contain another constant come first.
+const CONSTANTS: &[(&str, Pattern)] = &[
+    (
+        "AES S-box",
+        Pattern::Bytes(&[
+            0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5, 0x30, 0x01, 0x67, 0x2b, 0xfe, 0xd7,
+            0xab, 0x76, 0xca, 0x82, 0xc9, 0x7d, 0xfa, 0x59, 0x47, 0xf0, 0xad, 0xd4, 0xa2, 0xaf,
+            0x9c, 0xa4, 0x72, 0xc0,
+        ]),
+    ),
+    (
+        "AES inverse S-box",
+        Pattern::Bytes(&[
+            0x52, 0x09, 0x6a, 0xd5, 0x30, 0x36, 0xa5, 0x38, 0xbf, 0x40, 0xa3, 0x9e, 0x81, 0xf3,
+            0xd7, 0xfb, 0x7c, 0xe3, 0x39, 0x82, 0x9b, 0x2f, 0xff, 0x87, 0x34, 0x8e, 0x43, 0x44,
+            0xc4, 0xde, 0xe9, 0xcb,
+        ]),
+    ),
+    (
+        "ChaCha20/Salsa20 sigma",
+        Pattern::Words(&[0x61707865, 0x3320646e, 0x79622d32, 0x6b206574]),
+    ),
+    (
+        "ChaCha20/Salsa20 tau",
+        Pattern::Words(&[0x61707865, 0x3120646e, 0x79622d36, 0x6b206574]),
+    ),
+    (
+        "SHA-512 initial state",
+        Pattern::Quads(&[
+            0x6a09e667f3bcc908,
+            0xbb67ae8584caa73b,
+            0x3c6ef372fe94f82b,
+            0xa54ff53a5f1d36f1,
+        ]),
+    ),
+    (
+        "SHA-512 round constants",
+        Pattern::Quads(&[
+            0x428a2f98d728ae22,
+            0x7137449123ef65cd,
+            0xb5c0fbcfec4d3b2f,
+            0xe9b5dba58189dbbc,
+        ]),
+    ),
+    (
+        "SHA-256 initial state",
+        Pattern::Words(&[
+            0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+            0x5be0cd19,
+        ]),
+    ),
+    (
+        "SHA-224 initial state",
+        Pattern::Words(&[0xc1059ed8, 0x367cd507, 0x3070dd17, 0xf70e5939]),
+    ),
+    (
+        "SHA-256 round constants",
+        Pattern::Words(&[0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5]),
+    ),
+    (
+        "SHA-1 initial state",
+        Pattern::Words(&[0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0]),
+    ),
+    (
+        "MD5 initial state",
+        Pattern::Words(&[0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476]),
+    ),
+    (
+        "MD5 sine table",
+        Pattern::Words(&[0xd76aa478, 0xe8c7b756, 0x242070db, 0xc1bdceee]),
+    ),
+    (
+        "Blowfish P-array",
+        Pattern::Words(&[0x243f6a88, 0x85a308d3, 0x13198a2e, 0x03707344]),
+    ),
+    // the first entry of a CRC table is zero, which is too common to look for
+    (
+        "CRC-32 table",
+        Pattern::Words(&[0x77073096, 0xee0e612c, 0x990951ba, 0x076dc419]),
+    ),
+    (
+        "CRC-32C table",
+        Pattern::Words(&[0xf26b8303, 0xe13b70f7, 0x1350f3f4, 0xc79a971f]),
+    ),
+    (
+        "CRC-32 table (MSB first)",
+        Pattern::Words(&[0x04c11db7, 0x09823b6e, 0x0d4326d9, 0x130476dc]),
+    ),
+];
+
+/// Constant found in the binary.
+#[derive(Debug, Clone)]
+pub struct CryptoConstant {
+    pub addr: PhysAddr,
+    /// Number of bytes from the start of the constant until the end of its last word.
+    pub size: usize,
+    pub name: &'static str,
+    /// Function that uses the constant as an immediate, if it was found in code.
+    pub function: Option<String>,
+}
+
+/// Encoded parts of a constant, which have to appear in order.
+struct Needle {
+    name: &'static str,
+    parts: Vec<Vec<u8>>,
+}
+
+impl Needle {
+    /// Offset of the end of the needle if it's found at the start of `bytes`.
+    fn find(&self, bytes: &[u8]) -> Option<usize> {
+        let mut end = 0;
+        for (idx, part) in self.parts.iter().enumerate() {
+            let window = match idx {
+                0 => bytes.get(..part.len())?,
+                _ => &bytes[end..bytes.len().min(end + MAX_GAP + part.len())],
+            };
+
+            end += window.windows(part.len()).position(|window| window == part)? + part.len();
+        }
+
+        Some(end)
+    }
+}
+
+fn needles() -> Vec<Needle> {
+    let mut needles = Vec::new();
+    for (name, pattern) in CONSTANTS {
+        let (little, big): (Vec<Vec<u8>>, Vec<Vec<u8>>) = match pattern {
+            Pattern::Bytes(bytes) => {
+                needles.push(Needle {
+                    name,
+                    parts: vec![bytes.to_vec()],
+                });
+                continue;
+            }
+            Pattern::Words(words) => words
+                .iter()
+                .map(|word| (word.to_le_bytes().to_vec(), word.to_be_bytes().to_vec()))
+                .unzip(),
+            Pattern::Quads(quads) => quads
+                .iter()
+                .map(|quad| (quad.to_le_bytes().to_vec(), quad.to_be_bytes().to_vec()))
+                .unzip(),
+        };
+
+        needles.push(Needle {
+            name,
+            parts: little,
+        });
+        needles.push(Needle { name, parts: big });
+    }
+
+    needles
+}
+
+/// Constants in the bytes of the sections, sorted by their address.
+pub fn scan<'a>(sections: impl Iterator<Item = &'a Section>) -> Vec<CryptoConstant> {
+    let needles = needles();
+
+    // only the needles starting with a byte have to be checked at an offset starting with it
+    let mut candidates = vec![Vec::new(); 256];
+    for (idx, needle) in needles.iter().enumerate() {
+        candidates[needle.parts[0][0] as usize].push(idx);
+    }
+
+    let mut constants = Vec::new();
+    for section in sections.filter(|section| section.kind != SectionKind::Debug) {
+        let bytes = section.bytes();
+        let mut offset = 0;
+
+        while offset < bytes.len() {
+            let found = candidates[bytes[offset] as usize].iter().find_map(|&idx| {
+                let needle = &needles[idx];
+                needle.find(&bytes[offset..]).map(|size| (needle.name, size))
+            });
+
+            match found {
+                Some((name, size)) => {
+                    constants.push(CryptoConstant {
+                        addr: section.start + offset,
+                        size,
+                        name,
+                        function: None,
+                    });
+                    offset += size;
+                }
+                None => offset += 1,
+            }
+        }
+    }
+
+    constants.sort_unstable_by_key(|constant| constant.addr);
+    constants
+}
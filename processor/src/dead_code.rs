@@ -0,0 +1,140 @@
+//! Code that never runs, or that nothing is known to run.
+//!
+//! Blocks of a function that no path from its entrypoint reaches are either left behind by the
+//! compiler or data that got decoded as instructions. Functions that aren't reached from an
+//! entrypoint through calls, and of which the address isn't taken anywhere, can be unused library
+//! code but also functionality that's only triggered in unusual ways. Both are listed as
+//! candidates worth a closer look, not as proof that the code is dead.
+
+use crate::cfg::Cfg;
+use crate::Processor;
+use decoder::ir::Stmt;
+use processor_shared::{PhysAddr, SectionKind};
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// Why code is considered dead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeadCodeKind {
+    /// Instructions within a function that no path from its entrypoint reaches.
+    Unreachable,
+    /// Function that isn't called from an entrypoint and of which the address isn't taken.
+    Orphan,
+}
+
+impl fmt::Display for DeadCodeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Unreachable => "unreachable code",
+            Self::Orphan => "orphan function",
+        })
+    }
+}
+
+/// Range of code that's considered dead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeadCode {
+    pub addr: PhysAddr,
+    /// Address past the last instruction.
+    pub end: PhysAddr,
+    pub kind: DeadCodeKind,
+    /// Start of the function containing the code.
+    pub function: PhysAddr,
+}
+
+impl DeadCode {
+    /// Unreachable instructions of a function, see [`Cfg::unreachable`].
+    pub(crate) fn unreachable(cfg: &Cfg) -> impl Iterator<Item = Self> + '_ {
+        cfg.unreachable.iter().map(|range| Self {
+            addr: range.start,
+            end: range.end,
+            kind: DeadCodeKind::Unreachable,
+            function: cfg.addr,
+        })
+    }
+}
+
+impl Processor {
+    /// Functions in code sections, by the address they start at.
+    fn code_functions(&self) -> BTreeSet<PhysAddr> {
+        self.index
+            .functions()
+            .filter(|func| !func.item.imported())
+            .map(|func| func.addr)
+            .filter(|&addr| self.section_by_addr(addr).is_some_and(|s| s.kind == SectionKind::Code))
+            .collect()
+    }
+
+    /// Functions of which the address is used other than by a call, either as an immediate of an
+    /// instruction or as a pointer stored in a data section.
+    fn address_taken(&self, functions: &BTreeSet<PhysAddr>) -> BTreeSet<PhysAddr> {
+        let mut taken = BTreeSet::new();
+
+        for instruction in self.instructions_from(0) {
+            for stmt in self.instruction_ir(&instruction.item) {
+                let value = match stmt {
+                    Stmt::Assign { src, .. } | Stmt::Store { src, .. } => src.as_const(),
+                    Stmt::Jump(target) => target.as_const(),
+                    _ => None,
+                };
+
+                if let Some(addr) = value.map(|value| value as PhysAddr) {
+                    if functions.contains(&addr) {
+                        taken.insert(addr);
+                    }
+                }
+            }
+
+            for &target in self.indirect_targets(instruction.addr) {
+                taken.insert(target);
+            }
+        }
+
+        // function pointers in tables, such as vtables and callbacks
+        for pointer in self.data_pointers.values() {
+            if functions.contains(&pointer.target) {
+                taken.insert(pointer.target);
+            }
+        }
+
+        taken
+    }
+
+    /// Functions that aren't reached from an entrypoint, export or code that runs on its own
+    /// through calls, and of which the address isn't taken.
+    pub(crate) fn orphan_functions(&self) -> Vec<DeadCode> {
+        let functions = self.code_functions();
+
+        let mut queue: Vec<PhysAddr> = self.address_taken(&functions).into_iter().collect();
+        queue.push(self.entrypoint);
+        queue.extend(self.exports.iter().map(|export| export.addr));
+        queue.extend(self.autostart.iter().map(|start| start.addr));
+
+        let mut reached = BTreeSet::new();
+        while let Some(addr) = queue.pop() {
+            if reached.insert(addr) {
+                queue.extend(self.call_graph.callees(addr));
+            }
+        }
+
+        functions
+            .into_iter()
+            .filter(|addr| !reached.contains(addr))
+            .filter_map(|addr| {
+                let (start, end, name) = self.function_bounds(addr)?;
+
+                // symbols of sections aren't functions
+                if start != addr || self.sections.iter().any(|section| section.name == name) {
+                    return None;
+                }
+
+                Some(DeadCode {
+                    addr,
+                    end,
+                    kind: DeadCodeKind::Orphan,
+                    function: addr,
+                })
+            })
+            .collect()
+    }
+}
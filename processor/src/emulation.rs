@@ -0,0 +1,844 @@
+//! Running a single function over the [`ir`](decoder::ir) of its instructions, to see what it
+//! returns and which memory it writes given some arguments.
+//!
+//! Nothing outside of the binary is emulated. Calls into imports stop the emulation, memory that
+//! isn't part of any section reads as zero, and values the lowering doesn't model stop it once
+//! they're used.
+
+use crate::Processor;
+use decoder::ir::{BinaryOp, Cond, Condition, Expr, Flags, Reg, Stmt, UnaryOp};
+use object::{Architecture, BinaryFormat, Endianness};
+use processor_shared::PhysAddr;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Instructions that are executed before giving up, as the function might never return.
+pub const MAX_STEPS: usize = 1_000_000;
+
+/// Address the stack starts at, far away from anything a binary maps.
+const STACK_TOP_64: u64 = 0x7fff_fff0_0000;
+const STACK_TOP_32: u64 = 0x7ff0_0000;
+
+/// Bytes below the top of the stack that are considered part of it.
+const STACK_SIZE: u64 = 0x10_0000;
+
+/// Address the function returns to, which is never executed.
+const RETURN_ADDR: u64 = 0xdead_0000;
+
+#[derive(Debug)]
+pub enum Error {
+    /// Calling convention of the architecture isn't known.
+    UnsupportedArchitecture(Architecture),
+    /// There isn't a decoded instruction at the address.
+    NoInstructions(PhysAddr),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedArchitecture(arch) => {
+                f.write_fmt(format_args!("Emulating {arch:?} isn't supported."))
+            }
+            Self::NoInstructions(addr) => {
+                f.write_fmt(format_args!("There are no instructions at {addr:#x}."))
+            }
+        }
+    }
+}
+
+/// Why the emulation stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stop {
+    /// The function returned to its caller.
+    Returned,
+    /// An instruction stopped execution, e.g. by trapping.
+    Halted,
+    /// Execution continued at an address without a decoded instruction.
+    NoInstruction(PhysAddr),
+    /// A function that isn't part of the binary was called, such as an import.
+    External(PhysAddr),
+    /// A value was used that the instruction at an address produced, which isn't modelled.
+    Unmodelled(PhysAddr),
+    DivideByZero,
+    /// More than [`MAX_STEPS`] instructions were executed.
+    TooManySteps,
+}
+
+impl fmt::Display for Stop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Returned => f.write_str("returned"),
+            Self::Halted => f.write_str("halted"),
+            Self::NoInstruction(addr) => {
+                f.write_fmt(format_args!("continued at {addr:#x}, which isn't code"))
+            }
+            Self::External(addr) => f.write_fmt(format_args!(
+                "called {addr:#x}, which isn't part of the binary"
+            )),
+            Self::Unmodelled(addr) => f.write_fmt(format_args!(
+                "used a value of the instruction at {addr:#x}, which isn't modelled"
+            )),
+            Self::DivideByZero => f.write_str("divided by zero"),
+            Self::TooManySteps => f.write_fmt(format_args!(
+                "didn't return within {MAX_STEPS} instructions"
+            )),
+        }
+    }
+}
+
+/// Outcome of running a function.
+#[derive(Debug, Clone)]
+pub struct Emulation {
+    /// Address of the function.
+    pub addr: PhysAddr,
+    pub stop: Stop,
+    /// Address of the instruction that was executed last.
+    pub pc: PhysAddr,
+    /// Number of instructions that were executed.
+    pub steps: usize,
+    /// Value of the register holding the return value, if it's known.
+    pub returned: Option<u64>,
+    /// Values of the registers that were written.
+    pub registers: BTreeMap<&'static str, u64>,
+    /// Consecutive bytes written outside of the stack, sorted by address.
+    pub writes: Vec<(PhysAddr, Vec<u8>)>,
+    /// Instructions that ran without being modelled, which might make the outcome wrong.
+    pub skipped: Vec<PhysAddr>,
+}
+
+/// Registers a function receives its arguments in and returns its value in.
+pub(crate) struct Convention {
+    /// Short name the convention is commonly known by.
+    pub name: &'static str,
+    pub args: &'static [&'static str],
+    pub ret: &'static str,
+    pub sp: &'static str,
+    /// Register holding the return address, on architectures where calls don't push it.
+    pub link: Option<&'static str>,
+    /// Bytes the caller reserves on the stack in between the return address and arguments.
+    pub shadow: u64,
+}
+
+pub(crate) fn convention(arch: Architecture, format: BinaryFormat) -> Option<Convention> {
+    Some(match (arch, format) {
+        (Architecture::X86_64, BinaryFormat::Pe) => Convention {
+            name: "win64",
+            args: &["rcx", "rdx", "r8", "r9"],
+            ret: "rax",
+            sp: "rsp",
+            link: None,
+            shadow: 32,
+        },
+        (Architecture::X86_64 | Architecture::X86_64_X32, _) => Convention {
+            name: "sysv",
+            args: &["rdi", "rsi", "rdx", "rcx", "r8", "r9"],
+            ret: "rax",
+            sp: "rsp",
+            link: None,
+            shadow: 0,
+        },
+        (Architecture::I386, _) => Convention {
+            name: "cdecl",
+            args: &[],
+            ret: "eax",
+            sp: "esp",
+            link: None,
+            shadow: 0,
+        },
+        (Architecture::Aarch64 | Architecture::Aarch64_Ilp32, _) => Convention {
+            name: "aapcs64",
+            args: &["x0", "x1", "x2", "x3", "x4", "x5", "x6", "x7"],
+            ret: "x0",
+            sp: "sp",
+            link: Some("x30"),
+            shadow: 0,
+        },
+        (Architecture::Arm, _) => Convention {
+            name: "aapcs",
+            args: &["r0", "r1", "r2", "r3"],
+            ret: "r0",
+            sp: "sp",
+            link: Some("lr"),
+            shadow: 0,
+        },
+        (Architecture::Riscv32 | Architecture::Riscv64, _) => Convention {
+            name: "riscv",
+            args: &["a0", "a1", "a2", "a3", "a4", "a5", "a6", "a7"],
+            ret: "a0",
+            sp: "sp",
+            link: Some("ra"),
+            shadow: 0,
+        },
+        (Architecture::Mips | Architecture::Mips64, _) => Convention {
+            name: "mips",
+            args: &["a0", "a1", "a2", "a3"],
+            ret: "v0",
+            sp: "sp",
+            link: Some("ra"),
+            shadow: 0,
+        },
+        _ => return None,
+    })
+}
+
+fn mask(size: u8) -> u64 {
+    match size {
+        0..=7 => (1 << (size as u32 * 8)) - 1,
+        _ => u64::MAX,
+    }
+}
+
+/// Value of `size` bytes when interpreted as signed.
+fn signed(value: u64, size: u8) -> i64 {
+    let shift = 64 - 8 * size.clamp(1, 8) as u32;
+    ((value << shift) as i64) >> shift
+}
+
+/// Number of bytes an expression operates on, if it can be told.
+fn width(expr: &Expr) -> Option<u8> {
+    match expr {
+        Expr::Reg(reg) => Some(reg.size),
+        Expr::Load { size, .. } | Expr::Truncate(_, size) => Some(*size),
+        Expr::Unary(_, value) => width(value),
+        Expr::Binary(_, lhs, rhs) | Expr::Select(_, lhs, rhs) => match (width(lhs), width(rhs)) {
+            (Some(lhs), Some(rhs)) => Some(lhs.max(rhs)),
+            (lhs, rhs) => lhs.or(rhs),
+        },
+        Expr::Const(..) | Expr::SignExtend(..) | Expr::Cond(..) | Expr::Unknown => None,
+    }
+}
+
+/// Operands of whatever set the condition flags the last time.
+#[derive(Clone, Copy)]
+enum FlagState {
+    /// Subtraction of the second value from the first, of `size` bytes.
+    Compare(u64, u64, u8),
+    /// Value of `size` bytes, which neither carried nor overflowed.
+    Result(u64, u8),
+}
+
+impl FlagState {
+    fn holds(self, condition: Condition) -> bool {
+        let (result, size, carry, overflow) = match self {
+            Self::Compare(lhs, rhs, size) => {
+                let result = lhs.wrapping_sub(rhs) & mask(size);
+                let overflow = signed((lhs ^ rhs) & (lhs ^ result), size) < 0;
+                (result, size, lhs < rhs, overflow)
+            }
+            Self::Result(result, size) => (result, size, false, false),
+        };
+
+        let negative = signed(result, size) < 0;
+        match condition {
+            Condition::Eq => result == 0,
+            Condition::Ne => result != 0,
+            Condition::Lt => negative != overflow,
+            Condition::Le => result == 0 || negative != overflow,
+            Condition::Gt => result != 0 && negative == overflow,
+            Condition::Ge => negative == overflow,
+            Condition::Below => carry,
+            Condition::BelowEq => carry || result == 0,
+            Condition::Above => !carry && result != 0,
+            Condition::AboveEq => !carry,
+            Condition::Negative => negative,
+            Condition::NotNegative => !negative,
+            Condition::Overflow => overflow,
+            Condition::NoOverflow => !overflow,
+            Condition::Parity => (result as u8).count_ones().is_multiple_of(2),
+            Condition::NoParity => !(result as u8).count_ones().is_multiple_of(2),
+        }
+    }
+}
+
+/// Code and memory of the binary a function runs in.
+trait Image {
+    fn big_endian(&self) -> bool;
+
+    /// Byte at an address, zero if it isn't part of any section.
+    fn byte_at(&self, addr: PhysAddr) -> u8;
+
+    /// Lowered statements and width of the instruction at an address.
+    fn instruction_at(&self, addr: PhysAddr) -> Option<(Vec<Stmt>, usize)>;
+}
+
+impl Image for Processor {
+    fn big_endian(&self) -> bool {
+        self.endianness() == Endianness::Big
+    }
+
+    fn byte_at(&self, addr: PhysAddr) -> u8 {
+        let section = match self.section_by_addr(addr) {
+            Some(section) => section,
+            None => return 0,
+        };
+
+        let bytes = self.patched_bytes(section, addr, 1);
+        bytes.first().copied().unwrap_or(0)
+    }
+
+    fn instruction_at(&self, addr: PhysAddr) -> Option<(Vec<Stmt>, usize)> {
+        let instruction = self.instruction_by_addr(addr)?;
+        let width = self.instruction_width(&instruction);
+        Some((self.instruction_ir(&instruction), width))
+    }
+}
+
+/// Where execution continues after an instruction.
+enum Next {
+    Continue,
+    Jump(u64),
+    Call(u64),
+    Return,
+    Halt,
+}
+
+struct Machine<'a> {
+    image: &'a dyn Image,
+    /// Bytes in a pointer.
+    width: u8,
+    registers: BTreeMap<&'static str, u64>,
+    /// Registers holding a value that isn't modelled, along with the instruction that wrote it.
+    unknown: BTreeMap<&'static str, PhysAddr>,
+    /// Bytes that were written.
+    memory: BTreeMap<u64, u8>,
+    /// State of the flags, or the instruction that set them in a way that isn't modelled.
+    flags: Result<FlagState, PhysAddr>,
+    /// Address of the instruction that's executed.
+    pc: PhysAddr,
+}
+
+impl Machine<'_> {
+    fn size_of(&self, expr: &Expr) -> u8 {
+        width(expr).unwrap_or(self.width)
+    }
+
+    fn read_reg(&self, reg: Reg) -> Result<u64, Stop> {
+        if let Some(&addr) = self.unknown.get(reg.name) {
+            return Err(Stop::Unmodelled(addr));
+        }
+
+        // registers that weren't written yet are zero
+        let value = self.registers.get(reg.name).copied().unwrap_or(0);
+        Ok((value >> (reg.offset as u32 * 8)) & mask(reg.size))
+    }
+
+    fn write_reg(&mut self, reg: Reg, value: u64) {
+        if reg.name == "zero" {
+            return;
+        }
+
+        // writing part of a register leaves the rest of it unchanged
+        let shift = reg.offset as u32 * 8;
+        let bits = mask(reg.size) << shift;
+        let old = self.registers.get(reg.name).copied().unwrap_or(0);
+        self.registers.insert(reg.name, (old & !bits) | ((value << shift) & bits));
+
+        if reg.offset == 0 && reg.size >= self.width {
+            self.unknown.remove(reg.name);
+        }
+    }
+
+    fn byte(&self, addr: u64) -> u8 {
+        if let Some(&byte) = self.memory.get(&addr) {
+            return byte;
+        }
+
+        self.image.byte_at(addr as PhysAddr)
+    }
+
+    fn load(&self, addr: u64, size: u8) -> u64 {
+        let bytes = (0..size as u64).map(|offset| self.byte(addr.wrapping_add(offset)));
+        let mut bytes: Vec<u8> = bytes.collect();
+        if self.image.big_endian() {
+            bytes.reverse();
+        }
+
+        bytes.iter().rev().fold(0, |value, &byte| (value << 8) | byte as u64)
+    }
+
+    fn store(&mut self, addr: u64, size: u8, value: u64) {
+        for idx in 0..size as u64 {
+            let shift = match self.image.big_endian() {
+                false => idx * 8,
+                true => (size as u64 - idx - 1) * 8,
+            };
+
+            self.memory.insert(addr.wrapping_add(idx), (value >> shift) as u8);
+        }
+    }
+
+    fn eval(&self, expr: &Expr) -> Result<u64, Stop> {
+        let size = self.size_of(expr);
+        let value = match expr {
+            Expr::Const(value) => *value,
+            Expr::Reg(reg) => self.read_reg(*reg)?,
+            Expr::Load { addr, size } => self.load(self.eval(addr)?, *size),
+            Expr::Unary(op, value) => match op {
+                UnaryOp::Neg => self.eval(value)?.wrapping_neg() & mask(size),
+                UnaryOp::Not => !self.eval(value)? & mask(size),
+            },
+            Expr::Binary(op, lhs, rhs) => {
+                self.binary(*op, self.eval(lhs)?, self.eval(rhs)?, size)? & mask(size)
+            }
+            Expr::Truncate(value, size) => self.eval(value)? & mask(*size),
+            Expr::SignExtend(value, size) => signed(self.eval(value)?, *size) as u64,
+            Expr::Cond(cond) => self.holds(cond)? as u64,
+            Expr::Select(cond, then, otherwise) => match self.holds(cond)? {
+                true => self.eval(then)?,
+                false => self.eval(otherwise)?,
+            },
+            Expr::Unknown => return Err(Stop::Unmodelled(self.pc)),
+        };
+
+        Ok(value)
+    }
+
+    fn binary(&self, op: BinaryOp, lhs: u64, rhs: u64, size: u8) -> Result<u64, Stop> {
+        let (lhs, bits) = (lhs & mask(size), size as u64 * 8);
+        let divisor = |rhs: u64| match rhs & mask(size) {
+            0 => Err(Stop::DivideByZero),
+            rhs => Ok(rhs),
+        };
+
+        Ok(match op {
+            BinaryOp::Add => lhs.wrapping_add(rhs),
+            BinaryOp::Sub => lhs.wrapping_sub(rhs),
+            BinaryOp::Mul => lhs.wrapping_mul(rhs),
+            BinaryOp::Div => lhs / divisor(rhs)?,
+            BinaryOp::Rem => lhs % divisor(rhs)?,
+            BinaryOp::SignedDiv => {
+                signed(lhs, size).wrapping_div(signed(divisor(rhs)?, size)) as u64
+            }
+            BinaryOp::SignedRem => {
+                signed(lhs, size).wrapping_rem(signed(divisor(rhs)?, size)) as u64
+            }
+            BinaryOp::And => lhs & rhs,
+            BinaryOp::Or => lhs | rhs,
+            BinaryOp::Xor => lhs ^ rhs,
+            BinaryOp::Shl => lhs.checked_shl(rhs as u32).filter(|_| rhs < 64).unwrap_or(0),
+            BinaryOp::Shr => lhs.checked_shr(rhs as u32).filter(|_| rhs < 64).unwrap_or(0),
+            BinaryOp::Sar => (signed(lhs, size) >> rhs.min(63)) as u64,
+            BinaryOp::Ror => match rhs % bits {
+                0 => lhs,
+                rhs => (lhs >> rhs) | (lhs << (bits - rhs)),
+            },
+        })
+    }
+
+    fn holds(&self, cond: &Cond) -> Result<bool, Stop> {
+        match cond {
+            Cond::Flags(condition) => {
+                let flags = self.flags.map_err(Stop::Unmodelled)?;
+                Ok(flags.holds(*condition))
+            }
+            Cond::Compare(condition, lhs, rhs) => {
+                let size = width(lhs).or(width(rhs)).unwrap_or(self.width);
+                let (lhs, rhs) = (self.eval(lhs)? & mask(size), self.eval(rhs)? & mask(size));
+                Ok(FlagState::Compare(lhs, rhs, size).holds(*condition))
+            }
+        }
+    }
+
+    fn set_flags(&self, flags: &Flags) -> Result<FlagState, Stop> {
+        match flags {
+            Flags::Compare(lhs, rhs) => {
+                let size = width(lhs).or(width(rhs)).unwrap_or(self.width);
+                let (lhs, rhs) = (self.eval(lhs)? & mask(size), self.eval(rhs)? & mask(size));
+                Ok(FlagState::Compare(lhs, rhs, size))
+            }
+            Flags::Test(lhs, rhs) => {
+                let size = width(lhs).or(width(rhs)).unwrap_or(self.width);
+                let result = self.eval(lhs)? & self.eval(rhs)? & mask(size);
+                Ok(FlagState::Result(result, size))
+            }
+            Flags::Result(value) => {
+                let size = self.size_of(value);
+                Ok(FlagState::Result(self.eval(value)? & mask(size), size))
+            }
+            Flags::Unknown => Err(Stop::Unmodelled(self.pc)),
+        }
+    }
+
+    /// Execute the statements of a single instruction.
+    fn execute(&mut self, stmts: &[Stmt]) -> Result<Next, Stop> {
+        let mut next = Next::Continue;
+
+        for stmt in stmts {
+            match stmt {
+                Stmt::Assign {
+                    dst,
+                    src: Expr::Unknown,
+                } => {
+                    self.unknown.insert(dst.name, self.pc);
+                }
+                Stmt::Assign { dst, src } => {
+                    let value = self.eval(src)?;
+                    self.write_reg(*dst, value);
+                }
+                Stmt::Store { addr, size, src } => {
+                    let (addr, value) = (self.eval(addr)?, self.eval(src)?);
+                    self.store(addr, *size, value);
+                }
+                // flags that are never used don't stop the emulation
+                Stmt::Flags(flags) => self.flags = self.set_flags(flags).map_err(|_| self.pc),
+                Stmt::Jump(target) => next = Next::Jump(self.eval(target)?),
+                Stmt::Branch { cond, target } => {
+                    if self.holds(cond)? {
+                        next = Next::Jump(self.eval(target)?);
+                    }
+                }
+                Stmt::Call(target) => next = Next::Call(self.eval(target)?),
+                Stmt::Return => next = Next::Return,
+                Stmt::Halt => next = Next::Halt,
+                Stmt::Unknown => {}
+            }
+        }
+
+        Ok(next)
+    }
+
+    fn push(&mut self, sp: Reg, value: u64) {
+        let addr = self.registers[sp.name].wrapping_sub(self.width as u64);
+        self.store(addr, self.width, value);
+        self.write_reg(sp, addr);
+    }
+}
+
+/// Merge the bytes at consecutive addresses.
+fn consecutive(memory: impl Iterator<Item = (u64, u8)>) -> Vec<(PhysAddr, Vec<u8>)> {
+    let mut runs: Vec<(PhysAddr, Vec<u8>)> = Vec::new();
+
+    for (addr, byte) in memory {
+        let addr = addr as PhysAddr;
+        match runs.last_mut() {
+            Some((start, bytes)) if *start + bytes.len() == addr => bytes.push(byte),
+            _ => runs.push((addr, vec![byte])),
+        }
+    }
+
+    runs
+}
+
+impl Processor {
+    /// Name of the calling convention functions most likely follow, guessed from the
+    /// architecture and format of the binary.
+    pub fn calling_convention(&self) -> Option<&'static str> {
+        convention(self.arch, self.format).map(|convention| convention.name)
+    }
+
+    /// Run the function containing an address, passing it arguments as its calling convention
+    /// does.
+    pub fn emulate(&self, addr: PhysAddr, args: &[u64]) -> Result<Emulation, Error> {
+        let convention =
+            convention(self.arch, self.format).ok_or(Error::UnsupportedArchitecture(self.arch))?;
+
+        let start = self.function_bounds(addr).map_or(addr, |(start, ..)| start);
+        if self.instruction_by_addr(start).is_none() {
+            return Err(Error::NoInstructions(start));
+        }
+
+        let width = self.arch.address_size().map_or(8, |size| size.bytes());
+        Ok(run(self, start, &convention, width, args))
+    }
+}
+
+/// Run code from an address until it returns to its caller or stops otherwise.
+fn run(
+    image: &dyn Image,
+    start: PhysAddr,
+    convention: &Convention,
+    width: u8,
+    args: &[u64],
+) -> Emulation {
+    let stack_top = match width {
+        8 => STACK_TOP_64,
+        _ => STACK_TOP_32,
+    };
+
+    let mut machine = Machine {
+        image,
+        width,
+        registers: BTreeMap::new(),
+        unknown: BTreeMap::new(),
+        memory: BTreeMap::new(),
+        flags: Ok(FlagState::Result(0, width)),
+        pc: start,
+    };
+
+    let sp = Reg::full(convention.sp, width);
+    machine.write_reg(sp, stack_top);
+
+    let (in_registers, on_stack) = args.split_at(args.len().min(convention.args.len()));
+    for (name, &arg) in convention.args.iter().zip(in_registers) {
+        machine.write_reg(Reg::full(name, width), arg);
+    }
+
+    for &arg in on_stack.iter().rev() {
+        machine.push(sp, arg);
+    }
+
+    let stack = machine.registers[sp.name].wrapping_sub(convention.shadow);
+    machine.write_reg(sp, stack);
+    match convention.link {
+        Some(link) => machine.write_reg(Reg::full(link, width), RETURN_ADDR),
+        None => machine.push(sp, RETURN_ADDR),
+    }
+
+    // return addresses of the functions that were called
+    let mut calls = Vec::new();
+    let mut skipped = Vec::new();
+    let mut steps = 0;
+    let mut pc = start;
+
+    let stop = loop {
+        if steps == MAX_STEPS {
+            break Stop::TooManySteps;
+        }
+
+        let (stmts, size) = match image.instruction_at(pc) {
+            Some(instruction) => instruction,
+            None => break Stop::NoInstruction(pc),
+        };
+
+        steps += 1;
+        machine.pc = pc;
+
+        if stmts.contains(&Stmt::Unknown) {
+            skipped.push(pc);
+        }
+
+        let after = pc + size;
+        let next = match machine.execute(&stmts) {
+            Ok(next) => next,
+            Err(stop) => break stop,
+        };
+
+        match next {
+            Next::Continue => pc = after,
+            Next::Jump(target) => pc = target as PhysAddr,
+            Next::Call(target) => {
+                if image.instruction_at(target as PhysAddr).is_none() {
+                    break Stop::External(target as PhysAddr);
+                }
+
+                match convention.link {
+                    Some(link) => machine.write_reg(Reg::full(link, width), after as u64),
+                    None => machine.push(sp, after as u64),
+                }
+
+                calls.push(after);
+                pc = target as PhysAddr;
+            }
+            Next::Return => match calls.pop() {
+                Some(ret) => {
+                    // returning pops the address pushed by the call
+                    if convention.link.is_none() {
+                        let sp_value = machine.registers[sp.name].wrapping_add(width as u64);
+                        machine.write_reg(sp, sp_value);
+                    }
+
+                    pc = ret;
+                }
+                None => break Stop::Returned,
+            },
+            Next::Halt => break Stop::Halted,
+        }
+    };
+
+    skipped.dedup();
+    let returned = machine.read_reg(Reg::full(convention.ret, width)).ok();
+    let stack = stack_top - STACK_SIZE..stack_top;
+    let writes = machine.memory.into_iter().filter(|(addr, _)| !stack.contains(addr));
+
+    machine.registers.retain(|name, _| !machine.unknown.contains_key(name));
+
+    Emulation {
+        addr: start,
+        stop,
+        pc,
+        steps,
+        returned,
+        registers: machine.registers,
+        writes: consecutive(writes),
+        skipped,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Instructions two bytes apart starting at 0x1000, along with bytes of data.
+    #[derive(Default)]
+    struct Program {
+        big_endian: bool,
+        code: Vec<Vec<Stmt>>,
+        data: BTreeMap<PhysAddr, u8>,
+    }
+
+    impl Image for Program {
+        fn big_endian(&self) -> bool {
+            self.big_endian
+        }
+
+        fn byte_at(&self, addr: PhysAddr) -> u8 {
+            self.data.get(&addr).copied().unwrap_or(0)
+        }
+
+        fn instruction_at(&self, addr: PhysAddr) -> Option<(Vec<Stmt>, usize)> {
+            let idx = addr.checked_sub(0x1000).filter(|offset| offset % 2 == 0)? / 2;
+            Some((self.code.get(idx)?.clone(), 2))
+        }
+    }
+
+    fn machine(image: &dyn Image) -> Machine<'_> {
+        Machine {
+            image,
+            width: 8,
+            registers: BTreeMap::new(),
+            unknown: BTreeMap::new(),
+            memory: BTreeMap::new(),
+            flags: Ok(FlagState::Result(0, 8)),
+            pc: 0x1000,
+        }
+    }
+
+    fn run(program: &Program, args: &[u64]) -> Emulation {
+        let convention = convention(Architecture::X86_64, BinaryFormat::Elf).unwrap();
+        super::run(program, 0x1000, &convention, 8, args)
+    }
+
+    #[test]
+    fn masks() {
+        assert_eq!(mask(0), 0);
+        assert_eq!(mask(1), 0xFF);
+        assert_eq!(mask(4), 0xFFFF_FFFF);
+        assert_eq!(mask(8), u64::MAX);
+
+        assert_eq!(signed(0x7F, 1), 127);
+        assert_eq!(signed(0xFF, 1), -1);
+        assert_eq!(signed(0xFFFF_FFFF, 4), -1);
+        assert_eq!(signed(0xFFFF_FFFF, 8), 0xFFFF_FFFF);
+
+        // sizes out of range are clamped
+        assert_eq!(signed(0x80, 0), -128);
+        assert_eq!(signed(u64::MAX, 16), -1);
+    }
+
+    #[test]
+    fn flags() {
+        let compare = |lhs, rhs, condition| FlagState::Compare(lhs, rhs, 4).holds(condition);
+        assert!(compare(1, 2, Condition::Lt));
+        assert!(compare(1, 2, Condition::Below));
+        assert!(compare(2, 2, Condition::Le) && compare(2, 2, Condition::AboveEq));
+
+        // -1 is less than 1 when signed, but above it when unsigned
+        assert!(compare(0xFFFF_FFFF, 1, Condition::Lt));
+        assert!(compare(0xFFFF_FFFF, 1, Condition::Above));
+        assert!(!compare(0xFFFF_FFFF, 1, Condition::Below));
+
+        // the smallest value minus one overflows, which keeps it less than one
+        assert!(compare(0x8000_0000, 1, Condition::Overflow));
+        assert!(compare(0x8000_0000, 1, Condition::Lt));
+        assert!(!compare(0x8000_0000, 1, Condition::Ge));
+        assert!(!compare(0x8000_0000, 1, Condition::Negative));
+
+        let result = |value, size, condition| FlagState::Result(value, size).holds(condition);
+        assert!(result(0, 8, Condition::Eq));
+        assert!(result(0x80, 1, Condition::Negative));
+        assert!(result(0x80, 2, Condition::NotNegative));
+        assert!(result(0x80, 1, Condition::NoParity));
+        assert!(result(0x103, 2, Condition::Parity));
+        assert!(!result(0x80, 1, Condition::Overflow));
+    }
+
+    #[test]
+    fn binary() {
+        let program = Program::default();
+        let machine = machine(&program);
+        let binary =
+            |op, lhs, rhs, size| machine.binary(op, lhs, rhs, size).map(|value| value & mask(size));
+
+        assert_eq!(binary(BinaryOp::Ror, 0x1, 1, 1), Ok(0x80));
+        assert_eq!(binary(BinaryOp::Ror, 0x1234_5678, 8, 4), Ok(0x7812_3456));
+        assert_eq!(binary(BinaryOp::Ror, 0x1234_5678, 32, 4), Ok(0x1234_5678));
+
+        assert_eq!(binary(BinaryOp::Sar, 0x80, 7, 1), Ok(0xFF));
+        assert_eq!(binary(BinaryOp::Sar, 0x40, 7, 1), Ok(0));
+        assert_eq!(binary(BinaryOp::Sar, 0x8000, 200, 2), Ok(0xFFFF));
+        assert_eq!(binary(BinaryOp::Shl, 1, 64, 8), Ok(0));
+
+        // -6 / 2 and -7 % 2
+        assert_eq!(binary(BinaryOp::SignedDiv, 0xFFFA, 2, 2), Ok(0xFFFD));
+        assert_eq!(binary(BinaryOp::SignedRem, 0xFFF9, 2, 2), Ok(0xFFFF));
+
+        // divisors are truncated to the size of the operation before being checked
+        let zero = Err(Stop::DivideByZero);
+        assert_eq!(binary(BinaryOp::Div, 1, 0, 4), zero);
+        assert_eq!(binary(BinaryOp::Rem, 1, 0x1_0000_0000, 4), zero);
+        assert_eq!(binary(BinaryOp::SignedDiv, 1, 0, 8), zero);
+    }
+
+    #[test]
+    fn endianness() {
+        let mut program = Program {
+            big_endian: true,
+            ..Program::default()
+        };
+        program.data.extend([(0x2000, 0xAA), (0x2001, 0xBB)]);
+
+        let mut big = machine(&program);
+        assert_eq!(big.load(0x2000, 2), 0xAABB);
+
+        big.store(0x3000, 4, 0x1122_3344);
+        let stored: Vec<u8> = big.memory.values().copied().collect();
+        assert_eq!(stored, [0x11, 0x22, 0x33, 0x44]);
+        assert_eq!(big.load(0x3000, 4), 0x1122_3344);
+        assert_eq!(big.load(0x3000, 2), 0x1122);
+
+        let little = Program {
+            big_endian: false,
+            ..program
+        };
+        let mut little = machine(&little);
+        assert_eq!(little.load(0x2000, 2), 0xBBAA);
+
+        little.store(0x3000, 4, 0x1122_3344);
+        assert_eq!(little.load(0x3000, 2), 0x3344);
+    }
+
+    #[test]
+    fn returns() {
+        let rax = Reg::full("rax", 8);
+        let rdi = Expr::reg(Reg::full("rdi", 8));
+        let program = Program {
+            code: vec![
+                vec![Stmt::Assign {
+                    dst: rax,
+                    src: Expr::binary(BinaryOp::Add, rdi, Expr::Const(1)),
+                }],
+                vec![Stmt::Return],
+            ],
+            ..Program::default()
+        };
+
+        let emulation = run(&program, &[5]);
+        assert_eq!(emulation.stop, Stop::Returned);
+        assert_eq!(emulation.returned, Some(6));
+        assert_eq!(emulation.steps, 2);
+        assert_eq!(emulation.pc, 0x1002);
+    }
+
+    #[test]
+    fn too_many_steps() {
+        let program = Program {
+            code: vec![vec![Stmt::Jump(Expr::Const(0x1000))]],
+            ..Program::default()
+        };
+
+        let emulation = run(&program, &[]);
+        assert_eq!(emulation.stop, Stop::TooManySteps);
+        assert_eq!(emulation.steps, MAX_STEPS);
+        assert_eq!(emulation.pc, 0x1000);
+    }
+}
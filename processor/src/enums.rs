@@ -0,0 +1,280 @@
+//! Constants that immediates stand for.
+//!
+//! Enumerations come from the debug info, are defined by the user or are one of the common ones
+//! of libc and the Win32 API below. Applying an enumeration to an instruction shows its immediates
+//! by name, and arguments passed to functions with a known prototype are named by the
+//! enumeration of their parameter.
+
+use crate::typelib::ApiPrototype;
+use crate::Processor;
+use config::CONFIG;
+use debugvault::types::{self, Enum};
+use debugvault::Index;
+use processor_shared::PhysAddr;
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::sync::{Arc, OnceLock};
+use tokenizing::{Token, TokenKind};
+
+/// Enumerations of libc as defined on Linux, and of the Win32 API.
+const DEFINITIONS: &str = "
+enum open_flags {
+    O_RDONLY = 0, O_WRONLY = 0x1, O_RDWR = 0x2, O_CREAT = 0x40, O_EXCL = 0x80, O_NOCTTY = 0x100,
+    O_TRUNC = 0x200, O_APPEND = 0x400, O_NONBLOCK = 0x800, O_DSYNC = 0x1000, O_DIRECT = 0x4000,
+    O_DIRECTORY = 0x10000, O_NOFOLLOW = 0x20000, O_CLOEXEC = 0x80000
+};
+enum mmap_prot { PROT_NONE = 0, PROT_READ = 0x1, PROT_WRITE = 0x2, PROT_EXEC = 0x4 };
+enum mmap_flags {
+    MAP_SHARED = 0x1, MAP_PRIVATE = 0x2, MAP_FIXED = 0x10, MAP_ANONYMOUS = 0x20,
+    MAP_NORESERVE = 0x4000, MAP_POPULATE = 0x8000
+};
+enum seek_whence { SEEK_SET, SEEK_CUR, SEEK_END };
+enum access_mode { F_OK = 0, X_OK = 0x1, W_OK = 0x2, R_OK = 0x4 };
+enum signal {
+    SIGHUP = 1, SIGINT, SIGQUIT, SIGILL, SIGTRAP, SIGABRT, SIGBUS, SIGFPE, SIGKILL, SIGUSR1,
+    SIGSEGV, SIGUSR2, SIGPIPE, SIGALRM, SIGTERM, SIGSTKFLT, SIGCHLD, SIGCONT, SIGSTOP, SIGTSTP,
+    SIGTTIN, SIGTTOU, SIGURG, SIGXCPU, SIGXFSZ, SIGVTALRM, SIGPROF, SIGWINCH, SIGIO, SIGPWR,
+    SIGSYS
+};
+enum address_family { AF_UNSPEC, AF_UNIX, AF_INET, AF_INET6 = 10, AF_NETLINK = 16, AF_PACKET };
+enum socket_type {
+    SOCK_STREAM = 1, SOCK_DGRAM, SOCK_RAW, SOCK_NONBLOCK = 0x800, SOCK_CLOEXEC = 0x80000
+};
+enum fcntl_cmd {
+    F_DUPFD, F_GETFD, F_SETFD, F_GETFL, F_SETFL, F_GETLK, F_SETLK, F_SETLKW,
+    F_DUPFD_CLOEXEC = 1030
+};
+enum ptrace_request {
+    PTRACE_TRACEME, PTRACE_PEEKTEXT, PTRACE_PEEKDATA, PTRACE_PEEKUSER, PTRACE_POKETEXT,
+    PTRACE_POKEDATA, PTRACE_POKEUSER, PTRACE_CONT, PTRACE_KILL, PTRACE_SINGLESTEP,
+    PTRACE_GETREGS = 12, PTRACE_SETREGS, PTRACE_ATTACH = 16, PTRACE_DETACH, PTRACE_SYSCALL = 24
+};
+enum dlopen_flags {
+    RTLD_LAZY = 0x1, RTLD_NOW = 0x2, RTLD_NOLOAD = 0x4, RTLD_GLOBAL = 0x100, RTLD_NODELETE = 0x1000
+};
+enum wait_options { WNOHANG = 0x1, WUNTRACED = 0x2, WCONTINUED = 0x8 };
+enum access_rights {
+    DELETE = 0x10000, READ_CONTROL = 0x20000, WRITE_DAC = 0x40000, SYNCHRONIZE = 0x100000,
+    GENERIC_ALL = 0x10000000, GENERIC_EXECUTE = 0x20000000, GENERIC_WRITE = 0x40000000,
+    GENERIC_READ = 0x80000000
+};
+enum file_share { FILE_SHARE_READ = 0x1, FILE_SHARE_WRITE = 0x2, FILE_SHARE_DELETE = 0x4 };
+enum creation_disposition {
+    CREATE_NEW = 1, CREATE_ALWAYS, OPEN_EXISTING, OPEN_ALWAYS, TRUNCATE_EXISTING
+};
+enum file_attributes {
+    FILE_ATTRIBUTE_READONLY = 0x1, FILE_ATTRIBUTE_HIDDEN = 0x2, FILE_ATTRIBUTE_SYSTEM = 0x4,
+    FILE_ATTRIBUTE_DIRECTORY = 0x10, FILE_ATTRIBUTE_ARCHIVE = 0x20, FILE_ATTRIBUTE_NORMAL = 0x80,
+    FILE_ATTRIBUTE_TEMPORARY = 0x100, FILE_FLAG_DELETE_ON_CLOSE = 0x4000000,
+    FILE_FLAG_NO_BUFFERING = 0x20000000, FILE_FLAG_OVERLAPPED = 0x40000000,
+    FILE_FLAG_WRITE_THROUGH = 0x80000000
+};
+enum allocation_type {
+    MEM_COMMIT = 0x1000, MEM_RESERVE = 0x2000, MEM_DECOMMIT = 0x4000, MEM_RELEASE = 0x8000,
+    MEM_RESET = 0x80000, MEM_TOP_DOWN = 0x100000, MEM_LARGE_PAGES = 0x20000000
+};
+enum page_protection {
+    PAGE_NOACCESS = 0x1, PAGE_READONLY = 0x2, PAGE_READWRITE = 0x4, PAGE_WRITECOPY = 0x8,
+    PAGE_EXECUTE = 0x10, PAGE_EXECUTE_READ = 0x20, PAGE_EXECUTE_READWRITE = 0x40,
+    PAGE_EXECUTE_WRITECOPY = 0x80, PAGE_GUARD = 0x100, PAGE_NOCACHE = 0x200
+};
+enum process_access {
+    PROCESS_TERMINATE = 0x1, PROCESS_CREATE_THREAD = 0x2, PROCESS_VM_OPERATION = 0x8,
+    PROCESS_VM_READ = 0x10, PROCESS_VM_WRITE = 0x20, PROCESS_DUP_HANDLE = 0x40,
+    PROCESS_CREATE_PROCESS = 0x80, PROCESS_QUERY_INFORMATION = 0x400,
+    PROCESS_SUSPEND_RESUME = 0x800, PROCESS_QUERY_LIMITED_INFORMATION = 0x1000,
+    PROCESS_ALL_ACCESS = 0x1fffff
+};
+enum win32_error {
+    ERROR_SUCCESS, ERROR_INVALID_FUNCTION, ERROR_FILE_NOT_FOUND, ERROR_PATH_NOT_FOUND,
+    ERROR_TOO_MANY_OPEN_FILES, ERROR_ACCESS_DENIED, ERROR_INVALID_HANDLE,
+    ERROR_NOT_ENOUGH_MEMORY = 8, ERROR_INVALID_DATA = 13, ERROR_OUTOFMEMORY,
+    ERROR_SHARING_VIOLATION = 32, ERROR_HANDLE_EOF = 38, ERROR_NOT_SUPPORTED = 50,
+    ERROR_FILE_EXISTS = 80, ERROR_INVALID_PARAMETER = 87, ERROR_BROKEN_PIPE = 109,
+    ERROR_INSUFFICIENT_BUFFER = 122, ERROR_INVALID_NAME, ERROR_MOD_NOT_FOUND = 126,
+    ERROR_PROC_NOT_FOUND, ERROR_ALREADY_EXISTS = 183, ERROR_ENVVAR_NOT_FOUND = 203,
+    ERROR_MORE_DATA = 234, ERROR_NO_MORE_ITEMS = 259, ERROR_IO_PENDING = 997, ERROR_NOACCESS,
+    ERROR_TIMEOUT = 1460
+};
+enum message_box {
+    MB_OK, MB_OKCANCEL, MB_ABORTRETRYIGNORE, MB_YESNOCANCEL, MB_YESNO, MB_RETRYCANCEL,
+    MB_ICONERROR = 0x10, MB_ICONQUESTION = 0x20, MB_ICONWARNING = 0x30,
+    MB_ICONINFORMATION = 0x40, MB_TOPMOST = 0x40000
+};
+enum registry_key {
+    HKEY_CLASSES_ROOT = 0x80000000, HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE, HKEY_USERS
+};
+enum registry_access {
+    KEY_QUERY_VALUE = 0x1, KEY_SET_VALUE = 0x2, KEY_CREATE_SUB_KEY = 0x4,
+    KEY_ENUMERATE_SUB_KEYS = 0x8, KEY_NOTIFY = 0x10, KEY_CREATE_LINK = 0x20,
+    KEY_WOW64_64KEY = 0x100, KEY_WOW64_32KEY = 0x200, KEY_WRITE = 0x20006, KEY_READ = 0x20019,
+    KEY_ALL_ACCESS = 0xf003f
+};
+enum timeout { INFINITE = 0xffffffff };
+";
+
+/// Enumerations of the parameters of functions in the [`typelib`](crate::typelib), a function
+/// of `*` matches a parameter of any function.
+const PARAMETERS: &[(&str, &str, &str)] = &[
+    ("open", "flags", "open_flags"),
+    ("openat", "flags", "open_flags"),
+    ("mmap", "flags", "mmap_flags"),
+    ("*", "prot", "mmap_prot"),
+    ("*", "whence", "seek_whence"),
+    ("access", "mode", "access_mode"),
+    ("*", "sig", "signal"),
+    ("socket", "domain", "address_family"),
+    ("socket", "type", "socket_type"),
+    ("fcntl", "cmd", "fcntl_cmd"),
+    ("ptrace", "request", "ptrace_request"),
+    ("dlopen", "flags", "dlopen_flags"),
+    ("waitpid", "options", "wait_options"),
+    ("CreateFileA", "dwDesiredAccess", "access_rights"),
+    ("CreateFileW", "dwDesiredAccess", "access_rights"),
+    ("MapViewOfFile", "dwDesiredAccess", "access_rights"),
+    ("*", "dwShareMode", "file_share"),
+    ("*", "dwCreationDisposition", "creation_disposition"),
+    ("*", "dwFlagsAndAttributes", "file_attributes"),
+    ("*", "flAllocationType", "allocation_type"),
+    ("VirtualFree", "dwFreeType", "allocation_type"),
+    ("*", "flProtect", "page_protection"),
+    ("*", "flNewProtect", "page_protection"),
+    ("OpenProcess", "dwDesiredAccess", "process_access"),
+    ("SetLastError", "dwErrCode", "win32_error"),
+    ("*", "uType", "message_box"),
+    ("*", "hKey", "registry_key"),
+    ("*", "samDesired", "registry_access"),
+    ("*", "dwMilliseconds", "timeout"),
+];
+
+fn library() -> &'static [Enum] {
+    static LIBRARY: OnceLock<Vec<Enum>> = OnceLock::new();
+    LIBRARY.get_or_init(|| types::parse_enums(DEFINITIONS).unwrap_or_default())
+}
+
+/// Enumerations of the library and those described by the debug info, by name.
+pub(crate) fn known(index: &Index) -> BTreeMap<String, Arc<Enum>> {
+    library()
+        .iter()
+        .chain(index.enums.iter())
+        .map(|def| (def.name.clone(), Arc::new(def.clone())))
+        .collect()
+}
+
+/// Value of an immediate as it's displayed, e.g. `0x10`, `-0x8` or `#16`.
+fn parse_immediate(text: &str) -> Option<u64> {
+    let text = text.trim_start_matches(['#', '$']);
+    let (negative, text) = match text.strip_prefix('-') {
+        Some(text) => (true, text),
+        None => (false, text),
+    };
+
+    let value = match text.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).ok()?,
+        None => text.parse().ok()?,
+    };
+
+    Some(if negative {
+        value.wrapping_neg()
+    } else {
+        value
+    })
+}
+
+impl Processor {
+    /// Add the enumerations of a C definition, returning their names, see [`types`].
+    pub fn define_enums(&self, src: &str) -> Result<Vec<String>, types::Error> {
+        let parsed = types::parse_enums(src)?;
+        let names = parsed.iter().map(|def| def.name.clone()).collect();
+
+        let mut enums = self.enums.write().unwrap();
+        for def in parsed {
+            enums.insert(def.name.clone(), Arc::new(def));
+        }
+
+        Ok(names)
+    }
+
+    pub fn enum_by_name(&self, name: &str) -> Option<Arc<Enum>> {
+        self.enums.read().unwrap().get(name).cloned()
+    }
+
+    /// Names of the enumerations that can be applied.
+    pub fn enum_names(&self) -> Vec<String> {
+        self.enums.read().unwrap().keys().cloned().collect()
+    }
+
+    /// Show the immediates of the instruction at an address as constants of an enumeration.
+    ///
+    /// Returns the range of the instruction, if the enumeration is known.
+    pub fn apply_enum(&self, addr: PhysAddr, name: &str) -> Option<Range<usize>> {
+        let instruction = self.instruction_by_addr(addr)?;
+        let def = self.enum_by_name(name)?;
+        self.applied_enums.write().unwrap().insert(addr, def);
+        Some(addr..addr + self.instruction_width(&instruction))
+    }
+
+    /// Show the immediates of the instruction at an address as numbers again.
+    ///
+    /// Returns the range of the instruction.
+    pub fn remove_enum(&self, addr: PhysAddr) -> Option<Range<usize>> {
+        self.applied_enums.write().unwrap().remove(&addr)?;
+        let instruction = self.instruction_by_addr(addr)?;
+        Some(addr..addr + self.instruction_width(&instruction))
+    }
+
+    /// Enumeration applied to the instruction at an address.
+    pub fn enum_at(&self, addr: PhysAddr) -> Option<Arc<Enum>> {
+        self.applied_enums.read().unwrap().get(&addr).cloned()
+    }
+
+    /// Replace the immediates of an instruction by the constants they stand for.
+    pub(crate) fn symbolic_immediates(&self, addr: PhysAddr, mut tokens: Vec<Token>) -> Vec<Token> {
+        let def = match self.enum_at(addr) {
+            Some(def) => def,
+            None => return tokens,
+        };
+
+        for token in tokens.iter_mut() {
+            if token.kind != TokenKind::Immediate {
+                continue;
+            }
+
+            let name = parse_immediate(&token.text).and_then(|value| def.render(value));
+            if let Some(name) = name {
+                *token = Token::from_string(name, CONFIG.colors.src.constant)
+                    .with_kind(TokenKind::Immediate);
+            }
+        }
+
+        tokens
+    }
+
+    /// Enumeration of the value an instruction passes as an argument, either the one applied to
+    /// the instruction or the one of the parameter.
+    pub(crate) fn argument_enum(
+        &self,
+        addr: PhysAddr,
+        api: Option<&ApiPrototype>,
+        idx: usize,
+    ) -> Option<Arc<Enum>> {
+        if let Some(def) = self.enum_at(addr) {
+            return Some(def);
+        }
+
+        let api = api?;
+        let param = api.params.get(idx)?;
+        let name = PARAMETERS
+            .iter()
+            .find(|(func, param_name, _)| {
+                (*func == "*" || *func == api.name) && *param_name == param.name
+            })
+            .map(|(_, _, name)| *name);
+
+        // the type of a parameter can be an enumeration defined by the user
+        match name {
+            Some(name) => self.enum_by_name(name),
+            None => self.enum_by_name(param.ty.trim_start_matches("const ")),
+        }
+    }
+}
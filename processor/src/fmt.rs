@@ -15,6 +15,7 @@ impl fmt::Debug for super::Error {
             Self::UnknownArchitecture(arch) => {
                 f.write_fmt(format_args!("Unsupported architecture: '{arch:?}'."))
             }
+            Self::Cancelled => f.write_str("Loading the binary was cancelled."),
         }
     }
 }
@@ -0,0 +1,201 @@
+//! Rough profile of a function, to help decide which functions are worth reversing first.
+
+use crate::{Loop, Processor};
+use decoder::ir::{BinaryOp, Expr, Flags, Stmt};
+use processor_shared::PhysAddr;
+
+/// Registers that hold the stack pointer.
+pub(crate) const STACK_POINTERS: &[&str] = &["rsp", "esp", "sp"];
+
+/// Prefixes of vector register names, followed by the register's number.
+const SIMD_REGISTERS: &[&str] = &["xmm", "ymm", "zmm", "mm", "v", "q"];
+
+/// Approximate cycles per instruction of each category, modelled after a modern out-of-order core
+/// where nothing misses the cache.
+const ALU_CYCLES: usize = 1;
+const MEMORY_CYCLES: usize = 4;
+const BRANCH_CYCLES: usize = 2;
+const SIMD_CYCLES: usize = 3;
+const OTHER_CYCLES: usize = 1;
+const DIVISION_CYCLES: usize = 25;
+
+/// Instruction counts and other metrics of a single function.
+#[derive(Debug, Default, Clone)]
+pub struct FunctionStats {
+    pub addr: PhysAddr,
+    pub name: String,
+    pub instructions: usize,
+    /// Arithmetic, logic and comparisons.
+    pub alu: usize,
+    /// Loads and stores.
+    pub memory: usize,
+    /// Jumps, branches, calls and returns.
+    pub branch: usize,
+    /// Instructions operating on vector registers.
+    pub simd: usize,
+    /// Moves and anything that isn't modelled.
+    pub other: usize,
+    /// Estimated cycles it takes to execute every instruction once.
+    pub cycles: usize,
+    /// Bytes reserved on the stack, estimated from adjustments of the stack pointer.
+    pub stack_frame: u64,
+    /// Functions called directly, sorted by address.
+    pub callees: Vec<PhysAddr>,
+    /// Loops of the function's control flow graph, sorted by their header.
+    pub loops: Vec<Loop>,
+}
+
+fn reads_memory(expr: &Expr) -> bool {
+    match expr {
+        Expr::Load { .. } => true,
+        Expr::Unary(_, expr) | Expr::Truncate(expr, _) | Expr::SignExtend(expr, _) => {
+            reads_memory(expr)
+        }
+        Expr::Binary(_, lhs, rhs) => reads_memory(lhs) || reads_memory(rhs),
+        Expr::Select(_, then, otherwise) => reads_memory(then) || reads_memory(otherwise),
+        Expr::Const(..) | Expr::Reg(..) | Expr::Cond(..) | Expr::Unknown => false,
+    }
+}
+
+fn divides(expr: &Expr) -> bool {
+    matches!(
+        expr,
+        Expr::Binary(
+            BinaryOp::Div | BinaryOp::SignedDiv | BinaryOp::Rem | BinaryOp::SignedRem,
+            ..
+        )
+    )
+}
+
+/// Whether any word of an instruction names a vector register.
+fn uses_simd(text: &str) -> bool {
+    // leave out symbols, whose names could look like registers
+    let text = text.split('<').next().unwrap_or(text);
+
+    text.split(|c: char| !c.is_ascii_alphanumeric()).any(|word| {
+        SIMD_REGISTERS.iter().any(|prefix| {
+            word.strip_prefix(prefix)
+                .is_some_and(|num| !num.is_empty() && num.bytes().all(|b| b.is_ascii_digit()))
+        })
+    })
+}
+
+/// Value of a constant of `size` bytes when interpreted as signed.
+fn signed(value: u64, size: u8) -> i64 {
+    let shift = 64 - 8 * size.clamp(1, 8) as u32;
+    ((value << shift) as i64) >> shift
+}
+
+/// How much a statement grows the stack by, negative when it shrinks.
+pub(crate) fn stack_adjustment(stmt: &Stmt) -> i64 {
+    let (dst, src) = match stmt {
+        Stmt::Assign { dst, src } if STACK_POINTERS.contains(&dst.name) => (dst, src),
+        _ => return 0,
+    };
+
+    match src {
+        Expr::Binary(op, lhs, rhs) if **lhs == Expr::Reg(*dst) => match (op, &**rhs) {
+            (BinaryOp::Sub, Expr::Const(value)) => signed(*value, dst.size),
+            (BinaryOp::Add, Expr::Const(value)) => -signed(*value, dst.size),
+            _ => 0,
+        },
+        _ => 0,
+    }
+}
+
+impl Processor {
+    /// Profile the function containing an address.
+    pub fn function_stats(&self, addr: PhysAddr) -> Option<FunctionStats> {
+        let (start, end, name) = self.function_bounds(addr)?;
+
+        let mut stats = FunctionStats {
+            addr: start,
+            name,
+            callees: self.callees(start).collect(),
+            ..FunctionStats::default()
+        };
+
+        for instruction in self.instructions_from(start) {
+            if instruction.addr >= end {
+                break;
+            }
+
+            let stmts = self.instruction_ir(&instruction.item);
+            let tokens = self.instruction_tokens(&instruction.item, &self.index);
+            let text = String::from_iter(tokens.iter().map(|token| &token.text[..]));
+
+            let is_branch = stmts.iter().any(|stmt| {
+                matches!(
+                    stmt,
+                    Stmt::Jump(..)
+                        | Stmt::Branch { .. }
+                        | Stmt::Call(..)
+                        | Stmt::Return
+                        | Stmt::Halt
+                )
+            });
+
+            let is_memory = stmts.iter().any(|stmt| match stmt {
+                Stmt::Store { .. } => true,
+                Stmt::Assign { src, .. } => reads_memory(src),
+                _ => false,
+            });
+
+            let is_alu = stmts.iter().any(|stmt| match stmt {
+                Stmt::Assign { src, .. } => matches!(src, Expr::Unary(..) | Expr::Binary(..)),
+                Stmt::Flags(flags) => !matches!(flags, Flags::Unknown),
+                _ => false,
+            });
+
+            stats.instructions += 1;
+            stats.cycles += if is_branch {
+                stats.branch += 1;
+                BRANCH_CYCLES
+            } else if uses_simd(&text) {
+                stats.simd += 1;
+                SIMD_CYCLES
+            } else if is_memory {
+                stats.memory += 1;
+                MEMORY_CYCLES
+            } else if is_alu {
+                stats.alu += 1;
+                ALU_CYCLES
+            } else {
+                stats.other += 1;
+                OTHER_CYCLES
+            };
+
+            for stmt in stmts.iter() {
+                if let Stmt::Assign { src, .. } = stmt {
+                    if divides(src) {
+                        stats.cycles += DIVISION_CYCLES;
+                    }
+                }
+            }
+        }
+
+        stats.stack_frame = self.stack_frame(start, end);
+        stats.loops = self.cfg(start).map(|cfg| cfg.loops()).unwrap_or_default();
+        Some(stats)
+    }
+
+    /// Bytes a function reserves on the stack, estimated from adjustments of the stack pointer
+    /// by the instructions in between its start and end.
+    pub fn stack_frame(&self, start: PhysAddr, end: PhysAddr) -> u64 {
+        let mut depth = 0i64;
+        let mut max_depth = 0i64;
+        for instruction in self.instructions_from(start) {
+            if instruction.addr >= end {
+                break;
+            }
+
+            for stmt in self.instruction_ir(&instruction.item).iter() {
+                // epilogues restore the stack, so the frame is the deepest the stack gets
+                depth = std::cmp::max(depth + stack_adjustment(stmt), 0);
+                max_depth = std::cmp::max(max_depth, depth);
+            }
+        }
+
+        max_depth as u64
+    }
+}
@@ -0,0 +1,364 @@
+//! Hashes of the binary that are used to look it up in malware databases or to cluster it with
+//! similar binaries.
+//!
+//! TLSH is implemented here, as it's only needed for identifying binaries. It follows version 4 of
+//! the reference implementation, with a single byte checksum and 128 buckets.
+
+use crate::rich;
+use md5::Md5;
+use object::read::pe::{ImageNtHeaders, Import, PeFile};
+use object::read::File as ObjectFile;
+use object::LittleEndian as LE;
+use sha1::{Digest, Sha1};
+use sha2::Sha256;
+
+/// Hashes of the binary, hex encoded.
+#[derive(Debug, Clone, Default)]
+pub struct Hashes {
+    pub md5: String,
+    pub sha1: String,
+    pub sha256: String,
+    /// MD5 of the imports of a PE, see [`imphash`].
+    pub imphash: Option<String>,
+    /// Locality sensitive hash, which doesn't exist for binaries that are tiny or too uniform.
+    pub tlsh: Option<String>,
+    /// MD5 of the decoded Rich header of a PE, which binaries built the same way share.
+    pub rich: Option<String>,
+}
+
+impl Hashes {
+    pub fn compute(binary: &[u8], obj: Option<&ObjectFile>) -> Self {
+        Self {
+            md5: hex(&Md5::digest(binary)),
+            sha1: hex(&Sha1::digest(binary)),
+            sha256: hex(&Sha256::digest(binary)),
+            imphash: obj.and_then(imphash),
+            tlsh: tlsh(binary),
+            rich: obj.and_then(|obj| rich::parse(obj, binary)).map(|rich| rich.hash),
+        }
+    }
+
+    /// Names of the hashes along with their value, for those that exist.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        [
+            ("MD5", Some(&self.md5)),
+            ("SHA-1", Some(&self.sha1)),
+            ("SHA-256", Some(&self.sha256)),
+            ("Imphash", self.imphash.as_ref()),
+            ("TLSH", self.tlsh.as_ref()),
+            ("Rich hash", self.rich.as_ref()),
+        ]
+        .into_iter()
+        .filter_map(|(name, hash)| Some((name, hash?.as_str())))
+    }
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// MD5 of the imports of a PE, in the order they're listed, the way `pefile` computes it.
+///
+/// Every import is written as `library.function` in lowercase, where the extension of the library
+/// is left out. Functions imported by ordinal are named `ord<ordinal>`, `pefile` additionally knows
+/// the names of some of the ordinals of `ws2_32` and `oleaut32`.
+pub fn imphash(obj: &ObjectFile) -> Option<String> {
+    let imports = match obj {
+        ObjectFile::Pe32(pe) => pe_imports(pe)?,
+        ObjectFile::Pe64(pe) => pe_imports(pe)?,
+        _ => return None,
+    };
+
+    if imports.is_empty() {
+        return None;
+    }
+
+    Some(hex(&Md5::digest(imports.join(",").as_bytes())))
+}
+
+fn pe_imports<Pe: ImageNtHeaders>(pe: &PeFile<Pe>) -> Option<Vec<String>> {
+    let table = pe.import_table().ok()??;
+    let mut descriptors = table.descriptors().ok()?;
+    let mut imports = Vec::new();
+
+    while let Ok(Some(descriptor)) = descriptors.next() {
+        let library = String::from_utf8_lossy(table.name(descriptor.name.get(LE)).ok()?);
+        let library = library.to_lowercase();
+        let library = match library.rsplit_once('.') {
+            Some((name, "dll" | "ocx" | "sys")) => name.to_string(),
+            _ => library,
+        };
+
+        let thunks = match descriptor.original_first_thunk.get(LE) {
+            0 => descriptor.first_thunk.get(LE),
+            thunks => thunks,
+        };
+
+        let mut thunks = table.thunks(thunks).ok()?;
+        while let Ok(Some(thunk)) = thunks.next::<Pe>() {
+            let function = match table.import::<Pe>(thunk) {
+                Ok(Import::Name(_, name)) => String::from_utf8_lossy(name).to_lowercase(),
+                Ok(Import::Ordinal(ordinal)) => format!("ord{ordinal}"),
+                Err(..) => continue,
+            };
+
+            imports.push(format!("{library}.{function}"));
+        }
+    }
+
+    Some(imports)
+}
+
+/// Smallest binary TLSH considers.
+const TLSH_MIN_LEN: usize = 50;
+
+/// Number of buckets that make up the hash.
+const TLSH_BUCKETS: usize = 128;
+
+/// Pearson hashing table of TLSH.
+#[rustfmt::skip]
+const TLSH_TABLE: [u8; 256] = [
+    1, 87, 49, 12, 176, 178, 102, 166, 121, 193, 6, 84, 249, 230, 44, 163,
+    14, 197, 213, 181, 161, 85, 218, 80, 64, 239, 24, 226, 236, 142, 38, 200,
+    110, 177, 104, 103, 141, 253, 255, 50, 77, 101, 81, 18, 45, 96, 31, 222,
+    25, 107, 190, 70, 86, 237, 240, 34, 72, 242, 20, 214, 244, 227, 149, 235,
+    97, 234, 57, 22, 60, 250, 82, 175, 208, 5, 127, 199, 111, 62, 135, 248,
+    174, 169, 211, 58, 66, 154, 106, 195, 245, 171, 17, 187, 182, 179, 0, 243,
+    132, 56, 148, 75, 128, 133, 158, 100, 130, 126, 91, 13, 153, 246, 216, 219,
+    119, 68, 223, 78, 83, 88, 201, 99, 122, 11, 92, 32, 136, 114, 52, 10,
+    138, 30, 48, 183, 156, 35, 61, 26, 143, 74, 251, 94, 129, 162, 63, 152,
+    170, 7, 115, 167, 241, 206, 3, 150, 55, 59, 151, 220, 90, 53, 23, 131,
+    125, 173, 15, 238, 79, 95, 89, 16, 105, 137, 225, 224, 217, 160, 37, 123,
+    118, 73, 2, 157, 46, 116, 9, 145, 134, 228, 207, 212, 202, 215, 69, 229,
+    27, 188, 67, 124, 168, 252, 42, 4, 29, 108, 21, 247, 19, 205, 39, 203,
+    233, 40, 186, 147, 198, 192, 155, 33, 164, 191, 98, 204, 165, 180, 117, 76,
+    140, 36, 210, 172, 41, 54, 159, 8, 185, 232, 113, 196, 231, 47, 146, 120,
+    51, 65, 28, 144, 254, 221, 93, 189, 194, 139, 112, 43, 71, 109, 184, 209,
+];
+
+fn pearson(salt: u8, a: u8, b: u8, c: u8) -> u8 {
+    let mut hash = TLSH_TABLE[salt as usize];
+    hash = TLSH_TABLE[(hash ^ a) as usize];
+    hash = TLSH_TABLE[(hash ^ b) as usize];
+    TLSH_TABLE[(hash ^ c) as usize]
+}
+
+/// Logarithmic encoding of the length of the binary, with the precision of the reference
+/// implementation so that lengths close to a boundary are encoded the same.
+fn tlsh_length(len: usize) -> u8 {
+    let log = (len as f32).ln() as f64;
+    let encoded = match len {
+        0..=656 => log / 0.4054651,
+        657..=3199 => log / 0.26236426 - 8.72777,
+        _ => log / 0.0953102 - 62.5472,
+    };
+
+    (encoded.floor() as u32 & 0xFF) as u8
+}
+
+/// Swap the nibbles of a byte, which TLSH does for the bytes of its header.
+fn swap(byte: u8) -> u8 {
+    byte.rotate_left(4)
+}
+
+fn tlsh(bytes: &[u8]) -> Option<String> {
+    if bytes.len() < TLSH_MIN_LEN {
+        return None;
+    }
+
+    // every window of 5 bytes adds triplets of its bytes to buckets
+    let mut buckets = [0u32; 256];
+    let mut checksum = 0;
+    for window in bytes.windows(5) {
+        let [w4, w3, w2, w1, w0] = window.try_into().unwrap();
+        checksum = pearson(0, w0, w1, checksum);
+        buckets[pearson(2, w0, w1, w2) as usize] += 1;
+        buckets[pearson(3, w0, w1, w3) as usize] += 1;
+        buckets[pearson(5, w0, w2, w3) as usize] += 1;
+        buckets[pearson(7, w0, w2, w4) as usize] += 1;
+        buckets[pearson(11, w0, w1, w4) as usize] += 1;
+        buckets[pearson(13, w0, w3, w4) as usize] += 1;
+    }
+
+    let buckets = &buckets[..TLSH_BUCKETS];
+    let mut sorted = buckets.to_vec();
+    sorted.sort_unstable();
+    let quartiles = [
+        sorted[TLSH_BUCKETS / 4 - 1],
+        sorted[TLSH_BUCKETS / 2 - 1],
+        sorted[TLSH_BUCKETS * 3 / 4 - 1],
+    ];
+
+    // binaries where most buckets are empty don't have a meaningful hash
+    let nonzero = buckets.iter().filter(|&&count| count != 0).count();
+    if quartiles[2] == 0 || nonzero <= TLSH_BUCKETS / 2 {
+        return None;
+    }
+
+    let ratio = |quartile: u32| ((quartile as f32 * 100.0 / quartiles[2] as f32) as u32 % 16) as u8;
+    let ratios = ratio(quartiles[0]) | ratio(quartiles[1]) << 4;
+
+    // every bucket is encoded in two bits by the quartile it's in, with the last buckets first
+    let body = buckets.chunks_exact(4).rev().map(|chunk| {
+        chunk.iter().enumerate().fold(0u8, |byte, (idx, &count)| {
+            let quartile = quartiles.iter().filter(|&&quartile| count > quartile).count();
+            byte | (quartile as u8) << (idx * 2)
+        })
+    });
+
+    let header = [swap(checksum), swap(tlsh_length(bytes.len())), swap(ratios)];
+    let hash: Vec<u8> = header.into_iter().chain(body).collect();
+    Some(format!("T1{}", hex(&hash).to_uppercase()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Function imported by a PE built by [`pe32`].
+    enum Thunk {
+        Name(&'static str),
+        Ordinal(u16),
+    }
+
+    fn put16(bytes: &mut [u8], offset: usize, value: u16) {
+        bytes[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+    }
+
+    fn put32(bytes: &mut [u8], offset: usize, value: u32) {
+        bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    /// Smallest PE32 that imports functions, with the import table as its only section.
+    fn pe32(imports: &[(&str, &[Thunk])]) -> Vec<u8> {
+        const RVA: u32 = 0x1000;
+        const OPTIONAL_HEADER: usize = 0x58;
+        const SECTION_HEADER: usize = OPTIONAL_HEADER + 0xe0;
+
+        let mut idata = vec![0; (imports.len() + 1) * 20];
+        for (idx, (library, thunks)) in imports.iter().enumerate() {
+            let name = RVA + idata.len() as u32;
+            idata.extend_from_slice(library.as_bytes());
+            idata.push(0);
+
+            let mut entries = Vec::new();
+            for thunk in thunks.iter() {
+                match thunk {
+                    Thunk::Ordinal(ordinal) => entries.push(0x80000000 | *ordinal as u32),
+                    Thunk::Name(name) => {
+                        entries.push(RVA + idata.len() as u32);
+                        idata.extend_from_slice(&[0, 0]);
+                        idata.extend_from_slice(name.as_bytes());
+                        idata.push(0);
+                    }
+                }
+            }
+
+            idata.resize(idata.len().next_multiple_of(4), 0);
+            let table = RVA + idata.len() as u32;
+            for entry in entries.into_iter().chain([0]) {
+                idata.extend_from_slice(&entry.to_le_bytes());
+            }
+
+            put32(&mut idata, idx * 20, table);
+            put32(&mut idata, idx * 20 + 12, name);
+            put32(&mut idata, idx * 20 + 16, table);
+        }
+
+        let raw_size = idata.len().next_multiple_of(0x200);
+        let mut pe = vec![0; 0x200];
+        pe[..2].copy_from_slice(b"MZ");
+        put32(&mut pe, 0x3c, 0x40);
+        pe[0x40..0x44].copy_from_slice(b"PE\0\0");
+
+        put16(&mut pe, 0x44, 0x14c);
+        put16(&mut pe, 0x46, 1);
+        put16(&mut pe, 0x54, 0xe0);
+        put16(&mut pe, 0x56, 0x102);
+
+        put16(&mut pe, OPTIONAL_HEADER, 0x10b);
+        put32(&mut pe, OPTIONAL_HEADER + 28, 0x400000);
+        put32(&mut pe, OPTIONAL_HEADER + 32, 0x1000);
+        put32(&mut pe, OPTIONAL_HEADER + 36, 0x200);
+        put32(&mut pe, OPTIONAL_HEADER + 56, RVA + 0x1000);
+        put32(&mut pe, OPTIONAL_HEADER + 60, 0x200);
+        put16(&mut pe, OPTIONAL_HEADER + 68, 3);
+        put32(&mut pe, OPTIONAL_HEADER + 92, 16);
+        put32(&mut pe, OPTIONAL_HEADER + 104, RVA);
+        put32(&mut pe, OPTIONAL_HEADER + 108, idata.len() as u32);
+
+        pe[SECTION_HEADER..SECTION_HEADER + 6].copy_from_slice(b".idata");
+        put32(&mut pe, SECTION_HEADER + 8, idata.len() as u32);
+        put32(&mut pe, SECTION_HEADER + 12, RVA);
+        put32(&mut pe, SECTION_HEADER + 16, raw_size as u32);
+        put32(&mut pe, SECTION_HEADER + 20, 0x200);
+        put32(&mut pe, SECTION_HEADER + 36, 0xc0000040);
+
+        idata.resize(raw_size, 0);
+        pe.extend_from_slice(&idata);
+        pe
+    }
+
+    #[test]
+    fn md5_of_binary() {
+        let hashes = Hashes::compute(b"abc", None);
+        assert_eq!(hashes.md5, "900150983cd24fb0d6963f7d28e17f72");
+    }
+
+    #[test]
+    fn imphash_like_pefile() {
+        let pe = pe32(&[
+            (
+                "KERNEL32.dll",
+                &[Thunk::Name("ExitProcess"), Thunk::Name("GetProcAddress")],
+            ),
+            ("USER32.DLL", &[Thunk::Name("MessageBoxA")]),
+            ("custom.drv", &[Thunk::Ordinal(7)]),
+        ]);
+
+        // md5 of "kernel32.exitprocess,kernel32.getprocaddress,user32.messageboxa,custom.drv.ord7"
+        let obj = ObjectFile::parse(&pe[..]).unwrap();
+        assert_eq!(
+            imphash(&obj).as_deref(),
+            Some("8a16b1110983835a9a52b5fe2ce482f4")
+        );
+    }
+
+    #[test]
+    fn imphash_without_imports() {
+        let pe = pe32(&[]);
+        let obj = ObjectFile::parse(&pe[..]).unwrap();
+        assert_eq!(imphash(&obj), None);
+    }
+
+    // hashes were computed with a port of the reference implementation's `tlsh_impl.cpp`
+
+    #[test]
+    fn tlsh_of_text() {
+        let text: String = (0..32)
+            .map(|idx| format!("The quick brown fox jumps over the lazy dog {idx}.\n"))
+            .collect();
+
+        assert_eq!(
+            tlsh(text.as_bytes()).as_deref(),
+            Some("T1BD31429E316823E8B68B1D88838DD4B6C2D8D925B1725851A83161031C48236EDEC8A2")
+        );
+    }
+
+    #[test]
+    fn tlsh_of_noise() {
+        let noise: Vec<u8> =
+            (0..512u32).map(|idx| (idx.wrapping_mul(2654435761) >> 24) as u8).collect();
+
+        assert_eq!(
+            tlsh(&noise).as_deref(),
+            Some("T1EEF0C0D043FE34DD6D585A690570AFDDAE0701B1610BA485D74EA446E48A514B0156D9")
+        );
+    }
+
+    #[test]
+    fn tlsh_needs_enough_variety() {
+        assert_eq!(tlsh(&[0; 49]), None);
+        assert_eq!(tlsh(&[0; 4096]), None);
+    }
+}
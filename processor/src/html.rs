@@ -0,0 +1,174 @@
+//! Static HTML export of the listing, for sharing an analysis with people that don't run bite.
+//!
+//! The page is a single file without any scripts. Operands that refer to an exported address link
+//! to the line of that address and a sidebar lists every function.
+
+use crate::{BlockContent, Line, Processor};
+use config::CONFIG;
+use processor_shared::AddressMode;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::ops::Range;
+use tokenizing::Color32;
+
+impl Processor {
+    /// Write the listing of the given address ranges as a standalone HTML page.
+    pub fn export_html(
+        &self,
+        ranges: &[Range<usize>],
+        mode: AddressMode,
+        mut out: impl Write,
+    ) -> io::Result<()> {
+        let mut functions = Vec::new();
+        let mut lines = Vec::new();
+
+        for range in ranges {
+            for block in self.blocks(range.clone()) {
+                if let BlockContent::Label { symbol, .. } = &block.content {
+                    let name: String = symbol.name().iter().map(|t| &*t.text).collect();
+                    functions.push((block.addr, name));
+                }
+
+                lines.extend(block.lines(self, mode));
+            }
+        }
+
+        // only lines that are exported can be linked to
+        let anchors: HashSet<usize> = lines.iter().map(|line| line.addr).collect();
+
+        let mut palette = Palette::default();
+        let mut listing = String::new();
+        let mut prev_addr = None;
+        for line in &lines {
+            // the first line of an address is the one that gets linked to
+            let id = match prev_addr.replace(line.addr) == Some(line.addr) {
+                true => String::new(),
+                false => format!(" id=\"{}\"", anchor(line.addr)),
+            };
+
+            listing.push_str(&format!("<div{id}>"));
+            self.html_line(line, mode, &anchors, &mut palette, &mut listing);
+            listing.push_str("</div>\n");
+        }
+
+        let title = escape(&self.path.display().to_string());
+        writeln!(out, "<!DOCTYPE html>")?;
+        writeln!(out, "<html>")?;
+        writeln!(out, "<head>")?;
+        writeln!(out, "<meta charset=\"utf-8\">")?;
+        writeln!(out, "<title>{title}</title>")?;
+        writeln!(out, "<style>")?;
+        writeln!(out, "{}", stylesheet())?;
+        for (color, class) in palette.classes() {
+            writeln!(out, ".c{class} {{ color: {}; }}", hex(color))?;
+        }
+        writeln!(out, "</style>")?;
+        writeln!(out, "</head>")?;
+        writeln!(out, "<body>")?;
+
+        writeln!(out, "<nav>")?;
+        writeln!(out, "<h1>{title}</h1>")?;
+        for (addr, name) in &functions {
+            writeln!(out, "<a href=\"#{}\">{}</a>", anchor(*addr), escape(name))?;
+        }
+        writeln!(out, "</nav>")?;
+
+        writeln!(out, "<main>")?;
+        out.write_all(listing.as_bytes())?;
+        writeln!(out, "</main>")?;
+        writeln!(out, "</body>")?;
+        writeln!(out, "</html>")?;
+        out.flush()
+    }
+
+    fn html_line(
+        &self,
+        line: &Line,
+        mode: AddressMode,
+        anchors: &HashSet<usize>,
+        palette: &mut Palette,
+        html: &mut String,
+    ) {
+        for span in line.layout(&CONFIG.listing.columns, self, mode) {
+            let text = escape(&span.token.text);
+
+            // padding doesn't need to be colored
+            if span.token.text.trim().is_empty() {
+                html.push_str(&text);
+                continue;
+            }
+
+            let class = palette.class(span.token.color);
+            let _ = match span.token.target {
+                Some(target) if anchors.contains(&target) => write!(
+                    html,
+                    "<a class=\"c{class}\" href=\"#{}\">{text}</a>",
+                    anchor(target)
+                ),
+                _ => write!(html, "<span class=\"c{class}\">{text}</span>"),
+            };
+        }
+    }
+}
+
+/// CSS classes of every color used in the listing, so they aren't repeated for each span.
+#[derive(Default)]
+struct Palette {
+    classes: HashMap<[u8; 4], usize>,
+}
+
+impl Palette {
+    fn class(&mut self, color: Color32) -> usize {
+        let len = self.classes.len();
+        *self.classes.entry(color.to_array()).or_insert(len)
+    }
+
+    fn classes(&self) -> Vec<([u8; 4], usize)> {
+        let mut classes: Vec<_> = self.classes.iter().map(|(&c, &class)| (c, class)).collect();
+        classes.sort_unstable_by_key(|&(_, class)| class);
+        classes
+    }
+}
+
+fn stylesheet() -> String {
+    let primary = hex(CONFIG.colors.bg_primary.to_array());
+    let secondary = hex(CONFIG.colors.bg_secondary.to_array());
+    let delimiter = hex(CONFIG.colors.delimiter.to_array());
+
+    format!(
+        "\
+body {{ margin: 0; display: flex; height: 100vh; background: {primary}; color: {delimiter}; }}
+body, h1 {{ font: 14px monospace; }}
+h1 {{ margin: 0 0 8px 0; font-weight: bold; }}
+nav {{ width: 280px; padding: 8px; overflow: auto; white-space: nowrap; background: {secondary}; }}
+nav a {{ display: block; color: inherit; text-decoration: none; }}
+main {{ flex: 1; padding: 8px; overflow: auto; white-space: pre; }}
+main a {{ text-decoration: none; }}
+a:hover {{ text-decoration: underline; }}
+div:target {{ background: {secondary}; }}"
+    )
+}
+
+/// Identifier of the line of an address.
+fn anchor(addr: usize) -> String {
+    format!("x{addr:x}")
+}
+
+fn hex([r, g, b, _]: [u8; 4]) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for chr in text.chars() {
+        match chr {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(chr),
+        }
+    }
+    escaped
+}
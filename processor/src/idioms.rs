@@ -0,0 +1,525 @@
+//! Recognition of the boilerplate compilers put around the body of a function.
+//!
+//! Prologues and epilogues save and restore registers and set up the stack frame, stack canaries
+//! guard against buffer overflows by storing a secret value below the return address and checking
+//! it before returning. Compilers also inline builtins such as `memcpy`, `memset` and `strlen` as
+//! string instructions or unrolled vector loads and stores. None of these say much about what a
+//! function does, so the listing annotates them and can collapse them into a single line.
+
+use crate::function_stats::STACK_POINTERS;
+use crate::Processor;
+use decoder::ir::{BinaryOp, Expr, Stmt};
+use processor_shared::{AddressMap, Addressed, PhysAddr, SectionKind};
+use std::fmt;
+
+/// Registers holding the frame pointer.
+const FRAME_POINTERS: &[&str] = &["rbp", "ebp", "x29", "fp"];
+
+/// Registers holding the return value, which are loaded from the stack by unoptimized code.
+const RETURN_REGISTERS: &[&str] = &["rax", "eax", "x0", "r0", "a0", "v0"];
+
+/// Operands that read the canary, on Linux the thread control block holds it and on Windows
+/// it's a global.
+const CANARY_SOURCES: &[&str] = &[
+    "fs:[0x28]",
+    "gs:[0x14]",
+    "__stack_chk_guard",
+    "__security_cookie",
+];
+
+/// Functions called when a canary was overwritten, or that check it on Windows.
+const CANARY_CHECKS: &[&str] = &["__stack_chk_fail", "__security_check_cookie"];
+
+/// Instructions after reading the canary in which it has to be stored for it to be set up.
+const MAX_SETUP_LEN: usize = 4;
+
+/// Instructions that zero a vector register when both operands are the same register.
+const VECTOR_ZEROING: &[&str] = &[
+    "pxor", "xorps", "xorpd", "vpxor", "vxorps", "vxorpd", "vpxord",
+];
+
+/// Instructions that move an entire vector register from or to memory.
+#[rustfmt::skip]
+const VECTOR_MOVES: &[&str] = &[
+    "movaps", "movups", "movapd", "movupd", "movdqa", "movdqu", "vmovaps", "vmovups", "vmovapd",
+    "vmovupd", "vmovdqa", "vmovdqu", "vmovdqa64", "vmovdqu8", "vmovdqu64",
+];
+
+/// Bytes an unrolled copy or store has to span for it to count as an inlined builtin, rather than
+/// a few variables being initialized.
+const MIN_INLINED_BYTES: usize = 32;
+
+/// Instructions between comparing bytes to zero and finding the first match in a `strlen` loop.
+const MAX_SCAN_LEN: usize = 8;
+
+/// What a sequence of instructions does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdiomKind {
+    /// Saves registers and reserves the stack frame.
+    Prologue,
+    /// Restores registers and releases the stack frame, up to the return.
+    Epilogue,
+    /// Stores the stack canary in the stack frame.
+    CanarySetup,
+    /// Compares the stored stack canary with the original.
+    CanaryCheck,
+    /// Copies memory in place of a call to `memcpy`, with the number of bytes if it's known.
+    Memcpy(Option<usize>),
+    /// Fills memory in place of a call to `memset`, with the number of bytes if it's known.
+    Memset(Option<usize>),
+    /// Looks for the terminating zero of a string a vector at a time, in place of a call to
+    /// `strlen`.
+    Strlen,
+}
+
+impl fmt::Display for IdiomKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Prologue => f.write_str("prologue"),
+            Self::Epilogue => f.write_str("epilogue"),
+            Self::CanarySetup => f.write_str("canary setup"),
+            Self::CanaryCheck => f.write_str("canary check"),
+            Self::Memcpy(Some(bytes)) => write!(f, "inlined memcpy of {bytes} bytes"),
+            Self::Memcpy(None) => f.write_str("inlined memcpy"),
+            Self::Memset(Some(bytes)) => write!(f, "inlined memset of {bytes} bytes"),
+            Self::Memset(None) => f.write_str("inlined memset"),
+            Self::Strlen => f.write_str("inlined strlen"),
+        }
+    }
+}
+
+/// Instructions recognized as boilerplate, starting at the address it's stored at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Idiom {
+    pub kind: IdiomKind,
+    /// Address past the last instruction.
+    pub end: PhysAddr,
+    /// Number of instructions.
+    pub instructions: usize,
+}
+
+/// Instruction of a function, with what's needed to tell idioms apart.
+struct Inst {
+    addr: PhysAddr,
+    end: PhysAddr,
+    stmts: Vec<Stmt>,
+    text: String,
+}
+
+/// Whether an address is relative to the stack or frame pointer.
+fn on_stack(addr: &Expr) -> bool {
+    let base = match addr {
+        Expr::Reg(reg) => reg,
+        Expr::Binary(BinaryOp::Add | BinaryOp::Sub, lhs, rhs) => match (&**lhs, &**rhs) {
+            (Expr::Reg(reg), Expr::Const(..)) => reg,
+            _ => return false,
+        },
+        _ => return false,
+    };
+
+    STACK_POINTERS.contains(&base.name) || FRAME_POINTERS.contains(&base.name)
+}
+
+fn is_stack_pointer(expr: &Expr) -> bool {
+    matches!(expr, Expr::Reg(reg) if STACK_POINTERS.contains(&reg.name))
+}
+
+/// Bytes held by a vector register, e.g. `16` for `xmm0`.
+fn vector_width(operand: &str) -> Option<usize> {
+    let width = match operand.get(..3)? {
+        "xmm" => 16,
+        "ymm" => 32,
+        "zmm" => 64,
+        _ => return None,
+    };
+
+    let num = &operand[3..];
+    (!num.is_empty() && num.bytes().all(|b| b.is_ascii_digit())).then_some(width)
+}
+
+/// Whether the value is read from the stack.
+fn loads_stack(expr: &Expr) -> bool {
+    match expr {
+        Expr::Load { addr, .. } => on_stack(addr),
+        Expr::Truncate(expr, _) | Expr::SignExtend(expr, _) => loads_stack(expr),
+        _ => false,
+    }
+}
+
+impl Inst {
+    fn calls(&self) -> bool {
+        self.stmts.iter().any(|stmt| matches!(stmt, Stmt::Call(..)))
+    }
+
+    fn branches(&self) -> bool {
+        self.stmts
+            .iter()
+            .any(|stmt| matches!(stmt, Stmt::Branch { .. } | Stmt::Jump(..)))
+    }
+
+    fn returns(&self) -> bool {
+        self.stmts.iter().any(|stmt| matches!(stmt, Stmt::Return))
+    }
+
+    fn stores_stack(&self) -> bool {
+        self.stmts
+            .iter()
+            .any(|stmt| matches!(stmt, Stmt::Store { addr, .. } if on_stack(addr)))
+    }
+
+    fn mnemonic(&self) -> &str {
+        self.text.split(' ').next().unwrap_or_default()
+    }
+
+    fn operands(&self) -> Vec<&str> {
+        match self.text.split_once(' ') {
+            Some((_, operands)) => operands.split(", ").collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Vector register zeroed by xor'ing it with itself.
+    fn zeroes_vector(&self) -> Option<&str> {
+        if !VECTOR_ZEROING.contains(&self.mnemonic()) {
+            return None;
+        }
+
+        let operands = self.operands();
+        let reg = *operands.first()?;
+        (vector_width(reg).is_some() && operands.iter().all(|op| *op == reg)).then_some(reg)
+    }
+
+    /// Vector register loaded from memory.
+    fn loads_vector(&self) -> Option<&str> {
+        match self.operands()[..] {
+            [dst, src] if VECTOR_MOVES.contains(&self.mnemonic()) && src.contains('[') => {
+                vector_width(dst).map(|_| dst)
+            }
+            _ => None,
+        }
+    }
+
+    /// Vector register stored to memory, together with its width.
+    fn stores_vector(&self) -> Option<(&str, usize)> {
+        match self.operands()[..] {
+            [dst, src] if VECTOR_MOVES.contains(&self.mnemonic()) && dst.contains('[') => {
+                vector_width(src).map(|width| (src, width))
+            }
+            _ => None,
+        }
+    }
+
+    /// Bytes set to zero by storing a constant.
+    fn stores_zero(&self) -> Option<usize> {
+        match self.stmts[..] {
+            [Stmt::Store {
+                size,
+                src: Expr::Const(0),
+                ..
+            }] => Some(size as usize),
+            _ => None,
+        }
+    }
+
+    fn reads_canary(&self) -> bool {
+        CANARY_SOURCES.iter().any(|source| self.text.contains(source))
+    }
+
+    fn calls_canary_check(&self) -> bool {
+        self.calls() && CANARY_CHECKS.iter().any(|check| self.text.contains(check))
+    }
+
+    /// Pushing registers, adjusting the stack pointer and setting the frame pointer.
+    fn is_prologue(&self) -> bool {
+        if self.text.starts_with("endbr") {
+            return true;
+        }
+
+        !self.stmts.is_empty()
+            && self.stmts.iter().all(|stmt| match stmt {
+                Stmt::Assign { dst, .. } if STACK_POINTERS.contains(&dst.name) => true,
+                Stmt::Assign { dst, src } if FRAME_POINTERS.contains(&dst.name) => {
+                    is_stack_pointer(src) || on_stack(src)
+                }
+                Stmt::Store { addr, src, .. } => on_stack(addr) && matches!(src, Expr::Reg(..)),
+                _ => false,
+            })
+    }
+
+    /// Popping registers and releasing the stack frame.
+    fn is_epilogue(&self) -> bool {
+        !self.stmts.is_empty()
+            && self.stmts.iter().all(|stmt| match stmt {
+                Stmt::Assign { dst, .. } if STACK_POINTERS.contains(&dst.name) => true,
+                Stmt::Assign { dst, src } => {
+                    !RETURN_REGISTERS.contains(&dst.name) && loads_stack(src)
+                }
+                _ => false,
+            })
+    }
+
+    /// Reading the stored canary or the stack pointer it's mixed with on Windows.
+    fn prepares_check(&self) -> bool {
+        !self.stmts.is_empty()
+            && self.stmts.iter().all(|stmt| match stmt {
+                Stmt::Assign { src, .. } => match src {
+                    Expr::Binary(BinaryOp::Xor, _, rhs) => is_stack_pointer(rhs),
+                    src => loads_stack(src),
+                },
+                Stmt::Flags(..) => true,
+                _ => false,
+            })
+    }
+}
+
+/// String instruction copying or filling memory as many times as the count register says.
+fn string_instruction(inst: &Inst) -> Option<IdiomKind> {
+    if inst.text.starts_with("rep movs") {
+        return Some(IdiomKind::Memcpy(None));
+    }
+
+    if inst.text.starts_with("rep stos") {
+        return Some(IdiomKind::Memset(None));
+    }
+
+    None
+}
+
+/// Zeroed vector registers and zero constants stored to consecutive instructions, returning the
+/// number of instructions and bytes zeroed.
+fn unrolled_memset(insts: &[Inst]) -> Option<(usize, usize)> {
+    let mut zeroed = Vec::new();
+    let (mut len, mut stores, mut bytes) = (0, 0, 0);
+
+    for (idx, inst) in insts.iter().enumerate() {
+        if let Some(reg) = inst.zeroes_vector() {
+            zeroed.push(reg);
+            continue;
+        }
+
+        let size = match (inst.stores_vector(), inst.stores_zero()) {
+            (Some((reg, width)), _) if zeroed.contains(&reg) => width,
+            (_, Some(size)) => size,
+            _ => break,
+        };
+
+        len = idx + 1;
+        stores += 1;
+        bytes += size;
+    }
+
+    (stores >= 2 && bytes >= MIN_INLINED_BYTES).then_some((len, bytes))
+}
+
+/// Vector registers loaded from memory and stored elsewhere, returning the number of
+/// instructions and bytes copied.
+fn unrolled_memcpy(insts: &[Inst]) -> Option<(usize, usize)> {
+    let mut loaded = Vec::new();
+    let (mut len, mut stores, mut bytes) = (0, 0, 0);
+
+    for (idx, inst) in insts.iter().enumerate() {
+        if let Some(reg) = inst.loads_vector() {
+            loaded.push(reg);
+            continue;
+        }
+
+        match inst.stores_vector() {
+            Some((reg, width)) if loaded.contains(&reg) => {
+                len = idx + 1;
+                stores += 1;
+                bytes += width;
+            }
+            _ => break,
+        }
+    }
+
+    (stores >= 2 && bytes >= MIN_INLINED_BYTES).then_some((len, bytes))
+}
+
+/// Loop comparing a vector of bytes to a zeroed register and finding the first that's equal,
+/// starting at the comparison and returning the number of instructions up to the match.
+fn vector_strlen(insts: &[Inst], zeroed: &[&str]) -> Option<usize> {
+    let compare = &insts[0];
+    if !matches!(compare.mnemonic(), "pcmpeqb" | "vpcmpeqb") {
+        return None;
+    }
+
+    if !compare.operands().iter().any(|op| zeroed.contains(op)) {
+        return None;
+    }
+
+    let window = &insts[..insts.len().min(MAX_SCAN_LEN)];
+    let mask = window.iter().position(|inst| inst.mnemonic().ends_with("pmovmskb"))?;
+    let found = window[mask..]
+        .iter()
+        .position(|inst| matches!(inst.mnemonic(), "bsf" | "tzcnt"))?;
+
+    Some(mask + found + 1)
+}
+
+/// Idioms of a function, sorted by address and not overlapping.
+fn recognize(insts: &[Inst]) -> Vec<(usize, usize, IdiomKind)> {
+    let mut found = Vec::new();
+
+    let prologue = insts.iter().take_while(|inst| inst.is_prologue()).count();
+    if prologue > 0 {
+        found.push((0, prologue, IdiomKind::Prologue));
+    }
+
+    let mut canary_set = false;
+    let mut idx = prologue;
+    while idx < insts.len() {
+        let inst = &insts[idx];
+
+        if let Some(kind) = string_instruction(inst) {
+            found.push((idx, idx + 1, kind));
+            idx += 1;
+            continue;
+        }
+
+        if let Some((len, bytes)) = unrolled_memset(&insts[idx..]) {
+            found.push((idx, idx + len, IdiomKind::Memset(Some(bytes))));
+            idx += len;
+            continue;
+        }
+
+        if let Some((len, bytes)) = unrolled_memcpy(&insts[idx..]) {
+            found.push((idx, idx + len, IdiomKind::Memcpy(Some(bytes))));
+            idx += len;
+            continue;
+        }
+
+        // the register compared against is zeroed before the loop, along with loading the first
+        // vector of the string
+        let floor = found.last().map_or(0, |(_, end, _)| *end);
+        let before = &insts[floor.max(idx.saturating_sub(MAX_SCAN_LEN))..idx];
+        let zeroed: Vec<&str> = before.iter().filter_map(Inst::zeroes_vector).collect();
+        if let Some(len) = vector_strlen(&insts[idx..], &zeroed) {
+            let mut start = idx;
+            while start > floor
+                && idx - start < 2
+                && (insts[start - 1].zeroes_vector().is_some()
+                    || insts[start - 1].loads_vector().is_some())
+            {
+                start -= 1;
+            }
+
+            found.push((start, idx + len, IdiomKind::Strlen));
+            idx += len;
+            continue;
+        }
+
+        if inst.reads_canary() && !canary_set {
+            let window = &insts[idx..insts.len().min(idx + MAX_SETUP_LEN)];
+            if let Some(len) = window.iter().position(|inst| inst.stores_stack()) {
+                found.push((idx, idx + len + 1, IdiomKind::CanarySetup));
+                canary_set = true;
+                idx += len + 1;
+                continue;
+            }
+        }
+
+        if inst.reads_canary() || inst.calls_canary_check() {
+            // the stored canary is loaded first, on Windows it's mixed with the stack pointer too
+            let floor = found.last().map_or(0, |(_, end, _)| *end);
+            let mut start = idx;
+            while start > floor && idx - start < 2 && insts[start - 1].prepares_check() {
+                start -= 1;
+            }
+
+            // the canary is compared and branched on, unless a function compares it
+            let mut end = idx + 1;
+            if !inst.calls_canary_check() {
+                if let Some(len) = insts[end..].iter().take(2).position(|inst| inst.branches()) {
+                    end += len + 1;
+                }
+            }
+
+            // the call failing the check often follows the branch
+            if insts.get(end).is_some_and(|inst| inst.calls_canary_check()) {
+                end += 1;
+            }
+
+            match found.last_mut() {
+                Some((_, last, IdiomKind::CanaryCheck)) if *last == start => *last = end,
+                _ => found.push((start, end, IdiomKind::CanaryCheck)),
+            }
+
+            idx = end;
+            continue;
+        }
+
+        if inst.returns() {
+            let floor = found.last().map_or(0, |(_, end, _)| *end);
+            let mut start = idx;
+            while start > floor && insts[start - 1].is_epilogue() {
+                start -= 1;
+            }
+
+            if start < idx {
+                found.push((start, idx, IdiomKind::Epilogue));
+            }
+        }
+
+        idx += 1;
+    }
+
+    found
+}
+
+impl Processor {
+    /// Prologues, epilogues, stack canary checks and inlined builtins of every function, see
+    /// [`idioms`].
+    pub(crate) fn recognize_idioms(&self) -> AddressMap<Idiom> {
+        let mut idioms = AddressMap::default();
+        let mut starts: Vec<PhysAddr> = self.index.functions().map(|func| func.addr).collect();
+        starts.dedup();
+
+        for start in starts {
+            if !self.section_by_addr(start).is_some_and(|s| s.kind == SectionKind::Code) {
+                continue;
+            }
+
+            let end = match self.function_bounds(start) {
+                Some((addr, end, _)) if addr == start => end,
+                _ => continue,
+            };
+
+            let mut insts = Vec::new();
+            for instruction in self.instructions_from(start) {
+                if instruction.addr >= end {
+                    break;
+                }
+
+                let tokens = self.instruction_tokens(&instruction.item, &self.index);
+                insts.push(Inst {
+                    addr: instruction.addr,
+                    end: instruction.addr + self.instruction_width(&instruction.item),
+                    stmts: self.instruction_ir(&instruction.item),
+                    text: String::from_iter(tokens.iter().map(|token| &token.text[..])),
+                });
+            }
+
+            for (first, last, kind) in recognize(&insts) {
+                idioms.push(Addressed {
+                    addr: insts[first].addr,
+                    item: Idiom {
+                        kind,
+                        end: insts[last - 1].end,
+                        instructions: last - first,
+                    },
+                });
+            }
+        }
+
+        idioms
+    }
+
+    /// Idiom starting at an address.
+    pub fn idiom_at(&self, addr: PhysAddr) -> Option<&Idiom> {
+        match self.idioms.search(addr) {
+            Ok(idx) => Some(&self.idioms[idx].item),
+            Err(..) => None,
+        }
+    }
+}
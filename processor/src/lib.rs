@@ -1,14 +1,51 @@
 mod fmt;
+mod function_stats;
 mod blocks;
+mod bytecode;
+mod cache;
+mod callgraph;
+mod carving;
+mod codesign;
+mod html;
+mod lines;
+mod cfg;
+mod coredump;
+mod crypto;
+mod dead_code;
+mod emulation;
+mod enums;
+mod hashes;
+mod idioms;
+mod lifting;
+mod managed;
+mod mitigations;
+mod overlay;
+mod packers;
+mod pipeline;
+mod pointers;
+mod propagation;
+mod prototypes;
+mod registers;
+mod rich;
+mod signatures;
+mod strings;
+mod structs;
+mod sweep;
+mod syscalls;
+mod typelib;
 
 use decoder::{Decodable, Decoded};
 use object::{Endianness, Object, ObjectSegment};
 use object::{Architecture, BinaryFormat};
 use object::read::File as ObjectFile;
-use processor_shared::{AddressMap, Addressed, PhysAddr, Section, SectionKind, Segment};
-use debugvault::Index;
+use processor_shared::{AddressMap, Addressed, Permissions, PhysAddr, Section, SectionKind, Segment};
+use processor_shared::RegisterNames;
+use lines::AddressWidths;
+use debugvault::types::{Enum, Struct};
+use debugvault::{FileAttr, Index};
+use commands::Feature;
 use tokenizing::Token;
-use binformat::{elf, macho, pe, RawSymbol};
+use binformat::{dotnet, elf, go, kernel, macho, objc, pe, RawSymbol};
 
 use memmap2::Mmap;
 use x86_64::long_mode as x64;
@@ -16,10 +53,42 @@ use x86_64::protected_mode as x86;
 use arm::armv7 as armv7;
 use arm::armv8::a64 as aarch64;
 
+use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::mem::ManuallyDrop;
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
 
-pub use blocks::{BlockContent, Block};
+pub use blocks::{BlockContent, Block, FunctionHeader};
+pub use decoder::{ir, Reference};
+pub use mitigations::{Mitigation, Verdict};
+pub use overlay::Overlay;
+pub use packers::Detection;
+pub use pipeline::{AnalysisPipeline, Stage};
+pub use prototypes::Prototype;
+pub use rich::{RichEntry, RichHeader};
+pub use typelib::{ApiPrototype, Param};
+pub use binformat::macho::LoadCommand;
+pub use binformat::{AutoStart, AutoStartKind, Export, Import};
+pub use binformat::coredump::{CoreDump, Thread};
+pub use lines::{Kind, Line, Span};
+pub use callgraph::CallGraph;
+pub use carving::{Embedded, FileKind};
+pub use codesign::{Certificate, DigestAlgorithm, Signature, SignatureKind};
+pub use coredump::Frame;
+pub use crypto::CryptoConstant;
+pub use dead_code::{DeadCode, DeadCodeKind};
+pub use hashes::Hashes;
+pub use idioms::{Idiom, IdiomKind};
+pub use cfg::{BasicBlock, Cfg, Error as CfgError, Exit, Inst, Loop, Target};
+pub use emulation::{Emulation, Error as EmulationError, Stop as EmulationStop};
+pub use function_stats::FunctionStats;
+pub use lifting::PseudoCode;
+pub use pointers::{DataPointer, PointerKind};
+pub use strings::{Encoding, ExtractedString};
+pub use syscalls::Syscall;
 
 /// FIXME: This is way too large and way too broad.
 ///        Especially since these are being started for any address with a faulty decoding.
@@ -30,6 +99,8 @@ pub enum Error {
     NotAnExecutable,
     DecompressionFailed(object::Error),
     UnknownArchitecture(object::Architecture),
+    /// Loading was cancelled, see [`AnalysisPipeline::cancel`].
+    Cancelled,
 }
 
 pub union Instruction {
@@ -39,91 +110,117 @@ pub union Instruction {
     mips: ManuallyDrop<mips::Instruction>,
     armv7: ManuallyDrop<armv7::Instruction>,
     aarch64: ManuallyDrop<aarch64::Instruction>,
+    jvm: ManuallyDrop<java::jvm::Instruction>,
+    dalvik: ManuallyDrop<java::dalvik::Instruction>,
 }
 
-macro_rules! impl_recursion {
-    ($symbols:expr, $errors:expr, $instructions:expr, $sections:expr,
-     $max_instruction_width:expr, $decoder:expr, $arch:ident) => {{
-        $max_instruction_width = $decoder.max_width();
+macro_rules! impl_decoder {
+    ($decoder:expr, $arch:ident) => {{
+        fn decode_region(
+            bytes: &[u8],
+            base: PhysAddr,
+            start: PhysAddr,
+            end: PhysAddr,
+            seam: Option<PhysAddr>,
+        ) -> sweep::Decoded<Instruction> {
+            let decoded = sweep::decode(&$decoder, bytes, base, start, end, seam);
+            let decoded = decoded.map(|instruction| Instruction {
+                $arch: std::mem::ManuallyDrop::new(instruction),
+            });
 
-        let width_guess = if $max_instruction_width == 4 {
-            4
-        } else {
-            5
-        };
+            // SAFETY: every instruction was decoded into the field being dropped
+            unsafe { decoded.drop_with(drop_instruction) }
+        }
 
-        for section in $sections.iter().filter(|s| s.kind == SectionKind::Code) {
-            let mut prev_inst = None;
-            let mut reader = decoder::Reader::new(section.bytes());
-            let mut ip = section.start;
+        unsafe fn drop_instruction(instruction: &mut Instruction) {
+            std::mem::ManuallyDrop::drop(&mut instruction.$arch)
+        }
 
-            log::complex!(
-                w "[processor::recurse] analyzing section ",
-                b &*section.name,
-                w " <",
-                g format!("{:x}", section.start),
-                w "..",
-                g format!("{:x}", section.end),
-                w ">.",
-            );
+        let decode_region: DecodeRegion = decode_region;
 
-            // guessing an average of 5 byte long instructions
-            log::PROGRESS.set("Decoding instructions", section.bytes().len() / width_guess);
+        ($decoder.max_width(), decode_region)
+    }};
+}
 
-            loop {
-                // prefetch next cache line line
-                #[cfg(target_arch = "x86")]
-                unsafe {
-                    core::arch::x86::_mm_prefetch(
-                        reader.as_ptr() as *const i8,
-                        core::arch::x86::_MM_HINT_NTA
-                    );
-                }
+/// Instruction or error that keeps the decoding of its region alive, see [`sweep::Borrowed`].
+pub type Borrowed<T> = sweep::Borrowed<Instruction, T>;
 
-                #[cfg(target_arch = "x86_64")]
-                unsafe {
-                    core::arch::x86_64::_mm_prefetch(
-                        reader.as_ptr() as *const i8,
-                        core::arch::x86_64::_MM_HINT_NTA
-                    );
-                }
+/// Decodes the instructions in between two addresses, given the bytes at some base address and
+/// where the instructions before them end if that's known.
+type DecodeRegion =
+    fn(&[u8], PhysAddr, PhysAddr, PhysAddr, Option<PhysAddr>) -> sweep::Decoded<Instruction>;
 
-                match $decoder.decode(&mut reader) {
-                    Ok(mut instruction) => {
-                        instruction.update_rel_addrs(ip, prev_inst);
-
-                        let width = instruction.width();
-                        $instructions.push(Addressed {
-                            addr: ip,
-                            item: Instruction {
-                                $arch: std::mem::ManuallyDrop::new(instruction)
-                            }
-                        });
-
-                        prev_inst = $instructions.last().map(|inst| {
-                            unsafe { &*inst.item.$arch }
-                        });
-                        ip += width;
-                    }
-                    Err(error) => {
-                        if error.kind == decoder::ErrorKind::ExhaustedInput {
-                            break;
-                        }
-
-                        let width = error.size();
-                        $errors.push(Addressed {
-                            addr: ip,
-                            item: error
-                        });
-                        prev_inst = None;
-                        ip += width;
-                    }
-                }
+/// Functions that forward to an architecture's field of an [`Instruction`], along with it's
+/// maximum instruction width and the linear sweep that decodes into it.
+type Decoders = (
+    fn(&Instruction, &Index) -> Vec<Token>,
+    fn(&Instruction) -> usize,
+    fn(&Instruction, &mut Vec<ir::Stmt>),
+    usize,
+    DecodeRegion,
+);
 
-                log::PROGRESS.step();
-            }
+fn decoders(arch: Architecture) -> Result<Decoders, Error> {
+    let (instruction_tokens, instruction_width, instruction_lower) = unsafe {
+        match arch {
+            Architecture::Riscv32 | Architecture::Riscv64 => (
+                std::mem::transmute(<riscv::Instruction as Decoded>::tokens as usize),
+                std::mem::transmute(<riscv::Instruction as Decoded>::width as usize),
+                std::mem::transmute(<riscv::Instruction as Decoded>::lower as usize),
+            ),
+            Architecture::Mips | Architecture::Mips64 => (
+                std::mem::transmute(<mips::Instruction as Decoded>::tokens as usize),
+                std::mem::transmute(<mips::Instruction as Decoded>::width as usize),
+                std::mem::transmute(<mips::Instruction as Decoded>::lower as usize),
+            ),
+            Architecture::X86_64_X32 | Architecture::I386 => (
+                std::mem::transmute(<x86::Instruction as Decoded>::tokens as usize),
+                std::mem::transmute(<x86::Instruction as Decoded>::width as usize),
+                std::mem::transmute(<x86::Instruction as Decoded>::lower as usize),
+            ),
+            Architecture::X86_64 => (
+                std::mem::transmute(<x64::Instruction as Decoded>::tokens as usize),
+                std::mem::transmute(<x64::Instruction as Decoded>::width as usize),
+                std::mem::transmute(<x64::Instruction as Decoded>::lower as usize),
+            ),
+            Architecture::Arm => (
+                std::mem::transmute(<armv7::Instruction as Decoded>::tokens as usize),
+                std::mem::transmute(<armv7::Instruction as Decoded>::width as usize),
+                std::mem::transmute(<armv7::Instruction as Decoded>::lower as usize),
+            ),
+            Architecture::Aarch64 | Architecture::Aarch64_Ilp32 => (
+                std::mem::transmute(<aarch64::Instruction as Decoded>::tokens as usize),
+                std::mem::transmute(<aarch64::Instruction as Decoded>::width as usize),
+                std::mem::transmute(<aarch64::Instruction as Decoded>::lower as usize),
+            ),
+            arch => return Err(Error::UnknownArchitecture(arch)),
         }
-    }};
+    };
+
+    let (max_instruction_width, decode_region) = match arch {
+        Architecture::Riscv32 => impl_decoder!(riscv::Decoder { is_64: false }, riscv),
+        Architecture::Riscv64 => impl_decoder!(riscv::Decoder { is_64: true }, riscv),
+        Architecture::Mips | Architecture::Mips64 => {
+            impl_decoder!(mips::Decoder::default(), mips)
+        }
+        Architecture::X86_64_X32 | Architecture::I386 => {
+            impl_decoder!(x86::Decoder::default(), x86)
+        }
+        Architecture::X86_64 => impl_decoder!(x64::Decoder::default(), x64),
+        Architecture::Arm => impl_decoder!(armv7::Decoder::default(), armv7),
+        Architecture::Aarch64 | Architecture::Aarch64_Ilp32 => {
+            impl_decoder!(aarch64::Decoder::default(), aarch64)
+        }
+        _ => unreachable!(),
+    };
+
+    Ok((
+        instruction_tokens,
+        instruction_width,
+        instruction_lower,
+        max_instruction_width,
+        decode_region,
+    ))
 }
 
 /// Architecture agnostic analysis of a module.
@@ -134,14 +231,121 @@ pub struct Processor {
     /// Where the binary is located.
     pub path: std::path::PathBuf,
 
+    /// Hex encoded SHA-256 of the binary, used for identifying it's project.
+    pub hash: String,
+
+    /// Hashes for looking the binary up in malware databases or finding similar binaries.
+    pub hashes: Hashes,
+
     /// Symbol lookup by physical address.
     pub index: Index,
 
+    /// Address relative addresses are based on.
+    pub image_base: PhysAddr,
+
+    /// How long parsing and analyzing the binary took.
+    pub analysis_time: std::time::Duration,
+
+    /// How long every stage of parsing and analyzing the binary took.
+    pub timings: log::Profile,
+
+    /// Signs of packing or obfuscation found in the binary.
+    pub detections: Vec<Detection>,
+
+    /// Exploit mitigations the binary was built with.
+    pub mitigations: Vec<Mitigation>,
+
+    /// Rich header of a PE, which describes the toolchain that built it.
+    pub rich: Option<RichHeader>,
+
+    /// Authenticode signature of a PE or code signature of a Mach-O.
+    pub signature: Option<Signature>,
+
+    /// Whether the signature still covers the binary with the user's patches applied, reset when
+    /// patching.
+    signature_covers: Mutex<Option<bool>>,
+
+    /// Data appended past the end of the image, such as an installer's payload.
+    pub overlay: Option<Overlay>,
+
+    /// Files found inside the binary, such as archives and images.
+    pub embedded: Vec<Embedded>,
+
+    /// Constants of cryptographic algorithms, sorted by their address.
+    pub crypto: Vec<CryptoConstant>,
+
+    /// Prologues, epilogues, stack canary checks and inlined builtins, sorted by where they start.
+    pub idioms: AddressMap<Idiom>,
+
+    /// System calls made directly by the binary, sorted by the address of the instruction.
+    pub syscalls: AddressMap<Syscall>,
+
+    /// Pointers stored in data sections, by the address they're stored at.
+    pub data_pointers: BTreeMap<PhysAddr, DataPointer>,
+
+    /// Where the pointers in [`Processor::data_pointers`] are stored, by the address they point at.
+    data_xrefs: BTreeMap<PhysAddr, Vec<PhysAddr>>,
+
+    /// Strings in data sections along with the functions using them, sorted by address.
+    pub strings: Vec<ExtractedString>,
+
+    /// Strings used by every function, as indices into [`Processor::strings`].
+    function_strings: BTreeMap<PhysAddr, Vec<usize>>,
+
+    /// Jumps back to the header of a loop, from the address of the jump to the header.
+    back_edges: BTreeMap<PhysAddr, PhysAddr>,
+
+    /// Code that's never run or that nothing is known to run, sorted by address.
+    pub dead_code: Vec<DeadCode>,
+
+    /// Functions imported from shared libraries.
+    pub imports: Vec<Import>,
+
+    /// Functions exported by the binary.
+    pub exports: Vec<Export>,
+
+    /// Code that runs without the binary calling it, such as TLS callbacks.
+    pub autostart: Vec<AutoStart>,
+
+    /// Mach-O load commands, empty for other formats.
+    pub load_commands: Vec<LoadCommand>,
+
+    /// Objective-C classes defined by a Mach-O binary.
+    pub objc: objc::Metadata,
+
+    /// Managed types and methods of a .NET assembly.
+    pub dotnet: Option<dotnet::Metadata>,
+
+    /// Classes of a class file, jar or dex file, which hold bytecode instead of native code.
+    bytecode: Option<bytecode::Bytecode>,
+
+    /// Memory and threads of a crashed process, if the binary is a core dump.
+    pub coredump: Option<CoreDump>,
+
+    /// Calls between functions, see [`Processor::callers`] and [`Processor::callees`].
+    call_graph: CallGraph,
+
+    /// Inferred signatures of called functions, by address, see [`Processor::prototype`].
+    pub prototypes: BTreeMap<PhysAddr, Prototype>,
+
+    /// Targets of indirect calls and jumps, by the address of the instruction, see
+    /// [`Processor::indirect_targets`].
+    indirect: BTreeMap<PhysAddr, Vec<PhysAddr>>,
+
+    /// Number of characters required to display addresses in each [`AddressMode`].
+    ///
+    /// [`AddressMode`]: processor_shared::AddressMode
+    address_widths: AddressWidths,
+
     /// File handle to binary,
     _file: File,
 
-    /// A memory map of the binary.
-    _mmap: Mmap,
+    /// A memory map of the binary, backing the bytes of every section.
+    mmap: Mmap,
+
+    /// Bytes of sections that aren't in the memory map, such as the sections of a relocatable
+    /// object with their relocations applied or code read from the modules of a core dump.
+    _owned: Vec<Vec<u8>>,
 
     /// Object's sections sorted by address.
     sections: Vec<Section>,
@@ -149,13 +353,33 @@ pub struct Processor {
     /// Object's segments sorted by address.
     segments: Vec<Segment>,
 
-    /// Errors occurred in decoding instructions.
+    /// Code sections split up into regions, which are only decoded once they're needed.
     /// Sorted by address.
-    errors: AddressMap<decoder::Error>,
+    regions: Vec<sweep::Region<Instruction>>,
 
-    /// Successfully decoded instructions.
-    /// Sorted by address.
-    instructions: AddressMap<Instruction>,
+    /// Function pointer to the linear sweep over a region, see [`sweep::decode`].
+    decode_region: DecodeRegion,
+
+    /// Bytes overwritten by the user, by address.
+    patches: RwLock<BTreeMap<PhysAddr, u8>>,
+
+    /// Structures from the debug info or defined by the user, by name.
+    structs: RwLock<BTreeMap<String, Arc<Struct>>>,
+
+    /// Structures applied to data by the user, by the address they start at.
+    applied: RwLock<BTreeMap<PhysAddr, Arc<Struct>>>,
+
+    /// Enumerations from the debug info, the library or defined by the user, by name.
+    enums: RwLock<BTreeMap<String, Arc<Enum>>>,
+
+    /// Enumerations applied to the immediates of instructions by the user, by address.
+    applied_enums: RwLock<BTreeMap<PhysAddr, Arc<Enum>>>,
+
+    /// Analysis results from an earlier time the binary was opened.
+    cache: Mutex<cache::Cache>,
+
+    /// Whether loading of the binary got cancelled, which stops decoding it any further.
+    progress: Arc<pipeline::Progress>,
 
     /// How many bytes an instruction given the architecture.
     max_instruction_width: usize,
@@ -166,68 +390,223 @@ pub struct Processor {
     /// Function pointer to an [`Instruction`]'s implementation of [`Decoded::width`].
     instruction_width: fn(&Instruction) -> usize,
 
+    /// Function pointer to an [`Instruction`]'s implementation of [`Decoded::lower`].
+    instruction_lower: fn(&Instruction, &mut Vec<ir::Stmt>),
+
     /// Target's instruction set.
     arch: Architecture,
 
+    /// Target's object format, which determines the calling convention on x86-64.
+    format: BinaryFormat,
+
+    /// Whether registers are named by their ABI alias, see [`RegisterNames`].
+    abi_registers: AtomicBool,
+
     /// Target's endianness.
     endianness: Endianness,
 }
 
 impl Processor {
     pub fn parse<P: AsRef<std::path::Path>>(path: P) -> Result<Self, Error> {
+        Self::parse_with(path, Arc::default())
+    }
+
+    /// Parse a binary, checking between every [`Stage`] whether loading got cancelled.
+    pub(crate) fn parse_with<P: AsRef<std::path::Path>>(
+        path: P,
+        progress: Arc<pipeline::Progress>,
+    ) -> Result<Self, Error> {
+        let now = std::time::Instant::now();
+        progress.enter(Stage::Load)?;
+
         let file = std::fs::File::open(path.as_ref()).map_err(Error::IO)?;
         let mmap = unsafe { Mmap::map(&file).map_err(Error::IO)? };
         let binary: &'static [u8] = unsafe { std::mem::transmute(&mmap[..]) };
+
+        // bytecode isn't in an object file, so it's loaded without one
+        if let Some(bytecode) = bytecode::Bytecode::parse(binary) {
+            let path = path.as_ref().to_path_buf();
+            return Self::parse_bytecode(path, file, mmap, bytecode, progress, now);
+        }
+
+        // core dumps don't have sections, only the memory of the process
+        if let Some(dump) = CoreDump::parse(binary) {
+            let path = path.as_ref().to_path_buf();
+            return Self::parse_coredump(path, file, mmap, dump, progress, now);
+        }
+
         let obj = ObjectFile::parse(binary)?;
 
         let path = path.as_ref().to_path_buf();
-        let now = std::time::Instant::now();
+        let hashes = Hashes::compute(binary, Some(&obj));
+        let hash = hashes.sha256.clone();
+
+        progress.enter(Stage::Symbols)?;
 
         let mut syms = AddressMap::default();
         let mut sections = Vec::new();
+        let mut imports = Vec::new();
+        let mut exports = Vec::new();
+        let mut autostart = Vec::new();
+        let mut load_commands = Vec::new();
+        let mut function_starts = Vec::new();
+        let mut objc = objc::Metadata::default();
+        let mut dotnet = None;
+        let mut kernel = kernel::Kernel::default();
+        let mut relocated = Vec::new();
         match &obj {
             object::File::MachO32(macho) => {
                 let debug_info = macho::MachoDebugInfo::parse(macho)?;
                 sections.extend(debug_info.sections);
                 syms.extend(debug_info.syms);
+                imports.extend(debug_info.imports);
+                exports.extend(debug_info.exports);
+                load_commands.extend(debug_info.load_cmds);
+                function_starts.extend(debug_info.function_starts);
+                objc = debug_info.objc;
             }
             object::File::MachO64(macho) => {
                 let debug_info = macho::MachoDebugInfo::parse(macho)?;
                 sections.extend(debug_info.sections);
                 syms.extend(debug_info.syms);
+                imports.extend(debug_info.imports);
+                exports.extend(debug_info.exports);
+                load_commands.extend(debug_info.load_cmds);
+                function_starts.extend(debug_info.function_starts);
+                objc = debug_info.objc;
             }
             object::File::Elf32(elf) => {
                 let debug_info = elf::ElfDebugInfo::parse(elf)?;
                 sections.extend(debug_info.sections);
                 syms.extend(debug_info.syms);
+                imports.extend(debug_info.imports);
+                exports.extend(debug_info.exports);
+                autostart.extend(debug_info.autostart);
+                kernel = debug_info.kernel;
+                relocated = debug_info.relocated;
             }
             object::File::Elf64(elf) => {
                 let debug_info = elf::ElfDebugInfo::parse(elf)?;
                 sections.extend(debug_info.sections);
                 syms.extend(debug_info.syms);
+                imports.extend(debug_info.imports);
+                exports.extend(debug_info.exports);
+                autostart.extend(debug_info.autostart);
+                kernel = debug_info.kernel;
+                relocated = debug_info.relocated;
             }
             object::File::Pe32(pe) => {
                 let debug_info = pe::PeDebugInfo::parse(pe)?;
                 sections.extend(debug_info.sections);
                 syms.extend(debug_info.syms);
+                imports.extend(debug_info.imports);
+                exports.extend(debug_info.exports);
+                autostart.extend(debug_info.autostart);
+                dotnet = debug_info.dotnet;
             }
             object::File::Pe64(pe) => {
                 let debug_info = pe::PeDebugInfo::parse(pe)?;
                 sections.extend(debug_info.sections);
                 syms.extend(debug_info.syms);
+                imports.extend(debug_info.imports);
+                exports.extend(debug_info.exports);
+                autostart.extend(debug_info.autostart);
+                dotnet = debug_info.dotnet;
             }
             _ => {}
         }
 
-        for section in sections.iter() {
+        let mut named: std::collections::HashSet<PhysAddr> =
+            syms.iter().filter(|sym| !sym.item.name.is_empty()).map(|sym| sym.addr).collect();
+
+        // stripped kernels still name the symbols they export to modules
+        let kernel_syms = kernel.symbols();
+        for (addr, name) in kernel_syms.iter() {
+            if named.insert(*addr) {
+                syms.push(Addressed {
+                    addr: *addr,
+                    item: RawSymbol { name, module: None },
+                });
+            }
+        }
+
+        // sections are only named after where they start if nothing else is there, as the sections
+        // of relocatable objects usually start with a function
+        for section in sections.iter().filter(|section| !named.contains(&section.start)) {
             syms.push(Addressed {
                 addr: section.start,
                 item: RawSymbol { name: &section.name, module: None }
             });
         }
 
-        let index = Index::parse(&obj, &path, syms).map_err(Error::Debug)?;
-        let entrypoint = index.get_func_by_name("entry").unwrap_or(0);
+        // stripped Go binaries still name their functions for the runtime's stack traces
+        let pclntab = go::parse(&obj, &syms);
+
+        let mut index = Index::parse(&obj, &path, syms).map_err(Error::Debug)?;
+
+        if let Some(pclntab) = pclntab {
+            let funcs = pclntab.functions.into_iter().map(|func| (func.start, func.name));
+            index.insert_functions(funcs.collect());
+
+            let files: Vec<Arc<std::path::Path>> =
+                pclntab.files.iter().map(|file| Arc::from(std::path::Path::new(file))).collect();
+            let file_attrs = pclntab.lines.into_iter().map(|line| Addressed {
+                addr: line.addr,
+                item: FileAttr {
+                    path: Arc::clone(&files[line.file]),
+                    line: line.line,
+                    column_start: 0,
+                    column_end: 0,
+                },
+            });
+            index.insert_file_attrs(file_attrs.collect());
+        }
+
+        // methods are named after their class, which the symbol table usually doesn't do
+        let mut objc_syms: Vec<_> = objc.method_symbols().collect();
+        for (addr, selector) in objc.selector_refs.iter() {
+            objc_syms.push((*addr, format!("selRef_{selector}")));
+        }
+        index.insert_functions(objc_syms);
+
+        exports.extend(kernel.exports);
+
+        // code that runs on its own is a function, even if nothing calls it
+        let starts = autostart.iter().map(|start| (start.addr, start.name.clone()));
+        index.insert_functions(starts.collect());
+        if !autostart.is_empty() {
+            log::complex!(
+                w "[processor::parse] found ",
+                g autostart.len().to_string(),
+                w " auto-start locations.",
+            );
+        }
+
+        // managed methods only have a name in the metadata
+        if let Some(ref dotnet) = dotnet {
+            let methods = dotnet.methods.iter().map(|method| (method.addr, method.name.clone()));
+            index.insert_functions(methods.collect());
+        }
+
+        if !function_starts.is_empty() {
+            log::complex!(
+                w "[processor::parse] found ",
+                g function_starts.len().to_string(),
+                w " function starts.",
+            );
+        }
+
+        // functions without a symbol still get discovered through `LC_FUNCTION_STARTS`
+        if config::settings::SETTINGS.read().unwrap().discover_functions {
+            let funcs = function_starts.into_iter().map(|addr| (addr, format!("sub_{addr:x}")));
+            index.insert_functions(funcs.collect());
+        }
+        // kernel modules are entered through their init function once they're loaded
+        let entrypoint = match obj.kind() {
+            object::ObjectKind::Relocatable => index.get_func_by_name("init_module"),
+            _ => index.get_func_by_name("entry"),
+        };
+        let entrypoint = entrypoint.unwrap_or(0);
 
         if entrypoint != 0 {
             log::complex!(
@@ -259,7 +638,7 @@ impl Processor {
             let rva = entrypoint - obj.relative_address_base() as PhysAddr;
             let start = obj.relative_address_base() as PhysAddr + rva;
             let end = start + binary.len() - rva;
-            let section = Section::new(
+            let mut section = Section::new(
                 "flat".to_string(),
                 "GENERATED",
                 SectionKind::Code,
@@ -268,12 +647,20 @@ impl Processor {
                 end,
             );
 
+            section.file_offset = Some(rva as u64);
+            section.permissions = Permissions {
+                read: true,
+                write: false,
+                execute: true,
+            };
+
             sections.push(section);
         }
 
         if segments.is_empty() {
+            // sections of relocatable objects are laid out past the end of the file
             let start = obj.relative_address_base() as PhysAddr;
-            let end = start + binary.len();
+            let end = sections.iter().map(|s| s.end).fold(start + binary.len(), usize::max);
             let segment = Segment {
                 name: "flat (generated)".to_string(),
                 start,
@@ -283,124 +670,92 @@ impl Processor {
             segments.push(segment);
         }
 
-        let arch = obj.architecture();
-        let (instruction_tokens, instruction_width) = unsafe {
-            match arch {
-                Architecture::Riscv32 | Architecture::Riscv64 => (
-                    std::mem::transmute(<riscv::Instruction as Decoded>::tokens as usize),
-                    std::mem::transmute(<riscv::Instruction as Decoded>::width as usize),
-                ),
-                Architecture::Mips | Architecture::Mips64 => (
-                    std::mem::transmute(<mips::Instruction as Decoded>::tokens as usize),
-                    std::mem::transmute(<mips::Instruction as Decoded>::width as usize),
-                ),
-                Architecture::X86_64_X32 | Architecture::I386 => (
-                    std::mem::transmute(<x86::Instruction as Decoded>::tokens as usize),
-                    std::mem::transmute(<x86::Instruction as Decoded>::width as usize),
-                ),
-                Architecture::X86_64 => (
-                    std::mem::transmute(<x64::Instruction as Decoded>::tokens as usize),
-                    std::mem::transmute(<x64::Instruction as Decoded>::width as usize),
-                ),
-                Architecture::Arm => (
-                    std::mem::transmute(<armv7::Instruction as Decoded>::tokens as usize),
-                    std::mem::transmute(<armv7::Instruction as Decoded>::width as usize),
-                ),
-                Architecture::Aarch64 | Architecture::Aarch64_Ilp32 => (
-                    std::mem::transmute(<aarch64::Instruction as Decoded>::tokens as usize),
-                    std::mem::transmute(<aarch64::Instruction as Decoded>::width as usize),
-                ),
-                arch => return Err(Error::UnknownArchitecture(arch)),
-            }
+        // ELF's don't have an image base, so use the lowest loaded address instead
+        let image_base = match obj.relative_address_base() as PhysAddr {
+            0 => segments.first().map_or(0, |seg| seg.start),
+            base => base,
         };
 
-        let mut instructions = AddressMap::default();
-        let mut errors = AddressMap::default();
-        let max_instruction_width;
+        let address_widths = AddressWidths::new(&sections, &segments, image_base);
 
-        match arch {
-            Architecture::Riscv32 => {
-                impl_recursion!(
-                    &index,
-                    &mut errors,
-                    &mut instructions,
-                    &mut sections,
-                    max_instruction_width,
-                    riscv::Decoder { is_64: false },
-                    riscv
-                )
-            }
-            Architecture::Riscv64 => {
-                impl_recursion!(
-                    &index,
-                    &mut errors,
-                    &mut instructions,
-                    &mut sections,
-                    max_instruction_width,
-                    riscv::Decoder { is_64: true },
-                    riscv
-                )
-            }
-            Architecture::Mips | Architecture::Mips64 => {
-                impl_recursion!(
-                    &index,
-                    &mut errors,
-                    &mut instructions,
-                    &mut sections,
-                    max_instruction_width,
-                    mips::Decoder::default(),
-                    mips
-                )
-            }
-            Architecture::X86_64_X32 | Architecture::I386 => {
-                impl_recursion!(
-                    &index,
-                    &mut errors,
-                    &mut instructions,
-                    &mut sections,
-                    max_instruction_width,
-                    x86::Decoder::default(),
-                    x86
-                )
-            }
-            Architecture::X86_64 => {
-                impl_recursion!(
-                    &index,
-                    &mut errors,
-                    &mut instructions,
-                    &mut sections,
-                    max_instruction_width,
-                    x64::Decoder::default(),
-                    x64
-                )
-            }
-            Architecture::Arm => {
-                impl_recursion!(
-                    &index,
-                    &mut errors,
-                    &mut instructions,
-                    &mut sections,
-                    max_instruction_width,
-                    armv7::Decoder::default(),
-                    armv7
-                )
-            },
-            Architecture::Aarch64 | Architecture::Aarch64_Ilp32 => {
-                impl_recursion!(
-                    &index,
-                    &mut errors,
-                    &mut instructions,
-                    &mut sections,
-                    max_instruction_width,
-                    aarch64::Decoder::default(),
-                    aarch64
-                )
-            }
-            _ => unreachable!(),
+        progress.enter(Stage::Imports)?;
+
+        let detections = match Feature::Analysis.enabled() {
+            true => packers::detect(&obj, binary, &sections),
+            false => Vec::new(),
+        };
+        for detection in detections.iter() {
+            log::complex!(
+                w "[processor::parse] ",
+                y detection.to_string(),
+            );
+        }
+
+        if detections.iter().any(Detection::is_severe) {
+            log::complex!(
+                w "[processor::parse] ",
+                r "The binary is likely packed, its disassembly may be meaningless.",
+            );
+        }
+
+        let mitigations = match Feature::Analysis.enabled() {
+            true => mitigations::check(&obj, &imports),
+            false => Vec::new(),
         };
 
-        instructions.sort_unstable();
-        errors.sort_unstable();
+        let rich = rich::parse(&obj, binary);
+        if rich.as_ref().is_some_and(|rich| !rich.valid) {
+            log::complex!(
+                w "[processor::parse] ",
+                r "The checksum of the Rich header doesn't match, it was likely tampered with.",
+            );
+        }
+
+        let signature = codesign::parse(&obj, binary);
+        if let Some(ref signature) = signature {
+            log::complex!(
+                w "[processor::parse] ",
+                y signature.to_string(),
+            );
+
+            if !signature.covers(binary) {
+                log::complex!(
+                    w "[processor::parse] ",
+                    r "The signature doesn't cover the binary, it was modified after signing.",
+                );
+            }
+        }
+
+        let overlay = overlay::detect(&obj, binary);
+        if let Some(ref overlay) = overlay {
+            log::complex!(
+                w "[processor::parse] found ",
+                g overlay.size.to_string(),
+                w " bytes appended at ",
+                g format!("{:#X}", overlay.offset),
+                w ".",
+            );
+        }
+
+        let embedded = carving::scan(binary);
+        if !embedded.is_empty() {
+            log::complex!(
+                w "[processor::parse] found ",
+                g embedded.len().to_string(),
+                w " embedded files.",
+            );
+        }
+
+        progress.enter(Stage::Disassembly)?;
+
+        let arch = obj.architecture();
+        let (
+            instruction_tokens,
+            instruction_width,
+            instruction_lower,
+            max_instruction_width,
+            decode_region,
+        ) = decoders(arch)?;
 
         log::complex!(
             w "[processor::parse] took ",
@@ -409,46 +764,674 @@ impl Processor {
             w format!("{path:?}.")
         );
 
-        Ok(Self {
+        let regions = sweep::regions(&sections);
+        let cache = cache::Cache::load(&hash);
+        let mut processor = Self {
             entrypoint,
             path,
+            hash,
+            hashes,
             sections,
+            regions,
+            decode_region,
+            patches: RwLock::default(),
+            structs: RwLock::new(structs::known(&index)),
+            applied: RwLock::default(),
+            enums: RwLock::new(enums::known(&index)),
+            applied_enums: RwLock::default(),
+            cache: Mutex::new(cache),
+            progress,
             segments,
-            errors,
-            instructions,
             index,
+            image_base,
+            analysis_time: std::time::Duration::ZERO,
+            timings: log::Profile::default(),
+            detections,
+            mitigations,
+            rich,
+            signature,
+            signature_covers: Mutex::new(None),
+            overlay,
+            embedded,
+            crypto: Vec::new(),
+            idioms: AddressMap::default(),
+            syscalls: AddressMap::default(),
+            data_pointers: BTreeMap::new(),
+            data_xrefs: BTreeMap::new(),
+            strings: Vec::new(),
+            function_strings: BTreeMap::new(),
+            back_edges: BTreeMap::new(),
+            dead_code: Vec::new(),
+            imports,
+            exports,
+            autostart,
+            load_commands,
+            objc,
+            dotnet,
+            bytecode: None,
+            call_graph: CallGraph::default(),
+            prototypes: BTreeMap::new(),
+            indirect: BTreeMap::new(),
+            address_widths,
             _file: file,
-            _mmap: mmap,
+            mmap,
+            _owned: relocated,
+            coredump: None,
             max_instruction_width,
             instruction_tokens,
             instruction_width,
+            instruction_lower,
             arch,
+            format: obj.format(),
+            abi_registers: AtomicBool::new(false),
             endianness: obj.endianness(),
-        })
+        };
+
+        // analysis passes look at every instruction, otherwise regions are decoded once they're
+        // needed or by a background thread
+        if Feature::Analysis.enabled() {
+            let sigs = signatures::Signatures::load();
+            let cached = processor.cache.get_mut().unwrap().analysis.clone();
+            let analysis = match cached.filter(|cached| cached.signatures == sigs.digest()) {
+                Some(analysis) => {
+                    processor.progress.enter(Stage::Xrefs)?;
+                    log::complex!(
+                        w "[processor::parse] loaded analysis from ",
+                        g "cache",
+                        w ".",
+                    );
+                    analysis
+                }
+                None => {
+                    processor.decode_all();
+                    processor.progress.enter(Stage::Xrefs)?;
+
+                    let resolved = processor.resolve_indirect();
+                    let mut calls = processor.calls();
+                    calls.extend(resolved.calls.iter().copied());
+
+                    let mut indirect = resolved.calls;
+                    indirect.extend(resolved.jumps);
+
+                    let functions = processor.library_functions(&sigs, &calls);
+                    let analysis = cache::Analysis {
+                        signatures: sigs.digest(),
+                        calls,
+                        indirect,
+                        functions,
+                    };
+
+                    let cache = processor.cache.get_mut().unwrap();
+                    cache.analysis = Some(analysis.clone());
+                    cache.dirty = true;
+                    processor.save_cache();
+                    analysis
+                }
+            };
+
+            if !analysis.functions.is_empty() {
+                log::complex!(
+                    w "[processor::parse] recognized ",
+                    g analysis.functions.len().to_string(),
+                    w " library functions.",
+                );
+            }
+
+            processor.index.insert_functions(analysis.functions);
+            for &(site, target) in analysis.indirect.iter() {
+                processor.indirect.entry(site).or_default().push(target);
+            }
+
+            if !processor.indirect.is_empty() {
+                log::complex!(
+                    w "[processor::parse] resolved ",
+                    g processor.indirect.len().to_string(),
+                    w " indirect calls and jumps.",
+                );
+            }
+
+            processor.call_graph = CallGraph::build(&processor, &analysis.calls);
+
+            log::complex!(
+                w "[processor::parse] found ",
+                g processor.call_graph.edge_count().to_string(),
+                w " calls between functions.",
+            );
+
+            processor.prototypes = processor.infer_prototypes(&analysis.calls);
+            log::complex!(
+                w "[processor::parse] inferred arguments of ",
+                g processor.prototypes.len().to_string(),
+                w " functions.",
+            );
+
+            processor.crypto = processor.crypto_constants();
+            if !processor.crypto.is_empty() {
+                log::complex!(
+                    w "[processor::parse] found ",
+                    g processor.crypto.len().to_string(),
+                    w " cryptographic constants.",
+                );
+            }
+
+            processor.idioms = processor.recognize_idioms();
+
+            let os = syscalls::Os::detect(processor.format, binary);
+            processor.syscalls = processor.find_syscalls(os);
+            if !processor.syscalls.is_empty() {
+                log::complex!(
+                    w "[processor::parse] found ",
+                    g processor.syscalls.len().to_string(),
+                    w " system calls.",
+                );
+            }
+
+            processor.data_pointers = processor.sweep_pointers();
+            processor.data_xrefs = pointers::xrefs(&processor.data_pointers);
+            if !processor.data_pointers.is_empty() {
+                log::complex!(
+                    w "[processor::parse] found ",
+                    g processor.data_pointers.len().to_string(),
+                    w " pointers in data sections.",
+                );
+            }
+
+            processor.strings = processor.extract_strings();
+            processor.function_strings = strings::by_function(&processor.strings);
+            log::complex!(
+                w "[processor::parse] found ",
+                g processor.strings.len().to_string(),
+                w " strings used by ",
+                g processor.function_strings.len().to_string(),
+                w " functions.",
+            );
+
+            let mut back_edges = BTreeMap::new();
+            let mut dead_code = processor.orphan_functions();
+            for cfg in processor.function_cfgs() {
+                back_edges.extend(cfg.back_edges());
+                dead_code.extend(DeadCode::unreachable(&cfg));
+            }
+
+            dead_code.sort_unstable_by_key(|dead| dead.addr);
+            processor.dead_code = dead_code;
+            processor.back_edges = back_edges;
+            if !processor.back_edges.is_empty() {
+                log::complex!(
+                    w "[processor::parse] found ",
+                    g processor.back_edges.len().to_string(),
+                    w " jumps closing a loop.",
+                );
+            }
+
+            if !processor.dead_code.is_empty() {
+                log::complex!(
+                    w "[processor::parse] found ",
+                    g processor.dead_code.len().to_string(),
+                    w " unreachable blocks and orphan functions.",
+                );
+            }
+        }
+
+        processor.analysis_time = now.elapsed();
+        processor.progress.finish();
+        processor.timings = log::PROFILER.take(processor.analysis_time);
+        Ok(processor)
+    }
+
+    /// Signatures of every function that's called, see [`prototypes`].
+    fn infer_prototypes(&self, calls: &[(PhysAddr, PhysAddr)]) -> BTreeMap<PhysAddr, Prototype> {
+        let mut targets: Vec<PhysAddr> = calls.iter().map(|(_, target)| *target).collect();
+        targets.sort_unstable();
+        targets.dedup();
+
+        targets
+            .into_iter()
+            .filter_map(|addr| Some((addr, self.infer_prototype(addr)?)))
+            .collect()
+    }
+
+    /// Constants of cryptographic algorithms along with the functions using them, see [`crypto`].
+    fn crypto_constants(&self) -> Vec<CryptoConstant> {
+        let mut constants = crypto::scan(self.sections());
+        for constant in constants.iter_mut() {
+            if !self.section_by_addr(constant.addr).is_some_and(|s| s.kind == SectionKind::Code) {
+                continue;
+            }
+
+            let start = self.function_start(constant.addr);
+            let symbol = start.and_then(|start| self.index.get_sym_by_addr(start));
+            constant.function = symbol.map(|symbol| symbol.as_str().to_string());
+        }
+
+        constants
+    }
+
+    /// Constants of cryptographic algorithms starting within an address range.
+    pub fn crypto_in(&self, range: Range<PhysAddr>) -> &[CryptoConstant] {
+        let start = self.crypto.partition_point(|constant| constant.addr < range.start);
+        let end = self.crypto.partition_point(|constant| constant.addr < range.end);
+        &self.crypto[start..end]
+    }
+
+    /// Unnamed functions that match a signature, see [`signatures`].
+    fn library_functions(
+        &self,
+        sigs: &signatures::Signatures,
+        calls: &[(PhysAddr, PhysAddr)],
+    ) -> Vec<(PhysAddr, String)> {
+        if sigs.is_empty() {
+            return Vec::new();
+        }
+
+        let mut candidates = vec![self.entrypoint];
+        candidates.extend(calls.iter().map(|(_, target)| *target));
+
+        candidates.sort_unstable();
+        candidates.dedup();
+
+        let mut funcs = Vec::new();
+        for addr in candidates {
+            if addr == 0 || self.index.get_sym_by_addr(addr).is_some() {
+                continue;
+            }
+
+            let section = match self.section_by_addr(addr) {
+                Some(section) => section,
+                None => continue,
+            };
+
+            if let Some(names) = sigs.find(section.bytes_by_addr(addr, usize::MAX)) {
+                funcs.extend(names.iter().map(|(offset, name)| (addr + offset, name.clone())));
+            }
+        }
+
+        funcs
     }
 
     /// Relatively slow tokenization of an [`Instruction`].
     /// Xref's get resolved which requires some extra computation.
     pub fn instruction_tokens(&self, instruction: &Instruction, symbols: &Index) -> Vec<Token> {
-        (self.instruction_tokens)(instruction, symbols)
+        if let Some(ref bytecode) = self.bytecode {
+            return bytecode.tokens(instruction);
+        }
+
+        let tokens = (self.instruction_tokens)(instruction, symbols);
+        registers::apply(tokens, self.arch, self.format, self.register_names())
+    }
+
+    /// How registers are named by [`Processor::instruction_tokens`].
+    pub fn register_names(&self) -> RegisterNames {
+        match self.abi_registers.load(Ordering::Relaxed) {
+            true => RegisterNames::Abi,
+            false => RegisterNames::Architectural,
+        }
+    }
+
+    /// Change how registers are named, blocks that were already parsed keep their names.
+    pub fn set_register_names(&self, names: RegisterNames) {
+        self.abi_registers.store(names == RegisterNames::Abi, Ordering::Relaxed);
+    }
+
+    pub fn arch(&self) -> Architecture {
+        self.arch
+    }
+
+    pub fn format(&self) -> BinaryFormat {
+        self.format
+    }
+
+    /// Name of the bytecode format if the binary holds bytecode, for which [`Processor::format`]
+    /// and [`Processor::arch`] don't mean anything.
+    pub fn bytecode_format(&self) -> Option<&'static str> {
+        self.bytecode.as_ref().map(bytecode::Bytecode::format)
+    }
+
+    pub fn endianness(&self) -> Endianness {
+        self.endianness
     }
 
     pub fn instruction_width(&self, instruction: &Instruction) -> usize {
         (self.instruction_width)(instruction)
     }
 
-    pub fn error_by_addr(&self, addr: PhysAddr) -> Option<&decoder::Error> {
-        match self.errors.search(addr) {
-            Ok(idx) => Some(&self.errors[idx].item),
-            Err(..) => None,
+    /// Architecture independent effects of an [`Instruction`].
+    pub fn instruction_ir(&self, instruction: &Instruction) -> Vec<ir::Stmt> {
+        let mut stmts = Vec::new();
+        (self.instruction_lower)(instruction, &mut stmts);
+        stmts
+    }
+
+    /// Explanation of an instruction's mnemonic, if the architecture has a reference.
+    pub fn reference(&self, mnemonic: &str) -> Option<&'static Reference> {
+        match self.arch {
+            Architecture::X86_64 | Architecture::X86_64_X32 | Architecture::I386 => {
+                x86_64::reference::reference(mnemonic)
+            }
+            Architecture::Aarch64 | Architecture::Aarch64_Ilp32 => {
+                arm::armv8::reference::reference(mnemonic)
+            }
+            _ => None,
+        }
+    }
+
+    /// Instructions and errors of a region, decoding it if that hasn't happened yet.
+    fn decode(&self, region: &sweep::Region<Instruction>) -> Arc<sweep::Decoded<Instruction>> {
+        let idx = self.regions.partition_point(|other| other.start < region.start);
+        let mut decoded_now = false;
+        let decoded = region.get_or_decode(|| {
+            decoded_now = true;
+            self.decode_from(region, self.seam(idx))
+        });
+
+        if decoded_now {
+            self.realign(idx);
+        }
+
+        decoded
+    }
+
+    /// Where the instructions before a region end, if the region before it in the same section
+    /// was decoded.
+    fn seam(&self, idx: usize) -> Option<PhysAddr> {
+        let prev = self.regions.get(idx.checked_sub(1)?)?;
+        if prev.section != self.regions[idx].section {
+            return None;
         }
+
+        prev.get().map(|decoded| decoded.end)
+    }
+
+    /// Decode a region with the user's patches applied, starting at `seam` if it's known.
+    fn decode_from(
+        &self,
+        region: &sweep::Region<Instruction>,
+        seam: Option<PhysAddr>,
+    ) -> sweep::Decoded<Instruction> {
+        let section = &self.sections[region.section];
+        let lookbehind = sweep::LOOKBEHIND * self.max_instruction_width;
+        let base = seam.unwrap_or_else(|| {
+            std::cmp::max(section.start, region.start.saturating_sub(lookbehind))
+        });
+        let len = region.end.saturating_sub(base) + self.max_instruction_width;
+        let bytes = self.patched_bytes(section, base, len);
+
+        (self.decode_region)(&bytes, base, region.start, region.end, seam)
+    }
+
+    /// Decode the regions following a region again for as long as they don't line up with the
+    /// instructions before them. Returns the index of the last region that was decoded again.
+    fn realign(&self, mut idx: usize) -> usize {
+        while let Some(next) = self.regions.get(idx + 1) {
+            let redecoded = next.redecode(|decoded| {
+                let seam = self.seam(idx + 1)?;
+                if decoded.starts_at(seam) {
+                    return None;
+                }
+
+                Some(self.decode_from(next, Some(seam)))
+            });
+
+            if !redecoded {
+                break;
+            }
+
+            idx += 1;
+        }
+
+        idx
     }
 
-    pub fn instruction_by_addr(&self, addr: PhysAddr) -> Option<&Instruction> {
-        match self.instructions.search(addr) {
-            Ok(idx) => Some(&self.instructions[idx].item),
-            Err(..) => None,
+    /// Whether the user patched any bytes of a section.
+    pub fn is_patched(&self, section: &Section) -> bool {
+        let patches = self.patches.read().unwrap();
+        patches.range(section.start..section.end).next().is_some()
+    }
+
+    /// Bytes of a section with the user's patches applied.
+    pub fn patched_bytes<'a>(
+        &self,
+        section: &'a Section,
+        addr: PhysAddr,
+        len: usize,
+    ) -> Cow<'a, [u8]> {
+        let bytes = section.bytes_by_addr(addr, len);
+        let patches = self.patches.read().unwrap();
+        let mut patched = patches.range(addr..addr + bytes.len()).peekable();
+
+        if patched.peek().is_none() {
+            return Cow::Borrowed(bytes);
         }
+
+        let mut bytes = bytes.to_vec();
+        for (patch_addr, &byte) in patched {
+            bytes[patch_addr - addr] = byte;
+        }
+
+        Cow::Owned(bytes)
+    }
+
+    /// Bytes of the file the binary was loaded from with the user's patches applied.
+    pub fn patched_binary(&self) -> Cow<'_, [u8]> {
+        let patches = self.patches.read().unwrap();
+        if patches.is_empty() {
+            return Cow::Borrowed(self.binary());
+        }
+
+        let mut binary = self.binary().to_vec();
+        for (&addr, &byte) in patches.iter() {
+            let offset = self.section_by_addr(addr).and_then(|section| {
+                Some(section.file_offset? as usize + (addr - section.start))
+            });
+
+            if let Some(patched) = offset.and_then(|offset| binary.get_mut(offset)) {
+                *patched = byte;
+            }
+        }
+
+        Cow::Owned(binary)
+    }
+
+    /// Whether the code signature still covers the binary after it was patched, if it's signed.
+    pub fn signature_covers(&self) -> Option<bool> {
+        let signature = self.signature.as_ref()?;
+        let mut covers = self.signature_covers.lock().unwrap();
+        Some(*covers.get_or_insert_with(|| signature.covers(&self.patched_binary())))
+    }
+
+    /// Overwrite bytes of a code section, only decoding the regions around them again.
+    ///
+    /// Returns the addresses of which the instructions might have changed, or `None` if the bytes
+    /// aren't all part of a single code section.
+    pub fn patch(&self, addr: PhysAddr, bytes: &[u8]) -> Option<Range<PhysAddr>> {
+        let end = addr + bytes.len();
+        let section = self.section_by_addr(addr)?;
+
+        if section.kind != SectionKind::Code || end > section.start + section.bytes().len() {
+            return None;
+        }
+
+        let mut patches = self.patches.write().unwrap();
+        for (offset, &byte) in bytes.iter().enumerate() {
+            patches.insert(addr + offset, byte);
+        }
+        drop(patches);
+        *self.signature_covers.lock().unwrap() = None;
+
+        // instructions right before the patch might overlap with it, and the regions that follow
+        // it are decoded again if they no longer line up with it
+        let from = std::cmp::max(section.start, addr.saturating_sub(self.max_instruction_width));
+        let first = self.regions.partition_point(|region| region.end <= from);
+        let last = self.regions.partition_point(|region| region.start < end);
+
+        if first >= last {
+            return None;
+        }
+
+        for idx in first..last {
+            let region = &self.regions[idx];
+            region.replace(|| self.decode_from(region, self.seam(idx)));
+        }
+
+        let start = self.regions[first].start;
+        let end = self.regions[self.realign(last - 1)].end;
+
+        log::complex!(
+            w "[processor::patch] decoded ",
+            g format!("{:#x}", end - start),
+            w " bytes again after patching ",
+            g format!("{:#x}", addr),
+            w ".",
+        );
+
+        Some(start..end)
+    }
+
+    /// Decode every region that hasn't been decoded yet, spread out over all cores.
+    ///
+    /// Stops early if the [`AnalysisPipeline`] that loaded the binary got cancelled.
+    pub fn decode_all(&self) {
+        let pending: Vec<_> = self.regions.iter().filter(|region| !region.is_decoded()).collect();
+
+        if pending.is_empty() {
+            return;
+        }
+
+        let now = std::time::Instant::now();
+        let bytes: usize = pending.iter().map(|region| region.end - region.start).sum();
+        let task = log::PROGRESS.start("Decoding instructions", bytes);
+
+        sweep::parallel(pending.len(), |idx| {
+            if !self.progress.is_cancelled() && !task.is_cancelled() {
+                let region = pending[idx];
+                self.decode(region);
+                task.step_n(region.end - region.start);
+            }
+        });
+
+        log::complex!(
+            w "[processor::decode_all] took ",
+            y format!("{:#?}", now.elapsed()),
+            w " to decode ",
+            g format!("{:#x}", bytes),
+            w " bytes of code.",
+        );
+    }
+
+    /// Every instruction starting at or after an address, decoding regions as needed.
+    pub(crate) fn instructions_from(
+        &self,
+        addr: PhysAddr,
+    ) -> impl Iterator<Item = Borrowed<Addressed<Instruction>>> + '_ {
+        let first = self.regions.partition_point(|region| region.end <= addr);
+
+        self.regions[first..]
+            .iter()
+            .flat_map(|region| sweep::instructions(self.decode(region)))
+            .skip_while(move |instruction| instruction.addr < addr)
+    }
+
+    pub fn error_by_addr(&self, addr: PhysAddr) -> Option<Borrowed<decoder::Error>> {
+        let idx = self.regions.partition_point(|region| region.end <= addr);
+        let region = self.regions.get(idx).filter(|region| region.start <= addr)?;
+
+        Borrowed::new(self.decode(region), |decoded| {
+            match decoded.errors.search(addr) {
+                Ok(idx) => Some(&decoded.errors[idx].item),
+                Err(..) => None,
+            }
+        })
+    }
+
+    pub fn instruction_by_addr(&self, addr: PhysAddr) -> Option<Borrowed<Instruction>> {
+        let idx = self.regions.partition_point(|region| region.end <= addr);
+        let region = self.regions.get(idx).filter(|region| region.start <= addr)?;
+
+        Borrowed::new(self.decode(region), |decoded| {
+            match decoded.instructions.search(addr) {
+                Ok(idx) => Some(&decoded.instructions[idx].item),
+                Err(..) => None,
+            }
+        })
+    }
+
+    /// Closest instruction starting at or before an address, decoding regions as needed.
+    pub fn instruction_at_or_before(
+        &self,
+        addr: PhysAddr,
+    ) -> Option<Borrowed<Addressed<Instruction>>> {
+        let last = self.regions.partition_point(|region| region.start <= addr);
+
+        self.regions[..last].iter().rev().find_map(|region| {
+            Borrowed::new(self.decode(region), |decoded| {
+                decoded.instructions.at_or_before(addr)
+            })
+        })
+    }
+
+    /// First instruction starting after an address, decoding regions as needed.
+    pub fn next_instruction(&self, addr: PhysAddr) -> Option<Borrowed<Addressed<Instruction>>> {
+        let first = self.regions.partition_point(|region| region.end <= addr);
+
+        self.regions[first..].iter().find_map(|region| {
+            Borrowed::new(self.decode(region), |decoded| {
+                decoded.instructions.after(addr)
+            })
+        })
+    }
+
+    /// Address the instruction at an address jumps or branches to, if it's known.
+    pub fn branch_target(&self, addr: PhysAddr) -> Option<PhysAddr> {
+        let instruction = self.instruction_by_addr(addr)?;
+        self.instruction_ir(&instruction).into_iter().find_map(|stmt| match stmt {
+            ir::Stmt::Jump(ir::Expr::Const(target))
+            | ir::Stmt::Branch {
+                target: ir::Expr::Const(target),
+                ..
+            } => Some(target as PhysAddr),
+            _ => None,
+        })
+    }
+
+    /// Addresses of the instructions starting in a range, decoding regions as needed.
+    pub fn instructions_in(&self, range: Range<PhysAddr>) -> Vec<PhysAddr> {
+        let mut addrs = Vec::new();
+        let mut addr = range.start;
+
+        if self.instruction_by_addr(addr).is_some() {
+            addrs.push(addr);
+        }
+
+        while let Some(instruction) = self.next_instruction(addr) {
+            addr = instruction.addr;
+            if addr >= range.end {
+                break;
+            }
+            addrs.push(addr);
+        }
+
+        addrs
+    }
+
+    /// Address of the first instruction or error starting after an address.
+    fn next_decoded(&self, addr: PhysAddr) -> Option<PhysAddr> {
+        let first = self.regions.partition_point(|region| region.end <= addr);
+
+        self.regions[first..].iter().find_map(|region| {
+            let decoded = self.decode(region);
+            let instruction = decoded.instructions.after(addr).map(|inst| inst.addr);
+            let error = decoded.errors.after(addr).map(|err| err.addr);
+
+            match (instruction, error) {
+                (Some(a), Some(b)) => Some(std::cmp::min(a, b)),
+                (a, b) => a.or(b),
+            }
+        })
+    }
+
+    /// Bytes of the file the binary was loaded from.
+    pub fn binary(&self) -> &[u8] {
+        &self.mmap
     }
 
     pub fn segments(&self) -> impl DoubleEndedIterator<Item = &Segment> {
@@ -485,27 +1468,6 @@ impl Processor {
     }
 }
 
-impl Drop for Processor {
-    /// Required `Drop` impl as [`Instruction`]'s a non-copy union.
-    fn drop(&mut self) {
-        for Addressed { item: inst, .. } in self.instructions.iter_mut() {
-            match self.arch {
-                Architecture::X86_64 => unsafe { ManuallyDrop::drop(&mut inst.x64) },
-                Architecture::X86_64_X32 | Architecture::I386 => unsafe {
-                    ManuallyDrop::drop(&mut inst.x86)
-                },
-                Architecture::Riscv64 | Architecture::Riscv32 => unsafe {
-                    ManuallyDrop::drop(&mut inst.riscv)
-                },
-                Architecture::Mips | Architecture::Mips64 => unsafe {
-                    ManuallyDrop::drop(&mut inst.mips)
-                },
-                _ => {}
-            }
-        }
-    }
-}
-
 impl From<object::Error> for Error {
     fn from(err: object::Error) -> Self {
         Error::Object(err)
@@ -0,0 +1,1688 @@
+//! Translation of a function's control flow graph into a simple pseudo-C listing.
+//!
+//! Registers take the place of variables. Expressions are propagated within a basic block so that
+//! temporary registers disappear, conditional jumps are paired with the comparison that set their
+//! flags, and loops and forward branches are recovered as `do`/`while` and `if`/`else` statements.
+//! Control flow that can't be structured falls back to `goto`.
+
+use crate::cfg::{Cfg, Error, Exit, Inst, Target};
+use crate::Processor;
+use config::CONFIG;
+use debugvault::Index;
+use object::Architecture;
+use processor_shared::{PhysAddr, Section, SectionKind};
+use std::collections::BTreeSet;
+use std::ops::Range;
+use tokenizing::Token;
+
+/// Number of spaces a nested statement is indented by.
+const INDENT: usize = 4;
+
+/// Shortest run of characters that's shown as a string literal.
+const MIN_STRING_LEN: usize = 2;
+
+/// General purpose registers as `(64-bit name, 32-bit name, partial registers)`.
+const REGISTERS: [(&str, &str, &[&str]); 16] = [
+    ("rax", "eax", &["ax", "al", "ah"]),
+    ("rcx", "ecx", &["cx", "cl", "ch"]),
+    ("rdx", "edx", &["dx", "dl", "dh"]),
+    ("rbx", "ebx", &["bx", "bl", "bh"]),
+    ("rsp", "esp", &["sp", "spl"]),
+    ("rbp", "ebp", &["bp", "bpl"]),
+    ("rsi", "esi", &["si", "sil"]),
+    ("rdi", "edi", &["di", "dil"]),
+    ("r8", "r8d", &["r8w", "r8b"]),
+    ("r9", "r9d", &["r9w", "r9b"]),
+    ("r10", "r10d", &["r10w", "r10b"]),
+    ("r11", "r11d", &["r11w", "r11b"]),
+    ("r12", "r12d", &["r12w", "r12b"]),
+    ("r13", "r13d", &["r13w", "r13b"]),
+    ("r14", "r14d", &["r14w", "r14b"]),
+    ("r15", "r15d", &["r15w", "r15b"]),
+];
+
+/// Index of a general purpose register in [`REGISTERS`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Reg(usize);
+
+const RAX: Reg = Reg(0);
+const RSP: Reg = Reg(4);
+const RBP: Reg = Reg(5);
+
+/// Registers used for passing arguments in the System V ABI.
+const SYSV_ARGS: [Reg; 6] = [Reg(7), Reg(6), Reg(2), Reg(1), Reg(8), Reg(9)];
+
+/// Registers that aren't preserved across calls in the System V ABI.
+const SYSV_CLOBBERED: [Reg; 9] = [
+    Reg(0),
+    Reg(1),
+    Reg(2),
+    Reg(6),
+    Reg(7),
+    Reg(8),
+    Reg(9),
+    Reg(10),
+    Reg(11),
+];
+
+const KEYWORDS: &[&str] = &[
+    "asm", "break", "continue", "do", "else", "goto", "if", "return", "true", "while",
+];
+
+const TYPES: &[&str] = &[
+    "u8", "u16", "u32", "u64", "u128", "u256", "u512", "i8", "i16", "i32", "i64", "f80",
+];
+
+/// Whether a statement is made up of a single term, so it doesn't need parentheses when nested.
+fn is_atomic(text: &str) -> bool {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut prev = '\0';
+
+    for chr in text.chars() {
+        match chr {
+            '"' if prev != '\\' => in_string = !in_string,
+            '(' | '[' if !in_string => depth += 1,
+            ')' | ']' if !in_string => depth = depth.saturating_sub(1),
+            ' ' if !in_string && depth == 0 => return false,
+            _ => {}
+        }
+        prev = chr;
+    }
+
+    true
+}
+
+/// Expression computed by one or more instructions.
+#[derive(Debug, Clone)]
+struct Value {
+    text: String,
+    /// Registers the expression depends on.
+    reads: Vec<Reg>,
+    /// Whether the expression reads memory.
+    memory: bool,
+    /// Whether evaluating the expression has side effects, e.g. a call.
+    effect: bool,
+}
+
+impl Value {
+    fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            reads: Vec::new(),
+            memory: false,
+            effect: false,
+        }
+    }
+
+    /// The expression, wrapped in parentheses if it's made up of multiple terms.
+    fn atom(&self) -> String {
+        match is_atomic(&self.text) {
+            true => self.text.clone(),
+            false => format!("({})", self.text),
+        }
+    }
+
+    /// Combine two expressions with a binary operator.
+    fn binary(lhs: &Value, op: &str, rhs: &Value) -> Self {
+        let mut value = Value::new(format!("{} {op} {}", lhs.atom(), rhs.atom()));
+        value.depend_on(lhs);
+        value.depend_on(rhs);
+        value
+    }
+
+    /// Take over the dependencies of an expression that's part of this one.
+    fn depend_on(&mut self, other: &Value) {
+        for reg in other.reads.iter() {
+            if !self.reads.contains(reg) {
+                self.reads.push(*reg);
+            }
+        }
+
+        self.memory |= other.memory;
+        self.effect |= other.effect;
+    }
+}
+
+/// Comparison that decides a branch.
+#[derive(Debug, Clone)]
+struct Cond {
+    lhs: Value,
+    op: &'static str,
+    rhs: Value,
+}
+
+impl Cond {
+    fn negate(&self) -> Self {
+        let op = match self.op {
+            "==" => "!=",
+            "!=" => "==",
+            "<" => ">=",
+            ">=" => "<",
+            ">" => "<=",
+            _ => ">",
+        };
+
+        Self {
+            lhs: self.lhs.clone(),
+            op,
+            rhs: self.rhs.clone(),
+        }
+    }
+
+    fn text(&self) -> String {
+        format!("{} {} {}", self.lhs.text, self.op, self.rhs.text)
+    }
+
+    fn value(&self) -> Value {
+        let mut value = Value::new(self.text());
+        value.depend_on(&self.lhs);
+        value.depend_on(&self.rhs);
+        value
+    }
+}
+
+/// Operands of the last instruction that set the flags.
+#[derive(Debug, Clone)]
+enum Flags {
+    Compare(Value, Value),
+    Test(Value, Value),
+    Result(Value),
+}
+
+impl Flags {
+    /// The value the zero and sign flag are derived from.
+    fn value(&self) -> Value {
+        match self {
+            Flags::Compare(lhs, rhs) => Value::binary(lhs, "-", rhs),
+            Flags::Test(lhs, rhs) if lhs.text == rhs.text => lhs.clone(),
+            Flags::Test(lhs, rhs) => Value::binary(lhs, "&", rhs),
+            Flags::Result(value) => value.clone(),
+        }
+    }
+
+    fn reads(&self, reg: Reg) -> bool {
+        match self {
+            Flags::Compare(lhs, rhs) | Flags::Test(lhs, rhs) => {
+                lhs.reads.contains(&reg) || rhs.reads.contains(&reg)
+            }
+            Flags::Result(value) => value.reads.contains(&reg),
+        }
+    }
+
+    fn memory(&self) -> bool {
+        match self {
+            Flags::Compare(lhs, rhs) | Flags::Test(lhs, rhs) => lhs.memory || rhs.memory,
+            Flags::Result(value) => value.memory,
+        }
+    }
+}
+
+/// Reconstruct the condition of an x86 condition code, e.g. the `ne` in `jne`.
+fn condition(flags: Option<&Flags>, cc: &str) -> Cond {
+    let (lhs, rhs) = match flags {
+        Some(Flags::Compare(lhs, rhs)) => (lhs.clone(), rhs.clone()),
+        Some(flags) => (flags.value(), Value::new("0")),
+        None => (Value::new("flags"), Value::new("0")),
+    };
+
+    let difference = || match flags {
+        Some(flags) => flags.value(),
+        None => Value::new("flags"),
+    };
+
+    let (lhs, op, rhs) = match cc {
+        "e" | "z" => (lhs, "==", rhs),
+        "ne" | "nz" => (lhs, "!=", rhs),
+        "l" | "nge" | "b" | "nae" | "c" => (lhs, "<", rhs),
+        "ge" | "nl" | "ae" | "nb" | "nc" => (lhs, ">=", rhs),
+        "le" | "ng" | "be" | "na" => (lhs, "<=", rhs),
+        "g" | "nle" | "a" | "nbe" => (lhs, ">", rhs),
+        "s" => (difference(), "<", Value::new("0")),
+        "ns" => (difference(), ">=", Value::new("0")),
+        "o" | "no" | "p" | "pe" | "np" | "po" => {
+            let flag = if cc.contains('o') && cc != "po" {
+                "overflow"
+            } else {
+                "parity"
+            };
+            let difference = difference();
+            let mut lhs = Value::new(format!("{flag}({})", difference.text));
+            lhs.depend_on(&difference);
+            let op = if cc.starts_with('n') || cc == "po" {
+                "=="
+            } else {
+                "!="
+            };
+            (lhs, op, Value::new("0"))
+        }
+        _ => (Value::new(format!("{cc}(flags)")), "!=", Value::new("0")),
+    };
+
+    Cond { lhs, op, rhs }
+}
+
+#[derive(Debug, Clone)]
+enum Stmt {
+    Label(PhysAddr),
+    Assign(String, String, String),
+    Expr(String),
+    Asm(String),
+    Return(Option<String>),
+    Goto(PhysAddr),
+    IndirectJump(String),
+    Break,
+    Continue,
+    If {
+        cond: Cond,
+        then: Vec<Stmt>,
+        otherwise: Vec<Stmt>,
+    },
+    /// `do { .. } while (cond)` or `while (true) { .. }` if there's no condition.
+    Loop {
+        cond: Option<Cond>,
+        body: Vec<Stmt>,
+    },
+}
+
+/// How control leaves a lifted block.
+#[derive(Debug, Clone)]
+enum Edge {
+    Next(PhysAddr),
+    Jump(PhysAddr),
+    Branch(Cond, PhysAddr),
+    End,
+}
+
+impl Edge {
+    fn target(&self) -> Option<PhysAddr> {
+        match self {
+            Edge::Jump(target) | Edge::Branch(_, target) => Some(*target),
+            Edge::Next(..) | Edge::End => None,
+        }
+    }
+}
+
+/// Basic block translated into statements.
+struct Lifted {
+    start: PhysAddr,
+    end: PhysAddr,
+    stmts: Vec<Stmt>,
+    /// Flags when leaving the block.
+    flags: Option<Flags>,
+    edge: Edge,
+}
+
+/// Registers and flags while lifting a basic block.
+#[derive(Default)]
+struct State {
+    /// Register values that haven't been assigned yet, in the order they were computed.
+    pending: Vec<(Reg, Value)>,
+    flags: Option<Flags>,
+    /// Statement that stored the flags in a variable, if they weren't read since.
+    captured: Option<usize>,
+    stmts: Vec<Stmt>,
+}
+
+impl State {
+    /// Flags as read by a conditional instruction.
+    fn read_flags(&mut self) -> Option<&Flags> {
+        self.captured = None;
+        self.flags.as_ref()
+    }
+}
+
+enum Operand {
+    Reg {
+        reg: Reg,
+        name: String,
+        partial: bool,
+    },
+    Mem {
+        ty: &'static str,
+        addr: Address,
+    },
+    Other(Value),
+}
+
+/// Address of a memory operand.
+struct Address {
+    value: Value,
+    /// Name of the symbol the address points to.
+    symbol: Option<String>,
+    /// Address of a memory operand that doesn't depend on registers.
+    constant: Option<PhysAddr>,
+}
+
+/// Pseudo-C listing of a function.
+#[derive(Debug)]
+pub struct PseudoCode {
+    /// Entrypoint of the function.
+    pub addr: PhysAddr,
+    pub lines: Vec<String>,
+}
+
+impl PseudoCode {
+    /// Syntax highlighted lines, separated by newlines.
+    pub fn tokens(&self) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        for (idx, line) in self.lines.iter().enumerate() {
+            if idx != 0 {
+                tokens.push(Token::from_str("\n", CONFIG.colors.text));
+            }
+
+            highlight(line, &mut tokens);
+        }
+
+        tokens
+    }
+}
+
+fn highlight(line: &str, tokens: &mut Vec<Token>) {
+    let colors = &CONFIG.colors;
+    let chars: Vec<char> = line.chars().collect();
+    let mut idx = 0;
+
+    while idx < chars.len() {
+        let start = idx;
+        let chr = chars[idx];
+
+        let color = if chr == '"' {
+            idx += 1;
+            while idx < chars.len() && (chars[idx] != '"' || chars[idx - 1] == '\\') {
+                idx += 1;
+            }
+            idx = (idx + 1).min(chars.len());
+            colors.src.string
+        } else if chr.is_alphanumeric() || chr == '_' {
+            while idx < chars.len() && (chars[idx].is_alphanumeric() || "_.@$".contains(chars[idx]))
+            {
+                idx += 1;
+            }
+
+            let word = String::from_iter(&chars[start..idx]);
+            let next = chars[idx..].iter().find(|chr| **chr != ' ');
+            if KEYWORDS.contains(&&word[..]) {
+                colors.src.keyword
+            } else if TYPES.contains(&&word[..]) {
+                colors.src.tipe
+            } else if chr.is_ascii_digit() {
+                colors.src.constant
+            } else if word.starts_with("label_") {
+                colors.asm.label
+            } else if next == Some(&'(') {
+                colors.src.function
+            } else {
+                colors.src.variable
+            }
+        } else if chr == ' ' {
+            while idx < chars.len() && chars[idx] == ' ' {
+                idx += 1;
+            }
+            CONFIG.colors.text
+        } else {
+            idx += 1;
+            match chr {
+                '(' | ')' | '{' | '}' | '[' | ']' => colors.brackets,
+                ';' | ',' | ':' => colors.delimiter,
+                _ => colors.src.operator,
+            }
+        };
+
+        tokens.push(Token::from_string(
+            String::from_iter(&chars[start..idx]),
+            color,
+        ));
+    }
+}
+
+/// Turns the instructions of a function into statements.
+struct Lifter<'a> {
+    sections: Vec<&'a Section>,
+    index: &'a Index,
+    is_64: bool,
+}
+
+impl Lifter<'_> {
+    fn name(&self, reg: Reg) -> &'static str {
+        match self.is_64 {
+            true => REGISTERS[reg.0].0,
+            false => REGISTERS[reg.0].1,
+        }
+    }
+
+    /// Look up a general purpose register and whether the name refers to only part of it.
+    fn register(&self, name: &str) -> Option<(Reg, bool)> {
+        REGISTERS.iter().enumerate().find_map(|(idx, (r64, r32, partial))| {
+            if (self.is_64 && *r64 == name) || *r32 == name {
+                Some((Reg(idx), false))
+            } else if partial.contains(&name) {
+                Some((Reg(idx), true))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// General purpose registers mentioned in an operand.
+    fn registers_in(&self, operand: &str) -> Vec<Reg> {
+        operand
+            .split(|chr: char| !chr.is_ascii_alphanumeric())
+            .filter_map(|word| self.register(word))
+            .map(|(reg, _)| reg)
+            .collect()
+    }
+
+    fn reg_value(&self, reg: Reg) -> Value {
+        Value {
+            reads: vec![reg],
+            ..Value::new(self.name(reg))
+        }
+    }
+
+    /// Null terminated string at an address outside of code.
+    fn string_at(&self, addr: PhysAddr) -> Option<String> {
+        // the last section containing the address, as with [`Processor::section_by_addr`]
+        let section = self.sections.iter().rev().find(|s| s.start <= addr && addr <= s.end)?;
+        if section.kind == SectionKind::Code || addr >= section.end {
+            return None;
+        }
+
+        let bytes = section.bytes_by_addr(addr, section.end - addr);
+        let len = bytes.iter().position(|b| *b == 0)?;
+        let bytes = &bytes[..len];
+        if len < MIN_STRING_LEN
+            || !bytes.iter().all(|b| b.is_ascii_graphic() || b" \t\n\r".contains(b))
+        {
+            return None;
+        }
+
+        Some(format!("\"{}\"", bytes.escape_ascii()))
+    }
+
+    /// Assign a pending register value.
+    fn flush_reg(&self, state: &mut State, reg: Reg) {
+        if let Some(pos) = state.pending.iter().position(|(r, _)| *r == reg) {
+            let (reg, value) = state.pending.remove(pos);
+            let stmt = Stmt::Assign(self.name(reg).to_string(), "=".to_string(), value.text);
+            state.stmts.push(stmt);
+        }
+    }
+
+    /// Assign all pending register values that satisfy a predicate.
+    fn flush_if(&self, state: &mut State, pred: impl Fn(Reg, &Value) -> bool) {
+        let mut idx = 0;
+        while idx < state.pending.len() {
+            let (reg, value) = &state.pending[idx];
+            if pred(*reg, value) {
+                let (reg, value) = state.pending.remove(idx);
+                let stmt = Stmt::Assign(self.name(reg).to_string(), "=".to_string(), value.text);
+                state.stmts.push(stmt);
+            } else {
+                idx += 1;
+            }
+        }
+    }
+
+    /// Assign the values that might change by writing to memory.
+    fn flush_memory(&self, state: &mut State) {
+        self.flush_if(state, |_, value| value.memory || value.effect);
+        if state.flags.as_ref().is_some_and(Flags::memory) {
+            self.capture_flags(state);
+        }
+    }
+
+    /// Store the flags in a variable, as the values they're derived from are about to change.
+    fn capture_flags(&self, state: &mut State) {
+        if let Some(flags) = state.flags.take() {
+            let value = flags.value();
+            state.captured = Some(state.stmts.len());
+            state.stmts.push(Stmt::Assign(
+                "flags".to_string(),
+                "=".to_string(),
+                value.text,
+            ));
+            state.flags = Some(Flags::Result(Value::new("flags")));
+        }
+    }
+
+    /// Prepare for a register to be overwritten.
+    fn kill(&self, state: &mut State, reg: Reg) {
+        // values computed from the register's current value must be assigned before it changes
+        self.flush_if(state, |r, value| r != reg && value.reads.contains(&reg));
+
+        if state.flags.as_ref().is_some_and(|flags| flags.reads(reg)) {
+            self.capture_flags(state);
+        }
+
+        if let Some(pos) = state.pending.iter().position(|(r, _)| *r == reg) {
+            let (_, value) = state.pending.remove(pos);
+            if value.effect {
+                state.stmts.push(Stmt::Expr(value.text));
+            }
+        }
+    }
+
+    /// Give a register a new value.
+    fn assign(&self, state: &mut State, reg: Reg, value: Value) {
+        self.kill(state, reg);
+
+        if value.reads.contains(&reg) {
+            let stmt = Stmt::Assign(self.name(reg).to_string(), "=".to_string(), value.text);
+            state.stmts.push(stmt);
+        } else {
+            state.pending.push((reg, value));
+        }
+    }
+
+    /// Current value of a register, without duplicating any side effects.
+    fn read_reg(&self, state: &mut State, reg: Reg) -> Value {
+        match state.pending.iter().find(|(r, _)| *r == reg) {
+            Some((_, value)) if value.effect => {
+                self.flush_reg(state, reg);
+                self.reg_value(reg)
+            }
+            Some((_, value)) => value.clone(),
+            None => self.reg_value(reg),
+        }
+    }
+
+    /// Current value of a register, consuming any side effects.
+    fn take_reg(&self, state: &mut State, reg: Reg) -> Value {
+        match state.pending.iter().position(|(r, _)| *r == reg) {
+            Some(pos) => state.pending.remove(pos).1,
+            None => self.reg_value(reg),
+        }
+    }
+
+    fn address(&self, state: &mut State, inst: &Inst, text: &str) -> Address {
+        let symbol = |name: &str| Address {
+            value: Value::new(format!("&{name}")),
+            symbol: Some(name.to_string()),
+            constant: None,
+        };
+
+        if let Some(name) = text.strip_prefix('<').and_then(|s| s.strip_suffix('>')) {
+            return symbol(name);
+        }
+
+        let words: Vec<&str> = text.split(' ').collect();
+        let constant = match words[..] {
+            ["rip"] => Some(inst.end),
+            ["rip", op @ ("+" | "-"), disp] => {
+                let disp = PhysAddr::from_str_radix(disp.trim_start_matches("0x"), 16).ok();
+                disp.and_then(|disp| match op {
+                    "+" => inst.end.checked_add(disp),
+                    _ => inst.end.checked_sub(disp),
+                })
+            }
+            [word] => {
+                word.strip_prefix("0x").and_then(|hex| PhysAddr::from_str_radix(hex, 16).ok())
+            }
+            _ => None,
+        };
+
+        if let Some(addr) = constant {
+            if let Some(sym) = self.index.get_sym_by_addr(addr) {
+                return symbol(sym.as_str());
+            }
+
+            return Address {
+                value: Value::new(format!("{addr:#x}")),
+                symbol: None,
+                constant: Some(addr),
+            };
+        }
+
+        let mut value = Value::new(String::new());
+        for word in words {
+            let term = match self.register(word) {
+                Some((reg, false)) => self.read_reg(state, reg),
+                _ => Value::new(word),
+            };
+
+            if !value.text.is_empty() {
+                value.text.push(' ');
+            }
+
+            // terms of an address are joined by operators, so only nested expressions need
+            // parentheses
+            value.text += &term.atom();
+            value.depend_on(&term);
+        }
+
+        Address {
+            value,
+            symbol: None,
+            constant: None,
+        }
+    }
+
+    fn operand(&self, state: &mut State, inst: &Inst, text: &str) -> Operand {
+        if let Some((reg, partial)) = self.register(text) {
+            return Operand::Reg {
+                reg,
+                name: text.to_string(),
+                partial,
+            };
+        }
+
+        if let Some(open) = text.find('[') {
+            let ty = match text[..open].trim() {
+                "byte" => "u8",
+                "word" => "u16",
+                "dword" => "u32",
+                "xmmword" => "u128",
+                "ymmword" => "u256",
+                "zmmword" => "u512",
+                "tword" => "f80",
+                _ => "u64",
+            };
+
+            let inner = text[open + 1..].trim_end_matches(']');
+            return Operand::Mem {
+                ty,
+                addr: self.address(state, inst, inner),
+            };
+        }
+
+        match text.strip_prefix('<').and_then(|s| s.strip_suffix('>')) {
+            Some(symbol) => Operand::Other(Value::new(symbol)),
+            None => Operand::Other(Value::new(text)),
+        }
+    }
+
+    fn deref(&self, ty: &str, addr: &Address) -> Value {
+        let text = match (&addr.symbol, addr.constant) {
+            (Some(symbol), _) => symbol.clone(),
+            (None, Some(constant)) => format!("*({ty} *){constant:#x}"),
+            (None, None) => format!("*({ty} *){}", addr.value.atom()),
+        };
+
+        Value {
+            memory: true,
+            ..Value {
+                text,
+                ..addr.value.clone()
+            }
+        }
+    }
+
+    fn read(&self, state: &mut State, operand: &Operand) -> Value {
+        match operand {
+            Operand::Reg {
+                name,
+                partial: true,
+                reg,
+            } => {
+                self.flush_reg(state, *reg);
+                Value {
+                    reads: vec![*reg],
+                    ..Value::new(name.clone())
+                }
+            }
+            Operand::Reg { reg, .. } => self.read_reg(state, *reg),
+            Operand::Mem { ty, addr } => self.deref(ty, addr),
+            Operand::Other(value) => value.clone(),
+        }
+    }
+
+    /// Address of a memory operand, as computed by `lea`.
+    fn address_of(&self, operand: &Operand) -> Value {
+        match operand {
+            Operand::Mem { addr, .. } => {
+                match addr.constant.and_then(|addr| self.string_at(addr)) {
+                    Some(string) => Value::new(string),
+                    None => addr.value.clone(),
+                }
+            }
+            Operand::Reg { name, .. } => Value::new(name.clone()),
+            Operand::Other(value) => value.clone(),
+        }
+    }
+
+    /// Assign to an operand, with an operator such as `=` or `+=`.
+    fn write(&self, state: &mut State, dst: &Operand, op: &str, value: Value) {
+        let dst = match dst {
+            Operand::Reg {
+                reg,
+                partial: false,
+                ..
+            } if op == "=" => {
+                return self.assign(state, *reg, value);
+            }
+            Operand::Reg { reg, name, partial } => {
+                self.flush_reg(state, *reg);
+                self.kill(state, *reg);
+                match partial {
+                    true => name.clone(),
+                    false => self.name(*reg).to_string(),
+                }
+            }
+            Operand::Mem { ty, addr } => self.deref(ty, addr).text,
+            Operand::Other(value) => value.text.clone(),
+        };
+
+        state.stmts.push(Stmt::Assign(dst, op.to_string(), value.text));
+    }
+
+    /// Apply a binary operator to a destination operand.
+    fn arith(&self, state: &mut State, dst: &Operand, op: &str, src: Value) {
+        let old = self.read(state, dst);
+        match dst {
+            Operand::Reg {
+                reg,
+                partial: false,
+                ..
+            } if !old.reads.contains(reg) => {
+                // the register already has a pending value, so build on top of it
+                let value = Value::binary(&old, op, &src);
+                self.assign(state, *reg, value.clone());
+                state.flags = Some(Flags::Result(value));
+            }
+            _ => {
+                self.write(state, dst, &format!("{op}="), src);
+                state.flags = Some(Flags::Result(old));
+            }
+        }
+    }
+
+    /// Callee and arguments of a call to an operand.
+    fn call(&self, state: &mut State, inst: &Inst, target: &str) -> Value {
+        let mut callee = match target.strip_prefix('<').and_then(|s| s.strip_suffix('>')) {
+            Some(symbol) => Value::new(symbol),
+            None => match target.strip_prefix("0x").map(|hex| PhysAddr::from_str_radix(hex, 16)) {
+                Some(Ok(addr)) => match self.index.get_sym_by_addr(addr) {
+                    Some(symbol) => Value::new(symbol.as_str()),
+                    None => Value::new(format!("sub_{addr:x}")),
+                },
+                _ => {
+                    let operand = self.operand(state, inst, target);
+                    let value = self.read(state, &operand);
+                    Value {
+                        text: format!("(*{})", value.atom()),
+                        ..value
+                    }
+                }
+            },
+        };
+
+        let mut args = Vec::new();
+        if self.is_64 {
+            // arguments up to the last argument register that was set in this block
+            let count = SYSV_ARGS
+                .iter()
+                .rposition(|arg| state.pending.iter().any(|(reg, _)| reg == arg))
+                .map_or(0, |pos| pos + 1);
+
+            for arg in SYSV_ARGS[..count].iter() {
+                let value = self.take_reg(state, *arg);
+                callee.depend_on(&value);
+                args.push(value.text);
+            }
+        }
+
+        // values of registers that don't survive the call are never read
+        let clobbered: &[Reg] = if self.is_64 { &SYSV_CLOBBERED } else { &[RAX] };
+        for reg in clobbered {
+            self.kill(state, *reg);
+        }
+
+        Value {
+            text: format!("{}({})", callee.text, args.join(", ")),
+            memory: true,
+            effect: true,
+            ..callee
+        }
+    }
+
+    fn asm(&self, state: &mut State, inst: &Inst) {
+        self.flush_if(state, |_, _| true);
+        state.flags = None;
+        state.stmts.push(Stmt::Asm(inst.text()));
+    }
+
+    fn inst(&self, state: &mut State, inst: &Inst) {
+        let mnemonic = inst.mnemonic.as_str();
+        let ops = &inst.operands;
+
+        if !inst.prefixes.is_empty() {
+            return self.asm(state, inst);
+        }
+
+        // the stack pointer is only used for the frame, which isn't shown
+        let dst = ops.first().and_then(|op| self.register(op));
+        if dst.is_some_and(|(reg, _)| reg == RSP) && !matches!(mnemonic, "cmp" | "test" | "push") {
+            return;
+        }
+
+        // stores must happen after the memory reads that come before them
+        if ops.first().is_some_and(|op| op.contains('['))
+            && !matches!(mnemonic, "cmp" | "test" | "push" | "call" | "nop" | "lea")
+        {
+            self.flush_memory(state);
+        }
+
+        let binary_op = match mnemonic {
+            "add" => Some("+"),
+            "sub" => Some("-"),
+            "and" => Some("&"),
+            "or" => Some("|"),
+            "xor" => Some("^"),
+            "imul" => Some("*"),
+            "shl" | "sal" => Some("<<"),
+            "shr" | "sar" => Some(">>"),
+            _ => None,
+        };
+
+        match (mnemonic, ops.len()) {
+            ("nop" | "endbr64" | "endbr32" | "leave" | "push" | "cdqe" | "cwde" | "pause", _) => {}
+            ("pop", 1) => {
+                if let Some((reg, false)) = dst {
+                    self.kill(state, reg);
+                }
+            }
+            ("mov" | "movabs" | "movzx" | "movsx" | "movsxd", 2) => {
+                let src = self.operand(state, inst, &ops[1]);
+                if let Operand::Reg {
+                    reg,
+                    partial: false,
+                    ..
+                } = src
+                {
+                    // setting up the frame pointer or zero extending a register
+                    if dst == Some((RBP, false)) && reg == RSP || dst == Some((reg, false)) {
+                        return;
+                    }
+                }
+
+                let mut value = self.read(state, &src);
+                if mnemonic.starts_with("movs") {
+                    let ty = match self.is_64 && ops[0].starts_with('r') {
+                        true => "i64",
+                        false => "i32",
+                    };
+                    value.text = format!("({ty}){}", value.atom());
+                }
+
+                let dst = self.operand(state, inst, &ops[0]);
+                self.write(state, &dst, "=", value);
+            }
+            ("lea", 2) => {
+                let src = self.operand(state, inst, &ops[1]);
+                let value = self.address_of(&src);
+                let dst = self.operand(state, inst, &ops[0]);
+                self.write(state, &dst, "=", value);
+            }
+            ("xor" | "sub", 2) if ops[0] == ops[1] => {
+                let dst = self.operand(state, inst, &ops[0]);
+                self.write(state, &dst, "=", Value::new("0"));
+                state.flags = Some(Flags::Result(Value::new("0")));
+            }
+            (_, 2) if binary_op.is_some() => {
+                let src = self.operand(state, inst, &ops[1]);
+                let src = self.read(state, &src);
+                let dst = self.operand(state, inst, &ops[0]);
+                self.arith(state, &dst, binary_op.unwrap(), src);
+            }
+            ("imul", 3) => {
+                let lhs = self.operand(state, inst, &ops[1]);
+                let lhs = self.read(state, &lhs);
+                let rhs = self.operand(state, inst, &ops[2]);
+                let rhs = self.read(state, &rhs);
+                let dst = self.operand(state, inst, &ops[0]);
+                self.write(state, &dst, "=", Value::binary(&lhs, "*", &rhs));
+            }
+            ("inc" | "dec", 1) => {
+                let dst = self.operand(state, inst, &ops[0]);
+                let op = if mnemonic == "inc" { "+" } else { "-" };
+                self.arith(state, &dst, op, Value::new("1"));
+            }
+            ("neg" | "not", 1) => {
+                let dst = self.operand(state, inst, &ops[0]);
+                let old = self.read(state, &dst);
+                let op = if mnemonic == "neg" { "-" } else { "~" };
+                let value = Value {
+                    text: format!("{op}{}", old.atom()),
+                    ..old
+                };
+                self.write(state, &dst, "=", value.clone());
+                state.flags = Some(Flags::Result(value));
+            }
+            ("cmp" | "test", 2) => {
+                let lhs = self.operand(state, inst, &ops[0]);
+                let lhs = self.read(state, &lhs);
+                let rhs = self.operand(state, inst, &ops[1]);
+                let rhs = self.read(state, &rhs);
+                state.flags = Some(match mnemonic {
+                    "cmp" => Flags::Compare(lhs, rhs),
+                    _ => Flags::Test(lhs, rhs),
+                });
+            }
+            ("call", 1) => {
+                let value = self.call(state, inst, &ops[0]);
+                self.flush_if(state, |_, _| true);
+                state.flags = None;
+                state.pending.push((RAX, value));
+            }
+            (_, 2) if mnemonic.starts_with("cmov") => {
+                let cond = condition(state.read_flags(), &mnemonic[4..]);
+                let src = self.operand(state, inst, &ops[1]);
+                let src = self.read(state, &src);
+                let dst = self.operand(state, inst, &ops[0]);
+                let old = self.read(state, &dst);
+
+                let mut value = cond.value();
+                value.text = format!("{} ? {} : {}", value.text, src.atom(), old.atom());
+                value.depend_on(&src);
+                value.depend_on(&old);
+                self.write(state, &dst, "=", value);
+            }
+            (_, 1) if mnemonic.starts_with("set") => {
+                let cond = condition(state.read_flags(), &mnemonic[3..]);
+                let dst = self.operand(state, inst, &ops[0]);
+                self.write(state, &dst, "=", cond.value());
+            }
+            _ => self.asm(state, inst),
+        }
+    }
+
+    /// Registers an instruction reads before writing any, and the registers it overwrites.
+    ///
+    /// Calls are assumed to read all argument registers if `call_args` is set.
+    fn effects(&self, inst: &Inst, call_args: bool) -> (Vec<Reg>, Vec<Reg>) {
+        let mnemonic = inst.mnemonic.as_str();
+        let ops = &inst.operands;
+        let mut reads: Vec<Reg> = ops.iter().flat_map(|op| self.registers_in(op)).collect();
+        let mut writes = Vec::new();
+
+        match mnemonic {
+            "call" => {
+                if self.is_64 {
+                    if call_args {
+                        reads.extend(SYSV_ARGS);
+                    }
+                    writes.extend(SYSV_CLOBBERED);
+                } else {
+                    writes.push(RAX);
+                }
+                return (reads, writes);
+            }
+            "ret" => return (vec![RAX], writes),
+            "nop" | "endbr64" | "endbr32" | "leave" | "cdqe" | "cwde" | "pause" => {
+                return (Vec::new(), writes)
+            }
+            _ => {}
+        }
+
+        let zeroed = matches!(mnemonic, "xor" | "sub") && ops.len() == 2 && ops[0] == ops[1];
+        let overwritten = matches!(
+            mnemonic,
+            "mov" | "movabs" | "movzx" | "movsx" | "movsxd" | "lea" | "pop"
+        );
+        let modified = matches!(
+            mnemonic,
+            "add"
+                | "sub"
+                | "and"
+                | "or"
+                | "xor"
+                | "imul"
+                | "shl"
+                | "sal"
+                | "shr"
+                | "sar"
+                | "inc"
+                | "dec"
+                | "neg"
+                | "not"
+        ) || mnemonic.starts_with("cmov");
+
+        if let Some((reg, false)) = ops.first().and_then(|op| self.register(op)) {
+            if zeroed {
+                // zeroing a register doesn't depend on its value
+                reads.clear();
+                writes.push(reg);
+            } else if overwritten {
+                // the destination is only written to, all other operands are read
+                reads = ops[1..].iter().flat_map(|op| self.registers_in(op)).collect();
+                writes.push(reg);
+            } else if modified {
+                writes.push(reg);
+            }
+        }
+
+        (reads, writes)
+    }
+
+    /// Registers each block reads before assigning them and the registers each block assigns.
+    fn uses_and_defs(&self, cfg: &Cfg, call_args: bool) -> Vec<(BTreeSet<Reg>, BTreeSet<Reg>)> {
+        cfg.blocks
+            .iter()
+            .map(|block| {
+                let mut uses = BTreeSet::new();
+                let mut defs = BTreeSet::new();
+                for inst in block.insts.iter() {
+                    let (reads, writes) = self.effects(inst, call_args);
+                    uses.extend(reads.into_iter().filter(|reg| !defs.contains(reg)));
+                    defs.extend(writes);
+                }
+
+                // tail calls take arguments
+                let tail_call = matches!(block.exit, Exit::Jump(Target::External(..)));
+                if self.is_64 && call_args && tail_call {
+                    uses.extend(SYSV_ARGS.into_iter().filter(|reg| !defs.contains(reg)));
+                }
+
+                (uses, defs)
+            })
+            .collect()
+    }
+
+    /// Registers that are read before being overwritten, at the start and at the end of each block.
+    fn liveness(
+        &self,
+        cfg: &Cfg,
+        uses_and_defs: &[(BTreeSet<Reg>, BTreeSet<Reg>)],
+    ) -> (Vec<BTreeSet<Reg>>, Vec<BTreeSet<Reg>>) {
+        let successors: Vec<Vec<usize>> = cfg
+            .blocks
+            .iter()
+            .map(|block| {
+                let successors = block.successors().into_iter();
+                successors.filter_map(|addr| cfg.block_by_addr(addr)).collect()
+            })
+            .collect();
+
+        let mut live_in = vec![BTreeSet::new(); cfg.blocks.len()];
+        let mut live_out = vec![BTreeSet::new(); cfg.blocks.len()];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for idx in (0..cfg.blocks.len()).rev() {
+                let out: BTreeSet<Reg> = successors[idx]
+                    .iter()
+                    .flat_map(|&succ| live_in[succ].iter().copied())
+                    .collect();
+
+                let (uses, defs) = &uses_and_defs[idx];
+                let mut live: BTreeSet<Reg> = out.difference(defs).copied().collect();
+                live.extend(uses.iter().copied());
+
+                if live != live_in[idx] || out != live_out[idx] {
+                    live_in[idx] = live;
+                    live_out[idx] = out;
+                    changed = true;
+                }
+            }
+        }
+
+        (live_in, live_out)
+    }
+
+    fn block(
+        &self,
+        cfg: &Cfg,
+        idx: usize,
+        live_out: &BTreeSet<Reg>,
+        flags: Option<Flags>,
+        returns_value: bool,
+    ) -> Lifted {
+        let block = &cfg.blocks[idx];
+        let mut state = State {
+            flags,
+            ..State::default()
+        };
+
+        let body = match block.exit {
+            Exit::Jump(..) | Exit::Branch { .. } | Exit::Return => {
+                &block.insts[..block.insts.len() - 1]
+            }
+            Exit::Fallthrough(..) | Exit::Halt => &block.insts[..],
+        };
+
+        for inst in body {
+            self.inst(&mut state, inst);
+        }
+
+        let last = block.insts.last().unwrap();
+        let mut tail = Vec::new();
+        let mut flush_all = false;
+        let edge = match &block.exit {
+            Exit::Fallthrough(next) => Edge::Next(*next),
+            Exit::Halt => Edge::End,
+            Exit::Return => {
+                let value = state.pending.iter().any(|(reg, _)| *reg == RAX) || returns_value;
+                let value = value.then(|| self.take_reg(&mut state, RAX).text);
+                tail.push(Stmt::Return(value));
+                Edge::End
+            }
+            Exit::Jump(Target::Local(target)) => Edge::Jump(*target),
+            Exit::Jump(Target::External(..)) => {
+                let call = self.call(&mut state, last, &last.operands[0]);
+                tail.push(Stmt::Return(Some(call.text)));
+                Edge::End
+            }
+            Exit::Jump(Target::Indirect(operand)) => {
+                let operand = self.operand(&mut state, last, operand);
+                let value = self.read(&mut state, &operand);
+                tail.push(Stmt::IndirectJump(value.atom()));
+                flush_all = true;
+                Edge::End
+            }
+            Exit::Branch { target, next } => {
+                let cond = match last.mnemonic.as_str() {
+                    "jrcxz" | "jecxz" | "jcxz" => {
+                        let rcx = self.read_reg(&mut state, Reg(1));
+                        Cond {
+                            lhs: rcx,
+                            op: "==",
+                            rhs: Value::new("0"),
+                        }
+                    }
+                    "loop" | "loope" | "loopz" | "loopne" | "loopnz" => {
+                        let rcx = Operand::Reg {
+                            reg: Reg(1),
+                            name: self.name(Reg(1)).to_string(),
+                            partial: false,
+                        };
+                        self.arith(&mut state, &rcx, "-", Value::new("1"));
+                        let rcx = self.read_reg(&mut state, Reg(1));
+                        Cond {
+                            lhs: rcx,
+                            op: "!=",
+                            rhs: Value::new("0"),
+                        }
+                    }
+                    mnemonic => condition(state.read_flags(), &mnemonic[1..]),
+                };
+
+                match target {
+                    Target::Local(target) => Edge::Branch(cond, *target),
+                    Target::External(..) => {
+                        let call = self.call(&mut state, last, &last.operands[0]);
+                        let then = vec![Stmt::Return(Some(call.text))];
+                        tail.push(Stmt::If {
+                            cond,
+                            then,
+                            otherwise: Vec::new(),
+                        });
+                        Edge::Next(*next)
+                    }
+                    Target::Indirect(operand) => {
+                        let operand = self.operand(&mut state, last, operand);
+                        let value = self.read(&mut state, &operand);
+                        let then = vec![Stmt::IndirectJump(value.atom())];
+                        tail.push(Stmt::If {
+                            cond,
+                            then,
+                            otherwise: Vec::new(),
+                        });
+                        flush_all = true;
+                        Edge::Next(*next)
+                    }
+                }
+            }
+        };
+
+        // values that are used after the block must be assigned, the rest only for side effects
+        for (reg, value) in std::mem::take(&mut state.pending) {
+            if flush_all || live_out.contains(&reg) {
+                let stmt = Stmt::Assign(self.name(reg).to_string(), "=".to_string(), value.text);
+                state.stmts.push(stmt);
+            } else if value.effect {
+                state.stmts.push(Stmt::Expr(value.text));
+            }
+        }
+
+        // flags that were stored but never read are only of use to the blocks that follow
+        if let (Edge::End, Some(captured)) = (&edge, state.captured) {
+            state.stmts.remove(captured);
+        }
+
+        state.stmts.extend(tail);
+        Lifted {
+            start: block.start,
+            end: block.end,
+            stmts: state.stmts,
+            flags: state.flags,
+            edge,
+        }
+    }
+
+    /// Structure the lifted blocks of a function and render them.
+    fn function(&self, cfg: &Cfg) -> PseudoCode {
+        let uses_and_defs = self.uses_and_defs(cfg, true);
+        let (_, live_out) = self.liveness(cfg, &uses_and_defs);
+
+        // assume the function returns a value if the return register is ever assigned
+        let returns_value = uses_and_defs.iter().any(|(_, defs)| defs.contains(&RAX));
+
+        let mut predecessors = vec![Vec::new(); cfg.blocks.len()];
+        for (idx, block) in cfg.blocks.iter().enumerate() {
+            for succ in block.successors() {
+                if let Some(succ) = cfg.block_by_addr(succ) {
+                    predecessors[succ].push(idx);
+                }
+            }
+        }
+
+        let mut blocks: Vec<Lifted> = Vec::with_capacity(cfg.blocks.len());
+        for idx in 0..cfg.blocks.len() {
+            // flags carry over from a block that's the only way of getting here
+            let flags = match predecessors[idx][..] {
+                [pred] if pred < idx => blocks[pred].flags.clone(),
+                _ => None,
+            };
+
+            blocks.push(self.block(cfg, idx, &live_out[idx], flags, returns_value));
+        }
+
+        let mut structurer = Structurer {
+            blocks: &blocks,
+            labels: BTreeSet::new(),
+        };
+
+        let stmts = structurer.range(0..blocks.len(), false, None, None, None);
+
+        // arguments are the argument registers that are read before being assigned, other than by
+        // passing them on to calls
+        let (live_in, _) = self.liveness(cfg, &self.uses_and_defs(cfg, false));
+        let params = match self.is_64 {
+            true => {
+                let count = SYSV_ARGS.iter().rposition(|reg| live_in[0].contains(reg));
+                let count = count.map_or(0, |pos| pos + 1);
+                SYSV_ARGS[..count].iter().map(|reg| self.name(*reg)).collect()
+            }
+            false => Vec::new(),
+        };
+
+        let mut lines = vec![format!("{}({}) {{", cfg.name, params.join(", "))];
+        render(&stmts, 1, &structurer.labels, &mut lines);
+        lines.push("}".to_string());
+
+        PseudoCode {
+            addr: cfg.addr,
+            lines,
+        }
+    }
+}
+
+/// Innermost loop surrounding a statement.
+#[derive(Debug, Clone, Copy)]
+struct LoopCtx {
+    header: PhysAddr,
+    /// Where execution continues after the loop.
+    exit: Option<PhysAddr>,
+    /// Whether the loop is a `while (true)`, so `continue` goes back to the header.
+    infinite: bool,
+}
+
+/// Recovers structured control flow from lifted blocks.
+struct Structurer<'a> {
+    blocks: &'a [Lifted],
+    /// Labels that are the target of a `goto`.
+    labels: BTreeSet<PhysAddr>,
+}
+
+impl Structurer<'_> {
+    fn index(&self, addr: PhysAddr) -> Option<usize> {
+        self.blocks.binary_search_by_key(&addr, |block| block.start).ok()
+    }
+
+    fn jump(&mut self, target: PhysAddr, ctx: Option<LoopCtx>) -> Stmt {
+        match ctx {
+            Some(ctx) if ctx.exit == Some(target) => Stmt::Break,
+            Some(ctx) if ctx.infinite && ctx.header == target => Stmt::Continue,
+            _ => {
+                self.labels.insert(target);
+                Stmt::Goto(target)
+            }
+        }
+    }
+
+    /// Structure a range of blocks.
+    ///
+    /// `open_end` tells whether execution naturally continues at the block following the range,
+    /// `header` is a loop header that's being structured and `latch` a block whose exit is
+    /// replaced by the surrounding statement.
+    fn range(
+        &mut self,
+        range: Range<usize>,
+        open_end: bool,
+        header: Option<usize>,
+        latch: Option<usize>,
+        ctx: Option<LoopCtx>,
+    ) -> Vec<Stmt> {
+        let mut stmts = Vec::new();
+        let mut idx = range.start;
+
+        // whether the block at an index can be reached by falling out of the range
+        let reaches = |target: usize, from: usize| {
+            target > from && (target < range.end || (target == range.end && open_end))
+        };
+
+        while idx < range.end {
+            let block = &self.blocks[idx];
+
+            if header != Some(idx) {
+                // the last block in the range that jumps back to this one closes a loop
+                let back_edge = (idx..range.end)
+                    .rev()
+                    .find(|&j| self.blocks[j].edge.target() == Some(block.start));
+
+                if let Some(back_edge) = back_edge {
+                    let cond = match &self.blocks[back_edge].edge {
+                        Edge::Branch(cond, _) => Some(cond.clone()),
+                        _ => None,
+                    };
+
+                    let exit =
+                        self.blocks.get(back_edge + 1).filter(|_| reaches(back_edge + 1, idx));
+                    let ctx = LoopCtx {
+                        header: block.start,
+                        exit: exit.map(|block| block.start),
+                        infinite: cond.is_none(),
+                    };
+
+                    let body = self.range(
+                        idx..back_edge + 1,
+                        false,
+                        Some(idx),
+                        Some(back_edge),
+                        Some(ctx),
+                    );
+                    stmts.push(Stmt::Loop { cond, body });
+                    idx = back_edge + 1;
+                    continue;
+                }
+            }
+
+            stmts.push(Stmt::Label(block.start));
+            stmts.extend(block.stmts.iter().cloned());
+
+            if latch == Some(idx) {
+                idx += 1;
+                continue;
+            }
+
+            // whether execution naturally continues at an address after this block
+            let follows = |addr: PhysAddr| {
+                reaches(idx + 1, idx) && self.blocks.get(idx + 1).is_some_and(|b| b.start == addr)
+            };
+
+            match &block.edge {
+                Edge::Next(target) | Edge::Jump(target) => {
+                    if !follows(*target) {
+                        let stmt = self.jump(*target, ctx);
+                        stmts.push(stmt);
+                    }
+                }
+                Edge::End => {}
+                Edge::Branch(cond, target) => {
+                    match self.index(*target) {
+                        // both paths lead to the next block
+                        Some(t) if t == idx + 1 && reaches(t, idx) => {}
+                        Some(t) if reaches(t, idx) => {
+                            let last = t - 1;
+
+                            // the block before the target jumping over an else statement
+                            let join = match &self.blocks[last].edge {
+                                Edge::Jump(join) if last > idx => {
+                                    self.index(*join).filter(|&join| join > t && reaches(join, idx))
+                                }
+                                _ => None,
+                            };
+
+                            let cond = cond.negate();
+                            let stmt = match join {
+                                Some(join) => {
+                                    let then = self.range(idx + 1..t, true, None, Some(last), ctx);
+                                    let otherwise = self.range(t..join, true, None, None, ctx);
+                                    idx = join;
+                                    Stmt::If {
+                                        cond,
+                                        then,
+                                        otherwise,
+                                    }
+                                }
+                                None => {
+                                    let then = self.range(idx + 1..t, true, None, None, ctx);
+                                    idx = t;
+                                    Stmt::If {
+                                        cond,
+                                        then,
+                                        otherwise: Vec::new(),
+                                    }
+                                }
+                            };
+
+                            stmts.push(stmt);
+                            continue;
+                        }
+                        _ => {
+                            let then = vec![self.jump(*target, ctx)];
+                            stmts.push(Stmt::If {
+                                cond: cond.clone(),
+                                then,
+                                otherwise: Vec::new(),
+                            });
+                        }
+                    }
+
+                    if !follows(block.end) {
+                        let stmt = self.jump(block.end, ctx);
+                        stmts.push(stmt);
+                    }
+                }
+            }
+
+            idx += 1;
+        }
+
+        stmts
+    }
+}
+
+/// Remove labels that aren't jumped to.
+fn visible<'a>(stmts: &'a [Stmt], labels: &BTreeSet<PhysAddr>) -> Vec<&'a Stmt> {
+    stmts
+        .iter()
+        .filter(|stmt| !matches!(stmt, Stmt::Label(addr) if !labels.contains(addr)))
+        .collect()
+}
+
+fn render(stmts: &[Stmt], depth: usize, labels: &BTreeSet<PhysAddr>, lines: &mut Vec<String>) {
+    let indent = " ".repeat(depth * INDENT);
+
+    for stmt in visible(stmts, labels) {
+        match stmt {
+            Stmt::Label(addr) => {
+                let indent = " ".repeat(depth.saturating_sub(1) * INDENT);
+                lines.push(format!("{indent}label_{addr:x}:"));
+            }
+            Stmt::Assign(dst, op, value) => lines.push(format!("{indent}{dst} {op} {value};")),
+            Stmt::Expr(expr) => lines.push(format!("{indent}{expr};")),
+            Stmt::Asm(text) => lines.push(format!("{indent}asm(\"{text}\");")),
+            Stmt::Return(Some(value)) => lines.push(format!("{indent}return {value};")),
+            Stmt::Return(None) => lines.push(format!("{indent}return;")),
+            Stmt::Goto(addr) => lines.push(format!("{indent}goto label_{addr:x};")),
+            Stmt::IndirectJump(target) => lines.push(format!("{indent}goto *{target};")),
+            Stmt::Break => lines.push(format!("{indent}break;")),
+            Stmt::Continue => lines.push(format!("{indent}continue;")),
+            Stmt::If {
+                cond,
+                then,
+                otherwise,
+            } => {
+                lines.push(format!("{indent}if ({}) {{", cond.text()));
+                render(then, depth + 1, labels, lines);
+
+                let mut otherwise = otherwise;
+                loop {
+                    match &visible(otherwise, labels)[..] {
+                        [] => break,
+                        // collapse nested if statements into an else if chain
+                        [Stmt::If {
+                            cond,
+                            then,
+                            otherwise: nested,
+                        }] => {
+                            lines.push(format!("{indent}}} else if ({}) {{", cond.text()));
+                            render(then, depth + 1, labels, lines);
+                            otherwise = nested;
+                        }
+                        _ => {
+                            lines.push(format!("{indent}}} else {{"));
+                            render(otherwise, depth + 1, labels, lines);
+                            break;
+                        }
+                    }
+                }
+
+                lines.push(format!("{indent}}}"));
+            }
+            Stmt::Loop {
+                cond: Some(cond),
+                body,
+            } => {
+                lines.push(format!("{indent}do {{"));
+                render(body, depth + 1, labels, lines);
+                lines.push(format!("{indent}}} while ({});", cond.text()));
+            }
+            Stmt::Loop { cond: None, body } => {
+                lines.push(format!("{indent}while (true) {{"));
+                render(body, depth + 1, labels, lines);
+                lines.push(format!("{indent}}}"));
+            }
+        }
+    }
+}
+
+impl Processor {
+    /// Translate the function containing an address into pseudo-C.
+    pub fn lift(&self, addr: PhysAddr) -> Result<PseudoCode, Error> {
+        let cfg = self.cfg(addr)?;
+        let lifter = Lifter {
+            sections: self.sections().collect(),
+            index: &self.index,
+            is_64: self.arch == Architecture::X86_64,
+        };
+
+        Ok(lifter.function(&cfg))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cfg::BasicBlock;
+
+    /// Block of instructions that are a byte each.
+    fn block(start: PhysAddr, insts: &[&str], exit: Exit) -> BasicBlock {
+        let end = start + insts.len();
+        let insts = insts
+            .iter()
+            .enumerate()
+            .map(|(idx, text)| Inst::new(start + idx, start + idx + 1, text))
+            .collect();
+
+        BasicBlock {
+            start,
+            end,
+            insts,
+            exit,
+        }
+    }
+
+    fn lift(blocks: Vec<BasicBlock>) -> Vec<String> {
+        let cfg = Cfg {
+            addr: blocks[0].start,
+            name: "f".to_string(),
+            blocks,
+            unreachable: Vec::new(),
+        };
+
+        let index = Index::default();
+        let lifter = Lifter {
+            sections: Vec::new(),
+            index: &index,
+            is_64: true,
+        };
+
+        lifter.function(&cfg).lines
+    }
+
+    #[test]
+    fn propagation() {
+        let lines = lift(vec![block(
+            0x1000,
+            &["mov eax, edi", "add eax, esi", "shl eax, 2", "ret"],
+            Exit::Return,
+        )]);
+
+        let expected = ["f(rdi, rsi) {", "    return (rdi + rsi) << 2;", "}"];
+        assert_eq!(lines, expected);
+    }
+
+    #[test]
+    fn diamond() {
+        let lines = lift(vec![
+            block(
+                0x1000,
+                &["cmp edi, esi", "jge 0x1010"],
+                Exit::Branch {
+                    target: Target::Local(0x1010),
+                    next: 0x1002,
+                },
+            ),
+            block(
+                0x1002,
+                &["mov eax, 1", "jmp 0x1020"],
+                Exit::Jump(Target::Local(0x1020)),
+            ),
+            block(0x1010, &["mov eax, 2"], Exit::Fallthrough(0x1020)),
+            block(0x1020, &["ret"], Exit::Return),
+        ]);
+
+        let expected = [
+            "f(rdi, rsi) {",
+            "    if (rdi < rsi) {",
+            "        rax = 1;",
+            "    } else {",
+            "        rax = 2;",
+            "    }",
+            "    return rax;",
+            "}",
+        ];
+        assert_eq!(lines, expected);
+    }
+
+    #[test]
+    fn loops() {
+        let lines = lift(vec![
+            block(0x1000, &["xor eax, eax"], Exit::Fallthrough(0x1001)),
+            block(
+                0x1001,
+                &["add eax, edi", "dec edi", "jne 0x1001"],
+                Exit::Branch {
+                    target: Target::Local(0x1001),
+                    next: 0x1004,
+                },
+            ),
+            block(0x1004, &["ret"], Exit::Return),
+        ]);
+
+        let expected = [
+            "f(rdi) {",
+            "    rax = 0;",
+            "    do {",
+            "        rax += rdi;",
+            "        rdi -= 1;",
+            "    } while (rdi != 0);",
+            "    return rax;",
+            "}",
+        ];
+        assert_eq!(lines, expected);
+    }
+}
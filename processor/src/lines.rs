@@ -0,0 +1,668 @@
+//! Line oriented view of the listing.
+//!
+//! Every front-end (the GUI listing, exporters, third-party renderers) should render from
+//! [`Processor::lines`] or [`Block::lines`] instead of interpreting [`BlockContent`] themselves.
+//!
+//! Each span of a line belongs to a [`Column`], [`Line::layout`] arranges these in the order
+//! configured by the user.
+
+use crate::{Block, BlockContent, Processor};
+use config::settings::SETTINGS;
+use config::{Column, CONFIG};
+use processor_shared::{AddressMode, PhysAddr, Section, SectionKind, Segment};
+use std::ops::Range;
+use std::sync::Arc;
+use tokenizing::{Color32, Token};
+
+/// What a span of text in the listing represents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Kind {
+    /// Address column of a line.
+    Address,
+    /// Offset of an instruction into its function.
+    Offset,
+    /// Encoded bytes of an instruction.
+    Bytes,
+    /// Name of a symbol, including the surrounding brackets.
+    Label,
+    /// Start or end marker of a section.
+    Section,
+    /// Decoded instruction.
+    Instruction,
+    /// Instruction or data that failed to decode.
+    Error,
+    /// Strings, pointers, raw bytes and fields of datastructures.
+    Data,
+    /// Whitespace and punctuation.
+    Delimiter,
+    /// Comment attached by the user.
+    Comment,
+    /// Calling convention, stack frame and other facts below the name of a function.
+    Header,
+}
+
+#[derive(Debug, Clone)]
+pub struct Span {
+    pub kind: Kind,
+    pub column: Column,
+    pub token: Token,
+}
+
+/// A single line of the listing.
+#[derive(Debug, Clone)]
+pub struct Line {
+    /// Address the line belongs to.
+    pub addr: usize,
+    pub spans: Vec<Span>,
+    /// Column that spans are appended to, other than the address and bytes.
+    body: Column,
+}
+
+/// Minimum number of spaces between two columns.
+const COLUMN_SPACING: usize = 2;
+
+/// Width instructions are padded to, so that the columns following them line up.
+const INSTRUCTION_WIDTH: usize = 40;
+
+/// Number of pointers a label of data links back to, the rest are only counted.
+const MAX_XREFS_SHOWN: usize = 4;
+
+/// Padding in between columns is sliced out of this, so it doesn't have to be allocated.
+const SPACES: &str = "                                                                ";
+
+impl Line {
+    fn new(addr: usize, body: Column) -> Self {
+        Self {
+            addr,
+            spans: Vec::new(),
+            body,
+        }
+    }
+
+    fn push(&mut self, kind: Kind, text: &'static str, color: Color32) {
+        self.spans.push(Span {
+            kind,
+            column: self.body,
+            token: Token::from_str(text, color),
+        });
+    }
+
+    fn push_owned(&mut self, kind: Kind, text: String, color: Color32) {
+        self.spans.push(Span {
+            kind,
+            column: self.body,
+            token: Token::from_string(text, color),
+        });
+    }
+
+    fn extend(&mut self, kind: Kind, tokens: &[Token]) {
+        let column = self.body;
+        self.spans.extend(tokens.iter().cloned().map(|token| Span {
+            kind,
+            column,
+            token,
+        }));
+    }
+
+    /// Push a token that links to an address, such as the target of a pointer.
+    fn push_link(&mut self, kind: Kind, text: String, color: Color32, addr: PhysAddr) {
+        self.spans.push(Span {
+            kind,
+            column: self.body,
+            token: Token::from_string(text, color).with_target(addr),
+        });
+    }
+
+    fn address(addr: usize, text: String) -> Self {
+        let mut line = Self::new(addr, Column::Instruction);
+        line.spans.push(Span {
+            kind: Kind::Address,
+            column: Column::Address,
+            token: Token::from_string(text, CONFIG.colors.address),
+        });
+        line
+    }
+
+    /// Push a string that's likely to be repeated, see [`Token::interned`].
+    fn push_interned(&mut self, kind: Kind, text: &str, color: Color32) {
+        self.spans.push(Span {
+            kind,
+            column: self.body,
+            token: Token::interned(text, color),
+        });
+    }
+
+    /// Push the bytes of an instruction, cutting them off past the number of bytes shown.
+    fn bytes(&mut self, bytes: &Arc<str>, shown: usize) {
+        // two hex digits per byte separated by spaces, making room for the '..' when cut off
+        let token = match bytes.len() > shown * 3 - 1 {
+            true => {
+                let kept = bytes.get(..(shown - 1) * 3).unwrap_or(bytes);
+                Token::from_string(format!("{kept}.."), CONFIG.colors.bytes)
+            }
+            false => Token::from_shared(Arc::clone(bytes), CONFIG.colors.bytes),
+        };
+
+        self.spans.push(Span {
+            kind: Kind::Bytes,
+            column: Column::Bytes,
+            token,
+        });
+    }
+
+    /// Push the offset of the line into the function containing it, if there is one.
+    fn offset(&mut self, processor: &Processor) {
+        let start = match processor.function_start(self.addr) {
+            Some(start) => start,
+            None => return,
+        };
+
+        self.spans.push(Span {
+            kind: Kind::Offset,
+            column: Column::Offset,
+            token: Token::from_string(format!("+{:X}", self.addr - start), CONFIG.colors.address),
+        });
+    }
+
+    /// Attach a comment to the line.
+    pub fn comment(&mut self, comment: &str) {
+        self.spans.push(Span {
+            kind: Kind::Comment,
+            column: Column::Comment,
+            token: Token::from_string(format!("; {comment}"), CONFIG.colors.comment),
+        });
+    }
+
+    /// Tokens of the line without their kinds, in the order they were emitted.
+    pub fn tokens(&self) -> impl Iterator<Item = &Token> {
+        self.spans.iter().map(|span| &span.token)
+    }
+
+    /// Whether the line has anything to display in a column.
+    pub fn has_column(&self, column: Column) -> bool {
+        self.spans.iter().any(|span| span.column == column)
+    }
+
+    /// Arrange the spans in the given columns, hiding all other columns.
+    ///
+    /// Columns are padded to the width given by [`Processor::column_width`] such that
+    /// columns of consecutive lines line up.
+    pub fn layout(
+        &self,
+        columns: &[Column],
+        processor: &Processor,
+        mode: AddressMode,
+    ) -> Vec<Span> {
+        let mut spans = Vec::new();
+
+        for (idx, &column) in columns.iter().enumerate() {
+            let width = processor.column_width(column, mode);
+            let mut len = 0;
+            for span in self.spans.iter().filter(|span| span.column == column) {
+                len += span.token.text.chars().count();
+                spans.push(span.clone());
+            }
+
+            // variable width columns that are empty take up no space
+            if len == 0 && width == 0 {
+                continue;
+            }
+
+            if columns[idx + 1..].iter().any(|&next| self.has_column(next)) {
+                let padding = width.saturating_sub(len) + COLUMN_SPACING;
+                let token = match SPACES.get(..padding) {
+                    Some(spaces) => Token::from_str(spaces, CONFIG.colors.text),
+                    None => Token::from_string(" ".repeat(padding), CONFIG.colors.text),
+                };
+
+                spans.push(Span {
+                    kind: Kind::Delimiter,
+                    column,
+                    token,
+                });
+            }
+        }
+
+        spans
+    }
+}
+
+/// Number of characters required to display any address of a binary in each [`AddressMode`].
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct AddressWidths {
+    virt: usize,
+    relative: usize,
+    /// Width of an offset into the largest section.
+    offset: usize,
+    /// Width of the longest section name.
+    section_name: usize,
+}
+
+/// Number of hex digits needed to display a number.
+fn hex_digits(num: usize) -> usize {
+    num.checked_ilog(16).map_or(1, |digits| digits as usize + 1)
+}
+
+impl AddressWidths {
+    pub(crate) fn new(sections: &[Section], segments: &[Segment], image_base: PhysAddr) -> Self {
+        let sections = sections
+            .iter()
+            .filter(|sec| !matches!(sec.kind, SectionKind::Unloaded | SectionKind::Debug));
+
+        let mut widths = Self::default();
+        let mut max_addr = 0;
+        for section in sections {
+            max_addr = max_addr.max(section.end.saturating_sub(1));
+            widths.offset = widths.offset.max(hex_digits(section.end - section.start));
+            widths.section_name = widths.section_name.max(section.name.len());
+        }
+
+        for segment in segments {
+            max_addr = max_addr.max(segment.end.saturating_sub(1));
+        }
+
+        widths.virt = hex_digits(max_addr);
+        widths.relative = hex_digits(max_addr.saturating_sub(image_base));
+        widths
+    }
+}
+
+impl Block {
+    /// Split the block into the lines it's displayed as.
+    pub fn lines(&self, processor: &Processor, mode: AddressMode) -> Vec<Line> {
+        let mut lines = Vec::new();
+        let address = |addr| Line::address(addr, processor.format_address(addr, mode));
+
+        match &self.content {
+            BlockContent::Label { symbol, header } => {
+                lines.push(Line::new(self.addr, Column::Label));
+                let mut line = Line::new(self.addr, Column::Label);
+                line.push(Kind::Label, "<", CONFIG.colors.asm.label);
+                line.extend(Kind::Label, symbol.name());
+                line.push(Kind::Label, ">", CONFIG.colors.asm.label);
+                lines.push(line);
+
+                let mut line = Line::new(self.addr, Column::Label);
+                line.push_owned(Kind::Header, header.to_string(), CONFIG.colors.comment);
+                lines.push(line);
+            }
+            BlockContent::SectionStart { section } | BlockContent::SectionEnd { section } => {
+                let mut line = Line::new(self.addr, Column::Label);
+                let marker = match self.content {
+                    BlockContent::SectionStart { .. } => "section started",
+                    _ => "section ended",
+                };
+                line.push(Kind::Section, marker, CONFIG.colors.text);
+                line.push_owned(
+                    Kind::Section,
+                    format!(" {} ", section.name),
+                    CONFIG.colors.asm.section,
+                );
+                line.push(Kind::Delimiter, "{", CONFIG.colors.brackets);
+                if section.ident == "UNKNOWN" {
+                    line.push_interned(
+                        Kind::Section,
+                        &format!("{:?}", section.kind),
+                        CONFIG.colors.asm.component,
+                    );
+                } else {
+                    line.push(Kind::Section, section.ident, CONFIG.colors.asm.component);
+                }
+                line.push(Kind::Delimiter, "} ", CONFIG.colors.brackets);
+                let start = format!("{:x}", section.start);
+                let end = format!("{:x}", section.end);
+                line.push_owned(Kind::Address, start, CONFIG.colors.asm.segment);
+                line.push(Kind::Delimiter, "-", CONFIG.colors.delimiter);
+                line.push_owned(Kind::Address, end, CONFIG.colors.asm.segment);
+                lines.push(line);
+            }
+            BlockContent::Instruction { inst, bytes } => {
+                let mut line = address(self.addr);
+                line.offset(processor);
+                line.bytes(bytes, processor.bytes_shown());
+                line.extend(Kind::Instruction, inst);
+
+                // constants can be the immediates of an instruction
+                if !processor.crypto.is_empty() {
+                    let width = processor
+                        .instruction_by_addr(self.addr)
+                        .map_or(1, |inst| processor.instruction_width(&inst));
+                    for constant in processor.crypto_in(self.addr..self.addr + width) {
+                        line.comment(constant.name);
+                    }
+                }
+
+                if let Some(idiom) = processor.idiom_at(self.addr) {
+                    line.comment(&idiom.kind.to_string());
+                }
+
+                if let Some(syscall) = processor.syscall_at(self.addr) {
+                    line.comment(&format!("syscall: {syscall}"));
+                }
+
+                if let Some(header) = processor.loop_header(self.addr) {
+                    line.comment(&format!("loop to {header:#x}"));
+                }
+
+                let targets = processor.indirect_targets(self.addr);
+                if !targets.is_empty() {
+                    let names: Vec<String> = targets
+                        .iter()
+                        .map(|&target| match processor.index.get_sym_by_addr(target) {
+                            Some(symbol) => symbol.as_str().to_string(),
+                            None => format!("{target:#x}"),
+                        })
+                        .collect();
+                    line.comment(&format!("-> {}", names.join(", ")));
+                }
+
+                if let Some(args) = processor.call_arguments(self.addr) {
+                    line.comment(&format!("args: {}", args.join(", ")));
+                }
+
+                if let Some(access) = processor.field_access(self.addr) {
+                    line.comment(&access);
+                }
+                lines.push(line);
+            }
+            BlockContent::Error { err, bytes } => {
+                let mut line = address(self.addr);
+                line.offset(processor);
+                line.bytes(bytes, processor.bytes_shown());
+                line.push(Kind::Delimiter, "<", CONFIG.colors.brackets);
+                line.push_interned(Kind::Error, &format!("{err:?}"), CONFIG.colors.asm.invalid);
+                line.push(Kind::Delimiter, ">", CONFIG.colors.brackets);
+                lines.push(line);
+            }
+            BlockContent::CString { bytes } => {
+                let mut line = address(self.addr);
+                let lossy_string = String::from_utf8_lossy(bytes);
+                let escaped = format!("\"{}\"", lossy_string.escape_debug());
+                line.push_owned(Kind::Data, escaped, CONFIG.colors.asm.string);
+                lines.push(line);
+            }
+            BlockContent::Got { symbol, .. } => {
+                let mut line = address(self.addr);
+                line.push(Kind::Data, "<", CONFIG.colors.asm.label);
+                let name = symbol.name();
+                if name.is_empty() {
+                    line.push(Kind::Error, "unresolved", CONFIG.colors.asm.invalid);
+                } else {
+                    line.extend(Kind::Data, name);
+                }
+                line.push(Kind::Data, ">", CONFIG.colors.asm.label);
+                if let Some(api) = processor.api_prototype(self.addr) {
+                    line.comment(&api.to_string());
+                }
+                lines.push(line);
+            }
+            BlockContent::DataStructure { ident, fields } => {
+                // addr  struct Ident {
+                // addr      field: type = value
+                // addr      ...
+                // addr  }
+                let start_addr = fields[0].0;
+                let end_addr = fields[fields.len() - 1].0;
+
+                let mut line = address(start_addr);
+                line.push(Kind::Data, "struct ", CONFIG.colors.src.keyword);
+                line.push(Kind::Data, ident, CONFIG.colors.src.tipe);
+                line.push(Kind::Delimiter, " {", CONFIG.colors.delimiter);
+                lines.push(line);
+
+                for (addr, name, tipe, value) in fields {
+                    let mut line = address(*addr);
+                    line.push(Kind::Delimiter, "    ", CONFIG.colors.text);
+                    line.push(Kind::Data, name, CONFIG.colors.src.field);
+                    line.push(Kind::Delimiter, ": ", CONFIG.colors.text);
+                    line.push(Kind::Data, tipe, CONFIG.colors.src.tipe);
+                    line.push(Kind::Delimiter, " = ", CONFIG.colors.delimiter);
+                    line.push_owned(Kind::Data, value.clone(), CONFIG.colors.src.constant);
+                    lines.push(line);
+                }
+
+                let mut line = address(end_addr);
+                line.push(Kind::Delimiter, "}", CONFIG.colors.delimiter);
+                lines.push(line);
+            }
+            BlockContent::Struct { def, values } => {
+                let mut line = address(self.addr);
+                line.push(Kind::Data, "struct ", CONFIG.colors.src.keyword);
+                line.push_owned(Kind::Data, def.name.clone(), CONFIG.colors.src.tipe);
+                line.push(Kind::Delimiter, " {", CONFIG.colors.delimiter);
+                lines.push(line);
+
+                for (field, value) in def.fields.iter().zip(values) {
+                    let mut line = address(self.addr + field.offset);
+                    line.push(Kind::Delimiter, "    ", CONFIG.colors.text);
+                    line.push_owned(Kind::Data, field.name.clone(), CONFIG.colors.src.field);
+                    line.push(Kind::Delimiter, ": ", CONFIG.colors.text);
+                    line.push_owned(Kind::Data, field.ty.clone(), CONFIG.colors.src.tipe);
+                    line.push(Kind::Delimiter, " = ", CONFIG.colors.delimiter);
+                    line.push_owned(Kind::Data, value.clone(), CONFIG.colors.src.constant);
+                    lines.push(line);
+                }
+
+                let last = def.fields.last().map_or(0, |field| field.offset);
+                let mut line = address(self.addr + last);
+                line.push(Kind::Delimiter, "}", CONFIG.colors.delimiter);
+                lines.push(line);
+            }
+            BlockContent::Pointer { value, symbol, .. } => {
+                let mut line = address(self.addr);
+                let text = format!("{:#x}", value);
+                let pointer = processor.pointer_at(self.addr);
+                match pointer {
+                    Some(pointer) => {
+                        line.push_link(Kind::Data, text, CONFIG.colors.bytes, pointer.target)
+                    }
+                    None => line.push_owned(Kind::Data, text, CONFIG.colors.bytes),
+                }
+
+                if let Some(symbol) = symbol {
+                    line.push(Kind::Data, " <", CONFIG.colors.asm.label);
+                    line.extend(Kind::Data, symbol.name());
+                    line.push(Kind::Data, ">", CONFIG.colors.asm.label);
+                }
+
+                if let Some(label) = pointer.and_then(|p| processor.data_label(p.target)) {
+                    line.comment(&format!("-> {label}"));
+                }
+                lines.push(line);
+            }
+            BlockContent::DataLabel { name, xrefs } => {
+                let mut line = address(self.addr);
+                line.push(Kind::Label, "<", CONFIG.colors.asm.label);
+                line.push_owned(Kind::Label, name.clone(), CONFIG.colors.asm.label);
+                line.push(Kind::Label, ">", CONFIG.colors.asm.label);
+
+                // the first few pointers link back to where they're stored
+                line.push(Kind::Delimiter, "  ; xrefs ", CONFIG.colors.comment);
+                for (idx, &xref) in xrefs.iter().take(MAX_XREFS_SHOWN).enumerate() {
+                    if idx != 0 {
+                        line.push(Kind::Delimiter, ", ", CONFIG.colors.comment);
+                    }
+
+                    let text = format!("{xref:#x}");
+                    line.push_link(Kind::Data, text, CONFIG.colors.comment, xref);
+                }
+
+                if xrefs.len() > MAX_XREFS_SHOWN {
+                    let more = format!(" (+{} more)", xrefs.len() - MAX_XREFS_SHOWN);
+                    line.push_owned(Kind::Delimiter, more, CONFIG.colors.comment);
+                }
+                lines.push(line);
+            }
+            BlockContent::Folded {
+                symbol,
+                instructions,
+            } => {
+                let mut line = address(self.addr);
+                line.push(Kind::Label, "<", CONFIG.colors.asm.label);
+                line.extend(Kind::Label, symbol.name());
+                line.push(Kind::Label, ">", CONFIG.colors.asm.label);
+                let count = match instructions {
+                    1 => " (1 instruction)".to_string(),
+                    n => format!(" ({n} instructions)"),
+                };
+                line.push_owned(Kind::Delimiter, count, CONFIG.colors.comment);
+                lines.push(line);
+            }
+            BlockContent::Collapsed { kind, instructions } => {
+                let mut line = address(self.addr);
+                line.offset(processor);
+                let text = match instructions {
+                    1 => format!("; {kind} (1 instruction)"),
+                    n => format!("; {kind} ({n} instructions)"),
+                };
+                line.push_owned(Kind::Delimiter, text, CONFIG.colors.comment);
+                lines.push(line);
+            }
+            BlockContent::Bytes { bytes } => {
+                let mut off = 0;
+                for chunk in bytes.chunks(32) {
+                    let mut line = address(self.addr + off);
+                    let s = processor_shared::encode_hex_bytes_truncated(chunk, usize::MAX, false);
+                    line.push_owned(Kind::Data, s, CONFIG.colors.bytes);
+
+                    let addr = self.addr + off;
+                    for constant in processor.crypto_in(addr..addr + chunk.len()) {
+                        line.comment(constant.name);
+                    }
+                    lines.push(line);
+                    off += chunk.len();
+                }
+            }
+        }
+
+        lines
+    }
+}
+
+impl Processor {
+    /// All lines of the listing for blocks starting within an address range.
+    pub fn lines(&self, range: Range<usize>, mode: AddressMode) -> Vec<Line> {
+        self.blocks(range).iter().flat_map(|block| block.lines(self, mode)).collect()
+    }
+
+    /// All blocks of the listing starting within an address range.
+    pub fn blocks(&self, range: Range<usize>) -> Vec<Block> {
+        let boundaries = self.boundaries_in(range);
+        boundaries.into_iter().flat_map(|addr| self.parse_blocks(addr)).collect()
+    }
+
+    /// Blocks of the listing starting within an address range, unless there are more than
+    /// `limit` of them. Blocks past the limit aren't parsed.
+    pub fn blocks_limited(&self, range: Range<usize>, limit: usize) -> Option<Vec<Block>> {
+        let boundaries = self.boundaries_in(range);
+        let blocks = boundaries.into_iter().flat_map(|addr| self.parse_blocks(addr));
+        limited(blocks, limit)
+    }
+
+    /// Boundaries of the blocks starting within an address range, in order.
+    fn boundaries_in(&self, range: Range<usize>) -> Vec<PhysAddr> {
+        let mut boundaries = Vec::new();
+        for section in self.sections() {
+            if section.start < range.end && range.start <= section.end {
+                boundaries.extend(self.compute_section_boundaries(section));
+            }
+        }
+
+        boundaries.sort_unstable();
+        boundaries.dedup();
+        boundaries.retain(|addr| range.contains(addr));
+        boundaries
+    }
+
+    /// Format an address the way it's displayed in the address column of the listing.
+    ///
+    /// Addresses are padded to the widest address of the binary, so that they all align.
+    pub fn format_address(&self, addr: PhysAddr, mode: AddressMode) -> String {
+        let widths = &self.address_widths;
+        match mode {
+            AddressMode::Virtual => format!("{addr:0>width$X}", width = widths.virt),
+            AddressMode::Relative => {
+                let rva = addr.saturating_sub(self.image_base);
+                format!("{rva:0>width$X}", width = widths.relative)
+            }
+            AddressMode::Section => match self.section_by_addr(addr) {
+                Some(section) => format!(
+                    "{:>name_width$}+{:0>width$X}",
+                    section.name,
+                    addr - section.start,
+                    name_width = widths.section_name,
+                    width = widths.offset
+                ),
+                None => format!("{addr:>width$X}", width = self.address_width(mode)),
+            },
+        }
+    }
+
+    /// Number of characters of an address formatted using [`Processor::format_address`].
+    pub fn address_width(&self, mode: AddressMode) -> usize {
+        let widths = &self.address_widths;
+        match mode {
+            AddressMode::Virtual => widths.virt,
+            AddressMode::Relative => widths.relative,
+            AddressMode::Section => widths.section_name + 1 + widths.offset,
+        }
+    }
+
+    /// Number of bytes shown per instruction in the bytes column, see [`Settings::max_bytes`].
+    ///
+    /// [`Settings::max_bytes`]: config::settings::Settings::max_bytes
+    pub fn bytes_shown(&self) -> usize {
+        match SETTINGS.read().unwrap().max_bytes {
+            // at least one byte besides the '..' of bytes that are cut off
+            Some(max) => max.max(2).min(self.max_instruction_width),
+            None => self.max_instruction_width,
+        }
+    }
+
+    /// Number of characters a column is padded to when it's followed by another column.
+    ///
+    /// Columns with a width of zero vary in width and aren't aligned.
+    pub fn column_width(&self, column: Column, mode: AddressMode) -> usize {
+        match column {
+            Column::Address => self.address_width(mode),
+            // a plus followed by an offset into the largest section
+            Column::Offset => 1 + self.address_widths.offset,
+            // two hex digits per byte separated by spaces
+            Column::Bytes => self.bytes_shown() * 3 - 1,
+            Column::Instruction => INSTRUCTION_WIDTH,
+            Column::Label | Column::Comment => 0,
+        }
+    }
+}
+
+/// Items up to a limit, or `None` if there are more, which stops at the first item past it.
+fn limited<T>(items: impl Iterator<Item = T>, limit: usize) -> Option<Vec<T>> {
+    let mut taken = Vec::new();
+    for item in items {
+        if taken.len() == limit {
+            return None;
+        }
+
+        taken.push(item);
+    }
+
+    Some(taken)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits() {
+        assert_eq!(limited(0..3, 3), Some(vec![0, 1, 2]));
+        assert_eq!(limited(0..4, 3), None);
+        assert_eq!(limited(std::iter::empty::<u8>(), 0), Some(Vec::new()));
+
+        // only the item that's past the limit is taken, as there could be endlessly many
+        let mut taken = 0;
+        let items = (0..).inspect(|_| taken += 1);
+        assert_eq!(limited(items, 5), None);
+        assert_eq!(taken, 6);
+    }
+}
@@ -0,0 +1,44 @@
+//! Disassembly of the CIL method bodies of .NET assemblies, which aren't decoded as native code.
+
+use crate::Processor;
+use binformat::dotnet;
+use config::CONFIG;
+use decoder::{Decodable, Decoded};
+use processor_shared::PhysAddr;
+use tokenizing::{Token, TokenStream};
+
+impl Processor {
+    /// Instructions of a managed method along with their address, naming the types, methods,
+    /// fields and strings they refer to.
+    pub fn managed_instructions(&self, method: &dotnet::Method) -> Vec<(PhysAddr, Vec<Token>)> {
+        let (section, metadata) = match (self.section_by_addr(method.addr), &self.dotnet) {
+            (Some(section), Some(metadata)) => (section, metadata),
+            _ => return Vec::new(),
+        };
+
+        let bytes = self.patched_bytes(section, method.addr, method.size);
+        let mut reader = decoder::Reader::new(&bytes);
+        let mut addr = method.addr;
+        let mut lines = Vec::new();
+
+        while addr < method.addr + bytes.len() {
+            let mut stream = TokenStream::new();
+            match cil::Decoder.decode(&mut reader) {
+                Ok(mut inst) => {
+                    inst.update_rel_addrs(addr, None);
+                    inst.tokenize_with(&mut stream, &|token| metadata.token_name(token));
+                    lines.push((addr, stream.inner));
+                    addr += inst.width();
+                }
+                // the rest of the body can't be found once an instruction is invalid
+                Err(..) => {
+                    stream.push("(bad)", CONFIG.colors.asm.invalid);
+                    lines.push((addr, stream.inner));
+                    break;
+                }
+            }
+        }
+
+        lines
+    }
+}
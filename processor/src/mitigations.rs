@@ -0,0 +1,405 @@
+//! Summary of the exploit mitigations a binary was built with, similar to `checksec`.
+
+use binformat::Import;
+use object::read::elf::{Dyn, ElfFile, FileHeader, ProgramHeader};
+use object::read::macho::{MachHeader, MachOFile};
+use object::read::pe::{ImageNtHeaders, ImageOptionalHeader, PeFile};
+use object::read::File as ObjectFile;
+use object::{elf, macho, pe};
+use object::{Endianness, LittleEndian as LE, Object, ObjectSegment, ObjectSymbol, ReadRef, U32};
+use std::fmt;
+
+/// Debug directory entry holding the extended DLL characteristics.
+const IMAGE_DEBUG_TYPE_EX_DLLCHARACTERISTICS: u32 = 20;
+
+/// Extended DLL characteristic of images compatible with CET shadow stacks.
+const IMAGE_DLLCHARACTERISTICS_EX_CET_COMPAT: u32 = 0x1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Enabled,
+    /// Only some of the protection is applied.
+    Partial,
+    Disabled,
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Enabled => f.pad("enabled"),
+            Self::Partial => f.pad("partial"),
+            Self::Disabled => f.pad("disabled"),
+        }
+    }
+}
+
+/// Exploit mitigation along with why it's considered (not) present.
+#[derive(Debug, Clone)]
+pub struct Mitigation {
+    pub name: &'static str,
+    pub verdict: Verdict,
+    pub rationale: String,
+}
+
+impl Mitigation {
+    fn new(name: &'static str, verdict: Verdict, rationale: impl Into<String>) -> Self {
+        Self {
+            name,
+            verdict,
+            rationale: rationale.into(),
+        }
+    }
+}
+
+/// Symbol names with leading underscores removed, as Mach-O prefixes all of them.
+fn symbol_names<'a>(obj: &'a ObjectFile, imports: &'a [Import]) -> Vec<&'a str> {
+    let mut names: Vec<&str> = obj
+        .symbols()
+        .chain(obj.dynamic_symbols())
+        .filter_map(|sym| sym.name().ok())
+        .chain(imports.iter().map(|import| import.name.as_str()))
+        .map(|name| name.trim_start_matches('_'))
+        .collect();
+
+    names.sort_unstable();
+    names.dedup();
+    names
+}
+
+fn stack_canary(names: &[&str]) -> Mitigation {
+    match names.iter().find(|name| matches!(**name, "stack_chk_fail" | "stack_chk_guard")) {
+        Some(name) => Mitigation::new(
+            "Stack canary",
+            Verdict::Enabled,
+            format!("References {name}."),
+        ),
+        None => Mitigation::new("Stack canary", Verdict::Disabled, "No __stack_chk symbols."),
+    }
+}
+
+fn fortify(names: &[&str]) -> Mitigation {
+    let fortified = names.iter().filter(|name| name.ends_with("_chk")).count();
+    match fortified {
+        0 => Mitigation::new("Fortify", Verdict::Disabled, "No fortified functions."),
+        _ => Mitigation::new(
+            "Fortify",
+            Verdict::Enabled,
+            format!("Calls {fortified} fortified *_chk functions."),
+        ),
+    }
+}
+
+pub fn check(obj: &ObjectFile, imports: &[Import]) -> Vec<Mitigation> {
+    let names = symbol_names(obj, imports);
+
+    match obj {
+        ObjectFile::Elf32(elf) => check_elf(elf, &names),
+        ObjectFile::Elf64(elf) => check_elf(elf, &names),
+        ObjectFile::Pe32(pe) => check_pe(pe),
+        ObjectFile::Pe64(pe) => check_pe(pe),
+        ObjectFile::MachO32(macho) => check_macho(macho, &names),
+        ObjectFile::MachO64(macho) => check_macho(macho, &names),
+        _ => Vec::new(),
+    }
+}
+
+fn check_elf<Elf: FileHeader<Endian = Endianness>>(
+    elf: &ElfFile<Elf>,
+    names: &[&str],
+) -> Vec<Mitigation> {
+    let endian = elf.endian();
+    let data = elf.data();
+    let phdrs = elf.raw_segments();
+
+    let find_phdr = |typ| phdrs.iter().find(|phdr| phdr.p_type(endian) == typ);
+    let dynamic = phdrs.iter().find_map(|phdr| phdr.dynamic(endian, data).ok().flatten());
+    let dyn_val = |tag: u32| {
+        dynamic?
+            .iter()
+            .find(|entry| entry.d_tag(endian).into() == tag as u64)
+            .map(|entry| entry.d_val(endian).into())
+    };
+
+    let mut mitigations = Vec::new();
+
+    let flags_1 = dyn_val(elf::DT_FLAGS_1).unwrap_or(0);
+    mitigations.push(match elf.raw_header().e_type(endian) {
+        elf::ET_DYN if flags_1 & elf::DF_1_PIE as u64 != 0 => {
+            Mitigation::new("PIE", Verdict::Enabled, "Position independent executable.")
+        }
+        elf::ET_DYN if find_phdr(elf::PT_INTERP).is_some() => Mitigation::new(
+            "PIE",
+            Verdict::Enabled,
+            "Shared object with an interpreter.",
+        ),
+        elf::ET_DYN => Mitigation::new("PIE", Verdict::Enabled, "Shared object."),
+        _ => Mitigation::new("PIE", Verdict::Disabled, "Loaded at a fixed address."),
+    });
+
+    mitigations.push(match find_phdr(elf::PT_GNU_STACK) {
+        Some(phdr) if phdr.p_flags(endian) & elf::PF_X != 0 => {
+            Mitigation::new("NX", Verdict::Disabled, "PT_GNU_STACK is executable.")
+        }
+        Some(..) => Mitigation::new("NX", Verdict::Enabled, "PT_GNU_STACK isn't executable."),
+        None => Mitigation::new(
+            "NX",
+            Verdict::Disabled,
+            "No PT_GNU_STACK, the stack defaults to executable.",
+        ),
+    });
+
+    let bind_now = dyn_val(elf::DT_BIND_NOW).is_some()
+        || dyn_val(elf::DT_FLAGS).unwrap_or(0) & elf::DF_BIND_NOW as u64 != 0
+        || flags_1 & elf::DF_1_NOW as u64 != 0;
+
+    mitigations.push(match (find_phdr(elf::PT_GNU_RELRO).is_some(), bind_now) {
+        (true, true) => Mitigation::new(
+            "RELRO",
+            Verdict::Enabled,
+            "PT_GNU_RELRO with immediate binding, the GOT is read-only.",
+        ),
+        (true, false) => Mitigation::new(
+            "RELRO",
+            Verdict::Partial,
+            "PT_GNU_RELRO with lazy binding, the PLT's GOT is writable.",
+        ),
+        (false, _) => Mitigation::new("RELRO", Verdict::Disabled, "No PT_GNU_RELRO."),
+    });
+
+    mitigations.push(stack_canary(names));
+
+    let (name, property, forward, backward) = match elf.raw_header().e_machine(endian) {
+        elf::EM_AARCH64 => (
+            "BTI/PAC",
+            elf::GNU_PROPERTY_AARCH64_FEATURE_1_AND,
+            ("BTI", elf::GNU_PROPERTY_AARCH64_FEATURE_1_BTI),
+            ("PAC", elf::GNU_PROPERTY_AARCH64_FEATURE_1_PAC),
+        ),
+        _ => (
+            "CET",
+            elf::GNU_PROPERTY_X86_FEATURE_1_AND,
+            ("IBT", elf::GNU_PROPERTY_X86_FEATURE_1_IBT),
+            ("SHSTK", elf::GNU_PROPERTY_X86_FEATURE_1_SHSTK),
+        ),
+    };
+
+    let mut features = 0;
+    for phdr in phdrs {
+        let mut notes = match phdr.notes(endian, data) {
+            Ok(Some(notes)) => notes,
+            _ => continue,
+        };
+
+        while let Ok(Some(note)) = notes.next() {
+            let mut props = match note.gnu_properties(endian) {
+                Some(props) => props,
+                None => continue,
+            };
+
+            while let Ok(Some(prop)) = props.next() {
+                if prop.pr_type() == property {
+                    features = prop.data_u32(endian).unwrap_or(0);
+                }
+            }
+        }
+    }
+
+    let supported: Vec<&str> = [forward, backward]
+        .into_iter()
+        .filter(|(_, flag)| features & flag != 0)
+        .map(|(feature, _)| feature)
+        .collect();
+
+    mitigations.push(match supported.len() {
+        0 => Mitigation::new(name, Verdict::Disabled, "No GNU property marking support."),
+        1 => Mitigation::new(
+            name,
+            Verdict::Partial,
+            format!("Only marked as supporting {}.", supported[0]),
+        ),
+        _ => Mitigation::new(
+            name,
+            Verdict::Enabled,
+            format!("Marked as supporting {}.", supported.join(" and ")),
+        ),
+    });
+
+    mitigations.push(fortify(names));
+    mitigations
+}
+
+fn check_pe<Pe: ImageNtHeaders>(pe: &PeFile<Pe>) -> Vec<Mitigation> {
+    let headers = pe.nt_headers();
+    let characteristics = headers.optional_header().dll_characteristics();
+    let relocs_stripped =
+        headers.file_header().characteristics.get(LE) & pe::IMAGE_FILE_RELOCS_STRIPPED != 0;
+
+    let mut mitigations = Vec::new();
+
+    let dynamic_base = characteristics & pe::IMAGE_DLLCHARACTERISTICS_DYNAMIC_BASE != 0;
+    let high_entropy = characteristics & pe::IMAGE_DLLCHARACTERISTICS_HIGH_ENTROPY_VA != 0;
+    mitigations.push(match (dynamic_base, relocs_stripped) {
+        (true, true) => Mitigation::new(
+            "ASLR",
+            Verdict::Disabled,
+            "DYNAMIC_BASE is set, but the relocations are stripped.",
+        ),
+        (true, false) if pe.is_64() && !high_entropy => Mitigation::new(
+            "ASLR",
+            Verdict::Partial,
+            "DYNAMIC_BASE without HIGH_ENTROPY_VA, only 32 bits of the address are randomized.",
+        ),
+        (true, false) => Mitigation::new("ASLR", Verdict::Enabled, "DYNAMIC_BASE is set."),
+        (false, _) => Mitigation::new("ASLR", Verdict::Disabled, "DYNAMIC_BASE isn't set."),
+    });
+
+    mitigations.push(
+        match characteristics & pe::IMAGE_DLLCHARACTERISTICS_NX_COMPAT {
+            0 => Mitigation::new("NX", Verdict::Disabled, "NX_COMPAT isn't set."),
+            _ => Mitigation::new("NX", Verdict::Enabled, "NX_COMPAT is set."),
+        },
+    );
+
+    let sections = pe.section_table();
+    let load_config = pe
+        .data_directory(pe::IMAGE_DIRECTORY_ENTRY_LOAD_CONFIG)
+        .and_then(|dir| dir.data(pe.data(), &sections).ok());
+
+    let security_cookie = load_config.map(|config| {
+        // offset and width of `SecurityCookie`
+        let (offset, width) = match pe.is_64() {
+            true => (0x58, 8),
+            false => (0x3c, 4),
+        };
+
+        // older load configs are too short to have a cookie
+        let cookie = config.get(offset..offset + width);
+        cookie.is_some_and(|cookie| cookie.iter().any(|&byte| byte != 0))
+    });
+
+    mitigations.push(match security_cookie {
+        Some(true) => Mitigation::new(
+            "Stack canary",
+            Verdict::Enabled,
+            "Load config has a security cookie.",
+        ),
+        Some(false) => Mitigation::new(
+            "Stack canary",
+            Verdict::Disabled,
+            "Load config has no security cookie.",
+        ),
+        None => Mitigation::new(
+            "Stack canary",
+            Verdict::Disabled,
+            "No load config directory.",
+        ),
+    });
+
+    let guard_cf = characteristics & pe::IMAGE_DLLCHARACTERISTICS_GUARD_CF != 0;
+    let cet_compat = is_cet_compat(pe);
+
+    mitigations.push(match (guard_cf, cet_compat) {
+        (true, true) => Mitigation::new(
+            "CFG/CET",
+            Verdict::Enabled,
+            "GUARD_CF and CET_COMPAT are set.",
+        ),
+        (true, false) => Mitigation::new(
+            "CFG/CET",
+            Verdict::Partial,
+            "GUARD_CF is set, but it isn't CET_COMPAT.",
+        ),
+        (false, true) => Mitigation::new(
+            "CFG/CET",
+            Verdict::Partial,
+            "CET_COMPAT is set, but GUARD_CF isn't.",
+        ),
+        (false, false) => Mitigation::new(
+            "CFG/CET",
+            Verdict::Disabled,
+            "Neither GUARD_CF nor CET_COMPAT is set.",
+        ),
+    });
+
+    mitigations
+}
+
+/// Whether the extended DLL characteristics in the debug directory mark CET support.
+fn is_cet_compat<Pe: ImageNtHeaders>(pe: &PeFile<Pe>) -> bool {
+    let debug_dir = match pe.data_directory(pe::IMAGE_DIRECTORY_ENTRY_DEBUG) {
+        Some(dir) => dir.data(pe.data(), &pe.section_table()).unwrap_or(&[]),
+        None => return false,
+    };
+
+    let count = debug_dir.len() / std::mem::size_of::<pe::ImageDebugDirectory>();
+    let entries = match object::pod::slice_from_bytes::<pe::ImageDebugDirectory>(debug_dir, count) {
+        Ok((entries, _)) => entries,
+        Err(()) => return false,
+    };
+
+    entries.iter().any(|entry| {
+        if entry.typ.get(LE) != IMAGE_DEBUG_TYPE_EX_DLLCHARACTERISTICS {
+            return false;
+        }
+
+        let offset = entry.pointer_to_raw_data.get(LE) as u64;
+        let characteristics = pe.data().read_at::<U32<LE>>(offset);
+        characteristics
+            .is_ok_and(|chars| chars.get(LE) & IMAGE_DLLCHARACTERISTICS_EX_CET_COMPAT != 0)
+    })
+}
+
+fn check_macho<Mach: MachHeader<Endian = Endianness>>(
+    macho: &MachOFile<Mach>,
+    names: &[&str],
+) -> Vec<Mitigation> {
+    let endian = macho.endian();
+    let header = macho.raw_header();
+    let flags = header.flags(endian);
+
+    let mut mitigations = Vec::new();
+
+    mitigations.push(match header.filetype(endian) {
+        macho::MH_EXECUTE if flags & macho::MH_PIE != 0 => {
+            Mitigation::new("PIE", Verdict::Enabled, "MH_PIE is set.")
+        }
+        macho::MH_EXECUTE => Mitigation::new("PIE", Verdict::Disabled, "MH_PIE isn't set."),
+        _ => Mitigation::new(
+            "PIE",
+            Verdict::Enabled,
+            "Dynamic libraries are always relocatable.",
+        ),
+    });
+
+    mitigations.push(match flags & macho::MH_ALLOW_STACK_EXECUTION {
+        0 => Mitigation::new(
+            "NX",
+            Verdict::Enabled,
+            "MH_ALLOW_STACK_EXECUTION isn't set.",
+        ),
+        _ => Mitigation::new("NX", Verdict::Disabled, "MH_ALLOW_STACK_EXECUTION is set."),
+    });
+
+    let data_const = macho.segments().any(|segment| segment.name() == Ok(Some("__DATA_CONST")));
+    mitigations.push(match data_const {
+        true => Mitigation::new(
+            "RELRO",
+            Verdict::Enabled,
+            "Has a __DATA_CONST segment that's made read-only after binding.",
+        ),
+        false => Mitigation::new("RELRO", Verdict::Disabled, "No __DATA_CONST segment."),
+    });
+
+    mitigations.push(stack_canary(names));
+
+    let arm64e = header.cputype(endian) == macho::CPU_TYPE_ARM64
+        && header.cpusubtype(endian) & !macho::CPU_SUBTYPE_MASK == macho::CPU_SUBTYPE_ARM64E;
+
+    mitigations.push(match arm64e {
+        true => Mitigation::new("PAC", Verdict::Enabled, "Built for arm64e."),
+        false => Mitigation::new("PAC", Verdict::Disabled, "Not built for arm64e."),
+    });
+
+    mitigations.push(fortify(names));
+    mitigations
+}
@@ -0,0 +1,168 @@
+//! Data appended past the end of the image an object declares, such as the payload of an installer
+//! or a self-extracting archive, which the loader never maps.
+
+use crate::packers;
+use object::pe::IMAGE_DIRECTORY_ENTRY_SECURITY as SECURITY;
+use object::read::elf::{ElfFile, FileHeader};
+use object::read::pe::{ImageNtHeaders, ImageOptionalHeader, PeFile};
+use object::read::File as ObjectFile;
+use object::LittleEndian as LE;
+use object::{Object, ObjectSection, ObjectSegment};
+use std::fmt;
+use std::ops::Range;
+
+/// Size of an entry of a COFF symbol table, which mingw leaves past the sections of a PE.
+const COFF_SYMBOL_SIZE: usize = 18;
+
+/// Signatures of data that's commonly appended, along with their offset into the data.
+const SIGNATURES: &[(usize, &[u8], &str)] = &[
+    (0, b"PK\x03\x04", "ZIP archive"),
+    (0, b"7z\xBC\xAF\x27\x1C", "7-Zip archive"),
+    (0, b"Rar!\x1A\x07", "RAR archive"),
+    (0, b"MSCF", "Cabinet archive"),
+    (0, b"\x1F\x8B", "gzip data"),
+    (0, b"Inno Setup Setup Data", "Inno Setup data"),
+    (0, b"idska32\x1A", "Inno Setup data"),
+    (4, b"\xEF\xBE\xAD\xDENullsoftInst", "NSIS installer"),
+    (0, b"\x7FELF", "ELF binary"),
+    (0, b"MZ", "PE binary"),
+];
+
+/// Data past the end of the image.
+#[derive(Debug, Clone)]
+pub struct Overlay {
+    /// Offset into the file.
+    pub offset: usize,
+    pub size: usize,
+    /// Shannon entropy in bits per byte, close to 8 for compressed or encrypted data.
+    pub entropy: f64,
+    /// What the data looks like, if it's recognized.
+    pub kind: Option<&'static str>,
+    /// Size of the Authenticode signature at the end of the data, which isn't part of the image
+    /// but is expected there.
+    pub signature: usize,
+}
+
+impl Overlay {
+    pub fn range(&self) -> Range<usize> {
+        self.offset..self.offset + self.size
+    }
+}
+
+impl fmt::Display for Overlay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_fmt(format_args!(
+            "{} bytes appended at {:#X} with an entropy of {:.2}",
+            self.size, self.offset, self.entropy
+        ))?;
+
+        if let Some(kind) = self.kind {
+            f.write_fmt(format_args!(", likely {kind}"))?;
+        }
+
+        f.write_str(".")
+    }
+}
+
+/// End of the headers and the data of every section and segment of an ELF.
+fn elf_end<Elf: FileHeader>(elf: &ElfFile<Elf>) -> usize {
+    let endian = elf.endian();
+    let header = elf.raw_header();
+    let table = |offset: Elf::Word, count: u16, size: u16| {
+        offset.into() as usize + count as usize * size as usize
+    };
+
+    let programs = table(
+        header.e_phoff(endian),
+        header.e_phnum(endian),
+        header.e_phentsize(endian),
+    );
+    let sections = table(
+        header.e_shoff(endian),
+        header.e_shnum(endian),
+        header.e_shentsize(endian),
+    );
+    let data = elf
+        .sections()
+        .filter_map(|section| section.file_range())
+        .chain(elf.segments().map(|segment| segment.file_range()))
+        .map(|(offset, size)| (offset + size) as usize);
+
+    data.fold(
+        std::mem::size_of::<Elf>().max(programs).max(sections),
+        usize::max,
+    )
+}
+
+/// End of the headers, the raw data of every section and the COFF symbol table of a PE.
+fn pe_end<Pe: ImageNtHeaders>(pe: &PeFile<Pe>, binary: &[u8]) -> usize {
+    let mut end = pe.nt_headers().optional_header().size_of_headers() as usize;
+    for section in pe.section_table().iter() {
+        let offset = section.pointer_to_raw_data.get(LE) as usize;
+        end = end.max(offset + section.size_of_raw_data.get(LE) as usize);
+    }
+
+    // the symbol table is followed by a string table that starts with its size
+    let header = pe.nt_headers().file_header();
+    let symbols = header.pointer_to_symbol_table.get(LE) as usize;
+    if symbols != 0 {
+        let strings = symbols + header.number_of_symbols.get(LE) as usize * COFF_SYMBOL_SIZE;
+        let size = binary.get(strings..strings + 4).map_or(0, |size| {
+            u32::from_le_bytes(size.try_into().unwrap()) as usize
+        });
+        end = end.max(strings + size);
+    }
+
+    end
+}
+
+/// Offset where the image of a PE or ELF ends according to its headers.
+pub(crate) fn image_end(obj: &ObjectFile, binary: &[u8]) -> Option<usize> {
+    match obj {
+        ObjectFile::Elf32(elf) => Some(elf_end(elf)),
+        ObjectFile::Elf64(elf) => Some(elf_end(elf)),
+        ObjectFile::Pe32(pe) => Some(pe_end(pe, binary)),
+        ObjectFile::Pe64(pe) => Some(pe_end(pe, binary)),
+        _ => None,
+    }
+}
+
+/// Data past the end of a PE or ELF image, if there is any.
+pub fn detect(obj: &ObjectFile, binary: &[u8]) -> Option<Overlay> {
+    let end = image_end(obj, binary)?;
+    let certificate = match obj {
+        ObjectFile::Pe32(pe) => pe.data_directory(SECURITY),
+        ObjectFile::Pe64(pe) => pe.data_directory(SECURITY),
+        _ => None,
+    };
+
+    if end >= binary.len() {
+        return None;
+    }
+
+    // the certificate table's address is an offset into the file instead of the image
+    let signature = certificate.map_or(0..0, |dir| {
+        let offset = dir.virtual_address.get(LE) as usize;
+        offset..offset + dir.size.get(LE) as usize
+    });
+
+    let signature = match signature.end == binary.len() && signature.start >= end {
+        true => signature.len(),
+        false => 0,
+    };
+
+    let data = &binary[end..];
+    let kind = SIGNATURES
+        .iter()
+        .find(|(offset, magic, _)| data.get(*offset..).is_some_and(|data| data.starts_with(magic)))
+        .map(|(_, _, kind)| *kind)
+        .or((signature == data.len()).then_some("Authenticode signature"));
+
+    Some(Overlay {
+        offset: end,
+        size: data.len(),
+        entropy: packers::entropy(&data[..data.len() - signature]),
+        kind,
+        signature,
+    })
+}
@@ -0,0 +1,179 @@
+//! Detection of packers and obfuscators, whose code isn't meaningful to disassemble.
+
+use object::pe::IMAGE_DIRECTORY_ENTRY_COM_DESCRIPTOR;
+use object::read::File as ObjectFile;
+use object::{BinaryFormat, Object, ObjectSection};
+use processor_shared::{Section, SectionKind};
+use std::fmt;
+
+/// Entropy in bits per byte above which code is most likely compressed or encrypted.
+const ENTROPY_THRESHOLD: f64 = 7.2;
+
+/// Section names used by packers and protectors.
+const PACKER_SECTIONS: &[(&str, &str)] = &[
+    ("UPX0", "UPX"),
+    ("UPX1", "UPX"),
+    ("UPX2", "UPX"),
+    (".aspack", "ASPack"),
+    (".adata", "ASPack"),
+    ("MPRESS1", "MPRESS"),
+    ("MPRESS2", "MPRESS"),
+    (".petite", "Petite"),
+    (".nsp0", "NsPack"),
+    (".nsp1", "NsPack"),
+    (".themida", "Themida"),
+    (".winlice", "WinLicense"),
+    (".vmp0", "VMProtect"),
+    (".vmp1", "VMProtect"),
+    (".enigma1", "Enigma Protector"),
+    (".enigma2", "Enigma Protector"),
+    ("pebundle", "PEBundle"),
+    (".packed", "RLPack"),
+];
+
+/// Attributes that .NET obfuscators add to the assemblies they process.
+const DOTNET_OBFUSCATORS: &[(&[u8], &str)] = &[
+    (b"ConfusedByAttribute", "ConfuserEx"),
+    (b"DotfuscatorAttribute", "Dotfuscator"),
+    (b"SmartAssembly.Attributes", "SmartAssembly"),
+    (b"BabelObfuscatorAttribute", "Babel"),
+    (b"Xenocode.Client.Attributes", "Xenocode"),
+];
+
+/// Number of leading bytes that contain UPX's header.
+const UPX_HEADER_RANGE: usize = 0x1000;
+
+/// Section names generated by Delphi's compiler.
+const DELPHI_SECTIONS: &[&str] = &["CODE", "DATA", "BSS"];
+
+/// Something about a binary that suggests its code isn't what it'll execute.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Detection {
+    /// Compressed or encrypted by a known packer.
+    Packer(&'static str),
+    /// Managed .NET assembly, so most of its code is CIL bytecode.
+    DotNet,
+    /// .NET assembly processed by a known obfuscator.
+    Obfuscator(&'static str),
+    /// Compiled by Delphi.
+    Delphi,
+    /// Section holding code with the entropy of compressed or encrypted data.
+    HighEntropy { section: String, entropy: f64 },
+    /// Section name that no linker would produce.
+    AbnormalSectionName(String),
+}
+
+impl Detection {
+    /// Whether disassembling the binary will likely produce nonsense.
+    pub fn is_severe(&self) -> bool {
+        !matches!(self, Self::Delphi | Self::AbnormalSectionName(..))
+    }
+}
+
+impl fmt::Display for Detection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Packer(name) => f.write_fmt(format_args!("Packed using {name}.")),
+            Self::DotNet => f.write_str("Managed .NET assembly, the code is mostly CIL."),
+            Self::Obfuscator(name) => f.write_fmt(format_args!("Obfuscated using {name}.")),
+            Self::Delphi => f.write_str("Compiled using Delphi."),
+            Self::HighEntropy { section, entropy } => f.write_fmt(format_args!(
+                "Section '{section}' has an entropy of {entropy:.2}, it's likely packed."
+            )),
+            Self::AbnormalSectionName(name) => {
+                f.write_fmt(format_args!("Section name {name:?} is abnormal."))
+            }
+        }
+    }
+}
+
+/// Shannon entropy in bits per byte.
+pub(crate) fn entropy(bytes: &[u8]) -> f64 {
+    if bytes.is_empty() {
+        return 0.0;
+    }
+
+    let mut counts = [0usize; 256];
+    for &byte in bytes {
+        counts[byte as usize] += 1;
+    }
+
+    let len = bytes.len() as f64;
+    counts.iter().filter(|&&count| count > 0).fold(0.0, |entropy, &count| {
+        let p = count as f64 / len;
+        entropy - p * p.log2()
+    })
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|window| window == needle)
+}
+
+/// Whether a section name couldn't have been emitted by a regular toolchain.
+fn is_abnormal_name(name: &str) -> bool {
+    name.is_empty() || name.chars().any(|c| !c.is_ascii_graphic())
+}
+
+pub fn detect(obj: &ObjectFile, binary: &[u8], sections: &[Section]) -> Vec<Detection> {
+    let mut detections = Vec::new();
+    let mut names = Vec::new();
+
+    for section in obj.sections() {
+        match section.name() {
+            Ok(name) => names.push(name.to_string()),
+            Err(..) => detections.push(Detection::AbnormalSectionName(String::new())),
+        }
+    }
+
+    for name in names.iter() {
+        if let Some((_, packer)) = PACKER_SECTIONS.iter().find(|(section, _)| section == name) {
+            if !detections.contains(&Detection::Packer(packer)) {
+                detections.push(Detection::Packer(packer));
+            }
+        } else if is_abnormal_name(name) && obj.format() == BinaryFormat::Pe {
+            detections.push(Detection::AbnormalSectionName(name.clone()));
+        }
+    }
+
+    // UPX also packs binaries without sections, which still carry its magic
+    let header = &binary[..std::cmp::min(binary.len(), UPX_HEADER_RANGE)];
+    if !detections.contains(&Detection::Packer("UPX")) && contains(header, b"UPX!") {
+        detections.push(Detection::Packer("UPX"));
+    }
+
+    let clr_header = match obj {
+        ObjectFile::Pe32(pe) => pe.data_directory(IMAGE_DIRECTORY_ENTRY_COM_DESCRIPTOR),
+        ObjectFile::Pe64(pe) => pe.data_directory(IMAGE_DIRECTORY_ENTRY_COM_DESCRIPTOR),
+        _ => None,
+    };
+
+    if clr_header.is_some() {
+        detections.push(Detection::DotNet);
+
+        for (marker, obfuscator) in DOTNET_OBFUSCATORS {
+            if contains(binary, marker) {
+                detections.push(Detection::Obfuscator(obfuscator));
+            }
+        }
+    }
+
+    let is_delphi = DELPHI_SECTIONS.iter().all(|section| names.iter().any(|name| name == section))
+        || contains(binary, b"Embarcadero Delphi")
+        || contains(binary, b"Borland Delphi");
+
+    if is_delphi {
+        detections.push(Detection::Delphi);
+    }
+
+    for section in sections.iter().filter(|section| section.kind == SectionKind::Code) {
+        let entropy = entropy(section.bytes());
+        if entropy > ENTROPY_THRESHOLD {
+            detections.push(Detection::HighEntropy {
+                section: section.name.clone(),
+                entropy,
+            });
+        }
+    }
+
+    detections
+}
@@ -0,0 +1,160 @@
+//! Loading of a binary on a worker thread, split up into stages that can be cancelled.
+
+use crate::{Error, Processor};
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Step of loading a binary, in the order they run in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Stage {
+    /// Mapping the binary and parsing its headers.
+    Load,
+    /// Parsing the symbol table and debug info.
+    Symbols,
+    /// Checking imports for packers and exploit mitigations.
+    Imports,
+    /// Decoding the code sections.
+    Disassembly,
+    /// Finding calls between functions.
+    Xrefs,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 5] = [
+        Stage::Load,
+        Stage::Symbols,
+        Stage::Imports,
+        Stage::Disassembly,
+        Stage::Xrefs,
+    ];
+
+    /// Position of the stage, starting at one.
+    pub fn number(self) -> usize {
+        self as usize + 1
+    }
+}
+
+impl Stage {
+    fn name(self) -> &'static str {
+        match self {
+            Stage::Load => "Load",
+            Stage::Symbols => "Symbols",
+            Stage::Imports => "Imports",
+            Stage::Disassembly => "Disassembly",
+            Stage::Xrefs => "Xrefs",
+        }
+    }
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// State shared between a pipeline and the thread running it.
+#[derive(Debug, Default)]
+pub(crate) struct Progress {
+    cancelled: AtomicBool,
+    stage: AtomicUsize,
+    /// Task of loading the binary, which the progress of every stage is nested in.
+    task: Mutex<Option<log::Task>>,
+    /// Stage that's running and when it was entered, to time it.
+    entered: Mutex<Option<(Stage, Instant)>>,
+}
+
+impl Progress {
+    /// Move on to the next stage, unless the pipeline got cancelled.
+    pub(crate) fn enter(&self, stage: Stage) -> Result<(), Error> {
+        if self.is_cancelled() {
+            return Err(Error::Cancelled);
+        }
+
+        self.stage.store(stage as usize, Ordering::Relaxed);
+
+        // stages that were timed by a previous binary aren't part of this one's profile
+        if stage == Stage::Load {
+            log::PROFILER.take(Default::default());
+        }
+
+        self.end_stage();
+        *self.entered.lock().unwrap() = Some((stage, Instant::now()));
+
+        // the task is started on the thread running the stages, so their tasks get nested in it
+        let mut task = self.task.lock().unwrap();
+        let task =
+            task.get_or_insert_with(|| log::PROGRESS.start("Loading binary", Stage::ALL.len()));
+        task.set_steps_done(stage as usize);
+        Ok(())
+    }
+
+    /// Record how long the stage that's running took.
+    fn end_stage(&self) {
+        if let Some((stage, entered)) = self.entered.lock().unwrap().take() {
+            log::PROFILER.record(stage.name(), entered.elapsed());
+        }
+    }
+
+    /// Finish the task of loading the binary, once every stage ran.
+    pub(crate) fn finish(&self) {
+        self.end_stage();
+        self.task.lock().unwrap().take();
+    }
+
+    /// Whether the pipeline got cancelled, either by its handle or from the progress bar.
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+            || self.task.lock().unwrap().as_ref().is_some_and(log::Task::is_cancelled)
+    }
+}
+
+/// Handle to a binary being loaded on a worker thread.
+///
+/// Cancelling the pipeline also stops decoding the rest of the binary in the background, so it
+/// should be kept around for as long as the binary is loaded.
+pub struct AnalysisPipeline {
+    progress: Arc<Progress>,
+}
+
+impl AnalysisPipeline {
+    /// Start loading a binary, calling `finish` on the worker thread once it's done.
+    ///
+    /// `finish` isn't called if the pipeline got cancelled.
+    pub fn spawn<F>(path: PathBuf, finish: F) -> Self
+    where
+        F: FnOnce(Result<Processor, Error>) + Send + 'static,
+    {
+        let progress = Arc::new(Progress::default());
+        let worker = Arc::clone(&progress);
+
+        std::thread::spawn(move || {
+            let result = Processor::parse_with(&path, Arc::clone(&worker));
+
+            // the pipeline is kept around after failing, which shouldn't leave its task running
+            worker.finish();
+
+            if !worker.is_cancelled() {
+                finish(result);
+            }
+        });
+
+        Self { progress }
+    }
+
+    /// Stop at the next stage and drop whatever was loaded so far.
+    pub fn cancel(&self) {
+        self.progress.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.progress.is_cancelled()
+    }
+
+    /// Stage the worker thread is currently in.
+    pub fn stage(&self) -> Stage {
+        Stage::ALL[self.progress.stage.load(Ordering::Relaxed)]
+    }
+}
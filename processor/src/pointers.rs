@@ -0,0 +1,168 @@
+//! Pointers stored in data sections.
+//!
+//! Data sections are swept for machine words holding an address inside of a mapped section. To
+//! keep integers that happen to look like an address out, a pointer has to be aligned to its size
+//! and point at something that makes sense: the start of an instruction, a string or an aligned
+//! address. This turns vtables, tables of function pointers and tables of strings into links, and
+//! gives every address that's pointed at a label along with the pointers referring to it.
+
+use crate::Processor;
+use object::Endian;
+use processor_shared::{PhysAddr, Section, SectionKind};
+use std::collections::BTreeMap;
+
+/// Shortest run of printable characters pointed at that's considered a string.
+const MIN_STRING_LEN: usize = 4;
+
+/// What a pointer stored in a data section points at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointerKind {
+    /// Start of an instruction, such as a method of a vtable.
+    Code,
+    /// Start of a null terminated string.
+    String,
+    /// Anything else inside of a mapped section.
+    Data,
+}
+
+impl PointerKind {
+    /// Prefix of the label given to an address pointed at that doesn't have a symbol.
+    fn prefix(self) -> &'static str {
+        match self {
+            Self::Code => "loc",
+            Self::String => "str",
+            Self::Data => "data",
+        }
+    }
+}
+
+/// Pointer stored in a data section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DataPointer {
+    /// Address pointed at.
+    pub target: PhysAddr,
+    pub kind: PointerKind,
+}
+
+/// Whether an address starts a run of printable characters ending in a null terminator.
+fn starts_string(section: &Section, addr: PhysAddr) -> bool {
+    let printable = |byte: u8| byte.is_ascii_graphic() || byte.is_ascii_whitespace();
+
+    // pointers into the middle of a string are more likely to be a coincidence
+    if addr > section.start
+        && section.bytes_by_addr(addr - 1, 1).first().is_some_and(|&b| printable(b))
+    {
+        return false;
+    }
+
+    let bytes = section.bytes_by_addr(addr, usize::MAX);
+    match bytes.iter().position(|&byte| byte == b'\0') {
+        Some(len) => len >= MIN_STRING_LEN && bytes[..len].iter().all(|&byte| printable(byte)),
+        None => false,
+    }
+}
+
+/// Addresses pointed at by data pointers, along with where the pointers are stored.
+pub(crate) fn xrefs(
+    pointers: &BTreeMap<PhysAddr, DataPointer>,
+) -> BTreeMap<PhysAddr, Vec<PhysAddr>> {
+    let mut xrefs: BTreeMap<PhysAddr, Vec<PhysAddr>> = BTreeMap::new();
+    for (&addr, pointer) in pointers.iter() {
+        xrefs.entry(pointer.target).or_default().push(addr);
+    }
+
+    xrefs
+}
+
+impl Processor {
+    /// Size of a pointer on the binary's architecture.
+    pub(crate) fn pointer_size(&self) -> usize {
+        self.arch.address_size().map_or(8, |size| size.bytes() as usize)
+    }
+
+    /// What an address is if a pointer holds it, [`None`] if it's unlikely to be pointed at.
+    fn pointer_kind(&self, target: PhysAddr) -> Option<PointerKind> {
+        if target == 0 {
+            return None;
+        }
+
+        let section = self.section_by_addr(target).filter(|section| target < section.end)?;
+        match section.kind {
+            SectionKind::Code => self.instruction_by_addr(target).map(|_| PointerKind::Code),
+            // linkers merge strings that end the same, so these can point into a string
+            SectionKind::CString => Some(PointerKind::String),
+            _ if starts_string(section, target) => Some(PointerKind::String),
+            _ if target.is_multiple_of(4) || self.index.get_sym_by_addr(target).is_some() => {
+                Some(PointerKind::Data)
+            }
+            _ => None,
+        }
+    }
+
+    /// Sweep data sections for aligned words pointing inside of a mapped section, by the address
+    /// they're stored at.
+    pub(crate) fn sweep_pointers(&self) -> BTreeMap<PhysAddr, DataPointer> {
+        let mut pointers = BTreeMap::new();
+
+        for section in self.sections() {
+            let size = match section.kind {
+                SectionKind::Ptr32 => 4,
+                SectionKind::Ptr64 => 8,
+                SectionKind::Raw | SectionKind::Raw4 | SectionKind::Raw8 | SectionKind::Raw16 => {
+                    self.pointer_size()
+                }
+                _ => continue,
+            };
+
+            // pointers are aligned in memory, not relative to the start of their section
+            let start = section.start.next_multiple_of(size);
+            for addr in (start..section.end).step_by(size) {
+                let word = section.bytes_by_addr(addr, size);
+                let target = match word.len() {
+                    4 => self.endianness.read_u32_bytes(word.try_into().unwrap()) as PhysAddr,
+                    8 => self.endianness.read_u64_bytes(word.try_into().unwrap()) as PhysAddr,
+                    _ => break,
+                };
+
+                if let Some(kind) = self.pointer_kind(target) {
+                    pointers.insert(addr, DataPointer { target, kind });
+                }
+            }
+        }
+
+        pointers
+    }
+
+    /// Pointer stored in a data section at an address.
+    pub fn pointer_at(&self, addr: PhysAddr) -> Option<&DataPointer> {
+        self.data_pointers.get(&addr)
+    }
+
+    /// Addresses of the pointers in data sections that point at an address, sorted.
+    pub fn data_xrefs(&self, addr: PhysAddr) -> &[PhysAddr] {
+        self.data_xrefs.get(&addr).map_or(&[], Vec::as_slice)
+    }
+
+    /// Name of an address that's pointed at, either its symbol or a name made up of what it is
+    /// and its address, e.g. `str_4020a8`.
+    pub fn data_label(&self, addr: PhysAddr) -> Option<String> {
+        if let Some(symbol) = self.index.get_sym_by_addr(addr) {
+            return Some(symbol.as_str().to_string());
+        }
+
+        let pointer = self.data_xrefs(addr).first().and_then(|&at| self.pointer_at(at))?;
+        Some(format!("{}_{addr:x}", pointer.kind.prefix()))
+    }
+
+    /// First address past `addr` that a pointer is stored at or that's pointed at, where the bytes
+    /// of a data section are split up.
+    pub(crate) fn next_data_boundary(&self, addr: PhysAddr) -> Option<PhysAddr> {
+        let pointer = self.data_pointers.range(addr + 1..).next().map(|(&at, _)| at);
+        let label = self.data_xrefs.range(addr + 1..).next().map(|(&at, _)| at);
+
+        match (pointer, label) {
+            (Some(pointer), Some(label)) => Some(pointer.min(label)),
+            (pointer, label) => pointer.or(label),
+        }
+    }
+}
@@ -0,0 +1,348 @@
+//! Propagation of constants through the registers of a function, to find out where indirect calls
+//! and jumps go.
+//!
+//! Each register holds a small set of values it may have, which is enough for function pointers
+//! that are moved into a register before being called, pointers loaded from the GOT and methods
+//! loaded from a vtable whose address is known. Memory is only read where it doesn't change, so
+//! values stored at runtime aren't followed.
+
+use crate::Processor;
+use decoder::ir::{BinaryOp, Expr, Reg, Stmt, UnaryOp};
+use object::Architecture;
+use processor_shared::{PhysAddr, SectionKind};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Values a register holds before it's considered unknown.
+const MAX_VALUES: usize = 8;
+
+/// Functions with more instructions than this aren't analyzed.
+const MAX_INSTRUCTIONS: usize = 16384;
+
+/// Registers that keep their value across calls.
+fn callee_saved(arch: Architecture) -> &'static [&'static str] {
+    match arch {
+        Architecture::X86_64 | Architecture::X86_64_X32 => {
+            &["rbx", "rbp", "rsp", "r12", "r13", "r14", "r15"]
+        }
+        Architecture::I386 => &["ebx", "ebp", "esp", "esi", "edi"],
+        Architecture::Aarch64 | Architecture::Aarch64_Ilp32 => &[
+            "x19", "x20", "x21", "x22", "x23", "x24", "x25", "x26", "x27", "x28", "x29", "sp",
+        ],
+        Architecture::Arm => &["r4", "r5", "r6", "r7", "r8", "r9", "r10", "r11", "sp"],
+        Architecture::Riscv32 | Architecture::Riscv64 => &[
+            "s0", "s1", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11", "sp",
+        ],
+        Architecture::Mips | Architecture::Mips64 => {
+            &["s0", "s1", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "sp"]
+        }
+        _ => &[],
+    }
+}
+
+/// Possible values of every register whose value is known.
+type State = BTreeMap<&'static str, Vec<u64>>;
+
+/// Targets of indirect calls and jumps, as pairs of the instruction and where it goes.
+#[derive(Debug, Default)]
+pub(crate) struct Resolved {
+    pub calls: Vec<(PhysAddr, PhysAddr)>,
+    pub jumps: Vec<(PhysAddr, PhysAddr)>,
+}
+
+fn mask(size: u8) -> u64 {
+    match size {
+        0..=7 => (1 << (size as u32 * 8)) - 1,
+        _ => u64::MAX,
+    }
+}
+
+/// Value of `size` bytes when interpreted as signed.
+fn sign_extend(value: u64, size: u8) -> u64 {
+    let shift = 64 - 8 * size.clamp(1, 8) as u32;
+    (((value << shift) as i64) >> shift) as u64
+}
+
+fn binary(op: BinaryOp, lhs: u64, rhs: u64) -> Option<u64> {
+    Some(match op {
+        BinaryOp::Add => lhs.wrapping_add(rhs),
+        BinaryOp::Sub => lhs.wrapping_sub(rhs),
+        BinaryOp::Mul => lhs.wrapping_mul(rhs),
+        BinaryOp::And => lhs & rhs,
+        BinaryOp::Or => lhs | rhs,
+        BinaryOp::Xor => lhs ^ rhs,
+        BinaryOp::Shl => lhs.checked_shl(rhs as u32).unwrap_or(0),
+        BinaryOp::Shr => lhs.checked_shr(rhs as u32).unwrap_or(0),
+        _ => return None,
+    })
+}
+
+/// Union of two sets of values, unless there are too many to keep track of.
+fn union(lhs: &[u64], rhs: &[u64]) -> Option<Vec<u64>> {
+    let values: BTreeSet<u64> = lhs.iter().chain(rhs).copied().collect();
+    match values.len() <= MAX_VALUES {
+        true => Some(values.into_iter().collect()),
+        false => None,
+    }
+}
+
+/// Registers known in both states, with the values of either.
+fn merge(lhs: &State, rhs: &State) -> State {
+    lhs.iter()
+        .filter_map(|(reg, values)| Some((*reg, union(values, rhs.get(reg)?)?)))
+        .collect()
+}
+
+/// Update the values of a register, which is forgotten if only part of it is written.
+fn assign(state: &mut State, dst: &Reg, values: Option<Vec<u64>>, word: u8) {
+    match values {
+        Some(values) if dst.offset == 0 && dst.size >= word => {
+            state.insert(dst.name, values);
+        }
+        _ => {
+            state.remove(dst.name);
+        }
+    }
+}
+
+impl Processor {
+    /// Whether the bytes at an address stay the same while the binary runs.
+    fn is_constant(&self, addr: PhysAddr) -> bool {
+        self.section_by_addr(addr).is_some_and(|section| {
+            !section.permissions.write
+                || matches!(section.kind, SectionKind::Got32 | SectionKind::Got64)
+        })
+    }
+
+    /// Value of a pointer in memory that doesn't change, the address of the GOT entry itself
+    /// when it's only filled in by the loader.
+    fn constant_pointer(&self, addr: PhysAddr) -> Option<u64> {
+        let section = self.section_by_addr(addr)?;
+        if matches!(section.kind, SectionKind::Got32 | SectionKind::Got64) {
+            return Some(addr as u64);
+        }
+
+        self.read_word(addr).filter(|&value| value != 0).map(|value| value as u64)
+    }
+
+    /// Possible values of an expression given the values of registers.
+    fn evaluate(&self, expr: &Expr, state: &State, word: u8) -> Option<Vec<u64>> {
+        let values = match expr {
+            Expr::Const(value) => vec![*value],
+            Expr::Reg(reg) => {
+                let values = state.get(reg.name)?;
+                let shift = reg.offset as u32 * 8;
+                values.iter().map(|value| (value >> shift) & mask(reg.size)).collect()
+            }
+            Expr::Load { addr, size } if *size == word => {
+                let addrs = self.evaluate(addr, state, word)?;
+                addrs
+                    .into_iter()
+                    .map(|addr| {
+                        let addr = addr as PhysAddr;
+                        match self.is_constant(addr) {
+                            true => self.constant_pointer(addr),
+                            false => None,
+                        }
+                    })
+                    .collect::<Option<Vec<u64>>>()?
+            }
+            Expr::Unary(op, value) => {
+                let values = self.evaluate(value, state, word)?;
+                values
+                    .into_iter()
+                    .map(|value| match op {
+                        UnaryOp::Neg => value.wrapping_neg(),
+                        UnaryOp::Not => !value,
+                    })
+                    .collect()
+            }
+            Expr::Binary(op, lhs, rhs) => {
+                let lhs = self.evaluate(lhs, state, word)?;
+                let rhs = self.evaluate(rhs, state, word)?;
+                if lhs.len() * rhs.len() > MAX_VALUES {
+                    return None;
+                }
+
+                let mut values = Vec::new();
+                for &lhs in lhs.iter() {
+                    for &rhs in rhs.iter() {
+                        values.push(binary(*op, lhs, rhs)?);
+                    }
+                }
+                values
+            }
+            Expr::Truncate(value, size) => {
+                let values = self.evaluate(value, state, word)?;
+                values.into_iter().map(|value| value & mask(*size)).collect()
+            }
+            Expr::SignExtend(value, size) => {
+                let values = self.evaluate(value, state, word)?;
+                values.into_iter().map(|value| sign_extend(value, *size)).collect()
+            }
+            Expr::Load { .. } | Expr::Cond(..) | Expr::Select(..) | Expr::Unknown => return None,
+        };
+
+        let mut values: Vec<u64> = values.into_iter().map(|value| value & mask(word)).collect();
+        values.sort_unstable();
+        values.dedup();
+        Some(values)
+    }
+
+    /// Targets of the indirect calls and jumps of the function in between two addresses.
+    fn resolve_function(&self, start: PhysAddr, end: PhysAddr, resolved: &mut Resolved) {
+        let word = self.arch.address_size().map_or(8, |size| size.bytes());
+        let saved = callee_saved(self.arch);
+
+        let mut insts = Vec::new();
+        for instruction in self.instructions_from(start) {
+            if instruction.addr >= end {
+                break;
+            }
+
+            if insts.len() == MAX_INSTRUCTIONS {
+                return;
+            }
+
+            insts.push((instruction.addr, self.instruction_ir(&instruction.item)));
+        }
+
+        // registers written in a loop aren't known at its start, as the state at the end of the
+        // loop isn't known yet when getting there
+        let mut loops: BTreeMap<PhysAddr, BTreeSet<&'static str>> = BTreeMap::new();
+        for (idx, (addr, stmts)) in insts.iter().enumerate() {
+            for stmt in stmts.iter() {
+                let target = match stmt {
+                    Stmt::Jump(Expr::Const(target))
+                    | Stmt::Branch {
+                        target: Expr::Const(target),
+                        ..
+                    } => *target as PhysAddr,
+                    _ => continue,
+                };
+
+                if target > *addr || target < start {
+                    continue;
+                }
+
+                let first = insts.partition_point(|(addr, _)| *addr < target);
+                let written = loops.entry(target).or_default();
+                for (_, stmts) in &insts[first..=idx] {
+                    for stmt in stmts.iter() {
+                        if let Stmt::Assign { dst, .. } = stmt {
+                            written.insert(dst.name);
+                        }
+                    }
+                }
+            }
+        }
+
+        // states of forward branches, by where they go
+        let mut pending: BTreeMap<PhysAddr, State> = BTreeMap::new();
+        let mut state = Some(State::new());
+
+        for (addr, stmts) in insts.iter() {
+            if let Some(branched) = pending.remove(addr) {
+                state = Some(match state {
+                    Some(state) => merge(&state, &branched),
+                    None => branched,
+                });
+            }
+
+            if let Some(written) = loops.get(addr) {
+                if let Some(state) = state.as_mut() {
+                    state.retain(|reg, _| !written.contains(reg));
+                }
+            }
+
+            // unreachable unless jumped to from somewhere we don't know
+            let current = state.get_or_insert_with(State::new);
+
+            for stmt in stmts.iter() {
+                match stmt {
+                    Stmt::Assign { dst, src } => {
+                        let values = self.evaluate(src, current, word);
+                        assign(current, dst, values, word);
+                    }
+                    Stmt::Call(target) => {
+                        if !matches!(target, Expr::Const(..)) {
+                            if let Some(values) = self.evaluate(target, current, word) {
+                                let targets = values.into_iter().map(|value| value as PhysAddr);
+                                resolved.calls.extend(targets.map(|target| (*addr, target)));
+                            }
+                        }
+
+                        current.retain(|reg, _| saved.contains(reg));
+                    }
+                    Stmt::Jump(target) | Stmt::Branch { target, .. } => {
+                        if let Expr::Const(target) = target {
+                            let target = *target as PhysAddr;
+                            if target > *addr && target < end {
+                                let state = match pending.remove(&target) {
+                                    Some(other) => merge(current, &other),
+                                    None => current.clone(),
+                                };
+                                pending.insert(target, state);
+                            }
+                        } else if let Some(values) = self.evaluate(target, current, word) {
+                            let targets = values.into_iter().map(|value| value as PhysAddr);
+                            resolved.jumps.extend(targets.map(|target| (*addr, target)));
+                        }
+                    }
+                    Stmt::Store { .. } | Stmt::Flags(..) | Stmt::Unknown => {}
+                    Stmt::Return | Stmt::Halt => {}
+                }
+            }
+
+            // execution doesn't fall through to the next instruction
+            let exits = stmts
+                .iter()
+                .any(|stmt| matches!(stmt, Stmt::Jump(..) | Stmt::Return | Stmt::Halt));
+            if exits {
+                state = None;
+            }
+        }
+    }
+
+    /// Targets of indirect calls and jumps of every function, that are known without running it.
+    pub(crate) fn resolve_indirect(&self) -> Resolved {
+        let mut resolved = Resolved::default();
+        let mut starts: Vec<PhysAddr> = self.index.functions().map(|func| func.addr).collect();
+        starts.dedup();
+
+        for start in starts {
+            if !self.section_by_addr(start).is_some_and(|s| s.kind == SectionKind::Code) {
+                continue;
+            }
+
+            if let Some((addr, end, _)) = self.function_bounds(start) {
+                if addr == start {
+                    self.resolve_function(start, end, &mut resolved);
+                }
+            }
+        }
+
+        // only targets that are code or an import can be called
+        let is_target = |target: PhysAddr| match self.section_by_addr(target) {
+            Some(section) => matches!(
+                section.kind,
+                SectionKind::Code | SectionKind::Got32 | SectionKind::Got64
+            ),
+            None => false,
+        };
+
+        resolved.calls.retain(|&(_, target)| is_target(target));
+        resolved.jumps.retain(|&(_, target)| is_target(target));
+        resolved.calls.sort_unstable();
+        resolved.calls.dedup();
+        resolved.jumps.sort_unstable();
+        resolved.jumps.dedup();
+        resolved
+    }
+
+    /// Where an indirect call or jump goes, if it was resolved by [`propagation`].
+    ///
+    /// [`propagation`]: crate::propagation
+    pub fn indirect_targets(&self, addr: PhysAddr) -> &[PhysAddr] {
+        self.indirect.get(&addr).map_or(&[], |targets| &targets[..])
+    }
+}
@@ -0,0 +1,426 @@
+//! Inference of how functions receive their arguments.
+//!
+//! A register that passes arguments and is read before the function writes to it most likely
+//! holds an argument, as does stack memory above the return address. On 32-bit x86, where
+//! several conventions are in use, the registers read and the bytes popped by `ret` tell the
+//! conventions apart.
+
+use crate::emulation::convention;
+use crate::function_stats::{stack_adjustment, STACK_POINTERS};
+use crate::Processor;
+use decoder::ir::{BinaryOp, Cond, Expr, Flags, Reg, Stmt};
+use processor_shared::PhysAddr;
+
+/// Registers holding the frame pointer.
+const FRAME_POINTERS: &[&str] = &["rbp", "ebp"];
+
+/// Registers passing arguments to `fastcall` and `thiscall` functions on 32-bit x86.
+const FASTCALL_ARGS: &[&str] = &["ecx", "edx"];
+
+/// Stack slots above which reads are considered locals of a caller instead of arguments.
+const MAX_STACK_ARGS: u64 = 16;
+
+/// Instructions looked at when the end of a function isn't known.
+const MAX_INSTRUCTIONS: usize = 4096;
+
+/// Instructions looked at before a call for the values of its arguments.
+const MAX_LOOKBEHIND: usize = 16;
+
+/// Approximate signature of a function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Prototype {
+    /// Calling convention the function most likely follows.
+    pub convention: &'static str,
+    /// Number of arguments, counting the registers and stack slots the function reads.
+    pub args: usize,
+    /// Bytes of arguments the function pops off the stack when returning.
+    pub cleanup: u64,
+}
+
+/// Call `f` on every register an expression reads.
+fn visit_expr(expr: &Expr, f: &mut impl FnMut(&Reg)) {
+    match expr {
+        Expr::Reg(reg) => f(reg),
+        // zeroing idioms such as `xor eax, eax` don't depend on the register's value
+        Expr::Binary(BinaryOp::Xor | BinaryOp::Sub, lhs, rhs) if lhs == rhs => {}
+        Expr::Load { addr, .. } => visit_expr(addr, f),
+        Expr::Unary(_, expr) | Expr::Truncate(expr, _) | Expr::SignExtend(expr, _) => {
+            visit_expr(expr, f)
+        }
+        Expr::Binary(_, lhs, rhs) => {
+            visit_expr(lhs, f);
+            visit_expr(rhs, f);
+        }
+        Expr::Cond(cond) => visit_cond(cond, f),
+        Expr::Select(cond, then, otherwise) => {
+            visit_cond(cond, f);
+            visit_expr(then, f);
+            visit_expr(otherwise, f);
+        }
+        Expr::Const(..) | Expr::Unknown => {}
+    }
+}
+
+fn visit_cond(cond: &Cond, f: &mut impl FnMut(&Reg)) {
+    if let Cond::Compare(_, lhs, rhs) = cond {
+        visit_expr(lhs, f);
+        visit_expr(rhs, f);
+    }
+}
+
+/// Call `f` on every register a statement reads.
+fn visit_stmt(stmt: &Stmt, f: &mut impl FnMut(&Reg)) {
+    match stmt {
+        Stmt::Assign { src, .. } => visit_expr(src, f),
+        Stmt::Store { addr, src, .. } => {
+            visit_expr(addr, f);
+            visit_expr(src, f);
+        }
+        Stmt::Flags(Flags::Compare(lhs, rhs) | Flags::Test(lhs, rhs)) => {
+            visit_expr(lhs, f);
+            visit_expr(rhs, f);
+        }
+        Stmt::Flags(Flags::Result(expr)) | Stmt::Jump(expr) | Stmt::Call(expr) => {
+            visit_expr(expr, f)
+        }
+        Stmt::Branch { cond, target } => {
+            visit_cond(cond, f);
+            visit_expr(target, f);
+        }
+        Stmt::Flags(Flags::Unknown) | Stmt::Return | Stmt::Halt | Stmt::Unknown => {}
+    }
+}
+
+/// Call `f` on the address of every load of an expression.
+pub(crate) fn visit_loads(expr: &Expr, f: &mut impl FnMut(&Expr)) {
+    match expr {
+        Expr::Load { addr, .. } => {
+            f(addr);
+            visit_loads(addr, f);
+        }
+        Expr::Unary(_, expr) | Expr::Truncate(expr, _) | Expr::SignExtend(expr, _) => {
+            visit_loads(expr, f)
+        }
+        Expr::Binary(_, lhs, rhs) | Expr::Select(_, lhs, rhs) => {
+            visit_loads(lhs, f);
+            visit_loads(rhs, f);
+        }
+        Expr::Const(..) | Expr::Reg(..) | Expr::Cond(..) | Expr::Unknown => {}
+    }
+}
+
+/// Register and offset an address is relative to, e.g. `rsp` and `0x10` for `[rsp + 0x10]`.
+pub(crate) fn relative(addr: &Expr) -> Option<(&'static str, i64)> {
+    match addr {
+        Expr::Reg(reg) => Some((reg.name, 0)),
+        Expr::Binary(BinaryOp::Add, lhs, rhs) => match (&**lhs, &**rhs) {
+            (Expr::Reg(reg), Expr::Const(offset)) => Some((reg.name, *offset as i64)),
+            _ => None,
+        },
+        Expr::Binary(BinaryOp::Sub, lhs, rhs) => match (&**lhs, &**rhs) {
+            (Expr::Reg(reg), Expr::Const(offset)) => Some((reg.name, -(*offset as i64))),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Operand of a `ret imm16`, the bytes of arguments a function pops off the stack.
+fn ret_operand(text: &str) -> u64 {
+    let operand = text.split_whitespace().nth(1).unwrap_or("");
+    match operand.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).unwrap_or(0),
+        None => operand.parse().unwrap_or(0),
+    }
+}
+
+impl Processor {
+    /// Approximate signature of the function starting at an address, see [`prototypes`].
+    ///
+    /// [`prototypes`]: crate::prototypes
+    pub(crate) fn infer_prototype(&self, start: PhysAddr) -> Option<Prototype> {
+        let convention = convention(self.arch, self.format)?;
+        let end = match self.function_bounds(start) {
+            Some((addr, end, _)) if addr == start => Some(end),
+            _ => None,
+        };
+
+        let word = self.arch.address_size().map_or(8, |size| size.bytes() as i64);
+        let registers = match convention.args {
+            [] => FASTCALL_ARGS,
+            args => args,
+        };
+
+        // stack arguments start past the return address, unless it's passed in a register
+        let return_addr = if convention.link.is_some() { 0 } else { word };
+        let stack_base = return_addr + convention.shadow as i64;
+        let frame_base = stack_base + word;
+
+        let mut read = vec![false; registers.len()];
+        let mut written = vec![false; registers.len()];
+        let mut stack_slots = 0i64;
+        let mut cleanup = 0u64;
+        let mut depth = 0i64;
+
+        for instruction in self.instructions_from(start).take(MAX_INSTRUCTIONS) {
+            if end.is_some_and(|end| instruction.addr >= end) {
+                break;
+            }
+
+            let stmts = self.instruction_ir(&instruction.item);
+            for stmt in stmts.iter() {
+                visit_stmt(stmt, &mut |reg| {
+                    if let Some(idx) = registers.iter().position(|name| *name == reg.name) {
+                        read[idx] |= !written[idx];
+                    }
+                });
+
+                let exprs = match stmt {
+                    Stmt::Assign { src, .. } => vec![src],
+                    Stmt::Store { addr, src, .. } => vec![addr, src],
+                    _ => Vec::new(),
+                };
+
+                for expr in exprs {
+                    visit_loads(expr, &mut |addr| {
+                        let offset = match relative(addr) {
+                            Some((reg, offset)) if STACK_POINTERS.contains(&reg) => {
+                                offset - depth - stack_base
+                            }
+                            Some((reg, offset)) if FRAME_POINTERS.contains(&reg) => {
+                                offset - frame_base
+                            }
+                            _ => return,
+                        };
+
+                        if offset >= 0 && (offset / word) < MAX_STACK_ARGS as i64 {
+                            stack_slots = stack_slots.max(offset / word + 1);
+                        }
+                    });
+                }
+
+                match stmt {
+                    Stmt::Assign { dst, .. } => {
+                        if let Some(idx) = registers.iter().position(|name| *name == dst.name) {
+                            written[idx] = true;
+                        }
+                    }
+                    // whatever the callee leaves in argument registers isn't an argument
+                    Stmt::Call(..) => written.iter_mut().for_each(|written| *written = true),
+                    Stmt::Return if convention.link.is_none() => {
+                        let tokens = self.instruction_tokens(&instruction.item, &self.index);
+                        let text = String::from_iter(tokens.iter().map(|t| &t.text[..]));
+                        cleanup = cleanup.max(ret_operand(&text));
+                    }
+                    _ => {}
+                }
+
+                depth += stack_adjustment(stmt);
+            }
+
+            let returns = stmts.iter().any(|stmt| matches!(stmt, Stmt::Return | Stmt::Halt));
+            if end.is_none() && returns {
+                break;
+            }
+        }
+
+        let stack_args = std::cmp::max(stack_slots as usize, (cleanup / word as u64) as usize);
+        let (name, reg_args) = match convention.args {
+            [] => match (read[0], read[1]) {
+                (true, true) => ("fastcall", 2),
+                (true, false) => ("thiscall", 1),
+                _ if cleanup > 0 => ("stdcall", 0),
+                _ => (convention.name, 0),
+            },
+            args if stack_args > 0 => (convention.name, args.len()),
+            // arguments are assigned to registers in order, so unread ones still count
+            _ => match read.iter().rposition(|read| *read) {
+                Some(idx) => (convention.name, idx + 1),
+                None => (convention.name, 0),
+            },
+        };
+
+        Some(Prototype {
+            convention: name,
+            args: reg_args + stack_args,
+            cleanup,
+        })
+    }
+
+    /// Signature of the function starting at an address, inferring it if it wasn't called by
+    /// any other function.
+    pub fn prototype(&self, addr: PhysAddr) -> Option<Prototype> {
+        match self.prototypes.get(&addr) {
+            Some(prototype) => Some(*prototype),
+            None => self.infer_prototype(addr),
+        }
+    }
+
+    /// Values passed to the function called by the instruction at an address, found by looking
+    /// for the last writes to its arguments before the call. Arguments of functions from the
+    /// [`typelib`] are prefixed by their name.
+    ///
+    /// [`typelib`]: crate::typelib
+    pub fn call_arguments(&self, addr: PhysAddr) -> Option<Vec<String>> {
+        let instruction = self.instruction_by_addr(addr)?;
+        let target = self.instruction_ir(&instruction).into_iter().find_map(|stmt| match stmt {
+            Stmt::Call(Expr::Const(target)) => Some(target as PhysAddr),
+            // imports are called through their table entry
+            Stmt::Call(..) => self.indirect_targets(addr).first().copied(),
+            _ => None,
+        })?;
+
+        let convention = convention(self.arch, self.format)?;
+        let api = self.api_prototype(target);
+        let prototype = match api {
+            Some(api) => Prototype {
+                convention: convention.name,
+                args: api.params.len(),
+                cleanup: 0,
+            },
+            None => *self.prototypes.get(&target)?,
+        };
+
+        if prototype.args == 0 {
+            return None;
+        }
+
+        let registers = match prototype.convention {
+            "fastcall" => &FASTCALL_ARGS[..2],
+            "thiscall" => &FASTCALL_ARGS[..1],
+            _ => convention.args,
+        };
+
+        let word = self.arch.address_size().map_or(8, |size| size.bytes() as i64);
+        let reg_args = std::cmp::min(prototype.args, registers.len());
+        let mut values: Vec<Option<String>> = vec![None; prototype.args];
+        let mut pushes = 0;
+
+        let start = self.function_bounds(addr).map_or(0, |(start, ..)| start);
+        let mut cursor = addr;
+        for _ in 0..MAX_LOOKBEHIND {
+            let prev = match cursor.checked_sub(1) {
+                Some(prev) => self.instruction_at_or_before(prev),
+                None => None,
+            };
+
+            let prev = match prev {
+                Some(prev) => prev,
+                None => break,
+            };
+
+            if prev.addr < start {
+                break;
+            }
+
+            cursor = prev.addr;
+            let stmts = self.instruction_ir(&prev.item);
+            let control_flow = stmts.iter().any(|stmt| {
+                matches!(
+                    stmt,
+                    Stmt::Jump(..)
+                        | Stmt::Branch { .. }
+                        | Stmt::Call(..)
+                        | Stmt::Return
+                        | Stmt::Halt
+                )
+            });
+
+            if control_flow {
+                break;
+            }
+
+            for stmt in stmts.iter() {
+                let (slot, src) = match stmt {
+                    Stmt::Assign { dst, src } => {
+                        match registers[..reg_args].iter().position(|name| *name == dst.name) {
+                            Some(idx) => (idx, src),
+                            None => continue,
+                        }
+                    }
+                    // arguments on the stack are either pushed or moved to where the stack
+                    // pointer points to
+                    Stmt::Store { addr, src, .. } if convention.args.is_empty() => {
+                        match relative(addr) {
+                            Some((reg, offset)) if STACK_POINTERS.contains(&reg) && offset < 0 => {
+                                pushes += 1;
+                                (reg_args + pushes - 1, src)
+                            }
+                            Some((reg, offset)) if STACK_POINTERS.contains(&reg) => {
+                                (reg_args + (offset / word) as usize, src)
+                            }
+                            _ => continue,
+                        }
+                    }
+                    _ => continue,
+                };
+
+                if let Some(value) = values.get_mut(slot) {
+                    value.get_or_insert_with(|| {
+                        let symbolic = match src {
+                            Expr::Const(constant) => self
+                                .argument_enum(prev.addr, api, slot)
+                                .and_then(|def| def.render(*constant)),
+                            _ => None,
+                        };
+
+                        symbolic.unwrap_or_else(|| self.render_expr(src))
+                    });
+                }
+            }
+        }
+
+        let stack_passed = !convention.args.is_empty() && prototype.args > registers.len();
+        let shown = if stack_passed {
+            reg_args
+        } else {
+            prototype.args
+        };
+        let mut args: Vec<String> = values
+            .into_iter()
+            .enumerate()
+            .take(shown)
+            .map(|(idx, value)| {
+                let value = match value {
+                    Some(value) => value,
+                    None => registers.get(idx).map_or("?", |name| *name).to_string(),
+                };
+
+                match api {
+                    Some(api) => format!("{}={value}", api.params[idx].name),
+                    None => value,
+                }
+            })
+            .collect();
+
+        if stack_passed || api.is_some_and(|api| api.variadic) {
+            args.push("..".to_string());
+        }
+
+        Some(args)
+    }
+
+    /// Short textual form of an argument's value.
+    fn render_expr(&self, expr: &Expr) -> String {
+        let binary = |op: &str, lhs: &Expr, rhs: &Expr| {
+            format!("{} {op} {}", self.render_expr(lhs), self.render_expr(rhs))
+        };
+
+        match expr {
+            Expr::Const(value) => match self.index.get_sym_by_addr(*value as PhysAddr) {
+                Some(symbol) => symbol.as_str().to_string(),
+                None if *value < 10 => value.to_string(),
+                None => format!("{value:#x}"),
+            },
+            Expr::Reg(reg) => reg.name.to_string(),
+            Expr::Load { addr, .. } => format!("[{}]", self.render_expr(addr)),
+            Expr::Truncate(expr, _) | Expr::SignExtend(expr, _) => self.render_expr(expr),
+            Expr::Binary(BinaryOp::Xor | BinaryOp::Sub, lhs, rhs) if lhs == rhs => "0".to_string(),
+            Expr::Binary(BinaryOp::Add, lhs, rhs) => binary("+", lhs, rhs),
+            Expr::Binary(BinaryOp::Sub, lhs, rhs) => binary("-", lhs, rhs),
+            Expr::Binary(BinaryOp::Mul, lhs, rhs) => binary("*", lhs, rhs),
+            Expr::Binary(BinaryOp::Shl, lhs, rhs) => binary("<<", lhs, rhs),
+            _ => "?".to_string(),
+        }
+    }
+}
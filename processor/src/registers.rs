@@ -0,0 +1,138 @@
+//! Alternative names of registers, see [`RegisterNames`].
+
+use config::CONFIG;
+use object::{Architecture, BinaryFormat};
+use processor_shared::RegisterNames;
+use tokenizing::{MaybeStatic, Token, TokenKind};
+
+/// RISC-V registers as they're named by the decoder, in the order they're encoded.
+#[rustfmt::skip]
+const RISCV_ABI: [&str; 64] = [
+    "zero", "ra", "sp", "gp", "tp", "t0", "t1", "t2", "s0", "s1", "a0", "a1", "a2", "a3", "a4",
+    "a5", "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11", "t3", "t4",
+    "t5", "t6",
+    "ft0", "ft1", "ft2", "ft3", "ft4", "ft5", "ft6", "ft7", "fs0", "fs1", "fa0", "fa1", "fa2",
+    "fa3", "fa4", "fa5", "fa6", "fa7", "fs2", "fs3", "fs4", "fs5", "fs6", "fs7", "fs8", "fs9",
+    "fs10", "fs11", "ft8", "ft9", "ft10", "ft11",
+];
+
+#[rustfmt::skip]
+const RISCV_ARCH: [&str; 64] = [
+    "x0", "x1", "x2", "x3", "x4", "x5", "x6", "x7", "x8", "x9", "x10", "x11", "x12", "x13", "x14",
+    "x15", "x16", "x17", "x18", "x19", "x20", "x21", "x22", "x23", "x24", "x25", "x26", "x27",
+    "x28", "x29", "x30", "x31",
+    "f0", "f1", "f2", "f3", "f4", "f5", "f6", "f7", "f8", "f9", "f10", "f11", "f12", "f13", "f14",
+    "f15", "f16", "f17", "f18", "f19", "f20", "f21", "f22", "f23", "f24", "f25", "f26", "f27",
+    "f28", "f29", "f30", "f31",
+];
+
+/// MIPS registers in the order they're encoded, the decoder names them like [`mips::REGISTERS`].
+#[rustfmt::skip]
+const MIPS_ARCH: [&str; 32] = [
+    "$0", "$1", "$2", "$3", "$4", "$5", "$6", "$7", "$8", "$9", "$10", "$11", "$12", "$13", "$14",
+    "$15", "$16", "$17", "$18", "$19", "$20", "$21", "$22", "$23", "$24", "$25", "$26", "$27",
+    "$28", "$29", "$30", "$31",
+];
+
+/// ARM registers that the decoder names by their alias, as `(alias, architectural name)`.
+const ARMV7_ALIASES: [(&str, &str); 6] = [
+    ("sb", "r9"),
+    ("fp", "r11"),
+    ("ip", "r12"),
+    ("sp", "r13"),
+    ("lr", "r14"),
+    ("pc", "r15"),
+];
+
+/// AArch64 registers that have an alias, as `(architectural name, alias)`.
+const AARCH64_ALIASES: [(&str, &str); 2] = [("x29", "fp"), ("x30", "lr")];
+
+/// Registers used for passing arguments in the System V ABI, including their partial registers.
+const SYSV_ARGS: [&[&str]; 6] = [
+    &["rdi", "edi", "di", "dil"],
+    &["rsi", "esi", "si", "sil"],
+    &["rdx", "edx", "dx", "dl"],
+    &["rcx", "ecx", "cx", "cl"],
+    &["r8", "r8d", "r8w", "r8b"],
+    &["r9", "r9d", "r9w", "r9b"],
+];
+
+/// Registers used for passing arguments in the Windows x64 ABI, including their partial registers.
+const WIN64_ARGS: [&[&str]; 4] = [
+    &["rcx", "ecx", "cx", "cl"],
+    &["rdx", "edx", "dx", "dl"],
+    &["r8", "r8d", "r8w", "r8b"],
+    &["r9", "r9d", "r9w", "r9b"],
+];
+
+/// Annotations of the registers in [`SYSV_ARGS`] and [`WIN64_ARGS`].
+const ARG_ANNOTATIONS: [&str; 6] = ["(arg1)", "(arg2)", "(arg3)", "(arg4)", "(arg5)", "(arg6)"];
+
+/// Name of a register in the given style, if it differs from the one used by the decoder.
+fn rename(arch: Architecture, names: RegisterNames, reg: &str) -> Option<&'static str> {
+    match (arch, names) {
+        (Architecture::Riscv32 | Architecture::Riscv64, RegisterNames::Architectural) => {
+            let idx = RISCV_ABI.iter().position(|name| *name == reg)?;
+            Some(RISCV_ARCH[idx])
+        }
+        (Architecture::Mips | Architecture::Mips64, RegisterNames::Architectural) => {
+            let idx = mips::REGISTERS.iter().position(|name| *name == reg)?;
+            Some(MIPS_ARCH[idx])
+        }
+        (Architecture::Arm, RegisterNames::Architectural) => {
+            let (_, name) = ARMV7_ALIASES.iter().find(|(alias, _)| *alias == reg)?;
+            Some(name)
+        }
+        (Architecture::Aarch64 | Architecture::Aarch64_Ilp32, RegisterNames::Abi) => {
+            let (_, alias) = AARCH64_ALIASES.iter().find(|(name, _)| *name == reg)?;
+            Some(alias)
+        }
+        _ => None,
+    }
+}
+
+/// Apply the register naming style to the tokens of an instruction.
+pub fn apply(
+    tokens: Vec<Token>,
+    arch: Architecture,
+    format: BinaryFormat,
+    names: RegisterNames,
+) -> Vec<Token> {
+    if let (Architecture::X86_64, RegisterNames::Abi) = (arch, names) {
+        let args: &[&[&str]] = match format {
+            BinaryFormat::Pe => &WIN64_ARGS,
+            _ => &SYSV_ARGS,
+        };
+
+        return annotate(tokens, args);
+    }
+
+    let mut tokens = tokens;
+    for token in tokens.iter_mut().filter(|token| token.kind == TokenKind::Register) {
+        if let Some(name) = rename(arch, names, &token.text) {
+            token.text = MaybeStatic::Static(name);
+        }
+    }
+
+    tokens
+}
+
+/// Follow each register that passes an argument by the argument it holds.
+fn annotate(tokens: Vec<Token>, args: &[&[&str]]) -> Vec<Token> {
+    let mut annotated = Vec::with_capacity(tokens.len());
+
+    for token in tokens {
+        let arg = match token.kind {
+            TokenKind::Register => args.iter().position(|regs| regs.contains(&&*token.text)),
+            _ => None,
+        };
+
+        annotated.push(token);
+        if let Some(arg) = arg {
+            let annotation = ARG_ANNOTATIONS[arg];
+            annotated.push(Token::from_str(annotation, CONFIG.colors.asm.annotation));
+        }
+    }
+
+    annotated
+}
@@ -0,0 +1,193 @@
+//! The Rich header Microsoft's linker places between the DOS stub and the PE header, which lists the
+//! tools that produced the objects the binary is linked from.
+//!
+//! It's XOR encoded with a checksum of the DOS header and of its own entries, so a key that doesn't
+//! match the checksum means the header was edited or copied from another binary. As the header
+//! describes the build environment, its hash is shared between binaries built the same way.
+
+use crate::hashes;
+use md5::{Digest, Md5};
+use object::read::File as ObjectFile;
+
+/// Marks the start of the header once it's decoded.
+const DANS: u32 = u32::from_le_bytes(*b"DanS");
+
+/// Marks the end of the header, followed by the key.
+const RICH: &[u8] = b"Rich";
+
+/// Offset of the field pointing to the PE header, which the checksum leaves out.
+const E_LFANEW: usize = 0x3C;
+
+/// Padding between the start marker and the first entry.
+const PADDING: usize = 12;
+
+/// First product ID of the tools of Visual Studio 2012, from where every version has the same tools.
+const VS2012: u16 = 0x00C7;
+
+/// First product ID of Visual Studio 2015, which later versions kept.
+const VS2015: u16 = 0x00FD;
+
+/// Tools a version of Visual Studio starting with 2012 ships, in the order of their product ID.
+const TOOLS: [&str; 18] = [
+    "Alias object",
+    "PGO converter",
+    "Resource converter",
+    "Exports",
+    "Import library",
+    "Linker",
+    "Assembler",
+    "C compiler",
+    "C++ compiler",
+    "C compiler (CIL)",
+    "C++ compiler (CIL)",
+    "C compiler (LTCG)",
+    "C++ compiler (LTCG)",
+    "MSIL compiler (LTCG)",
+    "C compiler (PGO instrumented)",
+    "C++ compiler (PGO instrumented)",
+    "C compiler (PGO optimized)",
+    "C++ compiler (PGO optimized)",
+];
+
+/// First product ID of every version of Visual Studio.
+const VERSIONS: &[(u16, &str)] = &[
+    (0x0006, "Visual Studio 97/6.0"),
+    (0x005A, "Visual Studio 2003"),
+    (0x006D, "Visual Studio 2005"),
+    (0x0083, "Visual Studio 2008"),
+    (0x0098, "Visual Studio 2010"),
+    (VS2012, "Visual Studio 2012"),
+    (0x00D9, "Visual Studio 2013"),
+    (VS2015, "Visual Studio 2015"),
+];
+
+/// Versions of Visual Studio that kept the product IDs of 2015, by their first build number.
+const BUILDS: &[(u16, &str)] = &[
+    (25000, "Visual Studio 2017"),
+    (27500, "Visual Studio 2019"),
+    (30700, "Visual Studio 2022"),
+];
+
+/// Number of objects a single tool produced.
+#[derive(Debug, Clone, Copy)]
+pub struct RichEntry {
+    pub product: u16,
+    pub build: u16,
+    pub count: u32,
+}
+
+impl RichEntry {
+    fn comp_id(&self) -> u32 {
+        (self.product as u32) << 16 | self.build as u32
+    }
+
+    /// What the tool is, if the product ID is known.
+    pub fn tool(&self) -> Option<&'static str> {
+        match self.product {
+            0 => Some("Unmarked objects"),
+            1 => Some("Imports"),
+            product if product >= VS2012 => Some(TOOLS[(product - VS2012) as usize % TOOLS.len()]),
+            _ => None,
+        }
+    }
+
+    /// Version of Visual Studio the tool is from.
+    pub fn toolchain(&self) -> Option<&'static str> {
+        let (_, version) = VERSIONS.iter().rev().find(|(first, _)| self.product >= *first)?;
+        if self.product < VS2015 {
+            return Some(version);
+        }
+
+        let newer = BUILDS.iter().rev().find(|(first, _)| self.build >= *first);
+        Some(newer.map_or(version, |(_, version)| version))
+    }
+}
+
+/// Decoded Rich header.
+#[derive(Debug, Clone)]
+pub struct RichHeader {
+    /// Offset into the file.
+    pub offset: usize,
+    /// Value the header is XOR encoded with.
+    pub key: u32,
+    /// Whether the key matches the checksum of the DOS header and the entries.
+    pub valid: bool,
+    pub entries: Vec<RichEntry>,
+    /// MD5 of the decoded header, which is shared by binaries built by the same toolchain from the
+    /// same objects.
+    pub hash: String,
+}
+
+impl RichHeader {
+    /// Version of Visual Studio the binary was linked with.
+    pub fn linker(&self) -> Option<&'static str> {
+        self.entries
+            .iter()
+            .find(|entry| entry.tool() == Some("Linker"))
+            .and_then(RichEntry::toolchain)
+    }
+}
+
+fn u32_le(binary: &[u8], offset: usize) -> Option<u32> {
+    let bytes = binary.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+fn checksum(binary: &[u8], offset: usize, entries: &[RichEntry]) -> u32 {
+    let mut checksum = offset as u32;
+    for (idx, &byte) in binary[..offset].iter().enumerate() {
+        if !(E_LFANEW..E_LFANEW + 4).contains(&idx) {
+            checksum = checksum.wrapping_add((byte as u32).rotate_left(idx as u32));
+        }
+    }
+
+    for entry in entries {
+        checksum = checksum.wrapping_add(entry.comp_id().rotate_left(entry.count));
+    }
+
+    checksum
+}
+
+/// Rich header of a PE, if it has one.
+pub fn parse(obj: &ObjectFile, binary: &[u8]) -> Option<RichHeader> {
+    if !matches!(obj, ObjectFile::Pe32(_) | ObjectFile::Pe64(_)) {
+        return None;
+    }
+
+    // the header sits between the DOS stub and the PE header
+    let pe_header = u32_le(binary, E_LFANEW)? as usize;
+    let stub = binary.get(E_LFANEW + 4..pe_header)?;
+    let end = E_LFANEW + 4 + stub.windows(RICH.len()).rposition(|window| window == RICH)?;
+    let key = u32_le(binary, end + RICH.len())?;
+
+    // the start is found by walking back from the end until a word decodes to the marker
+    let offset = (1..=(end - E_LFANEW - 4) / 4)
+        .map(|words| end - words * 4)
+        .find(|&offset| u32_le(binary, offset).map(|word| word ^ key) == Some(DANS))?;
+
+    let clear: Vec<u8> = binary[offset..end]
+        .chunks_exact(4)
+        .flat_map(|word| (u32::from_le_bytes(word.try_into().unwrap()) ^ key).to_le_bytes())
+        .collect();
+
+    let entries: Vec<RichEntry> = clear
+        .get(4 + PADDING..)?
+        .chunks_exact(8)
+        .map(|entry| {
+            let comp_id = u32::from_le_bytes(entry[..4].try_into().unwrap());
+            RichEntry {
+                product: (comp_id >> 16) as u16,
+                build: comp_id as u16,
+                count: u32::from_le_bytes(entry[4..].try_into().unwrap()),
+            }
+        })
+        .collect();
+
+    Some(RichHeader {
+        offset,
+        key,
+        valid: checksum(binary, offset, &entries) == key,
+        entries,
+        hash: hashes::hex(&Md5::digest(&clear)),
+    })
+}
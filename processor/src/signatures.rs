@@ -0,0 +1,597 @@
+//! Recognition of library functions by their leading bytes, in the style of IDA's FLIRT.
+//!
+//! Signatures are loaded from the `signatures` folder in bite's data directory. Both FLIRT `.sig`
+//! files and a simple text format are supported. Each line of the text format has a pattern of hex
+//! bytes, where `..` matches any byte, followed by the name of the function it matches:
+//!
+//! ```text
+//! # memset from some libc
+//! 4889F8 4885D2 74.. 4088 memset
+//! ```
+
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Number of leading bytes of a module that are matched by a FLIRT pattern.
+const FLIRT_PATTERN_LEN: usize = 32;
+
+const FLIRT_MAGIC: &[u8] = b"IDASGN";
+const FEATURE_COMPRESSED: u16 = 0x10;
+
+const PARSE_MORE_PUBLIC_NAMES: u8 = 0x01;
+const PARSE_READ_TAIL_BYTES: u8 = 0x02;
+const PARSE_READ_REFERENCED_FUNCTIONS: u8 = 0x04;
+const PARSE_MORE_MODULES_WITH_SAME_CRC: u8 = 0x08;
+const PARSE_MORE_MODULES: u8 = 0x10;
+
+#[derive(Debug)]
+pub enum Error {
+    IO(std::io::Error),
+    /// The file ended before the signatures did.
+    Truncated,
+    UnsupportedVersion(u8),
+    /// Compressed signatures failed to decompress.
+    Decompression(std::io::Error),
+    /// Line of the text format that isn't a valid signature.
+    InvalidLine(usize),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IO(err) => f.write_fmt(format_args!("{err}")),
+            Self::Truncated => f.write_str("Unexpected end of file."),
+            Self::UnsupportedVersion(version) => {
+                f.write_fmt(format_args!("FLIRT version {version} isn't supported."))
+            }
+            Self::Decompression(err) => {
+                f.write_fmt(format_args!("Failed to decompress signatures: {err}."))
+            }
+            Self::InvalidLine(line) => f.write_fmt(format_args!("Line {line} isn't a signature.")),
+        }
+    }
+}
+
+/// Leading bytes of a module and the functions it contains.
+#[derive(Debug)]
+struct Signature {
+    /// Leading bytes of the module, where `None` matches any byte.
+    pattern: Vec<Option<u8>>,
+    /// Number of bytes following the first [`FLIRT_PATTERN_LEN`] bytes and their checksum.
+    crc: Option<(usize, u16)>,
+    /// Bytes at an offset into the module that must match.
+    tail: Vec<(usize, u8)>,
+    /// Public functions of the module by their offset into it.
+    names: Vec<(usize, String)>,
+}
+
+impl Signature {
+    fn matches(&self, bytes: &[u8]) -> bool {
+        if bytes.len() < self.pattern.len() {
+            return false;
+        }
+
+        let mut pattern = self.pattern.iter().zip(bytes);
+        if pattern.any(|(expected, byte)| matches!(expected, Some(expected) if expected != byte)) {
+            return false;
+        }
+
+        if let Some((len, crc)) = self.crc {
+            match bytes.get(FLIRT_PATTERN_LEN..FLIRT_PATTERN_LEN + len) {
+                Some(bytes) if crc16(bytes) == crc => {}
+                _ => return false,
+            }
+        }
+
+        self.tail.iter().all(|(offset, value)| bytes.get(*offset) == Some(value))
+    }
+}
+
+/// Library of function signatures.
+#[derive(Debug, Default)]
+pub struct Signatures {
+    sigs: Vec<Signature>,
+    /// Hash of every file that signatures were loaded from.
+    digest: Sha256,
+}
+
+impl Signatures {
+    /// Directory that signatures are loaded from.
+    pub fn directory() -> Option<PathBuf> {
+        let mut dir = dirs::data_dir()?;
+        dir.push("bite");
+        dir.push("signatures");
+        Some(dir)
+    }
+
+    /// Load all signature files from [`Signatures::directory`].
+    pub fn load() -> Self {
+        let mut this = Self::default();
+        let entries = match Self::directory().map(std::fs::read_dir) {
+            Some(Ok(entries)) => entries,
+            _ => return this,
+        };
+
+        // files are hashed in the same order every time
+        let mut paths: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+        paths.sort_unstable();
+
+        for path in paths {
+            if let Err(err) = this.load_file(&path) {
+                log::complex!(
+                    w "[signatures::load] ",
+                    y format!("Failed to load {path:?}: {err}"),
+                );
+            }
+        }
+
+        if !this.sigs.is_empty() {
+            log::complex!(
+                w "[signatures::load] loaded ",
+                g this.sigs.len().to_string(),
+                w " signatures.",
+            );
+        }
+
+        this
+    }
+
+    /// Add the signatures in a FLIRT `.sig` file or a file in the text format.
+    pub fn load_file(&mut self, path: &Path) -> Result<(), Error> {
+        let bytes = std::fs::read(path).map_err(Error::IO)?;
+
+        let mut sigs = if bytes.starts_with(FLIRT_MAGIC) {
+            parse_flirt(&bytes)?
+        } else {
+            parse_text(&String::from_utf8_lossy(&bytes))?
+        };
+
+        self.sigs.append(&mut sigs);
+        self.digest.update((bytes.len() as u64).to_le_bytes());
+        self.digest.update(&bytes);
+        Ok(())
+    }
+
+    /// Hash of the loaded signatures, which changes whenever a signature file is added, removed
+    /// or modified.
+    pub fn digest(&self) -> [u8; 32] {
+        self.digest.clone().finalize().into()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sigs.is_empty()
+    }
+
+    /// Functions of the first module whose signature matches the bytes, by their offset into it.
+    pub fn find(&self, bytes: &[u8]) -> Option<&[(usize, String)]> {
+        let sig = self.sigs.iter().find(|sig| sig.matches(bytes))?;
+        Some(&sig.names)
+    }
+}
+
+fn parse_text(text: &str) -> Result<Vec<Signature>, Error> {
+    let mut sigs = Vec::new();
+
+    for (idx, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (pattern, name) =
+            line.rsplit_once(char::is_whitespace).ok_or(Error::InvalidLine(idx + 1))?;
+        let pattern: String = pattern.split_whitespace().collect();
+        if pattern.is_empty() || pattern.len() % 2 == 1 {
+            return Err(Error::InvalidLine(idx + 1));
+        }
+
+        let pattern = pattern
+            .as_bytes()
+            .chunks(2)
+            .map(|chunk| match chunk {
+                b".." => Ok(None),
+                hex => std::str::from_utf8(hex)
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                    .map(Some)
+                    .ok_or(Error::InvalidLine(idx + 1)),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        sigs.push(Signature {
+            pattern,
+            crc: None,
+            tail: Vec::new(),
+            names: vec![(0, name.to_string())],
+        });
+    }
+
+    Ok(sigs)
+}
+
+/// Checksum used by FLIRT for the bytes following a pattern.
+fn crc16(bytes: &[u8]) -> u16 {
+    if bytes.is_empty() {
+        return 0;
+    }
+
+    let mut crc: u16 = 0xffff;
+    for &byte in bytes {
+        let mut data = byte;
+        for _ in 0..8 {
+            crc = match (crc ^ data as u16) & 1 {
+                1 => (crc >> 1) ^ 0x8408,
+                _ => crc >> 1,
+            };
+            data >>= 1;
+        }
+    }
+
+    (!crc).swap_bytes()
+}
+
+/// Reader of the variable length integers used by FLIRT.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    version: u8,
+}
+
+impl Reader<'_> {
+    fn u8(&mut self) -> Result<u8, Error> {
+        let (byte, rest) = self.bytes.split_first().ok_or(Error::Truncated)?;
+        self.bytes = rest;
+        Ok(*byte)
+    }
+
+    fn u16(&mut self) -> Result<u16, Error> {
+        Ok(u16::from_be_bytes([self.u8()?, self.u8()?]))
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&[u8], Error> {
+        if self.bytes.len() < len {
+            return Err(Error::Truncated);
+        }
+
+        let (bytes, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(bytes)
+    }
+
+    /// Integer of at most 15 bits.
+    fn max_2_bytes(&mut self) -> Result<u32, Error> {
+        let byte = self.u8()? as u32;
+        match byte & 0x80 {
+            0 => Ok(byte),
+            _ => Ok(((byte & 0x7f) << 8) + self.u8()? as u32),
+        }
+    }
+
+    /// Integer of at most 32 bits.
+    fn multiple_bytes(&mut self) -> Result<u32, Error> {
+        let byte = self.u8()? as u32;
+
+        if byte & 0x80 != 0x80 {
+            return Ok(byte);
+        }
+
+        if byte & 0xc0 != 0xc0 {
+            return Ok(((byte & 0x7f) << 8) + self.u8()? as u32);
+        }
+
+        if byte & 0xe0 != 0xe0 {
+            let high = ((byte & 0x3f) << 24) + ((self.u8()? as u32) << 16);
+            return Ok(high + self.u16()? as u32);
+        }
+
+        Ok(((self.u16()? as u32) << 16) + self.u16()? as u32)
+    }
+
+    /// Offset into a module, which got wider in version 9.
+    fn offset(&mut self) -> Result<usize, Error> {
+        match self.version {
+            9.. => self.multiple_bytes().map(|offset| offset as usize),
+            _ => self.max_2_bytes().map(|offset| offset as usize),
+        }
+    }
+
+    /// Bytes matched by a node of the tree, where bits set in the mask match any byte.
+    fn pattern(&mut self, pattern: &mut Vec<Option<u8>>) -> Result<(), Error> {
+        let len = self.u8()? as usize;
+        let mask = match len {
+            0..=0xf => self.max_2_bytes()? as u64,
+            0x10..=0x20 => self.multiple_bytes()? as u64,
+            _ => ((self.multiple_bytes()? as u64) << 32) | self.multiple_bytes()? as u64,
+        };
+
+        for idx in (0..len).rev() {
+            match mask.checked_shr(idx as u32).unwrap_or(0) & 1 {
+                1 => pattern.push(None),
+                _ => pattern.push(Some(self.u8()?)),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn tree(
+        &mut self,
+        pattern: &mut Vec<Option<u8>>,
+        sigs: &mut Vec<Signature>,
+    ) -> Result<(), Error> {
+        let children = self.multiple_bytes()?;
+        if children == 0 {
+            return self.leaf(pattern, sigs);
+        }
+
+        for _ in 0..children {
+            let len = pattern.len();
+            self.pattern(pattern)?;
+            self.tree(pattern, sigs)?;
+            pattern.truncate(len);
+        }
+
+        Ok(())
+    }
+
+    fn leaf(&mut self, pattern: &[Option<u8>], sigs: &mut Vec<Signature>) -> Result<(), Error> {
+        loop {
+            let crc_len = self.u8()? as usize;
+            let crc = self.u16()?;
+
+            let flags = loop {
+                // length of the module
+                self.offset()?;
+
+                let (names, flags) = self.public_names()?;
+                let mut tail = Vec::new();
+
+                if flags & PARSE_READ_TAIL_BYTES != 0 {
+                    let count = if self.version >= 8 { self.u8()? } else { 1 };
+                    for _ in 0..count {
+                        let offset = FLIRT_PATTERN_LEN + crc_len + self.offset()?;
+                        tail.push((offset, self.u8()?));
+                    }
+                }
+
+                if flags & PARSE_READ_REFERENCED_FUNCTIONS != 0 {
+                    self.referenced_functions()?;
+                }
+
+                sigs.push(Signature {
+                    pattern: pattern.to_vec(),
+                    crc: Some((crc_len, crc)),
+                    tail,
+                    names,
+                });
+
+                if flags & PARSE_MORE_MODULES_WITH_SAME_CRC == 0 {
+                    break flags;
+                }
+            };
+
+            if flags & PARSE_MORE_MODULES == 0 {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Names of the functions in a module and the flags that follow them.
+    fn public_names(&mut self) -> Result<(Vec<(usize, String)>, u8), Error> {
+        let mut names = Vec::new();
+        let mut offset = 0;
+
+        loop {
+            offset += self.offset()?;
+
+            // names are optionally preceded by flags and terminated by the next flags
+            let mut byte = self.u8()?;
+            if byte < 0x20 {
+                byte = self.u8()?;
+            }
+
+            let mut name = Vec::new();
+            while byte >= 0x20 {
+                name.push(byte);
+                byte = self.u8()?;
+            }
+
+            // unnamed functions are given the name "?"
+            if name != b"?" {
+                names.push((offset, String::from_utf8_lossy(&name).into_owned()));
+            }
+
+            if byte & PARSE_MORE_PUBLIC_NAMES == 0 {
+                return Ok((names, byte));
+            }
+        }
+    }
+
+    /// Skip over functions referenced by a module, these aren't used for matching.
+    fn referenced_functions(&mut self) -> Result<(), Error> {
+        let count = if self.version >= 8 { self.u8()? } else { 1 };
+
+        for _ in 0..count {
+            self.offset()?;
+
+            let len = match self.u8()? {
+                0 => self.multiple_bytes()? as usize,
+                len => len as usize,
+            };
+
+            self.bytes(len)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_flirt(bytes: &[u8]) -> Result<Vec<Signature>, Error> {
+    // magic, version, arch, file types, os types, app types, features, function count, crc,
+    // ctype, library name length and ctypes crc
+    const HEADER_LEN: usize = 6 + 1 + 1 + 4 + 2 + 2 + 2 + 2 + 2 + 12 + 1 + 2;
+
+    let header = bytes.get(..HEADER_LEN).ok_or(Error::Truncated)?;
+    let version = header[6];
+    let features = u16::from_le_bytes([header[16], header[17]]);
+    let name_len = header[34] as usize;
+
+    if !(5..=10).contains(&version) {
+        return Err(Error::UnsupportedVersion(version));
+    }
+
+    // function count, pattern size and an unknown field were added over time
+    let extension_len = match version {
+        10 => 8,
+        8..=9 => 6,
+        6..=7 => 4,
+        _ => 0,
+    };
+
+    let body_start = HEADER_LEN + extension_len + name_len;
+    let body = bytes.get(body_start..).ok_or(Error::Truncated)?;
+
+    let decompressed;
+    let body = if features & FEATURE_COMPRESSED != 0 {
+        decompressed = decompress(body).map_err(Error::Decompression)?;
+        &decompressed[..]
+    } else {
+        body
+    };
+
+    let mut sigs = Vec::new();
+    let mut reader = Reader {
+        bytes: body,
+        version,
+    };
+
+    reader.tree(&mut Vec::new(), &mut sigs)?;
+    Ok(sigs)
+}
+
+/// Older versions use raw deflate streams, newer ones have a zlib header.
+fn decompress(bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut decompressed = Vec::new();
+    if flate2::read::ZlibDecoder::new(bytes).read_to_end(&mut decompressed).is_ok() {
+        return Ok(decompressed);
+    }
+
+    decompressed.clear();
+    flate2::read::DeflateDecoder::new(bytes).read_to_end(&mut decompressed)?;
+    Ok(decompressed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// FLIRT file of version 7 holding `memcpy`, whose last pattern byte is a wildcard and which is
+    /// followed by two bytes covered by the CRC and a tail byte.
+    fn flirt() -> Vec<u8> {
+        // header and the fields added in version 6, without a library name
+        let mut bytes = FLIRT_MAGIC.to_vec();
+        bytes.resize(37 + 4, 0);
+        bytes[6] = 7;
+
+        // root with a single child matching 32 bytes
+        bytes.extend([1, FLIRT_PATTERN_LEN as u8, 0x01]);
+        bytes.extend(0x10..0x2F);
+
+        // no further children, then the CRC, the length of the module and its name
+        bytes.extend([0, 2]);
+        bytes.extend(crc16(&[0xAA, 0xBB]).to_be_bytes());
+        bytes.extend([0x40, 0]);
+        bytes.extend(b"memcpy");
+
+        // tail byte one past the bytes covered by the CRC
+        bytes.extend([PARSE_READ_TAIL_BYTES, 1, 0xCC]);
+        bytes
+    }
+
+    #[test]
+    fn wildcards() {
+        let text = "# memset from some libc\n\n4889F8 74.. C3 memset\n";
+        let sigs = parse_text(text).unwrap();
+        assert_eq!(sigs.len(), 1);
+        assert_eq!(sigs[0].names, [(0, "memset".to_string())]);
+
+        let sig = &sigs[0];
+        assert!(sig.matches(&[0x48, 0x89, 0xF8, 0x74, 0x00, 0xC3]));
+        assert!(sig.matches(&[0x48, 0x89, 0xF8, 0x74, 0xFF, 0xC3, 0x90]));
+        assert!(!sig.matches(&[0x48, 0x89, 0xF8, 0x75, 0x00, 0xC3]));
+        assert!(!sig.matches(&[0x48, 0x89, 0xF8, 0x74, 0x00]));
+    }
+
+    #[test]
+    fn malformed_text() {
+        let line = |text| match parse_text(text) {
+            Err(Error::InvalidLine(line)) => Some(line),
+            _ => None,
+        };
+
+        assert_eq!(line("4889F memset"), Some(1));
+        assert_eq!(line("48GG memset"), Some(1));
+        assert_eq!(line("memset"), Some(1));
+        assert_eq!(line("# comment\n\n48 .. memset\n4. memcpy"), Some(4));
+        assert_eq!(line("48 .. memset"), None);
+    }
+
+    #[test]
+    fn checksum() {
+        assert_eq!(crc16(&[]), 0);
+        assert_eq!(crc16(b"123456789"), 0x6E90);
+    }
+
+    #[test]
+    fn flirt_modules() {
+        let sigs = parse_flirt(&flirt()).unwrap();
+        assert_eq!(sigs.len(), 1);
+
+        let sig = &sigs[0];
+        assert_eq!(sig.names, [(0, "memcpy".to_string())]);
+        assert_eq!(sig.pattern.len(), FLIRT_PATTERN_LEN);
+        assert_eq!(sig.pattern.last(), Some(&None));
+        assert_eq!(sig.tail, [(FLIRT_PATTERN_LEN + 3, 0xCC)]);
+
+        let mut code: Vec<u8> = (0x10..0x2F).collect();
+        code.extend([0x99, 0xAA, 0xBB, 0x00, 0xCC]);
+        assert!(sig.matches(&code));
+
+        // the wildcard and bytes between the CRC and the tail aren't checked
+        code[31] = 0;
+        code[34] = 0xFF;
+        assert!(sig.matches(&code));
+
+        assert!(!sig.matches(&code[..code.len() - 1]));
+
+        code[33] ^= 0xFF;
+        assert!(!sig.matches(&code));
+        code[33] ^= 0xFF;
+
+        code[35] ^= 0xFF;
+        assert!(!sig.matches(&code));
+    }
+
+    #[test]
+    fn malformed_flirt() {
+        let bytes = flirt();
+        for len in 0..bytes.len() {
+            assert!(matches!(parse_flirt(&bytes[..len]), Err(Error::Truncated)));
+        }
+
+        let mut unsupported = bytes.clone();
+        unsupported[6] = 4;
+        assert!(matches!(
+            parse_flirt(&unsupported),
+            Err(Error::UnsupportedVersion(4))
+        ));
+
+        let mut compressed = bytes;
+        compressed[16] = FEATURE_COMPRESSED as u8;
+        assert!(matches!(
+            parse_flirt(&compressed),
+            Err(Error::Decompression(_))
+        ));
+    }
+}
@@ -0,0 +1,234 @@
+//! Strings stored in data sections, along with the functions using them.
+//!
+//! Strings are runs of printable ASCII or UTF-16 characters. A run is split up where something
+//! refers to the middle of it, as linkers merge strings that end the same and languages without
+//! null terminators place string literals back to back. A function uses a string when one of its
+//! instructions refers to the string's address, either directly or through a pointer stored in a
+//! data section.
+
+use crate::Processor;
+use decoder::ir::{Expr, Stmt};
+use processor_shared::{PhysAddr, Section, SectionKind};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::ops::Range;
+
+/// Shortest run of characters that's considered a string.
+const MIN_LEN: usize = 4;
+
+/// Shortest run of characters that's considered a string when something refers to it.
+const MIN_REFERENCED_LEN: usize = 2;
+
+/// How the characters of a string are encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Ascii,
+    /// Little endian UTF-16, as used by Windows.
+    Utf16,
+}
+
+impl Encoding {
+    /// Number of bytes taken up by a character.
+    fn width(self) -> usize {
+        match self {
+            Self::Ascii => 1,
+            Self::Utf16 => 2,
+        }
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Ascii => "ascii",
+            Self::Utf16 => "utf-16",
+        })
+    }
+}
+
+/// String found in a data section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractedString {
+    pub addr: PhysAddr,
+    pub text: String,
+    pub encoding: Encoding,
+    /// Start of the functions using the string, sorted.
+    pub functions: Vec<PhysAddr>,
+}
+
+fn printable(byte: u8) -> bool {
+    byte.is_ascii_graphic() || matches!(byte, b' ' | b'\t' | b'\n' | b'\r')
+}
+
+/// Runs of printable characters, as the offset of their first character and their number of
+/// characters.
+fn runs(bytes: &[u8], encoding: Encoding) -> Vec<(usize, usize)> {
+    let width = encoding.width();
+    let mut runs = Vec::new();
+    let mut start = None;
+
+    for (idx, char) in bytes.chunks_exact(width).enumerate() {
+        let printable = printable(char[0]) && char[1..].iter().all(|&byte| byte == 0);
+        match (printable, start) {
+            (true, None) => start = Some(idx),
+            (false, Some(first)) => {
+                runs.push((first * width, idx - first));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(first) = start {
+        runs.push((first * width, bytes.len() / width - first));
+    }
+
+    runs
+}
+
+/// Addresses an expression refers to, either as a constant or as where a value is loaded from.
+fn referenced(expr: &Expr, addrs: &mut Vec<PhysAddr>) {
+    match expr {
+        Expr::Const(value) => addrs.push(*value as PhysAddr),
+        Expr::Load { addr, .. } => referenced(addr, addrs),
+        _ => {}
+    }
+}
+
+/// Strings used by every function, as indices into `strings`.
+pub(crate) fn by_function(strings: &[ExtractedString]) -> BTreeMap<PhysAddr, Vec<usize>> {
+    let mut functions: BTreeMap<PhysAddr, Vec<usize>> = BTreeMap::new();
+    for (idx, string) in strings.iter().enumerate() {
+        for &function in string.functions.iter() {
+            functions.entry(function).or_default().push(idx);
+        }
+    }
+
+    functions
+}
+
+impl Processor {
+    /// Functions referring to an address from code, by the address referred to.
+    fn code_references(&self) -> BTreeMap<PhysAddr, BTreeSet<PhysAddr>> {
+        let mut references: BTreeMap<PhysAddr, BTreeSet<PhysAddr>> = BTreeMap::new();
+        let mut addrs = Vec::new();
+
+        for instruction in self.instructions_from(0) {
+            let function = match self.function_start(instruction.addr) {
+                Some(function) => function,
+                None => continue,
+            };
+
+            for stmt in self.instruction_ir(&instruction.item) {
+                match stmt {
+                    Stmt::Assign { src, .. } => referenced(&src, &mut addrs),
+                    Stmt::Store { addr, src, .. } => {
+                        referenced(&addr, &mut addrs);
+                        referenced(&src, &mut addrs);
+                    }
+                    _ => {}
+                }
+            }
+
+            for addr in addrs.drain(..) {
+                // strings are often used through a global pointing at them
+                if let Some(pointer) = self.pointer_at(addr) {
+                    references.entry(pointer.target).or_default().insert(function);
+                }
+
+                references.entry(addr).or_default().insert(function);
+            }
+        }
+
+        references
+    }
+
+    /// Strings in data sections along with the functions using them, sorted by address.
+    pub(crate) fn extract_strings(&self) -> Vec<ExtractedString> {
+        let references = self.code_references();
+        let mut strings = Vec::new();
+
+        for section in self.sections() {
+            if !matches!(
+                section.kind,
+                SectionKind::Raw
+                    | SectionKind::Raw4
+                    | SectionKind::Raw8
+                    | SectionKind::Raw16
+                    | SectionKind::CString
+            ) {
+                continue;
+            }
+
+            for encoding in [Encoding::Ascii, Encoding::Utf16] {
+                // characters are aligned in memory, not relative to the start of their section
+                let width = encoding.width();
+                let skip = section.start.next_multiple_of(width) - section.start;
+                let bytes = section.bytes().get(skip..).unwrap_or(&[]);
+
+                for (offset, len) in runs(bytes, encoding) {
+                    let start = section.start + skip + offset;
+                    let run = start..start + len * width;
+                    self.split_run(section, run, encoding, &references, &mut strings);
+                }
+            }
+        }
+
+        strings.sort_unstable_by_key(|string| string.addr);
+        strings
+    }
+
+    /// Split a run of characters up into strings where something refers to the middle of it.
+    fn split_run(
+        &self,
+        section: &Section,
+        run: Range<PhysAddr>,
+        encoding: Encoding,
+        references: &BTreeMap<PhysAddr, BTreeSet<PhysAddr>>,
+        strings: &mut Vec<ExtractedString>,
+    ) {
+        let width = encoding.width();
+        let inner = run.start + 1..run.end;
+        let mut bounds: Vec<PhysAddr> = references
+            .range(inner.clone())
+            .map(|(&addr, _)| addr)
+            .chain(self.data_xrefs.range(inner).map(|(&addr, _)| addr))
+            .filter(|addr| (addr - run.start).is_multiple_of(width))
+            .collect();
+
+        bounds.push(run.end);
+        bounds.sort_unstable();
+        bounds.dedup();
+
+        let mut addr = run.start;
+        for next in bounds {
+            let functions: Vec<PhysAddr> = match references.get(&addr) {
+                Some(functions) => functions.iter().copied().collect(),
+                None => Vec::new(),
+            };
+
+            let min_len = match functions.is_empty() && self.data_xrefs(addr).is_empty() {
+                true => MIN_LEN,
+                false => MIN_REFERENCED_LEN,
+            };
+
+            if (next - addr) / width >= min_len {
+                let bytes = section.bytes_by_addr(addr, next - addr);
+                strings.push(ExtractedString {
+                    addr,
+                    text: bytes.iter().step_by(width).map(|&byte| byte as char).collect(),
+                    encoding,
+                    functions,
+                });
+            }
+
+            addr = next;
+        }
+    }
+
+    /// Strings used by the function starting at an address, sorted by address.
+    pub fn strings_used_by(&self, function: PhysAddr) -> impl Iterator<Item = &ExtractedString> {
+        let indices = self.function_strings.get(&function).map_or(&[][..], Vec::as_slice);
+        indices.iter().map(|&idx| &self.strings[idx])
+    }
+}
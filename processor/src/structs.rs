@@ -0,0 +1,241 @@
+//! Structures applied to data.
+//!
+//! Structures are either described by debug info or defined by the user, see
+//! [`debugvault::types`]. Once applied at an address, the listing shows the data there as the
+//! structure's fields and instructions accessing them are annotated with the field's name.
+
+use crate::prototypes::{relative, visit_loads};
+use crate::Processor;
+use debugvault::types::{self, Field, Struct};
+use debugvault::Index;
+use decoder::ir::{BinaryOp, Expr, Stmt};
+use object::Endian;
+use processor_shared::{encode_hex_bytes_truncated, PhysAddr, Section, SectionKind};
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// Instructions looked at before an access for the address in its base register.
+const MAX_LOOKBEHIND: usize = 16;
+
+/// Characters of hex encoded bytes shown for fields that aren't numbers or strings.
+const MAX_BYTES_SHOWN: usize = 48;
+
+/// Structures described by the debug info, by name.
+pub(crate) fn known(index: &Index) -> BTreeMap<String, Arc<Struct>> {
+    index
+        .structs
+        .iter()
+        .map(|def| (def.name.clone(), Arc::new(def.clone())))
+        .collect()
+}
+
+impl Processor {
+    /// Add the structures of a C definition, returning their names, see [`types`].
+    pub fn define_structs(&self, src: &str) -> Result<Vec<String>, types::Error> {
+        let pointer_size = self.arch.address_size().map_or(8, |size| size.bytes() as usize);
+        let mut structs = self.structs.write().unwrap();
+        let parsed = types::parse(src, pointer_size, |name| {
+            structs.get(name).map(|def| (def.size, def.align()))
+        })?;
+
+        let names = parsed.iter().map(|def| def.name.clone()).collect();
+        for def in parsed {
+            structs.insert(def.name.clone(), Arc::new(def));
+        }
+
+        Ok(names)
+    }
+
+    pub fn struct_by_name(&self, name: &str) -> Option<Arc<Struct>> {
+        self.structs.read().unwrap().get(name).cloned()
+    }
+
+    /// Names of the structures that can be applied.
+    pub fn struct_names(&self) -> Vec<String> {
+        self.structs.read().unwrap().keys().cloned().collect()
+    }
+
+    /// Show the data at an address as a structure, replacing any structures it overlaps.
+    ///
+    /// Returns the section of which the boundaries changed, if the structure is known and fits
+    /// in a section without any other kind of data.
+    pub fn apply_struct(&self, addr: PhysAddr, name: &str) -> Option<Range<usize>> {
+        let section = self.section_by_addr(addr)?;
+        if !matches!(
+            section.kind,
+            SectionKind::Raw | SectionKind::Raw4 | SectionKind::Raw8 | SectionKind::Raw16
+        ) {
+            return None;
+        }
+
+        let def = self.struct_by_name(name)?;
+        let end = addr + def.size;
+        if def.size == 0 || end > section.start + section.bytes().len() {
+            return None;
+        }
+
+        let mut applied = self.applied.write().unwrap();
+        applied.retain(|&start, other| start + other.size <= addr || start >= end);
+        applied.insert(addr, def);
+
+        Some(section.start..section.end)
+    }
+
+    /// Stop showing the structure containing an address.
+    ///
+    /// Returns the section of which the boundaries changed.
+    pub fn remove_struct(&self, addr: PhysAddr) -> Option<Range<usize>> {
+        let (start, _) = self.struct_containing(addr)?;
+        self.applied.write().unwrap().remove(&start);
+
+        let section = self.section_by_addr(start)?;
+        Some(section.start..section.end)
+    }
+
+    /// Structure applied at an address.
+    pub fn struct_at(&self, addr: PhysAddr) -> Option<Arc<Struct>> {
+        self.applied.read().unwrap().get(&addr).cloned()
+    }
+
+    /// Structure applied over an address together with the address it starts at.
+    pub fn struct_containing(&self, addr: PhysAddr) -> Option<(PhysAddr, Arc<Struct>)> {
+        let applied = self.applied.read().unwrap();
+        let (&start, def) = applied.range(..=addr).next_back()?;
+        (addr < start + def.size).then(|| (start, Arc::clone(def)))
+    }
+
+    /// Address of the first structure applied past an address.
+    pub(crate) fn next_struct(&self, addr: PhysAddr) -> Option<PhysAddr> {
+        let applied = self.applied.read().unwrap();
+        applied.range(addr + 1..).next().map(|(&start, _)| start)
+    }
+
+    /// Values of the fields of a structure applied at an address, as they're shown.
+    pub(crate) fn struct_values(
+        &self,
+        section: &Section,
+        addr: PhysAddr,
+        def: &Struct,
+    ) -> Vec<String> {
+        def.fields
+            .iter()
+            .map(|field| self.field_value(section, addr + field.offset, field))
+            .collect()
+    }
+
+    fn field_value(&self, section: &Section, addr: PhysAddr, field: &Field) -> String {
+        let bytes = self.patched_bytes(section, addr, field.size);
+        if field.is_string() {
+            let end = bytes.iter().position(|&b| b == b'\0').unwrap_or(bytes.len());
+            return format!(
+                "\"{}\"",
+                String::from_utf8_lossy(&bytes[..end]).escape_debug()
+            );
+        }
+
+        let value = match bytes.len() {
+            1 => bytes[0] as u64,
+            2 => self.endianness.read_u16_bytes(bytes[..].try_into().unwrap()) as u64,
+            4 => self.endianness.read_u32_bytes(bytes[..].try_into().unwrap()) as u64,
+            8 => self.endianness.read_u64_bytes(bytes[..].try_into().unwrap()),
+            _ => return encode_hex_bytes_truncated(&bytes, MAX_BYTES_SHOWN, false),
+        };
+
+        if field.is_pointer() {
+            if let Some(symbol) = self.index.get_sym_by_addr(value as PhysAddr) {
+                return symbol.as_str().to_string();
+            }
+        }
+
+        format!("{value:#x}")
+    }
+
+    /// Constant last assigned to a register before the instruction at an address.
+    pub(crate) fn register_value(&self, addr: PhysAddr, reg: &str) -> Option<u64> {
+        let start = self.function_bounds(addr).map_or(0, |(start, ..)| start);
+        let mut cursor = addr;
+
+        for _ in 0..MAX_LOOKBEHIND {
+            let prev = self.instruction_at_or_before(cursor.checked_sub(1)?)?;
+            if prev.addr < start {
+                return None;
+            }
+
+            cursor = prev.addr;
+            for stmt in self.instruction_ir(&prev.item).iter().rev() {
+                match stmt {
+                    Stmt::Assign { dst, src } if dst.name == reg => {
+                        return match src {
+                            Expr::Const(value) => Some(*value),
+                            // registers are zeroed by xor'ing them with themselves
+                            Expr::Binary(BinaryOp::Xor, lhs, rhs) if lhs == rhs => Some(0),
+                            _ => None,
+                        };
+                    }
+                    Stmt::Jump(..)
+                    | Stmt::Branch { .. }
+                    | Stmt::Call(..)
+                    | Stmt::Return
+                    | Stmt::Halt => return None,
+                    _ => {}
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Name of the instance a structure applied at an address is shown as.
+    fn instance_name(&self, addr: PhysAddr, def: &Struct) -> String {
+        match self.index.get_sym_by_addr(addr) {
+            Some(symbol) => symbol.as_str().to_string(),
+            None => def.name.clone(),
+        }
+    }
+
+    /// Field of an applied structure the instruction at an address accesses, e.g. `obj->field`
+    /// for `[rdi+0x10]` where `rdi` points to `obj`, or `obj.field` for a direct access.
+    pub fn field_access(&self, addr: PhysAddr) -> Option<String> {
+        if self.applied.read().unwrap().is_empty() {
+            return None;
+        }
+
+        let instruction = self.instruction_by_addr(addr)?;
+        let mut addrs = Vec::new();
+        for stmt in self.instruction_ir(&instruction) {
+            match stmt {
+                Stmt::Assign { src, .. } => visit_loads(&src, &mut |load| addrs.push(load.clone())),
+                Stmt::Store {
+                    addr: store, src, ..
+                } => {
+                    visit_loads(&src, &mut |load| addrs.push(load.clone()));
+                    addrs.push(store);
+                }
+                _ => {}
+            }
+        }
+
+        addrs.into_iter().find_map(|access| {
+            if let Expr::Const(target) = access {
+                let (start, def) = self.struct_containing(target as PhysAddr)?;
+                let field = def.field_at(target as PhysAddr - start)?;
+                return Some(format!(
+                    "{}.{}",
+                    self.instance_name(start, &def),
+                    field.name
+                ));
+            }
+
+            let (reg, offset) = relative(&access)?;
+            let base = self.register_value(addr, reg)? as PhysAddr;
+            let def = self.struct_at(base)?;
+            let field = def.field_at(usize::try_from(offset).ok()?)?;
+            Some(format!(
+                "{}->{}",
+                self.instance_name(base, &def),
+                field.name
+            ))
+        })
+    }
+}
@@ -0,0 +1,421 @@
+//! Linear sweep over the code sections, split up into regions that are decoded on demand.
+//!
+//! On architectures with variable width instructions a region may start in the middle of an
+//! instruction. A region therefore starts decoding where the instructions of the region before it
+//! end. If that region wasn't decoded yet, decoding starts a few instructions before the region
+//! instead, which is nearly always enough to get back in step with the instructions before it.
+//! Once the region before it gets decoded, the region is decoded again if the two don't line up.
+
+use arc_swap::ArcSwapOption;
+use decoder::{Decodable, Decoded as _, ErrorKind, Reader};
+use processor_shared::{AddressMap, Addressed, PhysAddr, Section, SectionKind};
+use std::ops::Deref;
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Number of bytes in a region, small enough to decode one without the UI stalling.
+const REGION_SIZE: usize = 0x20000;
+
+/// Number of instructions decoded before the start of a region.
+pub const LOOKBEHIND: usize = 16;
+
+/// Instructions and errors of a single region, sorted by address.
+pub struct Decoded<I> {
+    pub instructions: AddressMap<I>,
+    pub errors: AddressMap<decoder::Error>,
+    /// Address following the last instruction or error, where the next region starts decoding.
+    pub end: PhysAddr,
+    /// Drops an instruction, for instructions that don't drop themselves.
+    drop: Option<unsafe fn(&mut I)>,
+}
+
+impl<I> Decoded<I> {
+    pub fn map<T>(mut self, f: impl Fn(I) -> T) -> Decoded<T> {
+        let instructions = std::mem::take(&mut self.instructions)
+            .mapping
+            .into_iter()
+            .map(|Addressed { addr, item }| Addressed {
+                addr,
+                item: f(item),
+            })
+            .collect();
+
+        Decoded {
+            instructions: AddressMap {
+                mapping: instructions,
+            },
+            errors: std::mem::take(&mut self.errors),
+            end: self.end,
+            drop: None,
+        }
+    }
+
+    /// Whether the first instruction or error starts at an address, meaning that decoding from
+    /// there would've found the same instructions.
+    pub fn starts_at(&self, addr: PhysAddr) -> bool {
+        let instruction = self.instructions.first().map(|inst| inst.addr);
+        let error = self.errors.first().map(|err| err.addr);
+
+        match (instruction, error) {
+            (Some(a), Some(b)) => std::cmp::min(a, b) == addr,
+            (a, b) => a.or(b).is_none_or(|first| first == addr),
+        }
+    }
+
+    /// Drop every instruction with `drop` once the decoding is dropped, for instructions that
+    /// don't drop themselves such as the fields of a union.
+    ///
+    /// # Safety
+    ///
+    /// `drop` has to be safe to call once on each of the instructions.
+    pub unsafe fn drop_with(mut self, drop: unsafe fn(&mut I)) -> Self {
+        self.drop = Some(drop);
+        self
+    }
+}
+
+impl<I> Drop for Decoded<I> {
+    fn drop(&mut self) {
+        if let Some(drop) = self.drop {
+            for Addressed { item, .. } in self.instructions.iter_mut() {
+                // SAFETY: the caller of `drop_with` made sure `drop` works on every instruction
+                unsafe { drop(item) };
+            }
+        }
+    }
+}
+
+/// Part of a region's decoding, which keeps the decoding alive after the region got patched.
+pub struct Borrowed<I, T> {
+    /// Only held onto so the item isn't freed.
+    _decoded: Arc<Decoded<I>>,
+    item: NonNull<T>,
+}
+
+impl<I, T> Borrowed<I, T> {
+    /// Borrow whatever part of a decoding `f` finds.
+    pub fn new(
+        decoded: Arc<Decoded<I>>,
+        f: impl FnOnce(&Decoded<I>) -> Option<&T>,
+    ) -> Option<Self> {
+        let item = NonNull::from(f(&decoded)?);
+        Some(Self {
+            _decoded: decoded,
+            item,
+        })
+    }
+}
+
+impl<I, T> Deref for Borrowed<I, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: the item is part of the decoding, which can't be dropped before `self` is
+        unsafe { self.item.as_ref() }
+    }
+}
+
+// SAFETY: the item is only ever read, just like the decoding it's a part of
+unsafe impl<I, T: Sync> Send for Borrowed<I, T> where Decoded<I>: Send + Sync {}
+unsafe impl<I, T: Sync> Sync for Borrowed<I, T> where Decoded<I>: Send + Sync {}
+
+/// Every instruction of a decoding, each keeping the decoding alive.
+pub fn instructions<I>(
+    decoded: Arc<Decoded<I>>,
+) -> impl Iterator<Item = Borrowed<I, Addressed<I>>> {
+    (0..decoded.instructions.len()).filter_map(move |idx| {
+        Borrowed::new(decoded.clone(), |decoded| decoded.instructions.get(idx))
+    })
+}
+
+/// Range of a code section that's decoded all at once.
+pub struct Region<I> {
+    /// Index of the section the region is part of.
+    pub section: usize,
+    pub start: PhysAddr,
+    pub end: PhysAddr,
+    /// Latest decoding, if the region was decoded yet. Readers hold onto the decoding they use,
+    /// so a decoding that got replaced after a patch is freed once nobody uses it anymore.
+    decoded: ArcSwapOption<Decoded<I>>,
+    /// Held while decoding, so a region is decoded only once and the decoding of a patch can't
+    /// be overwritten by an older one.
+    decoding: Mutex<()>,
+}
+
+impl<I> Region<I> {
+    pub fn is_decoded(&self) -> bool {
+        self.decoded.load().is_some()
+    }
+
+    /// Latest instructions and errors of the region, if it was decoded yet.
+    pub fn get(&self) -> Option<Arc<Decoded<I>>> {
+        self.decoded.load_full()
+    }
+
+    /// Latest instructions and errors of the region, calling `decode` if there aren't any yet.
+    pub fn get_or_decode(&self, decode: impl FnOnce() -> Decoded<I>) -> Arc<Decoded<I>> {
+        if let Some(decoded) = self.decoded.load_full() {
+            return decoded;
+        }
+
+        let _decoding = self.decoding.lock().unwrap();
+
+        // another thread might've decoded the region while this one was waiting on it
+        if let Some(decoded) = self.decoded.load_full() {
+            return decoded;
+        }
+
+        let decoded = Arc::new(decode());
+        self.decoded.store(Some(decoded.clone()));
+        decoded
+    }
+
+    /// Swap out the instructions and errors after the bytes of the region got patched.
+    pub fn replace(&self, decode: impl FnOnce() -> Decoded<I>) {
+        let _decoding = self.decoding.lock().unwrap();
+        self.decoded.store(Some(Arc::new(decode())));
+    }
+
+    /// Swap out the instructions and errors if the region was decoded and `redecode` returns a
+    /// new decoding. Returns whether the decoding was swapped out.
+    pub fn redecode(&self, redecode: impl FnOnce(&Decoded<I>) -> Option<Decoded<I>>) -> bool {
+        let _decoding = self.decoding.lock().unwrap();
+        let decoded = match self.decoded.load_full() {
+            Some(decoded) => decoded,
+            None => return false,
+        };
+
+        match redecode(&decoded) {
+            Some(redecoded) => {
+                self.decoded.store(Some(Arc::new(redecoded)));
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Split up every code section into regions, sorted by address.
+pub fn regions<I>(sections: &[Section]) -> Vec<Region<I>> {
+    let mut regions = Vec::new();
+
+    for (idx, section) in sections.iter().enumerate() {
+        if section.kind != SectionKind::Code {
+            continue;
+        }
+
+        let len = section.bytes().len();
+        for offset in (0..len).step_by(REGION_SIZE) {
+            regions.push(Region {
+                section: idx,
+                start: section.start + offset,
+                end: section.start + std::cmp::min(offset + REGION_SIZE, len),
+                decoded: ArcSwapOption::empty(),
+                decoding: Mutex::new(()),
+            });
+        }
+    }
+
+    regions.sort_unstable_by_key(|region| region.start);
+    regions
+}
+
+/// Decode the instructions starting in between `start` and `end`, where `bytes` are located at
+/// address `base`.
+///
+/// Decoding starts at `seam`, where the instructions before the region end, if it's known.
+pub fn decode<D: Decodable>(
+    decoder: &D,
+    bytes: &[u8],
+    base: PhysAddr,
+    start: PhysAddr,
+    end: PhysAddr,
+    seam: Option<PhysAddr>,
+) -> Decoded<D::Instruction> {
+    let lookbehind = LOOKBEHIND * decoder.max_width();
+    let mut ip = match seam {
+        Some(seam) => seam,
+        None => std::cmp::max(base, start.saturating_sub(lookbehind)),
+    };
+    let reader_at = |ip: PhysAddr| Reader::new(&bytes[ip - base..]);
+    let mut reader = reader_at(ip);
+    let mut entries: Vec<Addressed<Result<D::Instruction, decoder::Error>>> = Vec::new();
+
+    while ip < end {
+        // prefetch next cache line line
+        #[cfg(target_arch = "x86")]
+        unsafe {
+            core::arch::x86::_mm_prefetch(
+                reader.as_ptr() as *const i8,
+                core::arch::x86::_MM_HINT_NTA,
+            );
+        }
+
+        #[cfg(target_arch = "x86_64")]
+        unsafe {
+            core::arch::x86_64::_mm_prefetch(
+                reader.as_ptr() as *const i8,
+                core::arch::x86_64::_MM_HINT_NTA,
+            );
+        }
+
+        match decoder.decode(&mut reader) {
+            Ok(mut instruction) => {
+                let prev_inst = match entries.last() {
+                    Some(Addressed { item: Ok(prev), .. }) => Some(prev),
+                    _ => None,
+                };
+
+                instruction.update_rel_addrs(ip, prev_inst);
+
+                let width = instruction.width();
+                entries.push(Addressed {
+                    addr: ip,
+                    item: Ok(instruction),
+                });
+                ip += width;
+            }
+            Err(error) => {
+                if error.kind == ErrorKind::ExhaustedInput {
+                    break;
+                }
+
+                let width = error.size();
+                entries.push(Addressed {
+                    addr: ip,
+                    item: Err(error),
+                });
+                ip += width;
+
+                // errors don't always consume as many bytes as they're wide, so the reader has
+                // to be moved back to where the next instruction begins
+                reader = reader_at(ip);
+            }
+        }
+    }
+
+    let mut instructions = AddressMap::default();
+    let mut errors = AddressMap::default();
+
+    for Addressed { addr, item } in entries {
+        match item {
+            _ if addr < start => {}
+            Ok(item) => instructions.push(Addressed { addr, item }),
+            Err(item) => errors.push(Addressed { addr, item }),
+        }
+    }
+
+    Decoded {
+        instructions,
+        errors,
+        end: ip,
+        drop: None,
+    }
+}
+
+/// Call `f` with every index up to `count`, spread out over all cores.
+pub fn parallel(count: usize, f: impl Fn(usize) + Sync) {
+    let next = AtomicUsize::new(0);
+    let thread_count = std::thread::available_parallelism().map_or(1, |count| count.get());
+
+    std::thread::scope(|s| {
+        for _ in 0..std::cmp::min(thread_count, count) {
+            s.spawn(|| loop {
+                let idx = next.fetch_add(1, Ordering::Relaxed);
+                if idx >= count {
+                    break;
+                }
+
+                f(idx);
+            });
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+    unsafe fn count_drop(_: &mut u32) {
+        DROPPED.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn decoded(addr: PhysAddr) -> Decoded<u32> {
+        let decoded = Decoded {
+            instructions: AddressMap {
+                mapping: vec![Addressed { addr, item: 0 }],
+            },
+            errors: AddressMap::default(),
+            end: addr + 4,
+            drop: None,
+        };
+
+        unsafe { decoded.drop_with(count_drop) }
+    }
+
+    #[test]
+    fn replaced_decodings_are_freed() {
+        let region = Region {
+            section: 0,
+            start: 0,
+            end: 0x10,
+            decoded: ArcSwapOption::empty(),
+            decoding: Mutex::new(()),
+        };
+
+        let first = region.get_or_decode(|| decoded(0));
+        let borrowed = Borrowed::new(first, |decoded| decoded.instructions.first()).unwrap();
+        region.replace(|| decoded(4));
+
+        // the first decoding is still borrowed
+        assert_eq!(DROPPED.load(Ordering::Relaxed), 0);
+        assert_eq!(borrowed.addr, 0);
+
+        drop(borrowed);
+        assert_eq!(DROPPED.load(Ordering::Relaxed), 1);
+
+        let latest = region.get_or_decode(|| unreachable!());
+        assert_eq!(latest.instructions[0].addr, 4);
+
+        drop(latest);
+        drop(region);
+        assert_eq!(DROPPED.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn misaligned_decodings_are_redecoded() {
+        let region = Region {
+            section: 0,
+            start: 0x10,
+            end: 0x20,
+            decoded: ArcSwapOption::empty(),
+            decoding: Mutex::new(()),
+        };
+
+        // doesn't count drops, as the counter is shared with other tests
+        let decoded = |addr| Decoded {
+            instructions: AddressMap {
+                mapping: vec![Addressed { addr, item: 0u32 }],
+            },
+            errors: AddressMap::default(),
+            end: addr + 4,
+            drop: None,
+        };
+
+        // regions that weren't decoded yet are left alone
+        assert!(!region.redecode(|_| unreachable!()));
+
+        let redecode = |prev: &Decoded<u32>| match prev.starts_at(0x12) {
+            true => None,
+            false => Some(decoded(0x12)),
+        };
+
+        region.get_or_decode(|| decoded(0x10));
+        assert!(region.redecode(redecode));
+        assert!(!region.redecode(redecode));
+        assert_eq!(region.get().unwrap().instructions[0].addr, 0x12);
+    }
+}
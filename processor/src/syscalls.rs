@@ -0,0 +1,407 @@
+//! System calls made directly by a binary.
+//!
+//! A system call is made by a dedicated instruction, such as `syscall`, `svc #0` or `int 0x80`,
+//! with the number of the call in a register that's set right before it. The numbers differ between
+//! operating systems and often between architectures of the same one, so every combination has a
+//! table of its own. Calls that aren't in a table are shown by their number.
+
+use crate::Processor;
+use decoder::ir::Stmt;
+use object::{Architecture, BinaryFormat};
+use processor_shared::{AddressMap, Addressed, PhysAddr};
+use std::fmt;
+
+/// Byte of an ELF's identification holding the OS/ABI it targets.
+const EI_OSABI: usize = 7;
+
+/// OS/ABI of binaries built for FreeBSD, other ELF's are assumed to target Linux.
+const ELFOSABI_FREEBSD: u8 = 9;
+
+/// Class of BSD system calls on macOS, which `x86_64` binaries put in the upper bits of the
+/// number. Numbers of other classes, such as Mach traps, aren't in any table.
+const DARWIN_BSD_CLASS: u64 = 2;
+
+/// Linux on `x86_64`.
+#[rustfmt::skip]
+const LINUX_X86_64: &[(u64, &str)] = &[
+    (0, "read"), (1, "write"), (2, "open"), (3, "close"), (4, "stat"), (5, "fstat"),
+    (6, "lstat"), (7, "poll"), (8, "lseek"), (9, "mmap"), (10, "mprotect"), (11, "munmap"),
+    (12, "brk"), (13, "rt_sigaction"), (14, "rt_sigprocmask"), (15, "rt_sigreturn"),
+    (16, "ioctl"), (17, "pread64"), (18, "pwrite64"), (19, "readv"), (20, "writev"),
+    (21, "access"), (22, "pipe"), (23, "select"), (24, "sched_yield"), (25, "mremap"),
+    (26, "msync"), (27, "mincore"), (28, "madvise"), (29, "shmget"), (30, "shmat"),
+    (31, "shmctl"), (32, "dup"), (33, "dup2"), (34, "pause"), (35, "nanosleep"),
+    (36, "getitimer"), (37, "alarm"), (38, "setitimer"), (39, "getpid"), (40, "sendfile"),
+    (41, "socket"), (42, "connect"), (43, "accept"), (44, "sendto"), (45, "recvfrom"),
+    (46, "sendmsg"), (47, "recvmsg"), (48, "shutdown"), (49, "bind"), (50, "listen"),
+    (51, "getsockname"), (52, "getpeername"), (53, "socketpair"), (54, "setsockopt"),
+    (55, "getsockopt"), (56, "clone"), (57, "fork"), (58, "vfork"), (59, "execve"),
+    (60, "exit"), (61, "wait4"), (62, "kill"), (63, "uname"), (64, "semget"), (65, "semop"),
+    (66, "semctl"), (67, "shmdt"), (68, "msgget"), (69, "msgsnd"), (70, "msgrcv"),
+    (71, "msgctl"), (72, "fcntl"), (73, "flock"), (74, "fsync"), (75, "fdatasync"),
+    (76, "truncate"), (77, "ftruncate"), (78, "getdents"), (79, "getcwd"), (80, "chdir"),
+    (81, "fchdir"), (82, "rename"), (83, "mkdir"), (84, "rmdir"), (85, "creat"), (86, "link"),
+    (87, "unlink"), (88, "symlink"), (89, "readlink"), (90, "chmod"), (91, "fchmod"),
+    (92, "chown"), (93, "fchown"), (94, "lchown"), (95, "umask"), (96, "gettimeofday"),
+    (97, "getrlimit"), (98, "getrusage"), (99, "sysinfo"), (100, "times"), (101, "ptrace"),
+    (102, "getuid"), (103, "syslog"), (104, "getgid"), (105, "setuid"), (106, "setgid"),
+    (107, "geteuid"), (108, "getegid"), (109, "setpgid"), (110, "getppid"), (111, "getpgrp"),
+    (112, "setsid"), (113, "setreuid"), (114, "setregid"), (115, "getgroups"),
+    (116, "setgroups"), (117, "setresuid"), (118, "getresuid"), (119, "setresgid"),
+    (120, "getresgid"), (121, "getpgid"), (124, "getsid"), (125, "capget"), (126, "capset"),
+    (131, "sigaltstack"), (137, "statfs"), (138, "fstatfs"), (157, "prctl"),
+    (158, "arch_prctl"), (160, "setrlimit"), (161, "chroot"), (162, "sync"), (165, "mount"),
+    (166, "umount2"), (169, "reboot"), (170, "sethostname"), (175, "init_module"),
+    (176, "delete_module"), (186, "gettid"), (200, "tkill"), (201, "time"), (202, "futex"),
+    (203, "sched_setaffinity"), (204, "sched_getaffinity"), (217, "getdents64"),
+    (218, "set_tid_address"), (228, "clock_gettime"), (230, "clock_nanosleep"),
+    (231, "exit_group"), (232, "epoll_wait"), (233, "epoll_ctl"), (234, "tgkill"),
+    (247, "waitid"), (257, "openat"), (262, "newfstatat"), (263, "unlinkat"),
+    (272, "unshare"), (273, "set_robust_list"), (281, "epoll_pwait"), (288, "accept4"),
+    (290, "eventfd2"), (291, "epoll_create1"), (292, "dup3"), (293, "pipe2"),
+    (302, "prlimit64"), (308, "setns"), (310, "process_vm_readv"), (311, "process_vm_writev"),
+    (313, "finit_module"), (317, "seccomp"), (318, "getrandom"), (319, "memfd_create"),
+    (322, "execveat"), (332, "statx"), (334, "rseq"), (435, "clone3"), (439, "faccessat2"),
+];
+
+/// Linux on `x86`, made through `int 0x80`.
+#[rustfmt::skip]
+const LINUX_I386: &[(u64, &str)] = &[
+    (1, "exit"), (2, "fork"), (3, "read"), (4, "write"), (5, "open"), (6, "close"),
+    (7, "waitpid"), (8, "creat"), (9, "link"), (10, "unlink"), (11, "execve"), (12, "chdir"),
+    (13, "time"), (14, "mknod"), (15, "chmod"), (19, "lseek"), (20, "getpid"), (21, "mount"),
+    (23, "setuid"), (24, "getuid"), (26, "ptrace"), (27, "alarm"), (29, "pause"),
+    (33, "access"), (36, "sync"), (37, "kill"), (38, "rename"), (39, "mkdir"), (40, "rmdir"),
+    (41, "dup"), (42, "pipe"), (45, "brk"), (54, "ioctl"), (55, "fcntl"), (57, "setpgid"),
+    (60, "umask"), (61, "chroot"), (63, "dup2"), (64, "getppid"), (66, "setsid"),
+    (67, "sigaction"), (85, "readlink"), (88, "reboot"), (90, "mmap"), (91, "munmap"),
+    (102, "socketcall"), (114, "wait4"), (116, "sysinfo"), (119, "sigreturn"), (120, "clone"),
+    (122, "uname"), (125, "mprotect"), (140, "_llseek"), (141, "getdents"),
+    (142, "_newselect"), (146, "writev"), (162, "nanosleep"), (172, "prctl"),
+    (173, "rt_sigreturn"), (174, "rt_sigaction"), (175, "rt_sigprocmask"), (190, "vfork"),
+    (192, "mmap2"), (195, "stat64"), (197, "fstat64"), (199, "getuid32"), (224, "gettid"),
+    (240, "futex"), (243, "set_thread_area"), (252, "exit_group"), (258, "set_tid_address"),
+    (265, "clock_gettime"), (295, "openat"), (355, "getrandom"), (356, "memfd_create"),
+    (359, "socket"), (361, "bind"), (362, "connect"), (363, "listen"), (364, "accept4"),
+];
+
+/// Linux on `arm` with the EABI.
+#[rustfmt::skip]
+const LINUX_ARM: &[(u64, &str)] = &[
+    (1, "exit"), (2, "fork"), (3, "read"), (4, "write"), (5, "open"), (6, "close"),
+    (8, "creat"), (9, "link"), (10, "unlink"), (11, "execve"), (12, "chdir"), (15, "chmod"),
+    (19, "lseek"), (20, "getpid"), (21, "mount"), (23, "setuid"), (24, "getuid"),
+    (26, "ptrace"), (33, "access"), (36, "sync"), (37, "kill"), (38, "rename"),
+    (39, "mkdir"), (40, "rmdir"), (41, "dup"), (42, "pipe"), (45, "brk"), (54, "ioctl"),
+    (55, "fcntl"), (60, "umask"), (61, "chroot"), (63, "dup2"), (64, "getppid"),
+    (66, "setsid"), (85, "readlink"), (88, "reboot"), (91, "munmap"), (114, "wait4"),
+    (116, "sysinfo"), (120, "clone"), (122, "uname"), (125, "mprotect"), (162, "nanosleep"),
+    (172, "prctl"), (173, "rt_sigreturn"), (174, "rt_sigaction"), (175, "rt_sigprocmask"),
+    (190, "vfork"), (192, "mmap2"), (195, "stat64"), (197, "fstat64"), (224, "gettid"),
+    (240, "futex"), (248, "exit_group"), (256, "set_tid_address"), (263, "clock_gettime"),
+    (281, "socket"), (282, "bind"), (283, "connect"), (284, "listen"), (285, "accept"),
+    (290, "sendto"), (292, "recvfrom"), (322, "openat"), (384, "getrandom"),
+    (385, "memfd_create"),
+];
+
+/// Linux on architectures using the generic table, such as `aarch64`.
+#[rustfmt::skip]
+const LINUX_GENERIC: &[(u64, &str)] = &[
+    (17, "getcwd"), (23, "dup"), (24, "dup3"), (25, "fcntl"), (29, "ioctl"), (34, "mkdirat"),
+    (35, "unlinkat"), (37, "linkat"), (38, "renameat"), (40, "mount"), (48, "faccessat"),
+    (49, "chdir"), (51, "chroot"), (56, "openat"), (57, "close"), (59, "pipe2"),
+    (61, "getdents64"), (62, "lseek"), (63, "read"), (64, "write"), (65, "readv"),
+    (66, "writev"), (67, "pread64"), (68, "pwrite64"), (78, "readlinkat"),
+    (79, "newfstatat"), (80, "fstat"), (93, "exit"), (94, "exit_group"),
+    (96, "set_tid_address"), (98, "futex"), (99, "set_robust_list"), (101, "nanosleep"),
+    (113, "clock_gettime"), (115, "clock_nanosleep"), (117, "ptrace"), (124, "sched_yield"),
+    (129, "kill"), (130, "tkill"), (131, "tgkill"), (134, "rt_sigaction"),
+    (135, "rt_sigprocmask"), (139, "rt_sigreturn"), (142, "reboot"), (146, "setuid"),
+    (157, "setsid"), (160, "uname"), (167, "prctl"), (172, "getpid"), (173, "getppid"),
+    (174, "getuid"), (175, "geteuid"), (176, "getgid"), (177, "getegid"), (178, "gettid"),
+    (179, "sysinfo"), (198, "socket"), (200, "bind"), (201, "listen"), (202, "accept"),
+    (203, "connect"), (206, "sendto"), (207, "recvfrom"), (214, "brk"), (215, "munmap"),
+    (216, "mremap"), (220, "clone"), (221, "execve"), (222, "mmap"), (226, "mprotect"),
+    (233, "madvise"), (260, "wait4"), (261, "prlimit64"), (278, "getrandom"),
+    (279, "memfd_create"), (281, "execveat"), (435, "clone3"),
+];
+
+/// FreeBSD, which numbers its calls the same on every architecture.
+#[rustfmt::skip]
+const FREEBSD: &[(u64, &str)] = &[
+    (1, "exit"), (2, "fork"), (3, "read"), (4, "write"), (5, "open"), (6, "close"),
+    (7, "wait4"), (9, "link"), (10, "unlink"), (12, "chdir"), (15, "chmod"), (20, "getpid"),
+    (23, "setuid"), (24, "getuid"), (26, "ptrace"), (37, "kill"), (39, "getppid"),
+    (41, "dup"), (54, "ioctl"), (59, "execve"), (60, "umask"), (61, "chroot"), (66, "vfork"),
+    (73, "munmap"), (74, "mprotect"), (90, "dup2"), (92, "fcntl"), (93, "select"),
+    (97, "socket"), (98, "connect"), (104, "bind"), (106, "listen"), (116, "gettimeofday"),
+    (128, "rename"), (133, "sendto"), (136, "mkdir"), (137, "rmdir"), (202, "__sysctl"),
+    (240, "nanosleep"), (431, "thr_exit"), (432, "thr_self"), (433, "thr_kill"),
+    (454, "_umtx_op"), (477, "mmap"), (478, "lseek"), (499, "openat"), (542, "pipe2"),
+    (563, "getrandom"),
+];
+
+/// BSD calls of macOS.
+#[rustfmt::skip]
+const DARWIN: &[(u64, &str)] = &[
+    (1, "exit"), (2, "fork"), (3, "read"), (4, "write"), (5, "open"), (6, "close"),
+    (7, "wait4"), (9, "link"), (10, "unlink"), (12, "chdir"), (15, "chmod"), (20, "getpid"),
+    (23, "setuid"), (24, "getuid"), (26, "ptrace"), (37, "kill"), (39, "getppid"),
+    (41, "dup"), (54, "ioctl"), (59, "execve"), (60, "umask"), (61, "chroot"),
+    (73, "munmap"), (74, "mprotect"), (90, "dup2"), (92, "fcntl"), (93, "select"),
+    (97, "socket"), (98, "connect"), (104, "bind"), (106, "listen"), (116, "gettimeofday"),
+    (128, "rename"), (133, "sendto"), (136, "mkdir"), (137, "rmdir"), (197, "mmap"),
+    (199, "lseek"), (202, "sysctl"), (338, "stat64"), (339, "fstat64"), (340, "lstat64"),
+    (344, "getdirentries64"), (463, "openat"), (500, "getentropy"),
+];
+
+/// Native API of Windows 10 and 11 on `x86_64`. Windows renumbers its calls between builds,
+/// these are the ones that kept their number.
+#[rustfmt::skip]
+const WINDOWS_X86_64: &[(u64, &str)] = &[
+    (0x00, "NtAccessCheck"), (0x01, "NtWorkerFactoryWorkerReady"),
+    (0x02, "NtAcceptConnectPort"), (0x03, "NtMapUserPhysicalPagesScatter"),
+    (0x04, "NtWaitForSingleObject"), (0x05, "NtCallbackReturn"), (0x06, "NtReadFile"),
+    (0x07, "NtDeviceIoControlFile"), (0x08, "NtWriteFile"), (0x09, "NtRemoveIoCompletion"),
+    (0x0a, "NtReleaseSemaphore"), (0x0b, "NtReplyWaitReceivePort"), (0x0c, "NtReplyPort"),
+    (0x0d, "NtSetInformationThread"), (0x0e, "NtSetEvent"), (0x0f, "NtClose"),
+    (0x10, "NtQueryObject"), (0x11, "NtQueryInformationFile"), (0x12, "NtOpenKey"),
+    (0x13, "NtEnumerateValueKey"), (0x14, "NtFindAtom"), (0x15, "NtQueryDefaultLocale"),
+    (0x16, "NtQueryKey"), (0x17, "NtQueryValueKey"), (0x18, "NtAllocateVirtualMemory"),
+    (0x19, "NtQueryInformationProcess"), (0x1a, "NtWaitForMultipleObjects32"),
+    (0x1b, "NtWriteFileGather"), (0x1c, "NtSetInformationProcess"), (0x1d, "NtCreateKey"),
+    (0x1e, "NtFreeVirtualMemory"), (0x1f, "NtImpersonateClientOfPort"),
+    (0x20, "NtReleaseMutant"), (0x21, "NtQueryInformationToken"),
+    (0x22, "NtRequestWaitReplyPort"), (0x23, "NtQueryVirtualMemory"),
+    (0x24, "NtOpenThreadToken"), (0x25, "NtQueryInformationThread"), (0x26, "NtOpenProcess"),
+    (0x27, "NtSetInformationFile"), (0x28, "NtMapViewOfSection"),
+    (0x29, "NtAccessCheckAndAuditAlarm"), (0x2a, "NtUnmapViewOfSection"),
+    (0x2b, "NtReplyWaitReceivePortEx"), (0x2c, "NtTerminateProcess"),
+    (0x2d, "NtSetEventBoostPriority"), (0x2e, "NtReadFileScatter"),
+    (0x2f, "NtOpenThreadTokenEx"), (0x30, "NtOpenProcessTokenEx"),
+    (0x31, "NtQueryPerformanceCounter"), (0x32, "NtEnumerateKey"), (0x33, "NtOpenFile"),
+    (0x34, "NtDelayExecution"), (0x35, "NtQueryDirectoryFile"),
+    (0x36, "NtQuerySystemInformation"), (0x37, "NtOpenSection"), (0x38, "NtQueryTimer"),
+    (0x39, "NtFsControlFile"), (0x3a, "NtWriteVirtualMemory"),
+    (0x3b, "NtCloseObjectAuditAlarm"), (0x3c, "NtDuplicateObject"),
+    (0x3d, "NtQueryAttributesFile"), (0x3e, "NtClearEvent"), (0x3f, "NtReadVirtualMemory"),
+    (0x40, "NtOpenEvent"), (0x41, "NtAdjustPrivilegesToken"), (0x42, "NtDuplicateToken"),
+    (0x43, "NtContinue"), (0x44, "NtQueryDefaultUILanguage"), (0x45, "NtQueueApcThread"),
+    (0x46, "NtYieldExecution"), (0x47, "NtAddAtom"), (0x48, "NtCreateEvent"),
+    (0x49, "NtQueryVolumeInformationFile"), (0x4a, "NtCreateSection"),
+    (0x4b, "NtFlushBuffersFile"), (0x4c, "NtApphelpCacheControl"),
+    (0x4d, "NtCreateProcessEx"), (0x4e, "NtCreateThread"), (0x4f, "NtIsProcessInJob"),
+    (0x50, "NtProtectVirtualMemory"), (0x51, "NtQuerySection"), (0x52, "NtResumeThread"),
+    (0x53, "NtTerminateThread"), (0x54, "NtReadRequestData"), (0x55, "NtCreateFile"),
+];
+
+/// Operating system a binary is built for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Os {
+    Linux,
+    FreeBsd,
+    Darwin,
+    Windows,
+}
+
+impl Os {
+    pub(crate) fn detect(format: BinaryFormat, binary: &[u8]) -> Option<Self> {
+        match format {
+            BinaryFormat::Elf if binary.get(EI_OSABI) == Some(&ELFOSABI_FREEBSD) => {
+                Some(Self::FreeBsd)
+            }
+            BinaryFormat::Elf => Some(Self::Linux),
+            BinaryFormat::MachO => Some(Self::Darwin),
+            BinaryFormat::Pe | BinaryFormat::Coff => Some(Self::Windows),
+            _ => None,
+        }
+    }
+
+    /// Register holding the number of a call, and the names of the calls by number.
+    ///
+    /// `x86_64` Linux still takes calls made through the 32-bit instructions, which `compat` is
+    /// set for, and numbers those like `x86` does.
+    fn convention(
+        self,
+        arch: Architecture,
+        compat: bool,
+    ) -> Option<(&'static str, &'static [(u64, &'static str)])> {
+        Some(match (self, arch) {
+            (Self::Linux, Architecture::X86_64) if compat => ("eax", LINUX_I386),
+            (Self::Linux, Architecture::X86_64) => ("rax", LINUX_X86_64),
+            (Self::Linux, Architecture::I386) => ("eax", LINUX_I386),
+            (Self::Linux, Architecture::Arm) => ("r7", LINUX_ARM),
+            (Self::Linux, Architecture::Aarch64) => ("x8", LINUX_GENERIC),
+            (Self::FreeBsd, Architecture::X86_64) => ("rax", FREEBSD),
+            (Self::FreeBsd, Architecture::I386) => ("eax", FREEBSD),
+            (Self::FreeBsd, Architecture::Arm) => ("r7", FREEBSD),
+            (Self::FreeBsd, Architecture::Aarch64) => ("x8", FREEBSD),
+            (Self::Darwin, Architecture::X86_64) => ("rax", DARWIN),
+            (Self::Darwin, Architecture::Aarch64) => ("x16", DARWIN),
+            (Self::Windows, Architecture::X86_64) => ("rax", WINDOWS_X86_64),
+            (Self::Windows, Architecture::I386) => ("eax", &[]),
+            _ => return None,
+        })
+    }
+
+    /// Number of a call within its table.
+    fn table_number(self, number: u64) -> u64 {
+        match self {
+            Self::Darwin if number >> 24 == DARWIN_BSD_CLASS => number & 0xffffff,
+            _ => number,
+        }
+    }
+}
+
+/// Whether an instruction, as it's shown, makes a system call.
+fn is_syscall(text: &str) -> bool {
+    let mut parts = text.split_whitespace();
+    match parts.next() {
+        Some("syscall" | "sysenter" | "svc" | "swi") => true,
+        Some("int") => matches!(parts.next(), Some("0x80" | "0x2e")),
+        _ => false,
+    }
+}
+
+/// Whether an instruction, as it's shown, makes a system call the way `x86` does.
+fn is_compat(text: &str) -> bool {
+    let mut parts = text.split_whitespace();
+    match parts.next() {
+        Some("sysenter") => true,
+        Some("int") => parts.next() == Some("0x80"),
+        _ => false,
+    }
+}
+
+/// System call made by an instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Syscall {
+    /// Number of the call, if it's set shortly before the instruction.
+    pub number: Option<u64>,
+    /// Name of the call, if the number is in the table of the operating system.
+    pub name: Option<&'static str>,
+}
+
+impl fmt::Display for Syscall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.name, self.number) {
+            (Some(name), _) => f.write_str(name),
+            (None, Some(number)) => write!(f, "{number:#x}"),
+            (None, None) => f.write_str("unknown"),
+        }
+    }
+}
+
+impl Processor {
+    /// System calls of every instruction making one, see [`syscalls`].
+    pub(crate) fn find_syscalls(&self, os: Option<Os>) -> AddressMap<Syscall> {
+        let mut syscalls = AddressMap::default();
+        let os = match os {
+            Some(os) => os,
+            None => return syscalls,
+        };
+
+        if os.convention(self.arch, false).is_none() {
+            return syscalls;
+        }
+
+        for instruction in self.instructions_from(0) {
+            // system calls aren't modelled by the ir, so only those instructions are looked at
+            let stmts = self.instruction_ir(&instruction.item);
+            if !matches!(stmts.first(), Some(Stmt::Unknown)) {
+                continue;
+            }
+
+            let tokens = self.instruction_tokens(&instruction.item, &self.index);
+            let text = String::from_iter(tokens.iter().map(|token| &token.text[..]));
+            if !is_syscall(&text) {
+                continue;
+            }
+
+            let (reg, table) = match os.convention(self.arch, is_compat(&text)) {
+                Some(convention) => convention,
+                None => continue,
+            };
+
+            let number = self.register_value(instruction.addr, reg);
+            let name = number.and_then(|number| {
+                let number = os.table_number(number);
+                table.iter().find(|(n, _)| *n == number).map(|(_, name)| *name)
+            });
+
+            syscalls.push(Addressed {
+                addr: instruction.addr,
+                item: Syscall { number, name },
+            });
+        }
+
+        syscalls
+    }
+
+    /// System call made by the instruction at an address.
+    pub fn syscall_at(&self, addr: PhysAddr) -> Option<&Syscall> {
+        match self.syscalls.search(addr) {
+            Ok(idx) => Some(&self.syscalls[idx].item),
+            Err(..) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect() {
+        let mut elf = vec![0x7f, b'E', b'L', b'F', 2, 1, 1, 0];
+        assert_eq!(Os::detect(BinaryFormat::Elf, &elf), Some(Os::Linux));
+        elf[EI_OSABI] = ELFOSABI_FREEBSD;
+        assert_eq!(Os::detect(BinaryFormat::Elf, &elf), Some(Os::FreeBsd));
+        assert_eq!(Os::detect(BinaryFormat::Elf, &[]), Some(Os::Linux));
+        assert_eq!(Os::detect(BinaryFormat::MachO, &[]), Some(Os::Darwin));
+        assert_eq!(Os::detect(BinaryFormat::Pe, &[]), Some(Os::Windows));
+        assert_eq!(Os::detect(BinaryFormat::Wasm, &[]), None);
+    }
+
+    #[test]
+    fn syscall_instructions() {
+        assert!(is_syscall("syscall"));
+        assert!(is_syscall("svc #0x0"));
+        assert!(is_syscall("int 0x80"));
+        assert!(is_syscall("int 0x2e"));
+        assert!(!is_syscall("int 0x3"));
+        assert!(!is_syscall("int3"));
+        assert!(!is_syscall("call syscall"));
+
+        assert!(is_compat("int 0x80"));
+        assert!(is_compat("sysenter"));
+        assert!(!is_compat("syscall"));
+        assert!(!is_compat("int 0x2e"));
+    }
+
+    #[test]
+    fn conventions() {
+        let convention = Os::Linux.convention(Architecture::X86_64, false);
+        assert_eq!(convention, Some(("rax", LINUX_X86_64)));
+
+        // 32-bit calls made by 64-bit programs are numbered the way x86 does
+        let convention = Os::Linux.convention(Architecture::X86_64, true);
+        assert_eq!(convention, Some(("eax", LINUX_I386)));
+        let convention = Os::FreeBsd.convention(Architecture::X86_64, true);
+        assert_eq!(convention, Some(("rax", FREEBSD)));
+
+        let convention = Os::Darwin.convention(Architecture::Aarch64, false);
+        assert_eq!(convention, Some(("x16", DARWIN)));
+        assert_eq!(Os::Darwin.convention(Architecture::I386, false), None);
+    }
+
+    #[test]
+    fn table_numbers() {
+        // BSD calls on macOS have their class in the upper bits
+        assert_eq!(Os::Darwin.table_number(0x2000004), 4);
+        assert_eq!(Os::Darwin.table_number(4), 4);
+        // Mach traps are of another class, which isn't in the table
+        assert_eq!(Os::Darwin.table_number(0x100001f), 0x100001f);
+        assert_eq!(Os::Linux.table_number(0x2000004), 0x2000004);
+    }
+}
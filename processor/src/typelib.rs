@@ -0,0 +1,348 @@
+//! Prototypes of functions from the C standard library, POSIX and the Win32 API.
+//!
+//! Imported functions have no code to infer a prototype from, but the common ones have well known
+//! signatures. These are written down as C declarations and parsed on first use, so that call
+//! sites can name the arguments they pass.
+
+use crate::Processor;
+use processor_shared::PhysAddr;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::OnceLock;
+
+/// Declarations in the form of C prototypes, a trailing `...` marks a variadic function.
+const DECLARATIONS: &[&str] = &[
+    // C standard library
+    "void *malloc(size_t size)",
+    "void *calloc(size_t count, size_t size)",
+    "void *realloc(void *ptr, size_t size)",
+    "void free(void *ptr)",
+    "void *memcpy(void *dst, const void *src, size_t n)",
+    "void *memmove(void *dst, const void *src, size_t n)",
+    "void *memset(void *dst, int c, size_t n)",
+    "int memcmp(const void *s1, const void *s2, size_t n)",
+    "void *memchr(const void *s, int c, size_t n)",
+    "size_t strlen(const char *s)",
+    "size_t strnlen(const char *s, size_t maxlen)",
+    "char *strcpy(char *dst, const char *src)",
+    "char *strncpy(char *dst, const char *src, size_t n)",
+    "char *strcat(char *dst, const char *src)",
+    "char *strncat(char *dst, const char *src, size_t n)",
+    "int strcmp(const char *s1, const char *s2)",
+    "int strncmp(const char *s1, const char *s2, size_t n)",
+    "int strcasecmp(const char *s1, const char *s2)",
+    "int strncasecmp(const char *s1, const char *s2, size_t n)",
+    "char *strchr(const char *s, int c)",
+    "char *strrchr(const char *s, int c)",
+    "char *strstr(const char *haystack, const char *needle)",
+    "char *strdup(const char *s)",
+    "char *strndup(const char *s, size_t n)",
+    "char *strtok(char *s, const char *delim)",
+    "char *strerror(int errnum)",
+    "long strtol(const char *s, char **end, int base)",
+    "unsigned long strtoul(const char *s, char **end, int base)",
+    "long long strtoll(const char *s, char **end, int base)",
+    "unsigned long long strtoull(const char *s, char **end, int base)",
+    "double strtod(const char *s, char **end)",
+    "int atoi(const char *s)",
+    "long atol(const char *s)",
+    "int printf(const char *format, ...)",
+    "int fprintf(FILE *stream, const char *format, ...)",
+    "int sprintf(char *str, const char *format, ...)",
+    "int snprintf(char *str, size_t size, const char *format, ...)",
+    "int vprintf(const char *format, va_list ap)",
+    "int vfprintf(FILE *stream, const char *format, va_list ap)",
+    "int vsnprintf(char *str, size_t size, const char *format, va_list ap)",
+    "int scanf(const char *format, ...)",
+    "int sscanf(const char *str, const char *format, ...)",
+    "int puts(const char *s)",
+    "int putchar(int c)",
+    "int getchar(void)",
+    "int fputs(const char *s, FILE *stream)",
+    "int fputc(int c, FILE *stream)",
+    "int fgetc(FILE *stream)",
+    "char *fgets(char *s, int size, FILE *stream)",
+    "FILE *fopen(const char *path, const char *mode)",
+    "FILE *fdopen(int fd, const char *mode)",
+    "int fclose(FILE *stream)",
+    "size_t fread(void *ptr, size_t size, size_t count, FILE *stream)",
+    "size_t fwrite(const void *ptr, size_t size, size_t count, FILE *stream)",
+    "int fseek(FILE *stream, long offset, int whence)",
+    "long ftell(FILE *stream)",
+    "int fflush(FILE *stream)",
+    "int setvbuf(FILE *stream, char *buf, int mode, size_t size)",
+    "int remove(const char *path)",
+    "int rename(const char *old, const char *new)",
+    "char *getenv(const char *name)",
+    "int system(const char *command)",
+    "void exit(int status)",
+    "void _exit(int status)",
+    "void abort(void)",
+    "int atexit(void *func)",
+    "void qsort(void *base, size_t count, size_t size, void *compare)",
+    "void *bsearch(const void *key, const void *base, size_t count, size_t size, void *compare)",
+    "int rand(void)",
+    "void srand(unsigned int seed)",
+    "time_t time(time_t *t)",
+    "int __libc_start_main(void *main, int argc, char **argv, void *init, void *fini, void *rtld_fini, void *stack_end)",
+    "int __cxa_atexit(void *func, void *arg, void *dso_handle)",
+    "void __stack_chk_fail(void)",
+    // POSIX
+    "int open(const char *path, int flags, ...)",
+    "int openat(int dirfd, const char *path, int flags, ...)",
+    "int close(int fd)",
+    "ssize_t read(int fd, void *buf, size_t count)",
+    "ssize_t write(int fd, const void *buf, size_t count)",
+    "ssize_t pread(int fd, void *buf, size_t count, off_t offset)",
+    "ssize_t pwrite(int fd, const void *buf, size_t count, off_t offset)",
+    "off_t lseek(int fd, off_t offset, int whence)",
+    "int stat(const char *path, struct stat *buf)",
+    "int fstat(int fd, struct stat *buf)",
+    "int lstat(const char *path, struct stat *buf)",
+    "int access(const char *path, int mode)",
+    "int unlink(const char *path)",
+    "int mkdir(const char *path, mode_t mode)",
+    "int rmdir(const char *path)",
+    "int chdir(const char *path)",
+    "char *getcwd(char *buf, size_t size)",
+    "int chmod(const char *path, mode_t mode)",
+    "int dup(int fd)",
+    "int dup2(int oldfd, int newfd)",
+    "int pipe(int *fds)",
+    "int ioctl(int fd, unsigned long request, ...)",
+    "int fcntl(int fd, int cmd, ...)",
+    "void *mmap(void *addr, size_t len, int prot, int flags, int fd, off_t offset)",
+    "int munmap(void *addr, size_t len)",
+    "int mprotect(void *addr, size_t len, int prot)",
+    "pid_t fork(void)",
+    "int execve(const char *path, char **argv, char **envp)",
+    "int execvp(const char *file, char **argv)",
+    "int execl(const char *path, const char *arg, ...)",
+    "pid_t waitpid(pid_t pid, int *status, int options)",
+    "pid_t getpid(void)",
+    "pid_t getppid(void)",
+    "uid_t getuid(void)",
+    "uid_t geteuid(void)",
+    "int setuid(uid_t uid)",
+    "int kill(pid_t pid, int sig)",
+    "void *signal(int sig, void *handler)",
+    "int sigaction(int sig, const struct sigaction *act, struct sigaction *oldact)",
+    "long ptrace(int request, pid_t pid, void *addr, void *data)",
+    "unsigned int sleep(unsigned int seconds)",
+    "int usleep(useconds_t usec)",
+    "int socket(int domain, int type, int protocol)",
+    "int connect(int fd, const struct sockaddr *addr, socklen_t len)",
+    "int bind(int fd, const struct sockaddr *addr, socklen_t len)",
+    "int listen(int fd, int backlog)",
+    "int accept(int fd, struct sockaddr *addr, socklen_t *len)",
+    "ssize_t send(int fd, const void *buf, size_t len, int flags)",
+    "ssize_t recv(int fd, void *buf, size_t len, int flags)",
+    "ssize_t sendto(int fd, const void *buf, size_t len, int flags, const struct sockaddr *addr, socklen_t addrlen)",
+    "ssize_t recvfrom(int fd, void *buf, size_t len, int flags, struct sockaddr *addr, socklen_t *addrlen)",
+    "int setsockopt(int fd, int level, int name, const void *value, socklen_t len)",
+    "int shutdown(int fd, int how)",
+    "int getaddrinfo(const char *node, const char *service, const struct addrinfo *hints, struct addrinfo **res)",
+    "struct hostent *gethostbyname(const char *name)",
+    "void *dlopen(const char *path, int flags)",
+    "void *dlsym(void *handle, const char *symbol)",
+    "int dlclose(void *handle)",
+    "int pthread_create(pthread_t *thread, const pthread_attr_t *attr, void *start, void *arg)",
+    "int pthread_join(pthread_t thread, void **ret)",
+    "int pthread_mutex_lock(pthread_mutex_t *mutex)",
+    "int pthread_mutex_unlock(pthread_mutex_t *mutex)",
+    "long syscall(long number, ...)",
+    // Win32
+    "HANDLE CreateFileA(LPCSTR lpFileName, DWORD dwDesiredAccess, DWORD dwShareMode, LPSECURITY_ATTRIBUTES lpSecurityAttributes, DWORD dwCreationDisposition, DWORD dwFlagsAndAttributes, HANDLE hTemplateFile)",
+    "HANDLE CreateFileW(LPCWSTR lpFileName, DWORD dwDesiredAccess, DWORD dwShareMode, LPSECURITY_ATTRIBUTES lpSecurityAttributes, DWORD dwCreationDisposition, DWORD dwFlagsAndAttributes, HANDLE hTemplateFile)",
+    "BOOL ReadFile(HANDLE hFile, LPVOID lpBuffer, DWORD nNumberOfBytesToRead, LPDWORD lpNumberOfBytesRead, LPOVERLAPPED lpOverlapped)",
+    "BOOL WriteFile(HANDLE hFile, LPCVOID lpBuffer, DWORD nNumberOfBytesToWrite, LPDWORD lpNumberOfBytesWritten, LPOVERLAPPED lpOverlapped)",
+    "BOOL CloseHandle(HANDLE hObject)",
+    "BOOL DeleteFileA(LPCSTR lpFileName)",
+    "BOOL DeleteFileW(LPCWSTR lpFileName)",
+    "DWORD GetFileSize(HANDLE hFile, LPDWORD lpFileSizeHigh)",
+    "DWORD SetFilePointer(HANDLE hFile, LONG lDistanceToMove, PLONG lpDistanceToMoveHigh, DWORD dwMoveMethod)",
+    "HANDLE CreateFileMappingA(HANDLE hFile, LPSECURITY_ATTRIBUTES lpFileMappingAttributes, DWORD flProtect, DWORD dwMaximumSizeHigh, DWORD dwMaximumSizeLow, LPCSTR lpName)",
+    "LPVOID MapViewOfFile(HANDLE hFileMappingObject, DWORD dwDesiredAccess, DWORD dwFileOffsetHigh, DWORD dwFileOffsetLow, SIZE_T dwNumberOfBytesToMap)",
+    "LPVOID VirtualAlloc(LPVOID lpAddress, SIZE_T dwSize, DWORD flAllocationType, DWORD flProtect)",
+    "LPVOID VirtualAllocEx(HANDLE hProcess, LPVOID lpAddress, SIZE_T dwSize, DWORD flAllocationType, DWORD flProtect)",
+    "BOOL VirtualFree(LPVOID lpAddress, SIZE_T dwSize, DWORD dwFreeType)",
+    "BOOL VirtualProtect(LPVOID lpAddress, SIZE_T dwSize, DWORD flNewProtect, PDWORD lpflOldProtect)",
+    "HANDLE GetProcessHeap(void)",
+    "LPVOID HeapAlloc(HANDLE hHeap, DWORD dwFlags, SIZE_T dwBytes)",
+    "BOOL HeapFree(HANDLE hHeap, DWORD dwFlags, LPVOID lpMem)",
+    "HMODULE LoadLibraryA(LPCSTR lpLibFileName)",
+    "HMODULE LoadLibraryW(LPCWSTR lpLibFileName)",
+    "HMODULE LoadLibraryExA(LPCSTR lpLibFileName, HANDLE hFile, DWORD dwFlags)",
+    "HMODULE LoadLibraryExW(LPCWSTR lpLibFileName, HANDLE hFile, DWORD dwFlags)",
+    "HMODULE GetModuleHandleA(LPCSTR lpModuleName)",
+    "HMODULE GetModuleHandleW(LPCWSTR lpModuleName)",
+    "DWORD GetModuleFileNameA(HMODULE hModule, LPSTR lpFilename, DWORD nSize)",
+    "DWORD GetModuleFileNameW(HMODULE hModule, LPWSTR lpFilename, DWORD nSize)",
+    "FARPROC GetProcAddress(HMODULE hModule, LPCSTR lpProcName)",
+    "BOOL FreeLibrary(HMODULE hLibModule)",
+    "BOOL CreateProcessA(LPCSTR lpApplicationName, LPSTR lpCommandLine, LPSECURITY_ATTRIBUTES lpProcessAttributes, LPSECURITY_ATTRIBUTES lpThreadAttributes, BOOL bInheritHandles, DWORD dwCreationFlags, LPVOID lpEnvironment, LPCSTR lpCurrentDirectory, LPSTARTUPINFOA lpStartupInfo, LPPROCESS_INFORMATION lpProcessInformation)",
+    "BOOL CreateProcessW(LPCWSTR lpApplicationName, LPWSTR lpCommandLine, LPSECURITY_ATTRIBUTES lpProcessAttributes, LPSECURITY_ATTRIBUTES lpThreadAttributes, BOOL bInheritHandles, DWORD dwCreationFlags, LPVOID lpEnvironment, LPCWSTR lpCurrentDirectory, LPSTARTUPINFOW lpStartupInfo, LPPROCESS_INFORMATION lpProcessInformation)",
+    "HANDLE OpenProcess(DWORD dwDesiredAccess, BOOL bInheritHandle, DWORD dwProcessId)",
+    "BOOL TerminateProcess(HANDLE hProcess, UINT uExitCode)",
+    "void ExitProcess(UINT uExitCode)",
+    "HANDLE GetCurrentProcess(void)",
+    "DWORD GetCurrentProcessId(void)",
+    "DWORD GetCurrentThreadId(void)",
+    "HANDLE CreateThread(LPSECURITY_ATTRIBUTES lpThreadAttributes, SIZE_T dwStackSize, LPTHREAD_START_ROUTINE lpStartAddress, LPVOID lpParameter, DWORD dwCreationFlags, LPDWORD lpThreadId)",
+    "HANDLE CreateRemoteThread(HANDLE hProcess, LPSECURITY_ATTRIBUTES lpThreadAttributes, SIZE_T dwStackSize, LPTHREAD_START_ROUTINE lpStartAddress, LPVOID lpParameter, DWORD dwCreationFlags, LPDWORD lpThreadId)",
+    "BOOL ReadProcessMemory(HANDLE hProcess, LPCVOID lpBaseAddress, LPVOID lpBuffer, SIZE_T nSize, SIZE_T *lpNumberOfBytesRead)",
+    "BOOL WriteProcessMemory(HANDLE hProcess, LPVOID lpBaseAddress, LPCVOID lpBuffer, SIZE_T nSize, SIZE_T *lpNumberOfBytesWritten)",
+    "DWORD WaitForSingleObject(HANDLE hHandle, DWORD dwMilliseconds)",
+    "void Sleep(DWORD dwMilliseconds)",
+    "HANDLE CreateMutexA(LPSECURITY_ATTRIBUTES lpMutexAttributes, BOOL bInitialOwner, LPCSTR lpName)",
+    "HANDLE CreateEventA(LPSECURITY_ATTRIBUTES lpEventAttributes, BOOL bManualReset, BOOL bInitialState, LPCSTR lpName)",
+    "void EnterCriticalSection(LPCRITICAL_SECTION lpCriticalSection)",
+    "void LeaveCriticalSection(LPCRITICAL_SECTION lpCriticalSection)",
+    "DWORD GetLastError(void)",
+    "void SetLastError(DWORD dwErrCode)",
+    "BOOL IsDebuggerPresent(void)",
+    "DWORD GetTickCount(void)",
+    "BOOL QueryPerformanceCounter(LARGE_INTEGER *lpPerformanceCount)",
+    "DWORD GetEnvironmentVariableA(LPCSTR lpName, LPSTR lpBuffer, DWORD nSize)",
+    "int MultiByteToWideChar(UINT CodePage, DWORD dwFlags, LPCCH lpMultiByteStr, int cbMultiByte, LPWSTR lpWideCharStr, int cchWideChar)",
+    "int WideCharToMultiByte(UINT CodePage, DWORD dwFlags, LPCWCH lpWideCharStr, int cchWideChar, LPSTR lpMultiByteStr, int cbMultiByte, LPCCH lpDefaultChar, LPBOOL lpUsedDefaultChar)",
+    "LSTATUS RegOpenKeyExA(HKEY hKey, LPCSTR lpSubKey, DWORD ulOptions, REGSAM samDesired, PHKEY phkResult)",
+    "LSTATUS RegOpenKeyExW(HKEY hKey, LPCWSTR lpSubKey, DWORD ulOptions, REGSAM samDesired, PHKEY phkResult)",
+    "LSTATUS RegQueryValueExA(HKEY hKey, LPCSTR lpValueName, LPDWORD lpReserved, LPDWORD lpType, LPBYTE lpData, LPDWORD lpcbData)",
+    "LSTATUS RegSetValueExA(HKEY hKey, LPCSTR lpValueName, DWORD Reserved, DWORD dwType, const BYTE *lpData, DWORD cbData)",
+    "LSTATUS RegCloseKey(HKEY hKey)",
+    "int MessageBoxA(HWND hWnd, LPCSTR lpText, LPCSTR lpCaption, UINT uType)",
+    "int MessageBoxW(HWND hWnd, LPCWSTR lpText, LPCWSTR lpCaption, UINT uType)",
+    "int WSAStartup(WORD wVersionRequested, LPWSADATA lpWSAData)",
+    "HINTERNET InternetOpenA(LPCSTR lpszAgent, DWORD dwAccessType, LPCSTR lpszProxy, LPCSTR lpszProxyBypass, DWORD dwFlags)",
+    "HINTERNET InternetOpenUrlA(HINTERNET hInternet, LPCSTR lpszUrl, LPCSTR lpszHeaders, DWORD dwHeadersLength, DWORD dwFlags, DWORD_PTR dwContext)",
+    "BOOL InternetReadFile(HINTERNET hFile, LPVOID lpBuffer, DWORD dwNumberOfBytesToRead, LPDWORD lpdwNumberOfBytesRead)",
+];
+
+/// Argument of a known function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Param {
+    pub ty: String,
+    pub name: String,
+}
+
+/// Signature of a function from a system library.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiPrototype {
+    pub name: String,
+    pub returns: String,
+    pub params: Vec<Param>,
+    /// Whether more arguments may follow the named ones.
+    pub variadic: bool,
+}
+
+impl fmt::Display for ApiPrototype {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let separator = if self.returns.ends_with('*') { "" } else { " " };
+        f.write_fmt(format_args!("{}{separator}{}(", self.returns, self.name))?;
+        for (idx, param) in self.params.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(", ")?;
+            }
+
+            let separator = if param.ty.ends_with('*') { "" } else { " " };
+            f.write_fmt(format_args!("{}{separator}{}", param.ty, param.name))?;
+        }
+
+        match (self.variadic, self.params.is_empty()) {
+            (true, true) => f.write_str("...)"),
+            (true, false) => f.write_str(", ...)"),
+            (false, true) => f.write_str("void)"),
+            (false, false) => f.write_str(")"),
+        }
+    }
+}
+
+/// Split a declaration such as `const char *path` into its type and name.
+fn split_declarator(decl: &str) -> Option<(String, String)> {
+    let decl = decl.trim();
+    let start = decl.rfind(|c: char| !(c.is_alphanumeric() || c == '_'))? + 1;
+    let (ty, name) = decl.split_at(start);
+    if name.is_empty() || ty.trim().is_empty() {
+        return None;
+    }
+
+    Some((ty.trim().to_string(), name.to_string()))
+}
+
+fn parse(decl: &str) -> Option<ApiPrototype> {
+    let (head, params) = decl.strip_suffix(')')?.split_once('(')?;
+    let (returns, name) = split_declarator(head)?;
+    let mut prototype = ApiPrototype {
+        name,
+        returns,
+        params: Vec::new(),
+        variadic: false,
+    };
+
+    for param in params.split(',').map(str::trim) {
+        match param {
+            "void" => {}
+            "..." => prototype.variadic = true,
+            param => {
+                let (ty, name) = split_declarator(param)?;
+                prototype.params.push(Param { ty, name });
+            }
+        }
+    }
+
+    Some(prototype)
+}
+
+fn library() -> &'static BTreeMap<String, ApiPrototype> {
+    static LIBRARY: OnceLock<BTreeMap<String, ApiPrototype>> = OnceLock::new();
+    LIBRARY.get_or_init(|| {
+        DECLARATIONS
+            .iter()
+            .filter_map(|decl| parse(decl))
+            .map(|prototype| (prototype.name.clone(), prototype))
+            .collect()
+    })
+}
+
+/// Name of a symbol without the decorations added by linkers and calling conventions, e.g.
+/// `memcpy` for `memcpy@GLIBC_2.14`, `__imp__CreateFileA@28` or `_memcpy`.
+fn undecorate(name: &str) -> &str {
+    let name = name.split_once("@@").map_or(name, |(name, _)| name);
+    let name = name
+        .strip_prefix("__imp_")
+        .or_else(|| name.strip_prefix("_imp_"))
+        .unwrap_or(name);
+
+    // stdcall and fastcall functions are suffixed by the bytes of arguments they take
+    let name = match name.rsplit_once('@') {
+        Some((stripped, suffix))
+            if suffix.bytes().all(|b| b.is_ascii_digit())
+                || suffix.starts_with("GLIBC")
+                || suffix == "plt" =>
+        {
+            stripped
+        }
+        _ => name,
+    };
+
+    name.strip_prefix('@').unwrap_or(name)
+}
+
+/// Signature of a function from a system library by its symbol name.
+pub fn lookup(name: &str) -> Option<&'static ApiPrototype> {
+    let library = library();
+    let name = undecorate(name);
+
+    // cdecl functions are prefixed by an underscore on 32-bit Windows and on macOS
+    library.get(name).or_else(|| library.get(name.strip_prefix('_')?))
+}
+
+impl Processor {
+    /// Signature of the function from a system library whose symbol is at an address, usually
+    /// the thunk or table entry of an import.
+    pub fn api_prototype(&self, addr: PhysAddr) -> Option<&'static ApiPrototype> {
+        let symbol = self.index.get_sym_by_addr(addr)?;
+        lookup(symbol.as_str())
+    }
+}
@@ -1,4 +1,5 @@
 use object::{Pod, ReadRef};
+use std::fmt;
 
 /// Address in memory.
 pub type VirtAddr = usize;
@@ -6,6 +7,29 @@ pub type VirtAddr = usize;
 /// Address in the file world.
 pub type PhysAddr = usize;
 
+/// How addresses are displayed in the listing.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum AddressMode {
+    /// Full virtual address.
+    #[default]
+    Virtual,
+    /// Offset relative to the image base.
+    Relative,
+    /// Offset relative to the start of the section containing the address.
+    Section,
+}
+
+/// How registers are named in the listing.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RegisterNames {
+    /// Names given by the instruction set, e.g. `x10` on RISC-V and `r14` on ARM.
+    #[default]
+    Architectural,
+    /// Aliases given by the calling convention, e.g. `a0` on RISC-V and `lr` on ARM.
+    /// Registers that pass arguments on x86-64 are annotated with the argument they hold.
+    Abi,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum SectionKind {
     /// Anything we don't know how to parse or anything that is just bytes.
@@ -44,6 +68,23 @@ pub enum SectionKind {
     Unloaded
 }
 
+/// Access to a section once it's loaded.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Permissions {
+    pub read: bool,
+    pub write: bool,
+    pub execute: bool,
+}
+
+impl fmt::Display for Permissions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let flag = |set: bool, c: &'static str| if set { c } else { "-" };
+        f.write_str(flag(self.read, "r"))?;
+        f.write_str(flag(self.write, "w"))?;
+        f.write_str(flag(self.execute, "x"))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Section {
     /// Section name.
@@ -55,7 +96,7 @@ pub struct Section {
     /// What kind of data the section holds.
     pub kind: SectionKind,
 
-    /// Section data.
+    /// Section data, borrowed from the memory mapped binary instead of being copied.
     bytes: &'static [u8],
 
     /// Address where section starts.
@@ -63,6 +104,12 @@ pub struct Section {
 
     /// Section start + size of uncompressed data.
     pub end: PhysAddr,
+
+    /// Offset of the section in the file, if it's stored in the file at all.
+    pub file_offset: Option<u64>,
+
+    /// Access to the section once loaded.
+    pub permissions: Permissions,
 }
 
 impl Section {
@@ -80,7 +127,9 @@ impl Section {
             kind,
             bytes,
             start,
-            end
+            end,
+            file_offset: None,
+            permissions: Permissions::default(),
         }
     }
 
@@ -131,6 +180,20 @@ impl<T> AddressMap<T> {
     pub fn extend(&mut self, other: Self) {
         self.mapping.extend(other.mapping)
     }
+
+    /// Last item at or before an address, assumes [`Self`] is sorted.
+    #[inline]
+    pub fn at_or_before(&self, addr: usize) -> Option<&Addressed<T>> {
+        let idx = self.mapping.partition_point(|item| item.addr <= addr);
+        self.mapping[..idx].last()
+    }
+
+    /// First item after an address, assumes [`Self`] is sorted.
+    #[inline]
+    pub fn after(&self, addr: usize) -> Option<&Addressed<T>> {
+        let idx = self.mapping.partition_point(|item| item.addr <= addr);
+        self.mapping.get(idx)
+    }
 }
 
 impl<T> Default for AddressMap<T> {
@@ -186,69 +249,64 @@ impl<T> Ord for Addressed<T> {
 
 /// Truncates string past the max width with a '..'.
 pub fn encode_hex_bytes_truncated(bytes: &[u8], max_width: usize, is_padded: bool) -> String {
-    unsafe {
-        const HEX_NUGGET: [u8; 16] = *b"0123456789abcdef";
-
-        assert!(max_width > 2, "max width most be at least 2");
-
-        let len = bytes.len() * 3;
-        let pad = is_padded as usize * max_width.saturating_sub(len);
-        let mut buffer = Vec::with_capacity(len + pad);
-        let slice = &mut buffer[..];
-        let mut idx = 0;
-
-        // truncation has to occur
-        if bytes.len() * 3 > max_width {
-            let bytes = &bytes[..max_width / 3 - 1];
-
-            for byte in bytes {
-                *slice.get_unchecked_mut(idx) = HEX_NUGGET[(byte >> 4) as usize];
-                *slice.get_unchecked_mut(idx + 1) = HEX_NUGGET[(byte & 0b1111) as usize];
-                idx += 2;
-
-                if idx + 1 != len || is_padded {
-                    *slice.get_unchecked_mut(idx) = b' ';
-                    idx += 1;
-                }
-            }
-
-            *slice.get_unchecked_mut(idx) = b'.';
-            *slice.get_unchecked_mut(idx + 1) = b'.';
-            idx += 2;
-
-            if is_padded {
-                *slice.get_unchecked_mut(idx) = b' ';
-                *slice.get_unchecked_mut(idx + 1) = b' ';
-                idx += 2;
-            }
-
-            buffer.set_len(idx);
-            return String::from_utf8_unchecked(buffer);
-        }
+    const HEX_NUGGET: [u8; 16] = *b"0123456789abcdef";
 
-        for byte in bytes {
-            *slice.get_unchecked_mut(idx) = HEX_NUGGET[(byte >> 4) as usize];
-            *slice.get_unchecked_mut(idx + 1) = HEX_NUGGET[(byte & 0b1111) as usize];
-            idx += 2;
+    assert!(max_width > 2, "max width most be at least 2");
 
-            if idx + 1 != len || is_padded {
-                *slice.get_unchecked_mut(idx) = b' ';
-                idx += 1;
-            }
-        }
+    let len = bytes.len() * 3;
+    let pad = is_padded as usize * max_width.saturating_sub(len);
+    let mut buffer = String::with_capacity(len + pad);
+
+    // truncation has to occur
+    let truncated = len > max_width;
+    let shown = if truncated {
+        &bytes[..max_width / 3 - 1]
+    } else {
+        bytes
+    };
 
-        for _ in 0..pad {
-            *slice.get_unchecked_mut(idx) = b' ';
-            idx += 1;
+    for byte in shown {
+        buffer.push(HEX_NUGGET[(byte >> 4) as usize] as char);
+        buffer.push(HEX_NUGGET[(byte & 0b1111) as usize] as char);
+
+        if buffer.len() + 1 != len || is_padded {
+            buffer.push(' ');
         }
+    }
 
-        buffer.set_len(idx);
-        String::from_utf8_unchecked(buffer)
+    if truncated {
+        buffer.push_str(if is_padded { "..  " } else { ".." });
+        return buffer;
     }
+
+    buffer.extend(std::iter::repeat_n(' ', pad));
+    buffer
 }
 
 #[cfg(test)]
 mod tests {
+    use super::{AddressMap, Addressed};
+
+    #[test]
+    fn address_map_neighbours() {
+        let map = AddressMap {
+            mapping: [0x10, 0x14, 0x20]
+                .into_iter()
+                .map(|addr| Addressed { addr, item: () })
+                .collect(),
+        };
+
+        assert_eq!(map.at_or_before(0x8).map(|item| item.addr), None);
+        assert_eq!(map.at_or_before(0x14).map(|item| item.addr), Some(0x14));
+        assert_eq!(map.at_or_before(0x1f).map(|item| item.addr), Some(0x14));
+        assert_eq!(map.at_or_before(0x100).map(|item| item.addr), Some(0x20));
+
+        assert_eq!(map.after(0x8).map(|item| item.addr), Some(0x10));
+        assert_eq!(map.after(0x10).map(|item| item.addr), Some(0x14));
+        assert_eq!(map.after(0x15).map(|item| item.addr), Some(0x20));
+        assert_eq!(map.after(0x20).map(|item| item.addr), None);
+    }
+
     #[test]
     fn encode_hex_bytes_truncted() {
         assert_eq!(
@@ -275,5 +333,12 @@ mod tests {
             super::encode_hex_bytes_truncated(&[0x10, 0x12, 0x3], 11, true),
             "10 12 03   "
         );
+
+        assert_eq!(super::encode_hex_bytes_truncated(&[], 48, false), "");
+
+        assert_eq!(
+            super::encode_hex_bytes_truncated(&[0xaa; 20], 48, false),
+            format!("{}..", "aa ".repeat(15))
+        );
     }
 }
@@ -0,0 +1,200 @@
+//! Symbols of a binary written in formats other tools can import.
+//!
+//! Both formats are the ones read by [`crate::import`], so an export can be imported again.
+
+use crate::Project;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Prefix of names given to functions that don't have a symbol.
+const FUNCTION_PLACEHOLDER: &str = "sub_";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub addr: usize,
+    pub name: String,
+    pub function: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    pub name: String,
+    pub start: usize,
+    pub end: usize,
+    pub code: bool,
+}
+
+impl Project {
+    /// Symbols of the binary as the user sees them, with their renames applied and functions that
+    /// were only discovered given a placeholder name.
+    pub fn symbols(&self, symbols: impl IntoIterator<Item = Symbol>) -> Vec<Symbol> {
+        let mut merged: BTreeMap<usize, Symbol> =
+            symbols.into_iter().map(|sym| (sym.addr, sym)).collect();
+
+        for &addr in self.functions.iter() {
+            merged.entry(addr).or_insert_with(|| Symbol {
+                addr,
+                name: format!("{FUNCTION_PLACEHOLDER}{addr:x}"),
+                function: true,
+            });
+        }
+
+        for (&addr, name) in self.renames.iter() {
+            let function = self.functions.contains(&addr);
+            let symbol = merged.entry(addr).or_insert_with(|| Symbol {
+                addr,
+                name: String::new(),
+                function,
+            });
+
+            symbol.name = name.clone();
+        }
+
+        merged.into_values().collect()
+    }
+}
+
+/// Map file in the format IDA writes, listing the segments followed by the symbols in them.
+/// Symbols outside of any segment are left out, as they can't be addressed.
+pub fn map(segments: &[Segment], symbols: &[Symbol], entrypoint: Option<usize>) -> String {
+    let mut out = String::new();
+    let locate = |addr: usize| {
+        let idx = segments.iter().position(|seg| (seg.start..seg.end).contains(&addr))?;
+        Some(format!(
+            "{:04X}:{:08X}",
+            idx + 1,
+            addr - segments[idx].start
+        ))
+    };
+
+    let _ = writeln!(
+        out,
+        "\n Start         Length     Name                   Class"
+    );
+    for (idx, segment) in segments.iter().enumerate() {
+        let class = if segment.code { "CODE" } else { "DATA" };
+        let _ = writeln!(
+            out,
+            " {:04X}:00000000 {:09X}H {:<22} {class}",
+            idx + 1,
+            segment.end - segment.start,
+            segment.name,
+        );
+    }
+
+    let _ = writeln!(out, "\n\n  Address         Publics by Value\n");
+    for symbol in symbols {
+        // names are separated by whitespace, which demangled names contain
+        let name: String = symbol.name.split_whitespace().collect();
+        if let Some(location) = locate(symbol.addr) {
+            let _ = writeln!(out, " {location}       {name}");
+        }
+    }
+
+    if let Some(location) = entrypoint.and_then(locate) {
+        let _ = writeln!(out, "\nProgram entry point at {location}");
+    }
+
+    out
+}
+
+/// Symbol table in the format Ghidra exports, with the type of symbol that was found.
+pub fn csv(symbols: &[Symbol]) -> String {
+    let mut out = String::from("\"Name\",\"Location\",\"Type\"\n");
+
+    for symbol in symbols {
+        let kind = if symbol.function { "Function" } else { "Label" };
+        let name = symbol.name.replace('"', "\"\"");
+        let _ = writeln!(out, "\"{name}\",\"{:08x}\",\"{kind}\"", symbol.addr);
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::{Analysis, Format};
+
+    fn symbol(addr: usize, name: &str, function: bool) -> Symbol {
+        Symbol {
+            addr,
+            name: name.to_string(),
+            function,
+        }
+    }
+
+    fn segments() -> Vec<Segment> {
+        vec![
+            Segment {
+                name: ".text".to_string(),
+                start: 0x401000,
+                end: 0x402000,
+                code: true,
+            },
+            Segment {
+                name: ".data".to_string(),
+                start: 0x403000,
+                end: 0x403100,
+                code: false,
+            },
+        ]
+    }
+
+    #[test]
+    fn symbols() {
+        let mut project = Project::default();
+        project.rename(0x401000, "entry");
+        project.rename(0x403000, "config");
+        project.add_function(0x401080);
+
+        let symbols = project.symbols([symbol(0x401000, "_start", true)]);
+        assert_eq!(
+            symbols,
+            [
+                symbol(0x401000, "entry", true),
+                symbol(0x401080, "sub_401080", true),
+                symbol(0x403000, "config", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn map_roundtrip() {
+        let symbols = [
+            symbol(0x401000, "main", true),
+            symbol(0x401080, "std::vec::Vec<u8, A>::push", true),
+            symbol(0x403010, "config", false),
+            symbol(0x500000, "unmapped", false),
+        ];
+
+        let map = map(&segments(), &symbols, Some(0x401000));
+        assert!(map.contains(" 0001:00000000 000001000H .text                  CODE\n"));
+        assert!(map.contains(" 0002:00000010       config\n"));
+        assert!(map.ends_with("Program entry point at 0001:00000000\n"));
+
+        let segment = |number, _: &str| segments().get(number as usize - 1).map(|seg| seg.start);
+        let analysis = Analysis::parse(Format::Map, &map, segment).unwrap();
+        assert_eq!(analysis.names[&0x401000], "main");
+        assert_eq!(analysis.names[&0x401080], "std::vec::Vec<u8,A>::push");
+        assert_eq!(analysis.names[&0x403010], "config");
+        assert_eq!(analysis.names.len(), 3);
+    }
+
+    #[test]
+    fn csv_roundtrip() {
+        let symbols = [
+            symbol(0x401000, "main", true),
+            symbol(0x401080, "sub_401080", true),
+            symbol(0x403010, "say \"hi\"", false),
+        ];
+
+        let csv = csv(&symbols);
+        assert!(csv.starts_with("\"Name\",\"Location\",\"Type\"\n\"main\",\"00401000\""));
+
+        let analysis = Analysis::parse(Format::Ghidra, &csv, |_, _| None).unwrap();
+        assert_eq!(analysis.names[&0x403010], "say \"hi\"");
+        assert_eq!(analysis.names.len(), 2);
+        assert_eq!(analysis.functions.len(), 2);
+    }
+}
@@ -0,0 +1,647 @@
+//! Analysis done in other tools, which seeds the symbols, comments and functions of a project.
+//!
+//! Supported are the symbol and comment tables Ghidra exports as CSV, scripts of radare2 commands
+//! as written by `f*`, `afl*` and `CC*`, `.map` files written by IDA or MSVC's linker and the
+//! kernel's symbols as listed in `/proc/kallsyms`.
+
+use crate::{Error, Project};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// Prefixes of names that tools generate for things they didn't find a name for.
+const PLACEHOLDERS: [&str; 13] = [
+    "FUN_", "LAB_", "DAT_", "PTR_", "sub_", "loc_", "unk_", "off_", "byte_", "word_", "dword_",
+    "qword_", "fcn.",
+];
+
+/// Placeholders that are given to functions.
+const FUNCTION_PLACEHOLDERS: [&str; 3] = ["FUN_", "sub_", "fcn."];
+
+/// Prefixes of radare2 flags that describe the layout of the binary rather than its code.
+const R2_STRUCTURAL: [&str; 5] = ["section.", "segment.", "reloc.", "imp.", "sym.imp."];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Symbol or comment table exported by Ghidra.
+    Ghidra,
+    /// Script of radare2 commands.
+    Radare2,
+    /// Map file written by IDA or a linker.
+    Map,
+    /// Symbols of a running kernel, as listed in `/proc/kallsyms`.
+    Kallsyms,
+}
+
+impl Format {
+    /// Guess the format of a file by its extension, or otherwise by its contents.
+    pub fn detect(path: &Path, text: &str) -> Option<Self> {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("csv") => return Some(Self::Ghidra),
+            Some("map") => return Some(Self::Map),
+            Some("r2" | "rc") => return Some(Self::Radare2),
+            _ => {}
+        }
+
+        if path.file_name().is_some_and(|name| name == "kallsyms") {
+            return Some(Self::Kallsyms);
+        }
+
+        let first = text.lines().find(|line| !line.trim().is_empty())?.trim();
+        if first.starts_with('"') && first.contains(',') {
+            return Some(Self::Ghidra);
+        }
+
+        if text.contains("Publics by Value") || text.contains("Publics by Name") {
+            return Some(Self::Map);
+        }
+
+        // <address> <type> <name>
+        let words: Vec<&str> = first.split_whitespace().collect();
+        if let [addr, kind, _] = words[..] {
+            if addr.len() >= 8 && addr.bytes().all(|b| b.is_ascii_hexdigit()) && kind.len() == 1 {
+                return Some(Self::Kallsyms);
+            }
+        }
+
+        let r2 = ["f ", "fs ", "af+ ", "afn ", "CC ", "CCu ", "\"f ", "\"CC"];
+        r2.iter().any(|cmd| first.starts_with(cmd)).then_some(Self::Radare2)
+    }
+}
+
+/// Names, comments and functions found by another tool.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Analysis {
+    pub names: BTreeMap<usize, String>,
+    pub comments: BTreeMap<usize, String>,
+    pub functions: BTreeSet<usize>,
+    /// Where the image of a kernel starts, which kallsyms lists as `_text`.
+    pub kernel_base: Option<usize>,
+}
+
+impl Analysis {
+    /// Parse the analysis, resolving addresses of map files that are relative to a segment by
+    /// it's number and name.
+    pub fn parse(
+        format: Format,
+        text: &str,
+        segment: impl Fn(u16, &str) -> Option<usize>,
+    ) -> Result<Self, Error> {
+        let mut analysis = Self::default();
+
+        match format {
+            Format::Ghidra => analysis.parse_ghidra(text)?,
+            Format::Radare2 => analysis.parse_radare2(text),
+            Format::Map => analysis.parse_map(text, segment)?,
+            Format::Kallsyms => analysis.parse_kallsyms(text)?,
+        }
+
+        Ok(analysis)
+    }
+
+    /// Move every address so that the kernel's image starts at `addr`, undoing the kernel
+    /// randomizing where it's loaded. Does nothing if the analysis isn't of a kernel.
+    pub fn rebase(&mut self, addr: usize) {
+        let from = match self.kernel_base {
+            Some(from) => from,
+            None => return,
+        };
+
+        let rebase = |old: usize| old.wrapping_sub(from).wrapping_add(addr);
+        self.names = std::mem::take(&mut self.names)
+            .into_iter()
+            .map(|(old, name)| (rebase(old), name))
+            .collect();
+        self.comments = std::mem::take(&mut self.comments)
+            .into_iter()
+            .map(|(old, comment)| (rebase(old), comment))
+            .collect();
+        self.functions = self.functions.iter().map(|old| rebase(*old)).collect();
+        self.kernel_base = Some(addr);
+    }
+
+    /// Name an address, or only mark it as a function if the name is a placeholder.
+    fn name(&mut self, addr: usize, name: &str, function: bool) {
+        let placeholder = PLACEHOLDERS.iter().find(|prefix| {
+            name.strip_prefix(**prefix)
+                .is_some_and(|hex| !hex.is_empty() && hex.bytes().all(|b| b.is_ascii_hexdigit()))
+        });
+
+        if function || placeholder.is_some_and(|prefix| FUNCTION_PLACEHOLDERS.contains(prefix)) {
+            self.functions.insert(addr);
+        }
+
+        if placeholder.is_none() && !name.is_empty() {
+            self.names.insert(addr, name.to_string());
+        }
+    }
+
+    fn parse_ghidra(&mut self, text: &str) -> Result<(), Error> {
+        let mut lines = text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+        let header = match lines.next() {
+            Some((_, header)) => split_csv(header),
+            None => return Ok(()),
+        };
+
+        let column = |names: &[&str]| header.iter().position(|col| names.contains(&col.as_str()));
+        let addr = column(&["Location", "Address"]).ok_or(Error::InvalidImport(
+            1,
+            "there's no 'Location' or 'Address' column",
+        ))?;
+        let name = column(&["Name", "Label"]);
+        let comment = column(&["Comment"]);
+        let kind = column(&["Type"]);
+
+        if name.is_none() && comment.is_none() {
+            return Err(Error::InvalidImport(
+                1,
+                "there's no 'Name' or 'Comment' column",
+            ));
+        }
+
+        for (idx, line) in lines {
+            let fields = split_csv(line);
+            let field = |column: Option<usize>| column.and_then(|col| fields.get(col));
+
+            // external symbols don't have an address in the binary
+            let addr = match fields.get(addr).and_then(|addr| parse_ghidra_addr(addr)) {
+                Some(addr) => addr,
+                None if field(Some(addr)).is_some_and(|addr| addr.starts_with("External")) => {
+                    continue
+                }
+                None => return Err(Error::InvalidImport(idx + 1, "address is invalid")),
+            };
+
+            if let Some(name) = field(name) {
+                let function = field(kind).is_some_and(|kind| kind == "Function");
+                self.name(addr, name, function);
+            }
+
+            if let Some(comment) = field(comment).filter(|comment| !comment.is_empty()) {
+                self.comments.insert(addr, comment.clone());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Commands of a script that aren't understood are skipped, as scripts often change settings
+    /// or seek around in between.
+    fn parse_radare2(&mut self, text: &str) {
+        for line in text.lines() {
+            // commands can be quoted, so that they aren't interpreted by radare2 further
+            let line = line.trim();
+            let line = match line.strip_prefix('"') {
+                Some(line) => line.replacen('"', "", 1),
+                None => line.to_string(),
+            };
+
+            // commands are followed by their arguments, and optionally the address they apply to
+            let (cmd, at) = match line.rsplit_once(" @ ") {
+                Some((cmd, at)) => (cmd.trim(), parse_number(at.trim())),
+                None => (line.trim(), None),
+            };
+
+            let (name, args) = cmd.split_once(' ').unwrap_or((cmd, ""));
+            let words: Vec<&str> = args.split_whitespace().collect();
+
+            match name {
+                // f <name> [<size>] [<addr>]
+                "f" => {
+                    let addr = at.or_else(|| words.last().and_then(|addr| parse_number(addr)));
+                    let (flag, addr) = match (words.first(), addr) {
+                        (Some(flag), Some(addr)) if words.len() > 1 || at.is_some() => {
+                            (*flag, addr)
+                        }
+                        _ => continue,
+                    };
+
+                    if R2_STRUCTURAL.iter().any(|prefix| flag.starts_with(prefix)) {
+                        continue;
+                    }
+
+                    let function = flag.starts_with("fcn.");
+                    self.name(addr, flag.strip_prefix("sym.").unwrap_or(flag), function);
+                }
+                // af+ <addr> <name>
+                "af+" => {
+                    if let (Some(addr), Some(name)) = (words.first(), words.get(1)) {
+                        if let Some(addr) = parse_number(addr) {
+                            self.name(addr, name, true);
+                        }
+                    }
+                }
+                // afn <name> [<addr>]
+                "afn" => {
+                    let addr = at.or_else(|| words.get(1).and_then(|addr| parse_number(addr)));
+                    if let (Some(name), Some(addr)) = (words.first(), addr) {
+                        self.name(addr, name, true);
+                    }
+                }
+                "CC" | "CCu" => {
+                    let comment = match args.trim().strip_prefix("base64:") {
+                        Some(encoded) => match decode_base64(encoded) {
+                            Some(comment) => comment,
+                            None => continue,
+                        },
+                        None => args.trim().to_string(),
+                    };
+
+                    if let (Some(addr), false) = (at, comment.is_empty()) {
+                        self.comments.insert(addr, comment);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn parse_map(
+        &mut self,
+        text: &str,
+        segment: impl Fn(u16, &str) -> Option<usize>,
+    ) -> Result<(), Error> {
+        // names of the segments by their number, listed before the symbols
+        let mut segments = BTreeMap::new();
+        let mut in_publics = false;
+
+        for (idx, line) in text.lines().enumerate() {
+            let words: Vec<&str> = line.split_whitespace().collect();
+
+            if line.contains("Publics by Value") || line.contains("Publics by Name") {
+                in_publics = true;
+                continue;
+            }
+
+            let (number, offset) = match words.first().and_then(|addr| parse_segmented(addr)) {
+                Some(addr) => addr,
+                None => continue,
+            };
+
+            // <segment>:<offset> <length>H <name> <class>
+            if !in_publics {
+                if let (Some(_), Some(name)) = (words.get(1), words.get(2)) {
+                    segments.insert(number, name.to_string());
+                }
+                continue;
+            }
+
+            // <segment>:<offset> <name> [<rva+base> [f] [<object>]]
+            let name = match words.get(1) {
+                Some(name) => *name,
+                None => return Err(Error::InvalidImport(idx + 1, "symbol has no name")),
+            };
+
+            let absolute = words.get(2).filter(|addr| addr.len() >= 8);
+            let addr = match absolute.and_then(|addr| usize::from_str_radix(addr, 16).ok()) {
+                Some(addr) => addr,
+                None => {
+                    let name = segments.get(&number).map_or("", String::as_str);
+                    match segment(number, name) {
+                        Some(base) => base + offset,
+                        None => return Err(Error::InvalidImport(idx + 1, "segment is unknown")),
+                    }
+                }
+            };
+
+            let function = words.get(3).is_some_and(|flag| *flag == "f");
+            self.name(addr, name, function);
+        }
+
+        Ok(())
+    }
+
+    /// Symbols of modules are skipped, as they're loaded wherever the kernel found room for them.
+    fn parse_kallsyms(&mut self, text: &str) -> Result<(), Error> {
+        let mut hidden = true;
+
+        for (idx, line) in text.lines().enumerate() {
+            // <address> <type> <name> [[<module>]]
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let (addr, kind, name) = match words[..] {
+                [addr, kind, name] => (addr, kind, name),
+                [_, _, _, module] if module.starts_with('[') => continue,
+                [] => continue,
+                _ => return Err(Error::InvalidImport(idx + 1, "symbol has no type or name")),
+            };
+
+            let addr = match usize::from_str_radix(addr, 16) {
+                Ok(addr) => addr,
+                Err(..) => return Err(Error::InvalidImport(idx + 1, "address is invalid")),
+            };
+
+            // absolute symbols aren't in the kernel's image, e.g. offsets of per-CPU variables
+            hidden &= addr == 0;
+            if addr == 0 || kind.eq_ignore_ascii_case("a") {
+                continue;
+            }
+
+            if name == "_text" {
+                self.kernel_base = Some(addr);
+            }
+
+            self.name(addr, name, kind.eq_ignore_ascii_case("t"));
+        }
+
+        if hidden {
+            return Err(Error::InvalidImport(
+                1,
+                "every address is zero, as the kernel hides them from users other than root",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl Project {
+    /// Add analysis of another tool, without overwriting names or comments that are already
+    /// there. Returns how many names, comments and functions were added.
+    pub fn import(&mut self, analysis: Analysis) -> (usize, usize, usize) {
+        let mut added = (0, 0, 0);
+
+        for (addr, name) in analysis.names {
+            if let std::collections::btree_map::Entry::Vacant(entry) = self.renames.entry(addr) {
+                entry.insert(name);
+                added.0 += 1;
+            }
+        }
+
+        for (addr, comment) in analysis.comments {
+            if let std::collections::btree_map::Entry::Vacant(entry) = self.comments.entry(addr) {
+                entry.insert(comment);
+                added.1 += 1;
+            }
+        }
+
+        for addr in analysis.functions {
+            added.2 += self.functions.insert(addr) as usize;
+        }
+
+        self.dirty |= added != (0, 0, 0);
+        added
+    }
+}
+
+fn parse_number(s: &str) -> Option<usize> {
+    match s.strip_prefix("0x") {
+        Some(hex) => usize::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+}
+
+/// Addresses like `00401000`, `0x401000` or `ram:00401000`.
+fn parse_ghidra_addr(s: &str) -> Option<usize> {
+    let s = s.rsplit(':').next()?;
+    let s = s.strip_prefix("0x").unwrap_or(s);
+    usize::from_str_radix(s, 16).ok()
+}
+
+/// Addresses like `0001:00000010`, which are an offset into a segment.
+fn parse_segmented(s: &str) -> Option<(u16, usize)> {
+    let (segment, offset) = s.split_once(':')?;
+    let segment = u16::from_str_radix(segment, 16).ok()?;
+    let offset = usize::from_str_radix(offset, 16).ok()?;
+    Some((segment, offset))
+}
+
+/// Fields of a line of comma separated values, which may be quoted.
+fn split_csv(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            // quotes inside of a quoted field are escaped by doubling them
+            ('"', true) if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            ('"', _) => quoted = !quoted,
+            (',', false) => fields.push(std::mem::take(&mut field)),
+            (c, _) => field.push(c),
+        }
+    }
+
+    fields.push(field);
+    fields.into_iter().map(|field| field.trim().to_string()).collect()
+}
+
+fn decode_base64(s: &str) -> Option<String> {
+    let mut bytes = Vec::new();
+    let (mut bits, mut count) = (0u32, 0);
+
+    for c in s.trim_end_matches('=').bytes() {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+
+        bits = (bits << 6) | value as u32;
+        count += 6;
+        if count >= 8 {
+            count -= 8;
+            bytes.push((bits >> count) as u8);
+        }
+    }
+
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(format: Format, text: &str) -> Analysis {
+        Analysis::parse(format, text, |number, name| match (number, name) {
+            (1, ".text") => Some(0x401000),
+            _ => None,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn ghidra() {
+        let analysis = parse(
+            Format::Ghidra,
+            "\"Name\",\"Location\",\"Type\",\"Namespace\",\"Source\"\n\
+             \"main\",\"00401000\",\"Function\",\"Global\",\"Imported\"\n\
+             \"FUN_00401080\",\"00401080\",\"Function\",\"Global\",\"Default\"\n\
+             \"key, \"\"xor\"\"\",\"ram:00402000\",\"Label\",\"Global\",\"User Defined\"\n\
+             \"puts\",\"External[00000000]\",\"Function\",\"<EXTERNAL>\",\"Imported\"\n",
+        );
+
+        assert_eq!(analysis.names[&0x401000], "main");
+        assert_eq!(analysis.names[&0x402000], "key, \"xor\"");
+        assert_eq!(analysis.names.len(), 2);
+        assert_eq!(analysis.functions, BTreeSet::from([0x401000, 0x401080]));
+
+        let analysis = parse(
+            Format::Ghidra,
+            "Address,Comment\n0x401004,decrypts the config\n",
+        );
+        assert_eq!(analysis.comments[&0x401004], "decrypts the config");
+    }
+
+    #[test]
+    fn ghidra_invalid() {
+        let segment = |_, _: &str| None;
+        let text = "\"Name\",\"Type\"\n\"main\",\"Function\"\n";
+        assert!(Analysis::parse(Format::Ghidra, text, segment).is_err());
+
+        let text = "\"Name\",\"Location\"\n\"main\",\"somewhere\"\n";
+        assert!(Analysis::parse(Format::Ghidra, text, segment).is_err());
+    }
+
+    #[test]
+    fn radare2() {
+        let analysis = parse(
+            Format::Radare2,
+            "fs symbols\n\
+             f sym.main 42 0x00401000\n\
+             f fcn.00401080 16 0x00401080\n\
+             f section..text 4096 0x00401000\n\
+             f sym.imp.puts 6 0x00401200\n\
+             \"af+ 0x401100 parse_args f n\"\n\
+             afn decrypt @ 0x401180\n\
+             CCu base64:Y2hlY2tzIGFyZ2M= @ 0x401004\n\
+             \"CC xor key @ 0x401008\"\n\
+             e asm.arch=x86\n",
+        );
+
+        assert_eq!(analysis.names[&0x401000], "main");
+        assert_eq!(analysis.names[&0x401100], "parse_args");
+        assert_eq!(analysis.names[&0x401180], "decrypt");
+        assert_eq!(analysis.names.len(), 3);
+        assert!(analysis.functions.contains(&0x401080));
+        assert_eq!(analysis.comments[&0x401004], "checks argc");
+        assert_eq!(analysis.comments[&0x401008], "xor key");
+    }
+
+    #[test]
+    fn map() {
+        let analysis = parse(
+            Format::Map,
+            "\n Start         Length     Name                   Class\n \
+             0001:00000000 000001000H .text                  CODE\n\n  \
+             Address         Publics by Value\n\n \
+             0001:00000000       _main\n \
+             0001:00000080       sub_401080\n \
+             0002:00000010       _config                    00403010     config.obj\n \
+             0001:00000100       _decrypt                   00401100 f   crypt.obj\n\n\
+             Program entry point at 0001:00000000\n",
+        );
+
+        assert_eq!(analysis.names[&0x401000], "_main");
+        assert_eq!(analysis.names[&0x403010], "_config");
+        assert_eq!(analysis.names[&0x401100], "_decrypt");
+        assert_eq!(analysis.functions, BTreeSet::from([0x401080, 0x401100]));
+    }
+
+    #[test]
+    fn map_unknown_segment() {
+        let text = "Address Publics by Value\n0003:00000010 _data\n";
+        assert!(Analysis::parse(Format::Map, text, |_, _| None).is_err());
+    }
+
+    #[test]
+    fn kallsyms() {
+        let mut analysis = parse(
+            Format::Kallsyms,
+            "ffffffff9a000000 T _text\n\
+             ffffffff9a000000 T _stext\n\
+             ffffffff9a001000 T do_one_initcall\n\
+             ffffffff9b200000 D jiffies\n\
+             0000000000001000 A fixed_percpu_data\n\
+             ffffffffc0a01000 t ext4_fill_super\t[ext4]\n",
+        );
+
+        assert_eq!(analysis.names[&0xffffffff9a001000], "do_one_initcall");
+        assert_eq!(analysis.names[&0xffffffff9b200000], "jiffies");
+        assert_eq!(analysis.names.len(), 3);
+        assert_eq!(
+            analysis.functions,
+            BTreeSet::from([0xffffffff9a000000, 0xffffffff9a001000])
+        );
+        assert_eq!(analysis.kernel_base, Some(0xffffffff9a000000));
+
+        analysis.rebase(0xffffffff81000000);
+        assert_eq!(analysis.names[&0xffffffff81001000], "do_one_initcall");
+        assert!(analysis.functions.contains(&0xffffffff81001000));
+        assert_eq!(analysis.kernel_base, Some(0xffffffff81000000));
+    }
+
+    #[test]
+    fn kallsyms_hidden() {
+        let text = "0000000000000000 T _text\n0000000000000000 t do_one_initcall\n";
+        assert!(Analysis::parse(Format::Kallsyms, text, |_, _| None).is_err());
+    }
+
+    #[test]
+    fn detect() {
+        assert_eq!(Format::detect(Path::new("a.csv"), ""), Some(Format::Ghidra));
+        assert_eq!(
+            Format::detect(Path::new("a"), "\nf sym.main 1 0x10\n"),
+            Some(Format::Radare2)
+        );
+        assert_eq!(
+            Format::detect(Path::new("a.txt"), "Address Publics by Value"),
+            Some(Format::Map)
+        );
+        assert_eq!(
+            Format::detect(Path::new("/proc/kallsyms"), ""),
+            Some(Format::Kallsyms)
+        );
+        assert_eq!(
+            Format::detect(Path::new("syms.txt"), "ffffffff81000000 T _text\n"),
+            Some(Format::Kallsyms)
+        );
+        assert_eq!(Format::detect(Path::new("a"), "hello"), None);
+    }
+
+    #[test]
+    fn keeps_existing() {
+        let mut project = Project::default();
+        project.rename(0x10, "mine");
+
+        let mut analysis = Analysis::default();
+        analysis.names.insert(0x10, "theirs".to_string());
+        analysis.names.insert(0x20, "new".to_string());
+        analysis.functions.insert(0x20);
+
+        assert_eq!(project.import(analysis), (1, 0, 1));
+        assert_eq!(project.renames[&0x10], "mine");
+        assert_eq!(project.renames[&0x20], "new");
+    }
+
+    #[test]
+    fn functions_become_symbols() {
+        let analysis = parse(
+            Format::Radare2,
+            "afn main @ 0x401000\n\
+             f fcn.00401080 16 0x00401080\n\
+             \"af+ 0x401100 parse_args f n\"\n\
+             f obj.key 16 0x00402000\n",
+        );
+
+        let mut project = Project::default();
+        project.import(analysis);
+
+        // the binary's own symbols keep their name
+        let mut index = debugvault::Index::default();
+        index.insert_functions(vec![(0x401000, "entry".to_string())]);
+        index.insert_functions(project.named_functions());
+
+        let name = |addr| index.get_sym_by_addr(addr).map(|sym| sym.as_str().to_string());
+        assert_eq!(name(0x401000).as_deref(), Some("entry"));
+        assert_eq!(name(0x401080).as_deref(), Some("sub_401080"));
+        assert_eq!(index.get_func_by_name("parse_args"), Some(0x401100));
+        // names of data stay out of the functions
+        assert_eq!(name(0x402000), None);
+    }
+}
@@ -0,0 +1,430 @@
+//! Persisted analysis state of a binary.
+//!
+//! Everything the user adds on top of the automatic analysis (renamed symbols, comments, patches,
+//! bookmarks and discovered functions) is stored in a `.bite` file. The file is keyed by the
+//! SHA-256 of the binary, so opening the same binary again from anywhere restores the analysis.
+//! The file also keeps some [`Stats`] on how the binary was worked on.
+
+pub mod export;
+pub mod import;
+pub mod session;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Version of the project format, bumped on breaking changes.
+const VERSION: u32 = 1;
+
+/// File extension used for project files.
+pub const EXTENSION: &str = "bite";
+
+/// Number of analysis timings kept in [`Stats::analysis_times`].
+const MAX_ANALYSIS_TIMES: usize = 16;
+
+#[derive(Debug)]
+pub enum Error {
+    IO(std::io::Error),
+    Serialize(serde_yaml::Error),
+    MissingDataDir,
+    UnsupportedVersion(u32),
+    InvalidImport(usize, &'static str),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IO(err) => f.write_fmt(format_args!("Failed to access project: '{err}'.")),
+            Self::Serialize(err) => {
+                f.write_fmt(format_args!("Failed to (de)serialize project: '{err}'."))
+            }
+            Self::MissingDataDir => f.write_str("You must have a data directory set."),
+            Self::UnsupportedVersion(version) => {
+                f.write_fmt(format_args!("Project version {version} isn't supported."))
+            }
+            Self::InvalidImport(line, reason) => {
+                f.write_fmt(format_args!("Line {line} of the import is invalid, {reason}."))
+            }
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::IO(err)
+    }
+}
+
+impl From<serde_yaml::Error> for Error {
+    fn from(err: serde_yaml::Error) -> Self {
+        Error::Serialize(err)
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Project {
+    /// Version of the format the project was written in.
+    #[serde(default)]
+    pub version: u32,
+
+    /// Hex encoded SHA-256 of the binary.
+    pub hash: String,
+
+    /// Where the binary was last opened from.
+    #[serde(default)]
+    pub binary: PathBuf,
+
+    /// Symbol names assigned by the user, by address.
+    #[serde(default)]
+    pub renames: BTreeMap<usize, String>,
+
+    /// Comments attached to an address.
+    #[serde(default)]
+    pub comments: BTreeMap<usize, String>,
+
+    /// Bytes overwritten starting at an address.
+    #[serde(default)]
+    pub patches: BTreeMap<usize, Vec<u8>>,
+
+    /// Bookmarked addresses with a description.
+    #[serde(default)]
+    pub bookmarks: BTreeMap<usize, String>,
+
+    /// Function starts found by analysis or marked by the user.
+    #[serde(default)]
+    pub functions: BTreeSet<usize>,
+
+    /// Definitions of structures added by the user in C, by name.
+    #[serde(default)]
+    pub structs: BTreeMap<String, String>,
+
+    /// Names of the structures the data at an address is shown as.
+    #[serde(default)]
+    pub applied: BTreeMap<usize, String>,
+
+    /// Definitions of enumerations added by the user in C, by name.
+    #[serde(default)]
+    pub enums: BTreeMap<String, String>,
+
+    /// Names of the enumerations the immediates of the instruction at an address are shown as.
+    #[serde(default)]
+    pub applied_enums: BTreeMap<usize, String>,
+
+    /// Usage metrics of the project.
+    #[serde(default)]
+    pub stats: Stats,
+
+    /// When the time spent on the project was last added to [`Stats::time_spent`].
+    #[serde(skip)]
+    session_start: Option<Instant>,
+
+    /// Whether there are any changes that aren't yet written to disk.
+    #[serde(skip)]
+    dirty: bool,
+}
+
+/// Metrics of how a project was worked on, useful for estimating the effort it took.
+///
+/// These are only ever stored in the project file and never sent anywhere.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Stats {
+    /// Number of times the binary was opened.
+    #[serde(default)]
+    pub sessions: usize,
+
+    /// Time spent with the binary opened, over all sessions.
+    #[serde(default)]
+    pub time_spent: Duration,
+
+    /// Functions that were navigated to.
+    #[serde(default)]
+    pub functions_visited: BTreeSet<usize>,
+
+    /// Number of symbols renamed.
+    #[serde(default)]
+    pub renames: usize,
+
+    /// How long analyzing the binary took in the most recent sessions, oldest first.
+    #[serde(default)]
+    pub analysis_times: Vec<Duration>,
+}
+
+/// Hex encoded SHA-256 of some bytes.
+pub fn hash(bytes: &[u8]) -> String {
+    let digest = Sha256::digest(bytes);
+    digest.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Directory where all project files are stored.
+pub fn directory() -> Result<PathBuf, Error> {
+    match dirs::data_dir() {
+        Some(mut dir) => {
+            dir.push("bite");
+            dir.push("projects");
+            Ok(dir)
+        }
+        None => Err(Error::MissingDataDir),
+    }
+}
+
+impl Project {
+    /// Creates an empty project for a binary.
+    pub fn new(hash: &str, binary: &Path) -> Self {
+        Self {
+            version: VERSION,
+            hash: hash.to_string(),
+            binary: binary.to_path_buf(),
+            ..Default::default()
+        }
+    }
+
+    /// Where the project of a binary with a given hash is stored.
+    pub fn path_for(hash: &str) -> Result<PathBuf, Error> {
+        Ok(directory()?.join(hash).with_extension(EXTENSION))
+    }
+
+    /// Loads the project of a binary if it was opened before, otherwise create an empty one.
+    pub fn open(hash: &str, binary: &Path) -> Result<Self, Error> {
+        let path = Self::path_for(hash)?;
+
+        if !path.is_file() {
+            return Ok(Self::new(hash, binary));
+        }
+
+        let mut this = Self::load(&path)?;
+        if this.binary != binary {
+            this.binary = binary.to_path_buf();
+            this.dirty = true;
+        }
+
+        log::complex!(
+            w "[project::open] restored project ",
+            g format!("{path:?}"),
+            w "."
+        );
+
+        Ok(this)
+    }
+
+    /// Reads a project from a `.bite` file.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let raw = std::fs::read_to_string(path)?;
+        let this: Self = serde_yaml::from_str(&raw)?;
+
+        if this.version > VERSION {
+            return Err(Error::UnsupportedVersion(this.version));
+        }
+
+        Ok(this)
+    }
+
+    /// Writes the project to it's `.bite` file if anything changed.
+    pub fn save(&mut self) -> Result<(), Error> {
+        if let Some(start) = self.session_start.replace(Instant::now()) {
+            self.stats.time_spent += start.elapsed();
+            self.dirty = true;
+        }
+
+        if !self.dirty {
+            return Ok(());
+        }
+
+        let path = Self::path_for(&self.hash)?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        self.save_as(&path)?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Writes the project to a given path.
+    pub fn save_as(&self, path: &Path) -> Result<(), Error> {
+        let raw = serde_yaml::to_string(self)?;
+        std::fs::write(path, raw)?;
+        Ok(())
+    }
+
+    /// Whether there are any changes that aren't yet written to disk.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    pub fn rename(&mut self, addr: usize, name: impl Into<String>) {
+        self.renames.insert(addr, name.into());
+        self.stats.renames += 1;
+        self.dirty = true;
+    }
+
+    pub fn comment(&mut self, addr: usize, comment: impl Into<String>) {
+        let comment = comment.into();
+        if comment.is_empty() {
+            self.comments.remove(&addr);
+        } else {
+            self.comments.insert(addr, comment);
+        }
+        self.dirty = true;
+    }
+
+    pub fn patch(&mut self, addr: usize, bytes: Vec<u8>) {
+        self.patches.insert(addr, bytes);
+        self.dirty = true;
+    }
+
+    pub fn bookmark(&mut self, addr: usize, desc: impl Into<String>) {
+        self.bookmarks.insert(addr, desc.into());
+        self.dirty = true;
+    }
+
+    pub fn remove_bookmark(&mut self, addr: usize) {
+        self.dirty |= self.bookmarks.remove(&addr).is_some();
+    }
+
+    pub fn add_function(&mut self, addr: usize) {
+        self.dirty |= self.functions.insert(addr);
+    }
+
+    /// Functions along with their name, functions that weren't renamed are named after their
+    /// address.
+    pub fn named_functions(&self) -> Vec<(usize, String)> {
+        self.functions
+            .iter()
+            .map(|&addr| match self.renames.get(&addr) {
+                Some(name) => (addr, name.clone()),
+                None => (addr, format!("sub_{addr:x}")),
+            })
+            .collect()
+    }
+
+    pub fn define_struct(&mut self, name: impl Into<String>, definition: impl Into<String>) {
+        self.structs.insert(name.into(), definition.into());
+        self.dirty = true;
+    }
+
+    pub fn apply_struct(&mut self, addr: usize, name: impl Into<String>) {
+        let name = name.into();
+        if name.is_empty() {
+            self.applied.remove(&addr);
+        } else {
+            self.applied.insert(addr, name);
+        }
+        self.dirty = true;
+    }
+
+    pub fn define_enum(&mut self, name: impl Into<String>, definition: impl Into<String>) {
+        self.enums.insert(name.into(), definition.into());
+        self.dirty = true;
+    }
+
+    pub fn apply_enum(&mut self, addr: usize, name: impl Into<String>) {
+        let name = name.into();
+        if name.is_empty() {
+            self.applied_enums.remove(&addr);
+        } else {
+            self.applied_enums.insert(addr, name);
+        }
+        self.dirty = true;
+    }
+
+    /// Start tracking the time spent on the project, given how long the binary took to analyze.
+    pub fn start_session(&mut self, analysis_time: Duration) {
+        if self.stats.analysis_times.len() == MAX_ANALYSIS_TIMES {
+            self.stats.analysis_times.remove(0);
+        }
+
+        self.stats.analysis_times.push(analysis_time);
+        self.stats.sessions += 1;
+        self.session_start = Some(Instant::now());
+        self.dirty = true;
+    }
+
+    /// Time spent on the project, including the time since it was last saved.
+    pub fn time_spent(&self) -> Duration {
+        let unsaved = self.session_start.map_or(Duration::ZERO, |start| start.elapsed());
+        self.stats.time_spent + unsaved
+    }
+
+    pub fn visit_function(&mut self, addr: usize) {
+        self.dirty |= self.stats.functions_visited.insert(addr);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut project = Project::new(&hash(b"binary"), Path::new("/tmp/binary"));
+        project.rename(0x1000, "main");
+        project.comment(0x1004, "checks argc");
+        project.patch(0x1008, vec![0x90, 0x90]);
+        project.bookmark(0x2000, "interesting");
+        project.add_function(0x1000);
+        project.define_struct("point", "struct point { int x; int y; };");
+        project.apply_struct(0x3000, "point");
+        project.define_enum("mode", "enum mode { A = 0x1, B = 0x2 };");
+        project.apply_enum(0x1010, "mode");
+
+        let raw = serde_yaml::to_string(&project).unwrap();
+        let parsed: Project = serde_yaml::from_str(&raw).unwrap();
+        project.dirty = false;
+        assert_eq!(parsed, project);
+    }
+
+    #[test]
+    fn named_functions() {
+        let mut project = Project::default();
+        project.add_function(0x1000);
+        project.add_function(0x1080);
+        project.rename(0x1080, "parse_config");
+        project.rename(0x2000, "buffer");
+        assert_eq!(
+            project.named_functions(),
+            [
+                (0x1000, "sub_1000".to_string()),
+                (0x1080, "parse_config".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn hash_is_hex_sha256() {
+        assert_eq!(
+            hash(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn empty_comment_removes() {
+        let mut project = Project::default();
+        project.comment(0x10, "a");
+        project.comment(0x10, "");
+        assert!(project.comments.is_empty());
+        assert!(project.is_dirty());
+    }
+
+    #[test]
+    fn stats() {
+        let mut project = Project::default();
+        project.rename(0x10, "a");
+        project.rename(0x10, "b");
+        project.visit_function(0x10);
+        project.visit_function(0x10);
+        assert_eq!(project.stats.renames, 2);
+        assert_eq!(project.stats.functions_visited.len(), 1);
+
+        for _ in 0..=MAX_ANALYSIS_TIMES {
+            project.start_session(Duration::from_millis(1));
+        }
+
+        assert_eq!(project.stats.sessions, MAX_ANALYSIS_TIMES + 1);
+        assert_eq!(project.stats.analysis_times.len(), MAX_ANALYSIS_TIMES);
+        assert!(project.time_spent() >= project.stats.time_spent);
+    }
+}
@@ -0,0 +1,147 @@
+//! What was open when bite was last closed, so it can be restored on startup.
+//!
+//! Unlike projects, there is a single session shared by all binaries. It also keeps the files
+//! that were opened recently.
+
+use crate::Error;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Number of recently opened files that are remembered.
+pub const MAX_RECENT: usize = 10;
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    /// Files that were opened, most recent first.
+    #[serde(default)]
+    pub recent: Vec<PathBuf>,
+
+    /// Binaries that were open, in the order of their tabs.
+    #[serde(default)]
+    pub binaries: Vec<Binary>,
+
+    /// Index of the binary that was shown.
+    #[serde(default)]
+    pub active: usize,
+
+    /// How the panes were arranged.
+    #[serde(default)]
+    pub layout: Option<Layout>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Binary {
+    pub path: PathBuf,
+
+    /// Address the listing was at.
+    #[serde(default)]
+    pub addr: Option<usize>,
+}
+
+/// Arrangement of panes, identified by their title.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Layout {
+    Pane(String),
+    Tabs {
+        children: Vec<Layout>,
+        active: usize,
+    },
+    Horizontal(Vec<Layout>),
+    Vertical(Vec<Layout>),
+}
+
+/// Where the session is stored.
+pub fn path() -> Result<PathBuf, Error> {
+    match dirs::data_dir() {
+        Some(dir) => Ok(dir.join("bite").join("session.yaml")),
+        None => Err(Error::MissingDataDir),
+    }
+}
+
+impl Session {
+    /// Loads the previous session, which is empty if there wasn't one.
+    pub fn load() -> Result<Self, Error> {
+        let path = path()?;
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+
+        Self::load_from(&path)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self, Error> {
+        let raw = std::fs::read_to_string(path)?;
+        Ok(serde_yaml::from_str(&raw)?)
+    }
+
+    pub fn save(&self) -> Result<(), Error> {
+        let path = path()?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        self.save_as(&path)
+    }
+
+    pub fn save_as(&self, path: &Path) -> Result<(), Error> {
+        let raw = serde_yaml::to_string(self)?;
+        std::fs::write(path, raw)?;
+        Ok(())
+    }
+
+    /// Move a file to the front of the recently opened files.
+    pub fn add_recent(&mut self, path: &Path) {
+        self.recent.retain(|recent| recent != path);
+        self.recent.insert(0, path.to_path_buf());
+        self.recent.truncate(MAX_RECENT);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let session = Session {
+            recent: vec![PathBuf::from("/bin/ls")],
+            binaries: vec![Binary {
+                path: PathBuf::from("/bin/ls"),
+                addr: Some(0x1000),
+            }],
+            active: 0,
+            layout: Some(Layout::Horizontal(vec![
+                Layout::Tabs {
+                    children: vec![Layout::Pane("Disassembly".to_string())],
+                    active: 0,
+                },
+                Layout::Pane("Logs".to_string()),
+            ])),
+        };
+
+        let raw = serde_yaml::to_string(&session).unwrap();
+        let parsed: Session = serde_yaml::from_str(&raw).unwrap();
+        assert_eq!(parsed, session);
+
+        let parsed: Session = serde_yaml::from_str("recent: []").unwrap();
+        assert_eq!(parsed, Session::default());
+    }
+
+    #[test]
+    fn recent() {
+        let mut session = Session::default();
+        for idx in 0..MAX_RECENT + 2 {
+            session.add_recent(Path::new(&idx.to_string()));
+        }
+
+        session.add_recent(Path::new("5"));
+        assert_eq!(session.recent.len(), MAX_RECENT);
+        assert_eq!(session.recent[0], Path::new("5"));
+        assert_eq!(session.recent[1], Path::new("11"));
+        assert_eq!(
+            session.recent.iter().filter(|path| *path == Path::new("5")).count(),
+            1
+        );
+    }
+}
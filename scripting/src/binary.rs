@@ -0,0 +1,212 @@
+//! The loaded binary as seen by scripts.
+
+use processor::Processor;
+use processor_shared::PhysAddr;
+use project::Project;
+use rhai::{Array, Blob, Dynamic, Engine, Map, INT};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+/// Change to the project made by a script.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Edit {
+    Rename(PhysAddr, String),
+    Comment(PhysAddr, String),
+    Bookmark(PhysAddr, String),
+}
+
+#[derive(Debug, Default)]
+struct Annotations {
+    renames: BTreeMap<PhysAddr, String>,
+    comments: BTreeMap<PhysAddr, String>,
+    /// Changes that haven't been applied to the project yet.
+    edits: Vec<Edit>,
+}
+
+/// Handle to a binary that's passed to scripts as `bin`.
+///
+/// Scripts can't change the project directly, instead the changes they make are recorded and
+/// applied to the project using [`Binary::apply`] once they finish.
+#[derive(Clone)]
+pub struct Binary {
+    processor: Arc<Processor>,
+    annotations: Arc<Mutex<Annotations>>,
+}
+
+impl Binary {
+    pub fn new(processor: Arc<Processor>, project: &Project) -> Self {
+        let annotations = Annotations {
+            renames: project.renames.clone(),
+            comments: project.comments.clone(),
+            edits: Vec::new(),
+        };
+
+        Self {
+            processor,
+            annotations: Arc::new(Mutex::new(annotations)),
+        }
+    }
+
+    /// Apply the changes scripts made to a project, returning how many there were.
+    pub fn apply(&self, project: &mut Project) -> usize {
+        let edits = std::mem::take(&mut self.annotations.lock().unwrap().edits);
+        let count = edits.len();
+
+        for edit in edits {
+            match edit {
+                Edit::Rename(addr, name) => project.rename(addr, name),
+                Edit::Comment(addr, comment) => project.comment(addr, comment),
+                Edit::Bookmark(addr, desc) => project.bookmark(addr, desc),
+            }
+        }
+
+        count
+    }
+
+    fn edit(&self, edit: Edit) {
+        let mut annotations = self.annotations.lock().unwrap();
+        match &edit {
+            Edit::Rename(addr, name) => {
+                annotations.renames.insert(*addr, name.clone());
+            }
+            Edit::Comment(addr, comment) => {
+                annotations.comments.insert(*addr, comment.clone());
+            }
+            Edit::Bookmark(..) => {}
+        }
+        annotations.edits.push(edit);
+    }
+
+    /// Name of the symbol at an address, including the names given by the user.
+    fn symbol(&self, addr: PhysAddr) -> Option<String> {
+        if let Some(name) = self.annotations.lock().unwrap().renames.get(&addr) {
+            return Some(name.clone());
+        }
+
+        self.processor.index.get_sym_by_addr(addr).map(|sym| sym.as_str().to_string())
+    }
+
+    fn symbols(&self) -> Array {
+        let renames = &self.annotations.lock().unwrap().renames;
+        let mut symbols = Array::new();
+
+        for sym in self.processor.index.syms.iter() {
+            let name = match renames.get(&sym.addr) {
+                Some(name) => name.clone(),
+                None => sym.item.as_str().to_string(),
+            };
+
+            let mut map = Map::new();
+            map.insert("name".into(), name.into());
+            map.insert("addr".into(), (sym.addr as INT).into());
+            symbols.push(map.into());
+        }
+
+        symbols
+    }
+
+    fn address_of(&self, name: &str) -> Option<PhysAddr> {
+        let renames = &self.annotations.lock().unwrap().renames;
+        match renames.iter().find(|(_, rename)| rename.as_str() == name) {
+            Some((&addr, _)) => Some(addr),
+            None => self.processor.index.get_func_by_name(name),
+        }
+    }
+
+    /// Hashes of the binary by their lowercase name, `()` for those that couldn't be computed.
+    fn hashes(&self) -> Map {
+        let hashes = &self.processor.hashes;
+        let mut map = Map::new();
+        map.insert("md5".into(), hashes.md5.clone().into());
+        map.insert("sha1".into(), hashes.sha1.clone().into());
+        map.insert("sha256".into(), hashes.sha256.clone().into());
+        map.insert("imphash".into(), optional(hashes.imphash.clone()));
+        map.insert("tlsh".into(), optional(hashes.tlsh.clone()));
+        map.insert("rich".into(), optional(hashes.rich.clone()));
+        map
+    }
+
+    fn sections(&self) -> Array {
+        let mut sections = Array::new();
+
+        for section in self.processor.sections() {
+            let mut map = Map::new();
+            map.insert("name".into(), section.name.clone().into());
+            map.insert("kind".into(), format!("{:?}", section.kind).into());
+            map.insert("start".into(), (section.start as INT).into());
+            map.insert("end".into(), (section.end as INT).into());
+            sections.push(map.into());
+        }
+
+        sections
+    }
+
+    /// Bytes starting at an address with the user's patches applied, stopping at the end of the
+    /// section.
+    fn bytes(&self, addr: PhysAddr, len: usize) -> Blob {
+        match self.processor.section_by_addr(addr) {
+            Some(section) => self.processor.patched_bytes(section, addr, len).into_owned(),
+            None => Blob::new(),
+        }
+    }
+
+    fn instruction(&self, addr: PhysAddr) -> Option<String> {
+        let inst = self.processor.instruction_by_addr(addr)?;
+        let tokens = self.processor.instruction_tokens(&inst, &self.processor.index);
+        Some(tokens.iter().map(|token| &*token.text).collect())
+    }
+}
+
+/// Addresses are passed around as integers by scripts.
+fn addr(value: INT) -> PhysAddr {
+    value as PhysAddr
+}
+
+/// Scripts use `()` for values that don't exist.
+fn optional<T: Into<Dynamic>>(value: Option<T>) -> Dynamic {
+    value.map_or(Dynamic::UNIT, Into::into)
+}
+
+/// Make the methods of [`Binary`] available to scripts.
+pub(crate) fn register(engine: &mut Engine) {
+    engine
+        .register_type_with_name::<Binary>("Binary")
+        .register_get("path", |bin: &mut Binary| {
+            bin.processor.path.display().to_string()
+        })
+        .register_get("entrypoint", |bin: &mut Binary| {
+            bin.processor.entrypoint as INT
+        })
+        .register_get("hashes", |bin: &mut Binary| bin.hashes())
+        .register_fn("sections", |bin: &mut Binary| bin.sections())
+        .register_fn("symbols", |bin: &mut Binary| bin.symbols())
+        .register_fn("symbol", |bin: &mut Binary, at: INT| {
+            optional(bin.symbol(addr(at)))
+        })
+        .register_fn("address_of", |bin: &mut Binary, name: &str| {
+            optional(bin.address_of(name).map(|addr| addr as INT))
+        })
+        .register_fn("bytes", |bin: &mut Binary, at: INT, len: INT| {
+            bin.bytes(addr(at), len.max(0) as usize)
+        })
+        .register_fn("instruction", |bin: &mut Binary, at: INT| {
+            optional(bin.instruction(addr(at)))
+        })
+        .register_fn("next_instruction", |bin: &mut Binary, at: INT| {
+            let next = bin.processor.next_instruction(addr(at));
+            optional(next.map(|inst| inst.addr as INT))
+        })
+        .register_fn("comment_at", |bin: &mut Binary, at: INT| {
+            let annotations = bin.annotations.lock().unwrap();
+            optional(annotations.comments.get(&addr(at)).cloned())
+        })
+        .register_fn("rename", |bin: &mut Binary, at: INT, name: &str| {
+            bin.edit(Edit::Rename(addr(at), name.to_string()))
+        })
+        .register_fn("comment", |bin: &mut Binary, at: INT, comment: &str| {
+            bin.edit(Edit::Comment(addr(at), comment.to_string()))
+        })
+        .register_fn("bookmark", |bin: &mut Binary, at: INT, desc: &str| {
+            bin.edit(Edit::Bookmark(addr(at), desc.to_string()))
+        });
+}
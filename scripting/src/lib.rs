@@ -0,0 +1,298 @@
+//! Automation of bite using scripts written in [Rhai](https://rhai.rs).
+//!
+//! Plugins list their scripts in the `scripts` of their manifest, which run once when bite starts.
+//! Scripts can define an `on_load` function that's called with every binary that gets loaded and
+//! register panels that show the text returned by a function:
+//!
+//! ```rhai
+//! register_panel("Large functions", |bin| {
+//!     let text = "";
+//!     for sym in bin.symbols() {
+//!         text += `${sym.name} at ${sym.addr.to_hex()}\n`;
+//!     }
+//!     text
+//! });
+//!
+//! fn on_load(bin) {
+//!     for sym in bin.symbols() {
+//!         if sym.name.starts_with("FUN_") {
+//!             bin.rename(sym.addr, "sub_" + sym.addr.to_hex());
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! Scripts run from the terminal using `script <path>` get the loaded binary as `bin`. A [`Binary`]
+//! has a `path`, an `entrypoint`, `hashes` such as `bin.hashes.sha256` and the following methods:
+//!
+//! * `sections()` and `symbols()`, arrays of maps describing each of them.
+//! * `symbol(addr)` and `address_of(name)` to look up symbols.
+//! * `bytes(addr, len)`, `instruction(addr)` and `next_instruction(addr)` to read code and data.
+//! * `comment_at(addr)`, `rename(addr, name)`, `comment(addr, text)` and `bookmark(addr, desc)`.
+//!
+//! Lookups that don't find anything return `()`.
+
+mod binary;
+
+pub use binary::{Binary, Edit};
+
+use rhai::{CallFnOptions, Engine, EvalAltResult, FnPtr, Scope, AST};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Name of the function called with every binary that gets loaded.
+const ON_LOAD: &str = "on_load";
+
+#[derive(Debug)]
+pub enum Error {
+    /// The script failed to compile or raised an error while running.
+    Script(Box<EvalAltResult>),
+    /// A panel returned something other than a string.
+    NotText(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Script(err) => f.write_fmt(format_args!("{err}.")),
+            Self::NotText(tipe) => {
+                f.write_fmt(format_args!("Panels must return a string, not '{tipe}'."))
+            }
+        }
+    }
+}
+
+impl From<Box<EvalAltResult>> for Error {
+    fn from(err: Box<EvalAltResult>) -> Self {
+        Error::Script(err)
+    }
+}
+
+pub struct Script {
+    pub path: PathBuf,
+    ast: AST,
+}
+
+/// Script that failed to load.
+#[derive(Debug)]
+pub struct Rejected {
+    pub path: PathBuf,
+    pub reason: Error,
+}
+
+/// Panel registered by a script using `register_panel`.
+pub struct Panel {
+    pub title: String,
+    /// Index of the script that registered the panel.
+    script: usize,
+    render: FnPtr,
+}
+
+pub struct Scripts {
+    engine: Engine,
+    pub loaded: Vec<Script>,
+    pub rejected: Vec<Rejected>,
+    pub panels: Vec<Panel>,
+    /// Panels registered by the script that's running.
+    registered: Arc<Mutex<Vec<(String, FnPtr)>>>,
+}
+
+impl Default for Scripts {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scripts {
+    pub fn new() -> Self {
+        let registered: Arc<Mutex<Vec<(String, FnPtr)>>> = Arc::default();
+        let mut engine = Engine::new();
+
+        engine.on_print(|text| {
+            log::complex!(
+                w "[script] ",
+                w text.to_string(),
+            );
+        });
+
+        engine.on_debug(|text, source, pos| {
+            log::complex!(
+                w "[script] ",
+                g format!("{}:{pos} ", source.unwrap_or("script")),
+                w text.to_string(),
+            );
+        });
+
+        let panels = Arc::clone(&registered);
+        engine.register_fn("register_panel", move |title: &str, render: FnPtr| {
+            panels.lock().unwrap().push((title.to_string(), render));
+        });
+
+        binary::register(&mut engine);
+
+        Self {
+            engine,
+            loaded: Vec::new(),
+            rejected: Vec::new(),
+            panels: Vec::new(),
+            registered,
+        }
+    }
+
+    /// Run the scripts of every plugin that was loaded.
+    pub fn load(plugins: &plugins::Plugins) -> Self {
+        let mut this = Self::new();
+
+        for plugin in plugins.loaded.iter() {
+            for script in plugin.manifest.provides.scripts.iter() {
+                let path = plugin.path.join(script);
+                let ast = this.engine.compile_file(path.clone());
+                this.add(path, ast);
+            }
+        }
+
+        for script in this.loaded.iter() {
+            log::complex!(
+                w "[scripting::load] loaded ",
+                g script.path.display().to_string(),
+                w ".",
+            );
+        }
+
+        for Rejected { path, reason } in this.rejected.iter() {
+            log::complex!(
+                w "[scripting::load] ",
+                y format!("Rejected {path:?}: {reason}"),
+            );
+        }
+
+        this
+    }
+
+    fn add(&mut self, path: PathBuf, ast: Result<AST, Box<EvalAltResult>>) {
+        let result = ast.and_then(|ast| self.engine.run_ast(&ast).map(|_| ast));
+
+        // panels of a script that failed can't be rendered without it
+        let registered = std::mem::take(&mut *self.registered.lock().unwrap());
+
+        let ast = match result {
+            Ok(ast) => ast,
+            Err(err) => {
+                let reason = Error::Script(err);
+                self.rejected.push(Rejected { path, reason });
+                return;
+            }
+        };
+
+        let script = self.loaded.len();
+        for (title, render) in registered {
+            self.panels.push(Panel {
+                title,
+                script,
+                render,
+            });
+        }
+
+        self.loaded.push(Script { path, ast });
+    }
+
+    /// Call `on_load` of every script that defines it.
+    pub fn on_load(&self, binary: &Binary) {
+        for script in self.loaded.iter() {
+            let defined = script
+                .ast
+                .iter_functions()
+                .any(|func| func.name == ON_LOAD && func.params.len() == 1);
+
+            if !defined {
+                continue;
+            }
+
+            // the top level of the script already ran when it got loaded
+            let options = CallFnOptions::new().eval_ast(false);
+            let result = self.engine.call_fn_with_options::<rhai::Dynamic>(
+                options,
+                &mut Scope::new(),
+                &script.ast,
+                ON_LOAD,
+                (binary.clone(),),
+            );
+
+            if let Err(err) = result {
+                log::complex!(
+                    w "[scripting::on_load] ",
+                    y format!("{:?} failed: {}", script.path, Error::Script(err)),
+                );
+            }
+        }
+    }
+
+    /// Run a script with the binary available as `bin`.
+    pub fn run_file(&self, path: &Path, binary: &Binary) -> Result<(), Error> {
+        let ast = self.engine.compile_file(path.to_path_buf())?;
+
+        let mut scope = Scope::new();
+        scope.push("bin", binary.clone());
+        let result = self.engine.run_ast_with_scope(&mut scope, &ast);
+
+        // panels can only be registered by scripts that are loaded at startup
+        self.registered.lock().unwrap().clear();
+
+        result.map_err(Error::Script)
+    }
+
+    /// Text shown by a panel.
+    pub fn render(&self, panel: &Panel, binary: &Binary) -> Result<String, Error> {
+        let ast = &self.loaded[panel.script].ast;
+        let text: rhai::Dynamic = panel.render.call(&self.engine, ast, (binary.clone(),))?;
+
+        match text.into_string() {
+            Ok(text) => Ok(text),
+            Err(tipe) => Err(Error::NotText(tipe.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add(scripts: &mut Scripts, src: &str) {
+        let ast = scripts.engine.compile(src).map_err(Into::into);
+        scripts.add(PathBuf::from("test.rhai"), ast);
+    }
+
+    #[test]
+    fn panels() {
+        let mut scripts = Scripts::new();
+        add(
+            &mut scripts,
+            "register_panel(\"a\", |bin| \"\"); register_panel(\"b\", |bin| 1);",
+        );
+        add(
+            &mut scripts,
+            "fn on_load(bin) {}\nregister_panel(\"c\", |bin| bin.path);",
+        );
+
+        let titles: Vec<&str> = scripts.panels.iter().map(|p| p.title.as_str()).collect();
+        assert_eq!(titles, ["a", "b", "c"]);
+        assert_eq!(scripts.panels[2].script, 1);
+        assert_eq!(scripts.loaded.len(), 2);
+    }
+
+    #[test]
+    fn rejected() {
+        let mut scripts = Scripts::new();
+        add(
+            &mut scripts,
+            "register_panel(\"a\", |bin| \"\"); throw \"failed\";",
+        );
+        add(&mut scripts, "let x = ;");
+
+        assert!(scripts.loaded.is_empty());
+        assert!(scripts.panels.is_empty());
+        assert_eq!(scripts.rejected.len(), 2);
+        assert!(scripts.registered.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,217 @@
+//! Printing the disassembly of an object without opening a window, either to stdout in the format
+//! of `objdump -d` or as an HTML page.
+
+use crate::fail;
+use commands::ARGS;
+use config::{Column, CONFIG};
+use processor::{Block, BlockContent, Processor};
+use processor_shared::{AddressMode, SectionKind};
+use std::io::{BufWriter, IsTerminal, Write};
+use std::ops::Range;
+use tokenizing::{Color32, Token};
+
+pub fn disassemble() {
+    let path = ARGS.path.as_ref().expect("path is validated by the cli");
+    let processor = match Processor::parse(path) {
+        Ok(processor) => processor,
+        Err(err) => fail(format_args!("{err:?}")),
+    };
+
+    let ranges = match selected_ranges(&processor) {
+        Ok(ranges) => ranges,
+        Err(err) => fail(err),
+    };
+
+    if let Some(html) = &ARGS.html {
+        let result = std::fs::File::create(html).and_then(|file| {
+            processor.export_html(&ranges, AddressMode::Virtual, BufWriter::new(file))
+        });
+
+        if let Err(err) = result {
+            fail(format_args!("Failed to write '{}': {err}.", html.display()));
+        }
+
+        return;
+    }
+
+    let stdout = std::io::stdout();
+    let mut printer = Printer {
+        processor: &processor,
+        colored: stdout.is_terminal(),
+        out: BufWriter::new(stdout.lock()),
+    };
+
+    // stdout getting closed, e.g. by piping into `head`, isn't an error
+    let _ = printer.header().and_then(|_| {
+        for range in ranges {
+            printer.range(range)?;
+        }
+
+        printer.out.flush()
+    });
+}
+
+/// Address ranges of the sections and symbols selected on the command line.
+fn selected_ranges(processor: &Processor) -> Result<Vec<Range<usize>>, String> {
+    if ARGS.sections.is_empty() && ARGS.symbols.is_empty() {
+        let code = processor.sections().filter(|section| section.kind == SectionKind::Code);
+        return Ok(code.map(|section| section.start..section.end).collect());
+    }
+
+    let mut ranges = Vec::new();
+
+    for name in &ARGS.sections {
+        ranges.push(section_range(processor, name)?);
+    }
+
+    for name in &ARGS.symbols {
+        ranges.push(symbol_range(processor, name)?);
+    }
+
+    Ok(ranges)
+}
+
+/// Address range of the section with a name.
+pub fn section_range(processor: &Processor, name: &str) -> Result<Range<usize>, String> {
+    match processor.sections().find(|section| section.name == name) {
+        Some(section) => Ok(section.start..section.end),
+        None => Err(format!("Section '{name}' doesn't exist.")),
+    }
+}
+
+/// Address range of the symbol with a name, which runs up until the next symbol.
+pub fn symbol_range(processor: &Processor, name: &str) -> Result<Range<usize>, String> {
+    // imports share their name with the entry in the GOT, prefer the one that's code
+    let syms = &processor.index.syms;
+    let found: Vec<_> = syms
+        .iter()
+        .filter(|sym| sym.item.as_str() == name)
+        .filter_map(|sym| processor.section_by_addr(sym.addr).map(|sec| (sym.addr, sec)))
+        .collect();
+
+    let code = found.iter().find(|(_, section)| section.kind == SectionKind::Code);
+    let (addr, section) = match code.or(found.first()) {
+        Some(&found) => found,
+        None => return Err(format!("Symbol '{name}' doesn't exist.")),
+    };
+
+    let next = syms.get(syms.partition_point(|sym| sym.addr <= addr));
+    let end = next.map_or(section.end, |sym| sym.addr.min(section.end));
+    Ok(addr..end)
+}
+
+struct Printer<'a, W: Write> {
+    processor: &'a Processor,
+    colored: bool,
+    out: W,
+}
+
+impl<W: Write> Printer<'_, W> {
+    fn header(&mut self) -> std::io::Result<()> {
+        let path = self.processor.path.display();
+        let arch = self.processor.arch();
+        let bits = arch.address_size().map_or(64, |size| size.bytes() * 8);
+        let arch = format!("{arch:?}").to_lowercase().replace('_', "-");
+        let format = format!("{:?}{bits}-{arch}", self.processor.format()).to_lowercase();
+        writeln!(self.out)?;
+        writeln!(self.out, "{path}:     file format {format}")?;
+        Ok(())
+    }
+
+    fn range(&mut self, range: Range<usize>) -> std::io::Result<()> {
+        let blocks = self.processor.blocks(range.clone());
+        let content = blocks.first().map(|block| &block.content);
+
+        // selected symbols don't start at a section, but objdump still prints its name
+        if !matches!(content, Some(BlockContent::SectionStart { .. })) {
+            if let Some(section) = self.processor.section_by_addr(range.start) {
+                self.section(&section.name)?;
+            }
+        }
+
+        for block in &blocks {
+            self.block(block)?;
+        }
+
+        Ok(())
+    }
+
+    fn section(&mut self, name: &str) -> std::io::Result<()> {
+        writeln!(self.out)?;
+        writeln!(self.out, "Disassembly of section {name}:")?;
+        Ok(())
+    }
+
+    fn block(&mut self, block: &Block) -> std::io::Result<()> {
+        let bytes_width = self.processor.column_width(Column::Bytes, AddressMode::Virtual);
+
+        match &block.content {
+            BlockContent::SectionStart { section } => self.section(&section.name)?,
+            BlockContent::SectionEnd { .. } => {}
+            BlockContent::Label { symbol, .. } => {
+                let width = self
+                    .processor
+                    .arch()
+                    .address_size()
+                    .map_or(16, |size| size.bytes() as usize * 2);
+
+                writeln!(self.out)?;
+                self.paint(&format!("{:0width$x}", block.addr), CONFIG.colors.address)?;
+                self.paint(" <", CONFIG.colors.asm.label)?;
+                self.tokens(symbol.name())?;
+                self.paint(">:", CONFIG.colors.asm.label)?;
+                writeln!(self.out)?;
+            }
+            BlockContent::Instruction { inst, bytes } => {
+                self.address(block.addr)?;
+                self.paint(&format!("{bytes:<bytes_width$}"), CONFIG.colors.bytes)?;
+                write!(self.out, "\t")?;
+                self.tokens(inst)?;
+                writeln!(self.out)?;
+            }
+            BlockContent::Error { bytes, .. } => {
+                self.address(block.addr)?;
+                self.paint(&format!("{bytes:<bytes_width$}"), CONFIG.colors.bytes)?;
+                write!(self.out, "\t")?;
+                self.paint("(bad)", CONFIG.colors.asm.invalid)?;
+                writeln!(self.out)?;
+            }
+            // data is printed the way it's shown in the listing
+            _ => {
+                let columns = [Column::Address, Column::Bytes, Column::Instruction];
+                for line in block.lines(self.processor, AddressMode::Virtual) {
+                    let spans = line.layout(&columns, self.processor, AddressMode::Virtual);
+                    for span in spans {
+                        self.paint(&span.token.text, span.token.color)?;
+                    }
+                    writeln!(self.out)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn address(&mut self, addr: usize) -> std::io::Result<()> {
+        self.paint(&format!("{addr:>8x}:"), CONFIG.colors.address)?;
+        write!(self.out, "\t")
+    }
+
+    fn tokens(&mut self, tokens: &[Token]) -> std::io::Result<()> {
+        for token in tokens {
+            self.paint(&token.text, token.color)?;
+        }
+
+        Ok(())
+    }
+
+    /// Write text, colored using ANSI escape codes if stdout is a terminal.
+    fn paint(&mut self, text: &str, color: Color32) -> std::io::Result<()> {
+        if !self.colored {
+            return self.out.write_all(text.as_bytes());
+        }
+
+        let [r, g, b, _] = color.to_array();
+        write!(self.out, "\x1b[38;2;{r};{g};{b}m{text}\x1b[0m")
+    }
+}
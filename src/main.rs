@@ -6,9 +6,17 @@
 #[cfg(not(any(target_family = "windows", target_family = "unix")))]
 compile_error!("Bite can only be build for windows, macos and linux.");
 
+mod headless;
+mod server;
 mod wayland;
 use commands::ARGS;
 
+/// Print an error and exit, for the modes that don't have a window to show it in.
+fn fail(msg: impl std::fmt::Display) -> ! {
+    eprintln!("{msg}");
+    std::process::exit(1);
+}
+
 fn main() {
     #[cfg(target_os = "linux")]
     if nix::unistd::getuid() == 0.into() {
@@ -16,6 +24,16 @@ fn main() {
     }
 
     if ARGS.disassemble {
+        headless::disassemble();
+        return;
+    }
+
+    if ARGS.serve.is_some() {
+        server::serve();
+        return;
+    }
+
+    if ARGS.gui {
         let mut ui = gui::UI::new().unwrap();
         ui.process_args();
         ui.run();
@@ -0,0 +1,407 @@
+//! Analysis server for editors and other tools that want to use bite as a backend.
+//!
+//! Clients connect over TCP and send [JSON-RPC 2.0](https://www.jsonrpc.org/specification)
+//! requests, each on a line of its own. Responses are written back the same way. Every connection
+//! shares the binary that was loaded last, addresses are numbers or hex strings like `"0x1000"`.
+//!
+//! A range to disassemble ends with the section it starts in, and is refused if it has more than
+//! [`MAX_LINES`] lines.
+//!
+//! | method        | params                                        | result                       |
+//! |---------------|-----------------------------------------------|------------------------------|
+//! | `load`        | `path`                                        | description of the binary    |
+//! | `info`        |                                               | description of the binary    |
+//! | `disassemble` | `start` and `end`, a `symbol` or a `section`  | lines of the listing         |
+//! | `symbols`     | optional `filter` the names have to contain   | names and addresses          |
+//! | `symbol`      | `addr` to get a name or `name` to get an addr | name, address or `null`      |
+//! | `xrefs`       | `addr` of a function                          | its callers and callees      |
+
+use crate::fail;
+use crate::headless::{section_range, symbol_range};
+use commands::{Feature, ARGS};
+use config::CONFIG;
+use processor::Processor;
+use processor_shared::{AddressMode, PhysAddr};
+use serde_json::{json, Map, Value};
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// A method that needs a binary was called before one got loaded.
+const NOT_LOADED: i64 = -32001;
+/// The binary couldn't be parsed.
+const LOAD_FAILED: i64 = -32002;
+
+/// Most lines a `disassemble` request returns, larger ranges have to be requested in parts.
+const MAX_LINES: usize = 0x10000;
+
+/// Longest request in bytes, connections that send longer ones are closed.
+const MAX_REQUEST_LEN: usize = 0x100000;
+
+pub fn serve() {
+    let addr = ARGS.serve.expect("address is validated by the cli");
+
+    // anything other than loopback exposes the server to other machines
+    if !addr.ip().is_loopback() && !Feature::Network.enabled() {
+        fail(format_args!(
+            "Serving on '{addr}' requires networking, which is disabled."
+        ));
+    }
+
+    let server = Arc::new(Server::default());
+    if let Some(path) = &ARGS.path {
+        if let Err(err) = server.load(path) {
+            fail(&err.message);
+        }
+    }
+
+    let listener = match TcpListener::bind(addr) {
+        Ok(listener) => listener,
+        Err(err) => fail(format_args!("Failed to listen on '{addr}': {err}.")),
+    };
+
+    eprintln!("Listening on {addr}.");
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(_) => continue,
+        };
+
+        let server = Arc::clone(&server);
+        std::thread::spawn(move || server.connection(stream));
+    }
+}
+
+struct Error {
+    code: i64,
+    message: String,
+}
+
+impl Error {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    fn params(message: impl Into<String>) -> Self {
+        Self::new(INVALID_PARAMS, message)
+    }
+}
+
+#[derive(Default)]
+struct Server {
+    processor: RwLock<Option<Arc<Processor>>>,
+}
+
+impl Server {
+    fn connection(&self, stream: TcpStream) {
+        let mut reader = match stream.try_clone() {
+            Ok(stream) => BufReader::new(stream),
+            Err(_) => return,
+        };
+
+        let mut writer = BufWriter::new(stream);
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let len = match (&mut reader).take(MAX_REQUEST_LEN as u64).read_line(&mut line) {
+                Ok(0) | Err(_) => return,
+                Ok(len) => len,
+            };
+
+            // the rest of a request that's cut off can't be told apart from the next one
+            if len == MAX_REQUEST_LEN && !line.ends_with('\n') {
+                let message = format!("Requests can't be longer than {MAX_REQUEST_LEN} bytes.");
+                let err = Error::new(INVALID_REQUEST, message);
+                let _ = writeln!(writer, "{}", response(Value::Null, Err(err)));
+                let _ = writer.flush();
+                return;
+            }
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            // notifications don't get a response
+            let response = match self.message(&line) {
+                Some(response) => response,
+                None => continue,
+            };
+
+            if writeln!(writer, "{response}").and_then(|_| writer.flush()).is_err() {
+                return;
+            }
+        }
+    }
+
+    /// Handle a single request, returning the response if it has an id.
+    fn message(&self, message: &str) -> Option<Value> {
+        let request: Value = match serde_json::from_str(message) {
+            Ok(request) => request,
+            Err(err) => {
+                let err = Error::new(PARSE_ERROR, err.to_string());
+                return Some(response(Value::Null, Err(err)));
+            }
+        };
+
+        let method = match request.get("method").and_then(Value::as_str) {
+            Some(method) => method,
+            None => {
+                let id = request.get("id").cloned().unwrap_or_default();
+                let err = Error::new(INVALID_REQUEST, "Requests must have a method.");
+                return Some(response(id, Err(err)));
+            }
+        };
+
+        let params = request.get("params").cloned().unwrap_or_default();
+        let result = self.call(method, &params);
+        request.get("id").map(|id| response(id.clone(), result))
+    }
+
+    fn call(&self, method: &str, params: &Value) -> Result<Value, Error> {
+        match method {
+            "load" => {
+                let path = string(params, "path")?;
+                let processor = self.load(Path::new(path))?;
+                Ok(info(&processor))
+            }
+            "info" => self.processor().map(|processor| info(&processor)),
+            "disassemble" => self.disassemble(params),
+            "symbols" => self.symbols(params),
+            "symbol" => self.symbol(params),
+            "xrefs" => {
+                let processor = self.processor()?;
+                let addr = address(params, "addr")?;
+                Ok(json!({
+                    "callers": processor.callers(addr).collect::<Vec<_>>(),
+                    "callees": processor.callees(addr).collect::<Vec<_>>(),
+                }))
+            }
+            _ => {
+                let message = format!("Unknown method '{method}'.");
+                Err(Error::new(METHOD_NOT_FOUND, message))
+            }
+        }
+    }
+
+    fn load(&self, path: &Path) -> Result<Arc<Processor>, Error> {
+        let processor = match Processor::parse(path) {
+            Ok(processor) => Arc::new(processor),
+            Err(err) => {
+                let message = format!("Failed to load '{}': {err:?}", path.display());
+                return Err(Error::new(LOAD_FAILED, message));
+            }
+        };
+
+        *self.processor.write().unwrap() = Some(Arc::clone(&processor));
+        Ok(processor)
+    }
+
+    fn processor(&self) -> Result<Arc<Processor>, Error> {
+        match &*self.processor.read().unwrap() {
+            Some(processor) => Ok(Arc::clone(processor)),
+            None => Err(Error::new(NOT_LOADED, "No binary is loaded.")),
+        }
+    }
+
+    fn disassemble(&self, params: &Value) -> Result<Value, Error> {
+        let processor = self.processor()?;
+
+        let range = if let Some(name) = params.get("symbol").and_then(Value::as_str) {
+            symbol_range(&processor, name).map_err(Error::params)?
+        } else if let Some(name) = params.get("section").and_then(Value::as_str) {
+            section_range(&processor, name).map_err(Error::params)?
+        } else {
+            let (start, end) = (address(params, "start")?, address(params, "end")?);
+            let section = match processor.section_by_addr(start) {
+                Some(section) => section,
+                None => return Err(Error::params(format!("Address {start:#x} isn't mapped."))),
+            };
+
+            // ranges don't span sections, so that a range can't cover the whole address space
+            start..end.min(section.end)
+        };
+
+        let too_long = || {
+            let message = format!("Range has more than {MAX_LINES} lines, request less at once.");
+            Error::params(message)
+        };
+
+        // every block is at least a line
+        let blocks = processor.blocks_limited(range, MAX_LINES).ok_or_else(too_long)?;
+
+        let mut lines = Vec::new();
+        for line in blocks.iter().flat_map(|block| block.lines(&processor, AddressMode::Virtual)) {
+            if lines.len() == MAX_LINES {
+                return Err(too_long());
+            }
+
+            let spans = line.layout(&CONFIG.listing.columns, &processor, AddressMode::Virtual);
+            let text: String = spans.iter().map(|span| &*span.token.text).collect();
+            let spans: Vec<Value> = spans
+                .iter()
+                .map(|span| {
+                    let mut map = Map::new();
+                    map.insert(
+                        "kind".into(),
+                        format!("{:?}", span.kind).to_lowercase().into(),
+                    );
+                    map.insert("text".into(), span.token.text.to_string().into());
+                    if let Some(target) = span.token.target {
+                        map.insert("target".into(), target.into());
+                    }
+                    map.into()
+                })
+                .collect();
+
+            lines.push(json!({ "addr": line.addr, "text": text, "spans": spans }));
+        }
+
+        Ok(Value::Array(lines))
+    }
+
+    fn symbols(&self, params: &Value) -> Result<Value, Error> {
+        let processor = self.processor()?;
+        let filter = params.get("filter").and_then(Value::as_str).unwrap_or_default();
+
+        let symbols = processor
+            .index
+            .syms
+            .iter()
+            .filter(|sym| sym.item.as_str().contains(filter))
+            .map(|sym| json!({ "name": sym.item.as_str(), "addr": sym.addr }))
+            .collect();
+
+        Ok(Value::Array(symbols))
+    }
+
+    fn symbol(&self, params: &Value) -> Result<Value, Error> {
+        let processor = self.processor()?;
+
+        if let Some(name) = params.get("name").and_then(Value::as_str) {
+            return Ok(processor.index.get_func_by_name(name).into());
+        }
+
+        let addr = address(params, "addr")?;
+        let symbol = processor.index.get_sym_by_addr(addr);
+        Ok(symbol.map(|sym| sym.as_str().to_string()).into())
+    }
+}
+
+fn response(id: Value, result: Result<Value, Error>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(err) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": err.code, "message": err.message },
+        }),
+    }
+}
+
+fn info(processor: &Processor) -> Value {
+    let sections: Vec<Value> = processor
+        .sections()
+        .map(|section| {
+            json!({
+                "name": section.name,
+                "kind": format!("{:?}", section.kind).to_lowercase(),
+                "start": section.start,
+                "end": section.end,
+            })
+        })
+        .collect();
+
+    json!({
+        "path": processor.path.display().to_string(),
+        "format": format!("{:?}", processor.format()).to_lowercase(),
+        "arch": format!("{:?}", processor.arch()).to_lowercase(),
+        "entrypoint": processor.entrypoint,
+        "sections": sections,
+    })
+}
+
+fn string<'a>(params: &'a Value, name: &str) -> Result<&'a str, Error> {
+    match params.get(name).and_then(Value::as_str) {
+        Some(value) => Ok(value),
+        None => Err(Error::params(format!("Missing string '{name}'."))),
+    }
+}
+
+/// Addresses can be given as numbers, or as hex strings since not every client can represent
+/// 64-bit numbers.
+fn address(params: &Value, name: &str) -> Result<PhysAddr, Error> {
+    let value = params.get(name);
+
+    if let Some(addr) = value.and_then(Value::as_u64) {
+        return Ok(addr as PhysAddr);
+    }
+
+    let hex = value.and_then(Value::as_str).map(|s| s.trim_start_matches("0x"));
+    match hex.and_then(|hex| PhysAddr::from_str_radix(hex, 16).ok()) {
+        Some(addr) => Ok(addr),
+        None => Err(Error::params(format!("Missing address '{name}'."))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_code(response: &Value) -> Option<i64> {
+        response["error"]["code"].as_i64()
+    }
+
+    #[test]
+    fn messages() {
+        let server = Server::default();
+
+        let response = server.message(r#"{"jsonrpc": "2.0", "id": 1,"#).unwrap();
+        assert_eq!(error_code(&response), Some(PARSE_ERROR));
+        assert_eq!(response["id"], Value::Null);
+
+        let response = server.message(r#"{"jsonrpc": "2.0", "id": 2}"#).unwrap();
+        assert_eq!(error_code(&response), Some(INVALID_REQUEST));
+        assert_eq!(response["id"], 2);
+
+        let response = server.message(r#"{"jsonrpc": "2.0", "id": "a", "method": "run"}"#);
+        let response = response.unwrap();
+        assert_eq!(error_code(&response), Some(METHOD_NOT_FOUND));
+        assert_eq!(response["id"], "a");
+
+        // notifications don't get a response, not even when they fail
+        assert!(server.message(r#"{"jsonrpc": "2.0", "method": "info"}"#).is_none());
+        assert!(server.message(r#"{"jsonrpc": "2.0", "method": "run"}"#).is_none());
+    }
+
+    #[test]
+    fn calls_without_binary() {
+        let server = Server::default();
+        for method in ["info", "disassemble", "symbols", "symbol", "xrefs"] {
+            let err = server.call(method, &Value::Null).err().unwrap();
+            assert_eq!(err.code, NOT_LOADED);
+        }
+
+        let err = server.call("load", &json!({ "file": "a.out" })).err().unwrap();
+        assert_eq!(err.code, INVALID_PARAMS);
+    }
+
+    #[test]
+    fn addresses() {
+        let params = json!({ "a": 4096, "b": "0x1000", "c": "1000", "d": "0xg", "e": -1 });
+        assert_eq!(address(&params, "a").ok(), Some(0x1000));
+        assert_eq!(address(&params, "b").ok(), Some(0x1000));
+        assert_eq!(address(&params, "c").ok(), Some(0x1000));
+        assert_eq!(address(&params, "d").err().unwrap().code, INVALID_PARAMS);
+        assert_eq!(address(&params, "e").err().unwrap().code, INVALID_PARAMS);
+        assert_eq!(address(&params, "f").err().unwrap().code, INVALID_PARAMS);
+    }
+}
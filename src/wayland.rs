@@ -16,7 +16,7 @@ pub fn set_env() {
         let user = match get_first_direntry("/run/user") {
             Some(user) => user,
             None => {
-                log::warning!("Failed to guess wayland environmental variables.");
+                log::report!(log::Level::Warn, "Failed to guess wayland environmental variables.");
                 return;
             }
         };
@@ -0,0 +1,152 @@
+//! Storage for short strings that show up in many tokens, such as immediates and padding.
+//!
+//! Interned strings are copied into large chunks that are never freed, so tokens can refer to
+//! them as `&'static str` and cloning or creating a token with a string that was seen before
+//! doesn't allocate.
+//!
+//! The arena is shared by every binary that's opened, so it's limited to [`MAX_CHUNKS`] chunks.
+//! The strings seen first are usually the ones that are repeated the most, such as small
+//! immediates and instruction bytes, everything seen after the arena filled up is allocated like
+//! any other string.
+
+use std::collections::HashSet;
+use std::sync::{OnceLock, RwLock};
+
+/// Strings longer than this are unlikely to be repeated and aren't interned.
+pub const MAX_INTERNED_LEN: usize = 32;
+
+/// Number of bytes in a chunk of the arena.
+const CHUNK_SIZE: usize = 0x10000;
+
+/// Number of chunks the arena can grow to.
+const MAX_CHUNKS: usize = 16;
+
+struct Interner {
+    /// Every string in the arena.
+    strings: HashSet<&'static str>,
+    /// Part of the current chunk that isn't used yet.
+    free: &'static mut [u8],
+    /// Number of chunks allocated so far.
+    chunks: usize,
+    max_chunks: usize,
+}
+
+impl Default for Interner {
+    fn default() -> Self {
+        Self::with_max_chunks(MAX_CHUNKS)
+    }
+}
+
+impl Interner {
+    fn with_max_chunks(max_chunks: usize) -> Self {
+        Self {
+            strings: HashSet::new(),
+            free: &mut [],
+            chunks: 0,
+            max_chunks,
+        }
+    }
+
+    /// Whether a string that isn't interned yet doesn't fit anymore.
+    fn is_full(&self, text: &str) -> bool {
+        self.chunks == self.max_chunks && self.free.len() < text.len()
+    }
+
+    fn insert(&mut self, text: &str) -> Option<&'static str> {
+        if let Some(interned) = self.strings.get(text) {
+            return Some(interned);
+        }
+
+        if self.is_full(text) {
+            return None;
+        }
+
+        if self.free.len() < text.len() {
+            self.free = Box::leak(vec![0; CHUNK_SIZE].into_boxed_slice());
+            self.chunks += 1;
+        }
+
+        let (bytes, free) = std::mem::take(&mut self.free).split_at_mut(text.len());
+        bytes.copy_from_slice(text.as_bytes());
+        self.free = free;
+
+        // SAFETY: the bytes were copied from a `&str`
+        let interned = unsafe { std::str::from_utf8_unchecked(bytes) };
+        self.strings.insert(interned);
+        Some(interned)
+    }
+}
+
+fn interner() -> &'static RwLock<Interner> {
+    static INTERNER: OnceLock<RwLock<Interner>> = OnceLock::new();
+    INTERNER.get_or_init(RwLock::default)
+}
+
+/// Get a copy of a string that lives for the rest of the program.
+///
+/// Returns `None` for strings longer than [`MAX_INTERNED_LEN`] bytes and for strings that weren't
+/// seen before once the arena is full.
+pub fn intern(text: &str) -> Option<&'static str> {
+    if text.len() > MAX_INTERNED_LEN {
+        return None;
+    }
+
+    {
+        let interner = interner().read().unwrap();
+        if let Some(interned) = interner.strings.get(text) {
+            return Some(interned);
+        }
+
+        // once the arena is full it's only ever read from
+        if interner.is_full(text) {
+            return None;
+        }
+    }
+
+    interner().write().unwrap().insert(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deduplicates() {
+        let a = intern("0x1234").unwrap();
+        let b = intern(&format!("0x{:x}", 0x1234)).unwrap();
+
+        assert_eq!(a, "0x1234");
+        assert_eq!(a.as_ptr(), b.as_ptr());
+    }
+
+    #[test]
+    fn spans_chunks() {
+        let strings: Vec<String> = (0..CHUNK_SIZE / 4).map(|n| format!("{n:#x}")).collect();
+        let interned: Vec<&str> = strings.iter().map(|s| intern(s).unwrap()).collect();
+
+        assert_eq!(strings, interned);
+        assert_eq!(intern(""), Some(""));
+    }
+
+    #[test]
+    fn stops_growing() {
+        let mut interner = Interner::with_max_chunks(2);
+        let strings: Vec<String> = (0..CHUNK_SIZE).map(|n| format!("{n:08x}")).collect();
+
+        // two chunks fit a quarter of the strings
+        let interned = strings.iter().take_while(|s| interner.insert(s).is_some()).count();
+        assert_eq!(interned, CHUNK_SIZE / 4);
+        assert_eq!(interner.chunks, 2);
+
+        // strings seen before are still found, others aren't added
+        assert_eq!(interner.insert(&strings[0]), Some(strings[0].as_str()));
+        assert_eq!(interner.insert(&strings[CHUNK_SIZE - 1]), None);
+        assert_eq!(interner.strings.len(), interned);
+        assert_eq!(interner.chunks, 2);
+    }
+
+    #[test]
+    fn long_strings() {
+        assert_eq!(intern(&"a".repeat(MAX_INTERNED_LEN + 1)), None);
+    }
+}
@@ -2,7 +2,10 @@
 use std::ops::Deref;
 use std::sync::Arc;
 
+mod intern;
+
 pub use egui::Color32;
+pub use intern::{intern, MAX_INTERNED_LEN};
 
 // TODO: Uniform colors for different instructions sets.
 //       These groupings are from:
@@ -28,24 +31,6 @@ pub use egui::Color32;
 // * symbol
 // * function
 
-pub mod colors {
-    //! IBM inspired colors.
-
-    use egui::Color32;
-
-    macro_rules! color {
-        ($r:expr, $g:expr, $b:expr) => {
-            Color32::from_rgb($r, $g, $b)
-        };
-    }
-
-    pub const WHITE: Color32 = color!(0xff, 0xff, 0xff);
-    pub const GREEN: Color32 = color!(0x02, 0xed, 0x6e);
-    pub const GRAY35: Color32 = color!(0x35, 0x35, 0x35);
-    pub const GRAY60: Color32 = color!(0x60, 0x60, 0x60);
-    pub const GRAYAA: Color32 = color!(0xaa, 0xaa, 0xaa);
-}
-
 #[derive(Debug, Clone)]
 pub enum MaybeStatic {
     Dynamic(Arc<str>),
@@ -64,10 +49,24 @@ impl Deref for MaybeStatic {
     }
 }
 
+/// What a token represents, independent of how it's colored.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    #[default]
+    Other,
+    Register,
+    Immediate,
+    /// Reference to an address, either by name or as a number.
+    Symbol,
+}
+
 #[derive(Debug, Clone)]
 pub struct Token {
     pub text: MaybeStatic,
     pub color: Color32,
+    pub kind: TokenKind,
+    /// Address the token refers to, e.g. the destination of a branch.
+    pub target: Option<usize>,
 }
 
 impl Token {
@@ -76,6 +75,8 @@ impl Token {
         Self {
             text: MaybeStatic::Static(text),
             color,
+            kind: TokenKind::Other,
+            target: None,
         }
     }
 
@@ -84,8 +85,51 @@ impl Token {
         Self {
             text: MaybeStatic::Dynamic(Arc::from(text)),
             color,
+            kind: TokenKind::Other,
+            target: None,
+        }
+    }
+
+    /// Create a token from a string that's shared with other tokens or blocks, without copying it.
+    #[inline(always)]
+    pub fn from_shared(text: Arc<str>, color: Color32) -> Self {
+        Self {
+            text: MaybeStatic::Dynamic(text),
+            color,
+            kind: TokenKind::Other,
+            target: None,
+        }
+    }
+
+    /// Create a token from a string that's likely to be repeated, interning it if it's short.
+    #[inline(always)]
+    pub fn interned(text: &str, color: Color32) -> Self {
+        match intern(text) {
+            Some(text) => Self::from_str(text, color),
+            None => Self::from_string(text.to_string(), color),
+        }
+    }
+
+    /// Create a token that owns its text, unless it's short enough to be interned.
+    #[inline(always)]
+    pub fn from_owned(text: String, color: Color32) -> Self {
+        match intern(&text) {
+            Some(text) => Self::from_str(text, color),
+            None => Self::from_string(text, color),
         }
     }
+
+    #[inline(always)]
+    pub fn with_kind(mut self, kind: TokenKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    #[inline(always)]
+    pub fn with_target(mut self, addr: usize) -> Self {
+        self.target = Some(addr);
+        self
+    }
 }
 
 impl PartialEq for Token {
@@ -115,8 +159,25 @@ impl TokenStream {
         self.push_token(Token::from_str(text, color));
     }
 
+    /// Push a token that owns its text, interning it if it's short.
     pub fn push_owned(&mut self, text: String, color: Color32) {
-        self.push_token(Token::from_string(text, color));
+        self.push_token(Token::from_owned(text, color));
+    }
+
+    pub fn push_kind(&mut self, text: &'static str, color: Color32, kind: TokenKind) {
+        self.push_token(Token::from_str(text, color).with_kind(kind));
+    }
+
+    pub fn push_owned_kind(&mut self, text: String, color: Color32, kind: TokenKind) {
+        self.push_token(Token::from_owned(text, color).with_kind(kind));
+    }
+
+    /// Mark every token starting at index `start` as a symbol referring to an address.
+    pub fn set_target(&mut self, start: usize, addr: usize) {
+        for token in &mut self.inner[start..] {
+            token.kind = TokenKind::Symbol;
+            token.target = Some(addr);
+        }
     }
 
     pub fn clear(&mut self) {